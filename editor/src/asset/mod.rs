@@ -31,11 +31,12 @@ use crate::{
     fyrox::{
         asset::{
             manager::ResourceManager,
+            registry::METADATA_EXTENSION,
             state::ResourceState,
             untyped::{ResourceHeader, ResourceKind, UntypedResource},
         },
         core::{
-            futures::executor::block_on, log::Log, make_relative_path,
+            append_extension, futures::executor::block_on, log::Log, make_relative_path,
             parking_lot::lock_api::Mutex, pool::Handle, TypeUuidProvider, Uuid,
         },
         engine::Engine,
@@ -104,6 +105,7 @@ struct ContextMenu {
     delete: Handle<UiNode>,
     placement_target: Handle<UiNode>,
     dependencies: Handle<UiNode>,
+    reimport: Handle<UiNode>,
 }
 
 fn execute_command(command: &mut Command) {
@@ -154,6 +156,7 @@ impl ContextMenu {
         let copy_path;
         let copy_file_name;
         let dependencies;
+        let reimport;
         let menu = ContextMenuBuilder::new(
             PopupBuilder::new(WidgetBuilder::new()).with_content(
                 StackPanelBuilder::new(
@@ -170,6 +173,12 @@ impl ContextMenu {
                                 .build(ctx);
                             duplicate
                         })
+                        .with_child({
+                            reimport = MenuItemBuilder::new(WidgetBuilder::new())
+                                .with_content(MenuItemContent::text("Reimport"))
+                                .build(ctx);
+                            reimport
+                        })
                         .with_child({
                             copy_path = MenuItemBuilder::new(WidgetBuilder::new())
                                 .with_content(MenuItemContent::text("Copy Full Path"))
@@ -217,6 +226,7 @@ impl ContextMenu {
             placement_target: Default::default(),
             copy_file_name,
             dependencies,
+            reimport,
         }
     }
 
@@ -234,6 +244,15 @@ impl ContextMenu {
             {
                 if message.destination() == self.delete {
                     Log::verify(std::fs::remove_file(&item.path));
+                    let metadata_path = append_extension(&item.path, METADATA_EXTENSION);
+                    if metadata_path.exists() {
+                        Log::verify(std::fs::remove_file(metadata_path));
+                    }
+                    engine
+                        .resource_manager
+                        .state()
+                        .registry
+                        .unregister_path(&item.path);
                     return true;
                 } else if message.destination() == self.show_in_explorer {
                     if let Ok(canonical_path) = item.path.canonicalize() {
@@ -288,6 +307,14 @@ impl ContextMenu {
                             }
                         }
                     }
+                } else if message.destination() == self.reimport {
+                    // Resources are imported from their sources on loading, so reloading the
+                    // resource regenerates all its data using current import options.
+                    if let Ok(resource) =
+                        block_on(engine.resource_manager.request_untyped(&item.path))
+                    {
+                        engine.resource_manager.state().reload_resource(resource);
+                    }
                 } else if message.destination() == self.copy_path {
                     if let Ok(canonical_path) = item.path.canonicalize() {
                         put_path_to_clipboard(engine, canonical_path.as_os_str())
@@ -492,6 +519,7 @@ impl ResourceCreator {
                             let resource = UntypedResource(Arc::new(Mutex::new(ResourceHeader {
                                 kind: ResourceKind::External(path.clone()),
                                 type_uuid: instance.type_uuid(),
+                                resource_uuid: None,
                                 state: ResourceState::Ok(instance),
                             })));
 
@@ -557,6 +585,37 @@ pub struct AssetBrowser {
     pub preview_generators: AssetPreviewGeneratorsCollection,
 }
 
+/// Returns `true` if the resource could be saved back by the engine, so references in it could be
+/// fixed automatically.
+pub(crate) fn is_resource_writable(res: &UntypedResource) -> bool {
+    if [Texture::type_uuid(), SoundBuffer::type_uuid()].contains(&res.type_uuid()) {
+        return false;
+    };
+
+    // The engine cannot write FBX resources, so we must filter out these and warn the user
+    // that resource references cannot be automatically fixed.
+    if let Some(model) = res.try_cast::<Model>() {
+        let kind = model.kind();
+        if let Some(ext) = kind.path().and_then(|path| {
+            path.extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+        }) {
+            if matches!(
+                ext.as_str(),
+                "fbx" | "gltf" | "glb" | "usd" | "usda" | "usdc" | "usdz"
+            ) {
+                Log::warn(format!(
+                    "Resource {kind} cannot be scanned for \
+                    references, because FBX/GLTF/USD cannot be exported."
+                ));
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
 fn is_supported_resource(ext: &OsStr, resource_manager: &ResourceManager) -> bool {
     let Some(ext) = ext.to_str() else {
         return false;
@@ -1256,32 +1315,6 @@ impl AssetBrowser {
         resource_manager: &ResourceManager,
         message_sender: &MessageSender,
     ) {
-        fn filter(res: &UntypedResource) -> bool {
-            if [Texture::type_uuid(), SoundBuffer::type_uuid()].contains(&res.type_uuid()) {
-                return false;
-            };
-
-            // The engine cannot write FBX resources, so we must filter out these and warn the user
-            // that resource references cannot be automatically fixed.
-            if let Some(model) = res.try_cast::<Model>() {
-                let kind = model.kind();
-                if let Some(ext) = kind.path().and_then(|path| {
-                    path.extension()
-                        .map(|ext| ext.to_string_lossy().to_lowercase())
-                }) {
-                    if ext == "fbx" || ext == "gltf" || ext == "glb" {
-                        Log::warn(format!(
-                            "Resource {kind} cannot be scanned for \
-                        references, because FBX/GLTF cannot be exported."
-                        ));
-                        return false;
-                    }
-                }
-            }
-
-            true
-        }
-
         if let Some(item) = ui.try_get(dropped).and_then(|n| n.cast::<AssetItem>()) {
             if let Ok(relative_path) = make_relative_path(target_dir) {
                 if let Ok(resource) = block_on(resource_manager.request_untyped(&item.path)) {
@@ -1292,7 +1325,7 @@ impl AssetBrowser {
                                 resource,
                                 new_full_path,
                                 "./",
-                                filter,
+                                is_resource_writable,
                             )));
 
                             self.refresh(ui, resource_manager, message_sender);
@@ -1339,7 +1372,7 @@ impl AssetBrowser {
                                                         resource,
                                                         new_full_path,
                                                         "./",
-                                                        filter,
+                                                        is_resource_writable,
                                                     ),
                                                 ));
                                             }
@@ -325,6 +325,7 @@ impl CameraController {
                 let scale = match camera.projection() {
                     Projection::Perspective(perspective) => 2.0 * perspective.fov.tan(),
                     Projection::Orthographic(orthographic) => 2.0 * orthographic.vertical_size,
+                    Projection::OffCenter(off_center) => 2.0 * off_center.fov().tan(),
                 };
                 let side = camera
                     .side_vector()
@@ -348,7 +349,7 @@ impl CameraController {
         let camera = graph[self.camera].as_camera_mut();
 
         match *camera.projection_mut() {
-            Projection::Perspective(_) => {
+            Projection::Perspective(_) | Projection::OffCenter(_) => {
                 self.z_offset = (self.z_offset + delta).clamp(
                     -settings.camera.zoom_range.end,
                     -settings.camera.zoom_range.start,
@@ -555,7 +556,7 @@ impl CameraController {
         let camera = graph[self.camera].as_camera_mut();
 
         match camera.projection_value() {
-            Projection::Perspective(_) => {
+            Projection::Perspective(_) | Projection::OffCenter(_) => {
                 let global_transform = camera.global_transform();
                 let look = global_transform.look();
                 let side = global_transform.side();
@@ -20,6 +20,7 @@
 
 use crate::{
     fyrox::{
+        asset::pack::{PackCompression, PackKey, ResourcePackBuilder, DEFAULT_PACK_PATH},
         core::{
             log::{Log, LogMessage, MessageKind},
             pool::Handle,
@@ -88,6 +89,9 @@ struct ExportOptions {
     selected_build_target: usize,
     run_after_build: bool,
     open_destination_folder: bool,
+    pack_assets: bool,
+    compress_assets: bool,
+    encryption_passphrase: String,
 }
 
 impl Default for ExportOptions {
@@ -102,6 +106,9 @@ impl Default for ExportOptions {
             selected_build_target: 0,
             run_after_build: false,
             open_destination_folder: true,
+            pack_assets: false,
+            compress_assets: true,
+            encryption_passphrase: Default::default(),
         }
     }
 }
@@ -522,6 +529,56 @@ fn copy_binaries_wasm(package_dir_path: &Path, destination_folder: &Path) -> Res
     .map_err(|e| e.to_string())
 }
 
+fn pack_assets(export_options: &ExportOptions) -> Result<(), String> {
+    let key = if export_options.encryption_passphrase.is_empty() {
+        None
+    } else {
+        Some(PackKey::from_passphrase(
+            &export_options.encryption_passphrase,
+        ))
+    };
+
+    let mut builder = ResourcePackBuilder::new()
+        .with_compression(if export_options.compress_assets {
+            PackCompression::Lz4
+        } else {
+            PackCompression::None
+        })
+        .with_key(key);
+
+    for folder in export_options.assets_folders.iter() {
+        Log::info(format!(
+            "Trying to pack assets from {}...",
+            folder.display()
+        ));
+
+        builder
+            .add_directory(folder, |path| {
+                path.extension().map_or(true, |ext| {
+                    !export_options
+                        .ignored_extensions
+                        .iter()
+                        .any(|ignored| ext == OsStr::new(ignored))
+                })
+            })
+            .map_err(|e| e.to_string())?;
+    }
+
+    let pack_path = export_options.destination_folder.join(DEFAULT_PACK_PATH);
+    builder.save(&pack_path).map_err(|e| e.to_string())?;
+
+    Log::info(format!("Assets were packed to {}.", pack_path.display()));
+    if key.is_some() {
+        Log::warn(
+            "The asset pack is encrypted, the game must set the key using \
+            `Executor::set_asset_pack_key(Some(PackKey::from_passphrase(..)))` with the same \
+            passphrase.",
+        );
+    }
+
+    Ok(())
+}
+
 fn export(export_options: ExportOptions, cancel_flag: Arc<AtomicBool>) -> Result<(), String> {
     Log::info("Building the game...");
 
@@ -546,7 +603,16 @@ fn export(export_options: ExportOptions, cancel_flag: Arc<AtomicBool>) -> Result
 
     // Copy assets
     match export_options.target_platform {
+        TargetPlatform::PC if export_options.pack_assets => {
+            Log::info("Trying to pack the assets...");
+
+            pack_assets(&export_options)?;
+        }
         TargetPlatform::PC | TargetPlatform::WebAssembly => {
+            if export_options.pack_assets {
+                Log::warn("Asset packs are supported only on PC, the assets will be copied as is.");
+            }
+
             Log::info("Trying to copy the assets...");
 
             for folder in export_options.assets_folders {
@@ -564,6 +630,10 @@ fn export(export_options: ExportOptions, cancel_flag: Arc<AtomicBool>) -> Result
             }
         }
         TargetPlatform::Android => {
+            if export_options.pack_assets {
+                Log::warn("Asset packs are supported only on PC, the assets will be copied as is.");
+            }
+
             // Asset management on Android is quite annoying, because all other target platforms
             // uses the workspace manifest path as a root directory and all paths in code/assets
             // stored relatively to it. On Android, however, all your assets must be in unified
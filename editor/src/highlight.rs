@@ -204,6 +204,7 @@ impl SceneRenderPass for HighlightRenderPass {
                 z_far: ctx.camera.projection().z_far(),
                 view_matrix: ctx.camera.view_matrix(),
                 projection_matrix: ctx.camera.projection_matrix(),
+                pixel_grid_size: ctx.camera.pixel_grid_size(),
             };
 
             let mut render_bundle_storage =
@@ -251,6 +251,12 @@ pub fn calculate_gizmo_distance_scaling(
                     .metric_distance(&graph[camera].global_position())
         }
         Projection::Orthographic(ortho) => 0.4 * ortho.vertical_size,
+        Projection::OffCenter(proj) => {
+            distance_scale_factor(proj.fov())
+                * graph[gizmo_origin]
+                    .global_position()
+                    .metric_distance(&graph[camera].global_position())
+        }
     };
 
     Vector3::new(s, s, s)
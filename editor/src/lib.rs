@@ -143,6 +143,7 @@ use crate::{
         collider::ColliderPlugin, curve_editor::CurveEditorPlugin, material::MaterialPlugin,
         path_fixer::PathFixerPlugin, ragdoll::RagdollPlugin, settings::SettingsPlugin,
         stats::UiStatisticsPlugin, tilemap::TileMapEditorPlugin,
+        visual_script::VisualScriptEditorPlugin,
     },
     scene::{
         commands::{
@@ -695,6 +696,7 @@ impl Editor {
             serialization_context,
             task_pool,
             widget_constructors: Arc::new(new_widget_constructor_container()),
+            update_loop: Default::default(),
         })
         .unwrap();
 
@@ -966,6 +968,7 @@ impl Editor {
                 .with(AbsmEditorPlugin::default())
                 .with(UiStatisticsPlugin::default())
                 .with(CurveEditorPlugin::default())
+                .with(VisualScriptEditorPlugin::default())
                 .with(PathFixerPlugin::default())
                 .with(inspector_plugin),
             // Apparently, some window managers (like Wayland), does not send `Focused` event after the window
@@ -2104,6 +2107,11 @@ impl Editor {
 
         engine.resource_manager.state().destroy_unused_resources();
 
+        match block_on(engine.resource_manager.scan_resource_metadata(".", true)) {
+            Ok(count) => Log::info(format!("{count} resources were registered.")),
+            Err(err) => Log::err(format!("Unable to scan resource metadata. Reason: {err:?}")),
+        }
+
         self.asset_browser
             .set_working_directory(engine, &working_directory, &self.message_sender);
 
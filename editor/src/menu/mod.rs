@@ -205,6 +205,7 @@ impl Menu {
             message,
             &mut ctx.panels,
             ctx.engine.user_interfaces.first_mut(),
+            &ctx.engine.resource_manager,
         );
         self.file_menu.handle_ui_message(
             message,
@@ -19,8 +19,12 @@
 // SOFTWARE.
 
 use crate::{
+    asset::is_resource_writable,
     fyrox::{
-        asset::core::pool::Handle,
+        asset::{
+            core::{futures::executor::block_on, log::Log, pool::Handle},
+            manager::ResourceManager,
+        },
         gui::{menu::MenuItemMessage, message::UiMessage, BuildContext, UiNode, UserInterface},
     },
     menu::{create_menu_item, create_root_menu_item, Panels},
@@ -30,23 +34,32 @@ use crate::{
 pub struct UtilsMenu {
     pub menu: Handle<UiNode>,
     pub rendering_statistics: Handle<UiNode>,
+    pub fix_asset_references: Handle<UiNode>,
 }
 
 impl UtilsMenu {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let rendering_statistics;
+        let fix_asset_references;
         let menu = create_root_menu_item(
             "Utils",
-            vec![{
-                rendering_statistics = create_menu_item("Rendering Statistics", vec![], ctx);
-                rendering_statistics
-            }],
+            vec![
+                {
+                    rendering_statistics = create_menu_item("Rendering Statistics", vec![], ctx);
+                    rendering_statistics
+                },
+                {
+                    fix_asset_references = create_menu_item("Fix Asset References", vec![], ctx);
+                    fix_asset_references
+                },
+            ],
             ctx,
         );
 
         Self {
             menu,
             rendering_statistics,
+            fix_asset_references,
         }
     }
 
@@ -55,6 +68,7 @@ impl UtilsMenu {
         message: &UiMessage,
         panels: &mut Panels,
         ui: &mut UserInterface,
+        resource_manager: &ResourceManager,
     ) {
         if let Some(MenuItemMessage::Click) = message.data::<MenuItemMessage>() {
             if message.destination() == self.rendering_statistics {
@@ -62,6 +76,15 @@ impl UtilsMenu {
                     &mut ui.build_ctx(),
                     panels.scene_frame,
                 ))
+            } else if message.destination() == self.fix_asset_references {
+                match block_on(
+                    resource_manager.fix_redirected_references("./", is_resource_writable),
+                ) {
+                    Ok(count) => Log::info(format!("{count} resources were fixed.")),
+                    Err(err) => {
+                        Log::err(format!("Unable to fix asset references. Reason: {err:?}"))
+                    }
+                }
             }
         }
     }
@@ -61,15 +61,15 @@ use fyrox::gui::widget::WidgetMessage;
 use std::{any::Any, fmt::Debug};
 
 mod blendspace;
-mod canvas;
+pub(crate) mod canvas;
 pub mod command;
-mod connection;
-mod node;
+pub(crate) mod connection;
+pub(crate) mod node;
 mod parameter;
 mod segment;
 pub mod selectable;
 pub mod selection;
-mod socket;
+pub(crate) mod socket;
 mod state_graph;
 mod state_viewer;
 mod toolbar;
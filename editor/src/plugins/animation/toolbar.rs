@@ -825,13 +825,15 @@ impl Toolbar {
                 .with_title(WindowTitle::text("Select Animation To Import")),
         )
         .with_filter(Filter::new(|p: &Path| {
-            // TODO: Here we allow importing only FBX and GLTF files, but they can contain
+            // TODO: Here we allow importing only FBX, GLTF and USD files, but they can contain
             // multiple animations and it might be good to also add animation selector
             // that will be used to select a particular animation to import.
             p.is_dir()
                 || p.extension().is_some_and(|ext| {
-                    let ext = ext.to_string_lossy();
-                    ext.as_ref() == "fbx" || ext.as_ref() == "gltf" || ext.as_ref() == "glb"
+                    matches!(
+                        ext.to_string_lossy().as_ref(),
+                        "fbx" | "gltf" | "glb" | "usd" | "usda" | "usdc" | "usdz"
+                    )
                 })
         }))
         .build(ctx);
@@ -60,10 +60,10 @@ use crate::{
         renderer::framework::PolygonFillMode,
         resource::{
             curve::{CurveResource, CurveResourceState},
-            model::{MaterialSearchOptions, Model, ModelResource},
+            model::{AnimationTrim, MaterialSearchOptions, Model, ModelResource, ModelUpAxis},
             texture::{
-                CompressionOptions, MipFilter, TextureMagnificationFilter,
-                TextureMinificationFilter, TextureResource, TextureWrapMode,
+                BasisEncoding, CompressionOptions, MipFilter, TextureMagnificationFilter,
+                TextureMinificationFilter, TextureResource, TextureWrapMode, TranscodeTarget,
             },
         },
         scene::{
@@ -72,8 +72,8 @@ use crate::{
                 Base, LevelOfDetail, LodGroup, Mobility, Property, PropertyValue, ScriptRecord,
             },
             camera::{
-                ColorGradingLut, Exposure, OrthographicProjection, PerspectiveProjection,
-                Projection, SkyBox,
+                ColorGradingLut, Exposure, OffCenterProjection, OrthographicProjection,
+                PerspectiveProjection, PixelPerfectSettings, Projection, SkyBox,
             },
             collider::{
                 BallShape, BitMask, CapsuleShape, ColliderShape, ConeShape, ConvexPolyhedronShape,
@@ -85,7 +85,7 @@ use crate::{
             joint::*,
             light::{
                 directional::{CsmOptions, FrustumSplitOptions},
-                BaseLight,
+                BaseLight, ShadowTechnique,
             },
             mesh::{
                 surface::{BlendShape, Surface, SurfaceResource},
@@ -109,13 +109,13 @@ use crate::{
                     HighShelfFilterEffect, LowPassFilterEffect, LowShelfFilterEffect,
                 },
                 reverb::Reverb,
-                Attenuate, AudioBus, Biquad, DistanceModel, Effect, SoundBuffer,
-                SoundBufferResource, Status,
+                Attenuate, AudioBus, AudioBusSend, Biquad, DistanceModel, Effect, ListenerOutput,
+                SoundBuffer, SoundBufferResource, Status,
             },
             terrain::{Chunk, Layer},
             tilemap::brush::{TileMapBrush, TileMapBrushResource},
             tilemap::TileCollider,
-            tilemap::{tileset::TileSet, Tile},
+            tilemap::{tileset::TileSet, Tile, TileMapParallax},
             transform::Transform,
         },
     },
@@ -334,6 +334,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     >::new());
     container.register_inheritable_vec_collection::<Option<TileMapBrushResource>>();
     container.register_inheritable_inspectable::<TileMapBrush>();
+    container.register_inheritable_inspectable::<TileMapParallax>();
 
     container.register_inheritable_inspectable::<ColorGradingLut>();
     container.register_inheritable_inspectable::<InteractionGroups>();
@@ -354,6 +355,8 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
 
     container.insert(EnumPropertyEditorDefinition::<Effect>::new());
     container.insert(VecCollectionPropertyEditorDefinition::<Effect>::new());
+    container.insert(InspectablePropertyEditorDefinition::<AudioBusSend>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<AudioBusSend>::new());
 
     container.insert(InspectablePropertyEditorDefinition::<Attenuate>::new());
     container.insert(InspectablePropertyEditorDefinition::<LowPassFilterEffect>::new());
@@ -374,6 +377,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_inspectable::<CuboidEmitter>();
     container.register_inheritable_inspectable::<PerspectiveProjection>();
     container.register_inheritable_inspectable::<OrthographicProjection>();
+    container.register_inheritable_inspectable::<OffCenterProjection>();
     container.register_inheritable_inspectable::<Transform>();
     container.register_inheritable_inspectable::<CsmOptions>();
 
@@ -386,10 +390,15 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_option::<ColorGradingLut>();
     container.register_inheritable_option::<Biquad>();
     container.register_inheritable_option::<SkyBox>();
+    container.register_inheritable_option::<PixelPerfectSettings>();
+    container.register_inheritable_inspectable::<PixelPerfectSettings>();
 
     container.register_inheritable_inspectable::<SkyBox>();
 
     container.register_inheritable_enum::<dim2::collider::ColliderShape, _>();
+    container.register_inheritable_enum::<dim2::SortMode2D, _>();
+    container.register_inheritable_enum::<fyrox::scene::tilemap::TileGridLayout, _>();
+    container.register_inheritable_enum::<dim2::light::Light2DKind, _>();
     container.register_inheritable_enum::<CoefficientCombineRule, _>();
     container.register_inheritable_enum::<CompressionOptions, _>();
     container.register_inheritable_enum::<TextureWrapMode, _>();
@@ -402,8 +411,10 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_enum::<RigidBodyType, _>();
     container.register_inheritable_enum::<Exposure, _>();
     container.register_inheritable_enum::<FrustumSplitOptions, _>();
+    container.register_inheritable_enum::<ShadowTechnique, _>();
     container.register_inheritable_enum::<MaterialSearchOptions, _>();
     container.register_inheritable_enum::<DistanceModel, _>();
+    container.register_inheritable_enum::<ListenerOutput, _>();
     container.register_inheritable_enum::<sound::Renderer, _>();
     container.register_inheritable_enum::<RenderPath, _>();
     container.register_inheritable_enum::<CoordinateSystem, _>();
@@ -432,6 +443,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.register_inheritable_inspectable::<HeightfieldShape>();
     container.register_inheritable_inspectable::<dim2::collider::HeightfieldShape>();
     container.register_inheritable_inspectable::<dim2::collider::TileMapShape>();
+    container.register_inheritable_enum::<dim2::collider::TileColliderMergeMode, _>();
     container.register_inheritable_inspectable::<ConvexPolyhedronShape>();
     container.insert(SpriteSheetFramesContainerEditorDefinition);
 
@@ -475,6 +487,11 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
     container.insert(EnumPropertyEditorDefinition::<PolygonFillMode>::new());
 
     container.insert(EnumPropertyEditorDefinition::<MipFilter>::new());
+    container.insert(EnumPropertyEditorDefinition::<TranscodeTarget>::new());
+    container.insert(EnumPropertyEditorDefinition::<BasisEncoding>::new());
+    container.insert(EnumPropertyEditorDefinition::<ModelUpAxis>::new());
+    container.insert(InspectablePropertyEditorDefinition::<AnimationTrim>::new());
+    container.insert(EnumPropertyEditorDefinition::<AnimationTrim>::new_optional());
 
     container.register_inheritable_inspectable::<Limb>();
     container.insert(VecCollectionPropertyEditorDefinition::<Limb>::new());
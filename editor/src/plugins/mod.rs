@@ -29,3 +29,4 @@ pub mod ragdoll;
 pub mod settings;
 pub mod stats;
 pub mod tilemap;
+pub mod visual_script;
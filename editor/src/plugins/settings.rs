@@ -47,7 +47,10 @@ use crate::{
             window::{WindowBuilder, WindowMessage, WindowTitle},
             HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
         },
-        renderer::{CsmSettings, QualitySettings, ShadowMapPrecision},
+        renderer::{
+            CsmSettings, QualitySettings, ShadowFilterMode, ShadowFilterSettings,
+            ShadowMapPrecision,
+        },
     },
     menu::create_menu_item,
     message::MessageSender,
@@ -56,7 +59,7 @@ use crate::{
         build::BuildSettings,
         camera::CameraSettings,
         debugging::DebuggingSettings,
-        general::{EditorStyle, GeneralSettings, ScriptEditor},
+        general::{EditorStyle, GeneralSettings, SceneFileFormat, ScriptEditor},
         graphics::GraphicsSettings,
         keys::{KeyBindings, TerrainKeyBindings},
         model::ModelSettings,
@@ -81,10 +84,13 @@ fn make_property_editors_container(
     container.insert(InspectablePropertyEditorDefinition::<GraphicsSettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<SelectionSettings>::new());
     container.insert(EnumPropertyEditorDefinition::<ShadowMapPrecision>::new());
+    container.insert(EnumPropertyEditorDefinition::<ShadowFilterMode>::new());
     container.insert(EnumPropertyEditorDefinition::<ScriptEditor>::new());
     container.insert(EnumPropertyEditorDefinition::<EditorStyle>::new());
+    container.insert(EnumPropertyEditorDefinition::<SceneFileFormat>::new());
     container.insert(InspectablePropertyEditorDefinition::<DebuggingSettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<CsmSettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<ShadowFilterSettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<QualitySettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<CameraSettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<
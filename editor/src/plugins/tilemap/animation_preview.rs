@@ -0,0 +1,216 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The animation preview widget of the tile set editor. This allows the user to see
+//! the animation sequence of the selected tile, and to pause it and step through its frames.
+
+use crate::fyrox::{
+    core::{
+        algebra::Vector2, math::Rect, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
+        visitor::prelude::*,
+    },
+    gui::{
+        define_constructor, define_widget_deref,
+        draw::{CommandTexture, Draw, DrawingContext},
+        message::UiMessage,
+        widget::{Widget, WidgetBuilder},
+        BuildContext, Control, UiNode, UserInterface,
+    },
+    scene::tilemap::{
+        OrthoTransformation, TileAnimationState, TileDefinitionHandle, TileRenderData,
+    },
+};
+use std::ops::{Deref, DerefMut};
+
+use super::*;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum AnimationPreviewMessage {
+    /// Show the animation sequence that starts from the given tile, or nothing if the tile is none.
+    Tile(Option<TileDefinitionHandle>),
+    /// Start or stop playing the animation.
+    Playing(bool),
+    /// Stop playing the animation and move it by the given number of frames.
+    Step(i32),
+    /// The current frame of the animation has changed. This message is sent from the widget
+    /// with the index of the new frame, where frame 0 is the tile itself.
+    Frame(i32),
+}
+
+impl AnimationPreviewMessage {
+    define_constructor!(AnimationPreviewMessage:Tile => fn tile(Option<TileDefinitionHandle>), layout: false);
+    define_constructor!(AnimationPreviewMessage:Playing => fn playing(bool), layout: false);
+    define_constructor!(AnimationPreviewMessage:Step => fn step(i32), layout: false);
+    define_constructor!(AnimationPreviewMessage:Frame => fn frame(i32), layout: false);
+}
+
+/// The animation preview widget of the tile set editor. It plays the animation sequence of a tile
+/// using its own clock instead of the global clock, so that the animation can be paused and
+/// stepped through frame by frame, while the frame rate always comes from the tile set.
+#[derive(Clone, Debug, Visit, Reflect, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "0f0c8a3e-5f55-4b55-9d0e-4a4e38a3cf51")]
+pub struct AnimationPreview {
+    widget: Widget,
+    /// The tile editing state that is shared with palette widgets, the tile map interaction mode,
+    /// the tile map control panel, and others. It allows this widget access to the tile set
+    /// of the selected tiles.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub state: TileDrawStateRef,
+    /// The first tile of the animation sequence that is being shown.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    tile: Option<TileDefinitionHandle>,
+    /// The current frame of the animation and whether it is playing.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    animation: TileAnimationState,
+}
+
+define_widget_deref!(AnimationPreview);
+
+impl AnimationPreview {
+    /// The frame rate and the number of frames of the animation that is being shown.
+    fn timing(&self) -> Option<(f32, i32)> {
+        let tile = self.tile?;
+        let state = self.state.lock();
+        let mut tile_set = state.tile_set.as_ref()?.state();
+        tile_set.data()?.get_animation_timing(tile)
+    }
+    /// Move the animation to the given frame, wrapping around the length of the animation.
+    fn set_frame(&mut self, frame: i32, ui: &UserInterface) {
+        let Some((_, length)) = self.timing() else {
+            return;
+        };
+        self.animation.frame = frame.rem_euclid(length.max(1)) as f32;
+        self.send_frame(ui);
+    }
+    fn send_frame(&self, ui: &UserInterface) {
+        ui.send_message(AnimationPreviewMessage::frame(
+            self.handle(),
+            MessageDirection::FromWidget,
+            self.animation.frame_index(),
+        ));
+    }
+}
+
+impl Control for AnimationPreview {
+    fn draw(&self, ctx: &mut DrawingContext) {
+        let bounds = self.bounding_rect();
+        ctx.push_rect_filled(&bounds, None);
+        ctx.commit(
+            self.clip_bounds(),
+            self.widget.background(),
+            CommandTexture::None,
+            None,
+        );
+        let Some(tile) = self.tile else {
+            return;
+        };
+        let state = self.state.lock();
+        let Some(mut tile_set) = state.tile_set.as_ref().map(|t| t.state()) else {
+            return;
+        };
+        let Some(tile_set) = tile_set.data() else {
+            return;
+        };
+        let handle = tile_set
+            .get_animation_frame(tile, self.animation.frame_index())
+            .unwrap_or(tile);
+        let data = tile_set
+            .get_transformed_render_data(OrthoTransformation::identity(), handle)
+            .unwrap_or_else(TileRenderData::missing_data);
+        // The tile is drawn as the largest square that fits in the center of the widget.
+        // The texture coordinates are upside down relative to the UI.
+        let size = bounds.w().min(bounds.h());
+        let center = bounds.position + bounds.size * 0.5;
+        let position = center + Vector2::new(-0.5, 0.5) * size;
+        let rect = Rect {
+            position,
+            size: Vector2::new(size, -size),
+        };
+        draw_tile(rect, self.clip_bounds(), &data, ctx);
+    }
+
+    fn update(&mut self, dt: f32, ui: &mut UserInterface) {
+        if !self.animation.playing {
+            return;
+        }
+        let Some((frame_rate, length)) = self.timing() else {
+            return;
+        };
+        let frame = self.animation.frame_index();
+        self.animation.advance(dt, frame_rate, length);
+        if self.animation.frame_index() != frame {
+            self.send_frame(ui);
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+        if message.destination() != self.handle()
+            || message.direction() != MessageDirection::ToWidget
+        {
+            return;
+        }
+        if let Some(msg) = message.data::<AnimationPreviewMessage>() {
+            match msg {
+                AnimationPreviewMessage::Tile(tile) => {
+                    if self.tile != *tile {
+                        self.tile = *tile;
+                        self.animation.frame = 0.0;
+                        self.send_frame(ui);
+                    }
+                }
+                AnimationPreviewMessage::Playing(playing) => {
+                    self.animation.playing = *playing;
+                }
+                AnimationPreviewMessage::Step(step) => {
+                    self.animation.playing = false;
+                    self.set_frame(self.animation.frame_index() + step, ui);
+                }
+                AnimationPreviewMessage::Frame(_) => (),
+            }
+        }
+    }
+}
+
+pub struct AnimationPreviewBuilder {
+    widget_builder: WidgetBuilder,
+    state: TileDrawStateRef,
+}
+
+impl AnimationPreviewBuilder {
+    pub fn new(widget_builder: WidgetBuilder, state: TileDrawStateRef) -> Self {
+        Self {
+            widget_builder,
+            state,
+        }
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        ctx.add_node(UiNode::new(AnimationPreview {
+            widget: self.widget_builder.with_need_update(true).build(ctx),
+            state: self.state,
+            tile: None,
+            animation: TileAnimationState::default(),
+        }))
+    }
+}
@@ -37,7 +37,7 @@ use fyrox::{
                 TileSetResource,
             },
             OrthoTransform, OrthoTransformation, TileCollider, TileDefinitionHandle, TileMap,
-            TileSetUpdate, TilesUpdate,
+            TileSetUpdate, TileTerrain, TilesUpdate,
         },
     },
 };
@@ -953,6 +953,34 @@ impl CommandTrait for SetBrushTilesCommand {
     }
 }
 
+#[derive(Debug)]
+pub struct SetBrushTerrainsCommand {
+    pub brush: TileMapBrushResource,
+    pub terrains: Vec<TileTerrain>,
+}
+
+impl SetBrushTerrainsCommand {
+    fn swap(&mut self) {
+        let mut brush = self.brush.data_ref();
+        std::mem::swap(&mut brush.terrains, &mut self.terrains);
+        brush.change_count.set();
+    }
+}
+
+impl CommandTrait for SetBrushTerrainsCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Modify Brush Terrains".into()
+    }
+
+    fn execute(&mut self, _context: &mut dyn CommandContext) {
+        self.swap()
+    }
+
+    fn revert(&mut self, _context: &mut dyn CommandContext) {
+        self.swap()
+    }
+}
+
 #[derive(Debug)]
 pub struct SetTileSetTilesCommand {
     pub tile_set: TileSetResource,
@@ -981,6 +1009,9 @@ impl CommandTrait for SetTileSetTilesCommand {
     }
 }
 
+/// Replaces some of the tiles of a tile map. Executing the command swaps the tiles of the map
+/// with the tiles in the command, so afterwards the command holds the tiles that were replaced,
+/// and reverting the command swaps them back.
 #[derive(Debug)]
 pub struct SetMapTilesCommand {
     pub tile_map: Handle<Node>,
@@ -1017,6 +1048,66 @@ impl CommandTrait for SetMapTilesCommand {
     }
 }
 
+/// Erases the tiles of a tile map at the given positions. The erased tiles (along with their
+/// transformations) are remembered when the command is executed, so reverting the command puts
+/// them back.
+#[derive(Debug)]
+pub struct EraseMapTilesCommand {
+    pub tile_map: Handle<Node>,
+    pub positions: Vec<Vector2<i32>>,
+    erased: TilesUpdate,
+}
+
+impl EraseMapTilesCommand {
+    pub fn new(tile_map: Handle<Node>, positions: Vec<Vector2<i32>>) -> Self {
+        Self {
+            tile_map,
+            positions,
+            erased: TilesUpdate::default(),
+        }
+    }
+}
+
+impl CommandTrait for EraseMapTilesCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Erase Tiles".into()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        let context = context.get_mut::<GameSceneContext>();
+        let tile_map = context.scene.graph[self.tile_map]
+            .cast_mut::<TileMap>()
+            .expect("Cast to TileMap failed!");
+        let Some(mut tiles) = tile_map.tiles().map(|r| r.data_ref()) else {
+            return;
+        };
+        let Some(tiles) = tiles.as_loaded_mut() else {
+            return;
+        };
+        self.erased.clear();
+        for position in self.positions.iter() {
+            if tiles.get(*position).is_some() {
+                let _ = self.erased.insert(*position, None);
+            }
+        }
+        tiles.swap_tiles(&mut self.erased);
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        let context = context.get_mut::<GameSceneContext>();
+        let tile_map = context.scene.graph[self.tile_map]
+            .cast_mut::<TileMap>()
+            .expect("Cast to TileMap failed!");
+        let Some(mut tiles) = tile_map.tiles().map(|r| r.data_ref()) else {
+            return;
+        };
+        let Some(tiles) = tiles.as_loaded_mut() else {
+            return;
+        };
+        tiles.swap_tiles(&mut self.erased);
+    }
+}
+
 #[derive(Debug)]
 pub struct ModifyAnimationSpeedCommand {
     pub tile_set: TileSetResource,
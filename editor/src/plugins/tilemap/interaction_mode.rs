@@ -20,13 +20,16 @@
 
 //! The [`InteractionMode`] for editing a tile map.
 
-use commands::{MoveMapTileCommand, SetMapTilesCommand};
+use commands::{EraseMapTilesCommand, MoveMapTileCommand, SetMapTilesCommand};
 use fyrox::{
-    fxhash::FxHashMap,
+    core::log::Log,
+    fxhash::{hash64, FxHashMap},
+    gui::{copypasta::ClipboardProvider, message::KeyboardModifiers},
     scene::tilemap::{
-        tileset::TileSetRef, OptionTileRect, TileCursorEffect, TileEraseEffect, TileMapData,
-        TileOverlayEffect, TileSelectionEffect, TileSource, TileUpdateEffect, TilesUpdate,
-        TransTilesUpdate,
+        merge_tile_collider_outlines, outline_segments, tileset::TileSetRef, OptionTileRect,
+        OrthoTransformation, TileClipboard, TileClipboardError, TileCursorEffect, TileEraseEffect,
+        TileMapData, TileOverlayEffect, TileSelectionEffect, TileSource, TileUpdateEffect,
+        TileVariants, TilesUpdate, TransTilesUpdate, VariantTileSource,
     },
 };
 
@@ -74,6 +77,9 @@ pub struct TileMapInteractionMode {
     sender: MessageSender,
     /// The current state of mouse operations.
     mouse_mode: MouseMode,
+    /// The keyboard modifiers that were used to draw the current mouse motion.
+    /// When they change, the shape is redrawn even if the mouse stays in the same cell.
+    modifiers: KeyboardModifiers,
     /// These are the positions of tiles that are in the process of being selected, but not actually selected.
     /// Tile selection is a two-stage process to give the user a smooth experience. The actually selected tiles
     /// are stored in the [`TileDrawState::selection`] so that all interested parties can see what is currently
@@ -104,6 +110,7 @@ impl TileMapInteractionMode {
             current_grid_position: None,
             sender,
             mouse_mode: MouseMode::None,
+            modifiers: KeyboardModifiers::default(),
             selecting: FxHashSet::default(),
             overlay_effect: Arc::new(Mutex::new(TileOverlayEffect {
                 active: false,
@@ -157,17 +164,9 @@ impl TileMapInteractionMode {
         frame_size: Vector2<f32>,
     ) -> Option<Vector2<i32>> {
         let tile_map = scene.graph.try_get_of_type::<TileMap>(self.tile_map)?;
-        let global_transform = tile_map.global_transform();
-
         let camera = scene.graph[game_scene.camera_controller.camera].as_camera();
         let ray = camera.make_ray(mouse_position, frame_size);
-
-        let plane =
-            Plane::from_normal_and_point(&global_transform.look(), &global_transform.position())
-                .unwrap_or_default();
-
-        ray.plane_intersection_point(&plane)
-            .map(|intersection| tile_map.world_to_grid(intersection))
+        tile_map.ray_to_grid(&ray)
     }
     pub fn sync_to_state(&mut self) {
         let state = self.state.lock();
@@ -216,19 +215,152 @@ impl TileMapInteractionMode {
             }
         }
     }
-    fn delete(&mut self) {
-        let sel = &self.select_effect.lock().positions;
-        if sel.is_empty() {
+    /// Put the selected tiles of the tile map into the system clipboard in the text form of
+    /// [`TileClipboard`]. The tiles are copied by [`TileMap::copy_region`], so the transformations
+    /// of the cells are kept. If the tiles are selected somewhere else, like in a palette, the
+    /// current stamp is copied instead.
+    fn copy_to_clipboard(&self, controller: &mut dyn SceneController, engine: &mut Engine) {
+        let state = self.state.lock();
+        let tile_map = controller
+            .downcast_mut::<GameScene>()
+            .and_then(|game_scene| {
+                engine.scenes[game_scene.scene]
+                    .graph
+                    .try_get_of_type::<TileMap>(self.tile_map)
+            })
+            .filter(|_| state.selection_node() == self.tile_map);
+        let clipboard = match tile_map {
+            Some(tile_map) => {
+                let selection = state.selection_positions();
+                let mut bounds = OptionTileRect::default();
+                for position in selection.iter() {
+                    bounds.push(*position);
+                }
+                let Some(bounds) = *bounds else {
+                    return;
+                };
+                let mut region = tile_map.copy_region(bounds);
+                region.retain(|position| selection.contains(&position));
+                TileClipboard::from_region(tile_map.tile_set(), &region)
+            }
+            None => state.stamp_to_clipboard(),
+        };
+        if clipboard.is_empty() {
+            return;
+        }
+        if let Some(mut system_clipboard) = engine.user_interfaces.first_mut().clipboard_mut() {
+            let _ = system_clipboard.set_contents(clipboard.to_text());
+        }
+    }
+
+    /// Paste the tiles from the system clipboard, if it contains tiles. The tiles are pasted into
+    /// the tile map at the cursor by [`TileMap::paste_region_update`], the same way as at runtime.
+    /// If the cursor is not over the tile map or the tiles come from another tile set, the current
+    /// stamp is replaced by the tiles instead, and the other tile set is loaded.
+    fn paste_from_clipboard(&self, controller: &mut dyn SceneController, engine: &mut Engine) {
+        let Some(text) = engine
+            .user_interfaces
+            .first_mut()
+            .clipboard_mut()
+            .and_then(|mut clipboard| clipboard.get_contents().ok())
+        else {
+            return;
+        };
+        let clipboard = match TileClipboard::from_text(&text) {
+            Ok(clipboard) => clipboard,
+            Err(TileClipboardError::InvalidHeader) => return,
+            Err(err) => {
+                Log::err(format!("Unable to paste tiles. Reason: {err}"));
+                return;
+            }
+        };
+        let Some(game_scene) = controller.downcast_mut::<GameScene>() else {
+            return;
+        };
+        let scene = &engine.scenes[game_scene.scene];
+        let Some(tile_map) = scene.graph.try_get_of_type::<TileMap>(self.tile_map) else {
+            return;
+        };
+        let map_tile_set = tile_map.tile_set().cloned();
+        let same_tile_set = match map_tile_set.as_ref() {
+            Some(tile_set) => clipboard.matches_tile_set(tile_set),
+            None => clipboard.tile_set.is_none(),
+        };
+        let cursor = self.cursor_effect.lock().position;
+        if let (true, Some(position)) = (same_tile_set, cursor) {
+            let update = tile_map.paste_region_update(
+                position,
+                &clipboard.to_region(),
+                OrthoTransformation::identity(),
+            );
+            self.send_update(tile_map, update);
             return;
         }
+        let tile_set = match (map_tile_set, clipboard.tile_set.as_ref()) {
+            (Some(tile_set), _) if clipboard.matches_tile_set(&tile_set) => Some(tile_set),
+            (_, Some(path)) => {
+                Log::warn(format!(
+                    "Pasted tiles refer to a different tile set {}.",
+                    path.display()
+                ));
+                Some(engine.resource_manager.request::<TileSet>(path))
+            }
+            (tile_set, None) => tile_set,
+        };
+        self.state
+            .lock_mut("paste_from_clipboard")
+            .set_stamp_from_clipboard(&clipboard, tile_set);
+    }
+
+    fn delete(&mut self, controller: &mut dyn SceneController, engine: &mut Engine) {
+        let Some(game_scene) = controller.downcast_mut::<GameScene>() else {
+            return;
+        };
+        let scene = &engine.scenes[game_scene.scene];
+        let Some(tile_map) = scene.graph.try_get_of_type::<TileMap>(self.tile_map) else {
+            return;
+        };
         let mut update = TilesUpdate::default();
-        for position in sel {
+        for position in self.select_effect.lock().positions.iter() {
             let _ = update.insert(*position, None);
         }
-        self.sender.do_command(SetMapTilesCommand {
-            tile_map: self.tile_map,
-            tiles: update,
-        });
+        self.send_update(tile_map, update);
+    }
+
+    /// Send the command that applies the given update to the tile map, so it can be undone.
+    /// Cells that would not be changed by the update are left out, and nothing is sent if
+    /// no cell would change, so the command stack only records actual modifications.
+    /// An update that does nothing but erase tiles is sent as an [`EraseMapTilesCommand`].
+    fn send_update(&self, tile_map: &TileMap, mut update: TilesUpdate) {
+        if let Some(tiles) = tile_map.tiles().map(|r| r.data_ref()) {
+            if let Some(tiles) = tiles.as_loaded_ref() {
+                let unchanged = update
+                    .iter()
+                    .filter(|(position, handle)| {
+                        tiles.get(**position) == **handle
+                            && tiles.transformation(**position) == update.transformation(**position)
+                    })
+                    .map(|(position, _)| *position)
+                    .collect::<Vec<_>>();
+                for position in unchanged {
+                    update.remove(&position);
+                }
+            }
+        }
+        if update.is_empty() {
+            return;
+        }
+        if update.values().all(Option::is_none) {
+            self.sender.do_command(EraseMapTilesCommand::new(
+                self.tile_map,
+                update.keys().copied().collect(),
+            ));
+        } else {
+            self.sender.do_command(SetMapTilesCommand {
+                tile_map: self.tile_map,
+                tiles: update,
+            });
+        }
     }
 }
 
@@ -260,9 +392,86 @@ fn update_select(
     state.update_stamp(tile_map.tile_set().cloned(), |p| tiles.get(p));
 }
 
+/// Applies the terrains of the active brush of the tile map to the given update, so the painted
+/// terrain tiles and their neighbors get the tiles that match their surroundings.
+fn autotile(tile_map: &TileMap, update: &mut TilesUpdate) {
+    let Some(brush) = tile_map.active_brush().map(|r| r.data_ref()) else {
+        return;
+    };
+    let Some(brush) = brush.as_loaded_ref() else {
+        return;
+    };
+    if brush.terrains.is_empty() {
+        return;
+    }
+    let Some(tiles) = tile_map.tiles().map(|r| r.data_ref()) else {
+        return;
+    };
+    let Some(tiles) = tiles.as_loaded_ref() else {
+        return;
+    };
+    brush.autotile(tiles, update);
+}
+
+/// The tile variants of the active brush of the tile map, which are drawn randomly in place of
+/// the tiles of the brush.
+fn brush_variants(tile_map: &TileMap) -> Vec<TileVariants> {
+    let Some(brush) = tile_map.active_brush().map(|r| r.data_ref()) else {
+        return Vec::new();
+    };
+    brush
+        .as_loaded_ref()
+        .map(|b| b.variants.clone())
+        .unwrap_or_default()
+}
+
+/// Applies the keyboard modifiers to the shape that the given tool draws from `start` to `end`.
+/// Holding shift makes rects and ellipses square, and snaps lines to horizontal, vertical, or diagonal.
+/// Holding ctrl puts the center of the shape at `start` instead of one of its ends.
+fn constrain_shape(
+    tool: DrawingMode,
+    modifiers: KeyboardModifiers,
+    start: Vector2<i32>,
+    end: Vector2<i32>,
+) -> (Vector2<i32>, Vector2<i32>) {
+    let mut delta = end - start;
+    let (dx, dy) = (delta.x.abs(), delta.y.abs());
+    let sign = |v: i32| if v < 0 { -1 } else { 1 };
+    match tool {
+        DrawingMode::RectFill
+        | DrawingMode::NineSlice
+        | DrawingMode::Ellipse
+        | DrawingMode::Generate => {
+            if modifiers.shift {
+                let size = dx.max(dy);
+                delta = Vector2::new(size * sign(delta.x), size * sign(delta.y));
+            }
+        }
+        DrawingMode::Line => {
+            if modifiers.shift {
+                if 2 * dy < dx {
+                    delta.y = 0;
+                } else if 2 * dx < dy {
+                    delta.x = 0;
+                } else {
+                    let size = dx.max(dy);
+                    delta = Vector2::new(size * sign(delta.x), size * sign(delta.y));
+                }
+            }
+        }
+        _ => return (start, end),
+    }
+    if modifiers.control {
+        (start - delta, start + delta)
+    } else {
+        (start, start + delta)
+    }
+}
+
 fn draw(
     update: &mut TransTilesUpdate,
     tiles: &TileMapData,
+    variants: &[TileVariants],
     tool: DrawingMode,
     state: &TileDrawStateGuard<'_>,
     start: Vector2<i32>,
@@ -272,7 +481,9 @@ fn draw(
     match tool {
         DrawingMode::Pick => (),
         DrawingMode::Editor => (),
-        DrawingMode::Draw => update.draw_tiles(end, stamp),
+        DrawingMode::Draw => {
+            update.draw_tiles_from(end, stamp, &VariantTileSource(stamp, variants))
+        }
         DrawingMode::Erase => {
             if stamp.is_empty() {
                 update.erase(end);
@@ -283,9 +494,11 @@ fn draw(
         DrawingMode::RectFill => {
             update.clear();
             if state.random_mode {
-                update.rect_fill_random(start, end, stamp);
+                let source = RandomTileSource(stamp);
+                update.rect_fill_from(start, end, &VariantTileSource(&source, variants));
             } else {
-                update.rect_fill(start, end, stamp);
+                let source = stamp.repeat(start, end);
+                update.rect_fill_from(start, end, &VariantTileSource(&source, variants));
             }
         }
         DrawingMode::NineSlice => {
@@ -299,18 +512,37 @@ fn draw(
         DrawingMode::Line => {
             update.clear();
             if state.random_mode {
-                update.draw_line(start, end, &RandomTileSource(stamp));
+                let source = RandomTileSource(stamp);
+                update.draw_line(start, end, &VariantTileSource(&source, variants));
+            } else {
+                let source = stamp.repeat(start, end);
+                update.draw_line(start, end, &VariantTileSource(&source, variants));
+            }
+        }
+        DrawingMode::Ellipse => {
+            update.clear();
+            if state.random_mode {
+                let source = RandomTileSource(stamp);
+                update.ellipse_fill_from(start, end, &VariantTileSource(&source, variants));
             } else {
-                update.draw_line(start, end, &stamp.repeat(start, end));
+                let source = stamp.repeat(start, end);
+                update.ellipse_fill_from(start, end, &VariantTileSource(&source, variants));
             }
         }
         DrawingMode::FloodFill => {
             if state.random_mode {
-                update.flood_fill(tiles, end, &RandomTileSource(stamp));
+                let source = RandomTileSource(stamp);
+                update.flood_fill(tiles, end, &VariantTileSource(&source, variants));
             } else {
-                update.flood_fill(tiles, end, &stamp.repeat_anywhere());
+                let source = stamp.repeat_anywhere();
+                update.flood_fill(tiles, end, &VariantTileSource(&source, variants));
             }
         }
+        DrawingMode::Generate => {
+            update.clear();
+            // The seed depends on the rect, so the tiles do not change while the mouse stands still.
+            update.generate(start, end, stamp, tiles, hash64(&(start, end)));
+        }
     }
 }
 
@@ -331,6 +563,7 @@ impl InteractionMode for TileMapInteractionMode {
         let mods = engine.user_interfaces.first().keyboard_modifiers();
         let state = self.state.lock();
         self.current_tool = state.drawing_mode;
+        self.modifiers = mods;
         let grid_coord = self.pick_grid(scene, game_scene, mouse_position, frame_size);
         let Some(tile_map) = scene.graph.try_get_mut_of_type::<TileMap>(self.tile_map) else {
             return;
@@ -392,6 +625,7 @@ impl InteractionMode for TileMapInteractionMode {
                     draw(
                         &mut self.update_effect.lock().update,
                         tiles,
+                        &brush_variants(tile_map),
                         mode,
                         &state,
                         grid_coord,
@@ -456,12 +690,10 @@ impl InteractionMode for TileMapInteractionMode {
                         .clone_from(&self.select_effect.lock().positions);
                 } else if let Some(tile_set) = state.tile_set.as_ref().or(tile_map.tile_set()) {
                     let update_source = &mut self.update_effect.lock().update;
-                    let update =
+                    let mut update =
                         update_source.build_tiles_update(&TileSetRef::new(tile_set).as_loaded());
-                    self.sender.do_command(SetMapTilesCommand {
-                        tile_map: tile_map_handle,
-                        tiles: update,
-                    });
+                    autotile(tile_map, &mut update);
+                    self.send_update(tile_map, update);
                     update_source.clear();
                 }
             }
@@ -483,6 +715,7 @@ impl InteractionMode for TileMapInteractionMode {
             return;
         };
 
+        let mods = engine.user_interfaces.first().keyboard_modifiers();
         let scene = &mut engine.scenes[game_scene.scene];
 
         let grid_coord = self.pick_grid(scene, game_scene, mouse_position, frame_size);
@@ -509,12 +742,13 @@ impl InteractionMode for TileMapInteractionMode {
             return;
         };
 
-        if end == grid_coord {
+        if end == grid_coord && mods == self.modifiers {
             return;
         }
 
         let end = grid_coord;
         self.current_grid_position = Some(grid_coord);
+        self.modifiers = mods;
 
         let tile_map_handle = self.tile_map;
         let Some(tile_map) = scene.graph.try_get_mut_of_type::<TileMap>(tile_map_handle) else {
@@ -544,9 +778,11 @@ impl InteractionMode for TileMapInteractionMode {
                         end,
                     );
                 } else {
+                    let (start, end) = constrain_shape(self.current_tool, mods, start, end);
                     draw(
                         &mut self.update_effect.lock().update,
                         tiles,
+                        &brush_variants(tile_map),
                         self.current_tool,
                         &state,
                         start,
@@ -588,7 +824,7 @@ impl InteractionMode for TileMapInteractionMode {
             return;
         };
 
-        let transform = tile_map.global_transform();
+        let transform = tile_map.tile_map_transform();
         let ctx = &mut scene.drawing_context;
 
         let mut draw_line = |begin: Vector2<i32>, end: Vector2<i32>, color: Color| {
@@ -611,6 +847,37 @@ impl InteractionMode for TileMapInteractionMode {
         for x in -size..size {
             draw_line(Vector2::new(x, -size), Vector2::new(x, size), Color::WHITE);
         }
+
+        if !self.state.lock().show_colliders {
+            return;
+        }
+        let transform = tile_map.tile_map_transform();
+        let grid_layout = tile_map.grid_layout();
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+        tile_map.tile_collider_loop(|position, transformation, _, color, tile_collider| {
+            vertices.clear();
+            triangles.clear();
+            tile_collider.build_transformed_collider_shape(
+                &Matrix4::identity(),
+                grid_layout.cell_origin(position).to_homogeneous(),
+                transformation,
+                &mut vertices,
+                &mut triangles,
+            );
+            let outlines = merge_tile_collider_outlines(&vertices, &triangles);
+            for [begin, end] in outline_segments(&outlines) {
+                ctx.add_line(Line {
+                    begin: transform
+                        .transform_point(&Vector3::new(begin.x, begin.y, -0.02).into())
+                        .coords,
+                    end: transform
+                        .transform_point(&Vector3::new(end.x, end.y, -0.02).into())
+                        .coords,
+                    color,
+                });
+            }
+        });
     }
 
     fn activate(&mut self, _controller: &dyn SceneController, _engine: &mut Engine) {}
@@ -633,10 +900,18 @@ impl InteractionMode for TileMapInteractionMode {
     fn on_hot_key_pressed(
         &mut self,
         hotkey: &HotKey,
-        _controller: &mut dyn SceneController,
-        _engine: &mut Engine,
-        _settings: &Settings,
+        controller: &mut dyn SceneController,
+        engine: &mut Engine,
+        settings: &Settings,
     ) -> bool {
+        if *hotkey == settings.key_bindings.copy_selection {
+            self.copy_to_clipboard(controller, engine);
+            return true;
+        }
+        if *hotkey == settings.key_bindings.paste {
+            self.paste_from_clipboard(controller, engine);
+            return true;
+        }
         if let HotKey::Some { code, .. } = hotkey {
             match *code {
                 PICK_KEY => {
@@ -661,7 +936,7 @@ impl InteractionMode for TileMapInteractionMode {
                     return true;
                 }
                 DEL_KEY => {
-                    self.delete();
+                    self.delete(controller, engine);
                     return true;
                 }
                 _ => (),
@@ -22,6 +22,7 @@
 
 #![allow(clippy::collapsible_match)] // STFU
 
+mod animation_preview;
 mod collider_editor;
 mod colliders_tab;
 mod commands;
@@ -34,12 +35,14 @@ pub mod panel;
 mod panel_preview;
 mod preview;
 mod properties_tab;
+mod terrain_editor;
 mod tile_bounds_editor;
 mod tile_editor;
 mod tile_inspector;
 mod tile_prop_editor;
 pub mod tileset;
 
+use animation_preview::*;
 use collider_editor::*;
 use colliders_tab::*;
 use fyrox::gui::style::resource::StyleResourceExt;
@@ -53,6 +56,7 @@ use palette::PaletteWidget;
 use panel::TileMapPanel;
 use panel_preview::*;
 use properties_tab::*;
+use terrain_editor::*;
 use tile_bounds_editor::*;
 use tile_editor::*;
 use tile_inspector::*;
@@ -62,7 +66,6 @@ use crate::fyrox::{
     core::{
         algebra::{Matrix4, Vector2, Vector3},
         color::Color,
-        math::{plane::Plane, Matrix4Ext},
         parking_lot::{Mutex, MutexGuard},
         pool::Handle,
         reflect::prelude::*,
@@ -90,8 +93,8 @@ use crate::fyrox::{
         node::Node,
         tilemap::{
             tileset::{TileSet, TileSetResource},
-            RandomTileSource, Stamp, TileBook, TileCollider, TileDefinitionHandle, TileMap,
-            TilePaletteStage,
+            RandomTileSource, Stamp, TileBook, TileClipboard, TileCollider, TileDefinitionHandle,
+            TileMap, TilePaletteStage, TileResourceRevision,
         },
         Scene,
     },
@@ -124,6 +127,10 @@ lazy_static! {
     static ref NINE_SLICE_IMAGE: Option<TextureResource> =
         load_image!("../../../resources/nine_slice.png");
     static ref LINE_IMAGE: Option<TextureResource> = load_image!("../../../resources/line.png");
+    static ref ELLIPSE_IMAGE: Option<TextureResource> =
+        load_image!("../../../resources/circle.png");
+    static ref GENERATE_IMAGE: Option<TextureResource> =
+        load_image!("../../../resources/terrain.png");
     static ref TURN_LEFT_IMAGE: Option<TextureResource> =
         load_image!("../../../resources/turn_left.png");
     static ref TURN_RIGHT_IMAGE: Option<TextureResource> =
@@ -131,6 +138,8 @@ lazy_static! {
     static ref FLIP_X_IMAGE: Option<TextureResource> = load_image!("../../../resources/flip_x.png");
     static ref FLIP_Y_IMAGE: Option<TextureResource> = load_image!("../../../resources/flip_y.png");
     static ref RANDOM_IMAGE: Option<TextureResource> = load_image!("../../../resources/die.png");
+    static ref COLLIDER_IMAGE: Option<TextureResource> =
+        load_image!("../../../resources/collider.png");
     static ref PALETTE_IMAGE: Option<TextureResource> =
         load_image!("../../../resources/palette.png");
 }
@@ -200,9 +209,16 @@ pub enum DrawingMode {
     NineSlice,
     /// Drag the mouse to draw a line with the currently selected tiles.
     Line,
+    /// Drag the mouse to create an ellipse filled with the currently selected tiles.
+    /// The ellipse is inscribed in the rect between the start and the end of the mouse motion.
+    Ellipse,
     /// Use the currently active tile set editor field to modify the data of tiles in a tile set.
     /// This does nothing to tile maps or brushes.
     Editor,
+    /// Drag the mouse to create a rect filled with tiles generated by Wave Function Collapse,
+    /// using the currently selected tiles as an example of which tiles may be placed next to each other.
+    /// The tiles of a tile map around the rect are taken into account, so the generated tiles fit them.
+    Generate,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -250,6 +266,9 @@ pub struct TileMapEditorPlugin {
     /// The plugin provides a service where it holds onto some messages and sends them
     /// in the next frame.
     delayed_messages: Vec<DelayedMessage>,
+    /// The revisions of the resources of the tile set editor and the control panel, that were
+    /// used to build their widgets. They change when the resources are reloaded from disk.
+    tile_book_revisions: [Option<TileResourceRevision>; 2],
 }
 
 /// This is the state that is shared between the plugin, the palette widgets, the interaction mode,
@@ -274,6 +293,8 @@ pub struct TileDrawState {
     visible_colliders: FxHashSet<Uuid>,
     /// Does the user want tiles to be randomized?
     random_mode: bool,
+    /// Does the user want the colliders of the tile map to be drawn in the scene?
+    show_colliders: bool,
     /// The currently selected tiles.
     selection: TileDrawSelection,
 }
@@ -286,6 +307,7 @@ impl Debug for TileDrawState {
             .field("stamp", &self.stamp)
             .field("drawing_mode", &self.drawing_mode)
             .field("random_mode", &self.random_mode)
+            .field("show_colliders", &self.show_colliders)
             .field("selection", &self.selection)
             .finish()
     }
@@ -458,6 +480,27 @@ impl TileDrawState {
         self.selection.source = SelectionSource::None;
         self.on_selection_changed();
     }
+    /// Construct clipboard data from the current stamp, so that the tiles could be pasted
+    /// into some other tile map, possibly in some other editor instance.
+    pub fn stamp_to_clipboard(&self) -> TileClipboard {
+        TileClipboard::from_stamp(self.tile_set.as_ref(), &self.stamp)
+    }
+    /// Replace the current stamp with the tiles from the given clipboard data.
+    /// The selection is cleared, since the stamp no longer corresponds to any selected tiles.
+    pub fn set_stamp_from_clipboard(
+        &mut self,
+        clipboard: &TileClipboard,
+        tile_set: Option<TileSetResource>,
+    ) {
+        self.selection.positions.clear();
+        self.selection.source = SelectionSource::None;
+        self.on_selection_changed();
+        self.tile_set = tile_set;
+        clipboard.to_stamp(&mut self.stamp);
+        if self.drawing_mode == DrawingMode::Pick {
+            self.drawing_mode = DrawingMode::Draw;
+        }
+    }
     /// Update the stamp stored within this state to reflect the current selection
     /// and tile set. The given `tile_handle` function is used to determine the handles
     /// for each selection position.
@@ -465,15 +508,31 @@ impl TileDrawState {
     pub fn update_stamp<F>(&mut self, tile_set: Option<TileSetResource>, tile_handle: F)
     where
         F: Fn(Vector2<i32>) -> Option<TileDefinitionHandle>,
+    {
+        self.update_stamp_with_anchor(tile_set, None, tile_handle);
+    }
+    /// Update the stamp stored within this state to reflect the current selection
+    /// and tile set, like [`Self::update_stamp`]. If an anchor is given, the stamp is built
+    /// so that the tile at the anchor is at (0,0), instead of the center of the selection.
+    pub fn update_stamp_with_anchor<F>(
+        &mut self,
+        tile_set: Option<TileSetResource>,
+        anchor: Option<Vector2<i32>>,
+        tile_handle: F,
+    ) where
+        F: Fn(Vector2<i32>) -> Option<TileDefinitionHandle>,
     {
         self.tile_set = tile_set;
-        self.stamp.build(
-            self.selection
-                .positions
-                .iter()
-                .copied()
-                .filter_map(|p| Some((p, tile_handle(p)?))),
-        );
+        let tiles = self
+            .selection
+            .positions
+            .iter()
+            .copied()
+            .filter_map(|p| Some((p, tile_handle(p)?)));
+        match anchor {
+            Some(anchor) => self.stamp.build_with_anchor(tiles, anchor),
+            None => self.stamp.build(tiles),
+        }
     }
 }
 
@@ -563,6 +622,25 @@ impl TileMapEditorPlugin {
                 .active_editor = None;
         }
     }
+    /// Refreshes the tile set editor and the control panel, if their resources were reloaded.
+    fn sync_reloaded_resources(&mut self, ui: &mut UserInterface) {
+        let revisions = [
+            self.tile_set_editor
+                .as_ref()
+                .and_then(|e| e.tile_book().revision()),
+            self.panel.as_ref().and_then(|p| p.tile_book.revision()),
+        ];
+        if revisions == self.tile_book_revisions {
+            return;
+        }
+        self.tile_book_revisions = revisions;
+        if let Some(tile_set_editor) = self.tile_set_editor.as_mut() {
+            tile_set_editor.sync_to_model(ui);
+        }
+        if let Some(panel) = self.panel.as_mut() {
+            panel.sync_to_model(ui);
+        }
+    }
     fn send_delayed_messages(&mut self, ui: &mut UserInterface) {
         let msgs = &mut self.delayed_messages;
         for dm in msgs.iter_mut() {
@@ -694,6 +772,7 @@ impl EditorPlugin for TileMapEditorPlugin {
 
     fn on_update(&mut self, editor: &mut Editor) {
         self.send_delayed_messages(editor.engine.user_interfaces.first_mut());
+        self.sync_reloaded_resources(editor.engine.user_interfaces.first_mut());
 
         self.update_state();
 
@@ -37,6 +37,7 @@ use crate::fyrox::{
         type_traits::prelude::*,
         visitor::prelude::*,
     },
+    fxhash::hash64,
     fxhash::FxHashMap,
     fxhash::FxHashSet,
     graph::BaseSceneGraph,
@@ -53,9 +54,10 @@ use crate::fyrox::{
     material::{Material, MaterialResource},
     resource::texture::TextureKind,
     scene::tilemap::{
+        brush::TileBrushStamp,
         tileset::{TileSetPageSource, TileSetRef},
         OrthoTransformation, TileBook, TilePaletteStage, TileRect, TileRenderData, TileSetUpdate,
-        TileSource, TransTilesUpdate,
+        TileSource, Tiles, TransTilesUpdate,
     },
 };
 use std::cell::RefCell;
@@ -83,6 +85,8 @@ pub enum PaletteMessage {
     SelectAll,
     /// Select the given position.
     SelectOne(Vector2<i32>),
+    /// Select the given positions, replacing the current selection.
+    Select(Vec<Vector2<i32>>),
     /// Delete the selected tiles/pages in this view.
     Delete,
     /// Set the tint of the background material.
@@ -101,6 +105,7 @@ impl PaletteMessage {
     define_constructor!(PaletteMessage:Center => fn center(Vector2<i32>), layout: false);
     define_constructor!(PaletteMessage:SelectAll => fn select_all(), layout: false);
     define_constructor!(PaletteMessage:SelectOne => fn select_one(Vector2<i32>), layout: false);
+    define_constructor!(PaletteMessage:Select => fn select(Vec<Vector2<i32>>), layout: false);
     define_constructor!(PaletteMessage:Delete => fn delete(), layout: false);
     define_constructor!(PaletteMessage:MaterialColor => fn material_color(Color), layout: false);
     define_constructor!(PaletteMessage:SyncToState => fn sync_to_state(), layout: false);
@@ -350,7 +355,9 @@ impl PaletteWidget {
             DrawingMode::FloodFill => Some(CursorIcon::Crosshair),
             DrawingMode::RectFill => Some(CursorIcon::Crosshair),
             DrawingMode::Line => Some(CursorIcon::Crosshair),
+            DrawingMode::Ellipse => Some(CursorIcon::Crosshair),
             DrawingMode::NineSlice => Some(CursorIcon::Crosshair),
+            DrawingMode::Generate => Some(CursorIcon::Crosshair),
             DrawingMode::Editor => None,
         };
         self.send_cursor_icon(icon, ui);
@@ -585,15 +592,11 @@ impl PaletteWidget {
         }
     }
     pub fn sync_selection_to_model(&mut self) {
-        let page = self.page.unwrap_or_default();
         let mut state = self.state.lock_mut("sync_selection_to_model");
         state.tile_set = self.content.get_tile_set();
         self.selecting_tiles.clear();
         let stamp_trans = state.stamp.transformation();
-        state.update_stamp(self.content.get_tile_set(), |p| {
-            self.content
-                .get_tile_handle(ResourceTilePosition::new(self.stage(), page, p))
-        });
+        self.update_stamp(&mut state);
         state.stamp.transform(stamp_trans);
     }
     fn update_selection(&mut self) {
@@ -604,7 +607,6 @@ impl PaletteWidget {
         if self.kind == TilePaletteStage::Tiles && self.page.is_none() {
             return;
         }
-        let page = self.page.unwrap_or_default();
         let mut state = self.state.lock_mut("update_selection");
         state.tile_set = self.content.get_tile_set();
         state.set_palette(self.handle);
@@ -616,13 +618,10 @@ impl PaletteWidget {
         } else {
             positions.extend(rect.iter());
         }
-        state.update_stamp(self.content.get_tile_set(), |p| {
-            self.content
-                .get_tile_handle(ResourceTilePosition::new(self.stage(), page, p))
-        });
+        self.update_stamp(&mut state);
     }
     fn finalize_selection(&mut self, ui: &mut UserInterface) {
-        let MouseMode::Drawing { end, .. } = self.mode.clone() else {
+        let MouseMode::Drawing { start_tile, end } = self.mode.clone() else {
             return;
         };
         let end_tile = end.grid;
@@ -634,16 +633,18 @@ impl PaletteWidget {
             }
             TilePaletteStage::Pages => self.send_new_page(end_tile, ui),
         }
-        let page = self.page.unwrap_or_default();
         let mut state = self.state.lock_mut("finalize_selection");
         state.tile_set = self.content.get_tile_set();
         state.set_palette(self.handle);
+        // A click on a tile of a brush stamp selects the whole stamp.
+        if start_tile == end_tile && state.selection_positions().len() == 1 {
+            if let Some(stamp) = self.brush_stamp_at(end_tile) {
+                state.selection_positions_mut().extend(stamp.rect().iter());
+            }
+        }
         let positions = state.selection_positions();
         self.selecting_tiles.clone_from(positions);
-        state.update_stamp(self.content.get_tile_set(), |p| {
-            self.content
-                .get_tile_handle(ResourceTilePosition::new(self.stage(), page, p))
-        });
+        self.update_stamp(&mut state);
     }
     fn select_all(&mut self) {
         let Some(page) = self.page else {
@@ -659,26 +660,55 @@ impl PaletteWidget {
         let sel = state.selection_positions_mut();
         sel.clear();
         sel.extend(results.iter().copied());
-        state.update_stamp(self.content.get_tile_set(), |p| {
-            self.content
-                .get_tile_handle(ResourceTilePosition::new(self.stage(), page, p))
-        });
+        self.update_stamp(&mut state);
     }
     fn select_one(&mut self, position: Vector2<i32>) {
-        let Some(page) = self.page else {
+        self.select(&[position]);
+    }
+    fn select(&mut self, positions: &[Vector2<i32>]) {
+        if self.page.is_none() {
             return;
-        };
-        let mut state = self.state.lock_mut("select_one");
+        }
+        let mut state = self.state.lock_mut("select");
         state.tile_set = self.content.get_tile_set();
         state.set_palette(self.handle);
         let sel = state.selection_positions_mut();
         sel.clear();
-        sel.insert(position);
-        state.update_stamp(self.content.get_tile_set(), |p| {
+        sel.extend(positions.iter().copied());
+        self.update_stamp(&mut state);
+    }
+    /// Rebuild the stamp of the given state from the current selection. If the selection consists
+    /// of the tiles of a brush stamp, then the stamp is anchored at the anchor cell of the brush
+    /// stamp, so it is drawn, rotated and flipped around that cell.
+    fn update_stamp(&self, state: &mut TileDrawState) {
+        let page = self.page.unwrap_or_default();
+        let anchor = self.brush_stamp_anchor(state.selection_positions());
+        state.update_stamp_with_anchor(self.content.get_tile_set(), anchor, |p| {
             self.content
                 .get_tile_handle(ResourceTilePosition::new(self.stage(), page, p))
         });
     }
+    /// The brush stamp that contains the given position on the current page, if this palette
+    /// shows the tiles of a brush.
+    fn brush_stamp_at(&self, position: Vector2<i32>) -> Option<TileBrushStamp> {
+        let TileBook::Brush(brush) = &self.content else {
+            return None;
+        };
+        if self.kind != TilePaletteStage::Tiles {
+            return None;
+        }
+        let page = self.page?;
+        let mut brush = brush.state();
+        brush.data()?.stamp_at(page, position).cloned()
+    }
+    /// The anchor of the brush stamp, whose region is exactly the given positions.
+    fn brush_stamp_anchor(&self, positions: &FxHashSet<Vector2<i32>>) -> Option<Vector2<i32>> {
+        let stamp = self.brush_stamp_at(*positions.iter().next()?)?;
+        let rect = stamp.rect();
+        let area = (rect.size.x * rect.size.y) as usize;
+        (positions.len() == area && positions.iter().all(|p| rect.contains(*p)))
+            .then(|| stamp.anchor_position())
+    }
     fn begin_motion(&mut self, mode: DrawingMode, pos: MousePos, ui: &mut UserInterface) {
         match mode {
             DrawingMode::Pick => {
@@ -925,6 +955,22 @@ impl PaletteWidget {
                     self.update.draw_line(start, end, &stamp.repeat(start, end));
                 }
             }
+            DrawingMode::Ellipse => {
+                self.update.clear();
+                if state.random_mode {
+                    self.update
+                        .ellipse_fill_from(start, end, &RandomTileSource(stamp));
+                } else {
+                    self.update
+                        .ellipse_fill_from(start, end, &stamp.repeat(start, end));
+                }
+            }
+            DrawingMode::Generate => {
+                self.update.clear();
+                let seed = hash64(&(start, end));
+                self.update
+                    .generate(start, end, stamp, &Tiles::default(), seed);
+            }
             DrawingMode::Editor => {
                 if let Some(editor) = &state.active_editor {
                     if let Some(handle) = TileDefinitionHandle::try_new(page, end) {
@@ -959,9 +1005,11 @@ impl PaletteWidget {
             DrawingMode::Draw => self.send_update(),
             DrawingMode::Erase => self.send_update(),
             DrawingMode::Line => self.send_update(),
+            DrawingMode::Ellipse => self.send_update(),
             DrawingMode::FloodFill => self.send_update(),
             DrawingMode::RectFill => self.send_update(),
             DrawingMode::NineSlice => self.send_update(),
+            DrawingMode::Generate => self.send_update(),
             DrawingMode::Editor => self.send_tile_set_update(),
         }
     }
@@ -1256,6 +1304,8 @@ impl PaletteWidget {
             DrawingMode::RectFill => true,
             DrawingMode::NineSlice => true,
             DrawingMode::Line => true,
+            DrawingMode::Ellipse => true,
+            DrawingMode::Generate => true,
             DrawingMode::Editor => false,
         }
     }
@@ -1535,6 +1585,7 @@ impl Control for PaletteWidget {
                     }
                     PaletteMessage::SelectAll => self.select_all(),
                     PaletteMessage::SelectOne(v) => self.select_one(*v),
+                    PaletteMessage::Select(positions) => self.select(positions),
                     PaletteMessage::Delete => drop(self.delete_tiles(ui)),
                     PaletteMessage::MaterialColor(color) => self.material_color = *color,
                     PaletteMessage::SyncToState => self.sync_to_state(ui),
@@ -37,6 +37,7 @@ use crate::{
             decorator::DecoratorMessage,
             grid::{Column, GridBuilder, Row},
             message::{MessageDirection, UiMessage},
+            searchbar::{SearchBarBuilder, SearchBarMessage},
             widget::{WidgetBuilder, WidgetMessage},
             window::{WindowBuilder, WindowMessage, WindowTitle},
             wrap_panel::WrapPanelBuilder,
@@ -49,7 +50,10 @@ use crate::{
             text::{TextBuilder, TextMessage},
             window::Window,
         },
-        scene::tilemap::{tileset::TileSet, TileBook, *},
+        scene::tilemap::{
+            tileset::{NamableValue, TileSet, TileSetPropertyLayer, TileSetPropertyValue},
+            TileBook, *,
+        },
         scene::{
             node::Node,
             tilemap::{
@@ -79,6 +83,28 @@ fn highlight_tool_button(button: Handle<UiNode>, highlight: bool, ui: &UserInter
     ));
 }
 
+/// True if the given value of the given property matches the search text. Strings match if they
+/// contain the text. Numbers match if they are equal to the text, or if they have a named value
+/// whose name contains the text.
+fn property_matches(
+    layer: &TileSetPropertyLayer,
+    value: &TileSetPropertyValue,
+    text: &str,
+) -> bool {
+    let named = |value: NamableValue| {
+        layer
+            .named_values
+            .iter()
+            .any(|n| n.value == value && n.name.to_lowercase().contains(text))
+    };
+    match value {
+        TileSetPropertyValue::I32(v) => v.to_string() == text || named(NamableValue::I32(*v)),
+        TileSetPropertyValue::F32(v) => v.to_string() == text || named(NamableValue::F32(*v)),
+        TileSetPropertyValue::String(v) => v.to_lowercase().contains(text),
+        TileSetPropertyValue::NineSlice(v) => v.0.iter().any(|v| named(NamableValue::I8(*v))),
+    }
+}
+
 fn make_resource_chooser(
     ctx: &mut BuildContext,
     text: &str,
@@ -131,6 +157,8 @@ pub struct TileMapPanel {
     pages: Handle<UiNode>,
     /// The palette widget that allows the user to select the tiles to draw with.
     palette: Handle<UiNode>,
+    /// The search bar that selects the tiles whose properties match the search text.
+    search_bar: Handle<UiNode>,
     /// The button that switches the control to using the current brush, if there is one.
     brush_button: Handle<UiNode>,
     /// The button that switches the control to using the current tile set.
@@ -149,8 +177,14 @@ pub struct TileMapPanel {
     nine_slice_button: Handle<UiNode>,
     /// Tool selection button for the line tool.
     line_button: Handle<UiNode>,
+    /// Tool selection button for the ellipse fill tool.
+    ellipse_button: Handle<UiNode>,
+    /// Tool selection button for the procedural generation tool.
+    generate_button: Handle<UiNode>,
     /// Button that toggles the tools into random mode.
     random_button: Handle<UiNode>,
+    /// Button that toggles drawing the colliders of the tile map in the scene.
+    colliders_button: Handle<UiNode>,
     /// Button to rotate the selected tiles counter-clockwise by 90 degrees.
     left_button: Handle<UiNode>,
     /// Button to rotate the selected tiles clockwise by 90 degrees.
@@ -194,17 +228,28 @@ impl TileMapPanel {
                 .with_child(preview),
         )
         .build(ctx);
-        let pages_frame = BorderBuilder::new(
+        let search_bar = SearchBarBuilder::new(
             WidgetBuilder::new()
                 .on_row(2)
                 .with_margin(Thickness::uniform(2.0))
+                .with_tooltip(make_simple_tooltip(
+                    ctx,
+                    "Select the tiles with matching property values. \
+                    Type name=value to search only the named property.",
+                )),
+        )
+        .build(ctx);
+        let pages_frame = BorderBuilder::new(
+            WidgetBuilder::new()
+                .on_row(3)
+                .with_margin(Thickness::uniform(2.0))
                 .with_foreground(Brush::Solid(Color::BLACK).into())
                 .with_child(pages),
         )
         .build(ctx);
         let palette_frame = BorderBuilder::new(
             WidgetBuilder::new()
-                .on_row(3)
+                .on_row(4)
                 .with_margin(Thickness::uniform(2.0))
                 .with_foreground(Brush::Solid(Color::BLACK).into())
                 .with_child(palette),
@@ -263,7 +308,8 @@ impl TileMapPanel {
             width,
             height,
             RECT_FILL_IMAGE.clone(),
-            "Fill the rectangle using the current brush.",
+            "Fill the rectangle using the current brush. \
+            Hold shift to draw a square, hold ctrl to draw from the center.",
             Some(4),
         );
         let nine_slice_button = make_drawing_mode_button(
@@ -279,16 +325,35 @@ impl TileMapPanel {
             width,
             height,
             LINE_IMAGE.clone(),
-            "Draw a line using tiles from the given brush.",
+            "Draw a line using tiles from the given brush. \
+            Hold shift to snap the line to horizontal, vertical, or diagonal.",
             Some(6),
         );
+        let ellipse_button = make_drawing_mode_button(
+            ctx,
+            width,
+            height,
+            ELLIPSE_IMAGE.clone(),
+            "Fill the ellipse using the current brush. \
+            Hold shift to draw a circle, hold ctrl to draw from the center.",
+            Some(7),
+        );
+        let generate_button = make_drawing_mode_button(
+            ctx,
+            width,
+            height,
+            GENERATE_IMAGE.clone(),
+            "Fill the rectangle with tiles generated from the current brush, \
+            which is used as an example of how tiles may be placed next to each other.",
+            Some(8),
+        );
         let left_button = make_drawing_mode_button(
             ctx,
             width,
             height,
             TURN_LEFT_IMAGE.clone(),
             "Rotate left 90 degrees.",
-            Some(7),
+            Some(9),
         );
         let right_button = make_drawing_mode_button(
             ctx,
@@ -296,7 +361,7 @@ impl TileMapPanel {
             height,
             TURN_RIGHT_IMAGE.clone(),
             "Rotate right 90 degrees.",
-            Some(8),
+            Some(10),
         );
         let flip_x_button = make_drawing_mode_button(
             ctx,
@@ -304,7 +369,7 @@ impl TileMapPanel {
             height,
             FLIP_X_IMAGE.clone(),
             "Flip along x axis.",
-            Some(9),
+            Some(11),
         );
         let flip_y_button = make_drawing_mode_button(
             ctx,
@@ -312,7 +377,7 @@ impl TileMapPanel {
             height,
             FLIP_Y_IMAGE.clone(),
             "Flip along y axis.",
-            Some(10),
+            Some(12),
         );
         let random_button = make_drawing_mode_button(
             ctx,
@@ -320,7 +385,15 @@ impl TileMapPanel {
             height,
             RANDOM_IMAGE.clone(),
             "Toggle random fill mode.",
-            Some(11),
+            Some(13),
+        );
+        let colliders_button = make_drawing_mode_button(
+            ctx,
+            width,
+            height,
+            COLLIDER_IMAGE.clone(),
+            "Toggle showing the colliders of the tiles in the scene.",
+            Some(14),
         );
 
         let drawing_modes_panel = WrapPanelBuilder::new(
@@ -331,7 +404,9 @@ impl TileMapPanel {
                 .with_child(pick_button)
                 .with_child(rect_fill_button)
                 .with_child(nine_slice_button)
-                .with_child(line_button),
+                .with_child(line_button)
+                .with_child(ellipse_button)
+                .with_child(generate_button),
         )
         .with_orientation(Orientation::Horizontal)
         .build(ctx);
@@ -341,7 +416,8 @@ impl TileMapPanel {
                 .with_child(right_button)
                 .with_child(flip_x_button)
                 .with_child(flip_y_button)
-                .with_child(random_button),
+                .with_child(random_button)
+                .with_child(colliders_button),
         )
         .with_orientation(Orientation::Horizontal)
         .build(ctx);
@@ -369,11 +445,13 @@ impl TileMapPanel {
             WidgetBuilder::new()
                 .with_child(tile_set_name)
                 .with_child(header)
+                .with_child(search_bar)
                 .with_child(pages_frame)
                 .with_child(palette_frame),
         )
         .add_row(Row::auto())
         .add_row(Row::auto())
+        .add_row(Row::auto())
         .add_row(Row::stretch())
         .add_row(Row::generic(SizeMode::Stretch, 200.0))
         .add_column(Column::stretch())
@@ -394,6 +472,7 @@ impl TileMapPanel {
             preview,
             pages,
             palette,
+            search_bar,
             brush_button,
             tile_set_button,
             draw_button,
@@ -403,11 +482,14 @@ impl TileMapPanel {
             rect_fill_button,
             nine_slice_button,
             line_button,
+            ellipse_button,
+            generate_button,
             left_button,
             right_button,
             flip_x_button,
             flip_y_button,
             random_button,
+            colliders_button,
         }
     }
 
@@ -586,6 +668,81 @@ impl TileMapPanel {
         ));
     }
 
+    /// Select the tiles whose property values match the given search text, on the first page
+    /// that has any matching tiles. The text may be `name=value` to search only the property
+    /// with the given name, otherwise the values of all properties are searched.
+    fn search(&self, text: &str, ui: &mut UserInterface) {
+        let text = text.trim().to_lowercase();
+        if text.is_empty() {
+            return;
+        }
+        let (name, value) = match text.split_once('=') {
+            Some((name, value)) => (Some(name.trim()), value.trim()),
+            None => (None, text.as_str()),
+        };
+        let Some(tile_set) = self.tile_book.get_tile_set() else {
+            return;
+        };
+        let mut pages = self.tile_book.get_all_page_positions();
+        pages.sort_by_key(|p| (p.y, p.x));
+        // Find the handles before locking the tile set, since the tile book may be the tile set.
+        let pages = pages
+            .into_iter()
+            .map(|page| {
+                let positions = self.tile_book.get_all_tile_positions(page);
+                let tiles = self
+                    .tile_book
+                    .get_tile_iter(TilePaletteStage::Tiles, page, positions.into_iter())
+                    .collect::<Vec<_>>();
+                (page, tiles)
+            })
+            .collect::<Vec<_>>();
+        let tile_set = tile_set.data_ref();
+        let Some(tile_set) = tile_set.as_loaded_ref() else {
+            return;
+        };
+        let layers = tile_set
+            .properties
+            .iter()
+            .filter(|layer| name.map_or(true, |name| layer.name.to_lowercase() == name))
+            .collect::<Vec<_>>();
+        for (page, tiles) in pages {
+            let found = tiles
+                .into_iter()
+                .filter(|(_, handle)| {
+                    layers.iter().any(|layer| {
+                        tile_set
+                            .property_value(*handle, layer.uuid)
+                            .is_some_and(|v| property_matches(layer, &v, value))
+                    })
+                })
+                .map(|(position, _)| position)
+                .collect::<Vec<_>>();
+            let Some(first) = found.first().copied() else {
+                continue;
+            };
+            for destination in [self.pages, self.palette] {
+                ui.send_message(PaletteMessage::set_page(
+                    destination,
+                    MessageDirection::ToWidget,
+                    self.tile_book.clone(),
+                    Some(page),
+                ));
+            }
+            ui.send_message(PaletteMessage::center(
+                self.palette,
+                MessageDirection::ToWidget,
+                first,
+            ));
+            ui.send_message(PaletteMessage::select(
+                self.palette,
+                MessageDirection::ToWidget,
+                found,
+            ));
+            return;
+        }
+    }
+
     /// Process the effect of pressing one of the buttons.
     fn handle_button(&mut self, button: Handle<UiNode>, ui: &mut UserInterface) {
         if button == self.draw_button {
@@ -602,9 +759,16 @@ impl TileMapPanel {
             self.state.lock_mut("tool button").drawing_mode = DrawingMode::NineSlice;
         } else if button == self.line_button {
             self.state.lock_mut("tool button").drawing_mode = DrawingMode::Line;
+        } else if button == self.ellipse_button {
+            self.state.lock_mut("tool button").drawing_mode = DrawingMode::Ellipse;
+        } else if button == self.generate_button {
+            self.state.lock_mut("tool button").drawing_mode = DrawingMode::Generate;
         } else if button == self.random_button {
             let mut state = self.state.lock_mut("random button");
             state.random_mode = !state.random_mode;
+        } else if button == self.colliders_button {
+            let mut state = self.state.lock_mut("colliders button");
+            state.show_colliders = !state.show_colliders;
         } else if button == self.left_button {
             self.state.lock_mut("left button").stamp.rotate(1);
         } else if button == self.right_button {
@@ -637,6 +801,12 @@ impl TileMapPanel {
         }
         if let Some(ButtonMessage::Click) = message.data() {
             self.handle_button(message.destination(), ui);
+        } else if let Some(SearchBarMessage::Text(text)) = message.data() {
+            if message.destination() == self.search_bar
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.search(text, ui);
+            }
         } else if let Some(PaletteMessage::SetPage { .. }) = message.data() {
             if message.destination() == self.pages
                 && message.direction() == MessageDirection::FromWidget
@@ -721,9 +891,12 @@ impl TileMapPanel {
             self.rect_fill_button,
             self.nine_slice_button,
             self.line_button,
+            self.ellipse_button,
+            self.generate_button,
         ];
         let state = self.state.lock();
         highlight_tool_button(self.random_button, state.random_mode, ui);
+        highlight_tool_button(self.colliders_button, state.show_colliders, ui);
         match state.drawing_mode {
             DrawingMode::Draw => {
                 highlight_all_except(self.draw_button, &buttons, true, ui);
@@ -746,6 +919,12 @@ impl TileMapPanel {
             DrawingMode::Line { .. } => {
                 highlight_all_except(self.line_button, &buttons, true, ui);
             }
+            DrawingMode::Ellipse => {
+                highlight_all_except(self.ellipse_button, &buttons, true, ui);
+            }
+            DrawingMode::Generate => {
+                highlight_all_except(self.generate_button, &buttons, true, ui);
+            }
             _ => {
                 highlight_all(&buttons, false, ui);
             }
@@ -75,7 +75,7 @@ pub struct PanelPreview {
 
 define_widget_deref!(PanelPreview);
 
-fn draw_tile(
+pub fn draw_tile(
     position: Rect<f32>,
     clip_bounds: Rect<f32>,
     tile: &TileRenderData,
@@ -0,0 +1,525 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The [`TileEditor`] for the terrains of a brush. This allows the tile set editor
+//! to add and remove the terrains that the brush uses for autotiling, and to choose
+//! which neighbors each selected tile expects. See [`TileTerrainEditor`] for more information.
+
+use commands::SetBrushTerrainsCommand;
+use fyrox::{
+    core::{algebra::Vector2, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        button::{Button, ButtonBuilder, ButtonMessage},
+        decorator::{DecoratorBuilder, DecoratorMessage},
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        grid::{Column, GridBuilder, Row},
+        message::UiMessage,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        text_box::{TextBoxBuilder, TextCommitMode},
+        utils::make_simple_tooltip,
+        widget::WidgetBuilder,
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    scene::tilemap::{
+        AutotileRuleSet, TileDefinitionHandle, TileSetUpdate, TileTerrain, AUTOTILE_E, AUTOTILE_N,
+        AUTOTILE_NE, AUTOTILE_NW, AUTOTILE_S, AUTOTILE_SE, AUTOTILE_SW, AUTOTILE_W,
+    },
+};
+
+use crate::{send_sync_message, MSG_SYNC_FLAG};
+
+use super::*;
+
+const RULE_SET_NAMES: &[&str] = &["Blob (47 tiles)", "Edges (16 tiles)"];
+
+/// The mask bits of the neighbor buttons, row by row from the top left.
+/// The center button has no bit, because it stands for the tile itself.
+const MASK_BITS: [u8; 9] = [
+    AUTOTILE_NW,
+    AUTOTILE_N,
+    AUTOTILE_NE,
+    AUTOTILE_W,
+    0,
+    AUTOTILE_E,
+    AUTOTILE_SW,
+    AUTOTILE_S,
+    AUTOTILE_SE,
+];
+
+const MASK_LABELS: [&str; 9] = ["NW", "N", "NE", "W", "", "E", "SW", "S", "SE"];
+
+const CENTER: usize = 4;
+
+const MASK_BUTTON_SIZE: f32 = 28.0;
+
+fn rule_set_to_index(rule_set: AutotileRuleSet) -> usize {
+    match rule_set {
+        AutotileRuleSet::Blob47 => 0,
+        AutotileRuleSet::Edges16 => 1,
+    }
+}
+
+fn index_to_rule_set(index: usize) -> AutotileRuleSet {
+    match index {
+        1 => AutotileRuleSet::Edges16,
+        _ => AutotileRuleSet::Blob47,
+    }
+}
+
+/// The edge bits that must be set for the given corner bit to matter.
+fn corner_edges(bit: u8) -> u8 {
+    match bit {
+        AUTOTILE_NE => AUTOTILE_N | AUTOTILE_E,
+        AUTOTILE_SE => AUTOTILE_S | AUTOTILE_E,
+        AUTOTILE_SW => AUTOTILE_S | AUTOTILE_W,
+        AUTOTILE_NW => AUTOTILE_N | AUTOTILE_W,
+        _ => 0,
+    }
+}
+
+fn send_visibility(ui: &UserInterface, destination: Handle<UiNode>, visible: bool) {
+    ui.send_message(WidgetMessage::visibility(
+        destination,
+        MessageDirection::ToWidget,
+        visible,
+    ));
+}
+
+fn send_enabled(ui: &UserInterface, destination: Handle<UiNode>, enabled: bool) {
+    ui.send_message(WidgetMessage::enabled(
+        destination,
+        MessageDirection::ToWidget,
+        enabled,
+    ));
+}
+
+fn highlight_tool_button(button: Handle<UiNode>, highlight: bool, ui: &UserInterface) {
+    let decorator = *ui.try_get_of_type::<Button>(button).unwrap().decorator;
+    ui.send_message(DecoratorMessage::select(
+        decorator,
+        MessageDirection::ToWidget,
+        highlight,
+    ));
+}
+
+fn make_label(name: &str, ctx: &mut BuildContext) -> Handle<UiNode> {
+    TextBuilder::new(WidgetBuilder::new())
+        .with_vertical_text_alignment(VerticalAlignment::Center)
+        .with_text(name)
+        .build(ctx)
+}
+
+fn make_field(label: &str, field: Handle<UiNode>, ctx: &mut BuildContext) -> Handle<UiNode> {
+    let label = make_label(label, ctx);
+    GridBuilder::new(WidgetBuilder::new().with_child(label).with_child(field))
+        .add_row(Row::auto())
+        .add_column(Column::strict(FIELD_LABEL_WIDTH))
+        .add_column(Column::stretch())
+        .build(ctx)
+}
+
+fn make_button(
+    label: &str,
+    tooltip: &str,
+    widget_builder: WidgetBuilder,
+    ctx: &mut BuildContext,
+) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        widget_builder
+            .with_tooltip(make_simple_tooltip(ctx, tooltip))
+            .with_margin(Thickness::uniform(1.0)),
+    )
+    .with_back(
+        DecoratorBuilder::new(
+            BorderBuilder::new(
+                WidgetBuilder::new().with_foreground(ctx.style.property(Style::BRUSH_DARKER)),
+            )
+            .with_pad_by_corner_radius(false)
+            .with_corner_radius((4.0).into())
+            .with_stroke_thickness(Thickness::uniform(1.0).into()),
+        )
+        .with_selected_brush(ctx.style.property(Style::BRUSH_BRIGHT_BLUE))
+        .with_normal_brush(ctx.style.property(Style::BRUSH_LIGHT))
+        .with_hover_brush(ctx.style.property(Style::BRUSH_LIGHTER))
+        .with_pressed_brush(ctx.style.property(Style::BRUSH_LIGHTEST))
+        .build(ctx),
+    )
+    .with_content(
+        TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+            .with_horizontal_text_alignment(HorizontalAlignment::Center)
+            .with_vertical_text_alignment(VerticalAlignment::Center)
+            .with_text(label)
+            .build(ctx),
+    )
+    .build(ctx)
+}
+
+fn make_mask_button(index: usize, ctx: &mut BuildContext) -> Handle<UiNode> {
+    let tooltip = if index == CENTER {
+        "Toggle whether the selected tiles are a part of the terrain".to_string()
+    } else {
+        format!(
+            "Toggle whether the {} neighbor of the selected tiles is a part of the terrain",
+            MASK_LABELS[index]
+        )
+    };
+    let widget_builder = WidgetBuilder::new()
+        .on_row(index / 3)
+        .on_column(index % 3)
+        .with_width(MASK_BUTTON_SIZE)
+        .with_height(MASK_BUTTON_SIZE);
+    make_button(MASK_LABELS[index], &tooltip, widget_builder, ctx)
+}
+
+/// An editor for the terrains of a brush, which are used to automatically choose tiles
+/// depending on their neighbors. The user picks a terrain from a list, then toggles
+/// the buttons of a 3x3 grid to choose which neighbors the selected tiles expect to
+/// be a part of the same terrain. The center of the grid adds the selected tiles
+/// to the terrain or removes them from it.
+///
+/// Since terrain rules refer to tile set tiles, each selected brush tile is
+/// redirected to the tile set tile that it represents.
+pub struct TileTerrainEditor {
+    /// The handle for the overall editor.
+    handle: Handle<UiNode>,
+    /// The dropdown list of the terrains of the brush.
+    terrain_list: Handle<UiNode>,
+    /// The button that adds a new terrain to the brush.
+    add_button: Handle<UiNode>,
+    /// The button that removes the current terrain from the brush.
+    remove_button: Handle<UiNode>,
+    /// The field for the name of the current terrain.
+    name_field: Handle<UiNode>,
+    /// The dropdown list of rule sets for the current terrain.
+    rule_set_list: Handle<UiNode>,
+    /// The buttons for the neighbors of the selected tiles, in the order of [`MASK_BITS`].
+    mask_buttons: [Handle<UiNode>; 9],
+    /// The index of the terrain that is being edited.
+    terrain: Option<usize>,
+    /// The names of the terrains in the terrain list, so that the list is only rebuilt
+    /// when the names change.
+    names: Vec<String>,
+}
+
+impl TileTerrainEditor {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let terrain_list = DropdownListBuilder::new(WidgetBuilder::new().on_column(1)).build(ctx);
+        let add_button = make_button(
+            "+",
+            "Add a new terrain to the brush",
+            WidgetBuilder::new().on_column(2),
+            ctx,
+        );
+        let remove_button = make_button(
+            "-",
+            "Remove the terrain from the brush",
+            WidgetBuilder::new().on_column(3),
+            ctx,
+        );
+        let terrain_label = make_label("Terrain", ctx);
+        let terrain_grid = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child(terrain_label)
+                .with_child(terrain_list)
+                .with_child(add_button)
+                .with_child(remove_button),
+        )
+        .add_row(Row::auto())
+        .add_column(Column::strict(FIELD_LABEL_WIDTH))
+        .add_column(Column::stretch())
+        .add_column(Column::auto())
+        .add_column(Column::auto())
+        .build(ctx);
+        let name_field = TextBoxBuilder::new(WidgetBuilder::new().on_column(1))
+            .with_text_commit_mode(TextCommitMode::LostFocusPlusEnter)
+            .build(ctx);
+        let rule_set_list = DropdownListBuilder::new(WidgetBuilder::new().on_column(1))
+            .with_items(
+                RULE_SET_NAMES
+                    .iter()
+                    .map(|name| make_list_option(ctx, name))
+                    .collect(),
+            )
+            .with_selected(0)
+            .build(ctx);
+        let mask_buttons: [Handle<UiNode>; 9] =
+            std::array::from_fn(|index| make_mask_button(index, ctx));
+        let mask_grid = GridBuilder::new(
+            WidgetBuilder::new()
+                .on_column(1)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_children(mask_buttons),
+        )
+        .add_rows(vec![Row::auto(); 3])
+        .add_columns(vec![Column::auto(); 3])
+        .build(ctx);
+        let children = [
+            terrain_grid,
+            make_field("Name", name_field, ctx),
+            make_field("Rule Set", rule_set_list, ctx),
+            make_field("Neighbors", mask_grid, ctx),
+        ];
+        let handle =
+            StackPanelBuilder::new(WidgetBuilder::new().with_children(children)).build(ctx);
+        Self {
+            handle,
+            terrain_list,
+            add_button,
+            remove_button,
+            name_field,
+            rule_set_list,
+            mask_buttons,
+            terrain: None,
+            names: Vec::new(),
+        }
+    }
+    /// The tile set tiles of the selected brush tiles.
+    fn selected_tiles(state: &TileEditorState) -> Vec<TileDefinitionHandle> {
+        state.tile_redirect().map(|(_, h)| h).collect()
+    }
+    fn sync_terrain_list(&mut self, terrains: &[TileTerrain], ui: &mut UserInterface) {
+        let names = terrains.iter().map(|t| t.name.clone()).collect::<Vec<_>>();
+        if names != self.names {
+            let items = names
+                .iter()
+                .map(|name| make_list_option(&mut ui.build_ctx(), name))
+                .collect();
+            ui.send_message(DropdownListMessage::items(
+                self.terrain_list,
+                MessageDirection::ToWidget,
+                items,
+            ));
+            self.names = names;
+        }
+        self.terrain = match self.terrain {
+            Some(index) if index < terrains.len() => Some(index),
+            _ => terrains.len().checked_sub(1),
+        };
+        send_sync_message(
+            ui,
+            DropdownListMessage::selection(
+                self.terrain_list,
+                MessageDirection::ToWidget,
+                self.terrain,
+            ),
+        );
+    }
+    fn sync_mask_buttons(
+        &self,
+        terrain: Option<&TileTerrain>,
+        tiles: &[TileDefinitionHandle],
+        ui: &mut UserInterface,
+    ) {
+        let masks = terrain
+            .map(|t| tiles.iter().map(|h| t.rule_mask(*h)).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let is_member = !masks.is_empty() && masks.iter().all(Option::is_some);
+        let common = masks.iter().fold(0xFF, |acc, m| acc & m.unwrap_or(0));
+        let has_corners = terrain.is_some_and(|t| t.rule_set == AutotileRuleSet::Blob47);
+        for (index, button) in self.mask_buttons.iter().enumerate() {
+            let bit = MASK_BITS[index];
+            let (enabled, highlight) = if index == CENTER {
+                (terrain.is_some(), is_member)
+            } else {
+                let enabled = is_member && (has_corners || corner_edges(bit) == 0);
+                (enabled, is_member && common & bit != 0)
+            };
+            send_enabled(ui, *button, enabled);
+            highlight_tool_button(*button, highlight, ui);
+        }
+    }
+    /// Modify the terrain that is being edited according to a click on the mask button
+    /// with the given index. Returns false if nothing was changed.
+    fn toggle_mask(index: usize, terrain: &mut TileTerrain, state: &TileEditorState) -> bool {
+        let tiles = Self::selected_tiles(state);
+        if tiles.is_empty() {
+            return false;
+        }
+        let masks = tiles
+            .iter()
+            .map(|h| terrain.rule_mask(*h))
+            .collect::<Vec<_>>();
+        if index == CENTER {
+            if masks.iter().all(Option::is_some) {
+                for tile in tiles {
+                    terrain.remove_rule(tile);
+                }
+            } else {
+                for (tile, mask) in tiles.into_iter().zip(masks) {
+                    terrain.set_rule(tile, mask.unwrap_or(0));
+                }
+            }
+            return true;
+        }
+        let bit = MASK_BITS[index];
+        if masks.iter().any(Option::is_none) {
+            return false;
+        }
+        let set = !masks.iter().all(|m| m.unwrap_or(0) & bit != 0);
+        for (tile, mask) in tiles.into_iter().zip(masks) {
+            let mask = mask.unwrap_or(0);
+            let mask = if set {
+                mask | bit | corner_edges(bit)
+            } else {
+                mask & !bit
+            };
+            terrain.set_rule(tile, mask);
+        }
+        true
+    }
+    fn send_terrains(terrains: Vec<TileTerrain>, tile_book: &TileBook, sender: &MessageSender) {
+        let TileBook::Brush(brush) = tile_book else {
+            return;
+        };
+        sender.do_command(SetBrushTerrainsCommand {
+            brush: brush.clone(),
+            terrains,
+        });
+    }
+}
+
+impl TileEditor for TileTerrainEditor {
+    fn handle(&self) -> Handle<UiNode> {
+        self.handle
+    }
+
+    fn draw_button(&self) -> Handle<UiNode> {
+        Handle::NONE
+    }
+
+    fn sync_to_model(&mut self, _state: &TileEditorState, _ui: &mut UserInterface) {}
+
+    fn sync_to_state(&mut self, state: &TileEditorState, ui: &mut UserInterface) {
+        let brush = state.brush();
+        send_visibility(
+            ui,
+            self.handle,
+            brush.is_some() && state.tile_redirect().next().is_some(),
+        );
+        let Some(brush) = brush else {
+            return;
+        };
+        self.sync_terrain_list(&brush.terrains, ui);
+        let terrain = self.terrain.map(|index| &brush.terrains[index]);
+        send_enabled(ui, self.remove_button, terrain.is_some());
+        send_enabled(ui, self.name_field, terrain.is_some());
+        send_enabled(ui, self.rule_set_list, terrain.is_some());
+        send_sync_message(
+            ui,
+            TextMessage::text(
+                self.name_field,
+                MessageDirection::ToWidget,
+                terrain.map(|t| t.name.clone()).unwrap_or_default(),
+            ),
+        );
+        send_sync_message(
+            ui,
+            DropdownListMessage::selection(
+                self.rule_set_list,
+                MessageDirection::ToWidget,
+                Some(rule_set_to_index(
+                    terrain.map(|t| t.rule_set).unwrap_or_default(),
+                )),
+            ),
+        );
+        self.sync_mask_buttons(terrain, &Self::selected_tiles(state), ui);
+    }
+
+    fn draw_tile(
+        &self,
+        _handle: TileDefinitionHandle,
+        _subposition: Vector2<usize>,
+        _state: &TileDrawState,
+        _update: &mut TileSetUpdate,
+        _tile_resource: &TileBook,
+    ) {
+    }
+
+    fn handle_ui_message(
+        &mut self,
+        state: &mut TileEditorState,
+        message: &UiMessage,
+        ui: &mut UserInterface,
+        tile_book: &TileBook,
+        sender: &MessageSender,
+    ) {
+        if message.flags == MSG_SYNC_FLAG || message.direction() == MessageDirection::ToWidget {
+            return;
+        }
+        let Some(brush) = state.brush() else {
+            return;
+        };
+        let mut terrains = brush.terrains.clone();
+        let terrain = self.terrain.filter(|index| *index < terrains.len());
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.add_button {
+                self.terrain = Some(terrains.len());
+                terrains.push(TileTerrain {
+                    name: format!("Terrain {}", terrains.len() + 1),
+                    ..Default::default()
+                });
+                Self::send_terrains(terrains, tile_book, sender);
+            } else if message.destination() == self.remove_button {
+                if let Some(index) = terrain {
+                    terrains.remove(index);
+                    self.terrain = Some(index.saturating_sub(1));
+                    Self::send_terrains(terrains, tile_book, sender);
+                }
+            } else if let Some(index) = self
+                .mask_buttons
+                .iter()
+                .position(|b| *b == message.destination())
+            {
+                let Some(terrain) = terrain else {
+                    return;
+                };
+                if Self::toggle_mask(index, &mut terrains[terrain], state) {
+                    Self::send_terrains(terrains, tile_book, sender);
+                }
+            }
+        } else if let Some(DropdownListMessage::SelectionChanged(Some(index))) = message.data() {
+            if message.destination() == self.terrain_list {
+                self.terrain = Some(*index);
+                self.sync_to_state(state, ui);
+            } else if message.destination() == self.rule_set_list {
+                let Some(terrain) = terrain else {
+                    return;
+                };
+                let rule_set = index_to_rule_set(*index);
+                if terrains[terrain].rule_set != rule_set {
+                    terrains[terrain].set_rule_set(rule_set);
+                    Self::send_terrains(terrains, tile_book, sender);
+                }
+            }
+        } else if let Some(TextMessage::Text(text)) = message.data() {
+            if message.destination() == self.name_field {
+                let Some(terrain) = terrain else {
+                    return;
+                };
+                if terrains[terrain].name != *text {
+                    terrains[terrain].name = text.clone();
+                    Self::send_terrains(terrains, tile_book, sender);
+                }
+            }
+        }
+    }
+}
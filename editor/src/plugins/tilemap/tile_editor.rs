@@ -31,10 +31,13 @@ use commands::*;
 use fyrox::{
     fxhash::FxHashMap,
     gui::{
+        button::{ButtonContent, ButtonMessage},
         color::{ColorFieldBuilder, ColorFieldMessage},
         grid::*,
+        numeric::{NumericUpDownBuilder, NumericUpDownMessage},
         stack_panel::StackPanelBuilder,
-        text::TextBuilder,
+        text::{TextBuilder, TextMessage},
+        VerticalAlignment,
     },
     material::{MaterialResource, MaterialResourceExtension},
     scene::tilemap::{tileset::*, *},
@@ -635,3 +638,262 @@ impl TileEditor for TileHandleEditor {
         }
     }
 }
+
+/// An editor that previews the animation sequence of the selected tile. It has buttons to play,
+/// pause, and step through the frames of the animation, and a field for the duration of each
+/// frame, which controls the frame rate of the animation page that contains the sequence.
+pub struct TileAnimationEditor {
+    handle: Handle<UiNode>,
+    /// The widget that plays the animation.
+    preview: Handle<UiNode>,
+    /// The button that moves the animation to the previous frame.
+    previous_button: Handle<UiNode>,
+    /// The button that starts or stops playing the animation.
+    play_button: Handle<UiNode>,
+    /// The button that moves the animation to the next frame.
+    next_button: Handle<UiNode>,
+    /// The text that shows the current frame and the number of frames.
+    frame_text: Handle<UiNode>,
+    /// The field for the duration of each frame in seconds.
+    duration_field: Handle<UiNode>,
+    /// The first tile of the animation sequence that is being previewed.
+    tile: Option<TileDefinitionHandle>,
+    /// The current frame of the animation.
+    frame: i32,
+    /// The number of frames of the animation.
+    length: i32,
+    /// True if the animation is playing.
+    playing: bool,
+}
+
+fn make_text_button(
+    text: &str,
+    tooltip: &str,
+    column: usize,
+    ctx: &mut BuildContext,
+) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .on_column(column)
+            .with_min_size(Vector2::new(24.0, 0.0))
+            .with_margin(Thickness::uniform(1.0))
+            .with_tooltip(make_simple_tooltip(ctx, tooltip)),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+impl TileAnimationEditor {
+    pub fn new(state: TileDrawStateRef, ctx: &mut BuildContext) -> Self {
+        let preview = AnimationPreviewBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::uniform(1.0))
+                .with_height(96.0),
+            state,
+        )
+        .build(ctx);
+        let previous_button = make_text_button("<", "Previous frame", 0, ctx);
+        let play_button = make_text_button("Play", "Play or pause the animation", 1, ctx);
+        let next_button = make_text_button(">", "Next frame", 2, ctx);
+        let frame_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_column(3)
+                .with_margin(Thickness::left(4.0)),
+        )
+        .with_vertical_text_alignment(VerticalAlignment::Center)
+        .build(ctx);
+        let controls = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child(previous_button)
+                .with_child(play_button)
+                .with_child(next_button)
+                .with_child(frame_text),
+        )
+        .add_row(Row::auto())
+        .add_column(Column::auto())
+        .add_column(Column::auto())
+        .add_column(Column::auto())
+        .add_column(Column::stretch())
+        .build(ctx);
+        let duration_field = NumericUpDownBuilder::<f32>::new(WidgetBuilder::new().on_column(1))
+            .with_min_value(0.001)
+            .build(ctx);
+        let duration_label = make_label("Frame Duration", ctx);
+        let duration = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child(duration_label)
+                .with_child(duration_field),
+        )
+        .add_row(Row::auto())
+        .add_column(Column::strict(FIELD_LABEL_WIDTH))
+        .add_column(Column::stretch())
+        .build(ctx);
+        let label = make_label("Animation Preview", ctx);
+        let handle = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_child(label)
+                .with_child(preview)
+                .with_child(controls)
+                .with_child(duration),
+        )
+        .build(ctx);
+        Self {
+            handle,
+            preview,
+            previous_button,
+            play_button,
+            next_button,
+            frame_text,
+            duration_field,
+            tile: None,
+            frame: 0,
+            length: 0,
+            playing: false,
+        }
+    }
+    /// The tile whose animation should be previewed: the tile that the first selected cell
+    /// of an animation page refers to, or the first selected tile of any other page.
+    fn find_tile(state: &TileEditorState) -> Option<TileDefinitionHandle> {
+        state
+            .tile_redirect()
+            .map(|(_, h)| h)
+            .next()
+            .or_else(|| state.tile_handles().next())
+    }
+    fn sync_frame_text(&self, ui: &mut UserInterface) {
+        ui.send_message(TextMessage::text(
+            self.frame_text,
+            MessageDirection::ToWidget,
+            format!("Frame {} / {}", self.frame + 1, self.length),
+        ));
+    }
+    fn set_playing(&mut self, playing: bool, ui: &mut UserInterface) {
+        self.playing = playing;
+        ui.send_message(ButtonMessage::content(
+            self.play_button,
+            MessageDirection::ToWidget,
+            ButtonContent::text(if playing { "Pause" } else { "Play" }),
+        ));
+    }
+    fn step(&mut self, step: i32, ui: &mut UserInterface) {
+        self.set_playing(false, ui);
+        ui.send_message(AnimationPreviewMessage::step(
+            self.preview,
+            MessageDirection::ToWidget,
+            step,
+        ));
+    }
+    fn apply_duration(
+        &self,
+        duration: f32,
+        state: &TileEditorState,
+        tile_book: &TileBook,
+        sender: &MessageSender,
+    ) {
+        let TileBook::TileSet(tile_set) = tile_book else {
+            return;
+        };
+        if duration <= 0.0 {
+            return;
+        }
+        let Some(page) = self
+            .tile
+            .zip(state.tile_set())
+            .and_then(|(tile, t)| t.get_animation_page(tile))
+        else {
+            return;
+        };
+        sender.do_command(ModifyAnimationSpeedCommand {
+            tile_set: tile_set.clone(),
+            page,
+            frame_rate: duration.recip(),
+        });
+    }
+}
+
+impl TileEditor for TileAnimationEditor {
+    fn handle(&self) -> Handle<UiNode> {
+        self.handle
+    }
+    fn draw_button(&self) -> Handle<UiNode> {
+        Handle::NONE
+    }
+    fn sync_to_model(&mut self, _state: &TileEditorState, _ui: &mut UserInterface) {}
+    fn sync_to_state(&mut self, state: &TileEditorState, ui: &mut UserInterface) {
+        let tile = Self::find_tile(state);
+        let timing = tile
+            .zip(state.tile_set())
+            .and_then(|(tile, t)| t.get_animation_timing(tile));
+        send_visibility(ui, self.handle, timing.is_some());
+        let tile = timing.and(tile);
+        if self.tile != tile {
+            self.tile = tile;
+            self.frame = 0;
+            ui.send_message(AnimationPreviewMessage::tile(
+                self.preview,
+                MessageDirection::ToWidget,
+                tile,
+            ));
+        }
+        let Some((frame_rate, length)) = timing else {
+            return;
+        };
+        self.length = length;
+        self.sync_frame_text(ui);
+        let duration = if frame_rate != 0.0 {
+            frame_rate.abs().recip()
+        } else {
+            0.0
+        };
+        send_sync_message(
+            ui,
+            NumericUpDownMessage::value(self.duration_field, MessageDirection::ToWidget, duration),
+        );
+    }
+
+    fn draw_tile(
+        &self,
+        _handle: TileDefinitionHandle,
+        _subposition: Vector2<usize>,
+        _state: &TileDrawState,
+        _update: &mut TileSetUpdate,
+        _tile_resource: &TileBook,
+    ) {
+    }
+
+    fn handle_ui_message(
+        &mut self,
+        state: &mut TileEditorState,
+        message: &UiMessage,
+        ui: &mut UserInterface,
+        tile_book: &TileBook,
+        sender: &MessageSender,
+    ) {
+        if message.direction() == MessageDirection::ToWidget || message.flags == MSG_SYNC_FLAG {
+            return;
+        }
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.previous_button {
+                self.step(-1, ui);
+            } else if message.destination() == self.next_button {
+                self.step(1, ui);
+            } else if message.destination() == self.play_button {
+                self.set_playing(!self.playing, ui);
+                ui.send_message(AnimationPreviewMessage::playing(
+                    self.preview,
+                    MessageDirection::ToWidget,
+                    self.playing,
+                ));
+            }
+        } else if let Some(&AnimationPreviewMessage::Frame(frame)) = message.data() {
+            if message.destination() == self.preview {
+                self.frame = frame;
+                self.sync_frame_text(ui);
+            }
+        } else if let Some(&NumericUpDownMessage::<f32>::Value(duration)) = message.data() {
+            if message.destination() == self.duration_field {
+                self.apply_duration(duration, state, tile_book, sender);
+            }
+        }
+    }
+}
@@ -790,6 +790,8 @@ impl TileInspector {
             Arc::new(Mutex::new(TileMaterialEditor::new(ctx, sender.clone()))) as TileEditorRef,
             Arc::new(Mutex::new(TileColorEditor::new(ctx))) as TileEditorRef,
             Arc::new(Mutex::new(TileHandleEditor::new(None, ctx))) as TileEditorRef,
+            Arc::new(Mutex::new(TileAnimationEditor::new(state.clone(), ctx))) as TileEditorRef,
+            Arc::new(Mutex::new(TileTerrainEditor::new(ctx))) as TileEditorRef,
         ];
 
         let creator_label_0 = make_label("Create New Page", ctx);
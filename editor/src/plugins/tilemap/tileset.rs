@@ -403,6 +403,11 @@ impl TileSetEditor {
         editor
     }
 
+    /// The resource that is being edited.
+    pub fn tile_book(&self) -> &TileBook {
+        &self.tile_book
+    }
+
     pub fn set_tile_resource(&mut self, tile_book: TileBook, ui: &mut UserInterface) {
         self.try_save();
         self.tile_book = tile_book.clone();
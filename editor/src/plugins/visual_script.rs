@@ -0,0 +1,759 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Visual script editor allows creating and editing visual script graphs (`.vscript` files). It
+//! reuses the canvas of the ABSM editor to show and connect nodes of the graph.
+
+use crate::{
+    command::{Command, CommandContext, CommandStack, CommandTrait},
+    fyrox::{
+        asset::{untyped::ResourceKind, Resource},
+        core::{futures::executor::block_on, log::Log, pool::Handle, type_traits::prelude::*},
+        engine::Engine,
+        fxhash::FxHashMap,
+        graph::BaseSceneGraph,
+        gui::{
+            border::BorderBuilder,
+            file_browser::{FileBrowserMode, FileSelectorMessage},
+            grid::{Column, GridBuilder, Row},
+            menu::{
+                ContextMenuBuilder, MenuBuilder, MenuItemBuilder, MenuItemContent, MenuItemMessage,
+            },
+            message::{MessageDirection, UiMessage},
+            popup::PopupBuilder,
+            stack_panel::StackPanelBuilder,
+            style::{resource::StyleResourceExt, Style},
+            text::{TextBuilder, TextMessage},
+            text_box::TextBoxBuilder,
+            utils::make_simple_tooltip,
+            widget::{WidgetBuilder, WidgetMessage},
+            window::{WindowBuilder, WindowMessage, WindowTitle},
+            BuildContext, Orientation, RcUiNodeHandle, Thickness, UiNode, UserInterface,
+            VerticalAlignment,
+        },
+        script::visual::{
+            VisualNode, VisualNodeKind, VisualScriptGraph, VisualScriptGraphResource,
+        },
+    },
+    menu::create_menu_item,
+    plugin::EditorPlugin,
+    plugins::absm::{
+        canvas::{AbsmCanvasBuilder, AbsmCanvasMessage},
+        connection::ConnectionBuilder,
+        node::{AbsmNode, AbsmNodeBuilder, AbsmNodeMessage},
+        socket::{Socket, SocketBuilder, SocketDirection},
+        AbsmEditor,
+    },
+    send_sync_message,
+    utils::create_file_selector,
+    Editor, MSG_SYNC_FLAG,
+};
+use fyrox::core::some_or_return;
+use std::{path::PathBuf, str::FromStr};
+use strum::VariantNames;
+
+#[derive(Debug, ComponentProvider)]
+pub struct VisualScriptEditorContext {}
+
+impl CommandContext for VisualScriptEditorContext {}
+
+#[derive(Debug)]
+struct ModifyGraphCommand {
+    graph_resource: VisualScriptGraphResource,
+    graph: VisualScriptGraph,
+}
+
+impl ModifyGraphCommand {
+    fn swap(&mut self) {
+        std::mem::swap(&mut *self.graph_resource.data_ref(), &mut self.graph);
+    }
+}
+
+impl CommandTrait for ModifyGraphCommand {
+    fn name(&mut self, _: &dyn CommandContext) -> String {
+        "Modify Visual Script".to_owned()
+    }
+
+    fn execute(&mut self, _: &mut dyn CommandContext) {
+        self.swap();
+    }
+
+    fn revert(&mut self, _: &mut dyn CommandContext) {
+        self.swap();
+    }
+}
+
+struct FileMenu {
+    new: Handle<UiNode>,
+    load: Handle<UiNode>,
+    save: Handle<UiNode>,
+}
+
+struct EditMenu {
+    undo: Handle<UiNode>,
+    redo: Handle<UiNode>,
+    delete: Handle<UiNode>,
+    disconnect: Handle<UiNode>,
+}
+
+struct Menu {
+    file: FileMenu,
+    edit: EditMenu,
+}
+
+struct CanvasContextMenu {
+    menu: RcUiNodeHandle,
+    // Menu items with the names of node kinds they're creating.
+    items: Vec<(Handle<UiNode>, &'static str)>,
+}
+
+impl CanvasContextMenu {
+    fn new(ctx: &mut BuildContext) -> Self {
+        let items = VisualNodeKind::VARIANTS
+            .iter()
+            .map(|name| (create_menu_item(name, vec![], ctx), *name))
+            .collect::<Vec<_>>();
+
+        let menu = ContextMenuBuilder::new(
+            PopupBuilder::new(WidgetBuilder::new().with_visibility(false)).with_content(
+                StackPanelBuilder::new(
+                    WidgetBuilder::new().with_children(items.iter().map(|(item, _)| *item)),
+                )
+                .build(ctx),
+            ),
+        )
+        .build(ctx);
+        let menu = RcUiNodeHandle::new(menu, ctx.sender());
+
+        Self { menu, items }
+    }
+}
+
+pub struct VisualScriptEditorWindow {
+    window: Handle<UiNode>,
+    canvas: Handle<UiNode>,
+    canvas_context_menu: CanvasContextMenu,
+    parameter: Handle<UiNode>,
+    menu: Menu,
+    load_file_selector: Handle<UiNode>,
+    save_file_selector: Handle<UiNode>,
+    graph_resource: Option<VisualScriptGraphResource>,
+    path: PathBuf,
+    command_stack: CommandStack,
+    selection: Vec<Handle<VisualNode>>,
+}
+
+fn make_menu_item(text: &str, ctx: &mut BuildContext) -> Handle<UiNode> {
+    MenuItemBuilder::new(WidgetBuilder::new())
+        .with_content(MenuItemContent::text(text))
+        .build(ctx)
+}
+
+fn make_socket(
+    direction: SocketDirection,
+    index: usize,
+    parent_node: Handle<VisualNode>,
+    ui: &mut UserInterface,
+) -> Handle<UiNode> {
+    SocketBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+        .with_direction(direction)
+        .with_parent_node(parent_node.into())
+        .with_index(index)
+        .with_show_index(direction == SocketDirection::Input)
+        .build(&mut ui.build_ctx())
+}
+
+fn fetch_socket_model_handle(
+    socket: Handle<UiNode>,
+    ui: &UserInterface,
+) -> Option<(Handle<VisualNode>, usize)> {
+    ui.try_get(socket)
+        .and_then(|socket| socket.query_component::<Socket>())
+        .map(|socket| (socket.parent_node.into(), socket.index))
+}
+
+impl VisualScriptEditorWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let load_file_selector = create_file_selector(ctx, "vscript", FileBrowserMode::Open);
+        let save_file_selector = create_file_selector(
+            ctx,
+            "vscript",
+            FileBrowserMode::Save {
+                default_file_name: PathBuf::from("unnamed.vscript"),
+            },
+        );
+
+        let canvas_context_menu = CanvasContextMenu::new(ctx);
+
+        let new = make_menu_item("New", ctx);
+        let load = make_menu_item("Load", ctx);
+        let save = make_menu_item("Save", ctx);
+        let undo = make_menu_item("Undo", ctx);
+        let redo = make_menu_item("Redo", ctx);
+        let delete = make_menu_item("Delete Selected", ctx);
+        let disconnect = make_menu_item("Disconnect Inputs", ctx);
+
+        let canvas;
+        let parameter;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(800.0).with_height(600.0))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            MenuBuilder::new(WidgetBuilder::new())
+                                .with_items(vec![
+                                    MenuItemBuilder::new(WidgetBuilder::new())
+                                        .with_content(MenuItemContent::text("File"))
+                                        .with_items(vec![new, load, save])
+                                        .build(ctx),
+                                    MenuItemBuilder::new(WidgetBuilder::new())
+                                        .with_content(MenuItemContent::text("Edit"))
+                                        .with_items(vec![undo, redo, delete, disconnect])
+                                        .build(ctx),
+                                ])
+                                .build(ctx),
+                        )
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_child(
+                                        TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_vertical_alignment(VerticalAlignment::Center),
+                                        )
+                                        .with_text("Parameter")
+                                        .build(ctx),
+                                    )
+                                    .with_child({
+                                        parameter = TextBoxBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_enabled(false)
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_width(250.0)
+                                                .with_tooltip(make_simple_tooltip(
+                                                    ctx,
+                                                    "Parameter of selected nodes: topic, function \
+                                                    name, variable name, property path, constant \
+                                                    value or operator.",
+                                                )),
+                                        )
+                                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                                        .build(ctx);
+                                        parameter
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        )
+                        .with_child(
+                            BorderBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .with_margin(Thickness::uniform(1.0))
+                                    .with_child({
+                                        canvas = AbsmCanvasBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_enabled(false)
+                                                .with_context_menu(
+                                                    canvas_context_menu.menu.clone(),
+                                                ),
+                                        )
+                                        .build(ctx);
+                                        canvas
+                                    }),
+                            )
+                            .build(ctx),
+                        ),
+                )
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::stretch())
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .with_remove_on_close(true)
+            .with_title(WindowTitle::text("Visual Script Editor"))
+            .build(ctx);
+
+        Self {
+            window,
+            canvas,
+            canvas_context_menu,
+            parameter,
+            menu: Menu {
+                file: FileMenu { new, load, save },
+                edit: EditMenu {
+                    undo,
+                    redo,
+                    delete,
+                    disconnect,
+                },
+            },
+            load_file_selector,
+            save_file_selector,
+            graph_resource: None,
+            path: Default::default(),
+            command_stack: CommandStack::new(false, 2048),
+            selection: Default::default(),
+        }
+    }
+
+    fn destroy(self, ui: &UserInterface) {
+        ui.send_message(WidgetMessage::remove(
+            self.load_file_selector,
+            MessageDirection::ToWidget,
+        ));
+        ui.send_message(WidgetMessage::remove(
+            self.save_file_selector,
+            MessageDirection::ToWidget,
+        ));
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    pub fn open(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+            true,
+        ));
+    }
+
+    fn set_graph(&mut self, graph: VisualScriptGraphResource, ui: &mut UserInterface) {
+        self.graph_resource = Some(graph);
+        self.selection.clear();
+
+        ui.send_message(WidgetMessage::enabled(
+            self.canvas,
+            MessageDirection::ToWidget,
+            true,
+        ));
+
+        self.command_stack.clear(&mut VisualScriptEditorContext {});
+
+        self.sync_to_model(ui);
+        self.sync_title(ui);
+    }
+
+    fn sync_title(&self, ui: &UserInterface) {
+        let title = match self.graph_resource.as_ref().map(|graph| graph.kind()) {
+            Some(ResourceKind::External(path)) => {
+                format!("Visual Script Editor - {}", path.display())
+            }
+            Some(ResourceKind::Embedded) => "Visual Script Editor - Unnamed Graph".to_string(),
+            None => "Visual Script Editor".to_string(),
+        };
+
+        ui.send_message(WindowMessage::title(
+            self.window,
+            MessageDirection::ToWidget,
+            WindowTitle::text(title),
+        ));
+    }
+
+    fn sync_parameter(&self, ui: &UserInterface) {
+        let parameter = self.graph_resource.as_ref().and_then(|graph_resource| {
+            let graph = graph_resource.data_ref();
+            self.selection
+                .first()
+                .and_then(|handle| graph.nodes.try_borrow(*handle))
+                .and_then(|node| node.kind.parameter())
+        });
+
+        ui.send_message(WidgetMessage::enabled(
+            self.parameter,
+            MessageDirection::ToWidget,
+            parameter.is_some(),
+        ));
+        send_sync_message(
+            ui,
+            TextMessage::text(
+                self.parameter,
+                MessageDirection::ToWidget,
+                parameter.unwrap_or_default(),
+            ),
+        );
+    }
+
+    /// Rebuilds the canvas from scratch. Visual scripts are usually small, so there's no need to
+    /// sync views one-by-one.
+    fn sync_to_model(&mut self, ui: &mut UserInterface) {
+        for &child in ui.node(self.canvas).children() {
+            send_sync_message(ui, WidgetMessage::remove(child, MessageDirection::ToWidget));
+        }
+
+        let graph_resource = some_or_return!(self.graph_resource.clone());
+        let graph = graph_resource.data_ref();
+
+        self.selection
+            .retain(|handle| graph.nodes.is_valid_handle(*handle));
+
+        let mut views = FxHashMap::default();
+        for (handle, node) in graph.nodes.pair_iter() {
+            let input_sockets = (0..node.inputs.len())
+                .map(|index| make_socket(SocketDirection::Input, index, handle, ui))
+                .collect::<Vec<_>>();
+
+            let is_event = node.kind.is_event();
+            let view =
+                AbsmNodeBuilder::new(WidgetBuilder::new().with_desired_position(node.position))
+                    .with_title(node.kind.as_ref().to_string())
+                    .with_name(node.kind.parameter().unwrap_or_default())
+                    .with_can_add_sockets(node.kind.has_variable_inputs())
+                    .with_input_sockets(input_sockets.clone())
+                    .with_output_socket(make_socket(SocketDirection::Output, 0, handle, ui))
+                    .with_normal_brush(if is_event {
+                        ui.style.property(AbsmEditor::NORMAL_ROOT_COLOR)
+                    } else {
+                        ui.style.property(Style::BRUSH_LIGHTER_PRIMARY)
+                    })
+                    .with_selected_brush(if is_event {
+                        ui.style.property(AbsmEditor::SELECTED_ROOT_COLOR)
+                    } else {
+                        ui.style.property(Style::BRUSH_LIGHTER)
+                    })
+                    .with_model_handle(handle)
+                    .build(&mut ui.build_ctx());
+
+            send_sync_message(
+                ui,
+                WidgetMessage::link(view, MessageDirection::ToWidget, self.canvas),
+            );
+
+            views.insert(handle, (view, input_sockets));
+        }
+
+        // Force update layout to be able to fetch positions of sockets for connections.
+        ui.update_layout(ui.screen_size());
+
+        for (handle, node) in graph.nodes.pair_iter() {
+            let (dest_view, input_sockets) = &views[&handle];
+            for (input, dest_socket) in node.inputs.iter().zip(input_sockets) {
+                let Some((source_view, _)) = views.get(input) else {
+                    continue;
+                };
+
+                let source_socket = ui
+                    .node(*source_view)
+                    .query_component::<AbsmNode<VisualNode>>()
+                    .unwrap()
+                    .base
+                    .output_socket;
+
+                let connection = ConnectionBuilder::new(WidgetBuilder::new())
+                    .with_source_socket(source_socket)
+                    .with_source_node(*source_view)
+                    .with_dest_socket(*dest_socket)
+                    .with_dest_node(*dest_view)
+                    .build(self.canvas, &mut ui.build_ctx());
+
+                send_sync_message(
+                    ui,
+                    WidgetMessage::link(connection, MessageDirection::ToWidget, self.canvas),
+                );
+                send_sync_message(
+                    ui,
+                    WidgetMessage::lowermost(connection, MessageDirection::ToWidget),
+                );
+            }
+        }
+
+        let selection = self
+            .selection
+            .iter()
+            .filter_map(|handle| views.get(handle).map(|(view, _)| *view))
+            .collect::<Vec<_>>();
+        send_sync_message(
+            ui,
+            AbsmCanvasMessage::selection_changed(
+                self.canvas,
+                MessageDirection::ToWidget,
+                selection,
+            ),
+        );
+        send_sync_message(
+            ui,
+            AbsmCanvasMessage::force_sync_dependent_objects(
+                self.canvas,
+                MessageDirection::ToWidget,
+            ),
+        );
+
+        drop(graph);
+        self.sync_parameter(ui);
+    }
+
+    /// Applies the given change to a copy of the graph and puts it in the command stack, so the
+    /// change could be undone.
+    fn modify<F>(&mut self, ui: &mut UserInterface, func: F)
+    where
+        F: FnOnce(&mut VisualScriptGraph),
+    {
+        let graph_resource = some_or_return!(self.graph_resource.clone());
+        let mut graph = graph_resource.data_ref().clone();
+        func(&mut graph);
+        self.command_stack.do_command(
+            Command::new(ModifyGraphCommand {
+                graph_resource,
+                graph,
+            }),
+            &mut VisualScriptEditorContext {},
+        );
+        self.sync_to_model(ui);
+    }
+
+    fn open_file_selector(&self, file_selector: Handle<UiNode>, ui: &UserInterface) {
+        ui.send_message(FileSelectorMessage::root(
+            file_selector,
+            MessageDirection::ToWidget,
+            Some(std::env::current_dir().unwrap()),
+        ));
+
+        ui.send_message(WindowMessage::open_modal(
+            file_selector,
+            MessageDirection::ToWidget,
+            true,
+            true,
+        ));
+    }
+
+    fn save(&self) {
+        if let Some(graph_resource) = self.graph_resource.as_ref() {
+            if let Err(err) = graph_resource.save(&self.path) {
+                Log::err(format!(
+                    "Unable to save visual script to {}. Reason: {err}",
+                    self.path.display()
+                ));
+            }
+        }
+    }
+
+    fn handle_canvas_message(&mut self, message: &UiMessage, ui: &mut UserInterface) {
+        let msg = some_or_return!(message.data::<AbsmCanvasMessage>());
+        match msg {
+            AbsmCanvasMessage::CommitConnection {
+                source_socket,
+                dest_socket,
+            } => {
+                let (source, _) = some_or_return!(fetch_socket_model_handle(*source_socket, ui));
+                let (dest, index) = some_or_return!(fetch_socket_model_handle(*dest_socket, ui));
+                if source != dest {
+                    self.modify(ui, |graph| graph.connect(source, dest, index));
+                }
+            }
+            AbsmCanvasMessage::CommitDrag { entries } => {
+                let positions = entries
+                    .iter()
+                    .filter_map(|entry| {
+                        let view = ui.node(entry.node);
+                        view.query_component::<AbsmNode<VisualNode>>()
+                            .map(|node| (node.model_handle, view.actual_local_position()))
+                    })
+                    .collect::<Vec<_>>();
+                self.modify(ui, |graph| {
+                    for (handle, position) in positions {
+                        if let Some(node) = graph.nodes.try_borrow_mut(handle) {
+                            node.position = position;
+                        }
+                    }
+                });
+            }
+            AbsmCanvasMessage::SelectionChanged(selection)
+                if message.direction() == MessageDirection::FromWidget =>
+            {
+                self.selection = selection
+                    .iter()
+                    .filter_map(|view| {
+                        ui.node(*view)
+                            .query_component::<AbsmNode<VisualNode>>()
+                            .map(|node| node.model_handle)
+                    })
+                    .collect();
+                self.sync_parameter(ui);
+            }
+            _ => (),
+        }
+    }
+
+    pub fn handle_ui_message(mut self, message: &UiMessage, engine: &mut Engine) -> Option<Self> {
+        let ui = engine.user_interfaces.first_mut();
+
+        if message.destination() == self.canvas {
+            self.handle_canvas_message(message, ui);
+        } else if let Some(AbsmNodeMessage::AddInput) = message.data() {
+            if message.direction() != MessageDirection::FromWidget {
+                return Some(self);
+            }
+
+            if let Some(handle) = ui
+                .try_get(message.destination())
+                .and_then(|view| view.query_component::<AbsmNode<VisualNode>>())
+                .map(|node| node.model_handle)
+            {
+                self.modify(ui, |graph| {
+                    if let Some(node) = graph.nodes.try_borrow_mut(handle) {
+                        node.inputs.push(Handle::NONE);
+                    }
+                });
+            }
+        } else if let Some(TextMessage::Text(text)) = message.data() {
+            if message.destination() == self.parameter
+                && message.direction() == MessageDirection::FromWidget
+                && message.flags != MSG_SYNC_FLAG
+                && !self.selection.is_empty()
+            {
+                let selection = self.selection.clone();
+                self.modify(ui, |graph| {
+                    for handle in selection {
+                        if let Some(node) = graph.nodes.try_borrow_mut(handle) {
+                            node.kind.set_parameter(text);
+                        }
+                    }
+                });
+            }
+        } else if let Some(MenuItemMessage::Click) = message.data() {
+            if let Some((_, name)) = self
+                .canvas_context_menu
+                .items
+                .iter()
+                .find(|(item, _)| *item == message.destination())
+            {
+                let position = ui.node(self.canvas).screen_to_local(
+                    ui.node(self.canvas_context_menu.menu.handle())
+                        .screen_position(),
+                );
+                if let Ok(kind) = VisualNodeKind::from_str(name) {
+                    self.modify(ui, |graph| {
+                        graph.add_node(VisualNode::new(kind).with_position(position));
+                    });
+                }
+            } else if message.destination() == self.menu.edit.undo {
+                self.command_stack.undo(&mut VisualScriptEditorContext {});
+                self.sync_to_model(ui);
+            } else if message.destination() == self.menu.edit.redo {
+                self.command_stack.redo(&mut VisualScriptEditorContext {});
+                self.sync_to_model(ui);
+            } else if message.destination() == self.menu.edit.delete {
+                let selection = std::mem::take(&mut self.selection);
+                self.modify(ui, |graph| {
+                    for handle in selection {
+                        if graph.nodes.is_valid_handle(handle) {
+                            graph.remove_node(handle);
+                        }
+                    }
+                });
+            } else if message.destination() == self.menu.edit.disconnect {
+                let selection = self.selection.clone();
+                self.modify(ui, |graph| {
+                    for handle in selection {
+                        if let Some(node) = graph.nodes.try_borrow_mut(handle) {
+                            node.inputs.fill(Handle::NONE);
+                        }
+                    }
+                });
+            } else if message.destination() == self.menu.file.new {
+                self.path = Default::default();
+                self.set_graph(
+                    Resource::new_ok(Default::default(), VisualScriptGraph::default()),
+                    ui,
+                );
+            } else if message.destination() == self.menu.file.load {
+                self.open_file_selector(self.load_file_selector, ui);
+            } else if message.destination() == self.menu.file.save {
+                if self.path == PathBuf::default() {
+                    self.open_file_selector(self.save_file_selector, ui);
+                } else {
+                    self.save();
+                }
+            }
+        } else if let Some(FileSelectorMessage::Commit(path)) = message.data() {
+            if message.destination() == self.load_file_selector {
+                match block_on(engine.resource_manager.request::<VisualScriptGraph>(path)) {
+                    Ok(graph) => {
+                        self.path.clone_from(path);
+                        self.set_graph(graph, ui);
+                    }
+                    Err(err) => Log::err(format!(
+                        "Unable to load visual script {}. Reason: {err:?}",
+                        path.display()
+                    )),
+                }
+            } else if message.destination() == self.save_file_selector {
+                self.path.clone_from(path);
+                self.save();
+            }
+        } else if let Some(WindowMessage::Close) = message.data() {
+            if message.destination() == self.window
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.destroy(ui);
+                return None;
+            }
+        }
+
+        Some(self)
+    }
+}
+
+#[derive(Default)]
+pub struct VisualScriptEditorPlugin {
+    visual_script_editor_window: Option<VisualScriptEditorWindow>,
+    open_visual_script_editor: Handle<UiNode>,
+}
+
+impl VisualScriptEditorPlugin {
+    fn on_open_visual_script_editor_clicked(&mut self, editor: &mut Editor) {
+        let ui = editor.engine.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+        let window = self
+            .visual_script_editor_window
+            .get_or_insert_with(|| VisualScriptEditorWindow::new(ctx));
+        window.open(ui);
+    }
+}
+
+impl EditorPlugin for VisualScriptEditorPlugin {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ui = editor.engine.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+        self.open_visual_script_editor = create_menu_item("Visual Script Editor", vec![], ctx);
+        ui.send_message(MenuItemMessage::add_item(
+            editor.menu.utils_menu.menu,
+            MessageDirection::ToWidget,
+            self.open_visual_script_editor,
+        ));
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if message.destination() == self.open_visual_script_editor {
+                self.on_open_visual_script_editor_clicked(editor)
+            }
+        }
+        let window = some_or_return!(self.visual_script_editor_window.take());
+        self.visual_script_editor_window = window.handle_ui_message(message, &mut editor.engine);
+    }
+}
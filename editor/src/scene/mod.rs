@@ -270,7 +270,12 @@ impl GameScene {
         let mut visitor = Visitor::new();
         pure_scene.save("Scene", &mut visitor).unwrap();
 
-        if let Err(e) = visitor.save_binary(path) {
+        let result = match settings.general.scene_file_format.chunked_options() {
+            Some(options) => visitor.save_chunked(path, options),
+            None => visitor.save_binary(path),
+        };
+
+        if let Err(e) = result {
             Err(format!("Failed to save scene! Reason: {e}"))
         } else {
             if settings.debugging.save_scene_in_text_form {
@@ -639,7 +644,9 @@ impl SceneController for GameScene {
                         .unwrap();
 
                     let normal = match camera.projection() {
-                        Projection::Perspective(_) => Vector3::new(0.0, 1.0, 0.0),
+                        Projection::Perspective(_) | Projection::OffCenter(_) => {
+                            Vector3::new(0.0, 1.0, 0.0)
+                        }
                         Projection::Orthographic(_) => Vector3::new(0.0, 0.0, 1.0),
                     };
 
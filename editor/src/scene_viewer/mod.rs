@@ -665,7 +665,7 @@ impl SceneViewer {
                     .as_camera()
                     .projection()
                 {
-                    Projection::Perspective(_) => 0,
+                    Projection::Perspective(_) | Projection::OffCenter(_) => 0,
                     Projection::Orthographic(_) => 1,
                 }
             });
@@ -897,7 +897,8 @@ impl SceneViewer {
                                                 .as_camera()
                                                 .projection()
                                             {
-                                                Projection::Perspective(_) => {
+                                                Projection::Perspective(_)
+                                                | Projection::OffCenter(_) => {
                                                     ui.send_message(
                                                         DropdownListMessage::selection(
                                                             self.camera_projection,
@@ -18,7 +18,12 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use crate::fyrox::core::{reflect::prelude::*, type_traits::prelude::*, uuid_provider};
+use crate::fyrox::core::{
+    reflect::prelude::*,
+    type_traits::prelude::*,
+    uuid_provider,
+    visitor::chunked::{ChunkedFormatOptions, VisitorCompression},
+};
 use serde::{Deserialize, Serialize};
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
@@ -87,6 +92,56 @@ pub struct GeneralSettings {
 
     #[serde(default = "default_style")]
     pub style: EditorStyle,
+
+    #[reflect(
+        description = "Defines the format of saved scenes. Chunked formats store every subsystem of \
+        a scene separately, which allows reading a part of a scene without loading the rest, compressed \
+        formats produce smaller files. Legacy format could be read by older versions of the engine."
+    )]
+    #[serde(default)]
+    pub scene_file_format: SceneFileFormat,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "0d6f2c5e-87a3-4d0e-b3f4-1e9b7a62c2d8")]
+pub enum SceneFileFormat {
+    /// Plain binary format.
+    #[default]
+    Legacy,
+    /// Chunked format without compression.
+    Chunked,
+    /// Chunked format with LZ4 compression.
+    ChunkedLz4,
+}
+
+impl SceneFileFormat {
+    /// Returns options of the chunked format, or `None` for the legacy format.
+    pub fn chunked_options(self) -> Option<ChunkedFormatOptions> {
+        let compression = match self {
+            SceneFileFormat::Legacy => return None,
+            SceneFileFormat::Chunked => VisitorCompression::None,
+            SceneFileFormat::ChunkedLz4 => VisitorCompression::Lz4,
+        };
+        Some(ChunkedFormatOptions {
+            compression,
+            ..Default::default()
+        })
+    }
 }
 
 fn default_style() -> EditorStyle {
@@ -149,6 +204,7 @@ impl Default for GeneralSettings {
             generate_previews: default_generate_previews(),
             max_log_entries: default_max_log_entries(),
             style: EditorStyle::Dark,
+            scene_file_format: Default::default(),
         }
     }
 }
@@ -98,8 +98,11 @@ impl StatisticsWindow {
             if let Some(stats) = graphics_context.renderer.scene_data_map.get(&current_scene) {
                 let global_stats = graphics_context.renderer.get_statistics();
                 let statistics = format!(
-                    "FPS: {}\nFrame Time:{}\n{}",
-                    global_stats.frames_per_second, global_stats.pure_frame_time, stats.statistics
+                    "FPS: {}\nFrame Time:{}\n{}\n{}",
+                    global_stats.frames_per_second,
+                    global_stats.pure_frame_time,
+                    stats.statistics,
+                    engine.performance_statistics()
                 );
                 engine
                     .user_interfaces
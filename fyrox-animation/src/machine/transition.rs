@@ -99,6 +99,9 @@ macro_rules! define_two_args_node {
                         max_value: None,
                         step: None,
                         precision: None,
+                        category: "",
+                        slider: false,
+                        visible: true,
                         doc: "",
                     },
                     FieldInfo {
@@ -115,7 +118,11 @@ macro_rules! define_two_args_node {
                         min_value: None,
                         max_value: None,
                         step: None,
-                        precision: None,doc: "",
+                        precision: None,
+                        doc: "",
+                        category: "",
+                        slider: false,
+                        visible: true,
                     },
                 ])
             }
@@ -254,6 +261,9 @@ impl<T: EntityId> Reflect for NotNode<T> {
             max_value: None,
             step: None,
             precision: None,
+            category: "",
+            slider: false,
+            visible: true,
             doc: "",
         }])
     }
@@ -129,6 +129,15 @@ fn quote_field_prop(
 
     let description = field.description.clone().unwrap_or_default();
 
+    let category = field.category.clone().unwrap_or_default();
+
+    let slider = field.slider;
+
+    let visible = match field.visible_if {
+        None => quote! { true },
+        Some(ref method) => quote! { self.#method() },
+    };
+
     quote! {
         FieldInfo {
             owner_type_id: std::any::TypeId::of::<Self>(),
@@ -145,6 +154,9 @@ fn quote_field_prop(
             step: #step,
             precision: #precision,
             description: #description,
+            category: #category,
+            slider: #slider,
+            visible: #visible,
             type_name: std::any::type_name::<#ty>()
         }
     }
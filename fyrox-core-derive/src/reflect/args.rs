@@ -237,6 +237,27 @@ pub struct FieldArgs {
     /// Description of the property.
     #[darling(default)]
     pub description: Option<String>,
+
+    /// `#[reflect(category = "Movement")]`
+    ///
+    /// Name of a category of the property. Properties with the same category are grouped together
+    /// in the editor.
+    #[darling(default)]
+    pub category: Option<String>,
+
+    /// `#[reflect(slider)]`
+    ///
+    /// The field should be edited using a slider. Works only for numeric fields with both
+    /// `min_value` and `max_value` set!
+    #[darling(default)]
+    pub slider: bool,
+
+    /// `#[reflect(visible_if = "<method name>")]`
+    ///
+    /// A method, that decides whether the field should be shown in the editor or not.
+    /// Expected signature: `fn(&self) -> bool`
+    #[darling(default)]
+    pub visible_if: Option<Path>,
 }
 
 impl FieldArgs {
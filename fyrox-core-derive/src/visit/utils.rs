@@ -145,7 +145,9 @@ pub fn create_field_visits<'a>(
             } else {
                 quote! {
                     if let Err(err) = #prefix #ident.visit(#name, &mut region) {
-                        return Err(err);
+                        if !region.skips_invalid_fields() {
+                            return Err(err);
+                        }
                     }
                 }
             }
@@ -343,6 +343,9 @@ fn default_prop() -> FieldInfo<'static, 'static> {
         max_value: None,
         step: None,
         precision: None,
+        category: "",
+        slider: false,
+        visible: true,
         description: "",
         tag: "",
         type_name: "",
@@ -430,6 +433,9 @@ fn inspect_attributes() {
             max_value: Some(1.1),
             step: Some(0.1),
             precision: Some(3),
+            category: "",
+            slider: false,
+            visible: true,
             description: "This is a property description.",
             tag: "SomeTag",
             type_name: std::any::type_name::<f32>(),
@@ -440,6 +446,45 @@ fn inspect_attributes() {
     data.fields_info(&mut |fields_info| assert_eq!(fields_info[0..2], expected));
 }
 
+#[test]
+fn inspect_editor_attributes() {
+    #[derive(Debug, Default, Reflect)]
+    pub struct Data {
+        #[reflect(category = "Movement", min_value = 0.0, max_value = 10.0, slider)]
+        speed: f32,
+        #[reflect(category = "Movement", visible_if = "can_jump")]
+        jump_height: f32,
+        #[reflect(visible_if = "can_jump")]
+        jump_count: u32,
+    }
+
+    impl Data {
+        fn can_jump(&self) -> bool {
+            self.jump_count > 0
+        }
+    }
+
+    let mut data = Data::default();
+
+    data.fields_info(&mut |fields_info| {
+        assert_eq!(fields_info[0].category, "Movement");
+        assert!(fields_info[0].slider);
+        assert!(fields_info[0].visible);
+        assert_eq!(fields_info[1].category, "Movement");
+        assert!(!fields_info[1].slider);
+        assert!(!fields_info[1].visible);
+        assert_eq!(fields_info[2].category, "");
+        assert!(!fields_info[2].visible);
+    });
+
+    data.jump_count = 2;
+
+    data.fields_info(&mut |fields_info| {
+        assert!(fields_info[1].visible);
+        assert!(fields_info[2].visible);
+    });
+}
+
 #[test]
 fn inspect_struct() {
     #[derive(Debug, Default, Reflect)]
@@ -20,7 +20,7 @@
 
 //! Fight the compatibility hell with attributes! .. someday :)
 
-use fyrox_core::visitor::prelude::*;
+use fyrox_core::visitor::{prelude::*, VisitorFlags};
 
 // Comment it out and make sure it panics
 // #[derive(Debug, Clone, PartialEq, Visit)]
@@ -45,3 +45,52 @@ fn rename() {
 
     assert_eq!(data, data_default);
 }
+
+#[derive(Debug, Clone, PartialEq, Visit)]
+pub struct OldVersion {
+    pub kept: f32,
+    pub removed: u32,
+    pub retyped: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Visit)]
+pub struct NewVersion {
+    pub kept: f32,
+    pub retyped: String,
+    pub added: u64,
+}
+
+#[test]
+fn skip_invalid_fields() {
+    let mut old = OldVersion {
+        kept: 1.0,
+        removed: 2,
+        retyped: 3,
+    };
+    let mut visitor = Visitor::new();
+    old.visit("Data", &mut visitor).unwrap();
+    let data = visitor.save_binary_to_vec().unwrap();
+
+    let default = NewVersion {
+        kept: 0.0,
+        retyped: "default".to_string(),
+        added: 5,
+    };
+
+    // Strict reading must fail, because of missing and changed fields.
+    let mut new = default.clone();
+    let mut visitor = Visitor::load_from_memory(&data).unwrap();
+    assert!(new.visit("Data", &mut visitor).is_err());
+
+    let mut new = default.clone();
+    let mut visitor = Visitor::load_from_memory(&data).unwrap();
+    visitor.flags = VisitorFlags::SKIP_INVALID_FIELDS;
+    new.visit("Data", &mut visitor).unwrap();
+    assert_eq!(
+        new,
+        NewVersion {
+            kept: 1.0,
+            ..default
+        }
+    );
+}
@@ -20,9 +20,17 @@
 
 //! Simple logger. By default, it writes in the console only. To enable logging into a file, call
 //! [`Log::set_file_name`] somewhere in your `main` function.
+//!
+//! Besides plain text messages, the logger supports structured records with a category and a set
+//! of key-value fields (see [`LogRecord`]). Every message could be sent to any number of sinks,
+//! see [`sink`] module docs for the list of built-in sinks. An optional crash handler is available
+//! in [`crash`] module.
 
 use crate::parking_lot::Mutex;
-use std::fmt::{Debug, Display};
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display, Write as _},
+};
 
 use crate::instant::Instant;
 #[cfg(not(target_arch = "wasm32"))]
@@ -44,7 +52,14 @@ extern "C" {
     fn log(s: &str);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod crash;
+pub mod sink;
+
+use sink::{LogSink, LogSinkId};
+
 /// A message that could be sent by the logger to all listeners.
+#[derive(Clone, Debug)]
 pub struct LogMessage {
     /// Kind of the message: information, warning or error.
     pub kind: MessageKind,
@@ -53,6 +68,85 @@ pub struct LogMessage {
     /// Time point at which the message was recorded. It is relative to the moment when the
     /// logger was initialized.
     pub time: Duration,
+    /// Category of the message (for example - `resource` or `physics`). Empty for the messages
+    /// without a category.
+    pub category: String,
+    /// A set of key-value pairs attached to the message.
+    pub fields: Vec<(String, String)>,
+}
+
+impl LogMessage {
+    /// Formats the message as a single line (unless the content itself has line breaks) of text
+    /// with the kind and category prefix and the fields at the end, for example:
+    /// `[WARNING] [resource]: Unable to load a texture. path=data/foo.png`.
+    pub fn to_text(&self) -> String {
+        let mut text = String::with_capacity(self.content.len() + 16);
+        if self.category.is_empty() {
+            text.push_str(self.kind.as_str());
+        } else {
+            let _ = write!(text, "{} [{}]: ", self.kind.as_tag(), self.category);
+        }
+        let content = self.content.trim_end_matches('\n');
+        text.push_str(content);
+        for (key, value) in self.fields.iter() {
+            let _ = write!(text, " {key}={value}");
+        }
+        if content.len() != self.content.len() {
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// A structured log record, that has a category and a set of key-value fields. The record is
+/// written to the log by calling [`LogRecord::write`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_core::log::{LogRecord, MessageKind};
+/// LogRecord::new(MessageKind::Warning, "Unable to load a texture.")
+///     .with_category("resource")
+///     .with_field("path", "data/foo.png")
+///     .with_field("attempt", 2)
+///     .write();
+/// ```
+#[derive(Clone, Debug)]
+pub struct LogRecord {
+    kind: MessageKind,
+    content: String,
+    category: String,
+    fields: Vec<(String, String)>,
+}
+
+impl LogRecord {
+    /// Creates a new record of the given kind.
+    pub fn new<S: Into<String>>(kind: MessageKind, content: S) -> Self {
+        Self {
+            kind,
+            content: content.into(),
+            category: Default::default(),
+            fields: Default::default(),
+        }
+    }
+
+    /// Sets the category of the record.
+    pub fn with_category<S: Into<String>>(mut self, category: S) -> Self {
+        self.category = category.into();
+        self
+    }
+
+    /// Adds a key-value field to the record.
+    pub fn with_field<K: Into<String>, V: Display>(mut self, key: K, value: V) -> Self {
+        self.fields.push((key.into(), value.to_string()));
+        self
+    }
+
+    /// Writes the record to the log.
+    pub fn write(self) {
+        LOG.lock()
+            .write_message(self.kind, self.content + "\n", self.category, self.fields)
+    }
 }
 
 static LOG: LazyLock<Mutex<Log>> = LazyLock::new(|| {
@@ -60,13 +154,16 @@ static LOG: LazyLock<Mutex<Log>> = LazyLock::new(|| {
         #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
         file: None,
         verbosity: MessageKind::Information,
+        category_verbosity: Default::default(),
         listeners: Default::default(),
+        sinks: Default::default(),
+        last_sink_id: 0,
         time_origin: Instant::now(),
     })
 });
 
 /// A kind of message.
-#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Ord, Hash)]
+#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Ord, Hash, Debug)]
 #[repr(u32)]
 pub enum MessageKind {
     /// Some useful information.
@@ -85,6 +182,14 @@ impl MessageKind {
             MessageKind::Error => "[ERROR]: ",
         }
     }
+
+    fn as_tag(self) -> &'static str {
+        match self {
+            MessageKind::Information => "[INFO]",
+            MessageKind::Warning => "[WARNING]",
+            MessageKind::Error => "[ERROR]",
+        }
+    }
 }
 
 /// See module docs.
@@ -92,7 +197,10 @@ pub struct Log {
     #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
     file: Option<std::fs::File>,
     verbosity: MessageKind,
+    category_verbosity: HashMap<String, MessageKind>,
     listeners: Vec<Sender<LogMessage>>,
+    sinks: Vec<(LogSinkId, Box<dyn LogSink>)>,
+    last_sink_id: u64,
     time_origin: Instant,
 }
 
@@ -119,20 +227,44 @@ impl Log {
     where
         S: AsRef<str>,
     {
-        let mut msg = message.as_ref().to_owned();
-        if kind as u32 >= self.verbosity as u32 {
+        self.write_message(
+            kind,
+            message.as_ref().to_owned(),
+            Default::default(),
+            Default::default(),
+        )
+    }
+
+    fn write_message(
+        &mut self,
+        kind: MessageKind,
+        content: String,
+        category: String,
+        fields: Vec<(String, String)>,
+    ) {
+        let verbosity = self
+            .category_verbosity
+            .get(&category)
+            .copied()
+            .unwrap_or(self.verbosity);
+        if kind as u32 >= verbosity as u32 {
+            let message = LogMessage {
+                kind,
+                content,
+                time: Instant::now() - self.time_origin,
+                category,
+                fields,
+            };
+
             // Notify listeners about the message and remove all disconnected listeners.
-            self.listeners.retain(|listener| {
-                listener
-                    .send(LogMessage {
-                        kind,
-                        content: msg.clone(),
-                        time: Instant::now() - self.time_origin,
-                    })
-                    .is_ok()
-            });
-
-            msg.insert_str(0, kind.as_str());
+            self.listeners
+                .retain(|listener| listener.send(message.clone()).is_ok());
+
+            for (_, sink) in self.sinks.iter_mut() {
+                sink.write(&message);
+            }
+
+            let msg = message.to_text();
 
             #[cfg(target_arch = "wasm32")]
             {
@@ -210,11 +342,49 @@ impl Log {
         LOG.lock().verbosity = kind;
     }
 
-    /// Adds a listener that will receive a copy of every message passed into the log.
+    /// Sets verbosity level for the messages of the given category. It overrides the global
+    /// verbosity level (see [`Self::set_verbosity`]) for the category.
+    pub fn set_category_verbosity<S: Into<String>>(category: S, kind: MessageKind) {
+        LOG.lock().category_verbosity.insert(category.into(), kind);
+    }
+
+    /// Removes category-specific verbosity level, so the messages of the category will use the
+    /// global verbosity level.
+    pub fn reset_category_verbosity(category: &str) {
+        LOG.lock().category_verbosity.remove(category);
+    }
+
+    /// Adds a listener that will receive a copy of every message passed into the log. Listeners
+    /// could be used to show the messages in an in-game console, for example.
     pub fn add_listener(listener: Sender<LogMessage>) {
         LOG.lock().listeners.push(listener)
     }
 
+    /// Adds a new sink, that will receive every message passed into the log. Returns an id of the
+    /// sink, that could be used to remove it. See [`sink`] module docs for more info.
+    pub fn add_sink<S: LogSink>(sink: S) -> LogSinkId {
+        let mut log = LOG.lock();
+        log.last_sink_id += 1;
+        let id = LogSinkId(log.last_sink_id);
+        log.sinks.push((id, Box::new(sink)));
+        id
+    }
+
+    /// Removes a sink with the given id. Returns `true` if the sink was removed.
+    pub fn remove_sink(id: LogSinkId) -> bool {
+        let mut log = LOG.lock();
+        let count = log.sinks.len();
+        log.sinks.retain(|(sink_id, _)| *sink_id != id);
+        log.sinks.len() != count
+    }
+
+    /// Flushes all the sinks.
+    pub fn flush() {
+        for (_, sink) in LOG.lock().sinks.iter_mut() {
+            sink.flush();
+        }
+    }
+
     /// Allows you to verify that the result of operation is Ok, or print the error in the log.
     ///
     /// # Use cases
@@ -270,3 +440,59 @@ macro_rules! err {
         $crate::log::Log::err(format!($($arg)*))
     };
 }
+
+#[cfg(test)]
+mod test {
+    use crate::log::{Log, LogMessage, LogRecord, MessageKind};
+    use crate::parking_lot::Mutex;
+    use std::{sync::Arc, time::Duration};
+
+    #[test]
+    fn test_message_to_text() {
+        let message = LogMessage {
+            kind: MessageKind::Warning,
+            content: "Unable to load a texture.\n".to_string(),
+            time: Duration::default(),
+            category: "resource".to_string(),
+            fields: vec![("path".to_string(), "foo.png".to_string())],
+        };
+        assert_eq!(
+            message.to_text(),
+            "[WARNING] [resource]: Unable to load a texture. path=foo.png\n"
+        );
+    }
+
+    #[test]
+    fn test_structured_record_sink() {
+        let received = Arc::new(Mutex::new(Vec::<LogMessage>::new()));
+        let sink_received = received.clone();
+        let id = Log::add_sink(move |message: &LogMessage| {
+            if message.category == "log_sink_test" {
+                sink_received.lock().push(message.clone());
+            }
+        });
+
+        LogRecord::new(MessageKind::Error, "Test")
+            .with_category("log_sink_test")
+            .with_field("answer", 42)
+            .write();
+
+        // Messages below the category verbosity must be filtered out.
+        Log::set_category_verbosity("log_sink_test", MessageKind::Error);
+        LogRecord::new(MessageKind::Warning, "Filtered")
+            .with_category("log_sink_test")
+            .write();
+        Log::reset_category_verbosity("log_sink_test");
+
+        assert!(Log::remove_sink(id));
+        assert!(!Log::remove_sink(id));
+
+        let received = received.lock();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].content, "Test\n");
+        assert_eq!(
+            received[0].fields,
+            vec![("answer".to_string(), "42".to_string())]
+        );
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Crash handler, that writes a report when the application panics. See [`CrashHandler`] docs
+//! for more info.
+
+use crate::{
+    log::{sink::LogSink, Log, LogMessage},
+    parking_lot::Mutex,
+};
+use std::{
+    backtrace::Backtrace,
+    collections::{BTreeMap, VecDeque},
+    fmt::{Display, Write as _},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, LazyLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Options of the crash handler.
+#[derive(Clone, Debug)]
+pub struct CrashHandlerOptions {
+    /// A folder, where crash reports will be written to. Default is `crash_reports`.
+    pub directory: PathBuf,
+    /// Amount of the most recent log messages, that will be included in a report. Default is 100.
+    pub history_size: usize,
+}
+
+impl Default for CrashHandlerOptions {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("crash_reports"),
+            history_size: 100,
+        }
+    }
+}
+
+#[derive(Default)]
+struct CrashState {
+    options: CrashHandlerOptions,
+    state: BTreeMap<String, String>,
+}
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static STATE: LazyLock<Mutex<CrashState>> = LazyLock::new(Default::default);
+static HISTORY: LazyLock<Arc<Mutex<VecDeque<LogMessage>>>> = LazyLock::new(Default::default);
+
+// Locks could be held by the panicking thread, so the handler must never wait for them forever.
+const LOCK_TIMEOUT: Duration = Duration::from_millis(250);
+
+struct HistorySink {
+    history: Arc<Mutex<VecDeque<LogMessage>>>,
+    capacity: usize,
+}
+
+impl LogSink for HistorySink {
+    fn write(&mut self, message: &LogMessage) {
+        let mut history = self.history.lock();
+        if history.len() >= self.capacity {
+            history.pop_front();
+        }
+        history.push_back(message.clone());
+    }
+}
+
+/// Crash handler writes a text report to a file when the application panics. The report contains
+/// the panic message and its location, the name of the thread, OS info, a summary of the
+/// application state (see [`CrashHandler::set_state`]), the most recent log messages and the
+/// backtrace. The previously installed panic hook is still called after the report is written.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// # use fyrox_core::log::crash::CrashHandler;
+/// CrashHandler::install(Default::default());
+/// CrashHandler::set_state("Level", "Forest");
+/// ```
+pub struct CrashHandler;
+
+impl CrashHandler {
+    /// Installs the crash handler. Does nothing if the handler is already installed.
+    pub fn install(options: CrashHandlerOptions) {
+        if INSTALLED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        Log::add_sink(HistorySink {
+            history: HISTORY.clone(),
+            capacity: options.history_size.max(1),
+        });
+
+        STATE.lock().options = options;
+
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let message = if let Some(message) = info.payload().downcast_ref::<&str>() {
+                message.to_string()
+            } else if let Some(message) = info.payload().downcast_ref::<String>() {
+                message.clone()
+            } else {
+                "<unknown>".to_string()
+            };
+            let location = info.location().map(|location| location.to_string());
+            match write_report(&message, location.as_deref()) {
+                Ok(path) => eprintln!("Crash report was written to {}", path.display()),
+                Err(err) => eprintln!("Unable to write crash report. Reason: {err}"),
+            }
+            previous(info);
+        }));
+    }
+
+    /// Returns `true` if the crash handler is installed, `false` - otherwise.
+    pub fn is_installed() -> bool {
+        INSTALLED.load(Ordering::Relaxed)
+    }
+
+    /// Sets a value, that will be written to the "state" section of a crash report. It could be
+    /// used to provide some context for crashes, such as the current level or the amount of
+    /// objects. Does nothing if the handler is not installed.
+    pub fn set_state<K: Into<String>, V: Display>(key: K, value: V) {
+        if Self::is_installed() {
+            STATE.lock().state.insert(key.into(), value.to_string());
+        }
+    }
+
+    /// Removes a value from the "state" section of crash reports.
+    pub fn remove_state(key: &str) {
+        STATE.lock().state.remove(key);
+    }
+}
+
+fn make_report(message: &str, location: Option<&str>, timestamp: u64) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "=== Crash Report ===");
+    let _ = writeln!(report, "Time: {timestamp} (seconds since UNIX epoch)");
+    let _ = writeln!(
+        report,
+        "Thread: {}",
+        std::thread::current().name().unwrap_or("<unnamed>")
+    );
+    let _ = writeln!(report, "Message: {message}");
+    if let Some(location) = location {
+        let _ = writeln!(report, "Location: {location}");
+    }
+    let _ = writeln!(
+        report,
+        "OS: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+
+    let _ = writeln!(report, "\n=== State ===");
+    match STATE.try_lock_for(LOCK_TIMEOUT) {
+        Some(state) => {
+            for (key, value) in state.state.iter() {
+                let _ = writeln!(report, "{key}: {value}");
+            }
+        }
+        None => {
+            let _ = writeln!(report, "<unavailable>");
+        }
+    }
+
+    let _ = writeln!(report, "\n=== Recent Log Messages ===");
+    match HISTORY.try_lock_for(LOCK_TIMEOUT) {
+        Some(history) => {
+            for message in history.iter() {
+                let _ = write!(
+                    report,
+                    "[{:.3}s] {}",
+                    message.time.as_secs_f32(),
+                    message.to_text()
+                );
+                if !report.ends_with('\n') {
+                    report.push('\n');
+                }
+            }
+        }
+        None => {
+            let _ = writeln!(report, "<unavailable>");
+        }
+    }
+
+    let _ = writeln!(report, "\n=== Backtrace ===");
+    let _ = writeln!(report, "{}", Backtrace::force_capture());
+
+    report
+}
+
+fn write_report(message: &str, location: Option<&str>) -> std::io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let directory = STATE
+        .try_lock_for(LOCK_TIMEOUT)
+        .map(|state| state.options.directory.clone())
+        .unwrap_or_else(|| CrashHandlerOptions::default().directory);
+
+    let report = make_report(message, location, timestamp);
+
+    std::fs::create_dir_all(&directory)?;
+    let path = directory.join(format!("crash_{timestamp}.txt"));
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::log::crash::{make_report, CrashHandler, STATE};
+
+    #[test]
+    fn test_crash_report() {
+        STATE
+            .lock()
+            .state
+            .insert("Scenes".to_string(), "2".to_string());
+        let report = make_report("Something went wrong", Some("src/main.rs:1:1"), 0);
+        assert!(report.contains("Message: Something went wrong"));
+        assert!(report.contains("Location: src/main.rs:1:1"));
+        assert!(report.contains("Scenes: 2"));
+        CrashHandler::remove_state("Scenes");
+    }
+}
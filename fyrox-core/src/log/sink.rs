@@ -0,0 +1,358 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Log sinks are receivers of log messages. The logger always writes messages to the standard
+//! output (and to the log file, if any), sinks could be used to send the messages somewhere
+//! else. Built-in sinks are:
+//!
+//! - [`RotatingFileSink`] - writes messages to a file and rotates it when it becomes too large.
+//! - [`TcpSink`] - sends messages to a remote host over TCP.
+//!
+//! Messages could also be received via a channel (see [`super::Log::add_listener`]), which is
+//! useful to show them in an in-game console.
+
+use crate::log::LogMessage;
+
+/// A unique identifier of a sink, that could be used to remove it from the log.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LogSinkId(pub(super) u64);
+
+/// A receiver of log messages. Sinks are called while the logger is locked, so a sink must not
+/// write anything to the log, otherwise it will result in a deadlock.
+pub trait LogSink: Send + 'static {
+    /// Writes the message to the sink.
+    fn write(&mut self, message: &LogMessage);
+
+    /// Flushes all buffered messages (if any).
+    fn flush(&mut self) {}
+}
+
+impl<F> LogSink for F
+where
+    F: FnMut(&LogMessage) + Send + 'static,
+{
+    fn write(&mut self, message: &LogMessage) {
+        self(message)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use crate::{instant::Instant, log::sink::LogSink, log::LogMessage};
+    use std::{
+        ffi::OsString,
+        fs::File,
+        io::{self, BufWriter, Write},
+        net::{SocketAddr, TcpStream, ToSocketAddrs},
+        path::{Path, PathBuf},
+        sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+        time::Duration,
+    };
+
+    /// A sink, that writes messages to a file. When the file size exceeds the limit, the file is
+    /// renamed to `<name>.1`, the previous `<name>.1` is renamed to `<name>.2` and so on. The
+    /// oldest file is deleted when the amount of files exceeds the limit.
+    pub struct RotatingFileSink {
+        path: PathBuf,
+        max_size: u64,
+        max_files: usize,
+        writer: Option<BufWriter<File>>,
+        size: u64,
+    }
+
+    impl RotatingFileSink {
+        /// Creates a new sink, that writes messages to the file at the given path. `max_size` is
+        /// the maximum size of a single file in bytes, `max_files` is the maximum amount of old
+        /// files, that will be kept.
+        pub fn new<P: AsRef<Path>>(path: P, max_size: u64, max_files: usize) -> io::Result<Self> {
+            let path = path.as_ref().to_path_buf();
+            let file = File::create(&path)?;
+            Ok(Self {
+                path,
+                max_size: max_size.max(1),
+                max_files,
+                writer: Some(BufWriter::new(file)),
+                size: 0,
+            })
+        }
+
+        fn rotated_path(&self, index: usize) -> PathBuf {
+            let mut name = OsString::from(self.path.as_os_str());
+            name.push(format!(".{index}"));
+            PathBuf::from(name)
+        }
+
+        fn rotate(&mut self) -> io::Result<()> {
+            // Close the file first, some OSes do not allow renaming opened files.
+            if let Some(mut writer) = self.writer.take() {
+                writer.flush()?;
+            }
+
+            if self.max_files == 0 {
+                std::fs::remove_file(&self.path)?;
+            } else {
+                let _ = std::fs::remove_file(self.rotated_path(self.max_files));
+                for index in (1..self.max_files).rev() {
+                    let from = self.rotated_path(index);
+                    if from.exists() {
+                        std::fs::rename(from, self.rotated_path(index + 1))?;
+                    }
+                }
+                std::fs::rename(&self.path, self.rotated_path(1))?;
+            }
+
+            self.writer = Some(BufWriter::new(File::create(&self.path)?));
+            self.size = 0;
+            Ok(())
+        }
+    }
+
+    impl LogSink for RotatingFileSink {
+        fn write(&mut self, message: &LogMessage) {
+            let text = message.to_text();
+            if self.size > 0 && self.size + text.len() as u64 > self.max_size {
+                // The sink cannot use the log to report errors, so the rotation will be tried
+                // again on the next message.
+                let _ = self.rotate();
+            }
+            if let Some(writer) = self.writer.as_mut() {
+                if writer.write_all(text.as_bytes()).is_ok() {
+                    self.size += text.len() as u64;
+                }
+            }
+        }
+
+        fn flush(&mut self) {
+            if let Some(writer) = self.writer.as_mut() {
+                let _ = writer.flush();
+            }
+        }
+    }
+
+    impl Drop for RotatingFileSink {
+        fn drop(&mut self) {
+            self.flush();
+        }
+    }
+
+    enum TcpCommand {
+        Write(String),
+        Flush,
+    }
+
+    /// A sink, that sends messages to a remote host over TCP as lines of text (see
+    /// [`LogMessage::to_text`]). Network IO is done on a background thread, so the sink never
+    /// blocks the logger. If the connection is lost, the sink tries to reconnect periodically,
+    /// messages that were written while there was no connection are dropped. Messages are also
+    /// dropped if the remote host can't keep up and the queue of the sink is full.
+    pub struct TcpSink {
+        address: SocketAddr,
+        reconnect_interval: Duration,
+        sender: Option<SyncSender<TcpCommand>>,
+    }
+
+    impl TcpSink {
+        /// Timeout of connection and write operations.
+        pub const TIMEOUT: Duration = Duration::from_millis(250);
+
+        /// Maximum amount of messages, that are waiting to be sent.
+        pub const QUEUE_SIZE: usize = 1024;
+
+        /// Creates a new sink, that sends messages to the given address. The background thread
+        /// and the connection are created lazily, on the first message.
+        pub fn new<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+            let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "No address was resolved!")
+            })?;
+            Ok(Self {
+                address,
+                reconnect_interval: Duration::from_secs(5),
+                sender: None,
+            })
+        }
+
+        /// Sets the minimal interval between reconnection attempts. Default is 5 seconds.
+        pub fn with_reconnect_interval(mut self, interval: Duration) -> Self {
+            self.reconnect_interval = interval;
+            self
+        }
+
+        fn send(&mut self, command: TcpCommand) {
+            if self.sender.is_none() {
+                let (sender, receiver) = mpsc::sync_channel(Self::QUEUE_SIZE);
+                let worker = TcpWorker {
+                    address: self.address,
+                    reconnect_interval: self.reconnect_interval,
+                    stream: None,
+                    last_attempt: None,
+                };
+                // The sink cannot use the log to report errors, so the thread will be spawned
+                // again on the next message.
+                if std::thread::Builder::new()
+                    .name("TcpLogSink".to_string())
+                    .spawn(move || worker.run(receiver))
+                    .is_ok()
+                {
+                    self.sender = Some(sender);
+                }
+            }
+
+            if let Some(sender) = self.sender.as_ref() {
+                if let Err(TrySendError::Disconnected(_)) = sender.try_send(command) {
+                    self.sender = None;
+                }
+            }
+        }
+    }
+
+    impl LogSink for TcpSink {
+        fn write(&mut self, message: &LogMessage) {
+            let mut text = message.to_text();
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+            self.send(TcpCommand::Write(text));
+        }
+
+        fn flush(&mut self) {
+            if self.sender.is_some() {
+                self.send(TcpCommand::Flush);
+            }
+        }
+    }
+
+    // Owns the connection of a [`TcpSink`] and lives on a background thread. The thread stops
+    // when the sink is dropped.
+    struct TcpWorker {
+        address: SocketAddr,
+        reconnect_interval: Duration,
+        stream: Option<TcpStream>,
+        last_attempt: Option<Instant>,
+    }
+
+    impl TcpWorker {
+        fn connect(&mut self) {
+            if self
+                .last_attempt
+                .is_some_and(|time| time.elapsed() < self.reconnect_interval)
+            {
+                return;
+            }
+            self.last_attempt = Some(Instant::now());
+            self.stream = TcpStream::connect_timeout(&self.address, TcpSink::TIMEOUT)
+                .and_then(|stream| {
+                    stream.set_write_timeout(Some(TcpSink::TIMEOUT))?;
+                    stream.set_nodelay(true)?;
+                    Ok(stream)
+                })
+                .ok();
+        }
+
+        fn run(mut self, receiver: Receiver<TcpCommand>) {
+            for command in receiver {
+                match command {
+                    TcpCommand::Write(text) => {
+                        if self.stream.is_none() {
+                            self.connect();
+                        }
+                        if let Some(stream) = self.stream.as_mut() {
+                            if stream.write_all(text.as_bytes()).is_err() {
+                                self.stream = None;
+                            }
+                        }
+                    }
+                    TcpCommand::Flush => {
+                        if let Some(stream) = self.stream.as_mut() {
+                            let _ = stream.flush();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::log::{
+        sink::{LogSink, RotatingFileSink, TcpSink},
+        LogMessage, MessageKind,
+    };
+    use std::{
+        io::{BufRead, BufReader},
+        net::TcpListener,
+        time::Duration,
+    };
+
+    #[test]
+    fn test_rotating_file_sink() {
+        let dir = std::env::temp_dir().join(format!("fyrox_log_sink_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.log");
+
+        let message = LogMessage {
+            kind: MessageKind::Information,
+            content: "0123456789\n".to_string(),
+            time: Duration::default(),
+            category: Default::default(),
+            fields: Default::default(),
+        };
+
+        {
+            let mut sink = RotatingFileSink::new(&path, 32, 2).unwrap();
+            for _ in 0..10 {
+                sink.write(&message);
+            }
+        }
+
+        assert!(path.exists());
+        assert!(dir.join("test.log.1").exists());
+        assert!(dir.join("test.log.2").exists());
+        assert!(!dir.join("test.log.3").exists());
+        assert!(std::fs::metadata(&path).unwrap().len() <= 32);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_tcp_sink() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut sink = TcpSink::new(listener.local_addr().unwrap()).unwrap();
+
+        let message = LogMessage {
+            kind: MessageKind::Warning,
+            content: "Hello".to_string(),
+            time: Duration::default(),
+            category: Default::default(),
+            fields: Default::default(),
+        };
+        // Does not block, the message is sent from the background thread.
+        sink.write(&message);
+        sink.flush();
+
+        let (stream, _) = listener.accept().unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        assert_eq!(line, message.to_text() + "\n");
+    }
+}
@@ -114,6 +114,18 @@ pub struct FieldInfo<'a, 'b> {
 
     /// Maximum amount of decimal places for a numeric property.
     pub precision: Option<usize>,
+
+    /// Name of a category of the property. Properties with the same category are grouped together
+    /// in the editor. Empty string means that the property does not belong to any category.
+    pub category: &'b str,
+
+    /// A numeric property should be edited using a slider. Works only if both `min_value` and
+    /// `max_value` are set.
+    pub slider: bool,
+
+    /// A property should be shown in the editor. Could be `false` if the property is visible only
+    /// under some condition (see `#[reflect(visible_if = ..)]`).
+    pub visible: bool,
 }
 
 impl FieldInfo<'_, '_> {
@@ -143,6 +155,9 @@ impl fmt::Debug for FieldInfo<'_, '_> {
             .field("step", &self.step)
             .field("precision", &self.precision)
             .field("description", &self.description)
+            .field("category", &self.category)
+            .field("slider", &self.slider)
+            .field("visible", &self.visible)
             .finish()
     }
 }
@@ -162,6 +177,9 @@ impl PartialEq<Self> for FieldInfo<'_, '_> {
             && self.step == other.step
             && self.precision == other.precision
             && self.description == other.description
+            && self.category == other.category
+            && self.slider == other.slider
+            && self.visible == other.visible
     }
 }
 
@@ -20,11 +20,15 @@
 
 #[cfg(not(target_arch = "wasm32"))]
 use crate::futures::executor::ThreadPool;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 use std::{
     any::Any,
     future::Future,
-    sync::mpsc::{self, Receiver, Sender},
+    pin::Pin,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
 };
 use uuid::Uuid;
 
@@ -51,6 +55,10 @@ pub trait AsyncTask<R: AsyncTaskResult>: Future<Output = R> + Send + 'static {}
 #[cfg(not(target_arch = "wasm32"))]
 impl<T, R: AsyncTaskResult> AsyncTask<R> for T where T: Future<Output = R> + Send + 'static {}
 
+/// A type-erased task, that is passed to a [`TaskSpawner`].
+#[cfg(not(target_arch = "wasm32"))]
+pub type BoxedTask = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
 // ========
 // WASM
 #[cfg(target_arch = "wasm32")]
@@ -74,6 +82,10 @@ pub trait AsyncTask<R: AsyncTaskResult>: Future<Output = R> + 'static {}
 #[cfg(target_arch = "wasm32")]
 impl<T, R: AsyncTaskResult> AsyncTask<R> for T where T: Future<Output = R> + 'static {}
 
+/// A type-erased task, that is passed to a [`TaskSpawner`].
+#[cfg(target_arch = "wasm32")]
+pub type BoxedTask = Pin<Box<dyn Future<Output = ()> + 'static>>;
+
 // ========
 // Common
 impl dyn AsyncTaskResult {
@@ -82,6 +94,30 @@ impl dyn AsyncTaskResult {
     }
 }
 
+/// An external executor, that could be used to run the tasks of a [`TaskPool`] instead of its
+/// built-in thread pool. It is implemented for every `Fn(BoxedTask)` closure, so an executor of
+/// an async runtime could be plugged in like this:
+///
+/// ```rust,ignore
+/// let runtime = tokio::runtime::Handle::current();
+/// task_pool.set_spawner(Some(Arc::new(move |task| {
+///     runtime.spawn(task);
+/// })));
+/// ```
+pub trait TaskSpawner: Send + Sync + 'static {
+    /// Spawns the task on the executor. The task must be polled until completion.
+    fn spawn(&self, task: BoxedTask);
+}
+
+impl<F> TaskSpawner for F
+where
+    F: Fn(BoxedTask) + Send + Sync + 'static,
+{
+    fn spawn(&self, task: BoxedTask) {
+        self(task)
+    }
+}
+
 pub struct TaskResult {
     pub id: Uuid,
     pub payload: Box<dyn AsyncTaskResult>,
@@ -90,6 +126,7 @@ pub struct TaskResult {
 pub struct TaskPool {
     #[cfg(not(target_arch = "wasm32"))]
     thread_pool: ThreadPool,
+    spawner: RwLock<Option<Arc<dyn TaskSpawner>>>,
     sender: Sender<TaskResult>,
     receiver: Mutex<Receiver<TaskResult>>,
 }
@@ -107,18 +144,51 @@ impl TaskPool {
         Self {
             #[cfg(not(target_arch = "wasm32"))]
             thread_pool: ThreadPool::new().unwrap(),
+            spawner: Default::default(),
             sender,
             receiver: Mutex::new(receiver),
         }
     }
 
+    /// Creates a task pool, that runs its tasks on the given external executor.
+    #[inline]
+    pub fn with_spawner<S: TaskSpawner>(spawner: S) -> Self {
+        let pool = Self::new();
+        pool.set_spawner(Some(Arc::new(spawner)));
+        pool
+    }
+
+    /// Sets an external executor, that will be used to run all subsequently spawned tasks. `None`
+    /// switches the pool back to its built-in executor (a thread pool on PC or microtasks on
+    /// WebAssembly). Tasks that were already spawned continue to run on their executor.
+    #[inline]
+    pub fn set_spawner(&self, spawner: Option<Arc<dyn TaskSpawner>>) {
+        *self.spawner.write() = spawner;
+    }
+
+    /// Returns `true` if the pool uses an external executor.
+    #[inline]
+    pub fn has_external_spawner(&self) -> bool {
+        self.spawner.read().is_some()
+    }
+
+    // The lock must not be held while spawning, because an executor could run the task
+    // immediately and the task could spawn other tasks.
+    #[inline]
+    fn external_spawner(&self) -> Option<Arc<dyn TaskSpawner>> {
+        self.spawner.read().clone()
+    }
+
     #[inline]
     #[cfg(target_arch = "wasm32")]
     pub fn spawn_task<F>(&self, future: F)
     where
         F: Future<Output = ()> + 'static,
     {
-        crate::wasm_bindgen_futures::spawn_local(future);
+        match self.external_spawner() {
+            Some(spawner) => spawner.spawn(Box::pin(future)),
+            None => crate::wasm_bindgen_futures::spawn_local(future),
+        }
     }
 
     #[inline]
@@ -127,7 +197,10 @@ impl TaskPool {
     where
         F: Future<Output = ()> + Send + 'static,
     {
-        self.thread_pool.spawn_ok(future);
+        match self.external_spawner() {
+            Some(spawner) => spawner.spawn(Box::pin(future)),
+            None => self.thread_pool.spawn_ok(future),
+        }
     }
 
     #[inline]
@@ -155,3 +228,33 @@ impl TaskPool {
         self.receiver.lock().try_recv().ok()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::task::{BoxedTask, TaskPool};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn test_external_spawner() {
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let counter = spawned.clone();
+        let pool = TaskPool::with_spawner(move |task: BoxedTask| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            crate::futures::executor::block_on(task);
+        });
+        assert!(pool.has_external_spawner());
+
+        let id = pool.spawn_with_result(async { 42u32 });
+        assert_eq!(spawned.load(Ordering::SeqCst), 1);
+
+        let result = pool.next_task_result().unwrap();
+        assert_eq!(result.id, id);
+        assert_eq!(*result.payload.downcast::<u32>().unwrap(), 42);
+
+        pool.set_spawner(None);
+        assert!(!pool.has_external_spawner());
+    }
+}
@@ -0,0 +1,673 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Chunked (and optionally compressed) binary format of [`Visitor`]. The format consists of a
+//! header, a string table with all node and field names, a "skeleton" with top-level nodes and a
+//! set of chunks, each of which stores a sub-tree of the visitor (usually a separate subsystem,
+//! for example `Scene/Graph/Pool`). Chunks are compressed separately, which allows reading only
+//! a part of the data without decompressing the rest (see [`Visitor::load_chunks_from_memory`]).
+//!
+//! Layout of the format is the following:
+//!
+//! ```text
+//! Magic ("RG3C"), version (u8), compression (u8)
+//! String table: count (u32), then every string as length (u32) + UTF-8 bytes
+//! Skeleton: raw size (u64), compressed size (u64), compressed bytes
+//! Chunk directory: count (u32), then for every chunk: path length (u32), path as string
+//! indices (u32), offset (u64), raw size (u64), compressed size (u64)
+//! Chunk data
+//! ```
+
+use crate::visitor::{Field, VisitError, VisitResult, Visitor, VisitorFlags, VisitorNode};
+use crate::{
+    io,
+    pool::{Handle, Pool},
+};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use fxhash::FxHashMap;
+use std::{
+    fs::File,
+    io::{BufWriter, Cursor, Write},
+    path::Path,
+};
+
+const VERSION: u8 = 1;
+const INLINE_NODE: u8 = 0;
+const CHUNK_REFERENCE: u8 = 1;
+/// Maximum ratio between the raw size and the compressed size of a chunk. Sizes are read from
+/// the file, so they must be validated before allocating memory for the decompressed data.
+const MAX_COMPRESSION_RATIO: usize = 1 << 16;
+
+/// Compression of the data of [chunked format](self).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum VisitorCompression {
+    /// No compression.
+    #[default]
+    None,
+    /// Fast compression with moderate compression ratio. Requires `lz4` feature (enabled by
+    /// default), decompression will fail if the feature is disabled.
+    Lz4,
+    /// Slower compression with better compression ratio. Requires `zstd` feature, decompression
+    /// will fail if the feature is disabled.
+    Zstd,
+}
+
+impl VisitorCompression {
+    fn id(self) -> u8 {
+        match self {
+            VisitorCompression::None => 0,
+            VisitorCompression::Lz4 => 1,
+            VisitorCompression::Zstd => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, VisitError> {
+        match id {
+            0 => Ok(VisitorCompression::None),
+            1 => Ok(VisitorCompression::Lz4),
+            2 => Ok(VisitorCompression::Zstd),
+            _ => Err(VisitError::User(format!("Unknown compression {id}!"))),
+        }
+    }
+
+    fn compress(self, data: Vec<u8>) -> Result<Vec<u8>, VisitError> {
+        match self {
+            VisitorCompression::None => Ok(data),
+            #[cfg(feature = "lz4")]
+            VisitorCompression::Lz4 => Ok(lz4_flex::compress(&data)),
+            #[cfg(not(feature = "lz4"))]
+            VisitorCompression::Lz4 => Err(unsupported("lz4")),
+            #[cfg(feature = "zstd")]
+            VisitorCompression::Zstd => Ok(zstd::bulk::compress(&data, 0)?),
+            #[cfg(not(feature = "zstd"))]
+            VisitorCompression::Zstd => Err(unsupported("zstd")),
+        }
+    }
+
+    fn decompress(self, data: &[u8], raw_size: usize) -> Result<Vec<u8>, VisitError> {
+        let valid_size = match self {
+            VisitorCompression::None => raw_size == data.len(),
+            VisitorCompression::Lz4 | VisitorCompression::Zstd => {
+                raw_size <= data.len().saturating_mul(MAX_COMPRESSION_RATIO)
+            }
+        };
+        if !valid_size {
+            return Err(VisitError::User(format!(
+                "Invalid raw size {raw_size} of {} bytes of compressed data!",
+                data.len()
+            )));
+        }
+
+        match self {
+            VisitorCompression::None => Ok(data.to_vec()),
+            #[cfg(feature = "lz4")]
+            VisitorCompression::Lz4 => lz4_flex::decompress(data, raw_size)
+                .map_err(|err| VisitError::User(format!("Unable to decompress data: {err}"))),
+            #[cfg(not(feature = "lz4"))]
+            VisitorCompression::Lz4 => Err(unsupported("lz4")),
+            #[cfg(feature = "zstd")]
+            VisitorCompression::Zstd => Ok(zstd::bulk::decompress(data, raw_size)?),
+            #[cfg(not(feature = "zstd"))]
+            VisitorCompression::Zstd => Err(unsupported("zstd")),
+        }
+    }
+}
+
+#[cfg(any(not(feature = "lz4"), not(feature = "zstd")))]
+fn unsupported(feature: &str) -> VisitError {
+    VisitError::User(format!(
+        "{feature} compression requires `{feature}` feature!"
+    ))
+}
+
+/// Reads a length or a count from the given reader and checks that the remaining data could hold
+/// that many elements of the given size, so corrupted data can not cause huge allocations.
+fn read_len(reader: &mut Cursor<&[u8]>, element_size: usize) -> Result<usize, VisitError> {
+    let len = reader.read_u32::<LittleEndian>()? as usize;
+    let remaining = reader
+        .get_ref()
+        .len()
+        .saturating_sub(reader.position() as usize);
+    if len.saturating_mul(element_size) > remaining {
+        return Err(VisitError::User(format!(
+            "Invalid length {len}, only {remaining} bytes left!"
+        )));
+    }
+    Ok(len)
+}
+
+fn to_usize(value: u64) -> Result<usize, VisitError> {
+    usize::try_from(value).map_err(|_| VisitError::User(format!("Invalid size {value}!")))
+}
+
+/// Reads the given amount of bytes from the given reader without copying them.
+fn read_slice<'a>(reader: &mut Cursor<&'a [u8]>, len: usize) -> Result<&'a [u8], VisitError> {
+    let data: &'a [u8] = *reader.get_ref();
+    let begin = reader.position() as usize;
+    let slice = begin
+        .checked_add(len)
+        .and_then(|end| data.get(begin..end))
+        .ok_or_else(|| {
+            VisitError::User(format!("Unexpected end of data, expected {len} bytes!"))
+        })?;
+    reader.set_position((begin + len) as u64);
+    Ok(slice)
+}
+
+/// Options of [chunked format](self).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ChunkedFormatOptions {
+    /// Compression of the skeleton and the chunks.
+    pub compression: VisitorCompression,
+    /// Depth of the nodes (relative to the root node of the visitor) that will be stored in
+    /// separate chunks. The default value is 3, which places every subsystem of a scene (for
+    /// example `Scene/Graph/Pool` or `Scene/Graph/PhysicsWorld`) in a separate chunk.
+    pub chunk_depth: usize,
+}
+
+impl Default for ChunkedFormatOptions {
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "lz4")]
+            compression: VisitorCompression::Lz4,
+            #[cfg(not(feature = "lz4"))]
+            compression: VisitorCompression::None,
+            chunk_depth: 3,
+        }
+    }
+}
+
+/// Description of a chunk.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkInfo {
+    /// Names of the nodes from the root node of the visitor (excluding) to the root node of the
+    /// chunk (including), for example `["Scene", "Graph", "Pool"]`.
+    pub path: Vec<String>,
+    /// Size of the chunk data after decompression.
+    pub raw_size: u64,
+    /// Size of the chunk data in the file.
+    pub compressed_size: u64,
+    offset: u64,
+}
+
+impl ChunkInfo {
+    /// Returns the path of the chunk joined with `/`.
+    pub fn path_string(&self) -> String {
+        self.path.join("/")
+    }
+}
+
+#[derive(Default)]
+struct StringTable {
+    strings: Vec<String>,
+    indices: FxHashMap<String, u32>,
+}
+
+impl StringTable {
+    fn index_of(&mut self, string: &str) -> u32 {
+        if let Some(index) = self.indices.get(string) {
+            return *index;
+        }
+        let index = self.strings.len() as u32;
+        self.strings.push(string.to_string());
+        self.indices.insert(string.to_string(), index);
+        index
+    }
+
+    fn get(&self, index: u32) -> Result<&str, VisitError> {
+        self.strings
+            .get(index as usize)
+            .map(|s| s.as_str())
+            .ok_or_else(|| VisitError::User(format!("Invalid string index {index}!")))
+    }
+}
+
+fn read_string_table(reader: &mut Cursor<&[u8]>) -> Result<StringTable, VisitError> {
+    // Every string takes at least 4 bytes for its length.
+    let count = read_len(reader, 4)?;
+    let mut table = StringTable::default();
+    table.strings.reserve(count);
+    for _ in 0..count {
+        let len = read_len(reader, 1)?;
+        let raw = read_slice(reader, len)?;
+        table.strings.push(String::from_utf8(raw.to_vec())?);
+    }
+    Ok(table)
+}
+
+struct Header {
+    compression: VisitorCompression,
+    strings: StringTable,
+    skeleton: Vec<u8>,
+    chunks: Vec<ChunkInfo>,
+    data_offset: usize,
+}
+
+impl Header {
+    fn read(data: &[u8]) -> Result<Self, VisitError> {
+        if !Visitor::is_chunked(data) {
+            return Err(VisitError::NotSupportedFormat);
+        }
+
+        let mut reader = Cursor::new(&data[Visitor::CHUNKED_MAGIC.len()..]);
+        let version = reader.read_u8()?;
+        if version != VERSION {
+            return Err(VisitError::User(format!(
+                "Unsupported chunked format version {version}!"
+            )));
+        }
+        let compression = VisitorCompression::from_id(reader.read_u8()?)?;
+        let strings = read_string_table(&mut reader)?;
+
+        let raw_size = to_usize(reader.read_u64::<LittleEndian>()?)?;
+        let compressed_size = to_usize(reader.read_u64::<LittleEndian>()?)?;
+        let compressed = read_slice(&mut reader, compressed_size)?;
+        let skeleton = compression.decompress(compressed, raw_size)?;
+
+        // Every chunk takes at least 28 bytes: path length, offset, raw and compressed sizes.
+        let chunk_count = read_len(&mut reader, 28)?;
+        let mut chunks = Vec::with_capacity(chunk_count);
+        for _ in 0..chunk_count {
+            let path_len = read_len(&mut reader, 4)?;
+            let mut path = Vec::with_capacity(path_len);
+            for _ in 0..path_len {
+                path.push(strings.get(reader.read_u32::<LittleEndian>()?)?.to_string());
+            }
+            chunks.push(ChunkInfo {
+                path,
+                offset: reader.read_u64::<LittleEndian>()?,
+                raw_size: reader.read_u64::<LittleEndian>()?,
+                compressed_size: reader.read_u64::<LittleEndian>()?,
+            });
+        }
+
+        Ok(Self {
+            compression,
+            strings,
+            skeleton,
+            chunks,
+            data_offset: Visitor::CHUNKED_MAGIC.len() + reader.position() as usize,
+        })
+    }
+
+    fn chunk_data(&self, data: &[u8], chunk: &ChunkInfo) -> Result<Vec<u8>, VisitError> {
+        let out_of_bounds =
+            || VisitError::User(format!("Chunk {} is out of bounds!", chunk.path_string()));
+        let begin = to_usize(chunk.offset)?
+            .checked_add(self.data_offset)
+            .ok_or_else(out_of_bounds)?;
+        let end = to_usize(chunk.compressed_size)?
+            .checked_add(begin)
+            .ok_or_else(out_of_bounds)?;
+        let compressed = data.get(begin..end).ok_or_else(out_of_bounds)?;
+        self.compression
+            .decompress(compressed, to_usize(chunk.raw_size)?)
+    }
+}
+
+struct ChunkWriter<'a> {
+    visitor: &'a Visitor,
+    options: ChunkedFormatOptions,
+    strings: StringTable,
+    chunks: Vec<(Vec<u32>, Vec<u8>)>,
+}
+
+impl ChunkWriter<'_> {
+    fn write_node(
+        &mut self,
+        handle: Handle<VisitorNode>,
+        depth: usize,
+        path: &mut Vec<u32>,
+        writer: &mut dyn Write,
+    ) -> VisitResult {
+        let visitor = self.visitor;
+        let node = visitor.nodes.borrow(handle);
+        writer.write_u32::<LittleEndian>(self.strings.index_of(&node.name))?;
+
+        writer.write_u32::<LittleEndian>(node.fields.len() as u32)?;
+        for field in node.fields.iter() {
+            writer.write_u32::<LittleEndian>(self.strings.index_of(&field.name))?;
+            Field::save_kind(&field.kind, writer)?;
+        }
+
+        writer.write_u32::<LittleEndian>(node.children.len() as u32)?;
+        for &child_handle in node.children.iter() {
+            let child = visitor.nodes.borrow(child_handle);
+            path.push(self.strings.index_of(&child.name));
+            if depth + 1 == self.options.chunk_depth {
+                let mut chunk = Vec::new();
+                self.write_node(child_handle, depth + 1, path, &mut chunk)?;
+                writer.write_u8(CHUNK_REFERENCE)?;
+                writer.write_u32::<LittleEndian>(self.chunks.len() as u32)?;
+                self.chunks.push((path.clone(), chunk));
+            } else {
+                writer.write_u8(INLINE_NODE)?;
+                self.write_node(child_handle, depth + 1, path, writer)?;
+            }
+            path.pop();
+        }
+
+        Ok(())
+    }
+}
+
+struct ChunkReader<'a> {
+    data: &'a [u8],
+    header: &'a Header,
+    nodes: Pool<VisitorNode>,
+    filter: &'a mut dyn FnMut(&ChunkInfo) -> bool,
+}
+
+impl ChunkReader<'_> {
+    fn read_node(&mut self, reader: &mut Cursor<&[u8]>) -> Result<Handle<VisitorNode>, VisitError> {
+        let mut node = VisitorNode {
+            name: self
+                .header
+                .strings
+                .get(reader.read_u32::<LittleEndian>()?)?
+                .to_string(),
+            ..VisitorNode::default()
+        };
+
+        // Every field takes at least 5 bytes: name index and kind id.
+        let field_count = read_len(reader, 5)?;
+        for _ in 0..field_count {
+            let name = self
+                .header
+                .strings
+                .get(reader.read_u32::<LittleEndian>()?)?;
+            node.fields
+                .push(Field::new(name, Field::load_kind(reader)?));
+        }
+
+        // Every child takes at least 5 bytes: tag and name or chunk index.
+        let child_count = read_len(reader, 5)?;
+        let mut children = Vec::with_capacity(child_count);
+        for _ in 0..child_count {
+            match reader.read_u8()? {
+                INLINE_NODE => children.push(self.read_node(reader)?),
+                CHUNK_REFERENCE => {
+                    let index = reader.read_u32::<LittleEndian>()? as usize;
+                    let chunk =
+                        self.header.chunks.get(index).ok_or_else(|| {
+                            VisitError::User(format!("Invalid chunk index {index}!"))
+                        })?;
+                    if (self.filter)(chunk) {
+                        let chunk_data = self.header.chunk_data(self.data, chunk)?;
+                        children.push(self.read_node(&mut Cursor::new(chunk_data.as_slice()))?);
+                    }
+                }
+                tag => return Err(VisitError::User(format!("Invalid node tag {tag}!"))),
+            }
+        }
+
+        node.children.clone_from(&children);
+
+        let handle = self.nodes.spawn(node);
+        for child_handle in children.iter() {
+            self.nodes.borrow_mut(*child_handle).parent = handle;
+        }
+
+        Ok(handle)
+    }
+}
+
+impl Visitor {
+    /// Sequence of bytes that is written at the start of the data in [chunked format](self).
+    pub const CHUNKED_MAGIC: &'static str = "RG3C";
+
+    /// Returns `true` if the data is in [chunked format](self).
+    pub fn is_chunked(data: &[u8]) -> bool {
+        data.starts_with(Self::CHUNKED_MAGIC.as_bytes())
+    }
+
+    /// Writes the data of this visitor to the given writer in [chunked format](self). Such data
+    /// could be read by [`Visitor::load_from_memory`] the same way as the data written by
+    /// [`Visitor::save_binary_to_memory`].
+    pub fn save_chunked_to_memory<W: Write>(
+        &self,
+        mut writer: W,
+        options: ChunkedFormatOptions,
+    ) -> VisitResult {
+        let mut chunk_writer = ChunkWriter {
+            visitor: self,
+            options,
+            strings: Default::default(),
+            chunks: Default::default(),
+        };
+        let mut skeleton = Vec::new();
+        chunk_writer.write_node(self.root, 0, &mut Vec::new(), &mut skeleton)?;
+
+        writer.write_all(Self::CHUNKED_MAGIC.as_bytes())?;
+        writer.write_u8(VERSION)?;
+        writer.write_u8(options.compression.id())?;
+
+        writer.write_u32::<LittleEndian>(chunk_writer.strings.strings.len() as u32)?;
+        for string in chunk_writer.strings.strings.iter() {
+            writer.write_u32::<LittleEndian>(string.len() as u32)?;
+            writer.write_all(string.as_bytes())?;
+        }
+
+        writer.write_u64::<LittleEndian>(skeleton.len() as u64)?;
+        let skeleton = options.compression.compress(skeleton)?;
+        writer.write_u64::<LittleEndian>(skeleton.len() as u64)?;
+        writer.write_all(&skeleton)?;
+
+        let mut chunks = Vec::with_capacity(chunk_writer.chunks.len());
+        for (path, data) in chunk_writer.chunks {
+            let raw_size = data.len();
+            chunks.push((path, raw_size, options.compression.compress(data)?));
+        }
+
+        writer.write_u32::<LittleEndian>(chunks.len() as u32)?;
+        let mut offset = 0u64;
+        for (path, raw_size, data) in chunks.iter() {
+            writer.write_u32::<LittleEndian>(path.len() as u32)?;
+            for index in path {
+                writer.write_u32::<LittleEndian>(*index)?;
+            }
+            writer.write_u64::<LittleEndian>(offset)?;
+            writer.write_u64::<LittleEndian>(*raw_size as u64)?;
+            writer.write_u64::<LittleEndian>(data.len() as u64)?;
+            offset += data.len() as u64;
+        }
+
+        for (_, _, data) in chunks.iter() {
+            writer.write_all(data)?;
+        }
+
+        Ok(())
+    }
+
+    /// Encodes the data of this visitor in [chunked format](self).
+    pub fn save_chunked_to_vec(
+        &self,
+        options: ChunkedFormatOptions,
+    ) -> Result<Vec<u8>, VisitError> {
+        let mut writer = Cursor::new(Vec::new());
+        self.save_chunked_to_memory(&mut writer, options)?;
+        Ok(writer.into_inner())
+    }
+
+    /// Creates a file at the given path and writes the data of this visitor in
+    /// [chunked format](self).
+    pub fn save_chunked<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: ChunkedFormatOptions,
+    ) -> VisitResult {
+        let writer = BufWriter::new(File::create(path)?);
+        self.save_chunked_to_memory(writer, options)
+    }
+
+    /// Returns descriptions of every chunk of the data in [chunked format](self). Chunks are not
+    /// decompressed.
+    pub fn read_chunk_directory(data: &[u8]) -> Result<Vec<ChunkInfo>, VisitError> {
+        Ok(Header::read(data)?.chunks)
+    }
+
+    /// Creates a visitor from the data in [chunked format](self), reading only the chunks that
+    /// pass the given filter. Regions of skipped chunks will not exist in the visitor. It could
+    /// be used to read a part of a large file (for example, some metadata of a scene) without
+    /// decompressing and decoding the rest of the file.
+    pub fn load_chunks_from_memory<F>(data: &[u8], mut filter: F) -> Result<Self, VisitError>
+    where
+        F: FnMut(&ChunkInfo) -> bool,
+    {
+        let header = Header::read(data)?;
+        let mut reader = ChunkReader {
+            data,
+            header: &header,
+            nodes: Pool::new(),
+            filter: &mut filter,
+        };
+        let root = reader.read_node(&mut Cursor::new(header.skeleton.as_slice()))?;
+        Ok(Self {
+            nodes: reader.nodes,
+            rc_map: Default::default(),
+            arc_map: Default::default(),
+            reading: true,
+            current_node: root,
+            root,
+            blackboard: Default::default(),
+            flags: VisitorFlags::NONE,
+        })
+    }
+
+    /// Creates a visitor by reading the file at the given path, reading only the chunks that pass
+    /// the given filter. See [`Visitor::load_chunks_from_memory`] for more info.
+    pub async fn load_chunks<P, F>(path: P, filter: F) -> Result<Self, VisitError>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&ChunkInfo) -> bool,
+    {
+        Self::load_chunks_from_memory(&io::load_file(path).await?, filter)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::visitor::{
+        chunked::{ChunkedFormatOptions, VisitorCompression},
+        Visit, VisitResult, Visitor,
+    };
+
+    #[derive(Default, PartialEq, Debug)]
+    struct Data {
+        name: String,
+        values: Vec<u32>,
+        metadata: u64,
+    }
+
+    impl Visit for Data {
+        fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+            let mut region = visitor.enter_region(name)?;
+            {
+                let mut graph = region.enter_region("Graph")?;
+                self.name.visit("Name", &mut graph)?;
+                self.values.visit("Values", &mut graph)?;
+            }
+            self.metadata.visit("Metadata", &mut region)
+        }
+    }
+
+    fn make_data() -> Data {
+        Data {
+            name: "Test".to_string(),
+            values: (0..100).collect(),
+            metadata: 123,
+        }
+    }
+
+    #[test]
+    fn test_chunked_roundtrip() {
+        for compression in [VisitorCompression::None, VisitorCompression::Lz4] {
+            let mut data = make_data();
+            let mut visitor = Visitor::new();
+            data.visit("Data", &mut visitor).unwrap();
+            let bytes = visitor
+                .save_chunked_to_vec(ChunkedFormatOptions {
+                    compression,
+                    chunk_depth: 3,
+                })
+                .unwrap();
+            assert!(Visitor::is_chunked(&bytes));
+
+            let chunks = Visitor::read_chunk_directory(&bytes).unwrap();
+            assert_eq!(chunks.len(), 2);
+            assert_eq!(chunks[0].path_string(), "Data/Graph/Name");
+
+            let mut visitor = Visitor::load_from_memory(&bytes).unwrap();
+            let mut loaded = Data::default();
+            loaded.visit("Data", &mut visitor).unwrap();
+            assert_eq!(loaded, data);
+        }
+    }
+
+    #[test]
+    fn test_partial_read() {
+        let mut data = make_data();
+        let mut visitor = Visitor::new();
+        data.visit("Data", &mut visitor).unwrap();
+        let bytes = visitor
+            .save_chunked_to_vec(ChunkedFormatOptions::default())
+            .unwrap();
+
+        let mut visitor = Visitor::load_chunks_from_memory(&bytes, |_| false).unwrap();
+        let mut region = visitor.enter_region("Data").unwrap();
+        let mut metadata = 0u64;
+        metadata.visit("Metadata", &mut region).unwrap();
+        assert_eq!(metadata, 123);
+        let mut graph = region.enter_region("Graph").unwrap();
+        let mut values = Vec::<u32>::new();
+        assert!(values.visit("Values", &mut graph).is_err());
+    }
+
+    #[test]
+    fn test_corrupted_data() {
+        let mut data = make_data();
+        let mut visitor = Visitor::new();
+        data.visit("Data", &mut visitor).unwrap();
+        let bytes = visitor
+            .save_chunked_to_vec(ChunkedFormatOptions::default())
+            .unwrap();
+
+        // Truncated data must be rejected without panicking.
+        for len in 0..bytes.len() {
+            assert!(Visitor::load_chunks_from_memory(&bytes[..len], |_| true).is_err());
+        }
+
+        // Huge string count right after the magic, the version and the compression.
+        let mut corrupted = bytes.clone();
+        corrupted[6..10].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Visitor::load_chunks_from_memory(&corrupted, |_| true).is_err());
+
+        // Huge offset and sizes of the last chunk, that precede the chunk data, must not overflow.
+        let chunks = Visitor::read_chunk_directory(&bytes).unwrap();
+        let chunk_data_size = chunks
+            .iter()
+            .map(|chunk| chunk.compressed_size as usize)
+            .sum::<usize>();
+        let last_chunk = bytes.len() - chunk_data_size - 24;
+        let mut corrupted = bytes;
+        corrupted[last_chunk..last_chunk + 24].fill(0xFF);
+        assert!(Visitor::load_chunks_from_memory(&corrupted, |_| true).is_err());
+    }
+}
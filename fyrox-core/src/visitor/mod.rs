@@ -30,6 +30,8 @@
 
 pub use fyrox_core_derive::Visit;
 
+pub mod chunked;
+
 pub mod prelude {
     //! Types to use `#[derive(Visit)]`
     pub use super::{Visit, VisitError, VisitResult, Visitor};
@@ -761,6 +763,13 @@ impl Field {
     }
 
     fn save(field: &Field, file: &mut dyn Write) -> VisitResult {
+        let name = field.name.as_bytes();
+        file.write_u32::<LittleEndian>(name.len() as u32)?;
+        file.write_all(name)?;
+        Self::save_kind(&field.kind, file)
+    }
+
+    fn save_kind(kind: &FieldKind, file: &mut dyn Write) -> VisitResult {
         fn write_vec_n<T, const N: usize>(
             file: &mut dyn Write,
             type_id: u8,
@@ -776,10 +785,7 @@ impl Field {
             Ok(())
         }
 
-        let name = field.name.as_bytes();
-        file.write_u32::<LittleEndian>(name.len() as u32)?;
-        file.write_all(name)?;
-        match &field.kind {
+        match kind {
             FieldKind::U8(data) => {
                 file.write_u8(1)?;
                 file.write_u8(*data)?;
@@ -977,6 +983,16 @@ impl Field {
     }
 
     fn load(file: &mut dyn Read) -> Result<Field, VisitError> {
+        let name_len = file.read_u32::<LittleEndian>()? as usize;
+        let mut raw_name = vec![Default::default(); name_len];
+        file.read_exact(raw_name.as_mut_slice())?;
+        Ok(Field::new(
+            String::from_utf8(raw_name)?.as_str(),
+            Self::load_kind(file)?,
+        ))
+    }
+
+    fn load_kind(file: &mut dyn Read) -> Result<FieldKind, VisitError> {
         fn read_vec_n<T, S, const N: usize>(
             file: &mut dyn Read,
         ) -> Result<Matrix<T, Const<N>, U1, S>, VisitError>
@@ -991,137 +1007,131 @@ impl Field {
             Ok(vec)
         }
 
-        let name_len = file.read_u32::<LittleEndian>()? as usize;
-        let mut raw_name = vec![Default::default(); name_len];
-        file.read_exact(raw_name.as_mut_slice())?;
         let id = file.read_u8()?;
-        Ok(Field::new(
-            String::from_utf8(raw_name)?.as_str(),
-            match id {
-                1 => FieldKind::U8(file.read_u8()?),
-                2 => FieldKind::I8(file.read_i8()?),
-                3 => FieldKind::U16(file.read_u16::<LittleEndian>()?),
-                4 => FieldKind::I16(file.read_i16::<LittleEndian>()?),
-                5 => FieldKind::U32(file.read_u32::<LittleEndian>()?),
-                6 => FieldKind::I32(file.read_i32::<LittleEndian>()?),
-                7 => FieldKind::U64(file.read_u64::<LittleEndian>()?),
-                8 => FieldKind::I64(file.read_i64::<LittleEndian>()?),
-                9 => FieldKind::F32(file.read_f32::<LittleEndian>()?),
-                10 => FieldKind::F64(file.read_f64::<LittleEndian>()?),
-                11 => FieldKind::Vector3F32({
-                    let x = file.read_f32::<LittleEndian>()?;
-                    let y = file.read_f32::<LittleEndian>()?;
-                    let z = file.read_f32::<LittleEndian>()?;
-                    Vector3::new(x, y, z)
-                }),
-                12 => FieldKind::UnitQuaternion({
-                    let x = file.read_f32::<LittleEndian>()?;
-                    let y = file.read_f32::<LittleEndian>()?;
-                    let z = file.read_f32::<LittleEndian>()?;
-                    let w = file.read_f32::<LittleEndian>()?;
-                    UnitQuaternion::new_normalize(Quaternion::new(w, x, y, z))
-                }),
-                13 => FieldKind::Matrix4({
-                    let mut f = [0.0f32; 16];
-                    for n in &mut f {
-                        *n = file.read_f32::<LittleEndian>()?;
-                    }
-                    Matrix4::from_row_slice(&f)
-                }),
-                14 => FieldKind::BinaryBlob({
-                    let len = file.read_u32::<LittleEndian>()? as usize;
-                    let mut vec = vec![Default::default(); len];
-                    file.read_exact(vec.as_mut_slice())?;
-                    vec
-                }),
-                15 => FieldKind::Bool(file.read_u8()? != 0),
-                16 => FieldKind::Matrix3({
-                    let mut f = [0.0f32; 9];
-                    for n in &mut f {
-                        *n = file.read_f32::<LittleEndian>()?;
-                    }
-                    Matrix3::from_row_slice(&f)
-                }),
-                17 => FieldKind::Vector2F32({
-                    let x = file.read_f32::<LittleEndian>()?;
-                    let y = file.read_f32::<LittleEndian>()?;
-                    Vector2::new(x, y)
-                }),
-                18 => FieldKind::Vector4F32({
-                    let x = file.read_f32::<LittleEndian>()?;
-                    let y = file.read_f32::<LittleEndian>()?;
-                    let z = file.read_f32::<LittleEndian>()?;
-                    let w = file.read_f32::<LittleEndian>()?;
-                    Vector4::new(x, y, z, w)
-                }),
-                19 => FieldKind::Uuid({
-                    let mut bytes = uuid::Bytes::default();
-                    file.read_exact(&mut bytes)?;
-                    Uuid::from_bytes(bytes)
-                }),
-                20 => FieldKind::UnitComplex({
-                    let re = file.read_f32::<LittleEndian>()?;
-                    let im = file.read_f32::<LittleEndian>()?;
-                    UnitComplex::from_complex(Complex::new(re, im))
-                }),
-                21 => {
-                    let type_id = file.read_u8()?;
-                    let element_size = file.read_u32::<LittleEndian>()?;
-                    let data_size = file.read_u64::<LittleEndian>()?;
-                    let mut bytes = vec![0; data_size as usize];
-                    file.read_exact(&mut bytes)?;
-                    FieldKind::PodArray {
-                        type_id,
-                        element_size,
-                        bytes,
-                    }
+        Ok(match id {
+            1 => FieldKind::U8(file.read_u8()?),
+            2 => FieldKind::I8(file.read_i8()?),
+            3 => FieldKind::U16(file.read_u16::<LittleEndian>()?),
+            4 => FieldKind::I16(file.read_i16::<LittleEndian>()?),
+            5 => FieldKind::U32(file.read_u32::<LittleEndian>()?),
+            6 => FieldKind::I32(file.read_i32::<LittleEndian>()?),
+            7 => FieldKind::U64(file.read_u64::<LittleEndian>()?),
+            8 => FieldKind::I64(file.read_i64::<LittleEndian>()?),
+            9 => FieldKind::F32(file.read_f32::<LittleEndian>()?),
+            10 => FieldKind::F64(file.read_f64::<LittleEndian>()?),
+            11 => FieldKind::Vector3F32({
+                let x = file.read_f32::<LittleEndian>()?;
+                let y = file.read_f32::<LittleEndian>()?;
+                let z = file.read_f32::<LittleEndian>()?;
+                Vector3::new(x, y, z)
+            }),
+            12 => FieldKind::UnitQuaternion({
+                let x = file.read_f32::<LittleEndian>()?;
+                let y = file.read_f32::<LittleEndian>()?;
+                let z = file.read_f32::<LittleEndian>()?;
+                let w = file.read_f32::<LittleEndian>()?;
+                UnitQuaternion::new_normalize(Quaternion::new(w, x, y, z))
+            }),
+            13 => FieldKind::Matrix4({
+                let mut f = [0.0f32; 16];
+                for n in &mut f {
+                    *n = file.read_f32::<LittleEndian>()?;
                 }
-                22 => FieldKind::Matrix2({
-                    let mut f = [0.0f32; 3];
-                    for n in &mut f {
-                        *n = file.read_f32::<LittleEndian>()?;
-                    }
-                    Matrix2::from_row_slice(&f)
-                }),
-                23 => FieldKind::Vector2F64(read_vec_n(file)?),
-                24 => FieldKind::Vector3F64(read_vec_n(file)?),
-                25 => FieldKind::Vector4F64(read_vec_n(file)?),
-
-                26 => FieldKind::Vector2I8(read_vec_n(file)?),
-                27 => FieldKind::Vector3I8(read_vec_n(file)?),
-                28 => FieldKind::Vector4I8(read_vec_n(file)?),
-
-                29 => FieldKind::Vector2U8(read_vec_n(file)?),
-                30 => FieldKind::Vector3U8(read_vec_n(file)?),
-                31 => FieldKind::Vector4U8(read_vec_n(file)?),
-
-                32 => FieldKind::Vector2I16(read_vec_n(file)?),
-                33 => FieldKind::Vector3I16(read_vec_n(file)?),
-                34 => FieldKind::Vector4I16(read_vec_n(file)?),
-
-                35 => FieldKind::Vector2U16(read_vec_n(file)?),
-                36 => FieldKind::Vector3U16(read_vec_n(file)?),
-                37 => FieldKind::Vector4U16(read_vec_n(file)?),
-
-                38 => FieldKind::Vector2I32(read_vec_n(file)?),
-                39 => FieldKind::Vector3I32(read_vec_n(file)?),
-                40 => FieldKind::Vector4I32(read_vec_n(file)?),
-
-                41 => FieldKind::Vector2U32(read_vec_n(file)?),
-                42 => FieldKind::Vector3U32(read_vec_n(file)?),
-                43 => FieldKind::Vector4U32(read_vec_n(file)?),
-
-                44 => FieldKind::Vector2I64(read_vec_n(file)?),
-                45 => FieldKind::Vector3I64(read_vec_n(file)?),
-                46 => FieldKind::Vector4I64(read_vec_n(file)?),
-
-                47 => FieldKind::Vector2U64(read_vec_n(file)?),
-                48 => FieldKind::Vector3U64(read_vec_n(file)?),
-                49 => FieldKind::Vector4U64(read_vec_n(file)?),
-
-                _ => return Err(VisitError::UnknownFieldType(id)),
-            },
-        ))
+                Matrix4::from_row_slice(&f)
+            }),
+            14 => FieldKind::BinaryBlob({
+                let len = file.read_u32::<LittleEndian>()? as usize;
+                let mut vec = vec![Default::default(); len];
+                file.read_exact(vec.as_mut_slice())?;
+                vec
+            }),
+            15 => FieldKind::Bool(file.read_u8()? != 0),
+            16 => FieldKind::Matrix3({
+                let mut f = [0.0f32; 9];
+                for n in &mut f {
+                    *n = file.read_f32::<LittleEndian>()?;
+                }
+                Matrix3::from_row_slice(&f)
+            }),
+            17 => FieldKind::Vector2F32({
+                let x = file.read_f32::<LittleEndian>()?;
+                let y = file.read_f32::<LittleEndian>()?;
+                Vector2::new(x, y)
+            }),
+            18 => FieldKind::Vector4F32({
+                let x = file.read_f32::<LittleEndian>()?;
+                let y = file.read_f32::<LittleEndian>()?;
+                let z = file.read_f32::<LittleEndian>()?;
+                let w = file.read_f32::<LittleEndian>()?;
+                Vector4::new(x, y, z, w)
+            }),
+            19 => FieldKind::Uuid({
+                let mut bytes = uuid::Bytes::default();
+                file.read_exact(&mut bytes)?;
+                Uuid::from_bytes(bytes)
+            }),
+            20 => FieldKind::UnitComplex({
+                let re = file.read_f32::<LittleEndian>()?;
+                let im = file.read_f32::<LittleEndian>()?;
+                UnitComplex::from_complex(Complex::new(re, im))
+            }),
+            21 => {
+                let type_id = file.read_u8()?;
+                let element_size = file.read_u32::<LittleEndian>()?;
+                let data_size = file.read_u64::<LittleEndian>()?;
+                let mut bytes = vec![0; data_size as usize];
+                file.read_exact(&mut bytes)?;
+                FieldKind::PodArray {
+                    type_id,
+                    element_size,
+                    bytes,
+                }
+            }
+            22 => FieldKind::Matrix2({
+                let mut f = [0.0f32; 3];
+                for n in &mut f {
+                    *n = file.read_f32::<LittleEndian>()?;
+                }
+                Matrix2::from_row_slice(&f)
+            }),
+            23 => FieldKind::Vector2F64(read_vec_n(file)?),
+            24 => FieldKind::Vector3F64(read_vec_n(file)?),
+            25 => FieldKind::Vector4F64(read_vec_n(file)?),
+
+            26 => FieldKind::Vector2I8(read_vec_n(file)?),
+            27 => FieldKind::Vector3I8(read_vec_n(file)?),
+            28 => FieldKind::Vector4I8(read_vec_n(file)?),
+
+            29 => FieldKind::Vector2U8(read_vec_n(file)?),
+            30 => FieldKind::Vector3U8(read_vec_n(file)?),
+            31 => FieldKind::Vector4U8(read_vec_n(file)?),
+
+            32 => FieldKind::Vector2I16(read_vec_n(file)?),
+            33 => FieldKind::Vector3I16(read_vec_n(file)?),
+            34 => FieldKind::Vector4I16(read_vec_n(file)?),
+
+            35 => FieldKind::Vector2U16(read_vec_n(file)?),
+            36 => FieldKind::Vector3U16(read_vec_n(file)?),
+            37 => FieldKind::Vector4U16(read_vec_n(file)?),
+
+            38 => FieldKind::Vector2I32(read_vec_n(file)?),
+            39 => FieldKind::Vector3I32(read_vec_n(file)?),
+            40 => FieldKind::Vector4I32(read_vec_n(file)?),
+
+            41 => FieldKind::Vector2U32(read_vec_n(file)?),
+            42 => FieldKind::Vector3U32(read_vec_n(file)?),
+            43 => FieldKind::Vector4U32(read_vec_n(file)?),
+
+            44 => FieldKind::Vector2I64(read_vec_n(file)?),
+            45 => FieldKind::Vector3I64(read_vec_n(file)?),
+            46 => FieldKind::Vector4I64(read_vec_n(file)?),
+
+            47 => FieldKind::Vector2U64(read_vec_n(file)?),
+            48 => FieldKind::Vector3U64(read_vec_n(file)?),
+            49 => FieldKind::Vector4U64(read_vec_n(file)?),
+
+            _ => return Err(VisitError::UnknownFieldType(id)),
+        })
     }
 
     fn as_string(&self) -> String {
@@ -1231,6 +1241,12 @@ bitflags! {
         /// and therefore write its data. Otherwise, InheritableVariable has the special
         /// property of *not writing itself* when the `MODIFIED` flag is not set.
         const SERIALIZE_EVERYTHING = 1 << 1;
+        /// Tell derived [Visit::visit] implementations to skip fields that cannot be read, instead
+        /// of failing. A field cannot be read if it is missing or its type was changed. Skipped
+        /// fields keep their current values, so an object created with default values gets all
+        /// the matching fields from the serialized data and defaults for the rest. It is used to
+        /// migrate state of objects whose types were changed, for example during hot reloading.
+        const SKIP_INVALID_FIELDS = 1 << 2;
     }
 }
 
@@ -1356,6 +1372,13 @@ impl Visitor {
         self.reading
     }
 
+    /// Returns `true` if the [VisitorFlags::SKIP_INVALID_FIELDS] flag is set. Derived
+    /// [Visit::visit] implementations use it to decide whether an error of a field visit should
+    /// be propagated or not.
+    pub fn skips_invalid_fields(&self) -> bool {
+        self.flags.contains(VisitorFlags::SKIP_INVALID_FIELDS)
+    }
+
     fn current_node(&mut self) -> &mut VisitorNode {
         self.nodes.borrow_mut(self.current_node)
     }
@@ -1542,7 +1565,13 @@ impl Visitor {
     /// assuming that the bytes are in the format that would be produced
     /// by [Visitor::save_binary_to_vec].
     /// Return a [VisitError::NotSupportedFormat] if [Visitor::MAGIC] is not the first bytes read from the slice.
+    ///
+    /// The data in [chunked format](chunked) is also supported and detected automatically.
     pub fn load_from_memory(data: &[u8]) -> Result<Self, VisitError> {
+        if Self::is_chunked(data) {
+            return Self::load_chunks_from_memory(data, |_| true);
+        }
+
         let mut reader = Cursor::new(data);
         let mut magic: [u8; 4] = Default::default();
         reader.read_exact(&mut magic)?;
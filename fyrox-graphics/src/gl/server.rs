@@ -42,10 +42,10 @@ use crate::{
 use glow::HasContext;
 #[cfg(not(target_arch = "wasm32"))]
 use glutin::{
-    config::ConfigTemplateBuilder,
+    config::{Config, ConfigTemplateBuilder},
     context::{
         ContextApi, ContextAttributesBuilder, GlProfile, NotCurrentGlContext,
-        PossiblyCurrentContext, Version,
+        PossiblyCurrentContext, PossiblyCurrentGlContext, Version,
     },
     display::{GetGlDisplay, GlDisplay},
     surface::{GlSurface, Surface, SwapInterval, WindowSurface},
@@ -61,7 +61,7 @@ use std::rc::{Rc, Weak};
 use std::{ffi::CString, num::NonZeroU32};
 use winit::{
     event_loop::EventLoopWindowTarget,
-    window::{Window, WindowBuilder},
+    window::{Window, WindowBuilder, WindowId},
 };
 
 impl ToGlConstant for PolygonFace {
@@ -210,6 +210,11 @@ pub(crate) struct InnerState {
     gl_context: PossiblyCurrentContext,
     #[cfg(not(target_arch = "wasm32"))]
     gl_surface: Surface<WindowSurface>,
+    #[cfg(not(target_arch = "wasm32"))]
+    gl_config: Config,
+    #[cfg(not(target_arch = "wasm32"))]
+    secondary_surfaces: Vec<(WindowId, Surface<WindowSurface>)>,
+    current_window: Option<WindowId>,
 }
 
 impl InnerState {
@@ -217,6 +222,7 @@ impl InnerState {
         gl_kind: GlKind,
         #[cfg(not(target_arch = "wasm32"))] gl_context: PossiblyCurrentContext,
         #[cfg(not(target_arch = "wasm32"))] gl_surface: Surface<WindowSurface>,
+        #[cfg(not(target_arch = "wasm32"))] gl_config: Config,
     ) -> Self {
         Self {
             blend: false,
@@ -253,8 +259,24 @@ impl InnerState {
             gl_context,
             #[cfg(not(target_arch = "wasm32"))]
             gl_surface,
+            #[cfg(not(target_arch = "wasm32"))]
+            gl_config,
+            #[cfg(not(target_arch = "wasm32"))]
+            secondary_surfaces: Default::default(),
+            current_window: None,
         }
     }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn current_surface(&self) -> &Surface<WindowSurface> {
+        self.current_window
+            .and_then(|id| {
+                self.secondary_surfaces
+                    .iter()
+                    .find_map(|(surface_id, surface)| (*surface_id == id).then_some(surface))
+            })
+            .unwrap_or(&self.gl_surface)
+    }
 }
 
 pub struct GlGraphicsServer {
@@ -315,7 +337,7 @@ impl GlGraphicsServer {
         window_builder: WindowBuilder,
     ) -> Result<(Window, SharedGraphicsServer), FrameworkError> {
         #[cfg(not(target_arch = "wasm32"))]
-        let (window, gl_context, gl_surface, mut context, gl_kind) = {
+        let (window, gl_context, gl_surface, gl_config, mut context, gl_kind) = {
             let mut template = ConfigTemplateBuilder::new()
                 .prefer_hardware_accelerated(Some(true))
                 .with_stencil_size(8)
@@ -386,6 +408,7 @@ impl GlGraphicsServer {
                     window,
                     gl_context,
                     gl_surface,
+                    gl_config,
                     glow::Context::from_loader_function(|s| {
                         gl_display.get_proc_address(&CString::new(s).unwrap())
                     }),
@@ -564,6 +587,8 @@ impl GlGraphicsServer {
                 gl_context,
                 #[cfg(not(target_arch = "wasm32"))]
                 gl_surface,
+                #[cfg(not(target_arch = "wasm32"))]
+                gl_config,
             )),
             this: Default::default(),
         };
@@ -1120,7 +1145,7 @@ impl GraphicsServer for GlGraphicsServer {
         #[cfg(not(target_arch = "wasm32"))]
         {
             let state = self.state.borrow();
-            Ok(state.gl_surface.swap_buffers(&state.gl_context)?)
+            Ok(state.current_surface().swap_buffers(&state.gl_context)?)
         }
 
         #[cfg(target_arch = "wasm32")]
@@ -1134,7 +1159,7 @@ impl GraphicsServer for GlGraphicsServer {
         {
             use std::num::NonZeroU32;
             let state = self.state.borrow();
-            state.gl_surface.resize(
+            state.current_surface().resize(
                 &state.gl_context,
                 NonZeroU32::new(new_size.0).unwrap_or_else(|| NonZeroU32::new(1).unwrap()),
                 NonZeroU32::new(new_size.1).unwrap_or_else(|| NonZeroU32::new(1).unwrap()),
@@ -1142,6 +1167,104 @@ impl GraphicsServer for GlGraphicsServer {
         }
     }
 
+    fn create_window(
+        &self,
+        window_target: &EventLoopWindowTarget<()>,
+        window_builder: WindowBuilder,
+    ) -> Result<Window, FrameworkError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut state = self.state.borrow_mut();
+
+            // The window must use the same config as the main one, otherwise the context could not
+            // be made current on its surface.
+            let window =
+                glutin_winit::finalize_window(window_target, window_builder, &state.gl_config)
+                    .map_err(|err| FrameworkError::Custom(err.to_string()))?;
+            let attrs = window.build_surface_attributes(Default::default());
+            let gl_surface = unsafe {
+                state
+                    .gl_config
+                    .display()
+                    .create_window_surface(&state.gl_config, &attrs)?
+            };
+
+            // Secondary windows must not wait for vertical sync, otherwise every window will slow
+            // down the entire frame.
+            state.gl_context.make_current(&gl_surface)?;
+            Log::verify(gl_surface.set_swap_interval(&state.gl_context, SwapInterval::DontWait));
+            state.gl_context.make_current(state.current_surface())?;
+
+            state.secondary_surfaces.push((window.id(), gl_surface));
+
+            Ok(window)
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = (window_target, window_builder);
+            Err(FrameworkError::Custom(
+                "Multiple windows are not supported on WebAssembly!".to_string(),
+            ))
+        }
+    }
+
+    fn destroy_window(&self, window_id: WindowId) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if self.state.borrow().current_window == Some(window_id) {
+                Log::verify(self.set_current_window(None));
+            }
+            self.state
+                .borrow_mut()
+                .secondary_surfaces
+                .retain(|(id, _)| *id != window_id);
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = window_id;
+        }
+    }
+
+    fn set_current_window(&self, window_id: Option<WindowId>) -> Result<(), FrameworkError> {
+        let mut state = self.state.borrow_mut();
+
+        if state.current_window == window_id {
+            return Ok(());
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(window_id) = window_id {
+                if !state
+                    .secondary_surfaces
+                    .iter()
+                    .any(|(id, _)| *id == window_id)
+                {
+                    return Err(FrameworkError::Custom(format!(
+                        "There's no swapchain for window {window_id:?}!"
+                    )));
+                }
+            }
+            state.current_window = window_id;
+            state.gl_context.make_current(state.current_surface())?;
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            state.current_window = window_id;
+        }
+
+        // Default framebuffer is a different object now.
+        state.framebuffer = None;
+        unsafe {
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+
+        Ok(())
+    }
+
     fn capabilities(&self) -> ServerCapabilities {
         let gl = &self.gl;
         unsafe {
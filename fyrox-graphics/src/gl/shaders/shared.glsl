@@ -231,11 +231,98 @@ in samplerCube shadowMap)
     }
 }
 
+// Shadow filtering modes of S_SpotShadowFactor.
+#define SHADOW_FILTER_HARD 0
+#define SHADOW_FILTER_PCF 1
+#define SHADOW_FILTER_PCSS 2
+
+// Compares the depth with the four nearest texels of the shadow map and interpolates the results
+// bilinearly, just like hardware PCF does. Returns 1.0 if the point is lit, 0.0 - if in shadow.
+float Internal_ShadowCompareBilinear(in sampler2D shadowTexture, vec2 texCoord, float depth, float shadowMapInvSize)
+{
+    vec2 texel = texCoord / shadowMapInvSize - 0.5;
+    vec2 base = floor(texel);
+    vec2 fraction = texel - base;
+    vec2 baseTexCoord = (base + 0.5) * shadowMapInvSize;
+    float s00 = depth > texture(shadowTexture, baseTexCoord).r ? 0.0 : 1.0;
+    float s10 = depth > texture(shadowTexture, baseTexCoord + vec2(shadowMapInvSize, 0.0)).r ? 0.0 : 1.0;
+    float s01 = depth > texture(shadowTexture, baseTexCoord + vec2(0.0, shadowMapInvSize)).r ? 0.0 : 1.0;
+    float s11 = depth > texture(shadowTexture, baseTexCoord + vec2(shadowMapInvSize)).r ? 0.0 : 1.0;
+    return mix(mix(s00, s10, fraction.x), mix(s01, s11, fraction.x), fraction.y);
+}
+
+// Averages bilinear comparisons in a square kernel of kernelSize x kernelSize samples, spacing
+// is the distance between the samples in texels.
+float Internal_ShadowPcf(in sampler2D shadowTexture, vec2 texCoord, float depth, float shadowMapInvSize, int kernelSize, float spacing)
+{
+    int size = max(kernelSize, 1);
+    float center = float(size - 1) * 0.5;
+    float accumulator = 0.0;
+    for (int y = 0; y < size; ++y)
+    {
+        for (int x = 0; x < size; ++x)
+        {
+            vec2 offset = (vec2(x, y) - center) * spacing * shadowMapInvSize;
+            accumulator += Internal_ShadowCompareBilinear(shadowTexture, texCoord + offset, depth, shadowMapInvSize);
+        }
+    }
+    return accumulator / float(size * size);
+}
+
+// Percentage-closer soft shadows: the average depth of the shadow casters around the point
+// defines the size of the penumbra, so the shadows are sharp near the contact with their
+// casters and get softer with the distance from them.
+float Internal_ShadowPcss(
+    in sampler2D shadowTexture,
+    vec2 texCoord,
+    float depth,
+    vec3 fragmentPosition,
+    mat4 lightViewProjMatrix,
+    float shadowMapInvSize,
+    int kernelSize,
+    float searchRadius,
+    float penumbraScale)
+{
+    int size = max(kernelSize, 1);
+    float center = float(size - 1) * 0.5;
+    float spacing = size > 1 ? 2.0 * searchRadius / float(size - 1) : 0.0;
+    float blockerDepth = 0.0;
+    float blockerCount = 0.0;
+    for (int y = 0; y < size; ++y)
+    {
+        for (int x = 0; x < size; ++x)
+        {
+            vec2 offset = (vec2(x, y) - center) * spacing * shadowMapInvSize;
+            float sampleDepth = texture(shadowTexture, texCoord + offset).r;
+            if (sampleDepth < depth)
+            {
+                blockerDepth += sampleDepth;
+                blockerCount += 1.0;
+            }
+        }
+    }
+    if (blockerCount == 0.0)
+    {
+        return 1.0;
+    }
+    blockerDepth /= blockerCount;
+    vec3 blockerPosition = S_UnProject(vec3(texCoord, blockerDepth), inverse(lightViewProjMatrix));
+    float penumbra = min(distance(blockerPosition, fragmentPosition) * penumbraScale, searchRadius);
+    float filterSpacing = size > 1 ? 2.0 * penumbra / float(size - 1) : 0.0;
+    return Internal_ShadowPcf(shadowTexture, texCoord, depth, shadowMapInvSize, size, filterSpacing);
+}
+
 // Calculates spot light shadow factor where 1.0 - no shadow, 0.0 - fully in shadow.
 // Why value is inversed? To be able to directly multiply color to shadow factor.
+// shadowFilter is one of SHADOW_FILTER_* values, kernelSize is the amount of samples along each
+// axis, searchRadius (in texels) and penumbraScale (in texels per unit of distance between
+// a shadow caster and a receiver) are used only by PCSS.
 float S_SpotShadowFactor(
     bool shadowsEnabled,
-    bool softShadows,
+    int shadowFilter,
+    int kernelSize,
+    float searchRadius,
+    float penumbraScale,
     float shadowBias,
     vec3 fragmentPosition,
     mat4 lightViewProjMatrix,
@@ -248,27 +335,115 @@ in sampler2D spotShadowTexture)
 
         float biasedLightSpaceFragmentDepth = lightSpacePosition.z - shadowBias;
 
-        if (softShadows)
+        if (shadowFilter == SHADOW_FILTER_PCSS)
         {
-            float accumulator = 0.0;
+            return Internal_ShadowPcss(
+                spotShadowTexture, lightSpacePosition.xy, biasedLightSpaceFragmentDepth, fragmentPosition,
+                lightViewProjMatrix, shadowMapInvSize, kernelSize, searchRadius, penumbraScale);
+        }
+        else if (shadowFilter == SHADOW_FILTER_PCF)
+        {
+            return Internal_ShadowPcf(
+                spotShadowTexture, lightSpacePosition.xy, biasedLightSpaceFragmentDepth,
+                shadowMapInvSize, kernelSize, 1.0);
+        }
+        else
+        {
+            return biasedLightSpaceFragmentDepth > texture(spotShadowTexture, lightSpacePosition.xy).r ? 0.0 : 1.0;
+        }
+    } else {
+        return 1.0; // No shadow
+    }
+}
 
-            for (float y = -0.5; y <= 0.5; y += 0.5)
-            {
-                for (float x = -0.5; x <= 0.5; x += 0.5)
-                {
-                    vec2 fetchTexCoord = lightSpacePosition.xy + vec2(x, y) * shadowMapInvSize;
-                    if (biasedLightSpaceFragmentDepth > texture(spotShadowTexture, fetchTexCoord).r)
-                    {
-                        accumulator += 1.0;
-                    }
-                }
-            }
+// Shadow techniques of S_VarianceShadowFactor, must be in sync with the ShadowTechnique enum.
+#define SHADOW_TECHNIQUE_DEPTH_MAP 0
+#define SHADOW_TECHNIQUE_VARIANCE 1
+#define SHADOW_TECHNIQUE_EXPONENTIAL_VARIANCE 2
+
+// Converts the depth from a shadow map to the linear [0; 1] range. Variance shadow maps require
+// linear depth, because the precision of the depth of perspective projection is not enough for them.
+// depthRange contains the near and far planes of the projection of the light source.
+float S_LinearizeShadowDepth(float depth, bool perspective, vec2 depthRange)
+{
+    if (perspective)
+    {
+        float zNear = depthRange.x;
+        float zFar = depthRange.y;
+        float ndcDepth = depth * 2.0 - 1.0;
+        float viewDepth = 2.0 * zNear * zFar / (zFar + zNear - ndcDepth * (zFar - zNear));
+        return (viewDepth - zNear) / (zFar - zNear);
+    }
+    return depth;
+}
+
+// Calculates the moments of the given linear depth, which are stored in variance shadow maps.
+// Exponential variance shadow maps store the moments of positive and negative exponential warps of
+// the depth, exponents contains the positive and negative exponents.
+vec4 S_ShadowMoments(float depth, int technique, vec2 exponents)
+{
+    if (technique == SHADOW_TECHNIQUE_EXPONENTIAL_VARIANCE)
+    {
+        float warpedDepth = depth * 2.0 - 1.0;
+        float positive = exp(exponents.x * warpedDepth);
+        float negative = -exp(-exponents.y * warpedDepth);
+        return vec4(positive, positive * positive, negative, negative * negative);
+    }
+    return vec4(depth, depth * depth, 0.0, 0.0);
+}
 
-            return clamp(1.0 - accumulator / 9.0, 0.0, 1.0);
+// Calculates the upper bound of the probability of the point to be lit using Chebyshev's inequality.
+// The tail of the distribution is cut off by lightBleedingReduction to hide light bleeding.
+float Internal_ChebyshevUpperBound(vec2 moments, float depth, float minVariance, float lightBleedingReduction)
+{
+    if (depth <= moments.x)
+    {
+        return 1.0;
+    }
+    float variance = max(moments.y - moments.x * moments.x, minVariance);
+    float delta = depth - moments.x;
+    float pMax = variance / (variance + delta * delta);
+    return clamp((pMax - lightBleedingReduction) / (1.0 - lightBleedingReduction), 0.0, 1.0);
+}
+
+// Calculates shadow factor of a variance shadow map where 1.0 - no shadow, 0.0 - fully in shadow.
+// technique is one of SHADOW_TECHNIQUE_* values (except SHADOW_TECHNIQUE_DEPTH_MAP), the moments
+// texture must be filled using S_ShadowMoments with the same technique and exponents.
+float S_VarianceShadowFactor(
+    bool shadowsEnabled,
+    int technique,
+    float lightBleedingReduction,
+    vec2 exponents,
+    float shadowBias,
+    vec3 fragmentPosition,
+    mat4 lightViewProjMatrix,
+    bool perspective,
+    vec2 depthRange,
+    in sampler2D momentsTexture)
+{
+    if (shadowsEnabled)
+    {
+        const float minVariance = 0.00002;
+
+        vec3 lightSpacePosition = S_Project(fragmentPosition, lightViewProjMatrix);
+        float depth = S_LinearizeShadowDepth(lightSpacePosition.z, perspective, depthRange) - shadowBias;
+        vec4 moments = texture(momentsTexture, lightSpacePosition.xy);
+
+        if (technique == SHADOW_TECHNIQUE_EXPONENTIAL_VARIANCE)
+        {
+            vec4 warpedDepth = S_ShadowMoments(depth, technique, exponents);
+            // Scale the minimal variance by the derivative of the warp.
+            float positiveScale = 2.0 * exponents.x * warpedDepth.x;
+            float negativeScale = 2.0 * exponents.y * warpedDepth.z;
+            float positive = Internal_ChebyshevUpperBound(
+                moments.xy, warpedDepth.x, minVariance * positiveScale * positiveScale, lightBleedingReduction);
+            float negative = Internal_ChebyshevUpperBound(
+                moments.zw, warpedDepth.z, minVariance * negativeScale * negativeScale, lightBleedingReduction);
+            return min(positive, negative);
         }
         else
         {
-            return biasedLightSpaceFragmentDepth > texture(spotShadowTexture, lightSpacePosition.xy).r ? 0.0 : 1.0;
+            return Internal_ChebyshevUpperBound(moments.xy, depth, minVariance, lightBleedingReduction);
         }
     } else {
         return 1.0; // No shadow
@@ -126,6 +126,9 @@ const GL_COMPRESSED_RGB_S3TC_DXT1_EXT: u32 = 0x83F0;
 const GL_COMPRESSED_RGBA_S3TC_DXT1_EXT: u32 = 0x83F1;
 const GL_COMPRESSED_RGBA_S3TC_DXT3_EXT: u32 = 0x83F2;
 const GL_COMPRESSED_RGBA_S3TC_DXT5_EXT: u32 = 0x83F3;
+const GL_COMPRESSED_RGB8_ETC2: u32 = 0x9274;
+const GL_COMPRESSED_RGBA8_ETC2_EAC: u32 = 0x9278;
+const GL_COMPRESSED_RGBA_ASTC_4X4_KHR: u32 = 0x93B0;
 
 pub struct PixelDescriptor {
     pub data_type: u32,
@@ -183,6 +186,9 @@ impl PixelKind {
             PixelKind::DXT5RGBA => (0, 0, GL_COMPRESSED_RGBA_S3TC_DXT5_EXT, None),
             PixelKind::R8RGTC => (0, 0, COMPRESSED_RED_RGTC1, None),
             PixelKind::RG8RGTC => (0, 0, COMPRESSED_RG_RGTC2, None),
+            PixelKind::ETC2RGB => (0, 0, GL_COMPRESSED_RGB8_ETC2, None),
+            PixelKind::ETC2RGBA => (0, 0, GL_COMPRESSED_RGBA8_ETC2_EAC, None),
+            PixelKind::ASTC4x4RGBA => (0, 0, GL_COMPRESSED_RGBA_ASTC_4X4_KHR, None),
             PixelKind::RGB32F => (glow::FLOAT, glow::RGB, glow::RGB32F, None),
             PixelKind::RGBA32F => (glow::FLOAT, glow::RGBA, glow::RGBA32F, None),
             PixelKind::RGBA16F => (glow::HALF_FLOAT, glow::RGBA, glow::RGBA16F, None),
@@ -135,6 +135,12 @@ pub enum PixelKind {
     R11G11B10F,
     /// Red, Green, Blue (8-bit) + Alpha (2-bit).
     RGB10A2,
+    /// Compressed ETC2 RGB.
+    ETC2RGB,
+    /// Compressed ETC2 RGBA (with EAC alpha).
+    ETC2RGBA,
+    /// Compressed ASTC RGBA with 4x4 blocks.
+    ASTC4x4RGBA,
 }
 
 /// Element kind of pixel.
@@ -181,7 +187,10 @@ impl PixelKind {
             | Self::DXT3RGBA
             | Self::DXT5RGBA
             | Self::R8RGTC
-            | Self::RG8RGTC => None,
+            | Self::RG8RGTC
+            | Self::ETC2RGB
+            | Self::ETC2RGBA
+            | Self::ASTC4x4RGBA => None,
         }
     }
 
@@ -193,7 +202,10 @@ impl PixelKind {
             | Self::DXT3RGBA
             | Self::DXT5RGBA
             | Self::R8RGTC
-            | Self::RG8RGTC => true,
+            | Self::RG8RGTC
+            | Self::ETC2RGB
+            | Self::ETC2RGBA
+            | Self::ASTC4x4RGBA => true,
             // Explicit match for rest of formats instead of _ will help to not forget
             // to add new entry here.
             Self::RGBA16
@@ -259,6 +271,9 @@ impl PixelKind {
             | Self::DXT5RGBA
             | Self::R8RGTC
             | Self::RG8RGTC
+            | Self::ETC2RGB
+            | Self::ETC2RGBA
+            | Self::ASTC4x4RGBA
             | Self::RGB10A2
             | Self::LA8
             | Self::L8
@@ -306,11 +321,15 @@ pub fn image_3d_size_bytes(
         | PixelKind::D16
         | PixelKind::R16F => 2 * pixel_count,
         PixelKind::R8 | PixelKind::L8 | PixelKind::R8UI => pixel_count,
-        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC => {
+        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC | PixelKind::ETC2RGB => {
             let block_size = 8;
             ceil_div_4(width) * ceil_div_4(height) * ceil_div_4(depth) * block_size
         }
-        PixelKind::DXT3RGBA | PixelKind::DXT5RGBA | PixelKind::RG8RGTC => {
+        PixelKind::DXT3RGBA
+        | PixelKind::DXT5RGBA
+        | PixelKind::RG8RGTC
+        | PixelKind::ETC2RGBA
+        | PixelKind::ASTC4x4RGBA => {
             let block_size = 16;
             ceil_div_4(width) * ceil_div_4(height) * ceil_div_4(depth) * block_size
         }
@@ -345,11 +364,15 @@ pub fn image_2d_size_bytes(pixel_kind: PixelKind, width: usize, height: usize) -
         | PixelKind::D16
         | PixelKind::R16F => 2 * pixel_count,
         PixelKind::R8 | PixelKind::L8 | PixelKind::R8UI => pixel_count,
-        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC => {
+        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC | PixelKind::ETC2RGB => {
             let block_size = 8;
             ceil_div_4(width) * ceil_div_4(height) * block_size
         }
-        PixelKind::DXT3RGBA | PixelKind::DXT5RGBA | PixelKind::RG8RGTC => {
+        PixelKind::DXT3RGBA
+        | PixelKind::DXT5RGBA
+        | PixelKind::RG8RGTC
+        | PixelKind::ETC2RGBA
+        | PixelKind::ASTC4x4RGBA => {
             let block_size = 16;
             ceil_div_4(width) * ceil_div_4(height) * block_size
         }
@@ -383,11 +406,15 @@ pub fn image_1d_size_bytes(pixel_kind: PixelKind, length: usize) -> usize {
         | PixelKind::D16
         | PixelKind::R16F => 2 * length,
         PixelKind::R8 | PixelKind::L8 | PixelKind::R8UI => length,
-        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC => {
+        PixelKind::DXT1RGB | PixelKind::DXT1RGBA | PixelKind::R8RGTC | PixelKind::ETC2RGB => {
             let block_size = 8;
             ceil_div_4(length) * block_size
         }
-        PixelKind::DXT3RGBA | PixelKind::DXT5RGBA | PixelKind::RG8RGTC => {
+        PixelKind::DXT3RGBA
+        | PixelKind::DXT5RGBA
+        | PixelKind::RG8RGTC
+        | PixelKind::ETC2RGBA
+        | PixelKind::ASTC4x4RGBA => {
             let block_size = 16;
             ceil_div_4(length) * block_size
         }
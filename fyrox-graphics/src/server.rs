@@ -43,6 +43,10 @@ use std::{
     cell::RefCell,
     rc::{Rc, Weak},
 };
+use winit::{
+    event_loop::EventLoopWindowTarget,
+    window::{Window, WindowBuilder, WindowId},
+};
 
 /// Graphics server capabilities.
 #[derive(Debug)]
@@ -160,6 +164,25 @@ pub trait GraphicsServer: Downcast {
     /// be called.
     fn set_frame_size(&self, new_size: (u32, u32));
 
+    /// Creates a new window, that shares every GPU resource with the main window of the graphics
+    /// server. The window has its own swapchain, use [`Self::set_current_window`] to render into it.
+    /// The swapchain of the window must be destroyed by [`Self::destroy_window`] before the window
+    /// is dropped.
+    fn create_window(
+        &self,
+        window_target: &EventLoopWindowTarget<()>,
+        window_builder: WindowBuilder,
+    ) -> Result<Window, FrameworkError>;
+
+    /// Destroys the swapchain of a window created by [`Self::create_window`]. If the window is the
+    /// current one, the main window becomes current.
+    fn destroy_window(&self, window_id: WindowId);
+
+    /// Sets the window, which back buffer will be used for rendering. Every subsequent call of
+    /// [`Self::swap_buffers`] and [`Self::set_frame_size`] will affect the swapchain of this window.
+    /// `None` means the main window.
+    fn set_current_window(&self, window_id: Option<WindowId>) -> Result<(), FrameworkError>;
+
     /// Returns current capabilities of the graphics server. See [`ServerCapabilities`] for more info.
     fn capabilities(&self) -> ServerCapabilities;
 
@@ -0,0 +1,499 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Console commands with typed arguments. See [`Command`] docs for more info.
+
+use crate::{
+    console::{cvar::CVarRegistry, ConsoleError},
+    fxhash::FxHashMap,
+    plugin::PluginContext,
+};
+use std::fmt::{Debug, Display, Formatter};
+
+/// Type of command arguments and console variables.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValueKind {
+    /// `true`/`false`, `1`/`0` or `on`/`off`.
+    Bool,
+    /// Signed integer number.
+    Integer,
+    /// Floating point number.
+    Float,
+    /// Any string. Strings with spaces must be enclosed in double quotes.
+    String,
+}
+
+impl Display for ValueKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ValueKind::Bool => "bool",
+            ValueKind::Integer => "integer",
+            ValueKind::Float => "float",
+            ValueKind::String => "string",
+        })
+    }
+}
+
+/// A typed value of a command argument or a console variable.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// Boolean value.
+    Bool(bool),
+    /// Integer value.
+    Integer(i64),
+    /// Floating point value.
+    Float(f64),
+    /// String value.
+    String(String),
+}
+
+impl Value {
+    /// Returns the kind of the value.
+    pub fn kind(&self) -> ValueKind {
+        match self {
+            Value::Bool(_) => ValueKind::Bool,
+            Value::Integer(_) => ValueKind::Integer,
+            Value::Float(_) => ValueKind::Float,
+            Value::String(_) => ValueKind::String,
+        }
+    }
+
+    /// Tries to parse a value of the given kind from a string.
+    pub fn parse(kind: ValueKind, str: &str) -> Option<Self> {
+        match kind {
+            ValueKind::Bool => match str.to_lowercase().as_str() {
+                "true" | "1" | "on" => Some(Value::Bool(true)),
+                "false" | "0" | "off" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            ValueKind::Integer => str.parse().ok().map(Value::Integer),
+            ValueKind::Float => str.parse().ok().map(Value::Float),
+            ValueKind::String => Some(Value::String(str.to_string())),
+        }
+    }
+
+    /// Returns the boolean value, if the value is [`Value::Bool`].
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer value, if the value is [`Value::Integer`].
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the floating point value, if the value is [`Value::Float`] or [`Value::Integer`].
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Float(value) => Some(*value),
+            Value::Integer(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the string value, if the value is [`Value::String`].
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Integer(value) => write!(f, "{value}"),
+            Value::Float(value) => write!(f, "{value}"),
+            Value::String(value) => write!(f, "\"{value}\""),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Integer(value as i64)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Integer(value)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Float(value as f64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+/// Definition of a command argument.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArgumentDefinition {
+    /// Name of the argument. It is used to fetch the argument value from [`Arguments`].
+    pub name: String,
+    /// Kind of the argument.
+    pub kind: ValueKind,
+    /// Optional arguments could be omitted, they must be defined after all the required arguments.
+    pub optional: bool,
+}
+
+/// Parsed values of command arguments.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Arguments {
+    values: Vec<(String, Value)>,
+}
+
+impl Arguments {
+    /// Returns a value of the argument with the given name. Returns `None` if there's no such
+    /// argument, or it is an optional argument that was omitted.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values
+            .iter()
+            .find_map(|(arg_name, value)| (arg_name == name).then_some(value))
+    }
+
+    /// Returns a boolean value of the argument with the given name.
+    pub fn bool(&self, name: &str) -> Option<bool> {
+        self.get(name).and_then(Value::as_bool)
+    }
+
+    /// Returns an integer value of the argument with the given name.
+    pub fn integer(&self, name: &str) -> Option<i64> {
+        self.get(name).and_then(Value::as_integer)
+    }
+
+    /// Returns a floating point value of the argument with the given name.
+    pub fn float(&self, name: &str) -> Option<f64> {
+        self.get(name).and_then(Value::as_float)
+    }
+
+    /// Returns a string value of the argument with the given name.
+    pub fn string(&self, name: &str) -> Option<&str> {
+        self.get(name).and_then(Value::as_str)
+    }
+
+    /// Returns the amount of passed arguments.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if no arguments were passed.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// A context of command execution.
+pub struct CommandContext<'a, 'b, 'c> {
+    /// Plugin context, that provides access to the engine.
+    pub plugin_context: &'a mut PluginContext<'b, 'c>,
+    /// Console variables.
+    pub cvars: &'a mut CVarRegistry,
+}
+
+/// A result of a command. `Ok` contains a text, that will be printed to the console (could be
+/// empty), `Err` contains an error description.
+pub type CommandResult = Result<String, String>;
+
+/// A closure, that is called when a command is executed.
+pub type CommandHandler = Box<dyn FnMut(&Arguments, &mut CommandContext) -> CommandResult>;
+
+/// A console command. It has a name, a description, a set of typed arguments and a handler, that
+/// is called when the command is executed. Arguments are parsed and validated before the handler
+/// is called.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::console::command::{Command, ValueKind};
+/// let command = Command::new("give", "Gives an item to the player.", |args, _ctx| {
+///     let item = args.string("item").unwrap();
+///     let count = args.integer("count").unwrap_or(1);
+///     Ok(format!("Given {count} x {item}"))
+/// })
+/// .with_argument("item", ValueKind::String)
+/// .with_optional_argument("count", ValueKind::Integer);
+///
+/// assert_eq!(command.usage(), "give <item: string> [count: integer]");
+/// ```
+pub struct Command {
+    name: String,
+    description: String,
+    arguments: Vec<ArgumentDefinition>,
+    handler: CommandHandler,
+}
+
+impl Debug for Command {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Command")
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("arguments", &self.arguments)
+            .finish()
+    }
+}
+
+impl Command {
+    /// Creates a new command without arguments.
+    pub fn new<N, D, H>(name: N, description: D, handler: H) -> Self
+    where
+        N: Into<String>,
+        D: Into<String>,
+        H: FnMut(&Arguments, &mut CommandContext) -> CommandResult + 'static,
+    {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            arguments: Default::default(),
+            handler: Box::new(handler),
+        }
+    }
+
+    /// Adds a required argument to the command.
+    pub fn with_argument<N: Into<String>>(mut self, name: N, kind: ValueKind) -> Self {
+        assert!(
+            self.arguments.iter().all(|arg| !arg.optional),
+            "Required arguments must be defined before optional ones!"
+        );
+        self.arguments.push(ArgumentDefinition {
+            name: name.into(),
+            kind,
+            optional: false,
+        });
+        self
+    }
+
+    /// Adds an optional argument to the command.
+    pub fn with_optional_argument<N: Into<String>>(mut self, name: N, kind: ValueKind) -> Self {
+        self.arguments.push(ArgumentDefinition {
+            name: name.into(),
+            kind,
+            optional: true,
+        });
+        self
+    }
+
+    /// Returns the name of the command.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the description of the command.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the definitions of the arguments of the command.
+    pub fn arguments(&self) -> &[ArgumentDefinition] {
+        &self.arguments
+    }
+
+    /// Returns usage string of the command, for example `spawn <name: string> [count: integer]`.
+    pub fn usage(&self) -> String {
+        let mut usage = self.name.clone();
+        for arg in self.arguments.iter() {
+            if arg.optional {
+                usage += &format!(" [{}: {}]", arg.name, arg.kind);
+            } else {
+                usage += &format!(" <{}: {}>", arg.name, arg.kind);
+            }
+        }
+        usage
+    }
+
+    /// Parses the given tokens into a set of arguments.
+    pub fn parse_arguments(&self, tokens: &[String]) -> Result<Arguments, ConsoleError> {
+        let required = self.arguments.iter().filter(|arg| !arg.optional).count();
+        if tokens.len() < required || tokens.len() > self.arguments.len() {
+            return Err(ConsoleError::WrongArgumentCount {
+                usage: self.usage(),
+                count: tokens.len(),
+            });
+        }
+
+        let mut arguments = Arguments::default();
+        for (definition, token) in self.arguments.iter().zip(tokens) {
+            let value = Value::parse(definition.kind, token).ok_or_else(|| {
+                ConsoleError::InvalidArgument {
+                    name: definition.name.clone(),
+                    kind: definition.kind,
+                    value: token.clone(),
+                }
+            })?;
+            arguments.values.push((definition.name.clone(), value));
+        }
+        Ok(arguments)
+    }
+
+    pub(crate) fn execute(
+        &mut self,
+        tokens: &[String],
+        context: &mut CommandContext,
+    ) -> Result<String, ConsoleError> {
+        let arguments = self.parse_arguments(tokens)?;
+        (self.handler)(&arguments, context).map_err(ConsoleError::Failed)
+    }
+}
+
+/// A set of registered commands.
+#[derive(Default, Debug)]
+pub struct CommandRegistry {
+    commands: FxHashMap<String, Command>,
+}
+
+impl CommandRegistry {
+    /// Registers a new command. If there's a command with the same name, it will be replaced and
+    /// returned.
+    pub fn register(&mut self, command: Command) -> Option<Command> {
+        self.commands.insert(command.name.clone(), command)
+    }
+
+    /// Removes a command with the given name.
+    pub fn unregister(&mut self, name: &str) -> Option<Command> {
+        self.commands.remove(name)
+    }
+
+    /// Returns a reference to a command with the given name.
+    pub fn get(&self, name: &str) -> Option<&Command> {
+        self.commands.get(name)
+    }
+
+    pub(crate) fn get_mut(&mut self, name: &str) -> Option<&mut Command> {
+        self.commands.get_mut(name)
+    }
+
+    /// Returns an iterator over all registered commands.
+    pub fn iter(&self) -> impl Iterator<Item = &Command> {
+        self.commands.values()
+    }
+}
+
+/// Splits a command line into tokens. Tokens are separated by whitespaces, tokens with
+/// whitespaces could be enclosed in double quotes.
+pub fn tokenize(line: &str) -> Result<Vec<String>, ConsoleError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+    for ch in line.chars() {
+        match ch {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            ch if ch.is_whitespace() && !in_quotes => {
+                if has_token {
+                    tokens.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            ch => {
+                current.push(ch);
+                has_token = true;
+            }
+        }
+    }
+    if in_quotes {
+        return Err(ConsoleError::UnterminatedQuote);
+    }
+    if has_token {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::console::{
+        command::{tokenize, Command, Value, ValueKind},
+        ConsoleError,
+    };
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize(r#"say "hello world"  42 """#).unwrap(),
+            vec!["say", "hello world", "42", ""]
+        );
+        assert!(matches!(
+            tokenize(r#"say "hello"#),
+            Err(ConsoleError::UnterminatedQuote)
+        ));
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        let command = Command::new("spawn", "", |_, _| Ok(Default::default()))
+            .with_argument("name", ValueKind::String)
+            .with_optional_argument("count", ValueKind::Integer);
+
+        let args = command
+            .parse_arguments(&["enemy".to_string(), "3".to_string()])
+            .unwrap();
+        assert_eq!(args.string("name"), Some("enemy"));
+        assert_eq!(args.integer("count"), Some(3));
+
+        let args = command.parse_arguments(&["enemy".to_string()]).unwrap();
+        assert_eq!(args.integer("count"), None);
+
+        assert!(matches!(
+            command.parse_arguments(&[]),
+            Err(ConsoleError::WrongArgumentCount { count: 0, .. })
+        ));
+        assert!(matches!(
+            command.parse_arguments(&["enemy".to_string(), "three".to_string()]),
+            Err(ConsoleError::InvalidArgument { .. })
+        ));
+        assert_eq!(Value::parse(ValueKind::Bool, "on"), Some(Value::Bool(true)));
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Console variables (cvars) are named typed values, that could be changed at runtime from the
+//! console. See [`CVarRegistry`] docs for more info.
+
+use crate::{
+    console::{
+        command::{Value, ValueKind},
+        ConsoleError,
+    },
+    plugin::PluginContext,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{Debug, Formatter},
+};
+
+/// A closure, that is called when a value of a console variable is changed from the console. It
+/// could be used to apply the new value to the engine or the game. If the closure returns an
+/// error, the change is reverted.
+pub type CVarCallback = Box<dyn FnMut(&Value, &mut PluginContext) -> Result<(), String>>;
+
+/// A console variable.
+pub struct CVar {
+    value: Value,
+    default: Value,
+    description: String,
+    on_change: Option<CVarCallback>,
+}
+
+impl Debug for CVar {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CVar")
+            .field("value", &self.value)
+            .field("default", &self.default)
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+impl CVar {
+    /// Returns current value of the variable.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Returns default value of the variable.
+    pub fn default_value(&self) -> &Value {
+        &self.default
+    }
+
+    /// Returns the description of the variable.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// A set of console variables. Every variable has a fixed type, which is defined by its default
+/// value. Game code could read the variables at any time, while the console could change them
+/// using `set <name> <value>` command.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::console::cvar::CVarRegistry;
+/// let mut cvars = CVarRegistry::default();
+/// cvars.register("player.god_mode", false, "Makes the player invulnerable.");
+/// cvars.register("player.speed", 3.5, "Movement speed of the player.");
+///
+/// if cvars.bool("player.god_mode") == Some(false) {
+///     // Apply damage.
+/// }
+/// ```
+#[derive(Default, Debug)]
+pub struct CVarRegistry {
+    vars: BTreeMap<String, CVar>,
+}
+
+impl CVarRegistry {
+    /// Registers a new variable. If there's a variable with the same name, it will be replaced.
+    pub fn register<N, V, D>(&mut self, name: N, default: V, description: D)
+    where
+        N: Into<String>,
+        V: Into<Value>,
+        D: Into<String>,
+    {
+        let default = default.into();
+        self.vars.insert(
+            name.into(),
+            CVar {
+                value: default.clone(),
+                default,
+                description: description.into(),
+                on_change: None,
+            },
+        );
+    }
+
+    /// Registers a new variable with a callback, that will be called when the value is changed
+    /// from the console (see [`CVarCallback`] docs for more info).
+    pub fn register_with_callback<N, V, D, C>(
+        &mut self,
+        name: N,
+        default: V,
+        description: D,
+        on_change: C,
+    ) where
+        N: Into<String>,
+        V: Into<Value>,
+        D: Into<String>,
+        C: FnMut(&Value, &mut PluginContext) -> Result<(), String> + 'static,
+    {
+        let name = name.into();
+        self.register(name.clone(), default, description);
+        if let Some(cvar) = self.vars.get_mut(&name) {
+            cvar.on_change = Some(Box::new(on_change));
+        }
+    }
+
+    /// Removes a variable with the given name.
+    pub fn unregister(&mut self, name: &str) -> Option<CVar> {
+        self.vars.remove(name)
+    }
+
+    /// Returns a reference to a variable with the given name.
+    pub fn get(&self, name: &str) -> Option<&CVar> {
+        self.vars.get(name)
+    }
+
+    /// Returns a value of the variable with the given name.
+    pub fn value(&self, name: &str) -> Option<&Value> {
+        self.get(name).map(CVar::value)
+    }
+
+    /// Returns a boolean value of the variable with the given name.
+    pub fn bool(&self, name: &str) -> Option<bool> {
+        self.value(name).and_then(Value::as_bool)
+    }
+
+    /// Returns an integer value of the variable with the given name.
+    pub fn integer(&self, name: &str) -> Option<i64> {
+        self.value(name).and_then(Value::as_integer)
+    }
+
+    /// Returns a floating point value of the variable with the given name.
+    pub fn float(&self, name: &str) -> Option<f64> {
+        self.value(name).and_then(Value::as_float)
+    }
+
+    /// Returns a string value of the variable with the given name.
+    pub fn string(&self, name: &str) -> Option<&str> {
+        self.value(name).and_then(Value::as_str)
+    }
+
+    /// Sets a new value of the variable without calling its callback. It could be used by game
+    /// code to keep the variable in sync with the state it reflects.
+    pub fn set_silent<V: Into<Value>>(&mut self, name: &str, value: V) -> Result<(), ConsoleError> {
+        let cvar = self
+            .vars
+            .get_mut(name)
+            .ok_or_else(|| ConsoleError::UnknownVariable(name.to_string()))?;
+        let value = coerce(value.into(), cvar.default.kind()).ok_or_else(|| {
+            ConsoleError::InvalidArgument {
+                name: name.to_string(),
+                kind: cvar.default.kind(),
+                value: Default::default(),
+            }
+        })?;
+        cvar.value = value;
+        Ok(())
+    }
+
+    /// Parses a new value of the variable from the string and sets it. The callback of the
+    /// variable is called, and if it fails, the previous value is restored.
+    pub fn set_from_str(
+        &mut self,
+        name: &str,
+        str: &str,
+        context: &mut PluginContext,
+    ) -> Result<(), ConsoleError> {
+        let cvar = self
+            .vars
+            .get_mut(name)
+            .ok_or_else(|| ConsoleError::UnknownVariable(name.to_string()))?;
+        let kind = cvar.default.kind();
+        let value = Value::parse(kind, str).ok_or_else(|| ConsoleError::InvalidArgument {
+            name: name.to_string(),
+            kind,
+            value: str.to_string(),
+        })?;
+        let previous = std::mem::replace(&mut cvar.value, value);
+        if let Some(on_change) = cvar.on_change.as_mut() {
+            if let Err(err) = on_change(&cvar.value, context) {
+                cvar.value = previous;
+                return Err(ConsoleError::Failed(err));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns an iterator over all the variables sorted by their names.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &CVar)> {
+        self.vars.iter().map(|(name, cvar)| (name.as_str(), cvar))
+    }
+}
+
+fn coerce(value: Value, kind: ValueKind) -> Option<Value> {
+    match (value, kind) {
+        (Value::Integer(value), ValueKind::Float) => Some(Value::Float(value as f64)),
+        (value, kind) if value.kind() == kind => Some(value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::console::cvar::CVarRegistry;
+
+    #[test]
+    fn test_cvar_types() {
+        let mut cvars = CVarRegistry::default();
+        cvars.register("speed", 2.0, "");
+        cvars.register("god_mode", false, "");
+
+        assert_eq!(cvars.float("speed"), Some(2.0));
+        assert!(cvars.set_silent("speed", 3).is_ok());
+        assert_eq!(cvars.float("speed"), Some(3.0));
+        assert!(cvars.set_silent("god_mode", 1).is_err());
+        assert!(cvars.set_silent("unknown", 1).is_err());
+        assert_eq!(cvars.bool("god_mode"), Some(false));
+    }
+}
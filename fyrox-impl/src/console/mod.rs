@@ -0,0 +1,787 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! In-game developer console. It is an overlay with a command line, that could be used to run
+//! commands (see [`command`] module) and to change console variables (see [`cvar`] module) at
+//! runtime. See [`ConsolePlugin`] docs for more info.
+
+pub mod command;
+pub mod cvar;
+
+use crate::{
+    console::{
+        command::{tokenize, Command, CommandContext, CommandRegistry, ValueKind},
+        cvar::CVarRegistry,
+    },
+    core::{
+        color::Color,
+        log::{Log, LogMessage, MessageKind},
+        parking_lot::Mutex,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::update_loop::FramePacing,
+    event::{ElementState, Event, WindowEvent},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        grid::{Column, GridBuilder, Row},
+        message::{KeyCode as UiKeyCode, MessageDirection, UiMessage},
+        scroll_viewer::{ScrollViewerBuilder, ScrollViewerMessage},
+        stack_panel::StackPanelBuilder,
+        style::{resource::StyleResourceExt, Style},
+        text::{TextBuilder, TextMessage},
+        text_box::{TextBoxBuilder, TextCommitMode},
+        widget::{WidgetBuilder, WidgetMessage},
+        Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    keyboard::{KeyCode, PhysicalKey},
+    plugin::{Plugin, PluginContext},
+};
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+    sync::{
+        mpsc::{self, Receiver},
+        Arc,
+    },
+};
+
+/// An error, that may occur during command execution.
+#[derive(Debug)]
+pub enum ConsoleError {
+    /// There's no command with the given name.
+    UnknownCommand(String),
+    /// There's no console variable with the given name.
+    UnknownVariable(String),
+    /// Amount of the arguments does not match the command definition.
+    WrongArgumentCount {
+        /// Usage string of the command.
+        usage: String,
+        /// Actual amount of the arguments.
+        count: usize,
+    },
+    /// An argument cannot be parsed.
+    InvalidArgument {
+        /// Name of the argument.
+        name: String,
+        /// Expected kind of the argument.
+        kind: ValueKind,
+        /// Actual value of the argument.
+        value: String,
+    },
+    /// A command line has a quote without a closing pair.
+    UnterminatedQuote,
+    /// A command has failed.
+    Failed(String),
+}
+
+impl Display for ConsoleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsoleError::UnknownCommand(name) => write!(f, "Unknown command {name}."),
+            ConsoleError::UnknownVariable(name) => write!(f, "Unknown variable {name}."),
+            ConsoleError::WrongArgumentCount { usage, count } => {
+                write!(f, "Wrong amount of arguments ({count}). Usage: {usage}")
+            }
+            ConsoleError::InvalidArgument { name, kind, value } => {
+                write!(f, "Invalid value \"{value}\" of {name}, expected {kind}.")
+            }
+            ConsoleError::UnterminatedQuote => write!(f, "Unterminated quote."),
+            ConsoleError::Failed(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ConsoleError {}
+
+#[derive(Debug)]
+struct ConsoleUi {
+    root: Handle<UiNode>,
+    scroll_viewer: Handle<UiNode>,
+    lines_panel: Handle<UiNode>,
+    input: Handle<UiNode>,
+    lines: VecDeque<Handle<UiNode>>,
+}
+
+impl ConsoleUi {
+    fn new(ui: &mut UserInterface, visible: bool) -> Self {
+        let ctx = &mut ui.build_ctx();
+        let lines_panel = StackPanelBuilder::new(WidgetBuilder::new()).build(ctx);
+        let scroll_viewer = ScrollViewerBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_content(lines_panel)
+        .build(ctx);
+        // Filter out the symbols of the default toggle key.
+        let input = TextBoxBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_height(22.0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_text_commit_mode(TextCommitMode::Immediate)
+        .with_vertical_text_alignment(VerticalAlignment::Center)
+        .with_filter(Arc::new(Mutex::new(|c: char| c != '`' && c != '~')))
+        .build(ctx);
+        let grid = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child(scroll_viewer)
+                .with_child(input),
+        )
+        .add_row(Row::stretch())
+        .add_row(Row::auto())
+        .add_column(Column::stretch())
+        .build(ctx);
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(visible)
+                .with_height(ConsolePlugin::HEIGHT)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_background(Brush::Solid(Color::from_rgba(20, 20, 20, 220)).into())
+                .with_child(grid),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            scroll_viewer,
+            lines_panel,
+            input,
+            lines: Default::default(),
+        }
+    }
+
+    fn add_line(&mut self, ui: &mut UserInterface, kind: MessageKind, text: &str, max: usize) {
+        let ctx = &mut ui.build_ctx();
+        let foreground = match kind {
+            MessageKind::Information => ctx.style.property(Style::BRUSH_TEXT),
+            MessageKind::Warning => ctx.style.property(Style::BRUSH_WARNING),
+            MessageKind::Error => ctx.style.property(Style::BRUSH_ERROR),
+        };
+        let line = TextBuilder::new(WidgetBuilder::new().with_foreground(foreground))
+            .with_text(text.trim_end())
+            .build(ctx);
+        ui.send_message(WidgetMessage::link(
+            line,
+            MessageDirection::ToWidget,
+            self.lines_panel,
+        ));
+        self.lines.push_back(line);
+
+        while self.lines.len() > max {
+            if let Some(line) = self.lines.pop_front() {
+                ui.send_message(WidgetMessage::remove(line, MessageDirection::ToWidget));
+            }
+        }
+
+        ui.send_message(ScrollViewerMessage::scroll_to_end(
+            self.scroll_viewer,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    fn clear(&mut self, ui: &UserInterface) {
+        for line in self.lines.drain(..) {
+            ui.send_message(WidgetMessage::remove(line, MessageDirection::ToWidget));
+        }
+    }
+
+    fn set_input(&self, ui: &UserInterface, text: &str) {
+        ui.send_message(TextMessage::text(
+            self.input,
+            MessageDirection::ToWidget,
+            text.to_string(),
+        ));
+    }
+
+    fn set_visibility(&self, ui: &UserInterface, visible: bool) {
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+        if visible {
+            ui.send_message(WidgetMessage::topmost(
+                self.root,
+                MessageDirection::ToWidget,
+            ));
+            ui.send_message(WidgetMessage::focus(self.input, MessageDirection::ToWidget));
+        }
+    }
+}
+
+/// In-game developer console. It is an overlay at the top of the screen, that is shown and hidden
+/// by a toggle key (<kbd>`</kbd> by default). It shows log messages and the output of commands.
+/// The console supports:
+///
+/// - Commands with typed arguments (see [`Command`]). They could be registered via
+///   [`Self::commands`].
+/// - Console variables (see [`cvar::CVarRegistry`]), that could be read by game code and changed
+///   from the console via `set <name> <value>`. Some engine settings are exposed as variables
+///   too (`engine.*` and `log.*`).
+/// - Autocompletion of command and variable names by <kbd>Tab</kbd> and command history by
+///   <kbd>Up</kbd>/<kbd>Down</kbd>.
+///
+/// Built-in commands are: `help [command]`, `clear`, `echo`, `cvars [prefix]`, `get <name>`,
+/// `set <name> <value>` and `reset <name>`.
+///
+/// The console is a usual plugin, so it could be used in shipped builds as well, for example
+/// only in debug builds:
+///
+/// ```rust,no_run
+/// # use fyrox_impl::{
+/// #     console::{command::{Command, ValueKind}, ConsolePlugin},
+/// #     engine::executor::Executor,
+/// # };
+/// let mut executor = Executor::new();
+/// if cfg!(debug_assertions) {
+///     let mut console = ConsolePlugin::new();
+///     console.cvars.register("player.god_mode", false, "Makes the player invulnerable.");
+///     console.commands.register(
+///         Command::new("spawn", "Spawns an enemy.", |args, _ctx| {
+///             Ok(format!("Spawned {}", args.string("name").unwrap()))
+///         })
+///         .with_argument("name", ValueKind::String),
+///     );
+///     executor.add_plugin(console);
+/// }
+/// executor.run()
+/// ```
+///
+/// Scripts could access the console (to read the variables, for example) via
+/// `ctx.plugins.get::<ConsolePlugin>()`.
+#[derive(Debug, Visit, Reflect)]
+pub struct ConsolePlugin {
+    /// A set of registered commands.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub commands: CommandRegistry,
+    /// A set of registered console variables.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub cvars: CVarRegistry,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    toggle_key: KeyCode,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    is_open: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    max_lines: usize,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    ui: Option<ConsoleUi>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    input: String,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    history: Vec<String>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    history_position: Option<usize>,
+    // Lines that were printed before the UI was created.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pending_lines: Vec<(MessageKind, String)>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    log_receiver: Option<Receiver<LogMessage>>,
+}
+
+impl Default for ConsolePlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConsolePlugin {
+    /// Height of the console overlay (in units).
+    pub const HEIGHT: f32 = 300.0;
+
+    /// Creates a new console with the built-in commands and variables.
+    pub fn new() -> Self {
+        let mut console = Self {
+            commands: Default::default(),
+            cvars: Default::default(),
+            toggle_key: KeyCode::Backquote,
+            is_open: false,
+            max_lines: 256,
+            ui: None,
+            input: Default::default(),
+            history: Default::default(),
+            history_position: None,
+            pending_lines: Default::default(),
+            log_receiver: None,
+        };
+        console.register_built_in_commands();
+        console.register_engine_cvars();
+        console
+    }
+
+    /// Sets a key, that opens and closes the console.
+    pub fn set_toggle_key(&mut self, key: KeyCode) {
+        self.toggle_key = key;
+    }
+
+    /// Returns a key, that opens and closes the console.
+    pub fn toggle_key(&self) -> KeyCode {
+        self.toggle_key
+    }
+
+    /// Sets the maximum amount of lines, that will be kept in the console. Default is 256.
+    pub fn set_max_lines(&mut self, max_lines: usize) {
+        self.max_lines = max_lines.max(1);
+    }
+
+    /// Returns `true` if the console is open, `false` - otherwise.
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    /// Opens or closes the console.
+    pub fn set_open(&mut self, open: bool, context: &PluginContext) {
+        self.is_open = open;
+        if let Some(console_ui) = self.ui.as_ref() {
+            console_ui.set_visibility(context.user_interfaces.first(), open);
+        }
+    }
+
+    /// Prints a line of text to the console.
+    pub fn print(&mut self, kind: MessageKind, text: &str, context: &mut PluginContext) {
+        match self.ui.as_mut() {
+            Some(console_ui) => console_ui.add_line(
+                context.user_interfaces.first_mut(),
+                kind,
+                text,
+                self.max_lines,
+            ),
+            None => self.pending_lines.push((kind, text.to_string())),
+        }
+    }
+
+    /// Executes a command line. The line itself and the result of the command are printed to the
+    /// console.
+    pub fn execute(&mut self, line: &str, context: &mut PluginContext) {
+        self.print(MessageKind::Information, &format!("> {line}"), context);
+        match self.execute_silent(line, context) {
+            Ok(output) => {
+                if !output.is_empty() {
+                    self.print(MessageKind::Information, &output, context);
+                }
+            }
+            Err(err) => self.print(MessageKind::Error, &err.to_string(), context),
+        }
+    }
+
+    /// Executes a command line and returns the output of the command.
+    pub fn execute_silent(
+        &mut self,
+        line: &str,
+        context: &mut PluginContext,
+    ) -> Result<String, ConsoleError> {
+        let tokens = tokenize(line)?;
+        let Some((name, args)) = tokens.split_first() else {
+            return Ok(Default::default());
+        };
+
+        match name.as_str() {
+            "help" => Ok(self.help(args.first().map(|s| s.as_str()))),
+            "clear" => {
+                if let Some(console_ui) = self.ui.as_mut() {
+                    console_ui.clear(context.user_interfaces.first());
+                }
+                self.pending_lines.clear();
+                Ok(Default::default())
+            }
+            _ => {
+                let command = self
+                    .commands
+                    .get_mut(name)
+                    .ok_or_else(|| ConsoleError::UnknownCommand(name.clone()))?;
+                command.execute(
+                    args,
+                    &mut CommandContext {
+                        plugin_context: context,
+                        cvars: &mut self.cvars,
+                    },
+                )
+            }
+        }
+    }
+
+    /// Returns a list of possible completions for the given input. Command names are completed
+    /// for the first word, variable names are completed for the first argument of `get`, `set`
+    /// and `reset` commands. The list is sorted and every completion is a full command line.
+    pub fn complete(&self, input: &str) -> Vec<String> {
+        let mut completions = match input.split_once(char::is_whitespace) {
+            None => ["help", "clear"]
+                .into_iter()
+                .chain(self.commands.iter().map(|command| command.name()))
+                .filter(|name| name.starts_with(input))
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>(),
+            Some((command @ ("get" | "set" | "reset"), prefix)) => {
+                let prefix = prefix.trim_start();
+                if prefix.contains(char::is_whitespace) {
+                    Vec::new()
+                } else {
+                    self.cvars
+                        .iter()
+                        .filter(|(name, _)| name.starts_with(prefix))
+                        .map(|(name, _)| format!("{command} {name}"))
+                        .collect()
+                }
+            }
+            Some(_) => Vec::new(),
+        };
+        completions.sort();
+        completions
+    }
+
+    fn help(&self, command: Option<&str>) -> String {
+        if let Some(name) = command {
+            return match self.commands.get(name) {
+                Some(command) => format!("{}\n  {}", command.usage(), command.description()),
+                None => ConsoleError::UnknownCommand(name.to_string()).to_string(),
+            };
+        }
+        let mut commands = self.commands.iter().collect::<Vec<_>>();
+        commands.sort_by(|a, b| a.name().cmp(b.name()));
+        let mut help = "help [command] - Shows the list of commands or help for a command.\n\
+            clear - Clears the console."
+            .to_string();
+        for command in commands {
+            help += &format!("\n{} - {}", command.usage(), command.description());
+        }
+        help
+    }
+
+    fn register_built_in_commands(&mut self) {
+        self.commands.register(
+            Command::new("echo", "Prints the text.", |args, _| {
+                Ok(args.string("text").unwrap_or_default().to_string())
+            })
+            .with_argument("text", ValueKind::String),
+        );
+        self.commands.register(
+            Command::new("cvars", "Shows the list of variables.", |args, ctx| {
+                let prefix = args.string("prefix").unwrap_or_default();
+                Ok(ctx
+                    .cvars
+                    .iter()
+                    .filter(|(name, _)| name.starts_with(prefix))
+                    .map(|(name, cvar)| {
+                        format!("{name} = {} - {}", cvar.value(), cvar.description())
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            })
+            .with_optional_argument("prefix", ValueKind::String),
+        );
+        self.commands.register(
+            Command::new("get", "Shows the value of a variable.", |args, ctx| {
+                let name = args.string("name").unwrap_or_default();
+                ctx.cvars
+                    .value(name)
+                    .map(|value| format!("{name} = {value}"))
+                    .ok_or_else(|| ConsoleError::UnknownVariable(name.to_string()).to_string())
+            })
+            .with_argument("name", ValueKind::String),
+        );
+        self.commands.register(
+            Command::new("set", "Sets the value of a variable.", |args, ctx| {
+                let name = args.string("name").unwrap_or_default();
+                let value = args.string("value").unwrap_or_default();
+                ctx.cvars
+                    .set_from_str(name, value, ctx.plugin_context)
+                    .map_err(|err| err.to_string())?;
+                Ok(Default::default())
+            })
+            .with_argument("name", ValueKind::String)
+            .with_argument("value", ValueKind::String),
+        );
+        self.commands.register(
+            Command::new(
+                "reset",
+                "Sets the default value of a variable.",
+                |args, ctx| {
+                    let name = args.string("name").unwrap_or_default();
+                    let default = ctx
+                        .cvars
+                        .get(name)
+                        .map(|cvar| match cvar.default_value() {
+                            // Strings are displayed with quotes.
+                            command::Value::String(string) => string.clone(),
+                            value => value.to_string(),
+                        })
+                        .ok_or_else(|| {
+                            ConsoleError::UnknownVariable(name.to_string()).to_string()
+                        })?;
+                    ctx.cvars
+                        .set_from_str(name, &default, ctx.plugin_context)
+                        .map_err(|err| err.to_string())?;
+                    Ok(Default::default())
+                },
+            )
+            .with_argument("name", ValueKind::String),
+        );
+    }
+
+    fn register_engine_cvars(&mut self) {
+        let defaults = crate::engine::update_loop::UpdateLoopSettings::default();
+        self.cvars.register_with_callback(
+            "engine.update_rate",
+            defaults.update_rate,
+            "Fixed rate of game logic updates (in updates per second).",
+            |value, ctx| {
+                let rate = value.as_float().unwrap_or_default() as f32;
+                if rate <= 0.0 {
+                    return Err("Update rate must be greater than zero!".to_string());
+                }
+                ctx.update_loop.update_rate = rate;
+                Ok(())
+            },
+        );
+        self.cvars.register_with_callback(
+            "engine.max_catch_up_steps",
+            defaults.max_catch_up_steps as i64,
+            "Maximum amount of fixed updates, that could be performed in a row.",
+            |value, ctx| {
+                ctx.update_loop.max_catch_up_steps =
+                    value.as_integer().unwrap_or_default().max(1) as usize;
+                Ok(())
+            },
+        );
+        self.cvars.register_with_callback(
+            "engine.max_frame_rate",
+            defaults.max_frame_rate.unwrap_or_default(),
+            "Maximum amount of rendered frames per second, zero means no limit.",
+            |value, ctx| {
+                let rate = value.as_float().unwrap_or_default() as f32;
+                ctx.update_loop.max_frame_rate = (rate > 0.0).then_some(rate);
+                Ok(())
+            },
+        );
+        self.cvars.register_with_callback(
+            "engine.busy_wait",
+            defaults.frame_pacing == FramePacing::BusyWait,
+            "Spin instead of sleeping while waiting for the next update or frame.",
+            |value, ctx| {
+                ctx.update_loop.frame_pacing = if value.as_bool().unwrap_or_default() {
+                    FramePacing::BusyWait
+                } else {
+                    FramePacing::Sleep
+                };
+                Ok(())
+            },
+        );
+        self.cvars.register_with_callback(
+            "log.verbosity",
+            "info",
+            "Minimal level of log messages: info, warning or error.",
+            |value, _| {
+                Log::set_verbosity(match value.as_str().unwrap_or_default() {
+                    "info" => MessageKind::Information,
+                    "warning" => MessageKind::Warning,
+                    "error" => MessageKind::Error,
+                    _ => return Err("Expected info, warning or error.".to_string()),
+                });
+                Ok(())
+            },
+        );
+    }
+
+    fn sync_engine_cvars(&mut self, context: &PluginContext) {
+        // The variables could be removed by the user, so the errors are ignored.
+        let settings = *context.update_loop;
+        let _ = self
+            .cvars
+            .set_silent("engine.update_rate", settings.update_rate);
+        let _ = self.cvars.set_silent(
+            "engine.max_catch_up_steps",
+            settings.max_catch_up_steps as i64,
+        );
+        let _ = self.cvars.set_silent(
+            "engine.max_frame_rate",
+            settings.max_frame_rate.unwrap_or_default(),
+        );
+        let _ = self.cvars.set_silent(
+            "engine.busy_wait",
+            settings.frame_pacing == FramePacing::BusyWait,
+        );
+    }
+
+    fn complete_input(&mut self, context: &mut PluginContext) {
+        let completions = self.complete(&self.input);
+        let completed = match completions.as_slice() {
+            [] => return,
+            [single] => format!("{single} "),
+            [first, rest @ ..] => {
+                // Complete the common prefix and show all the options.
+                let mut common = first.clone();
+                for completion in rest {
+                    while !completion.starts_with(&common) {
+                        common.pop();
+                    }
+                }
+                self.print(MessageKind::Information, &completions.join("  "), context);
+                common
+            }
+        };
+        self.set_input(&completed, context);
+    }
+
+    fn navigate_history(&mut self, backward: bool, context: &mut PluginContext) {
+        if self.history.is_empty() {
+            return;
+        }
+        let position = match (self.history_position, backward) {
+            (None, true) => Some(self.history.len() - 1),
+            (None, false) => None,
+            (Some(position), true) => Some(position.saturating_sub(1)),
+            (Some(position), false) => (position + 1 < self.history.len()).then_some(position + 1),
+        };
+        self.history_position = position;
+        let text = position
+            .map(|position| self.history[position].clone())
+            .unwrap_or_default();
+        self.set_input(&text, context);
+    }
+
+    fn set_input(&mut self, text: &str, context: &PluginContext) {
+        self.input = text.to_string();
+        if let Some(console_ui) = self.ui.as_ref() {
+            console_ui.set_input(context.user_interfaces.first(), text);
+        }
+    }
+
+    fn submit_input(&mut self, context: &mut PluginContext) {
+        let line = std::mem::take(&mut self.input);
+        self.set_input("", context);
+        self.history_position = None;
+        if line.trim().is_empty() {
+            return;
+        }
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+        self.execute(&line, context);
+    }
+}
+
+impl Plugin for ConsolePlugin {
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        self.sync_engine_cvars(&context);
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.ui.is_none() {
+            let mut console_ui = ConsoleUi::new(context.user_interfaces.first_mut(), self.is_open);
+            for (kind, text) in self.pending_lines.drain(..) {
+                console_ui.add_line(
+                    context.user_interfaces.first_mut(),
+                    kind,
+                    &text,
+                    self.max_lines,
+                );
+            }
+            self.ui = Some(console_ui);
+
+            let (sender, receiver) = mpsc::channel();
+            Log::add_listener(sender);
+            self.log_receiver = Some(receiver);
+        }
+
+        let messages = self
+            .log_receiver
+            .as_ref()
+            .map(|receiver| receiver.try_iter().collect::<Vec<_>>())
+            .unwrap_or_default();
+        for message in messages {
+            self.print(message.kind, &message.to_text(), context);
+        }
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, context: PluginContext) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event, .. },
+            ..
+        } = event
+        {
+            if event.state == ElementState::Pressed
+                && !event.repeat
+                && event.physical_key == PhysicalKey::Code(self.toggle_key)
+            {
+                self.set_open(!self.is_open, &context);
+            }
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        let Some(input) = self.ui.as_ref().map(|console_ui| console_ui.input) else {
+            return;
+        };
+
+        if message.destination() != input || message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(TextMessage::Text(text)) = message.data() {
+            self.input.clone_from(text);
+        } else if let Some(WidgetMessage::KeyDown(key)) = message.data() {
+            match key {
+                UiKeyCode::Enter | UiKeyCode::NumpadEnter => self.submit_input(context),
+                UiKeyCode::Tab => self.complete_input(context),
+                UiKeyCode::ArrowUp => self.navigate_history(true, context),
+                UiKeyCode::ArrowDown => self.navigate_history(false, context),
+                _ => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::console::{command::Command, ConsolePlugin};
+
+    #[test]
+    fn test_completion() {
+        let mut console = ConsolePlugin::new();
+        console
+            .commands
+            .register(Command::new("spawn", "", |_, _| Ok(Default::default())));
+        console.cvars.register("player.speed", 1.0, "");
+        console.cvars.register("player.god_mode", false, "");
+
+        assert_eq!(console.complete("spa"), vec!["spawn"]);
+        assert_eq!(console.complete("c"), vec!["clear", "cvars"]);
+        assert_eq!(
+            console.complete("set player."),
+            vec!["set player.god_mode", "set player.speed"]
+        );
+        assert!(console.complete("spawn x").is_empty());
+    }
+}
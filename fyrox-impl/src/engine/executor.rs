@@ -21,18 +21,19 @@
 //! Executor is a small wrapper that manages plugins and scripts for your game.
 
 use crate::{
-    asset::manager::ResourceManager,
+    asset::{manager::ResourceManager, pack::PackKey},
     core::{
         instant::Instant,
         log::{Log, MessageKind},
         task::TaskPool,
     },
     engine::{
+        update_loop::{FramePacing, UpdateLoopSettings},
         Engine, EngineInitParams, GraphicsContext, GraphicsContextParams, SerializationContext,
     },
     event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
-    plugin::Plugin,
+    plugin::{LifecycleEvent, Plugin},
     utils::translate_event,
     window::WindowAttributes,
 };
@@ -40,25 +41,132 @@ use clap::Parser;
 use fyrox_ui::constructor::new_widget_constructor_container;
 use std::{
     ops::{Deref, DerefMut},
+    path::PathBuf,
     sync::Arc,
+    time::Duration,
 };
 
 #[derive(Parser, Debug, Default)]
 #[clap(author, version, about, long_about = None)]
+#[cfg_attr(any(target_arch = "wasm32", target_os = "android"), allow(dead_code))]
 struct Args {
     #[clap(short, long, default_value = None)]
     override_scene: Option<String>,
+    /// Bakes the assets of the game and exits without starting the game. See
+    /// `fyrox::utils::bake` docs for more info.
+    #[clap(long)]
+    bake: bool,
+    /// Path of the asset pack, that will be generated by baking.
+    #[clap(long, default_value = None)]
+    bake_pack: Option<PathBuf>,
+    /// Compression of the generated asset pack: none, lz4 or zstd.
+    #[clap(long, default_value = "lz4")]
+    bake_pack_compression: String,
+    /// A passphrase that will be used to encrypt the generated asset pack.
+    #[clap(long, default_value = None)]
+    bake_pack_passphrase: Option<String>,
+    /// Path of a scene, that should have its lightmap baked. Could be specified multiple times.
+    #[clap(long)]
+    bake_lightmap: Vec<PathBuf>,
+    /// Disables validation of resource references during baking.
+    #[clap(long)]
+    bake_skip_validation: bool,
+}
+
+/// Returns `true` if the executor is started with `--bake` command line argument.
+fn is_bake_requested() -> bool {
+    #[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+    {
+        Args::try_parse().is_ok_and(|args| args.bake)
+    }
+    #[cfg(any(target_arch = "wasm32", target_os = "android"))]
+    {
+        false
+    }
+}
+
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+fn bake_assets(engine: &Engine, args: &Args) -> bool {
+    use crate::{
+        asset::pack::PackCompression,
+        core::futures::executor::block_on,
+        utils::bake::{bake, BakeOptions, LightmapBakeOptions, PackBakeOptions},
+    };
+
+    let compression = match args.bake_pack_compression.as_str() {
+        "none" => PackCompression::None,
+        "lz4" => PackCompression::Lz4,
+        "zstd" => PackCompression::Zstd,
+        other => {
+            Log::err(format!("Unknown asset pack compression {other}!"));
+            return false;
+        }
+    };
+
+    let options = BakeOptions {
+        validate_references: !args.bake_skip_validation,
+        lightmaps: (!args.bake_lightmap.is_empty()).then(|| LightmapBakeOptions {
+            scenes: args.bake_lightmap.clone(),
+            ..Default::default()
+        }),
+        pack: args.bake_pack.clone().map(|path| PackBakeOptions {
+            path,
+            compression,
+            key: args
+                .bake_pack_passphrase
+                .as_deref()
+                .map(PackKey::from_passphrase),
+        }),
+        ..Default::default()
+    };
+
+    match block_on(bake(
+        &engine.resource_manager,
+        engine.serialization_context.clone(),
+        &options,
+    )) {
+        Ok(report) => {
+            Log::info(report.to_string());
+            report.is_ok()
+        }
+        Err(err) => {
+            Log::err(err.to_string());
+            false
+        }
+    }
+}
+
+/// Mounts the asset pack from the working directory (if any) to the resource manager.
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+pub(crate) fn mount_default_asset_pack(resource_manager: &ResourceManager, key: Option<PackKey>) {
+    use crate::asset::pack::{ResourcePack, DEFAULT_PACK_PATH};
+    if std::path::Path::new(DEFAULT_PACK_PATH).exists() {
+        match ResourcePack::open(DEFAULT_PACK_PATH, key) {
+            Ok(pack) => {
+                Log::info(format!(
+                    "Asset pack {DEFAULT_PACK_PATH} was mounted. Files: {}.",
+                    pack.len()
+                ));
+                resource_manager.mount_pack(pack);
+            }
+            Err(err) => {
+                Log::err(format!(
+                    "Unable to mount asset pack {DEFAULT_PACK_PATH}. Reason: {err}"
+                ));
+            }
+        }
+    }
 }
 
 /// Executor is a small wrapper that manages plugins and scripts for your game.
 pub struct Executor {
-    event_loop: EventLoop<()>,
+    /// `None` only if the executor is created by [`Executor::new`] for baking, see [`Executor::run`].
+    event_loop: Option<EventLoop<()>>,
     engine: Engine,
-    desired_update_rate: f32,
     headless: bool,
-    throttle_threshold: f32,
-    throttle_frame_interval: usize,
     resource_hot_reloading: bool,
+    #[cfg_attr(any(target_arch = "wasm32", target_os = "android"), allow(dead_code))]
+    asset_pack_key: Option<PackKey>,
 }
 
 impl Deref for Executor {
@@ -83,7 +191,7 @@ impl Default for Executor {
 
 impl Executor {
     /// Default update rate in frames per second.
-    pub const DEFAULT_UPDATE_RATE: f32 = 60.0;
+    pub const DEFAULT_UPDATE_RATE: f32 = UpdateLoopSettings::DEFAULT_UPDATE_RATE;
     /// Default time step (in seconds).
     pub const DEFAULT_TIME_STEP: f32 = 1.0 / Self::DEFAULT_UPDATE_RATE;
 
@@ -92,6 +200,13 @@ impl Executor {
     pub fn from_params(
         event_loop: EventLoop<()>,
         graphics_context_params: GraphicsContextParams,
+    ) -> Self {
+        Self::with_event_loop(Some(event_loop), graphics_context_params)
+    }
+
+    fn with_event_loop(
+        event_loop: Option<EventLoop<()>>,
+        graphics_context_params: GraphicsContextParams,
     ) -> Self {
         let serialization_context = Arc::new(SerializationContext::new());
         let task_pool = Arc::new(TaskPool::new());
@@ -101,17 +216,16 @@ impl Executor {
             serialization_context,
             task_pool,
             widget_constructors: Arc::new(new_widget_constructor_container()),
+            update_loop: Default::default(),
         })
         .unwrap();
 
         Self {
             event_loop,
             engine,
-            desired_update_rate: Self::DEFAULT_UPDATE_RATE,
             headless: false,
-            throttle_threshold: 2.0 * Self::DEFAULT_TIME_STEP,
-            throttle_frame_interval: 5,
             resource_hot_reloading: true,
+            asset_pack_key: None,
         }
     }
 
@@ -122,8 +236,12 @@ impl Executor {
         window_attributes.resizable = true;
         window_attributes.title = "Fyrox Game".to_string();
 
-        Self::from_params(
-            EventLoop::new().unwrap(),
+        // Baking does not need a window, so the event loop is not created in this case. This way
+        // the assets could be baked on machines without a display, such as CI build agents.
+        let event_loop = (!is_bake_requested()).then(|| EventLoop::new().unwrap());
+
+        Self::with_event_loop(
+            event_loop,
             GraphicsContextParams {
                 window_attributes,
                 vsync: true,
@@ -149,9 +267,17 @@ impl Executor {
         self.resource_hot_reloading
     }
 
+    /// Sets a key, that will be used to open an encrypted asset pack. See [`Executor::run`] docs
+    /// for more info about asset packs.
+    pub fn set_asset_pack_key(&mut self, key: Option<PackKey>) {
+        self.asset_pack_key = key;
+    }
+
     /// Defines whether the executor should initialize graphics context or not. Headless mode could
     /// be useful for game servers, where you don't need to have a window, renderer, sound, etc.
-    /// By default, headless mode is off.
+    /// By default, headless mode is off. Keep in mind, that the executor still needs an event loop
+    /// (and thus a windowing system), use [`super::headless::HeadlessExecutor`] to run the game
+    /// without it.
     pub fn set_headless(&mut self, headless: bool) {
         self.headless = headless;
     }
@@ -173,12 +299,12 @@ impl Executor {
     /// ground and some other nasty things. Throttle threshold should be at reasonably high levels
     /// (usually 2x-3x of the fixed time step).
     pub fn set_throttle_threshold(&mut self, threshold: f32) {
-        self.throttle_threshold = threshold.max(0.001);
+        self.engine.update_loop.throttle_threshold = threshold.max(0.001);
     }
 
     /// Returns current throttle threshold. See [`Self::set_throttle_threshold`] docs for more info.
     pub fn throttle_threshold(&self) -> f32 {
-        self.throttle_threshold
+        self.engine.update_loop.throttle_threshold
     }
 
     /// Sets the amount of frames (consecutive) that will be allowed to have lag spikes and the engine
@@ -187,23 +313,57 @@ impl Executor {
     /// Variable time step could be bad for physics, which may result in objects falling through the
     /// ground, etc. Default is 5 frames.
     pub fn set_throttle_frame_interval(&mut self, interval: usize) {
-        self.throttle_frame_interval = interval;
+        self.engine.update_loop.throttle_frame_interval = interval;
     }
 
     /// Returns current throttle frame interval. See [`Self::set_throttle_frame_interval`] docs for
     /// more info.
     pub fn throttle_frame_interval(&self) -> usize {
-        self.throttle_frame_interval
+        self.engine.update_loop.throttle_frame_interval
     }
 
     /// Sets the desired update rate in frames per second.
     pub fn set_desired_update_rate(&mut self, update_rate: f32) {
-        self.desired_update_rate = update_rate.abs();
+        self.engine.update_loop.update_rate = update_rate.abs();
     }
 
     /// Returns desired update rate in frames per second.
     pub fn desired_update_rate(&self) -> f32 {
-        self.desired_update_rate
+        self.engine.update_loop.update_rate
+    }
+
+    /// Sets the maximum amount of fixed updates, that could be performed in a row when the game
+    /// logic lags behind the real time. Default is 5.
+    pub fn set_max_catch_up_steps(&mut self, steps: usize) {
+        self.engine.update_loop.max_catch_up_steps = steps;
+    }
+
+    /// Returns the maximum amount of fixed updates, that could be performed in a row. See
+    /// [`Self::set_max_catch_up_steps`] docs for more info.
+    pub fn max_catch_up_steps(&self) -> usize {
+        self.engine.update_loop.max_catch_up_steps
+    }
+
+    /// Sets the maximum amount of rendered frames per second. `None` means that the frame rate is
+    /// limited by vertical synchronization only. Default is `None`.
+    pub fn set_max_frame_rate(&mut self, frame_rate: Option<f32>) {
+        self.engine.update_loop.max_frame_rate = frame_rate;
+    }
+
+    /// Returns the maximum amount of rendered frames per second.
+    pub fn max_frame_rate(&self) -> Option<f32> {
+        self.engine.update_loop.max_frame_rate
+    }
+
+    /// Sets the way the executor waits for the next update or frame. See [`FramePacing`] docs for
+    /// more info.
+    pub fn set_frame_pacing(&mut self, frame_pacing: FramePacing) {
+        self.engine.update_loop.frame_pacing = frame_pacing;
+    }
+
+    /// Returns the way the executor waits for the next update or frame.
+    pub fn frame_pacing(&self) -> FramePacing {
+        self.engine.update_loop.frame_pacing
     }
 
     /// Adds new plugin to the executor, the plugin will be enabled only on [`Executor::run`].
@@ -214,19 +374,36 @@ impl Executor {
         self.engine.add_plugin(plugin)
     }
 
-    /// Runs the executor - starts your game.
+    /// Runs the executor - starts your game. If there's an asset pack in the working directory
+    /// (see [`crate::asset::pack::DEFAULT_PACK_PATH`]), it will be mounted to the resource manager
+    /// before starting the game. Encrypted packs require a key, see [`Executor::set_asset_pack_key`].
+    ///
+    /// If the executor is started with `--bake` command line argument, it bakes the assets of the
+    /// game (see [`crate::utils::bake`]) and exits with non-zero exit code if there were any errors.
+    /// Run the executor with `--help` to see all the baking options.
     pub fn run(self) {
         let mut engine = self.engine;
-        let event_loop = self.event_loop;
+
+        let args = Args::try_parse().unwrap_or_default();
+
+        #[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+        if args.bake {
+            let is_ok = bake_assets(&engine, &args);
+            std::process::exit(if is_ok { 0 } else { 1 });
+        }
+
+        #[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+        mount_default_asset_pack(&engine.resource_manager, self.asset_pack_key);
+
+        let event_loop = self
+            .event_loop
+            .expect("The event loop is missing only when the assets are baked!");
         let headless = self.headless;
-        let throttle_threshold = self.throttle_threshold;
-        let throttle_frame_interval = self.throttle_frame_interval;
 
         if self.resource_hot_reloading {
             #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
             {
                 use crate::core::watcher::FileSystemWatcher;
-                use std::time::Duration;
                 match FileSystemWatcher::new(".", Duration::from_secs(1)) {
                     Ok(watcher) => {
                         engine.resource_manager.state().set_watcher(Some(watcher));
@@ -238,19 +415,22 @@ impl Executor {
             }
         }
 
-        let args = Args::try_parse().unwrap_or_default();
-
         engine.enable_plugins(args.override_scene.as_deref(), true, Some(&event_loop));
 
         let mut previous = Instant::now();
-        let fixed_time_step = 1.0 / self.desired_update_rate;
+        let mut last_frame_request = previous;
         let mut lag = 0.0;
         let mut frame_counter = 0usize;
         let mut last_throttle_frame_number = 0usize;
+        let mut paused = false;
 
         run_executor(event_loop, move |event, window_target| {
             window_target.set_control_flow(ControlFlow::Wait);
 
+            // Update loop settings could be changed at runtime, so fetch them on every event.
+            let settings = engine.update_loop;
+            let fixed_time_step = settings.fixed_time_step();
+
             engine.handle_os_event_by_plugins(&event, fixed_time_step, window_target, &mut lag);
 
             let scenes = engine
@@ -268,39 +448,88 @@ impl Executor {
             }
 
             match event {
-                Event::Resumed if !headless => {
-                    engine
-                        .initialize_graphics_context(window_target)
-                        .expect("Unable to initialize graphics context!");
+                Event::Resumed => {
+                    // Some platforms could send this event without the previous suspension, so
+                    // the graphics context could be initialized already.
+                    if !headless && !engine.graphics_context.is_initialized() {
+                        engine
+                            .initialize_graphics_context(window_target)
+                            .expect("Unable to initialize graphics context!");
+
+                        engine.handle_graphics_context_created_by_plugins(
+                            fixed_time_step,
+                            window_target,
+                            &mut lag,
+                        );
+                    }
+
+                    if paused {
+                        paused = false;
+
+                        // Do not try to catch up with the time spent in background.
+                        previous = Instant::now();
+                        lag = 0.0;
+
+                        engine.handle_lifecycle_event_by_plugins(
+                            LifecycleEvent::Resumed,
+                            fixed_time_step,
+                            window_target,
+                            &mut lag,
+                        );
+                    }
+                }
+                Event::Suspended => {
+                    paused = true;
 
-                    engine.handle_graphics_context_created_by_plugins(
+                    // Notify the plugins first, so they could save their state while the graphics
+                    // context is still alive.
+                    engine.handle_lifecycle_event_by_plugins(
+                        LifecycleEvent::Paused,
                         fixed_time_step,
                         window_target,
                         &mut lag,
                     );
+
+                    if !headless && engine.graphics_context.is_initialized() {
+                        engine
+                            .destroy_graphics_context()
+                            .expect("Unable to destroy graphics context!");
+
+                        engine.handle_graphics_context_destroyed_by_plugins(
+                            fixed_time_step,
+                            window_target,
+                            &mut lag,
+                        );
+                    }
                 }
-                Event::Suspended if !headless => {
-                    engine
-                        .destroy_graphics_context()
-                        .expect("Unable to destroy graphics context!");
+                Event::MemoryWarning => {
+                    engine.handle_low_memory();
 
-                    engine.handle_graphics_context_destroyed_by_plugins(
+                    engine.handle_lifecycle_event_by_plugins(
+                        LifecycleEvent::LowMemory,
                         fixed_time_step,
                         window_target,
                         &mut lag,
                     );
                 }
+                // The game is in background, there's nothing to update or render.
+                Event::AboutToWait if paused => (),
                 Event::AboutToWait => {
                     let elapsed = previous.elapsed();
                     previous = Instant::now();
-                    lag += elapsed.as_secs_f32();
+                    // Drop the lag that exceeds the maximum amount of catch-up steps.
+                    lag = settings.clamp_lag(lag + elapsed.as_secs_f32());
+
+                    // Scripts and plugins receive the alpha that is predicted for the frame that
+                    // will be rendered after the updates.
+                    engine.set_interpolation_alpha(settings.interpolation_alpha(lag));
 
                     // Update rate stabilization loop.
                     while lag >= fixed_time_step {
                         let time_step;
-                        if lag >= throttle_threshold
+                        if lag >= settings.throttle_threshold
                             && (frame_counter - last_throttle_frame_number
-                                >= throttle_frame_interval)
+                                >= settings.throttle_frame_interval)
                         {
                             // Modify the delta time to let the game internals to fast-forward the
                             // logic by the current lag.
@@ -326,11 +555,54 @@ impl Executor {
                         }
                     }
 
+                    engine.set_interpolation_alpha(settings.interpolation_alpha(lag));
+
+                    let now = Instant::now();
+                    let frame_interval = settings.frame_interval();
+                    let mut redraw_requested = false;
                     if let GraphicsContext::Initialized(ref ctx) = engine.graphics_context {
-                        ctx.window.request_redraw();
+                        if frame_interval
+                            .map_or(true, |interval| now - last_frame_request >= interval)
+                        {
+                            ctx.window.request_redraw();
+                            last_frame_request = now;
+                            redraw_requested = true;
+                        }
+                    }
+
+                    match settings.frame_pacing {
+                        FramePacing::Sleep if !redraw_requested => {
+                            // Wake up either on the next logic update or on the next frame,
+                            // whichever comes first.
+                            let mut wait = Duration::from_secs_f32(
+                                (fixed_time_step - lag).clamp(0.0, fixed_time_step),
+                            );
+                            if let (Some(interval), GraphicsContext::Initialized(_)) =
+                                (frame_interval, &engine.graphics_context)
+                            {
+                                wait = wait.min(
+                                    (last_frame_request + interval).saturating_duration_since(now),
+                                );
+                            }
+
+                            #[cfg(not(target_arch = "wasm32"))]
+                            window_target.set_control_flow(ControlFlow::WaitUntil(now + wait));
+
+                            #[cfg(target_arch = "wasm32")]
+                            {
+                                let _ = wait;
+                                window_target.set_control_flow(ControlFlow::Poll);
+                            }
+                        }
+                        FramePacing::Sleep => (),
+                        FramePacing::BusyWait => window_target.set_control_flow(ControlFlow::Poll),
                     }
                 }
-                Event::WindowEvent { event, .. } => {
+                Event::WindowEvent { window_id, event } => {
+                    if engine.handle_secondary_window_event(window_id, &event) {
+                        return;
+                    }
+
                     match event {
                         WindowEvent::CloseRequested => window_target.exit(),
                         WindowEvent::Resized(size) => {
@@ -350,6 +622,12 @@ impl Executor {
 
                             engine.render().unwrap();
 
+                            engine.handle_after_rendering_by_plugins(
+                                fixed_time_step,
+                                window_target,
+                                &mut lag,
+                            );
+
                             frame_counter += 1;
                         }
                         _ => (),
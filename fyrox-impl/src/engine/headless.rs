@@ -0,0 +1,345 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Headless executor is a game loop without any window, graphics context or audio device. See
+//! [`HeadlessExecutor`] docs for more info.
+
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+use crate::engine::executor::mount_default_asset_pack;
+use crate::{
+    asset::{manager::ResourceManager, pack::PackKey},
+    core::{instant::Instant, log::Log, task::TaskPool},
+    engine::{update_loop::UpdateLoopSettings, Engine, EngineInitParams, SerializationContext},
+    plugin::Plugin,
+};
+use fyrox_ui::constructor::new_widget_constructor_container;
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// A handle, that could be used to stop a running [`HeadlessExecutor`]. It could be cloned and
+/// sent to other threads (for example, to a console input handler) or stored in a plugin.
+#[derive(Clone, Default, Debug)]
+pub struct ExitHandle(Arc<AtomicBool>);
+
+impl ExitHandle {
+    /// Requests the executor to stop. The executor will stop after the current update tick.
+    pub fn exit(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the exit was requested.
+    pub fn is_exit_requested(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Headless executor runs plugins, scenes (including physics) and scripts without any window,
+/// renderer or audio device. Unlike [`super::executor::Executor`] in headless mode, it does not
+/// need an event loop and thus a windowing system, so it could be used for dedicated game servers
+/// and headless simulation in CI.
+///
+/// Plugins receive no graphics context ([`crate::engine::GraphicsContext::Uninitialized`]) and no
+/// window target, so they should not try to create windows or use the renderer. User interfaces
+/// are not updated.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// # use fyrox_impl::engine::headless::HeadlessExecutor;
+/// let mut executor = HeadlessExecutor::new();
+/// executor.set_desired_update_rate(30.0);
+/// // executor.add_plugin(GameServer::default());
+/// executor.run();
+/// ```
+///
+/// To run a simulation for a fixed amount of ticks as fast as possible, disable the real-time mode
+/// and set the frame limit:
+///
+/// ```rust,no_run
+/// # use fyrox_impl::engine::headless::HeadlessExecutor;
+/// let mut executor = HeadlessExecutor::new();
+/// executor.set_real_time(false);
+/// executor.set_frame_limit(Some(600));
+/// let engine = executor.run();
+/// // Check the state of the scenes here.
+/// ```
+pub struct HeadlessExecutor {
+    engine: Engine,
+    real_time: bool,
+    frame_limit: Option<usize>,
+    exit_handle: ExitHandle,
+    #[cfg_attr(any(target_arch = "wasm32", target_os = "android"), allow(dead_code))]
+    asset_pack_key: Option<PackKey>,
+}
+
+impl Deref for HeadlessExecutor {
+    type Target = Engine;
+
+    fn deref(&self) -> &Self::Target {
+        &self.engine
+    }
+}
+
+impl DerefMut for HeadlessExecutor {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.engine
+    }
+}
+
+impl Default for HeadlessExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeadlessExecutor {
+    /// Default update rate in frames per second.
+    pub const DEFAULT_UPDATE_RATE: f32 = UpdateLoopSettings::DEFAULT_UPDATE_RATE;
+
+    /// Creates new headless executor.
+    pub fn new() -> Self {
+        let serialization_context = Arc::new(SerializationContext::new());
+        let task_pool = Arc::new(TaskPool::new());
+        let engine = Engine::new(EngineInitParams {
+            graphics_context_params: Default::default(),
+            resource_manager: ResourceManager::new(task_pool.clone()),
+            serialization_context,
+            task_pool,
+            widget_constructors: Arc::new(new_widget_constructor_container()),
+            update_loop: Default::default(),
+        })
+        .unwrap();
+
+        Self {
+            engine,
+            real_time: true,
+            frame_limit: None,
+            exit_handle: Default::default(),
+            asset_pack_key: None,
+        }
+    }
+
+    /// Sets the desired update rate in frames per second.
+    pub fn set_desired_update_rate(&mut self, update_rate: f32) {
+        self.engine.update_loop.update_rate = update_rate.abs().max(0.001);
+    }
+
+    /// Returns desired update rate in frames per second.
+    pub fn desired_update_rate(&self) -> f32 {
+        self.engine.update_loop.update_rate
+    }
+
+    /// Defines whether the executor should wait between update ticks to match the desired update
+    /// rate or not. When the real-time mode is off, the game logic is updated as fast as possible
+    /// with the fixed time step, which is useful for simulations in CI. Enabled by default. The way
+    /// the executor waits in the real-time mode is defined by [`UpdateLoopSettings::frame_pacing`].
+    pub fn set_real_time(&mut self, real_time: bool) {
+        self.real_time = real_time;
+    }
+
+    /// Returns `true` if the real-time mode is on. See [`Self::set_real_time`] docs for more info.
+    pub fn is_real_time(&self) -> bool {
+        self.real_time
+    }
+
+    /// Sets the maximum amount of update ticks, after which the executor will stop. `None` means
+    /// that the executor will run until [`ExitHandle::exit`] is called. Default is `None`.
+    pub fn set_frame_limit(&mut self, frame_limit: Option<usize>) {
+        self.frame_limit = frame_limit;
+    }
+
+    /// Returns current frame limit. See [`Self::set_frame_limit`] docs for more info.
+    pub fn frame_limit(&self) -> Option<usize> {
+        self.frame_limit
+    }
+
+    /// Sets the maximum amount of update ticks, that could be performed in a row when the game
+    /// logic lags behind the real time. The rest of the lag is dropped, which prevents the server
+    /// from hanging up when the logic takes too much time. Default is 5 ticks.
+    pub fn set_max_catch_up_steps(&mut self, steps: usize) {
+        self.engine.update_loop.max_catch_up_steps = steps.max(1);
+    }
+
+    /// Returns the maximum amount of catch-up ticks. See [`Self::set_max_catch_up_steps`] docs for
+    /// more info.
+    pub fn max_catch_up_steps(&self) -> usize {
+        self.engine.update_loop.max_catch_up_steps
+    }
+
+    /// Returns a handle, that could be used to stop the executor.
+    pub fn exit_handle(&self) -> ExitHandle {
+        self.exit_handle.clone()
+    }
+
+    /// Sets a key, that will be used to open an encrypted asset pack. See
+    /// [`super::executor::Executor::run`] docs for more info about asset packs.
+    pub fn set_asset_pack_key(&mut self, key: Option<PackKey>) {
+        self.asset_pack_key = key;
+    }
+
+    /// Adds new plugin to the executor, the plugin will be enabled only on [`Self::run`].
+    pub fn add_plugin<P>(&mut self, plugin: P)
+    where
+        P: Plugin + 'static,
+    {
+        self.engine.add_plugin(plugin)
+    }
+
+    /// Runs the executor until [`ExitHandle::exit`] is called or the frame limit is reached. Returns
+    /// the engine, so its state could be examined after the run. If there's an asset pack in the
+    /// working directory (see [`crate::asset::pack::DEFAULT_PACK_PATH`]), it will be mounted to the
+    /// resource manager before start.
+    pub fn run(self) -> Engine {
+        let mut engine = self.engine;
+
+        #[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+        mount_default_asset_pack(&engine.resource_manager, self.asset_pack_key);
+
+        engine.enable_plugins(None, true, None);
+
+        let mut frame_counter = 0usize;
+        let mut lag = 0.0;
+        let mut previous = Instant::now();
+
+        let is_running = |frame_counter: usize| {
+            !self.exit_handle.is_exit_requested()
+                && self.frame_limit.map_or(true, |limit| frame_counter < limit)
+        };
+
+        Log::info("Headless executor has started.");
+
+        while is_running(frame_counter) {
+            // Update loop settings could be changed by plugins at runtime.
+            let settings = engine.update_loop;
+            let fixed_time_step = settings.fixed_time_step();
+
+            if self.real_time {
+                let elapsed = previous.elapsed();
+                previous = Instant::now();
+                lag = settings.clamp_lag(lag + elapsed.as_secs_f32());
+
+                if lag < fixed_time_step {
+                    settings
+                        .frame_pacing
+                        .wait_until(previous + Duration::from_secs_f32(fixed_time_step - lag));
+                    continue;
+                }
+            } else {
+                lag = fixed_time_step;
+            }
+
+            engine.set_interpolation_alpha(settings.interpolation_alpha(lag));
+
+            while lag >= fixed_time_step && is_running(frame_counter) {
+                let scenes = engine
+                    .scenes
+                    .pair_iter()
+                    .map(|(s, _)| s)
+                    .collect::<Vec<_>>();
+                for scene_handle in scenes {
+                    if !engine.has_scripted_scene(scene_handle) {
+                        engine.register_scripted_scene(scene_handle);
+                    }
+                }
+
+                engine.update_headless(fixed_time_step, &mut lag, Default::default());
+
+                // The update call above could modify the lag.
+                if lag >= fixed_time_step {
+                    lag -= fixed_time_step;
+                } else if lag < 0.0 {
+                    lag = 0.0;
+                }
+
+                frame_counter += 1;
+            }
+        }
+
+        Log::info(format!(
+            "Headless executor has stopped after {frame_counter} frames."
+        ));
+
+        engine
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{reflect::prelude::*, visitor::prelude::*},
+        engine::{headless::HeadlessExecutor, GraphicsContext},
+        plugin::{Plugin, PluginContext},
+    };
+
+    #[derive(Default, Debug, Visit, Reflect)]
+    struct Counter {
+        updates: usize,
+        post_updates: usize,
+    }
+
+    impl Plugin for Counter {
+        fn update(&mut self, context: &mut PluginContext) {
+            assert!(matches!(
+                context.graphics_context,
+                GraphicsContext::Uninitialized(_)
+            ));
+            assert!(context.window_target.is_none());
+            self.updates += 1;
+        }
+
+        fn post_update(&mut self, _context: &mut PluginContext) {
+            self.post_updates += 1;
+        }
+    }
+
+    #[test]
+    fn test_headless_executor_frame_limit() {
+        let mut executor = HeadlessExecutor::new();
+        executor.set_real_time(false);
+        executor.set_frame_limit(Some(10));
+        executor.add_plugin(Counter::default());
+
+        let engine = executor.run();
+        let counter = engine.plugins()[0].cast::<Counter>().unwrap();
+        assert_eq!(counter.updates, 10);
+        assert_eq!(counter.post_updates, 10);
+        assert!(
+            (engine.elapsed_time() - 10.0 / HeadlessExecutor::DEFAULT_UPDATE_RATE).abs() < 1e-4
+        );
+    }
+
+    #[test]
+    fn test_headless_executor_exit_handle() {
+        let mut executor = HeadlessExecutor::new();
+        executor.set_real_time(false);
+        executor.exit_handle().exit();
+        executor.add_plugin(Counter::default());
+
+        let engine = executor.run();
+        let counter = engine.plugins()[0].cast::<Counter>().unwrap();
+        assert_eq!(counter.updates, 0);
+    }
+}
@@ -32,6 +32,7 @@ use crate::{
     resource::model::ModelResource,
     scene::{
         base::{visit_opt_script, NodeScriptMessage},
+        graph::script_index::ScriptIndex,
         node::{container::NodeContainer, Node},
         Scene,
     },
@@ -86,6 +87,12 @@ impl SceneState {
 
             if is_node_belongs_to_plugin(serialization_context, node, plugin) {
                 // The entire node belongs to plugin, serialize it entirely.
+                for record in node.scripts.iter_mut() {
+                    if let Some(script) = record.script.as_mut() {
+                        script.on_before_reload();
+                    }
+                }
+
                 // Take the node out of the graph first.
                 let (ticket, node) = scene.graph.take_reserve(handle);
                 let mut container = NodeContainer::new(node);
@@ -98,8 +105,10 @@ impl SceneState {
             } else {
                 // The node does not belong to the plugin, try to check its scripts.
                 for (script_index, record) in node.scripts.iter_mut().enumerate() {
-                    if let Some(script) = record.script.as_ref() {
+                    if let Some(script) = record.script.as_mut() {
                         if is_script_belongs_to_plugin(serialization_context, script, plugin) {
+                            script.on_before_reload();
+
                             // Take the script out of the node and serialize it. The script will be
                             // dropped and destroyed.
                             let mut script = record.script.take();
@@ -142,19 +151,40 @@ impl SceneState {
 
         let script_message_sender = scene.graph.script_message_sender.clone();
         let message_sender = scene.graph.message_sender.clone();
-        self.deserialize_into_scene_internal(
+        let script_index = scene.graph.script_index().clone();
+        // Reloaded scripts must be notified, so they could restore their runtime state.
+        let reload_sender = &script_message_sender.clone();
+        let result = self.deserialize_into_scene_internal(
             |handle: Handle<Node>, index, script| {
+                if script.is_some() {
+                    Log::verify(reload_sender.send(NodeScriptMessage::ReloadScript {
+                        handle,
+                        script_index: index,
+                    }));
+                }
                 scene.graph[handle].scripts[index].script = script;
             },
             |handle: Handle<Node>, node| {
+                for (script_index, record) in node.scripts.iter().enumerate() {
+                    if record.script.is_some() {
+                        Log::verify(reload_sender.send(NodeScriptMessage::ReloadScript {
+                            handle,
+                            script_index,
+                        }));
+                    }
+                }
                 scene2.graph[handle] = node;
             },
             script_message_sender,
             message_sender,
+            script_index,
             serialization_context,
             resource_manager,
             widget_constructors,
-        )
+        );
+        // Scripts and nodes were replaced directly, so the index must be filled from scratch.
+        scene.graph.rebuild_script_index();
+        result
     }
 
     pub fn deserialize_into_prefab_scene(
@@ -166,7 +196,8 @@ impl SceneState {
     ) -> Result<(), String> {
         let script_message_sender = prefab.data_ref().scene.graph.script_message_sender.clone();
         let message_sender = prefab.data_ref().scene.graph.message_sender.clone();
-        self.deserialize_into_scene_internal(
+        let script_index = prefab.data_ref().scene.graph.script_index().clone();
+        let result = self.deserialize_into_scene_internal(
             |handle: Handle<Node>, index, script| {
                 prefab.data_ref().scene.graph[handle].scripts[index].script = script;
             },
@@ -175,10 +206,13 @@ impl SceneState {
             },
             script_message_sender,
             message_sender,
+            script_index,
             serialization_context,
             resource_manager,
             widget_constructors,
-        )
+        );
+        prefab.data_ref().scene.graph.rebuild_script_index();
+        result
     }
 
     pub fn deserialize_into_scene_internal<S, N>(
@@ -187,6 +221,7 @@ impl SceneState {
         mut set_node: N,
         script_message_sender: Sender<NodeScriptMessage>,
         message_sender: Sender<NodeMessage>,
+        script_index: ScriptIndex,
         serialization_context: &Arc<SerializationContext>,
         resource_manager: &ResourceManager,
         widget_constructors: &Arc<WidgetConstructorContainer>,
@@ -233,6 +268,7 @@ impl SceneState {
                         node_state.node,
                         message_sender.clone(),
                         script_message_sender.clone(),
+                        script_index.clone(),
                     );
                     set_node(node_state.node, new_node);
 
@@ -261,6 +297,9 @@ pub fn make_reading_visitor(
     widget_constructors: &Arc<WidgetConstructorContainer>,
 ) -> Result<Visitor, VisitError> {
     let mut visitor = Visitor::load_from_memory(binary_blob)?;
+    // New versions of types may have different set of fields, transfer only the matching ones and
+    // keep default values for the rest.
+    visitor.flags = VisitorFlags::SKIP_INVALID_FIELDS;
     visitor.blackboard.register(serialization_context.clone());
     visitor
         .blackboard
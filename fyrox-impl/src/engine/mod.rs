@@ -25,7 +25,10 @@
 
 pub mod error;
 pub mod executor;
+pub mod headless;
 pub mod task;
+pub mod update_loop;
+pub mod window;
 
 mod hotreload;
 
@@ -52,9 +55,13 @@ use crate::{
         task::TaskPool,
         variable::try_inherit_properties,
         visitor::VisitError,
+        ImmutableString,
     },
-    engine::{error::EngineError, task::TaskPoolHandler},
-    event::Event,
+    engine::{
+        error::EngineError, task::TaskPoolHandler, update_loop::UpdateLoopSettings,
+        window::WindowContainer,
+    },
+    event::{Event, WindowEvent},
     graph::{BaseSceneGraph, NodeMapping, SceneGraph},
     gui::{
         constructor::WidgetConstructorContainer,
@@ -70,14 +77,20 @@ use crate::{
         Material,
     },
     plugin::{
-        dylib::DyLibDynamicPlugin, DynamicPlugin, Plugin, PluginContainer, PluginContext,
-        PluginRegistrationContext,
+        dylib::DyLibDynamicPlugin, DynamicPlugin, LifecycleEvent, Plugin, PluginContainer,
+        PluginContext, PluginRegistrationContext,
     },
     renderer::{framework::error::FrameworkError, Renderer},
     resource::{
+        atlas::{loader::SpriteAtlasLoader, SpriteAtlas},
         curve::{loader::CurveLoader, CurveResourceState},
+        localization::{
+            loader::{LocalizationLoader, StringTableLoader},
+            Localization, LocalizationResource, StringTable,
+        },
         model::{loader::ModelLoader, Model, ModelResource},
         texture::{self, loader::TextureLoader, Texture, TextureKind},
+        video::{loader::VideoLoader, Video},
     },
     scene::{
         base::NodeScriptMessage,
@@ -97,11 +110,20 @@ use crate::{
         Scene, SceneContainer, SceneLoader,
     },
     script::{
-        constructor::ScriptConstructorContainer, PluginsRefMut, RoutingStrategy, Script,
-        ScriptContext, ScriptDeinitContext, ScriptMessage, ScriptMessageContext, ScriptMessageKind,
-        ScriptMessageSender, UniversalScriptContext,
+        blackboard::{loader::BlackboardLoader, Blackboard, BlackboardResource},
+        bus::MessageBus,
+        constructor::ScriptConstructorContainer,
+        scheduler::{ScheduledAction, Scheduler},
+        visual::{loader::VisualScriptGraphLoader, VisualScript, VisualScriptGraph},
+        MessageFilter, PluginsRefMut, RoutingStrategy, Script, ScriptContext, ScriptDeinitContext,
+        ScriptMessage, ScriptMessageContext, ScriptMessageKind, ScriptMessageSender, Topic,
+        UniversalScriptContext,
     },
-    window::{Window, WindowBuilder},
+    utils::{
+        behavior::{definition::BehaviorTreeDefinition, loader::BehaviorTreeDefinitionLoader},
+        translate_event,
+    },
+    window::{Window, WindowBuilder, WindowId},
 };
 use fxhash::{FxHashMap, FxHashSet};
 use fyrox_animation::AnimationTracksData;
@@ -150,9 +172,14 @@ impl Default for SerializationContext {
 impl SerializationContext {
     /// Creates default serialization context.
     pub fn new() -> Self {
+        let script_constructors = ScriptConstructorContainer::new();
+        script_constructors.add::<VisualScript>("Visual Script");
+        #[cfg(feature = "lua")]
+        script_constructors.add::<crate::script::lua::LuaScript>("Lua Script");
+
         Self {
             node_constructors: new_node_constructor_container(),
-            script_constructors: ScriptConstructorContainer::new(),
+            script_constructors,
         }
     }
 }
@@ -168,6 +195,10 @@ pub struct PerformanceStatistics {
 
     /// Amount of time spent in plugins updating.
     pub plugins_time: Duration,
+
+    /// Per-type statistics of scripts of all scenes. See [`ScriptPerformanceStatistics`] docs for
+    /// more info.
+    pub script_statistics: ScriptPerformanceStatistics,
 }
 
 impl Display for PerformanceStatistics {
@@ -176,7 +207,113 @@ impl Display for PerformanceStatistics {
             f,
             "Performance Statistics:\n\tUI: {:?}\n\tScripts: {:?}\n\tPlugins: {:?}",
             self.ui_time, self.scripts_time, self.plugins_time
-        )
+        )?;
+        write!(f, "{}", self.script_statistics)
+    }
+}
+
+/// Performance statistics of a single script type.
+#[derive(Debug, Default, Clone)]
+pub struct ScriptTypeStatistics {
+    /// Type name of the script.
+    pub name: String,
+
+    /// Amount of time spent in `on_update` method of every script instance of the type.
+    pub update_time: Duration,
+
+    /// Amount of `on_update` calls.
+    pub update_count: usize,
+
+    /// Amount of time spent in `on_message` method of every script instance of the type.
+    pub message_time: Duration,
+
+    /// Amount of `on_message` calls.
+    pub message_count: usize,
+}
+
+impl ScriptTypeStatistics {
+    /// Returns total amount of time spent in the methods of the script type.
+    pub fn total(&self) -> Duration {
+        self.update_time + self.message_time
+    }
+}
+
+/// Per-type performance statistics of scripts, that is collected for every frame. It could be used
+/// to find which script type takes the most of the frame time.
+#[derive(Debug, Default, Clone)]
+pub struct ScriptPerformanceStatistics {
+    /// Statistics of every script type, that was executed on the last frame. The key is a type
+    /// uuid of the script.
+    pub types: FxHashMap<Uuid, ScriptTypeStatistics>,
+}
+
+impl Display for ScriptPerformanceStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for stats in self.sorted() {
+            writeln!(
+                f,
+                "\t\t{}: {:?} (Updates: {}, Messages: {})",
+                stats.name,
+                stats.total(),
+                stats.update_count,
+                stats.message_count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl ScriptPerformanceStatistics {
+    /// Returns statistics of every script type sorted by total time in descending order, so the
+    /// slowest script type goes first.
+    pub fn sorted(&self) -> Vec<&ScriptTypeStatistics> {
+        let mut stats = self.types.values().collect::<Vec<_>>();
+        stats.sort_by(|a, b| b.total().cmp(&a.total()));
+        stats
+    }
+
+    /// Returns statistics of the script type with the given type uuid.
+    pub fn get(&self, type_uuid: &Uuid) -> Option<&ScriptTypeStatistics> {
+        self.types.get(type_uuid)
+    }
+
+    fn entry(&mut self, script: &Script) -> &mut ScriptTypeStatistics {
+        self.types
+            .entry(script.id())
+            .or_insert_with(|| ScriptTypeStatistics {
+                // Type name is copied, because it could belong to a hot-reloadable plugin.
+                name: script.type_name().to_string(),
+                ..Default::default()
+            })
+    }
+
+    pub(crate) fn measure_update<F>(&mut self, script: &mut Script, func: F)
+    where
+        F: FnOnce(&mut Script),
+    {
+        let time = instant::Instant::now();
+        func(script);
+        let elapsed = instant::Instant::now() - time;
+        let entry = self.entry(script);
+        entry.update_time += elapsed;
+        entry.update_count += 1;
+    }
+
+    pub(crate) fn measure_message<F>(&mut self, script: &mut Script, func: F)
+    where
+        F: FnOnce(&mut Script),
+    {
+        let time = instant::Instant::now();
+        func(script);
+        let elapsed = instant::Instant::now() - time;
+        let entry = self.entry(script);
+        entry.message_time += elapsed;
+        entry.message_count += 1;
+    }
+
+    /// Removes statistics of every script type.
+    pub fn clear(&mut self) {
+        self.types.clear();
     }
 }
 
@@ -188,6 +325,9 @@ pub struct InitializedGraphicsContext {
     /// Current renderer.
     pub renderer: Renderer,
 
+    /// Secondary windows. See [`WindowContainer`] docs for more info.
+    pub windows: WindowContainer,
+
     params: GraphicsContextParams,
 }
 
@@ -216,6 +356,27 @@ impl InitializedGraphicsContext {
             }
         }
     }
+
+    /// Creates a new secondary window, that shares every GPU resource with the main window. See
+    /// [`WindowContainer`] docs for more info.
+    pub fn create_window(
+        &mut self,
+        window_target: &EventLoopWindowTarget<()>,
+        window_builder: WindowBuilder,
+    ) -> Result<WindowId, FrameworkError> {
+        let window = self
+            .renderer
+            .graphics_server()
+            .create_window(window_target, window_builder)?;
+        Ok(self.windows.add(window))
+    }
+
+    /// Destroys a secondary window with the given id. Returns `false` if there's no such window.
+    pub fn destroy_window(&mut self, id: WindowId) -> bool {
+        // The swapchain must be destroyed before the window.
+        self.renderer.graphics_server().destroy_window(id);
+        self.windows.remove(id).is_some()
+    }
 }
 
 /// Graphics context of the engine, it could be in two main states:
@@ -263,6 +424,11 @@ impl GraphicsContext {
             panic!("Graphics context is uninitialized!")
         }
     }
+
+    /// Returns `true` if the graphics context is initialized.
+    pub fn is_initialized(&self) -> bool {
+        matches!(self, GraphicsContext::Initialized(_))
+    }
 }
 
 struct SceneLoadingOptions {
@@ -477,6 +643,9 @@ pub struct Engine {
 
     model_events_receiver: Receiver<ResourceEvent>,
 
+    // Reload events that should be passed to plugins.
+    resource_reload_events: Vec<ResourceEvent>,
+
     #[allow(dead_code)] // Keep engine instance alive.
     sound_engine: SoundEngine,
 
@@ -488,6 +657,12 @@ pub struct Engine {
     // Amount of time (in seconds) that passed from creation of the engine.
     elapsed_time: f32,
 
+    /// Parameters of the main loop of the engine. See [`UpdateLoopSettings`] docs for more info.
+    pub update_loop: UpdateLoopSettings,
+
+    // Fraction of the fixed time step, that was accumulated but not simulated yet.
+    interpolation_alpha: f32,
+
     /// A special container that is able to create nodes by their type UUID. Use a copy of this
     /// value whenever you need it as a parameter in other parts of the engine.
     pub serialization_context: Arc<SerializationContext>,
@@ -502,6 +677,7 @@ pub struct Engine {
 /// Performs dispatch of script messages.
 pub struct ScriptMessageDispatcher {
     type_groups: FxHashMap<TypeId, FxHashSet<Handle<Node>>>,
+    topic_groups: FxHashMap<(TypeId, ImmutableString), FxHashMap<Handle<Node>, MessageFilter>>,
     message_receiver: Receiver<ScriptMessage>,
 }
 
@@ -509,6 +685,7 @@ impl ScriptMessageDispatcher {
     fn new(message_receiver: Receiver<ScriptMessage>) -> Self {
         Self {
             type_groups: Default::default(),
+            topic_groups: Default::default(),
             message_receiver,
         }
     }
@@ -531,11 +708,40 @@ impl ScriptMessageDispatcher {
         }
     }
 
+    /// Subscribes a node to receive messages published to the given topic (see
+    /// [`ScriptMessageSender::publish`]), but only from the senders accepted by the `filter`.
+    /// Subscribing to the same topic again replaces the filter. Subscription is automatically
+    /// removed if the node dies.
+    pub fn subscribe_to_topic<T: 'static>(
+        &mut self,
+        topic: &Topic<T>,
+        receiver: Handle<Node>,
+        filter: MessageFilter,
+    ) {
+        self.topic_groups
+            .entry((TypeId::of::<T>(), topic.name().clone()))
+            .or_default()
+            .insert(receiver, filter);
+    }
+
+    /// Unsubscribes a node from receiving messages of the given topic.
+    pub fn unsubscribe_from_topic<T: 'static>(&mut self, topic: &Topic<T>, receiver: Handle<Node>) {
+        if let Some(group) = self
+            .topic_groups
+            .get_mut(&(TypeId::of::<T>(), topic.name().clone()))
+        {
+            group.remove(&receiver);
+        }
+    }
+
     /// Unsubscribes a node from receiving any messages.
     pub fn unsubscribe(&mut self, receiver: Handle<Node>) {
         for group in self.type_groups.values_mut() {
             group.remove(&receiver);
         }
+        for group in self.topic_groups.values_mut() {
+            group.remove(&receiver);
+        }
     }
 
     fn dispatch_messages(
@@ -546,13 +752,61 @@ impl ScriptMessageDispatcher {
         resource_manager: &ResourceManager,
         dt: f32,
         elapsed_time: f32,
+        interpolation_alpha: f32,
         message_sender: &ScriptMessageSender,
+        message_bus: &MessageBus,
+        blackboard: &BlackboardResource,
+        localization: &LocalizationResource,
         user_interfaces: &mut UiContainer,
         graphics_context: &mut GraphicsContext,
         task_pool: &mut TaskPoolHandler,
+        statistics: &mut ScriptPerformanceStatistics,
     ) {
         while let Ok(message) = self.message_receiver.try_recv() {
-            let receivers = self.type_groups.get(&message.payload.deref().type_id());
+            let type_id = message.payload.deref().type_id();
+
+            if let ScriptMessageKind::Topic { ref name, sender } = message.kind {
+                // Topics could be published without any subscribers, so there's no warning.
+                let Some(group) = self.topic_groups.get(&(type_id, name.clone())) else {
+                    continue;
+                };
+
+                let receivers = group
+                    .iter()
+                    .filter_map(|(receiver, filter)| {
+                        filter.accepts(sender, scene).then_some(*receiver)
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut payload = message.payload;
+                for receiver in receivers {
+                    let mut context = ScriptMessageContext {
+                        dt,
+                        elapsed_time,
+                        plugins: PluginsRefMut(plugins),
+                        handle: receiver,
+                        scene,
+                        scene_handle,
+                        resource_manager,
+                        message_sender,
+                        message_bus,
+                        blackboard,
+                        localization,
+                        task_pool,
+                        graphics_context,
+                        user_interfaces,
+                        script_index: 0,
+                    };
+
+                    process_node_scripts(&mut context, &mut |s, ctx| {
+                        statistics.measure_message(s, |s| s.on_message(&mut *payload, ctx))
+                    });
+                }
+
+                continue;
+            }
+
+            let receivers = self.type_groups.get(&type_id);
 
             if receivers.map_or(true, |r| r.is_empty()) {
                 Log::warn(format!(
@@ -576,6 +830,9 @@ impl ScriptMessageDispatcher {
                                 scene_handle,
                                 resource_manager,
                                 message_sender,
+                                message_bus,
+                                blackboard,
+                                localization,
                                 task_pool,
                                 graphics_context,
                                 user_interfaces,
@@ -583,7 +840,7 @@ impl ScriptMessageDispatcher {
                             };
 
                             process_node_scripts(&mut context, &mut |s, ctx| {
-                                s.on_message(&mut *payload, ctx)
+                                statistics.measure_message(s, |s| s.on_message(&mut *payload, ctx))
                             })
                         }
                     }
@@ -602,6 +859,9 @@ impl ScriptMessageDispatcher {
                                     scene_handle,
                                     resource_manager,
                                     message_sender,
+                                    message_bus,
+                                    blackboard,
+                                    localization,
                                     task_pool,
                                     graphics_context,
                                     user_interfaces,
@@ -610,7 +870,9 @@ impl ScriptMessageDispatcher {
 
                                 if receivers.contains(&node) {
                                     process_node_scripts(&mut context, &mut |s, ctx| {
-                                        s.on_message(&mut *payload, ctx)
+                                        statistics.measure_message(s, |s| {
+                                            s.on_message(&mut *payload, ctx)
+                                        })
                                     });
                                 }
 
@@ -628,6 +890,9 @@ impl ScriptMessageDispatcher {
                                     scene_handle,
                                     resource_manager,
                                     message_sender,
+                                    message_bus,
+                                    blackboard,
+                                    localization,
                                     task_pool,
                                     graphics_context,
                                     user_interfaces,
@@ -636,7 +901,9 @@ impl ScriptMessageDispatcher {
 
                                 if receivers.contains(&node) {
                                     process_node_scripts(&mut context, &mut |s, ctx| {
-                                        s.on_message(&mut *payload, ctx)
+                                        statistics.measure_message(s, |s| {
+                                            s.on_message(&mut *payload, ctx)
+                                        })
                                     });
                                 }
                             }
@@ -653,6 +920,9 @@ impl ScriptMessageDispatcher {
                                 scene_handle,
                                 resource_manager,
                                 message_sender,
+                                message_bus,
+                                blackboard,
+                                localization,
                                 task_pool,
                                 graphics_context,
                                 user_interfaces,
@@ -660,10 +930,12 @@ impl ScriptMessageDispatcher {
                             };
 
                             process_node_scripts(&mut context, &mut |s, ctx| {
-                                s.on_message(&mut *payload, ctx)
+                                statistics.measure_message(s, |s| s.on_message(&mut *payload, ctx))
                             });
                         }
                     }
+                    // Handled above.
+                    ScriptMessageKind::Topic { .. } => (),
                 }
             }
         }
@@ -677,6 +949,7 @@ pub struct ScriptedScene {
     /// Script message sender.
     pub message_sender: ScriptMessageSender,
     message_dispatcher: ScriptMessageDispatcher,
+    scheduler: Scheduler,
 }
 
 /// Script processor is used to run script methods in a strict order.
@@ -685,6 +958,16 @@ pub struct ScriptProcessor {
     wait_list: Vec<ResourceWaitContext>,
     /// A list of scenes.
     pub scripted_scenes: Vec<ScriptedScene>,
+    /// Global message bus, that delivers messages to plugins and scripts of every scripted scene.
+    /// See [`MessageBus`] docs for more info.
+    pub message_bus: MessageBus,
+    /// Global blackboard, that is shared between plugins and scripts of every scripted scene. See
+    /// [`Blackboard`] docs for more info.
+    pub blackboard: BlackboardResource,
+    /// Global localization, that is shared between plugins and scripts of every scripted scene.
+    /// See [`Localization`] docs for more info.
+    pub localization: LocalizationResource,
+    statistics: ScriptPerformanceStatistics,
 }
 
 impl ScriptProcessor {
@@ -701,10 +984,13 @@ impl ScriptProcessor {
         assert!(!self.has_scripted_scene(scene));
 
         let (tx, rx) = channel();
+        self.message_bus
+            .add_scene_sender(ScriptMessageSender { sender: tx.clone() });
         self.scripted_scenes.push(ScriptedScene {
             handle: scene,
             message_sender: ScriptMessageSender { sender: tx },
             message_dispatcher: ScriptMessageDispatcher::new(rx),
+            scheduler: Default::default(),
         });
 
         self.wait_list
@@ -721,7 +1007,10 @@ impl ScriptProcessor {
         user_interfaces: &mut UiContainer,
         dt: f32,
         elapsed_time: f32,
+        interpolation_alpha: f32,
     ) {
+        self.statistics.clear();
+
         self.wait_list
             .retain_mut(|context| !context.is_all_loaded());
 
@@ -741,9 +1030,9 @@ impl ScriptProcessor {
             }
 
             // Fill in initial handles to nodes to initialize, start, update.
-            let mut update_queue = VecDeque::new();
-            let mut start_queue = VecDeque::new();
-            let script_message_sender = scene.graph.script_message_sender.clone();
+            let mut update_list = Vec::new();
+            let mut start_list = Vec::new();
+            let mut init_list = Vec::new();
             for (handle, node) in scene.graph.pair_iter_mut() {
                 // Remove unused script entries.
                 node.scripts
@@ -752,25 +1041,35 @@ impl ScriptProcessor {
                 if node.is_globally_enabled() {
                     for (i, entry) in node.scripts.iter().enumerate() {
                         if let Some(script) = entry.script.as_ref() {
+                            let entry = (script.execution_order(), handle, i);
                             if script.initialized {
                                 if script.started {
-                                    update_queue.push_back((handle, i));
+                                    update_list.push(entry);
                                 } else {
-                                    start_queue.push_back((handle, i));
+                                    start_list.push(entry);
                                 }
                             } else {
-                                script_message_sender
-                                    .send(NodeScriptMessage::InitializeScript {
-                                        handle,
-                                        script_index: i,
-                                    })
-                                    .unwrap();
+                                init_list.push(entry);
                             }
                         }
                     }
                 }
             }
 
+            // Sorting is stable, so the scripts with the same execution order keep the order of
+            // their nodes in the graph.
+            let mut update_queue = sort_by_execution_order(update_list);
+            let mut start_queue = sort_by_execution_order(start_list);
+            let script_message_sender = scene.graph.script_message_sender.clone();
+            for (handle, script_index) in sort_by_execution_order(init_list) {
+                script_message_sender
+                    .send(NodeScriptMessage::InitializeScript {
+                        handle,
+                        script_index,
+                    })
+                    .unwrap();
+            }
+
             // We'll gather all scripts queued for destruction and destroy them all at once at the
             // end of the frame.
             let mut destruction_queue = VecDeque::new();
@@ -788,9 +1087,14 @@ impl ScriptProcessor {
                     resource_manager,
                     message_sender: &scripted_scene.message_sender,
                     message_dispatcher: &mut scripted_scene.message_dispatcher,
+                    message_bus: &self.message_bus,
+                    blackboard: &self.blackboard,
+                    localization: &self.localization,
+                    scheduler: &mut scripted_scene.scheduler,
                     task_pool,
                     graphics_context,
                     user_interfaces,
+                    interpolation_alpha,
                     script_index: 0,
                 };
 
@@ -828,6 +1132,23 @@ impl ScriptProcessor {
                                 // Destruction is delayed to the end of the frame.
                                 destruction_queue.push_back((handle, script, script_index));
                             }
+                            NodeScriptMessage::ReloadScript {
+                                handle,
+                                script_index,
+                            } => {
+                                context.handle = handle;
+                                context.script_index = script_index;
+
+                                process_node_script(
+                                    script_index,
+                                    &mut context,
+                                    &mut |script, context| {
+                                        if script.initialized {
+                                            script.on_after_reload(context);
+                                        }
+                                    },
+                                );
+                            }
                         }
                     }
 
@@ -874,7 +1195,8 @@ impl ScriptProcessor {
                         context.script_index = script_index;
 
                         process_node_script(script_index, &mut context, &mut |script, context| {
-                            script.on_update(context);
+                            self.statistics
+                                .measure_update(script, |script| script.on_update(context));
                         });
                     }
                 }
@@ -887,6 +1209,60 @@ impl ScriptProcessor {
                 }
             }
 
+            // Run scheduled actions after the update, so the actions scheduled in `on_update` with
+            // zero delay are executed on the same frame.
+            let due = scripted_scene.scheduler.take_due(dt, scene);
+            if !due.is_empty() {
+                let mut context = ScriptContext {
+                    dt,
+                    elapsed_time,
+                    plugins: PluginsRefMut(plugins),
+                    handle: Default::default(),
+                    scene,
+                    scene_handle: scripted_scene.handle,
+                    resource_manager,
+                    message_sender: &scripted_scene.message_sender,
+                    message_dispatcher: &mut scripted_scene.message_dispatcher,
+                    message_bus: &self.message_bus,
+                    blackboard: &self.blackboard,
+                    localization: &self.localization,
+                    scheduler: &mut scripted_scene.scheduler,
+                    task_pool,
+                    graphics_context,
+                    user_interfaces,
+                    interpolation_alpha,
+                    script_index: 0,
+                };
+
+                for (schedule_handle, mut entry) in due {
+                    context.scheduler.begin(schedule_handle);
+
+                    match entry.action {
+                        ScheduledAction::Script {
+                            node,
+                            script_index,
+                            ref mut closure,
+                        } => {
+                            context.handle = node;
+                            context.script_index = script_index;
+
+                            process_node_script(
+                                script_index,
+                                &mut context,
+                                &mut |script, context| {
+                                    if script.initialized && script.started {
+                                        closure(&mut **script, context);
+                                    }
+                                },
+                            );
+                        }
+                        ScheduledAction::Message(ref mut send) => send(context.message_sender),
+                    }
+
+                    context.scheduler.end(schedule_handle, entry);
+                }
+            }
+
             // Dispatch script messages only when everything is initialized and updated. This has to
             // be done this way, because all those methods could spawn new messages. However, if a new
             // message is spawned directly in `on_message` the dispatcher will correctly handle it
@@ -898,10 +1274,15 @@ impl ScriptProcessor {
                 resource_manager,
                 dt,
                 elapsed_time,
+                interpolation_alpha,
                 &scripted_scene.message_sender,
+                &self.message_bus,
+                &self.blackboard,
+                &self.localization,
                 user_interfaces,
                 graphics_context,
                 task_pool,
+                &mut self.statistics,
             );
 
             // As the last step, destroy queued scripts.
@@ -924,6 +1305,10 @@ impl ScriptProcessor {
 
                 // Unregister self in message dispatcher.
                 scripted_scene.message_dispatcher.unsubscribe(handle);
+                // Cancel every scheduled action of the script.
+                scripted_scene
+                    .scheduler
+                    .remove_script_entries(handle, index);
 
                 // `on_deinit` could also spawn new nodes, but we won't take those into account on
                 // this frame. They'll be correctly handled on next frame.
@@ -1111,6 +1496,8 @@ pub struct EngineInitParams {
     pub resource_manager: ResourceManager,
     /// Task pool for asynchronous task management.
     pub task_pool: Arc<TaskPool>,
+    /// Parameters of the main loop of the engine. See [`UpdateLoopSettings`] docs for more info.
+    pub update_loop: UpdateLoopSettings,
 }
 
 fn process_node_script<T, C>(index: usize, context: &mut C, func: &mut T) -> bool
@@ -1180,6 +1567,15 @@ where
     }
 }
 
+fn sort_by_execution_order(
+    mut list: Vec<(i32, Handle<Node>, usize)>,
+) -> VecDeque<(Handle<Node>, usize)> {
+    list.sort_by_key(|(order, _, _)| *order);
+    list.into_iter()
+        .map(|(_, handle, index)| (handle, index))
+        .collect()
+}
+
 pub(crate) fn process_scripts<T>(
     scene: &mut Scene,
     scene_handle: Handle<Scene>,
@@ -1187,11 +1583,16 @@ pub(crate) fn process_scripts<T>(
     resource_manager: &ResourceManager,
     message_sender: &ScriptMessageSender,
     message_dispatcher: &mut ScriptMessageDispatcher,
+    message_bus: &MessageBus,
+    blackboard: &BlackboardResource,
+    localization: &LocalizationResource,
+    scheduler: &mut Scheduler,
     task_pool: &mut TaskPoolHandler,
     graphics_context: &mut GraphicsContext,
     user_interfaces: &mut UiContainer,
     dt: f32,
     elapsed_time: f32,
+    interpolation_alpha: f32,
     mut func: T,
 ) where
     T: FnMut(&mut Script, &mut ScriptContext),
@@ -1206,20 +1607,38 @@ pub(crate) fn process_scripts<T>(
         resource_manager,
         message_sender,
         message_dispatcher,
+        message_bus,
+        blackboard,
+        localization,
+        scheduler,
         task_pool,
         graphics_context,
         user_interfaces,
+        interpolation_alpha,
         script_index: 0,
     };
 
-    for node_index in 0..context.scene.graph.capacity() {
-        context.handle = context.scene.graph.handle_from_index(node_index);
+    let mut list = Vec::new();
+    for (handle, node) in context.scene.graph.pair_iter() {
+        for (index, entry) in node.scripts.iter().enumerate() {
+            if let Some(script) = entry.script.as_ref() {
+                list.push((script.execution_order(), handle, index));
+            }
+        }
+    }
+
+    for (handle, index) in sort_by_execution_order(list) {
+        context.handle = handle;
+        context.script_index = index;
 
-        process_node_scripts(&mut context, &mut func);
+        process_node_script(index, &mut context, &mut func);
     }
 }
 
-pub(crate) fn initialize_resource_manager_loaders(
+/// Registers standard resource loaders, constructors and built-in resources in the given resource
+/// manager. The engine does this automatically, this function is useful only if a resource manager
+/// is used without the engine (for example, in command line tools).
+pub fn initialize_resource_manager_loaders(
     resource_manager: &ResourceManager,
     serialization_context: Arc<SerializationContext>,
 ) {
@@ -1237,6 +1656,12 @@ pub(crate) fn initialize_resource_manager_loaders(
     };
     state.loaders.set(gltf_loader);
 
+    let usd_loader = super::resource::usd::UsdLoader {
+        resource_manager: resource_manager.clone(),
+        default_import_options: Default::default(),
+    };
+    state.loaders.set(usd_loader);
+
     for shader in ShaderResource::standard_shaders() {
         state.built_in_resources.add((*shader).clone());
     }
@@ -1288,6 +1713,17 @@ pub(crate) fn initialize_resource_manager_loaders(
     state.constructors_container.add::<CustomTileCollider>();
     state.constructors_container.add::<AnimationTracksData>();
     state.constructors_container.add::<Style>();
+    state.constructors_container.add::<SpriteAtlas>();
+    state.constructors_container.add::<StringTable>();
+    state.constructors_container.add::<Localization>();
+    state.constructors_container.add::<Video>();
+    state.constructors_container.add::<VisualScriptGraph>();
+    state.constructors_container.add::<Blackboard>();
+    state.constructors_container.add::<BehaviorTreeDefinition>();
+    #[cfg(feature = "lua")]
+    state
+        .constructors_container
+        .add::<crate::script::lua::LuaSource>();
 
     let loaders = &mut state.loaders;
     loaders.set(model_loader);
@@ -1299,6 +1735,15 @@ pub(crate) fn initialize_resource_manager_loaders(
     });
     loaders.set(ShaderLoader);
     loaders.set(CurveLoader);
+    loaders.set(SpriteAtlasLoader);
+    loaders.set(StringTableLoader);
+    loaders.set(LocalizationLoader);
+    loaders.set(VideoLoader);
+    loaders.set(VisualScriptGraphLoader);
+    loaders.set(BlackboardLoader);
+    loaders.set(BehaviorTreeDefinitionLoader);
+    #[cfg(feature = "lua")]
+    loaders.set(crate::script::lua::loader::LuaSourceLoader);
     loaders.set(HrirSphereLoader);
     loaders.set(MaterialLoader {
         resource_manager: resource_manager.clone(),
@@ -1358,6 +1803,7 @@ impl Engine {
     ///     serialization_context: Arc::new(SerializationContext::new()),
     ///     task_pool,
     ///     widget_constructors: Arc::new(new_widget_constructor_container()),
+    ///     update_loop: Default::default(),
     /// })
     /// .unwrap();
     /// ```
@@ -1370,6 +1816,7 @@ impl Engine {
             widget_constructors,
             resource_manager,
             task_pool,
+            update_loop,
         } = params;
 
         initialize_resource_manager_loaders(&resource_manager, serialization_context.clone());
@@ -1385,6 +1832,7 @@ impl Engine {
         Ok(Self {
             graphics_context: GraphicsContext::Uninitialized(graphics_context_params),
             model_events_receiver: tx,
+            resource_reload_events: Default::default(),
             async_scene_loader: AsyncSceneLoader::new(
                 resource_manager.clone(),
                 serialization_context.clone(),
@@ -1400,6 +1848,8 @@ impl Engine {
             script_processor: Default::default(),
             plugins_enabled: false,
             elapsed_time: 0.0,
+            update_loop,
+            interpolation_alpha: 0.0,
             task_pool: TaskPoolHandler::new(task_pool),
         })
     }
@@ -1464,6 +1914,7 @@ impl Engine {
             self.graphics_context = GraphicsContext::Initialized(InitializedGraphicsContext {
                 renderer,
                 window,
+                windows: Default::default(),
                 params: params.clone(),
             });
 
@@ -1531,6 +1982,44 @@ impl Engine {
         }
     }
 
+    /// Frees as much memory as possible: destroys resources that aren't used anywhere and clears
+    /// GPU caches of the renderer (the data will be re-uploaded to GPU on demand). This method is
+    /// called automatically by the executor when the OS reports that it is running low on memory.
+    pub fn handle_low_memory(&mut self) {
+        self.resource_manager.state().destroy_unused_resources();
+        if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
+            ctx.renderer.flush();
+        }
+        Log::warn("Low memory warning was received, unused resources were freed.");
+    }
+
+    /// Routes an OS event of a secondary window (see [`WindowContainer`]) to the user interface of
+    /// the window and destroys the window when it is closed. Returns `false` if the event does not
+    /// belong to any secondary window, such events should be handled as usual.
+    pub fn handle_secondary_window_event(
+        &mut self,
+        window_id: WindowId,
+        event: &WindowEvent,
+    ) -> bool {
+        let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context else {
+            return false;
+        };
+
+        if !ctx.windows.contains(window_id) {
+            return false;
+        }
+
+        if let WindowEvent::CloseRequested = event {
+            ctx.destroy_window(window_id);
+        } else if let Some(os_event) = translate_event(event) {
+            if let Some(window) = ctx.windows.get_mut(window_id) {
+                window.user_interface.process_os_event(&os_event);
+            }
+        }
+
+        true
+    }
+
     /// Adjust size of the frame to be rendered. Must be called after the window size changes.
     /// Will update the renderer and GL context frame size.
     pub fn set_frame_size(&mut self, new_size: (u32, u32)) -> Result<(), FrameworkError> {
@@ -1548,6 +2037,18 @@ impl Engine {
         self.elapsed_time
     }
 
+    /// Returns the fraction of the fixed time step, that was accumulated but not simulated yet. See
+    /// [`UpdateLoopSettings::interpolation_alpha`] docs for more info.
+    pub fn interpolation_alpha(&self) -> f32 {
+        self.interpolation_alpha
+    }
+
+    /// Sets the interpolation alpha, that will be passed to plugins and scripts. It is set by
+    /// executors automatically, this method is useful only if you're writing your own game loop.
+    pub fn set_interpolation_alpha(&mut self, alpha: f32) {
+        self.interpolation_alpha = alpha.clamp(0.0, 1.0);
+    }
+
     /// Performs single update tick with given time delta. Engine internally will perform update
     /// of all scenes, sub-systems, user interface, etc. Must be called in order to get engine
     /// functioning.
@@ -1568,12 +2069,27 @@ impl Engine {
         lag: &mut f32,
         switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
     ) {
-        self.handle_async_scene_loading(dt, lag, window_target);
+        self.handle_async_scene_loading(dt, lag, Some(window_target));
         self.pre_update(dt, window_target, lag, switches);
         self.post_update(dt, &Default::default(), lag, window_target);
         self.handle_plugins_hot_reloading(dt, window_target, lag, |_| {});
     }
 
+    /// Performs single update tick without an event loop. It is the same as [`Self::update`], but
+    /// plugins receive no window target (see [`PluginContext::window_target`]) and dynamic plugins
+    /// are not hot-reloaded. It is intended to be used in headless mode, when there's no graphics
+    /// context (see [`crate::engine::headless::HeadlessExecutor`]).
+    pub fn update_headless(
+        &mut self,
+        dt: f32,
+        lag: &mut f32,
+        switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
+    ) {
+        self.handle_async_scene_loading(dt, lag, None);
+        self.pre_update_internal(dt, None, lag, switches);
+        self.post_update_internal(dt, &Default::default(), lag, None);
+    }
+
     /// Tries to hot-reload dynamic plugins marked for reloading.
     ///
     /// ## Platform-specific
@@ -1604,7 +2120,7 @@ impl Engine {
         &mut self,
         dt: f32,
         lag: &mut f32,
-        window_target: &EventLoopWindowTarget<()>,
+        window_target: Option<&EventLoopWindowTarget<()>>,
     ) {
         let len = self.async_scene_loader.loading_scenes.len();
         let mut n = 0;
@@ -1628,9 +2144,14 @@ impl Engine {
                             performance_statistics: &self.performance_statistics,
                             elapsed_time: self.elapsed_time,
                             script_processor: &self.script_processor,
+                            message_bus: &self.script_processor.message_bus,
+                            blackboard: &self.script_processor.blackboard,
+                            localization: &self.script_processor.localization,
                             async_scene_loader: &mut self.async_scene_loader,
-                            window_target: Some(window_target),
+                            window_target,
                             task_pool: &mut self.task_pool,
+                            update_loop: &mut self.update_loop,
+                            interpolation_alpha: self.interpolation_alpha,
                         };
 
                         for plugin in self.plugins.iter_mut() {
@@ -1661,9 +2182,14 @@ impl Engine {
                     performance_statistics: &self.performance_statistics,
                     elapsed_time: self.elapsed_time,
                     script_processor: &self.script_processor,
+                    message_bus: &self.script_processor.message_bus,
+                    blackboard: &self.script_processor.blackboard,
+                    localization: &self.script_processor.localization,
                     async_scene_loader: &mut self.async_scene_loader,
-                    window_target: Some(window_target),
+                    window_target,
                     task_pool: &mut self.task_pool,
+                    update_loop: &mut self.update_loop,
+                    interpolation_alpha: self.interpolation_alpha,
                 };
 
                 match loading_result.result {
@@ -1794,14 +2320,28 @@ impl Engine {
         window_target: &EventLoopWindowTarget<()>,
         lag: &mut f32,
         switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
+    ) {
+        self.pre_update_internal(dt, Some(window_target), lag, switches)
+    }
+
+    fn pre_update_internal(
+        &mut self,
+        dt: f32,
+        window_target: Option<&EventLoopWindowTarget<()>>,
+        lag: &mut f32,
+        switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
     ) {
         self.resource_manager.state().update(dt);
         self.handle_model_events();
 
+        #[cfg(not(target_arch = "wasm32"))]
+        self.update_crash_state();
+
         let window_size = if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
             let inner_size = ctx.window.inner_size();
             let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
             ctx.renderer.update_caches(dt);
+            ctx.windows.sync_render_targets(&mut self.scenes);
             window_size
         } else {
             Vector2::new(1.0, 1.0)
@@ -1832,6 +2372,35 @@ impl Engine {
         self.handle_scripts(dt);
     }
 
+    // Keeps the engine state summary of the crash report up to date.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update_crash_state(&self) {
+        use crate::core::log::crash::CrashHandler;
+
+        if !CrashHandler::is_installed() {
+            return;
+        }
+
+        CrashHandler::set_state("Engine: Elapsed Time", self.elapsed_time);
+        CrashHandler::set_state("Engine: Plugins", self.plugins.len());
+        CrashHandler::set_state("Engine: Scenes", self.scenes.iter().count());
+        CrashHandler::set_state(
+            "Engine: Scene Nodes",
+            self.scenes
+                .iter()
+                .map(|scene| scene.graph.node_count())
+                .sum::<u32>(),
+        );
+        CrashHandler::set_state(
+            "Engine: Graphics Context",
+            if let GraphicsContext::Initialized(_) = self.graphics_context {
+                "Initialized"
+            } else {
+                "Uninitialized"
+            },
+        );
+    }
+
     /// Performs post update for the engine.
     ///
     /// Normally, this is called from `Engine::update()`.
@@ -1843,7 +2412,17 @@ impl Engine {
         lag: &mut f32,
         window_target: &EventLoopWindowTarget<()>,
     ) {
-        if let GraphicsContext::Initialized(ref ctx) = self.graphics_context {
+        self.post_update_internal(dt, ui_update_switches, lag, Some(window_target))
+    }
+
+    fn post_update_internal(
+        &mut self,
+        dt: f32,
+        ui_update_switches: &UiUpdateSwitches,
+        lag: &mut f32,
+        window_target: Option<&EventLoopWindowTarget<()>>,
+    ) {
+        if let GraphicsContext::Initialized(ref mut ctx) = self.graphics_context {
             let inner_size = ctx.window.inner_size();
             let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
 
@@ -1851,11 +2430,23 @@ impl Engine {
             for ui in self.user_interfaces.iter_mut() {
                 ui.update(window_size, dt, ui_update_switches);
             }
+            for window in ctx.windows.iter_mut() {
+                let frame_size = window.frame_size();
+                window
+                    .user_interface
+                    .update(frame_size, dt, ui_update_switches);
+            }
             self.performance_statistics.ui_time = instant::Instant::now() - time;
-            self.elapsed_time += dt;
-
-            self.post_update_plugins(dt, window_target, lag);
         }
+
+        // Game logic must run even if there's no graphics context (for example, on game servers).
+        self.elapsed_time += dt;
+        self.post_update_plugins(dt, window_target, lag);
+    }
+
+    /// Returns performance statistics of the last frame.
+    pub fn performance_statistics(&self) -> &PerformanceStatistics {
+        &self.performance_statistics
     }
 
     /// Returns true if the scene is registered for script processing.
@@ -1881,15 +2472,17 @@ impl Engine {
             &mut self.user_interfaces,
             dt,
             self.elapsed_time,
+            self.interpolation_alpha,
         );
 
         self.performance_statistics.scripts_time = instant::Instant::now() - time;
+        self.performance_statistics.script_statistics = self.script_processor.statistics.clone();
     }
 
     fn handle_async_tasks(
         &mut self,
         dt: f32,
-        window_target: &EventLoopWindowTarget<()>,
+        window_target: Option<&EventLoopWindowTarget<()>>,
         lag: &mut f32,
     ) {
         while let Some(result) = self.task_pool.inner().next_task_result() {
@@ -1910,9 +2503,14 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        message_bus: &self.script_processor.message_bus,
+                        blackboard: &self.script_processor.blackboard,
+                        localization: &self.script_processor.localization,
                         async_scene_loader: &mut self.async_scene_loader,
-                        window_target: Some(window_target),
+                        window_target,
                         task_pool: &mut self.task_pool,
+                        update_loop: &mut self.update_loop,
+                        interpolation_alpha: self.interpolation_alpha,
                     },
                 )
             } else if let Some(node_task_handler) = self.task_pool.pop_node_task_handler(result.id)
@@ -1945,9 +2543,14 @@ impl Engine {
                                         resource_manager: &self.resource_manager,
                                         message_sender: &scripted_scene.message_sender,
                                         message_dispatcher: &mut scripted_scene.message_dispatcher,
+                                        message_bus: &self.script_processor.message_bus,
+                                        blackboard: &self.script_processor.blackboard,
+                                        localization: &self.script_processor.localization,
+                                        scheduler: &mut scripted_scene.scheduler,
                                         task_pool: &mut self.task_pool,
                                         graphics_context: &mut self.graphics_context,
                                         user_interfaces: &mut self.user_interfaces,
+                                        interpolation_alpha: self.interpolation_alpha,
                                         script_index: node_task_handler.script_index,
                                     },
                                 );
@@ -1977,12 +2580,40 @@ impl Engine {
                 }
             }
         }
+
+        // Collect the calls first, so the calls that are posted by the calls themselves will be
+        // executed on the next frame.
+        let main_thread_calls =
+            std::iter::from_fn(|| self.task_pool.pop_main_thread_call()).collect::<Vec<_>>();
+        for call in main_thread_calls {
+            call(&mut PluginContext {
+                scenes: &mut self.scenes,
+                resource_manager: &self.resource_manager,
+                graphics_context: &mut self.graphics_context,
+                dt,
+                lag,
+                user_interfaces: &mut self.user_interfaces,
+                serialization_context: &self.serialization_context,
+                widget_constructors: &self.widget_constructors,
+                performance_statistics: &self.performance_statistics,
+                elapsed_time: self.elapsed_time,
+                script_processor: &self.script_processor,
+                message_bus: &self.script_processor.message_bus,
+                blackboard: &self.script_processor.blackboard,
+                localization: &self.script_processor.localization,
+                async_scene_loader: &mut self.async_scene_loader,
+                window_target,
+                task_pool: &mut self.task_pool,
+                update_loop: &mut self.update_loop,
+                interpolation_alpha: self.interpolation_alpha,
+            });
+        }
     }
 
     fn update_plugins(
         &mut self,
         dt: f32,
-        window_target: &EventLoopWindowTarget<()>,
+        window_target: Option<&EventLoopWindowTarget<()>>,
         lag: &mut f32,
     ) {
         let time = instant::Instant::now();
@@ -2004,11 +2635,35 @@ impl Engine {
                 performance_statistics: &self.performance_statistics,
                 elapsed_time: self.elapsed_time,
                 script_processor: &self.script_processor,
+                message_bus: &self.script_processor.message_bus,
+                blackboard: &self.script_processor.blackboard,
+                localization: &self.script_processor.localization,
                 async_scene_loader: &mut self.async_scene_loader,
-                window_target: Some(window_target),
+                window_target,
                 task_pool: &mut self.task_pool,
+                update_loop: &mut self.update_loop,
+                interpolation_alpha: self.interpolation_alpha,
             };
 
+            for event in self.resource_reload_events.drain(..) {
+                for plugin in self.plugins.iter_mut() {
+                    match event {
+                        ResourceEvent::Reloaded(ref resource) => {
+                            plugin.on_resource_reloaded(resource, &mut context)
+                        }
+                        ResourceEvent::DependencyReloaded {
+                            ref resource,
+                            ref dependency,
+                        } => plugin.on_resource_dependency_reloaded(
+                            resource,
+                            dependency,
+                            &mut context,
+                        ),
+                        _ => (),
+                    }
+                }
+            }
+
             for plugin in self.plugins.iter_mut() {
                 plugin.update(&mut context);
             }
@@ -2037,9 +2692,14 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        message_bus: &self.script_processor.message_bus,
+                        blackboard: &self.script_processor.blackboard,
+                        localization: &self.script_processor.localization,
                         async_scene_loader: &mut self.async_scene_loader,
-                        window_target: Some(window_target),
+                        window_target,
                         task_pool: &mut self.task_pool,
+                        update_loop: &mut self.update_loop,
+                        interpolation_alpha: self.interpolation_alpha,
                     };
 
                     for plugin in self.plugins.iter_mut() {
@@ -2047,6 +2707,9 @@ impl Engine {
                     }
                 }
             }
+        } else {
+            // Nobody is interested in these events.
+            self.resource_reload_events.clear();
         }
 
         self.performance_statistics.plugins_time = instant::Instant::now() - time;
@@ -2055,7 +2718,7 @@ impl Engine {
     fn post_update_plugins(
         &mut self,
         dt: f32,
-        window_target: &EventLoopWindowTarget<()>,
+        window_target: Option<&EventLoopWindowTarget<()>>,
         lag: &mut f32,
     ) {
         let time = instant::Instant::now();
@@ -2073,9 +2736,14 @@ impl Engine {
                 performance_statistics: &self.performance_statistics,
                 elapsed_time: self.elapsed_time,
                 script_processor: &self.script_processor,
+                message_bus: &self.script_processor.message_bus,
+                blackboard: &self.script_processor.blackboard,
+                localization: &self.script_processor.localization,
                 async_scene_loader: &mut self.async_scene_loader,
-                window_target: Some(window_target),
+                window_target,
                 task_pool: &mut self.task_pool,
+                update_loop: &mut self.update_loop,
+                interpolation_alpha: self.interpolation_alpha,
             };
 
             for plugin in self.plugins.iter_mut() {
@@ -2109,9 +2777,14 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        message_bus: &self.script_processor.message_bus,
+                        blackboard: &self.script_processor.blackboard,
+                        localization: &self.script_processor.localization,
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target: Some(window_target),
                         task_pool: &mut self.task_pool,
+                        update_loop: &mut self.update_loop,
+                        interpolation_alpha: self.interpolation_alpha,
                     },
                 );
             }
@@ -2138,9 +2811,14 @@ impl Engine {
                     performance_statistics: &self.performance_statistics,
                     elapsed_time: self.elapsed_time,
                     script_processor: &self.script_processor,
+                    message_bus: &self.script_processor.message_bus,
+                    blackboard: &self.script_processor.blackboard,
+                    localization: &self.script_processor.localization,
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    update_loop: &mut self.update_loop,
+                    interpolation_alpha: self.interpolation_alpha,
                 });
             }
         }
@@ -2166,14 +2844,56 @@ impl Engine {
                     performance_statistics: &self.performance_statistics,
                     elapsed_time: self.elapsed_time,
                     script_processor: &self.script_processor,
+                    message_bus: &self.script_processor.message_bus,
+                    blackboard: &self.script_processor.blackboard,
+                    localization: &self.script_processor.localization,
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    update_loop: &mut self.update_loop,
+                    interpolation_alpha: self.interpolation_alpha,
                 });
             }
         }
     }
 
+    pub(crate) fn handle_lifecycle_event_by_plugins(
+        &mut self,
+        event: LifecycleEvent,
+        dt: f32,
+        window_target: &EventLoopWindowTarget<()>,
+        lag: &mut f32,
+    ) {
+        if self.plugins_enabled {
+            for plugin in self.plugins.iter_mut() {
+                plugin.on_lifecycle_event(
+                    event,
+                    PluginContext {
+                        scenes: &mut self.scenes,
+                        resource_manager: &self.resource_manager,
+                        graphics_context: &mut self.graphics_context,
+                        dt,
+                        lag,
+                        user_interfaces: &mut self.user_interfaces,
+                        serialization_context: &self.serialization_context,
+                        widget_constructors: &self.widget_constructors,
+                        performance_statistics: &self.performance_statistics,
+                        elapsed_time: self.elapsed_time,
+                        script_processor: &self.script_processor,
+                        message_bus: &self.script_processor.message_bus,
+                        blackboard: &self.script_processor.blackboard,
+                        localization: &self.script_processor.localization,
+                        async_scene_loader: &mut self.async_scene_loader,
+                        window_target: Some(window_target),
+                        task_pool: &mut self.task_pool,
+                        update_loop: &mut self.update_loop,
+                        interpolation_alpha: self.interpolation_alpha,
+                    },
+                );
+            }
+        }
+    }
+
     pub(crate) fn handle_before_rendering_by_plugins(
         &mut self,
         dt: f32,
@@ -2194,9 +2914,47 @@ impl Engine {
                     performance_statistics: &self.performance_statistics,
                     elapsed_time: self.elapsed_time,
                     script_processor: &self.script_processor,
+                    message_bus: &self.script_processor.message_bus,
+                    blackboard: &self.script_processor.blackboard,
+                    localization: &self.script_processor.localization,
+                    async_scene_loader: &mut self.async_scene_loader,
+                    window_target: Some(window_target),
+                    task_pool: &mut self.task_pool,
+                    update_loop: &mut self.update_loop,
+                    interpolation_alpha: self.interpolation_alpha,
+                });
+            }
+        }
+    }
+
+    pub(crate) fn handle_after_rendering_by_plugins(
+        &mut self,
+        dt: f32,
+        window_target: &EventLoopWindowTarget<()>,
+        lag: &mut f32,
+    ) {
+        if self.plugins_enabled {
+            for plugin in self.plugins.iter_mut() {
+                plugin.after_rendering(PluginContext {
+                    scenes: &mut self.scenes,
+                    resource_manager: &self.resource_manager,
+                    graphics_context: &mut self.graphics_context,
+                    dt,
+                    lag,
+                    user_interfaces: &mut self.user_interfaces,
+                    serialization_context: &self.serialization_context,
+                    widget_constructors: &self.widget_constructors,
+                    performance_statistics: &self.performance_statistics,
+                    elapsed_time: self.elapsed_time,
+                    script_processor: &self.script_processor,
+                    message_bus: &self.script_processor.message_bus,
+                    blackboard: &self.script_processor.blackboard,
+                    localization: &self.script_processor.localization,
                     async_scene_loader: &mut self.async_scene_loader,
                     window_target: Some(window_target),
                     task_pool: &mut self.task_pool,
+                    update_loop: &mut self.update_loop,
+                    interpolation_alpha: self.interpolation_alpha,
                 });
             }
         }
@@ -2230,11 +2988,16 @@ impl Engine {
                     &self.resource_manager,
                     &scripted_scene.message_sender,
                     &mut scripted_scene.message_dispatcher,
+                    &self.script_processor.message_bus,
+                    &self.script_processor.blackboard,
+                    &self.script_processor.localization,
+                    &mut scripted_scene.scheduler,
                     &mut self.task_pool,
                     &mut self.graphics_context,
                     &mut self.user_interfaces,
                     dt,
                     self.elapsed_time,
+                    self.interpolation_alpha,
                     |script, context| {
                         if script.initialized && script.started {
                             script.on_os_event(event, context);
@@ -2251,6 +3014,10 @@ impl Engine {
     /// You should only call this manually if you don't use that method.
     pub fn handle_model_events(&mut self) {
         while let Ok(event) = self.model_events_receiver.try_recv() {
+            if let ResourceEvent::Reloaded(_) | ResourceEvent::DependencyReloaded { .. } = event {
+                self.resource_reload_events.push(event.clone());
+            }
+
             if let ResourceEvent::Reloaded(resource) = event {
                 if let Some(model) = resource.try_cast::<Model>() {
                     Log::info(format!(
@@ -2299,6 +3066,24 @@ impl Engine {
                     .map(|ui| ui.get_drawing_context()),
                 &ctx.window,
             )?;
+
+            for window in ctx.windows.iter_mut() {
+                window.user_interface.set_time(self.elapsed_time);
+                window.user_interface.draw();
+
+                let scene = window.scene();
+                let scene = self
+                    .scenes
+                    .try_get(scene)
+                    .filter(|s| *s.enabled)
+                    .map(|_| scene);
+
+                ctx.renderer.render_secondary_window_and_swap_buffers(
+                    &window.window,
+                    scene,
+                    window.user_interface.get_drawing_context(),
+                )?;
+            }
         }
 
         Ok(())
@@ -2331,9 +3116,14 @@ impl Engine {
                             performance_statistics: &self.performance_statistics,
                             elapsed_time: self.elapsed_time,
                             script_processor: &self.script_processor,
+                            message_bus: &self.script_processor.message_bus,
+                            blackboard: &self.script_processor.blackboard,
+                            localization: &self.script_processor.localization,
                             async_scene_loader: &mut self.async_scene_loader,
                             window_target,
                             task_pool: &mut self.task_pool,
+                            update_loop: &mut self.update_loop,
+                            interpolation_alpha: self.interpolation_alpha,
                         },
                     );
                 }
@@ -2354,9 +3144,14 @@ impl Engine {
                         performance_statistics: &self.performance_statistics,
                         elapsed_time: self.elapsed_time,
                         script_processor: &self.script_processor,
+                        message_bus: &self.script_processor.message_bus,
+                        blackboard: &self.script_processor.blackboard,
+                        localization: &self.script_processor.localization,
                         async_scene_loader: &mut self.async_scene_loader,
                         window_target,
                         task_pool: &mut self.task_pool,
+                        update_loop: &mut self.update_loop,
+                        interpolation_alpha: self.interpolation_alpha,
                     });
                 }
             }
@@ -2463,6 +3258,28 @@ impl Engine {
         }
         plugin.prepare_to_reload();
 
+        plugin.as_loaded_mut().on_before_reload(PluginContext {
+            scenes: &mut self.scenes,
+            resource_manager: &self.resource_manager,
+            user_interfaces: &mut self.user_interfaces,
+            graphics_context: &mut self.graphics_context,
+            dt,
+            lag,
+            serialization_context: &self.serialization_context,
+            widget_constructors: &self.widget_constructors,
+            performance_statistics: &Default::default(),
+            elapsed_time: self.elapsed_time,
+            script_processor: &self.script_processor,
+            message_bus: &self.script_processor.message_bus,
+            blackboard: &self.script_processor.blackboard,
+            localization: &self.script_processor.localization,
+            async_scene_loader: &mut self.async_scene_loader,
+            window_target: Some(window_target),
+            task_pool: &mut self.task_pool,
+            update_loop: &mut self.update_loop,
+            interpolation_alpha: self.interpolation_alpha,
+        });
+
         let plugin_type_id = plugin.as_loaded_ref().type_id();
         let plugin_assembly_name = plugin.as_loaded_ref().assembly_name();
 
@@ -2659,9 +3476,14 @@ impl Engine {
             performance_statistics: &Default::default(),
             elapsed_time: self.elapsed_time,
             script_processor: &self.script_processor,
+            message_bus: &self.script_processor.message_bus,
+            blackboard: &self.script_processor.blackboard,
+            localization: &self.script_processor.localization,
             async_scene_loader: &mut self.async_scene_loader,
             window_target: Some(window_target),
             task_pool: &mut self.task_pool,
+            update_loop: &mut self.update_loop,
+            interpolation_alpha: self.interpolation_alpha,
         });
 
         Log::info(format!("Plugin {plugin_index} was successfully reloaded!"));
@@ -2940,6 +3762,7 @@ mod test {
                 &mut user_interfaces,
                 0.0,
                 0.0,
+                0.0,
             );
 
             match iteration {
@@ -3105,6 +3928,7 @@ mod test {
                 &mut user_interfaces,
                 0.0,
                 0.0,
+                0.0,
             );
 
             match iteration {
@@ -3127,24 +3951,118 @@ mod test {
         }
     }
 
+    #[derive(Debug, Clone, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+    #[type_uuid(id = "c1f3e0a6-7d3b-4a8e-9f0b-2f6d5e1c8a47")]
+    struct OrderedScript {
+        #[reflect(hidden)]
+        #[visit(skip)]
+        sender: Sender<i32>,
+        order: i32,
+    }
+
+    impl ScriptTrait for OrderedScript {
+        fn on_update(&mut self, _ctx: &mut ScriptContext) {
+            self.sender.send(self.order).unwrap();
+        }
+
+        fn execution_order(&self) -> i32 {
+            self.order
+        }
+    }
+
+    #[test]
+    fn test_execution_order() {
+        let resource_manager = ResourceManager::new(Arc::new(Default::default()));
+        let mut scene = Scene::new();
+
+        let (tx, rx) = mpsc::channel();
+
+        for order in [100, -100, 0] {
+            PivotBuilder::new(BaseBuilder::new().with_script(OrderedScript {
+                sender: tx.clone(),
+                order,
+            }))
+            .build(&mut scene.graph);
+        }
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_script(OrderedScript {
+                    sender: tx.clone(),
+                    order: 50,
+                })
+                .with_script(OrderedScript {
+                    sender: tx,
+                    order: -50,
+                }),
+        )
+        .build(&mut scene.graph);
+
+        let mut scene_container = SceneContainer::new(Default::default());
+        let scene_handle = scene_container.add(scene);
+        let mut script_processor = ScriptProcessor::default();
+        script_processor.register_scripted_scene(scene_handle, &resource_manager);
+
+        let mut task_pool = TaskPoolHandler::new(Arc::new(TaskPool::new()));
+        let mut gc = GraphicsContext::Uninitialized(Default::default());
+        let mut user_interfaces = UiContainer::default();
+
+        for _ in 0..2 {
+            script_processor.handle_scripts(
+                &mut scene_container,
+                &mut Vec::new(),
+                &resource_manager,
+                &mut task_pool,
+                &mut gc,
+                &mut user_interfaces,
+                0.0,
+                0.0,
+                0.0,
+            );
+
+            assert_eq!(rx.try_iter().collect::<Vec<_>>(), [-100, -50, 0, 50, 100]);
+
+            let stats = script_processor
+                .statistics
+                .get(&OrderedScript::type_uuid())
+                .unwrap();
+            assert_eq!(stats.update_count, 5);
+            assert_eq!(stats.message_count, 0);
+        }
+    }
+
     #[derive(Clone, Debug, PartialEq, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
     #[type_uuid(id = "7bcbf9b4-9546-42d3-965a-de055ab85475")]
     pub struct ScriptSpawningAsyncTasks {
         num: Option<u32>,
+        message: Option<u32>,
     }
 
+    #[derive(Debug)]
+    struct AsyncTaskMessage(u32);
+
     impl ScriptTrait for ScriptSpawningAsyncTasks {
         fn on_start(&mut self, ctx: &mut ScriptContext) {
-            ctx.task_pool.spawn_script_task(
-                ctx.scene_handle,
-                ctx.handle,
-                ctx.script_index,
+            ctx.spawn_task(
                 async move { 123u32 },
                 |result, script: &mut ScriptSpawningAsyncTasks, _ctx| {
                     assert_eq!(result, 123u32);
                     script.num = Some(result);
                 },
-            )
+            );
+
+            ctx.message_dispatcher
+                .subscribe_to::<AsyncTaskMessage>(ctx.handle);
+            ctx.spawn_task_with_message(async move { AsyncTaskMessage(321) });
+        }
+
+        fn on_message(
+            &mut self,
+            message: &mut dyn ScriptMessagePayload,
+            _ctx: &mut ScriptMessageContext,
+        ) {
+            if let Some(AsyncTaskMessage(value)) = message.downcast_ref::<AsyncTaskMessage>() {
+                self.message = Some(*value);
+            }
         }
     }
 
@@ -3175,6 +4093,7 @@ mod test {
             widget_constructors: Arc::new(Default::default()),
             resource_manager: ResourceManager::new(task_pool.clone()),
             task_pool,
+            update_loop: Default::default(),
         })
         .unwrap();
         engine.enable_plugins(None, true, None);
@@ -3183,7 +4102,10 @@ mod test {
 
         let handle = PivotBuilder::new(
             BaseBuilder::new()
-                .with_script(ScriptSpawningAsyncTasks { num: None })
+                .with_script(ScriptSpawningAsyncTasks {
+                    num: None,
+                    message: None,
+                })
                 .with_script(ScriptWithoutAsyncTasks {}),
         )
         .build(&mut scene.graph);
@@ -3207,7 +4129,10 @@ mod test {
             scripts
                 .next()
                 .and_then(|s| s.cast::<ScriptSpawningAsyncTasks>()),
-            Some(&ScriptSpawningAsyncTasks { num: Some(123) })
+            Some(&ScriptSpawningAsyncTasks {
+                num: Some(123),
+                message: Some(321),
+            })
         );
         assert_eq!(
             scripts
@@ -3368,6 +4293,7 @@ mod test {
                 &mut user_interfaces,
                 0.0,
                 0.0,
+                0.0,
             );
 
             match iteration {
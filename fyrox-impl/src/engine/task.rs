@@ -23,16 +23,23 @@
 use crate::plugin::PluginContainer;
 use crate::{
     core::{
+        futures::channel::oneshot,
         pool::Handle,
         task::{AsyncTask, AsyncTaskResult, TaskPool},
         uuid::Uuid,
     },
     plugin::{Plugin, PluginContext},
     scene::{node::Node, Scene},
-    script::{ScriptContext, ScriptTrait},
+    script::{ScriptContext, ScriptMessagePayload, ScriptTrait},
 };
 use fxhash::FxHashMap;
-use std::sync::Arc;
+use std::{
+    future::Future,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+};
 
 pub(crate) type NodeTaskHandlerClosure = Box<
     dyn for<'a, 'b, 'c> Fn(
@@ -50,6 +57,75 @@ pub(crate) type PluginTaskHandler = Box<
     ),
 >;
 
+pub(crate) type MainThreadCall = Box<dyn for<'a, 'b> FnOnce(&mut PluginContext<'a, 'b>) + Send>;
+
+/// A handle, that allows async code running outside the engine (on the engine's task pool or on
+/// any other executor, for example - tokio) to run closures on the main thread with full access
+/// to the engine via [`PluginContext`]. It is cheap to clone and could be sent to other threads.
+///
+/// Every closure is executed at the beginning of the next game loop iteration, together with
+/// the "on-complete" closures of the tasks. The result of the closure is returned back to the
+/// caller via a future.
+///
+/// ## Example
+///
+/// ```rust ,no_run
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     engine::task::MainThreadDispatcher,
+/// #     asset::manager::ResourceManager,
+/// #     resource::model::{Model, ModelResourceExtension},
+/// #     scene::{node::Node, Scene},
+/// # };
+/// async fn spawn_enemy(
+///     resource_manager: ResourceManager,
+///     dispatcher: MainThreadDispatcher,
+///     scene: Handle<Scene>,
+/// ) -> Option<Handle<Node>> {
+///     // Resources are futures, so they could be awaited on any thread.
+///     let model = resource_manager.request::<Model>("enemy.fbx").await.ok()?;
+///     // Scenes could only be modified on the main thread.
+///     dispatcher
+///         .run(move |ctx| {
+///             let scene = ctx.scenes.try_get_mut(scene)?;
+///             Some(model.instantiate(scene))
+///         })
+///         .await
+///         .flatten()
+/// }
+/// ```
+#[derive(Clone)]
+pub struct MainThreadDispatcher {
+    sender: Sender<MainThreadCall>,
+}
+
+impl MainThreadDispatcher {
+    /// Schedules the given closure for execution on the main thread and returns a future, that
+    /// resolves to the closure's result. The future resolves to `None` if the engine was destroyed
+    /// before the closure was executed.
+    pub fn run<F, R>(&self, func: F) -> impl Future<Output = Option<R>> + Send + 'static
+    where
+        F: for<'a, 'b> FnOnce(&mut PluginContext<'a, 'b>) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (result_sender, result_receiver) = oneshot::channel();
+        let _ = self.sender.send(Box::new(move |context| {
+            // The receiver could be dropped if the caller is not interested in the result anymore.
+            let _ = result_sender.send(func(context));
+        }));
+        async move { result_receiver.await.ok() }
+    }
+
+    /// Schedules the given closure for execution on the main thread without waiting for its
+    /// completion.
+    pub fn post<F>(&self, func: F)
+    where
+        F: for<'a, 'b> FnOnce(&mut PluginContext<'a, 'b>) + Send + 'static,
+    {
+        let _ = self.sender.send(Box::new(func));
+    }
+}
+
 pub(crate) struct NodeTaskHandler {
     pub(crate) scene_handle: Handle<Scene>,
     pub(crate) node_handle: Handle<Node>,
@@ -76,18 +152,31 @@ pub(crate) struct NodeTaskHandler {
 /// on a scene node basis - when a task is done, the "on-complete" closure will be provided with a
 /// wide context, allowing you to modify the caller's node state. See the docs for the respective
 /// methods for more info.
+///
+/// ## External executors
+///
+/// Tasks are executed on the built-in executor of the task pool by default. It could be replaced
+/// with an executor of an async runtime (for example - tokio) using [`TaskPool::set_spawner`] of
+/// the [`Self::inner`] task pool. Results of the tasks are still delivered to the "on-complete"
+/// closures on the main thread. Async code that runs outside the engine could use
+/// [`Self::main_thread_dispatcher`] to get access to the engine state on the main thread.
 pub struct TaskPoolHandler {
     task_pool: Arc<TaskPool>,
     plugin_task_handlers: FxHashMap<Uuid, PluginTaskHandler>,
     node_task_handlers: FxHashMap<Uuid, NodeTaskHandler>,
+    main_thread_sender: Sender<MainThreadCall>,
+    main_thread_receiver: Receiver<MainThreadCall>,
 }
 
 impl TaskPoolHandler {
     pub(crate) fn new(task_pool: Arc<TaskPool>) -> Self {
+        let (main_thread_sender, main_thread_receiver) = mpsc::channel();
         Self {
             task_pool,
             plugin_task_handlers: Default::default(),
             node_task_handlers: Default::default(),
+            main_thread_sender,
+            main_thread_receiver,
         }
     }
 
@@ -141,7 +230,7 @@ impl TaskPoolHandler {
     /// }
     /// ```
     #[inline]
-    pub fn spawn_plugin_task<F, T, P, C>(&mut self, future: F, on_complete: C)
+    pub fn spawn_plugin_task<F, T, P, C>(&mut self, future: F, on_complete: C) -> Uuid
     where
         F: AsyncTask<T>,
         T: AsyncTaskResult,
@@ -160,6 +249,7 @@ impl TaskPoolHandler {
                 on_complete(*typed, plugin, context)
             }),
         );
+        task_id
     }
 
     /// Spawns a task represented by the `future`, that does something and then adds the result to
@@ -214,7 +304,8 @@ impl TaskPoolHandler {
         script_index: usize,
         future: F,
         on_complete: C,
-    ) where
+    ) -> Uuid
+    where
         F: AsyncTask<T>,
         T: AsyncTaskResult,
         for<'a, 'b, 'c> C: Fn(T, &mut S, &mut ScriptContext<'a, 'b, 'c>) + 'static,
@@ -237,6 +328,68 @@ impl TaskPoolHandler {
                 }),
             },
         );
+        task_id
+    }
+
+    /// Spawns a task represented by the `future` and sends its result as a targeted script message
+    /// to the given node when the task is finished. The message is delivered to
+    /// [`ScriptTrait::on_message`] of the node's scripts, that are subscribed to messages of type `T`.
+    /// The message is not sent if the node or the script was deleted while the task was running.
+    /// Returns an id of the task, that could be used to cancel it (see [`Self::cancel_task`]).
+    #[inline]
+    pub fn spawn_script_message_task<F, T>(
+        &mut self,
+        scene_handle: Handle<Scene>,
+        node_handle: Handle<Node>,
+        script_index: usize,
+        future: F,
+    ) -> Uuid
+    where
+        F: AsyncTask<T>,
+        T: AsyncTaskResult + ScriptMessagePayload,
+    {
+        let task_id = self.task_pool.spawn_with_result(future);
+        self.node_task_handlers.insert(
+            task_id,
+            NodeTaskHandler {
+                scene_handle,
+                node_handle,
+                script_index,
+                closure: Box::new(move |result, _script, context| {
+                    let result = result.downcast::<T>().expect("Types must match");
+                    context
+                        .message_sender
+                        .send_to_target(context.handle, *result);
+                }),
+            },
+        );
+        task_id
+    }
+
+    /// Cancels a task with the given id, spawned by [`Self::spawn_plugin_task`],
+    /// [`Self::spawn_script_task`] or [`Self::spawn_script_message_task`]. The task itself will
+    /// run to completion, but its result will be discarded. Returns `true` if the task was pending
+    /// and `false` if it was already finished or cancelled.
+    #[inline]
+    pub fn cancel_task(&mut self, task_id: Uuid) -> bool {
+        self.plugin_task_handlers.remove(&task_id).is_some()
+            || self.node_task_handlers.remove(&task_id).is_some()
+    }
+
+    /// Returns `true` if a task with the given id is still pending, `false` - otherwise.
+    #[inline]
+    pub fn is_task_pending(&self, task_id: Uuid) -> bool {
+        self.plugin_task_handlers.contains_key(&task_id)
+            || self.node_task_handlers.contains_key(&task_id)
+    }
+
+    /// Returns a new dispatcher, that could be used by async code to run closures on the main
+    /// thread. See [`MainThreadDispatcher`] docs for more info.
+    #[inline]
+    pub fn main_thread_dispatcher(&self) -> MainThreadDispatcher {
+        MainThreadDispatcher {
+            sender: self.main_thread_sender.clone(),
+        }
     }
 
     /// Returns a reference to the underlying, low level task pool, that could be used to for special
@@ -255,4 +408,9 @@ impl TaskPoolHandler {
     pub(crate) fn pop_node_task_handler(&mut self, id: Uuid) -> Option<NodeTaskHandler> {
         self.node_task_handlers.remove(&id)
     }
+
+    #[inline]
+    pub(crate) fn pop_main_thread_call(&mut self) -> Option<MainThreadCall> {
+        self.main_thread_receiver.try_recv().ok()
+    }
 }
@@ -0,0 +1,162 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Parameters of the main loop of the engine. See [`UpdateLoopSettings`] docs for more info.
+
+use crate::core::instant::Instant;
+use std::time::Duration;
+
+/// Defines how an executor waits for the next logic update or the next frame.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FramePacing {
+    /// The thread sleeps until the next update or frame. It has the lowest CPU usage, but the
+    /// precision of timing depends on the OS scheduler (usually 1-2 milliseconds).
+    #[default]
+    Sleep,
+    /// The thread spins until the next update or frame. It has the most precise timing, but it
+    /// occupies an entire CPU core.
+    BusyWait,
+}
+
+impl FramePacing {
+    /// Blocks the current thread until the given moment of time using the pacing strategy.
+    pub fn wait_until(self, deadline: Instant) {
+        match self {
+            FramePacing::Sleep => {
+                let now = Instant::now();
+                if deadline > now {
+                    std::thread::sleep(deadline - now);
+                }
+            }
+            FramePacing::BusyWait => {
+                while Instant::now() < deadline {
+                    std::hint::spin_loop();
+                }
+            }
+        }
+    }
+}
+
+/// Parameters of the main loop of the engine. The game logic (plugins, scripts, physics, etc.) is
+/// updated with a fixed time step, while the rendering is done as often as possible (or limited
+/// by [`Self::max_frame_rate`]). The real time that passed between frames is accumulated in a
+/// "lag" variable, which is then split into a number of fixed time steps. The remainder of the lag
+/// defines an interpolation alpha (see [`Self::interpolation_alpha`]), that could be used to
+/// smooth out the movement of objects between logic updates.
+///
+/// The settings could be set on engine initialization (see [`super::EngineInitParams`]) and
+/// changed at runtime (see [`super::Engine::update_loop`] and
+/// [`crate::plugin::PluginContext::update_loop`]).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct UpdateLoopSettings {
+    /// Fixed rate of game logic updates (in updates per second). Default is 60.
+    pub update_rate: f32,
+
+    /// Maximum amount of fixed updates, that could be performed in a row when the game logic lags
+    /// behind the real time. The rest of the lag is dropped, which prevents the game from hanging
+    /// up when its logic takes too much time. Default is 5.
+    pub max_catch_up_steps: usize,
+
+    /// The lag threshold (in seconds), at which the executor will stop trying to stabilize the
+    /// update rate of the game logic and will perform a single update with the increased time step
+    /// instead. The default value is two fixed time steps (33.3(3) milliseconds).
+    ///
+    /// Physics could suffer from variable time step which may result in objects falling through the
+    /// ground and some other nasty things. Throttle threshold should be at reasonably high levels
+    /// (usually 2x-3x of the fixed time step).
+    pub throttle_threshold: f32,
+
+    /// The amount of consecutive frames that will be allowed to have lag spikes without modifying the
+    /// time step. See [`Self::throttle_threshold`] for more info. Default is 5 frames.
+    pub throttle_frame_interval: usize,
+
+    /// Maximum amount of rendered frames per second. `None` means that the frame rate is limited by
+    /// vertical synchronization only. Default is `None`.
+    pub max_frame_rate: Option<f32>,
+
+    /// Defines how the executor waits for the next update or frame. See [`FramePacing`] docs for
+    /// more info. Default is [`FramePacing::Sleep`].
+    pub frame_pacing: FramePacing,
+}
+
+impl Default for UpdateLoopSettings {
+    fn default() -> Self {
+        Self {
+            update_rate: Self::DEFAULT_UPDATE_RATE,
+            max_catch_up_steps: 5,
+            throttle_threshold: 2.0 / Self::DEFAULT_UPDATE_RATE,
+            throttle_frame_interval: 5,
+            max_frame_rate: None,
+            frame_pacing: Default::default(),
+        }
+    }
+}
+
+impl UpdateLoopSettings {
+    /// Default update rate in updates per second.
+    pub const DEFAULT_UPDATE_RATE: f32 = 60.0;
+
+    /// Returns the time step (in seconds) of the game logic.
+    pub fn fixed_time_step(&self) -> f32 {
+        1.0 / self.update_rate.abs().max(0.001)
+    }
+
+    /// Returns the minimal interval between frames, if the frame rate is limited.
+    pub fn frame_interval(&self) -> Option<Duration> {
+        self.max_frame_rate
+            .filter(|rate| *rate > 0.0)
+            .map(|rate| Duration::from_secs_f32(1.0 / rate))
+    }
+
+    /// Clamps the given lag (in seconds), so it does not exceed [`Self::max_catch_up_steps`] fixed
+    /// time steps.
+    pub fn clamp_lag(&self, lag: f32) -> f32 {
+        lag.min(self.fixed_time_step() * self.max_catch_up_steps.max(1) as f32)
+    }
+
+    /// Calculates the interpolation alpha for the given lag. The alpha is a fraction of the fixed
+    /// time step, that was accumulated but not simulated yet, it is always in `[0; 1)` range. Visual
+    /// state of an object could be interpolated between its previous and current logic states with
+    /// this alpha to get smooth movement when the frame rate is higher than the update rate.
+    pub fn interpolation_alpha(&self, lag: f32) -> f32 {
+        let time_step = self.fixed_time_step();
+        (lag.max(0.0) % time_step / time_step).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::engine::update_loop::UpdateLoopSettings;
+
+    #[test]
+    fn test_lag_and_alpha() {
+        let settings = UpdateLoopSettings {
+            update_rate: 10.0,
+            max_catch_up_steps: 3,
+            ..Default::default()
+        };
+        assert_eq!(settings.fixed_time_step(), 0.1);
+        assert_eq!(settings.clamp_lag(1.0), 0.3);
+        assert_eq!(settings.clamp_lag(0.25), 0.25);
+        assert!((settings.interpolation_alpha(0.25) - 0.5).abs() < 1.0e-4);
+        assert_eq!(settings.interpolation_alpha(-1.0), 0.0);
+        assert_eq!(settings.frame_interval(), None);
+    }
+}
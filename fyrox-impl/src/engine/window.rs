@@ -0,0 +1,192 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Secondary OS windows. See [`WindowContainer`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector2, pool::Handle},
+    gui::UserInterface,
+    resource::texture::{TextureKind, TextureResource, TextureResourceExtension},
+    scene::{Scene, SceneContainer},
+    window::{Window, WindowId},
+};
+
+/// A secondary OS window, that has its own swapchain and user interface and could optionally show a
+/// scene. Main use of such windows is tool applications and multi-monitor setups.
+pub struct SecondaryWindow {
+    /// OS window.
+    pub window: Window,
+    /// User interface of the window. It receives input events of the window only and it is drawn
+    /// on top of the scene of the window (if any).
+    pub user_interface: UserInterface,
+    scene: Handle<Scene>,
+    render_target: Option<(Handle<Scene>, TextureResource)>,
+}
+
+impl SecondaryWindow {
+    /// Returns unique id of the window.
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    /// Returns size of the client area of the window in pixels.
+    pub fn frame_size(&self) -> Vector2<f32> {
+        let inner_size = self.window.inner_size();
+        Vector2::new(inner_size.width as f32, inner_size.height as f32)
+    }
+
+    /// Returns a handle of the scene, that is shown in the window. Could be [`Handle::NONE`].
+    pub fn scene(&self) -> Handle<Scene> {
+        self.scene
+    }
+
+    /// Sets a scene, that will be shown in the window. The engine will render the scene into a
+    /// render target of the size of the window (replacing the render target of the scene), so the
+    /// scene disappears from the main window. Cameras of the scene should use viewports as usual.
+    /// Use [`Handle::NONE`] to show the user interface only.
+    pub fn set_scene(&mut self, scene: Handle<Scene>) {
+        self.scene = scene;
+    }
+}
+
+/// A container for secondary OS windows. Every window has its own swapchain and
+/// [`UserInterface`] and could show a scene (see [`SecondaryWindow::set_scene`]). The container is
+/// a part of [`super::InitializedGraphicsContext`], so the windows are destroyed with the graphics
+/// context.
+///
+/// OS events of each window are routed to its user interface, while the events of the main window
+/// are routed to the user interfaces of the engine. Plugins receive the events of every window, use
+/// `window_id` of [`crate::event::Event::WindowEvent`] to distinguish them. A secondary window is
+/// destroyed when a user closes it.
+#[derive(Default)]
+pub struct WindowContainer {
+    windows: Vec<SecondaryWindow>,
+    // Render targets, that were assigned by the container to scenes that are no longer shown in any
+    // window. They are removed from the scenes on the next sync.
+    released_render_targets: Vec<(Handle<Scene>, TextureResource)>,
+}
+
+impl WindowContainer {
+    pub(crate) fn add(&mut self, window: Window) -> WindowId {
+        let id = window.id();
+        let inner_size = window.inner_size();
+        self.windows.push(SecondaryWindow {
+            window,
+            user_interface: UserInterface::new(Vector2::new(
+                inner_size.width as f32,
+                inner_size.height as f32,
+            )),
+            scene: Handle::NONE,
+            render_target: None,
+        });
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: WindowId) -> Option<SecondaryWindow> {
+        let index = self.windows.iter().position(|window| window.id() == id)?;
+        let mut window = self.windows.remove(index);
+        if let Some(render_target) = window.render_target.take() {
+            self.released_render_targets.push(render_target);
+        }
+        Some(window)
+    }
+
+    /// Returns `true` if the container has a window with the given id.
+    pub fn contains(&self, id: WindowId) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Tries to borrow a window using its id.
+    pub fn get(&self, id: WindowId) -> Option<&SecondaryWindow> {
+        self.windows.iter().find(|window| window.id() == id)
+    }
+
+    /// Tries to borrow a window using its id.
+    pub fn get_mut(&mut self, id: WindowId) -> Option<&mut SecondaryWindow> {
+        self.windows.iter_mut().find(|window| window.id() == id)
+    }
+
+    /// Returns an iterator over the windows.
+    pub fn iter(&self) -> impl Iterator<Item = &SecondaryWindow> {
+        self.windows.iter()
+    }
+
+    /// Returns an iterator over the windows.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut SecondaryWindow> {
+        self.windows.iter_mut()
+    }
+
+    /// Returns total amount of secondary windows.
+    pub fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    /// Returns `true` if there's no secondary windows.
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// Makes sure that every scene shown in a window has a render target of the size of the window
+    /// and removes render targets from scenes that are no longer shown in any window.
+    pub(crate) fn sync_render_targets(&mut self, scenes: &mut SceneContainer) {
+        for window in self.windows.iter_mut() {
+            if let Some((scene, _)) = window.render_target.as_ref() {
+                if *scene != window.scene || !scenes.is_valid_handle(window.scene) {
+                    self.released_render_targets
+                        .extend(window.render_target.take());
+                }
+            }
+
+            let Some(scene) = scenes.try_get_mut(window.scene) else {
+                continue;
+            };
+
+            let inner_size = window.window.inner_size();
+            let (width, height) = (inner_size.width.max(1), inner_size.height.max(1));
+
+            let is_up_to_date = window.render_target.as_ref().is_some_and(|(_, rt)| {
+                let TextureKind::Rectangle {
+                    width: rt_width,
+                    height: rt_height,
+                } = rt.data_ref().kind()
+                else {
+                    return false;
+                };
+                rt_width == width
+                    && rt_height == height
+                    && scene.rendering_options.render_target.as_ref() == Some(rt)
+            });
+
+            if !is_up_to_date {
+                let render_target = TextureResource::new_render_target(width, height);
+                scene.rendering_options.render_target = Some(render_target.clone());
+                window.render_target = Some((window.scene, render_target));
+            }
+        }
+
+        for (scene, render_target) in self.released_render_targets.drain(..) {
+            if let Some(scene) = scenes.try_get_mut(scene) {
+                if scene.rendering_options.render_target.as_ref() == Some(&render_target) {
+                    scene.rendering_options.render_target = None;
+                }
+            }
+        }
+    }
+}
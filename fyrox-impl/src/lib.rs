@@ -33,13 +33,19 @@
 #![allow(clippy::doc_lazy_continuation)]
 #![allow(clippy::mutable_key_type)]
 
+pub mod console;
 pub mod engine;
 pub mod material;
+pub mod modding;
+pub mod platform;
 pub mod plugin;
 pub mod renderer;
+pub mod replay;
 pub mod resource;
+pub mod save;
 pub mod scene;
 pub mod script;
+pub mod settings;
 pub mod utils;
 
 pub use crate::core::rand;
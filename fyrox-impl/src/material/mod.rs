@@ -34,24 +34,27 @@ use crate::{
     },
     core::{
         algebra::{Matrix2, Matrix3, Matrix4, Vector2, Vector3, Vector4},
+        array_as_u8_slice,
         color::Color,
         io::FileLoadError,
         parking_lot::Mutex,
         reflect::prelude::*,
         sstorage::ImmutableString,
         uuid::{uuid, Uuid},
+        value_as_u8_slice,
         visitor::{prelude::*, RegionGuard},
         TypeUuidProvider,
     },
     material::shader::{SamplerFallback, ShaderResource, ShaderResourceExtension},
     resource::texture::TextureResource,
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHasher};
 use fyrox_core::Downcast;
 use lazy_static::lazy_static;
 use std::{
     error::Error,
     fmt::{Display, Formatter},
+    hash::{Hash, Hasher},
     path::Path,
     sync::Arc,
 };
@@ -412,6 +415,32 @@ impl MaterialProperty {
         /// Tries to unwrap property value as 4x4 matrix array.
         as_matrix4_array = Matrix4Array -> [Matrix4<f32>]
     );
+
+    fn hash_content(&self, hasher: &mut impl Hasher) {
+        std::mem::discriminant(self).hash(hasher);
+        match self {
+            MaterialProperty::Float(v) => hasher.write(value_as_u8_slice(v)),
+            MaterialProperty::FloatArray(v) => hasher.write(array_as_u8_slice(v)),
+            MaterialProperty::Int(v) => hasher.write(value_as_u8_slice(v)),
+            MaterialProperty::IntArray(v) => hasher.write(array_as_u8_slice(v)),
+            MaterialProperty::UInt(v) => hasher.write(value_as_u8_slice(v)),
+            MaterialProperty::UIntArray(v) => hasher.write(array_as_u8_slice(v)),
+            MaterialProperty::Vector2(v) => hasher.write(value_as_u8_slice(v)),
+            MaterialProperty::Vector2Array(v) => hasher.write(array_as_u8_slice(v)),
+            MaterialProperty::Vector3(v) => hasher.write(value_as_u8_slice(v)),
+            MaterialProperty::Vector3Array(v) => hasher.write(array_as_u8_slice(v)),
+            MaterialProperty::Vector4(v) => hasher.write(value_as_u8_slice(v)),
+            MaterialProperty::Vector4Array(v) => hasher.write(array_as_u8_slice(v)),
+            MaterialProperty::Matrix2(v) => hasher.write(value_as_u8_slice(v)),
+            MaterialProperty::Matrix2Array(v) => hasher.write(array_as_u8_slice(v)),
+            MaterialProperty::Matrix3(v) => hasher.write(value_as_u8_slice(v)),
+            MaterialProperty::Matrix3Array(v) => hasher.write(array_as_u8_slice(v)),
+            MaterialProperty::Matrix4(v) => hasher.write(value_as_u8_slice(v)),
+            MaterialProperty::Matrix4Array(v) => hasher.write(array_as_u8_slice(v)),
+            MaterialProperty::Bool(v) => hasher.write_u8(*v as u8),
+            MaterialProperty::Color(v) => hasher.write(value_as_u8_slice(v)),
+        }
+    }
 }
 
 impl Default for MaterialProperty {
@@ -796,6 +825,11 @@ impl Material {
         Self::from_shader(ShaderResource::standard_tile())
     }
 
+    /// Creates new instance of standard instanced tile material.
+    pub fn standard_tile_instanced() -> Self {
+        Self::from_shader(ShaderResource::standard_tile_instanced())
+    }
+
     /// Creates a new material instance with given shader. By default, a material does not store any
     /// resource bindings. In this case the renderer will use shader default values for rendering.
     /// Materials could be considered as container with values that overwrites shader values.
@@ -1053,6 +1087,45 @@ impl Material {
         &self.resource_bindings
     }
 
+    /// Calculates a hash of the shader and all resource bindings of the material. Two materials
+    /// with the same content hash are interchangeable for rendering, even if they are separate
+    /// resources. It is used by the renderer to merge draw calls of 2D nodes.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = FxHasher::default();
+        hasher.write_u64(self.shader.key());
+        // Iteration order of hash maps depends on their history, so every binding is hashed
+        // separately and the hashes are combined in an order-independent way.
+        let bindings_hash =
+            self.resource_bindings
+                .iter()
+                .map(|(name, binding)| {
+                    let mut hasher = FxHasher::default();
+                    name.hash(&mut hasher);
+                    match binding {
+                        MaterialResourceBinding::Texture(binding) => hasher
+                            .write_u64(binding.value.as_ref().map_or(0, |texture| texture.key())),
+                        MaterialResourceBinding::PropertyGroup(group) => {
+                            hasher.write_u64(
+                                group
+                                    .properties
+                                    .iter()
+                                    .map(|(name, property)| {
+                                        let mut hasher = FxHasher::default();
+                                        name.hash(&mut hasher);
+                                        property.hash_content(&mut hasher);
+                                        hasher.finish()
+                                    })
+                                    .fold(0, u64::wrapping_add),
+                            );
+                        }
+                    }
+                    hasher.finish()
+                })
+                .fold(0, u64::wrapping_add);
+        hasher.write_u64(bindings_hash);
+        hasher.finish()
+    }
+
     /// Tries to find a sampler with the given name and returns its texture (if any).
     pub fn texture(&self, name: impl Into<ImmutableString>) -> Option<TextureResource> {
         self.resource_bindings.get(&name.into()).and_then(|v| {
@@ -497,6 +497,9 @@ pub const STANDARD_TERRAIN_SHADER_NAME: &str = "StandardTerrain";
 /// A name of the standard tile shader.
 pub const STANDARD_TILE_SHADER_NAME: &str = "StandardTile";
 
+/// A name of the standard instanced tile shader.
+pub const STANDARD_TILE_INSTANCED_SHADER_NAME: &str = "StandardTileInstanced";
+
 /// A name of the standard sprite shader.
 pub const STANDARD_SPRITE_SHADER_NAME: &str = "StandardSprite";
 
@@ -815,11 +818,16 @@ pub trait ShaderResourceExtension: Sized {
     /// Returns an instance of standard tile shader.
     fn standard_tile() -> Self;
 
+    /// Returns an instance of standard tile shader, that takes the data of every tile from
+    /// per-instance vertex attributes. It is used by tile maps to draw whole chunks of tiles with
+    /// a single instanced draw call.
+    fn standard_tile_instanced() -> Self;
+
     /// Returns an instance of standard two-sides terrain shader.
     fn standard_twosides() -> Self;
 
     /// Returns a list of standard shader.
-    fn standard_shaders() -> [&'static BuiltInResource<Shader>; 7];
+    fn standard_shaders() -> [&'static BuiltInResource<Shader>; 8];
 }
 
 impl ShaderResourceExtension for ShaderResource {
@@ -851,11 +859,15 @@ impl ShaderResourceExtension for ShaderResource {
         STANDARD_TILE.resource()
     }
 
+    fn standard_tile_instanced() -> Self {
+        STANDARD_TILE_INSTANCED.resource()
+    }
+
     fn standard_twosides() -> Self {
         STANDARD_TWOSIDES.resource()
     }
 
-    fn standard_shaders() -> [&'static BuiltInResource<Shader>; 7] {
+    fn standard_shaders() -> [&'static BuiltInResource<Shader>; 8] {
         [
             &STANDARD,
             &STANDARD_2D,
@@ -864,6 +876,7 @@ impl ShaderResourceExtension for ShaderResource {
             &STANDARD_TERRAIN,
             &STANDARD_TWOSIDES,
             &STANDARD_TILE,
+            &STANDARD_TILE_INSTANCED,
         ]
     }
 }
@@ -911,6 +924,13 @@ lazy_static! {
                 Shader::from_string_bytes(data).unwrap(),
             )
         });
+    static ref STANDARD_TILE_INSTANCED: BuiltInResource<Shader> = BuiltInResource::new(
+        embedded_data_source!("standard/tile_instanced.shader"),
+        |data| ShaderResource::new_ok(
+            STANDARD_TILE_INSTANCED_SHADER_NAME.into(),
+            Shader::from_string_bytes(data).unwrap(),
+        )
+    );
     static ref STANDARD_TWOSIDES: BuiltInResource<Shader> = BuiltInResource::new(
         embedded_data_source!("standard/standard-two-sides.shader"),
         |data| ShaderResource::new_ok(
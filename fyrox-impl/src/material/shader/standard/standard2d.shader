@@ -7,6 +7,23 @@
             kind: Texture(kind: Sampler2D, fallback: White),
             binding: 0
         ),
+        (
+            name: "normalTexture",
+            kind: Texture(kind: Sampler2D, fallback: Normal),
+            binding: 1
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (
+                    // Defines how much the normal map affects 2D lighting. Zero means that
+                    // the normal map is ignored.
+                    name: "normalMapStrength",
+                    kind: Float(0.0),
+                ),
+            ]),
+            binding: 3
+        ),
         (
             name: "fyrox_instanceData",
             kind: PropertyGroup([
@@ -116,6 +133,63 @@
                     FragColor = vec4(lighting, 1.0) * color * S_SRGBToLinear(texture(diffuseTexture, texCoord));
                 }
                "#,
+        ),
+        (
+            name: "Normals2D",
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec4 vertexColor;
+
+                out vec2 texCoord;
+                out vec4 color;
+
+                void main()
+                {
+                    texCoord = vertexTexCoord;
+                    gl_Position = fyrox_instanceData.worldViewProjection * vec4(vertexPosition, 1.0);
+                    color = vertexColor;
+                }
+               "#,
+
+           fragment_shader:
+               r#"
+                out vec4 FragColor;
+
+                in vec2 texCoord;
+                in vec4 color;
+
+                void main()
+                {
+                    // Transparent pixels must not overwrite normals of the pixels behind them.
+                    if (color.a * texture(diffuseTexture, texCoord).a < 0.5) {
+                        discard;
+                    }
+
+                    FragColor = vec4(texture(normalTexture, texCoord).xyz, properties.normalMapStrength);
+                }
+               "#,
         )
     ],
 )
\ No newline at end of file
@@ -0,0 +1,209 @@
+(
+    name: "StandardTileInstancedShader",
+
+    resources: [
+        (
+            name: "diffuseTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "normalTexture",
+            kind: Texture(kind: Sampler2D, fallback: Normal),
+            binding: 1
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (
+                    // Defines how much the normal map affects 2D lighting. Zero means that
+                    // the normal map is ignored.
+                    name: "normalMapStrength",
+                    kind: Float(0.0),
+                ),
+            ]),
+            binding: 3
+        ),
+        (
+            name: "fyrox_instanceData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 0
+        ),
+        (
+            name: "fyrox_lightData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 1
+        ),
+        (
+            name: "fyrox_lightsBlock",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 2
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: Some(LessOrEqual),
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: SrcAlpha,
+                        dfactor: OneMinusSrcAlpha,
+                        alpha_sfactor: SrcAlpha,
+                        alpha_dfactor: OneMinusSrcAlpha,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+            vertex_shader:
+               r#"
+                // Every attribute is fetched once per tile.
+                layout(location = 0) in vec2 tilePosition;
+                layout(location = 1) in vec4 tileTexCoords0;
+                layout(location = 2) in vec4 tileTexCoords1;
+                layout(location = 3) in vec4 tileColor;
+
+                out vec2 texCoord;
+                out vec4 color;
+                out vec3 fragmentPosition;
+
+                void main()
+                {
+                    // The indices of the two triangles of a tile select the corners of its quad.
+                    vec2 corners[4] = vec2[4](vec2(1.0, 1.0), vec2(0.0, 1.0), vec2(0.0, 0.0), vec2(1.0, 0.0));
+                    vec2 texCoords[4] = vec2[4](tileTexCoords0.xy, tileTexCoords0.zw, tileTexCoords1.xy, tileTexCoords1.zw);
+                    vec4 vertexPosition = vec4(tilePosition + corners[gl_VertexID], 0.0, 1.0);
+
+                    texCoord = texCoords[gl_VertexID] / vec2(textureSize(diffuseTexture, 0));
+                    fragmentPosition = (fyrox_instanceData.worldMatrix * vertexPosition).xyz;
+                    gl_Position = fyrox_instanceData.worldViewProjection * vertexPosition;
+                    color = tileColor;
+                }
+               "#,
+
+           fragment_shader:
+               r#"
+                out vec4 FragColor;
+
+                in vec2 texCoord;
+                in vec4 color;
+                in vec3 fragmentPosition;
+
+                void main()
+                {
+                    vec3 lighting = fyrox_lightData.ambientLightColor.xyz;
+                    for(int i = 0; i < min(fyrox_lightsBlock.lightCount, 16); ++i) {
+                        // "Unpack" light parameters.
+                        float halfHotspotAngleCos = fyrox_lightsBlock.lightsParameters[i].x;
+                        float halfConeAngleCos = fyrox_lightsBlock.lightsParameters[i].y;
+                        vec3 lightColor = fyrox_lightsBlock.lightsColorRadius[i].xyz;
+                        float radius = fyrox_lightsBlock.lightsColorRadius[i].w;
+                        vec3 lightPosition = fyrox_lightsBlock.lightsPosition[i];
+                        vec3 direction = fyrox_lightsBlock.lightsDirection[i];
+
+                        // Calculate lighting.
+                        vec3 toFragment = fragmentPosition - lightPosition;
+                        float distance = length(toFragment);
+                        vec3 toFragmentNormalized = toFragment / distance;
+                        float distanceAttenuation = S_LightDistanceAttenuation(distance, radius);
+                        float spotAngleCos = dot(toFragmentNormalized, direction);
+                        float directionalAttenuation = smoothstep(halfConeAngleCos, halfHotspotAngleCos, spotAngleCos);
+                        lighting += lightColor * (distanceAttenuation * directionalAttenuation);
+                    }
+
+                    FragColor = vec4(lighting, 1.0) * color * S_SRGBToLinear(texture(diffuseTexture, texCoord));
+                }
+               "#,
+        ),
+        (
+            name: "Normals2D",
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+            vertex_shader:
+               r#"
+                // Every attribute is fetched once per tile.
+                layout(location = 0) in vec2 tilePosition;
+                layout(location = 1) in vec4 tileTexCoords0;
+                layout(location = 2) in vec4 tileTexCoords1;
+                layout(location = 3) in vec4 tileColor;
+
+                out vec2 texCoord;
+                out vec4 color;
+
+                void main()
+                {
+                    // The indices of the two triangles of a tile select the corners of its quad.
+                    vec2 corners[4] = vec2[4](vec2(1.0, 1.0), vec2(0.0, 1.0), vec2(0.0, 0.0), vec2(1.0, 0.0));
+                    vec2 texCoords[4] = vec2[4](tileTexCoords0.xy, tileTexCoords0.zw, tileTexCoords1.xy, tileTexCoords1.zw);
+                    vec4 vertexPosition = vec4(tilePosition + corners[gl_VertexID], 0.0, 1.0);
+
+                    texCoord = texCoords[gl_VertexID] / vec2(textureSize(diffuseTexture, 0));
+                    gl_Position = fyrox_instanceData.worldViewProjection * vertexPosition;
+                    color = tileColor;
+                }
+               "#,
+
+           fragment_shader:
+               r#"
+                out vec4 FragColor;
+
+                in vec2 texCoord;
+                in vec4 color;
+
+                void main()
+                {
+                    // Transparent pixels must not overwrite normals of the pixels behind them.
+                    if (color.a * texture(diffuseTexture, texCoord).a < 0.5) {
+                        discard;
+                    }
+
+                    FragColor = vec4(texture(normalTexture, texCoord).xyz, properties.normalMapStrength);
+                }
+               "#,
+        )
+    ],
+)
\ No newline at end of file
@@ -0,0 +1,281 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Mod manifest format. See [`ModManifest`] docs for more info.
+
+use crate::modding::ModError;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Name of the manifest file, that must be located in the root directory of every mod.
+pub const MOD_MANIFEST_FILE_NAME: &str = "mod.ron";
+
+/// Version of a mod in `major.minor.patch` form. Missing components are treated as zeros.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(try_from = "String", into = "String")]
+pub struct ModVersion {
+    /// Major version. Mods with different major versions are considered incompatible.
+    pub major: u32,
+    /// Minor version.
+    pub minor: u32,
+    /// Patch version.
+    pub patch: u32,
+}
+
+impl ModVersion {
+    /// Creates new version.
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Returns `true` if this version could be used where the `required` version is expected. The
+    /// versions must have the same major version and this version must not be older than the
+    /// required one.
+    pub fn is_compatible_with(&self, required: &ModVersion) -> bool {
+        self.major == required.major && self >= required
+    }
+}
+
+impl Display for ModVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for ModVersion {
+    type Err = ModError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut components = [0; 3];
+        let mut count = 0;
+        for part in s.trim().split('.') {
+            let component = components
+                .get_mut(count)
+                .ok_or_else(|| ModError::InvalidVersion(s.to_string()))?;
+            *component = part
+                .parse()
+                .map_err(|_| ModError::InvalidVersion(s.to_string()))?;
+            count += 1;
+        }
+        Ok(Self::new(components[0], components[1], components[2]))
+    }
+}
+
+impl TryFrom<String> for ModVersion {
+    type Error = ModError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ModVersion> for String {
+    fn from(value: ModVersion) -> Self {
+        value.to_string()
+    }
+}
+
+/// A dependency of a mod.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModDependency {
+    /// Id of the mod.
+    pub id: String,
+    /// Minimal compatible version of the mod, `None` means any version.
+    #[serde(default)]
+    pub version: Option<ModVersion>,
+    /// Optional dependencies only affect the load order, the mod is loaded even if they are
+    /// missing.
+    #[serde(default)]
+    pub optional: bool,
+}
+
+fn default_override_assets() -> bool {
+    true
+}
+
+/// Mod manifest describes a mod - its identity, dependencies and content. Manifests are stored in
+/// [`MOD_MANIFEST_FILE_NAME`] files in RON format:
+///
+/// ```text
+/// (
+///     id: "better_weapons",
+///     name: "Better Weapons",
+///     version: "1.2.0",
+///     description: "Rebalanced weapons and new models.",
+///     authors: ["Modder"],
+///     dependencies: [
+///         (id: "weapon_pack", version: Some("2.0")),
+///         (id: "hd_textures", optional: true),
+///     ],
+///     scenes: ["data/levels/arena.rgs"],
+///     resources: ["data/models/rifle.fbx"],
+///     scripts: ["data/scripts/rifle.lua"],
+/// )
+/// ```
+///
+/// Content of a mod is either its directory, or an asset pack (see [`Self::pack`]). In both cases it
+/// is mounted as an additional asset root, so the paths of scenes, resources and scripts are the
+/// usual resource paths.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModManifest {
+    /// Unique id of the mod.
+    pub id: String,
+    /// Human-readable name of the mod.
+    pub name: String,
+    /// Version of the mod.
+    pub version: ModVersion,
+    /// Description of the mod.
+    #[serde(default)]
+    pub description: String,
+    /// Authors of the mod.
+    #[serde(default)]
+    pub authors: Vec<String>,
+    /// Dependencies of the mod. Dependencies are always loaded before the mod.
+    #[serde(default)]
+    pub dependencies: Vec<ModDependency>,
+    /// Path of an asset pack (relative to the mod directory) with the content of the mod. If not
+    /// set, the mod directory itself is used as an asset root.
+    #[serde(default)]
+    pub pack: Option<PathBuf>,
+    /// If `true` (default), assets of the mod replace the assets of the game and previously loaded
+    /// mods with the same paths. Otherwise, the mod could only add new assets.
+    #[serde(default = "default_override_assets")]
+    pub override_assets: bool,
+    /// Scenes provided by the mod.
+    #[serde(default)]
+    pub scenes: Vec<PathBuf>,
+    /// Resources, that should be loaded when the mod is loaded.
+    #[serde(default)]
+    pub resources: Vec<PathBuf>,
+    /// Lua scripts of the mod (requires `lua` feature).
+    #[serde(default)]
+    pub scripts: Vec<PathBuf>,
+}
+
+impl ModManifest {
+    /// Creates new manifest with the given id and version.
+    pub fn new(id: impl Into<String>, version: ModVersion) -> Self {
+        let id = id.into();
+        Self {
+            name: id.clone(),
+            id,
+            version,
+            description: Default::default(),
+            authors: Default::default(),
+            dependencies: Default::default(),
+            pack: None,
+            override_assets: true,
+            scenes: Default::default(),
+            resources: Default::default(),
+            scripts: Default::default(),
+        }
+    }
+
+    /// Adds a dependency to the manifest.
+    pub fn with_dependency(mut self, id: impl Into<String>, version: Option<ModVersion>) -> Self {
+        self.dependencies.push(ModDependency {
+            id: id.into(),
+            version,
+            optional: false,
+        });
+        self
+    }
+
+    /// Adds an optional dependency to the manifest.
+    pub fn with_optional_dependency(mut self, id: impl Into<String>) -> Self {
+        self.dependencies.push(ModDependency {
+            id: id.into(),
+            version: None,
+            optional: true,
+        });
+        self
+    }
+
+    /// Parses a manifest from the given RON text.
+    pub fn parse(text: &str) -> Result<Self, ModError> {
+        ron::from_str(text).map_err(|error| ModError::Manifest {
+            path: Default::default(),
+            reason: error.to_string(),
+        })
+    }
+
+    /// Loads a manifest from the given file.
+    pub fn from_file(path: &Path) -> Result<Self, ModError> {
+        let text = std::fs::read_to_string(path)?;
+        ron::from_str(&text).map_err(|error| ModError::Manifest {
+            path: path.to_path_buf(),
+            reason: error.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mod_version() {
+        let version = "1.2".parse::<ModVersion>().unwrap();
+        assert_eq!(version, ModVersion::new(1, 2, 0));
+        assert_eq!(version.to_string(), "1.2.0");
+        assert!(version.is_compatible_with(&ModVersion::new(1, 1, 5)));
+        assert!(!version.is_compatible_with(&ModVersion::new(1, 3, 0)));
+        assert!(!version.is_compatible_with(&ModVersion::new(0, 9, 0)));
+        assert!("1.2.3.4".parse::<ModVersion>().is_err());
+        assert!("1.x".parse::<ModVersion>().is_err());
+    }
+
+    #[test]
+    fn test_manifest_parse() {
+        let manifest = ModManifest::parse(
+            r#"(
+                id: "better_weapons",
+                name: "Better Weapons",
+                version: "1.2.0",
+                dependencies: [
+                    (id: "weapon_pack", version: Some("2.0")),
+                    (id: "hd_textures", optional: true),
+                ],
+                scenes: ["data/arena.rgs"],
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.version, ModVersion::new(1, 2, 0));
+        assert_eq!(manifest.dependencies.len(), 2);
+        assert_eq!(
+            manifest.dependencies[0].version,
+            Some(ModVersion::new(2, 0, 0))
+        );
+        assert!(manifest.dependencies[1].optional);
+        assert!(manifest.override_assets);
+        assert_eq!(manifest.scenes, vec![PathBuf::from("data/arena.rgs")]);
+        assert!(ModManifest::parse("(id: \"a\")").is_err());
+    }
+}
@@ -0,0 +1,564 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Runtime mod loading. Mods are directories (or asset packs) with a manifest, that are mounted as
+//! additional asset roots at runtime, so they could add new assets or override the assets of the
+//! game. See [`ModManager`] and [`ModManifest`] docs for more info.
+
+use crate::{
+    asset::{
+        manager::ResourceManager,
+        mount::MountPolicy,
+        pack::{PackError, ResourcePack},
+        untyped::UntypedResource,
+    },
+    core::log::Log,
+    fxhash::{FxHashMap, FxHashSet},
+};
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+pub mod manifest;
+
+pub use manifest::{ModDependency, ModManifest, ModVersion, MOD_MANIFEST_FILE_NAME};
+
+/// An error that may occur during mod discovery or loading.
+#[derive(Debug)]
+pub enum ModError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// A manifest is malformed.
+    Manifest {
+        /// Path of the manifest.
+        path: PathBuf,
+        /// Description of the problem.
+        reason: String,
+    },
+    /// A version string is malformed.
+    InvalidVersion(String),
+    /// An asset pack of a mod cannot be mounted.
+    Pack(PackError),
+    /// A mod with the same id was already added.
+    DuplicateId(String),
+    /// A required dependency is missing, disabled or failed to load.
+    MissingDependency {
+        /// Id of the dependency.
+        dependency: String,
+    },
+    /// A dependency has incompatible version.
+    IncompatibleVersion {
+        /// Id of the dependency.
+        dependency: String,
+        /// Required version.
+        required: ModVersion,
+        /// Actual version of the dependency.
+        found: ModVersion,
+    },
+    /// Mods depend on each other.
+    DependencyCycle(Vec<String>),
+}
+
+impl Display for ModError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModError::Io(v) => {
+                write!(f, "An i/o error has occurred {v}")
+            }
+            ModError::Manifest { path, reason } => {
+                write!(f, "Mod manifest {} is malformed: {reason}", path.display())
+            }
+            ModError::InvalidVersion(v) => {
+                write!(f, "{v} is not a valid version")
+            }
+            ModError::Pack(v) => {
+                write!(f, "Unable to mount asset pack of a mod: {v}")
+            }
+            ModError::DuplicateId(v) => {
+                write!(f, "A mod with {v} id already exists")
+            }
+            ModError::MissingDependency { dependency } => {
+                write!(f, "Required dependency {dependency} is not available")
+            }
+            ModError::IncompatibleVersion {
+                dependency,
+                required,
+                found,
+            } => {
+                write!(
+                    f,
+                    "Dependency {dependency} has version {found}, but {required} is required"
+                )
+            }
+            ModError::DependencyCycle(v) => {
+                write!(f, "Mods {} depend on each other", v.join(", "))
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for ModError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<PackError> for ModError {
+    fn from(e: PackError) -> Self {
+        Self::Pack(e)
+    }
+}
+
+/// State of a mod.
+#[derive(Debug)]
+pub enum ModState {
+    /// The mod was discovered, but not loaded yet.
+    Discovered,
+    /// The mod is disabled and won't be loaded.
+    Disabled,
+    /// Content of the mod is mounted.
+    Loaded,
+    /// The mod cannot be loaded.
+    Failed(ModError),
+}
+
+/// A mod with its manifest and state.
+#[derive(Debug)]
+pub struct Mod {
+    /// Manifest of the mod.
+    pub manifest: ModManifest,
+    /// Root directory of the mod.
+    pub directory: PathBuf,
+    /// Current state of the mod.
+    pub state: ModState,
+}
+
+impl Mod {
+    /// Returns `true` if the mod is loaded.
+    pub fn is_loaded(&self) -> bool {
+        matches!(self.state, ModState::Loaded)
+    }
+
+    /// Returns `true` if the mod is not disabled.
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self.state, ModState::Disabled)
+    }
+}
+
+/// Calculates load order of the given manifests. Returns indices of the manifests in the load order
+/// (dependencies go first) and a list of manifests that cannot be loaded together with the reasons.
+/// Mods without dependencies between each other keep their relative order.
+pub fn resolve_load_order(manifests: &[&ModManifest]) -> (Vec<usize>, Vec<(usize, ModError)>) {
+    let index_of = manifests
+        .iter()
+        .enumerate()
+        .map(|(index, manifest)| (manifest.id.as_str(), index))
+        .collect::<FxHashMap<_, _>>();
+
+    let mut failed = FxHashMap::<usize, ModError>::default();
+
+    // Check dependencies until nothing changes, because a failure could propagate to dependents.
+    loop {
+        let mut changed = false;
+        for (index, manifest) in manifests.iter().enumerate() {
+            if failed.contains_key(&index) {
+                continue;
+            }
+            for dependency in manifest.dependencies.iter() {
+                let error = match index_of.get(dependency.id.as_str()) {
+                    Some(dependency_index) if !failed.contains_key(dependency_index) => {
+                        let found = manifests[*dependency_index].version;
+                        match dependency.version {
+                            Some(required) if !found.is_compatible_with(&required) => {
+                                Some(ModError::IncompatibleVersion {
+                                    dependency: dependency.id.clone(),
+                                    required,
+                                    found,
+                                })
+                            }
+                            _ => None,
+                        }
+                    }
+                    _ if dependency.optional => None,
+                    _ => Some(ModError::MissingDependency {
+                        dependency: dependency.id.clone(),
+                    }),
+                };
+                if let Some(error) = error {
+                    failed.insert(index, error);
+                    changed = true;
+                    break;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    // Topological sort (Kahn's algorithm), that always picks the first ready mod to keep the order
+    // stable.
+    let mut dependents = vec![Vec::new(); manifests.len()];
+    let mut pending_dependencies = vec![0usize; manifests.len()];
+    for (index, manifest) in manifests.iter().enumerate() {
+        if failed.contains_key(&index) {
+            continue;
+        }
+        let dependencies = manifest
+            .dependencies
+            .iter()
+            .filter_map(|dependency| index_of.get(dependency.id.as_str()).copied())
+            .filter(|dependency| !failed.contains_key(dependency) && *dependency != index)
+            .collect::<FxHashSet<_>>();
+        pending_dependencies[index] = dependencies.len();
+        for dependency in dependencies {
+            dependents[dependency].push(index);
+        }
+    }
+
+    let mut ready = (0..manifests.len())
+        .filter(|index| !failed.contains_key(index) && pending_dependencies[*index] == 0)
+        .collect::<VecDeque<_>>();
+    let mut order = Vec::new();
+    while let Some(index) = ready.pop_front() {
+        order.push(index);
+        for &dependent in dependents[index].iter() {
+            pending_dependencies[dependent] -= 1;
+            if pending_dependencies[dependent] == 0 {
+                let position = ready.partition_point(|other| *other < dependent);
+                ready.insert(position, dependent);
+            }
+        }
+    }
+
+    // Everything that is left either forms a cycle or depends on one.
+    let cycle = (0..manifests.len())
+        .filter(|index| !failed.contains_key(index) && !order.contains(index))
+        .collect::<Vec<_>>();
+    let cycle_ids = cycle
+        .iter()
+        .map(|index| manifests[*index].id.clone())
+        .collect::<Vec<_>>();
+    for index in cycle {
+        failed.insert(index, ModError::DependencyCycle(cycle_ids.clone()));
+    }
+
+    let mut failed = failed.into_iter().collect::<Vec<_>>();
+    failed.sort_by_key(|(index, _)| *index);
+    (order, failed)
+}
+
+/// Mod manager discovers mods, resolves their dependencies and mounts their content to a resource
+/// manager.
+///
+/// ## Directory layout
+///
+/// Every mod is a directory with a [`MOD_MANIFEST_FILE_NAME`] manifest, mods are discovered in a
+/// common directory (usually `mods` near the game executable):
+///
+/// ```text
+/// mods/
+///     better_weapons/
+///         mod.ron
+///         data/models/rifle.fbx
+///     weapon_pack/
+///         mod.ron
+///         content.fyrpak
+/// ```
+///
+/// ## Load order
+///
+/// Dependencies are always loaded before their dependents, other mods are loaded in the order they
+/// were added (discovered mods are sorted by their directory names). Mods loaded later have higher
+/// priority, so their assets override the assets of the game and the mods loaded before them (unless
+/// [`ModManifest::override_assets`] is `false`).
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// # use fyrox_impl::{asset::manager::ResourceManager, modding::ModManager};
+/// fn load_mods(resource_manager: &ResourceManager) {
+///     let mut mods = ModManager::new();
+///     mods.discover("mods").unwrap();
+///     mods.load(resource_manager);
+///     for scene in mods.scenes() {
+///         println!("A mod provides {} scene", scene.display());
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct ModManager {
+    mods: Vec<Mod>,
+    load_order: Vec<usize>,
+}
+
+impl ModManager {
+    /// Creates new empty mod manager.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Discovers mods in the given directory. Every subdirectory with a manifest is added as a mod,
+    /// malformed mods are reported to the log and skipped. Returns amount of added mods.
+    pub fn discover(&mut self, directory: impl AsRef<Path>) -> Result<usize, ModError> {
+        let mut directories = std::fs::read_dir(directory)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.join(MOD_MANIFEST_FILE_NAME).is_file())
+            .collect::<Vec<_>>();
+        directories.sort();
+
+        let mut count = 0;
+        for directory in directories {
+            let result = ModManifest::from_file(&directory.join(MOD_MANIFEST_FILE_NAME))
+                .and_then(|manifest| self.add(manifest, &directory));
+            match result {
+                Ok(()) => count += 1,
+                Err(err) => Log::err(format!(
+                    "Unable to add mod from {}. Reason: {err}",
+                    directory.display()
+                )),
+            }
+        }
+        Ok(count)
+    }
+
+    /// Adds a mod with the given manifest and root directory.
+    pub fn add(
+        &mut self,
+        manifest: ModManifest,
+        directory: impl AsRef<Path>,
+    ) -> Result<(), ModError> {
+        if self.find(&manifest.id).is_some() {
+            return Err(ModError::DuplicateId(manifest.id));
+        }
+        self.mods.push(Mod {
+            manifest,
+            directory: directory.as_ref().to_path_buf(),
+            state: ModState::Discovered,
+        });
+        Ok(())
+    }
+
+    /// Returns every known mod.
+    pub fn mods(&self) -> &[Mod] {
+        &self.mods
+    }
+
+    /// Tries to find a mod by its id.
+    pub fn find(&self, id: &str) -> Option<&Mod> {
+        self.mods.iter().find(|m| m.manifest.id == id)
+    }
+
+    /// Enables or disables a mod. Changes are applied on the next [`Self::load`] call, content of
+    /// a loaded mod stays mounted until the game is restarted. Returns `false` if there's no such
+    /// mod.
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) -> bool {
+        let Some(m) = self.mods.iter_mut().find(|m| m.manifest.id == id) else {
+            return false;
+        };
+        match (enabled, &m.state) {
+            (false, ModState::Discovered | ModState::Failed(_)) => m.state = ModState::Disabled,
+            (true, ModState::Disabled) => m.state = ModState::Discovered,
+            _ => (),
+        }
+        true
+    }
+
+    /// Resolves dependencies of enabled mods and mounts content of every mod, that wasn't loaded yet,
+    /// to the resource manager. Mods that cannot be loaded are marked as failed and reported to the
+    /// log. Returns amount of loaded mods.
+    pub fn load(&mut self, resource_manager: &ResourceManager) -> usize {
+        let candidates = self
+            .mods
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.is_enabled())
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let manifests = candidates
+            .iter()
+            .map(|index| &self.mods[*index].manifest)
+            .collect::<Vec<_>>();
+        let (order, failed) = resolve_load_order(&manifests);
+
+        for (index, error) in failed {
+            let m = &mut self.mods[candidates[index]];
+            Log::err(format!(
+                "Unable to load mod {}. Reason: {error}",
+                m.manifest.id
+            ));
+            m.state = ModState::Failed(error);
+        }
+
+        self.load_order.clear();
+        for index in order {
+            let index = candidates[index];
+            let m = &mut self.mods[index];
+            if !m.is_loaded() {
+                match Self::mount(m, resource_manager) {
+                    Ok(()) => {
+                        Log::info(format!(
+                            "Mod {} {} was loaded.",
+                            m.manifest.id, m.manifest.version
+                        ));
+                        m.state = ModState::Loaded;
+                    }
+                    Err(err) => {
+                        Log::err(format!(
+                            "Unable to load mod {}. Reason: {err}",
+                            m.manifest.id
+                        ));
+                        m.state = ModState::Failed(err);
+                        continue;
+                    }
+                }
+            }
+            self.load_order.push(index);
+        }
+
+        self.load_order.len()
+    }
+
+    fn mount(m: &Mod, resource_manager: &ResourceManager) -> Result<(), ModError> {
+        let policy = if m.manifest.override_assets {
+            MountPolicy::Override
+        } else {
+            MountPolicy::Append
+        };
+        match m.manifest.pack.as_ref() {
+            Some(pack) => {
+                let pack = ResourcePack::open(m.directory.join(pack), None)?;
+                resource_manager.mount_pack_with_policy(pack, policy);
+            }
+            None => resource_manager.mount_directory(&m.directory, policy),
+        }
+        Ok(())
+    }
+
+    /// Returns an iterator over loaded mods in their load order.
+    pub fn loaded_mods(&self) -> impl Iterator<Item = &Mod> {
+        self.load_order.iter().map(|index| &self.mods[*index])
+    }
+
+    /// Returns an iterator over the scenes provided by the loaded mods.
+    pub fn scenes(&self) -> impl Iterator<Item = &Path> {
+        self.loaded_mods()
+            .flat_map(|m| m.manifest.scenes.iter().map(|path| path.as_path()))
+    }
+
+    /// Requests every resource listed by the loaded mods. Returned resources could be awaited to
+    /// make sure that the content of the mods is ready.
+    pub fn request_resources(&self, resource_manager: &ResourceManager) -> Vec<UntypedResource> {
+        self.loaded_mods()
+            .flat_map(|m| m.manifest.resources.iter())
+            .map(|path| resource_manager.request_untyped(path))
+            .collect()
+    }
+
+    /// Creates a node for every loaded mod with scripts, with a Lua script for each of them. Returns
+    /// handles of the created nodes.
+    #[cfg(feature = "lua")]
+    pub fn instantiate_scripts(
+        &self,
+        scene: &mut crate::scene::Scene,
+        resource_manager: &ResourceManager,
+    ) -> Vec<crate::core::pool::Handle<crate::scene::node::Node>> {
+        use crate::{
+            scene::{base::BaseBuilder, pivot::PivotBuilder},
+            script::lua::{LuaScript, LuaSource},
+        };
+
+        self.loaded_mods()
+            .filter(|m| !m.manifest.scripts.is_empty())
+            .map(|m| {
+                let mut base = BaseBuilder::new().with_name(format!("Mod {}", m.manifest.id));
+                for path in m.manifest.scripts.iter() {
+                    base = base
+                        .with_script(LuaScript::new(resource_manager.request::<LuaSource>(path)));
+                }
+                PivotBuilder::new(base).build(&mut scene.graph)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn version(major: u32) -> ModVersion {
+        ModVersion::new(major, 0, 0)
+    }
+
+    #[test]
+    fn test_resolve_load_order() {
+        let manifests = [
+            ModManifest::new("addon", version(1)).with_dependency("base", Some(version(1))),
+            ModManifest::new("base", version(1)).with_optional_dependency("missing"),
+            ModManifest::new("broken", version(1)).with_dependency("missing", None),
+            ModManifest::new("outdated", version(1)).with_dependency("base", Some(version(2))),
+            ModManifest::new("dependent", version(1)).with_dependency("broken", None),
+            ModManifest::new("a", version(1)).with_dependency("b", None),
+            ModManifest::new("b", version(1)).with_dependency("a", None),
+            ModManifest::new("standalone", version(1)).with_optional_dependency("addon"),
+        ];
+        let (order, failed) = resolve_load_order(&manifests.iter().collect::<Vec<_>>());
+        assert_eq!(order, vec![1, 0, 7]);
+
+        let failed = failed
+            .into_iter()
+            .map(|(index, error)| (manifests[index].id.as_str(), error))
+            .collect::<Vec<_>>();
+        assert_eq!(failed.len(), 5);
+        assert!(matches!(
+            failed[0],
+            ("broken", ModError::MissingDependency { .. })
+        ));
+        assert!(matches!(
+            failed[1],
+            ("outdated", ModError::IncompatibleVersion { .. })
+        ));
+        assert!(matches!(
+            failed[2],
+            ("dependent", ModError::MissingDependency { .. })
+        ));
+        assert!(matches!(failed[3], ("a", ModError::DependencyCycle(_))));
+        assert!(matches!(failed[4], ("b", ModError::DependencyCycle(_))));
+    }
+
+    #[test]
+    fn test_mod_manager_enable() {
+        let mut manager = ModManager::new();
+        manager
+            .add(ModManifest::new("foo", version(1)), "mods/foo")
+            .unwrap();
+        assert!(manager
+            .add(ModManifest::new("foo", version(2)), "mods/foo2")
+            .is_err());
+        assert!(manager.set_enabled("foo", false));
+        assert!(!manager.find("foo").unwrap().is_enabled());
+        assert!(manager.set_enabled("foo", true));
+        assert!(manager.find("foo").unwrap().is_enabled());
+        assert!(!manager.set_enabled("bar", true));
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Local implementation of platform services. See [`LocalPlatformServices`] docs for more info.
+
+use crate::{
+    fxhash::{FxHashMap, FxHashSet},
+    platform::{CloudFile, PlatformError, PlatformEvent, PlatformServices},
+};
+use std::{collections::VecDeque, path::PathBuf};
+
+/// Local implementation of platform services. Achievements, statistics and rich presence are kept
+/// in memory, "cloud" files are stored in a local folder. It is useful during development, when
+/// the actual platform is not available, and as a fallback for platforms without any services.
+#[derive(Debug)]
+pub struct LocalPlatformServices {
+    cloud_directory: PathBuf,
+    achievements: FxHashSet<String>,
+    stats: FxHashMap<String, f32>,
+    rich_presence: FxHashMap<String, String>,
+    events: VecDeque<PlatformEvent>,
+}
+
+impl LocalPlatformServices {
+    /// Creates new local services, that store cloud files in the given folder. The folder is
+    /// created on the first write.
+    pub fn new(cloud_directory: impl Into<PathBuf>) -> Self {
+        Self {
+            cloud_directory: cloud_directory.into(),
+            achievements: Default::default(),
+            stats: Default::default(),
+            rich_presence: Default::default(),
+            events: Default::default(),
+        }
+    }
+
+    /// Returns the value of the statistic with the given name.
+    pub fn stat(&self, name: &str) -> Option<f32> {
+        self.stats.get(name).copied()
+    }
+
+    /// Returns the value of the rich presence key.
+    pub fn rich_presence(&self, key: &str) -> Option<&str> {
+        self.rich_presence.get(key).map(|v| v.as_str())
+    }
+
+    /// Emulates showing or hiding of the platform overlay.
+    pub fn set_overlay_active(&mut self, active: bool) {
+        self.events
+            .push_back(PlatformEvent::OverlayActivated(active));
+    }
+
+    fn cloud_file_path(&self, name: &str) -> Result<PathBuf, PlatformError> {
+        // Cloud file names are flat, do not let them escape the cloud folder.
+        if name.is_empty() || name.contains(['/', '\\']) || name == ".." {
+            return Err(PlatformError::Backend(format!(
+                "Invalid cloud file name {name}!"
+            )));
+        }
+        Ok(self.cloud_directory.join(name))
+    }
+}
+
+impl PlatformServices for LocalPlatformServices {
+    fn name(&self) -> &str {
+        "Local"
+    }
+
+    fn poll_event(&mut self) -> Option<PlatformEvent> {
+        self.events.pop_front()
+    }
+
+    fn unlock_achievement(&mut self, id: &str) -> Result<(), PlatformError> {
+        if self.achievements.insert(id.to_string()) {
+            self.events
+                .push_back(PlatformEvent::AchievementUnlocked(id.to_string()));
+        }
+        Ok(())
+    }
+
+    fn clear_achievement(&mut self, id: &str) -> Result<(), PlatformError> {
+        self.achievements.remove(id);
+        Ok(())
+    }
+
+    fn is_achievement_unlocked(&self, id: &str) -> Result<bool, PlatformError> {
+        Ok(self.achievements.contains(id))
+    }
+
+    fn set_stat(&mut self, name: &str, value: f32) -> Result<(), PlatformError> {
+        self.stats.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    fn set_rich_presence(&mut self, key: &str, value: Option<&str>) -> Result<(), PlatformError> {
+        match value {
+            Some(value) => {
+                self.rich_presence
+                    .insert(key.to_string(), value.to_string());
+            }
+            None => {
+                self.rich_presence.remove(key);
+            }
+        }
+        Ok(())
+    }
+
+    fn is_cloud_enabled(&self) -> bool {
+        true
+    }
+
+    fn write_cloud_file(&mut self, name: &str, data: &[u8]) -> Result<(), PlatformError> {
+        let path = self.cloud_file_path(name)?;
+        std::fs::create_dir_all(&self.cloud_directory)?;
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+
+    fn read_cloud_file(&mut self, name: &str) -> Result<Vec<u8>, PlatformError> {
+        Ok(std::fs::read(self.cloud_file_path(name)?)?)
+    }
+
+    fn delete_cloud_file(&mut self, name: &str) -> Result<(), PlatformError> {
+        std::fs::remove_file(self.cloud_file_path(name)?)?;
+        Ok(())
+    }
+
+    fn cloud_files(&self) -> Result<Vec<CloudFile>, PlatformError> {
+        if !self.cloud_directory.exists() {
+            return Ok(Vec::new());
+        }
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(&self.cloud_directory)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                files.push(CloudFile {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    size: metadata.len(),
+                });
+            }
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_local_platform_services() {
+        let directory = std::env::temp_dir().join("fyrox_local_platform_test");
+        let _ = std::fs::remove_dir_all(&directory);
+        let mut services = LocalPlatformServices::new(&directory);
+
+        services.unlock_achievement("FIRST_BLOOD").unwrap();
+        services.unlock_achievement("FIRST_BLOOD").unwrap();
+        assert!(services.is_achievement_unlocked("FIRST_BLOOD").unwrap());
+        assert_eq!(
+            services.poll_event(),
+            Some(PlatformEvent::AchievementUnlocked(
+                "FIRST_BLOOD".to_string()
+            ))
+        );
+        assert_eq!(services.poll_event(), None);
+
+        services.write_cloud_file("save.bin", &[1, 2, 3]).unwrap();
+        assert_eq!(services.read_cloud_file("save.bin").unwrap(), [1, 2, 3]);
+        assert_eq!(
+            services.cloud_files().unwrap(),
+            [CloudFile {
+                name: "save.bin".to_string(),
+                size: 3
+            }]
+        );
+        assert!(services.write_cloud_file("../escape", &[]).is_err());
+        services.delete_cloud_file("save.bin").unwrap();
+        assert!(services.cloud_files().unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
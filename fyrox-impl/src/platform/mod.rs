@@ -0,0 +1,286 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Platform services are services provided by a store or a platform the game is shipped on
+//! (Steam, for example): achievements, statistics, rich presence, cloud saves, etc. This module
+//! provides a unified interface for such services ([`PlatformServices`]) and a plugin, that
+//! drives them ([`PlatformPlugin`]).
+//!
+//! Available implementations:
+//!
+//! - [`local::LocalPlatformServices`] - keeps achievements and statistics in memory and stores
+//! cloud files in a local folder. Useful for development and for platforms without any services.
+//! - `steam::SteamPlatformServices` - Steamworks implementation, available with `steamworks`
+//! feature.
+
+use crate::{
+    core::{log::Log, reflect::prelude::*, visitor::prelude::*},
+    engine::GraphicsContext,
+    plugin::{Plugin, PluginContext},
+    window::CursorGrabMode,
+};
+use std::fmt::{Debug, Display, Formatter};
+
+pub mod local;
+#[cfg(feature = "steamworks")]
+pub mod steam;
+
+/// An error that may occur when using platform services.
+#[derive(Debug)]
+pub enum PlatformError {
+    /// The operation is not supported by the current platform.
+    NotSupported,
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// Platform-specific error.
+    Backend(String),
+}
+
+impl Display for PlatformError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlatformError::NotSupported => {
+                write!(f, "The operation is not supported by the platform.")
+            }
+            PlatformError::Io(v) => write!(f, "An i/o error has occurred: {v}"),
+            PlatformError::Backend(v) => write!(f, "Platform error: {v}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for PlatformError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// An event, that is sent by a platform.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlatformEvent {
+    /// Platform overlay (for example, Steam overlay) was shown (`true`) or hidden (`false`).
+    OverlayActivated(bool),
+    /// An achievement was unlocked.
+    AchievementUnlocked(String),
+}
+
+/// A file stored in the platform cloud.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CloudFile {
+    /// Name of the file.
+    pub name: String,
+    /// Size of the file in bytes.
+    pub size: u64,
+}
+
+/// A unified interface for services of a platform. Every method has a default implementation,
+/// that reports that the operation is not supported, so implementations could provide only the
+/// services available on their platform.
+pub trait PlatformServices: Debug + 'static {
+    /// Returns the name of the platform, for example `Steam`.
+    fn name(&self) -> &str;
+
+    /// Processes pending callbacks of the platform. Called by [`PlatformPlugin`] every frame.
+    fn update(&mut self) {}
+
+    /// Fetches the next pending event of the platform.
+    fn poll_event(&mut self) -> Option<PlatformEvent> {
+        None
+    }
+
+    /// Unlocks the achievement with the given id.
+    fn unlock_achievement(
+        &mut self,
+        #[allow(unused_variables)] id: &str,
+    ) -> Result<(), PlatformError> {
+        Err(PlatformError::NotSupported)
+    }
+
+    /// Locks the achievement with the given id back. Useful for testing.
+    fn clear_achievement(
+        &mut self,
+        #[allow(unused_variables)] id: &str,
+    ) -> Result<(), PlatformError> {
+        Err(PlatformError::NotSupported)
+    }
+
+    /// Returns `true` if the achievement with the given id is unlocked.
+    fn is_achievement_unlocked(
+        &self,
+        #[allow(unused_variables)] id: &str,
+    ) -> Result<bool, PlatformError> {
+        Err(PlatformError::NotSupported)
+    }
+
+    /// Sets a new value of the statistic with the given name. Statistics could be used to unlock
+    /// progress achievements automatically (for example, "kill 100 enemies").
+    fn set_stat(
+        &mut self,
+        #[allow(unused_variables)] name: &str,
+        #[allow(unused_variables)] value: f32,
+    ) -> Result<(), PlatformError> {
+        Err(PlatformError::NotSupported)
+    }
+
+    /// Sends the modified achievements and statistics to the platform servers.
+    fn store_stats(&mut self) -> Result<(), PlatformError> {
+        Ok(())
+    }
+
+    /// Sets (or removes if `value` is `None`) a rich presence key, that is shown to the friends of
+    /// the player (for example, `status` = `Exploring the Abyss`).
+    fn set_rich_presence(
+        &mut self,
+        #[allow(unused_variables)] key: &str,
+        #[allow(unused_variables)] value: Option<&str>,
+    ) -> Result<(), PlatformError> {
+        Err(PlatformError::NotSupported)
+    }
+
+    /// Returns `true` if cloud saves are enabled for the game and the player.
+    fn is_cloud_enabled(&self) -> bool {
+        false
+    }
+
+    /// Writes a file with the given name to the cloud.
+    fn write_cloud_file(
+        &mut self,
+        #[allow(unused_variables)] name: &str,
+        #[allow(unused_variables)] data: &[u8],
+    ) -> Result<(), PlatformError> {
+        Err(PlatformError::NotSupported)
+    }
+
+    /// Reads a file with the given name from the cloud.
+    fn read_cloud_file(
+        &mut self,
+        #[allow(unused_variables)] name: &str,
+    ) -> Result<Vec<u8>, PlatformError> {
+        Err(PlatformError::NotSupported)
+    }
+
+    /// Deletes a file with the given name from the cloud.
+    fn delete_cloud_file(
+        &mut self,
+        #[allow(unused_variables)] name: &str,
+    ) -> Result<(), PlatformError> {
+        Err(PlatformError::NotSupported)
+    }
+
+    /// Returns a list of files stored in the cloud.
+    fn cloud_files(&self) -> Result<Vec<CloudFile>, PlatformError> {
+        Err(PlatformError::NotSupported)
+    }
+}
+
+/// A plugin, that drives platform services: processes their callbacks and events every frame and
+/// keeps the main window usable while the platform overlay is shown (the overlay needs the cursor
+/// to be visible and not grabbed). Other plugins and scripts could access the services via
+/// `context.plugins.get::<PlatformPlugin>()`.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox_impl::{
+///     engine::executor::Executor,
+///     platform::{local::LocalPlatformServices, PlatformPlugin},
+/// };
+///
+/// fn add_platform(executor: &mut Executor) {
+///     executor.add_plugin(PlatformPlugin::new(LocalPlatformServices::new("cloud")));
+/// }
+///
+/// fn on_boss_defeated(platform: &mut PlatformPlugin) {
+///     let services = platform.services_mut();
+///     if services.unlock_achievement("BOSS_DEFEATED").is_ok() {
+///         let _ = services.store_stats();
+///     }
+/// }
+/// ```
+#[derive(Debug, Visit, Reflect)]
+pub struct PlatformPlugin {
+    #[visit(skip)]
+    #[reflect(hidden)]
+    services: Box<dyn PlatformServices>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    overlay_active: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    events: Vec<PlatformEvent>,
+}
+
+impl PlatformPlugin {
+    /// Creates a new plugin, that uses the given platform services.
+    pub fn new<S: PlatformServices>(services: S) -> Self {
+        Self {
+            services: Box::new(services),
+            overlay_active: false,
+            events: Default::default(),
+        }
+    }
+
+    /// Returns a reference to the platform services.
+    pub fn services(&self) -> &dyn PlatformServices {
+        &*self.services
+    }
+
+    /// Returns a reference to the platform services.
+    pub fn services_mut(&mut self) -> &mut dyn PlatformServices {
+        &mut *self.services
+    }
+
+    /// Returns `true` if the platform overlay is shown. Games should usually pause the game and
+    /// ignore input while the overlay is shown.
+    pub fn is_overlay_active(&self) -> bool {
+        self.overlay_active
+    }
+
+    /// Returns the events of the platform, that were received during the current frame.
+    pub fn events(&self) -> &[PlatformEvent] {
+        &self.events
+    }
+}
+
+impl Plugin for PlatformPlugin {
+    fn update(&mut self, context: &mut PluginContext) {
+        self.events.clear();
+        self.services.update();
+
+        while let Some(event) = self.services.poll_event() {
+            if let PlatformEvent::OverlayActivated(active) = event {
+                self.overlay_active = active;
+
+                // The overlay is drawn on top of the game, and it needs a free cursor to be
+                // usable. The game should restore its cursor state when the overlay is hidden.
+                if active {
+                    if let GraphicsContext::Initialized(ref graphics_context) =
+                        context.graphics_context
+                    {
+                        let window = &graphics_context.window;
+                        Log::verify(window.set_cursor_grab(CursorGrabMode::None));
+                        window.set_cursor_visible(true);
+                    }
+                }
+            }
+
+            self.events.push(event);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Steamworks implementation of platform services. See [`SteamPlatformServices`] docs for more
+//! info.
+
+use crate::platform::{CloudFile, PlatformError, PlatformEvent, PlatformServices};
+use std::{
+    fmt::{Debug, Formatter},
+    io::{Read, Write},
+    sync::mpsc::{channel, Receiver},
+};
+use steamworks::{CallbackHandle, Client, GameOverlayActivated, SingleClient};
+
+/// Steamworks implementation of platform services. Requires the Steam client to be running, and
+/// the game to be either launched from Steam, or to have `steam_appid.txt` file next to the
+/// executable during development.
+pub struct SteamPlatformServices {
+    client: Client,
+    single: SingleClient,
+    receiver: Receiver<PlatformEvent>,
+    // Keeps the callbacks registered.
+    _callbacks: Vec<CallbackHandle>,
+}
+
+impl Debug for SteamPlatformServices {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SteamPlatformServices")
+    }
+}
+
+fn steam_error(operation: &str) -> PlatformError {
+    PlatformError::Backend(format!("Steam was unable to {operation}."))
+}
+
+impl SteamPlatformServices {
+    /// Initializes Steamworks API for the game with the given app id.
+    pub fn new(app_id: u32) -> Result<Self, PlatformError> {
+        let (client, single) =
+            Client::init_app(app_id).map_err(|err| PlatformError::Backend(format!("{err:?}")))?;
+
+        let (sender, receiver) = channel();
+        let overlay_callback = client.register_callback(move |event: GameOverlayActivated| {
+            let _ = sender.send(PlatformEvent::OverlayActivated(event.active));
+        });
+
+        Ok(Self {
+            client,
+            single,
+            receiver,
+            _callbacks: vec![overlay_callback],
+        })
+    }
+
+    /// Returns the Steamworks client, that could be used to access the services that are not
+    /// covered by [`PlatformServices`].
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl PlatformServices for SteamPlatformServices {
+    fn name(&self) -> &str {
+        "Steam"
+    }
+
+    fn update(&mut self) {
+        self.single.run_callbacks();
+    }
+
+    fn poll_event(&mut self) -> Option<PlatformEvent> {
+        self.receiver.try_recv().ok()
+    }
+
+    fn unlock_achievement(&mut self, id: &str) -> Result<(), PlatformError> {
+        self.client
+            .user_stats()
+            .achievement(id)
+            .set()
+            .map_err(|_| steam_error("unlock the achievement"))
+    }
+
+    fn clear_achievement(&mut self, id: &str) -> Result<(), PlatformError> {
+        self.client
+            .user_stats()
+            .achievement(id)
+            .clear()
+            .map_err(|_| steam_error("clear the achievement"))
+    }
+
+    fn is_achievement_unlocked(&self, id: &str) -> Result<bool, PlatformError> {
+        self.client
+            .user_stats()
+            .achievement(id)
+            .get()
+            .map_err(|_| steam_error("fetch the achievement"))
+    }
+
+    fn set_stat(&mut self, name: &str, value: f32) -> Result<(), PlatformError> {
+        self.client
+            .user_stats()
+            .set_stat_f32(name, value)
+            .map_err(|_| steam_error("set the statistic"))
+    }
+
+    fn store_stats(&mut self) -> Result<(), PlatformError> {
+        self.client
+            .user_stats()
+            .store_stats()
+            .map_err(|_| steam_error("store the statistics"))
+    }
+
+    fn set_rich_presence(&mut self, key: &str, value: Option<&str>) -> Result<(), PlatformError> {
+        if self.client.friends().set_rich_presence(key, value) {
+            Ok(())
+        } else {
+            Err(steam_error("set the rich presence"))
+        }
+    }
+
+    fn is_cloud_enabled(&self) -> bool {
+        let storage = self.client.remote_storage();
+        storage.is_cloud_enabled_for_account() && storage.is_cloud_enabled_for_app()
+    }
+
+    fn write_cloud_file(&mut self, name: &str, data: &[u8]) -> Result<(), PlatformError> {
+        let mut writer = self.client.remote_storage().file(name).write();
+        writer.write_all(data)?;
+        Ok(())
+    }
+
+    fn read_cloud_file(&mut self, name: &str) -> Result<Vec<u8>, PlatformError> {
+        let mut data = Vec::new();
+        self.client
+            .remote_storage()
+            .file(name)
+            .read()
+            .read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    fn delete_cloud_file(&mut self, name: &str) -> Result<(), PlatformError> {
+        if self.client.remote_storage().file(name).delete() {
+            Ok(())
+        } else {
+            Err(steam_error("delete the cloud file"))
+        }
+    }
+
+    fn cloud_files(&self) -> Result<Vec<CloudFile>, PlatformError> {
+        Ok(self
+            .client
+            .remote_storage()
+            .files()
+            .into_iter()
+            .map(|file| CloudFile {
+                name: file.name,
+                size: file.size,
+            })
+            .collect())
+    }
+}
@@ -25,7 +25,7 @@
 pub mod dylib;
 
 use crate::{
-    asset::manager::ResourceManager,
+    asset::{manager::ResourceManager, untyped::UntypedResource},
     core::{
         pool::Handle,
         reflect::Reflect,
@@ -33,15 +33,20 @@ use crate::{
         Downcast,
     },
     engine::{
-        task::TaskPoolHandler, AsyncSceneLoader, GraphicsContext, PerformanceStatistics,
-        ScriptProcessor, SerializationContext,
+        task::TaskPoolHandler, update_loop::UpdateLoopSettings, AsyncSceneLoader, GraphicsContext,
+        PerformanceStatistics, ScriptProcessor, SerializationContext,
     },
     event::Event,
     gui::{
         constructor::WidgetConstructorContainer,
         inspector::editors::PropertyEditorDefinitionContainer, message::UiMessage, UiContainer,
     },
+    resource::localization::{Localization, LocalizationResource},
     scene::{Scene, SceneContainer},
+    script::{
+        blackboard::{Blackboard, BlackboardResource},
+        bus::MessageBus,
+    },
 };
 use std::{
     ops::{Deref, DerefMut},
@@ -112,6 +117,29 @@ impl DerefMut for PluginContainer {
     }
 }
 
+/// Application lifecycle event. Such events are sent mostly on mobile platforms (Android and iOS),
+/// where the OS could send the application to background at any time.
+///
+/// Loss and recreation of the graphics context are reported separately via
+/// [`Plugin::on_graphics_context_destroyed`] and [`Plugin::on_graphics_context_initialized`]. On
+/// mobile platforms the graphics context is destroyed right after [`LifecycleEvent::Paused`] and
+/// created again right before [`LifecycleEvent::Resumed`]. GPU resources of the engine (textures,
+/// meshes, shaders, etc.) are re-uploaded automatically, but any GPU objects created manually by
+/// plugins must be re-created when the new graphics context is initialized.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    /// The application was sent to background. This is the last chance to save the game state,
+    /// because the OS could terminate the application at any moment after this event. Game logic
+    /// is not updated while the application is paused.
+    Paused,
+    /// The application was brought back to foreground.
+    Resumed,
+    /// The OS reported that it is running low on memory. The engine frees unused resources and
+    /// GPU caches right before sending this event, plugins should free their caches as well,
+    /// otherwise the OS could terminate the application.
+    LowMemory,
+}
+
 /// Contains plugin environment for the registration stage.
 pub struct PluginRegistrationContext<'a> {
     /// A reference to serialization context of the engine. See [`SerializationContext`] for more
@@ -175,6 +203,18 @@ pub struct PluginContext<'a, 'b> {
     /// Script processor is used to run script methods in a strict order.
     pub script_processor: &'a ScriptProcessor,
 
+    /// Global message bus, that could be used to communicate with scripts and other plugins. See
+    /// [`MessageBus`] docs for more info.
+    pub message_bus: &'a MessageBus,
+
+    /// Global blackboard, that could be used to share some state with scripts and other plugins. See
+    /// [`Blackboard`] docs for more info.
+    pub blackboard: &'a BlackboardResource,
+
+    /// Global localization, that could be used to fetch translated strings for the current
+    /// language. See [`Localization`] docs for more info.
+    pub localization: &'a LocalizationResource,
+
     /// Asynchronous scene loader. It is used to request scene loading. See [`AsyncSceneLoader`] docs
     /// for usage example.
     pub async_scene_loader: &'a mut AsyncSceneLoader,
@@ -185,6 +225,14 @@ pub struct PluginContext<'a, 'b> {
 
     /// Task pool for asynchronous task management.
     pub task_pool: &'a mut TaskPoolHandler,
+
+    /// Parameters of the main loop of the engine, that could be changed at runtime. See
+    /// [`UpdateLoopSettings`] docs for more info.
+    pub update_loop: &'a mut UpdateLoopSettings,
+
+    /// Fraction of the fixed time step, that was accumulated but not simulated yet. It is always in
+    /// `[0; 1)` range and could be used to interpolate visual state of objects between logic updates.
+    pub interpolation_alpha: f32,
 }
 
 impl dyn Plugin {
@@ -270,6 +318,14 @@ pub trait Plugin: Downcast + Visit + Reflect {
     /// cross-plugins interactions are possible.
     fn on_loaded(&mut self, #[allow(unused_variables)] context: PluginContext) {}
 
+    /// This method is called when your plugin is about to be re-loaded from a dynamic library. It is
+    /// called before any of the plugin's content (the plugin itself, its scripts and scene nodes) is
+    /// serialized, so it could be used to move runtime state into serializable fields. After the
+    /// new version of the library is loaded, the state is transferred into a new plugin instance,
+    /// fields are matched by their names and new fields get their default values. Then
+    /// [`Plugin::on_loaded`] is called. This method is called **only for dynamic plugins!**
+    fn on_before_reload(&mut self, #[allow(unused_variables)] context: PluginContext) {}
+
     /// The method is called before plugin will be disabled. It should be used for clean up, or some
     /// additional actions.
     fn on_deinit(&mut self, #[allow(unused_variables)] context: PluginContext) {}
@@ -303,10 +359,23 @@ pub trait Plugin: Downcast + Visit + Reflect {
     /// data (render something to texture, that can be used later in the main frame).
     fn before_rendering(&mut self, #[allow(unused_variables)] context: PluginContext) {}
 
+    /// The method is called after the frame was rendered. It could be useful to read or copy the
+    /// rendered data (for example, to submit it to a head-mounted display).
+    fn after_rendering(&mut self, #[allow(unused_variables)] context: PluginContext) {}
+
     /// The method is called when the current graphics context was destroyed.
     fn on_graphics_context_destroyed(&mut self, #[allow(unused_variables)] context: PluginContext) {
     }
 
+    /// The method is called when the application is paused, resumed or runs low on memory. See
+    /// [`LifecycleEvent`] docs for more info.
+    fn on_lifecycle_event(
+        &mut self,
+        #[allow(unused_variables)] event: LifecycleEvent,
+        #[allow(unused_variables)] context: PluginContext,
+    ) {
+    }
+
     /// The method will be called when there is any message from main user interface instance
     /// of the engine.
     fn on_ui_message(
@@ -316,6 +385,27 @@ pub trait Plugin: Downcast + Visit + Reflect {
     ) {
     }
 
+    /// This method is called when a resource was reloaded, for example when its source file was
+    /// changed and the resource manager has a file system watcher. It is called for resources of
+    /// any type, including the custom ones registered by the plugin. The resource data is already
+    /// replaced with the new one, so it could be used to re-create any data derived from it.
+    fn on_resource_reloaded(
+        &mut self,
+        #[allow(unused_variables)] resource: &UntypedResource,
+        #[allow(unused_variables)] context: &mut PluginContext,
+    ) {
+    }
+
+    /// This method is called when a `dependency` of the `resource` was reloaded. For example, it is
+    /// called for a custom resource that stores a texture, when the texture was reloaded.
+    fn on_resource_dependency_reloaded(
+        &mut self,
+        #[allow(unused_variables)] resource: &UntypedResource,
+        #[allow(unused_variables)] dependency: &UntypedResource,
+        #[allow(unused_variables)] context: &mut PluginContext,
+    ) {
+    }
+
     /// This method is called when the engine starts loading a scene from the given `path`. It could
     /// be used to "catch" the moment when the scene is about to be loaded; to show a progress bar
     /// for example. See [`AsyncSceneLoader`] docs for usage example.
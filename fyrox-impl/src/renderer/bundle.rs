@@ -60,12 +60,13 @@ use crate::{
     },
     resource::texture::TextureResource,
     scene::{
+        dim2::SortMode2D,
         graph::Graph,
         light::{
             directional::{CsmOptions, DirectionalLight},
             point::PointLight,
             spot::SpotLight,
-            BaseLight,
+            BaseLight, ShadowTechnique,
         },
         mesh::{
             buffer::{
@@ -102,6 +103,9 @@ pub struct ObserverInfo {
     pub view_matrix: Matrix4<f32>,
     /// Projection matrix of the observer.
     pub projection_matrix: Matrix4<f32>,
+    /// Size of a virtual pixel (in world units) of the observer. It is defined only for cameras in
+    /// pixel-perfect mode, 2D nodes snap their positions to the pixel grid of this size.
+    pub pixel_grid_size: Option<f32>,
 }
 
 /// Render context is used to collect render data from the scene nodes. It provides all required information about
@@ -130,17 +134,56 @@ impl RenderContext<'_> {
     /// using Z coordinate. This index could be used for back-to-front sorting to prevent blending
     /// issues.
     pub fn calculate_sorting_index(&self, global_position: Vector3<f32>) -> u64 {
+        self.calculate_sorting_index_2d(global_position, SortMode2D::Depth)
+    }
+
+    /// Snaps the translation part of the given transform to the virtual pixel grid of the
+    /// observer (see [`ObserverInfo::pixel_grid_size`]). The transform is returned as is, if the
+    /// observer has no pixel grid.
+    pub fn snap_to_pixel_grid(&self, mut transform: Matrix4<f32>) -> Matrix4<f32> {
+        if let Some(pixel_size) = self.observer_info.pixel_grid_size {
+            for i in 12..14 {
+                transform[i] = (transform[i] / pixel_size).round() * pixel_size;
+            }
+        }
+        transform
+    }
+
+    /// Calculates sorting index of the given point using the given 2D sorting mode. The index
+    /// consists of two parts: the distance to the observer (higher bits) and the order within the
+    /// nodes at the same distance (lower bits). Depth-sorted nodes always go first at the same
+    /// distance, Y-sorted nodes are ordered by world Y coordinate of the point. See [`SortMode2D`]
+    /// docs for more info.
+    pub fn calculate_sorting_index_2d(
+        &self,
+        global_position: Vector3<f32>,
+        sort_mode: SortMode2D,
+    ) -> u64 {
         let granularity = 1000.0;
-        u64::MAX
-            - (self
-                .observer_info
-                .view_matrix
-                .transform_point(&(global_position.into()))
-                .z
-                * granularity) as u64
+        // The observer looks along -Z axis in the view space.
+        let depth = -self
+            .observer_info
+            .view_matrix
+            .transform_point(&(global_position.into()))
+            .z;
+        let depth = (depth * granularity).clamp(0.0, u32::MAX as f32) as u64;
+        let order = match sort_mode {
+            SortMode2D::Depth => u32::MAX as u64,
+            SortMode2D::WorldY => {
+                let y = (global_position.y * granularity) as i64 + i32::MAX as i64;
+                y.clamp(0, u32::MAX as i64 - 1) as u64
+            }
+        };
+        u64::MAX - ((depth << 32) | order)
     }
 }
 
+/// Returns `true` if the given sort index was calculated for a Y-sorted node. Such nodes must not
+/// be batched with the nodes at different Y, otherwise they will be drawn in wrong order.
+fn is_y_sorted(sort_index: u64) -> bool {
+    sort_index as u32 != 0
+}
+
 #[allow(missing_docs)] // TODO
 pub struct BundleRenderContext<'a> {
     pub texture_cache: &'a mut TextureCache,
@@ -576,6 +619,17 @@ impl RenderDataBundle {
             }
         }
 
+        // Surfaces with per-instance vertex attributes store one vertex per each drawn copy of
+        // their elements, so every surface instance is drawn with a single instanced draw call.
+        let instance_count = {
+            let data = self.data.data_ref();
+            data.vertex_buffer
+                .layout()
+                .iter()
+                .any(|attribute| attribute.divisor > 0)
+                .then(|| data.vertex_buffer.vertex_count() as usize)
+        };
+
         for (instance, uniform_data) in self
             .instances
             .iter()
@@ -625,21 +679,34 @@ impl RenderDataBundle {
                 };
             }
 
-            stats += render_context.frame_buffer.draw(
-                geometry,
-                render_context.viewport,
-                &*render_pass.program,
-                &render_pass.draw_params,
-                &[
-                    ResourceBindGroup {
-                        bindings: &material_bindings,
-                    },
-                    ResourceBindGroup {
-                        bindings: &instance_bindings,
-                    },
-                ],
-                instance.element_range,
-            )?;
+            let resources = [
+                ResourceBindGroup {
+                    bindings: &material_bindings,
+                },
+                ResourceBindGroup {
+                    bindings: &instance_bindings,
+                },
+            ];
+
+            if let Some(instance_count) = instance_count {
+                stats += render_context.frame_buffer.draw_instances(
+                    instance_count,
+                    geometry,
+                    render_context.viewport,
+                    &*render_pass.program,
+                    &render_pass.draw_params,
+                    &resources,
+                );
+            } else {
+                stats += render_context.frame_buffer.draw(
+                    geometry,
+                    render_context.viewport,
+                    &*render_pass.program,
+                    &render_pass.draw_params,
+                    &resources,
+                    instance.element_range,
+                )?;
+            }
         }
 
         Ok(stats)
@@ -654,6 +721,7 @@ pub trait RenderDataBundleStorageTrait {
     /// - Material
     /// - Vertex Type
     /// - Render Path
+    /// - Sort Index (only for Y-sorted 2D nodes, see [`SortMode2D`])
     ///
     /// If one of these parameters is different, then a new bundle will be created and used to store
     /// the given vertices and indices. If an appropriate bundle exists, the method will store
@@ -664,7 +732,8 @@ pub trait RenderDataBundleStorageTrait {
     /// This method is used to reduce amount of draw calls of underlying GAPI, by merging small
     /// portions of data into one big block that shares drawing parameters and can be rendered in
     /// a single draw call. The vertices in this case should be pre-processed by applying world
-    /// transform to them. This is so-called dynamic batching.
+    /// transform to them. This is so-called dynamic batching. Materials are compared by their
+    /// content, so the nodes with separate, but equal materials will be merged as well.
     ///
     /// Do not use this method if you have a mesh with lots of vertices and triangles, because
     /// pre-processing them on CPU could take more time than rendering them directly on GPU one-by-one.
@@ -724,6 +793,7 @@ pub struct LightSource {
     pub intensity: f32,
     pub scatter_enabled: bool,
     pub scatter: Vector3<f32>,
+    pub shadow_technique: ShadowTechnique,
 }
 
 /// Bundle storage handles bundle generation for a scene before rendering. It is used to optimize
@@ -734,6 +804,9 @@ pub struct RenderDataBundleStorage {
     /// A sorted list of bundles.
     pub bundles: Vec<RenderDataBundle>,
     pub light_sources: Vec<LightSource>,
+    /// Keys and temporary surfaces of the bundles created by dynamic batching. See
+    /// [`crate::renderer::cache::batch::DynamicBatchCache`] for more info.
+    pub(crate) dynamic_batches: Vec<(u64, SurfaceResource)>,
 }
 
 pub struct RenderDataBundleStorageOptions {
@@ -755,6 +828,7 @@ impl RenderDataBundleStorage {
             observer_info,
             bundles: Default::default(),
             light_sources: Default::default(),
+            dynamic_batches: Default::default(),
         }
     }
 
@@ -775,6 +849,7 @@ impl RenderDataBundleStorage {
             observer_info: observer_info.clone(),
             bundles: Vec::with_capacity(capacity),
             light_sources: Default::default(),
+            dynamic_batches: Default::default(),
         };
 
         let frustum = Frustum::from_view_projection_matrix(
@@ -842,6 +917,7 @@ impl RenderDataBundleStorage {
                             intensity: base_light.intensity(),
                             scatter_enabled: base_light.is_scatter_enabled(),
                             scatter: base_light.scatter(),
+                            shadow_technique: base_light.shadow_technique(),
                         };
 
                         storage.light_sources.push(source);
@@ -885,7 +961,9 @@ impl RenderDataBundleStorage {
 
     /// Sorts the bundles by their respective sort index.
     pub fn sort(&mut self) {
-        self.bundles.sort_unstable_by_key(|b| b.sort_index);
+        // Stable sorting keeps the order of the nodes with the same index, which is important for
+        // 2D nodes that are usually placed at the same depth.
+        self.bundles.sort_by_key(|b| b.sort_index);
     }
 
     pub fn write_global_uniform_blocks(
@@ -1033,6 +1111,7 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
     /// - Material
     /// - Vertex Type
     /// - Render Path
+    /// - Sort Index (only for Y-sorted 2D nodes, see [`SortMode2D`])
     ///
     /// If one of these parameters is different, then a new bundle will be created and used to store
     /// the given vertices and indices. If an appropriate bundle exists, the method will store the
@@ -1056,10 +1135,21 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
         node_handle: Handle<Node>,
         func: &mut dyn FnMut(VertexBufferRefMut, TriangleBufferRefMut),
     ) {
+        // Materials are compared by their content, not by their identity. This allows merging 2D
+        // nodes that use separate instances of the same material (for example, sprites that use
+        // the same atlas) into a single draw call.
+        let material_key = material
+            .state()
+            .data()
+            .map_or_else(|| material.key(), |material| material.content_hash());
+
         let mut hasher = FxHasher::default();
-        hasher.write_u64(material.key());
+        hasher.write_u64(material_key);
         layout.hash(&mut hasher);
         hasher.write_u32(render_path as u32);
+        if is_y_sorted(sort_index) {
+            hasher.write_u64(sort_index);
+        }
         let key = hasher.finish();
 
         let bundle = if let Some(&bundle_index) = self.bundle_map.get(&key) {
@@ -1085,6 +1175,7 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
             );
 
             self.bundle_map.insert(key, self.bundles.len());
+            self.dynamic_batches.push((key, data.clone()));
             self.bundles.push(RenderDataBundle {
                 data,
                 sort_index,
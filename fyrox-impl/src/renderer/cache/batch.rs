@@ -0,0 +1,198 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Dynamic batch cache keeps the surfaces of dynamic batches (see
+//! [`crate::renderer::bundle::RenderDataBundleStorageTrait::push_triangles`]) between frames. Without it, every batch would
+//! get a new GPU buffer each frame. With the cache, each batch keeps its GPU buffer, and the buffer
+//! is uploaded again only when the content of the batch changes (for example, when some sprite
+//! moves).
+
+use crate::{
+    asset::untyped::ResourceKind,
+    renderer::{bundle::RenderDataBundleStorage, cache::TimeToLive},
+    scene::mesh::surface::{SurfaceData, SurfaceResource},
+};
+use fxhash::FxHashMap;
+
+struct DynamicBatch {
+    data: SurfaceResource,
+    time_to_live: TimeToLive,
+}
+
+/// See module docs.
+#[derive(Default)]
+pub struct DynamicBatchCache {
+    batches: FxHashMap<u64, DynamicBatch>,
+}
+
+/// Copies the content of the source into the destination, but only if it differs. The destination
+/// keeps its identity, so it will use the same GPU buffer.
+fn synchronize(dest: &mut SurfaceData, source: &SurfaceData) {
+    if dest.vertex_buffer.raw_data() != source.vertex_buffer.raw_data() {
+        let vertex_size = source.vertex_buffer.vertex_size() as usize;
+        let mut vertex_buffer = dest.vertex_buffer.modify();
+        vertex_buffer.clear();
+        for vertex in source.vertex_buffer.raw_data().chunks_exact(vertex_size) {
+            // Layouts of the buffers are the same, since it is a part of the batch key.
+            vertex_buffer.push_vertex_raw(vertex).unwrap();
+        }
+    }
+
+    if dest.geometry_buffer.triangles_ref() != source.geometry_buffer.triangles_ref() {
+        dest.geometry_buffer
+            .set_triangles(source.geometry_buffer.triangles_ref().to_vec());
+    }
+}
+
+impl DynamicBatchCache {
+    /// Replaces temporary surfaces of the dynamic batches in the given storage with persistent
+    /// ones.
+    pub fn apply(&mut self, storage: &mut RenderDataBundleStorage) {
+        let mut replacements = FxHashMap::default();
+
+        for (key, temporary) in storage.dynamic_batches.drain(..) {
+            let batch = self.batches.entry(key).or_insert_with(|| DynamicBatch {
+                data: SurfaceResource::new_ok(
+                    ResourceKind::Embedded,
+                    SurfaceData::new(
+                        temporary.data_ref().vertex_buffer.clone_empty(0),
+                        Default::default(),
+                    ),
+                ),
+                time_to_live: Default::default(),
+            });
+            batch.time_to_live = Default::default();
+
+            synchronize(&mut batch.data.data_ref(), &temporary.data_ref());
+
+            replacements.insert(temporary.key(), batch.data.clone());
+        }
+
+        if replacements.is_empty() {
+            return;
+        }
+
+        for bundle in storage.bundles.iter_mut() {
+            if let Some(persistent) = replacements.get(&bundle.data.key()) {
+                bundle.data = persistent.clone();
+                bundle.time_to_live = Default::default();
+            }
+        }
+    }
+
+    /// Removes the batches that were not used for a while.
+    pub fn update(&mut self, dt: f32) {
+        self.batches.retain(|_, batch| {
+            *batch.time_to_live -= dt;
+            *batch.time_to_live > 0.0
+        });
+    }
+
+    /// Removes all the batches from the cache.
+    pub fn clear(&mut self) {
+        self.batches.clear();
+    }
+
+    /// Returns the amount of batches in the cache.
+    pub fn alive_count(&self) -> usize {
+        self.batches.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::{algebra::Vector3, math::TriangleDefinition, pool::Handle, value_as_u8_slice},
+        material::{Material, MaterialResource},
+        renderer::bundle::{ObserverInfo, RenderDataBundleStorageTrait},
+        scene::{
+            dim2::rectangle::RectangleVertex,
+            mesh::{buffer::VertexTrait, RenderPath},
+        },
+    };
+
+    fn push_sprite(storage: &mut RenderDataBundleStorage, material: &MaterialResource, x: f32) {
+        storage.push_triangles(
+            RectangleVertex::layout(),
+            material,
+            RenderPath::Forward,
+            0,
+            Handle::NONE,
+            &mut |mut vertex_buffer, mut triangle_buffer| {
+                let start_vertex_index = vertex_buffer.vertex_count();
+                for y in [0.0, 1.0, 2.0] {
+                    let vertex = RectangleVertex {
+                        position: Vector3::new(x, y, 0.0),
+                        ..Default::default()
+                    };
+                    vertex_buffer
+                        .push_vertex_raw(value_as_u8_slice(&vertex))
+                        .unwrap();
+                }
+                triangle_buffer.push_triangles_iter_with_offset(
+                    start_vertex_index,
+                    [TriangleDefinition([0, 1, 2])].into_iter(),
+                );
+            },
+        );
+    }
+
+    fn make_storage(second_sprite_position: f32) -> RenderDataBundleStorage {
+        let mut storage = RenderDataBundleStorage::new_empty(ObserverInfo::default());
+        // Separate materials with the same content.
+        let a = MaterialResource::new_ok(Default::default(), Material::standard_2d());
+        let b = MaterialResource::new_ok(Default::default(), Material::standard_2d());
+        push_sprite(&mut storage, &a, 0.0);
+        push_sprite(&mut storage, &b, second_sprite_position);
+        storage
+    }
+
+    #[test]
+    fn test_dynamic_batch_reuse() {
+        let mut cache = DynamicBatchCache::default();
+
+        let mut storage = make_storage(1.0);
+        assert_eq!(storage.bundles.len(), 1);
+        cache.apply(&mut storage);
+        let data = storage.bundles[0].data.clone();
+        let modifications_count = data.data_ref().vertex_buffer.modifications_count();
+        assert_eq!(data.data_ref().vertex_buffer.vertex_count(), 6);
+
+        // Nothing has changed, the same surface must be used without any modifications.
+        let mut storage = make_storage(1.0);
+        cache.apply(&mut storage);
+        assert_eq!(storage.bundles[0].data.key(), data.key());
+        assert_eq!(
+            data.data_ref().vertex_buffer.modifications_count(),
+            modifications_count
+        );
+
+        // One of the sprites has moved.
+        let mut storage = make_storage(2.0);
+        cache.apply(&mut storage);
+        assert_eq!(storage.bundles[0].data.key(), data.key());
+        assert_ne!(
+            data.data_ref().vertex_buffer.modifications_count(),
+            modifications_count
+        );
+        assert_eq!(cache.alive_count(), 1);
+    }
+}
@@ -29,6 +29,7 @@ use std::{
     sync::Arc,
 };
 
+pub mod batch;
 pub mod geometry;
 pub mod shader;
 pub mod texture;
@@ -93,6 +93,9 @@ fn convert_pixel_kind(texture_kind: TexturePixelKind) -> PixelKind {
         TexturePixelKind::DXT5RGBA => PixelKind::DXT5RGBA,
         TexturePixelKind::R8RGTC => PixelKind::R8RGTC,
         TexturePixelKind::RG8RGTC => PixelKind::RG8RGTC,
+        TexturePixelKind::ETC2RGB => PixelKind::ETC2RGB,
+        TexturePixelKind::ETC2RGBA => PixelKind::ETC2RGBA,
+        TexturePixelKind::ASTC4x4RGBA => PixelKind::ASTC4x4RGBA,
         TexturePixelKind::RGB32F => PixelKind::RGB32F,
         TexturePixelKind::RGBA32F => PixelKind::RGBA32F,
         TexturePixelKind::Luminance8 => PixelKind::L8,
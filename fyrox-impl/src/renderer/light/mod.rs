@@ -51,6 +51,9 @@ use crate::{
             csm::{CsmRenderContext, CsmRenderer},
             point::{PointShadowMapRenderContext, PointShadowMapRenderer},
             spot::SpotShadowMapRenderer,
+            variance::{
+                VarianceShadowMapRenderContext, VarianceShadowMapRenderer, VarianceShadowParameters,
+            },
         },
         skybox_shader::SkyboxShader,
         ssao::ScreenSpaceAmbientOcclusionRenderer,
@@ -60,6 +63,7 @@ use crate::{
     },
     scene::{
         camera::Camera,
+        light::directional::CSM_NUM_CASCADES,
         mesh::{
             buffer::{TriangleBuffer, VertexBuffer},
             surface::SurfaceData,
@@ -75,6 +79,9 @@ pub mod directional;
 pub mod point;
 pub mod spot;
 
+/// Near plane of the projection of spot light shadow maps.
+const SPOT_SHADOW_MAP_Z_NEAR: f32 = 0.01;
+
 pub struct DeferredLightRenderer {
     pub ssao_renderer: ScreenSpaceAmbientOcclusionRenderer,
     spot_light_shader: SpotLightShader,
@@ -90,6 +97,7 @@ pub struct DeferredLightRenderer {
     spot_shadow_map_renderer: SpotShadowMapRenderer,
     point_shadow_map_renderer: PointShadowMapRenderer,
     csm_renderer: CsmRenderer,
+    variance_shadow_map_renderer: VarianceShadowMapRenderer,
     light_volume: LightVolumeRenderer,
 }
 
@@ -210,6 +218,7 @@ impl DeferredLightRenderer {
                 server,
                 settings.spot_shadow_map_size,
                 quality_defaults.spot_shadow_map_precision,
+                quality_defaults.spot_shadows_cache_static_geometry,
             )?,
             point_shadow_map_renderer: PointShadowMapRenderer::new(
                 server,
@@ -221,7 +230,9 @@ impl DeferredLightRenderer {
                 server,
                 quality_defaults.csm_settings.size,
                 quality_defaults.csm_settings.precision,
+                quality_defaults.csm_settings.cache_static_geometry,
             )?,
+            variance_shadow_map_renderer: VarianceShadowMapRenderer::new(server)?,
         })
     }
 
@@ -232,11 +243,14 @@ impl DeferredLightRenderer {
     ) -> Result<(), FrameworkError> {
         if settings.spot_shadow_map_size != self.spot_shadow_map_renderer.base_size()
             || settings.spot_shadow_map_precision != self.spot_shadow_map_renderer.precision()
+            || settings.spot_shadows_cache_static_geometry
+                != self.spot_shadow_map_renderer.caches_static_geometry()
         {
             self.spot_shadow_map_renderer = SpotShadowMapRenderer::new(
                 server,
                 settings.spot_shadow_map_size,
                 settings.spot_shadow_map_precision,
+                settings.spot_shadows_cache_static_geometry,
             )?;
         }
         if settings.point_shadow_map_size != self.point_shadow_map_renderer.base_size()
@@ -250,11 +264,14 @@ impl DeferredLightRenderer {
         }
         if settings.csm_settings.precision != self.csm_renderer.precision()
             || settings.csm_settings.size != self.csm_renderer.size()
+            || settings.csm_settings.cache_static_geometry
+                != self.csm_renderer.caches_static_geometry()
         {
             self.csm_renderer = CsmRenderer::new(
                 server,
                 settings.csm_settings.size,
                 settings.csm_settings.precision,
+                settings.csm_settings.cache_static_geometry,
             )?;
         }
         self.ssao_renderer.set_radius(settings.ssao_radius);
@@ -533,6 +550,17 @@ impl DeferredLightRenderer {
 
             let mut light_view_projection = Matrix4::identity();
 
+            let variance_parameters =
+                VarianceShadowParameters::from_technique(&light.shadow_technique);
+            let (shadow_technique, light_bleeding_reduction, shadow_exponents) =
+                variance_parameters.map_or((0, 0.0, Vector2::default()), |parameters| {
+                    (
+                        parameters.technique,
+                        parameters.light_bleeding_reduction,
+                        parameters.exponents,
+                    )
+                });
+
             // Mark lit areas in stencil buffer to do light calculations only on them.
             let uniform_buffer = uniform_buffer_cache.write(
                 StaticUniformBuffer::<256>::new().with(&(view_projection * bounding_shape_matrix)),
@@ -631,7 +659,7 @@ impl DeferredLightRenderer {
                     LightSourceKind::Spot {
                         full_cone_angle, ..
                     } => {
-                        let z_near = 0.01;
+                        let z_near = SPOT_SHADOW_MAP_Z_NEAR;
                         let z_far = light_radius;
                         let light_projection_matrix =
                             Matrix4::new_perspective(1.0, full_cone_angle, z_near, z_far);
@@ -655,6 +683,7 @@ impl DeferredLightRenderer {
                             server,
                             &scene.graph,
                             elapsed_time,
+                            light.handle,
                             light.position,
                             light_view_matrix,
                             z_near,
@@ -668,6 +697,24 @@ impl DeferredLightRenderer {
                             uniform_memory_allocator,
                         )?;
 
+                        if let Some(parameters) = variance_parameters {
+                            pass_stats += self.variance_shadow_map_renderer.render(
+                                VarianceShadowMapRenderContext {
+                                    server,
+                                    quad: &*self.quad,
+                                    uniform_buffer_cache,
+                                    depth: self
+                                        .spot_shadow_map_renderer
+                                        .cascade_texture(cascade_index),
+                                    size: self.spot_shadow_map_renderer.cascade_size(cascade_index),
+                                    slot: 0,
+                                    parameters,
+                                    perspective: true,
+                                    depth_range: Vector2::new(z_near, z_far),
+                                },
+                            )?;
+                        }
+
                         light_stats.spot_shadow_maps_rendered += 1;
                     }
                     LightSourceKind::Point { .. } => {
@@ -704,6 +751,24 @@ impl DeferredLightRenderer {
                             uniform_memory_allocator,
                         })?;
 
+                        if let Some(parameters) = variance_parameters {
+                            for (slot, cascade) in self.csm_renderer.cascades().iter().enumerate() {
+                                pass_stats += self.variance_shadow_map_renderer.render(
+                                    VarianceShadowMapRenderContext {
+                                        server,
+                                        quad: &*self.quad,
+                                        uniform_buffer_cache,
+                                        depth: cascade.texture(),
+                                        size: self.csm_renderer.size(),
+                                        slot,
+                                        parameters,
+                                        perspective: false,
+                                        depth_range: Vector2::default(),
+                                    },
+                                )?;
+                            }
+                        }
+
                         light_stats.csm_rendered += 1;
                     }
                     LightSourceKind::Unknown => {}
@@ -756,8 +821,18 @@ impl DeferredLightRenderer {
 
                         light_stats.spot_lights_rendered += 1;
 
-                        let inv_size = 1.0
-                            / (self.spot_shadow_map_renderer.cascade_size(cascade_index) as f32);
+                        let shadow_map_size =
+                            self.spot_shadow_map_renderer.cascade_size(cascade_index);
+                        let inv_size = 1.0 / (shadow_map_size as f32);
+                        // Variance shadow maps replace the depth shadow map.
+                        let shadow_texture = variance_parameters
+                            .and_then(|_| {
+                                self.variance_shadow_map_renderer
+                                    .moments(shadow_map_size, 0)
+                            })
+                            .unwrap_or_else(|| {
+                                self.spot_shadow_map_renderer.cascade_texture(cascade_index)
+                            });
                         let uniform_buffer = uniform_buffer_cache.write(
                             StaticUniformBuffer::<1024>::new()
                                 .with(&frame_matrix)
@@ -776,7 +851,18 @@ impl DeferredLightRenderer {
                                 .with(&shadows_alpha)
                                 .with(&cookie_enabled)
                                 .with(&shadows_enabled)
-                                .with(&settings.spot_soft_shadows),
+                                .with(
+                                    &settings
+                                        .shadow_filter
+                                        .shader_filter(settings.spot_soft_shadows),
+                                )
+                                .with(&(settings.shadow_filter.kernel_size as i32))
+                                .with(&settings.shadow_filter.pcss_search_radius)
+                                .with(&settings.shadow_filter.pcss_penumbra_scale)
+                                .with(&shadow_technique)
+                                .with(&light_bleeding_reduction)
+                                .with(&shadow_exponents)
+                                .with(&Vector2::new(SPOT_SHADOW_MAP_Z_NEAR, light_radius)),
                         )?;
 
                         frame_buffer.draw(
@@ -803,9 +889,7 @@ impl DeferredLightRenderer {
                                         &shader.material_sampler,
                                     ),
                                     ResourceBinding::texture(
-                                        &self
-                                            .spot_shadow_map_renderer
-                                            .cascade_texture(cascade_index),
+                                        &shadow_texture,
                                         &shader.spot_shadow_texture,
                                     ),
                                     ResourceBinding::texture(
@@ -901,6 +985,16 @@ impl DeferredLightRenderer {
                             self.csm_renderer.cascades()[2].view_proj_matrix,
                         ];
 
+                        // Variance shadow maps replace the depth shadow maps of the cascades.
+                        let cascade_textures: [_; CSM_NUM_CASCADES] = std::array::from_fn(|i| {
+                            variance_parameters
+                                .and_then(|_| {
+                                    self.variance_shadow_map_renderer
+                                        .moments(self.csm_renderer.size(), i)
+                                })
+                                .unwrap_or_else(|| self.csm_renderer.cascades()[i].texture())
+                        });
+
                         let uniform_buffer = uniform_buffer_cache.write(
                             StaticUniformBuffer::<1024>::new()
                                 .with(&frame_matrix)
@@ -913,7 +1007,17 @@ impl DeferredLightRenderer {
                                 .with(&light.intensity)
                                 .with(&shadows_enabled)
                                 .with(&csm_options.shadow_bias())
-                                .with(&settings.csm_settings.pcf)
+                                .with(
+                                    &settings
+                                        .shadow_filter
+                                        .shader_filter(settings.csm_settings.pcf),
+                                )
+                                .with(&(settings.shadow_filter.kernel_size as i32))
+                                .with(&settings.shadow_filter.pcss_search_radius)
+                                .with(&settings.shadow_filter.pcss_penumbra_scale)
+                                .with(&shadow_technique)
+                                .with(&light_bleeding_reduction)
+                                .with(&shadow_exponents)
                                 .with(&(1.0 / (self.csm_renderer.size() as f32)))
                                 .with_slice(&distances),
                         )?;
@@ -954,15 +1058,15 @@ impl DeferredLightRenderer {
                                         &shader.material_sampler,
                                     ),
                                     ResourceBinding::texture(
-                                        &self.csm_renderer.cascades()[0].texture(),
+                                        &cascade_textures[0],
                                         &shader.shadow_cascade0,
                                     ),
                                     ResourceBinding::texture(
-                                        &self.csm_renderer.cascades()[1].texture(),
+                                        &cascade_textures[1],
                                         &shader.shadow_cascade1,
                                     ),
                                     ResourceBinding::texture(
-                                        &self.csm_renderer.cascades()[2].texture(),
+                                        &cascade_textures[2],
                                         &shader.shadow_cascade2,
                                     ),
                                     ResourceBinding::Buffer {
@@ -999,6 +1103,11 @@ impl DeferredLightRenderer {
             }
         }
 
+        // Free the static shadow caches of the light sources, that are gone or no longer cast
+        // shadows.
+        self.csm_renderer.remove_unused_static_caches();
+        self.spot_shadow_map_renderer.remove_unused_static_caches();
+
         Ok((pass_stats, light_stats))
     }
 }
@@ -0,0 +1,636 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! 2D light renderer renders [`Light2D`] nodes of a scene into a light buffer and multiplies the
+//! frame by it. Shadows are rendered using stencil buffer: every occluder segment is extruded away
+//! from the light into a shadow volume, that masks pixels which must not be lit. Soft shadows are
+//! made by accumulating multiple samples of the light with jittered origins of shadow volumes.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        color::Color,
+        math::{Rect, TriangleDefinition},
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        bundle::{BundleRenderContext, RenderDataBundleStorage},
+        cache::{
+            shader::ShaderCache,
+            texture::TextureCache,
+            uniform::{UniformBufferCache, UniformMemoryAllocator},
+        },
+        flat_shader::FlatShader,
+        framework::{
+            buffer::BufferUsage,
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, BufferLocation, FrameBuffer, ResourceBindGroup,
+                ResourceBinding,
+            },
+            geometry_buffer::{
+                AttributeDefinition, AttributeKind, ElementsDescriptor, GeometryBuffer,
+                GeometryBufferDescriptor, VertexBufferData, VertexBufferDescriptor,
+            },
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{GpuTexture, GpuTextureDescriptor, GpuTextureKind, PixelKind},
+            server::GraphicsServer,
+            uniform::StaticUniformBuffer,
+            BlendFactor, BlendFunc, BlendParameters, ColorMask, CompareFunc, DrawParameters,
+            ElementRange, StencilAction, StencilFunc, StencilOp,
+        },
+        make_viewport_matrix, FallbackResources, GeometryCache, QualitySettings,
+        RenderPassStatistics,
+    },
+    scene::{
+        camera::Camera,
+        dim2::light::{collect_occluder_segments, Light2D, Light2DKind, OccluderSegment},
+        graph::Graph,
+        mesh::RenderPath,
+    },
+};
+use std::{cell::RefCell, f32::consts::PI, rc::Rc};
+
+/// Amount of samples of a light with soft shadows.
+const SOFT_SHADOW_SAMPLES: usize = 8;
+
+struct Light2DShader {
+    program: Box<dyn GpuProgram>,
+    uniform_block_binding: usize,
+    normal_texture: UniformLocation,
+    falloff_texture: UniformLocation,
+}
+
+impl Light2DShader {
+    fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/light2d_fs.glsl");
+        let vertex_source = include_str!("shaders/light2d_vs.glsl");
+        let program = server.create_program("Light2DShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            uniform_block_binding: program
+                .uniform_block_index(&ImmutableString::new("Uniforms"))?,
+            normal_texture: program.uniform_location(&ImmutableString::new("normalTexture"))?,
+            falloff_texture: program.uniform_location(&ImmutableString::new("falloffTexture"))?,
+            program,
+        })
+    }
+}
+
+struct Shadow2DShader {
+    program: Box<dyn GpuProgram>,
+    uniform_block_binding: usize,
+}
+
+impl Shadow2DShader {
+    fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/shadow2d_fs.glsl");
+        let vertex_source = include_str!("shaders/shadow2d_vs.glsl");
+        let program = server.create_program("Shadow2DShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            uniform_block_binding: program
+                .uniform_block_index(&ImmutableString::new("Uniforms"))?,
+            program,
+        })
+    }
+}
+
+pub(crate) struct Light2DRenderContext<'a> {
+    pub server: &'a dyn GraphicsServer,
+    pub graph: &'a Graph,
+    pub camera: &'a Camera,
+    pub viewport: Rect<i32>,
+    pub ambient_light: Color,
+    pub bundle_storage: &'a RenderDataBundleStorage,
+    pub geometry_cache: &'a mut GeometryCache,
+    pub shader_cache: &'a mut ShaderCache,
+    pub texture_cache: &'a mut TextureCache,
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+    pub uniform_memory_allocator: &'a mut UniformMemoryAllocator,
+    pub fallback_resources: &'a FallbackResources,
+    pub quality_settings: &'a QualitySettings,
+    pub quad: &'a dyn GeometryBuffer,
+    pub frame_buffer: &'a mut dyn FrameBuffer,
+}
+
+/// Renderer of 2D lights, it has to be created per scene, because it contains scene-sized light
+/// buffer.
+pub struct Light2DRenderer {
+    light_shader: Light2DShader,
+    shadow_shader: Shadow2DShader,
+    flat_shader: FlatShader,
+    light_framebuffer: Box<dyn FrameBuffer>,
+    normal_framebuffer: Box<dyn FrameBuffer>,
+    shadow_geometry: Box<dyn GeometryBuffer>,
+    normals_pass_name: ImmutableString,
+    lights: Vec<LightInfo>,
+    segments: Vec<OccluderSegment>,
+    shadow_vertices: Vec<Vector3<f32>>,
+    shadow_triangles: Vec<TriangleDefinition>,
+}
+
+struct LightInfo {
+    kind: Light2DKind,
+    position: Vector3<f32>,
+    direction: Vector2<f32>,
+    color: Color,
+    intensity: f32,
+    radius: f32,
+    height: f32,
+    shadow_softness: f32,
+    cast_shadows: bool,
+    falloff_texture: Option<Rc<RefCell<dyn GpuTexture>>>,
+}
+
+fn distance_to_segment(point: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    let ab = b - a;
+    let t = ((point - a).dot(&ab) / ab.norm_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    (a + ab.scale(t) - point).norm()
+}
+
+/// Returns an offset of a sample of a light with soft shadows. Samples are evenly distributed
+/// inside a disc using golden angle spiral.
+fn sample_offset(index: usize, count: usize, radius: f32) -> Vector2<f32> {
+    if count <= 1 {
+        return Vector2::default();
+    }
+    let golden_angle = PI * (3.0 - 5.0f32.sqrt());
+    let distance = radius * ((index as f32 + 0.5) / count as f32).sqrt();
+    let angle = index as f32 * golden_angle;
+    Vector2::new(angle.cos(), angle.sin()).scale(distance)
+}
+
+/// Extrudes every segment within the given extent away from the origin. Far edge of each volume
+/// is split in two parts, so the volume always covers everything behind the segment up to the
+/// extent, even if the segment is very close to the origin.
+fn build_shadow_volumes(
+    origin: Vector2<f32>,
+    z: f32,
+    extent: f32,
+    segments: &[OccluderSegment],
+    vertices: &mut Vec<Vector3<f32>>,
+    triangles: &mut Vec<TriangleDefinition>,
+) {
+    vertices.clear();
+    triangles.clear();
+
+    let length = 2.0 * extent;
+    for &[a, b] in segments {
+        if distance_to_segment(origin, a, b) >= extent {
+            continue;
+        }
+
+        let (Some(da), Some(db)) = (
+            (a - origin).try_normalize(f32::EPSILON),
+            (b - origin).try_normalize(f32::EPSILON),
+        ) else {
+            continue;
+        };
+
+        // The segment lies on a ray from the origin, so it casts no shadow.
+        let Some(middle) = (da + db).try_normalize(f32::EPSILON) else {
+            continue;
+        };
+
+        let far_a = a + da.scale(length);
+        let far_b = b + db.scale(length);
+        let far_middle =
+            origin + middle.scale((a - origin).norm().max((b - origin).norm()) + length);
+
+        let first = vertices.len() as u32;
+        for point in [a, b, far_b, far_middle, far_a] {
+            vertices.push(Vector3::new(point.x, point.y, z));
+        }
+        triangles.push(TriangleDefinition([first, first + 1, first + 2]));
+        triangles.push(TriangleDefinition([first, first + 2, first + 3]));
+        triangles.push(TriangleDefinition([first, first + 3, first + 4]));
+    }
+}
+
+impl Light2DRenderer {
+    pub fn new(
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, FrameworkError> {
+        let depth_stencil = server.create_2d_render_target(PixelKind::D24S8, width, height)?;
+        let light_texture = server.create_2d_render_target(PixelKind::RGBA16F, width, height)?;
+        let light_framebuffer = server.create_frame_buffer(
+            Some(Attachment {
+                kind: AttachmentKind::DepthStencil,
+                texture: depth_stencil,
+            }),
+            vec![Attachment {
+                kind: AttachmentKind::Color,
+                texture: light_texture,
+            }],
+        )?;
+
+        let normal_texture = server.create_texture(GpuTextureDescriptor {
+            kind: GpuTextureKind::Rectangle { width, height },
+            pixel_kind: PixelKind::RGBA8,
+            ..Default::default()
+        })?;
+        let normal_framebuffer = server.create_frame_buffer(
+            None,
+            vec![Attachment {
+                kind: AttachmentKind::Color,
+                texture: normal_texture,
+            }],
+        )?;
+
+        let shadow_geometry = server.create_geometry_buffer(GeometryBufferDescriptor {
+            elements: ElementsDescriptor::Triangles(&[]),
+            buffers: &[VertexBufferDescriptor {
+                usage: BufferUsage::DynamicDraw,
+                attributes: &[AttributeDefinition {
+                    location: 0,
+                    kind: AttributeKind::Float,
+                    component_count: 3,
+                    normalized: false,
+                    divisor: 0,
+                }],
+                data: VertexBufferData::new::<Vector3<f32>>(None),
+            }],
+            usage: BufferUsage::DynamicDraw,
+        })?;
+
+        Ok(Self {
+            light_shader: Light2DShader::new(server)?,
+            shadow_shader: Shadow2DShader::new(server)?,
+            flat_shader: FlatShader::new(server)?,
+            light_framebuffer,
+            normal_framebuffer,
+            shadow_geometry,
+            normals_pass_name: ImmutableString::new("Normals2D"),
+            lights: Default::default(),
+            segments: Default::default(),
+            shadow_vertices: Default::default(),
+            shadow_triangles: Default::default(),
+        })
+    }
+
+    fn light_texture(&self) -> Rc<RefCell<dyn GpuTexture>> {
+        self.light_framebuffer.color_attachments()[0]
+            .texture
+            .clone()
+    }
+
+    fn normal_texture(&self) -> Rc<RefCell<dyn GpuTexture>> {
+        self.normal_framebuffer.color_attachments()[0]
+            .texture
+            .clone()
+    }
+
+    fn collect_lights(
+        &mut self,
+        server: &dyn GraphicsServer,
+        graph: &Graph,
+        camera: &Camera,
+        texture_cache: &mut TextureCache,
+    ) {
+        self.lights.clear();
+
+        let frustum = camera.frustum();
+        for light in graph
+            .linear_iter()
+            .filter(|node| node.is_globally_enabled())
+            .filter_map(|node| node.cast::<Light2D>())
+        {
+            let kind = light.kind();
+            if kind != Light2DKind::Global
+                && !frustum.is_intersects_aabb(&light.world_bounding_box())
+            {
+                continue;
+            }
+
+            self.lights.push(LightInfo {
+                kind,
+                position: light.global_position(),
+                direction: light.direction(),
+                color: light.color(),
+                intensity: light.intensity(),
+                radius: light.radius(),
+                height: light.height(),
+                shadow_softness: light.shadow_softness(),
+                cast_shadows: light.cast_shadows() && kind != Light2DKind::Global,
+                falloff_texture: light
+                    .falloff_texture()
+                    .and_then(|texture| texture_cache.get(server, texture).cloned()),
+            });
+        }
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        ctx: Light2DRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut stats = RenderPassStatistics::default();
+
+        let Light2DRenderContext {
+            server,
+            graph,
+            camera,
+            viewport,
+            ambient_light,
+            bundle_storage,
+            geometry_cache,
+            shader_cache,
+            texture_cache,
+            uniform_buffer_cache,
+            uniform_memory_allocator,
+            fallback_resources,
+            quality_settings,
+            quad,
+            frame_buffer,
+        } = ctx;
+
+        self.collect_lights(server, graph, camera, texture_cache);
+        if self.lights.is_empty() {
+            return Ok(stats);
+        }
+
+        self.segments.clear();
+        if self.lights.iter().any(|light| light.cast_shadows) {
+            collect_occluder_segments(graph, &mut self.segments);
+        }
+
+        // Render normals of the sprites and tiles first, alpha channel contains strength of the
+        // normal map, so the objects without normal maps will be lit uniformly.
+        self.normal_framebuffer
+            .clear(viewport, Some(Color::from_rgba(0, 0, 0, 0)), None, None);
+        stats += bundle_storage.render_to_frame_buffer(
+            server,
+            geometry_cache,
+            shader_cache,
+            |bundle| bundle.render_path == RenderPath::Forward,
+            |_| true,
+            BundleRenderContext {
+                texture_cache,
+                render_pass_name: &self.normals_pass_name,
+                frame_buffer: &mut *self.normal_framebuffer,
+                viewport,
+                uniform_memory_allocator,
+                use_pom: quality_settings.use_parallax_mapping,
+                light_position: &Default::default(),
+                fallback_resources,
+                ambient_light,
+                scene_depth: None,
+            },
+        )?;
+
+        // Ambient light is the base level of the light buffer.
+        self.light_framebuffer
+            .clear(viewport, Some(ambient_light), None, Some(0));
+
+        let frame_matrix = make_viewport_matrix(viewport);
+        let view_projection = camera.view_projection_matrix();
+        let inv_view_projection = view_projection.try_inverse().unwrap_or_default();
+        let normal_texture = self.normal_texture();
+
+        for light in self.lights.iter() {
+            let sample_count = if light.cast_shadows && light.shadow_softness > 0.0 {
+                SOFT_SHADOW_SAMPLES
+            } else {
+                1
+            };
+
+            let (half_cone_angle_cos, half_hotspot_cone_angle_cos) = match light.kind {
+                Light2DKind::Spot {
+                    cone_angle,
+                    falloff_angle_delta,
+                } => (
+                    (cone_angle * 0.5).cos(),
+                    ((cone_angle - falloff_angle_delta).max(0.0) * 0.5).cos(),
+                ),
+                _ => (-1.0, -1.0),
+            };
+            let kind_index: i32 = match light.kind {
+                Light2DKind::Point => 0,
+                Light2DKind::Spot { .. } => 1,
+                Light2DKind::Global => 2,
+            };
+
+            // Every sample contributes equal part of the light.
+            let color = light
+                .color
+                .srgb_to_linear_f32()
+                .xyz()
+                .scale(light.intensity / sample_count as f32);
+
+            for sample in 0..sample_count {
+                if light.cast_shadows {
+                    let origin = light.position.xy()
+                        + sample_offset(sample, sample_count, light.shadow_softness);
+
+                    build_shadow_volumes(
+                        origin,
+                        light.position.z,
+                        light.radius + light.shadow_softness,
+                        &self.segments,
+                        &mut self.shadow_vertices,
+                        &mut self.shadow_triangles,
+                    );
+
+                    // Clear stencil only.
+                    self.light_framebuffer.clear(viewport, None, None, Some(0));
+
+                    if !self.shadow_triangles.is_empty() {
+                        self.shadow_geometry
+                            .set_buffer_data_of_type(0, &self.shadow_vertices);
+                        self.shadow_geometry.set_triangles(&self.shadow_triangles);
+
+                        // Mark shadowed pixels in the stencil buffer.
+                        stats += self.light_framebuffer.draw(
+                            &*self.shadow_geometry,
+                            viewport,
+                            &*self.shadow_shader.program,
+                            &DrawParameters {
+                                cull_face: None,
+                                color_write: ColorMask::all(false),
+                                depth_write: false,
+                                stencil_test: Some(StencilFunc {
+                                    func: CompareFunc::Always,
+                                    ref_value: 1,
+                                    mask: 0xFFFF_FFFF,
+                                }),
+                                depth_test: None,
+                                blend: None,
+                                stencil_op: StencilOp {
+                                    zpass: StencilAction::Replace,
+                                    ..Default::default()
+                                },
+                                scissor_box: None,
+                            },
+                            &[ResourceBindGroup {
+                                bindings: &[ResourceBinding::Buffer {
+                                    buffer: uniform_buffer_cache.write(
+                                        StaticUniformBuffer::<256>::new().with(&view_projection),
+                                    )?,
+                                    binding: BufferLocation::Auto {
+                                        shader_location: self.shadow_shader.uniform_block_binding,
+                                    },
+                                    data_usage: Default::default(),
+                                }],
+                            }],
+                            ElementRange::Full,
+                        )?;
+                    }
+                }
+
+                let shader = &self.light_shader;
+                let falloff_texture = light
+                    .falloff_texture
+                    .as_ref()
+                    .unwrap_or(&fallback_resources.white_dummy);
+                stats += self.light_framebuffer.draw(
+                    quad,
+                    viewport,
+                    &*shader.program,
+                    &DrawParameters {
+                        cull_face: None,
+                        color_write: Default::default(),
+                        depth_write: false,
+                        stencil_test: if light.cast_shadows {
+                            Some(StencilFunc {
+                                func: CompareFunc::Equal,
+                                ref_value: 0,
+                                mask: 0xFFFF_FFFF,
+                            })
+                        } else {
+                            None
+                        },
+                        depth_test: None,
+                        blend: Some(BlendParameters {
+                            func: BlendFunc::new(BlendFactor::One, BlendFactor::One),
+                            ..Default::default()
+                        }),
+                        stencil_op: Default::default(),
+                        scissor_box: None,
+                    },
+                    &[ResourceBindGroup {
+                        bindings: &[
+                            ResourceBinding::texture(&normal_texture, &shader.normal_texture),
+                            ResourceBinding::texture(falloff_texture, &shader.falloff_texture),
+                            ResourceBinding::Buffer {
+                                buffer: uniform_buffer_cache.write(
+                                    StaticUniformBuffer::<256>::new()
+                                        .with(&frame_matrix)
+                                        .with(&inv_view_projection)
+                                        .with(&color.push(1.0))
+                                        .with(&light.position)
+                                        .with(&light.radius)
+                                        .with(&light.direction)
+                                        .with(&light.height)
+                                        .with(&half_cone_angle_cos)
+                                        .with(&half_hotspot_cone_angle_cos)
+                                        .with(&kind_index)
+                                        .with(&light.falloff_texture.is_some()),
+                                )?,
+                                binding: BufferLocation::Auto {
+                                    shader_location: shader.uniform_block_binding,
+                                },
+                                data_usage: Default::default(),
+                            },
+                        ],
+                    }],
+                    ElementRange::Full,
+                )?;
+            }
+        }
+
+        // Multiply the frame by the light buffer, alpha of the frame is kept as is.
+        stats += frame_buffer.draw(
+            quad,
+            viewport,
+            &*self.flat_shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: Some(BlendParameters {
+                    func: BlendFunc::new_separate(
+                        BlendFactor::DstColor,
+                        BlendFactor::Zero,
+                        BlendFactor::Zero,
+                        BlendFactor::One,
+                    ),
+                    ..Default::default()
+                }),
+                stencil_op: Default::default(),
+                scissor_box: None,
+            },
+            &[ResourceBindGroup {
+                bindings: &[
+                    ResourceBinding::texture(
+                        &self.light_texture(),
+                        &self.flat_shader.diffuse_texture,
+                    ),
+                    ResourceBinding::Buffer {
+                        buffer: uniform_buffer_cache
+                            .write(StaticUniformBuffer::<256>::new().with(&frame_matrix))?,
+                        binding: BufferLocation::Auto {
+                            shader_location: self.flat_shader.uniform_buffer_binding,
+                        },
+                        data_usage: Default::default(),
+                    },
+                ],
+            }],
+            ElementRange::Full,
+        )?;
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_shadow_volumes() {
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+
+        let segments = [
+            [Vector2::new(-1.0, 1.0), Vector2::new(1.0, 1.0)],
+            // Out of the light.
+            [Vector2::new(-1.0, 10.0), Vector2::new(1.0, 10.0)],
+            // Lies on a ray from the light.
+            [Vector2::new(0.0, -1.0), Vector2::new(0.0, -2.0)],
+        ];
+        build_shadow_volumes(
+            Vector2::default(),
+            0.0,
+            5.0,
+            &segments,
+            &mut vertices,
+            &mut triangles,
+        );
+
+        assert_eq!(vertices.len(), 5);
+        assert_eq!(triangles.len(), 3);
+        // Far points must be out of the light.
+        for vertex in &vertices[2..] {
+            assert!(vertex.xy().norm() > 5.0);
+        }
+    }
+}
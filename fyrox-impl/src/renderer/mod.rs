@@ -44,6 +44,7 @@ mod fxaa;
 mod gbuffer;
 mod hdr;
 mod light;
+mod light_2d;
 mod light_volume;
 mod occlusion;
 mod shadow;
@@ -73,8 +74,8 @@ use crate::{
         bloom::BloomRenderer,
         bundle::{ObserverInfo, RenderDataBundleStorage, RenderDataBundleStorageOptions},
         cache::{
-            geometry::GeometryCache, shader::ShaderCache, texture::TextureCache,
-            uniform::UniformBufferCache, uniform::UniformMemoryAllocator,
+            batch::DynamicBatchCache, geometry::GeometryCache, shader::ShaderCache,
+            texture::TextureCache, uniform::UniformBufferCache, uniform::UniformMemoryAllocator,
         },
         debug_renderer::DebugRenderer,
         flat_shader::FlatShader,
@@ -97,6 +98,7 @@ use crate::{
         gbuffer::{GBuffer, GBufferRenderContext},
         hdr::HighDynamicRangeRenderer,
         light::{DeferredLightRenderer, DeferredRendererContext},
+        light_2d::{Light2DRenderContext, Light2DRenderer},
         ui_renderer::{UiRenderContext, UiRenderer},
         visibility::VisibilityCache,
     },
@@ -155,6 +157,81 @@ pub enum ShadowMapPrecision {
 
 uuid_provider!(ShadowMapPrecision = "f9b2755b-248e-46ba-bcab-473eac1acdb8");
 
+/// Filtering technique of soft shadows of spot and directional lights.
+#[derive(
+    Copy,
+    Clone,
+    Hash,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Ord,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum ShadowFilterMode {
+    /// Percentage-closer filtering. The shadow map is sampled in a square kernel around each
+    /// pixel, which gives shadows with soft edges of constant width.
+    #[default]
+    Pcf,
+    /// Percentage-closer soft shadows (contact-hardening shadows). The width of the soft edge
+    /// depends on the distance between the shadow caster and the receiver, so the shadows are
+    /// sharp near their casters and get softer with the distance. Twice as expensive as `Pcf`.
+    Pcss,
+}
+
+uuid_provider!(ShadowFilterMode = "af4da1ad-4add-44e0-a926-9b39210f2a9a");
+
+/// Soft shadows settings of spot and directional lights. The settings are used only if the
+/// soft shadows of the respective lights are enabled (see [`QualitySettings::spot_soft_shadows`]
+/// and [`CsmSettings::pcf`]).
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct ShadowFilterSettings {
+    /// Filtering technique of the shadows.
+    pub mode: ShadowFilterMode,
+    /// The amount of samples along each axis of the filter kernel, so `3` means 3x3 samples.
+    /// Bigger kernels give smoother shadows, but are more expensive.
+    #[reflect(min_value = 1.0, max_value = 8.0, step = 1.0)]
+    pub kernel_size: u32,
+    /// The largest radius (in shadow map texels) of the soft edge of the shadows in
+    /// [`ShadowFilterMode::Pcss`] mode.
+    #[reflect(min_value = 0.0)]
+    pub pcss_search_radius: f32,
+    /// How fast the soft edge of the shadows gets wider with the distance between the shadow
+    /// caster and the receiver, in shadow map texels per unit of distance. Used only in
+    /// [`ShadowFilterMode::Pcss`] mode.
+    #[reflect(min_value = 0.0)]
+    pub pcss_penumbra_scale: f32,
+}
+
+impl Default for ShadowFilterSettings {
+    fn default() -> Self {
+        Self {
+            mode: ShadowFilterMode::Pcf,
+            kernel_size: 3,
+            pcss_search_radius: 8.0,
+            pcss_penumbra_scale: 4.0,
+        }
+    }
+}
+
+impl ShadowFilterSettings {
+    /// The filter of the shadows in the form of `SHADOW_FILTER_*` constants of the shaders.
+    fn shader_filter(&self, soft_shadows: bool) -> i32 {
+        match (soft_shadows, self.mode) {
+            (false, _) => 0,
+            (true, ShadowFilterMode::Pcf) => 1,
+            (true, ShadowFilterMode::Pcss) => 2,
+        }
+    }
+}
+
 /// Cascaded-shadow maps settings.
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect, Eq)]
 pub struct CsmSettings {
@@ -170,6 +247,20 @@ pub struct CsmSettings {
 
     /// Whether to use Percentage-Closer Filtering or not.
     pub pcf: bool,
+
+    /// Whether to cache static geometry of each cascade between frames or not. When enabled, objects
+    /// with [`crate::scene::base::Mobility::Static`] mobility are rendered into a separate depth buffer
+    /// of each directional light, which covers a slightly larger area than a cascade. The buffer is
+    /// re-rendered only when the light source or the set of static objects changes, or when the
+    /// camera leaves the cached area. Only the remaining objects are rendered every frame. This
+    /// trades additional video memory (a buffer per cascade of each light) for performance.
+    ///
+    /// The cached depth can only be reused if the texels of cascades stay fixed in the world, so
+    /// with caching enabled the cascades are bounded by spheres and snapped to their texels instead
+    /// of being fitted tightly to the camera frustum. It removes shimmering of shadow edges when
+    /// the camera moves, but lowers the effective resolution of shadows a bit.
+    #[serde(default)]
+    pub cache_static_geometry: bool,
 }
 
 impl Default for CsmSettings {
@@ -179,6 +270,7 @@ impl Default for CsmSettings {
             size: 2048,
             precision: ShadowMapPrecision::Full,
             pcf: true,
+            cache_static_geometry: false,
         }
     }
 }
@@ -219,10 +311,21 @@ pub struct QualitySettings {
     /// Specifies the distance from the camera at which spot shadows start to fade out.
     /// Shadows beyond this distance will gradually become less visible.
     pub spot_shadows_fade_out_range: f32,
+    /// Whether to cache static geometry of spot shadow maps between frames or not. When enabled,
+    /// objects with [`crate::scene::base::Mobility::Static`] mobility are rendered into a separate
+    /// depth buffer of each spot light only when the light source or the set of static objects
+    /// changes, and only the remaining objects are rendered every frame. This trades additional
+    /// video memory (a buffer per spot light) for performance.
+    #[serde(default)]
+    pub spot_shadows_cache_static_geometry: bool,
 
     /// Cascaded-shadow maps settings.
     pub csm_settings: CsmSettings,
 
+    /// Soft shadows settings of spot and directional lights.
+    #[serde(default)]
+    pub shadow_filter: ShadowFilterSettings,
+
     /// Whether to use screen space ambient occlusion or not.
     pub use_ssao: bool,
     /// Radius of sampling hemisphere used in SSAO, it defines much ambient
@@ -274,6 +377,7 @@ impl QualitySettings {
             spot_shadows_enabled: true,
             spot_soft_shadows: true,
             spot_shadows_fade_out_range: 1.0,
+            spot_shadows_cache_static_geometry: false,
 
             use_ssao: true,
             ssao_radius: 0.5,
@@ -291,6 +395,12 @@ impl QualitySettings {
 
             csm_settings: Default::default(),
 
+            shadow_filter: ShadowFilterSettings {
+                mode: ShadowFilterMode::Pcss,
+                kernel_size: 5,
+                ..Default::default()
+            },
+
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
         }
@@ -310,6 +420,7 @@ impl QualitySettings {
             spot_shadows_enabled: true,
             spot_soft_shadows: true,
             spot_shadows_fade_out_range: 1.0,
+            spot_shadows_cache_static_geometry: false,
 
             use_ssao: true,
             ssao_radius: 0.5,
@@ -330,8 +441,11 @@ impl QualitySettings {
                 size: 2048,
                 precision: ShadowMapPrecision::Full,
                 pcf: true,
+                cache_static_geometry: false,
             },
 
+            shadow_filter: Default::default(),
+
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
         }
@@ -351,6 +465,7 @@ impl QualitySettings {
             spot_shadows_enabled: true,
             spot_soft_shadows: false,
             spot_shadows_fade_out_range: 1.0,
+            spot_shadows_cache_static_geometry: false,
 
             use_ssao: true,
             ssao_radius: 0.5,
@@ -371,8 +486,11 @@ impl QualitySettings {
                 size: 512,
                 precision: ShadowMapPrecision::Full,
                 pcf: false,
+                cache_static_geometry: false,
             },
 
+            shadow_filter: Default::default(),
+
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
         }
@@ -392,6 +510,7 @@ impl QualitySettings {
             spot_shadows_enabled: false,
             spot_soft_shadows: false,
             spot_shadows_fade_out_range: 1.0,
+            spot_shadows_cache_static_geometry: false,
 
             use_ssao: false,
             ssao_radius: 0.5,
@@ -412,8 +531,11 @@ impl QualitySettings {
                 size: 512,
                 precision: ShadowMapPrecision::Half,
                 pcf: false,
+                cache_static_geometry: false,
             },
 
+            shadow_filter: Default::default(),
+
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
         }
@@ -498,6 +620,9 @@ pub struct AssociatedSceneData {
     /// bleeding effect (glow effect).
     pub bloom_renderer: BloomRenderer,
 
+    /// 2D lights renderer contains scene-sized light buffer.
+    pub light_2d_renderer: Light2DRenderer,
+
     /// Rendering statistics for a scene.
     pub statistics: SceneStatistics,
 }
@@ -565,6 +690,7 @@ impl AssociatedSceneData {
             gbuffer: GBuffer::new(server, width, height)?,
             hdr_renderer: HighDynamicRangeRenderer::new(server)?,
             bloom_renderer: BloomRenderer::new(server, width, height)?,
+            light_2d_renderer: Light2DRenderer::new(server, width, height)?,
             hdr_scene_framebuffer,
             ldr_scene_framebuffer,
             ldr_temp_framebuffer,
@@ -589,6 +715,50 @@ impl AssociatedSceneData {
         );
     }
 
+    /// Upscales the given part of the final frame to the given viewport with integer scale and
+    /// nearest filtering. The upscaled frame is centered in the viewport, the rest is filled with
+    /// black color.
+    fn upscale_frame(&mut self, source: Rect<i32>, viewport: Rect<i32>, scale: i32) {
+        let width = source.w() * scale;
+        let height = source.h() * scale;
+        let x = viewport.x() + (viewport.w() - width) / 2;
+        let y = viewport.y() + (viewport.h() - height) / 2;
+
+        // Blitting uses nearest filtering.
+        self.ldr_scene_framebuffer.blit_to(
+            &*self.ldr_temp_framebuffer,
+            source.x(),
+            source.y(),
+            source.x() + source.w(),
+            source.y() + source.h(),
+            x,
+            y,
+            x + width,
+            y + height,
+            true,
+            false,
+            false,
+        );
+
+        self.ldr_scene_framebuffer
+            .clear(viewport, Some(Color::BLACK), None, None);
+
+        self.ldr_temp_framebuffer.blit_to(
+            &*self.ldr_scene_framebuffer,
+            x,
+            y,
+            x + width,
+            y + height,
+            x,
+            y,
+            x + width,
+            y + height,
+            true,
+            false,
+            false,
+        );
+    }
+
     /// Returns high-dynamic range frame buffer texture.
     pub fn hdr_scene_frame_texture(&self) -> Rc<RefCell<dyn GpuTexture>> {
         self.hdr_scene_framebuffer.color_attachments()[0]
@@ -693,6 +863,7 @@ pub struct Renderer {
     pub uniform_buffer_cache: UniformBufferCache,
     shader_cache: ShaderCache,
     geometry_cache: GeometryCache,
+    dynamic_batch_cache: DynamicBatchCache,
     forward_renderer: ForwardRenderer,
     fxaa_renderer: FxaaRenderer,
     texture_event_receiver: Receiver<ResourceEvent>,
@@ -1044,6 +1215,7 @@ impl Renderer {
             backbuffer_clear_color: Color::BLACK,
             texture_cache: Default::default(),
             geometry_cache: Default::default(),
+            dynamic_batch_cache: Default::default(),
             forward_renderer: ForwardRenderer::new(),
             ui_frame_buffers: Default::default(),
             fxaa_renderer: FxaaRenderer::new(&*server)?,
@@ -1158,6 +1330,7 @@ impl Renderer {
     pub fn flush(&mut self) {
         self.texture_cache.clear();
         self.geometry_cache.clear();
+        self.dynamic_batch_cache.clear();
     }
 
     /// Renders given UI into specified render target. This method is especially useful if you need
@@ -1285,6 +1458,7 @@ impl Renderer {
         self.update_texture_cache(dt);
         self.update_shader_cache(dt);
         self.geometry_cache.update(dt);
+        self.dynamic_batch_cache.update(dt);
     }
 
     /// Unconditionally renders a scene and returns a reference to a [`AssociatedSceneData`] instance
@@ -1377,9 +1551,22 @@ impl Renderer {
         }) {
             let visibility_cache = self.visibility_cache.get_or_register(graph, camera_handle);
 
-            let viewport = camera.viewport_pixels(frame_size);
+            let full_viewport = camera.viewport_pixels(frame_size);
+
+            // In pixel-perfect mode the scene is rendered at low resolution in the corner of the
+            // viewport and then upscaled to the entire viewport.
+            let pixel_perfect = camera.pixel_perfect_resolution(frame_size);
+            let viewport = match pixel_perfect {
+                Some((resolution, _)) => Rect::new(
+                    full_viewport.x(),
+                    full_viewport.y(),
+                    resolution.x,
+                    resolution.y,
+                ),
+                None => full_viewport,
+            };
 
-            let bundle_storage = RenderDataBundleStorage::from_graph(
+            let mut bundle_storage = RenderDataBundleStorage::from_graph(
                 graph,
                 elapsed_time,
                 ObserverInfo {
@@ -1388,6 +1575,7 @@ impl Renderer {
                     z_far: camera.projection().z_far(),
                     view_matrix: camera.view_matrix(),
                     projection_matrix: camera.projection_matrix(),
+                    pixel_grid_size: camera.pixel_grid_size(),
                 },
                 GBUFFER_PASS_NAME.clone(),
                 RenderDataBundleStorageOptions {
@@ -1395,6 +1583,9 @@ impl Renderer {
                 },
             );
 
+            // Keep GPU buffers of dynamic batches (sprites, tiles, etc.) between frames.
+            self.dynamic_batch_cache.apply(&mut bundle_storage);
+
             server.set_polygon_fill_mode(
                 PolygonFace::FrontAndBack,
                 scene.rendering_options.polygon_rasterization_mode,
@@ -1475,6 +1666,27 @@ impl Renderer {
                     uniform_memory_allocator: &mut self.uniform_memory_allocator,
                 })?;
 
+            scene_associated_data.statistics +=
+                scene_associated_data
+                    .light_2d_renderer
+                    .render(Light2DRenderContext {
+                        server,
+                        graph,
+                        camera,
+                        viewport,
+                        ambient_light: scene.rendering_options.ambient_lighting_color,
+                        bundle_storage: &bundle_storage,
+                        geometry_cache: &mut self.geometry_cache,
+                        shader_cache: &mut self.shader_cache,
+                        texture_cache: &mut self.texture_cache,
+                        uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                        uniform_memory_allocator: &mut self.uniform_memory_allocator,
+                        fallback_resources: &self.fallback_resources,
+                        quality_settings: &self.quality_settings,
+                        quad: &*self.quad,
+                        frame_buffer: &mut *scene_associated_data.hdr_scene_framebuffer,
+                    })?;
+
             for render_pass in self.scene_render_passes.iter() {
                 scene_associated_data.statistics +=
                     render_pass
@@ -1548,6 +1760,11 @@ impl Renderer {
                 )?;
             }
 
+            if let Some((_, scale)) = pixel_perfect {
+                scene_associated_data.upscale_frame(viewport, full_viewport, scale);
+            }
+            let viewport = full_viewport;
+
             // Render debug geometry in the LDR frame buffer.
             self.debug_renderer.set_lines(&scene.drawing_context.lines);
             scene_associated_data.statistics += self.debug_renderer.render(
@@ -1697,4 +1914,79 @@ impl Renderer {
         self.statistics.pipeline = self.server.pipeline_statistics();
         Ok(())
     }
+
+    fn render_secondary_window_frame(
+        &mut self,
+        frame_size: (u32, u32),
+        scene: Option<Handle<Scene>>,
+        drawing_context: &DrawingContext,
+    ) -> Result<(), FrameworkError> {
+        let viewport = Rect::new(0, 0, frame_size.0 as i32, frame_size.1 as i32);
+        self.backbuffer.clear(
+            viewport,
+            Some(self.backbuffer_clear_color),
+            Some(1.0),
+            Some(0),
+        );
+
+        let mut statistics = RenderPassStatistics::default();
+
+        // The scene was already rendered into its render target, so it just needs to be copied.
+        if let Some(scene_data) = scene.and_then(|scene| self.scene_data_map.get(&scene)) {
+            statistics += blit_pixels(
+                &mut self.uniform_buffer_cache,
+                &mut *self.backbuffer,
+                scene_data.ldr_scene_frame_texture(),
+                &self.flat_shader,
+                viewport,
+                &*self.quad,
+            )?;
+        }
+
+        statistics += self.ui_renderer.render(UiRenderContext {
+            server: &*self.server,
+            viewport,
+            frame_buffer: &mut *self.backbuffer,
+            frame_width: frame_size.0 as f32,
+            frame_height: frame_size.1 as f32,
+            drawing_context,
+            fallback_resources: &self.fallback_resources,
+            texture_cache: &mut self.texture_cache,
+            uniform_buffer_cache: &mut self.uniform_buffer_cache,
+            flat_shader: &self.flat_shader,
+        })?;
+
+        self.statistics += statistics;
+
+        Ok(())
+    }
+
+    /// Renders the contents of a secondary window (created by [`GraphicsServer::create_window`]) and
+    /// presents it. The scene (if any) must be rendered into a render target beforehand, the
+    /// content of the render target is stretched over the entire window and the UI is drawn on top
+    /// of it.
+    pub(crate) fn render_secondary_window_and_swap_buffers(
+        &mut self,
+        window: &Window,
+        scene: Option<Handle<Scene>>,
+        drawing_context: &DrawingContext,
+    ) -> Result<(), FrameworkError> {
+        let inner_size = window.inner_size();
+        if inner_size.width == 0 || inner_size.height == 0 {
+            return Ok(());
+        }
+        let frame_size = (inner_size.width, inner_size.height);
+
+        self.server.set_current_window(Some(window.id()))?;
+        self.server.set_frame_size(frame_size);
+        let result = self
+            .render_secondary_window_frame(frame_size, scene, drawing_context)
+            .and_then(|_| {
+                window.pre_present_notify();
+                self.server.swap_buffers()
+            });
+        // The main window must be current after this method regardless of the result.
+        self.server.set_current_window(None)?;
+        result
+    }
 }
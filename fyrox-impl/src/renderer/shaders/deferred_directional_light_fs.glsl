@@ -19,7 +19,13 @@ layout(std140) uniform Uniforms {
     float lightIntensity;
     bool shadowsEnabled;
     float shadowBias;
-    bool softShadows;
+    int shadowFilter;
+    int shadowKernelSize;
+    float shadowSearchRadius;
+    float shadowPenumbraScale;
+    int shadowTechnique;
+    float shadowLightBleedingReduction;
+    vec2 shadowExponents;
     float shadowMapInvSize;
     float cascadeDistances[NUM_CASCADES];
 };
@@ -30,7 +36,16 @@ out vec4 FragColor;
 // Returns **inverted** shadow factor where 1 - fully bright, 0 - fully in shadow.
 float CsmGetShadow(in sampler2D sampler, in vec3 fragmentPosition, in mat4 lightViewProjMatrix)
 {
-    return S_SpotShadowFactor(shadowsEnabled, softShadows, shadowBias, fragmentPosition, lightViewProjMatrix, shadowMapInvSize, sampler);
+    if (shadowTechnique == SHADOW_TECHNIQUE_DEPTH_MAP) {
+        return S_SpotShadowFactor(
+            shadowsEnabled, shadowFilter, shadowKernelSize, shadowSearchRadius, shadowPenumbraScale,
+            shadowBias, fragmentPosition, lightViewProjMatrix, shadowMapInvSize, sampler);
+    } else {
+        // Cascades contain the moments of variance shadow maps, orthographic depth is linear.
+        return S_VarianceShadowFactor(
+            shadowsEnabled, shadowTechnique, shadowLightBleedingReduction, shadowExponents,
+            shadowBias, fragmentPosition, lightViewProjMatrix, false, vec2(0.0), sampler);
+    }
 }
 
 void main()
@@ -14,7 +14,13 @@ layout (std140) uniform Uniforms {
     float lightIntensity;
     bool shadowsEnabled;
     float shadowBias;
-    bool softShadows;
+    int shadowFilter;
+    int shadowKernelSize;
+    float shadowSearchRadius;
+    float shadowPenumbraScale;
+    int shadowTechnique;
+    float shadowLightBleedingReduction;
+    vec2 shadowExponents;
     float shadowMapInvSize;
     float cascadeDistances[NUM_CASCADES];
 };
@@ -22,7 +22,14 @@ layout (std140) uniform Uniforms {
     float shadowAlpha;
     bool cookieEnabled;
     bool shadowsEnabled;
-    bool softShadows;
+    int shadowFilter;
+    int shadowKernelSize;
+    float shadowSearchRadius;
+    float shadowPenumbraScale;
+    int shadowTechnique;
+    float shadowLightBleedingReduction;
+    vec2 shadowExponents;
+    vec2 shadowDepthRange;
 };
 
 in vec2 texCoord;
@@ -53,9 +60,19 @@ void main()
     float spotAngleCos = dot(lightDirection, ctx.fragmentToLight);
     float coneFactor = smoothstep(halfConeAngleCos, halfHotspotConeAngleCos, spotAngleCos);
 
-    float shadow = S_SpotShadowFactor(
-        shadowsEnabled, softShadows, shadowBias, fragmentPosition,
-        lightViewProjMatrix, shadowMapInvSize, spotShadowTexture);
+    float shadow;
+    if (shadowTechnique == SHADOW_TECHNIQUE_DEPTH_MAP) {
+        shadow = S_SpotShadowFactor(
+            shadowsEnabled, shadowFilter, shadowKernelSize, shadowSearchRadius, shadowPenumbraScale,
+            shadowBias, fragmentPosition,
+            lightViewProjMatrix, shadowMapInvSize, spotShadowTexture);
+    } else {
+        // Shadow texture contains the moments of a variance shadow map.
+        shadow = S_VarianceShadowFactor(
+            shadowsEnabled, shadowTechnique, shadowLightBleedingReduction, shadowExponents,
+            shadowBias, fragmentPosition, lightViewProjMatrix, true, shadowDepthRange,
+            spotShadowTexture);
+    }
     float finalShadow = mix(1.0, shadow, shadowAlpha);
 
     vec4 cookieAttenuation = vec4(1.0);
@@ -18,7 +18,14 @@ layout (std140) uniform Uniforms {
     float shadowAlpha;
     bool cookieEnabled;
     bool shadowsEnabled;
-    bool softShadows;
+    int shadowFilter;
+    int shadowKernelSize;
+    float shadowSearchRadius;
+    float shadowPenumbraScale;
+    int shadowTechnique;
+    float shadowLightBleedingReduction;
+    vec2 shadowExponents;
+    vec2 shadowDepthRange;
 };
 
 out vec2 texCoord;
@@ -0,0 +1,68 @@
+uniform sampler2D normalTexture;
+uniform sampler2D falloffTexture;
+
+layout (std140) uniform Uniforms {
+    mat4 worldViewProjection;
+    mat4 invViewProjection;
+    vec4 lightColor;
+    vec3 lightPosition;
+    float lightRadius;
+    vec2 lightDirection;
+    float lightHeight;
+    float halfConeAngleCos;
+    float halfHotspotConeAngleCos;
+    int lightKind;
+    bool useFalloffTexture;
+};
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+// Must be in sync with Light2DKind.
+const int KIND_POINT = 0;
+const int KIND_SPOT = 1;
+const int KIND_GLOBAL = 2;
+
+void main()
+{
+    vec4 normal = texture(normalTexture, texCoord);
+
+    if (lightKind == KIND_GLOBAL) {
+        FragColor = lightColor;
+        return;
+    }
+
+    // Find world-space position of the fragment on the plane of the light.
+    vec3 nearPoint = S_UnProject(vec3(texCoord, 0.0), invViewProjection);
+    vec3 farPoint = S_UnProject(vec3(texCoord, 1.0), invViewProjection);
+    vec3 ray = farPoint - nearPoint;
+    float t = abs(ray.z) > 0.00001 ? (lightPosition.z - nearPoint.z) / ray.z : 0.0;
+    vec2 fragmentPosition = nearPoint.xy + ray.xy * t;
+
+    vec2 lightToFragment = fragmentPosition - lightPosition.xy;
+    float distance = length(lightToFragment);
+    if (distance >= lightRadius) {
+        discard;
+    }
+
+    float k = distance / lightRadius;
+    float attenuation;
+    if (useFalloffTexture) {
+        attenuation = texture(falloffTexture, vec2(k, 0.5)).r;
+    } else {
+        attenuation = (1.0 - k) * (1.0 - k);
+    }
+
+    if (lightKind == KIND_SPOT) {
+        vec2 direction = distance > 0.00001 ? lightToFragment / distance : lightDirection;
+        attenuation *= smoothstep(halfConeAngleCos, halfHotspotConeAngleCos, dot(direction, lightDirection));
+    }
+
+    // X axis of the world is opposite to X axis of textures in 2D.
+    vec3 n = normalize(normal.xyz * 2.0 - 1.0);
+    n.x = -n.x;
+    vec3 l = normalize(vec3(-lightToFragment, lightHeight));
+    float shading = mix(1.0, max(dot(n, l), 0.0), normal.a);
+
+    FragColor = vec4(lightColor.rgb * attenuation * shading, 1.0);
+}
@@ -0,0 +1,24 @@
+layout (location = 0) in vec3 vertexPosition;
+layout (location = 1) in vec2 vertexTexCoord;
+
+layout (std140) uniform Uniforms {
+    mat4 worldViewProjection;
+    mat4 invViewProjection;
+    vec4 lightColor;
+    vec3 lightPosition;
+    float lightRadius;
+    vec2 lightDirection;
+    float lightHeight;
+    float halfConeAngleCos;
+    float halfHotspotConeAngleCos;
+    int lightKind;
+    bool useFalloffTexture;
+};
+
+out vec2 texCoord;
+
+void main()
+{
+    texCoord = vertexTexCoord;
+    gl_Position = worldViewProjection * vec4(vertexPosition, 1.0);
+}
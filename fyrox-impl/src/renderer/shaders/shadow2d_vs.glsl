@@ -0,0 +1,10 @@
+layout (location = 0) in vec3 vertexPosition;
+
+layout (std140) uniform Uniforms {
+    mat4 worldViewProjection;
+};
+
+void main()
+{
+    gl_Position = worldViewProjection * vec4(vertexPosition, 1.0);
+}
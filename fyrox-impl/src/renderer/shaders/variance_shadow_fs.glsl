@@ -0,0 +1,41 @@
+// Converts a shadow map to a variance shadow map and blurs it. Horizontal pass reads the depth
+// from the shadow map and converts it to moments, vertical pass reads the moments.
+
+uniform sampler2D image;
+
+layout (std140) uniform Uniforms {
+    mat4 worldViewProjection;
+    vec2 pixelSize;
+    vec2 exponents;
+    vec2 depthRange;
+    int technique;
+    int blurRadius;
+    bool horizontal;
+    bool perspective;
+};
+
+in vec2 texCoord;
+
+out vec4 outMoments;
+
+vec4 FetchMoments(vec2 coord)
+{
+    if (horizontal) {
+        float depth = S_LinearizeShadowDepth(texture(image, coord).r, perspective, depthRange);
+        return S_ShadowMoments(depth, technique, exponents);
+    } else {
+        return texture(image, coord);
+    }
+}
+
+void main()
+{
+    vec2 direction = horizontal ? vec2(pixelSize.x, 0.0) : vec2(0.0, pixelSize.y);
+
+    vec4 moments = vec4(0.0);
+    for (int i = -blurRadius; i <= blurRadius; ++i) {
+        moments += FetchMoments(texCoord + direction * float(i));
+    }
+
+    outMoments = moments / float(2 * blurRadius + 1);
+}
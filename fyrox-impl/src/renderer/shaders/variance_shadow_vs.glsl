@@ -0,0 +1,21 @@
+layout (location = 0) in vec3 vertexPosition;
+layout (location = 1) in vec2 vertexTexCoord;
+
+layout (std140) uniform Uniforms {
+    mat4 worldViewProjection;
+    vec2 pixelSize;
+    vec2 exponents;
+    vec2 depthRange;
+    int technique;
+    int blurRadius;
+    bool horizontal;
+    bool perspective;
+};
+
+out vec2 texCoord;
+
+void main()
+{
+    texCoord = vertexTexCoord;
+    gl_Position = worldViewProjection * vec4(vertexPosition, 1.0);
+}
@@ -39,6 +39,9 @@ use crate::{
             gpu_texture::{GpuTexture, PixelKind},
             server::GraphicsServer,
         },
+        shadow::{
+            is_static_instance, static_instances_signature, LightShadowCaches, StaticShadowCache,
+        },
         FallbackResources, RenderPassStatistics, ShadowMapPrecision, DIRECTIONAL_SHADOW_PASS_NAME,
     },
     scene::{
@@ -92,8 +95,18 @@ impl Cascade {
     }
 }
 
+/// The static geometry of a cascade is cached for an area, that is larger than the cascade by this
+/// fraction of the cascade size on every side. The cache is re-rendered only when the camera moves
+/// far enough for the cascade to leave this area.
+const STATIC_CACHE_MARGIN: usize = 4;
+
+/// Depth range of cascades in the units of their radius. Objects outside of the camera frustum
+/// could still cast shadows on visible objects, so the depth range is much larger than the frustum.
+const DEPTH_RANGE_SCALE: f32 = 10.0;
+
 pub struct CsmRenderer {
     cascades: [Cascade; CSM_NUM_CASCADES],
+    static_caches: Option<LightShadowCaches<[StaticShadowCache; CSM_NUM_CASCADES]>>,
     size: usize,
     precision: ShadowMapPrecision,
 }
@@ -112,11 +125,136 @@ pub(crate) struct CsmRenderContext<'a, 'c> {
     pub uniform_memory_allocator: &'a mut UniformMemoryAllocator,
 }
 
+fn snap(value: f32, step: f32) -> f32 {
+    (value / step).round() * step
+}
+
+fn orthographic(center: Vector3<f32>, half_extent: f32, depth_extent: f32) -> Matrix4<f32> {
+    // Light space is left-handed, while the orthographic projection is right-handed, so the depth
+    // range is negated to cover `[center.z - depth_extent, center.z + depth_extent]` of light space.
+    Matrix4::new_orthographic(
+        center.x - half_extent,
+        center.x + half_extent,
+        center.y - half_extent,
+        center.y + half_extent,
+        -(center.z + depth_extent),
+        depth_extent - center.z,
+    )
+}
+
+/// Fits the projection of a cascade tightly to the bounds of its frustum part. Such cascades have
+/// the best possible resolution, but their texels move and change their size with the camera.
+fn fitted_cascade_matrices(
+    frustum: &Frustum,
+    light_direction: Vector3<f32>,
+    light_up_vec: Vector3<f32>,
+) -> (Matrix4<f32>, Matrix4<f32>) {
+    let center = frustum.center();
+    let observer_position = center + light_direction;
+    let light_view_matrix = Matrix4::look_at_lh(
+        &Point3::from(observer_position),
+        &Point3::from(center),
+        &light_up_vec,
+    );
+
+    let mut aabb = AxisAlignedBoundingBox::default();
+    for corner in frustum.corners() {
+        let light_space_corner = light_view_matrix
+            .transform_point(&Point3::from(corner))
+            .coords;
+        aabb.add_point(light_space_corner);
+    }
+
+    // Make sure most of the objects outside of the frustum will cast shadows.
+    let z_mult = 10.0;
+    if aabb.min.z < 0.0 {
+        aabb.min.z *= z_mult;
+    } else {
+        aabb.min.z /= z_mult;
+    }
+    if aabb.max.z < 0.0 {
+        aabb.max.z /= z_mult;
+    } else {
+        aabb.max.z *= z_mult;
+    }
+
+    let cascade_projection_matrix = Matrix4::new_orthographic(
+        aabb.min.x, aabb.max.x, aabb.min.y, aabb.max.y, aabb.min.z, aabb.max.z,
+    );
+
+    (light_view_matrix, cascade_projection_matrix)
+}
+
+/// Placement of a cascade in light space, that is snapped to its texels. It is used when static
+/// geometry is cached: the cached depth can only be reused if the texels stay fixed in the world.
+struct StableCascade {
+    radius: f32,
+    texel_size: f32,
+    depth_extent: f32,
+    center: Vector3<f32>,
+    region_center: Vector3<f32>,
+}
+
+impl StableCascade {
+    fn new(
+        light_view_matrix: &Matrix4<f32>,
+        center: Vector3<f32>,
+        radius: f32,
+        size: usize,
+        margin: usize,
+    ) -> Self {
+        let texel_size = 2.0 * radius / size as f32;
+        let light_space_center = light_view_matrix
+            .transform_point(&Point3::from(center))
+            .coords;
+
+        // The cached area is snapped to a coarse grid, so it stays the same while the cascade
+        // moves within its margin.
+        let region_step = margin.max(1) as f32 * texel_size;
+        let region_center = light_space_center.map(|c| snap(c, region_step));
+
+        // The cascade is snapped to texels, it removes shimmering of shadow edges and allows
+        // to copy the cached static geometry texel-to-texel. Its depth range matches the one
+        // of the cached area, so the depth values are compatible.
+        let center = Vector3::new(
+            snap(light_space_center.x, texel_size),
+            snap(light_space_center.y, texel_size),
+            region_center.z,
+        );
+
+        Self {
+            radius,
+            texel_size,
+            depth_extent: radius * DEPTH_RANGE_SCALE,
+            center,
+            region_center,
+        }
+    }
+
+    fn projection_matrix(&self) -> Matrix4<f32> {
+        orthographic(self.center, self.radius, self.depth_extent)
+    }
+
+    fn region_projection_matrix(&self, margin: usize) -> Matrix4<f32> {
+        let half_extent = self.radius + margin as f32 * self.texel_size;
+        orthographic(self.region_center, half_extent, self.depth_extent)
+    }
+
+    /// Offset of the cascade in the cached area in texels. Both centers are snapped to texels, so
+    /// the offset is always a whole number of texels.
+    fn region_offset(&self, margin: usize) -> Vector2<i32> {
+        (self.center - self.region_center)
+            .xy()
+            .map(|c| (c / self.texel_size).round() as i32 + margin as i32)
+    }
+}
+
 impl CsmRenderer {
     pub fn new(
         server: &dyn GraphicsServer,
         size: usize,
         precision: ShadowMapPrecision,
+        cache_static_geometry: bool,
     ) -> Result<Self, FrameworkError> {
         Ok(Self {
             precision,
@@ -126,6 +264,7 @@ impl CsmRenderer {
                 Cascade::new(server, size, precision)?,
                 Cascade::new(server, size, precision)?,
             ],
+            static_caches: cache_static_geometry.then(Default::default),
         })
     }
 
@@ -141,6 +280,32 @@ impl CsmRenderer {
         &self.cascades
     }
 
+    /// Returns `true` if the renderer caches static geometry of cascades between frames.
+    pub fn caches_static_geometry(&self) -> bool {
+        self.static_caches.is_some()
+    }
+
+    /// Forces the static geometry of every cascade to be re-rendered on the next frame. The cache
+    /// tracks the position and visibility of static objects automatically, but it cannot detect
+    /// other changes, such as modifications of meshes or materials of static objects.
+    pub fn invalidate_static_cache(&mut self) {
+        if let Some(static_caches) = self.static_caches.as_mut() {
+            for light_caches in static_caches.iter_mut() {
+                for static_cache in light_caches {
+                    static_cache.invalidate();
+                }
+            }
+        }
+    }
+
+    /// Removes the static caches of the light sources, that were not rendered since the last call.
+    /// Should be called once per frame.
+    pub fn remove_unused_static_caches(&mut self) {
+        if let Some(static_caches) = self.static_caches.as_mut() {
+            static_caches.remove_unused();
+        }
+    }
+
     pub(crate) fn render(
         &mut self,
         ctx: CsmRenderContext,
@@ -175,6 +340,29 @@ impl CsmRenderer {
             .try_normalize(f32::EPSILON)
             .unwrap_or_else(Vector3::z);
 
+        // When static geometry is cached, light space depends only on the direction of the light,
+        // so the texels of the cascades (and the cached static geometry) stay fixed in the world
+        // while the camera moves.
+        let light_view_matrix = Matrix4::look_at_lh(
+            &Point3::from(light_direction),
+            &Point3::origin(),
+            &light_up_vec,
+        );
+
+        let size = self.size;
+        let margin = size / STATIC_CACHE_MARGIN;
+        let mut static_caches = match self.static_caches.as_mut() {
+            Some(static_caches) => Some(static_caches.get_or_create(light.handle, || {
+                let cache_size = size + 2 * margin;
+                Ok([
+                    StaticShadowCache::new(state, cache_size, self.precision)?,
+                    StaticShadowCache::new(state, cache_size, self.precision)?,
+                    StaticShadowCache::new(state, cache_size, self.precision)?,
+                ])
+            })?),
+            None => None,
+        };
+
         let z_values = match csm_options.split_options {
             FrustumSplitOptions::Absolute { far_planes } => [
                 camera.projection().z_near(),
@@ -208,64 +396,152 @@ impl CsmRenderer {
             let frustum =
                 Frustum::from_view_projection_matrix(projection_matrix * camera.view_matrix())
                     .unwrap_or_default();
-
             let center = frustum.center();
             let observer_position = center + light_direction;
-            let light_view_matrix = Matrix4::look_at_lh(
-                &Point3::from(observer_position),
-                &Point3::from(center),
-                &light_up_vec,
-            );
 
-            let mut aabb = AxisAlignedBoundingBox::default();
-            for corner in frustum.corners() {
-                let light_space_corner = light_view_matrix
-                    .transform_point(&Point3::from(corner))
-                    .coords;
-                aabb.add_point(light_space_corner);
-            }
+            let stable_cascade = static_caches.is_some().then(|| {
+                // The radius of the bounding sphere of the cascade does not depend on the position
+                // and the orientation of the camera, so the cascade has fixed extent and the size
+                // of its texels does not change when the camera rotates.
+                let local_frustum =
+                    Frustum::from_view_projection_matrix(projection_matrix).unwrap_or_default();
+                let local_center = local_frustum.center();
+                let radius = local_frustum
+                    .corners()
+                    .iter()
+                    .map(|corner| corner.metric_distance(&local_center))
+                    .fold(f32::EPSILON, f32::max);
+                StableCascade::new(&light_view_matrix, center, radius, size, margin)
+            });
 
-            // Make sure most of the objects outside of the frustum will cast shadows.
-            let z_mult = 10.0;
-            if aabb.min.z < 0.0 {
-                aabb.min.z *= z_mult;
-            } else {
-                aabb.min.z /= z_mult;
-            }
-            if aabb.max.z < 0.0 {
-                aabb.max.z /= z_mult;
-            } else {
-                aabb.max.z *= z_mult;
-            }
+            let (view_matrix, cascade_projection_matrix) = match stable_cascade {
+                Some(ref stable_cascade) => (light_view_matrix, stable_cascade.projection_matrix()),
+                None => fitted_cascade_matrices(&frustum, light_direction, light_up_vec),
+            };
 
-            let cascade_projection_matrix = Matrix4::new_orthographic(
-                aabb.min.x, aabb.max.x, aabb.min.y, aabb.max.y, aabb.min.z, aabb.max.z,
-            );
-
-            let light_view_projection = cascade_projection_matrix * light_view_matrix;
+            let light_view_projection = cascade_projection_matrix * view_matrix;
             self.cascades[i].view_proj_matrix = light_view_projection;
             self.cascades[i].z_far = z_far;
 
-            let viewport = Rect::new(0, 0, self.size as i32, self.size as i32);
+            let viewport = Rect::new(0, 0, size as i32, size as i32);
             let framebuffer = &mut *self.cascades[i].frame_buffer;
-            framebuffer.clear(viewport, None, Some(1.0), None);
+
+            let cascade_observer_info = ObserverInfo {
+                observer_position,
+                z_near,
+                z_far,
+                view_matrix,
+                projection_matrix: cascade_projection_matrix,
+                pixel_grid_size: None,
+            };
+
+            if let (Some(static_caches), Some(stable_cascade)) =
+                (static_caches.as_mut(), stable_cascade)
+            {
+                let static_cache = &mut static_caches[i];
+                let region_projection_matrix = stable_cascade.region_projection_matrix(margin);
+                let region_view_projection = region_projection_matrix * light_view_matrix;
+
+                // The storage is collected for the whole cached area, it is a superset of the
+                // objects of the cascade.
+                let mut bundle_storage = RenderDataBundleStorage::from_graph(
+                    graph,
+                    elapsed_time,
+                    ObserverInfo {
+                        projection_matrix: region_projection_matrix,
+                        ..cascade_observer_info.clone()
+                    },
+                    DIRECTIONAL_SHADOW_PASS_NAME.clone(),
+                    RenderDataBundleStorageOptions {
+                        collect_lights: false,
+                    },
+                );
+                let static_signature = static_instances_signature(graph, &bundle_storage);
+
+                // Static geometry is rendered only when the light, the cached area or the set of
+                // static objects changes, otherwise the cached depth is reused.
+                if !static_cache.is_valid_for(&region_view_projection, static_signature) {
+                    let cache_size = static_cache.size() as i32;
+                    let cache_framebuffer = static_cache.frame_buffer_mut();
+                    let cache_viewport = Rect::new(0, 0, cache_size, cache_size);
+                    cache_framebuffer.clear(cache_viewport, None, Some(1.0), None);
+
+                    stats += bundle_storage.render_to_frame_buffer(
+                        state,
+                        geom_cache,
+                        shader_cache,
+                        |_| true,
+                        |instance| is_static_instance(graph, instance),
+                        BundleRenderContext {
+                            texture_cache,
+                            render_pass_name: &DIRECTIONAL_SHADOW_PASS_NAME,
+                            frame_buffer: cache_framebuffer,
+                            viewport: cache_viewport,
+                            uniform_memory_allocator,
+                            use_pom: false,
+                            light_position: &Default::default(),
+                            fallback_resources,
+                            ambient_light: Color::WHITE, // TODO
+                            scene_depth: None,
+                        },
+                    )?;
+
+                    static_cache.validate(region_view_projection, static_signature);
+                }
+
+                let offset = stable_cascade.region_offset(margin);
+                let size = size as i32;
+                static_cache.frame_buffer().blit_to(
+                    &*framebuffer,
+                    offset.x,
+                    offset.y,
+                    offset.x + size,
+                    offset.y + size,
+                    0,
+                    0,
+                    size,
+                    size,
+                    false,
+                    true,
+                    false,
+                );
+
+                bundle_storage.observer_info = cascade_observer_info;
+                stats += bundle_storage.render_to_frame_buffer(
+                    state,
+                    geom_cache,
+                    shader_cache,
+                    |_| true,
+                    |instance| !is_static_instance(graph, instance),
+                    BundleRenderContext {
+                        texture_cache,
+                        render_pass_name: &DIRECTIONAL_SHADOW_PASS_NAME,
+                        frame_buffer: framebuffer,
+                        viewport,
+                        uniform_memory_allocator,
+                        use_pom: false,
+                        light_position: &Default::default(),
+                        fallback_resources,
+                        ambient_light: Color::WHITE, // TODO
+                        scene_depth: None,
+                    },
+                )?;
+
+                continue;
+            }
 
             let bundle_storage = RenderDataBundleStorage::from_graph(
                 graph,
                 elapsed_time,
-                ObserverInfo {
-                    observer_position,
-                    z_near,
-                    z_far,
-                    view_matrix: light_view_matrix,
-                    projection_matrix: cascade_projection_matrix,
-                },
+                cascade_observer_info,
                 DIRECTIONAL_SHADOW_PASS_NAME.clone(),
                 RenderDataBundleStorageOptions {
                     collect_lights: false,
                 },
             );
 
+            framebuffer.clear(viewport, None, Some(1.0), None);
+
             stats += bundle_storage.render_to_frame_buffer(
                 state,
                 geom_cache,
@@ -290,3 +566,98 @@ impl CsmRenderer {
         Ok(stats)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RADIUS: f32 = 10.0;
+    const SIZE: usize = 1024;
+    const MARGIN: usize = SIZE / STATIC_CACHE_MARGIN;
+
+    fn light_view_matrix() -> Matrix4<f32> {
+        let light_direction = Vector3::new(-1.0, -2.0, 0.5).normalize();
+        Matrix4::look_at_lh(
+            &Point3::from(light_direction),
+            &Point3::origin(),
+            &Vector3::z(),
+        )
+    }
+
+    fn to_world(light_view_matrix: &Matrix4<f32>, light_space: Vector3<f32>) -> Vector3<f32> {
+        light_view_matrix
+            .try_inverse()
+            .unwrap()
+            .transform_point(&Point3::from(light_space))
+            .coords
+    }
+
+    #[test]
+    fn test_stable_cascade_sub_texel_movement() {
+        let view = light_view_matrix();
+        let texel_size = 2.0 * RADIUS / SIZE as f32;
+        let center = to_world(
+            &view,
+            Vector3::new(123.0 * texel_size, -45.0 * texel_size, 10.0),
+        );
+        let cascade = StableCascade::new(&view, center, RADIUS, SIZE, MARGIN);
+        let projection = cascade.projection_matrix();
+        let region_projection = cascade.region_projection_matrix(MARGIN);
+
+        for offset in [
+            Vector3::new(0.4, 0.0, 0.0),
+            Vector3::new(-0.4, 0.0, 0.0),
+            Vector3::new(0.0, 0.4, 0.0),
+            Vector3::new(0.0, -0.4, 0.0),
+            Vector3::new(0.3, -0.3, 0.3),
+            Vector3::new(-0.3, 0.3, -0.3),
+        ] {
+            let moved = to_world(
+                &view,
+                Vector3::new(123.0 * texel_size, -45.0 * texel_size, 10.0) + offset * texel_size,
+            );
+            assert!(moved.metric_distance(&center) < texel_size);
+            let moved_cascade = StableCascade::new(&view, moved, RADIUS, SIZE, MARGIN);
+            assert_eq!(moved_cascade.projection_matrix(), projection);
+            assert_eq!(
+                moved_cascade.region_projection_matrix(MARGIN),
+                region_projection
+            );
+        }
+    }
+
+    #[test]
+    fn test_stable_cascade_shifts_by_whole_texels() {
+        let view = light_view_matrix();
+        let texel_size = 2.0 * RADIUS / SIZE as f32;
+        let center = Vector3::new(3.17, -1.29, 7.63);
+        let cascade = StableCascade::new(&view, center, RADIUS, SIZE, MARGIN);
+        let projection = cascade.projection_matrix();
+
+        for offset in [
+            Vector3::new(0.9, 0.0, 0.0),
+            Vector3::new(0.0, -0.9, 0.0),
+            Vector3::new(0.0, 0.0, 0.9),
+            Vector3::new(0.5, 0.5, -0.5),
+        ] {
+            let moved = center + offset * texel_size;
+            let moved_cascade = StableCascade::new(&view, moved, RADIUS, SIZE, MARGIN);
+            let moved_projection = moved_cascade.projection_matrix();
+
+            // The extent of the cascade never changes, it can only be shifted by at most one texel
+            // on each axis.
+            assert_eq!(moved_projection[(0, 0)], projection[(0, 0)]);
+            assert_eq!(moved_projection[(1, 1)], projection[(1, 1)]);
+            for row in 0..2 {
+                let shift =
+                    (projection[(row, 3)] - moved_projection[(row, 3)]) * RADIUS / texel_size;
+                assert!((shift - shift.round()).abs() < 1.0e-3);
+                assert!(shift.round().abs() <= 1.0);
+            }
+
+            let offset = moved_cascade.region_offset(MARGIN);
+            assert!((0..=2 * MARGIN as i32).contains(&offset.x));
+            assert!((0..=2 * MARGIN as i32).contains(&offset.y));
+        }
+    }
+}
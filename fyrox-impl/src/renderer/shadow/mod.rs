@@ -23,6 +23,28 @@
 pub mod csm;
 pub mod point;
 pub mod spot;
+pub mod variance;
+
+use crate::{
+    core::{algebra::Matrix4, pool::Handle},
+    renderer::{
+        bundle::{RenderDataBundleStorage, SurfaceInstanceData},
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, FrameBuffer},
+            gpu_texture::PixelKind,
+            server::GraphicsServer,
+        },
+        ShadowMapPrecision,
+    },
+    scene::{base::Mobility, graph::Graph, node::Node},
+};
+use fxhash::{FxHashMap, FxHasher};
+use fyrox_graph::BaseSceneGraph;
+use std::{
+    collections::hash_map::Entry,
+    hash::{Hash, Hasher},
+};
 
 pub fn cascade_size(base_size: usize, cascade: usize) -> usize {
     match cascade {
@@ -32,3 +54,154 @@ pub fn cascade_size(base_size: usize, cascade: usize) -> usize {
         _ => unreachable!(),
     }
 }
+
+/// Returns `true` if the given surface instance belongs to static geometry, which contribution
+/// to a shadow map can be cached between frames. Skinned surfaces and surfaces with blend shapes
+/// are always treated as dynamic, because their shape may change even if their node does not move.
+pub(crate) fn is_static_instance(graph: &Graph, instance: &SurfaceInstanceData) -> bool {
+    instance.bone_matrices.is_empty()
+        && instance.blend_shapes_weights.is_empty()
+        && graph
+            .try_get(instance.node_handle)
+            .is_some_and(|node| node.mobility() == Mobility::Static)
+}
+
+/// Calculates a signature of the static instances of the given bundle storage. The signature
+/// changes when a static object is added, removed, moved, hidden or when its surface or material
+/// is replaced. The storage is collected every frame anyway, so there is no need to walk the whole
+/// graph to track static objects.
+pub(crate) fn static_instances_signature(graph: &Graph, storage: &RenderDataBundleStorage) -> u64 {
+    let mut hasher = FxHasher::default();
+    for bundle in storage.bundles.iter() {
+        for instance in bundle.instances.iter() {
+            if is_static_instance(graph, instance) {
+                bundle.data.key().hash(&mut hasher);
+                bundle.material.key().hash(&mut hasher);
+                instance.node_handle.hash(&mut hasher);
+                for value in instance.world_transform.iter() {
+                    hasher.write_u32(value.to_bits());
+                }
+            }
+        }
+    }
+    hasher.finish()
+}
+
+#[derive(Copy, Clone, PartialEq)]
+struct StaticShadowCacheKey {
+    view_projection: Matrix4<f32>,
+    static_signature: u64,
+}
+
+/// A depth buffer that holds the static geometry of a scene rendered from the point of view of a
+/// light source. It is re-rendered only when the light source or the set of static objects changes,
+/// all dynamic objects are rendered on top of a copy of this buffer every frame.
+pub struct StaticShadowCache {
+    frame_buffer: Box<dyn FrameBuffer>,
+    size: usize,
+    key: Option<StaticShadowCacheKey>,
+}
+
+impl StaticShadowCache {
+    pub fn new(
+        server: &dyn GraphicsServer,
+        size: usize,
+        precision: ShadowMapPrecision,
+    ) -> Result<Self, FrameworkError> {
+        let depth = server.create_2d_render_target(
+            match precision {
+                ShadowMapPrecision::Full => PixelKind::D32F,
+                ShadowMapPrecision::Half => PixelKind::D16,
+            },
+            size,
+            size,
+        )?;
+
+        Ok(Self {
+            frame_buffer: server.create_frame_buffer(
+                Some(Attachment {
+                    kind: AttachmentKind::Depth,
+                    texture: depth,
+                }),
+                Default::default(),
+            )?,
+            size,
+            key: None,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Forces the cache to be re-rendered next time it is used.
+    pub fn invalidate(&mut self) {
+        self.key = None;
+    }
+
+    /// Returns `true` if the cache holds the static geometry for the given view-projection matrix
+    /// and static geometry signature.
+    pub fn is_valid_for(&self, view_projection: &Matrix4<f32>, static_signature: u64) -> bool {
+        self.key.is_some_and(|key| {
+            key.view_projection == *view_projection && key.static_signature == static_signature
+        })
+    }
+
+    /// Marks the cache as valid for the given parameters. Must be called after the static geometry
+    /// was rendered into the frame buffer of the cache.
+    pub fn validate(&mut self, view_projection: Matrix4<f32>, static_signature: u64) {
+        self.key = Some(StaticShadowCacheKey {
+            view_projection,
+            static_signature,
+        });
+    }
+
+    pub fn frame_buffer(&self) -> &dyn FrameBuffer {
+        &*self.frame_buffer
+    }
+
+    pub fn frame_buffer_mut(&mut self) -> &mut dyn FrameBuffer {
+        &mut *self.frame_buffer
+    }
+}
+
+/// Static shadow caches of light sources. Every light source has its own caches, so multiple
+/// light sources do not invalidate each other. The caches of light sources, that were not rendered
+/// during a frame, are removed by [`Self::remove_unused`] to free video memory.
+pub struct LightShadowCaches<T> {
+    caches: FxHashMap<Handle<Node>, (T, bool)>,
+}
+
+impl<T> Default for LightShadowCaches<T> {
+    fn default() -> Self {
+        Self {
+            caches: Default::default(),
+        }
+    }
+}
+
+impl<T> LightShadowCaches<T> {
+    /// Returns the caches of the given light source (creating them, if needed) and marks them as
+    /// used.
+    pub fn get_or_create(
+        &mut self,
+        light: Handle<Node>,
+        create: impl FnOnce() -> Result<T, FrameworkError>,
+    ) -> Result<&mut T, FrameworkError> {
+        let entry = match self.caches.entry(light) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert((create()?, false)),
+        };
+        entry.1 = true;
+        Ok(&mut entry.0)
+    }
+
+    /// Removes the caches, that were not used since the last call of this method.
+    pub fn remove_unused(&mut self) {
+        self.caches.retain(|_, (_, used)| std::mem::take(used));
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.caches.values_mut().map(|(caches, _)| caches)
+    }
+}
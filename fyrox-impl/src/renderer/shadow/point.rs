@@ -227,6 +227,7 @@ impl PointShadowMapRenderer {
                     z_far,
                     view_matrix: light_view_matrix,
                     projection_matrix: light_projection_matrix,
+                    pixel_grid_size: None,
                 },
                 POINT_SHADOW_PASS_NAME.clone(),
                 RenderDataBundleStorageOptions {
@@ -23,6 +23,7 @@ use crate::{
         algebra::{Matrix4, Vector3},
         color::Color,
         math::Rect,
+        pool::Handle,
     },
     renderer::{
         bundle::{
@@ -36,11 +37,14 @@ use crate::{
             gpu_texture::{GpuTexture, PixelKind},
             server::GraphicsServer,
         },
-        shadow::cascade_size,
+        shadow::{
+            cascade_size, is_static_instance, static_instances_signature, LightShadowCaches,
+            StaticShadowCache,
+        },
         FallbackResources, GeometryCache, RenderPassStatistics, ShadowMapPrecision,
         SPOT_SHADOW_PASS_NAME,
     },
-    scene::graph::Graph,
+    scene::{graph::Graph, node::Node},
 };
 use std::{cell::RefCell, rc::Rc};
 
@@ -51,6 +55,7 @@ pub struct SpotShadowMapRenderer {
     //  1 - medium, for lights with medium distance to camera.
     //  2 - small, for farthest lights.
     cascades: [Box<dyn FrameBuffer>; 3],
+    static_caches: Option<LightShadowCaches<StaticShadowCache>>,
     size: usize,
 }
 
@@ -59,6 +64,7 @@ impl SpotShadowMapRenderer {
         server: &dyn GraphicsServer,
         size: usize,
         precision: ShadowMapPrecision,
+        cache_static_geometry: bool,
     ) -> Result<Self, FrameworkError> {
         fn make_cascade(
             server: &dyn GraphicsServer,
@@ -91,6 +97,7 @@ impl SpotShadowMapRenderer {
                 make_cascade(server, cascade_size(size, 1), precision)?,
                 make_cascade(server, cascade_size(size, 2), precision)?,
             ],
+            static_caches: cache_static_geometry.then(Default::default),
         })
     }
 
@@ -114,12 +121,35 @@ impl SpotShadowMapRenderer {
         cascade_size(self.size, cascade)
     }
 
+    /// Returns `true` if the renderer caches static geometry of shadow maps between frames.
+    pub fn caches_static_geometry(&self) -> bool {
+        self.static_caches.is_some()
+    }
+
+    /// Forces the static geometry of every shadow map to be re-rendered on the next frame.
+    pub fn invalidate_static_cache(&mut self) {
+        if let Some(static_caches) = self.static_caches.as_mut() {
+            for static_cache in static_caches.iter_mut() {
+                static_cache.invalidate();
+            }
+        }
+    }
+
+    /// Removes the static caches of the light sources, that were not rendered since the last call.
+    /// Should be called once per frame.
+    pub fn remove_unused_static_caches(&mut self) {
+        if let Some(static_caches) = self.static_caches.as_mut() {
+            static_caches.remove_unused();
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn render(
         &mut self,
         server: &dyn GraphicsServer,
         graph: &Graph,
         elapsed_time: f32,
+        light: Handle<Node>,
         light_position: Vector3<f32>,
         light_view_matrix: Matrix4<f32>,
         z_near: f32,
@@ -139,8 +169,6 @@ impl SpotShadowMapRenderer {
 
         let viewport = Rect::new(0, 0, cascade_size as i32, cascade_size as i32);
 
-        framebuffer.clear(viewport, None, Some(1.0), None);
-
         let bundle_storage = RenderDataBundleStorage::from_graph(
             graph,
             elapsed_time,
@@ -150,6 +178,7 @@ impl SpotShadowMapRenderer {
                 z_far,
                 view_matrix: light_view_matrix,
                 projection_matrix: light_projection_matrix,
+                pixel_grid_size: None,
             },
             SPOT_SHADOW_PASS_NAME.clone(),
             RenderDataBundleStorageOptions {
@@ -157,12 +186,77 @@ impl SpotShadowMapRenderer {
             },
         );
 
+        let static_cache = match self.static_caches.as_mut() {
+            Some(static_caches) => {
+                let static_cache = static_caches.get_or_create(light, || {
+                    StaticShadowCache::new(server, cascade_size, self.precision)
+                })?;
+                // The light could switch to another cascade, the cache must match its size.
+                if static_cache.size() != cascade_size {
+                    *static_cache = StaticShadowCache::new(server, cascade_size, self.precision)?;
+                }
+                Some(static_cache)
+            }
+            None => None,
+        };
+
+        if let Some(static_cache) = static_cache {
+            let view_projection = light_projection_matrix * light_view_matrix;
+            let static_signature = static_instances_signature(graph, &bundle_storage);
+
+            if !static_cache.is_valid_for(&view_projection, static_signature) {
+                let cache_framebuffer = static_cache.frame_buffer_mut();
+                cache_framebuffer.clear(viewport, None, Some(1.0), None);
+
+                statistics += bundle_storage.render_to_frame_buffer(
+                    server,
+                    geom_cache,
+                    shader_cache,
+                    |_| true,
+                    |instance| is_static_instance(graph, instance),
+                    BundleRenderContext {
+                        texture_cache,
+                        render_pass_name: &SPOT_SHADOW_PASS_NAME,
+                        frame_buffer: cache_framebuffer,
+                        viewport,
+                        uniform_memory_allocator,
+                        use_pom: false,
+                        light_position: &Default::default(),
+                        fallback_resources,
+                        ambient_light: Color::WHITE, // TODO
+                        scene_depth: None,
+                    },
+                )?;
+
+                static_cache.validate(view_projection, static_signature);
+            }
+
+            let size = cascade_size as i32;
+            static_cache.frame_buffer().blit_to(
+                &*framebuffer,
+                0,
+                0,
+                size,
+                size,
+                0,
+                0,
+                size,
+                size,
+                false,
+                true,
+                false,
+            );
+        } else {
+            framebuffer.clear(viewport, None, Some(1.0), None);
+        }
+
+        let caches_static_geometry = self.static_caches.is_some();
         statistics += bundle_storage.render_to_frame_buffer(
             server,
             geom_cache,
             shader_cache,
             |_| true,
-            |_| true,
+            |instance| !caches_static_geometry || !is_static_instance(graph, instance),
             BundleRenderContext {
                 texture_cache,
                 render_pass_name: &SPOT_SHADOW_PASS_NAME,
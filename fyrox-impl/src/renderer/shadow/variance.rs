@@ -0,0 +1,289 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Variance shadow maps. A regular depth shadow map is converted into a map of depth moments, which
+//! is then blurred. See [`ShadowTechnique`] for more info.
+
+use crate::{
+    core::{algebra::Vector2, math::Rect, sstorage::ImmutableString},
+    renderer::{
+        cache::uniform::UniformBufferCache,
+        framework::{
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, BufferLocation, FrameBuffer, ResourceBindGroup,
+                ResourceBinding,
+            },
+            geometry_buffer::GeometryBuffer,
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{
+                GpuTexture, GpuTextureDescriptor, GpuTextureKind, MagnificationFilter,
+                MinificationFilter, PixelKind, WrapMode,
+            },
+            server::GraphicsServer,
+            uniform::StaticUniformBuffer,
+            DrawParameters, ElementRange,
+        },
+        make_viewport_matrix, RenderPassStatistics,
+    },
+    scene::light::ShadowTechnique,
+};
+use fxhash::FxHashMap;
+use std::{cell::RefCell, collections::hash_map::Entry, rc::Rc};
+
+struct Shader {
+    program: Box<dyn GpuProgram>,
+    image: UniformLocation,
+    uniform_block_binding: usize,
+}
+
+impl Shader {
+    fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("../shaders/variance_shadow_fs.glsl");
+        let vertex_source = include_str!("../shaders/variance_shadow_vs.glsl");
+
+        let program =
+            server.create_program("VarianceShadowShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            image: program.uniform_location(&ImmutableString::new("image"))?,
+            uniform_block_binding: program
+                .uniform_block_index(&ImmutableString::new("Uniforms"))?,
+            program,
+        })
+    }
+}
+
+/// Parameters of a variance shadow technique in the form suitable for the shaders.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct VarianceShadowParameters {
+    /// One of `SHADOW_TECHNIQUE_*` constants of the shaders.
+    pub technique: i32,
+    pub blur_radius: u32,
+    pub light_bleeding_reduction: f32,
+    /// Positive and negative exponents of the depth warp.
+    pub exponents: Vector2<f32>,
+}
+
+impl VarianceShadowParameters {
+    /// Returns [`None`] if the given technique is not a variance one.
+    pub fn from_technique(technique: &ShadowTechnique) -> Option<Self> {
+        match *technique {
+            ShadowTechnique::DepthMap => None,
+            ShadowTechnique::Variance {
+                blur_radius,
+                light_bleeding_reduction,
+            } => Some(Self {
+                technique: 1,
+                blur_radius,
+                light_bleeding_reduction,
+                exponents: Vector2::default(),
+            }),
+            ShadowTechnique::ExponentialVariance {
+                blur_radius,
+                light_bleeding_reduction,
+                positive_exponent,
+                negative_exponent,
+            } => Some(Self {
+                technique: 2,
+                blur_radius,
+                light_bleeding_reduction,
+                exponents: Vector2::new(positive_exponent, negative_exponent),
+            }),
+        }
+    }
+}
+
+struct VarianceShadowMap {
+    h_framebuffer: Box<dyn FrameBuffer>,
+    v_framebuffer: Box<dyn FrameBuffer>,
+}
+
+fn create_framebuffer(
+    server: &dyn GraphicsServer,
+    size: usize,
+) -> Result<Box<dyn FrameBuffer>, FrameworkError> {
+    // Moments must be filtered linearly, and exponential warps require full precision.
+    let moments = server.create_texture(GpuTextureDescriptor {
+        kind: GpuTextureKind::Rectangle {
+            width: size,
+            height: size,
+        },
+        pixel_kind: PixelKind::RGBA32F,
+        min_filter: MinificationFilter::Linear,
+        mag_filter: MagnificationFilter::Linear,
+        s_wrap_mode: WrapMode::ClampToEdge,
+        t_wrap_mode: WrapMode::ClampToEdge,
+        r_wrap_mode: WrapMode::ClampToEdge,
+        ..Default::default()
+    })?;
+
+    server.create_frame_buffer(
+        None,
+        vec![Attachment {
+            kind: AttachmentKind::Color,
+            texture: moments,
+        }],
+    )
+}
+
+pub(crate) struct VarianceShadowMapRenderContext<'a> {
+    pub server: &'a dyn GraphicsServer,
+    pub quad: &'a dyn GeometryBuffer,
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+    /// Depth shadow map that will be converted.
+    pub depth: Rc<RefCell<dyn GpuTexture>>,
+    pub size: usize,
+    /// Index of the map, maps with the same size and slot share the same textures.
+    pub slot: usize,
+    pub parameters: VarianceShadowParameters,
+    /// Whether the shadow map was rendered using perspective projection or not.
+    pub perspective: bool,
+    /// Near and far planes of the projection of the shadow map.
+    pub depth_range: Vector2<f32>,
+}
+
+/// Converts depth shadow maps into variance shadow maps. Textures of variance shadow maps are
+/// created on demand and reused by every light source, that have shadow maps of the same size.
+pub struct VarianceShadowMapRenderer {
+    shader: Shader,
+    maps: FxHashMap<(usize, usize), VarianceShadowMap>,
+}
+
+impl VarianceShadowMapRenderer {
+    pub fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: Shader::new(server)?,
+            maps: Default::default(),
+        })
+    }
+
+    /// Returns the moments texture of the variance shadow map with the given size and slot, if any.
+    pub fn moments(&self, size: usize, slot: usize) -> Option<Rc<RefCell<dyn GpuTexture>>> {
+        self.maps
+            .get(&(size, slot))
+            .map(|map| map.v_framebuffer.color_attachments()[0].texture.clone())
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        ctx: VarianceShadowMapRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut stats = RenderPassStatistics::default();
+
+        let VarianceShadowMapRenderContext {
+            server,
+            quad,
+            uniform_buffer_cache,
+            depth,
+            size,
+            slot,
+            parameters,
+            perspective,
+            depth_range,
+        } = ctx;
+
+        let map = match self.maps.entry((size, slot)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(VarianceShadowMap {
+                h_framebuffer: create_framebuffer(server, size)?,
+                v_framebuffer: create_framebuffer(server, size)?,
+            }),
+        };
+
+        let viewport = Rect::new(0, 0, size as i32, size as i32);
+        let inv_size = Vector2::repeat(1.0 / size as f32);
+        let shader = &self.shader;
+        let draw_params = DrawParameters {
+            cull_face: None,
+            color_write: Default::default(),
+            depth_write: false,
+            stencil_test: None,
+            depth_test: None,
+            blend: None,
+            stencil_op: Default::default(),
+            scissor_box: None,
+        };
+
+        // Convert the depth to moments and blur them horizontally first.
+        stats += map.h_framebuffer.draw(
+            quad,
+            viewport,
+            &*shader.program,
+            &draw_params,
+            &[ResourceBindGroup {
+                bindings: &[
+                    ResourceBinding::texture(&depth, &shader.image),
+                    ResourceBinding::Buffer {
+                        buffer: uniform_buffer_cache.write(
+                            StaticUniformBuffer::<256>::new()
+                                .with(&make_viewport_matrix(viewport))
+                                .with(&inv_size)
+                                .with(&parameters.exponents)
+                                .with(&depth_range)
+                                .with(&parameters.technique)
+                                .with(&(parameters.blur_radius as i32))
+                                .with(&true)
+                                .with(&perspective),
+                        )?,
+                        binding: BufferLocation::Auto {
+                            shader_location: shader.uniform_block_binding,
+                        },
+                        data_usage: Default::default(),
+                    },
+                ],
+            }],
+            ElementRange::Full,
+        )?;
+
+        // Then blur vertically.
+        let h_blurred_texture = map.h_framebuffer.color_attachments()[0].texture.clone();
+        stats += map.v_framebuffer.draw(
+            quad,
+            viewport,
+            &*shader.program,
+            &draw_params,
+            &[ResourceBindGroup {
+                bindings: &[
+                    ResourceBinding::texture(&h_blurred_texture, &shader.image),
+                    ResourceBinding::Buffer {
+                        buffer: uniform_buffer_cache.write(
+                            StaticUniformBuffer::<256>::new()
+                                .with(&make_viewport_matrix(viewport))
+                                .with(&inv_size)
+                                .with(&parameters.exponents)
+                                .with(&depth_range)
+                                .with(&parameters.technique)
+                                .with(&(parameters.blur_radius as i32))
+                                .with(&false)
+                                .with(&perspective),
+                        )?,
+                        binding: BufferLocation::Auto {
+                            shader_location: shader.uniform_block_binding,
+                        },
+                        data_usage: Default::default(),
+                    },
+                ],
+            }],
+            ElementRange::Full,
+        )?;
+
+        Ok(stats)
+    }
+}
@@ -0,0 +1,366 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Gameplay replays. A replay is a sequence of input events grouped by fixed logic updates
+//! ("ticks"), which together with deterministic stepping of the engine (fixed time step and
+//! deterministic script execution order) allows to reproduce a gameplay session exactly. Recorders
+//! also take periodic snapshots of a scene, so the replay could be scrubbed without simulating
+//! it from the very beginning. See [`ReplayRecorder`] and [`ReplayPlayer`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector2, visitor::prelude::*},
+    event::{DeviceEvent, ElementState, Event, MouseScrollDelta, WindowEvent},
+    fxhash::FxHashSet,
+    gui::message::{KeyCode, MouseButton},
+    keyboard::PhysicalKey,
+    scene::Scene,
+    utils::{translate_button, translate_key_to_ui},
+};
+use std::path::Path;
+
+pub mod player;
+pub mod recorder;
+
+pub use player::ReplayPlayer;
+pub use recorder::ReplayRecorder;
+
+/// Input event in a platform-independent and serializable form. Games that support replays should
+/// drive their logic by these events (see [`InputState`]) instead of raw OS events, so the same code
+/// works for live input and for replays.
+#[derive(Clone, Debug, PartialEq, Visit)]
+pub enum InputEvent {
+    /// A key was pressed or released.
+    Key {
+        /// Code of the key.
+        code: KeyCode,
+        /// `true` if the key was pressed, `false` - released.
+        pressed: bool,
+    },
+    /// A mouse button was pressed or released.
+    MouseButton {
+        /// Mouse button.
+        button: MouseButton,
+        /// `true` if the button was pressed, `false` - released.
+        pressed: bool,
+    },
+    /// Cursor was moved to a new position (in window coordinates).
+    CursorMoved {
+        /// New position of the cursor.
+        position: Vector2<f32>,
+    },
+    /// Raw mouse movement, that is not limited by the window bounds.
+    MouseMotion {
+        /// Movement delta.
+        delta: Vector2<f32>,
+    },
+    /// Mouse wheel was scrolled.
+    MouseWheel {
+        /// Scroll delta.
+        delta: Vector2<f32>,
+    },
+    /// A game-defined action, for example from a gamepad or a network message.
+    Action {
+        /// Name of the action.
+        name: String,
+        /// Value of the action.
+        value: f32,
+    },
+}
+
+impl Default for InputEvent {
+    fn default() -> Self {
+        Self::MouseMotion {
+            delta: Default::default(),
+        }
+    }
+}
+
+impl InputEvent {
+    /// Tries to convert an OS event to the input event. Key repeats are ignored.
+    pub fn from_os_event(event: &Event<()>) -> Option<Self> {
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::KeyboardInput { event, .. } if !event.repeat => {
+                    if let PhysicalKey::Code(code) = event.physical_key {
+                        Some(Self::Key {
+                            code: translate_key_to_ui(code),
+                            pressed: event.state == ElementState::Pressed,
+                        })
+                    } else {
+                        None
+                    }
+                }
+                WindowEvent::MouseInput { state, button, .. } => Some(Self::MouseButton {
+                    button: translate_button(*button),
+                    pressed: *state == ElementState::Pressed,
+                }),
+                WindowEvent::CursorMoved { position, .. } => Some(Self::CursorMoved {
+                    position: Vector2::new(position.x as f32, position.y as f32),
+                }),
+                WindowEvent::MouseWheel { delta, .. } => Some(Self::MouseWheel {
+                    delta: match delta {
+                        MouseScrollDelta::LineDelta(x, y) => Vector2::new(*x, *y),
+                        MouseScrollDelta::PixelDelta(delta) => {
+                            Vector2::new(delta.x as f32, delta.y as f32)
+                        }
+                    },
+                }),
+                _ => None,
+            },
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } => Some(Self::MouseMotion {
+                delta: Vector2::new(delta.0 as f32, delta.1 as f32),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A simple state of input devices, that is built from [`InputEvent`]s. Per-tick values (mouse
+/// motion, wheel and actions) are accumulated until [`Self::end_tick`] is called.
+#[derive(Clone, Debug, Default)]
+pub struct InputState {
+    keys: FxHashSet<KeyCode>,
+    mouse_buttons: FxHashSet<MouseButton>,
+    cursor_position: Vector2<f32>,
+    mouse_motion: Vector2<f32>,
+    mouse_wheel: Vector2<f32>,
+    actions: Vec<(String, f32)>,
+}
+
+impl InputState {
+    /// Applies the event to the state.
+    pub fn apply(&mut self, event: &InputEvent) {
+        match event {
+            InputEvent::Key { code, pressed } => {
+                if *pressed {
+                    self.keys.insert(*code);
+                } else {
+                    self.keys.remove(code);
+                }
+            }
+            InputEvent::MouseButton { button, pressed } => {
+                if *pressed {
+                    self.mouse_buttons.insert(*button);
+                } else {
+                    self.mouse_buttons.remove(button);
+                }
+            }
+            InputEvent::CursorMoved { position } => self.cursor_position = *position,
+            InputEvent::MouseMotion { delta } => self.mouse_motion += *delta,
+            InputEvent::MouseWheel { delta } => self.mouse_wheel += *delta,
+            InputEvent::Action { name, value } => self.actions.push((name.clone(), *value)),
+        }
+    }
+
+    /// Applies every event to the state.
+    pub fn apply_all<'a>(&mut self, events: impl IntoIterator<Item = &'a InputEvent>) {
+        for event in events {
+            self.apply(event)
+        }
+    }
+
+    /// Resets per-tick values. Must be called at the end of every logic update.
+    pub fn end_tick(&mut self) {
+        self.mouse_motion = Vector2::default();
+        self.mouse_wheel = Vector2::default();
+        self.actions.clear();
+    }
+
+    /// Resets the entire state.
+    pub fn clear(&mut self) {
+        *self = Default::default();
+    }
+
+    /// Returns `true` if the key is held down.
+    pub fn is_key_pressed(&self, code: KeyCode) -> bool {
+        self.keys.contains(&code)
+    }
+
+    /// Returns `true` if the mouse button is held down.
+    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.contains(&button)
+    }
+
+    /// Returns the last known position of the cursor.
+    pub fn cursor_position(&self) -> Vector2<f32> {
+        self.cursor_position
+    }
+
+    /// Returns accumulated mouse movement of the current tick.
+    pub fn mouse_motion(&self) -> Vector2<f32> {
+        self.mouse_motion
+    }
+
+    /// Returns accumulated mouse wheel scroll of the current tick.
+    pub fn mouse_wheel(&self) -> Vector2<f32> {
+        self.mouse_wheel
+    }
+
+    /// Returns the value of the given action, if it was triggered during the current tick.
+    pub fn action(&self, name: &str) -> Option<f32> {
+        self.actions
+            .iter()
+            .rev()
+            .find_map(|(action, value)| (action == name).then_some(*value))
+    }
+}
+
+/// Input events of a single logic update.
+#[derive(Clone, Debug, Default, PartialEq, Visit)]
+pub struct ReplayFrame {
+    /// Events in the order they were received.
+    pub events: Vec<InputEvent>,
+}
+
+/// Snapshot of a scene at the beginning of a tick.
+#[derive(Debug)]
+pub struct ReplaySnapshot {
+    /// Index of the tick.
+    pub tick: usize,
+    /// A 1:1 copy of the scene, so the handles of its nodes are the same as in the original scene.
+    pub scene: Scene,
+}
+
+/// Replay is a recorded gameplay session. Only the input is stored in files, snapshots are kept in
+/// memory and used for scrubbing. Replays saved to files are played back by simulating them from
+/// the initial state of the game (which is up to the game to restore, for example by loading the
+/// same level with the same [`Self::seed`]).
+#[derive(Debug, Default, Visit)]
+pub struct Replay {
+    /// Update rate (in updates per second) that was used while recording.
+    pub update_rate: f32,
+    /// A seed for random number generators, that should be used by the game to make its logic
+    /// deterministic.
+    pub seed: u64,
+    /// Input events of each tick.
+    pub frames: Vec<ReplayFrame>,
+    /// Snapshots sorted by their ticks.
+    #[visit(skip)]
+    pub snapshots: Vec<ReplaySnapshot>,
+}
+
+impl Replay {
+    /// Returns total amount of ticks in the replay.
+    pub fn tick_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns total duration of the replay in seconds.
+    pub fn duration(&self) -> f32 {
+        self.frames.len() as f32 / self.update_rate.max(f32::EPSILON)
+    }
+
+    /// Returns the closest snapshot, that was taken at the given tick or before it.
+    pub fn snapshot_before(&self, tick: usize) -> Option<&ReplaySnapshot> {
+        let index = self
+            .snapshots
+            .partition_point(|snapshot| snapshot.tick <= tick);
+        index.checked_sub(1).map(|index| &self.snapshots[index])
+    }
+
+    /// Saves the input of the replay to a file.
+    pub fn save(&mut self, path: impl AsRef<Path>) -> VisitResult {
+        let mut visitor = Visitor::new();
+        self.visit("Replay", &mut visitor)?;
+        visitor.save_binary(path)
+    }
+
+    /// Loads a replay from a file.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::load_binary(path).await?;
+        let mut replay = Replay::default();
+        replay.visit("Replay", &mut visitor)?;
+        Ok(replay)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_input_state() {
+        let mut state = InputState::default();
+        state.apply_all(&[
+            InputEvent::Key {
+                code: KeyCode::KeyW,
+                pressed: true,
+            },
+            InputEvent::MouseMotion {
+                delta: Vector2::new(1.0, 2.0),
+            },
+            InputEvent::MouseMotion {
+                delta: Vector2::new(1.0, 2.0),
+            },
+            InputEvent::Action {
+                name: "Fire".to_string(),
+                value: 1.0,
+            },
+        ]);
+        assert!(state.is_key_pressed(KeyCode::KeyW));
+        assert_eq!(state.mouse_motion(), Vector2::new(2.0, 4.0));
+        assert_eq!(state.action("Fire"), Some(1.0));
+
+        state.end_tick();
+        assert!(state.is_key_pressed(KeyCode::KeyW));
+        assert_eq!(state.mouse_motion(), Vector2::default());
+        assert_eq!(state.action("Fire"), None);
+
+        state.apply(&InputEvent::Key {
+            code: KeyCode::KeyW,
+            pressed: false,
+        });
+        assert!(!state.is_key_pressed(KeyCode::KeyW));
+    }
+
+    #[test]
+    fn test_replay_visit() {
+        let mut replay = Replay {
+            update_rate: 30.0,
+            seed: 123,
+            frames: vec![
+                ReplayFrame::default(),
+                ReplayFrame {
+                    events: vec![InputEvent::Action {
+                        name: "Jump".to_string(),
+                        value: 1.0,
+                    }],
+                },
+            ],
+            snapshots: Vec::new(),
+        };
+
+        let mut visitor = Visitor::new();
+        replay.visit("Replay", &mut visitor).unwrap();
+        let mut data = Vec::new();
+        visitor.save_binary_to_memory(&mut data).unwrap();
+
+        let mut visitor = Visitor::load_from_memory(&data).unwrap();
+        let mut loaded = Replay::default();
+        loaded.visit("Replay", &mut visitor).unwrap();
+        assert_eq!(loaded.update_rate, 30.0);
+        assert_eq!(loaded.seed, 123);
+        assert_eq!(loaded.frames, replay.frames);
+        assert_eq!(loaded.duration(), 2.0 / 30.0);
+    }
+}
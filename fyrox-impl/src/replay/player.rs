@@ -0,0 +1,218 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Replay player. See [`ReplayPlayer`] docs for more info.
+
+use crate::{
+    engine::update_loop::UpdateLoopSettings,
+    replay::{InputEvent, Replay},
+    scene::Scene,
+};
+
+/// Replay player feeds recorded input to the game tick by tick. Replays are played back by
+/// simulating the game with the recorded input, so the game must use [`Self::next_tick`] instead of
+/// live input while the replay is playing.
+///
+/// The typical usage is:
+///
+/// 1) Restore the initial state of the game (for example, by calling [`Self::seek`] with zero tick,
+/// which restores the initial snapshot) and call [`Self::apply_update_loop_settings`].
+/// 2) In every [`crate::plugin::Plugin::update`] call [`Self::next_tick`] and feed the returned
+/// events to the game logic. When the player is catching up after a seek, the game should simulate
+/// ticks in a loop while [`Self::is_catching_up`] returns `true`, without rendering them.
+///
+/// ## Scrubbing
+///
+/// [`Self::seek`] restores the closest snapshot that was taken before the requested tick and then
+/// the player fast-forwards to the requested tick by simulating the ticks in between. This way any
+/// moment of a replay could be reached quickly, which is useful for kill-cams and debugging.
+#[derive(Debug)]
+pub struct ReplayPlayer {
+    replay: Replay,
+    tick: usize,
+    target_tick: Option<usize>,
+    paused: bool,
+}
+
+impl ReplayPlayer {
+    /// Creates new player for the given replay.
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            tick: 0,
+            target_tick: None,
+            paused: false,
+        }
+    }
+
+    /// Returns the replay that is played by the player.
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Consumes the player and returns the replay.
+    pub fn into_replay(self) -> Replay {
+        self.replay
+    }
+
+    /// Makes the update loop deterministic: sets the update rate of the replay and disables time
+    /// step throttling, so every tick is simulated with the same time step as it was recorded.
+    pub fn apply_update_loop_settings(&self, settings: &mut UpdateLoopSettings) {
+        settings.update_rate = self.replay.update_rate;
+        settings.throttle_threshold = f32::MAX;
+    }
+
+    /// Returns input events of the current tick and moves to the next one. Returns `None` if the
+    /// player is paused (and not catching up) or if the replay has ended.
+    pub fn next_tick(&mut self) -> Option<&[InputEvent]> {
+        if self.paused && !self.is_catching_up() {
+            return None;
+        }
+
+        let frame = self.replay.frames.get(self.tick)?;
+        self.tick += 1;
+        if self.target_tick.is_some_and(|target| self.tick >= target) {
+            self.target_tick = None;
+        }
+        Some(&frame.events)
+    }
+
+    /// Moves playback position to the given tick. The closest snapshot before the tick is restored
+    /// into the scene, the rest of the ticks must be simulated by the game (see
+    /// [`Self::is_catching_up`]). Returns `false` if there is no snapshot before the tick.
+    pub fn seek(&mut self, tick: usize, scene: &mut Scene) -> bool {
+        let tick = tick.min(self.replay.tick_count());
+        let Some(snapshot) = self.replay.snapshot_before(tick) else {
+            return false;
+        };
+
+        *scene = snapshot.scene.clone_one_to_one().0;
+        self.tick = snapshot.tick;
+        self.target_tick = (tick > snapshot.tick).then_some(tick);
+        true
+    }
+
+    /// Returns `true` if the player is fast-forwarding to a tick requested by [`Self::seek`]. Ticks
+    /// should be simulated as fast as possible in this mode.
+    pub fn is_catching_up(&self) -> bool {
+        self.target_tick.is_some()
+    }
+
+    /// Returns amount of ticks left to simulate until the player reaches the tick requested by
+    /// [`Self::seek`].
+    pub fn catch_up_ticks(&self) -> usize {
+        self.target_tick
+            .map_or(0, |target| target.saturating_sub(self.tick))
+    }
+
+    /// Pauses or resumes the playback.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Returns `true` if the playback is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Returns index of the next tick to be played.
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    /// Returns current playback position in seconds.
+    pub fn time(&self) -> f32 {
+        self.tick as f32 / self.replay.update_rate.max(f32::EPSILON)
+    }
+
+    /// Returns `true` if every tick of the replay was played.
+    pub fn is_finished(&self) -> bool {
+        self.tick >= self.replay.tick_count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, pool::Handle},
+        replay::{InputEvent, ReplayPlayer, ReplayRecorder},
+        scene::{base::BaseBuilder, node::Node, pivot::PivotBuilder, Scene},
+    };
+
+    fn simulate(scene: &mut Scene, node: Handle<Node>, events: &[InputEvent]) {
+        for event in events {
+            if let InputEvent::Action { value, .. } = event {
+                let position = **scene.graph[node].local_transform().position();
+                scene.graph[node]
+                    .local_transform_mut()
+                    .set_position(position + Vector3::new(*value, 0.0, 0.0));
+            }
+        }
+    }
+
+    fn position(scene: &Scene, node: Handle<Node>) -> f32 {
+        scene.graph[node].local_transform().position().x
+    }
+
+    #[test]
+    fn test_record_and_scrub() {
+        let mut scene = Scene::new();
+        let node = PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+
+        let mut recorder = ReplayRecorder::new(&scene, 60.0, 0).with_snapshot_interval(4);
+        for _ in 0..10 {
+            let event = InputEvent::Action {
+                name: "Move".to_string(),
+                value: 1.0,
+            };
+            recorder.record(event.clone());
+            simulate(&mut scene, node, &[event]);
+            recorder.end_tick(&scene);
+        }
+        assert_eq!(position(&scene, node), 10.0);
+
+        let replay = recorder.finish();
+        assert_eq!(replay.tick_count(), 10);
+        // Initial snapshot + snapshots at ticks 4 and 8.
+        assert_eq!(replay.snapshots.len(), 3);
+
+        let mut player = ReplayPlayer::new(replay);
+        assert!(player.seek(6, &mut scene));
+        assert_eq!(player.tick(), 4);
+        assert_eq!(position(&scene, node), 4.0);
+        assert_eq!(player.catch_up_ticks(), 2);
+
+        player.set_paused(true);
+        while player.is_catching_up() {
+            let events = player.next_tick().unwrap().to_vec();
+            simulate(&mut scene, node, &events);
+        }
+        assert_eq!(position(&scene, node), 6.0);
+        assert!(player.next_tick().is_none());
+
+        player.set_paused(false);
+        while let Some(events) = player.next_tick() {
+            let events = events.to_vec();
+            simulate(&mut scene, node, &events);
+        }
+        assert!(player.is_finished());
+        assert_eq!(position(&scene, node), 10.0);
+    }
+}
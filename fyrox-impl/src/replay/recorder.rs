@@ -0,0 +1,129 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Replay recorder. See [`ReplayRecorder`] docs for more info.
+
+use crate::{
+    event::Event,
+    replay::{InputEvent, Replay, ReplayFrame, ReplaySnapshot},
+    scene::Scene,
+};
+
+/// Replay recorder collects input events and groups them by logic updates ("ticks"). It also takes
+/// snapshots of a scene every [`Self::snapshot_interval`] ticks, which are then used to scrub the
+/// replay.
+///
+/// The typical usage is:
+///
+/// 1) Create the recorder when a level is loaded, so it takes the initial snapshot.
+/// 2) Pass every OS event to [`Self::record_os_event`] in [`crate::plugin::Plugin::on_os_event`]
+/// and use the returned [`InputEvent`] to drive the game logic.
+/// 3) Call [`Self::end_tick`] at the end of every [`crate::plugin::Plugin::update`].
+/// 4) Call [`Self::finish`] to get the [`Replay`].
+///
+/// The game must be deterministic for replays to work, which means that it should use only the
+/// recorded input, the fixed time step and random number generators seeded with [`Replay::seed`].
+#[derive(Debug)]
+pub struct ReplayRecorder {
+    replay: Replay,
+    current_frame: ReplayFrame,
+    snapshot_interval: usize,
+}
+
+impl ReplayRecorder {
+    /// Default amount of ticks between scene snapshots.
+    pub const DEFAULT_SNAPSHOT_INTERVAL: usize = 300;
+
+    /// Creates new recorder. `update_rate` must be the same as in the update loop settings of the
+    /// engine (see [`crate::engine::update_loop::UpdateLoopSettings::update_rate`]). The initial
+    /// snapshot of the scene is taken immediately.
+    pub fn new(scene: &Scene, update_rate: f32, seed: u64) -> Self {
+        let mut recorder = Self {
+            replay: Replay {
+                update_rate,
+                seed,
+                ..Default::default()
+            },
+            current_frame: Default::default(),
+            snapshot_interval: Self::DEFAULT_SNAPSHOT_INTERVAL,
+        };
+        recorder.take_snapshot(scene);
+        recorder
+    }
+
+    /// Sets amount of ticks between scene snapshots. Lower values make scrubbing faster at the cost
+    /// of higher memory usage.
+    pub fn with_snapshot_interval(mut self, snapshot_interval: usize) -> Self {
+        self.snapshot_interval = snapshot_interval.max(1);
+        self
+    }
+
+    /// Returns amount of ticks between scene snapshots.
+    pub fn snapshot_interval(&self) -> usize {
+        self.snapshot_interval
+    }
+
+    /// Records the given event in the current tick.
+    pub fn record(&mut self, event: InputEvent) {
+        self.current_frame.events.push(event);
+    }
+
+    /// Converts an OS event to [`InputEvent`] and records it. Returns the converted event, so it could
+    /// be used by the game logic.
+    pub fn record_os_event(&mut self, event: &Event<()>) -> Option<InputEvent> {
+        let event = InputEvent::from_os_event(event)?;
+        self.record(event.clone());
+        Some(event)
+    }
+
+    /// Finishes the current tick. The scene must be in the state after the tick was simulated, it
+    /// will be used as a snapshot of the next tick if needed.
+    pub fn end_tick(&mut self, scene: &Scene) {
+        self.replay
+            .frames
+            .push(std::mem::take(&mut self.current_frame));
+        if self.tick() % self.snapshot_interval == 0 {
+            self.take_snapshot(scene);
+        }
+    }
+
+    /// Returns index of the current tick.
+    pub fn tick(&self) -> usize {
+        self.replay.frames.len()
+    }
+
+    /// Returns the replay recorded so far.
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Finishes the recording and returns the replay. Events of the unfinished tick are discarded.
+    pub fn finish(self) -> Replay {
+        self.replay
+    }
+
+    fn take_snapshot(&mut self, scene: &Scene) {
+        let (scene, _) = scene.clone_one_to_one();
+        self.replay.snapshots.push(ReplaySnapshot {
+            tick: self.tick(),
+            scene,
+        });
+    }
+}
@@ -0,0 +1,624 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Sprite atlas is a resource, that packs a folder of images into a single texture and stores
+//! texture coordinates of every image. See [`SpriteAtlas`] docs for more info.
+
+use crate::{
+    asset::{io::ResourceIo, untyped::ResourceKind, Resource, ResourceData},
+    core::{
+        algebra::Vector2, io::FileLoadError, math::Rect, rectpack::RectPacker, reflect::prelude::*,
+        type_traits::prelude::*, visitor::prelude::*,
+    },
+    fxhash::FxHashMap,
+    material::{Material, MaterialResource, MaterialResourceBinding, MaterialTextureBinding},
+    resource::texture::{Texture, TextureKind, TexturePixelKind, TextureResource},
+    scene::{
+        dim2::rectangle::Rectangle,
+        tilemap::{
+            tileset::{
+                TileBounds, TileDefinition, TileMaterialBounds, TileSetPage, TileSetPageSource,
+            },
+            TileDefinitionHandle, TileGridMap,
+        },
+    },
+};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+pub mod loader;
+
+/// An error that may occur during sprite atlas loading.
+#[derive(Debug)]
+pub enum SpriteAtlasError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+    /// The atlas descriptor is malformed.
+    Descriptor(ron::error::SpannedError),
+    /// Unable to decode a source image.
+    Image {
+        /// Path of the image.
+        path: PathBuf,
+        /// Actual error.
+        error: image::ImageError,
+    },
+    /// Sprites do not fit into an atlas of the maximum size.
+    DoesNotFit {
+        /// Maximum size of the atlas.
+        max_size: u32,
+    },
+}
+
+impl Display for SpriteAtlasError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpriteAtlasError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            SpriteAtlasError::Descriptor(v) => {
+                write!(f, "Unable to parse sprite atlas descriptor. Reason: {v}")
+            }
+            SpriteAtlasError::Image { path, error } => {
+                write!(
+                    f,
+                    "Unable to load image {}. Reason: {error}",
+                    path.display()
+                )
+            }
+            SpriteAtlasError::DoesNotFit { max_size } => {
+                write!(
+                    f,
+                    "Sprites do not fit into an atlas of {max_size}x{max_size} pixels."
+                )
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for SpriteAtlasError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for SpriteAtlasError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::Descriptor(e)
+    }
+}
+
+/// Content of an atlas descriptor file (`*.atlas`). The descriptor defines a folder with source
+/// images and the packing settings. It is stored in RON format, for example:
+///
+/// ```ron
+/// (
+///     folder: "sprites",
+///     padding: 2,
+///     trim: true,
+/// )
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpriteAtlasDescriptor {
+    /// Path of a folder with source images, relative to the descriptor file. The folder is
+    /// scanned recursively.
+    pub folder: PathBuf,
+    /// Amount of pixels between sprites. Padding prevents colors of neighbouring sprites from
+    /// bleeding into each other when the atlas is filtered or mip-mapped.
+    pub padding: u32,
+    /// If `true`, padding is filled with the edge pixels of sprites instead of transparent
+    /// pixels, which removes seams between tiles.
+    pub extrude: bool,
+    /// If `true`, fully transparent borders of images are cut off.
+    pub trim: bool,
+    /// Pixels with alpha less or equal to this value are considered transparent when trimming.
+    pub alpha_threshold: u8,
+    /// Maximum size of the atlas texture side in pixels.
+    pub max_size: u32,
+}
+
+impl Default for SpriteAtlasDescriptor {
+    fn default() -> Self {
+        Self {
+            folder: PathBuf::from("."),
+            padding: 2,
+            extrude: true,
+            trim: true,
+            alpha_threshold: 0,
+            max_size: 4096,
+        }
+    }
+}
+
+/// An image, that should be packed into an atlas.
+pub struct SpriteSource {
+    /// Name of the sprite, that will be used to fetch the sprite from the atlas.
+    pub name: String,
+    /// Pixels of the sprite.
+    pub image: RgbaImage,
+}
+
+/// Location of a sprite in an atlas.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct AtlasSprite {
+    /// Rectangle of the sprite in the atlas texture in pixels.
+    pub bounds: Rect<u32>,
+    /// Rectangle of the sprite in the atlas texture in normalized texture coordinates. It could be
+    /// used directly as UV rectangle of [`Rectangle`] nodes.
+    pub uv_rect: Rect<f32>,
+    /// Size of the source image before trimming.
+    pub source_size: Vector2<u32>,
+    /// Offset of the trimmed rectangle in the source image. Could be used to restore the original
+    /// position of the sprite.
+    pub trim_offset: Vector2<u32>,
+}
+
+/// Sprite atlas is a texture, that contains multiple sprites packed together. It is created from a
+/// folder of images described by an `.atlas` file (see [`SpriteAtlasDescriptor`]). Using one atlas
+/// instead of a separate texture per sprite allows the renderer to batch sprites together.
+///
+/// Sprites are named by their path relative to the source folder without extension, for example
+/// `enemies/bat` for `sprites/enemies/bat.png`.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     asset::manager::ResourceManager,
+/// #     resource::atlas::SpriteAtlas,
+/// #     scene::dim2::rectangle::Rectangle,
+/// # };
+/// async fn set_sprite(resource_manager: ResourceManager, rectangle: &mut Rectangle) {
+///     let atlas = resource_manager
+///         .request::<SpriteAtlas>("data/sprites.atlas")
+///         .await
+///         .unwrap();
+///     atlas.data_ref().apply_to_rectangle("enemies/bat", rectangle);
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "b8cbb854-4a05-49ab-987f-ff6c9556d894")]
+pub struct SpriteAtlas {
+    texture: Option<TextureResource>,
+    material: Option<MaterialResource>,
+    tile_material: Option<MaterialResource>,
+    size: Vector2<u32>,
+    sprites: FxHashMap<String, AtlasSprite>,
+}
+
+impl ResourceData for SpriteAtlas {
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, _path: &Path) -> Result<(), Box<dyn Error>> {
+        Err(
+            "Sprite atlas is generated from its descriptor and cannot be saved!"
+                .to_string()
+                .into(),
+        )
+    }
+
+    fn can_be_saved(&self) -> bool {
+        false
+    }
+}
+
+/// Returns the bounds of non-transparent pixels of the image or `None` if the image is fully
+/// transparent.
+fn opaque_bounds(image: &RgbaImage, alpha_threshold: u8) -> Option<Rect<u32>> {
+    let mut min = Vector2::new(u32::MAX, u32::MAX);
+    let mut max = Vector2::new(0, 0);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel[3] > alpha_threshold {
+            min = Vector2::new(min.x.min(x), min.y.min(y));
+            max = Vector2::new(max.x.max(x), max.y.max(y));
+        }
+    }
+    (min.x <= max.x).then(|| Rect::new(min.x, min.y, max.x - min.x + 1, max.y - min.y + 1))
+}
+
+struct PackItem {
+    name: String,
+    image: RgbaImage,
+    source_size: Vector2<u32>,
+    trim_offset: Vector2<u32>,
+}
+
+/// Finds the smallest power-of-two square size, that fits all the items.
+fn find_layout(
+    sizes: &[Vector2<u32>],
+    padding: u32,
+    max_size: u32,
+) -> Option<(u32, Vec<Rect<u32>>)> {
+    let padded = |size: &Vector2<u32>| size.add_scalar(2 * padding);
+    let area = sizes
+        .iter()
+        .map(|size| {
+            let size = padded(size);
+            size.x as u64 * size.y as u64
+        })
+        .sum::<u64>();
+    let largest = sizes
+        .iter()
+        .map(|size| padded(size).max())
+        .max()
+        .unwrap_or(1);
+
+    let mut size = ((area as f64).sqrt().ceil() as u32)
+        .max(largest)
+        .next_power_of_two();
+    while size <= max_size {
+        let mut packer = RectPacker::new(size, size);
+        let placements = sizes
+            .iter()
+            .map(|item| {
+                let item = padded(item);
+                packer.find_free(item.x, item.y)
+            })
+            .collect::<Option<Vec<_>>>();
+        if let Some(placements) = placements {
+            return Some((size, placements));
+        }
+        size *= 2;
+    }
+    None
+}
+
+impl SpriteAtlas {
+    /// Packs the given images into a new atlas using the given settings.
+    pub fn pack(
+        sources: Vec<SpriteSource>,
+        descriptor: &SpriteAtlasDescriptor,
+    ) -> Result<Self, SpriteAtlasError> {
+        let mut items = sources
+            .into_iter()
+            .map(|source| {
+                let source_size = Vector2::new(source.image.width(), source.image.height());
+                let bounds = if descriptor.trim {
+                    opaque_bounds(&source.image, descriptor.alpha_threshold)
+                        // Keep one pixel of fully transparent images so they are still present
+                        // in the atlas.
+                        .unwrap_or_else(|| Rect::new(0, 0, 1, 1))
+                } else {
+                    Rect::new(0, 0, source_size.x, source_size.y)
+                };
+                PackItem {
+                    name: source.name,
+                    image: image::imageops::crop_imm(
+                        &source.image,
+                        bounds.x(),
+                        bounds.y(),
+                        bounds.w(),
+                        bounds.h(),
+                    )
+                    .to_image(),
+                    source_size,
+                    trim_offset: bounds.position,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Packing of tall images first gives tighter results.
+        items.sort_by(|a, b| {
+            b.image
+                .height()
+                .cmp(&a.image.height())
+                .then(b.image.width().cmp(&a.image.width()))
+                .then(a.name.cmp(&b.name))
+        });
+
+        let sizes = items
+            .iter()
+            .map(|item| Vector2::new(item.image.width(), item.image.height()))
+            .collect::<Vec<_>>();
+        let (size, placements) = find_layout(&sizes, descriptor.padding, descriptor.max_size)
+            .ok_or(SpriteAtlasError::DoesNotFit {
+                max_size: descriptor.max_size,
+            })?;
+
+        let mut pixels = RgbaImage::new(size, size);
+        let mut sprites = FxHashMap::default();
+        let padding = descriptor.padding as i64;
+        for (item, placement) in items.into_iter().zip(placements) {
+            let (width, height) = item.image.dimensions();
+            let origin = Vector2::new(
+                placement.x() + descriptor.padding,
+                placement.y() + descriptor.padding,
+            );
+
+            let border = if descriptor.extrude { padding } else { 0 };
+            for y in -border..height as i64 + border {
+                for x in -border..width as i64 + border {
+                    let source_x = x.clamp(0, width as i64 - 1) as u32;
+                    let source_y = y.clamp(0, height as i64 - 1) as u32;
+                    pixels.put_pixel(
+                        (origin.x as i64 + x) as u32,
+                        (origin.y as i64 + y) as u32,
+                        *item.image.get_pixel(source_x, source_y),
+                    );
+                }
+            }
+
+            let k = 1.0 / size as f32;
+            sprites.insert(
+                item.name,
+                AtlasSprite {
+                    bounds: Rect::new(origin.x, origin.y, width, height),
+                    uv_rect: Rect::new(
+                        origin.x as f32 * k,
+                        origin.y as f32 * k,
+                        width as f32 * k,
+                        height as f32 * k,
+                    ),
+                    source_size: item.source_size,
+                    trim_offset: item.trim_offset,
+                },
+            );
+        }
+
+        let texture = Texture::from_bytes(
+            TextureKind::Rectangle {
+                width: size,
+                height: size,
+            },
+            TexturePixelKind::RGBA8,
+            pixels.into_raw(),
+        )
+        .map(|texture| TextureResource::new_ok(ResourceKind::Embedded, texture));
+
+        let make_material = |mut material: Material| {
+            material.bind(
+                "diffuseTexture",
+                MaterialResourceBinding::Texture(MaterialTextureBinding {
+                    value: texture.clone(),
+                }),
+            );
+            MaterialResource::new_ok(ResourceKind::Embedded, material)
+        };
+
+        Ok(Self {
+            material: Some(make_material(Material::standard_2d())),
+            tile_material: Some(make_material(Material::standard_tile())),
+            texture,
+            size: Vector2::new(size, size),
+            sprites,
+        })
+    }
+
+    /// Returns the texture of the atlas.
+    pub fn texture(&self) -> Option<&TextureResource> {
+        self.texture.as_ref()
+    }
+
+    /// Returns the size of the atlas texture in pixels.
+    pub fn size(&self) -> Vector2<u32> {
+        self.size
+    }
+
+    /// Returns a standard 2D material, that uses the atlas texture. The material is shared by all
+    /// users of the atlas, so their sprites could be batched together.
+    pub fn material(&self) -> Option<&MaterialResource> {
+        self.material.as_ref()
+    }
+
+    /// Returns the sprite with the given name.
+    pub fn sprite(&self, name: &str) -> Option<&AtlasSprite> {
+        self.sprites.get(name)
+    }
+
+    /// Returns an iterator over all the sprites of the atlas with their names.
+    pub fn sprites(&self) -> impl Iterator<Item = (&str, &AtlasSprite)> {
+        self.sprites
+            .iter()
+            .map(|(name, sprite)| (name.as_str(), sprite))
+    }
+
+    /// Makes the given rectangle to show the sprite with the given name. Returns `false` if there
+    /// is no such sprite.
+    pub fn apply_to_rectangle(&self, name: &str, rectangle: &mut Rectangle) -> bool {
+        let (Some(sprite), Some(material)) = (self.sprite(name), self.material.as_ref()) else {
+            return false;
+        };
+        rectangle
+            .material_mut()
+            .set_value_and_mark_modified(material.clone());
+        rectangle.set_uv_rect(sprite.uv_rect);
+        true
+    }
+
+    /// Creates a freeform tile set page, that contains every sprite of the atlas as a tile. Tiles
+    /// are sorted by sprite names and laid out in rows of the given width, starting from the
+    /// `(0, -1)` tile. The page should be inserted at `page_position` of a tile set.
+    pub fn make_tile_set_page(&self, page_position: Vector2<i32>, columns: u32) -> TileSetPage {
+        let mut names = self.sprites.keys().collect::<Vec<_>>();
+        names.sort();
+
+        let columns = columns.max(1) as usize;
+        let material = self.tile_material.clone().unwrap_or_default();
+        let mut tiles = TileGridMap::default();
+        for (index, name) in names.into_iter().enumerate() {
+            let bounds = self.sprites[name].bounds;
+            let position = Vector2::new((index % columns) as i32, -1 - (index / columns) as i32);
+            let left_top_corner = bounds.position;
+            tiles.insert(
+                position,
+                TileDefinition {
+                    material_bounds: TileMaterialBounds {
+                        material: material.clone(),
+                        bounds: TileBounds {
+                            left_top_corner,
+                            right_top_corner: left_top_corner + Vector2::new(bounds.w(), 0),
+                            left_bottom_corner: left_top_corner + Vector2::new(0, bounds.h()),
+                            right_bottom_corner: left_top_corner + bounds.size,
+                        },
+                    },
+                    data: Default::default(),
+                },
+            );
+        }
+
+        TileSetPage {
+            icon: TileDefinitionHandle {
+                page: page_position,
+                tile: Vector2::new(0, -1),
+            },
+            source: TileSetPageSource::Freeform(tiles),
+        }
+    }
+
+    /// Loads an atlas using the descriptor at the given path.
+    pub async fn from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, SpriteAtlasError> {
+        let bytes = io.load_file(path).await?;
+        let descriptor = ron::de::from_bytes::<SpriteAtlasDescriptor>(&bytes)?;
+        let folder = path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join(&descriptor.folder);
+
+        let mut sources = Vec::new();
+        for image_path in io.walk_directory(&folder).await? {
+            let is_image = image_path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ext.to_lowercase())
+                .map_or(false, |ext| {
+                    matches!(
+                        ext.as_str(),
+                        "png" | "jpg" | "jpeg" | "tga" | "bmp" | "gif" | "tif" | "tiff"
+                    )
+                });
+            if !is_image {
+                continue;
+            }
+
+            let data = io.load_file(&image_path).await?;
+            let image = image::load_from_memory(&data)
+                .map_err(|error| SpriteAtlasError::Image {
+                    path: image_path.clone(),
+                    error,
+                })?
+                .to_rgba8();
+            let name = image_path
+                .strip_prefix(&folder)
+                .unwrap_or(&image_path)
+                .with_extension("")
+                .to_string_lossy()
+                .replace('\\', "/");
+            sources.push(SpriteSource { name, image });
+        }
+
+        Self::pack(sources, &descriptor)
+    }
+}
+
+/// Type alias for sprite atlas resources.
+pub type SpriteAtlasResource = Resource<SpriteAtlas>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::Rgba;
+
+    fn make_image(width: u32, height: u32, opaque: Rect<u32>) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, y| {
+            let inside = x >= opaque.x()
+                && x < opaque.x() + opaque.w()
+                && y >= opaque.y()
+                && y < opaque.y() + opaque.h();
+            if inside {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        })
+    }
+
+    #[test]
+    fn test_opaque_bounds() {
+        let image = make_image(8, 8, Rect::new(2, 3, 4, 2));
+        assert_eq!(opaque_bounds(&image, 0), Some(Rect::new(2, 3, 4, 2)));
+        assert_eq!(opaque_bounds(&RgbaImage::new(4, 4), 0), None);
+    }
+
+    #[test]
+    fn test_pack() {
+        let descriptor = SpriteAtlasDescriptor {
+            padding: 1,
+            ..Default::default()
+        };
+        let atlas = SpriteAtlas::pack(
+            vec![
+                SpriteSource {
+                    name: "a".to_string(),
+                    image: make_image(8, 8, Rect::new(2, 2, 4, 4)),
+                },
+                SpriteSource {
+                    name: "b".to_string(),
+                    image: make_image(6, 3, Rect::new(0, 0, 6, 3)),
+                },
+            ],
+            &descriptor,
+        )
+        .unwrap();
+
+        let a = atlas.sprite("a").unwrap();
+        assert_eq!(a.bounds.size, Vector2::new(4, 4));
+        assert_eq!(a.trim_offset, Vector2::new(2, 2));
+        assert_eq!(a.source_size, Vector2::new(8, 8));
+
+        let b = atlas.sprite("b").unwrap();
+        assert_eq!(b.bounds.size, Vector2::new(6, 3));
+
+        // Sprites must not overlap including their padding.
+        let overlaps = |a: Rect<u32>, b: Rect<u32>| {
+            a.x() < b.x() + b.w() + 2
+                && b.x() < a.x() + a.w() + 2
+                && a.y() < b.y() + b.h() + 2
+                && b.y() < a.y() + a.h() + 2
+        };
+        assert!(!overlaps(a.bounds, b.bounds));
+
+        let size = atlas.size().x as f32;
+        assert_eq!(a.uv_rect.x(), a.bounds.x() as f32 / size);
+    }
+
+    #[test]
+    fn test_does_not_fit() {
+        let descriptor = SpriteAtlasDescriptor {
+            max_size: 8,
+            trim: false,
+            ..Default::default()
+        };
+        let result = SpriteAtlas::pack(
+            vec![SpriteSource {
+                name: "a".to_string(),
+                image: RgbaImage::new(16, 16),
+            }],
+            &descriptor,
+        );
+        assert!(matches!(result, Err(SpriteAtlasError::DoesNotFit { .. })));
+    }
+}
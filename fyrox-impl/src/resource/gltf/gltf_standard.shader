@@ -42,6 +42,11 @@
             kind: Texture(kind: Sampler3D, fallback: Volume),
             binding: 7
         ),
+        (
+            name: "transmissionTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 8
+        ),
         (
             name: "properties",
             kind: PropertyGroup([
@@ -49,6 +54,14 @@
                     name: "texCoordScale",
                     kind: Vector2((1.0, 1.0)),
                 ),
+                (
+                    name: "texCoordOffset",
+                    kind: Vector2((0.0, 0.0)),
+                ),
+                (
+                    name: "texCoordRotation",
+                    kind: Float(0.0),
+                ),
                 (
                     name: "layerIndex",
                     kind: UInt(0),
@@ -69,6 +82,29 @@
                     name: "parallaxScale",
                     kind: Float(0.08),
                 ),
+                (
+                    name: "metallicFactor",
+                    kind: Float(1.0),
+                ),
+                (
+                    name: "roughnessFactor",
+                    kind: Float(1.0),
+                ),
+                // The deferred renderer cannot refract light, so meshes with transmissive
+                // materials are rendered with the forward renderer, which approximates
+                // transmission with alpha blending.
+                (
+                    name: "transmissionFactor",
+                    kind: Float(0.0),
+                ),
+                (
+                    name: "clearcoatFactor",
+                    kind: Float(0.0),
+                ),
+                (
+                    name: "clearcoatRoughnessFactor",
+                    kind: Float(0.0),
+                ),
             ]),
             binding: 0
         ),
@@ -227,6 +263,15 @@
                 in vec3 binormal;
                 in vec2 secondTexCoord;
 
+                // KHR_texture_transform: translation * rotation * scale.
+                vec2 TransformTexCoord(vec2 uv)
+                {
+                    float c = cos(properties.texCoordRotation);
+                    float s = sin(properties.texCoordRotation);
+                    mat2 rotation = mat2(c, s, -s, c);
+                    return rotation * (uv * properties.texCoordScale) + properties.texCoordOffset;
+                }
+
                 void main()
                 {
                     mat3 tangentSpace = mat3(tangent, binormal, normal);
@@ -238,12 +283,12 @@
                         tc = S_ComputeParallaxTextureCoordinates(
                             heightTexture,
                             toFragmentTangentSpace,
-                            texCoord * properties.texCoordScale,
+                            TransformTexCoord(texCoord),
                             properties.parallaxCenter,
                             properties.parallaxScale
                         );
                     } else {
-                        tc = texCoord * properties.texCoordScale;
+                        tc = TransformTexCoord(texCoord);
                     }
 
                     outColor = properties.diffuseColor * texture(diffuseTexture, tc);
@@ -259,6 +304,8 @@
 
                     outMaterial.x = properties.metallicFactor * texture(metallicRoughnessTexture, tc).b; // Metallic
                     outMaterial.y = properties.roughnessFactor * texture(metallicRoughnessTexture, tc).g; // Roughness
+                    // Clear coat layer is approximated by blending its roughness with the base one.
+                    outMaterial.y = mix(outMaterial.y, properties.clearcoatRoughnessFactor, properties.clearcoatFactor);
                     outMaterial.z = texture(aoTexture, tc).r;
                     outMaterial.a = 1.0;
 
@@ -306,22 +353,27 @@
                r#"
                 layout(location = 0) in vec3 vertexPosition;
                 layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec3 vertexNormal;
                 layout(location = 4) in vec4 boneWeights;
                 layout(location = 5) in vec4 boneIndices;
 
                 out vec3 position;
+                out vec3 normal;
                 out vec2 texCoord;
 
                 void main()
                 {
                     vec4 localPosition = vec4(0);
+                    vec3 localNormal = vec3(0);
 
                     vec4 inputPosition = vec4(vertexPosition, 1.0);
+                    vec3 inputNormal = vertexNormal;
 
                     for (int i = 0; i < fyrox_instanceData.blendShapesCount; ++i) {
                         TBlendShapeOffsets offsets = S_FetchBlendShapeOffsets(blendShapesStorage, gl_VertexID, i);
                         float weight = fyrox_instanceData.blendShapesWeights[i / 4][i % 4];
                         inputPosition.xyz += offsets.position * weight;
+                        inputNormal += offsets.normal * weight;
                     }
 
                     if (fyrox_instanceData.useSkeletalAnimation)
@@ -340,12 +392,20 @@
                         localPosition += m1 * inputPosition * boneWeights.y;
                         localPosition += m2 * inputPosition * boneWeights.z;
                         localPosition += m3 * inputPosition * boneWeights.w;
+
+                        localNormal += mat3(m0) * inputNormal * boneWeights.x;
+                        localNormal += mat3(m1) * inputNormal * boneWeights.y;
+                        localNormal += mat3(m2) * inputNormal * boneWeights.z;
+                        localNormal += mat3(m3) * inputNormal * boneWeights.w;
                     }
                     else
                     {
                         localPosition = inputPosition;
+                        localNormal = inputNormal;
                     }
                     gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
+                    position = vec3(fyrox_instanceData.worldMatrix * localPosition);
+                    normal = normalize(mat3(fyrox_instanceData.worldMatrix) * localNormal);
                     texCoord = vertexTexCoord;
                 }
                "#,
@@ -354,11 +414,38 @@
                r#"
                 out vec4 FragColor;
 
+                in vec3 position;
+                in vec3 normal;
                 in vec2 texCoord;
 
+                // KHR_texture_transform: translation * rotation * scale.
+                vec2 TransformTexCoord(vec2 uv)
+                {
+                    float c = cos(properties.texCoordRotation);
+                    float s = sin(properties.texCoordRotation);
+                    mat2 rotation = mat2(c, s, -s, c);
+                    return rotation * (uv * properties.texCoordScale) + properties.texCoordOffset;
+                }
+
                 void main()
                 {
-                    FragColor = properties.diffuseColor * texture(diffuseTexture, texCoord);
+                    vec2 tc = TransformTexCoord(texCoord);
+                    vec4 diffuse = properties.diffuseColor * texture(diffuseTexture, tc);
+
+                    // KHR_materials_transmission: the transmitted fraction of the light comes from the
+                    // objects behind the surface, which is approximated with alpha blending. The light
+                    // reflected by the surface (Fresnel term of a dielectric with F0 = 0.04) is not
+                    // transmitted, so the specular reflections stay visible on transparent surfaces.
+                    float transmission = properties.transmissionFactor * texture(transmissionTexture, tc).r;
+                    float roughness = properties.roughnessFactor * texture(metallicRoughnessTexture, tc).g;
+                    vec3 toCamera = normalize(fyrox_cameraData.position - position);
+                    float cosTheta = clamp(abs(dot(normalize(normal), toCamera)), 0.0, 1.0);
+                    float specular = (0.04 + 0.96 * pow(1.0 - cosTheta, 5.0)) * (1.0 - roughness);
+
+                    vec3 color = diffuse.rgb * (1.0 - transmission) + vec3(specular)
+                        + properties.emissionStrength * texture(emissionTexture, tc).rgb;
+                    float opacity = 1.0 - transmission * (1.0 - specular);
+                    FragColor = vec4(color / max(opacity, 0.001), opacity * diffuse.a);
                 }
                "#,
         ),
@@ -0,0 +1,111 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Import of punctual lights defined by the `KHR_lights_punctual` extension.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        color::Color,
+        pool::Handle,
+    },
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        light::{
+            directional::DirectionalLightBuilder, point::PointLightBuilder, spot::SpotLightBuilder,
+            BaseLightBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+    },
+};
+use gltf::khr_lights_punctual::Kind;
+
+/// Lights without explicit range are infinite in glTF, but every light in the engine must have
+/// a finite radius, so this value is used instead.
+const DEFAULT_LIGHT_RANGE: f32 = 10.0;
+
+/// glTF intensity of point and spot lights is a luminous intensity in candela, and intensity of
+/// directional lights is an illuminance in lux. Engine light intensities are unitless multipliers,
+/// so glTF intensities are divided by this value: a 100 cd light lits a surface at 1 m with 100 lx,
+/// both of them become lights of 1.0 intensity and keep their relative brightness.
+const REFERENCE_PHOTOMETRIC_INTENSITY: f32 = 100.0;
+
+/// Converts glTF photometric intensity (candela or lux, see [`REFERENCE_PHOTOMETRIC_INTENSITY`])
+/// to the intensity of engine lights.
+fn convert_intensity(intensity: f32) -> f32 {
+    intensity.max(0.0) / REFERENCE_PHOTOMETRIC_INTENSITY
+}
+
+/// Creates a light node for the light attached to the given glTF node (if any) and links it to
+/// `parent`. glTF lights shine along -Z axis of their node, while the engine lights shine along
+/// -Y axis, so the light is created as a separate child node with a rotation that maps one to another.
+pub fn import_light(
+    node: &gltf::Node,
+    parent: Handle<Node>,
+    graph: &mut Graph,
+) -> Option<Handle<Node>> {
+    let light = node.light()?;
+
+    let name = light
+        .name()
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("{}_Light", node.name().unwrap_or("")));
+
+    let base_builder = BaseBuilder::new().with_name(name).with_local_transform(
+        TransformBuilder::new()
+            .with_local_rotation(UnitQuaternion::from_axis_angle(
+                &Vector3::x_axis(),
+                90.0f32.to_radians(),
+            ))
+            .build(),
+    );
+
+    // glTF colors are linear.
+    let color = Color::from(Vector3::from(light.color())).linear_to_srgb();
+    let base_light_builder = BaseLightBuilder::new(base_builder)
+        .with_color(color)
+        .with_intensity(convert_intensity(light.intensity()));
+    let range = light.range().unwrap_or(DEFAULT_LIGHT_RANGE);
+
+    let light_node = match light.kind() {
+        Kind::Directional => DirectionalLightBuilder::new(base_light_builder).build_node(),
+        Kind::Point => PointLightBuilder::new(base_light_builder)
+            .with_radius(range)
+            .build_node(),
+        Kind::Spot {
+            inner_cone_angle,
+            outer_cone_angle,
+        } => {
+            // glTF cone angles are measured from the center of the cone, the engine uses
+            // full angles.
+            SpotLightBuilder::new(base_light_builder)
+                .with_hotspot_cone_angle(2.0 * inner_cone_angle)
+                .with_falloff_angle_delta(2.0 * (outer_cone_angle - inner_cone_angle).max(0.0))
+                .with_distance(range)
+                .build_node()
+        }
+    };
+
+    let handle = graph.add_node(light_node);
+    graph.link_nodes(handle, parent);
+    Some(handle)
+}
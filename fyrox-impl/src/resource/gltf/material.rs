@@ -22,7 +22,11 @@ use std::path::{Path, PathBuf};
 
 use crate::{
     asset::{manager::ResourceManager, state::LoadError, untyped::ResourceKind, Resource},
-    core::{algebra::Vector4, color::Color, log::Log},
+    core::{
+        algebra::{Vector2, Vector4},
+        color::Color,
+        log::Log,
+    },
     material::{
         shader::{Shader, ShaderResource},
         Material, MaterialProperty, MaterialResource,
@@ -176,6 +180,11 @@ async fn import_material(
             textures,
             tex.texture().index(),
         )?;
+        // The shader has only one set of texture coordinates transform, so the transform of the
+        // base color texture is used for every texture of the material.
+        if let Some(transform) = tex.texture_transform() {
+            set_texture_transform(&mut result, &transform);
+        }
     }
     if let Some(tex) = mat.normal_texture() {
         set_texture(
@@ -209,12 +218,68 @@ async fn import_material(
         "diffuseColor",
         Vector4::<f32>::from(pbr.base_color_factor()).into(),
     );
-    set_material_vector3(&mut result, "emissionStrength", mat.emissive_factor());
+    let emissive_strength = mat.emissive_strength().unwrap_or(1.0);
+    set_material_vector3(
+        &mut result,
+        "emissionStrength",
+        mat.emissive_factor().map(|c| c * emissive_strength),
+    );
+    if let Some(transmission) = mat.transmission() {
+        set_material_scalar(
+            &mut result,
+            "transmissionFactor",
+            transmission.transmission_factor(),
+        );
+        if let Some(tex) = transmission.transmission_texture() {
+            set_texture(
+                &mut result,
+                "transmissionTexture",
+                textures,
+                tex.texture().index(),
+            )?;
+        }
+    }
+    if let Some(clearcoat) = mat.extension_value("KHR_materials_clearcoat") {
+        let factor = |name: &str| {
+            clearcoat
+                .get(name)
+                .and_then(|v| v.as_f64())
+                .map(|v| v as f32)
+        };
+        set_material_scalar(
+            &mut result,
+            "clearcoatFactor",
+            factor("clearcoatFactor").unwrap_or(0.0),
+        );
+        set_material_scalar(
+            &mut result,
+            "clearcoatRoughnessFactor",
+            factor("clearcoatRoughnessFactor").unwrap_or(0.0),
+        );
+    }
     set_material_scalar(&mut result, "metallicFactor", pbr.metallic_factor());
     set_material_scalar(&mut result, "roughnessFactor", pbr.roughness_factor());
     Ok(Resource::new_ok(ResourceKind::Embedded, result))
 }
 
+/// Returns `true` if the material lets the light through (`KHR_materials_transmission`). Such
+/// materials cannot be rendered by the deferred renderer, so meshes that use them must be
+/// rendered with the forward renderer.
+pub fn is_transmissive(mat: &gltf::Material) -> bool {
+    mat.transmission().is_some_and(|transmission| {
+        transmission.transmission_factor() > 0.0 || transmission.transmission_texture().is_some()
+    })
+}
+
+/// Maps `KHR_texture_transform` to the texture coordinates transform of the glTF shader.
+fn set_texture_transform(material: &mut Material, transform: &gltf::texture::TextureTransform) {
+    set_material_vector2(material, "texCoordScale", transform.scale());
+    set_material_vector2(material, "texCoordOffset", transform.offset());
+    // The shader uses the usual rotation matrix, while glTF defines the rotation with the opposite
+    // sign (u' = u * cos + v * sin, v' = v * cos - u * sin), because its V axis points down.
+    set_material_scalar(material, "texCoordRotation", -transform.rotation());
+}
+
 fn set_material_scalar(material: &mut Material, name: &'static str, value: f32) {
     let value: MaterialProperty = MaterialProperty::Float(value);
     material.set_property(name, value);
@@ -225,6 +290,11 @@ fn set_material_color(material: &mut Material, name: &'static str, color: Color)
     material.set_property(name, value);
 }
 
+fn set_material_vector2(material: &mut Material, name: &'static str, vector: [f32; 2]) {
+    let value: MaterialProperty = MaterialProperty::Vector2(Vector2::from(vector));
+    material.set_property(name, value);
+}
+
 fn set_material_vector3(material: &mut Material, name: &'static str, vector: [f32; 3]) {
     let value: MaterialProperty = MaterialProperty::Vector3(vector.into());
     material.set_property(name, value);
@@ -385,3 +455,59 @@ async fn search_for_path(filename: &str, context: &TextureContext<'_>) -> Option
         MaterialSearchOptions::UsePathDirectly => Some(filename.into()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gltf::Gltf;
+
+    const TEXTURE_TRANSFORM: &str = r#"{
+        "asset": { "version": "2.0" },
+        "extensionsUsed": ["KHR_texture_transform", "KHR_materials_transmission"],
+        "images": [{ "uri": "diffuse.png" }],
+        "textures": [{ "source": 0 }],
+        "materials": [{
+            "pbrMetallicRoughness": {
+                "baseColorTexture": {
+                    "index": 0,
+                    "extensions": {
+                        "KHR_texture_transform": { "rotation": 1.5707964 }
+                    }
+                }
+            },
+            "extensions": {
+                "KHR_materials_transmission": { "transmissionFactor": 1.0 }
+            }
+        }]
+    }"#;
+
+    #[test]
+    fn texture_transform_rotation_sign() {
+        let gltf = Gltf::from_slice(TEXTURE_TRANSFORM.as_bytes()).unwrap();
+        let mat = gltf.materials().next().unwrap();
+        assert!(is_transmissive(&mat));
+        let transform = mat
+            .pbr_metallic_roughness()
+            .base_color_texture()
+            .unwrap()
+            .texture_transform()
+            .unwrap();
+
+        let mut material = Material::from_shader(GLTF_SHADER.clone());
+        set_texture_transform(&mut material, &transform);
+        let Some(MaterialProperty::Float(angle)) = material
+            .property_group_ref("properties")
+            .and_then(|group| group.property_ref("texCoordRotation"))
+        else {
+            panic!("Texture coordinates rotation must be set!");
+        };
+
+        // Same as `TransformTexCoord` of the shader: mat2(c, s, -s, c) * uv.
+        let (s, c) = angle.sin_cos();
+        let uv = Vector2::new(1.0f32, 0.0);
+        let rotated = Vector2::new(c * uv.x - s * uv.y, s * uv.x + c * uv.y);
+
+        // glTF: u' = u * cos + v * sin, v' = v * cos - u * sin.
+        assert!((rotated - Vector2::new(0.0, -1.0)).norm() < 1.0e-5);
+    }
+}
@@ -39,13 +39,15 @@ use crate::graph::BaseSceneGraph;
 use crate::graph::NodeMapping;
 use crate::gui::core::io::FileLoadError;
 use crate::material::MaterialResource;
-use crate::resource::model::{MaterialSearchOptions, Model, ModelImportOptions};
+use crate::resource::model::{
+    apply_import_options, MaterialSearchOptions, Model, ModelImportOptions,
+};
 use crate::resource::texture::{TextureError, TextureResource};
 use crate::scene::animation::{AnimationContainer, AnimationPlayerBuilder};
 use crate::scene::base::BaseBuilder;
 use crate::scene::graph::Graph;
 use crate::scene::mesh::surface::{BlendShape, Surface, SurfaceResource};
-use crate::scene::mesh::{Mesh, MeshBuilder};
+use crate::scene::mesh::{Mesh, MeshBuilder, RenderPath};
 use crate::scene::node::Node;
 use crate::scene::pivot::PivotBuilder;
 use crate::scene::transform::TransformBuilder;
@@ -53,8 +55,9 @@ use crate::scene::Scene;
 
 mod animation;
 mod iter;
+mod light;
 mod material;
-mod node_names;
+pub(crate) mod node_names;
 mod simplify;
 mod surface;
 mod uri;
@@ -321,7 +324,7 @@ async fn load(
         io,
         resource_manager,
         model_path: path.clone(),
-        search_options: options.material_search_options,
+        search_options: options.material_search_options.clone(),
     };
     let root_name = path
         .file_name()
@@ -331,6 +334,7 @@ async fn load(
     scene.graph[root].set_name(root_name.clone());
     import_from_path(&mut scene.graph, &context).await?;
     node_names::resolve_name_conflicts(context.model_path.as_path(), &mut scene.graph);
+    apply_import_options(&mut scene, &options);
     Ok(Model::new(NodeMapping::UseNames, scene))
 }
 
@@ -363,6 +367,7 @@ async fn import_from_slice(slice: &[u8], graph: &mut Graph, context: &ImportCont
     )?);
     imports.families = Some(import_nodes(&doc, graph, &imports)?);
     link_child_nodes(&doc, graph, &imports)?;
+    import_lights(&doc, graph, &imports)?;
     let node_handles: Vec<Handle<Node>> = imports
         .families
         .as_ref()
@@ -699,6 +704,12 @@ fn import_node(
         .with_inv_bind_pose_transform(inv_bind_pose);
     if let Some(mesh) = node.mesh() {
         let mut mesh_builder = MeshBuilder::new(base_builder);
+        if mesh
+            .primitives()
+            .any(|primitive| material::is_transmissive(&primitive.material()))
+        {
+            mesh_builder = mesh_builder.with_render_path(RenderPath::Forward);
+        }
         let mesh = meshes
             .get(mesh.index())
             .ok_or(GltfLoadError::InvalidIndex)?;
@@ -726,6 +737,17 @@ fn link_child_nodes(doc: &Document, graph: &mut Graph, imports: &ImportResults)
     Ok(())
 }
 
+fn import_lights(doc: &Document, graph: &mut Graph, imports: &ImportResults) -> Result<()> {
+    let families: &[NodeFamily] = imports.families.as_ref().unwrap().as_slice();
+    for node in doc.nodes() {
+        let family = families
+            .get(node.index())
+            .ok_or(GltfLoadError::InvalidIndex)?;
+        light::import_light(&node, family.main_node, graph);
+    }
+    Ok(())
+}
+
 fn import_skins(doc: &gltf::Document, imports: &ImportResults) -> Result<Vec<SkinData>> {
     let mut result: Vec<SkinData> = Vec::with_capacity(doc.skins().len());
     for skin in doc.skins() {
@@ -0,0 +1,639 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Localization allows a game to show its text in multiple languages. Translated strings are stored
+//! in string tables ([`StringTable`]) per language, which are combined together by [`Localization`].
+//! See [`Localization`] docs for more info.
+
+use crate::{
+    asset::{io::ResourceIo, Resource, ResourceData},
+    core::{io::FileLoadError, reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+    fxhash::FxHashMap,
+};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Write},
+    path::Path,
+    string::FromUtf8Error,
+};
+
+pub mod loader;
+pub mod text;
+
+/// An error that may occur during string table or localization loading.
+#[derive(Debug)]
+pub enum LocalizationError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+    /// A string table is not a valid UTF-8 text.
+    Utf8(FromUtf8Error),
+    /// A string table has a malformed line.
+    Syntax {
+        /// Number of the line (starting from one).
+        line: usize,
+        /// Description of the problem.
+        reason: String,
+    },
+    /// An error that may occur due to version incompatibilities.
+    Visit(VisitError),
+}
+
+impl Display for LocalizationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LocalizationError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            LocalizationError::Utf8(v) => {
+                write!(f, "String table is not a valid UTF-8 text. Reason: {v}")
+            }
+            LocalizationError::Syntax { line, reason } => {
+                write!(f, "Syntax error at line {line} of a string table: {reason}")
+            }
+            LocalizationError::Visit(v) => {
+                write!(
+                    f,
+                    "An error that may occur due to version incompatibilities. {v:?}"
+                )
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for LocalizationError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<FromUtf8Error> for LocalizationError {
+    fn from(e: FromUtf8Error) -> Self {
+        Self::Utf8(e)
+    }
+}
+
+impl From<VisitError> for LocalizationError {
+    fn from(e: VisitError) -> Self {
+        Self::Visit(e)
+    }
+}
+
+/// String table is a set of translated strings of a single language, where every string is
+/// identified by a key. String tables are stored in simple text files (`*.strings`) with one
+/// `key = value` pair per line, for example:
+///
+/// ```text
+/// # Main menu
+/// menu.start = Start Game
+/// menu.greeting = Hello, {name}!
+/// tutorial.hint = Press W to move forward.\nPress Space to jump.
+/// ```
+///
+/// Lines starting with `#` are comments. Keys cannot contain whitespace, values are trimmed and
+/// may contain `\n`, `\t`, `\\` and `\#` escape sequences. Values may have named placeholders in
+/// curly braces, see [`format_string`] for more info.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "6f1a3c52-9b8e-4f0d-a7c4-3e52d1b9f086")]
+pub struct StringTable {
+    strings: FxHashMap<String, String>,
+}
+
+fn unescape(value: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('\\') => result.push('\\'),
+                Some('#') => result.push('#'),
+                Some(other) => return Err(format!("unknown escape sequence \\{other}")),
+                None => return Err("unterminated escape sequence".to_string()),
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    Ok(result)
+}
+
+fn escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            '\\' => result.push_str("\\\\"),
+            '#' => result.push_str("\\#"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+impl StringTable {
+    /// Parses a string table from its text representation. See [`StringTable`] docs for the
+    /// format description.
+    pub fn parse(text: &str) -> Result<Self, LocalizationError> {
+        let mut strings = FxHashMap::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let syntax_error = |reason: String| LocalizationError::Syntax {
+                line: index + 1,
+                reason,
+            };
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| syntax_error("expected `key = value` pair".to_string()))?;
+            let key = key.trim();
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                return Err(syntax_error(format!("invalid key `{key}`")));
+            }
+            let value = unescape(value.trim()).map_err(syntax_error)?;
+            if strings.insert(key.to_string(), value).is_some() {
+                return Err(syntax_error(format!("duplicate key `{key}`")));
+            }
+        }
+        Ok(Self { strings })
+    }
+
+    /// Loads a string table from the given file.
+    pub async fn from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, LocalizationError> {
+        let bytes = io.load_file(path).await?;
+        Self::parse(&String::from_utf8(bytes)?)
+    }
+
+    /// Converts the table to its text representation. Keys are sorted, so the output is stable
+    /// and could be stored in a version control system.
+    pub fn to_text(&self) -> String {
+        let mut keys = self.strings.keys().collect::<Vec<_>>();
+        keys.sort();
+        let mut text = String::new();
+        for key in keys {
+            let _ = writeln!(text, "{key} = {}", escape(&self.strings[key]));
+        }
+        text
+    }
+
+    /// Returns a string with the given key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).map(|s| s.as_str())
+    }
+
+    /// Sets a new string for the given key and returns the previous one (if any).
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        self.strings.insert(key.into(), value.into())
+    }
+
+    /// Removes a string with the given key.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.strings.remove(key)
+    }
+
+    /// Returns an iterator over all `(key, value)` pairs of the table.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.strings.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Returns total amount of strings in the table.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns `true` if the table has no strings.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+impl ResourceData for StringTable {
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, self.to_text())?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+/// Type alias for string table resources.
+pub type StringTableResource = Resource<StringTable>;
+
+/// A value, that could be substituted in a localized string. See [`format_string`] for more info.
+pub type FormatArgument<'a> = (&'a str, &'a dyn Display);
+
+/// Replaces named placeholders (`{name}`) in the given template with the values of the respective
+/// arguments. Placeholders without a matching argument are left as is, `{{` and `}}` could be used
+/// to output literal braces.
+///
+/// ```rust
+/// # use fyrox_impl::resource::localization::format_string;
+/// assert_eq!(
+///     format_string(
+///         "Hello, {name}! You have {count} new messages.",
+///         &[("name", &"Bob"), ("count", &3)]
+///     ),
+///     "Hello, Bob! You have 3 new messages."
+/// );
+/// ```
+pub fn format_string(template: &str, arguments: &[FormatArgument]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(position) = rest.find(['{', '}']) {
+        result.push_str(&rest[..position]);
+        rest = &rest[position..];
+
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            result.push_str(&rest[..1]);
+            rest = &rest[2..];
+        } else if let Some(end) = rest.strip_prefix('{').and_then(|s| s.find('}')) {
+            let name = &rest[1..end + 1];
+            match arguments.iter().find(|(arg_name, _)| *arg_name == name) {
+                Some((_, value)) => {
+                    let _ = write!(result, "{value}");
+                }
+                None => result.push_str(&rest[..end + 2]),
+            }
+            rest = &rest[end + 2..];
+        } else {
+            result.push_str(&rest[..1]);
+            rest = &rest[1..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// A set of string tables of a single language.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct LanguageTables {
+    /// Name of the language, for example `en` or `pt-BR`.
+    pub language: String,
+    /// String tables of the language. Tables are searched in order, so the first table that has a
+    /// key wins.
+    pub tables: Vec<StringTableResource>,
+}
+
+/// Localization is a project-wide storage of string tables of all supported languages. It has the
+/// current language, which could be changed at runtime, and a fallback chain, that defines which
+/// languages are used when the current language does not have a string.
+///
+/// ## Lookup
+///
+/// A string is searched in the following languages (in order):
+///
+/// 1) The current language (for example, `pt-BR`).
+/// 2) Base languages of the current language (`pt` for `pt-BR`).
+/// 3) The fallback languages in the order they're specified.
+///
+/// If no language has the string, the key itself is returned, so missing translations are easy to
+/// spot.
+///
+/// ## Usage
+///
+/// The engine has a global localization instance, that is accessible from plugins and scripts
+/// via their contexts (`context.localization`). [`LocalizationResourceExtension`] provides
+/// shortcuts for the most common operations:
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     asset::manager::ResourceManager,
+/// #     resource::localization::{
+/// #         LocalizationResource, LocalizationResourceExtension, StringTable,
+/// #     },
+/// # };
+/// async fn setup(localization: &LocalizationResource, resource_manager: &ResourceManager) {
+///     let english = resource_manager
+///         .request::<StringTable>("data/strings/en.strings")
+///         .await
+///         .unwrap();
+///     let german = resource_manager
+///         .request::<StringTable>("data/strings/de.strings")
+///         .await
+///         .unwrap();
+///
+///     let mut state = localization.state();
+///     let localization = state.data().unwrap();
+///     localization.add_table("en", english);
+///     localization.add_table("de", german);
+///     localization.set_fallback_languages(vec!["en".to_string()]);
+///     localization.set_language("de");
+/// }
+///
+/// fn greeting(localization: &LocalizationResource, name: &str) -> String {
+///     localization.format("menu.greeting", &[("name", &name)])
+/// }
+/// ```
+///
+/// String tables are regular resources, so they're hot-reloaded when their files change. Use
+/// [`text::LocalizedTextBindings`] to keep text of UI widgets in sync with the current language and
+/// [`text::LocalizedNodeTextBindings`] to do the same for 3D text and other scene nodes.
+#[derive(Clone, Debug, Default, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "0b7e8d6a-2f4c-4c1e-9d35-8a6f0e4b2c17")]
+pub struct Localization {
+    language: String,
+    fallback_languages: Vec<String>,
+    languages: Vec<LanguageTables>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    revision: u64,
+}
+
+impl Localization {
+    /// Creates new localization with the given current language.
+    pub fn new(language: impl Into<String>) -> Self {
+        Self {
+            language: language.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Loads localization from the given file.
+    pub async fn from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, LocalizationError> {
+        let bytes = io.load_file(path).await?;
+        let mut visitor = Visitor::load_from_memory(&bytes)?;
+        let mut localization = Self::default();
+        localization.visit("Localization", &mut visitor)?;
+        Ok(localization)
+    }
+
+    /// Returns the current language.
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Changes the current language. All the strings fetched after this call will be taken from
+    /// the new language.
+    pub fn set_language(&mut self, language: impl Into<String>) {
+        let language = language.into();
+        if self.language != language {
+            self.language = language;
+            self.mark_changed();
+        }
+    }
+
+    /// Returns the fallback languages.
+    pub fn fallback_languages(&self) -> &[String] {
+        &self.fallback_languages
+    }
+
+    /// Sets new fallback languages, that will be used (in order) when the current language does
+    /// not have a string.
+    pub fn set_fallback_languages(&mut self, languages: Vec<String>) {
+        self.fallback_languages = languages;
+        self.mark_changed();
+    }
+
+    /// Adds a new string table to the given language. The table is added to the end of the list of
+    /// tables of the language, so it has the lowest priority.
+    pub fn add_table(&mut self, language: impl Into<String>, table: StringTableResource) {
+        let language = language.into();
+        match self.languages.iter_mut().find(|l| l.language == language) {
+            Some(entry) => entry.tables.push(table),
+            None => self.languages.push(LanguageTables {
+                language,
+                tables: vec![table],
+            }),
+        }
+        self.mark_changed();
+    }
+
+    /// Removes the given language with all its tables.
+    pub fn remove_language(&mut self, language: &str) -> Option<LanguageTables> {
+        let index = self.languages.iter().position(|l| l.language == language)?;
+        self.mark_changed();
+        Some(self.languages.remove(index))
+    }
+
+    /// Returns all the languages with their tables.
+    pub fn languages(&self) -> &[LanguageTables] {
+        &self.languages
+    }
+
+    /// Returns the string tables of the given language.
+    pub fn tables(&self, language: &str) -> Option<&[StringTableResource]> {
+        self.languages
+            .iter()
+            .find(|l| l.language == language)
+            .map(|l| l.tables.as_slice())
+    }
+
+    /// Returns a number, that is changed every time when the localization is modified (the
+    /// language, the fallback chain or the tables). It could be used to check whether the localized
+    /// text should be updated.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Forces every user of the localization to refresh its localized text. Useful when a string
+    /// table was modified directly.
+    pub fn mark_changed(&mut self) {
+        self.revision = self.revision.wrapping_add(1);
+    }
+
+    /// Returns the languages that will be searched for a string, in order. See [`Localization`]
+    /// docs for more info.
+    pub fn lookup_chain(&self) -> Vec<&str> {
+        let mut chain = Vec::new();
+        let mut language = self.language.as_str();
+        loop {
+            chain.push(language);
+            match language.rfind(['-', '_']) {
+                Some(index) => language = &language[..index],
+                None => break,
+            }
+        }
+        for fallback in self.fallback_languages.iter() {
+            if !chain.contains(&fallback.as_str()) {
+                chain.push(fallback);
+            }
+        }
+        chain
+    }
+
+    /// Tries to find a string with the given key, walking the lookup chain. Tables that are still
+    /// loading (or failed to load) are ignored.
+    pub fn try_get(&self, key: &str) -> Option<String> {
+        for language in self.lookup_chain() {
+            for table in self.tables(language).into_iter().flatten() {
+                if let Some(string) = table.state().data_ref().and_then(|table| table.get(key)) {
+                    return Some(string.to_string());
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns a string with the given key, or the key itself if there's no such string.
+    pub fn get(&self, key: &str) -> String {
+        self.try_get(key).unwrap_or_else(|| key.to_string())
+    }
+
+    /// Returns a string with the given key with the placeholders replaced by the given arguments.
+    /// See [`format_string`] for more info.
+    pub fn format(&self, key: &str, arguments: &[FormatArgument]) -> String {
+        format_string(&self.get(key), arguments)
+    }
+}
+
+impl ResourceData for Localization {
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut visitor = Visitor::new();
+        self.visit("Localization", &mut visitor)?;
+        visitor.save_binary(path)?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+/// Type alias for localization resources.
+pub type LocalizationResource = Resource<Localization>;
+
+/// Shortcuts for localization resources. Every method returns the key itself, if the
+/// localization is not loaded.
+pub trait LocalizationResourceExtension {
+    /// Returns the current language.
+    fn language(&self) -> String;
+
+    /// Changes the current language. See [`Localization::set_language`].
+    fn set_language(&self, language: &str);
+
+    /// Returns a string with the given key. See [`Localization::get`].
+    fn get(&self, key: &str) -> String;
+
+    /// Returns a formatted string with the given key. See [`Localization::format`].
+    fn format(&self, key: &str, arguments: &[FormatArgument]) -> String;
+
+    /// Returns the revision of the localization. See [`Localization::revision`].
+    fn revision(&self) -> u64;
+}
+
+impl LocalizationResourceExtension for LocalizationResource {
+    fn language(&self) -> String {
+        self.state()
+            .data_ref()
+            .map(|l| l.language.clone())
+            .unwrap_or_default()
+    }
+
+    fn set_language(&self, language: &str) {
+        if let Some(localization) = self.state().data() {
+            localization.set_language(language);
+        }
+    }
+
+    fn get(&self, key: &str) -> String {
+        self.state()
+            .data_ref()
+            .map(|l| l.get(key))
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    fn format(&self, key: &str, arguments: &[FormatArgument]) -> String {
+        let template = self.get(key);
+        format_string(&template, arguments)
+    }
+
+    fn revision(&self) -> u64 {
+        self.state()
+            .data_ref()
+            .map(|l| l.revision)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::asset::untyped::ResourceKind;
+
+    fn table(text: &str) -> StringTableResource {
+        StringTableResource::new_ok(ResourceKind::Embedded, StringTable::parse(text).unwrap())
+    }
+
+    #[test]
+    fn test_string_table_parse() {
+        let table =
+            StringTable::parse("# Comment\n\nmenu.start = Start Game\nhint = Line\\nNext \\# 1\n")
+                .unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.get("menu.start"), Some("Start Game"));
+        assert_eq!(table.get("hint"), Some("Line\nNext # 1"));
+        assert_eq!(StringTable::parse(&table.to_text()).unwrap(), table);
+
+        assert!(matches!(
+            StringTable::parse("a = 1\nno value"),
+            Err(LocalizationError::Syntax { line: 2, .. })
+        ));
+        assert!(StringTable::parse("a = 1\na = 2").is_err());
+        assert!(StringTable::parse("bad key = 1").is_err());
+    }
+
+    #[test]
+    fn test_format_string() {
+        assert_eq!(
+            format_string("{a} + {b} = {c}", &[("a", &1), ("b", &2), ("c", &3)]),
+            "1 + 2 = 3"
+        );
+        assert_eq!(
+            format_string("{{a}} {missing}", &[("a", &1)]),
+            "{a} {missing}"
+        );
+        assert_eq!(format_string("unclosed {a", &[("a", &1)]), "unclosed {a");
+    }
+
+    #[test]
+    fn test_localization_fallback() {
+        let mut localization = Localization::new("pt-BR");
+        localization.add_table("en", table("a = A\nb = B\nc = C"));
+        localization.add_table("pt", table("a = A-pt\nb = B-pt"));
+        localization.add_table("pt-BR", table("a = A-br"));
+        localization.set_fallback_languages(vec!["en".to_string()]);
+
+        assert_eq!(localization.lookup_chain(), ["pt-BR", "pt", "en"]);
+        assert_eq!(localization.get("a"), "A-br");
+        assert_eq!(localization.get("b"), "B-pt");
+        assert_eq!(localization.get("c"), "C");
+        assert_eq!(localization.get("d"), "d");
+
+        let revision = localization.revision();
+        localization.set_language("en");
+        assert_ne!(localization.revision(), revision);
+        assert_eq!(localization.get("a"), "A");
+    }
+}
@@ -0,0 +1,410 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Integration of localization with text widgets and scene nodes. See [`LocalizedTextBindings`] and
+//! [`LocalizedNodeTextBindings`] docs for more info.
+
+use crate::{
+    core::{log::Log, pool::Handle, reflect::Reflect},
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        message::MessageDirection,
+        text::{Text, TextMessage},
+        UiNode, UserInterface,
+    },
+    resource::localization::{format_string, LocalizationResource, LocalizationResourceExtension},
+    scene::{graph::Graph, node::Node},
+};
+use std::fmt::Display;
+
+/// A prefix of text, that marks the rest of the text as a localization key. For example, a text
+/// widget with `@menu.start` text will be bound to `menu.start` key by
+/// [`LocalizedTextBindings::bind_tagged`].
+pub const KEY_PREFIX: char = '@';
+
+/// Default name of a string property of scene nodes, that is used by
+/// [`LocalizedNodeTextBindings`] (for example, text of 3D text nodes).
+pub const TEXT_PROPERTY: &str = "text";
+
+struct Binding<T> {
+    target: Handle<T>,
+    key: String,
+    arguments: Vec<(String, String)>,
+}
+
+impl<T> Binding<T> {
+    fn text(&self, localization: &LocalizationResource) -> String {
+        let arguments = self
+            .arguments
+            .iter()
+            .map(|(name, value)| (name.as_str(), value as &dyn Display))
+            .collect::<Vec<_>>();
+        format_string(&localization.get(&self.key), &arguments)
+    }
+}
+
+fn bind<T>(
+    bindings: &mut Vec<Binding<T>>,
+    target: Handle<T>,
+    key: String,
+    arguments: Vec<(String, String)>,
+) {
+    bindings.retain(|b| b.target != target);
+    bindings.push(Binding {
+        target,
+        key,
+        arguments,
+    });
+}
+
+fn set_arguments<T>(
+    bindings: &mut [Binding<T>],
+    target: Handle<T>,
+    arguments: Vec<(String, String)>,
+) -> bool {
+    if let Some(binding) = bindings.iter_mut().find(|b| b.target == target) {
+        if binding.arguments != arguments {
+            binding.arguments = arguments;
+            return true;
+        }
+    }
+    false
+}
+
+fn key<T>(bindings: &[Binding<T>], target: Handle<T>) -> Option<&str> {
+    bindings
+        .iter()
+        .find(|b| b.target == target)
+        .map(|b| b.key.as_str())
+}
+
+/// A set of text widgets, which text is defined by localization keys. The bindings keep text of the
+/// widgets in sync with the current language of a localization: every time when the language (or
+/// any other part of the localization) changes, the widgets receive new text via
+/// [`TextMessage::Text`] messages. It means that any widget that handles this message (for example,
+/// `Text` or `TextBox`) could be bound.
+///
+/// UI scenes made in the editor could mark their text as localizable by using `@key` text (see
+/// [`KEY_PREFIX`]), such widgets could be bound all at once using [`Self::bind_tagged`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     gui::{UiNode, UserInterface},
+/// #     resource::localization::{text::LocalizedTextBindings, LocalizationResource},
+/// # };
+/// struct Menu {
+///     bindings: LocalizedTextBindings,
+/// }
+///
+/// impl Menu {
+///     fn new(ui: &UserInterface, score: Handle<UiNode>) -> Self {
+///         let mut bindings = LocalizedTextBindings::default();
+///         bindings.bind_tagged(ui);
+///         bindings.bind_with_arguments(score, "menu.score", vec![("score".into(), "0".into())]);
+///         Self { bindings }
+///     }
+///
+///     // Should be called every frame, for example in `Plugin::update`.
+///     fn update(&mut self, localization: &LocalizationResource, ui: &UserInterface) {
+///         self.bindings.update(localization, ui);
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct LocalizedTextBindings {
+    bindings: Vec<Binding<UiNode>>,
+    revision: Option<u64>,
+}
+
+impl LocalizedTextBindings {
+    /// Binds the given widget to the given localization key. If the widget is already bound, its
+    /// key will be replaced.
+    pub fn bind(&mut self, widget: Handle<UiNode>, key: impl Into<String>) {
+        self.bind_with_arguments(widget, key, Default::default())
+    }
+
+    /// Binds the given widget to the given localization key, which string has placeholders. See
+    /// [`format_string`] for more info.
+    pub fn bind_with_arguments(
+        &mut self,
+        widget: Handle<UiNode>,
+        key: impl Into<String>,
+        arguments: Vec<(String, String)>,
+    ) {
+        bind(&mut self.bindings, widget, key.into(), arguments);
+        self.revision = None;
+    }
+
+    /// Sets new arguments of the given widget. Useful for strings with dynamic values, like scores.
+    pub fn set_arguments(&mut self, widget: Handle<UiNode>, arguments: Vec<(String, String)>) {
+        if set_arguments(&mut self.bindings, widget, arguments) {
+            self.revision = None;
+        }
+    }
+
+    /// Removes the binding of the given widget. The widget keeps its current text.
+    pub fn unbind(&mut self, widget: Handle<UiNode>) {
+        self.bindings.retain(|b| b.target != widget);
+    }
+
+    /// Removes every binding.
+    pub fn clear(&mut self) {
+        self.bindings.clear();
+    }
+
+    /// Returns the localization key of the given widget.
+    pub fn key(&self, widget: Handle<UiNode>) -> Option<&str> {
+        key(&self.bindings, widget)
+    }
+
+    /// Binds every [`Text`] widget of the given user interface, which text starts with
+    /// [`KEY_PREFIX`]. Returns the amount of new bindings.
+    pub fn bind_tagged(&mut self, ui: &UserInterface) -> usize {
+        let mut count = 0;
+        for (handle, node) in ui.nodes().pair_iter() {
+            let Some(text) = node.query_component::<Text>() else {
+                continue;
+            };
+            if let Some(key) = text.text().strip_prefix(KEY_PREFIX) {
+                self.bind(handle, key);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Updates text of the bound widgets, if the localization has changed since the last call.
+    /// Bindings of deleted widgets are removed.
+    pub fn update(&mut self, localization: &LocalizationResource, ui: &UserInterface) {
+        let revision = localization.revision();
+        if self.revision == Some(revision) {
+            return;
+        }
+        self.revision = Some(revision);
+
+        self.bindings.retain(|b| ui.try_get(b.target).is_some());
+        for binding in self.bindings.iter() {
+            ui.send_message(TextMessage::text(
+                binding.target,
+                MessageDirection::ToWidget,
+                binding.text(localization),
+            ));
+        }
+    }
+}
+
+/// A set of scene nodes, which text is defined by localization keys. It is the same as
+/// [`LocalizedTextBindings`], but for 3D text and any other scene node, that has a string property
+/// with its text. The property is set via reflection, so the bindings work with any node (or
+/// plugin node type), that has such property. The name of the property is [`TEXT_PROPERTY`] by
+/// default, use [`Self::with_property`] to change it.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     resource::localization::{text::LocalizedNodeTextBindings, LocalizationResource},
+/// #     scene::{node::Node, Scene},
+/// # };
+/// fn bind(scene: &Scene, sign: Handle<Node>) -> LocalizedNodeTextBindings {
+///     let mut bindings = LocalizedNodeTextBindings::default();
+///     bindings.bind_tagged(&scene.graph);
+///     bindings.bind(sign, "level.sign");
+///     bindings
+/// }
+///
+/// // Should be called every frame, for example in `Plugin::update`.
+/// fn update(
+///     bindings: &mut LocalizedNodeTextBindings,
+///     localization: &LocalizationResource,
+///     scene: &mut Scene,
+/// ) {
+///     bindings.update(localization, &mut scene.graph);
+/// }
+/// ```
+pub struct LocalizedNodeTextBindings {
+    bindings: Vec<Binding<Node>>,
+    property: String,
+    revision: Option<u64>,
+}
+
+impl Default for LocalizedNodeTextBindings {
+    fn default() -> Self {
+        Self {
+            bindings: Default::default(),
+            property: TEXT_PROPERTY.to_string(),
+            revision: None,
+        }
+    }
+}
+
+impl LocalizedNodeTextBindings {
+    /// Sets the name (or a path, for example `script.text`) of a string property, that will receive
+    /// localized text.
+    pub fn with_property(mut self, property: impl Into<String>) -> Self {
+        self.property = property.into();
+        self.revision = None;
+        self
+    }
+
+    /// Returns the name of a string property, that receives localized text.
+    pub fn property(&self) -> &str {
+        &self.property
+    }
+
+    /// Binds the given node to the given localization key. If the node is already bound, its key
+    /// will be replaced.
+    pub fn bind(&mut self, node: Handle<Node>, key: impl Into<String>) {
+        self.bind_with_arguments(node, key, Default::default())
+    }
+
+    /// Binds the given node to the given localization key, which string has placeholders. See
+    /// [`format_string`] for more info.
+    pub fn bind_with_arguments(
+        &mut self,
+        node: Handle<Node>,
+        key: impl Into<String>,
+        arguments: Vec<(String, String)>,
+    ) {
+        bind(&mut self.bindings, node, key.into(), arguments);
+        self.revision = None;
+    }
+
+    /// Sets new arguments of the given node.
+    pub fn set_arguments(&mut self, node: Handle<Node>, arguments: Vec<(String, String)>) {
+        if set_arguments(&mut self.bindings, node, arguments) {
+            self.revision = None;
+        }
+    }
+
+    /// Removes the binding of the given node. The node keeps its current text.
+    pub fn unbind(&mut self, node: Handle<Node>) {
+        self.bindings.retain(|b| b.target != node);
+    }
+
+    /// Removes every binding.
+    pub fn clear(&mut self) {
+        self.bindings.clear();
+    }
+
+    /// Returns the localization key of the given node.
+    pub fn key(&self, node: Handle<Node>) -> Option<&str> {
+        key(&self.bindings, node)
+    }
+
+    /// Binds every node of the given graph, which text property starts with [`KEY_PREFIX`].
+    /// Returns the amount of new bindings.
+    pub fn bind_tagged(&mut self, graph: &Graph) -> usize {
+        let mut tagged = Vec::new();
+        for (handle, node) in graph.pair_iter() {
+            (node as &dyn Reflect).resolve_path(&self.property, &mut |result| {
+                if let Ok(property) = result {
+                    property.downcast_ref::<String>(&mut |text| {
+                        if let Some(key) = text.and_then(|text| text.strip_prefix(KEY_PREFIX)) {
+                            tagged.push((handle, key.to_string()));
+                        }
+                    })
+                }
+            });
+        }
+        let count = tagged.len();
+        for (handle, key) in tagged {
+            self.bind(handle, key);
+        }
+        count
+    }
+
+    /// Updates text of the bound nodes, if the localization has changed since the last call.
+    /// Bindings of deleted nodes are removed.
+    pub fn update(&mut self, localization: &LocalizationResource, graph: &mut Graph) {
+        let revision = localization.revision();
+        if self.revision == Some(revision) {
+            return;
+        }
+        self.revision = Some(revision);
+
+        self.bindings.retain(|b| graph.try_get(b.target).is_some());
+        for binding in self.bindings.iter() {
+            let text = binding.text(localization);
+            let node = &mut graph[binding.target];
+            (node as &mut dyn Reflect).set_field_by_path(
+                &self.property,
+                Box::new(text),
+                &mut |result| {
+                    if result.is_err() {
+                        Log::warn(format!(
+                            "Unable to set localized text of {}: the node has no {} string \
+                            property!",
+                            binding.target, self.property
+                        ))
+                    }
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        resource::localization::{
+            text::LocalizedNodeTextBindings, Localization, LocalizationResource, StringTable,
+            StringTableResource,
+        },
+        scene::{base::BaseBuilder, pivot::PivotBuilder, Scene},
+    };
+
+    #[test]
+    fn test_node_text_bindings() {
+        let mut localization = Localization::new("en");
+        localization.add_table(
+            "en",
+            StringTableResource::new_ok(
+                ResourceKind::Embedded,
+                StringTable::parse("sign = Exit\nscore = Score: {score}").unwrap(),
+            ),
+        );
+        let localization = LocalizationResource::new_ok(ResourceKind::Embedded, localization);
+
+        let mut scene = Scene::new();
+        let sign = PivotBuilder::new(BaseBuilder::new().with_tag("@sign".to_string()))
+            .build(&mut scene.graph);
+        let score = PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+
+        // Pivots have no text, so the tag is used instead.
+        let mut bindings = LocalizedNodeTextBindings::default().with_property("tag");
+        assert_eq!(bindings.bind_tagged(&scene.graph), 1);
+        assert_eq!(bindings.key(sign), Some("sign"));
+        bindings.bind_with_arguments(score, "score", vec![("score".into(), "1".into())]);
+
+        bindings.update(&localization, &mut scene.graph);
+        assert_eq!(scene.graph[sign].tag(), "Exit");
+        assert_eq!(scene.graph[score].tag(), "Score: 1");
+
+        bindings.set_arguments(score, vec![("score".into(), "2".into())]);
+        bindings.update(&localization, &mut scene.graph);
+        assert_eq!(scene.graph[score].tag(), "Score: 2");
+    }
+}
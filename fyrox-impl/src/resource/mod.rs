@@ -22,8 +22,12 @@
 
 #![warn(missing_docs)]
 
+pub mod atlas;
 pub mod curve;
 pub mod fbx;
 pub mod gltf;
+pub mod localization;
 pub mod model;
 pub mod texture;
+pub mod usd;
+pub mod video;
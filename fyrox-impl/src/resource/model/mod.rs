@@ -59,7 +59,12 @@ use crate::{
     graph::{BaseSceneGraph, NodeHandleMap, NodeMapping, PrefabData, SceneGraph, SceneGraphNode},
     resource::fbx::{self, error::FbxError},
     scene::{
-        animation::Animation, base::SceneNodeId, graph::Graph, node::Node, transform::Transform,
+        animation::{Animation, AnimationPlayer},
+        base::{BaseBuilder, SceneNodeId},
+        graph::Graph,
+        node::Node,
+        pivot::PivotBuilder,
+        transform::{Transform, TransformBuilder},
         Scene, SceneLoader,
     },
 };
@@ -707,20 +712,166 @@ impl MaterialSearchOptions {
 ///
 /// ```text
 /// (
-///     material_search_options: RecursiveUp
+///     material_search_options: RecursiveUp,
+///     scale: 0.01,
+///     up_axis: Z,
+///     animation_trim: Some((start: 0.5, end: 2.0)),
 /// )
 /// ```
 ///
-/// Check documentation of the field of the structure for more info about each parameter.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, Reflect, Eq)]
+/// Check documentation of the field of the structure for more info about each parameter. Scale, up
+/// axis and animation trimming are ignored for native scenes (`rgs`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
 pub struct ModelImportOptions {
     /// See [`MaterialSearchOptions`] docs for more info.
     #[serde(default)]
     pub material_search_options: MaterialSearchOptions,
+
+    /// Uniform scale that will be applied to the model. It could be used to convert the units of
+    /// the source model to the units of the engine (meters).
+    #[serde(default = "default_scale")]
+    #[reflect(min_value = 0.0001)]
+    pub scale: f32,
+
+    /// Up axis of the source model. See [`ModelUpAxis`] docs for more info.
+    #[serde(default)]
+    pub up_axis: ModelUpAxis,
+
+    /// Optional time range (in seconds) that will be applied to every animation of the model. See
+    /// [`AnimationTrim`] docs for more info.
+    #[serde(default)]
+    pub animation_trim: Option<AnimationTrim>,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+impl Default for ModelImportOptions {
+    fn default() -> Self {
+        Self {
+            material_search_options: Default::default(),
+            scale: default_scale(),
+            up_axis: Default::default(),
+            animation_trim: None,
+        }
+    }
 }
 
 impl ImportOptions for ModelImportOptions {}
 
+/// Up axis of a source model. The engine uses Y axis as the up axis, the models that use some other
+/// axis will be rotated to match the engine's coordinate system.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Visit,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum ModelUpAxis {
+    /// X axis is the up axis.
+    X,
+    /// Y axis is the up axis. This is **default** option, no rotation is applied.
+    #[default]
+    Y,
+    /// Z axis is the up axis. This is typical for models exported from CAD software and some
+    /// modelling software.
+    Z,
+}
+
+uuid_provider!(ModelUpAxis = "0a6b7cf3-1b4e-4e6c-9c4a-2b1f1ea3c7d5");
+
+impl ModelUpAxis {
+    /// Returns a rotation that converts the coordinate system with this up axis to the engine's
+    /// coordinate system.
+    pub fn rotation(self) -> UnitQuaternion<f32> {
+        match self {
+            ModelUpAxis::X => {
+                UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 90.0f32.to_radians())
+            }
+            ModelUpAxis::Y => UnitQuaternion::identity(),
+            ModelUpAxis::Z => {
+                UnitQuaternion::from_axis_angle(&Vector3::x_axis(), -90.0f32.to_radians())
+            }
+        }
+    }
+}
+
+/// Time range (in seconds) that will be applied to every animation of a model. It could be used
+/// to cut unwanted parts of animations, for example, a T-pose in the beginning of an animation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect, Visit, Default)]
+pub struct AnimationTrim {
+    /// Start time of the animations in seconds.
+    #[reflect(min_value = 0.0)]
+    pub start: f32,
+    /// End time of the animations in seconds.
+    #[reflect(min_value = 0.0)]
+    pub end: f32,
+}
+
+uuid_provider!(AnimationTrim = "4f1d0e7a-5a0c-4b3e-8f5d-6c2a9e0b1d3f");
+
+/// Name of the node, that is created to apply scale and up axis conversion of a model.
+pub const IMPORT_TRANSFORM_NODE_NAME: &str = "__ImportTransform";
+
+/// Applies scale, up axis conversion and animation trimming from the given import options to an
+/// imported model scene. The transform is applied to a new node, that becomes a parent of every
+/// top-level node of the model, so the animations of the top-level nodes are not affected by it.
+pub fn apply_import_options(scene: &mut Scene, options: &ModelImportOptions) {
+    let graph = &mut scene.graph;
+
+    let rotation = options.up_axis.rotation();
+    if options.scale != 1.0 || options.up_axis != ModelUpAxis::Y {
+        let root = graph.get_root();
+        let top_level_nodes = graph[root].children().to_vec();
+
+        let pivot = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name(IMPORT_TRANSFORM_NODE_NAME)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_rotation(rotation)
+                        .with_local_scale(Vector3::repeat(options.scale))
+                        .build(),
+                ),
+        )
+        .build(graph);
+
+        for node in top_level_nodes {
+            graph.link_nodes(node, pivot);
+        }
+    }
+
+    if let Some(trim) = options.animation_trim.as_ref() {
+        let start = trim.start.max(0.0);
+        let end = trim.end.max(start);
+        for node in graph.linear_iter_mut() {
+            if let Some(animation_player) = node.cast_mut::<AnimationPlayer>() {
+                for animation in animation_player
+                    .animations_mut()
+                    .get_value_mut_silent()
+                    .iter_mut()
+                {
+                    let time_slice = animation.time_slice();
+                    let start = start.clamp(time_slice.start, time_slice.end);
+                    let end = end.clamp(start, time_slice.end);
+                    animation.set_time_slice(start..end);
+                    animation.rewind();
+                }
+            }
+        }
+    }
+}
+
 /// All possible errors that may occur while trying to load model from some
 /// data source.
 #[derive(Debug)]
@@ -795,6 +946,7 @@ impl Model {
                     &model_import_options,
                 )
                 .await?;
+                apply_import_options(&mut scene, &model_import_options);
                 // Set NodeMapping::UseNames as mapping here because FBX does not have
                 // any persistent unique ids, and we have to use names.
                 (scene, NodeMapping::UseNames)
@@ -0,0 +1,176 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Reader of USDZ packages. USDZ is a zip archive, that stores its entries without compression,
+//! so the entries could be used directly without any decompression.
+
+use std::ops::Range;
+
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+const LOCAL_FILE_HEADER_SIZE: usize = 30;
+const DATA_DESCRIPTOR_FLAG: u16 = 0x08;
+
+/// Returns `true` if the data starts with the signature of zip archives.
+pub fn is_usdz(data: &[u8]) -> bool {
+    data.starts_with(&LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, String> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| "Unexpected end of USDZ data!".to_string())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| "Unexpected end of USDZ data!".to_string())
+}
+
+/// Normalizes a path inside of an archive - removes `.` and resolves `..` components.
+pub fn normalize_path(path: &str) -> String {
+    let mut components = Vec::new();
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => (),
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    components.join("/")
+}
+
+/// A USDZ package with its entries.
+pub struct UsdzArchive {
+    data: Vec<u8>,
+    entries: Vec<(String, Range<usize>)>,
+}
+
+impl UsdzArchive {
+    /// Reads the list of entries of the given archive.
+    pub fn read(data: Vec<u8>) -> Result<Self, String> {
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while offset + 4 <= data.len() && read_u32(&data, offset)? == LOCAL_FILE_HEADER_SIGNATURE {
+            let flags = read_u16(&data, offset + 6)?;
+            let compression = read_u16(&data, offset + 8)?;
+            let compressed_size = read_u32(&data, offset + 18)? as usize;
+            let name_length = read_u16(&data, offset + 26)? as usize;
+            let extra_length = read_u16(&data, offset + 28)? as usize;
+
+            let name_start = offset + LOCAL_FILE_HEADER_SIZE;
+            let name = data
+                .get(name_start..name_start + name_length)
+                .map(|name| String::from_utf8_lossy(name).to_string())
+                .ok_or_else(|| "Unexpected end of USDZ data!".to_string())?;
+
+            if flags & DATA_DESCRIPTOR_FLAG != 0 {
+                return Err(format!(
+                    "USDZ entry {name} uses data descriptor, which is not allowed!"
+                ));
+            }
+            if compression != 0 {
+                return Err(format!(
+                    "USDZ entry {name} is compressed, which is not allowed!"
+                ));
+            }
+
+            let data_start = name_start + name_length + extra_length;
+            let data_end = data_start + compressed_size;
+            if data_end > data.len() {
+                return Err(format!("USDZ entry {name} is out of bounds!"));
+            }
+
+            entries.push((normalize_path(&name), data_start..data_end));
+            offset = data_end;
+        }
+
+        Ok(Self { data, entries })
+    }
+
+    /// Returns the path and the content of the root layer of the package, which is the first
+    /// USD file in the archive.
+    pub fn root_layer(&self) -> Option<(&str, &[u8])> {
+        self.entries
+            .iter()
+            .find(|(name, _)| {
+                let name = name.to_lowercase();
+                name.ends_with(".usda") || name.ends_with(".usdc") || name.ends_with(".usd")
+            })
+            .map(|(name, range)| (name.as_str(), &self.data[range.clone()]))
+    }
+
+    /// Returns the content of the entry at the given path.
+    pub fn get(&self, path: &str) -> Option<&[u8]> {
+        let path = normalize_path(path);
+        self.entries
+            .iter()
+            .find(|(name, _)| *name == path)
+            .map(|(_, range)| &self.data[range.clone()])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_entry(name: &str, content: &[u8], compression: u16) -> Vec<u8> {
+        let mut data = LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes().to_vec();
+        data.extend_from_slice(&20u16.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&compression.to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(name.as_bytes());
+        data.extend_from_slice(content);
+        data
+    }
+
+    #[test]
+    fn test_read_usdz() {
+        let mut data = make_entry("scene.usda", b"#usda 1.0", 0);
+        data.extend(make_entry("textures/albedo.png", b"png", 0));
+        // Central directory is ignored.
+        data.extend_from_slice(&0x02014b50u32.to_le_bytes());
+
+        assert!(is_usdz(&data));
+        let archive = UsdzArchive::read(data).unwrap();
+        assert_eq!(
+            archive.root_layer(),
+            Some(("scene.usda", b"#usda 1.0".as_slice()))
+        );
+        assert_eq!(
+            archive.get("./textures/../textures/albedo.png"),
+            Some(b"png".as_slice())
+        );
+        assert_eq!(archive.get("missing.png"), None);
+    }
+
+    #[test]
+    fn test_compressed_usdz() {
+        let data = make_entry("scene.usda", b"#usda 1.0", 8);
+        assert!(UsdzArchive::read(data).is_err());
+    }
+}
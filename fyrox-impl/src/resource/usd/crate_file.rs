@@ -0,0 +1,1434 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Reader of binary USD layers - crate files (`.usdc`). Crate files are the default format of
+//! `.usd` files and of the root layers of `.usdz` packages. The reader produces the same [`Layer`]
+//! as the text parser, so the rest of the importer does not depend on the format of the layer.
+//!
+//! Only crate files of version 0.4.0 and newer are supported (USD 19.x and newer), older files
+//! should be re-saved with `usdcat`.
+
+use super::parser::{Attribute, Layer, Prim, Specifier, Value};
+use crate::fxhash::FxHashMap;
+use half::f16;
+
+const MAGIC: &[u8; 8] = b"PXR-USDC";
+const BOOTSTRAP_SIZE: usize = 88;
+const SECTION_NAME_SIZE: usize = 16;
+/// Arrays with less elements are never compressed.
+const MIN_COMPRESSED_ARRAY_SIZE: usize = 16;
+/// Terminator of field sets.
+const INVALID_INDEX: i64 = u32::MAX as i64;
+/// LZ4 cannot expand the data more than this.
+const MAX_LZ4_RATIO: usize = 255;
+
+/// Fields of prims, that are not stored in the metadata.
+const PRIM_FIELDS: &[&str] = &[
+    "specifier",
+    "typeName",
+    "primChildren",
+    "properties",
+    "variantSetChildren",
+];
+/// Fields of properties, that are not stored in the metadata.
+const PROPERTY_FIELDS: &[&str] = &[
+    "typeName",
+    "default",
+    "timeSamples",
+    "connectionPaths",
+    "targetPaths",
+    "connectionChildren",
+    "targetChildren",
+    "variability",
+    "custom",
+];
+
+const SPEC_TYPE_ATTRIBUTE: i64 = 1;
+const SPEC_TYPE_PRIM: i64 = 6;
+const SPEC_TYPE_PSEUDO_ROOT: i64 = 7;
+const SPEC_TYPE_RELATIONSHIP: i64 = 8;
+
+const TYPE_BOOL: u8 = 1;
+const TYPE_UCHAR: u8 = 2;
+const TYPE_INT: u8 = 3;
+const TYPE_UINT: u8 = 4;
+const TYPE_INT64: u8 = 5;
+const TYPE_UINT64: u8 = 6;
+const TYPE_HALF: u8 = 7;
+const TYPE_FLOAT: u8 = 8;
+const TYPE_DOUBLE: u8 = 9;
+const TYPE_STRING: u8 = 10;
+const TYPE_TOKEN: u8 = 11;
+const TYPE_ASSET_PATH: u8 = 12;
+const TYPE_MATRIX2D: u8 = 13;
+const TYPE_MATRIX4D: u8 = 15;
+const TYPE_QUATD: u8 = 16;
+const TYPE_QUATH: u8 = 18;
+const TYPE_VEC2D: u8 = 19;
+const TYPE_VEC4I: u8 = 30;
+const TYPE_DICTIONARY: u8 = 31;
+const TYPE_TOKEN_LIST_OP: u8 = 32;
+const TYPE_STRING_LIST_OP: u8 = 33;
+const TYPE_PATH_LIST_OP: u8 = 34;
+const TYPE_INT_LIST_OP: u8 = 36;
+const TYPE_INT64_LIST_OP: u8 = 37;
+const TYPE_UINT_LIST_OP: u8 = 38;
+const TYPE_UINT64_LIST_OP: u8 = 39;
+const TYPE_PATH_VECTOR: u8 = 40;
+const TYPE_TOKEN_VECTOR: u8 = 41;
+const TYPE_SPECIFIER: u8 = 42;
+const TYPE_PERMISSION: u8 = 43;
+const TYPE_VARIABILITY: u8 = 44;
+const TYPE_TIME_SAMPLES: u8 = 46;
+const TYPE_DOUBLE_VECTOR: u8 = 48;
+const TYPE_STRING_VECTOR: u8 = 50;
+const TYPE_VALUE_BLOCK: u8 = 51;
+const TYPE_TIME_CODE: u8 = 56;
+
+/// Returns `true` if the data starts with the signature of crate files.
+pub fn is_crate(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+fn eof() -> String {
+    "Unexpected end of USDC data!".to_string()
+}
+
+/// Decompresses the data, that was compressed by `TfFastCompression` - a sequence of LZ4 blocks.
+fn decompress(data: &[u8], max_size: usize) -> Result<Vec<u8>, String> {
+    let (&chunk_count, mut data) = data.split_first().ok_or_else(eof)?;
+    let max_size = max_size.min(data.len().saturating_mul(MAX_LZ4_RATIO).saturating_add(64));
+    let mut output = vec![0; max_size];
+    let error = |err: lz4_flex::block::DecompressError| format!("Invalid LZ4 data: {err}");
+    let written = if chunk_count == 0 {
+        lz4_flex::block::decompress_into(data, &mut output).map_err(error)?
+    } else {
+        let mut written = 0;
+        for _ in 0..chunk_count {
+            let mut reader = Reader::new(data);
+            let size = reader.u32()? as usize;
+            let chunk = reader.bytes(size)?;
+            written +=
+                lz4_flex::block::decompress_into(chunk, &mut output[written..]).map_err(error)?;
+            data = &data[reader.position..];
+        }
+        written
+    };
+    output.truncate(written);
+    Ok(output)
+}
+
+/// Decodes integers, that were encoded by `Usd_IntegerCompression`. Every integer is stored as a
+/// delta from the previous one, the deltas are stored using the smallest possible size defined
+/// by a 2-bit code. `wide` defines whether the integers are 64-bit or 32-bit.
+fn decode_integers(data: &[u8], count: usize, wide: bool) -> Result<Vec<i64>, String> {
+    let mut reader = Reader::new(data);
+    let common = if wide {
+        reader.i64()?
+    } else {
+        reader.i32()? as i64
+    };
+    let codes = reader.bytes(count.div_ceil(4))?;
+    let mut values = Vec::with_capacity(count);
+    let mut previous = 0i64;
+    for i in 0..count {
+        let delta = match (codes[i / 4] >> (2 * (i % 4))) & 3 {
+            0 => common,
+            1 if wide => reader.i16()? as i64,
+            1 => reader.bytes(1)?[0] as i8 as i64,
+            2 if wide => reader.i32()? as i64,
+            2 => reader.i16()? as i64,
+            _ if wide => reader.i64()?,
+            _ => reader.i32()? as i64,
+        };
+        previous = previous.wrapping_add(delta);
+        if !wide {
+            previous = previous as i32 as i64;
+        }
+        values.push(previous);
+    }
+    Ok(values)
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+struct Version(u8, u8, u8);
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn at(data: &'a [u8], position: u64) -> Result<Self, String> {
+        let position = usize::try_from(position).map_err(|_| eof())?;
+        if position > data.len() {
+            return Err(eof());
+        }
+        Ok(Self { data, position })
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .position
+            .checked_add(count)
+            .and_then(|end| self.data.get(self.position..end))
+            .ok_or_else(eof)?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        self.array().map(i16::from_le_bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        self.array().map(i32::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn i64(&mut self) -> Result<i64, String> {
+        self.array().map(i64::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        self.array().map(f32::from_le_bytes)
+    }
+
+    fn f64(&mut self) -> Result<f64, String> {
+        self.array().map(f64::from_le_bytes)
+    }
+
+    /// Reads the amount of elements, that is followed by the elements of the given size. The
+    /// amount is checked against the remaining data to prevent huge allocations.
+    fn count(&mut self, element_size: usize) -> Result<usize, String> {
+        let count = usize::try_from(self.u64()?).map_err(|_| eof())?;
+        if count.saturating_mul(element_size) > self.data.len() - self.position {
+            return Err(eof());
+        }
+        Ok(count)
+    }
+
+    /// Follows a relative offset, that is stored at the current position.
+    fn jump(&mut self) -> Result<(), String> {
+        let start = self.position as i64;
+        let offset = self.i64()?;
+        let position = start.checked_add(offset).ok_or_else(eof)?;
+        *self = Self::at(self.data, u64::try_from(position).map_err(|_| eof())?)?;
+        Ok(())
+    }
+
+    /// Reads integers compressed by [`decode_integers`].
+    fn compressed_ints(&mut self, count: usize, wide: bool) -> Result<Vec<i64>, String> {
+        let compressed_size = usize::try_from(self.u64()?).map_err(|_| eof())?;
+        let compressed = self.bytes(compressed_size)?;
+        let int_size = if wide { 8 } else { 4 };
+        let max_size = count
+            .saturating_mul(int_size)
+            .saturating_add(count.div_ceil(4))
+            .saturating_add(int_size);
+        let values = decode_integers(&decompress(compressed, max_size)?, count, wide)?;
+        Ok(values)
+    }
+
+    /// Reads compressed unsigned 32-bit indices.
+    fn compressed_indices(&mut self, count: usize) -> Result<Vec<i64>, String> {
+        let values = self.compressed_ints(count, false)?;
+        Ok(values
+            .into_iter()
+            .map(|value| value as u32 as i64)
+            .collect())
+    }
+}
+
+/// Packed reference to a value - either an inlined value or an offset of the value in the file.
+#[derive(Copy, Clone, Debug)]
+struct ValueRep(u64);
+
+impl ValueRep {
+    fn is_array(self) -> bool {
+        self.0 & (1 << 63) != 0
+    }
+
+    fn is_inlined(self) -> bool {
+        self.0 & (1 << 62) != 0
+    }
+
+    fn is_compressed(self) -> bool {
+        self.0 & (1 << 61) != 0
+    }
+
+    fn type_id(self) -> u8 {
+        (self.0 >> 48) as u8
+    }
+
+    fn payload(self) -> u64 {
+        self.0 & ((1 << 48) - 1)
+    }
+}
+
+/// Component type of vectors, quaternions and matrices.
+#[derive(Copy, Clone)]
+enum Component {
+    Double,
+    Float,
+    Half,
+    Int,
+}
+
+impl Component {
+    fn size(self) -> usize {
+        match self {
+            Component::Double => 8,
+            Component::Float | Component::Int => 4,
+            Component::Half => 2,
+        }
+    }
+
+    fn read(self, reader: &mut Reader) -> Result<f64, String> {
+        Ok(match self {
+            Component::Double => reader.f64()?,
+            Component::Float => reader.f32()? as f64,
+            Component::Half => f16::from_bits(reader.u16()?).to_f64(),
+            Component::Int => reader.i32()? as f64,
+        })
+    }
+}
+
+/// Shape of vector, quaternion and matrix types.
+#[derive(Copy, Clone)]
+enum Shape {
+    Vector(usize),
+    Quaternion,
+    Matrix(usize),
+}
+
+impl Shape {
+    fn of(type_id: u8) -> Option<(Shape, Component)> {
+        Some(match type_id {
+            TYPE_MATRIX2D..=TYPE_MATRIX4D => (
+                Shape::Matrix((type_id - TYPE_MATRIX2D) as usize + 2),
+                Component::Double,
+            ),
+            TYPE_QUATD..=TYPE_QUATH => (
+                Shape::Quaternion,
+                [Component::Double, Component::Float, Component::Half]
+                    [(type_id - TYPE_QUATD) as usize],
+            ),
+            TYPE_VEC2D..=TYPE_VEC4I => {
+                let index = (type_id - TYPE_VEC2D) as usize;
+                (
+                    Shape::Vector(index / 4 + 2),
+                    [
+                        Component::Double,
+                        Component::Float,
+                        Component::Half,
+                        Component::Int,
+                    ][index % 4],
+                )
+            }
+            _ => return None,
+        })
+    }
+
+    fn component_count(self) -> usize {
+        match self {
+            Shape::Vector(size) => size,
+            Shape::Quaternion => 4,
+            Shape::Matrix(size) => size * size,
+        }
+    }
+
+    /// Converts the components to the same value that the text parser produces.
+    fn to_value(self, components: Vec<f64>) -> Value {
+        let numbers = |components: &[f64]| {
+            Value::Tuple(components.iter().map(|c| Value::Number(*c)).collect())
+        };
+        match self {
+            Shape::Vector(_) => numbers(&components),
+            // Quaternions are stored as (i, j, k, real), but written as (real, i, j, k).
+            Shape::Quaternion => {
+                numbers(&[components[3], components[0], components[1], components[2]])
+            }
+            // Matrices are stored row by row.
+            Shape::Matrix(size) => Value::Tuple(components.chunks(size).map(numbers).collect()),
+        }
+    }
+}
+
+struct Spec {
+    path: usize,
+    field_set: usize,
+    spec_type: i64,
+}
+
+struct CrateFile<'a> {
+    data: &'a [u8],
+    version: Version,
+    tokens: Vec<String>,
+    strings: Vec<i64>,
+    fields: Vec<(i64, ValueRep)>,
+    field_sets: Vec<i64>,
+    paths: Vec<String>,
+    specs: Vec<Spec>,
+}
+
+impl<'a> CrateFile<'a> {
+    fn read(data: &'a [u8]) -> Result<Self, String> {
+        if !is_crate(data) || data.len() < BOOTSTRAP_SIZE {
+            return Err("Invalid USDC header!".to_string());
+        }
+        let version = Version(data[8], data[9], data[10]);
+        if version < Version(0, 4, 0) {
+            return Err(format!(
+                "USDC version {}.{}.{} is not supported, re-save the file using usdcat.",
+                version.0, version.1, version.2
+            ));
+        }
+
+        let mut file = Self {
+            data,
+            version,
+            tokens: Default::default(),
+            strings: Default::default(),
+            fields: Default::default(),
+            field_sets: Default::default(),
+            paths: Default::default(),
+            specs: Default::default(),
+        };
+
+        let mut toc = Reader::at(data, Reader::at(data, 16)?.u64()?)?;
+        let section_count = toc.count(SECTION_NAME_SIZE + 16)?;
+        let mut sections = FxHashMap::default();
+        for _ in 0..section_count {
+            let name = toc.bytes(SECTION_NAME_SIZE)?;
+            let name = String::from_utf8_lossy(name)
+                .trim_end_matches('\0')
+                .to_string();
+            let start = toc.u64()?;
+            let _size = toc.u64()?;
+            sections.insert(name, start);
+        }
+        let section = |name: &str| match sections.get(name) {
+            Some(start) => Reader::at(data, *start),
+            None => Err(format!("USDC file has no {name} section!")),
+        };
+
+        file.tokens = Self::read_tokens(&mut section("TOKENS")?)?;
+
+        let mut strings = section("STRINGS")?;
+        let count = strings.count(4)?;
+        file.strings = (0..count)
+            .map(|_| strings.u32().map(|index| index as i64))
+            .collect::<Result<_, _>>()?;
+
+        let mut fields = section("FIELDS")?;
+        let count = fields.count(0)?;
+        let names = fields.compressed_indices(count)?;
+        let reps_size = usize::try_from(fields.u64()?).map_err(|_| eof())?;
+        let reps = decompress(fields.bytes(reps_size)?, count.saturating_mul(8))?;
+        let mut reps = Reader::new(&reps);
+        file.fields = names
+            .into_iter()
+            .map(|name| reps.u64().map(|rep| (name, ValueRep(rep))))
+            .collect::<Result<_, _>>()?;
+
+        let mut field_sets = section("FIELDSETS")?;
+        let count = field_sets.count(0)?;
+        file.field_sets = field_sets.compressed_indices(count)?;
+
+        file.paths = file.read_paths(&mut section("PATHS")?)?;
+
+        let mut specs = section("SPECS")?;
+        let count = specs.count(0)?;
+        let paths = specs.compressed_indices(count)?;
+        let field_sets = specs.compressed_indices(count)?;
+        let spec_types = specs.compressed_indices(count)?;
+        file.specs = paths
+            .into_iter()
+            .zip(field_sets)
+            .zip(spec_types)
+            .map(|((path, field_set), spec_type)| Spec {
+                path: path as usize,
+                field_set: field_set as usize,
+                spec_type,
+            })
+            .collect();
+
+        Ok(file)
+    }
+
+    fn read_tokens(reader: &mut Reader) -> Result<Vec<String>, String> {
+        let count = reader.count(0)?;
+        let uncompressed_size = usize::try_from(reader.u64()?).map_err(|_| eof())?;
+        let compressed_size = usize::try_from(reader.u64()?).map_err(|_| eof())?;
+        let data = decompress(reader.bytes(compressed_size)?, uncompressed_size)?;
+        // Tokens are stored as null-terminated strings.
+        let tokens = data
+            .split(|c| *c == 0)
+            .take(count)
+            .map(|token| String::from_utf8_lossy(token).to_string())
+            .collect::<Vec<_>>();
+        if tokens.len() != count {
+            return Err("USDC file has not enough tokens!".to_string());
+        }
+        Ok(tokens)
+    }
+
+    fn read_paths(&self, reader: &mut Reader) -> Result<Vec<String>, String> {
+        let path_count = reader.count(0)?;
+        let count = reader.count(0)?;
+        let path_indices = reader.compressed_indices(count)?;
+        let element_tokens = reader.compressed_ints(count, false)?;
+        let jumps = reader.compressed_ints(count, false)?;
+
+        let invalid = || "Invalid USDC path tree!".to_string();
+        let mut paths = vec![String::new(); path_count.min(count)];
+        // The paths are stored as a tree in depth-first order, every entry has a jump to its
+        // next sibling. The tree is traversed without recursion to handle deep hierarchies.
+        let mut stack = vec![(0usize, None::<String>)];
+        let mut visited = 0;
+        while let Some((mut index, mut parent)) = stack.pop() {
+            loop {
+                visited += 1;
+                if index >= count || visited > count {
+                    return Err(invalid());
+                }
+                let path = match parent {
+                    None => "/".to_string(),
+                    Some(ref parent) => {
+                        let token = element_tokens[index];
+                        let name = self.token(token.abs())?;
+                        if token < 0 {
+                            format!("{parent}.{name}")
+                        } else if parent == "/" {
+                            format!("/{name}")
+                        } else {
+                            format!("{parent}/{name}")
+                        }
+                    }
+                };
+                *paths
+                    .get_mut(path_indices[index] as usize)
+                    .ok_or_else(invalid)? = path.clone();
+
+                let jump = jumps[index];
+                let has_child = jump > 0 || jump == -1;
+                let has_sibling = jump >= 0;
+                if has_child {
+                    if has_sibling {
+                        stack.push((index + jump as usize, parent.clone()));
+                    }
+                    parent = Some(path);
+                }
+                if !has_child && !has_sibling {
+                    break;
+                }
+                index += 1;
+            }
+        }
+        Ok(paths)
+    }
+
+    fn token(&self, index: i64) -> Result<&str, String> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.tokens.get(index))
+            .map(|token| token.as_str())
+            .ok_or_else(|| format!("Invalid USDC token index {index}!"))
+    }
+
+    fn string(&self, index: i64) -> Result<&str, String> {
+        let token = usize::try_from(index)
+            .ok()
+            .and_then(|index| self.strings.get(index))
+            .ok_or_else(|| format!("Invalid USDC string index {index}!"))?;
+        self.token(*token)
+    }
+
+    fn path(&self, index: i64) -> Result<&str, String> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.paths.get(index))
+            .map(|path| path.as_str())
+            .ok_or_else(|| format!("Invalid USDC path index {index}!"))
+    }
+
+    /// Returns the fields of the given spec as pairs of names and values.
+    fn spec_fields(&self, spec: &Spec) -> Result<Vec<(&str, ValueRep)>, String> {
+        let mut fields = Vec::new();
+        for field in self.field_sets.get(spec.field_set..).unwrap_or_default() {
+            if *field == INVALID_INDEX {
+                break;
+            }
+            let (name, rep) = usize::try_from(*field)
+                .ok()
+                .and_then(|field| self.fields.get(field))
+                .ok_or_else(|| format!("Invalid USDC field index {field}!"))?;
+            fields.push((self.token(*name)?, *rep));
+        }
+        Ok(fields)
+    }
+
+    /// Reads a single element of the given type, that is not inlined.
+    fn element(&self, type_id: u8, reader: &mut Reader) -> Result<Option<Value>, String> {
+        if let Some((shape, component)) = Shape::of(type_id) {
+            let components = (0..shape.component_count())
+                .map(|_| component.read(reader))
+                .collect::<Result<_, _>>()?;
+            return Ok(Some(shape.to_value(components)));
+        }
+        Ok(Some(match type_id {
+            TYPE_BOOL => Value::Bool(reader.u8()? != 0),
+            TYPE_UCHAR => Value::Number(reader.u8()? as f64),
+            TYPE_INT => Value::Number(reader.i32()? as f64),
+            TYPE_UINT => Value::Number(reader.u32()? as f64),
+            TYPE_INT64 => Value::Number(reader.i64()? as f64),
+            TYPE_UINT64 => Value::Number(reader.u64()? as f64),
+            TYPE_HALF => Value::Number(f16::from_bits(reader.u16()?).to_f64()),
+            TYPE_FLOAT => Value::Number(reader.f32()? as f64),
+            TYPE_DOUBLE | TYPE_TIME_CODE => Value::Number(reader.f64()?),
+            TYPE_STRING => Value::String(self.string(reader.u32()? as i64)?.to_string()),
+            TYPE_TOKEN => Value::String(self.token(reader.u32()? as i64)?.to_string()),
+            TYPE_ASSET_PATH => Value::AssetPath(self.token(reader.u32()? as i64)?.to_string()),
+            _ => return Ok(None),
+        }))
+    }
+
+    fn element_size(type_id: u8) -> usize {
+        match Shape::of(type_id) {
+            Some((shape, component)) => shape.component_count() * component.size(),
+            None => match type_id {
+                TYPE_BOOL | TYPE_UCHAR => 1,
+                TYPE_HALF => 2,
+                TYPE_INT64 | TYPE_UINT64 | TYPE_DOUBLE | TYPE_TIME_CODE => 8,
+                _ => 4,
+            },
+        }
+    }
+
+    fn inlined(&self, rep: ValueRep) -> Result<Option<Value>, String> {
+        let bits = rep.payload() as u32;
+        let bytes = bits.to_le_bytes().map(|byte| byte as i8 as f64);
+        if let Some((shape, _)) = Shape::of(rep.type_id()) {
+            // Vectors and diagonal matrices with small integer components are inlined as bytes.
+            let components = match shape {
+                Shape::Matrix(size) => (0..size * size)
+                    .map(|i| {
+                        if i % (size + 1) == 0 {
+                            bytes[i / size]
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect(),
+                _ => bytes[..shape.component_count()].to_vec(),
+            };
+            return Ok(Some(shape.to_value(components)));
+        }
+        let identifier = |names: &[&str]| {
+            names
+                .get(bits as usize)
+                .map(|name| Value::Identifier(name.to_string()))
+        };
+        Ok(match rep.type_id() {
+            TYPE_BOOL => Some(Value::Bool(bits != 0)),
+            TYPE_UCHAR | TYPE_UINT | TYPE_UINT64 => Some(Value::Number(bits as f64)),
+            TYPE_INT | TYPE_INT64 => Some(Value::Number(bits as i32 as f64)),
+            TYPE_HALF => Some(Value::Number(f16::from_bits(bits as u16).to_f64())),
+            // Doubles are inlined as floats, if they could be represented exactly.
+            TYPE_FLOAT | TYPE_DOUBLE | TYPE_TIME_CODE => {
+                Some(Value::Number(f32::from_bits(bits) as f64))
+            }
+            TYPE_STRING => Some(Value::String(self.string(bits as i64)?.to_string())),
+            TYPE_TOKEN => Some(Value::String(self.token(bits as i64)?.to_string())),
+            TYPE_ASSET_PATH => Some(Value::AssetPath(self.token(bits as i64)?.to_string())),
+            TYPE_SPECIFIER => identifier(&["def", "over", "class"]),
+            TYPE_PERMISSION => identifier(&["public", "private"]),
+            TYPE_VARIABILITY => identifier(&["varying", "uniform"]),
+            TYPE_VALUE_BLOCK => Some(Value::None),
+            TYPE_DICTIONARY => Some(Value::Dictionary),
+            _ => None,
+        })
+    }
+
+    fn array(&self, rep: ValueRep) -> Result<Option<Value>, String> {
+        // Empty arrays have no data.
+        if rep.payload() == 0 {
+            return Ok(Some(Value::Array(Vec::new())));
+        }
+
+        let type_id = rep.type_id();
+        let mut reader = Reader::at(self.data, rep.payload())?;
+        let count = if self.version < Version(0, 7, 0) {
+            reader.u32()? as usize
+        } else {
+            usize::try_from(reader.u64()?).map_err(|_| eof())?
+        };
+
+        let numbers = |values: Vec<f64>| {
+            Some(Value::Array(
+                values.into_iter().map(Value::Number).collect(),
+            ))
+        };
+        let compressed = rep.is_compressed() && count >= MIN_COMPRESSED_ARRAY_SIZE;
+        match type_id {
+            TYPE_INT | TYPE_UINT | TYPE_INT64 | TYPE_UINT64
+                if compressed && self.version >= Version(0, 5, 0) =>
+            {
+                let wide = matches!(type_id, TYPE_INT64 | TYPE_UINT64);
+                let values = reader.compressed_ints(count, wide)?;
+                let values = values
+                    .into_iter()
+                    .map(|value| match type_id {
+                        TYPE_UINT => value as u32 as f64,
+                        TYPE_UINT64 => value as u64 as f64,
+                        _ => value as f64,
+                    })
+                    .collect();
+                return Ok(numbers(values));
+            }
+            TYPE_HALF | TYPE_FLOAT | TYPE_DOUBLE
+                if compressed && self.version >= Version(0, 6, 0) =>
+            {
+                let values = match reader.u8()? {
+                    // Integers stored as floating-point numbers.
+                    b'i' => reader
+                        .compressed_ints(count, false)?
+                        .into_iter()
+                        .map(|value| value as f64)
+                        .collect(),
+                    // Lookup table with indices.
+                    b't' => {
+                        let table_size = reader.u32()? as usize;
+                        let element_size = Self::element_size(type_id);
+                        if table_size.saturating_mul(element_size)
+                            > reader.data.len() - reader.position
+                        {
+                            return Err(eof());
+                        }
+                        let table = (0..table_size)
+                            .map(|_| match self.element(type_id, &mut reader)? {
+                                Some(Value::Number(value)) => Ok(value),
+                                _ => Err(eof()),
+                            })
+                            .collect::<Result<Vec<_>, _>>()?;
+                        reader
+                            .compressed_indices(count)?
+                            .into_iter()
+                            .map(|index| {
+                                table
+                                    .get(index as usize)
+                                    .copied()
+                                    .ok_or_else(|| "Invalid USDC lookup table index!".to_string())
+                            })
+                            .collect::<Result<_, _>>()?
+                    }
+                    code => return Err(format!("Unknown USDC array compression {code}!")),
+                };
+                return Ok(numbers(values));
+            }
+            _ => (),
+        }
+
+        if count.saturating_mul(Self::element_size(type_id)) > reader.data.len() - reader.position {
+            return Err(eof());
+        }
+        let mut values = Vec::with_capacity(count);
+        for _ in 0..count {
+            match self.element(type_id, &mut reader)? {
+                Some(value) => values.push(value),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(Value::Array(values)))
+    }
+
+    /// Reads a list operation and merges all of its items into an array, composition of list
+    /// operations is not supported.
+    fn list_op(&self, type_id: u8, reader: &mut Reader) -> Result<Option<Value>, String> {
+        let header = reader.u8()?;
+        let item_size = if matches!(type_id, TYPE_INT64_LIST_OP | TYPE_UINT64_LIST_OP) {
+            8
+        } else {
+            4
+        };
+        let mut items = Vec::new();
+        // Explicit, added, deleted, ordered, prepended and appended items. Deleted and ordered
+        // items are skipped.
+        for (bit, keep) in [
+            (1 << 1, true),
+            (1 << 2, true),
+            (1 << 5, true),
+            (1 << 6, true),
+            (1 << 3, false),
+            (1 << 4, false),
+        ] {
+            if header & bit == 0 {
+                continue;
+            }
+            let count = reader.count(item_size)?;
+            for _ in 0..count {
+                let item = match type_id {
+                    TYPE_TOKEN_LIST_OP => {
+                        Value::String(self.token(reader.u32()? as i64)?.to_string())
+                    }
+                    TYPE_STRING_LIST_OP => {
+                        Value::String(self.string(reader.u32()? as i64)?.to_string())
+                    }
+                    TYPE_PATH_LIST_OP => Value::Path(self.path(reader.u32()? as i64)?.to_string()),
+                    TYPE_INT_LIST_OP => Value::Number(reader.i32()? as f64),
+                    TYPE_UINT_LIST_OP => Value::Number(reader.u32()? as f64),
+                    TYPE_INT64_LIST_OP => Value::Number(reader.i64()? as f64),
+                    TYPE_UINT64_LIST_OP => Value::Number(reader.u64()? as f64),
+                    _ => return Ok(None),
+                };
+                if keep {
+                    items.push(item);
+                }
+            }
+        }
+        Ok(Some(Value::Array(items)))
+    }
+
+    /// Unpacks the value. Returns `None` for the types, that have no representation in [`Value`].
+    fn value(&self, rep: ValueRep) -> Result<Option<Value>, String> {
+        if rep.is_array() {
+            return self.array(rep);
+        }
+        if rep.is_inlined() {
+            return self.inlined(rep);
+        }
+
+        let type_id = rep.type_id();
+        let mut reader = Reader::at(self.data, rep.payload())?;
+        match type_id {
+            TYPE_TOKEN_LIST_OP..=TYPE_UINT64_LIST_OP => self.list_op(type_id, &mut reader),
+            TYPE_PATH_VECTOR | TYPE_TOKEN_VECTOR | TYPE_STRING_VECTOR | TYPE_DOUBLE_VECTOR => {
+                let count = reader.count(4)?;
+                let values = (0..count)
+                    .map(|_| {
+                        Ok(match type_id {
+                            TYPE_PATH_VECTOR => {
+                                Value::Path(self.path(reader.u32()? as i64)?.to_string())
+                            }
+                            TYPE_TOKEN_VECTOR => {
+                                Value::String(self.token(reader.u32()? as i64)?.to_string())
+                            }
+                            TYPE_STRING_VECTOR => {
+                                Value::String(self.string(reader.u32()? as i64)?.to_string())
+                            }
+                            _ => Value::Number(reader.f64()?),
+                        })
+                    })
+                    .collect::<Result<_, String>>()?;
+                Ok(Some(Value::Array(values)))
+            }
+            TYPE_DICTIONARY => Ok(Some(Value::Dictionary)),
+            _ => self.element(type_id, &mut reader),
+        }
+    }
+
+    fn time_samples(&self, rep: ValueRep) -> Result<Vec<(f64, Value)>, String> {
+        // Times and values are stored at relative offsets.
+        let mut reader = Reader::at(self.data, rep.payload())?;
+        reader.jump()?;
+        let times = self
+            .value(ValueRep(reader.u64()?))?
+            .and_then(|times| {
+                times.map_array(|time| match time {
+                    Value::Number(time) => Some(*time),
+                    _ => None,
+                })
+            })
+            .unwrap_or_default();
+        reader.jump()?;
+        let count = reader.count(8)?;
+        if count != times.len() {
+            return Err("USDC time samples have mismatching times and values!".to_string());
+        }
+        let mut samples = Vec::with_capacity(count);
+        for time in times {
+            if let Some(value) = self.value(ValueRep(reader.u64()?))? {
+                samples.push((time, value));
+            }
+        }
+        Ok(samples)
+    }
+
+    fn field_value(
+        &self,
+        fields: &[(&str, ValueRep)],
+        name: &str,
+    ) -> Result<Option<Value>, String> {
+        match fields.iter().find(|(field, _)| *field == name) {
+            Some((_, rep)) => self.value(*rep),
+            None => Ok(None),
+        }
+    }
+
+    fn field_string(&self, fields: &[(&str, ValueRep)], name: &str) -> Result<String, String> {
+        Ok(self
+            .field_value(fields, name)?
+            .and_then(|value| value.as_str().map(|value| value.to_string()))
+            .unwrap_or_default())
+    }
+
+    /// Returns names of child prims or properties, that are stored in the given field.
+    fn field_names(&self, fields: &[(&str, ValueRep)], name: &str) -> Result<Vec<String>, String> {
+        Ok(self
+            .field_value(fields, name)?
+            .and_then(|value| value.map_array(|name| name.as_str().map(|name| name.to_string())))
+            .unwrap_or_default())
+    }
+
+    fn metadata(
+        &self,
+        fields: &[(&str, ValueRep)],
+        skip: &[&str],
+    ) -> Result<FxHashMap<String, Value>, String> {
+        let mut metadata = FxHashMap::default();
+        for (name, rep) in fields {
+            if skip.contains(name) {
+                continue;
+            }
+            if let Some(value) = self.value(*rep)? {
+                metadata.insert(name.to_string(), value);
+            }
+        }
+        Ok(metadata)
+    }
+
+    fn attribute(&self, fields: &[(&str, ValueRep)]) -> Result<Attribute, String> {
+        let mut attribute = Attribute {
+            type_name: self.field_string(fields, "typeName")?,
+            value: self.field_value(fields, "default")?,
+            connection: self
+                .field_value(fields, "connectionPaths")?
+                .and_then(|paths| {
+                    paths
+                        .as_array()?
+                        .first()?
+                        .as_str()
+                        .map(|path| path.to_string())
+                }),
+            metadata: self.metadata(fields, PROPERTY_FIELDS)?,
+            ..Default::default()
+        };
+        if let Some((_, rep)) = fields
+            .iter()
+            .find(|(name, rep)| *name == "timeSamples" && rep.type_id() == TYPE_TIME_SAMPLES)
+        {
+            attribute.time_samples = self.time_samples(*rep)?;
+        }
+        Ok(attribute)
+    }
+
+    fn prim(
+        &self,
+        path: &str,
+        name: &str,
+        specs: &FxHashMap<&str, &Spec>,
+    ) -> Result<Option<Prim>, String> {
+        let Some(spec) = specs
+            .get(path)
+            .filter(|spec| spec.spec_type == SPEC_TYPE_PRIM)
+        else {
+            return Ok(None);
+        };
+
+        let fields = self.spec_fields(spec)?;
+        let mut prim = Prim {
+            specifier: match self.field_value(&fields, "specifier")? {
+                Some(Value::Identifier(specifier)) if specifier == "over" => Specifier::Over,
+                Some(Value::Identifier(specifier)) if specifier == "class" => Specifier::Class,
+                _ => Specifier::Def,
+            },
+            type_name: self.field_string(&fields, "typeName")?,
+            name: name.to_string(),
+            metadata: self.metadata(&fields, PRIM_FIELDS)?,
+            ..Default::default()
+        };
+
+        for name in self.field_names(&fields, "properties")? {
+            let Some(spec) = specs.get(format!("{path}.{name}").as_str()) else {
+                continue;
+            };
+            let fields = self.spec_fields(spec)?;
+            match spec.spec_type {
+                SPEC_TYPE_ATTRIBUTE => {
+                    prim.attributes.insert(name, self.attribute(&fields)?);
+                }
+                SPEC_TYPE_RELATIONSHIP => {
+                    let targets = self
+                        .field_value(&fields, "targetPaths")?
+                        .and_then(|targets| {
+                            targets.map_array(|target| target.as_str().map(|t| t.to_string()))
+                        })
+                        .unwrap_or_default();
+                    prim.relationships.insert(name, targets);
+                }
+                _ => (),
+            }
+        }
+
+        for name in self.field_names(&fields, "primChildren")? {
+            if let Some(child) = self.prim(&format!("{path}/{name}"), &name, specs)? {
+                prim.children.push(child);
+            }
+        }
+
+        Ok(Some(prim))
+    }
+
+    fn layer(&self) -> Result<Layer, String> {
+        let specs = self
+            .specs
+            .iter()
+            .filter_map(|spec| Some((self.paths.get(spec.path)?.as_str(), spec)))
+            .collect::<FxHashMap<_, _>>();
+        let root = self
+            .specs
+            .iter()
+            .find(|spec| spec.spec_type == SPEC_TYPE_PSEUDO_ROOT)
+            .ok_or_else(|| "USDC file has no pseudo-root!".to_string())?;
+
+        let fields = self.spec_fields(root)?;
+        let mut layer = Layer {
+            metadata: self.metadata(&fields, &["primChildren"])?,
+            prims: Vec::new(),
+        };
+        for name in self.field_names(&fields, "primChildren")? {
+            if let Some(prim) = self.prim(&format!("/{name}"), &name, &specs)? {
+                layer.prims.push(prim);
+            }
+        }
+        Ok(layer)
+    }
+}
+
+/// Reads a binary USD layer (crate file).
+pub fn read_crate(data: &[u8]) -> Result<Layer, String> {
+    CrateFile::read(data)?.layer()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::algebra::Vector3;
+
+    fn compress(data: &[u8]) -> Vec<u8> {
+        let mut compressed = vec![0];
+        compressed.extend(lz4_flex::block::compress(data));
+        compressed
+    }
+
+    /// Writes the integers in compressed form, every delta is stored with its full size.
+    fn write_ints(output: &mut Vec<u8>, values: &[i64]) {
+        let mut data = 0i32.to_le_bytes().to_vec();
+        data.extend(std::iter::repeat(0xFF).take(values.len().div_ceil(4)));
+        let mut previous = 0;
+        for value in values {
+            data.extend_from_slice(&((value - previous) as i32).to_le_bytes());
+            previous = *value;
+        }
+        let compressed = compress(&data);
+        output.extend_from_slice(&(compressed.len() as u64).to_le_bytes());
+        output.extend(compressed);
+    }
+
+    fn rep(type_id: u8, payload: u64) -> u64 {
+        ((type_id as u64) << 48) | payload
+    }
+
+    fn inlined(type_id: u8, payload: u32) -> u64 {
+        rep(type_id, payload as u64) | (1 << 62)
+    }
+
+    fn array(type_id: u8, offset: usize) -> u64 {
+        rep(type_id, offset as u64) | (1 << 63)
+    }
+
+    /// Minimal writer of crate files.
+    #[derive(Default)]
+    struct Writer {
+        data: Vec<u8>,
+        tokens: Vec<String>,
+        fields: Vec<(i64, u64)>,
+        field_sets: Vec<i64>,
+        specs: Vec<(i64, i64, i64)>,
+    }
+
+    impl Writer {
+        fn new() -> Self {
+            Self {
+                data: vec![0; BOOTSTRAP_SIZE],
+                // Property tokens are stored as negative indices, so zero index must not be used.
+                tokens: vec![String::new()],
+                ..Default::default()
+            }
+        }
+
+        fn token(&mut self, token: &str) -> u32 {
+            match self.tokens.iter().position(|t| t == token) {
+                Some(index) => index as u32,
+                None => {
+                    self.tokens.push(token.to_string());
+                    self.tokens.len() as u32 - 1
+                }
+            }
+        }
+
+        fn token_vector(&mut self, tokens: &[&str]) -> u64 {
+            let indices = tokens.iter().map(|t| self.token(t)).collect::<Vec<_>>();
+            let offset = self.data.len();
+            self.data
+                .extend_from_slice(&(indices.len() as u64).to_le_bytes());
+            for index in indices {
+                self.data.extend_from_slice(&index.to_le_bytes());
+            }
+            rep(TYPE_TOKEN_VECTOR, offset as u64)
+        }
+
+        fn spec(&mut self, path: i64, spec_type: i64, fields: &[(&str, u64)]) {
+            self.specs
+                .push((path, self.field_sets.len() as i64, spec_type));
+            for (name, rep) in fields {
+                let name = self.token(name) as i64;
+                self.field_sets.push(self.fields.len() as i64);
+                self.fields.push((name, *rep));
+            }
+            self.field_sets.push(INVALID_INDEX);
+        }
+
+        fn finish(mut self, paths: &[(i64, i64)]) -> Vec<u8> {
+            let mut sections = Vec::new();
+
+            sections.push(("TOKENS", self.data.len()));
+            let mut tokens = Vec::new();
+            for token in self.tokens.iter() {
+                tokens.extend_from_slice(token.as_bytes());
+                tokens.push(0);
+            }
+            let compressed = compress(&tokens);
+            for value in [self.tokens.len(), tokens.len(), compressed.len()] {
+                self.data.extend_from_slice(&(value as u64).to_le_bytes());
+            }
+            self.data.extend(compressed);
+
+            sections.push(("STRINGS", self.data.len()));
+            self.data.extend_from_slice(&0u64.to_le_bytes());
+
+            sections.push(("FIELDS", self.data.len()));
+            self.data
+                .extend_from_slice(&(self.fields.len() as u64).to_le_bytes());
+            let names = self.fields.iter().map(|f| f.0).collect::<Vec<_>>();
+            write_ints(&mut self.data, &names);
+            let reps = self
+                .fields
+                .iter()
+                .flat_map(|f| f.1.to_le_bytes())
+                .collect::<Vec<_>>();
+            let compressed = compress(&reps);
+            self.data
+                .extend_from_slice(&(compressed.len() as u64).to_le_bytes());
+            self.data.extend(compressed);
+
+            sections.push(("FIELDSETS", self.data.len()));
+            self.data
+                .extend_from_slice(&(self.field_sets.len() as u64).to_le_bytes());
+            write_ints(&mut self.data, &self.field_sets.clone());
+
+            // Paths are given as pairs of element tokens and jumps in depth-first order.
+            sections.push(("PATHS", self.data.len()));
+            for _ in 0..2 {
+                self.data
+                    .extend_from_slice(&(paths.len() as u64).to_le_bytes());
+            }
+            write_ints(&mut self.data, &(0..paths.len() as i64).collect::<Vec<_>>());
+            write_ints(
+                &mut self.data,
+                &paths.iter().map(|p| p.0).collect::<Vec<_>>(),
+            );
+            write_ints(
+                &mut self.data,
+                &paths.iter().map(|p| p.1).collect::<Vec<_>>(),
+            );
+
+            sections.push(("SPECS", self.data.len()));
+            self.data
+                .extend_from_slice(&(self.specs.len() as u64).to_le_bytes());
+            for i in 0..3 {
+                let values = self
+                    .specs
+                    .iter()
+                    .map(|s| [s.0, s.1, s.2][i])
+                    .collect::<Vec<_>>();
+                write_ints(&mut self.data, &values);
+            }
+
+            let toc_offset = self.data.len() as u64;
+            self.data
+                .extend_from_slice(&(sections.len() as u64).to_le_bytes());
+            for (name, start) in sections {
+                let mut name = name.as_bytes().to_vec();
+                name.resize(SECTION_NAME_SIZE, 0);
+                self.data.extend(name);
+                self.data.extend_from_slice(&(start as u64).to_le_bytes());
+                self.data.extend_from_slice(&0u64.to_le_bytes());
+            }
+
+            self.data[0..8].copy_from_slice(MAGIC);
+            self.data[8..11].copy_from_slice(&[0, 8, 0]);
+            self.data[16..24].copy_from_slice(&toc_offset.to_le_bytes());
+            self.data
+        }
+    }
+
+    fn make_crate() -> Vec<u8> {
+        let mut writer = Writer::new();
+
+        let points = writer.data.len();
+        writer.data.extend_from_slice(&2u64.to_le_bytes());
+        for component in [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0] {
+            writer.data.extend_from_slice(&component.to_le_bytes());
+        }
+
+        let counts = writer.data.len();
+        writer.data.extend_from_slice(&20u64.to_le_bytes());
+        write_ints(&mut writer.data, &(0..20).collect::<Vec<_>>());
+
+        // Explicit list operation with a single path.
+        let targets = writer.data.len();
+        writer.data.push(0b11);
+        writer.data.extend_from_slice(&1u64.to_le_bytes());
+        writer.data.extend_from_slice(&1u32.to_le_bytes());
+
+        let times = writer.data.len();
+        writer.data.extend_from_slice(&2u64.to_le_bytes());
+        for time in [0.0f64, 10.0] {
+            writer.data.extend_from_slice(&time.to_le_bytes());
+        }
+        let samples = writer.data.len();
+        for value in [
+            8,
+            rep(TYPE_DOUBLE_VECTOR, times as u64),
+            8,
+            2,
+            inlined(TYPE_FLOAT, 1.0f32.to_bits()),
+            inlined(TYPE_FLOAT, 2.0f32.to_bits()),
+        ] {
+            writer.data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let root_children = writer.token_vector(&["Root"]);
+        let properties = writer.token_vector(&["points", "material:binding", "counts", "radius"]);
+        let children = writer.token_vector(&[]);
+        let z = writer.token("Z");
+        let mesh = writer.token("Mesh");
+        let vertex = writer.token("vertex");
+        let point_type = writer.token("point3f[]");
+        let int_type = writer.token("int[]");
+        let float_type = writer.token("float");
+
+        writer.spec(
+            0,
+            SPEC_TYPE_PSEUDO_ROOT,
+            &[
+                ("upAxis", inlined(TYPE_TOKEN, z)),
+                ("primChildren", root_children),
+            ],
+        );
+        writer.spec(
+            1,
+            SPEC_TYPE_PRIM,
+            &[
+                ("specifier", inlined(TYPE_SPECIFIER, 0)),
+                ("typeName", inlined(TYPE_TOKEN, mesh)),
+                ("properties", properties),
+                ("primChildren", children),
+            ],
+        );
+        writer.spec(
+            2,
+            SPEC_TYPE_ATTRIBUTE,
+            &[
+                ("typeName", inlined(TYPE_TOKEN, point_type)),
+                ("default", array(24, points)),
+                ("interpolation", inlined(TYPE_TOKEN, vertex)),
+            ],
+        );
+        writer.spec(
+            3,
+            SPEC_TYPE_RELATIONSHIP,
+            &[("targetPaths", rep(TYPE_PATH_LIST_OP, targets as u64))],
+        );
+        writer.spec(
+            4,
+            SPEC_TYPE_ATTRIBUTE,
+            &[
+                ("typeName", inlined(TYPE_TOKEN, int_type)),
+                ("default", array(TYPE_INT, counts) | (1 << 61)),
+            ],
+        );
+        writer.spec(
+            5,
+            SPEC_TYPE_ATTRIBUTE,
+            &[
+                ("typeName", inlined(TYPE_TOKEN, float_type)),
+                ("default", inlined(TYPE_FLOAT, 0.5f32.to_bits())),
+                ("timeSamples", rep(TYPE_TIME_SAMPLES, samples as u64)),
+            ],
+        );
+
+        let root = writer.token("Root") as i64;
+        let mut property = |name: &str| -(writer.token(name) as i64);
+        let paths = [
+            (0, -1),
+            (root, -1),
+            (property("points"), 0),
+            (property("material:binding"), 0),
+            (property("counts"), 0),
+            (property("radius"), -2),
+        ];
+        writer.finish(&paths)
+    }
+
+    #[test]
+    fn test_decode_integers() {
+        // Common value 1, then deltas: common, small (-2), medium (300) and large (100000).
+        let mut data = 1i32.to_le_bytes().to_vec();
+        data.push(0b11_10_01_00);
+        data.push(-2i8 as u8);
+        data.extend_from_slice(&300i16.to_le_bytes());
+        data.extend_from_slice(&100000i32.to_le_bytes());
+        assert_eq!(
+            decode_integers(&data, 4, false).unwrap(),
+            vec![1, -1, 299, 100299]
+        );
+        assert!(decode_integers(&data, 5, false).is_err());
+    }
+
+    #[test]
+    fn test_inlined_values() {
+        let file = CrateFile {
+            data: &[],
+            version: Version(0, 8, 0),
+            tokens: Vec::new(),
+            strings: Vec::new(),
+            fields: Vec::new(),
+            field_sets: Vec::new(),
+            paths: Vec::new(),
+            specs: Vec::new(),
+        };
+        let value = |type_id, payload| file.inlined(ValueRep(inlined(type_id, payload))).unwrap();
+        assert_eq!(
+            value(24, u32::from_le_bytes([1, 255, 3, 0]))
+                .unwrap()
+                .as_vec3(),
+            Some(Vector3::new(1.0, -1.0, 3.0))
+        );
+        let matrix = value(TYPE_MATRIX4D, u32::from_le_bytes([1, 2, 3, 1]))
+            .unwrap()
+            .as_matrix4()
+            .unwrap();
+        assert_eq!(matrix[(1, 1)], 2.0);
+        assert_eq!(matrix[(2, 2)], 3.0);
+        assert_eq!(matrix[(0, 1)], 0.0);
+        assert_eq!(
+            value(TYPE_DOUBLE, 0.25f32.to_bits()),
+            Some(Value::Number(0.25))
+        );
+    }
+
+    #[test]
+    fn test_read_crate() {
+        let data = make_crate();
+        assert!(is_crate(&data));
+        let layer = read_crate(&data).unwrap();
+        assert_eq!(
+            layer.metadata.get("upAxis"),
+            Some(&Value::String("Z".to_string()))
+        );
+        assert_eq!(layer.prims.len(), 1);
+
+        let root = &layer.prims[0];
+        assert_eq!(root.name, "Root");
+        assert_eq!(root.type_name, "Mesh");
+        assert_eq!(root.specifier, Specifier::Def);
+        assert!(root.children.is_empty());
+
+        let points = root.attribute("points").unwrap();
+        assert_eq!(points.type_name, "point3f[]");
+        assert_eq!(points.metadata_str("interpolation"), Some("vertex"));
+        assert_eq!(
+            root.value("points")
+                .unwrap()
+                .map_array(|v| v.as_vec3())
+                .unwrap(),
+            vec![Vector3::new(1.0, 2.0, 3.0), Vector3::new(4.0, 5.0, 6.0)]
+        );
+
+        assert_eq!(
+            root.value("counts")
+                .unwrap()
+                .map_array(|v| v.as_i32())
+                .unwrap(),
+            (0..20).collect::<Vec<_>>()
+        );
+
+        assert_eq!(root.relationship("material:binding"), Some("/Root"));
+
+        let radius = root.attribute("radius").unwrap();
+        assert_eq!(radius.value, Some(Value::Number(0.5)));
+        assert_eq!(radius.time_samples.len(), 2);
+        assert_eq!(radius.sample(Some(5.0)), Some(Value::Number(1.5)));
+    }
+
+    #[test]
+    fn test_read_truncated_crate() {
+        let data = make_crate();
+        for length in [0, 8, BOOTSTRAP_SIZE, data.len() / 2, data.len() - 1] {
+            assert!(read_crate(&data[..length]).is_err());
+        }
+    }
+}
@@ -0,0 +1,1397 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! [UsdLoader] enables the importing of USD stages from `*.usda` text layers, `*.usdc` binary
+//! crate files, `*.usd` files (either text or binary) and `*.usdz` packages. Prims are converted
+//! to scene nodes, `UsdPreviewSurface` materials are converted to the standard material and
+//! skeletons with their animations are converted to bones and animation tracks.
+//!
+//! Only flattened stages are supported - composition arcs (references, payloads, sublayers and
+//! variants) are ignored.
+
+mod archive;
+mod crate_file;
+mod parser;
+
+pub use parser::{parse_layer, Attribute, Layer, Prim, Specifier, UsdParseError, Value};
+
+use crate::{
+    asset::{
+        io::ResourceIo, loader, manager::ResourceManager, options, state::LoadError,
+        untyped::ResourceKind,
+    },
+    core::{
+        algebra::{Matrix4, Quaternion, UnitQuaternion, Vector2, Vector3, Vector4},
+        color::Color,
+        log::Log,
+        math::{
+            curve::{CurveKey, CurveKeyKind},
+            triangulator::triangulate,
+            Matrix4Ext,
+        },
+        pool::Handle,
+        TypeUuidProvider,
+    },
+    fxhash::FxHashMap,
+    graph::{BaseSceneGraph, NodeMapping},
+    gui::core::io::FileLoadError,
+    material::{
+        Material, MaterialProperty, MaterialResource, MaterialResourceBinding,
+        MaterialTextureBinding,
+    },
+    resource::{
+        model::{apply_import_options, Model, ModelImportOptions},
+        texture::{Texture, TextureImportOptions, TextureResource, TextureResourceExtension},
+    },
+    scene::{
+        animation::{Animation, AnimationContainer, AnimationPlayerBuilder, Track},
+        base::BaseBuilder,
+        graph::Graph,
+        light::{
+            directional::DirectionalLightBuilder, point::PointLightBuilder, spot::SpotLightBuilder,
+            BaseLightBuilder,
+        },
+        mesh::{
+            buffer::VertexTrait,
+            surface::{Surface, SurfaceBuilder, SurfaceData, SurfaceResource},
+            vertex::{AnimatedVertex, StaticVertex},
+            Mesh, MeshBuilder,
+        },
+        node::Node,
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+    utils::raw_mesh::RawMeshBuilder,
+};
+use archive::{is_usdz, UsdzArchive};
+use crate_file::{is_crate, read_crate};
+use fyrox_animation::track::TrackBinding;
+use std::{
+    fmt::{Display, Formatter},
+    hash::Hash,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Lights in USD have no range, but every light in the engine must have a finite radius, so this
+/// value is used instead.
+const DEFAULT_LIGHT_RANGE: f32 = 10.0;
+
+/// An error that may occur during USD import.
+#[derive(Debug)]
+pub enum UsdLoadError {
+    /// An i/o error has occurred.
+    File(FileLoadError),
+    /// The layer has invalid syntax.
+    Parse(UsdParseError),
+    /// The USDZ package is malformed.
+    Archive(String),
+    /// The binary crate file is malformed.
+    Crate(String),
+    /// The path of the stage is invalid.
+    InvalidPath,
+}
+
+impl Display for UsdLoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UsdLoadError::File(err) => write!(f, "File load error: {err:?}"),
+            UsdLoadError::Parse(err) => Display::fmt(err, f),
+            UsdLoadError::Archive(err) => write!(f, "USDZ error: {err}"),
+            UsdLoadError::Crate(err) => write!(f, "USDC error: {err}"),
+            UsdLoadError::InvalidPath => write!(f, "Invalid path."),
+        }
+    }
+}
+
+impl From<FileLoadError> for UsdLoadError {
+    fn from(error: FileLoadError) -> Self {
+        UsdLoadError::File(error)
+    }
+}
+
+impl From<UsdParseError> for UsdLoadError {
+    fn from(error: UsdParseError) -> Self {
+        UsdLoadError::Parse(error)
+    }
+}
+
+/// This object performs the loading of USD stages with extensions "usd", "usda", "usdc" or "usdz".
+pub struct UsdLoader {
+    /// Resource manager is needed to load textures referenced by the stage.
+    pub resource_manager: ResourceManager,
+    /// Default import options, that will be used if there is no import options file.
+    pub default_import_options: ModelImportOptions,
+}
+
+impl loader::ResourceLoader for UsdLoader {
+    fn extensions(&self) -> &[&str] {
+        &["usd", "usda", "usdc", "usdz"]
+    }
+
+    fn data_type_uuid(&self) -> crate::core::type_traits::prelude::Uuid {
+        Model::type_uuid()
+    }
+
+    fn load(&self, path: PathBuf, io: Arc<dyn ResourceIo>) -> loader::BoxedLoaderFuture {
+        let resource_manager = self.resource_manager.clone();
+        let default_import_options = self.default_import_options.clone();
+
+        Box::pin(async move {
+            let import_options = options::try_get_import_settings(&path, io.as_ref())
+                .await
+                .unwrap_or(default_import_options);
+
+            let model = load(path, io, resource_manager, import_options)
+                .await
+                .map_err(LoadError::new)?;
+
+            Ok(loader::LoaderPayload::new(model))
+        })
+    }
+
+    fn try_load_import_settings(
+        &self,
+        resource_path: PathBuf,
+        io: Arc<dyn ResourceIo>,
+    ) -> loader::BoxedImportOptionsLoaderFuture {
+        Box::pin(async move {
+            options::try_get_import_settings_opaque::<ModelImportOptions>(&resource_path, &*io)
+                .await
+        })
+    }
+
+    fn default_import_options(&self) -> Option<Box<dyn options::BaseImportOptions>> {
+        Some(Box::<ModelImportOptions>::default())
+    }
+}
+
+async fn load(
+    path: PathBuf,
+    io: Arc<dyn ResourceIo>,
+    resource_manager: ResourceManager,
+    options: ModelImportOptions,
+) -> Result<Model, UsdLoadError> {
+    let data = io.load_file(&path).await?;
+
+    let (archive, data) = if is_usdz(&data) {
+        let archive = UsdzArchive::read(data).map_err(UsdLoadError::Archive)?;
+        (Some(archive), Vec::new())
+    } else {
+        (None, data)
+    };
+
+    let (layer_path, layer_data) = match archive.as_ref() {
+        Some(archive) => archive
+            .root_layer()
+            .ok_or_else(|| UsdLoadError::Archive("USDZ package has no USD layers!".to_string()))?,
+        None => ("", data.as_slice()),
+    };
+
+    let layer = if is_crate(layer_data) {
+        read_crate(layer_data).map_err(UsdLoadError::Crate)?
+    } else {
+        parse_layer(&String::from_utf8_lossy(layer_data))?
+    };
+
+    let mut scene = Scene::new();
+    let root_name = path
+        .file_name()
+        .ok_or(UsdLoadError::InvalidPath)?
+        .to_string_lossy();
+    let root = scene.graph.get_root();
+    scene.graph[root].set_name(root_name.clone());
+
+    let layer_directory = layer_path
+        .rsplit_once('/')
+        .map(|(directory, _)| directory.to_string())
+        .unwrap_or_default();
+    let mut converter = Converter::new(
+        &layer,
+        &resource_manager,
+        &path,
+        archive.as_ref().map(|archive| (archive, layer_directory)),
+    );
+    converter.convert(&mut scene.graph);
+
+    super::gltf::node_names::resolve_name_conflicts(&path, &mut scene.graph);
+    apply_import_options(&mut scene, &options);
+    Ok(Model::new(NodeMapping::UseNames, scene))
+}
+
+/// Decomposes the given matrix into translation, rotation and scale. Shear is discarded.
+fn decompose(matrix: &Matrix4<f32>) -> (Vector3<f32>, UnitQuaternion<f32>, Vector3<f32>) {
+    let translation = Vector3::new(matrix[(0, 3)], matrix[(1, 3)], matrix[(2, 3)]);
+    let basis = matrix.basis();
+    let mut scale = Vector3::new(
+        basis.column(0).norm(),
+        basis.column(1).norm(),
+        basis.column(2).norm(),
+    );
+    if basis.determinant() < 0.0 {
+        scale.x = -scale.x;
+    }
+    let mut rotation = basis;
+    for (i, scale) in scale.iter().enumerate() {
+        if *scale != 0.0 {
+            rotation.column_mut(i).unscale_mut(*scale);
+        }
+    }
+    let rotation = UnitQuaternion::from_matrix(&rotation);
+    (translation, rotation, scale)
+}
+
+fn euler_to_matrix(order: &str, angles: Vector3<f32>) -> Matrix4<f32> {
+    let mut matrix = Matrix4::identity();
+    // Rotations are applied in the listed order, so the first one is the rightmost one.
+    for (axis, angle) in order.chars().zip(angles.iter()) {
+        let axis = match axis {
+            'X' => Vector3::x_axis(),
+            'Y' => Vector3::y_axis(),
+            _ => Vector3::z_axis(),
+        };
+        matrix =
+            UnitQuaternion::from_axis_angle(&axis, angle.to_radians()).to_homogeneous() * matrix;
+    }
+    matrix
+}
+
+/// Converts the value of a transform operation to a matrix. The type of the operation is defined
+/// by its name, for example `xformOp:rotateXYZ:pivot`.
+fn xform_op_matrix(name: &str, value: &Value) -> Option<Matrix4<f32>> {
+    let kind = name.split(':').nth(1)?;
+    Some(match kind {
+        "translate" => Matrix4::new_translation(&value.as_vec3()?),
+        "scale" => match value.as_vec3() {
+            Some(scale) => Matrix4::new_nonuniform_scaling(&scale),
+            None => Matrix4::new_scaling(value.as_f32()?),
+        },
+        "rotateX" | "rotateY" | "rotateZ" => {
+            let angle = value.as_f32()?;
+            euler_to_matrix(&kind[6..], Vector3::new(angle, 0.0, 0.0))
+        }
+        "orient" => {
+            let q = value.as_vec4()?;
+            UnitQuaternion::from_quaternion(Quaternion::new(q.x, q.y, q.z, q.w)).to_homogeneous()
+        }
+        "transform" => value.as_matrix4()?,
+        kind if kind.starts_with("rotate") && kind.len() == 9 => {
+            euler_to_matrix(&kind[6..], value.as_vec3()?)
+        }
+        _ => return None,
+    })
+}
+
+/// Returns ordered list of transform operations of the prim.
+fn xform_ops(prim: &Prim) -> Vec<String> {
+    prim.value("xformOpOrder")
+        .and_then(|order| order.map_array(|op| op.as_str().map(|op| op.to_string())))
+        .unwrap_or_default()
+}
+
+/// Calculates local transform of the prim at the given time.
+fn local_transform(prim: &Prim, time: Option<f64>) -> Matrix4<f32> {
+    let mut matrix = Matrix4::identity();
+    for op in xform_ops(prim) {
+        if op == "!resetXformStack!" {
+            matrix = Matrix4::identity();
+            continue;
+        }
+        let (inverse, name) = match op.strip_prefix("!invert!") {
+            Some(name) => (true, name),
+            None => (false, op.as_str()),
+        };
+        let Some(value) = prim
+            .attribute(name)
+            .and_then(|attribute| attribute.sample(time))
+        else {
+            continue;
+        };
+        let Some(mut op_matrix) = xform_op_matrix(name, &value) else {
+            Log::warn(format!("USD: unsupported transform operation {name}"));
+            continue;
+        };
+        if inverse {
+            op_matrix = op_matrix.try_inverse().unwrap_or_else(Matrix4::identity);
+        }
+        matrix *= op_matrix;
+    }
+    matrix
+}
+
+/// Collects sorted union of the time samples of the given attributes.
+fn sample_times<'a>(attributes: impl Iterator<Item = Option<&'a Attribute>>) -> Vec<f64> {
+    let mut times = attributes
+        .flatten()
+        .flat_map(|attribute| attribute.time_samples.iter().map(|(time, _)| *time))
+        .collect::<Vec<_>>();
+    times.sort_by(f64::total_cmp);
+    times.dedup();
+    times
+}
+
+fn add_key(track: &mut Track, time: f32, value: Vector3<f32>) {
+    let curves = track.data_container_mut().curves_mut();
+    for (curve, value) in curves.iter_mut().zip(value.iter()) {
+        curve.add_key(CurveKey::new(time, *value, CurveKeyKind::Linear));
+    }
+}
+
+fn quaternion_to_euler(q: UnitQuaternion<f32>) -> Vector3<f32> {
+    let (roll, pitch, yaw) = q.euler_angles();
+    Vector3::new(roll, pitch, yaw)
+}
+
+/// Returns the path of the prim, that owns the property with the given path, for example
+/// `/Material/Shader.outputs:rgb` -> `/Material/Shader`.
+fn property_owner(path: &str) -> &str {
+    let name_start = path.rfind('/').unwrap_or_default();
+    match path[name_start..].find('.') {
+        Some(dot) => &path[..name_start + dot],
+        None => path,
+    }
+}
+
+fn parent_path(path: &str) -> Option<&str> {
+    match path.rsplit_once('/') {
+        Some(("", _)) | None => None,
+        Some((parent, _)) => Some(parent),
+    }
+}
+
+/// Values of a primvar (or any other attribute with interpolation) of a mesh.
+struct Primvar<T> {
+    values: Vec<T>,
+    indices: Option<Vec<usize>>,
+    interpolation: String,
+    element_size: usize,
+}
+
+impl<T: Clone> Primvar<T> {
+    fn read(
+        prim: &Prim,
+        name: &str,
+        counts: (usize, usize, usize),
+        convert: impl Fn(&Value) -> Option<T>,
+    ) -> Option<Self> {
+        let attribute = prim.attribute(name)?;
+        let values = attribute.sample(None)?.map_array(convert)?;
+        let indices = prim
+            .value(&format!("{name}:indices"))
+            .and_then(|indices| indices.map_array(|index| index.as_i32().map(|i| i as usize)));
+        let element_size = attribute
+            .metadata
+            .get("elementSize")
+            .and_then(|size| size.as_i32())
+            .unwrap_or(1)
+            .max(1) as usize;
+
+        // Interpolation could be omitted, in this case it is deduced from the amount of values.
+        let interpolation = match attribute.metadata_str("interpolation") {
+            Some(interpolation) => interpolation.to_string(),
+            None => {
+                let (face_count, point_count, corner_count) = counts;
+                let count = indices.as_ref().map_or(values.len(), |i| i.len()) / element_size;
+                if count == corner_count {
+                    "faceVarying"
+                } else if count == point_count {
+                    "vertex"
+                } else if count == face_count {
+                    "uniform"
+                } else {
+                    "constant"
+                }
+                .to_string()
+            }
+        };
+
+        Some(Self {
+            values,
+            indices,
+            interpolation,
+            element_size,
+        })
+    }
+
+    fn element_index(&self, face: usize, point: usize, corner: usize) -> usize {
+        match self.interpolation.as_str() {
+            "constant" => 0,
+            "uniform" => face,
+            "faceVarying" => corner,
+            _ => point,
+        }
+    }
+
+    fn get(&self, face: usize, point: usize, corner: usize) -> Option<T> {
+        let index = self.element_index(face, point, corner);
+        let index = match self.indices.as_ref() {
+            Some(indices) => *indices.get(index)?,
+            None => index,
+        };
+        self.values.get(index).cloned()
+    }
+
+    fn get_element(&self, face: usize, point: usize, corner: usize) -> Option<&[T]> {
+        let start = self.element_index(face, point, corner) * self.element_size;
+        self.values.get(start..start + self.element_size)
+    }
+}
+
+/// Bones created for a skeleton prim.
+struct SkeletonBones {
+    joints: Vec<String>,
+    bones: Vec<Handle<Node>>,
+}
+
+struct Converter<'a> {
+    layer: &'a Layer,
+    resource_manager: &'a ResourceManager,
+    model_path: &'a Path,
+    archive: Option<(&'a UsdzArchive, String)>,
+    prims: FxHashMap<String, &'a Prim>,
+    nodes: FxHashMap<String, Handle<Node>>,
+    meshes: Vec<String>,
+    skeletons: FxHashMap<String, SkeletonBones>,
+    materials: FxHashMap<String, MaterialResource>,
+    textures: FxHashMap<String, Option<TextureResource>>,
+    /// Transform that converts the stage to Y-up meters.
+    correction: Matrix4<f32>,
+    animation: Animation,
+}
+
+impl<'a> Converter<'a> {
+    fn new(
+        layer: &'a Layer,
+        resource_manager: &'a ResourceManager,
+        model_path: &'a Path,
+        archive: Option<(&'a UsdzArchive, String)>,
+    ) -> Self {
+        let mut correction = Matrix4::identity();
+        if let Some(meters_per_unit) = layer.metadata.get("metersPerUnit").and_then(Value::as_f32) {
+            correction = Matrix4::new_scaling(meters_per_unit);
+        }
+        if layer.metadata.get("upAxis").and_then(Value::as_str) == Some("Z") {
+            correction *=
+                UnitQuaternion::from_axis_angle(&Vector3::x_axis(), -90.0f32.to_radians())
+                    .to_homogeneous();
+        }
+
+        let mut animation = Animation::default();
+        animation.set_name("Animation");
+
+        Self {
+            layer,
+            resource_manager,
+            model_path,
+            archive,
+            prims: Default::default(),
+            nodes: Default::default(),
+            meshes: Default::default(),
+            skeletons: Default::default(),
+            materials: Default::default(),
+            textures: Default::default(),
+            correction,
+            animation,
+        }
+    }
+
+    fn convert(&mut self, graph: &mut Graph) {
+        let layer = self.layer;
+        for prim in layer.prims.iter() {
+            self.collect_prims(prim, "");
+        }
+
+        let root = graph.get_root();
+        for prim in layer.prims.iter() {
+            self.convert_prim(prim, format!("/{}", prim.name), root, true, graph);
+        }
+
+        for path in std::mem::take(&mut self.meshes) {
+            let surfaces = self.convert_mesh(&path);
+            if let Some(mesh) = graph[self.nodes[&path]].cast_mut::<Mesh>() {
+                mesh.set_surfaces(surfaces);
+            }
+        }
+
+        let mut animation = std::mem::take(&mut self.animation);
+        if !animation.track_bindings().is_empty() {
+            animation.fit_length_to_content();
+            let mut animations = AnimationContainer::new();
+            animations.add(animation);
+            AnimationPlayerBuilder::new(BaseBuilder::new().with_name("AnimationPlayer"))
+                .with_animations(animations)
+                .build(graph);
+        }
+    }
+
+    fn collect_prims(&mut self, prim: &'a Prim, parent: &str) {
+        if prim.specifier == Specifier::Class {
+            return;
+        }
+        let path = format!("{parent}/{}", prim.name);
+        for child in prim.children.iter() {
+            self.collect_prims(child, &path);
+        }
+        self.prims.insert(path, prim);
+    }
+
+    fn seconds(&self, time: f64) -> f32 {
+        let metadata = &self.layer.metadata;
+        let start = metadata
+            .get("startTimeCode")
+            .and_then(Value::as_f32)
+            .unwrap_or_default() as f64;
+        let time_codes_per_second = metadata
+            .get("timeCodesPerSecond")
+            .or_else(|| metadata.get("framesPerSecond"))
+            .and_then(Value::as_f32)
+            .unwrap_or(24.0) as f64;
+        ((time - start) / time_codes_per_second) as f32
+    }
+
+    fn add_transform_tracks(&mut self, node: Handle<Node>, samples: Vec<(f64, Matrix4<f32>)>) {
+        let mut position = Track::new_position();
+        let mut rotation = Track::new_rotation();
+        let mut scale = Track::new_scale();
+        for (time, matrix) in samples {
+            let time = self.seconds(time);
+            let (t, r, s) = decompose(&matrix);
+            add_key(&mut position, time, t);
+            add_key(&mut rotation, time, quaternion_to_euler(r));
+            add_key(&mut scale, time, s);
+        }
+        for track in [position, rotation, scale] {
+            self.animation
+                .add_track_with_binding(TrackBinding::new(node), track);
+        }
+    }
+
+    fn make_base(&self, prim: &Prim, top_level: bool) -> BaseBuilder {
+        let correction = if top_level {
+            self.correction
+        } else {
+            Matrix4::identity()
+        };
+        let (position, rotation, scale) = decompose(&(correction * local_transform(prim, None)));
+        let visible =
+            prim.value("visibility").as_ref().and_then(Value::as_str) != Some("invisible");
+        BaseBuilder::new()
+            .with_name(prim.name.as_str())
+            .with_visibility(visible)
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .with_local_rotation(rotation)
+                    .with_local_scale(scale)
+                    .build(),
+            )
+    }
+
+    /// Looks for the relationship with the given name on the prim or on its ancestors.
+    fn find_relationship(&self, path: &str, name: &str) -> Option<&'a str> {
+        let mut current = Some(path);
+        while let Some(path) = current {
+            if let Some(target) = self
+                .prims
+                .get(path)
+                .copied()
+                .and_then(|prim| prim.relationship(name))
+            {
+                return Some(target);
+            }
+            current = parent_path(path);
+        }
+        None
+    }
+
+    fn convert_prim(
+        &mut self,
+        prim: &'a Prim,
+        path: String,
+        parent: Handle<Node>,
+        top_level: bool,
+        graph: &mut Graph,
+    ) {
+        if prim.specifier == Specifier::Class {
+            return;
+        }
+
+        for arc in [
+            "references",
+            "payload",
+            "inherits",
+            "specializes",
+            "variantSets",
+        ] {
+            if prim.metadata.contains_key(arc) {
+                Log::warn(format!(
+                    "USD: {arc} of {path} are ignored, only flattened stages are supported."
+                ));
+            }
+        }
+
+        let base = self.make_base(prim, top_level);
+        let handle = match prim.type_name.as_str() {
+            "Material" | "Shader" | "NodeGraph" | "GeomSubset" | "SkelAnimation" | "BlendShape" => {
+                return
+            }
+            "Mesh" => {
+                self.meshes.push(path.clone());
+                MeshBuilder::new(base).build(graph)
+            }
+            _ => PivotBuilder::new(base).build(graph),
+        };
+        graph.link_nodes(handle, parent);
+        self.nodes.insert(path.clone(), handle);
+
+        self.convert_transform_animation(prim, handle, top_level);
+
+        match prim.type_name.as_str() {
+            "DistantLight" | "SphereLight" | "DiskLight" | "RectLight" | "CylinderLight" => {
+                convert_light(prim, handle, graph)
+            }
+            "Skeleton" => self.convert_skeleton(prim, &path, handle, graph),
+            _ => (),
+        }
+
+        for child in prim.children.iter() {
+            self.convert_prim(
+                child,
+                format!("{path}/{}", child.name),
+                handle,
+                false,
+                graph,
+            );
+        }
+    }
+
+    fn convert_transform_animation(&mut self, prim: &Prim, node: Handle<Node>, top_level: bool) {
+        let ops = xform_ops(prim);
+        let times = sample_times(
+            ops.iter()
+                .map(|op| prim.attribute(op.trim_start_matches("!invert!"))),
+        );
+        if times.is_empty() {
+            return;
+        }
+        let correction = if top_level {
+            self.correction
+        } else {
+            Matrix4::identity()
+        };
+        let samples = times
+            .into_iter()
+            .map(|time| (time, correction * local_transform(prim, Some(time))))
+            .collect();
+        self.add_transform_tracks(node, samples);
+    }
+
+    fn convert_skeleton(
+        &mut self,
+        prim: &Prim,
+        path: &str,
+        skeleton: Handle<Node>,
+        graph: &mut Graph,
+    ) {
+        let joints = prim
+            .value("joints")
+            .and_then(|joints| joints.map_array(|joint| joint.as_str().map(|j| j.to_string())))
+            .unwrap_or_default();
+        let binds = prim
+            .value("bindTransforms")
+            .and_then(|binds| binds.map_array(Value::as_matrix4))
+            .unwrap_or_default();
+        let rests = prim
+            .value("restTransforms")
+            .and_then(|rests| rests.map_array(Value::as_matrix4))
+            .filter(|rests| rests.len() == joints.len());
+
+        let mut bones = Vec::<Handle<Node>>::with_capacity(joints.len());
+        let mut rest_poses = Vec::with_capacity(joints.len());
+        for (index, joint) in joints.iter().enumerate() {
+            // Parent joints are always listed before their children.
+            let parent_index = joint
+                .rsplit_once('/')
+                .and_then(|(parent, _)| joints[..index].iter().position(|j| j == parent));
+            let bind = binds.get(index).cloned().unwrap_or_else(Matrix4::identity);
+            let rest = match rests.as_ref() {
+                Some(rests) => rests[index],
+                None => {
+                    let parent_bind = parent_index
+                        .and_then(|parent| binds.get(parent).cloned())
+                        .unwrap_or_else(Matrix4::identity);
+                    parent_bind.try_inverse().unwrap_or_else(Matrix4::identity) * bind
+                }
+            };
+            let (position, rotation, scale) = decompose(&rest);
+            rest_poses.push((position, rotation, scale));
+
+            let name = joint.rsplit('/').next().unwrap_or(joint);
+            let bone = PivotBuilder::new(
+                BaseBuilder::new()
+                    .with_name(name)
+                    .with_inv_bind_pose_transform(
+                        bind.try_inverse().unwrap_or_else(Matrix4::identity),
+                    )
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(position)
+                            .with_local_rotation(rotation)
+                            .with_local_scale(scale)
+                            .build(),
+                    ),
+            )
+            .build(graph);
+            graph.link_nodes(bone, parent_index.map_or(skeleton, |parent| bones[parent]));
+            bones.push(bone);
+        }
+
+        if let Some(animation) = self
+            .find_relationship(path, "skel:animationSource")
+            .and_then(|source| self.prims.get(source).copied())
+        {
+            self.convert_skel_animation(animation, &joints, &bones, &rest_poses);
+        }
+
+        self.skeletons
+            .insert(path.to_string(), SkeletonBones { joints, bones });
+    }
+
+    fn convert_skel_animation(
+        &mut self,
+        animation: &Prim,
+        joints: &[String],
+        bones: &[Handle<Node>],
+        rest_poses: &[(Vector3<f32>, UnitQuaternion<f32>, Vector3<f32>)],
+    ) {
+        let animated_joints = animation
+            .value("joints")
+            .and_then(|joints| joints.map_array(|joint| joint.as_str().map(|j| j.to_string())))
+            .unwrap_or_default();
+        let attributes =
+            ["translations", "rotations", "scales"].map(|name| animation.attribute(name));
+        let times = sample_times(attributes.iter().copied());
+        if times.is_empty() {
+            return;
+        }
+
+        let mut tracks = animated_joints
+            .iter()
+            .map(|_| {
+                (
+                    Track::new_position(),
+                    Track::new_rotation(),
+                    Track::new_scale(),
+                )
+            })
+            .collect::<Vec<_>>();
+        for time in times {
+            let [translations, rotations, scales] =
+                attributes.map(|attribute| attribute.and_then(|a| a.sample(Some(time))));
+            let seconds = self.seconds(time);
+            for (index, joint) in animated_joints.iter().enumerate() {
+                let Some(bone_index) = joints.iter().position(|j| j == joint) else {
+                    continue;
+                };
+                let (rest_position, rest_rotation, rest_scale) = rest_poses[bone_index];
+                let element = |values: &Option<Value>| {
+                    values
+                        .as_ref()
+                        .and_then(|values| values.as_array())
+                        .and_then(|values| values.get(index))
+                        .cloned()
+                };
+                let position = element(&translations)
+                    .and_then(|v| v.as_vec3())
+                    .unwrap_or(rest_position);
+                let rotation = element(&rotations)
+                    .and_then(|v| v.as_vec4())
+                    .map(|q| UnitQuaternion::from_quaternion(Quaternion::new(q.x, q.y, q.z, q.w)))
+                    .unwrap_or(rest_rotation);
+                let scale = element(&scales)
+                    .and_then(|v| v.as_vec3())
+                    .unwrap_or(rest_scale);
+
+                let (position_track, rotation_track, scale_track) = &mut tracks[index];
+                add_key(position_track, seconds, position);
+                add_key(rotation_track, seconds, quaternion_to_euler(rotation));
+                add_key(scale_track, seconds, scale);
+            }
+        }
+
+        for (joint, (position, rotation, scale)) in animated_joints.iter().zip(tracks) {
+            let Some(bone_index) = joints.iter().position(|j| j == joint) else {
+                continue;
+            };
+            let binding = TrackBinding::new(bones[bone_index]);
+            self.animation
+                .add_track_with_binding(binding.clone(), position);
+            self.animation
+                .add_track_with_binding(binding.clone(), rotation);
+            self.animation.add_track_with_binding(binding, scale);
+        }
+    }
+
+    /// Finds the skeleton, that is used to deform the mesh at the given path. The skeleton is
+    /// either specified explicitly or the first skeleton of the closest `SkelRoot` is used.
+    fn mesh_skeleton(&self, path: &str) -> Option<&SkeletonBones> {
+        if let Some(skeleton) = self.find_relationship(path, "skel:skeleton") {
+            return self.skeletons.get(skeleton);
+        }
+        let mut current = parent_path(path);
+        while let Some(ancestor) = current {
+            if self.prims.get(ancestor).map(|prim| prim.type_name.as_str()) == Some("SkelRoot") {
+                let prefix = format!("{ancestor}/");
+                return self
+                    .skeletons
+                    .iter()
+                    .filter(|(skeleton, _)| skeleton.starts_with(&prefix))
+                    .min_by(|a, b| a.0.cmp(b.0))
+                    .map(|(_, skeleton)| skeleton);
+            }
+            current = parent_path(ancestor);
+        }
+        None
+    }
+
+    fn mesh_skin(&self, prim: &Prim, path: &str, sizes: (usize, usize, usize)) -> Option<MeshSkin> {
+        let indices = Primvar::read(prim, "primvars:skel:jointIndices", sizes, |index| {
+            index.as_i32().map(|index| index.max(0) as usize)
+        })?;
+        let weights = Primvar::read(prim, "primvars:skel:jointWeights", sizes, Value::as_f32)?;
+        let skeleton = self.mesh_skeleton(path)?;
+
+        // Meshes could use their own order of joints.
+        let joint_map = match prim
+            .value("skel:joints")
+            .and_then(|joints| joints.map_array(|joint| joint.as_str().map(|j| j.to_string())))
+        {
+            Some(joints) => joints
+                .iter()
+                .map(|joint| {
+                    skeleton
+                        .joints
+                        .iter()
+                        .position(|j| j == joint)
+                        .unwrap_or_default()
+                })
+                .collect(),
+            None => (0..skeleton.joints.len()).collect(),
+        };
+
+        if skeleton.bones.len() > u8::MAX as usize + 1 {
+            Log::warn(format!(
+                "USD: skeleton of {path} has more than 256 joints, extra joints are ignored."
+            ));
+        }
+
+        Some(MeshSkin {
+            bones: skeleton.bones.clone(),
+            joint_map,
+            indices,
+            weights,
+            geom_bind: prim
+                .value("primvars:skel:geomBindTransform")
+                .and_then(|matrix| matrix.as_matrix4())
+                .unwrap_or_else(Matrix4::identity),
+        })
+    }
+
+    fn convert_mesh(&mut self, path: &str) -> Vec<Surface> {
+        let prim = self.prims[path];
+        let Some(points) = prim
+            .value("points")
+            .and_then(|points| points.map_array(Value::as_vec3))
+        else {
+            return Vec::new();
+        };
+        let counts = read_indices(prim, "faceVertexCounts");
+        let indices = read_indices(prim, "faceVertexIndices");
+        let sizes = (counts.len(), points.len(), indices.len());
+
+        let normals = Primvar::read(prim, "primvars:normals", sizes, Value::as_vec3)
+            .or_else(|| Primvar::read(prim, "normals", sizes, Value::as_vec3));
+        let tex_coords = uv_primvar(prim, sizes);
+        let left_handed =
+            prim.value("orientation").as_ref().and_then(Value::as_str) == Some("leftHanded");
+        let skin = self.mesh_skin(prim, path, sizes);
+
+        // Faces could be split into subsets with their own materials.
+        let mut face_groups = vec![0; counts.len()];
+        let mut group_materials = vec![self
+            .bound_material(path)
+            .or_else(|| display_color_material(prim))];
+        for subset in prim.children.iter() {
+            if subset.type_name != "GeomSubset"
+                || subset
+                    .value("elementType")
+                    .as_ref()
+                    .and_then(Value::as_str)
+                    .map_or(false, |kind| kind != "face")
+            {
+                continue;
+            }
+            let group = group_materials.len();
+            group_materials.push(self.bound_material(&format!("{path}/{}", subset.name)));
+            for face in read_indices(subset, "indices") {
+                if let Some(face_group) = face_groups.get_mut(face) {
+                    *face_group = group;
+                }
+            }
+        }
+
+        let mut groups = vec![Vec::<AnimatedVertex>::new(); group_materials.len()];
+        let mut polygon = Vec::new();
+        let mut triangles = Vec::new();
+        let mut corner_start = 0;
+        for (face, &count) in counts.iter().enumerate() {
+            let corners = corner_start..corner_start + count;
+            corner_start += count;
+            let Some(face_points) = indices.get(corners.clone()) else {
+                break;
+            };
+            if count < 3 {
+                continue;
+            }
+
+            polygon.clear();
+            polygon.extend(
+                face_points
+                    .iter()
+                    .map(|&point| points.get(point).cloned().unwrap_or_else(Vector3::zeros)),
+            );
+            triangles.clear();
+            if count == 3 {
+                triangles.push([0, 1, 2]);
+            } else {
+                triangulate(&polygon, &mut triangles);
+            }
+
+            let mut face_normal = (polygon[1] - polygon[0])
+                .cross(&(polygon[2] - polygon[0]))
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::y);
+            if left_handed {
+                face_normal = -face_normal;
+            }
+
+            for triangle in triangles.iter() {
+                let triangle = if left_handed {
+                    [triangle[0], triangle[2], triangle[1]]
+                } else {
+                    *triangle
+                };
+                for local in triangle {
+                    let point = face_points[local];
+                    let corner = corners.start + local;
+                    let mut position = polygon[local];
+                    let mut normal = normals
+                        .as_ref()
+                        .and_then(|normals| normals.get(face, point, corner))
+                        .unwrap_or(face_normal);
+                    let tex_coord = tex_coords
+                        .as_ref()
+                        .and_then(|tex_coords| tex_coords.get(face, point, corner))
+                        // USD uses bottom-left origin of texture coordinates.
+                        .map(|uv| Vector2::new(uv.x, 1.0 - uv.y))
+                        .unwrap_or_else(Vector2::zeros);
+
+                    let mut bone_weights = [0.0; 4];
+                    let mut bone_indices = [0; 4];
+                    if let Some(skin) = skin.as_ref() {
+                        position = skin.geom_bind.transform_point(&position.into()).coords;
+                        normal = skin
+                            .geom_bind
+                            .transform_vector(&normal)
+                            .try_normalize(f32::EPSILON)
+                            .unwrap_or(normal);
+                        skin.fill_weights(
+                            face,
+                            point,
+                            corner,
+                            &mut bone_weights,
+                            &mut bone_indices,
+                        );
+                    }
+
+                    groups[face_groups[face]].push(AnimatedVertex {
+                        position,
+                        tex_coord,
+                        normal,
+                        tangent: Vector4::zeros(),
+                        bone_weights,
+                        bone_indices,
+                    });
+                }
+            }
+        }
+
+        let mut surfaces = Vec::new();
+        for (vertices, material) in groups.into_iter().zip(group_materials) {
+            if vertices.is_empty() {
+                continue;
+            }
+            let mut data = if skin.is_some() {
+                build_surface_data(vertices, |vertex| vertex)
+            } else {
+                build_surface_data(vertices, |vertex| StaticVertex {
+                    position: vertex.position,
+                    tex_coord: vertex.tex_coord,
+                    normal: vertex.normal,
+                    tangent: vertex.tangent,
+                })
+            };
+            if let Err(err) = data.calculate_tangents() {
+                Log::err(format!(
+                    "USD: unable to calculate tangents of {path}. Reason: {err:?}"
+                ));
+            }
+            let mut builder = SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::External(self.model_path.to_path_buf()),
+                data,
+            ));
+            if let Some(material) = material {
+                builder = builder.with_material(material);
+            }
+            if let Some(skin) = skin.as_ref() {
+                builder = builder.with_bones(skin.bones.clone());
+            }
+            surfaces.push(builder.build());
+        }
+        surfaces
+    }
+
+    fn bound_material(&mut self, path: &str) -> Option<MaterialResource> {
+        let target = self.find_relationship(path, "material:binding")?;
+        if let Some(material) = self.materials.get(target) {
+            return Some(material.clone());
+        }
+        let material = self.convert_material(target)?;
+        self.materials.insert(target.to_string(), material.clone());
+        Some(material)
+    }
+
+    fn convert_material(&mut self, path: &str) -> Option<MaterialResource> {
+        let prim = *self.prims.get(path)?;
+        let shader = prim
+            .attribute("outputs:surface")
+            .and_then(|output| output.connection.as_deref())
+            .and_then(|connection| self.prims.get(property_owner(connection)).copied())
+            .or_else(|| {
+                prim.children.iter().find(|child| {
+                    child.value("info:id").as_ref().and_then(Value::as_str)
+                        == Some("UsdPreviewSurface")
+                })
+            });
+
+        let mut material = Material::standard();
+        if let Some(shader) = shader {
+            let input = |name: &str| shader.value(&format!("inputs:{name}"));
+
+            let color = input("diffuseColor")
+                .and_then(|color| color.as_vec3())
+                .unwrap_or_else(|| Vector3::repeat(1.0));
+            let opacity = input("opacity")
+                .and_then(|opacity| opacity.as_f32())
+                .unwrap_or(1.0);
+            material.set_property(
+                "diffuseColor",
+                MaterialProperty::Color(
+                    Color::from(Vector4::new(color.x, color.y, color.z, opacity)).linear_to_srgb(),
+                ),
+            );
+
+            if let Some(emission) = input("emissiveColor").and_then(|color| color.as_vec3()) {
+                material.set_property("emissionStrength", MaterialProperty::Vector3(emission));
+            }
+
+            for (input, property) in [
+                ("diffuseColor", "diffuseTexture"),
+                ("normal", "normalTexture"),
+                ("metallic", "metallicTexture"),
+                ("roughness", "roughnessTexture"),
+                ("emissiveColor", "emissionTexture"),
+                ("occlusion", "aoTexture"),
+                ("displacement", "heightTexture"),
+            ] {
+                if let Some(texture) = self.input_texture(shader, input) {
+                    material.bind(
+                        property,
+                        MaterialResourceBinding::Texture(MaterialTextureBinding {
+                            value: Some(texture),
+                        }),
+                    );
+                }
+            }
+        }
+
+        Some(MaterialResource::new_ok(ResourceKind::Embedded, material))
+    }
+
+    /// Returns the texture of `UsdUVTexture` shader, that is connected to the given input.
+    fn input_texture(&mut self, shader: &Prim, input: &str) -> Option<TextureResource> {
+        let connection = shader
+            .attribute(&format!("inputs:{input}"))?
+            .connection
+            .as_deref()?;
+        let texture = self.prims.get(property_owner(connection))?;
+        let file = texture.value("inputs:file")?.as_str()?.to_string();
+        self.load_texture(&file)
+    }
+
+    fn load_texture(&mut self, file: &str) -> Option<TextureResource> {
+        if let Some(texture) = self.textures.get(file) {
+            return texture.clone();
+        }
+
+        let texture = match self.archive.as_ref() {
+            Some((archive, directory)) => match archive.get(&format!("{directory}/{file}")) {
+                Some(data) => TextureResource::load_from_memory(
+                    ResourceKind::Embedded,
+                    data,
+                    TextureImportOptions::default(),
+                )
+                .map_err(|err| {
+                    Log::err(format!(
+                        "USD: unable to load texture {file}. Reason: {err:?}"
+                    ))
+                })
+                .ok(),
+                None => {
+                    Log::warn(format!("USD: texture {file} is not found in the package."));
+                    None
+                }
+            },
+            None => {
+                let path = match self.model_path.parent() {
+                    Some(directory) => directory.join(file),
+                    None => PathBuf::from(file),
+                };
+                Some(self.resource_manager.request::<Texture>(path))
+            }
+        };
+
+        self.textures.insert(file.to_string(), texture.clone());
+        texture
+    }
+}
+
+/// Skinning data of a mesh.
+struct MeshSkin {
+    bones: Vec<Handle<Node>>,
+    /// Maps joint indices of the mesh to the bones of the skeleton.
+    joint_map: Vec<usize>,
+    indices: Primvar<usize>,
+    weights: Primvar<f32>,
+    geom_bind: Matrix4<f32>,
+}
+
+impl MeshSkin {
+    /// Picks four most influential joints of a vertex and normalizes their weights.
+    fn fill_weights(
+        &self,
+        face: usize,
+        point: usize,
+        corner: usize,
+        bone_weights: &mut [f32; 4],
+        bone_indices: &mut [u8; 4],
+    ) {
+        let (Some(indices), Some(weights)) = (
+            self.indices.get_element(face, point, corner),
+            self.weights.get_element(face, point, corner),
+        ) else {
+            return;
+        };
+        let mut influences = indices
+            .iter()
+            .zip(weights)
+            .map(|(index, weight)| (*index, *weight))
+            .collect::<Vec<_>>();
+        influences.sort_by(|a, b| b.1.total_cmp(&a.1));
+        influences.truncate(4);
+        let total = influences.iter().map(|(_, weight)| weight).sum::<f32>();
+        if total <= 0.0 {
+            return;
+        }
+        for (i, (index, weight)) in influences.into_iter().enumerate() {
+            let bone = self.joint_map.get(index).cloned().unwrap_or_default();
+            bone_indices[i] = bone.min(u8::MAX as usize) as u8;
+            bone_weights[i] = weight / total;
+        }
+    }
+}
+
+fn read_indices(prim: &Prim, name: &str) -> Vec<usize> {
+    prim.value(name)
+        .and_then(|values| values.map_array(|v| v.as_i32().map(|i| i.max(0) as usize)))
+        .unwrap_or_default()
+}
+
+fn build_surface_data<T>(
+    vertices: Vec<AnimatedVertex>,
+    convert: impl Fn(AnimatedVertex) -> T,
+) -> SurfaceData
+where
+    T: VertexTrait + Hash + PartialEq,
+{
+    let mut builder = RawMeshBuilder::<T>::new(vertices.len(), vertices.len());
+    for vertex in vertices {
+        builder.insert(convert(vertex));
+    }
+    SurfaceData::from_raw_mesh(builder.build())
+}
+
+/// Returns texture coordinates of the mesh. There is no standard name for them, so the most
+/// common names are checked first and then any other `texCoord2f` primvar is used.
+fn uv_primvar(prim: &Prim, sizes: (usize, usize, usize)) -> Option<Primvar<Vector2<f32>>> {
+    for name in [
+        "primvars:st",
+        "primvars:st0",
+        "primvars:UVMap",
+        "primvars:uv",
+    ] {
+        if let Some(primvar) = Primvar::read(prim, name, sizes, Value::as_vec2) {
+            return Some(primvar);
+        }
+    }
+    let name = prim
+        .attributes
+        .iter()
+        .filter(|(name, attribute)| {
+            name.starts_with("primvars:")
+                && !name.ends_with(":indices")
+                && attribute.type_name.starts_with("texCoord2")
+        })
+        .map(|(name, _)| name)
+        .min()?;
+    Primvar::read(prim, name, sizes, Value::as_vec2)
+}
+
+/// Creates a material from `displayColor` primvar, that is used by meshes without materials.
+fn display_color_material(prim: &Prim) -> Option<MaterialResource> {
+    let color = prim.value("primvars:displayColor")?;
+    let color = match color.as_array() {
+        Some(colors) => colors.first()?.as_vec3(),
+        None => color.as_vec3(),
+    }?;
+    let mut material = Material::standard();
+    material.set_property(
+        "diffuseColor",
+        MaterialProperty::Color(Color::from(color).linear_to_srgb()),
+    );
+    Some(MaterialResource::new_ok(ResourceKind::Embedded, material))
+}
+
+/// Creates a light node for a light prim. USD lights shine along -Z axis of their prim, while the
+/// engine lights shine along -Y axis, so the light is created as a separate child node with a
+/// rotation that maps one to another.
+fn convert_light(prim: &Prim, parent: Handle<Node>, graph: &mut Graph) {
+    // Older versions of USD use names without `inputs:` prefix.
+    let input = |name: &str| {
+        prim.value(&format!("inputs:{name}"))
+            .or_else(|| prim.value(name))
+            .and_then(|value| value.as_f32().or_else(|| value.as_vec3().map(|v| v.x)))
+    };
+    let color = prim
+        .value("inputs:color")
+        .or_else(|| prim.value("color"))
+        .and_then(|color| color.as_vec3())
+        .unwrap_or_else(|| Vector3::repeat(1.0));
+    let intensity =
+        input("intensity").unwrap_or(1.0) * 2.0f32.powf(input("exposure").unwrap_or(0.0));
+
+    let base_builder = BaseBuilder::new()
+        .with_name(format!("{}_Light", prim.name))
+        .with_local_transform(
+            TransformBuilder::new()
+                .with_local_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::x_axis(),
+                    90.0f32.to_radians(),
+                ))
+                .build(),
+        );
+    let base_light_builder = BaseLightBuilder::new(base_builder)
+        .with_color(Color::from(color).linear_to_srgb())
+        .with_intensity(intensity.max(0.0));
+
+    let light = if prim.type_name == "DistantLight" {
+        DirectionalLightBuilder::new(base_light_builder).build_node()
+    } else if let Some(angle) = prim.value("shaping:cone:angle").and_then(|a| a.as_f32()) {
+        // Cone angle is measured from the center of the cone, the engine uses full angles.
+        let softness = prim
+            .value("shaping:cone:softness")
+            .and_then(|s| s.as_f32())
+            .unwrap_or_default()
+            .clamp(0.0, 1.0);
+        let angle = angle.to_radians();
+        SpotLightBuilder::new(base_light_builder)
+            .with_hotspot_cone_angle(2.0 * angle * (1.0 - softness))
+            .with_falloff_angle_delta(2.0 * angle * softness)
+            .with_distance(DEFAULT_LIGHT_RANGE)
+            .build_node()
+    } else {
+        PointLightBuilder::new(base_light_builder)
+            .with_radius(DEFAULT_LIGHT_RANGE)
+            .build_node()
+    };
+
+    let handle = graph.add_node(light);
+    graph.link_nodes(handle, parent);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_xform_ops() {
+        let layer = parse_layer(
+            r#"def Xform "A" {
+                double3 xformOp:translate = (1, 2, 3)
+                float xformOp:rotateY = 90
+                float3 xformOp:scale = (2, 2, 2)
+                uniform token[] xformOpOrder = ["xformOp:translate", "xformOp:rotateY", "xformOp:scale"]
+            }"#,
+        )
+        .unwrap();
+        let matrix = local_transform(&layer.prims[0], None);
+        let (translation, rotation, scale) = decompose(&matrix);
+        assert!((translation - Vector3::new(1.0, 2.0, 3.0)).norm() < 1.0e-5);
+        assert!((scale - Vector3::repeat(2.0)).norm() < 1.0e-5);
+        let expected = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 90.0f32.to_radians());
+        assert!(rotation.angle_to(&expected) < 1.0e-4);
+
+        // Scale is applied first, then rotation, then translation.
+        let point = matrix.transform_point(&Vector3::new(1.0, 0.0, 0.0).into());
+        assert!((point.coords - Vector3::new(1.0, 2.0, 1.0)).norm() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_property_owner() {
+        assert_eq!(
+            property_owner("/Root/Material/Shader.outputs:surface"),
+            "/Root/Material/Shader"
+        );
+        assert_eq!(property_owner("/Root/Material"), "/Root/Material");
+        assert_eq!(parent_path("/Root/Material"), Some("/Root"));
+        assert_eq!(parent_path("/Root"), None);
+    }
+}
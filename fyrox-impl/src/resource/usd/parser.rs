@@ -0,0 +1,892 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Parser of the text representation of USD layers (`.usda`). The parser builds a plain tree of
+//! prims with their attributes and relationships, composition arcs (references, payloads,
+//! variants, sublayers) are not resolved.
+
+use crate::{
+    core::algebra::{Matrix4, Vector2, Vector3, Vector4},
+    fxhash::FxHashMap,
+};
+use std::fmt::{Display, Formatter};
+
+/// An error that may occur during parsing of a USD layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsdParseError {
+    /// Line at which the error has occurred.
+    pub line: usize,
+    /// Description of the error.
+    pub message: String,
+}
+
+impl Display for UsdParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "USD parse error at line {}: {}", self.line, self.message)
+    }
+}
+
+/// A value of an attribute or a metadata entry.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// Explicitly blocked value (`None`).
+    None,
+    /// Boolean value.
+    Bool(bool),
+    /// Any numeric value.
+    Number(f64),
+    /// Strings and tokens.
+    String(String),
+    /// An identifier, that is used as a value (for example, enumeration values in metadata).
+    Identifier(String),
+    /// Asset path (`@path@`).
+    AssetPath(String),
+    /// Prim or property path (`</Path>`).
+    Path(String),
+    /// Tuple of values, for example `(1, 2, 3)`.
+    Tuple(Vec<Value>),
+    /// Array of values, for example `[1, 2, 3]`.
+    Array(Vec<Value>),
+    /// Dictionary. Its content is not parsed.
+    Dictionary,
+}
+
+impl Value {
+    /// Tries to get the value as a number.
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Value::Number(value) => Some(*value as f32),
+            Value::Bool(value) => Some(if *value { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+
+    /// Tries to get the value as an integer.
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            Value::Number(value) => Some(*value as i32),
+            _ => None,
+        }
+    }
+
+    /// Tries to get the value as a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value)
+            | Value::Identifier(value)
+            | Value::AssetPath(value)
+            | Value::Path(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Tries to get the value as a slice of values.
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) | Value::Tuple(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    fn as_components<const N: usize>(&self) -> Option<[f32; N]> {
+        let values = self.as_array()?;
+        if values.len() != N {
+            return None;
+        }
+        let mut result = [0.0; N];
+        for (dest, value) in result.iter_mut().zip(values) {
+            *dest = value.as_f32()?;
+        }
+        Some(result)
+    }
+
+    /// Tries to get the value as a 2D vector.
+    pub fn as_vec2(&self) -> Option<Vector2<f32>> {
+        self.as_components::<2>().map(Vector2::from)
+    }
+
+    /// Tries to get the value as a 3D vector.
+    pub fn as_vec3(&self) -> Option<Vector3<f32>> {
+        self.as_components::<3>().map(Vector3::from)
+    }
+
+    /// Tries to get the value as a 4D vector. Keep in mind, that quaternions are stored in
+    /// `(real, i, j, k)` order.
+    pub fn as_vec4(&self) -> Option<Vector4<f32>> {
+        self.as_components::<4>().map(Vector4::from)
+    }
+
+    /// Tries to get the value as a matrix. USD uses row vectors, so the matrix is transposed to
+    /// match the engine's convention.
+    pub fn as_matrix4(&self) -> Option<Matrix4<f32>> {
+        let rows = self.as_array()?;
+        if rows.len() != 4 {
+            return None;
+        }
+        let mut matrix = Matrix4::identity();
+        for (i, row) in rows.iter().enumerate() {
+            let row = row.as_vec4()?;
+            for j in 0..4 {
+                // Row `i` of USD matrix becomes column `i`.
+                matrix[(j, i)] = row[j];
+            }
+        }
+        Some(matrix)
+    }
+
+    /// Tries to convert every element of an array using the given function.
+    pub fn map_array<T>(&self, func: impl Fn(&Value) -> Option<T>) -> Option<Vec<T>> {
+        self.as_array()?.iter().map(func).collect()
+    }
+
+    /// Linearly interpolates between two values. Non-numeric values are not interpolated.
+    pub fn lerp(&self, other: &Value, t: f64) -> Value {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => Value::Number(a + (b - a) * t),
+            (Value::Tuple(a), Value::Tuple(b)) if a.len() == b.len() => {
+                Value::Tuple(a.iter().zip(b).map(|(a, b)| a.lerp(b, t)).collect())
+            }
+            (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+                Value::Array(a.iter().zip(b).map(|(a, b)| a.lerp(b, t)).collect())
+            }
+            _ => self.clone(),
+        }
+    }
+}
+
+/// An attribute of a prim.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Attribute {
+    /// Type name of the attribute, for example `float3[]`.
+    pub type_name: String,
+    /// Default value of the attribute.
+    pub value: Option<Value>,
+    /// Time samples of the attribute, sorted by time.
+    pub time_samples: Vec<(f64, Value)>,
+    /// Path of the connected property (`.connect`).
+    pub connection: Option<String>,
+    /// Metadata of the attribute (`interpolation`, `elementSize`, etc.).
+    pub metadata: FxHashMap<String, Value>,
+}
+
+impl Attribute {
+    /// Returns the value of the attribute at the given time. If the time is not specified, the
+    /// default value is returned (or the first time sample if there is no default value).
+    pub fn sample(&self, time: Option<f64>) -> Option<Value> {
+        let Some(time) = time else {
+            return self
+                .value
+                .clone()
+                .or_else(|| self.time_samples.first().map(|(_, value)| value.clone()));
+        };
+
+        if self.time_samples.is_empty() {
+            return self.value.clone();
+        }
+
+        let next = self.time_samples.partition_point(|(t, _)| *t <= time);
+        if next == 0 {
+            return Some(self.time_samples[0].1.clone());
+        }
+        let (prev_time, prev) = &self.time_samples[next - 1];
+        match self.time_samples.get(next) {
+            Some((next_time, next)) => {
+                let t = (time - prev_time) / (next_time - prev_time);
+                Some(prev.lerp(next, t))
+            }
+            None => Some(prev.clone()),
+        }
+    }
+
+    /// Returns the value of the given metadata entry as string.
+    pub fn metadata_str(&self, name: &str) -> Option<&str> {
+        self.metadata.get(name).and_then(|value| value.as_str())
+    }
+}
+
+/// Specifier of a prim.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Specifier {
+    /// `def` - defines a prim.
+    #[default]
+    Def,
+    /// `over` - overrides a prim defined somewhere else.
+    Over,
+    /// `class` - defines an abstract prim.
+    Class,
+}
+
+/// A prim of a USD layer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Prim {
+    /// Specifier of the prim.
+    pub specifier: Specifier,
+    /// Type of the prim (`Xform`, `Mesh`, etc.), could be empty.
+    pub type_name: String,
+    /// Name of the prim.
+    pub name: String,
+    /// Metadata of the prim.
+    pub metadata: FxHashMap<String, Value>,
+    /// Attributes of the prim.
+    pub attributes: FxHashMap<String, Attribute>,
+    /// Relationships of the prim with the paths of their targets.
+    pub relationships: FxHashMap<String, Vec<String>>,
+    /// Child prims.
+    pub children: Vec<Prim>,
+}
+
+impl Prim {
+    /// Returns the attribute with the given name.
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.get(name)
+    }
+
+    /// Returns the default value of the attribute with the given name.
+    pub fn value(&self, name: &str) -> Option<Value> {
+        self.attributes.get(name)?.sample(None)
+    }
+
+    /// Returns the first target of the relationship with the given name.
+    pub fn relationship(&self, name: &str) -> Option<&str> {
+        self.relationships
+            .get(name)
+            .and_then(|targets| targets.first())
+            .map(|target| target.as_str())
+    }
+}
+
+/// A parsed USD layer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Layer {
+    /// Metadata of the layer (`upAxis`, `metersPerUnit`, `timeCodesPerSecond`, etc.).
+    pub metadata: FxHashMap<String, Value>,
+    /// Root prims of the layer.
+    pub prims: Vec<Prim>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    String(String),
+    Number(f64),
+    AssetPath(String),
+    Path(String),
+    Punct(char),
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | ':' | '.' | '!')
+}
+
+impl Lexer<'_> {
+    fn error(&self, message: impl Into<String>) -> UsdParseError {
+        UsdParseError {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn read_while(&mut self, first: char, predicate: impl Fn(char) -> bool) -> String {
+        let mut string = first.to_string();
+        while let Some(&c) = self.chars.peek() {
+            if !predicate(c) {
+                break;
+            }
+            string.push(c);
+            self.next_char();
+        }
+        string
+    }
+
+    fn read_delimited(&mut self, end: char) -> Result<String, UsdParseError> {
+        let mut string = String::new();
+        loop {
+            match self.next_char() {
+                Some(c) if c == end => return Ok(string),
+                Some(c) => string.push(c),
+                None => return Err(self.error(format!("Expected {end}"))),
+            }
+        }
+    }
+
+    fn read_string(&mut self, quote: char) -> Result<String, UsdParseError> {
+        let mut string = String::new();
+
+        // Triple-quoted strings could span multiple lines.
+        let mut triple = false;
+        if self.chars.peek() == Some(&quote) {
+            self.next_char();
+            if self.chars.peek() == Some(&quote) {
+                self.next_char();
+                triple = true;
+            } else {
+                // Empty string.
+                return Ok(string);
+            }
+        }
+
+        loop {
+            match self.next_char() {
+                Some('\\') => match self.next_char() {
+                    Some('n') => string.push('\n'),
+                    Some('t') => string.push('\t'),
+                    Some(c) => string.push(c),
+                    None => break,
+                },
+                Some(c) if c == quote => {
+                    if !triple {
+                        return Ok(string);
+                    }
+                    if self.chars.peek() == Some(&quote) {
+                        self.next_char();
+                        if self.chars.peek() == Some(&quote) {
+                            self.next_char();
+                            return Ok(string);
+                        }
+                        string.push(quote);
+                    }
+                    string.push(quote);
+                }
+                Some(c) => string.push(c),
+                None => break,
+            }
+        }
+
+        Err(self.error("Unterminated string"))
+    }
+
+    fn number(&self, string: &str) -> Result<Token, UsdParseError> {
+        string
+            .parse::<f64>()
+            .map(Token::Number)
+            .map_err(|_| self.error(format!("Invalid number {string}")))
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(Token, usize)>, UsdParseError> {
+        let mut tokens = Vec::new();
+        while let Some(c) = self.next_char() {
+            let line = self.line;
+            let token = match c {
+                c if c.is_whitespace() => continue,
+                '#' => {
+                    while let Some(&c) = self.chars.peek() {
+                        if c == '\n' {
+                            break;
+                        }
+                        self.next_char();
+                    }
+                    continue;
+                }
+                '"' | '\'' => Token::String(self.read_string(c)?),
+                '@' => Token::AssetPath(self.read_delimited('@')?),
+                '<' => Token::Path(self.read_delimited('>')?),
+                '(' | ')' | '[' | ']' | '{' | '}' | '=' | ',' | ';' => Token::Punct(c),
+                ':' => Token::Punct(c),
+                c if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') => {
+                    let string = self.read_while(c, |c| {
+                        c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')
+                    });
+                    self.number(&string)?
+                }
+                c if is_identifier_char(c) => {
+                    Token::Identifier(self.read_while(c, is_identifier_char))
+                }
+                c => return Err(self.error(format!("Unexpected character {c}"))),
+            };
+            tokens.push((token, line));
+        }
+        Ok(tokens)
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+}
+
+impl Parser {
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or_else(|| self.tokens.last())
+            .map(|(_, line)| *line)
+            .unwrap_or_default()
+    }
+
+    fn error(&self, message: impl Into<String>) -> UsdParseError {
+        UsdParseError {
+            line: self.line(),
+            message: message.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Result<Token, UsdParseError> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .map(|(token, _)| token.clone())
+            .ok_or_else(|| self.error("Unexpected end of file"))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    fn is_identifier(&self, name: &str) -> bool {
+        matches!(self.peek(), Some(Token::Identifier(identifier)) if identifier == name)
+    }
+
+    fn expect_punct(&mut self, c: char) -> Result<(), UsdParseError> {
+        match self.next()? {
+            Token::Punct(p) if p == c => Ok(()),
+            token => Err(self.error(format!("Expected {c}, got {token:?}"))),
+        }
+    }
+
+    fn expect_identifier(&mut self) -> Result<String, UsdParseError> {
+        match self.next()? {
+            Token::Identifier(identifier) => Ok(identifier),
+            token => Err(self.error(format!("Expected identifier, got {token:?}"))),
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while self.is_punct(';') || self.is_punct(',') {
+            self.position += 1;
+        }
+    }
+
+    fn skip_balanced(&mut self, open: char, close: char) -> Result<(), UsdParseError> {
+        self.expect_punct(open)?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next()? {
+                Token::Punct(c) if c == open => depth += 1,
+                Token::Punct(c) if c == close => depth -= 1,
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_sequence(&mut self, close: char) -> Result<Vec<Value>, UsdParseError> {
+        let mut values = Vec::new();
+        loop {
+            self.skip_separators();
+            if self.is_punct(close) {
+                self.position += 1;
+                return Ok(values);
+            }
+            values.push(self.parse_value()?);
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, UsdParseError> {
+        Ok(match self.next()? {
+            Token::Number(value) => Value::Number(value),
+            Token::String(value) => Value::String(value),
+            Token::AssetPath(value) => {
+                // References could be specified as an asset path with a prim path.
+                if let Some(Token::Path(_)) = self.peek() {
+                    self.position += 1;
+                }
+                Value::AssetPath(value)
+            }
+            Token::Path(value) => Value::Path(value),
+            Token::Identifier(identifier) => match identifier.as_str() {
+                "None" => Value::None,
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "inf" => Value::Number(f64::INFINITY),
+                "nan" => Value::Number(f64::NAN),
+                _ => Value::Identifier(identifier),
+            },
+            Token::Punct('(') => Value::Tuple(self.parse_sequence(')')?),
+            Token::Punct('[') => Value::Array(self.parse_sequence(']')?),
+            Token::Punct('{') => {
+                self.position -= 1;
+                self.skip_balanced('{', '}')?;
+                Value::Dictionary
+            }
+            token => return Err(self.error(format!("Unexpected token {token:?}"))),
+        })
+    }
+
+    fn parse_metadata(&mut self) -> Result<FxHashMap<String, Value>, UsdParseError> {
+        let mut metadata = FxHashMap::default();
+        self.expect_punct('(')?;
+        loop {
+            self.skip_separators();
+            match self.next()? {
+                Token::Punct(')') => return Ok(metadata),
+                // Documentation string.
+                Token::String(doc) => {
+                    metadata.insert("doc".to_string(), Value::String(doc));
+                }
+                Token::Identifier(mut key) => {
+                    if matches!(
+                        key.as_str(),
+                        "prepend" | "append" | "add" | "delete" | "reorder"
+                    ) {
+                        key = self.expect_identifier()?;
+                    }
+                    if self.is_punct('=') {
+                        self.position += 1;
+                        let value = self.parse_value()?;
+                        metadata.insert(key, value);
+                    }
+                }
+                token => return Err(self.error(format!("Unexpected token {token:?}"))),
+            }
+        }
+    }
+
+    fn parse_time_samples(&mut self) -> Result<Vec<(f64, Value)>, UsdParseError> {
+        let mut samples = Vec::new();
+        self.expect_punct('{')?;
+        loop {
+            self.skip_separators();
+            match self.next()? {
+                Token::Punct('}') => break,
+                Token::Number(time) => {
+                    self.expect_punct(':')?;
+                    samples.push((time, self.parse_value()?));
+                }
+                token => return Err(self.error(format!("Unexpected token {token:?}"))),
+            }
+        }
+        samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(samples)
+    }
+
+    fn parse_targets(&mut self) -> Result<Vec<String>, UsdParseError> {
+        let value = self.parse_value()?;
+        Ok(match value {
+            Value::Path(path) => vec![path],
+            Value::Array(paths) => paths
+                .into_iter()
+                .filter_map(|path| match path {
+                    Value::Path(path) => Some(path),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        })
+    }
+
+    fn parse_relationship(&mut self, prim: &mut Prim) -> Result<(), UsdParseError> {
+        let name = self.expect_identifier()?;
+        let name = name.strip_suffix(".default").unwrap_or(&name).to_string();
+        let targets = if self.is_punct('=') {
+            self.position += 1;
+            self.parse_targets()?
+        } else {
+            Vec::new()
+        };
+        if self.is_punct('(') {
+            self.parse_metadata()?;
+        }
+        prim.relationships.entry(name).or_default().extend(targets);
+        Ok(())
+    }
+
+    fn parse_attribute(&mut self, first: String, prim: &mut Prim) -> Result<(), UsdParseError> {
+        let mut type_name = first;
+        while matches!(
+            type_name.as_str(),
+            "custom" | "uniform" | "varying" | "config"
+        ) {
+            type_name = self.expect_identifier()?;
+        }
+        if self.is_punct('[') {
+            self.expect_punct('[')?;
+            self.expect_punct(']')?;
+            type_name.push_str("[]");
+        }
+
+        let full_name = self.expect_identifier()?;
+        let (name, suffix) = match full_name.rsplit_once('.') {
+            Some((name, suffix)) if matches!(suffix, "timeSamples" | "connect" | "spline") => {
+                (name.to_string(), suffix)
+            }
+            _ => (full_name.clone(), ""),
+        };
+
+        let attribute = prim.attributes.entry(name).or_default();
+        attribute.type_name = type_name;
+
+        if self.is_punct('=') {
+            self.position += 1;
+            match suffix {
+                "timeSamples" => attribute.time_samples = self.parse_time_samples()?,
+                "connect" => {
+                    attribute.connection = self.parse_targets()?.into_iter().next();
+                }
+                "spline" => self.skip_balanced('{', '}')?,
+                _ => attribute.value = Some(self.parse_value()?),
+            }
+        }
+
+        if self.is_punct('(') {
+            let metadata = self.parse_metadata()?;
+            attribute.metadata.extend(metadata);
+        }
+
+        Ok(())
+    }
+
+    fn parse_prim(&mut self, specifier: &str) -> Result<Prim, UsdParseError> {
+        let mut prim = Prim {
+            specifier: match specifier {
+                "over" => Specifier::Over,
+                "class" => Specifier::Class,
+                _ => Specifier::Def,
+            },
+            ..Default::default()
+        };
+
+        if let Some(Token::Identifier(_)) = self.peek() {
+            prim.type_name = self.expect_identifier()?;
+        }
+
+        prim.name = match self.next()? {
+            Token::String(name) => name,
+            token => return Err(self.error(format!("Expected prim name, got {token:?}"))),
+        };
+
+        if self.is_punct('(') {
+            prim.metadata = self.parse_metadata()?;
+        }
+
+        self.expect_punct('{')?;
+        loop {
+            self.skip_separators();
+            match self.next()? {
+                Token::Punct('}') => break,
+                Token::Identifier(identifier) => match identifier.as_str() {
+                    "def" | "over" | "class" => {
+                        let child = self.parse_prim(&identifier)?;
+                        prim.children.push(child);
+                    }
+                    "variantSet" => {
+                        self.next()?;
+                        self.expect_punct('=')?;
+                        self.skip_balanced('{', '}')?;
+                    }
+                    "reorder" => {
+                        self.expect_identifier()?;
+                        self.expect_punct('=')?;
+                        self.parse_value()?;
+                    }
+                    "rel" => self.parse_relationship(&mut prim)?,
+                    "prepend" | "append" | "add" | "delete" if self.is_identifier("rel") => {
+                        self.position += 1;
+                        self.parse_relationship(&mut prim)?;
+                    }
+                    "custom" if self.is_identifier("rel") => {
+                        self.position += 1;
+                        self.parse_relationship(&mut prim)?;
+                    }
+                    _ => self.parse_attribute(identifier, &mut prim)?,
+                },
+                token => return Err(self.error(format!("Unexpected token {token:?}"))),
+            }
+        }
+
+        Ok(prim)
+    }
+}
+
+/// Parses the text representation of a USD layer.
+pub fn parse_layer(text: &str) -> Result<Layer, UsdParseError> {
+    let lexer = Lexer {
+        chars: text.chars().peekable(),
+        line: 1,
+    };
+    let mut parser = Parser {
+        tokens: lexer.tokenize()?,
+        position: 0,
+    };
+
+    let mut layer = Layer::default();
+    if parser.is_punct('(') {
+        layer.metadata = parser.parse_metadata()?;
+    }
+
+    while parser.peek().is_some() {
+        parser.skip_separators();
+        let specifier = parser.expect_identifier()?;
+        if !matches!(specifier.as_str(), "def" | "over" | "class") {
+            return Err(parser.error(format!("Unexpected {specifier}")));
+        }
+        let prim = parser.parse_prim(&specifier)?;
+        layer.prims.push(prim);
+    }
+
+    Ok(layer)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LAYER: &str = r#"#usda 1.0
+(
+    defaultPrim = "Root"
+    upAxis = "Z"
+    timeCodesPerSecond = 30
+)
+
+def Xform "Root" (
+    kind = "component"
+)
+{
+    double3 xformOp:translate = (1, 2, 3)
+    double3 xformOp:translate.timeSamples = {
+        0: (0, 0, 0),
+        10: (10, 0, 0),
+    }
+    uniform token[] xformOpOrder = ["xformOp:translate"]
+
+    def Mesh "Quad" (
+        prepend apiSchemas = ["MaterialBindingAPI"]
+    )
+    {
+        int[] faceVertexCounts = [4]
+        int[] faceVertexIndices = [0, 1, 2, 3]
+        point3f[] points = [(-1, 0, -1), (1, 0, -1), (1, 0, 1), (-1, 0, 1)]
+        texCoord2f[] primvars:st = [(0, 0), (1, 0), (1, 1), (0, 1)] (
+            interpolation = "vertex"
+        )
+        rel material:binding = </Root/Material>
+    }
+
+    def Material "Material"
+    {
+        token outputs:surface.connect = </Root/Material/Shader.outputs:surface>
+
+        def Shader "Shader"
+        {
+            uniform token info:id = "UsdPreviewSurface"
+            color3f inputs:diffuseColor = (0.5, 0.25, 1)
+            asset inputs:file = @textures/albedo.png@
+        }
+    }
+}
+"#;
+
+    #[test]
+    fn test_parse_layer() {
+        let layer = parse_layer(LAYER).unwrap();
+        assert_eq!(
+            layer.metadata.get("upAxis"),
+            Some(&Value::String("Z".to_string()))
+        );
+        assert_eq!(layer.prims.len(), 1);
+
+        let root = &layer.prims[0];
+        assert_eq!(root.type_name, "Xform");
+        assert_eq!(root.name, "Root");
+        assert_eq!(root.children.len(), 2);
+
+        let translate = root.attribute("xformOp:translate").unwrap();
+        assert_eq!(
+            translate.value.as_ref().unwrap().as_vec3(),
+            Some(Vector3::new(1.0, 2.0, 3.0))
+        );
+        assert_eq!(translate.time_samples.len(), 2);
+        assert_eq!(
+            translate.sample(Some(5.0)).unwrap().as_vec3(),
+            Some(Vector3::new(5.0, 0.0, 0.0))
+        );
+
+        let mesh = &root.children[0];
+        assert_eq!(
+            mesh.relationship("material:binding"),
+            Some("/Root/Material")
+        );
+        let st = mesh.attribute("primvars:st").unwrap();
+        assert_eq!(st.metadata_str("interpolation"), Some("vertex"));
+        assert_eq!(
+            mesh.value("points")
+                .unwrap()
+                .map_array(|v| v.as_vec3())
+                .unwrap()
+                .len(),
+            4
+        );
+
+        let material = &root.children[1];
+        assert_eq!(
+            material
+                .attribute("outputs:surface")
+                .unwrap()
+                .connection
+                .as_deref(),
+            Some("/Root/Material/Shader.outputs:surface")
+        );
+        let shader = &material.children[0];
+        assert_eq!(
+            shader.value("inputs:file"),
+            Some(Value::AssetPath("textures/albedo.png".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_matrix() {
+        let layer = parse_layer(
+            "def Xform \"A\" { matrix4d xformOp:transform = ( (1, 0, 0, 0), (0, 1, 0, 0), (0, 0, 1, 0), (4, 5, 6, 1) ) }",
+        )
+        .unwrap();
+        let matrix = layer.prims[0]
+            .value("xformOp:transform")
+            .unwrap()
+            .as_matrix4()
+            .unwrap();
+        assert_eq!(matrix[(0, 3)], 4.0);
+        assert_eq!(matrix[(1, 3)], 5.0);
+        assert_eq!(matrix[(2, 3)], 6.0);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse_layer("def Xform \"A\" {\n  float x = \n}").unwrap_err();
+        assert_eq!(error.line, 3);
+    }
+}
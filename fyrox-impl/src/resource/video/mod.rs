@@ -0,0 +1,641 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Video playback. A video is a sequence of frames decoded on demand by a [`VideoDecoder`], that is
+//! streamed into a texture by [`VideoPlayer`]. The texture could then be used by any material, for
+//! example on a mesh or on a [`crate::scene::dim2::rectangle::Rectangle`] node. See [`Video`] and
+//! [`VideoPlayer`] docs for more info.
+
+use crate::{
+    asset::{
+        io::ResourceIo, manager::ResourceManager, untyped::ResourceKind, Resource, ResourceData,
+    },
+    core::{
+        io::FileLoadError, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
+        visitor::prelude::*,
+    },
+    graph::SceneGraph,
+    material::MaterialResource,
+    resource::texture::{
+        Texture, TextureKind, TextureMagnificationFilter, TextureMinificationFilter,
+        TexturePixelKind, TextureResource, TextureWrapMode,
+    },
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        node::Node,
+        sound::{Sound, SoundBuffer, SoundBuilder, Status},
+    },
+};
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+pub mod loader;
+
+/// An error that may occur during video loading or decoding.
+#[derive(Debug)]
+pub enum VideoError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+    /// A video descriptor is malformed.
+    Descriptor(ron::error::SpannedError),
+    /// A frame cannot be decoded.
+    Decode(String),
+    /// A video has no frames.
+    NoFrames,
+    /// A video resource is not loaded yet (or failed to load).
+    NotLoaded,
+}
+
+impl Display for VideoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VideoError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            VideoError::Descriptor(v) => {
+                write!(f, "Video descriptor is malformed. Reason: {v}")
+            }
+            VideoError::Decode(v) => {
+                write!(f, "Unable to decode a video frame. Reason: {v}")
+            }
+            VideoError::NoFrames => {
+                write!(f, "Video has no frames.")
+            }
+            VideoError::NotLoaded => {
+                write!(f, "Video resource is not loaded.")
+            }
+        }
+    }
+}
+
+impl Error for VideoError {}
+
+impl From<FileLoadError> for VideoError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for VideoError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::Descriptor(e)
+    }
+}
+
+impl From<image::ImageError> for VideoError {
+    fn from(e: image::ImageError) -> Self {
+        Self::Decode(e.to_string())
+    }
+}
+
+/// Basic information about a video stream.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct VideoInfo {
+    /// Width of every frame in pixels.
+    pub width: u32,
+    /// Height of every frame in pixels.
+    pub height: u32,
+    /// Amount of frames per second.
+    pub frame_rate: f32,
+    /// Total amount of frames.
+    pub frame_count: usize,
+}
+
+impl VideoInfo {
+    /// Returns total duration of the video in seconds.
+    pub fn duration(&self) -> f32 {
+        self.frame_count as f32 / self.frame_rate
+    }
+
+    /// Returns an index of the frame that should be shown at the given time (in seconds).
+    pub fn frame_at(&self, time: f32) -> usize {
+        ((time.max(0.0) * self.frame_rate) as usize).min(self.frame_count.saturating_sub(1))
+    }
+}
+
+/// Video decoder is responsible for providing frames of a video. The engine has only one built-in
+/// decoder ([`ImageSequenceDecoder`]), other codecs could be plugged in by implementing this trait
+/// and creating a video using [`Video::from_decoder`].
+pub trait VideoDecoder: Send + Debug + 'static {
+    /// Returns basic information about the video stream.
+    fn info(&self) -> VideoInfo;
+
+    /// Decodes a frame with the given index. The result must be tightly packed RGBA8 pixels with
+    /// the size defined by [`VideoInfo`].
+    fn decode_frame(&mut self, index: usize) -> Result<Vec<u8>, VideoError>;
+}
+
+/// A decoder that treats a sequence of compressed images (png, jpg, etc.) as video frames. Frames are
+/// kept compressed in memory and decoded only when they're needed.
+#[derive(Debug)]
+pub struct ImageSequenceDecoder {
+    frames: Vec<Vec<u8>>,
+    info: VideoInfo,
+}
+
+impl ImageSequenceDecoder {
+    /// Creates new decoder from the given compressed frames. The size of the video is defined by the
+    /// first frame, every other frame must have the same size.
+    pub fn new(frames: Vec<Vec<u8>>, frame_rate: f32) -> Result<Self, VideoError> {
+        let first = frames.first().ok_or(VideoError::NoFrames)?;
+        let image = image::load_from_memory(first)?;
+        Ok(Self {
+            info: VideoInfo {
+                width: image.width(),
+                height: image.height(),
+                frame_rate: frame_rate.max(f32::EPSILON),
+                frame_count: frames.len(),
+            },
+            frames,
+        })
+    }
+}
+
+impl VideoDecoder for ImageSequenceDecoder {
+    fn info(&self) -> VideoInfo {
+        self.info
+    }
+
+    fn decode_frame(&mut self, index: usize) -> Result<Vec<u8>, VideoError> {
+        let data = self
+            .frames
+            .get(index)
+            .ok_or_else(|| VideoError::Decode(format!("Frame {index} is out of bounds!")))?;
+        let image = image::load_from_memory(data)?.to_rgba8();
+        if image.width() != self.info.width || image.height() != self.info.height {
+            return Err(VideoError::Decode(format!(
+                "Frame {index} has different size than the first frame!"
+            )));
+        }
+        Ok(image.into_raw())
+    }
+}
+
+/// Contents of a video descriptor (`*.video` file).
+#[derive(Deserialize)]
+struct VideoDescriptor {
+    /// A folder with frames, relative to the descriptor. Frames are sorted by their file names.
+    frames: PathBuf,
+    frame_rate: f32,
+    /// Optional path to a sound file, relative to the descriptor.
+    #[serde(default)]
+    audio: Option<PathBuf>,
+}
+
+/// Video is a resource that provides frames for [`VideoPlayer`]. Videos are loaded from `*.video`
+/// files, that are RON descriptors of image sequences:
+///
+/// ```text
+/// (
+///     frames: "intro_frames",
+///     frame_rate: 30.0,
+///     audio: Some("intro.ogg"),
+/// )
+/// ```
+///
+/// Every image in the `frames` folder is a frame, frames are sorted by their file names. Any other
+/// codec could be used by implementing [`VideoDecoder`] and creating the video using
+/// [`Video::from_decoder`].
+#[derive(Debug, Default, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "4c0f8e2a-5d71-4b3e-9a6c-8e1f2d7b0c53")]
+pub struct Video {
+    #[visit(skip)]
+    #[reflect(hidden)]
+    decoder: Option<Box<dyn VideoDecoder>>,
+    audio: Option<PathBuf>,
+}
+
+impl Video {
+    /// Creates new video from the given decoder and an optional path to a sound file, that will be
+    /// played along with the video.
+    pub fn from_decoder(decoder: Box<dyn VideoDecoder>, audio: Option<PathBuf>) -> Self {
+        Self {
+            decoder: Some(decoder),
+            audio,
+        }
+    }
+
+    /// Tries to load a video from the given descriptor file.
+    pub async fn from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, VideoError> {
+        let bytes = io.load_file(path).await?;
+        let descriptor = ron::de::from_bytes::<VideoDescriptor>(&bytes)?;
+        let directory = path.parent().unwrap_or(Path::new(""));
+
+        let mut frame_paths = io
+            .read_directory(&directory.join(&descriptor.frames))
+            .await?
+            .collect::<Vec<_>>();
+        frame_paths.sort();
+
+        let mut frames = Vec::with_capacity(frame_paths.len());
+        for frame_path in frame_paths {
+            if !io.is_dir(&frame_path).await {
+                frames.push(io.load_file(&frame_path).await?);
+            }
+        }
+
+        Ok(Self::from_decoder(
+            Box::new(ImageSequenceDecoder::new(frames, descriptor.frame_rate)?),
+            descriptor.audio.map(|audio| directory.join(audio)),
+        ))
+    }
+
+    /// Returns basic information about the video stream.
+    pub fn info(&self) -> Option<VideoInfo> {
+        self.decoder.as_ref().map(|decoder| decoder.info())
+    }
+
+    /// Decodes a frame with the given index.
+    pub fn decode_frame(&mut self, index: usize) -> Result<Vec<u8>, VideoError> {
+        self.decoder
+            .as_mut()
+            .ok_or(VideoError::NoFrames)?
+            .decode_frame(index)
+    }
+
+    /// Returns a path to a sound file, that should be played along with the video.
+    pub fn audio(&self) -> Option<&Path> {
+        self.audio.as_deref()
+    }
+}
+
+impl ResourceData for Video {
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, _path: &Path) -> Result<(), Box<dyn Error>> {
+        Err("Saving is not supported!".to_string().into())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        false
+    }
+}
+
+/// Type alias for video resources.
+pub type VideoResource = Resource<Video>;
+
+/// Playback state of a [`VideoPlayer`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PlaybackState {
+    /// The video is stopped and rewound to the beginning.
+    #[default]
+    Stopped,
+    /// The video is playing.
+    Playing,
+    /// The video is paused at its current position.
+    Paused,
+}
+
+/// Video player streams frames of a [`Video`] into a texture. The texture could be bound to any
+/// material (see [`Self::bind_to_material`]), so the video could be shown on meshes, rectangles or
+/// UI images.
+///
+/// If the video has an audio track, it could be played by a sound node created by
+/// [`Self::attach_audio`] (or set by [`Self::set_sound`]). In this case the playback position of the
+/// sound is used as the clock of the video, which keeps the video in sync with its audio. Sound node
+/// is controlled by the player, so it should not be played or stopped manually.
+///
+/// The player must be updated every frame by calling [`Self::update`].
+#[derive(Debug)]
+pub struct VideoPlayer {
+    video: VideoResource,
+    info: VideoInfo,
+    texture: TextureResource,
+    sound: Handle<Node>,
+    state: PlaybackState,
+    position: f32,
+    speed: f32,
+    looping: bool,
+    current_frame: Option<usize>,
+    sound_needs_sync: bool,
+}
+
+impl VideoPlayer {
+    /// Creates new player for the given video. The video resource must be fully loaded, otherwise
+    /// [`VideoError::NotLoaded`] will be returned.
+    pub fn new(video: VideoResource) -> Result<Self, VideoError> {
+        let info = {
+            let state = video.state();
+            state
+                .data_ref()
+                .and_then(|video| video.info())
+                .ok_or(VideoError::NotLoaded)?
+        };
+
+        let mut texture = Texture::from_bytes(
+            TextureKind::Rectangle {
+                width: info.width,
+                height: info.height,
+            },
+            TexturePixelKind::RGBA8,
+            vec![0; info.width as usize * info.height as usize * 4],
+        )
+        .ok_or_else(|| VideoError::Decode("Invalid frame size!".to_string()))?;
+        texture.set_minification_filter(TextureMinificationFilter::Linear);
+        texture.set_magnification_filter(TextureMagnificationFilter::Linear);
+        texture.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+        texture.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+
+        Ok(Self {
+            video,
+            info,
+            texture: TextureResource::new_ok(ResourceKind::Embedded, texture),
+            sound: Handle::NONE,
+            state: PlaybackState::Stopped,
+            position: 0.0,
+            speed: 1.0,
+            looping: false,
+            current_frame: None,
+            sound_needs_sync: false,
+        })
+    }
+
+    /// Returns the video that is played by the player.
+    pub fn video(&self) -> &VideoResource {
+        &self.video
+    }
+
+    /// Returns basic information about the video stream.
+    pub fn info(&self) -> VideoInfo {
+        self.info
+    }
+
+    /// Returns a texture that contains the current frame of the video.
+    pub fn texture(&self) -> &TextureResource {
+        &self.texture
+    }
+
+    /// Binds the video texture to the given property of the material. For example, standard
+    /// materials use `diffuseTexture` property.
+    pub fn bind_to_material(&self, material: &MaterialResource, property: &str) {
+        material
+            .data_ref()
+            .bind(property, Some(self.texture.clone()));
+    }
+
+    /// Creates a sound node for the audio track of the video (if any) and makes it the clock of
+    /// the player. Returns a handle of the new node.
+    pub fn attach_audio(
+        &mut self,
+        resource_manager: &ResourceManager,
+        graph: &mut Graph,
+    ) -> Option<Handle<Node>> {
+        let path = self.video.state().data_ref()?.audio()?.to_path_buf();
+        let buffer = resource_manager.request::<SoundBuffer>(path);
+        let sound = SoundBuilder::new(BaseBuilder::new().with_name("VideoAudio"))
+            .with_buffer(Some(buffer))
+            .with_status(Status::Stopped)
+            .build(graph);
+        self.set_sound(sound);
+        Some(sound)
+    }
+
+    /// Sets a sound node that plays the audio track of the video. [`Handle::NONE`] could be used to
+    /// detach the sound.
+    pub fn set_sound(&mut self, sound: Handle<Node>) {
+        self.sound = sound;
+        self.sound_needs_sync = true;
+    }
+
+    /// Returns a handle of the sound node that plays the audio track of the video.
+    pub fn sound(&self) -> Handle<Node> {
+        self.sound
+    }
+
+    /// Starts (or resumes) the playback.
+    pub fn play(&mut self) {
+        self.state = PlaybackState::Playing;
+        self.sound_needs_sync = true;
+    }
+
+    /// Pauses the playback at its current position.
+    pub fn pause(&mut self) {
+        if self.state == PlaybackState::Playing {
+            self.state = PlaybackState::Paused;
+            self.sound_needs_sync = true;
+        }
+    }
+
+    /// Stops the playback and rewinds the video to the beginning.
+    pub fn stop(&mut self) {
+        self.state = PlaybackState::Stopped;
+        self.position = 0.0;
+        self.sound_needs_sync = true;
+    }
+
+    /// Returns current playback state.
+    pub fn state(&self) -> PlaybackState {
+        self.state
+    }
+
+    /// Moves the playback position to the given time (in seconds).
+    pub fn seek(&mut self, time: f32) {
+        self.position = time.clamp(0.0, self.info.duration());
+        self.sound_needs_sync = true;
+    }
+
+    /// Returns current playback position (in seconds).
+    pub fn position(&self) -> f32 {
+        self.position
+    }
+
+    /// Sets playback speed multiplier. The speed also changes the pitch of the audio track.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+        self.sound_needs_sync = true;
+    }
+
+    /// Returns playback speed multiplier.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Defines whether the video should start over when it ends or not.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+        self.sound_needs_sync = true;
+    }
+
+    /// Returns `true` if the video starts over when it ends.
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Returns an index of the frame that is currently shown.
+    pub fn current_frame(&self) -> Option<usize> {
+        self.current_frame
+    }
+
+    /// Applies the state of the player to the sound node. Returns `true` if the sound defines the
+    /// playback position.
+    fn sync_sound(&mut self, graph: &mut Graph) -> bool {
+        let Some(sound) = graph.try_get_mut_of_type::<Sound>(self.sound) else {
+            return false;
+        };
+
+        if self.sound_needs_sync {
+            sound.set_looping(self.looping);
+            sound.set_pitch(self.speed as f64);
+            sound.set_playback_time(self.position);
+            match self.state {
+                PlaybackState::Stopped => sound.stop(),
+                PlaybackState::Playing => sound.play(),
+                PlaybackState::Paused => sound.pause(),
+            }
+            self.sound_needs_sync = false;
+            false
+        } else if self.state == PlaybackState::Playing && sound.status() == Status::Playing {
+            // Audio is the clock, so the video is always in sync with it.
+            self.position = sound.playback_time();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Advances the playback and uploads new frame into the texture if needed. Must be called every
+    /// frame.
+    pub fn update(&mut self, dt: f32, graph: &mut Graph) {
+        let clocked_by_sound = self.sync_sound(graph);
+
+        if self.state == PlaybackState::Playing && !clocked_by_sound {
+            self.position += dt * self.speed;
+        }
+
+        let duration = self.info.duration();
+        if self.position >= duration {
+            if self.looping {
+                self.position %= duration;
+            } else {
+                self.position = duration;
+                self.state = PlaybackState::Stopped;
+                self.sound_needs_sync = true;
+            }
+        }
+
+        let frame = self.info.frame_at(self.position);
+        if self.current_frame != Some(frame) {
+            self.upload_frame(frame);
+        }
+    }
+
+    fn upload_frame(&mut self, index: usize) {
+        let frame = {
+            let mut state = self.video.state();
+            let Some(video) = state.data() else {
+                return;
+            };
+            match video.decode_frame(index) {
+                Ok(frame) => frame,
+                Err(err) => {
+                    crate::core::log::Log::err(format!(
+                        "Unable to decode frame {index} of a video. Reason: {err}"
+                    ));
+                    return;
+                }
+            }
+        };
+
+        let mut texture = self.texture.data_ref();
+        let mut data = texture.modify();
+        let destination = data.data_mut();
+        if destination.len() == frame.len() {
+            destination.copy_from_slice(&frame);
+        }
+        self.current_frame = Some(index);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::{ImageFormat, Rgba, RgbaImage};
+    use std::io::Cursor;
+
+    fn frame(value: u8) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        RgbaImage::from_pixel(2, 2, Rgba([value; 4]))
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    fn video(frame_count: u8) -> VideoResource {
+        let frames = (0..frame_count).map(frame).collect();
+        let decoder = ImageSequenceDecoder::new(frames, 2.0).unwrap();
+        VideoResource::new_ok(
+            ResourceKind::Embedded,
+            Video::from_decoder(Box::new(decoder), None),
+        )
+    }
+
+    #[test]
+    fn test_video_info() {
+        let video = video(3);
+        let info = video.data_ref().info().unwrap();
+        assert_eq!(info.width, 2);
+        assert_eq!(info.height, 2);
+        assert_eq!(info.duration(), 1.5);
+        assert_eq!(info.frame_at(0.6), 1);
+        assert_eq!(info.frame_at(10.0), 2);
+        assert!(ImageSequenceDecoder::new(Vec::new(), 1.0).is_err());
+    }
+
+    #[test]
+    fn test_video_player() {
+        let mut graph = Graph::new();
+        let mut player = VideoPlayer::new(video(3)).unwrap();
+
+        player.update(0.1, &mut graph);
+        assert_eq!(player.current_frame(), Some(0));
+        assert_eq!(player.position(), 0.0);
+
+        player.play();
+        player.update(0.6, &mut graph);
+        assert_eq!(player.current_frame(), Some(1));
+        assert_eq!(player.texture().data_ref().data(), &[1; 16]);
+
+        player.pause();
+        player.update(1.0, &mut graph);
+        assert_eq!(player.current_frame(), Some(1));
+
+        player.seek(1.2);
+        player.update(0.0, &mut graph);
+        assert_eq!(player.current_frame(), Some(2));
+
+        player.play();
+        player.update(1.0, &mut graph);
+        assert_eq!(player.state(), PlaybackState::Stopped);
+
+        player.set_looping(true);
+        player.stop();
+        player.play();
+        player.update(1.75, &mut graph);
+        assert_eq!(player.state(), PlaybackState::Playing);
+        assert_eq!(player.current_frame(), Some(0));
+    }
+}
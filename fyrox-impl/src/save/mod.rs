@@ -0,0 +1,972 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Save-game subsystem. It is separate from scene serialization and stores only the state that
+//! could be changed at runtime (scene "deltas"), instead of dumping whole scenes. See [`SaveGame`]
+//! and [`SaveSlots`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        log::Log,
+        pool::Handle,
+        uuid::Uuid,
+        visitor::{prelude::*, BinaryBlob},
+    },
+    fxhash::{FxHashMap, FxHashSet},
+    graph::{BaseSceneGraph, SceneGraph},
+    resource::model::{ModelResource, ModelResourceExtension},
+    scene::{base::SceneNodeId, node::Node, Scene},
+};
+use std::{
+    fmt::{Debug, Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+/// Extension of save files.
+pub const SAVE_EXTENSION: &str = "save";
+
+/// Extension of files with save metadata. Metadata is stored separately, so the list of saves
+/// could be shown without loading the saves.
+pub const SAVE_INFO_EXTENSION: &str = "saveinfo";
+
+/// Extension of save screenshots.
+pub const SAVE_SCREENSHOT_EXTENSION: &str = "png";
+
+/// A trait for entities (scripts and scene nodes) that have a state that should be stored in save
+/// files. The trait is used instead of [`Visit`], because save files usually need just a small
+/// portion of the data, and it must be versioned separately from the scene format.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox_impl::{core::visitor::prelude::*, save::SaveState};
+///
+/// struct Player {
+///     health: f32,
+///     ammo: u32,
+/// }
+///
+/// impl SaveState for Player {
+///     fn save_state_version(&self) -> u32 {
+///         1
+///     }
+///
+///     fn visit_save_state(&mut self, version: u32, visitor: &mut Visitor) -> VisitResult {
+///         self.health.visit("Health", visitor)?;
+///         // Ammo was added in the version 1.
+///         if version >= 1 {
+///             self.ammo.visit("Ammo", visitor)?;
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait SaveState {
+    /// Returns current version of the state layout. It should be increased every time when the
+    /// layout changes, the version is stored in save files and then passed to
+    /// [`SaveState::visit_save_state`] on load, so old saves could be migrated.
+    fn save_state_version(&self) -> u32 {
+        0
+    }
+
+    /// Reads or writes the state. `version` is the current version when saving, or the version
+    /// that was stored in a save file when loading.
+    fn visit_save_state(&mut self, version: u32, visitor: &mut Visitor) -> VisitResult;
+}
+
+/// An error that may occur during save or load.
+#[derive(Debug)]
+pub enum SaveError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// Unable to serialize or deserialize a save.
+    Visit(VisitError),
+    /// Unable to save a screenshot.
+    Image(image::ImageError),
+    /// Slot name is empty or contains path separators.
+    InvalidSlotName(String),
+    /// A save was made by a newer version of the game.
+    UnsupportedVersion {
+        /// Version of the save.
+        version: u32,
+        /// The latest supported version.
+        current: u32,
+    },
+    /// There is no migration from the given version to the next one.
+    MissingMigration(u32),
+    /// A migration has failed.
+    Migration {
+        /// Source version of the migration.
+        version: u32,
+        /// Reason of the failure.
+        reason: String,
+    },
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "I/O error: {err}"),
+            SaveError::Visit(err) => write!(f, "Serialization error: {err}"),
+            SaveError::Image(err) => write!(f, "Unable to save screenshot: {err}"),
+            SaveError::InvalidSlotName(name) => write!(f, "Invalid save slot name {name}!"),
+            SaveError::UnsupportedVersion { version, current } => write!(
+                f,
+                "Save version {version} is newer than the supported version {current}!"
+            ),
+            SaveError::MissingMigration(version) => {
+                write!(f, "There is no migration from save version {version}!")
+            }
+            SaveError::Migration { version, reason } => {
+                write!(f, "Unable to migrate save from version {version}: {reason}")
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<VisitError> for SaveError {
+    fn from(err: VisitError) -> Self {
+        Self::Visit(err)
+    }
+}
+
+impl From<image::ImageError> for SaveError {
+    fn from(err: image::ImageError) -> Self {
+        Self::Image(err)
+    }
+}
+
+/// Serialized state of an entity that implements [`SaveState`].
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct StateData {
+    /// Type uuid of the entity. It is used to check whether the state belongs to the entity.
+    pub type_uuid: Uuid,
+    /// Version of the state layout.
+    pub version: u32,
+    /// Serialized state.
+    pub data: Vec<u8>,
+}
+
+impl Visit for StateData {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.type_uuid.visit("TypeUuid", &mut region)?;
+        self.version.visit("Version", &mut region)?;
+        BinaryBlob {
+            vec: &mut self.data,
+        }
+        .visit("Data", &mut region)?;
+
+        Ok(())
+    }
+}
+
+impl StateData {
+    /// Serializes the state of the given entity.
+    pub fn capture(type_uuid: Uuid, state: &mut dyn SaveState) -> Result<Self, VisitError> {
+        let version = state.save_state_version();
+        let mut visitor = Visitor::new();
+        {
+            let mut region = visitor.enter_region("State")?;
+            state.visit_save_state(version, &mut region)?;
+        }
+        Ok(Self {
+            type_uuid,
+            version,
+            data: visitor.save_binary_to_vec()?,
+        })
+    }
+
+    /// Deserializes the state into the given entity.
+    pub fn restore(&self, state: &mut dyn SaveState) -> VisitResult {
+        let mut visitor = Visitor::load_from_memory(&self.data)?;
+        let mut region = visitor.enter_region("State")?;
+        state.visit_save_state(self.version, &mut region)
+    }
+}
+
+/// Saved state of a script.
+#[derive(Default, Clone, Debug, Visit, PartialEq)]
+pub struct ScriptStateData {
+    /// Index of the script in the node.
+    pub index: u32,
+    /// Script state.
+    pub state: StateData,
+}
+
+/// Dynamic state of a scene node.
+#[derive(Default, Clone, Debug, Visit, PartialEq)]
+pub struct NodeDelta {
+    /// Id of the node.
+    pub id: SceneNodeId,
+    /// Local position of the node.
+    pub local_position: Vector3<f32>,
+    /// Local rotation of the node.
+    pub local_rotation: UnitQuaternion<f32>,
+    /// Local scale of the node.
+    pub local_scale: Vector3<f32>,
+    /// Local visibility of the node.
+    pub visibility: bool,
+    /// Enabled flag of the node.
+    pub enabled: bool,
+    /// State of the node itself, if it implements [`SaveState`].
+    pub node_state: Option<StateData>,
+    /// States of the scripts of the node that implement [`SaveState`].
+    pub script_states: Vec<ScriptStateData>,
+}
+
+/// A pair of a handle of a node in a prefab and an id of its copy in a scene.
+#[derive(Default, Clone, Debug, Visit, PartialEq)]
+pub struct IdMapping {
+    /// Handle of the node in the prefab.
+    pub original: Handle<Node>,
+    /// Id of the instance of the node.
+    pub id: SceneNodeId,
+}
+
+/// Prefab instance, that could be created at runtime.
+#[derive(Default, Clone, Debug, Visit, PartialEq)]
+pub struct PrefabInstanceDelta {
+    /// Prefab of the instance.
+    pub model: Option<ModelResource>,
+    /// Id of the parent node of the instance.
+    pub parent: SceneNodeId,
+    /// Ids of every node of the instance.
+    pub ids: Vec<IdMapping>,
+}
+
+impl PrefabInstanceDelta {
+    fn root_id(&self) -> Option<SceneNodeId> {
+        let model = self.model.as_ref()?;
+        let root = model.data_ref().get_scene().graph.get_root();
+        self.ids
+            .iter()
+            .find(|mapping| mapping.original == root)
+            .map(|mapping| mapping.id)
+    }
+}
+
+/// Runtime changes of a scene, relative to the scene stored in a file. The delta contains a set
+/// of alive nodes, prefab instances (with stable ids of their nodes) and dynamic state of every
+/// node. To restore the delta, load the scene from the file and call [`SceneDelta::restore`].
+///
+/// # Limitations
+///
+/// Only the nodes from the scene file and the nodes of prefab instances could be restored. Nodes
+/// that were created "manually" at runtime, without prefabs, will be lost.
+#[derive(Default, Clone, Debug, Visit, PartialEq)]
+pub struct SceneDelta {
+    /// Path to the scene file.
+    pub scene_path: PathBuf,
+    /// Ids of every alive node of the scene.
+    pub alive: Vec<SceneNodeId>,
+    /// Prefab instances of the scene.
+    pub instances: Vec<PrefabInstanceDelta>,
+    /// Dynamic state of every node of the scene.
+    pub nodes: Vec<NodeDelta>,
+}
+
+impl SceneDelta {
+    /// Captures the state of the given scene. `scene_path` is a path to the file from which the
+    /// scene was loaded.
+    pub fn capture(scene: &mut Scene, scene_path: impl AsRef<Path>) -> Result<Self, VisitError> {
+        let graph = &mut scene.graph;
+        let root = graph.get_root();
+        let handles = graph.traverse_handle_iter(root).collect::<Vec<_>>();
+
+        let mut delta = Self {
+            scene_path: scene_path.as_ref().to_path_buf(),
+            ..Default::default()
+        };
+
+        for &handle in handles.iter() {
+            if handle == root {
+                continue;
+            }
+
+            let node = &graph[handle];
+            delta.alive.push(node.instance_id());
+
+            if node.is_resource_instance_root() {
+                if let Some(model) = node.resource() {
+                    let ids = graph
+                        .traverse_iter(handle)
+                        .filter(|(_, n)| n.resource().as_ref() == Some(&model))
+                        .map(|(_, n)| IdMapping {
+                            original: n.original_handle_in_resource(),
+                            id: n.instance_id(),
+                        })
+                        .collect();
+                    delta.instances.push(PrefabInstanceDelta {
+                        model: Some(model),
+                        parent: graph
+                            .try_get(node.parent())
+                            .map(|parent| parent.instance_id())
+                            .unwrap_or_default(),
+                        ids,
+                    });
+                }
+            }
+
+            let node = &mut graph[handle];
+            let transform = node.local_transform();
+            let mut node_delta = NodeDelta {
+                id: node.instance_id(),
+                local_position: **transform.position(),
+                local_rotation: **transform.rotation(),
+                local_scale: **transform.scale(),
+                visibility: node.visibility(),
+                enabled: node.is_enabled(),
+                node_state: None,
+                script_states: Default::default(),
+            };
+
+            let type_uuid = node.id();
+            if let Some(state) = node.as_save_state_mut() {
+                node_delta.node_state = Some(StateData::capture(type_uuid, state)?);
+            }
+
+            for index in 0..node.script_count() {
+                if let Some(script) = node.script_mut(index) {
+                    let type_uuid = script.id();
+                    if let Some(state) = script.as_save_state_mut() {
+                        node_delta.script_states.push(ScriptStateData {
+                            index: index as u32,
+                            state: StateData::capture(type_uuid, state)?,
+                        });
+                    }
+                }
+            }
+
+            delta.nodes.push(node_delta);
+        }
+
+        Ok(delta)
+    }
+
+    /// Applies the delta to the given scene. The scene must be freshly loaded from the file, that
+    /// was used to capture the delta. Prefab instances that were created at runtime are
+    /// re-instantiated, nodes that were deleted at runtime are removed, then the state of every
+    /// node is restored.
+    pub fn restore(&self, scene: &mut Scene) -> VisitResult {
+        for instance in self.instances.iter() {
+            let Some(model) = instance.model.as_ref() else {
+                continue;
+            };
+
+            // Nested instances and instances from the scene file already exist.
+            if instance
+                .root_id()
+                .map_or(true, |id| scene.graph.id_to_node_handle(id).is_some())
+            {
+                continue;
+            }
+
+            let ids = instance
+                .ids
+                .iter()
+                .map(|mapping| (mapping.original, mapping.id))
+                .collect::<FxHashMap<_, _>>();
+            let instance_root = model.begin_instantiation(scene).with_ids(&ids).finish();
+
+            match scene.graph.id_to_node_handle(instance.parent).cloned() {
+                Some(parent) => scene.graph.link_nodes(instance_root, parent),
+                None => Log::warn(format!(
+                    "Unable to find parent {} of a prefab instance. The instance will be \
+                    attached to the root.",
+                    instance.parent.0
+                )),
+            }
+        }
+
+        let alive = self.alive.iter().cloned().collect::<FxHashSet<_>>();
+        let root = scene.graph.get_root();
+        let dead = scene
+            .graph
+            .pair_iter()
+            .filter(|(handle, node)| *handle != root && !alive.contains(&node.instance_id()))
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+        for handle in dead {
+            // The node could be already removed together with its parent.
+            if scene.graph.is_valid_handle(handle) {
+                scene.graph.remove_node(handle);
+            }
+        }
+
+        for node_delta in self.nodes.iter() {
+            let Some((_, node)) = scene.graph.node_by_id_mut(node_delta.id) else {
+                Log::warn(format!(
+                    "Unable to restore state of node {}, because it does not exist!",
+                    node_delta.id.0
+                ));
+                continue;
+            };
+
+            node.local_transform_mut()
+                .set_position(node_delta.local_position)
+                .set_rotation(node_delta.local_rotation)
+                .set_scale(node_delta.local_scale);
+            node.set_visibility(node_delta.visibility);
+            node.set_enabled(node_delta.enabled);
+
+            if let Some(node_state) = node_delta.node_state.as_ref() {
+                let type_uuid = node.id();
+                match node.as_save_state_mut() {
+                    Some(state) if type_uuid == node_state.type_uuid => {
+                        node_state.restore(state)?
+                    }
+                    _ => Log::warn(format!(
+                        "Unable to restore state of node {}, because its type has changed!",
+                        node_delta.id.0
+                    )),
+                }
+            }
+
+            for script_state in node_delta.script_states.iter() {
+                let restored = match node.script_mut(script_state.index as usize) {
+                    Some(script) if script.id() == script_state.state.type_uuid => {
+                        match script.as_save_state_mut() {
+                            Some(state) => {
+                                script_state.state.restore(state)?;
+                                true
+                            }
+                            None => false,
+                        }
+                    }
+                    _ => false,
+                };
+
+                if !restored {
+                    Log::warn(format!(
+                        "Unable to restore state of script {} of node {}!",
+                        script_state.index, node_delta.id.0
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Metadata of a save, that is shown in save/load menus.
+#[derive(Default, Clone, Debug, Visit, PartialEq)]
+pub struct SaveMetadata {
+    /// Human-readable name of the save.
+    pub name: String,
+    /// Time of the save in seconds since UNIX epoch. It is set automatically by [`SaveSlots::save`].
+    pub timestamp: u64,
+    /// Total play time in seconds.
+    pub play_time: f32,
+    /// Arbitrary game-specific values (player level, location name, etc.).
+    pub custom: FxHashMap<String, String>,
+}
+
+/// An RGBA8 image, that will be stored together with a save.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct SaveScreenshot {
+    /// Width of the image.
+    pub width: u32,
+    /// Height of the image.
+    pub height: u32,
+    /// Pixels of the image in RGBA8 format.
+    pub pixels: Vec<u8>,
+}
+
+/// Contents of a save file.
+#[derive(Default, Clone, Debug, Visit, PartialEq)]
+pub struct SaveGame {
+    /// Version of the save. It is set automatically by [`SaveSlots`].
+    pub version: u32,
+    /// Metadata of the save.
+    pub metadata: SaveMetadata,
+    /// Deltas of the scenes.
+    pub scenes: Vec<SceneDelta>,
+    /// Global game-specific states, that does not belong to any scene (quest log, inventory,
+    /// etc.).
+    pub globals: FxHashMap<String, StateData>,
+}
+
+impl SaveGame {
+    /// Creates a new save with the given name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            metadata: SaveMetadata {
+                name: name.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Captures the state of the given scene and adds it to the save. See [`SceneDelta::capture`]
+    /// for more info.
+    pub fn add_scene(
+        &mut self,
+        scene: &mut Scene,
+        scene_path: impl AsRef<Path>,
+    ) -> Result<(), VisitError> {
+        self.scenes.push(SceneDelta::capture(scene, scene_path)?);
+        Ok(())
+    }
+
+    /// Stores the global state under the given key.
+    pub fn store_global(
+        &mut self,
+        key: impl Into<String>,
+        state: &mut dyn SaveState,
+    ) -> Result<(), VisitError> {
+        self.globals
+            .insert(key.into(), StateData::capture(Uuid::nil(), state)?);
+        Ok(())
+    }
+
+    /// Restores the global state with the given key. Returns `Ok(false)` if there is no such
+    /// state.
+    pub fn restore_global(&self, key: &str, state: &mut dyn SaveState) -> Result<bool, VisitError> {
+        match self.globals.get(key) {
+            Some(data) => {
+                data.restore(state)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn save_to_vec(&mut self) -> Result<Vec<u8>, VisitError> {
+        let mut visitor = Visitor::new();
+        self.visit("SaveGame", &mut visitor)?;
+        visitor.save_binary_to_vec()
+    }
+
+    fn load_from_memory(data: &[u8]) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::load_from_memory(data)?;
+        let mut save = Self::default();
+        save.visit("SaveGame", &mut visitor)?;
+        Ok(save)
+    }
+}
+
+/// Information about an occupied save slot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaveSlotInfo {
+    /// Name of the slot.
+    pub slot: String,
+    /// Version of the save.
+    pub version: u32,
+    /// Metadata of the save.
+    pub metadata: SaveMetadata,
+    /// Path to the screenshot of the save, if any.
+    pub screenshot: Option<PathBuf>,
+}
+
+#[derive(Default, Visit)]
+struct SaveInfo {
+    version: u32,
+    metadata: SaveMetadata,
+}
+
+impl SaveInfo {
+    fn load_from_file(path: &Path) -> Result<Self, SaveError> {
+        let mut visitor = Visitor::load_from_memory(&std::fs::read(path)?)?;
+        let mut info = Self::default();
+        info.visit("SaveInfo", &mut visitor)?;
+        Ok(info)
+    }
+}
+
+/// A migration of a save from some version to the next one.
+pub type SaveMigration = Box<dyn Fn(&mut SaveGame) -> Result<(), String> + Send + Sync>;
+
+/// A set of save slots stored in a directory. Every slot contains a save file, a metadata file and
+/// an optional screenshot. Saves are versioned, when a save of older version is loaded, every
+/// registered migration starting from the version of the save is applied to it sequentially.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use fyrox_impl::save::{SaveGame, SaveSlots};
+///
+/// let mut slots = SaveSlots::new("saves", 2)
+///     .with_migration(0, |save| {
+///         save.metadata.custom.insert("Difficulty".to_string(), "Normal".to_string());
+///         Ok(())
+///     })
+///     .with_migration(1, |_| Ok(()));
+///
+/// slots.save("quick", &mut SaveGame::new("Quick Save"), None).unwrap();
+/// let save = slots.load("quick").unwrap();
+/// ```
+pub struct SaveSlots {
+    directory: PathBuf,
+    version: u32,
+    migrations: FxHashMap<u32, SaveMigration>,
+}
+
+impl Debug for SaveSlots {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SaveSlots")
+            .field("directory", &self.directory)
+            .field("version", &self.version)
+            .finish()
+    }
+}
+
+impl SaveSlots {
+    /// Creates a new set of save slots in the given directory. `version` is the current version of
+    /// saves of the game.
+    pub fn new(directory: impl AsRef<Path>, version: u32) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+            version,
+            migrations: Default::default(),
+        }
+    }
+
+    /// Registers a migration from `from_version` to `from_version + 1`.
+    pub fn register_migration<F>(&mut self, from_version: u32, migration: F)
+    where
+        F: Fn(&mut SaveGame) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.migrations.insert(from_version, Box::new(migration));
+    }
+
+    /// Registers a migration from `from_version` to `from_version + 1`.
+    pub fn with_migration<F>(mut self, from_version: u32, migration: F) -> Self
+    where
+        F: Fn(&mut SaveGame) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.register_migration(from_version, migration);
+        self
+    }
+
+    /// Returns current version of saves.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the directory with saves.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    fn slot_path(&self, slot: &str, extension: &str) -> Result<PathBuf, SaveError> {
+        if slot.is_empty() || slot.contains(['/', '\\', '.']) {
+            return Err(SaveError::InvalidSlotName(slot.to_string()));
+        }
+        Ok(self.directory.join(slot).with_extension(extension))
+    }
+
+    /// Writes the save to the given slot, overwriting previous save in the slot (if any).
+    pub fn save(
+        &self,
+        slot: &str,
+        save: &mut SaveGame,
+        screenshot: Option<&SaveScreenshot>,
+    ) -> Result<(), SaveError> {
+        let save_path = self.slot_path(slot, SAVE_EXTENSION)?;
+        let info_path = self.slot_path(slot, SAVE_INFO_EXTENSION)?;
+        let screenshot_path = self.slot_path(slot, SAVE_SCREENSHOT_EXTENSION)?;
+
+        std::fs::create_dir_all(&self.directory)?;
+
+        save.version = self.version;
+        save.metadata.timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        std::fs::write(&save_path, save.save_to_vec()?)?;
+
+        let mut info = SaveInfo {
+            version: save.version,
+            metadata: save.metadata.clone(),
+        };
+        let mut visitor = Visitor::new();
+        info.visit("SaveInfo", &mut visitor)?;
+        visitor.save_binary(&info_path)?;
+
+        match screenshot {
+            Some(screenshot) => image::save_buffer(
+                &screenshot_path,
+                &screenshot.pixels,
+                screenshot.width,
+                screenshot.height,
+                image::ColorType::Rgba8,
+            )?,
+            None => {
+                if screenshot_path.exists() {
+                    std::fs::remove_file(&screenshot_path)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads the save from the given slot and migrates it to the current version, if needed.
+    pub fn load(&self, slot: &str) -> Result<SaveGame, SaveError> {
+        let data = std::fs::read(self.slot_path(slot, SAVE_EXTENSION)?)?;
+        let save = SaveGame::load_from_memory(&data)?;
+        self.migrate(save)
+    }
+
+    /// Migrates the given save to the current version.
+    pub fn migrate(&self, mut save: SaveGame) -> Result<SaveGame, SaveError> {
+        if save.version > self.version {
+            return Err(SaveError::UnsupportedVersion {
+                version: save.version,
+                current: self.version,
+            });
+        }
+
+        while save.version < self.version {
+            let migration = self
+                .migrations
+                .get(&save.version)
+                .ok_or(SaveError::MissingMigration(save.version))?;
+            migration(&mut save).map_err(|reason| SaveError::Migration {
+                version: save.version,
+                reason,
+            })?;
+            save.version += 1;
+        }
+
+        Ok(save)
+    }
+
+    /// Returns `true` if the given slot contains a save.
+    pub fn exists(&self, slot: &str) -> bool {
+        self.slot_path(slot, SAVE_EXTENSION)
+            .is_ok_and(|path| path.exists())
+    }
+
+    /// Deletes the save from the given slot.
+    pub fn delete(&self, slot: &str) -> Result<(), SaveError> {
+        for extension in [
+            SAVE_EXTENSION,
+            SAVE_INFO_EXTENSION,
+            SAVE_SCREENSHOT_EXTENSION,
+        ] {
+            let path = self.slot_path(slot, extension)?;
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns information about every occupied slot, the most recent saves go first. Only
+    /// metadata files are read, so the method is cheap even for large saves.
+    pub fn list(&self) -> Result<Vec<SaveSlotInfo>, SaveError> {
+        let mut slots = Vec::new();
+
+        if !self.directory.exists() {
+            return Ok(slots);
+        }
+
+        for entry in std::fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .map_or(true, |ext| ext != SAVE_INFO_EXTENSION)
+            {
+                continue;
+            }
+
+            let Some(slot) = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+            else {
+                continue;
+            };
+
+            let info = match SaveInfo::load_from_file(&path) {
+                Ok(info) => info,
+                Err(err) => {
+                    Log::err(format!("Unable to read save info {path:?}: {err}"));
+                    continue;
+                }
+            };
+
+            let screenshot = path.with_extension(SAVE_SCREENSHOT_EXTENSION);
+            slots.push(SaveSlotInfo {
+                slot,
+                version: info.version,
+                metadata: info.metadata,
+                screenshot: screenshot.exists().then_some(screenshot),
+            });
+        }
+
+        slots.sort_by(|a, b| b.metadata.timestamp.cmp(&a.metadata.timestamp));
+
+        Ok(slots)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, uuid::Uuid, visitor::prelude::*},
+        graph::BaseSceneGraph,
+        save::{SaveError, SaveGame, SaveSlots, SaveState, SceneDelta, StateData},
+        scene::{
+            base::{BaseBuilder, SceneNodeId},
+            pivot::PivotBuilder,
+            Scene,
+        },
+    };
+
+    #[derive(Default, Debug, PartialEq)]
+    struct Inventory {
+        gold: u32,
+        items: Vec<String>,
+    }
+
+    impl SaveState for Inventory {
+        fn save_state_version(&self) -> u32 {
+            1
+        }
+
+        fn visit_save_state(&mut self, version: u32, visitor: &mut Visitor) -> VisitResult {
+            self.gold.visit("Gold", visitor)?;
+            if version >= 1 {
+                self.items.visit("Items", visitor)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn make_scene(ids: &[Uuid]) -> Scene {
+        let mut scene = Scene::new();
+        for id in ids {
+            PivotBuilder::new(BaseBuilder::new().with_instance_id(SceneNodeId(*id)))
+                .build(&mut scene.graph);
+        }
+        scene
+    }
+
+    #[test]
+    fn test_state_data() {
+        let mut inventory = Inventory {
+            gold: 123,
+            items: vec!["Sword".to_string()],
+        };
+        let data = StateData::capture(Uuid::nil(), &mut inventory).unwrap();
+        assert_eq!(data.version, 1);
+
+        let mut restored = Inventory::default();
+        data.restore(&mut restored).unwrap();
+        assert_eq!(restored, inventory);
+    }
+
+    #[test]
+    fn test_save_roundtrip() {
+        let mut save = SaveGame::new("Test");
+        save.store_global(
+            "Inventory",
+            &mut Inventory {
+                gold: 5,
+                items: Default::default(),
+            },
+        )
+        .unwrap();
+
+        let data = save.save_to_vec().unwrap();
+        let loaded = SaveGame::load_from_memory(&data).unwrap();
+        assert_eq!(loaded, save);
+
+        let mut inventory = Inventory::default();
+        assert!(loaded.restore_global("Inventory", &mut inventory).unwrap());
+        assert_eq!(inventory.gold, 5);
+        assert!(!loaded.restore_global("Quests", &mut inventory).unwrap());
+    }
+
+    #[test]
+    fn test_migrations() {
+        let slots = SaveSlots::new("saves", 2)
+            .with_migration(0, |save| {
+                save.metadata.play_time += 1.0;
+                Ok(())
+            })
+            .with_migration(1, |save| {
+                save.metadata.play_time *= 10.0;
+                Ok(())
+            });
+
+        let save = slots.migrate(SaveGame::default()).unwrap();
+        assert_eq!(save.version, 2);
+        assert_eq!(save.metadata.play_time, 10.0);
+
+        let save = SaveGame {
+            version: 3,
+            ..Default::default()
+        };
+        assert!(matches!(
+            slots.migrate(save),
+            Err(SaveError::UnsupportedVersion { .. })
+        ));
+
+        let slots = SaveSlots::new("saves", 1);
+        assert!(matches!(
+            slots.migrate(SaveGame::default()),
+            Err(SaveError::MissingMigration(0))
+        ));
+        assert!(matches!(
+            slots.save("../evil", &mut SaveGame::default(), None),
+            Err(SaveError::InvalidSlotName(_))
+        ));
+    }
+
+    #[test]
+    fn test_scene_delta() {
+        let ids = [Uuid::new_v4(), Uuid::new_v4()];
+
+        let mut scene = make_scene(&ids);
+        let (moved, _) = scene.graph.node_by_id(SceneNodeId(ids[0])).unwrap();
+        scene.graph[moved]
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 2.0, 3.0));
+        let (removed, _) = scene.graph.node_by_id(SceneNodeId(ids[1])).unwrap();
+        scene.graph.remove_node(removed);
+
+        let delta = SceneDelta::capture(&mut scene, "test.rgs").unwrap();
+        assert_eq!(delta.alive, vec![SceneNodeId(ids[0])]);
+
+        let mut fresh_scene = make_scene(&ids);
+        delta.restore(&mut fresh_scene).unwrap();
+
+        assert!(fresh_scene.graph.node_by_id(SceneNodeId(ids[1])).is_none());
+        let (_, node) = fresh_scene.graph.node_by_id(SceneNodeId(ids[0])).unwrap();
+        assert_eq!(
+            **node.local_transform().position(),
+            Vector3::new(1.0, 2.0, 3.0)
+        );
+    }
+}
@@ -37,7 +37,7 @@ use crate::{
     engine::SerializationContext,
     graph::BaseSceneGraph,
     resource::model::ModelResource,
-    scene::{node::Node, transform::Transform},
+    scene::{graph::script_index::ScriptIndex, node::Node, transform::Transform},
     script::{Script, ScriptTrait},
 };
 use fyrox_core::algebra::UnitQuaternion;
@@ -279,6 +279,13 @@ pub enum NodeScriptMessage {
         /// Index of the script.
         script_index: usize,
     },
+    /// A script was re-created during hot reloading and needs to restore its runtime state.
+    ReloadScript {
+        /// Node handle.
+        handle: Handle<Node>,
+        /// Index of the script.
+        script_index: usize,
+    },
 }
 
 /// Unique id of a node, that could be used as a reliable "index" of the node. This id is mostly
@@ -316,6 +323,10 @@ pub struct ScriptRecord {
     pub(crate) script: Option<Script>,
     #[reflect(hidden)]
     pub(crate) should_be_deleted: bool,
+    // Type uuid of the script under which the record was registered in the script index of the
+    // graph. It is stored, because the script could be temporarily taken from the record.
+    #[reflect(hidden)]
+    pub(crate) indexed: Option<Uuid>,
 }
 
 impl ScriptRecord {
@@ -323,6 +334,7 @@ impl ScriptRecord {
         Self {
             script: Some(script),
             should_be_deleted: false,
+            indexed: None,
         }
     }
 }
@@ -448,6 +460,9 @@ pub struct Base {
     #[reflect(hidden)]
     message_sender: Option<Sender<NodeMessage>>,
 
+    #[reflect(hidden)]
+    script_index: Option<ScriptIndex>,
+
     // Name is not inheritable, because property inheritance works bad with external 3D models.
     // They use names to search "original" nodes.
     #[reflect(setter = "set_name_internal")]
@@ -583,10 +598,12 @@ impl Base {
         self_handle: Handle<Node>,
         message_sender: Sender<NodeMessage>,
         script_message_sender: Sender<NodeScriptMessage>,
+        script_index: ScriptIndex,
     ) {
         self.self_handle = self_handle;
         self.message_sender = Some(message_sender.clone());
         self.script_message_sender = Some(script_message_sender);
+        self.set_script_index(script_index);
         self.local_transform
             .set_message_data(message_sender.clone(), self_handle);
         self.visibility
@@ -871,6 +888,8 @@ impl Base {
     /// in either the current update tick (if it was removed from some other script) or in the next
     /// update tick of the parent graph.
     pub fn remove_script(&mut self, index: usize) {
+        self.unindex_script(index);
+
         // Send script to the graph to destroy script instances correctly.
         if let Some(entry) = self.scripts.get_mut(index) {
             entry.should_be_deleted = true;
@@ -922,6 +941,8 @@ impl Base {
                 }
             }
         }
+
+        self.index_script(index);
     }
 
     /// Adds a new script to the scene node. The new script will be initialized either in the current
@@ -934,6 +955,7 @@ impl Base {
     {
         let script_index = self.scripts.len();
         self.scripts.push(ScriptRecord::new(Script::new(script)));
+        self.index_script(script_index);
         if let Some(sender) = self.script_message_sender.as_ref() {
             Log::verify(sender.send(NodeScriptMessage::InitializeScript {
                 handle: self.self_handle,
@@ -942,6 +964,53 @@ impl Base {
         }
     }
 
+    /// Registers a script with the given index in the script index of the graph (if any).
+    fn index_script(&mut self, index: usize) {
+        self.unindex_script(index);
+
+        if let (Some(script_index), Some(entry)) =
+            (self.script_index.as_ref(), self.scripts.get_mut(index))
+        {
+            if !entry.should_be_deleted {
+                if let Some(script) = entry.script.as_ref() {
+                    let script_type = script.id();
+                    script_index.register(self.self_handle, script_type);
+                    entry.indexed = Some(script_type);
+                }
+            }
+        }
+    }
+
+    /// Removes a script with the given index from the script index of the graph (if any).
+    fn unindex_script(&mut self, index: usize) {
+        if let (Some(script_index), Some(entry)) =
+            (self.script_index.as_ref(), self.scripts.get_mut(index))
+        {
+            if let Some(script_type) = entry.indexed.take() {
+                script_index.unregister(self.self_handle, script_type);
+            }
+        }
+    }
+
+    /// Registers all scripts of the node in the given script index. Registrations in a previous
+    /// index are discarded without removal, because the node could be a copy of some other node
+    /// that is still registered there.
+    pub(crate) fn set_script_index(&mut self, script_index: ScriptIndex) {
+        self.script_index = Some(script_index);
+        for i in 0..self.scripts.len() {
+            self.scripts[i].indexed = None;
+            self.index_script(i);
+        }
+    }
+
+    /// Removes all scripts of the node from the script index it is registered in.
+    pub(crate) fn detach_script_index(&mut self) {
+        for i in 0..self.scripts.len() {
+            self.unindex_script(i);
+        }
+        self.script_index = None;
+    }
+
     /// Checks if the node has a script of a particular type. Returns `false` if there is no such
     /// script.
     #[inline]
@@ -1388,6 +1457,7 @@ impl BaseBuilder {
             self_handle: Default::default(),
             script_message_sender: None,
             message_sender: None,
+            script_index: None,
             name: self.name.into(),
             children: self.children,
             local_transform: TrackedProperty::unbound(
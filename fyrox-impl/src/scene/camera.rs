@@ -179,12 +179,114 @@ impl OrthographicProjection {
     }
 }
 
+/// Perspective projection with an asymmetric (off-center) viewing frustum. Every side of the frustum
+/// is defined by its own angle, which makes it possible to match the optics of head-mounted displays,
+/// where the frustum of each eye is shifted towards the nose. Aspect ratio of the frame is ignored,
+/// it is defined by the angles.
+#[derive(Reflect, Clone, Debug, PartialEq, Visit, Serialize, Deserialize)]
+pub struct OffCenterProjection {
+    /// Angle (in radians) between the view direction and the left side of the frustum. Usually
+    /// negative.
+    #[reflect(min_value = -1.57, max_value = 1.57, step = 0.01)]
+    pub angle_left: f32,
+    /// Angle (in radians) between the view direction and the right side of the frustum. Usually
+    /// positive.
+    #[reflect(min_value = -1.57, max_value = 1.57, step = 0.01)]
+    pub angle_right: f32,
+    /// Angle (in radians) between the view direction and the top side of the frustum. Usually
+    /// positive.
+    #[reflect(min_value = -1.57, max_value = 1.57, step = 0.01)]
+    pub angle_up: f32,
+    /// Angle (in radians) between the view direction and the bottom side of the frustum. Usually
+    /// negative.
+    #[reflect(min_value = -1.57, max_value = 1.57, step = 0.01)]
+    pub angle_down: f32,
+    /// Location of the near clipping plane. If it is larger than [`Self::z_far`] then it will be
+    /// treated like far clipping plane.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub z_near: f32,
+    /// Location of the far clipping plane. If it is less than [`Self::z_near`] then it will be
+    /// treated like near clipping plane.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub z_far: f32,
+}
+
+impl Default for OffCenterProjection {
+    fn default() -> Self {
+        let half_fov = 37.5f32.to_radians();
+        Self {
+            angle_left: -half_fov,
+            angle_right: half_fov,
+            angle_up: half_fov,
+            angle_down: -half_fov,
+            z_near: 0.025,
+            z_far: 2048.0,
+        }
+    }
+}
+
+impl OffCenterProjection {
+    /// Returns vertical field of view of the frustum in radians.
+    #[inline]
+    pub fn fov(&self) -> f32 {
+        self.angle_up - self.angle_down
+    }
+
+    /// Returns off-center perspective projection matrix.
+    #[inline]
+    pub fn matrix(&self) -> Matrix4<f32> {
+        let limit = 10.0 * f32::EPSILON;
+
+        let z_near = self.z_far.min(self.z_near).max(limit);
+        let mut z_far = self.z_far.max(self.z_near);
+
+        // Prevent planes from superimposing which could cause panic.
+        if z_far - z_near < limit {
+            z_far += limit;
+        }
+
+        let left = z_near * self.angle_left.tan();
+        let mut right = z_near * self.angle_right.tan();
+        let bottom = z_near * self.angle_down.tan();
+        let mut top = z_near * self.angle_up.tan();
+
+        // Prevent collapsing of the frustum.
+        if right - left < limit {
+            right = left + limit;
+        }
+        if top - bottom < limit {
+            top = bottom + limit;
+        }
+
+        Matrix4::new(
+            2.0 * z_near / (right - left),
+            0.0,
+            (right + left) / (right - left),
+            0.0,
+            0.0,
+            2.0 * z_near / (top - bottom),
+            (top + bottom) / (top - bottom),
+            0.0,
+            0.0,
+            0.0,
+            -(z_far + z_near) / (z_far - z_near),
+            -2.0 * z_far * z_near / (z_far - z_near),
+            0.0,
+            0.0,
+            -1.0,
+            0.0,
+        )
+    }
+}
+
 /// A method of projection. Different projection types suitable for different purposes:
 ///
 /// 1) Perspective projection most useful for 3D games, it makes a scene to look most natural,
 /// objects will look smaller with increasing distance.
 /// 2) Orthographic projection most useful for 2D games, objects won't look smaller with increasing
 /// distance.
+/// 3) Off-center projection is a perspective projection with asymmetric frustum, it is used for
+/// rendering to head-mounted displays (VR).
 #[derive(
     Reflect,
     Clone,
@@ -202,6 +304,8 @@ pub enum Projection {
     Perspective(PerspectiveProjection),
     /// See [`OrthographicProjection`] docs.
     Orthographic(OrthographicProjection),
+    /// See [`OffCenterProjection`] docs.
+    OffCenter(OffCenterProjection),
 }
 
 uuid_provider!(Projection = "0eb5bec0-fc4e-4945-99b6-e6c5392ad971");
@@ -214,6 +318,7 @@ impl Projection {
         match self {
             Projection::Perspective(ref mut v) => v.z_near = z_near,
             Projection::Orthographic(ref mut v) => v.z_near = z_near,
+            Projection::OffCenter(ref mut v) => v.z_near = z_near,
         }
         self
     }
@@ -225,6 +330,7 @@ impl Projection {
         match self {
             Projection::Perspective(ref mut v) => v.z_far = z_far,
             Projection::Orthographic(ref mut v) => v.z_far = z_far,
+            Projection::OffCenter(ref mut v) => v.z_far = z_far,
         }
         self
     }
@@ -235,6 +341,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_near = z_near,
             Projection::Orthographic(v) => v.z_near = z_near,
+            Projection::OffCenter(v) => v.z_near = z_near,
         }
     }
 
@@ -244,6 +351,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_far = z_far,
             Projection::Orthographic(v) => v.z_far = z_far,
+            Projection::OffCenter(v) => v.z_far = z_far,
         }
     }
 
@@ -253,6 +361,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_near,
             Projection::Orthographic(v) => v.z_near,
+            Projection::OffCenter(v) => v.z_near,
         }
     }
 
@@ -262,6 +371,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_far,
             Projection::Orthographic(v) => v.z_far,
+            Projection::OffCenter(v) => v.z_far,
         }
     }
 
@@ -271,6 +381,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.matrix(frame_size),
             Projection::Orthographic(v) => v.matrix(frame_size),
+            Projection::OffCenter(v) => v.matrix(),
         }
     }
 
@@ -285,6 +396,12 @@ impl Projection {
     pub fn is_orthographic(&self) -> bool {
         matches!(self, Projection::Orthographic(_))
     }
+
+    /// Returns `true` if the current projection is off-center.
+    #[inline]
+    pub fn is_off_center(&self) -> bool {
+        matches!(self, Projection::OffCenter(_))
+    }
 }
 
 impl Default for Projection {
@@ -331,6 +448,55 @@ impl Default for Exposure {
     }
 }
 
+/// Settings of the pixel-perfect mode of a camera. See [`Camera::set_pixel_perfect`] for more info.
+#[derive(Visit, Clone, PartialEq, Debug, Reflect)]
+pub struct PixelPerfectSettings {
+    /// Amount of virtual pixels per one world unit. It should match the amount of texels per unit
+    /// of sprites and tiles in the scene.
+    #[reflect(min_value = 1.0, step = 1.0)]
+    pub pixels_per_unit: f32,
+    /// Desired resolution (in virtual pixels) of the camera. The camera picks the largest integer
+    /// scale at which the reference resolution fits in the viewport and uses the viewport size
+    /// divided by the scale as its internal resolution.
+    pub reference_resolution: Vector2<u32>,
+}
+
+uuid_provider!(PixelPerfectSettings = "5d7f0a47-60f6-4e5f-8a57-86b8b0b3f2c4");
+
+impl Default for PixelPerfectSettings {
+    fn default() -> Self {
+        Self {
+            pixels_per_unit: 16.0,
+            reference_resolution: Vector2::new(320, 180),
+        }
+    }
+}
+
+impl PixelPerfectSettings {
+    /// Returns the largest integer scale at which the reference resolution fits in a viewport of
+    /// the given size. The scale is always at least 1.
+    pub fn scale(&self, viewport_size: Vector2<i32>) -> i32 {
+        let horizontal = viewport_size.x / self.reference_resolution.x.max(1) as i32;
+        let vertical = viewport_size.y / self.reference_resolution.y.max(1) as i32;
+        horizontal.min(vertical).max(1)
+    }
+
+    /// Returns the size of a virtual pixel in world units.
+    pub fn pixel_size(&self) -> f32 {
+        1.0 / self.pixels_per_unit.max(f32::EPSILON)
+    }
+
+    /// Snaps the given world-space position to the virtual pixel grid.
+    pub fn snap(&self, position: Vector3<f32>) -> Vector3<f32> {
+        let pixel_size = self.pixel_size();
+        Vector3::new(
+            (position.x / pixel_size).round() * pixel_size,
+            (position.y / pixel_size).round() * pixel_size,
+            position.z,
+        )
+    }
+}
+
 /// Camera allows you to see world from specific point in world. You must have at least one camera in
 /// your scene to see anything.
 ///
@@ -345,6 +511,14 @@ impl Default for Exposure {
 /// Skybox is a cube around the camera with six textures forming seamless "sky". It could be anything,
 /// starting from simple blue sky and ending with outer space.
 ///
+/// ## Pixel-perfect mode
+///
+/// Orthographic cameras could be switched to pixel-perfect mode (see [`Camera::set_pixel_perfect`]),
+/// which is useful for retro-style 2D games. In this mode the scene is rendered at low internal
+/// resolution and then upscaled with integer scale and nearest filtering, the camera and sprites
+/// are snapped to the virtual pixel grid. This removes shimmering of the pixel art when the camera
+/// or the objects move.
+///
 /// ## Multiple cameras
 ///
 /// Fyrox supports multiple cameras per scene, it means that you can create split screen games, make
@@ -382,6 +556,10 @@ pub struct Camera {
     #[reflect(setter = "set_color_grading_enabled")]
     color_grading_enabled: InheritableVariable<bool>,
 
+    #[visit(optional)]
+    #[reflect(setter = "set_pixel_perfect")]
+    pixel_perfect: InheritableVariable<Option<PixelPerfectSettings>>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     view_matrix: Matrix4<f32>,
@@ -444,12 +622,33 @@ impl Camera {
     /// this method, it will be called automatically when new frame starts.
     #[inline]
     pub fn calculate_matrices(&mut self, frame_size: Vector2<f32>) {
-        let pos = self.base.global_position();
+        let mut pos = self.base.global_position();
         let look = self.base.look_vector();
         let up = self.base.up_vector();
 
-        self.view_matrix = Matrix4::look_at_rh(&Point3::from(pos), &Point3::from(pos + look), &up);
-        self.projection_matrix = self.projection.matrix(frame_size);
+        if let (Some((resolution, _)), Some(settings), Projection::Orthographic(ortho)) = (
+            self.pixel_perfect_resolution(frame_size),
+            self.pixel_perfect.as_ref(),
+            &*self.projection,
+        ) {
+            // Snap the edges of the view (not its center) to the pixel grid, otherwise the
+            // view will be shifted by half of a pixel if the resolution is odd.
+            let half_size = resolution.cast::<f32>().scale(0.5 * settings.pixel_size());
+            let corner = settings.snap(pos - Vector3::new(half_size.x, half_size.y, 0.0));
+            pos = corner + Vector3::new(half_size.x, half_size.y, 0.0);
+
+            self.view_matrix =
+                Matrix4::look_at_rh(&Point3::from(pos), &Point3::from(pos + look), &up);
+            self.projection_matrix = OrthographicProjection {
+                vertical_size: half_size.y,
+                ..ortho.clone()
+            }
+            .matrix(resolution.cast::<f32>());
+        } else {
+            self.view_matrix =
+                Matrix4::look_at_rh(&Point3::from(pos), &Point3::from(pos + look), &up);
+            self.projection_matrix = self.projection.matrix(frame_size);
+        }
     }
 
     /// Sets new viewport in resolution-independent format. In other words
@@ -629,16 +828,19 @@ impl Camera {
             .try_normalize(f32::EPSILON)
             .unwrap_or_default();
 
-        match self.projection.deref() {
-            Projection::Perspective(perspective) => {
-                let radius = aabb.half_extents().max();
-                let distance = radius / (perspective.fov * 0.5).sin();
+        let fit_perspective = |fov: f32| {
+            let radius = aabb.half_extents().max();
+            let distance = radius / (fov * 0.5).sin();
 
-                FitParameters::Perspective {
-                    position: aabb.center() - look_vector.scale(distance),
-                    distance,
-                }
+            FitParameters::Perspective {
+                position: aabb.center() - look_vector.scale(distance),
+                distance,
             }
+        };
+
+        match self.projection.deref() {
+            Projection::Perspective(perspective) => fit_perspective(perspective.fov),
+            Projection::OffCenter(off_center) => fit_perspective(off_center.fov()),
             Projection::Orthographic(_) => {
                 let mut min_x = f32::MAX;
                 let mut min_y = f32::MAX;
@@ -734,6 +936,56 @@ impl Camera {
     pub fn exposure(&self) -> Exposure {
         *self.exposure
     }
+
+    /// Enables or disables pixel-perfect mode of the camera. The mode works only with orthographic
+    /// projection, in this mode the scene is rendered at the internal resolution (see
+    /// [`PixelPerfectSettings`]) and then upscaled to the viewport with integer scale and nearest
+    /// filtering. Vertical size of the orthographic projection is ignored, it is defined by the
+    /// internal resolution and the amount of pixels per unit. The camera position is snapped to
+    /// the virtual pixel grid, as well as the positions of sprites and tile maps. The mode is
+    /// designed for 2D scenes, 3D meshes that are rendered using deferred rendering path are not
+    /// affected by the internal resolution.
+    pub fn set_pixel_perfect(
+        &mut self,
+        settings: Option<PixelPerfectSettings>,
+    ) -> Option<PixelPerfectSettings> {
+        self.pixel_perfect.set_value_and_mark_modified(settings)
+    }
+
+    /// Returns current pixel-perfect settings of the camera, if any.
+    pub fn pixel_perfect(&self) -> Option<&PixelPerfectSettings> {
+        self.pixel_perfect.as_ref()
+    }
+
+    /// Returns the internal resolution (in virtual pixels) and the integer scale of the camera in
+    /// pixel-perfect mode. Returns `None` if the mode is disabled or the camera does not use
+    /// orthographic projection.
+    pub fn pixel_perfect_resolution(
+        &self,
+        frame_size: Vector2<f32>,
+    ) -> Option<(Vector2<i32>, i32)> {
+        let settings = self.pixel_perfect.as_ref()?;
+        if !self.projection.is_orthographic() {
+            return None;
+        }
+        let viewport = self.viewport_pixels(frame_size);
+        let scale = settings.scale(Vector2::new(viewport.w(), viewport.h()));
+        Some((
+            Vector2::new((viewport.w() / scale).max(1), (viewport.h() / scale).max(1)),
+            scale,
+        ))
+    }
+
+    /// Returns the size of a virtual pixel in world units if the camera is in pixel-perfect mode.
+    pub fn pixel_grid_size(&self) -> Option<f32> {
+        if self.projection.is_orthographic() {
+            self.pixel_perfect
+                .as_ref()
+                .map(|settings| settings.pixel_size())
+        } else {
+            None
+        }
+    }
 }
 
 impl ConstructorProvider<Node, Graph> for Camera {
@@ -1075,6 +1327,7 @@ pub struct CameraBuilder {
     color_grading_lut: Option<ColorGradingLut>,
     color_grading_enabled: bool,
     projection: Projection,
+    pixel_perfect: Option<PixelPerfectSettings>,
 }
 
 impl CameraBuilder {
@@ -1093,6 +1346,7 @@ impl CameraBuilder {
             color_grading_lut: None,
             color_grading_enabled: false,
             projection: Projection::default(),
+            pixel_perfect: None,
         }
     }
 
@@ -1168,6 +1422,12 @@ impl CameraBuilder {
         self
     }
 
+    /// Sets desired pixel-perfect settings. See [`Camera::set_pixel_perfect`] for more info.
+    pub fn with_pixel_perfect(mut self, settings: PixelPerfectSettings) -> Self {
+        self.pixel_perfect = Some(settings);
+        self
+    }
+
     /// Creates new instance of camera.
     pub fn build_camera(self) -> Camera {
         Camera {
@@ -1188,6 +1448,7 @@ impl CameraBuilder {
             exposure: self.exposure.into(),
             color_grading_lut: self.color_grading_lut.into(),
             color_grading_enabled: self.color_grading_enabled.into(),
+            pixel_perfect: self.pixel_perfect.into(),
         }
     }
 
@@ -1594,3 +1855,44 @@ impl SkyBox {
         self.back.clone()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pixel_perfect_camera() {
+        let settings = PixelPerfectSettings {
+            pixels_per_unit: 16.0,
+            reference_resolution: Vector2::new(320, 180),
+        };
+        assert_eq!(settings.scale(Vector2::new(1920, 1080)), 6);
+        assert_eq!(settings.scale(Vector2::new(1366, 768)), 4);
+        assert_eq!(settings.scale(Vector2::new(100, 100)), 1);
+
+        let mut camera = CameraBuilder::new(BaseBuilder::new())
+            .with_projection(Projection::Orthographic(Default::default()))
+            .with_pixel_perfect(settings)
+            .build_camera();
+        camera
+            .global_transform
+            .set(Matrix4::new_translation(&Vector3::new(0.01, 0.02, 0.0)));
+
+        let frame_size = Vector2::new(1366.0, 768.0);
+        assert_eq!(
+            camera.pixel_perfect_resolution(frame_size),
+            Some((Vector2::new(341, 192), 4))
+        );
+
+        camera.calculate_matrices(frame_size);
+
+        // Left edge of the view must be on the pixel grid.
+        let left = camera
+            .view_projection_matrix()
+            .try_inverse()
+            .unwrap()
+            .transform_point(&Point3::new(-1.0, 0.0, 0.0));
+        let pixels = left.x * 16.0;
+        assert!((pixels - pixels.round()).abs() < 1.0e-3);
+    }
+}
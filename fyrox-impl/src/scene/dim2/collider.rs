@@ -173,13 +173,54 @@ pub struct HeightfieldShape {
     pub geometry_source: GeometrySource,
 }
 
-/// Arbitrary tile map shape.
+/// Defines how the shapes of individual tiles are combined into a single collider shape of a tile
+/// map.
+#[derive(
+    Copy,
+    Clone,
+    Default,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum TileColliderMergeMode {
+    /// Every triangle of every tile is added to a single triangle mesh as is. This is the fastest
+    /// option, but bodies that slide across the tiles could bump into the seams between them
+    /// (so called "ghost collisions").
+    #[default]
+    TriangleMesh,
+    /// Triangles of adjacent tiles are merged into as large convex polygons as possible. The
+    /// amount of seams is greatly reduced, and the shape remains solid, so the bodies that got
+    /// inside of it are pushed out.
+    ConvexPolygons,
+    /// Only the outer edges of the tiles are used, with collinear edges of adjacent tiles merged
+    /// into single segments. This option completely removes the seams on flat surfaces, but the
+    /// resulting shape is hollow.
+    EdgeChains,
+}
+
+/// Arbitrary tile map shape, that is built from the tile colliders of a collider layer of the tile
+/// map's tile set. The shape is updated automatically when the tiles of the tile map are changed.
+/// The shapes are cached per chunk of the tile map, so only the modified chunks are built again,
+/// and the tiles are merged (see [`TileColliderMergeMode`]) only with the tiles of the same chunk.
+/// Changes of the tile colliders in the tile set are not tracked, the shape must be set again
+/// to apply them.
 #[derive(Default, Clone, Debug, PartialEq, Visit, Reflect, Eq)]
 pub struct TileMapShape {
     /// A handle to tile map scene node.
     pub tile_map: GeometrySource,
     /// Name of a collider layer in the tile map's tile set.
     pub layer_name: ImmutableString,
+    /// Defines how the shapes of tiles are combined. See [`TileColliderMergeMode`] docs for more
+    /// info.
+    #[visit(optional)]
+    pub merge_mode: TileColliderMergeMode,
 }
 
 /// Possible collider shapes.
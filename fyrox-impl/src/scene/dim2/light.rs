@@ -0,0 +1,512 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! 2D light is a light source that lights 2D scenes (rectangles and tile maps) in a separate
+//! overlay pass. It supports point, spot and global lights, falloff textures, normal maps and
+//! hard or soft shadows cast by 2D occluders.
+//!
+//! See [`Light2D`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector2, Vector3},
+        color::Color,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    resource::texture::TextureResource,
+    scene::{
+        base::{Base, BaseBuilder},
+        debug::SceneDrawingContext,
+        dim2::rectangle::Rectangle,
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait},
+        tilemap::{merge_tile_collider_outlines, outline_segments, TileMap},
+    },
+};
+use fyrox_graph::constructor::ConstructorProvider;
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines the shape of the light emitted by a [`Light2D`].
+#[derive(
+    Copy, Clone, Default, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum Light2DKind {
+    /// The light is emitted in all directions within the radius of the light.
+    #[default]
+    Point,
+    /// The light is emitted in a cone along local Y axis of the light within the radius of the
+    /// light.
+    Spot {
+        /// Full angle (in radians) of the cone, the light is not emitted outside of it.
+        #[reflect(min_value = 0.0, max_value = 6.28, step = 0.1)]
+        cone_angle: f32,
+        /// Angle (in radians) at the edge of the cone, where the light smoothly fades out.
+        #[reflect(min_value = 0.0, max_value = 6.28, step = 0.1)]
+        falloff_angle_delta: f32,
+    },
+    /// The light is applied to the entire scene equally, it has no attenuation and does not cast
+    /// shadows. It is used as an ambient light for 2D scenes.
+    Global,
+}
+
+/// A segment of a shadow occluder in world coordinates.
+pub type OccluderSegment = [Vector2<f32>; 2];
+
+/// 2D light is a light source for 2D scenes. 2D lights are rendered in a separate overlay pass:
+/// the light of every 2D light is accumulated in a light map, which is then multiplied with the
+/// rendered scene. The pass is active only if there is at least one visible 2D light in the
+/// scene. The light map is filled with the ambient lighting color of the scene first, so the areas
+/// that are not lit by any 2D light will have this color. [`Light2DKind::Global`] lights could be
+/// used to add more light to the entire scene.
+///
+/// ## Attenuation
+///
+/// Point and spot lights fade out with the distance from the light source. By default, the light
+/// fades out quadratically, a custom falloff could be set by a falloff texture (see
+/// [`Self::set_falloff_texture`]).
+///
+/// ## Normal maps
+///
+/// Rectangles and tile maps with the standard materials support normal maps. The normal map should
+/// be set to `normalTexture` property of the material and `normalMapStrength` property should be
+/// set to a value larger than zero. The height of the light (see [`Self::set_height`]) defines how
+/// steep the light hits the surfaces.
+///
+/// ## Shadows
+///
+/// Point and spot lights cast shadows (see [`Base::set_cast_shadows`]) from 2D occluders, which are
+/// rectangles with enabled [`Rectangle::set_shadow_caster`] and tile maps with a shadow collider
+/// layer (see [`TileMap::set_shadow_collider`]). Shadows are hard by default, soft shadows could be
+/// enabled by [`Self::set_shadow_softness`].
+#[derive(Debug, Reflect, Clone, Visit, ComponentProvider)]
+pub struct Light2D {
+    base: Base,
+
+    #[reflect(setter = "set_kind")]
+    kind: InheritableVariable<Light2DKind>,
+
+    #[reflect(setter = "set_color")]
+    color: InheritableVariable<Color>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_intensity")]
+    intensity: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_radius")]
+    radius: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_falloff_texture")]
+    falloff_texture: InheritableVariable<Option<TextureResource>>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_height")]
+    height: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.01)]
+    #[reflect(setter = "set_shadow_softness")]
+    shadow_softness: InheritableVariable<f32>,
+}
+
+impl Deref for Light2D {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Light2D {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Light2D {
+    fn type_uuid() -> Uuid {
+        uuid!("5b7cb6e2-5d6b-4c2c-9a8e-1b3f4f4b8f0e")
+    }
+}
+
+impl Default for Light2D {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            kind: Default::default(),
+            color: InheritableVariable::new_modified(Color::WHITE),
+            intensity: InheritableVariable::new_modified(1.0),
+            radius: InheritableVariable::new_modified(5.0),
+            falloff_texture: Default::default(),
+            height: InheritableVariable::new_modified(1.0),
+            shadow_softness: Default::default(),
+        }
+    }
+}
+
+impl Light2D {
+    /// Returns the kind of the light.
+    pub fn kind(&self) -> Light2DKind {
+        *self.kind
+    }
+
+    /// Sets a new kind of the light.
+    pub fn set_kind(&mut self, kind: Light2DKind) -> Light2DKind {
+        self.kind.set_value_and_mark_modified(kind)
+    }
+
+    /// Returns the color of the light.
+    pub fn color(&self) -> Color {
+        *self.color
+    }
+
+    /// Sets a new color of the light.
+    pub fn set_color(&mut self, color: Color) -> Color {
+        self.color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns the intensity of the light.
+    pub fn intensity(&self) -> f32 {
+        *self.intensity
+    }
+
+    /// Sets a new intensity of the light. The color of the light is multiplied by the intensity,
+    /// values larger than 1.0 could be used to create overly bright lights.
+    pub fn set_intensity(&mut self, intensity: f32) -> f32 {
+        self.intensity
+            .set_value_and_mark_modified(intensity.max(0.0))
+    }
+
+    /// Returns the radius of the light.
+    pub fn radius(&self) -> f32 {
+        *self.radius
+    }
+
+    /// Sets a new radius of the light. The light does not affect anything outside of the radius.
+    /// The radius is ignored for [`Light2DKind::Global`] lights.
+    pub fn set_radius(&mut self, radius: f32) -> f32 {
+        self.radius.set_value_and_mark_modified(radius.abs())
+    }
+
+    /// Returns the current falloff texture of the light.
+    pub fn falloff_texture(&self) -> Option<&TextureResource> {
+        self.falloff_texture.as_ref()
+    }
+
+    /// Sets a new falloff texture of the light. The red channel of the first row of the texture
+    /// defines the attenuation of the light, the left side of the texture corresponds to the
+    /// center of the light and the right side - to the radius of the light. If there's no
+    /// falloff texture, the light fades out quadratically.
+    pub fn set_falloff_texture(
+        &mut self,
+        texture: Option<TextureResource>,
+    ) -> Option<TextureResource> {
+        self.falloff_texture.set_value_and_mark_modified(texture)
+    }
+
+    /// Returns the height of the light.
+    pub fn height(&self) -> f32 {
+        *self.height
+    }
+
+    /// Sets a new height of the light above the scene plane. It is used only for normal mapping:
+    /// the lower the height, the steeper the light hits the surfaces.
+    pub fn set_height(&mut self, height: f32) -> f32 {
+        self.height.set_value_and_mark_modified(height.max(0.0))
+    }
+
+    /// Returns the shadow softness of the light.
+    pub fn shadow_softness(&self) -> f32 {
+        *self.shadow_softness
+    }
+
+    /// Sets a new shadow softness of the light. It defines the radius (in world units) of the
+    /// light source, larger values make wider penumbra of the shadows. Zero value means hard
+    /// shadows. Keep in mind, that soft shadows are more expensive to render.
+    pub fn set_shadow_softness(&mut self, softness: f32) -> f32 {
+        self.shadow_softness
+            .set_value_and_mark_modified(softness.max(0.0))
+    }
+
+    /// Returns the direction (in world coordinates) of the spot light.
+    pub fn direction(&self) -> Vector2<f32> {
+        self.global_transform()
+            .transform_vector(&Vector3::y())
+            .xy()
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector2::y)
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for Light2D {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Light (2D)", |_| {
+                Light2DBuilder::new(BaseBuilder::new().with_name("Light2D"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("2D")
+    }
+}
+
+impl NodeTrait for Light2D {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::from_radius(*self.radius)
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform_without_scaling())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
+        if *self.kind != Light2DKind::Global {
+            ctx.draw_circle(
+                Vector3::default(),
+                *self.radius,
+                32,
+                Matrix4::new_translation(&self.global_position()),
+                Color::GREEN,
+            );
+        }
+    }
+}
+
+/// Allows you to build 2D light in declarative manner.
+pub struct Light2DBuilder {
+    base_builder: BaseBuilder,
+    kind: Light2DKind,
+    color: Color,
+    intensity: f32,
+    radius: f32,
+    falloff_texture: Option<TextureResource>,
+    height: f32,
+    shadow_softness: f32,
+}
+
+impl Light2DBuilder {
+    /// Creates new 2D light builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            kind: Default::default(),
+            color: Color::WHITE,
+            intensity: 1.0,
+            radius: 5.0,
+            falloff_texture: None,
+            height: 1.0,
+            shadow_softness: 0.0,
+        }
+    }
+
+    /// Sets the desired kind of the light.
+    pub fn with_kind(mut self, kind: Light2DKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Sets the desired color of the light.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the desired intensity of the light.
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Sets the desired radius of the light.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets the desired falloff texture of the light. See [`Light2D::set_falloff_texture`] for
+    /// more info.
+    pub fn with_falloff_texture(mut self, texture: TextureResource) -> Self {
+        self.falloff_texture = Some(texture);
+        self
+    }
+
+    /// Sets the desired height of the light. See [`Light2D::set_height`] for more info.
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Sets the desired shadow softness of the light. See [`Light2D::set_shadow_softness`] for
+    /// more info.
+    pub fn with_shadow_softness(mut self, softness: f32) -> Self {
+        self.shadow_softness = softness;
+        self
+    }
+
+    /// Creates new [`Light2D`] instance.
+    pub fn build_light(self) -> Light2D {
+        Light2D {
+            base: self.base_builder.build_base(),
+            kind: self.kind.into(),
+            color: self.color.into(),
+            intensity: self.intensity.into(),
+            radius: self.radius.into(),
+            falloff_texture: self.falloff_texture.into(),
+            height: self.height.into(),
+            shadow_softness: self.shadow_softness.into(),
+        }
+    }
+
+    /// Creates new [`Light2D`] node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_light())
+    }
+
+    /// Creates new [`Light2D`] node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+/// Collects shadow occluder segments (in world coordinates) of every globally enabled occluder in
+/// the graph. The occluders are rectangles with enabled [`Rectangle::shadow_caster`] flag and tile
+/// maps with a shadow collider layer ([`TileMap::shadow_collider`]). Only the outlines of tile
+/// colliders are used (see [`merge_tile_collider_outlines`]), because the edges between adjacent
+/// tiles are fully covered by the shadows of the outer edges.
+pub fn collect_occluder_segments(graph: &Graph, segments: &mut Vec<OccluderSegment>) {
+    for node in graph.linear_iter() {
+        if !node.is_globally_enabled() {
+            continue;
+        }
+
+        if let Some(rectangle) = node.cast::<Rectangle>() {
+            if rectangle.shadow_caster() {
+                let transform = rectangle.global_transform();
+                let corners = [(-0.5, 0.5), (0.5, 0.5), (0.5, -0.5), (-0.5, -0.5)].map(|(x, y)| {
+                    transform
+                        .transform_point(&Point3::new(x, y, 0.0))
+                        .xy()
+                        .coords
+                });
+                for i in 0..corners.len() {
+                    segments.push([corners[i], corners[(i + 1) % corners.len()]]);
+                }
+            }
+        } else if let Some(tile_map) = node.cast::<TileMap>() {
+            collect_tile_map_segments(tile_map, segments);
+        }
+    }
+}
+
+fn collect_tile_map_segments(tile_map: &TileMap, segments: &mut Vec<OccluderSegment>) {
+    let collider_name = tile_map.shadow_collider();
+    if collider_name.is_empty() {
+        return;
+    }
+
+    let Some(tile_set) = tile_map.tile_set() else {
+        return;
+    };
+    let tile_set = tile_set.data_ref();
+    let Some(tile_set) = tile_set.as_loaded_ref() else {
+        return;
+    };
+    let Some(collider_uuid) = tile_set.collider_name_to_uuid(collider_name) else {
+        return;
+    };
+    let Some(tiles) = tile_map.tiles() else {
+        return;
+    };
+    let tiles = tiles.data_ref();
+    let Some(tiles) = tiles.as_loaded_ref() else {
+        return;
+    };
+
+    let transform = tile_map.tile_map_transform();
+    let grid_layout = tile_map.grid_layout();
+    let mut vertices = Vec::new();
+    let mut triangles = Vec::new();
+    for (position, handle) in tiles.iter() {
+        let Some(data) = tile_set.get_tile_data(handle.into()) else {
+            continue;
+        };
+        if let Some(collider) = data.colliders.get(&collider_uuid) {
+            collider.build_transformed_collider_shape(
+                &transform,
+                grid_layout.cell_origin(position).to_homogeneous(),
+                tiles.transformation(position),
+                &mut vertices,
+                &mut triangles,
+            );
+        }
+    }
+
+    let outlines = merge_tile_collider_outlines(&vertices, &triangles);
+    segments.extend(outline_segments(&outlines));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scene::{
+        base::BaseBuilder, dim2::rectangle::RectangleBuilder, transform::TransformBuilder,
+    };
+
+    #[test]
+    fn test_rectangle_occluder_segments() {
+        let mut graph = Graph::new();
+        RectangleBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(2.0, 0.0, 0.0))
+                    .with_local_scale(Vector3::new(2.0, 2.0, 1.0))
+                    .build(),
+            ),
+        )
+        .with_shadow_caster(true)
+        .build(&mut graph);
+        // Rectangles are not occluders by default.
+        RectangleBuilder::new(BaseBuilder::new()).build(&mut graph);
+        graph.update_hierarchical_data();
+
+        let mut segments = Vec::new();
+        collect_occluder_segments(&graph, &mut segments);
+        assert_eq!(segments.len(), 4);
+        assert_eq!(
+            segments[0],
+            [Vector2::new(1.0, 1.0), Vector2::new(3.0, 1.0)]
+        );
+        assert_eq!(
+            segments[2],
+            [Vector2::new(3.0, -1.0), Vector2::new(1.0, -1.0)]
+        );
+    }
+}
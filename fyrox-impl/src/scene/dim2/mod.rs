@@ -23,6 +23,38 @@
 
 pub mod collider;
 pub mod joint;
+pub mod light;
 pub mod physics;
 pub mod rectangle;
 pub mod rigidbody;
+
+use crate::core::{reflect::prelude::*, visitor::prelude::*};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how a 2D node ([`rectangle::Rectangle`] or [`crate::scene::tilemap::TileMap`]) is
+/// ordered relative to other nodes when rendering.
+#[derive(
+    Copy,
+    Clone,
+    Default,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum SortMode2D {
+    /// The node is sorted by its distance to the observer. Nodes sorted by depth are drawn before
+    /// any Y-sorted nodes at the same depth, which makes this mode suitable for backgrounds.
+    #[default]
+    Depth,
+    /// The node is sorted by world Y coordinate of its sort origin among other Y-sorted nodes at
+    /// the same depth. Nodes with lower Y coordinate are drawn on top of the nodes with higher
+    /// Y coordinate, which is used to create depth illusion in top-down games - a character that
+    /// stands below a tree is drawn in front of it, and vice versa.
+    WorldY,
+}
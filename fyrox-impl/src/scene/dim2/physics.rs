@@ -23,7 +23,7 @@
 use crate::{
     core::{
         algebra::{
-            Isometry2, Isometry3, Matrix4, Point2, Rotation3, Translation2, Translation3,
+            Isometry2, Isometry3, Matrix4, Point2, Point3, Rotation3, Translation2, Translation3,
             UnitComplex, UnitQuaternion, UnitVector2, Vector2, Vector3,
         },
         arrayvec::ArrayVec,
@@ -33,9 +33,10 @@ use crate::{
         parking_lot::Mutex,
         pool::Handle,
         reflect::prelude::*,
+        uuid::Uuid,
         variable::{InheritableVariable, VariableFlags},
         visitor::prelude::*,
-        BiDirHashMap, ImmutableString,
+        BiDirHashMap,
     },
     graph::{BaseSceneGraph, SceneGraphNode},
     scene::{
@@ -43,8 +44,11 @@ use crate::{
         collider::{self},
         debug::SceneDrawingContext,
         dim2::{
-            self, collider::ColliderShape, collider::TileMapShape, joint::JointLocalFrames,
-            joint::JointParams, rigidbody::ApplyAction,
+            self,
+            collider::{ColliderShape, TileColliderMergeMode, TileMapShape},
+            joint::JointLocalFrames,
+            joint::JointParams,
+            rigidbody::ApplyAction,
         },
         graph::{
             isometric_global_transform,
@@ -52,9 +56,13 @@ use crate::{
             Graph, NodePool,
         },
         node::{Node, NodeTrait},
-        tilemap::TileMap,
+        tilemap::{
+            merge_tile_collider_outlines, merge_tile_collider_polygons, tileset::TileSet, TileMap,
+            TileMapData, TileRect,
+        },
     },
 };
+use fxhash::{FxHashMap, FxHasher};
 pub use rapier2d::geometry::shape::*;
 use rapier2d::geometry::BroadPhase;
 use rapier2d::{
@@ -74,7 +82,7 @@ use std::{
     cell::RefCell,
     cmp::Ordering,
     fmt::{Debug, Formatter},
-    hash::Hash,
+    hash::{Hash, Hasher},
     num::NonZeroUsize,
     sync::Arc,
 };
@@ -323,56 +331,238 @@ fn convert_joint_params(
     joint
 }
 
-fn tile_map_to_collider_shape(
-    tile_map: &GeometrySource,
-    owner_inv_transform: Matrix4<f32>,
-    nodes: &NodePool,
-    collider_name: &ImmutableString,
-) -> Option<SharedShape> {
-    let tile_map = nodes.try_borrow(tile_map.0)?.component_ref::<TileMap>()?;
-
-    let tile_set_resource = tile_map.tile_set()?.data_ref();
-    let tile_set = tile_set_resource.as_loaded_ref()?;
+/// Shapes of the tiles of a chunk of a tile map in local coordinates of the tile map.
+struct TileMapChunkShape {
+    /// Revision of the chunk, which is used to detect changes of its tiles.
+    revision: u64,
+    /// Triangles of the tile colliders, they're kept only for [`TileColliderMergeMode::TriangleMesh`].
+    vertices: Vec<Point2<f32>>,
+    triangles: Vec<[u32; 3]>,
+    /// Convex polygons or outlines of the chunk, depending on the merge mode.
+    polygons: Vec<Vec<Point2<f32>>>,
+}
 
-    let tile_scale = tile_map.tile_scale();
-    let global_transform = owner_inv_transform
-        * tile_map.global_transform()
-        * Matrix4::new_nonuniform_scaling(&Vector3::new(-tile_scale.x, tile_scale.y, 1.0));
+impl TileMapChunkShape {
+    fn new(
+        revision: u64,
+        rect: TileRect,
+        tile_map: &TileMap,
+        tile_set: &TileSet,
+        tiles: &TileMapData,
+        collider_uuid: Uuid,
+        merge_mode: TileColliderMergeMode,
+    ) -> Self {
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+        for (tile_position, handle) in tiles.bounded_iter(rect.into()) {
+            let Some(tile_definition) = tile_set.get_tile_data(handle.into()) else {
+                continue;
+            };
 
-    let mut vertices = Vec::new();
-    let mut triangles = Vec::new();
+            if let Some(collider) = tile_definition.colliders.get(&collider_uuid) {
+                let position = tile_map
+                    .grid_layout()
+                    .cell_origin(tile_position)
+                    .to_homogeneous();
+                collider.build_transformed_collider_shape(
+                    &Matrix4::identity(),
+                    position,
+                    tiles.transformation(tile_position),
+                    &mut vertices,
+                    &mut triangles,
+                );
+            }
+        }
 
-    let collider_uuid = tile_set.collider_name_to_uuid(collider_name)?;
-    let tile_data = tile_map.tiles()?.data_ref();
-    let tile_data = tile_data.as_loaded_ref()?;
-    for (position, handle) in tile_data.iter() {
-        let Some(tile_definition) = tile_set.get_tile_data(handle.into()) else {
-            continue;
+        let polygons = match merge_mode {
+            TileColliderMergeMode::TriangleMesh => Vec::new(),
+            TileColliderMergeMode::ConvexPolygons => {
+                merge_tile_collider_polygons(&vertices, &triangles)
+            }
+            TileColliderMergeMode::EdgeChains => {
+                merge_tile_collider_outlines(&vertices, &triangles)
+            }
         };
+        if merge_mode != TileColliderMergeMode::TriangleMesh {
+            vertices = Vec::new();
+            triangles = Vec::new();
+        }
 
-        if let Some(collider) = tile_definition.colliders.get(&collider_uuid) {
-            let position = position.cast::<f32>().to_homogeneous();
-            collider.build_collider_shape(
-                &global_transform,
-                position,
-                &mut vertices,
-                &mut triangles,
-            );
+        Self {
+            revision,
+            vertices,
+            triangles,
+            polygons,
         }
     }
+}
+
+/// Shapes of the chunks of a tile map, that are used to update the shape of a tile map collider
+/// incrementally. When the tiles of the tile map are changed, only the shapes of the modified
+/// chunks are built again, and the shape of the collider is assembled from the shapes of the
+/// chunks. As a consequence, tiles are merged only with the tiles of the same chunk.
+#[derive(Default)]
+struct TileMapColliderCache {
+    /// Hash of everything that affects the shapes of every chunk: the tile map, its tile set and
+    /// layout, the collider layer and the merge mode.
+    hash: u64,
+    chunks: FxHashMap<Vector2<i32>, TileMapChunkShape>,
+}
 
-    if triangles.is_empty() {
-        None
-    } else {
-        Some(SharedShape::trimesh(vertices, triangles))
+fn tile_map_collider_hash(shape: &TileMapShape, tile_map: &TileMap) -> u64 {
+    let mut hasher = FxHasher::default();
+    shape.tile_map.0.hash(&mut hasher);
+    shape.layer_name.hash(&mut hasher);
+    shape.merge_mode.hash(&mut hasher);
+    tile_map.tile_set().map(|t| t.key()).hash(&mut hasher);
+    tile_map.grid_layout().hash(&mut hasher);
+    hasher.finish()
+}
+
+impl TileMapColliderCache {
+    /// Returns true if the tiles of the tile map were changed since the last call of
+    /// [`Self::build_shape`].
+    fn is_outdated(&self, shape: &TileMapShape, nodes: &NodePool) -> bool {
+        let Some(tile_map) = nodes
+            .try_borrow(shape.tile_map.0)
+            .and_then(|n| n.component_ref::<TileMap>())
+        else {
+            return false;
+        };
+        if self.hash != tile_map_collider_hash(shape, tile_map) {
+            return true;
+        }
+        let Some(tiles) = tile_map.tiles() else {
+            return !self.chunks.is_empty();
+        };
+        let tiles = tiles.data_ref();
+        let Some(tiles) = tiles.as_loaded_ref() else {
+            return false;
+        };
+        let mut count = 0;
+        for (rect, revision) in tiles.chunks() {
+            count += 1;
+            if self
+                .chunks
+                .get(&rect.position)
+                .map_or(true, |chunk| chunk.revision != revision)
+            {
+                return true;
+            }
+        }
+        count != self.chunks.len()
+    }
+
+    /// Builds the shape of the tile map collider, only the chunks that were changed since the
+    /// last call are built again.
+    fn build_shape(
+        &mut self,
+        shape: &TileMapShape,
+        owner_inv_transform: Matrix4<f32>,
+        nodes: &NodePool,
+    ) -> Option<SharedShape> {
+        let tile_map = nodes
+            .try_borrow(shape.tile_map.0)?
+            .component_ref::<TileMap>()?;
+
+        let hash = tile_map_collider_hash(shape, tile_map);
+        if self.hash != hash {
+            self.hash = hash;
+            self.chunks.clear();
+        }
+
+        let tile_set_resource = tile_map.tile_set()?.data_ref();
+        let tile_set = tile_set_resource.as_loaded_ref()?;
+        let collider_uuid = tile_set.collider_name_to_uuid(&shape.layer_name)?;
+        let tile_data = tile_map.tiles()?.data_ref();
+        let tile_data = tile_data.as_loaded_ref()?;
+
+        let mut chunks = FxHashMap::default();
+        for (rect, revision) in tile_data.chunks() {
+            let chunk = match self.chunks.remove(&rect.position) {
+                Some(chunk) if chunk.revision == revision => chunk,
+                _ => TileMapChunkShape::new(
+                    revision,
+                    rect,
+                    tile_map,
+                    tile_set,
+                    tile_data,
+                    collider_uuid,
+                    shape.merge_mode,
+                ),
+            };
+            chunks.insert(rect.position, chunk);
+        }
+        self.chunks = chunks;
+
+        let transform = owner_inv_transform * tile_map.tile_map_transform();
+        let transform_point =
+            |p: &Point2<f32>| transform.transform_point(&Point3::new(p.x, p.y, 0.0)).xy();
+        // Mirroring transformations change the winding of polygons, but convex polygons must be
+        // counter-clockwise.
+        let is_mirrored =
+            transform[(0, 0)] * transform[(1, 1)] - transform[(0, 1)] * transform[(1, 0)] < 0.0;
+
+        match shape.merge_mode {
+            TileColliderMergeMode::TriangleMesh => {
+                let mut vertices = Vec::new();
+                let mut triangles = Vec::new();
+                for chunk in self.chunks.values() {
+                    let origin = vertices.len() as u32;
+                    triangles.extend(chunk.triangles.iter().map(|t| t.map(|i| i + origin)));
+                    vertices.extend(chunk.vertices.iter().map(transform_point));
+                }
+                if triangles.is_empty() {
+                    None
+                } else {
+                    Some(SharedShape::trimesh(vertices, triangles))
+                }
+            }
+            TileColliderMergeMode::ConvexPolygons => {
+                let shapes = self
+                    .chunks
+                    .values()
+                    .flat_map(|chunk| chunk.polygons.iter())
+                    .filter_map(|polygon| {
+                        let mut points = polygon.iter().map(transform_point).collect::<Vec<_>>();
+                        if is_mirrored {
+                            points.reverse();
+                        }
+                        SharedShape::convex_polyline(points)
+                    })
+                    .map(|shape| (Isometry2::identity(), shape))
+                    .collect::<Vec<_>>();
+                if shapes.is_empty() {
+                    None
+                } else {
+                    Some(SharedShape::compound(shapes))
+                }
+            }
+            TileColliderMergeMode::EdgeChains => {
+                let mut points = Vec::new();
+                let mut indices = Vec::new();
+                for outline in self.chunks.values().flat_map(|chunk| chunk.polygons.iter()) {
+                    let origin = points.len() as u32;
+                    let count = outline.len() as u32;
+                    indices.extend((0..count).map(|i| [origin + i, origin + (i + 1) % count]));
+                    points.extend(outline.iter().map(transform_point));
+                }
+                if points.is_empty() {
+                    None
+                } else {
+                    Some(SharedShape::polyline(points, Some(indices)))
+                }
+            }
+        }
     }
 }
 
-// Converts descriptor in a shared shape.
+// Converts descriptor in a shared shape. The cache is used only by tile map shapes.
 fn collider_shape_into_native_shape(
     shape: &ColliderShape,
     owner_inv_transform: Matrix4<f32>,
     nodes: &NodePool,
+    tile_map_cache: &mut TileMapColliderCache,
 ) -> Option<SharedShape> {
     match shape {
         ColliderShape::Ball(ball) => Some(SharedShape::ball(ball.radius)),
@@ -399,10 +589,9 @@ fn collider_shape_into_native_shape(
         ColliderShape::Heightfield(_) => {
             None // TODO
         }
-        ColliderShape::TileMap(TileMapShape {
-            tile_map,
-            layer_name: collider_layer_name,
-        }) => tile_map_to_collider_shape(tile_map, owner_inv_transform, nodes, collider_layer_name),
+        ColliderShape::TileMap(tile_map_shape) => {
+            tile_map_cache.build_shape(tile_map_shape, owner_inv_transform, nodes)
+        }
     }
 }
 
@@ -482,6 +671,10 @@ pub struct PhysicsWorld {
     #[visit(skip)]
     #[reflect(hidden)]
     debug_render_pipeline: Mutex<DebugRenderPipeline>,
+    // Shapes of the chunks of tile maps, that are used by tile map colliders.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    tile_map_colliders: FxHashMap<ColliderHandle, TileMapColliderCache>,
 }
 
 impl Clone for PhysicsWorld {
@@ -591,6 +784,7 @@ impl PhysicsWorld {
             query: RefCell::new(Default::default()),
             performance_statistics: Default::default(),
             debug_render_pipeline: Default::default(),
+            tile_map_colliders: Default::default(),
         }
     }
 
@@ -672,6 +866,7 @@ impl PhysicsWorld {
     }
 
     pub(crate) fn remove_collider(&mut self, handle: ColliderHandle) -> bool {
+        self.tile_map_colliders.remove(&handle);
         self.colliders
             .remove(handle, &mut self.islands, &mut self.bodies, false)
             .is_some()
@@ -1058,6 +1253,36 @@ impl PhysicsWorld {
         }
     }
 
+    /// Rebuilds the shape of the given tile map collider, if the tiles of its tile map were
+    /// changed. Only the modified chunks of the tile map are built again.
+    fn sync_tile_map_collider(
+        &mut self,
+        nodes: &NodePool,
+        handle: Handle<Node>,
+        collider_node: &scene::dim2::collider::Collider,
+        shape: &TileMapShape,
+    ) {
+        let native_handle = collider_node.native.get();
+        if native_handle == ColliderHandle::invalid() {
+            return;
+        }
+        let cache = self.tile_map_colliders.entry(native_handle).or_default();
+        if !cache.is_outdated(shape, nodes) {
+            return;
+        }
+        let inv_global_transform = isometric_global_transform(nodes, handle)
+            .try_inverse()
+            .unwrap_or_default();
+        if let Some(new_shape) = cache.build_shape(shape, inv_global_transform, nodes) {
+            if let Some(native) = self.colliders.get_mut(native_handle) {
+                native.set_shape(new_shape);
+            }
+        } else {
+            self.remove_collider(native_handle);
+            collider_node.native.set(ColliderHandle::invalid());
+        }
+    }
+
     pub(crate) fn sync_to_collider_node(
         &mut self,
         nodes: &NodePool,
@@ -1113,9 +1338,19 @@ impl PhysicsWorld {
                             .try_inverse()
                             .unwrap_or_default();
 
-                        if let Some(shape) =
-                            collider_shape_into_native_shape(&v, inv_global_transform, nodes)
-                        {
+                        // The shape description was changed, so every chunk is built again.
+                        let mut tile_map_cache = TileMapColliderCache::default();
+                        let shape = collider_shape_into_native_shape(
+                            &v,
+                            inv_global_transform,
+                            nodes,
+                            &mut tile_map_cache,
+                        );
+                        if let ColliderShape::TileMap(_) = v {
+                            self.tile_map_colliders
+                                .insert(collider_node.native.get(), tile_map_cache);
+                        }
+                        if let Some(shape) = shape {
                             native.set_shape(shape);
                         } else {
                             remove_collider = true;
@@ -1127,6 +1362,10 @@ impl PhysicsWorld {
                     }
                 }
             }
+
+            if let ColliderShape::TileMap(tile_map_shape) = collider_node.shape() {
+                self.sync_tile_map_collider(nodes, handle, collider_node, tile_map_shape);
+            }
         } else if let Some(parent_body) = nodes
             .try_borrow(collider_node.parent())
             .and_then(|n| n.cast::<dim2::rigidbody::RigidBody>())
@@ -1136,10 +1375,12 @@ impl PhysicsWorld {
                 let inv_global_transform = isometric_global_transform(nodes, handle)
                     .try_inverse()
                     .unwrap();
+                let mut tile_map_cache = TileMapColliderCache::default();
                 if let Some(shape) = collider_shape_into_native_shape(
                     collider_node.shape(),
                     inv_global_transform,
                     nodes,
+                    &mut tile_map_cache,
                 ) {
                     let mut builder = ColliderBuilder::new(shape)
                         .position(Isometry2 {
@@ -1173,6 +1414,11 @@ impl PhysicsWorld {
 
                     collider_node.native.set(native_handle);
 
+                    if let ColliderShape::TileMap(_) = collider_node.shape() {
+                        self.tile_map_colliders
+                            .insert(native_handle, tile_map_cache);
+                    }
+
                     Log::writeln(
                         MessageKind::Information,
                         format!(
@@ -40,6 +40,7 @@ use crate::{
     renderer::{self, bundle::RenderContext},
     scene::{
         base::{Base, BaseBuilder},
+        dim2::SortMode2D,
         graph::Graph,
         mesh::buffer::{
             VertexAttributeDataType, VertexAttributeDescriptor, VertexAttributeUsage, VertexTrait,
@@ -175,6 +176,15 @@ pub struct Rectangle {
     uv_rect: InheritableVariable<Rect<f32>>,
 
     material: InheritableVariable<MaterialResource>,
+
+    #[reflect(setter = "set_sort_mode")]
+    sort_mode: InheritableVariable<SortMode2D>,
+
+    #[reflect(setter = "set_sort_origin")]
+    sort_origin: InheritableVariable<Vector2<f32>>,
+
+    #[reflect(setter = "set_shadow_caster")]
+    shadow_caster: InheritableVariable<bool>,
 }
 
 impl Visit for Rectangle {
@@ -196,6 +206,9 @@ impl Visit for Rectangle {
         self.base.visit("Base", &mut region)?;
         self.color.visit("Color", &mut region)?;
         let _ = self.uv_rect.visit("UvRect", &mut region);
+        let _ = self.sort_mode.visit("SortMode", &mut region);
+        let _ = self.sort_origin.visit("SortOrigin", &mut region);
+        let _ = self.shadow_caster.visit("ShadowCaster", &mut region);
 
         Ok(())
     }
@@ -211,6 +224,9 @@ impl Default for Rectangle {
                 Default::default(),
                 Material::standard_2d(),
             )),
+            sort_mode: Default::default(),
+            sort_origin: Default::default(),
+            shadow_caster: Default::default(),
         }
     }
 }
@@ -274,6 +290,45 @@ impl Rectangle {
     pub fn set_uv_rect(&mut self, uv_rect: Rect<f32>) -> Rect<f32> {
         self.uv_rect.set_value_and_mark_modified(uv_rect)
     }
+
+    /// Returns current sorting mode of the rectangle.
+    pub fn sort_mode(&self) -> SortMode2D {
+        *self.sort_mode
+    }
+
+    /// Sets a new sorting mode of the rectangle. Use [`SortMode2D::WorldY`] for characters and props
+    /// in top-down games, so they will be correctly drawn behind or in front of each other (and
+    /// Y-sorted tile maps). See [`SortMode2D`] docs for more info.
+    pub fn set_sort_mode(&mut self, sort_mode: SortMode2D) -> SortMode2D {
+        self.sort_mode.set_value_and_mark_modified(sort_mode)
+    }
+
+    /// Returns current sort origin of the rectangle.
+    pub fn sort_origin(&self) -> Vector2<f32> {
+        *self.sort_origin
+    }
+
+    /// Sets a new sort origin of the rectangle. The sort origin is a point in local coordinates of
+    /// the rectangle, which world Y coordinate is used for sorting in [`SortMode2D::WorldY`] mode.
+    /// For example, `(0.0, -0.5)` is the bottom edge of the rectangle which is usually where the
+    /// feet of a character are. The default value is `(0.0, 0.0)` which is the center of the
+    /// rectangle.
+    pub fn set_sort_origin(&mut self, sort_origin: Vector2<f32>) -> Vector2<f32> {
+        self.sort_origin.set_value_and_mark_modified(sort_origin)
+    }
+
+    /// Returns `true` if the rectangle casts 2D shadows, `false` - otherwise.
+    pub fn shadow_caster(&self) -> bool {
+        *self.shadow_caster
+    }
+
+    /// Defines whether the rectangle casts shadows from 2D lights (see
+    /// [`super::light::Light2D`]) or not. The entire rectangle is used as an occluder, regardless
+    /// of its texture. Rectangles do not cast 2D shadows by default.
+    pub fn set_shadow_caster(&mut self, shadow_caster: bool) -> bool {
+        self.shadow_caster
+            .set_value_and_mark_modified(shadow_caster)
+    }
 }
 
 impl ConstructorProvider<Node, Graph> for Rectangle {
@@ -311,7 +366,7 @@ impl NodeTrait for Rectangle {
             return RdcControlFlow::Continue;
         }
 
-        let global_transform = self.global_transform();
+        let global_transform = ctx.snap_to_pixel_grid(self.global_transform());
 
         type Vertex = RectangleVertex;
 
@@ -348,7 +403,10 @@ impl NodeTrait for Rectangle {
 
         let triangles = [TriangleDefinition([0, 1, 2]), TriangleDefinition([2, 3, 0])];
 
-        let sort_index = ctx.calculate_sorting_index(self.global_position());
+        let sort_origin = global_transform
+            .transform_point(&Point3::new(self.sort_origin.x, self.sort_origin.y, 0.0))
+            .coords;
+        let sort_index = ctx.calculate_sorting_index_2d(sort_origin, *self.sort_mode);
 
         ctx.storage.push_triangles(
             Vertex::layout(),
@@ -380,6 +438,9 @@ pub struct RectangleBuilder {
     color: Color,
     uv_rect: Rect<f32>,
     material: MaterialResource,
+    sort_mode: SortMode2D,
+    sort_origin: Vector2<f32>,
+    shadow_caster: bool,
 }
 
 impl RectangleBuilder {
@@ -390,6 +451,9 @@ impl RectangleBuilder {
             color: Color::WHITE,
             uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
             material: MaterialResource::new_ok(Default::default(), Material::standard_2d()),
+            sort_mode: Default::default(),
+            sort_origin: Default::default(),
+            shadow_caster: false,
         }
     }
 
@@ -412,6 +476,27 @@ impl RectangleBuilder {
         self
     }
 
+    /// Sets the desired sorting mode of the rectangle. See [`Rectangle::set_sort_mode`] for more
+    /// info.
+    pub fn with_sort_mode(mut self, sort_mode: SortMode2D) -> Self {
+        self.sort_mode = sort_mode;
+        self
+    }
+
+    /// Sets the desired sort origin of the rectangle. See [`Rectangle::set_sort_origin`] for more
+    /// info.
+    pub fn with_sort_origin(mut self, sort_origin: Vector2<f32>) -> Self {
+        self.sort_origin = sort_origin;
+        self
+    }
+
+    /// Sets whether the rectangle casts 2D shadows or not. See [`Rectangle::set_shadow_caster`] for
+    /// more info.
+    pub fn with_shadow_caster(mut self, shadow_caster: bool) -> Self {
+        self.shadow_caster = shadow_caster;
+        self
+    }
+
     /// Creates new [`Rectangle`] instance.
     pub fn build_rectangle(self) -> Rectangle {
         Rectangle {
@@ -419,6 +504,9 @@ impl RectangleBuilder {
             color: self.color.into(),
             uv_rect: self.uv_rect.into(),
             material: self.material.into(),
+            sort_mode: self.sort_mode.into(),
+            sort_origin: self.sort_origin.into(),
+            shadow_caster: self.shadow_caster.into(),
         }
     }
 
@@ -52,6 +52,7 @@ use crate::{
         math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
         pool::{ErasedHandle, Handle, MultiBorrowContext, Pool, Ticket},
         reflect::prelude::*,
+        type_traits::TypeUuidProvider,
         visitor::{Visit, VisitResult, Visitor},
     },
     graph::{AbstractSceneGraph, AbstractSceneNode, BaseSceneGraph, NodeHandleMap, SceneGraph},
@@ -64,6 +65,7 @@ use crate::{
         graph::{
             event::{GraphEvent, GraphEventBroadcaster},
             physics::{PhysicsPerformanceStatistics, PhysicsWorld},
+            script_index::ScriptIndex,
         },
         mesh::Mesh,
         navmesh,
@@ -88,6 +90,7 @@ use std::{
 
 pub mod event;
 pub mod physics;
+pub mod script_index;
 
 /// Graph performance statistics. Allows you to find out "hot" parts of the scene graph, which
 /// parts takes the most time to update.
@@ -168,6 +171,9 @@ pub struct Graph {
     pub(crate) message_receiver: Receiver<NodeMessage>,
 
     instance_id_map: FxHashMap<SceneNodeId, Handle<Node>>,
+
+    #[reflect(hidden)]
+    script_index: ScriptIndex,
 }
 
 impl Default for Graph {
@@ -190,6 +196,7 @@ impl Default for Graph {
             lightmap: None,
             instance_id_map: Default::default(),
             message_receiver,
+            script_index: Default::default(),
         }
     }
 }
@@ -298,10 +305,12 @@ impl Graph {
         // Add it to the pool.
         let mut pool = Pool::new();
         let root = pool.spawn(Node::new(root_node));
+        let script_index = ScriptIndex::default();
         pool[root].on_connected_to_graph(
             root,
             message_sender.clone(),
             script_message_sender.clone(),
+            script_index.clone(),
         );
 
         let instance_id_map = FxHashMap::from_iter([(instance_id, root)]);
@@ -321,6 +330,7 @@ impl Graph {
             lightmap: None,
             instance_id_map,
             message_receiver,
+            script_index,
         }
     }
 
@@ -648,15 +658,62 @@ impl Graph {
     }
 
     fn restore_dynamic_node_data(&mut self) {
+        self.script_index.clear();
         for (handle, node) in self.pool.pair_iter_mut() {
             node.on_connected_to_graph(
                 handle,
                 self.message_sender.clone(),
                 self.script_message_sender.clone(),
+                self.script_index.clone(),
             );
         }
     }
 
+    /// Re-registers scripts of every node in the script index. It is needed when scripts were
+    /// replaced bypassing the methods of [`Base`](crate::scene::base::Base), for example during
+    /// hot reloading.
+    pub(crate) fn rebuild_script_index(&mut self) {
+        self.script_index.clear();
+        for node in self.pool.iter_mut() {
+            node.set_script_index(self.script_index.clone());
+        }
+    }
+
+    /// Returns a reference to the script index of the graph, that could be used to find nodes
+    /// with scripts of a particular type.
+    pub fn script_index(&self) -> &ScriptIndex {
+        &self.script_index
+    }
+
+    /// Returns handles of all nodes that have at least one script of the given type `T`. Unlike
+    /// iterating over the entire graph and checking every node, this method uses the script index
+    /// of the graph and its cost depends only on the amount of such nodes. Returned handles are
+    /// sorted.
+    pub fn nodes_with_script<T>(&self) -> Vec<Handle<Node>>
+    where
+        T: ScriptTrait + TypeUuidProvider,
+    {
+        self.script_index
+            .nodes_with_script_type(<T as TypeUuidProvider>::type_uuid())
+    }
+
+    /// Returns an iterator that yields every script of the given type `T` in the graph together with
+    /// the handle of its node. See [`Self::nodes_with_script`] for more info. Scripts, that are
+    /// temporarily taken from their nodes (for example, the script whose method is executing right
+    /// now) are not yielded.
+    pub fn scripts_of_type<T>(&self) -> impl Iterator<Item = (Handle<Node>, &T)>
+    where
+        T: ScriptTrait + TypeUuidProvider,
+    {
+        self.nodes_with_script::<T>()
+            .into_iter()
+            .filter_map(|handle| self.pool.try_borrow(handle).map(|node| (handle, node)))
+            .flat_map(|(handle, node)| {
+                node.try_get_scripts::<T>()
+                    .map(move |script| (handle, script))
+            })
+    }
+
     // Fix property flags for scenes made before inheritance system was fixed. By default, all inheritable properties
     // must be marked as modified in nodes without any parent resource.
     pub(crate) fn mark_ancestor_nodes_as_modified(&mut self) {
@@ -1055,6 +1112,8 @@ impl Graph {
     }
 
     fn sync_native(&mut self, switches: &GraphUpdateSwitches) {
+        self.sound_context.begin_listeners_sync();
+
         let mut sync_context = SyncContext {
             nodes: &self.pool,
             physics: &mut self.physics,
@@ -1066,6 +1125,8 @@ impl Graph {
         for (handle, node) in self.pool.pair_iter() {
             node.sync_native(handle, &mut sync_context);
         }
+
+        self.sound_context.end_listeners_sync();
     }
 
     fn update_node(
@@ -1240,6 +1301,7 @@ impl Graph {
     pub(crate) fn take_reserve_internal(&mut self, handle: Handle<Node>) -> (Ticket<Node>, Node) {
         let (ticket, mut node) = self.pool.take_reserve(handle);
         self.instance_id_map.remove(&node.instance_id);
+        node.detach_script_index();
         node.on_removed_from_graph(self);
         (ticket, node)
     }
@@ -1256,6 +1318,7 @@ impl Graph {
         let instance_id = node.instance_id;
         let handle = self.pool.put_back(ticket, node);
         self.instance_id_map.insert(instance_id, handle);
+        self.pool[handle].set_script_index(self.script_index.clone());
         handle
     }
 
@@ -1705,8 +1768,9 @@ impl BaseSceneGraph for Graph {
 
         let script_message_sender = self.script_message_sender.clone();
         let message_sender = self.message_sender.clone();
+        let script_index = self.script_index.clone();
         let node = &mut self.pool[handle];
-        node.on_connected_to_graph(handle, message_sender, script_message_sender);
+        node.on_connected_to_graph(handle, message_sender, script_message_sender, script_index);
 
         self.instance_id_map.insert(node.instance_id, handle);
 
@@ -1727,6 +1791,7 @@ impl BaseSceneGraph for Graph {
             // Remove associated entities.
             let mut node = self.pool.free(handle);
             self.instance_id_map.remove(&node.instance_id);
+            node.detach_script_index();
             node.on_removed_from_graph(self);
 
             self.event_broadcaster
@@ -1942,6 +2007,48 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_scripts_of_type() {
+        let mut graph = Graph::new();
+
+        let a = PivotBuilder::new(BaseBuilder::new().with_script(MyScript {
+            foo: "A".to_string(),
+            bar: 1.0,
+        }))
+        .build(&mut graph);
+        let b = PivotBuilder::new(BaseBuilder::new().with_script(MyOtherScript {
+            baz: 2,
+            foobar: vec![],
+        }))
+        .build(&mut graph);
+
+        assert_eq!(graph.nodes_with_script::<MyScript>(), vec![a]);
+        assert_eq!(graph.nodes_with_script::<MyOtherScript>(), vec![b]);
+
+        graph[b].add_script(MyScript {
+            foo: "B".to_string(),
+            bar: 2.0,
+        });
+        assert_eq!(
+            graph
+                .scripts_of_type::<MyScript>()
+                .map(|(handle, script)| (handle, script.foo.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(a, "A"), (b, "B")]
+        );
+
+        graph[b].remove_script(0);
+        assert!(graph.nodes_with_script::<MyOtherScript>().is_empty());
+
+        graph.remove_node(a);
+        assert_eq!(graph.nodes_with_script::<MyScript>(), vec![b]);
+
+        let (ticket, node) = graph.take_reserve(b);
+        assert!(graph.nodes_with_script::<MyScript>().is_empty());
+        graph.put_back(ticket, node);
+        assert_eq!(graph.nodes_with_script::<MyScript>(), vec![b]);
+    }
+
     #[test]
     fn graph_init_test() {
         let graph = Graph::new();
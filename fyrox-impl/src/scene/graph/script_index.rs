@@ -0,0 +1,116 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Index of scripts assigned to the nodes of a graph. See [`ScriptIndex`] docs for more info.
+
+use crate::{
+    core::{parking_lot::Mutex, pool::Handle, uuid::Uuid},
+    scene::node::Node,
+};
+use fxhash::FxHashMap;
+use std::sync::Arc;
+
+/// Script index allows to find all nodes carrying a script of a particular type without iterating
+/// over the entire graph. The index is shared between a graph and its nodes and updated every
+/// time when a script is added to a node or removed from it. Scripts are identified by their type
+/// uuids, which means that the index stays valid across hot reloading.
+#[derive(Clone, Default, Debug)]
+pub struct ScriptIndex {
+    // Script type uuid -> node handle -> count of scripts of the type on the node.
+    map: Arc<Mutex<FxHashMap<Uuid, FxHashMap<Handle<Node>, usize>>>>,
+}
+
+impl ScriptIndex {
+    pub(crate) fn register(&self, node: Handle<Node>, script_type: Uuid) {
+        *self
+            .map
+            .lock()
+            .entry(script_type)
+            .or_default()
+            .entry(node)
+            .or_default() += 1;
+    }
+
+    pub(crate) fn unregister(&self, node: Handle<Node>, script_type: Uuid) {
+        let mut map = self.map.lock();
+        if let Some(nodes) = map.get_mut(&script_type) {
+            if let Some(count) = nodes.get_mut(&node) {
+                *count = count.saturating_sub(1);
+                if *count == 0 {
+                    nodes.remove(&node);
+                }
+            }
+            if nodes.is_empty() {
+                map.remove(&script_type);
+            }
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        self.map.lock().clear();
+    }
+
+    /// Returns handles of all nodes that have at least one script with the given type uuid. The
+    /// handles are sorted, so the order is stable between runs.
+    pub fn nodes_with_script_type(&self, script_type: Uuid) -> Vec<Handle<Node>> {
+        let mut nodes = self
+            .map
+            .lock()
+            .get(&script_type)
+            .map(|nodes| nodes.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        nodes.sort_unstable_by_key(|handle| (handle.index(), handle.generation()));
+        nodes
+    }
+
+    /// Returns total count of scripts with the given type uuid across all nodes.
+    pub fn script_count(&self, script_type: Uuid) -> usize {
+        self.map
+            .lock()
+            .get(&script_type)
+            .map_or(0, |nodes| nodes.values().sum())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_script_index() {
+        let index = ScriptIndex::default();
+        let script_type = Uuid::new_v4();
+        let a = Handle::<Node>::new(2, 1);
+        let b = Handle::<Node>::new(1, 1);
+
+        index.register(a, script_type);
+        index.register(a, script_type);
+        index.register(b, script_type);
+        assert_eq!(index.nodes_with_script_type(script_type), vec![b, a]);
+        assert_eq!(index.script_count(script_type), 3);
+
+        index.unregister(a, script_type);
+        assert_eq!(index.nodes_with_script_type(script_type), vec![b, a]);
+        index.unregister(a, script_type);
+        index.unregister(b, script_type);
+        assert!(index.nodes_with_script_type(script_type).is_empty());
+        assert_eq!(index.script_count(script_type), 0);
+    }
+}
@@ -34,19 +34,22 @@
 //!
 //! Most of light sources supports shadows (via shadows maps) and light scattering,
 //! these are common effects for modern games but still can significantly impact
-//! performance.
+//! performance. Spot and directional light sources could also use variance shadow
+//! maps instead of the classic depth shadow maps, see [`ShadowTechnique`] for more info.
 
 use crate::{
     core::{
         algebra::Vector3,
         color::Color,
         reflect::prelude::*,
+        uuid_provider,
         variable::InheritableVariable,
         visitor::{Visit, VisitResult, Visitor},
     },
     scene::base::{Base, BaseBuilder},
 };
 use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 pub mod directional;
 pub mod point;
@@ -64,6 +67,70 @@ pub const DEFAULT_SCATTER_G: f32 = 0.03;
 /// significant value and you'll clearly see light volume with such settings.
 pub const DEFAULT_SCATTER_B: f32 = 0.03;
 
+/// Defines how shadows of a light source are stored and filtered. Point light sources always use
+/// [`ShadowTechnique::DepthMap`], other techniques are supported only by spot and directional light
+/// sources.
+#[derive(
+    Reflect, Clone, Copy, Visit, Debug, PartialEq, Default, AsRefStr, EnumString, VariantNames,
+)]
+pub enum ShadowTechnique {
+    /// Classic shadow maps, that store the depth of the closest shadow caster. Such shadows are
+    /// filtered using the shadow filter from the quality settings of the renderer.
+    ///
+    /// This is default technique.
+    #[default]
+    DepthMap,
+    /// Variance shadow maps (VSM), that store the mean depth of the shadow casters and its square.
+    /// Such shadow maps can be blurred as any other image, which gives soft and stable shadows with
+    /// fixed cost per pixel. Overlapping shadow casters could cause light bleeding - lit areas
+    /// inside the shadows.
+    Variance {
+        /// Radius of the blur kernel in texels. The larger the radius, the softer the shadows are.
+        blur_radius: u32,
+        /// Fraction of the shadow that is cut off to hide light bleeding. Must be in `[0; 1)` range,
+        /// larger values remove more light bleeding but make the shadows harder and darker.
+        light_bleeding_reduction: f32,
+    },
+    /// Exponential variance shadow maps (EVSM), that store the moments of exponentially warped
+    /// depth. This technique has much less light bleeding than [`ShadowTechnique::Variance`], but
+    /// it is slightly more expensive.
+    ExponentialVariance {
+        /// Radius of the blur kernel in texels. The larger the radius, the softer the shadows are.
+        blur_radius: u32,
+        /// Fraction of the shadow that is cut off to hide light bleeding. Must be in `[0; 1)` range,
+        /// larger values remove more light bleeding but make the shadows harder and darker.
+        light_bleeding_reduction: f32,
+        /// Exponent of the positive depth warp. Larger values reduce light bleeding, but values
+        /// larger than `42.0` overflow 32-bit floating point numbers.
+        positive_exponent: f32,
+        /// Exponent of the negative depth warp. Larger values reduce light bleeding, but values
+        /// larger than `42.0` overflow 32-bit floating point numbers.
+        negative_exponent: f32,
+    },
+}
+
+uuid_provider!(ShadowTechnique = "9e14da31-b56e-4d86-b40f-d3109e51f526");
+
+impl ShadowTechnique {
+    /// Creates variance shadow technique with default parameters.
+    pub fn variance() -> Self {
+        Self::Variance {
+            blur_radius: 2,
+            light_bleeding_reduction: 0.2,
+        }
+    }
+
+    /// Creates exponential variance shadow technique with default parameters.
+    pub fn exponential_variance() -> Self {
+        Self::ExponentialVariance {
+            blur_radius: 2,
+            light_bleeding_reduction: 0.1,
+            positive_exponent: 40.0,
+            negative_exponent: 5.0,
+        }
+    }
+}
+
 /// Light scene node. It contains common properties of light such as color,
 /// scattering factor (per color channel) and other useful properties. Exact
 /// behavior defined by specific light kind.
@@ -84,6 +151,10 @@ pub struct BaseLight {
     #[reflect(min_value = 0.0, step = 0.1)]
     #[reflect(setter = "set_intensity")]
     intensity: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_shadow_technique")]
+    shadow_technique: InheritableVariable<ShadowTechnique>,
 }
 
 impl Deref for BaseLight {
@@ -112,6 +183,7 @@ impl Default for BaseLight {
             )),
             scatter_enabled: InheritableVariable::new_modified(true),
             intensity: InheritableVariable::new_modified(1.0),
+            shadow_technique: InheritableVariable::new_modified(ShadowTechnique::default()),
         }
     }
 }
@@ -179,6 +251,18 @@ impl BaseLight {
     pub fn is_scatter_enabled(&self) -> bool {
         *self.scatter_enabled
     }
+
+    /// Sets new shadow technique of the light source. See [`ShadowTechnique`] for more info.
+    #[inline]
+    pub fn set_shadow_technique(&mut self, technique: ShadowTechnique) -> ShadowTechnique {
+        self.shadow_technique.set_value_and_mark_modified(technique)
+    }
+
+    /// Returns current shadow technique of the light source.
+    #[inline]
+    pub fn shadow_technique(&self) -> ShadowTechnique {
+        *self.shadow_technique
+    }
 }
 
 /// Light scene node builder. Provides easy declarative way of creating light scene
@@ -189,6 +273,7 @@ pub struct BaseLightBuilder {
     scatter_factor: Vector3<f32>,
     scatter_enabled: bool,
     intensity: f32,
+    shadow_technique: ShadowTechnique,
 }
 
 impl BaseLightBuilder {
@@ -203,6 +288,7 @@ impl BaseLightBuilder {
             scatter_factor: Vector3::new(DEFAULT_SCATTER_R, DEFAULT_SCATTER_G, DEFAULT_SCATTER_B),
             scatter_enabled: true,
             intensity: 1.0,
+            shadow_technique: Default::default(),
         }
     }
 
@@ -230,6 +316,12 @@ impl BaseLightBuilder {
         self
     }
 
+    /// Sets desired shadow technique.
+    pub fn with_shadow_technique(mut self, technique: ShadowTechnique) -> Self {
+        self.shadow_technique = technique;
+        self
+    }
+
     /// Creates new instance of base light.
     pub fn build(self) -> BaseLight {
         BaseLight {
@@ -238,6 +330,7 @@ impl BaseLightBuilder {
             scatter: self.scatter_factor.into(),
             scatter_enabled: self.scatter_enabled.into(),
             intensity: self.intensity.into(),
+            shadow_technique: self.shadow_technique.into(),
         }
     }
 }
@@ -23,6 +23,7 @@
 
 use crate::{
     asset::{
+        budget::ResourceMemoryCategory,
         io::ResourceIo,
         loader::{BoxedLoaderFuture, LoaderPayload, ResourceLoader},
         state::LoadError,
@@ -249,6 +250,15 @@ impl ResourceData for SurfaceData {
     fn can_be_saved(&self) -> bool {
         true
     }
+
+    fn memory_usage(&self) -> usize {
+        self.vertex_buffer.raw_data().len()
+            + std::mem::size_of_val(self.geometry_buffer.triangles_ref())
+    }
+
+    fn memory_category(&self) -> ResourceMemoryCategory {
+        ResourceMemoryCategory::Mesh
+    }
 }
 
 impl SurfaceData {
@@ -58,6 +58,7 @@ use crate::{
         log::{Log, MessageKind},
         pool::{Handle, Pool, Ticket},
         reflect::prelude::*,
+        type_traits::TypeUuidProvider,
         visitor::{Visit, VisitError, VisitResult, Visitor},
     },
     engine::SerializationContext,
@@ -72,6 +73,7 @@ use crate::{
         node::Node,
         sound::SoundEngine,
     },
+    script::ScriptTrait,
     utils::navmesh::Navmesh,
 };
 use asset::io::ResourceIo;
@@ -470,6 +472,27 @@ impl Scene {
         )
     }
 
+    /// Returns an iterator that yields every script of the given type `T` in the scene together
+    /// with the handle of its node. The scripts are found using the script index of the graph, so
+    /// there's no need to iterate over the entire graph. See [`Graph::scripts_of_type`] for more
+    /// info.
+    pub fn scripts_of_type<T>(&self) -> impl Iterator<Item = (Handle<Node>, &T)>
+    where
+        T: ScriptTrait + TypeUuidProvider,
+    {
+        self.graph.scripts_of_type::<T>()
+    }
+
+    /// Returns handles of all nodes in the scene that have at least one script of the given type
+    /// `T`. Use this method if you need mutable access to the scripts. See
+    /// [`Graph::nodes_with_script`] for more info.
+    pub fn nodes_with_script<T>(&self) -> Vec<Handle<Node>>
+    where
+        T: ScriptTrait + TypeUuidProvider,
+    {
+        self.graph.nodes_with_script::<T>()
+    }
+
     fn visit(&mut self, region_name: &str, visitor: &mut Visitor) -> VisitResult {
         let mut region = visitor.enter_region(region_name)?;
 
@@ -26,7 +26,7 @@ use crate::scene::{
     animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
     camera::Camera,
     decal::Decal,
-    dim2::{self, rectangle::Rectangle},
+    dim2::{self, light::Light2D, rectangle::Rectangle},
     light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
     mesh::Mesh,
     navmesh::NavigationalMesh,
@@ -54,6 +54,7 @@ pub fn new_node_constructor_container() -> NodeConstructorContainer {
     container.add::<dim2::collider::Collider>();
     container.add::<dim2::joint::Joint>();
     container.add::<Rectangle>();
+    container.add::<Light2D>();
     container.add::<dim2::rigidbody::RigidBody>();
     container.add::<DirectionalLight>();
     container.add::<PointLight>();
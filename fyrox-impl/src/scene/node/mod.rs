@@ -40,6 +40,7 @@ use crate::{
     graph::SceneGraphNode,
     renderer::bundle::RenderContext,
     resource::model::ModelResource,
+    save::SaveState,
     scene::{
         self,
         animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
@@ -241,6 +242,12 @@ pub trait NodeTrait: BaseNodeTrait + Reflect + Visit + ComponentProvider {
     fn validate(&self, #[allow(unused_variables)] scene: &Scene) -> Result<(), String> {
         Ok(())
     }
+
+    /// Returns the node as [`SaveState`], if the node has a state that should be stored in save
+    /// files. See [`crate::save`] module docs for more info.
+    fn as_save_state_mut(&mut self) -> Option<&mut dyn SaveState> {
+        None
+    }
 }
 
 /// Node is the basic building block for 3D scenes. It has multiple variants, but all of them share some
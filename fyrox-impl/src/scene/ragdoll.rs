@@ -104,6 +104,9 @@ impl Reflect for Limb {
                 max_value: None,
                 step: None,
                 precision: None,
+                category: "",
+                slider: false,
+                visible: true,
                 doc: "",
             },
             FieldInfo {
@@ -121,6 +124,9 @@ impl Reflect for Limb {
                 max_value: None,
                 step: None,
                 precision: None,
+                category: "",
+                slider: false,
+                visible: true,
                 doc: "",
             },
             FieldInfo {
@@ -138,6 +144,9 @@ impl Reflect for Limb {
                 max_value: None,
                 step: None,
                 precision: None,
+                category: "",
+                slider: false,
+                visible: true,
                 doc: "",
             },
         ])
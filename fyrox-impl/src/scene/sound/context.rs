@@ -26,12 +26,16 @@ use crate::{
         pool::Handle,
         visitor::prelude::*,
     },
-    scene::{node::Node, sound::Sound},
+    scene::{
+        node::Node,
+        sound::{listener::Listener, Sound},
+    },
 };
 use fxhash::FxHashSet;
 use fyrox_sound::{
     bus::AudioBusGraph,
     context::DistanceModel,
+    listener::Listener as NativeListener,
     renderer::Renderer,
     source::{SoundSource, SoundSourceBuilder, Status},
 };
@@ -42,6 +46,8 @@ use std::{sync::MutexGuard, time::Duration};
 pub struct SoundContext {
     #[visit(optional)]
     pub(crate) native: fyrox_sound::context::SoundContext,
+    #[visit(skip)]
+    synced_listeners: usize,
 }
 
 /// Proxy for guarded access to the sound context.
@@ -124,7 +130,10 @@ impl Default for SoundContext {
         // There's no need to serialize native sources, because they'll be re-created automatically.
         state.serialization_options.skip_sources = true;
         drop(state);
-        Self { native }
+        Self {
+            native,
+            synced_listeners: 0,
+        }
     }
 }
 
@@ -137,9 +146,43 @@ impl SoundContext {
     pub fn deep_clone(&self) -> Self {
         Self {
             native: self.native.deep_clone(),
+            synced_listeners: 0,
         }
     }
 
+    pub(crate) fn begin_listeners_sync(&mut self) {
+        self.synced_listeners = 0;
+    }
+
+    /// Writes the state of the given listener node to the next native listener. The first synced listener
+    /// becomes the primary one, the rest are added as additional listeners.
+    pub(crate) fn sync_listener(&mut self, listener: &Listener) {
+        let mut state = self.native.state();
+        let native = if self.synced_listeners == 0 {
+            state.listener_mut()
+        } else {
+            let index = self.synced_listeners - 1;
+            let additional = state.additional_listeners_mut();
+            if index >= additional.len() {
+                additional.push(NativeListener::new());
+            }
+            &mut additional[index]
+        };
+        native.set_position(listener.global_position());
+        native.set_orientation_lh(listener.look_vector(), listener.up_vector());
+        native.set_gain(listener.gain());
+        native.set_output(listener.output());
+        self.synced_listeners += 1;
+    }
+
+    pub(crate) fn end_listeners_sync(&mut self) {
+        let additional_count = self.synced_listeners.saturating_sub(1);
+        self.native
+            .state()
+            .additional_listeners_mut()
+            .truncate(additional_count);
+    }
+
     /// Returns locked inner state of the sound context.
     pub fn state(&self) -> SoundContextGuard {
         SoundContextGuard {
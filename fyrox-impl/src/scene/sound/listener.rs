@@ -31,6 +31,7 @@ use crate::{
         reflect::prelude::*,
         type_traits::prelude::*,
         uuid::{uuid, Uuid},
+        variable::InheritableVariable,
         visitor::prelude::*,
     },
     scene::{
@@ -41,6 +42,7 @@ use crate::{
 };
 use fyrox_graph::constructor::ConstructorProvider;
 use fyrox_graph::BaseSceneGraph;
+use fyrox_sound::listener::ListenerOutput;
 use std::ops::{Deref, DerefMut};
 
 /// Listener represents directional microphone-like device. It receives sound from surroundings
@@ -49,17 +51,63 @@ use std::ops::{Deref, DerefMut};
 /// basis's side-vector defines ear axis where -X is for left ear and +X for right. Look vector (Z+)
 /// defines "face" of the listener.
 ///
-/// There can be only one listener at a time, if you create multiple listeners, the last one will
-/// have priority.
+/// A scene can have multiple enabled listeners, in this case every sound is rendered for every
+/// listener and the loudest result of each channel is used. This is useful for local split-screen games - each
+/// player could have a listener attached to their camera. Use [`Listener::set_gain`] to control the
+/// weight of each listener in the final mix and [`Listener::set_output`] to route a listener to a
+/// specific output channel. Disable listeners that should not be heard.
 ///
 /// Usually listener is attached to the main camera, however there might be some other rare cases
 /// and you can attach listener to any node you like.
 ///
 /// 2D sound sources (with spatial blend == 0.0) are not influenced by listener's position and
 /// orientation.
-#[derive(Visit, Reflect, Default, Clone, Debug, ComponentProvider)]
+#[derive(Visit, Reflect, Clone, Debug, ComponentProvider)]
 pub struct Listener {
     base: Base,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_gain")]
+    gain: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_output")]
+    output: InheritableVariable<ListenerOutput>,
+}
+
+impl Default for Listener {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            gain: 1.0.into(),
+            output: Default::default(),
+        }
+    }
+}
+
+impl Listener {
+    /// Sets the weight of the listener in the final mix. It is useful when there are multiple listeners
+    /// in the scene, for example two listeners of a split-screen game could use `0.5` each to keep the
+    /// overall loudness the same as with a single listener.
+    pub fn set_gain(&mut self, gain: f32) -> f32 {
+        self.gain.set_value_and_mark_modified(gain.max(0.0))
+    }
+
+    /// Returns the weight of the listener in the final mix.
+    pub fn gain(&self) -> f32 {
+        *self.gain
+    }
+
+    /// Sets output channels of the listener. See [`ListenerOutput`] docs for more info.
+    pub fn set_output(&mut self, output: ListenerOutput) -> ListenerOutput {
+        self.output.set_value_and_mark_modified(output)
+    }
+
+    /// Returns output channels of the listener.
+    pub fn output(&self) -> ListenerOutput {
+        *self.output
+    }
 }
 
 impl Deref for Listener {
@@ -117,28 +165,45 @@ impl NodeTrait for Listener {
             return;
         }
 
-        let mut state = context.sound_context.native.state();
-        let native = state.listener_mut();
-        native.set_position(self.global_position());
-        native.set_orientation_lh(self.look_vector(), self.up_vector());
+        context.sound_context.sync_listener(self);
     }
 }
 
 /// Allows you to create listener in declarative manner.
 pub struct ListenerBuilder {
     base_builder: BaseBuilder,
+    gain: f32,
+    output: ListenerOutput,
 }
 
 impl ListenerBuilder {
     /// Creates new listner builder.
     pub fn new(base_builder: BaseBuilder) -> Self {
-        Self { base_builder }
+        Self {
+            base_builder,
+            gain: 1.0,
+            output: Default::default(),
+        }
+    }
+
+    /// Sets the weight of the listener in the final mix. See [`Listener::set_gain`] for more info.
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Sets output channels of the listener. See [`Listener::set_output`] for more info.
+    pub fn with_output(mut self, output: ListenerOutput) -> Self {
+        self.output = output;
+        self
     }
 
     /// Creates listener instance.
     pub fn build_listener(self) -> Listener {
         Listener {
             base: self.base_builder.build_base(),
+            gain: self.gain.into(),
+            output: self.output.into(),
         }
     }
 
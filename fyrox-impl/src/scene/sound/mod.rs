@@ -53,6 +53,7 @@ pub use fyrox_sound::{
     engine::SoundEngine,
     error::SoundError,
     hrtf::HrirSphere,
+    listener::ListenerOutput,
     renderer::{hrtf::*, Renderer},
     source::Status,
 };
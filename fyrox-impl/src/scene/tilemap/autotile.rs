@@ -0,0 +1,340 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Rule-based autotiling for tile map brushes. See [`TileTerrain`] docs for more info.
+
+use crate::core::{
+    algebra::Vector2, reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*,
+};
+use fxhash::FxHashSet;
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+use super::*;
+
+/// The bit of an autotile mask that is set when the cell above is a part of the same terrain.
+pub const AUTOTILE_N: u8 = 1;
+/// The bit of an autotile mask that is set when the cell above and to the right is a part of the
+/// same terrain.
+pub const AUTOTILE_NE: u8 = 2;
+/// The bit of an autotile mask that is set when the cell to the right is a part of the same
+/// terrain.
+pub const AUTOTILE_E: u8 = 4;
+/// The bit of an autotile mask that is set when the cell below and to the right is a part of the
+/// same terrain.
+pub const AUTOTILE_SE: u8 = 8;
+/// The bit of an autotile mask that is set when the cell below is a part of the same terrain.
+pub const AUTOTILE_S: u8 = 16;
+/// The bit of an autotile mask that is set when the cell below and to the left is a part of the
+/// same terrain.
+pub const AUTOTILE_SW: u8 = 32;
+/// The bit of an autotile mask that is set when the cell to the left is a part of the same
+/// terrain.
+pub const AUTOTILE_W: u8 = 64;
+/// The bit of an autotile mask that is set when the cell above and to the left is a part of the
+/// same terrain.
+pub const AUTOTILE_NW: u8 = 128;
+
+const EDGES: u8 = AUTOTILE_N | AUTOTILE_E | AUTOTILE_S | AUTOTILE_W;
+
+/// Neighbor offsets in the order of the bits of an autotile mask.
+const NEIGHBORS: [(i32, i32); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+
+/// Corner bits along with the pair of edge bits that must be set for the corner to matter.
+const CORNERS: [(u8, u8); 4] = [
+    (AUTOTILE_NE, AUTOTILE_N | AUTOTILE_E),
+    (AUTOTILE_SE, AUTOTILE_S | AUTOTILE_E),
+    (AUTOTILE_SW, AUTOTILE_S | AUTOTILE_W),
+    (AUTOTILE_NW, AUTOTILE_N | AUTOTILE_W),
+];
+
+/// Defines which neighbors of a cell affect the choice of its tile.
+#[derive(
+    Copy,
+    Clone,
+    Default,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum AutotileRuleSet {
+    /// All eight neighbors are used, but a corner neighbor only matters when both of the edge
+    /// neighbors next to it are a part of the terrain. This gives 47 distinct tiles, including
+    /// inner corners.
+    #[default]
+    Blob47,
+    /// Only the four edge neighbors are used, which gives 16 distinct tiles without inner corners.
+    Edges16,
+}
+
+impl AutotileRuleSet {
+    /// Removes the bits of the given mask that do not affect the choice of a tile in this rule
+    /// set. See [`AUTOTILE_N`] and other constants for the meaning of the bits.
+    pub fn reduce(self, mask: u8) -> u8 {
+        match self {
+            AutotileRuleSet::Edges16 => mask & EDGES,
+            AutotileRuleSet::Blob47 => CORNERS.iter().fold(mask, |mask, (corner, edges)| {
+                if mask & edges == *edges {
+                    mask
+                } else {
+                    mask & !corner
+                }
+            }),
+        }
+    }
+
+    /// All the distinct masks of this rule set in ascending order, 47 or 16 of them. A terrain
+    /// needs a rule for each of these masks to cover every possible arrangement of cells.
+    pub fn masks(self) -> Vec<u8> {
+        let mut masks = (0..=255u8).map(|m| self.reduce(m)).collect::<Vec<_>>();
+        masks.sort_unstable();
+        masks.dedup();
+        masks
+    }
+}
+
+/// A tile that is used for the cells of a terrain whose neighbors match the mask.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Visit, Reflect)]
+pub struct AutotileRule {
+    /// The arrangement of the neighbors of the cell, reduced by the rule set of the terrain.
+    /// See [`AUTOTILE_N`] and other constants for the meaning of the bits.
+    pub mask: u8,
+    /// The tile to use for the cell.
+    pub tile: TileDefinitionHandle,
+}
+
+/// A terrain is a set of tiles that are automatically chosen depending on the neighbors of each
+/// cell. When the cells of a tile map are painted with any tile of a terrain, the painted cells
+/// and their neighbors are replaced by the tiles, whose rules match the arrangement of the
+/// terrain cells around them, so edges, outer corners and inner corners are drawn automatically.
+///
+/// Terrains are stored in [`super::brush::TileMapBrush::terrains`] and applied by
+/// [`super::brush::TileMapBrush::autotile`]. Autotiling assumes square cells.
+#[derive(Clone, Default, Debug, PartialEq, Visit, Reflect)]
+pub struct TileTerrain {
+    /// The name of the terrain, that is shown in the editor.
+    pub name: String,
+    /// Defines which neighbors of a cell affect the choice of its tile.
+    pub rule_set: AutotileRuleSet,
+    /// The tiles of the terrain along with the masks that select them.
+    pub rules: Vec<AutotileRule>,
+}
+
+impl TileTerrain {
+    /// True if the given tile is one of the tiles of the terrain.
+    pub fn contains(&self, handle: TileDefinitionHandle) -> bool {
+        self.rules.iter().any(|r| r.tile == handle)
+    }
+
+    /// The mask of the rule that selects the given tile, if the tile is a part of the terrain.
+    pub fn rule_mask(&self, handle: TileDefinitionHandle) -> Option<u8> {
+        self.rules.iter().find(|r| r.tile == handle).map(|r| r.mask)
+    }
+
+    /// Makes the given tile a part of the terrain, selected by the given mask. The mask is
+    /// reduced by the rule set of the terrain, and any previous rule of the tile is replaced.
+    pub fn set_rule(&mut self, tile: TileDefinitionHandle, mask: u8) {
+        let mask = self.rule_set.reduce(mask);
+        match self.rules.iter_mut().find(|r| r.tile == tile) {
+            Some(rule) => rule.mask = mask,
+            None => self.rules.push(AutotileRule { mask, tile }),
+        }
+    }
+
+    /// Removes the given tile from the terrain. Returns true if the tile was a part of it.
+    pub fn remove_rule(&mut self, tile: TileDefinitionHandle) -> bool {
+        let count = self.rules.len();
+        self.rules.retain(|r| r.tile != tile);
+        self.rules.len() != count
+    }
+
+    /// Changes the rule set of the terrain and reduces the masks of its rules to match.
+    pub fn set_rule_set(&mut self, rule_set: AutotileRuleSet) {
+        self.rule_set = rule_set;
+        for rule in self.rules.iter_mut() {
+            rule.mask = rule_set.reduce(rule.mask);
+        }
+    }
+
+    /// Finds the tile for the given (unreduced) mask. If there is no rule for the exact mask, the
+    /// corners are ignored, so an incomplete 47-tile terrain degrades to a 16-tile one.
+    pub fn tile(&self, mask: u8) -> Option<TileDefinitionHandle> {
+        let mask = self.rule_set.reduce(mask);
+        let find = |mask: u8| self.rules.iter().find(|r| r.mask == mask).map(|r| r.tile);
+        find(mask).or_else(|| find(mask & EDGES))
+    }
+
+    /// Calculates the mask of the cell at the given position, using the given function to
+    /// check whether a cell is a part of the terrain.
+    pub fn neighbor_mask<F>(&self, position: Vector2<i32>, mut is_terrain: F) -> u8
+    where
+        F: FnMut(Vector2<i32>) -> bool,
+    {
+        let mask = NEIGHBORS
+            .iter()
+            .enumerate()
+            .filter(|(_, (x, y))| is_terrain(position + Vector2::new(*x, *y)))
+            .fold(0, |mask, (i, _)| mask | (1 << i));
+        self.rule_set.reduce(mask)
+    }
+
+    /// Replaces the terrain tiles of the cells that are changed by the given update, along with
+    /// their neighbors, with the tiles that match their neighbors. The new tiles are written into
+    /// the update. The given source provides the tiles that are not changed by the update.
+    pub fn autotile<S: TileSource>(&self, source: &S, update: &mut TilesUpdate) {
+        if self.rules.is_empty() {
+            return;
+        }
+        let get = |update: &TilesUpdate, position: Vector2<i32>| match update.get(&position) {
+            Some(value) => *value,
+            None => source.get_at(position),
+        };
+        let mut dirty = FxHashSet::default();
+        for position in update.keys() {
+            dirty.insert(*position);
+            dirty.extend(
+                NEIGHBORS
+                    .iter()
+                    .map(|(x, y)| position + Vector2::new(*x, *y)),
+            );
+        }
+        // Only terrain tiles are replaced with other terrain tiles, so the changes do not affect
+        // the masks of other cells and could be applied all at once.
+        let mut changes = Vec::new();
+        for position in dirty {
+            if !get(update, position).is_some_and(|h| self.contains(h)) {
+                continue;
+            }
+            let mask = self.neighbor_mask(position, |p| {
+                get(update, p).is_some_and(|h| self.contains(h))
+            });
+            if let Some(tile) = self.tile(mask) {
+                if get(update, position) != Some(tile) {
+                    changes.push((position, tile));
+                }
+            }
+        }
+        for (position, tile) in changes {
+            update.insert(position, Some(tile));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_autotile_masks() {
+        assert_eq!(AutotileRuleSet::Blob47.masks().len(), 47);
+        assert_eq!(AutotileRuleSet::Edges16.masks().len(), 16);
+        assert_eq!(
+            AutotileRuleSet::Blob47.reduce(AUTOTILE_NE | AUTOTILE_N),
+            AUTOTILE_N
+        );
+        assert_eq!(
+            AutotileRuleSet::Blob47.reduce(AUTOTILE_NE | AUTOTILE_N | AUTOTILE_E),
+            AUTOTILE_NE | AUTOTILE_N | AUTOTILE_E
+        );
+    }
+
+    #[test]
+    fn test_terrain_rules() {
+        let a = TileDefinitionHandle::new(0, 0, 1, 0);
+        let b = TileDefinitionHandle::new(0, 0, 2, 0);
+        let mut terrain = TileTerrain::default();
+        terrain.set_rule(a, AUTOTILE_N | AUTOTILE_NE);
+        terrain.set_rule(b, AUTOTILE_N | AUTOTILE_NE | AUTOTILE_E);
+        assert_eq!(terrain.rule_mask(a), Some(AUTOTILE_N));
+        terrain.set_rule(a, AUTOTILE_S);
+        assert_eq!(terrain.rules.len(), 2);
+        assert_eq!(terrain.rule_mask(a), Some(AUTOTILE_S));
+        terrain.set_rule_set(AutotileRuleSet::Edges16);
+        assert_eq!(terrain.rule_mask(b), Some(AUTOTILE_N | AUTOTILE_E));
+        assert!(terrain.remove_rule(a));
+        assert!(!terrain.remove_rule(a));
+        assert_eq!(terrain.rule_mask(a), None);
+    }
+
+    #[test]
+    fn test_autotile() {
+        let rule_set = AutotileRuleSet::Blob47;
+        let tile = |mask: u8| TileDefinitionHandle::new(0, 0, mask as i16, 0);
+        let terrain = TileTerrain {
+            name: "Grass".into(),
+            rule_set,
+            rules: rule_set
+                .masks()
+                .into_iter()
+                .map(|mask| AutotileRule {
+                    mask,
+                    tile: tile(mask),
+                })
+                .collect(),
+        };
+        let source = TileMapData::default();
+        let mut update = TilesUpdate::default();
+        for y in 0..3 {
+            for x in 0..3 {
+                update.insert(Vector2::new(x, y), Some(tile(0)));
+            }
+        }
+        terrain.autotile(&source, &mut update);
+        assert_eq!(update[&Vector2::new(1, 1)], Some(tile(255)));
+        assert_eq!(
+            update[&Vector2::new(0, 0)],
+            Some(tile(AUTOTILE_N | AUTOTILE_NE | AUTOTILE_E))
+        );
+        assert_eq!(
+            update[&Vector2::new(1, 2)],
+            Some(tile(
+                AUTOTILE_E | AUTOTILE_SE | AUTOTILE_S | AUTOTILE_SW | AUTOTILE_W
+            ))
+        );
+
+        // Erasing the center turns the neighbors into inner corners.
+        let mut source = TileMapData::default();
+        for (position, handle) in update.iter() {
+            source.set(*position, handle.unwrap());
+        }
+        let mut update = TilesUpdate::default();
+        update.insert(Vector2::new(1, 1), None);
+        terrain.autotile(&source, &mut update);
+        assert_eq!(update[&Vector2::new(1, 1)], None);
+        assert_eq!(
+            update[&Vector2::new(1, 0)],
+            Some(tile(AUTOTILE_E | AUTOTILE_W))
+        );
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Baking of tile maps into static meshes. See [`TileMap::bake_to_mesh`] docs for more info.
+
+use crate::{
+    graph::SceneGraph,
+    scene::mesh::{
+        surface::{Surface, SurfaceBuilder},
+        MeshBuilder,
+    },
+};
+
+use super::*;
+
+impl TileMap {
+    /// Creates the surfaces of a mesh, that looks exactly like the tiles of the tile map, one
+    /// surface per material. The vertices are in the local coordinates of the tile map node.
+    /// Animated tiles are baked at their first frame and effects are ignored.
+    pub fn bake_surfaces(&self) -> Vec<Surface> {
+        let Some(tile_set) = self.tile_set() else {
+            return Vec::new();
+        };
+        let mut tile_set = TileSetRef::new(tile_set);
+        let tile_set = tile_set.as_loaded();
+        let Some(tiles) = self.tiles() else {
+            return Vec::new();
+        };
+        let tiles = tiles.data_ref();
+        let Some(tiles) = tiles.as_loaded_ref() else {
+            return Vec::new();
+        };
+        // The tile map flips the x-axis and scales by the cell size, see
+        // [`TileMap::tile_map_transform`].
+        let cell_size = self.cell_size();
+        let flip = Matrix4::new_nonuniform_scaling(&Vector3::new(-cell_size.x, cell_size.y, 1.0));
+        let mut batches = Vec::new();
+        for (position, handle) in tiles.iter() {
+            let Some(data) = tile_set.get_tile_render_data(handle.into()) else {
+                continue;
+            };
+            let data = data.transformed(tiles.transformation(position));
+            let origin = self.grid_layout.cell_origin(position);
+            push_tile_vertices(&mut batches, data, origin, &flip);
+        }
+        batches
+            .into_iter()
+            .map(|(material, vertices)| {
+                SurfaceBuilder::new(make_batch_surface(vertices))
+                    .with_material(material)
+                    .build()
+            })
+            .collect()
+    }
+
+    /// Converts the tile map with the given handle into a static mesh, which is much cheaper to
+    /// render for the tile maps that never change at runtime, because the mesh needs no
+    /// processing on CPU every frame. The mesh is added next to the tile map, with the same local
+    /// transform, and the tile map is made invisible, so it still could be used for physics or
+    /// property queries. Returns the handle of the mesh, or `None` if there is no tile map with
+    /// the given handle. See [`Self::bake_surfaces`] for more info.
+    pub fn bake_to_mesh(tile_map: Handle<Node>, graph: &mut Graph) -> Option<Handle<Node>> {
+        let node = graph.try_get_mut_of_type::<TileMap>(tile_map)?;
+        let surfaces = node.bake_surfaces();
+        let name = format!("{} (Baked)", node.name());
+        let local_transform = node.local_transform().clone();
+        let parent = node.parent();
+        node.set_visibility(false);
+        let mesh = MeshBuilder::new(
+            BaseBuilder::new()
+                .with_name(name)
+                .with_local_transform(local_transform),
+        )
+        .with_surfaces(surfaces)
+        .with_render_path(RenderPath::Forward)
+        .build(graph);
+        if parent.is_some() {
+            graph.link_nodes(mesh, parent);
+        }
+        Some(mesh)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scene::tilemap::{
+        test_fixture::{self, FLOOR},
+        tileset::TileData,
+    };
+
+    fn v(x: i32, y: i32) -> Vector2<i32> {
+        Vector2::new(x, y)
+    }
+
+    #[test]
+    fn test_bake_surfaces() {
+        let tile_map = test_fixture::make_tile_map(
+            test_fixture::make_tile_set(|_| TileData::default()),
+            [(v(0, 0), FLOOR), (v(2, 1), FLOOR)],
+        );
+
+        let surfaces = tile_map.bake_surfaces();
+        assert_eq!(surfaces.len(), 1);
+        let data = surfaces[0].data_ref().data_ref();
+        assert_eq!(data.vertex_buffer.vertex_count(), 8);
+        assert_eq!(data.geometry_buffer.len(), 4);
+    }
+}
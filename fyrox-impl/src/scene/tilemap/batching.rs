@@ -0,0 +1,351 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Batched rendering of tile maps. The vertices of the tiles of every chunk are kept in a vertex
+//! buffer on GPU, which is regenerated only when the tiles of the chunk are changed. See
+//! [`TileMap::render_batched_chunks`] docs for more info.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, Vector2},
+        math::TriangleDefinition,
+    },
+    material::{
+        shader::{ShaderResource, ShaderResourceExtension},
+        MaterialResource,
+    },
+    renderer::{bundle::SurfaceInstanceData, framework::ElementRange},
+    scene::mesh::{
+        buffer::{TriangleBuffer, VertexBuffer},
+        surface::{SurfaceData, SurfaceResource},
+    },
+};
+use fxhash::{FxHashMap, FxHashSet, FxHasher};
+use std::hash::{Hash, Hasher};
+
+use super::*;
+
+/// Tiles of a chunk that share the same material.
+#[derive(Debug)]
+struct TileBatch {
+    /// The material of the tiles in the tile set.
+    material: MaterialResource,
+    /// Vertices of the tiles in local coordinates of the tile map, four vertices per tile.
+    surface: SurfaceResource,
+}
+
+/// Vertex data of a chunk of a tile map.
+#[derive(Debug)]
+struct BatchedChunk {
+    /// Hash of the tile set and the revision of the chunk, which is used to detect changes.
+    hash: u64,
+    /// Batches of the tiles of the chunk, except the animated tiles.
+    batches: Vec<TileBatch>,
+    /// Positions of the animated tiles of the chunk, which must be rendered tile by tile, because
+    /// their handles change over time.
+    animated: Vec<Vector2<i32>>,
+}
+
+/// Vertex data of the chunks of a tile map, that is reused until their tiles are changed. The
+/// changes are detected using the revisions of the chunks (see [`TileMapData::chunk_revision`]),
+/// so editing a few tiles regenerates only the chunks that contain them.
+#[derive(Default, Debug)]
+pub(super) struct TileMapBatchCache {
+    chunks: FxHashMap<Vector2<i32>, BatchedChunk>,
+}
+
+/// Collects the vertices of the tiles of the chunk with the given rect, grouped by their
+/// materials. Animated tiles are skipped and their positions are added to the given vector.
+fn make_tile_vertices(
+    tile_set: &OptionTileSet,
+    tiles: &TileMapData,
+    rect: TileRect,
+    grid_layout: TileGridLayout,
+    animated: &mut Vec<Vector2<i32>>,
+) -> Vec<(MaterialResource, Vec<TileVertex>)> {
+    let identity = Matrix4::identity();
+    let mut batches = Vec::new();
+    for (position, handle) in tiles.bounded_iter(rect.into()) {
+        if tile_set.get_animated_version(0.0, handle).is_some() {
+            animated.push(position);
+            continue;
+        }
+        let Some(data) = tile_set.get_tile_render_data(handle.into()) else {
+            continue;
+        };
+        let data = data.transformed(tiles.transformation(position));
+        let origin = grid_layout.cell_origin(position);
+        push_tile_vertices(&mut batches, data, origin, &identity);
+    }
+    batches
+}
+
+/// Adds the vertices of a tile with the given render data, whose cell has the given origin, to
+/// the batch of the material of the tile. The vertices are transformed by the given matrix.
+pub(super) fn push_tile_vertices(
+    batches: &mut Vec<(MaterialResource, Vec<TileVertex>)>,
+    data: TileRenderData,
+    origin: Vector2<f32>,
+    transform: &Matrix4<f32>,
+) {
+    // Tiles without bounds use zero texture coordinates, which is the same as the plain color.
+    let (material, uvs) = match data.material_bounds {
+        Some(TileMaterialBounds { material, bounds }) => (
+            material,
+            [
+                bounds.right_top_corner,
+                bounds.left_top_corner,
+                bounds.left_bottom_corner,
+                bounds.right_bottom_corner,
+            ],
+        ),
+        None => (DEFAULT_TILE_MATERIAL.clone(), Default::default()),
+    };
+    let vertices = [(1.0, 1.0), (0.0, 1.0), (0.0, 0.0), (1.0, 0.0)]
+        .into_iter()
+        .zip(uvs)
+        .map(|((x, y), uv)| {
+            make_tile_vertex(transform, origin + Vector2::new(x, y), uv, data.color)
+        });
+    if let Some((_, batch)) = batches.iter_mut().find(|(m, _)| m.key() == material.key()) {
+        batch.extend(vertices);
+    } else {
+        batches.push((material, vertices.collect()));
+    }
+}
+
+pub(super) fn make_batch_surface(vertices: Vec<TileVertex>) -> SurfaceResource {
+    let triangles = (0..vertices.len() as u32 / 4)
+        .flat_map(|i| {
+            let first = i * 4;
+            [
+                TriangleDefinition([first, first + 1, first + 2]),
+                TriangleDefinition([first + 2, first + 3, first]),
+            ]
+        })
+        .collect();
+    let vertex_buffer = VertexBuffer::new(vertices.len(), vertices).unwrap();
+    SurfaceResource::new_ok(
+        ResourceKind::Embedded,
+        SurfaceData::new(vertex_buffer, TriangleBuffer::new(triangles)),
+    )
+}
+
+/// Returns true if the given material is loaded and uses the standard tile shader, which
+/// transforms the vertices by the world matrix of the tile map.
+fn is_standard_tile_material(material: &MaterialResource) -> bool {
+    let mut state = material.state();
+    state.data().map_or(false, |material| {
+        material.shader().key() == ShaderResource::standard_tile().key()
+    })
+}
+
+impl TileMapBatchCache {
+    /// Removes every cached chunk.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    /// Removes the chunks, that no longer exist in the given tile data.
+    fn remove_stale(&mut self, tiles: &TileMapData) {
+        self.chunks
+            .retain(|position, _| tiles.chunk_revision(*position).is_some());
+    }
+
+    /// Returns the batched chunk with the given rect, the vertices are collected again if the
+    /// tiles or the tile set were changed since the last call. Edits of the tile set are detected
+    /// by its change count, see [`ChangeFlag::count`].
+    fn chunk(
+        &mut self,
+        rect: TileRect,
+        revision: u64,
+        tile_set_resource: Option<&TileSetResource>,
+        tile_set: &OptionTileSet,
+        tiles: &TileMapData,
+        grid_layout: TileGridLayout,
+    ) -> &BatchedChunk {
+        let mut hasher = FxHasher::default();
+        tile_set_resource.hash(&mut hasher);
+        tile_set.change_count().hash(&mut hasher);
+        revision.hash(&mut hasher);
+        grid_layout.hash(&mut hasher);
+        let hash = hasher.finish();
+        let is_outdated = self
+            .chunks
+            .get(&rect.position)
+            .map_or(true, |chunk| chunk.hash != hash);
+        if is_outdated {
+            let mut animated = Vec::new();
+            let batches = make_tile_vertices(tile_set, tiles, rect, grid_layout, &mut animated)
+                .into_iter()
+                .map(|(material, vertices)| TileBatch {
+                    material,
+                    surface: make_batch_surface(vertices),
+                })
+                .collect();
+            self.chunks.insert(
+                rect.position,
+                BatchedChunk {
+                    hash,
+                    batches,
+                    animated,
+                },
+            );
+        }
+        &self.chunks[&rect.position]
+    }
+}
+
+impl TileMapRenderContext<'_, '_> {
+    /// True if any tile in the given rect is hidden or tinted by an effect, so the tiles of the
+    /// rect must be rendered one by one.
+    pub(super) fn has_effects_in(&self, rect: TileRect) -> bool {
+        (!self.hidden_tiles.is_empty() || !self.tile_tints.is_empty())
+            && rect
+                .iter()
+                .any(|p| !self.is_tile_visible(p) || self.tile_tint(p).is_some())
+    }
+}
+
+impl TileMap {
+    /// Renders the visible chunks, that are not in the given set of already rendered chunks,
+    /// from the vertex buffers cached on GPU and adds their positions to the set, so their tiles
+    /// could be skipped. The vertices of a chunk are regenerated only when its tiles are changed,
+    /// so a game could modify lots of tiles every frame (destructible terrain, for example)
+    /// without rebuilding the render data of the whole tile map. Animated tiles are rendered tile
+    /// by tile after the rest of their chunk. Chunks that contain hidden or tinted tiles and
+    /// chunks with custom tile shaders are rendered tile by tile.
+    /// Y-sorted tile maps are always rendered tile by tile, because every row of tiles is sorted
+    /// separately.
+    pub(super) fn render_batched_chunks(
+        &self,
+        ctx: &mut TileMapRenderContext,
+        tiles: &TileMapData,
+        rendered_chunks: &mut FxHashSet<Vector2<i32>>,
+    ) {
+        let bounds = ctx.visible_bounds();
+        if bounds.is_none() || ctx.sort_mode != SortMode2D::Depth {
+            return;
+        }
+        let mut cache = self.batch_cache.lock();
+        cache.remove_stale(tiles);
+        for (rect, revision) in tiles.bounded_chunks(bounds) {
+            if rendered_chunks.contains(&rect.position) || ctx.has_effects_in(rect) {
+                continue;
+            }
+            let chunk = cache.chunk(
+                rect,
+                revision,
+                self.tile_set.as_ref(),
+                &ctx.tile_set,
+                tiles,
+                ctx.grid_layout,
+            );
+            if !chunk
+                .batches
+                .iter()
+                .all(|batch| is_standard_tile_material(&batch.material))
+            {
+                continue;
+            }
+            let sort_index = ctx.sorting_index(rect.position);
+            for batch in chunk.batches.iter() {
+                ctx.context.storage.push(
+                    &batch.surface,
+                    &batch.material,
+                    RenderPath::Forward,
+                    sort_index,
+                    SurfaceInstanceData {
+                        world_transform: ctx.transform,
+                        bone_matrices: Default::default(),
+                        blend_shapes_weights: Default::default(),
+                        element_range: ElementRange::Full,
+                        node_handle: ctx.tile_map_handle,
+                    },
+                );
+            }
+            for &position in chunk.animated.iter() {
+                if let Some(handle) = tiles.get(position) {
+                    let handle = self.get_animated_version(ctx, position, handle);
+                    ctx.draw_transformed_tile(position, handle, tiles.transformation(position));
+                }
+            }
+            rendered_chunks.insert(rect.position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        asset::untyped::ResourceKind,
+        scene::tilemap::{
+            test_fixture::{self, FLOOR},
+            tileset::TileData,
+        },
+    };
+
+    #[test]
+    fn test_tile_set_change_invalidates_chunk() {
+        let tile_set = TileSetResource::new_ok(
+            ResourceKind::Embedded,
+            test_fixture::make_tile_set(|_| TileData::default()),
+        );
+        let mut tiles = TileMapData::default();
+        tiles.set(Vector2::new(0, 0), FLOOR);
+        let (rect, revision) = tiles
+            .bounded_chunks(TileRect::new(0, 0, 1, 1).into())
+            .next()
+            .unwrap();
+        let mut cache = TileMapBatchCache::default();
+        let chunk_hash = |cache: &mut TileMapBatchCache| {
+            let mut tile_set_ref = TileSetRef::new(&tile_set);
+            cache
+                .chunk(
+                    rect,
+                    revision,
+                    Some(&tile_set),
+                    &tile_set_ref.as_loaded(),
+                    &tiles,
+                    TileGridLayout::default(),
+                )
+                .hash
+        };
+        let hash = chunk_hash(&mut cache);
+        assert_eq!(chunk_hash(&mut cache), hash);
+        // The editor sets the change flag on every modification of the tile set.
+        tile_set.data_ref().change_count.set();
+        assert_ne!(chunk_hash(&mut cache), hash);
+    }
+
+    #[test]
+    fn test_batch_surface() {
+        let surface = make_batch_surface(vec![TileVertex::default(); 8]);
+        let data = surface.data_ref();
+        assert_eq!(data.vertex_buffer.vertex_count(), 8);
+        assert_eq!(data.geometry_buffer.len(), 4);
+        assert_eq!(
+            data.geometry_buffer.triangles_ref()[3],
+            TriangleDefinition([6, 7, 4])
+        );
+    }
+}
@@ -43,6 +43,7 @@ use crate::{
         type_traits::prelude::*,
         visitor::prelude::*,
     },
+    rand::{seq::SliceRandom, Rng},
     scene::debug::SceneDrawingContext,
 };
 use std::{
@@ -164,6 +165,84 @@ fn draw_tile_outline(
     );
 }
 
+/// A tile that may be drawn in place of another tile, see [`TileVariants`].
+#[derive(Copy, Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct TileVariant {
+    /// The tile to draw.
+    pub tile: TileDefinitionHandle,
+    /// The chance of the tile to be chosen relative to the other variants. Variants with zero
+    /// weight are never chosen.
+    pub weight: f32,
+}
+
+impl Default for TileVariant {
+    fn default() -> Self {
+        Self {
+            tile: TileDefinitionHandle::default(),
+            weight: 1.0,
+        }
+    }
+}
+
+/// A set of weighted variants of a brush tile. When a brush tile that has variants is drawn,
+/// one of the variants is chosen randomly according to the weights, so painting with a single
+/// grass tile scatters visually varied grass tiles. The tile itself is drawn only if it is one of
+/// its own variants.
+///
+/// Variants are stored in [`TileMapBrush::variants`] and applied by [`VariantTileSource`], so
+/// every drawing tool that uses a [`TileSource`] benefits from them.
+#[derive(Clone, Default, Debug, PartialEq, Visit, Reflect)]
+pub struct TileVariants {
+    /// The brush tile that is replaced by its variants.
+    pub tile: TileDefinitionHandle,
+    /// The tiles to choose from.
+    pub variants: Vec<TileVariant>,
+}
+
+impl TileVariants {
+    /// Randomly chooses one of the variants according to their weights. Returns `None` if there
+    /// are no variants with positive weight.
+    pub fn choose<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<TileDefinitionHandle> {
+        self.variants
+            .choose_weighted(rng, |v| v.weight.max(0.0))
+            .ok()
+            .map(|v| v.tile)
+    }
+}
+
+/// A multi-cell structure on a page of a brush, such as a house that is made of 3x3 tiles. When
+/// a stamp is selected, the whole structure is drawn by a single click, so that the anchor cell of
+/// the stamp is placed at the clicked cell. Rotations and flips of the stamp are made around the
+/// anchor cell, see [`TileMapBrush::build_stamp`].
+#[derive(Clone, Default, Debug, PartialEq, Visit, Reflect)]
+pub struct TileBrushStamp {
+    /// The name of the stamp, that is shown in the editor.
+    pub name: String,
+    /// The position of the brush page that contains the tiles of the stamp.
+    pub page: Vector2<i32>,
+    /// The left-bottom corner of the region of the page that contains the tiles of the stamp.
+    pub position: Vector2<i32>,
+    /// The width and height of the region of the page that contains the tiles of the stamp.
+    pub size: Vector2<i32>,
+    /// The position of the anchor cell relative to the left-bottom corner of the region.
+    pub anchor: Vector2<i32>,
+}
+
+impl TileBrushStamp {
+    /// The region of the brush page that contains the tiles of the stamp.
+    pub fn rect(&self) -> TileRect {
+        TileRect::new(self.position.x, self.position.y, self.size.x, self.size.y)
+    }
+    /// The position of the anchor cell on the brush page.
+    pub fn anchor_position(&self) -> Vector2<i32> {
+        self.position + self.anchor
+    }
+    /// True if the given position of the given page is within the region of the stamp.
+    pub fn contains(&self, page: Vector2<i32>, position: Vector2<i32>) -> bool {
+        self.page == page && self.rect().contains(position)
+    }
+}
+
 /// Tile map brush is a set of tiles arranged in arbitrary shape, that can be used to draw on a tile
 /// map.
 #[derive(Default, Debug, Clone, Visit, Reflect, TypeUuidProvider)]
@@ -178,15 +257,36 @@ pub struct TileMapBrush {
     /// users to customize the organization of pages.
     #[reflect(hidden)]
     pub pages: TileGridMap<TileMapBrushPage>,
+    /// Terrains that choose the tiles automatically depending on the neighbors of the painted
+    /// cells. See [`TileTerrain`] docs for more info.
+    #[visit(optional)]
+    pub terrains: Vec<TileTerrain>,
+    /// Weighted variants of the tiles of the brush, that are drawn randomly in place of the
+    /// tiles. See [`TileVariants`] docs for more info.
+    #[visit(optional)]
+    pub variants: Vec<TileVariants>,
+    /// Multi-cell structures of the brush, that are drawn as a whole. See [`TileBrushStamp`]
+    /// docs for more info.
+    #[visit(optional)]
+    pub stamps: Vec<TileBrushStamp>,
     /// A count of changes since last save. New changes add +1. Reverting to previous
     /// states add -1. Reverting to a state before the last save can result in negative
     /// values. Saving is unnecessary whenever this value is 0.
     #[reflect(hidden)]
     #[visit(skip)]
     pub change_count: ChangeFlag,
+    /// See [`TileMapBrush::revision`].
+    #[reflect(hidden)]
+    #[visit(skip)]
+    revision: TileResourceRevision,
 }
 
 impl TileMapBrush {
+    /// The revision of the data of the brush, that changes when the brush is reloaded.
+    /// See [`TileResourceRevision`] for more info.
+    pub fn revision(&self) -> TileResourceRevision {
+        self.revision
+    }
     /// True if there is a tile at the given position.
     pub fn has_tile_at(&self, page: Vector2<i32>, tile: Vector2<i32>) -> bool {
         let Some(page) = self.pages.get(&page) else {
@@ -198,6 +298,53 @@ impl TileMapBrush {
     pub fn has_page_at(&self, page: Vector2<i32>) -> bool {
         self.pages.contains_key(&page)
     }
+    /// The index of the terrain that contains the given tile, if any.
+    pub fn terrain_of(&self, handle: TileDefinitionHandle) -> Option<usize> {
+        self.terrains.iter().position(|t| t.contains(handle))
+    }
+    /// The variants of the given tile, if any.
+    pub fn variants_of(&self, handle: TileDefinitionHandle) -> Option<&TileVariants> {
+        self.variants.iter().find(|v| v.tile == handle)
+    }
+    /// The stamp that contains the given position of the given page, if any.
+    pub fn stamp_at(&self, page: Vector2<i32>, position: Vector2<i32>) -> Option<&TileBrushStamp> {
+        self.stamps.iter().find(|s| s.contains(page, position))
+    }
+    /// The stamp with the given name, if any.
+    pub fn find_stamp(&self, name: &str) -> Option<&TileBrushStamp> {
+        self.stamps.iter().find(|s| s.name == name)
+    }
+    /// Creates a [`Stamp`] from the tiles of the given brush stamp, so that the anchor cell is at
+    /// (0,0), and then applies the given transformation to it. The result may be drawn by
+    /// [`TransTilesUpdate::draw_tiles`] or by [`TileMap::draw_stamp`], and since it is a
+    /// [`TileSource`], it may be used by any other drawing tool.
+    pub fn build_stamp(
+        &self,
+        stamp: &TileBrushStamp,
+        transformation: OrthoTransformation,
+    ) -> Stamp {
+        let mut result = Stamp::default();
+        if let Some(page) = self.pages.get(&stamp.page) {
+            result.build_with_anchor(
+                stamp
+                    .rect()
+                    .iter()
+                    .filter_map(|p| Some((p, page.find_tile_at_position(p)?))),
+                stamp.anchor_position(),
+            );
+        }
+        result.transform(transformation);
+        result
+    }
+    /// Applies the terrains of this brush to the given update: the cells of the update, that
+    /// are painted with terrain tiles, and their neighbors get the tiles that match the
+    /// arrangement of the terrain around them. The given source provides the tiles that are
+    /// not changed by the update, usually it is the data of the tile map that is being edited.
+    pub fn autotile<S: TileSource>(&self, source: &S, update: &mut TilesUpdate) {
+        for terrain in self.terrains.iter() {
+            terrain.autotile(source, update);
+        }
+    }
     /// The handle stored at the given position.
     pub fn tile_redirect(&self, handle: TileDefinitionHandle) -> Option<TileDefinitionHandle> {
         self.find_tile_at_position(TilePaletteStage::Tiles, handle.page(), handle.tile())
@@ -0,0 +1,295 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Rasterization of tile maps into textures. It is useful for minimaps and fog-of-war overlays, that
+//! should not require a second camera rendering the tile map every frame. See
+//! [`TileMap::render_to_texture`] docs for more info.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{algebra::Vector2, color::Color},
+    fxhash::FxHashMap,
+    resource::texture::{
+        Texture, TextureKind, TextureMagnificationFilter, TextureMinificationFilter,
+        TexturePixelKind, TextureResource, TextureWrapMode,
+    },
+    scene::tilemap::{
+        tileset::{OptionTileSet, TileMaterialBounds, TileSetRef},
+        OrthoTransform, OrthoTransformation, TileDefinitionHandle, TileMap, TileMapData, TileRect,
+        TileRenderData,
+    },
+};
+
+/// Defines how tiles are rasterized by [`TileMap::render_to_pixels`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TileMapCaptureMode {
+    /// Every tile is filled with its color.
+    Color,
+    /// Tiles sample the diffuse texture of their materials (using the nearest texel), which is then
+    /// multiplied by the tile color. Tiles fall back to their color, if the texture is not loaded or
+    /// its pixel format is not supported (compressed or floating point formats).
+    #[default]
+    Material,
+}
+
+/// A CPU copy of the first mip level of a texture, that is used for sampling.
+struct TexelSource {
+    width: u32,
+    height: u32,
+    pixel_kind: TexturePixelKind,
+    bytes: Vec<u8>,
+}
+
+impl TexelSource {
+    fn new(texture: &TextureResource) -> Option<Self> {
+        let state = texture.state();
+        let texture = state.data_ref()?;
+        let TextureKind::Rectangle { width, height } = texture.kind() else {
+            return None;
+        };
+        match texture.pixel_kind() {
+            TexturePixelKind::R8
+            | TexturePixelKind::RGB8
+            | TexturePixelKind::BGR8
+            | TexturePixelKind::RGBA8
+            | TexturePixelKind::BGRA8 => Some(Self {
+                width,
+                height,
+                pixel_kind: texture.pixel_kind(),
+                bytes: texture.mip_level_data(0).to_vec(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn texel(&self, position: Vector2<f32>) -> Option<Color> {
+        let x = (position.x.max(0.0) as u32).min(self.width.saturating_sub(1));
+        let y = (position.y.max(0.0) as u32).min(self.height.saturating_sub(1));
+        let bpp = self.pixel_kind.size_in_bytes()?;
+        let index = (y as usize * self.width as usize + x as usize) * bpp;
+        let p = self.bytes.get(index..index + bpp)?;
+        Some(match self.pixel_kind {
+            TexturePixelKind::R8 => Color::from_rgba(p[0], p[0], p[0], 255),
+            TexturePixelKind::RGB8 => Color::from_rgba(p[0], p[1], p[2], 255),
+            TexturePixelKind::BGR8 => Color::from_rgba(p[2], p[1], p[0], 255),
+            TexturePixelKind::RGBA8 => Color::from_rgba(p[0], p[1], p[2], p[3]),
+            TexturePixelKind::BGRA8 => Color::from_rgba(p[2], p[1], p[0], p[3]),
+            _ => return None,
+        })
+    }
+}
+
+fn modulate(a: Color, b: Color) -> Color {
+    let mul = |a: u8, b: u8| ((a as u16 * b as u16) / 255) as u8;
+    Color::from_rgba(mul(a.r, b.r), mul(a.g, b.g), mul(a.b, b.b), mul(a.a, b.a))
+}
+
+/// Samples the tile material at the given point of the tile, `local` is in `[0; 1]` range, where
+/// `(0, 0)` is the left-bottom corner of the tile.
+fn sample_material(
+    textures: &mut FxHashMap<TextureResource, Option<TexelSource>>,
+    material_bounds: &TileMaterialBounds,
+    local: Vector2<f32>,
+) -> Option<Color> {
+    let texture = material_bounds
+        .material
+        .state()
+        .data_ref()?
+        .texture("diffuseTexture")?;
+    let source = textures
+        .entry(texture)
+        .or_insert_with_key(TexelSource::new)
+        .as_ref()?;
+
+    let bounds = &material_bounds.bounds;
+    let lerp = |a: Vector2<u32>, b: Vector2<u32>, t: f32| {
+        a.cast::<f32>() + (b.cast::<f32>() - a.cast::<f32>()) * t
+    };
+    let top = lerp(bounds.left_top_corner, bounds.right_top_corner, local.x);
+    let bottom = lerp(
+        bounds.left_bottom_corner,
+        bounds.right_bottom_corner,
+        local.x,
+    );
+    source.texel(bottom + (top - bottom) * local.y)
+}
+
+impl TileMap {
+    /// Rasterizes the given region of the tile map into a new texture with the given resolution (in
+    /// pixels). Tiles sample their materials (see [`TileMapCaptureMode::Material`]), empty cells are
+    /// transparent. The rasterization is done on CPU on demand, so it should not be called every frame
+    /// for large regions. Returns `None` if the tile map has no tile set or tiles, or if the region
+    /// or the resolution is empty.
+    ///
+    /// The texture could be shown on a UI image or a rectangle to create a minimap. Use
+    /// [`Self::render_to_pixels`] to update an existing texture (for example, for fog-of-war).
+    pub fn render_to_texture(
+        &self,
+        region: TileRect,
+        resolution: Vector2<u32>,
+    ) -> Option<TextureResource> {
+        let pixels = self.render_to_pixels(region, resolution, TileMapCaptureMode::Material)?;
+        make_texture(resolution, pixels)
+    }
+
+    /// Rasterizes the given region of the tile map into RGBA8 pixels with the given resolution. The
+    /// first row of pixels is the top of the region. See [`Self::render_to_texture`] for more info.
+    pub fn render_to_pixels(
+        &self,
+        region: TileRect,
+        resolution: Vector2<u32>,
+        mode: TileMapCaptureMode,
+    ) -> Option<Vec<u8>> {
+        if resolution.x == 0 || resolution.y == 0 || region.size.x <= 0 || region.size.y <= 0 {
+            return None;
+        }
+
+        let mut tile_set = TileSetRef::new(self.tile_set()?);
+        let tile_set = tile_set.as_loaded();
+        let tiles = self.tiles.as_ref()?.data_ref();
+        let tiles = tiles.as_loaded_ref()?;
+
+        Some(rasterize_tiles(&tile_set, tiles, region, resolution, mode))
+    }
+}
+
+/// Creates a texture with nearest filtering from the given RGBA8 pixels.
+pub(super) fn make_texture(resolution: Vector2<u32>, pixels: Vec<u8>) -> Option<TextureResource> {
+    let mut texture = Texture::from_bytes(
+        TextureKind::Rectangle {
+            width: resolution.x,
+            height: resolution.y,
+        },
+        TexturePixelKind::RGBA8,
+        pixels,
+    )?;
+    texture.set_minification_filter(TextureMinificationFilter::Nearest);
+    texture.set_magnification_filter(TextureMagnificationFilter::Nearest);
+    texture.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+    texture.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+    Some(TextureResource::new_ok(ResourceKind::Embedded, texture))
+}
+
+/// Rasterizes the given region of the tiles into RGBA8 pixels, the first row of pixels is the top
+/// of the region. The resolution and the region must not be empty. This is the implementation of
+/// [`TileMap::render_to_pixels`] for the cases when the tile set and the tiles are already locked.
+pub(super) fn rasterize_tiles(
+    tile_set: &OptionTileSet,
+    tiles: &TileMapData,
+    region: TileRect,
+    resolution: Vector2<u32>,
+    mode: TileMapCaptureMode,
+) -> Vec<u8> {
+    let mut render_data =
+        FxHashMap::<(TileDefinitionHandle, OrthoTransformation), Option<TileRenderData>>::default();
+    let mut textures = FxHashMap::default();
+    let mut pixels = vec![0; resolution.x as usize * resolution.y as usize * 4];
+    let size = region.size.cast::<f32>();
+    let origin = region.position.cast::<f32>();
+
+    for (row, row_pixels) in pixels
+        .chunks_exact_mut(resolution.x as usize * 4)
+        .enumerate()
+    {
+        let v = (row as f32 + 0.5) / resolution.y as f32;
+        let y = origin.y + size.y * (1.0 - v);
+        for (column, pixel) in row_pixels.chunks_exact_mut(4).enumerate() {
+            let u = (column as f32 + 0.5) / resolution.x as f32;
+            let x = origin.x + size.x * u;
+            let cell = Vector2::new(x.floor() as i32, y.floor() as i32);
+
+            let Some(handle) = tiles.get(cell) else {
+                continue;
+            };
+            let transformation = tiles.transformation(cell);
+            let Some(data) = render_data
+                .entry((handle, transformation))
+                .or_insert_with(|| {
+                    tile_set
+                        .get_tile_render_data(handle.into())
+                        .map(|data| data.transformed(transformation))
+                })
+            else {
+                continue;
+            };
+
+            let mut color = data.color;
+            if mode == TileMapCaptureMode::Material {
+                if let Some(texel) = data.material_bounds.as_ref().and_then(|bounds| {
+                    let local = Vector2::new(x - x.floor(), y - y.floor());
+                    sample_material(&mut textures, bounds, local)
+                }) {
+                    color = modulate(texel, color);
+                }
+            }
+
+            pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    pixels
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scene::tilemap::{
+        test_fixture::{self, FLOOR},
+        tileset::TileData,
+    };
+
+    fn make_tile_map() -> TileMap {
+        let tile_set = test_fixture::make_tile_set(|_| TileData {
+            color: Color::RED,
+            ..Default::default()
+        });
+        test_fixture::make_tile_map(
+            tile_set,
+            [(Vector2::new(0, 0), FLOOR), (Vector2::new(-1, 1), FLOOR)],
+        )
+    }
+
+    #[test]
+    fn test_render_to_pixels() {
+        let tile_map = make_tile_map();
+        let pixels = tile_map
+            .render_to_pixels(
+                TileRect::new(-1, 0, 2, 2),
+                Vector2::new(2, 2),
+                TileMapCaptureMode::Color,
+            )
+            .unwrap();
+        let red = [255, 0, 0, 255];
+        let empty = [0, 0, 0, 0];
+        // The first row is the top of the region.
+        assert_eq!(&pixels[0..4], &red);
+        assert_eq!(&pixels[4..8], &empty);
+        assert_eq!(&pixels[8..12], &empty);
+        assert_eq!(&pixels[12..16], &red);
+
+        assert!(tile_map
+            .render_to_pixels(
+                TileRect::new(0, 0, 0, 0),
+                Vector2::new(2, 2),
+                Default::default()
+            )
+            .is_none());
+    }
+}
@@ -0,0 +1,592 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Clipboard interchange format for tile selections and copying of tile map regions. See
+//! [`TileClipboard`] and [`CopiedTileRegion`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector2, visitor::prelude::*},
+    fxhash::FxHashMap,
+};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Write},
+    path::PathBuf,
+};
+
+use super::*;
+
+/// The first line of the text form of [`TileClipboard`].
+const TEXT_HEADER: &str = "FyroxTiles";
+/// The version of the text form of [`TileClipboard`].
+const TEXT_VERSION: u32 = 1;
+
+/// An error that may occur while reading [`TileClipboard`].
+#[derive(Debug)]
+pub enum TileClipboardError {
+    /// The data does not start with the expected header, most likely it is not tile data at all.
+    InvalidHeader,
+    /// The data was written by a newer version of the format.
+    UnsupportedVersion(u32),
+    /// A line of the text form could not be parsed. Contains the line number, starting from one.
+    InvalidLine(usize),
+    /// The binary form could not be read.
+    Visit(VisitError),
+}
+
+impl Display for TileClipboardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "The data is not a tile selection."),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "Unsupported tile clipboard version {version}.")
+            }
+            Self::InvalidLine(line) => write!(f, "Invalid tile clipboard data at line {line}."),
+            Self::Visit(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl Error for TileClipboardError {}
+
+impl From<VisitError> for TileClipboardError {
+    fn from(value: VisitError) -> Self {
+        Self::Visit(value)
+    }
+}
+
+/// A tile selection in a form that is independent of any particular tile map, tile set or brush,
+/// so it could be copied from one of them and pasted into another one, even in a different
+/// editor instance. It contains the handles of the tiles along with the path of the tile set
+/// the handles refer to, so the receiver could load the tile set (if needed) and check that the
+/// handles are meaningful.
+///
+/// There are two representations of the data: a text form ([`Self::to_text`] and
+/// [`Self::from_text`]) that is suitable for system clipboards and could be read or edited
+/// by a human, and a compact binary form ([`Self::to_bytes`] and [`Self::from_bytes`]).
+/// The text form looks like this:
+///
+/// ```text
+/// FyroxTiles 1
+/// tile_set data/tiles/dungeon.tileset
+/// transform 0 1
+/// 0,0 (0,0):(1,2)
+/// 1,0 (0,0):(2,2) 1 0
+/// ```
+///
+/// The `tile_set` line is omitted if the tile set is embedded. The `transform` line contains the
+/// flip flag and the amount of counter-clockwise rotations of the selection (see
+/// [`OrthoTransformation`]), it could be omitted as well. Every other line contains the position
+/// of a tile in the selection followed by the handle of the tile and, optionally, by the flip flag
+/// and the rotation of the cell of the tile.
+#[derive(Clone, Debug, Default, PartialEq, Visit)]
+pub struct TileClipboard {
+    /// The path of the tile set that the handles refer to. `None` if the tile set is embedded.
+    pub tile_set: Option<PathBuf>,
+    /// The transformation of the selection. The positions of [`Self::tiles`] are not transformed.
+    pub transformation: OrthoTransformation,
+    /// The tiles of the selection.
+    pub tiles: Tiles,
+    /// The transformations of the cells of the tiles (see [`TileMapData::transformation`]). The
+    /// cells with the identity transformation are omitted.
+    #[visit(optional)]
+    pub transformations: FxHashMap<Vector2<i32>, OrthoTransformation>,
+}
+
+impl TileClipboard {
+    /// Creates clipboard data for the given tiles from the given tile set.
+    pub fn new<I>(tile_set: Option<&TileSetResource>, tiles: I) -> Self
+    where
+        I: IntoIterator<Item = (Vector2<i32>, TileDefinitionHandle)>,
+    {
+        let mut result = Self {
+            tile_set: tile_set.and_then(|r| r.kind().into_path()),
+            ..Default::default()
+        };
+        result.tiles.extend(tiles);
+        result
+    }
+
+    /// Creates clipboard data from the given stamp, that uses the tiles of the given tile set.
+    pub fn from_stamp(tile_set: Option<&TileSetResource>, stamp: &Stamp) -> Self {
+        let transformation = stamp.transformation();
+        let inverse = transformation.inverted();
+        Self {
+            transformation,
+            ..Self::new(
+                tile_set,
+                stamp
+                    .iter()
+                    .map(|(position, handle)| (position.transformed(inverse), *handle)),
+            )
+        }
+    }
+
+    /// Creates clipboard data from the given copied region, that uses the tiles of the given tile
+    /// set. The transformations of the cells are kept.
+    pub fn from_region(tile_set: Option<&TileSetResource>, region: &CopiedTileRegion) -> Self {
+        let mut result = Self::new(
+            tile_set,
+            region
+                .tiles
+                .iter()
+                .map(|(position, (_, handle))| (*position, *handle)),
+        );
+        result.transformations.extend(
+            region
+                .tiles
+                .iter()
+                .filter(|(_, (transformation, _))| !transformation.is_identity())
+                .map(|(position, (transformation, _))| (*position, *transformation)),
+        );
+        result
+    }
+
+    /// Creates a region from the tiles of the clipboard, that could be pasted into a tile map
+    /// by [`TileMap::paste_region`]. The transformation of the selection is applied to the
+    /// positions and the transformations of the tiles.
+    pub fn to_region(&self) -> CopiedTileRegion {
+        let mut tiles = TileGridMap::default();
+        for (position, handle) in self.tiles.iter() {
+            let transformation = self
+                .transformations
+                .get(position)
+                .copied()
+                .unwrap_or_default();
+            tiles.insert(
+                position.transformed(self.transformation),
+                (transformation.transformed(self.transformation), *handle),
+            );
+        }
+        let mut bounds = OptionTileRect::default();
+        for position in tiles.keys() {
+            bounds.push(*position);
+        }
+        CopiedTileRegion {
+            region: TileRegion {
+                origin: Vector2::new(0, 0),
+                bounds,
+            },
+            tiles,
+        }
+    }
+
+    /// Fills the given stamp with the tiles of the clipboard. Stamps have no transformations of
+    /// individual cells, so the tiles are drawn without them.
+    pub fn to_stamp(&self, stamp: &mut Stamp) {
+        stamp.build(self.tiles.iter().map(|(p, h)| (*p, *h)));
+        stamp.transform(self.transformation);
+    }
+
+    /// True if there are no tiles in the clipboard.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// True if the handles of the clipboard refer to the given tile set. Embedded tile sets cannot
+    /// be told apart, so any embedded tile set matches clipboard data without a tile set path.
+    pub fn matches_tile_set(&self, tile_set: &TileSetResource) -> bool {
+        self.tile_set == tile_set.kind().into_path()
+    }
+
+    /// Writes the clipboard into its text form.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{TEXT_HEADER} {TEXT_VERSION}\n");
+        if let Some(path) = self.tile_set.as_ref() {
+            let _ = writeln!(text, "tile_set {}", path.to_string_lossy());
+        }
+        if !self.transformation.is_identity() {
+            let _ = writeln!(
+                text,
+                "transform {} {}",
+                self.transformation.is_flipped() as u8,
+                self.transformation.rotation()
+            );
+        }
+        let mut tiles = self.tiles.iter().collect::<Vec<_>>();
+        tiles.sort_by_key(|(p, _)| (-p.y, p.x));
+        for (position, handle) in tiles {
+            let _ = write!(text, "{},{} {}", position.x, position.y, handle);
+            if let Some(transformation) = self.transformations.get(position) {
+                let _ = write!(
+                    text,
+                    " {} {}",
+                    transformation.is_flipped() as u8,
+                    transformation.rotation()
+                );
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Reads the clipboard from its text form.
+    pub fn from_text(text: &str) -> Result<Self, TileClipboardError> {
+        let mut lines = text.lines().enumerate();
+        let version = lines
+            .next()
+            .and_then(|(_, line)| line.trim().strip_prefix(TEXT_HEADER))
+            .and_then(|version| version.trim().parse::<u32>().ok())
+            .ok_or(TileClipboardError::InvalidHeader)?;
+        if version > TEXT_VERSION {
+            return Err(TileClipboardError::UnsupportedVersion(version));
+        }
+
+        let mut result = Self::default();
+        for (index, line) in lines {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = || TileClipboardError::InvalidLine(index + 1);
+            if let Some(path) = line.strip_prefix("tile_set ") {
+                result.tile_set = Some(PathBuf::from(path.trim()));
+            } else if let Some(transform) = line.strip_prefix("transform ") {
+                let mut numbers = transform.split_whitespace().map(|n| n.parse::<i8>());
+                let (Some(Ok(flipped)), Some(Ok(rotation))) = (numbers.next(), numbers.next())
+                else {
+                    return Err(error());
+                };
+                result.transformation = OrthoTransformation::new(flipped != 0, rotation);
+            } else {
+                let (position, tile) = line.split_once(' ').ok_or_else(error)?;
+                let (x, y) = position.split_once(',').ok_or_else(error)?;
+                let position = Vector2::new(
+                    x.trim().parse().map_err(|_| error())?,
+                    y.trim().parse().map_err(|_| error())?,
+                );
+                let mut tile = tile.split_whitespace();
+                let handle = tile
+                    .next()
+                    .and_then(TileDefinitionHandle::parse)
+                    .ok_or_else(error)?;
+                result.tiles.insert(position, handle);
+                match (tile.next(), tile.next(), tile.next()) {
+                    (None, _, _) => (),
+                    (Some(flipped), Some(rotation), None) => {
+                        let flipped = flipped.parse::<i8>().map_err(|_| error())?;
+                        let rotation = rotation.parse::<i8>().map_err(|_| error())?;
+                        let transformation = OrthoTransformation::new(flipped != 0, rotation);
+                        if !transformation.is_identity() {
+                            result.transformations.insert(position, transformation);
+                        }
+                    }
+                    _ => return Err(error()),
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Writes the clipboard into its binary form.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, VisitError> {
+        let mut visitor = Visitor::new();
+        self.clone().visit("TileClipboard", &mut visitor)?;
+        visitor.save_binary_to_vec()
+    }
+
+    /// Reads the clipboard from its binary form.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, TileClipboardError> {
+        let mut visitor = Visitor::load_from_memory(data)?;
+        let mut result = Self::default();
+        result.visit("TileClipboard", &mut visitor)?;
+        Ok(result)
+    }
+}
+
+/// Tiles copied from a [`TileRegion`] of a tile map by [`TileMap::copy_region`], that could be
+/// pasted into this or any other tile map by [`TileMap::paste_region`]. The positions of the
+/// tiles are relative to the origin of the region, and every tile keeps the transformation of
+/// its cell. Use [`TileClipboard::from_region`] to store the tiles as text or binary data.
+#[derive(Clone, Debug, Default)]
+pub struct CopiedTileRegion {
+    /// The region the tiles were copied from, in the coordinates of the source tile map. The
+    /// origin of the region is the cell that corresponds to (0,0) of [`Self::tiles`].
+    pub region: TileRegion,
+    /// The copied tiles along with the transformations of their cells.
+    pub tiles: TileGridMap<(OrthoTransformation, TileDefinitionHandle)>,
+}
+
+impl CopiedTileRegion {
+    /// True if there are no tiles in the region.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Removes every tile, which position in the source tile map does not satisfy the given
+    /// predicate. It is useful to copy arbitrary selections, instead of whole rectangles.
+    pub fn retain<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(Vector2<i32>) -> bool,
+    {
+        let origin = self.region.origin;
+        self.tiles
+            .retain(|position, _| predicate(*position + origin));
+    }
+}
+
+impl TileMap {
+    /// Copies the tiles of the given rectangle, so they could be pasted into this or any other
+    /// tile map by [`Self::paste_region`]. The positions of the tiles are relative to the
+    /// position of the rectangle. See [`CopiedTileRegion`] docs for more info.
+    pub fn copy_region(&self, rect: TileRect) -> CopiedTileRegion {
+        let mut tiles = TileGridMap::default();
+        if let Some(data) = self.tiles().map(|r| r.data_ref()) {
+            if let Some(data) = data.as_loaded_ref() {
+                for (position, handle) in data.bounded_iter(rect.into()) {
+                    tiles.insert(
+                        position - rect.position,
+                        (data.transformation(position), handle),
+                    );
+                }
+            }
+        }
+        CopiedTileRegion {
+            region: TileRegion {
+                origin: rect.position,
+                bounds: rect.into(),
+            },
+            tiles,
+        }
+    }
+
+    /// Creates the update that pastes the given tiles, so that the cell (0,0) of the region is
+    /// placed at the given position. The region is flipped and rotated by the given transformation
+    /// around that cell, and the transformation is combined with the transformations of the cells.
+    /// The tiles are replaced by their transformed versions from the tile set, if there are any.
+    /// The update could be applied by [`Self::swap_tiles`], see also [`Self::paste_region`].
+    pub fn paste_region_update(
+        &self,
+        position: Vector2<i32>,
+        region: &CopiedTileRegion,
+        transformation: OrthoTransformation,
+    ) -> TilesUpdate {
+        let mut update = TransTilesUpdate::default();
+        for (tile_position, (tile_transformation, handle)) in region.tiles.iter() {
+            update.insert(
+                position + tile_position.transformed(transformation),
+                Some((tile_transformation.transformed(transformation), *handle)),
+            );
+        }
+        self.build_tiles_update(&update)
+    }
+
+    /// Pastes the given tiles, so that the cell (0,0) of the region is placed at the given
+    /// position, see [`Self::paste_region_update`] for more info. The editor pastes tiles the same
+    /// way. Returns the update that reverts the change, when passed to [`Self::swap_tiles`].
+    pub fn paste_region(
+        &mut self,
+        position: Vector2<i32>,
+        region: &CopiedTileRegion,
+        transformation: OrthoTransformation,
+    ) -> TilesUpdate {
+        let mut update = self.paste_region_update(position, region, transformation);
+        self.swap_tiles(&mut update);
+        update
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const A: TileDefinitionHandle = TileDefinitionHandle::new(0, 0, 1, 0);
+    const B: TileDefinitionHandle = TileDefinitionHandle::new(0, 0, 2, 0);
+
+    fn clipboard() -> TileClipboard {
+        let mut clipboard = TileClipboard::new(
+            None,
+            [
+                (Vector2::new(0, 0), TileDefinitionHandle::new(0, 0, 1, 2)),
+                (Vector2::new(-1, 3), TileDefinitionHandle::new(-2, 1, 0, -5)),
+            ],
+        );
+        clipboard.tile_set = Some(PathBuf::from("data/dungeon.tileset"));
+        clipboard.transformation = OrthoTransformation::new(true, 3);
+        clipboard
+            .transformations
+            .insert(Vector2::new(-1, 3), OrthoTransformation::new(false, 2));
+        clipboard
+    }
+
+    #[test]
+    fn test_tile_clipboard_text() {
+        let clipboard = clipboard();
+        let text = clipboard.to_text();
+        assert!(text.starts_with("FyroxTiles 1\ntile_set data/dungeon.tileset\n"));
+        assert_eq!(TileClipboard::from_text(&text).unwrap(), clipboard);
+
+        assert!(matches!(
+            TileClipboard::from_text("Hello"),
+            Err(TileClipboardError::InvalidHeader)
+        ));
+        assert!(matches!(
+            TileClipboard::from_text("FyroxTiles 1\n0,0 (0,0)"),
+            Err(TileClipboardError::InvalidLine(2))
+        ));
+    }
+
+    #[test]
+    fn test_tile_clipboard_binary() {
+        let clipboard = clipboard();
+        let bytes = clipboard.to_bytes().unwrap();
+        assert_eq!(TileClipboard::from_bytes(&bytes).unwrap(), clipboard);
+    }
+
+    #[test]
+    fn test_tile_clipboard_stamp() {
+        let clipboard = clipboard();
+        let mut stamp = Stamp::default();
+        clipboard.to_stamp(&mut stamp);
+        assert_eq!(stamp.transformation(), clipboard.transformation);
+        let copy = TileClipboard::from_stamp(None, &stamp);
+        assert_eq!(copy.transformation, clipboard.transformation);
+        assert_eq!(copy.tiles.len(), 2);
+        assert!(copy.tile_set.is_none());
+    }
+
+    fn make_tile_map() -> TileMap {
+        let mut data = TileMapData::default();
+        data.set(Vector2::new(3, 3), A);
+        data.set(Vector2::new(4, 3), B);
+        data.set_transformation(Vector2::new(4, 3), OrthoTransformation::new(true, 0));
+        data.set(Vector2::new(9, 9), B);
+        let mut tile_map = TileMap::default();
+        tile_map.set_tiles(TileMapDataResource::new_ok(ResourceKind::Embedded, data));
+        tile_map
+    }
+
+    fn tile_at(tile_map: &TileMap, position: Vector2<i32>) -> Option<TileDefinitionHandle> {
+        let tiles = tile_map.tiles().unwrap().data_ref();
+        tiles.as_loaded_ref().unwrap().get(position)
+    }
+
+    fn transformation_at(tile_map: &TileMap, position: Vector2<i32>) -> OrthoTransformation {
+        let tiles = tile_map.tiles().unwrap().data_ref();
+        tiles.as_loaded_ref().unwrap().transformation(position)
+    }
+
+    #[test]
+    fn test_copy_paste_region() {
+        let mut tile_map = make_tile_map();
+
+        let region = tile_map.copy_region(TileRect::new(3, 3, 2, 2));
+        assert_eq!(region.tiles.len(), 2);
+        assert_eq!(region.region.origin, Vector2::new(3, 3));
+        assert_eq!(
+            region.tiles.get(&Vector2::new(1, 0)),
+            Some(&(OrthoTransformation::new(true, 0), B))
+        );
+
+        let mut undo = tile_map.paste_region(
+            Vector2::new(-5, 0),
+            &region,
+            OrthoTransformation::new(false, 1),
+        );
+        assert_eq!(tile_at(&tile_map, Vector2::new(-5, 0)), Some(A));
+        assert_eq!(tile_at(&tile_map, Vector2::new(-5, 1)), Some(B));
+        assert_eq!(
+            transformation_at(&tile_map, Vector2::new(-5, 0)),
+            OrthoTransformation::new(false, 1)
+        );
+        // The cell transformation is combined with the transformation of the region.
+        assert_eq!(
+            transformation_at(&tile_map, Vector2::new(-5, 1)),
+            OrthoTransformation::new(true, 1)
+        );
+
+        tile_map.swap_tiles(&mut undo);
+        assert_eq!(tile_at(&tile_map, Vector2::new(-5, 0)), None);
+        assert_eq!(tile_at(&tile_map, Vector2::new(-5, 1)), None);
+    }
+
+    #[test]
+    fn test_paste_region_keeps_cell_transformations() {
+        let mut tile_map = make_tile_map();
+        let region = tile_map.copy_region(TileRect::new(3, 3, 2, 1));
+
+        let update = tile_map.paste_region_update(
+            Vector2::new(0, -3),
+            &region,
+            OrthoTransformation::identity(),
+        );
+        // Building the update does not change the tiles.
+        assert_eq!(tile_at(&tile_map, Vector2::new(0, -3)), None);
+        assert_eq!(update.get(&Vector2::new(1, -3)), Some(&Some(B)));
+        assert_eq!(
+            update.transformation(Vector2::new(1, -3)),
+            OrthoTransformation::new(true, 0)
+        );
+
+        tile_map.paste_region(
+            Vector2::new(0, -3),
+            &region,
+            OrthoTransformation::identity(),
+        );
+        assert_eq!(tile_at(&tile_map, Vector2::new(0, -3)), Some(A));
+        assert_eq!(
+            transformation_at(&tile_map, Vector2::new(1, -3)),
+            OrthoTransformation::new(true, 0)
+        );
+        // The source tiles stay intact.
+        assert_eq!(tile_at(&tile_map, Vector2::new(3, 3)), Some(A));
+    }
+
+    #[test]
+    fn test_copy_region_retain() {
+        let tile_map = make_tile_map();
+        let mut region = tile_map.copy_region(TileRect::new(0, 0, 10, 10));
+        assert_eq!(region.tiles.len(), 3);
+        region.retain(|position| position != Vector2::new(9, 9));
+        assert_eq!(region.tiles.len(), 2);
+        assert!(tile_map
+            .copy_region(TileRect::new(-10, -10, 5, 5))
+            .is_empty());
+        assert!(TileMap::default()
+            .copy_region(TileRect::new(0, 0, 10, 10))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tile_clipboard_region() {
+        let tile_map = make_tile_map();
+        let region = tile_map.copy_region(TileRect::new(3, 3, 2, 2));
+        let clipboard = TileClipboard::from_region(None, &region);
+        assert_eq!(clipboard.transformations.len(), 1);
+
+        let text = clipboard.to_text();
+        assert!(text.contains("1,0 (0,0):(2,0) 1 0\n"));
+        let copy = TileClipboard::from_text(&text).unwrap();
+        assert_eq!(copy, clipboard);
+
+        let pasted = copy.to_region();
+        assert_eq!(pasted.tiles, region.tiles);
+
+        // The transformation of the selection is applied to the region.
+        let mut rotated = copy;
+        rotated.transformation = OrthoTransformation::new(false, 1);
+        let pasted = rotated.to_region();
+        assert_eq!(
+            pasted.tiles.get(&Vector2::new(0, 1)),
+            Some(&(OrthoTransformation::new(true, 1), B))
+        );
+        assert!(pasted.region.bounds.contains(Vector2::new(0, 1)));
+    }
+}
@@ -0,0 +1,381 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Colliders of tiles are built independently from each other, so the shape of a tile map consists
+//! of lots of small triangles, and bodies that slide across the tile map could bump into the seams
+//! between them. Functions of this module combine the triangles of adjacent tiles into larger
+//! convex polygons ([`merge_tile_collider_polygons`]) or outlines ([`merge_tile_collider_outlines`]).
+
+use crate::core::algebra::{Point2, Vector2};
+use fxhash::FxHashMap;
+
+/// Vertices of neighbouring tiles are not shared, so the vertices are welded by their positions
+/// quantized with this scale.
+const WELD_SCALE: f32 = 1024.0;
+
+/// Relative tolerance for collinearity tests.
+const COLLINEAR_EPSILON: f32 = 1.0e-4;
+
+/// Triangles with welded vertices and consistent (counter-clockwise) winding.
+struct WeldedMesh {
+    vertices: Vec<Point2<f32>>,
+    triangles: Vec<[usize; 3]>,
+}
+
+impl WeldedMesh {
+    fn new(vertices: &[Point2<f32>], triangles: &[[u32; 3]]) -> Self {
+        let mut welded = Vec::new();
+        let mut map = FxHashMap::default();
+        let indices = vertices
+            .iter()
+            .map(|v| {
+                let key = (v.coords * WELD_SCALE).map(|c| c.round() as i64);
+                *map.entry((key.x, key.y)).or_insert_with(|| {
+                    welded.push(*v);
+                    welded.len() - 1
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut result = Vec::with_capacity(triangles.len());
+        for triangle in triangles {
+            let [Some(a), Some(b), Some(c)] = triangle.map(|i| indices.get(i as usize).copied())
+            else {
+                continue;
+            };
+            // Tiles could be flipped, and so their triangles, the winding is restored here.
+            let area = (welded[b] - welded[a]).perp(&(welded[c] - welded[a]));
+            if area > f32::EPSILON {
+                result.push([a, b, c]);
+            } else if area < -f32::EPSILON {
+                result.push([a, c, b]);
+            }
+        }
+
+        Self {
+            vertices: welded,
+            triangles: result,
+        }
+    }
+}
+
+fn polygon_edges(polygon: &[usize]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(&a, &b)| (a, b))
+}
+
+/// Returns the cross and dot products of the edges adjacent to `current` vertex, normalized by
+/// the lengths of the edges.
+fn corner(prev: Point2<f32>, current: Point2<f32>, next: Point2<f32>) -> (f32, f32) {
+    let a = current - prev;
+    let b = next - current;
+    let scale = (a.norm() * b.norm()).max(f32::EPSILON);
+    (a.perp(&b) / scale, a.dot(&b) / scale)
+}
+
+fn is_collinear(prev: Point2<f32>, current: Point2<f32>, next: Point2<f32>) -> bool {
+    let (cross, dot) = corner(prev, current, next);
+    cross.abs() <= COLLINEAR_EPSILON && dot > 0.0
+}
+
+fn is_convex(vertices: &[Point2<f32>], polygon: &[usize]) -> bool {
+    let n = polygon.len();
+    (0..n).all(|i| {
+        let prev = vertices[polygon[(i + n - 1) % n]];
+        let next = vertices[polygon[(i + 1) % n]];
+        let (cross, dot) = corner(prev, vertices[polygon[i]], next);
+        cross > COLLINEAR_EPSILON || (cross.abs() <= COLLINEAR_EPSILON && dot > 0.0)
+    })
+}
+
+fn remove_collinear(vertices: &[Point2<f32>], polygon: &mut Vec<usize>) {
+    let mut i = 0;
+    while polygon.len() > 3 && i < polygon.len() {
+        let n = polygon.len();
+        let prev = vertices[polygon[(i + n - 1) % n]];
+        let next = vertices[polygon[(i + 1) % n]];
+        if is_collinear(prev, vertices[polygon[i]], next) {
+            polygon.remove(i);
+            // The removal could make the previous vertex collinear as well.
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Merges two counter-clockwise polygons that share the edge `a -> b` (`b -> a` in `other`).
+/// Collinear vertices are not removed while merging, so the polygons could share a chain of
+/// edges, which is found first. Returns `None` if the result is not convex.
+fn merge_pair(
+    vertices: &[Point2<f32>],
+    polygon: &[usize],
+    other: &[usize],
+    a: usize,
+    b: usize,
+) -> Option<Vec<usize>> {
+    let n = polygon.len();
+    let is_shared = |from: usize, to: usize| polygon_edges(other).any(|edge| edge == (to, from));
+
+    // Indices of the first and the last vertex of the shared chain in the polygon.
+    let mut start = polygon.iter().position(|&i| i == a)?;
+    let mut end = (start + 1) % n;
+    if polygon[end] != b {
+        return None;
+    }
+    let mut shared = 1;
+    while shared < n && is_shared(polygon[(start + n - 1) % n], polygon[start]) {
+        start = (start + n - 1) % n;
+        shared += 1;
+    }
+    while shared < n && is_shared(polygon[end], polygon[(end + 1) % n]) {
+        end = (end + 1) % n;
+        shared += 1;
+    }
+    if shared == n {
+        return None;
+    }
+
+    // [end, ..., start] of the polygon followed by the vertices of the other polygon between
+    // start and end.
+    let mut merged = Vec::with_capacity(n + other.len());
+    let mut i = end;
+    loop {
+        merged.push(polygon[i]);
+        if i == start {
+            break;
+        }
+        i = (i + 1) % n;
+    }
+    let m = other.len();
+    let other_start = other.iter().position(|&i| i == polygon[start])?;
+    let mut k = (other_start + 1) % m;
+    while other[k] != polygon[end] {
+        if k == other_start {
+            return None;
+        }
+        merged.push(other[k]);
+        k = (k + 1) % m;
+    }
+
+    // Polygons that share more than one chain would produce duplicated vertices.
+    let mut sorted = merged.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+    if sorted.len() != merged.len() || !is_convex(vertices, &merged) {
+        return None;
+    }
+
+    Some(merged)
+}
+
+/// Merges the given triangles (usually produced by [`super::TileCollider::build_collider_shape`])
+/// into larger convex polygons. Vertices of adjacent triangles do not need to be shared, they're
+/// matched by their positions. The winding of the triangles does not matter either, but the
+/// resulting polygons are always counter-clockwise. Collinear vertices are removed from the
+/// resulting polygons.
+pub fn merge_tile_collider_polygons(
+    vertices: &[Point2<f32>],
+    triangles: &[[u32; 3]],
+) -> Vec<Vec<Point2<f32>>> {
+    let mesh = WeldedMesh::new(vertices, triangles);
+    let mut polygons = mesh
+        .triangles
+        .iter()
+        .map(|t| Some(t.to_vec()))
+        .collect::<Vec<_>>();
+
+    // Every polygon is merged at most once per pass, so the edge map stays valid during a pass.
+    // The longest shared edges are merged first, this way the triangles of a tile are merged
+    // together before they're merged with the triangles of neighbouring tiles.
+    loop {
+        let mut edges = FxHashMap::default();
+        for (index, polygon) in polygons.iter().enumerate() {
+            if let Some(polygon) = polygon {
+                for edge in polygon_edges(polygon) {
+                    edges.insert(edge, index);
+                }
+            }
+        }
+
+        let mut candidates = edges
+            .iter()
+            .filter_map(|(&(a, b), &i)| {
+                let j = *edges.get(&(b, a))?;
+                (i < j).then_some((i, j, a, b))
+            })
+            .collect::<Vec<_>>();
+        let length = |&(_, _, a, b): &(usize, usize, usize, usize)| {
+            (mesh.vertices[a] - mesh.vertices[b]).norm_squared()
+        };
+        candidates.sort_by(|x, y| length(y).total_cmp(&length(x)).then(x.cmp(y)));
+
+        let mut touched = vec![false; polygons.len()];
+        let mut merged_any = false;
+        for (i, j, a, b) in candidates {
+            if touched[i] || touched[j] {
+                continue;
+            }
+            let (Some(polygon), Some(other)) = (polygons[i].as_ref(), polygons[j].as_ref()) else {
+                continue;
+            };
+            if let Some(merged) = merge_pair(&mesh.vertices, polygon, other, a, b) {
+                polygons[i] = Some(merged);
+                polygons[j] = None;
+                touched[i] = true;
+                touched[j] = true;
+                merged_any = true;
+            }
+        }
+
+        if !merged_any {
+            break;
+        }
+    }
+
+    polygons
+        .into_iter()
+        .flatten()
+        .map(|mut polygon| {
+            remove_collinear(&mesh.vertices, &mut polygon);
+            polygon.into_iter().map(|i| mesh.vertices[i]).collect()
+        })
+        .collect()
+}
+
+/// Finds the outlines of the area covered by the given triangles (usually produced by
+/// [`super::TileCollider::build_collider_shape`]). The edges that are shared by adjacent triangles
+/// are removed, collinear edges are merged into single edges. Every outline is a closed loop,
+/// outer outlines are counter-clockwise, outlines of holes are clockwise.
+pub fn merge_tile_collider_outlines(
+    vertices: &[Point2<f32>],
+    triangles: &[[u32; 3]],
+) -> Vec<Vec<Point2<f32>>> {
+    let mesh = WeldedMesh::new(vertices, triangles);
+
+    let mut counts = FxHashMap::<(usize, usize), u32>::default();
+    for triangle in mesh.triangles.iter() {
+        for (a, b) in polygon_edges(triangle) {
+            *counts.entry((a.min(b), a.max(b))).or_default() += 1;
+        }
+    }
+
+    let mut outgoing = FxHashMap::<usize, Vec<usize>>::default();
+    for triangle in mesh.triangles.iter() {
+        for (a, b) in polygon_edges(triangle) {
+            if counts.get(&(a.min(b), a.max(b))) == Some(&1) {
+                outgoing.entry(a).or_default().push(b);
+            }
+        }
+    }
+
+    let mut starts = outgoing.keys().copied().collect::<Vec<_>>();
+    starts.sort_unstable();
+
+    let mut outlines = Vec::new();
+    for start in starts {
+        while let Some(mut current) = outgoing.get_mut(&start).and_then(|edges| edges.pop()) {
+            let mut outline = vec![start];
+            while current != start {
+                outline.push(current);
+                let Some(next) = outgoing.get_mut(&current).and_then(|edges| edges.pop()) else {
+                    break;
+                };
+                current = next;
+            }
+            remove_collinear(&mesh.vertices, &mut outline);
+            outlines.push(outline.into_iter().map(|i| mesh.vertices[i]).collect());
+        }
+    }
+    outlines
+}
+
+/// Converts the given outlines into a list of segments.
+pub fn outline_segments(
+    outlines: &[Vec<Point2<f32>>],
+) -> impl Iterator<Item = [Vector2<f32>; 2]> + '_ {
+    outlines.iter().flat_map(|outline| {
+        outline
+            .iter()
+            .zip(outline.iter().cycle().skip(1))
+            .map(|(a, b)| [a.coords, b.coords])
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Two rows of three unit squares each, built the same way as rectangle tile colliders.
+    fn squares() -> (Vec<Point2<f32>>, Vec<[u32; 3]>) {
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+        for y in 0..2 {
+            for x in 0..3 {
+                let origin = vertices.len() as u32;
+                for (dx, dy) in [(0, 0), (1, 0), (1, 1), (0, 1)] {
+                    vertices.push(Point2::new((x + dx) as f32, (y + dy) as f32));
+                }
+                triangles.push([origin, origin + 1, origin + 2]);
+                // Flipped tile.
+                triangles.push([origin + 3, origin + 2, origin]);
+            }
+        }
+        (vertices, triangles)
+    }
+
+    #[test]
+    fn test_merge_tile_collider_polygons() {
+        let (vertices, triangles) = squares();
+        let polygons = merge_tile_collider_polygons(&vertices, &triangles);
+        assert_eq!(polygons.len(), 1);
+        let mut polygon = polygons[0].clone();
+        assert_eq!(polygon.len(), 4);
+        polygon.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        assert_eq!(
+            polygon,
+            vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(0.0, 2.0),
+                Point2::new(3.0, 0.0),
+                Point2::new(3.0, 2.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_tile_collider_outlines() {
+        let (mut vertices, mut triangles) = squares();
+        let outlines = merge_tile_collider_outlines(&vertices, &triangles);
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(outlines[0].len(), 4);
+        assert_eq!(outline_segments(&outlines).count(), 4);
+
+        // A separate tile produces a separate outline.
+        let origin = vertices.len() as u32;
+        vertices.extend([(5.0, 0.0), (6.0, 0.0), (6.0, 1.0)].map(|(x, y)| Point2::new(x, y)));
+        triangles.push([origin, origin + 1, origin + 2]);
+        let outlines = merge_tile_collider_outlines(&vertices, &triangles);
+        assert_eq!(outlines.len(), 2);
+        assert_eq!(outline_segments(&outlines).count(), 7);
+    }
+}
@@ -18,7 +18,11 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use std::{collections::hash_map, path::Path};
+use std::{
+    collections::hash_map,
+    path::Path,
+    sync::atomic::{self, AtomicU64},
+};
 
 use crate::asset::{Resource, ResourceData};
 use fxhash::FxHashMap;
@@ -38,6 +42,14 @@ const HEIGHT_BITS: i32 = (CHUNK_HEIGHT - 1) as i32;
 /// Resource for storing the tile handles of a tile map.
 pub type TileMapDataResource = Resource<TileMapData>;
 
+/// The counter is shared by all tile map data, so a revision identifies the content of a chunk
+/// even if tile map data is replaced.
+static NEXT_CHUNK_REVISION: AtomicU64 = AtomicU64::new(1);
+
+fn next_chunk_revision() -> u64 {
+    NEXT_CHUNK_REVISION.fetch_add(1, atomic::Ordering::Relaxed)
+}
+
 /// Given a tile position, calculate the position of the chunk containing that tile
 /// and the position of the tile within that chunk, and return them as a pair:
 /// (chunk position, tile position within chunk)
@@ -52,34 +64,114 @@ fn tile_position_to_chunk_position(position: Vector2<i32>) -> (Vector2<i32>, Vec
     )
 }
 
+/// The rect of tiles covered by the chunk that contains the given tile position.
+pub(super) fn chunk_rect(position: Vector2<i32>) -> TileRect {
+    let (chunk, _) = tile_position_to_chunk_position(position);
+    TileRect::new(chunk.x, chunk.y, CHUNK_WIDTH as i32, CHUNK_HEIGHT as i32)
+}
+
 #[derive(Clone, Debug, Reflect)]
-struct Chunk([TileDefinitionHandle; CHUNK_WIDTH * CHUNK_HEIGHT]);
+struct Chunk {
+    handles: [TileDefinitionHandle; CHUNK_WIDTH * CHUNK_HEIGHT],
+    /// The smallest rect that contains every non-empty cell, relative to the origin of the chunk.
+    #[reflect(hidden)]
+    bounds: OptionTileRect,
+    /// See [`TileMapData::chunk_revision`].
+    #[reflect(hidden)]
+    revision: u64,
+    /// The transformations of the tiles in the same order as the handles, or nothing if every
+    /// tile of the chunk is not transformed.
+    #[reflect(hidden)]
+    transforms: Vec<OrthoTransformation>,
+}
+
+fn encode_transformation(transformation: OrthoTransformation) -> i8 {
+    let value = transformation.rotation() + 1;
+    if transformation.is_flipped() {
+        -value
+    } else {
+        value
+    }
+}
+
+fn decode_transformation(value: i8) -> Option<OrthoTransformation> {
+    matches!(value, -4..=-1 | 1..=4).then(|| OrthoTransformation::new(value < 0, value.abs() - 1))
+}
 
 impl Visit for Chunk {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
         if visitor.is_reading() {
-            let mut data = Vec::default();
-            BinaryBlob { vec: &mut data }.visit(name, visitor)?;
-            if data.len() != CHUNK_WIDTH * CHUNK_HEIGHT {
-                return Err(VisitError::User(
-                    "Wrong number of handles in a chunk".into(),
-                ));
+            // The handles are stored in the compact form of [`Tiles`], but the chunks that were
+            // saved as a dense array of handles before the compact form was introduced can still
+            // be loaded.
+            let mut bytes = Vec::<u8>::new();
+            let mut blob = BinaryBlob { vec: &mut bytes };
+            if blob.visit(&format!("{name}Tiles"), visitor).is_ok() {
+                self.decode_handles(&bytes)?;
+            } else {
+                let mut data = Vec::default();
+                BinaryBlob { vec: &mut data }.visit(name, visitor)?;
+                if data.len() != CHUNK_WIDTH * CHUNK_HEIGHT {
+                    return Err(VisitError::User(
+                        "Wrong number of handles in a chunk".into(),
+                    ));
+                }
+                self.handles
+                    .clone_from_slice(&data[0..CHUNK_WIDTH * CHUNK_HEIGHT]);
+            }
+            self.update_bounds();
+            self.revision = next_chunk_revision();
+            // The transformations are optional, because they are only written for the chunks
+            // with transformed tiles.
+            let mut transforms = Vec::<i8>::default();
+            let mut blob = BinaryBlob {
+                vec: &mut transforms,
+            };
+            if blob.visit(&format!("{name}Transforms"), visitor).is_ok() {
+                if transforms.len() != CHUNK_WIDTH * CHUNK_HEIGHT {
+                    return Err(VisitError::User(
+                        "Wrong number of transformations in a chunk".into(),
+                    ));
+                }
+                self.transforms = transforms
+                    .into_iter()
+                    .map(|value| {
+                        decode_transformation(value).ok_or_else(|| {
+                            VisitError::User(format!("Invalid tile transformation: {value}"))
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
             }
-            self.0
-                .clone_from_slice(&data[0..CHUNK_WIDTH * CHUNK_HEIGHT]);
             Ok(())
         } else {
             BinaryBlob {
-                vec: &mut self.0.to_vec(),
+                vec: &mut self.encode_handles(),
             }
-            .visit(name, visitor)
+            .visit(&format!("{name}Tiles"), visitor)?;
+            if !self.transforms.is_empty() {
+                BinaryBlob {
+                    vec: &mut self
+                        .transforms
+                        .iter()
+                        .copied()
+                        .map(encode_transformation)
+                        .collect::<Vec<_>>(),
+                }
+                .visit(&format!("{name}Transforms"), visitor)?;
+            }
+            Ok(())
         }
     }
 }
 
 impl Default for Chunk {
     fn default() -> Self {
-        Self([TileDefinitionHandle::EMPTY; CHUNK_WIDTH * CHUNK_HEIGHT])
+        Self {
+            handles: [TileDefinitionHandle::EMPTY; CHUNK_WIDTH * CHUNK_HEIGHT],
+            bounds: OptionTileRect::default(),
+            revision: next_chunk_revision(),
+            transforms: Vec::default(),
+        }
     }
 }
 
@@ -89,34 +181,122 @@ impl std::ops::Index<Vector2<i32>> for Chunk {
     fn index(&self, index: Vector2<i32>) -> &Self::Output {
         let x: usize = index.x.try_into().unwrap();
         let y: usize = index.y.try_into().unwrap();
-        &self.0[x + y * CHUNK_WIDTH]
-    }
-}
-
-impl std::ops::IndexMut<Vector2<i32>> for Chunk {
-    fn index_mut(&mut self, index: Vector2<i32>) -> &mut Self::Output {
-        let x: usize = index.x.try_into().unwrap();
-        let y: usize = index.y.try_into().unwrap();
-        &mut self.0[x + y * CHUNK_WIDTH]
+        &self.handles[x + y * CHUNK_WIDTH]
     }
 }
 
 impl Chunk {
-    fn iter(&self, offset: Vector2<i32>) -> ChunkIterator {
+    /// Encodes the non-empty cells of the chunk using [`encode_tiles`].
+    fn encode_handles(&self) -> Vec<u8> {
+        let mut tiles = Tiles::default();
+        for (index, handle) in self.handles.iter().enumerate() {
+            if !handle.is_empty() {
+                let position = Vector2::new(index % CHUNK_WIDTH, index / CHUNK_WIDTH);
+                tiles.insert(position.map(|c| c as i32), *handle);
+            }
+        }
+        encode_tiles(&tiles)
+    }
+    /// Replaces the handles of the chunk with the ones, that were encoded by
+    /// [`Self::encode_handles`].
+    fn decode_handles(&mut self, bytes: &[u8]) -> Result<(), VisitError> {
+        self.handles = [TileDefinitionHandle::EMPTY; CHUNK_WIDTH * CHUNK_HEIGHT];
+        for (position, handle) in decode_tiles(bytes)?.iter() {
+            let x = usize::try_from(position.x)
+                .ok()
+                .filter(|x| *x < CHUNK_WIDTH);
+            let y = usize::try_from(position.y)
+                .ok()
+                .filter(|y| *y < CHUNK_HEIGHT);
+            let (Some(x), Some(y)) = (x, y) else {
+                return Err(VisitError::User(format!(
+                    "Tile position ({}, {}) is out of chunk bounds",
+                    position.x, position.y
+                )));
+            };
+            self.handles[x + y * CHUNK_WIDTH] = *handle;
+        }
+        Ok(())
+    }
+    /// Iterates over the non-empty cells of the chunk that lie within the given bounds. The bounds
+    /// and the resulting positions are offset by the given origin of the chunk.
+    fn iter(&self, origin: Vector2<i32>, bounds: Option<TileRect>) -> ChunkIterator {
+        let mut cells: OptionTileRect = self.bounds.map(|rect| rect.translate(origin)).into();
+        if let Some(bounds) = bounds {
+            cells.clip(bounds);
+        }
         ChunkIterator {
-            position: Vector2::new(0, 0),
+            cells: cells.iter(),
+            origin,
             chunk: self,
-            offset,
         }
     }
     fn is_empty(&self) -> bool {
-        self.0.iter().all(|h| *h == TileDefinitionHandle::EMPTY)
+        self.bounds.is_none()
+    }
+    fn transformation(&self, position: Vector2<i32>) -> OrthoTransformation {
+        let x: usize = position.x.try_into().unwrap();
+        let y: usize = position.y.try_into().unwrap();
+        self.transforms
+            .get(x + y * CHUNK_WIDTH)
+            .copied()
+            .unwrap_or_default()
+    }
+    /// Writes the transformation of the given cell, if the cell is not empty.
+    fn set_transformation(&mut self, position: Vector2<i32>, transformation: OrthoTransformation) {
+        let x: usize = position.x.try_into().unwrap();
+        let y: usize = position.y.try_into().unwrap();
+        let index = x + y * CHUNK_WIDTH;
+        if self.handles[index].is_empty() || self.transformation(position) == transformation {
+            return;
+        }
+        if self.transforms.is_empty() {
+            self.transforms = vec![OrthoTransformation::identity(); CHUNK_WIDTH * CHUNK_HEIGHT];
+        }
+        self.transforms[index] = transformation;
+        self.revision = next_chunk_revision();
+    }
+    /// Writes the handle to the given cell and returns the previous handle of the cell.
+    /// The transformation of the cell is reset, if the handle is changed.
+    fn set(
+        &mut self,
+        position: Vector2<i32>,
+        handle: TileDefinitionHandle,
+    ) -> TileDefinitionHandle {
+        let x: usize = position.x.try_into().unwrap();
+        let y: usize = position.y.try_into().unwrap();
+        let previous = std::mem::replace(&mut self.handles[x + y * CHUNK_WIDTH], handle);
+        if previous == handle {
+            return previous;
+        }
+        if let Some(transformation) = self.transforms.get_mut(x + y * CHUNK_WIDTH) {
+            *transformation = OrthoTransformation::identity();
+        }
+        self.revision = next_chunk_revision();
+        if !handle.is_empty() {
+            self.bounds.push(position);
+        } else if !self.bounds.deflate(1, 1).contains(position) {
+            // The removed cell was on the border, so the bounds may shrink.
+            self.update_bounds();
+        }
+        previous
+    }
+    fn update_bounds(&mut self) {
+        self.bounds = OptionTileRect::default();
+        for (i, handle) in self.handles.iter().enumerate() {
+            if !handle.is_empty() {
+                self.bounds.push(Vector2::new(
+                    (i % CHUNK_WIDTH) as i32,
+                    (i / CHUNK_WIDTH) as i32,
+                ));
+            }
+        }
     }
 }
 
 struct ChunkIterator<'a> {
-    position: Vector2<i32>,
-    offset: Vector2<i32>,
+    cells: OptionRectIter,
+    origin: Vector2<i32>,
     chunk: &'a Chunk,
 }
 
@@ -125,52 +305,48 @@ impl Iterator for ChunkIterator<'_> {
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            if self.position.y >= CHUNK_HEIGHT as i32 {
-                return None;
-            }
-            let result_position = self.position;
-            let result = self.chunk[result_position];
-            if self.position.x < (CHUNK_WIDTH - 1) as i32 {
-                self.position.x += 1;
-            } else {
-                self.position.x = 0;
-                self.position.y += 1;
-            }
+            let position = self.cells.next()?;
+            let result = self.chunk[position - self.origin];
             if !result.is_empty() {
-                return Some((result_position + self.offset, result));
+                return Some((position, result));
             }
         }
     }
 }
 
 /// Iterator over the tiles of a [`TileMapData`] in the form of (position, handle).
-pub struct TileMapDataIterator<'a, P: 'a> {
-    predicate: P,
-    map_iter: hash_map::Iter<'a, Vector2<i32>, Chunk>,
+pub struct TileMapDataIterator<'a> {
+    /// Only the tiles within these bounds are visited, `None` means that every tile is visited.
+    bounds: Option<TileRect>,
+    map_iter: Option<hash_map::Iter<'a, Vector2<i32>, Chunk>>,
     chunk_iter: Option<ChunkIterator<'a>>,
 }
 
-impl<P: FnMut(Vector2<i32>) -> bool> Iterator for TileMapDataIterator<'_, P> {
+impl Iterator for TileMapDataIterator<'_> {
     type Item = (Vector2<i32>, TileDefinitionHandle);
     fn next(&mut self) -> Option<Self::Item> {
-        let chunk_iter = match &mut self.chunk_iter {
-            Some(iter) => iter,
-            None => self.next_chunk()?,
-        };
-        if let Some(result) = chunk_iter.next() {
-            Some(result)
-        } else {
-            self.next_chunk()?.next()
+        loop {
+            if let Some(result) = self.chunk_iter.as_mut().and_then(|iter| iter.next()) {
+                return Some(result);
+            }
+            self.next_chunk()?;
         }
     }
 }
 
-impl<'a, P: FnMut(Vector2<i32>) -> bool> TileMapDataIterator<'a, P> {
+impl<'a> TileMapDataIterator<'a> {
     fn next_chunk(&mut self) -> Option<&mut ChunkIterator<'a>> {
         loop {
-            let (pos, chunk) = self.map_iter.next()?;
-            if (self.predicate)(*pos) {
-                return Some(self.chunk_iter.insert(chunk.iter(*pos)));
+            let (pos, chunk) = self.map_iter.as_mut()?.next()?;
+            // Whole chunks are skipped by their bounds, without looking at their cells.
+            let is_visible = match self.bounds {
+                Some(bounds) => chunk
+                    .bounds
+                    .is_some_and(|rect| rect.translate(*pos).intersects(bounds)),
+                None => !chunk.is_empty(),
+            };
+            if is_visible {
+                return Some(self.chunk_iter.insert(chunk.iter(*pos, self.bounds)));
             }
         }
     }
@@ -222,8 +398,10 @@ impl TileSource for TileMapData {
 impl BoundedTileSource for TileMapData {
     fn bounding_rect(&self) -> OptionTileRect {
         let mut rect = OptionTileRect::default();
-        for (pos, _) in self.iter() {
-            rect.push(pos);
+        for (pos, chunk) in self.content.iter() {
+            if let Some(bounds) = *chunk.bounds {
+                rect.extend_to_contain(bounds.translate(*pos));
+            }
         }
         rect
     }
@@ -232,38 +410,68 @@ impl BoundedTileSource for TileMapData {
 impl TileMapData {
     /// Iterate over all pairs of (position, handle) in this data.
     pub fn iter(&self) -> impl Iterator<Item = (Vector2<i32>, TileDefinitionHandle)> + '_ {
-        let map_iter = self.content.iter();
         TileMapDataIterator {
-            predicate: |_| true,
-            map_iter,
+            bounds: None,
+            map_iter: Some(self.content.iter()),
             chunk_iter: None,
         }
     }
-    /// Iterate over all pairs of (position, handle) in this data.
+    /// Iterate over all pairs of (position, handle) within the given bounds. The tiles are
+    /// stored in chunks, and the chunks whose tiles are all outside of the bounds are skipped
+    /// entirely, so this is much faster than filtering the result of [`Self::iter`].
     pub fn bounded_iter(
         &self,
         bounds: OptionTileRect,
     ) -> impl Iterator<Item = (Vector2<i32>, TileDefinitionHandle)> + '_ {
-        let map_iter = self.content.iter();
         TileMapDataIterator {
-            predicate: move |pos: Vector2<i32>| {
-                bounds.intersects(TileRect::new(
-                    pos.x,
-                    pos.y,
-                    CHUNK_WIDTH as i32,
-                    CHUNK_HEIGHT as i32,
-                ))
-            },
-            map_iter,
+            bounds: *bounds,
+            // Empty bounds contain nothing, so there is no need to look at the chunks at all.
+            map_iter: bounds.is_some().then(|| self.content.iter()),
             chunk_iter: None,
         }
     }
+    /// Iterate over all non-empty chunks in the form of (chunk rect, chunk revision). See
+    /// [`Self::chunk_revision`] for more info about revisions.
+    pub fn chunks(&self) -> impl Iterator<Item = (TileRect, u64)> + '_ {
+        self.content
+            .iter()
+            .filter(|(_, chunk)| chunk.bounds.is_some())
+            .map(|(pos, chunk)| (chunk_rect(*pos), chunk.revision))
+    }
+    /// Iterate over the non-empty chunks whose tiles intersect the given bounds in the form of
+    /// (chunk rect, chunk revision). See [`Self::chunk_revision`] for more info about revisions.
+    pub(super) fn bounded_chunks(
+        &self,
+        bounds: OptionTileRect,
+    ) -> impl Iterator<Item = (TileRect, u64)> + '_ {
+        self.content.iter().filter_map(move |(pos, chunk)| {
+            let is_visible = chunk
+                .bounds
+                .is_some_and(|rect| bounds.intersects(rect.translate(*pos)));
+            is_visible.then_some((chunk_rect(*pos), chunk.revision))
+        })
+    }
+    /// A number that identifies the current content of the chunk that contains the given tile
+    /// position, or `None` if there is no such chunk. Every change of the tiles of the chunk gives
+    /// it a new revision that was never used before by any chunk, so the revision could be used
+    /// to cache some data per chunk and rebuild it only for the chunks that were edited.
+    pub fn chunk_revision(&self, position: Vector2<i32>) -> Option<u64> {
+        let (chunk, _) = tile_position_to_chunk_position(position);
+        self.content.get(&chunk).map(|chunk| chunk.revision)
+    }
     /// Apply the updates specified in the given `TileUpdate` and modify it so that it
     /// contains the tiles require to undo the change. Calling `swap_tiles` twice with the same
     /// `TileUpdate` object will do the changes and then undo them, leaving the tiles unchanged in the end.
     pub fn swap_tiles(&mut self, tiles: &mut TilesUpdate) {
-        for (p, h) in tiles.iter_mut() {
-            *h = self.replace(*p, *h);
+        let positions = tiles.keys().copied().collect::<Vec<_>>();
+        for p in positions {
+            let transformation = tiles.transformation(p);
+            let previous_transformation = self.transformation(p);
+            if let Some(h) = tiles.get_mut(&p) {
+                *h = self.replace(p, *h);
+            }
+            self.set_transformation(p, transformation);
+            tiles.set_transformation(p, previous_transformation);
         }
     }
     /// Get the handle for the tile at the given position, if one exists.
@@ -277,6 +485,25 @@ impl TileMapData {
             Some(handle)
         }
     }
+    /// Get the transformation of the tile at the given position. Empty cells and the tiles
+    /// without transformation have the identity transformation.
+    pub fn transformation(&self, position: Vector2<i32>) -> OrthoTransformation {
+        let (chunk, pos) = tile_position_to_chunk_position(position);
+        self.content
+            .get(&chunk)
+            .map(|chunk| chunk.transformation(pos))
+            .unwrap_or_default()
+    }
+    /// Set the transformation of the tile at the given position. The tile is flipped and rotated
+    /// when it is rendered, so there is no need for a transform page in the tile set for every
+    /// transformed version of the tile. Does nothing, if there is no tile at the position.
+    /// The transformation is reset when the tile is replaced or removed.
+    pub fn set_transformation(&mut self, position: Vector2<i32>, value: OrthoTransformation) {
+        let (chunk, pos) = tile_position_to_chunk_position(position);
+        if let Some(chunk) = self.content.get_mut(&chunk) {
+            chunk.set_transformation(pos, value);
+        }
+    }
     /// Replace the handle at the given position with the given handle and return the original
     /// handle at that position.
     pub fn replace(
@@ -286,17 +513,14 @@ impl TileMapData {
     ) -> Option<TileDefinitionHandle> {
         let (chunk, pos) = tile_position_to_chunk_position(position);
         if let Some(chunk) = self.content.get_mut(&chunk) {
-            let handle = &mut chunk[pos];
-            let result = *handle;
-            *handle = value.unwrap_or(TileDefinitionHandle::EMPTY);
+            let result = chunk.set(pos, value.unwrap_or(TileDefinitionHandle::EMPTY));
             if result.is_empty() {
                 None
             } else {
                 Some(result)
             }
         } else if let Some(value) = value {
-            let chunk = self.content.entry(chunk).or_default();
-            chunk[pos] = value;
+            self.content.entry(chunk).or_default().set(pos, value);
             None
         } else {
             None
@@ -305,15 +529,29 @@ impl TileMapData {
     /// Set a new handle for the tile at the given position.
     pub fn set(&mut self, position: Vector2<i32>, value: TileDefinitionHandle) {
         let (chunk, pos) = tile_position_to_chunk_position(position);
-        let chunk = self.content.entry(chunk).or_default();
-        chunk[pos] = value;
+        self.content.entry(chunk).or_default().set(pos, value);
     }
     /// Remove the tile at the given position.
     pub fn remove(&mut self, position: Vector2<i32>) {
         let (chunk, pos) = tile_position_to_chunk_position(position);
         if let Some(chunk) = self.content.get_mut(&chunk) {
-            chunk[pos] = TileDefinitionHandle::EMPTY;
+            chunk.set(pos, TileDefinitionHandle::EMPTY);
+        }
+    }
+    /// Positions of the left-bottom corners of all the chunks.
+    pub(super) fn chunk_positions(&self) -> impl Iterator<Item = Vector2<i32>> + '_ {
+        self.content.keys().copied()
+    }
+    /// Removes the chunk that contains the given tile position and returns its tiles.
+    pub(super) fn take_chunk(&mut self, position: Vector2<i32>) -> Tiles {
+        let (chunk_position, _) = tile_position_to_chunk_position(position);
+        let mut tiles = Tiles::default();
+        if let Some(chunk) = self.content.remove(&chunk_position) {
+            for (p, h) in chunk.iter(chunk_position, None) {
+                tiles.insert(p, h);
+            }
         }
+        tiles
     }
     /// Remove all empty chunks.
     pub fn shrink_to_fit(&mut self) {
@@ -419,4 +657,153 @@ mod tests {
         coords.sort_by(|(a, _), (b, _)| v_ord(a, b));
         assert_eq!(result, coords);
     }
+    #[test]
+    fn bounded_iter() {
+        let mut data = TileMapData::default();
+        data.set(v(1, 1), h(1, 2, 3, 4));
+        data.set(v(14, 14), h(1, 2, 3, 5));
+        data.set(v(-3, 2), h(1, 2, 3, 6));
+        data.set(v(40, 40), h(1, 2, 3, 7));
+        let mut result = data
+            .bounded_iter(TileRect::new(-3, 0, 6, 16).into())
+            .collect::<Vec<_>>();
+        result.sort_by(|(a, _), (b, _)| v_ord(a, b));
+        assert_eq!(
+            result,
+            vec![(v(-3, 2), h(1, 2, 3, 6)), (v(1, 1), h(1, 2, 3, 4))]
+        );
+        assert_eq!(data.bounded_iter(OptionTileRect::default()).count(), 0);
+        // The bounds intersect the chunk, but not its tiles.
+        let bounds = TileRect::new(33, 33, 5, 5).into();
+        assert_eq!(data.bounded_chunks(bounds).count(), 0);
+    }
+    #[test]
+    fn chunk_bounds() {
+        let mut data = TileMapData::default();
+        data.set(v(1, 1), h(1, 2, 3, 4));
+        data.set(v(5, 3), h(1, 2, 3, 5));
+        data.set(v(17, 2), h(1, 2, 3, 6));
+        assert_eq!(data.bounding_rect(), TileRect::new(1, 1, 17, 3).into());
+        data.remove(v(17, 2));
+        data.remove(v(5, 3));
+        assert_eq!(data.bounding_rect(), TileRect::new(1, 1, 1, 1).into());
+        data.remove(v(1, 1));
+        assert_eq!(data.bounding_rect(), OptionTileRect::default());
+        data.shrink_to_fit();
+        assert!(data.content.is_empty());
+    }
+    #[test]
+    fn chunk_revision() {
+        let mut data = TileMapData::default();
+        assert_eq!(data.chunk_revision(v(0, 0)), None);
+        data.set(v(0, 0), h(1, 2, 3, 4));
+        data.set(v(16, 0), h(1, 2, 3, 4));
+        let first = data.chunk_revision(v(0, 0)).unwrap();
+        let second = data.chunk_revision(v(16, 0)).unwrap();
+        assert_ne!(first, second);
+        data.set(v(3, 3), h(1, 2, 3, 5));
+        assert_ne!(data.chunk_revision(v(0, 0)), Some(first));
+        // Other chunks are not affected by the change.
+        assert_eq!(data.chunk_revision(v(16, 0)), Some(second));
+        // Writing the same handle is not a change.
+        let current = data.chunk_revision(v(0, 0));
+        data.set(v(3, 3), h(1, 2, 3, 5));
+        assert_eq!(data.chunk_revision(v(0, 0)), current);
+    }
+    #[test]
+    fn transformation() {
+        let flipped = OrthoTransformation::new(true, 1);
+        let mut data = TileMapData::default();
+        data.set(v(1, 1), h(1, 2, 3, 4));
+        data.set(v(2, 1), h(1, 2, 3, 5));
+        let revision = data.chunk_revision(v(1, 1));
+        data.set_transformation(v(1, 1), flipped);
+        assert_eq!(data.transformation(v(1, 1)), flipped);
+        assert_ne!(data.chunk_revision(v(1, 1)), revision);
+        // Empty cells can't be transformed.
+        data.set_transformation(v(3, 1), flipped);
+        assert!(data.transformation(v(3, 1)).is_identity());
+
+        let mut visitor = Visitor::new();
+        data.visit("Data", &mut visitor).unwrap();
+        let bytes = visitor.save_binary_to_vec().unwrap();
+        let mut visitor = Visitor::load_from_memory(&bytes).unwrap();
+        let mut loaded = TileMapData::default();
+        loaded.visit("Data", &mut visitor).unwrap();
+        assert_eq!(loaded.transformation(v(1, 1)), flipped);
+        assert!(loaded.transformation(v(2, 1)).is_identity());
+
+        // Replacing the tile resets the transformation.
+        data.set(v(1, 1), h(1, 2, 3, 6));
+        assert!(data.transformation(v(1, 1)).is_identity());
+    }
+    #[test]
+    fn swap_tiles_transformation() {
+        let flipped = OrthoTransformation::new(true, 0);
+        let rotated = OrthoTransformation::new(false, 1);
+        let mut data = TileMapData::default();
+        data.set(v(1, 1), h(1, 2, 3, 4));
+        data.set_transformation(v(1, 1), flipped);
+
+        // Erasing the flipped tile and undoing it brings the flip back.
+        let mut update = TilesUpdate::default();
+        update.insert(v(1, 1), None);
+        data.swap_tiles(&mut update);
+        assert_eq!(data.get(v(1, 1)), None);
+        assert_eq!(update.get(&v(1, 1)), Some(&Some(h(1, 2, 3, 4))));
+        assert_eq!(update.transformation(v(1, 1)), flipped);
+        data.swap_tiles(&mut update);
+        assert_eq!(data.get(v(1, 1)), Some(h(1, 2, 3, 4)));
+        assert_eq!(data.transformation(v(1, 1)), flipped);
+
+        // Drawing a rotated tile over the flipped tile and undoing it.
+        let mut update = TilesUpdate::default();
+        update.insert_transformed(v(1, 1), Some((rotated, h(1, 2, 3, 5))));
+        data.swap_tiles(&mut update);
+        assert_eq!(data.get(v(1, 1)), Some(h(1, 2, 3, 5)));
+        assert_eq!(data.transformation(v(1, 1)), rotated);
+        data.swap_tiles(&mut update);
+        assert_eq!(data.get(v(1, 1)), Some(h(1, 2, 3, 4)));
+        assert_eq!(data.transformation(v(1, 1)), flipped);
+
+        // Drawing the same tile without transformation changes only the transformation.
+        let mut update = TilesUpdate::default();
+        update.insert(v(1, 1), Some(h(1, 2, 3, 4)));
+        data.swap_tiles(&mut update);
+        assert!(data.transformation(v(1, 1)).is_identity());
+        data.swap_tiles(&mut update);
+        assert_eq!(data.transformation(v(1, 1)), flipped);
+    }
+    #[test]
+    fn chunk_visit() {
+        let mut chunk = Chunk::default();
+        chunk.handles[0] = h(1, 2, 3, 4);
+        chunk.handles[1] = h(1, 2, 3, 4);
+        chunk.handles[CHUNK_WIDTH * CHUNK_HEIGHT - 1] = h(-1, 0, 5, 6);
+        chunk.update_bounds();
+
+        let mut visitor = Visitor::new();
+        chunk.visit("Chunk", &mut visitor).unwrap();
+        let bytes = visitor.save_binary_to_vec().unwrap();
+        let mut visitor = Visitor::load_from_memory(&bytes).unwrap();
+        let mut loaded = Chunk::default();
+        loaded.visit("Chunk", &mut visitor).unwrap();
+        assert_eq!(loaded.handles, chunk.handles);
+        assert_eq!(loaded.bounds, chunk.bounds);
+
+        // The chunks were stored as a dense array of handles before the compact encoding was
+        // added.
+        let mut visitor = Visitor::new();
+        BinaryBlob {
+            vec: &mut chunk.handles.to_vec(),
+        }
+        .visit("Chunk", &mut visitor)
+        .unwrap();
+        let old_bytes = visitor.save_binary_to_vec().unwrap();
+        let mut visitor = Visitor::load_from_memory(&old_bytes).unwrap();
+        let mut loaded = Chunk::default();
+        loaded.visit("Chunk", &mut visitor).unwrap();
+        assert_eq!(loaded.handles, chunk.handles);
+        assert!(bytes.len() < old_bytes.len());
+    }
 }
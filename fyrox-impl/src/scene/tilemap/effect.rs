@@ -166,6 +166,159 @@ impl TileMapEffect for TileUpdateEffect {
     }
 }
 
+/// Defines how [`TileHighlightEffect`] marks the cells.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TileHighlightMode {
+    /// Draws a quad of the color over every cell. The color should be semi-transparent to keep
+    /// the tiles visible.
+    Fill,
+    /// Draws a border of the color and the given thickness (in cells) inside every cell.
+    Outline {
+        /// The thickness of the border as a fraction of the size of a cell.
+        thickness: f32,
+    },
+    /// Multiplies the color of the tiles in the cells by the color, so it only works if the
+    /// effect is in [`TileMap::before_effects`]. Empty cells stay empty.
+    Tint,
+}
+
+/// Marks the given cells with the given color, for example to show the movement range of a unit
+/// in a tactics game or the selected cells. Unlike [`TileSelectionEffect`], the effect does not
+/// need a material, so it could be created and modified by a game at any moment.
+#[derive(Debug)]
+pub struct TileHighlightEffect {
+    /// True if the cells are to be marked. If false, then this effect does nothing.
+    pub active: bool,
+    /// The positions of the cells to mark.
+    pub positions: FxHashSet<Vector2<i32>>,
+    /// The color of the marks.
+    pub color: Color,
+    /// The way the cells are marked.
+    pub mode: TileHighlightMode,
+}
+
+impl TileHighlightEffect {
+    /// Creates an effect that fills the given cells with the given color.
+    pub fn new<I>(positions: I, color: Color) -> Self
+    where
+        I: IntoIterator<Item = Vector2<i32>>,
+    {
+        Self {
+            active: true,
+            positions: positions.into_iter().collect(),
+            color,
+            mode: TileHighlightMode::Fill,
+        }
+    }
+
+    /// Sets the way the cells are marked.
+    pub fn with_mode(mut self, mode: TileHighlightMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Replaces the marked cells with the given cells.
+    pub fn set_positions<I>(&mut self, positions: I)
+    where
+        I: IntoIterator<Item = Vector2<i32>>,
+    {
+        self.positions.clear();
+        self.positions.extend(positions);
+    }
+}
+
+impl TileMapEffect for TileHighlightEffect {
+    fn render_special_tiles(&self, context: &mut TileMapRenderContext) {
+        if !self.active {
+            return;
+        }
+        let data = TileRenderData {
+            material_bounds: None,
+            color: self.color,
+        };
+        for &position in self.positions.iter() {
+            match self.mode {
+                TileHighlightMode::Fill => context.push_tile(position, &data),
+                TileHighlightMode::Outline { thickness } => {
+                    push_color_outline(position, self.color, thickness, context)
+                }
+                TileHighlightMode::Tint => context.set_tile_tint(position, Some(self.color)),
+            }
+        }
+    }
+}
+
+/// Scrolls the given tiles within the given rectangle over time, which is useful for conveyor
+/// belts, waterfalls and scrolling backgrounds. The tiles move by whole cells and wrap around
+/// at the edges of the rectangle, so the rectangle is always filled with a repeating pattern
+/// of the tiles. For smooth motion within a cell, combine it with animated tiles.
+///
+/// The effect hides the tiles of the tile map in the rectangle, so it should be added to
+/// [`TileMap::before_effects`] in order to replace them.
+#[derive(Debug)]
+pub struct TileScrollEffect {
+    /// True if the tiles are to be drawn. If false, then this effect does nothing.
+    pub active: bool,
+    /// The area of the tile map that is filled with the scrolled tiles.
+    pub rect: TileRect,
+    /// The tiles to scroll, relative to the left-bottom corner of the rectangle. The tiles
+    /// outside of the rectangle are never rendered.
+    pub tiles: FxHashMap<Vector2<i32>, TileDefinitionHandle>,
+    /// The scrolling speed in cells per second.
+    pub velocity: Vector2<f32>,
+}
+
+impl TileScrollEffect {
+    /// Creates the effect that scrolls the tiles of the given rectangle of the given tiles.
+    pub fn from_tiles(tiles: &TileMapData, rect: TileRect, velocity: Vector2<f32>) -> Self {
+        Self {
+            active: true,
+            rect,
+            tiles: tiles
+                .bounded_iter(rect.into())
+                .map(|(position, handle)| (position - rect.position, handle))
+                .collect(),
+            velocity,
+        }
+    }
+
+    /// The position of the tile, relative to the left-bottom corner of the rectangle, that is
+    /// rendered at the given position of the tile map at the given time.
+    pub fn source_position(&self, position: Vector2<i32>, time: f32) -> Vector2<i32> {
+        let offset = (self.velocity * time).map(|v| v.floor() as i32);
+        let local = position - self.rect.position - offset;
+        Vector2::new(
+            local.x.rem_euclid(self.rect.w().max(1)),
+            local.y.rem_euclid(self.rect.h().max(1)),
+        )
+    }
+}
+
+impl TileMapEffect for TileScrollEffect {
+    fn render_special_tiles(&self, context: &mut TileMapRenderContext) {
+        if !self.active {
+            return;
+        }
+        let time = context.context.elapsed_time;
+        // Without visible bounds, the whole tile map is rendered.
+        let visible = match *context.visible_bounds() {
+            Some(bounds) => self.rect.clip_by(bounds),
+            None => self.rect.into(),
+        };
+        for position in visible.iter() {
+            if !context.is_tile_visible(position) {
+                continue;
+            }
+            context.set_tile_visible(position, false);
+            let source = self.source_position(position, time);
+            if let Some(&handle) = self.tiles.get(&source) {
+                let handle = context.get_animated_version(handle);
+                context.draw_tile(position, handle);
+            }
+        }
+    }
+}
+
 fn make_highlight_vertex(transform: &Matrix4<f32>, position: Vector2<f32>) -> StaticVertex {
     StaticVertex {
         position: transform
@@ -184,7 +337,7 @@ fn push_highlight(
     ctx: &mut TileMapRenderContext,
 ) {
     let transform = ctx.transform();
-    let position = position.cast::<f32>();
+    let position = ctx.cell_origin(position);
     let t = thickness;
     let vertices = [
         (0.0, 1.0),
@@ -233,13 +386,65 @@ fn push_highlight(
     );
 }
 
+fn push_color_outline(
+    position: Vector2<i32>,
+    color: Color,
+    thickness: f32,
+    ctx: &mut TileMapRenderContext,
+) {
+    let transform = ctx.transform();
+    let sort_index = ctx.sorting_index(position);
+    let position = ctx.cell_origin(position);
+    let t = thickness;
+    let vertices = [
+        (0.0, 1.0),
+        (1.0, 1.0),
+        (1.0, 0.0),
+        (0.0, 0.0),
+        (t, 1.0 - t),
+        (1.0 - t, 1.0 - t),
+        (1.0 - t, t),
+        (t, t),
+    ]
+    .map(|(x, y)| Vector2::new(x, y))
+    .map(|p| make_rect_vertex(transform, position + p, color));
+
+    let triangles = [
+        [0, 4, 5],
+        [0, 1, 5],
+        [1, 5, 6],
+        [1, 2, 6],
+        [2, 6, 7],
+        [2, 3, 7],
+        [3, 7, 4],
+        [3, 0, 4],
+    ]
+    .map(TriangleDefinition);
+
+    ctx.context.storage.push_triangles(
+        RectangleVertex::layout(),
+        &STANDARD_2D.resource,
+        RenderPath::Forward,
+        sort_index,
+        ctx.tile_map_handle(),
+        &mut move |mut vertex_buffer, mut triangle_buffer| {
+            let start_vertex_index = vertex_buffer.vertex_count();
+
+            vertex_buffer.push_vertices(&vertices).unwrap();
+
+            triangle_buffer
+                .push_triangles_iter_with_offset(start_vertex_index, triangles.into_iter());
+        },
+    );
+}
+
 fn push_cursor(
     position: Vector2<i32>,
     material: &MaterialResource,
     ctx: &mut TileMapRenderContext,
 ) {
     let transform = ctx.transform();
-    let position = position.cast::<f32>();
+    let position = ctx.cell_origin(position);
     let vertices = [(0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)]
         .map(|(x, y)| Vector2::new(x, y))
         .map(|p| make_highlight_vertex(transform, position + p));
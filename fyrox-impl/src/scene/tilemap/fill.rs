@@ -0,0 +1,252 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Filling of the tiles of a tile map at runtime, without the editor. See [`TileMap::flood_fill`]
+//! docs for more info.
+
+use crate::core::algebra::Vector2;
+
+use super::*;
+
+impl TileMap {
+    /// Applies the given update to the tiles and replaces it with the update that reverts the
+    /// change, so calling this method twice with the same update leaves the tiles unchanged.
+    /// If the tile map has no loaded tiles, the update is cleared, since there is nothing to revert.
+    pub fn swap_tiles(&mut self, update: &mut TilesUpdate) {
+        let Some(tiles) = self.tiles.as_ref() else {
+            update.clear();
+            return;
+        };
+        let mut tiles = tiles.data_ref();
+        match tiles.as_loaded_mut() {
+            Some(tiles) => tiles.swap_tiles(update),
+            None => update.clear(),
+        }
+    }
+
+    /// Fills the area of the same tiles (or of empty cells) that contains the given position with
+    /// the tiles from the given source. The area is limited by the bounding rectangle of the tiles,
+    /// extended to contain the given position. Returns the update that reverts the change, when
+    /// passed to [`Self::swap_tiles`].
+    ///
+    /// The transformation of the source is applied using the tile set of the tile map, just like
+    /// the editor does it.
+    pub fn flood_fill<S: TileSource>(&mut self, position: Vector2<i32>, source: &S) -> TilesUpdate {
+        let mut update = TransTilesUpdate::default();
+        if let Some(tiles) = self.tiles.as_ref() {
+            let tiles = tiles.data_ref();
+            if let Some(tiles) = tiles.as_loaded_ref() {
+                update.flood_fill(tiles, position, source);
+            }
+        }
+        self.apply_trans_update(&update)
+    }
+
+    /// Fills the given rectangle with the tiles from the given source. The source is sampled at
+    /// the positions relative to the left-bottom corner of the rectangle. Returns the update that
+    /// reverts the change, when passed to [`Self::swap_tiles`]. A rectangle with zero or negative
+    /// size fills nothing.
+    pub fn fill_rect<S: TileSource>(&mut self, rect: TileRect, source: &S) -> TilesUpdate {
+        if rect.size.x <= 0 || rect.size.y <= 0 {
+            return TilesUpdate::default();
+        }
+        let mut update = TransTilesUpdate::default();
+        update.rect_fill_from(
+            rect.position,
+            rect.position + rect.size - Vector2::new(1, 1),
+            source,
+        );
+        self.apply_trans_update(&update)
+    }
+
+    /// Fills the ellipse that is inscribed in the given rectangle with the tiles from the given
+    /// source. A cell is filled when its center is within the ellipse, and the source is sampled
+    /// just like [`Self::fill_rect`] does it. Returns the update that reverts the change, when
+    /// passed to [`Self::swap_tiles`]. A rectangle with zero or negative size fills nothing.
+    pub fn fill_ellipse<S: TileSource>(&mut self, rect: TileRect, source: &S) -> TilesUpdate {
+        if rect.size.x <= 0 || rect.size.y <= 0 {
+            return TilesUpdate::default();
+        }
+        let mut update = TransTilesUpdate::default();
+        update.ellipse_fill_from(
+            rect.position,
+            rect.position + rect.size - Vector2::new(1, 1),
+            source,
+        );
+        self.apply_trans_update(&update)
+    }
+
+    /// Draws the tiles of the given stamp, so that the cell (0,0) of the stamp is placed at the
+    /// given position. This places a whole multi-cell structure at once, such as a stamp made by
+    /// [`TileMapBrush::build_stamp`]. Returns the update that reverts the change, when passed to
+    /// [`Self::swap_tiles`].
+    pub fn draw_stamp(&mut self, position: Vector2<i32>, stamp: &Stamp) -> TilesUpdate {
+        let mut update = TransTilesUpdate::default();
+        update.draw_tiles(position, stamp);
+        self.apply_trans_update(&update)
+    }
+
+    /// Replaces the handles of the given update with their transformed versions from the tile set
+    /// of the tile map. Transformations without a transformed version of the tile are kept for the
+    /// cells, see [`TransTilesUpdate::build_tiles_update`].
+    pub(super) fn build_tiles_update(&self, update: &TransTilesUpdate) -> TilesUpdate {
+        match self.tile_set() {
+            Some(tile_set) => update.build_tiles_update(&TileSetRef::new(tile_set).as_loaded()),
+            None => {
+                let mut tiles = TilesUpdate::default();
+                for (position, value) in update.iter() {
+                    tiles.insert_transformed(*position, *value);
+                }
+                tiles
+            }
+        }
+    }
+
+    fn apply_trans_update(&mut self, update: &TransTilesUpdate) -> TilesUpdate {
+        let mut tiles = self.build_tiles_update(update);
+        self.swap_tiles(&mut tiles);
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scene::tilemap::{
+        test_fixture::{self, FLOOR, WALL},
+        tileset::TileData,
+    };
+
+    fn v(x: i32, y: i32) -> Vector2<i32> {
+        Vector2::new(x, y)
+    }
+
+    fn make_tile_map() -> TileMap {
+        // A room with the walls around the 3x3 area of empty cells.
+        let room = TileRect::new(1, 1, 3, 3);
+        let walls = TileRect::new(0, 0, 5, 5)
+            .iter()
+            .filter(|position| !room.contains(*position))
+            .map(|position| (position, WALL));
+        test_fixture::make_tile_map(test_fixture::make_tile_set(|_| TileData::default()), walls)
+    }
+
+    fn get(tile_map: &TileMap, position: Vector2<i32>) -> Option<TileDefinitionHandle> {
+        tile_map.tiles().unwrap().data_ref().get(position)
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let mut tile_map = make_tile_map();
+        let source = SingleTileSource(OrthoTransformation::default(), FLOOR);
+        let mut undo = tile_map.flood_fill(v(2, 2), &source);
+        assert_eq!(undo.len(), 9);
+        assert_eq!(get(&tile_map, v(1, 3)), Some(FLOOR));
+        assert_eq!(get(&tile_map, v(0, 0)), Some(WALL));
+        tile_map.swap_tiles(&mut undo);
+        assert_eq!(get(&tile_map, v(1, 3)), None);
+        tile_map.swap_tiles(&mut undo);
+        assert_eq!(get(&tile_map, v(1, 3)), Some(FLOOR));
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let mut tile_map = make_tile_map();
+        let source = SingleTileSource(OrthoTransformation::default(), FLOOR);
+        let mut undo = tile_map.fill_rect(TileRect::new(4, 4, 2, 1), &source);
+        assert_eq!(undo.get(&v(4, 4)), Some(&Some(WALL)));
+        assert_eq!(undo.get(&v(5, 4)), Some(&None));
+        assert_eq!(get(&tile_map, v(5, 4)), Some(FLOOR));
+        tile_map.swap_tiles(&mut undo);
+        assert_eq!(get(&tile_map, v(4, 4)), Some(WALL));
+        assert_eq!(get(&tile_map, v(5, 4)), None);
+    }
+
+    #[test]
+    fn test_fill_empty_rect() {
+        let mut tile_map = make_tile_map();
+        let source = SingleTileSource(OrthoTransformation::default(), FLOOR);
+        for rect in [
+            TileRect::new(1, 1, 0, 0),
+            TileRect::new(1, 1, 2, 0),
+            TileRect::new(2, 2, -2, -2),
+        ] {
+            assert!(tile_map.fill_rect(rect, &source).is_empty());
+            assert!(tile_map.fill_ellipse(rect, &source).is_empty());
+        }
+        for position in TileRect::new(0, 0, 3, 3).iter() {
+            assert_ne!(get(&tile_map, position), Some(FLOOR));
+        }
+    }
+
+    #[test]
+    fn test_fill_ellipse() {
+        let mut tile_map = make_tile_map();
+        let source = SingleTileSource(OrthoTransformation::default(), FLOOR);
+        let mut undo = tile_map.fill_ellipse(TileRect::new(10, 10, 5, 5), &source);
+        assert_eq!(undo.len(), 21);
+        assert_eq!(get(&tile_map, v(12, 12)), Some(FLOOR));
+        assert_eq!(get(&tile_map, v(11, 10)), Some(FLOOR));
+        assert_eq!(get(&tile_map, v(10, 12)), Some(FLOOR));
+        assert_eq!(get(&tile_map, v(10, 10)), None);
+        assert_eq!(get(&tile_map, v(14, 14)), None);
+        tile_map.swap_tiles(&mut undo);
+        assert_eq!(get(&tile_map, v(12, 12)), None);
+
+        // A single row is filled entirely.
+        let undo = tile_map.fill_ellipse(TileRect::new(10, 20, 4, 1), &source);
+        assert_eq!(undo.len(), 4);
+    }
+
+    #[test]
+    fn test_draw_stamp() {
+        let mut tiles = Tiles::default();
+        tiles.insert(v(10, 10), WALL);
+        tiles.insert(v(11, 10), FLOOR);
+        tiles.insert(v(12, 10), WALL);
+        let mut brush = TileMapBrush::default();
+        brush
+            .pages
+            .insert(v(0, 0), TileMapBrushPage { icon: WALL, tiles });
+        brush.stamps.push(TileBrushStamp {
+            name: "Door".into(),
+            page: v(0, 0),
+            position: v(10, 10),
+            size: v(3, 1),
+            anchor: v(1, 0),
+        });
+        assert!(brush.stamp_at(v(0, 0), v(12, 10)).is_some());
+        assert!(brush.stamp_at(v(0, 0), v(13, 10)).is_none());
+        let door = brush.find_stamp("Door").unwrap();
+        let stamp = brush.build_stamp(door, OrthoTransformation::new(false, 1));
+        assert_eq!(stamp.get_at(v(0, 0)), Some(FLOOR));
+        assert_eq!(stamp.get_at(v(0, 1)), Some(WALL));
+        assert_eq!(stamp.get_at(v(0, -1)), Some(WALL));
+
+        let mut tile_map = make_tile_map();
+        let mut undo = tile_map.draw_stamp(v(2, 2), &stamp);
+        assert_eq!(undo.len(), 3);
+        assert_eq!(get(&tile_map, v(2, 2)), Some(FLOOR));
+        assert_eq!(get(&tile_map, v(2, 3)), Some(WALL));
+        assert_eq!(get(&tile_map, v(2, 1)), Some(WALL));
+        tile_map.swap_tiles(&mut undo);
+        assert_eq!(get(&tile_map, v(2, 2)), None);
+    }
+}
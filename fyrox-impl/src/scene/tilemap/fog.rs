@@ -0,0 +1,278 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Fog of war for tile maps. See [`FogOfWarEffect`] docs for more info.
+
+use crate::core::{algebra::Vector2, color::Color, visitor::prelude::*};
+use fxhash::FxHashSet;
+
+use super::*;
+
+/// Visibility state of a single cell of a tile map covered by fog of war.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub enum FogCellState {
+    /// The cell has never been seen by any view source.
+    #[default]
+    Unseen,
+    /// The cell was seen before, but it is not visible by any view source right now.
+    Explored,
+    /// The cell is currently visible by at least one view source.
+    Visible,
+}
+
+/// A source of vision for [`FogOfWarEffect`], for example a player character or a watchtower.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub struct FogViewSource {
+    /// The cell position of the source.
+    pub position: Vector2<i32>,
+    /// The radius of vision in cells.
+    pub radius: u32,
+}
+
+impl FogViewSource {
+    /// Creates a new view source at the given cell with the given radius.
+    pub fn new(position: Vector2<i32>, radius: u32) -> Self {
+        Self { position, radius }
+    }
+}
+
+/// Fog of war effect hides the cells of a tile map that were never seen by any view source and
+/// darkens the cells that were explored before, but are not visible right now. The effect must
+/// be added to [`TileMap::before_effects`], because it modifies the way the tiles of the tile
+/// map are rendered.
+///
+/// The visibility is updated by [`FogOfWarEffect::update`] (or
+/// [`FogOfWarEffect::update_with_occluders`]), usually once per frame from a script. The set of
+/// explored cells is serialized when the effect is visited, so it can be stored in saved games
+/// along with the rest of the game state, while currently visible cells are recalculated on the
+/// next update.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector2, parking_lot::Mutex},
+/// #     scene::tilemap::{FogOfWarEffect, FogViewSource, TileMap},
+/// # };
+/// # use std::sync::Arc;
+/// fn add_fog(tile_map: &mut TileMap) -> Arc<Mutex<FogOfWarEffect>> {
+///     let fog = Arc::new(Mutex::new(FogOfWarEffect::default()));
+///     tile_map.before_effects.push(fog.clone());
+///     fog
+/// }
+///
+/// fn update_fog(fog: &Mutex<FogOfWarEffect>, player_cell: Vector2<i32>) {
+///     fog.lock().update(&[FogViewSource::new(player_cell, 6)]);
+/// }
+/// ```
+#[derive(Clone, Debug, Visit)]
+pub struct FogOfWarEffect {
+    /// True if the fog is rendered. If false, then this effect does nothing, but the visibility
+    /// is still tracked.
+    pub active: bool,
+    /// The color that is multiplied with the color of the explored tiles that are not visible
+    /// right now.
+    pub explored_tint: Color,
+    /// The color that is multiplied with the color of the tiles that were never seen. `None`
+    /// means that such tiles are not rendered at all.
+    pub unseen_tint: Option<Color>,
+    explored: FxHashSet<Vector2<i32>>,
+    #[visit(skip)]
+    visible: FxHashSet<Vector2<i32>>,
+}
+
+impl Default for FogOfWarEffect {
+    fn default() -> Self {
+        Self {
+            active: true,
+            explored_tint: Color::opaque(90, 90, 110),
+            unseen_tint: None,
+            explored: Default::default(),
+            visible: Default::default(),
+        }
+    }
+}
+
+impl FogOfWarEffect {
+    /// Returns the state of the cell at the given position.
+    pub fn cell_state(&self, position: Vector2<i32>) -> FogCellState {
+        if self.visible.contains(&position) {
+            FogCellState::Visible
+        } else if self.explored.contains(&position) {
+            FogCellState::Explored
+        } else {
+            FogCellState::Unseen
+        }
+    }
+
+    /// The set of cells that were seen at least once.
+    pub fn explored(&self) -> &FxHashSet<Vector2<i32>> {
+        &self.explored
+    }
+
+    /// The set of cells that are visible right now.
+    pub fn visible(&self) -> &FxHashSet<Vector2<i32>> {
+        &self.visible
+    }
+
+    /// Marks the given cells as explored without making them visible. Could be used to reveal
+    /// parts of a map, for example when a player picks up a map of a dungeon.
+    pub fn reveal<I: IntoIterator<Item = Vector2<i32>>>(&mut self, positions: I) {
+        self.explored.extend(positions);
+    }
+
+    /// Forgets every explored and visible cell, so the whole tile map becomes unseen.
+    pub fn reset(&mut self) {
+        self.explored.clear();
+        self.visible.clear();
+    }
+
+    /// Recalculates visible cells using the given view sources. Every cell within the radius of
+    /// a source becomes visible and explored, cells that were visible before, but are out of reach
+    /// of every source now, stay explored.
+    pub fn update(&mut self, sources: &[FogViewSource]) {
+        self.update_with_occluders(sources, |_| false)
+    }
+
+    /// Recalculates visible cells using the given view sources, just as [`FogOfWarEffect::update`]
+    /// does, but also checks line of sight. `is_opaque` should return `true` for the cells that
+    /// block vision (walls, for instance). Opaque cells themselves could be seen, but everything
+    /// behind them is hidden.
+    pub fn update_with_occluders<F>(&mut self, sources: &[FogViewSource], mut is_opaque: F)
+    where
+        F: FnMut(Vector2<i32>) -> bool,
+    {
+        self.visible.clear();
+        for source in sources {
+            let radius = source.radius as i32;
+            let radius_sqr = radius * radius;
+            for y in -radius..=radius {
+                for x in -radius..=radius {
+                    if x * x + y * y > radius_sqr {
+                        continue;
+                    }
+                    let position = source.position + Vector2::new(x, y);
+                    if self.visible.contains(&position)
+                        || !is_line_of_sight(source.position, position, &mut is_opaque)
+                    {
+                        continue;
+                    }
+                    self.visible.insert(position);
+                }
+            }
+        }
+        self.explored.extend(self.visible.iter().copied());
+    }
+}
+
+/// Walks the cells between `from` and `to` (excluding both ends) using Bresenham's algorithm
+/// and checks that none of them is opaque.
+fn is_line_of_sight<F>(from: Vector2<i32>, to: Vector2<i32>, is_opaque: &mut F) -> bool
+where
+    F: FnMut(Vector2<i32>) -> bool,
+{
+    let dx = (to.x - from.x).abs();
+    let dy = -(to.y - from.y).abs();
+    let sx = (to.x - from.x).signum();
+    let sy = (to.y - from.y).signum();
+    let mut error = dx + dy;
+    let mut current = from;
+    loop {
+        let doubled_error = 2 * error;
+        if doubled_error >= dy {
+            error += dy;
+            current.x += sx;
+        }
+        if doubled_error <= dx {
+            error += dx;
+            current.y += sy;
+        }
+        if current == to {
+            return true;
+        }
+        if is_opaque(current) {
+            return false;
+        }
+    }
+}
+
+impl TileMapEffect for FogOfWarEffect {
+    fn render_special_tiles(&self, context: &mut TileMapRenderContext) {
+        if !self.active {
+            return;
+        }
+        for position in context.visible_bounds().iter() {
+            match self.cell_state(position) {
+                FogCellState::Visible => (),
+                FogCellState::Explored => context.set_tile_tint(position, Some(self.explored_tint)),
+                FogCellState::Unseen => {
+                    if let Some(tint) = self.unseen_tint {
+                        context.set_tile_tint(position, Some(tint));
+                    } else {
+                        context.set_tile_visible(position, false);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fog_of_war_update() {
+        let mut fog = FogOfWarEffect::default();
+        fog.update(&[FogViewSource::new(Vector2::new(0, 0), 2)]);
+        assert_eq!(fog.cell_state(Vector2::new(0, 0)), FogCellState::Visible);
+        assert_eq!(fog.cell_state(Vector2::new(2, 0)), FogCellState::Visible);
+        assert_eq!(fog.cell_state(Vector2::new(2, 2)), FogCellState::Unseen);
+
+        fog.update(&[FogViewSource::new(Vector2::new(10, 0), 1)]);
+        assert_eq!(fog.cell_state(Vector2::new(0, 0)), FogCellState::Explored);
+        assert_eq!(fog.cell_state(Vector2::new(10, 1)), FogCellState::Visible);
+
+        // A wall at x = 11 hides everything behind it.
+        fog.update_with_occluders(&[FogViewSource::new(Vector2::new(10, 0), 3)], |p| p.x == 11);
+        assert_eq!(fog.cell_state(Vector2::new(11, 0)), FogCellState::Visible);
+        assert_eq!(fog.cell_state(Vector2::new(12, 0)), FogCellState::Unseen);
+        assert_eq!(fog.cell_state(Vector2::new(9, 0)), FogCellState::Visible);
+    }
+
+    #[test]
+    fn test_fog_of_war_visit() {
+        let mut fog = FogOfWarEffect::default();
+        fog.update(&[FogViewSource::new(Vector2::new(3, 4), 1)]);
+
+        let mut visitor = Visitor::new();
+        fog.visit("Fog", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut loaded = FogOfWarEffect::default();
+        let mut visitor = Visitor::load_from_memory(&data).unwrap();
+        loaded.visit("Fog", &mut visitor).unwrap();
+
+        assert_eq!(loaded.explored(), fog.explored());
+        assert!(loaded.visible().is_empty());
+        assert_eq!(
+            loaded.cell_state(Vector2::new(3, 4)),
+            FogCellState::Explored
+        );
+    }
+}
@@ -0,0 +1,451 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Procedural generation of tile maps. See [`TileMapGenerator`] docs for more info.
+
+use crate::core::algebra::Vector2;
+use fxhash::FxHashMap;
+use std::fmt::Debug;
+
+use super::*;
+
+/// A source of procedurally generated tiles. A generator must be deterministic: the same seed
+/// always produces the same tiles, and the tile of a cell does not depend on the rect that
+/// was requested, so a world could be generated chunk by chunk and the chunks fit each other
+/// seamlessly. This makes every generator usable as a chunk provider for [`TileMapStreaming`],
+/// see [`GeneratedTileChunkProvider`].
+///
+/// ```rust
+/// # use fyrox_impl::scene::tilemap::{
+/// #     CaveGenerator, TileDefinitionHandle, TileMap, TileRect,
+/// # };
+/// fn make_cave(tile_map: &mut TileMap, wall: TileDefinitionHandle) {
+///     let generator = CaveGenerator::new(wall);
+///     tile_map.fill_generated(TileRect::new(0, 0, 64, 64), &generator, 42);
+/// }
+/// ```
+pub trait TileMapGenerator: Debug + Send {
+    /// Returns the tiles of the given rect, generated with the given seed. Tiles outside of the
+    /// rect are ignored.
+    fn generate_chunk(&self, rect: TileRect, seed: u64) -> Tiles;
+}
+
+/// A hash of the seed and the cell position, that is used as a random number which is the same
+/// for every chunk that contains the cell.
+fn hash_cell(seed: u64, position: Vector2<i32>) -> u64 {
+    fn mix(mut x: u64) -> u64 {
+        x = x.wrapping_add(0x9E3779B97F4A7C15);
+        x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+        x ^ (x >> 31)
+    }
+    let position = ((position.x as u32 as u64) << 32) | position.y as u32 as u64;
+    mix(mix(seed) ^ position)
+}
+
+/// A random number in `[0, 1)` for the given seed and cell position.
+fn cell_random(seed: u64, position: Vector2<i32>) -> f32 {
+    (hash_cell(seed, position) >> 40) as f32 / (1u64 << 24) as f32
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Two-dimensional Perlin noise at the given point, in the range `[0, 1]`. Integer points are
+/// the corners of the noise lattice, so the noise changes smoothly between them.
+pub fn perlin_noise(seed: u64, point: Vector2<f32>) -> f32 {
+    let floor = point.map(f32::floor);
+    let corner = floor.map(|v| v as i32);
+    let fraction = point - floor;
+    let dot = |dx: i32, dy: i32| {
+        let angle = cell_random(seed, corner + Vector2::new(dx, dy)) * std::f32::consts::TAU;
+        let offset = fraction - Vector2::new(dx as f32, dy as f32);
+        angle.cos() * offset.x + angle.sin() * offset.y
+    };
+    let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let (u, v) = (fade(fraction.x), fade(fraction.y));
+    let bottom = lerp(dot(0, 0), dot(1, 0), u);
+    let top = lerp(dot(0, 1), dot(1, 1), u);
+    // The noise of unit gradients is in the range [-sqrt(0.5), sqrt(0.5)].
+    (lerp(bottom, top, v) * std::f32::consts::FRAC_1_SQRT_2 + 0.5).clamp(0.0, 1.0)
+}
+
+/// A layer of [`NoiseTerrainGenerator`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoiseTerrainLayer {
+    /// The cells with the noise value below this height (and above the height of the previous
+    /// layer) get the tile of the layer.
+    pub height: f32,
+    /// The tile of the layer. `None` leaves the cells of the layer empty.
+    pub tile: Option<TileDefinitionHandle>,
+}
+
+/// Generates terrain from fractal Perlin noise. Every cell gets a noise value in the range
+/// `[0, 1]`, and the value is mapped to a tile using layers sorted by height, for example water
+/// below `0.3`, sand below `0.4`, grass below `0.7` and rocks above it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoiseTerrainGenerator {
+    /// The size of the features of the terrain, in cells.
+    pub scale: f32,
+    /// The amount of noise layers. Every next octave has twice the frequency and half the
+    /// amplitude of the previous one, which adds smaller details to the terrain.
+    pub octaves: u32,
+    /// The layers of the terrain, sorted by height.
+    pub layers: Vec<NoiseTerrainLayer>,
+}
+
+impl Default for NoiseTerrainGenerator {
+    fn default() -> Self {
+        Self {
+            scale: 16.0,
+            octaves: 4,
+            layers: Vec::new(),
+        }
+    }
+}
+
+impl NoiseTerrainGenerator {
+    /// Creates a new generator with the given layers.
+    pub fn new(layers: Vec<NoiseTerrainLayer>) -> Self {
+        Self {
+            layers,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the size of the features of the terrain, in cells.
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets the amount of noise layers.
+    pub fn with_octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    /// The noise value of the cell at the given position, in the range `[0, 1]`.
+    pub fn height(&self, seed: u64, position: Vector2<i32>) -> f32 {
+        let point = (position.cast::<f32>() + Vector2::repeat(0.5)) / self.scale.max(f32::EPSILON);
+        let mut sum = 0.0;
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        for octave in 0..self.octaves.max(1) {
+            sum += amplitude * perlin_noise(seed.wrapping_add(octave as u64), point * frequency);
+            total += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        sum / total
+    }
+}
+
+impl TileMapGenerator for NoiseTerrainGenerator {
+    fn generate_chunk(&self, rect: TileRect, seed: u64) -> Tiles {
+        let mut tiles = Tiles::default();
+        for position in rect.iter() {
+            let height = self.height(seed, position);
+            let tile = self
+                .layers
+                .iter()
+                .find(|layer| height < layer.height)
+                .and_then(|layer| layer.tile);
+            if let Some(tile) = tile {
+                tiles.insert(position, tile);
+            }
+        }
+        tiles
+    }
+}
+
+/// Generates caves using cellular automata. The cells are randomly filled with walls, and then
+/// the walls are smoothed several times: a wall stays a wall if it has enough wall neighbors,
+/// and an empty cell becomes a wall if it has even more of them. The result looks like a network
+/// of natural caves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaveGenerator {
+    /// The tile of the walls.
+    pub wall: TileDefinitionHandle,
+    /// The tile of the floor. `None` leaves the floor cells empty.
+    pub floor: Option<TileDefinitionHandle>,
+    /// The chance of a cell to be a wall before smoothing, in the range `[0, 1]`.
+    pub fill_probability: f32,
+    /// The amount of smoothing steps.
+    pub iterations: u32,
+    /// The amount of wall neighbors (out of eight), that turns an empty cell into a wall.
+    pub birth_limit: usize,
+    /// The amount of wall neighbors (out of eight), that is needed for a wall to stay a wall.
+    pub survival_limit: usize,
+}
+
+impl CaveGenerator {
+    /// Creates a new generator with the given wall tile and common settings.
+    pub fn new(wall: TileDefinitionHandle) -> Self {
+        Self {
+            wall,
+            floor: None,
+            fill_probability: 0.45,
+            iterations: 4,
+            birth_limit: 5,
+            survival_limit: 4,
+        }
+    }
+
+    /// Sets the tile of the floor.
+    pub fn with_floor(mut self, floor: TileDefinitionHandle) -> Self {
+        self.floor = Some(floor);
+        self
+    }
+
+    /// Sets the chance of a cell to be a wall before smoothing.
+    pub fn with_fill_probability(mut self, fill_probability: f32) -> Self {
+        self.fill_probability = fill_probability;
+        self
+    }
+
+    /// Sets the amount of smoothing steps.
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+}
+
+impl TileMapGenerator for CaveGenerator {
+    fn generate_chunk(&self, rect: TileRect, seed: u64) -> Tiles {
+        // A smoothing step spreads the influence of a cell by one cell, so the automaton runs
+        // on a larger area to make the cells of the rect independent of the rect itself.
+        let margin = self.iterations as i32 + 1;
+        let area = rect.inflate(margin, margin);
+        let (width, height) = (area.size.x, area.size.y);
+        let index = |x: i32, y: i32| (y * width + x) as usize;
+        let mut walls = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let position = area.position + Vector2::new(x, y);
+                walls.push(cell_random(seed, position) < self.fill_probability);
+            }
+        }
+        for _ in 0..self.iterations {
+            let mut next = walls.clone();
+            for y in 0..height {
+                for x in 0..width {
+                    let mut count = 0;
+                    for dy in -1..=1 {
+                        for dx in -1..=1 {
+                            let (nx, ny) = (x + dx, y + dy);
+                            if (dx, dy) == (0, 0) {
+                                continue;
+                            }
+                            // Cells outside of the area are walls.
+                            if nx < 0
+                                || ny < 0
+                                || nx >= width
+                                || ny >= height
+                                || walls[index(nx, ny)]
+                            {
+                                count += 1;
+                            }
+                        }
+                    }
+                    next[index(x, y)] = if walls[index(x, y)] {
+                        count >= self.survival_limit
+                    } else {
+                        count >= self.birth_limit
+                    };
+                }
+            }
+            walls = next;
+        }
+        let mut tiles = Tiles::default();
+        for position in rect.iter() {
+            let local = position - area.position;
+            let tile = if walls[index(local.x, local.y)] {
+                Some(self.wall)
+            } else {
+                self.floor
+            };
+            if let Some(tile) = tile {
+                tiles.insert(position, tile);
+            }
+        }
+        tiles
+    }
+}
+
+/// A chunk provider for [`TileMapStreaming`] that generates the chunks using the given
+/// generator, so the world is endless. The changes of the chunks could be kept in memory when
+/// the chunks are unloaded, otherwise the chunks are generated again when they are loaded.
+#[derive(Debug)]
+pub struct GeneratedTileChunkProvider<G> {
+    generator: G,
+    seed: u64,
+    keep_changes: bool,
+    changed: FxHashMap<Vector2<i32>, Tiles>,
+}
+
+impl<G: TileMapGenerator> GeneratedTileChunkProvider<G> {
+    /// Creates a new provider that uses the given generator and seed. The changes of the chunks
+    /// are kept by default.
+    pub fn new(generator: G, seed: u64) -> Self {
+        Self {
+            generator,
+            seed,
+            keep_changes: true,
+            changed: Default::default(),
+        }
+    }
+
+    /// Defines whether the changes of the chunks are kept when the chunks are unloaded.
+    pub fn with_keep_changes(mut self, keep_changes: bool) -> Self {
+        self.keep_changes = keep_changes;
+        self
+    }
+
+    /// The generator of the chunks.
+    pub fn generator(&self) -> &G {
+        &self.generator
+    }
+
+    /// The seed of the generator.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Forgets the changes of all chunks, so the chunks are generated again when they are loaded.
+    pub fn clear_changes(&mut self) {
+        self.changed.clear();
+    }
+}
+
+impl<G: TileMapGenerator> TileChunkProvider for GeneratedTileChunkProvider<G> {
+    fn load_chunk(&mut self, rect: TileRect) -> Option<Tiles> {
+        let tiles = self
+            .changed
+            .remove(&rect.position)
+            .unwrap_or_else(|| self.generator.generate_chunk(rect, self.seed));
+        (!tiles.is_empty()).then_some(tiles)
+    }
+
+    fn unload_chunk(&mut self, rect: TileRect, tiles: Tiles) {
+        if self.keep_changes && tiles != self.generator.generate_chunk(rect, self.seed) {
+            self.changed.insert(rect.position, tiles);
+        }
+    }
+}
+
+impl TileMap {
+    /// Fills the given rectangle of the tile map with tiles from the given generator. The cells
+    /// that are left empty by the generator are cleared. Returns the update that reverts the
+    /// change, when passed to [`Self::swap_tiles`]. See [`TileMapGenerator`] docs for more info.
+    pub fn fill_generated<G: TileMapGenerator + ?Sized>(
+        &mut self,
+        rect: TileRect,
+        generator: &G,
+        seed: u64,
+    ) -> TilesUpdate {
+        let generated = generator.generate_chunk(rect, seed);
+        let mut update = TilesUpdate::default();
+        for position in rect.iter() {
+            update.insert(position, generated.get(&position).copied());
+        }
+        self.swap_tiles(&mut update);
+        update
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tile(x: i16) -> TileDefinitionHandle {
+        TileDefinitionHandle::new(0, 0, x, 0)
+    }
+
+    /// Generates the rect as a whole and as four quarters, which must give the same tiles.
+    fn assert_chunk_aware<G: TileMapGenerator>(generator: &G) {
+        let whole = generator.generate_chunk(TileRect::new(-8, -8, 16, 16), 3);
+        let mut parts = Tiles::default();
+        for (x, y) in [(-8, -8), (0, -8), (-8, 0), (0, 0)] {
+            let chunk = generator.generate_chunk(TileRect::new(x, y, 8, 8), 3);
+            parts.extend(chunk.iter().map(|(p, h)| (*p, *h)));
+        }
+        assert!(!whole.is_empty());
+        assert_eq!(whole, parts);
+    }
+
+    #[test]
+    fn test_perlin_noise() {
+        for i in 0..100 {
+            let point = Vector2::new(i as f32 * 0.37, i as f32 * -0.61);
+            let value = perlin_noise(5, point);
+            assert!((0.0..=1.0).contains(&value));
+            assert_eq!(value, perlin_noise(5, point));
+        }
+        // The noise is zero at the corners of the lattice.
+        assert_eq!(perlin_noise(5, Vector2::new(2.0, -3.0)), 0.5);
+    }
+
+    #[test]
+    fn test_noise_terrain() {
+        let generator = NoiseTerrainGenerator::new(vec![
+            NoiseTerrainLayer {
+                height: 0.5,
+                tile: Some(tile(0)),
+            },
+            NoiseTerrainLayer {
+                height: 1.1,
+                tile: Some(tile(1)),
+            },
+        ])
+        .with_scale(4.0);
+        assert_chunk_aware(&generator);
+        let tiles = generator.generate_chunk(TileRect::new(0, 0, 16, 16), 3);
+        assert_eq!(tiles.len(), 256);
+    }
+
+    #[test]
+    fn test_caves() {
+        let generator = CaveGenerator::new(tile(0)).with_floor(tile(1));
+        assert_chunk_aware(&generator);
+        let tiles = generator.generate_chunk(TileRect::new(0, 0, 16, 16), 3);
+        assert!(tiles.values().any(|h| *h == tile(0)));
+        assert!(tiles.values().any(|h| *h == tile(1)));
+    }
+
+    #[test]
+    fn test_provider() {
+        let rect = TileRect::new(0, 0, 8, 8);
+        let mut provider = GeneratedTileChunkProvider::new(CaveGenerator::new(tile(0)), 1);
+        let mut tiles = provider.load_chunk(rect).unwrap();
+        tiles.insert(Vector2::new(100, 100), tile(2));
+        provider.unload_chunk(rect, tiles.clone());
+        assert_eq!(provider.load_chunk(rect).as_ref(), Some(&tiles));
+        // The changes are taken by the loaded chunk, so the next chunk is generated again.
+        let tiles = provider.load_chunk(rect).unwrap();
+        assert!(tiles.get(&Vector2::new(100, 100)).is_none());
+
+        let mut provider = provider.with_keep_changes(false);
+        provider.unload_chunk(rect, Tiles::default());
+        assert_eq!(provider.load_chunk(rect), Some(tiles));
+    }
+}
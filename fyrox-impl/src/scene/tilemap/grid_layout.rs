@@ -0,0 +1,250 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Layouts of tile map cells. See [`TileGridLayout`] docs for more info.
+
+use crate::core::{algebra::Vector2, reflect::prelude::*, visitor::prelude::*};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+const SQUARE_NEIGHBORS: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const POINTY_EVEN_NEIGHBORS: [(i32, i32); 6] =
+    [(1, 0), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1)];
+const POINTY_ODD_NEIGHBORS: [(i32, i32); 6] = [(1, 0), (1, 1), (0, 1), (-1, 0), (0, -1), (1, -1)];
+const FLAT_EVEN_NEIGHBORS: [(i32, i32); 6] = [(0, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0)];
+const FLAT_ODD_NEIGHBORS: [(i32, i32); 6] = [(0, 1), (1, 1), (1, 0), (0, -1), (-1, 0), (-1, 1)];
+
+/// Defines how the cells of a tile map are arranged. Every tile is still rendered as a 1x1 quad
+/// (before [`super::TileMap::tile_scale`] is applied), hexagonal layouts only shift the quads of
+/// the cells, so hexagonal tiles must be drawn inside of a rectangle that touches all six corners
+/// of the hexagon.
+///
+/// Hexagonal layouts use "offset" coordinates: every cell is still addressed by a pair of
+/// integers, but every odd row (or column) is shifted by half of a cell. This keeps rectangular
+/// regions of cells rectangular on screen, so selections, brushes and chunks work as usual.
+#[derive(
+    Copy,
+    Clone,
+    Default,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum TileGridLayout {
+    /// Cells are squares, that are arranged in rows and columns. Every cell has four neighbors.
+    #[default]
+    Square,
+    /// Cells are hexagons with a vertex at the top. The rows overlap by a quarter of a cell and
+    /// every odd row is shifted right by half of a cell. Every cell has six neighbors.
+    HexPointyTop,
+    /// Cells are hexagons with a flat edge at the top. The columns overlap by a quarter of a cell
+    /// and every odd column is shifted up by half of a cell. Every cell has six neighbors.
+    HexFlatTop,
+}
+
+/// Left-bottom corner of the cell in the pointy-top layout.
+fn pointy_cell_origin(cell: Vector2<i32>) -> Vector2<f32> {
+    Vector2::new(
+        cell.x as f32 + 0.5 * (cell.y & 1) as f32,
+        0.75 * cell.y as f32,
+    )
+}
+
+/// Axial coordinates of the given cell in the pointy-top layout.
+fn pointy_to_axial(cell: Vector2<i32>) -> Vector2<i32> {
+    Vector2::new(cell.x - (cell.y - (cell.y & 1)) / 2, cell.y)
+}
+
+/// Offset coordinates of the given axial coordinates in the pointy-top layout.
+fn pointy_from_axial(axial: Vector2<i32>) -> Vector2<i32> {
+    Vector2::new(axial.x + (axial.y - (axial.y & 1)) / 2, axial.y)
+}
+
+/// Finds the cell of the pointy-top layout that contains the given point.
+fn pointy_local_to_cell(position: Vector2<f32>) -> Vector2<i32> {
+    // Move the center of the (0, 0) cell to the origin and squash the point horizontally to make
+    // the hexagons regular, with the distance between the center and a vertex of 0.5.
+    let x = (position.x - 0.5) * 3.0f32.sqrt() * 0.5;
+    let y = position.y - 0.5;
+    let q = (3.0f32.sqrt() / 3.0 * x - y / 3.0) * 2.0;
+    let r = (2.0 / 3.0 * y) * 2.0;
+    pointy_from_axial(round_axial(q, r))
+}
+
+/// Rounds fractional axial coordinates to the nearest hexagon.
+fn round_axial(q: f32, r: f32) -> Vector2<i32> {
+    let s = -q - r;
+    let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+    let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+    if dq > dr && dq > ds {
+        rq = -rr - rs;
+    } else if dr > ds {
+        rr = -rq - rs;
+    }
+    Vector2::new(rq as i32, rr as i32)
+}
+
+impl TileGridLayout {
+    /// True for the hexagonal layouts.
+    pub fn is_hex(self) -> bool {
+        self != TileGridLayout::Square
+    }
+
+    /// The left-bottom corner of the quad of the given cell in the local coordinates of a tile map.
+    pub fn cell_origin(self, cell: Vector2<i32>) -> Vector2<f32> {
+        match self {
+            TileGridLayout::Square => cell.cast::<f32>(),
+            TileGridLayout::HexPointyTop => pointy_cell_origin(cell),
+            TileGridLayout::HexFlatTop => pointy_cell_origin(cell.yx()).yx(),
+        }
+    }
+
+    /// The center of the given cell in the local coordinates of a tile map.
+    pub fn cell_center(self, cell: Vector2<i32>) -> Vector2<f32> {
+        self.cell_origin(cell) + Vector2::repeat(0.5)
+    }
+
+    /// Finds the cell that contains the given point in the local coordinates of a tile map.
+    pub fn local_to_cell(self, position: Vector2<f32>) -> Vector2<i32> {
+        match self {
+            TileGridLayout::Square => {
+                Vector2::new(position.x.floor() as i32, position.y.floor() as i32)
+            }
+            TileGridLayout::HexPointyTop => pointy_local_to_cell(position),
+            TileGridLayout::HexFlatTop => pointy_local_to_cell(position.yx()).yx(),
+        }
+    }
+
+    /// The offsets from the given cell to all of its neighbors.
+    fn neighbor_offsets(self, cell: Vector2<i32>) -> &'static [(i32, i32)] {
+        match self {
+            TileGridLayout::Square => &SQUARE_NEIGHBORS,
+            TileGridLayout::HexPointyTop if cell.y & 1 == 0 => &POINTY_EVEN_NEIGHBORS,
+            TileGridLayout::HexPointyTop => &POINTY_ODD_NEIGHBORS,
+            TileGridLayout::HexFlatTop if cell.x & 1 == 0 => &FLAT_EVEN_NEIGHBORS,
+            TileGridLayout::HexFlatTop => &FLAT_ODD_NEIGHBORS,
+        }
+    }
+
+    /// Iterates over the cells that share an edge with the given cell, counterclockwise. Square
+    /// cells have four neighbors, hexagonal cells have six.
+    pub fn neighbors(self, cell: Vector2<i32>) -> impl Iterator<Item = Vector2<i32>> {
+        self.neighbor_offsets(cell)
+            .iter()
+            .map(move |(x, y)| cell + Vector2::new(*x, *y))
+    }
+
+    /// True if the given cells share an edge.
+    pub fn are_neighbors(self, a: Vector2<i32>, b: Vector2<i32>) -> bool {
+        self.neighbors(a).any(|n| n == b)
+    }
+
+    /// The number of steps between neighboring cells that it takes to get from one cell to
+    /// another. For square cells this is the Manhattan distance.
+    pub fn distance(self, a: Vector2<i32>, b: Vector2<i32>) -> u32 {
+        let (a, b) = match self {
+            TileGridLayout::Square => return (a - b).abs().sum() as u32,
+            TileGridLayout::HexPointyTop => (pointy_to_axial(a), pointy_to_axial(b)),
+            TileGridLayout::HexFlatTop => (pointy_to_axial(a.yx()), pointy_to_axial(b.yx())),
+        };
+        let d = a - b;
+        ((d.x.abs() + d.y.abs() + (d.x + d.y).abs()) / 2) as u32
+    }
+
+    /// The bounds (minimum and maximum corners) in the local coordinates of a tile map of the
+    /// quads of the cells of the given rect.
+    pub fn rect_bounds(
+        self,
+        position: Vector2<i32>,
+        size: Vector2<i32>,
+    ) -> (Vector2<f32>, Vector2<f32>) {
+        let min = position.cast::<f32>();
+        let size = size.cast::<f32>();
+        // Every odd row (or column) is shifted by half of a cell, while the cells overlap by a
+        // quarter of a cell in the other direction.
+        let pointy = |min: Vector2<f32>, size: Vector2<f32>| {
+            (
+                Vector2::new(min.x, 0.75 * min.y),
+                Vector2::new(min.x + size.x + 0.5, 0.75 * (min.y + size.y) + 0.25),
+            )
+        };
+        match self {
+            TileGridLayout::Square => (min, min + size),
+            TileGridLayout::HexPointyTop => pointy(min, size),
+            TileGridLayout::HexFlatTop => {
+                let (min, max) = pointy(min.yx(), size.yx());
+                (min.yx(), max.yx())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hex_local_to_cell() {
+        for layout in [
+            TileGridLayout::Square,
+            TileGridLayout::HexPointyTop,
+            TileGridLayout::HexFlatTop,
+        ] {
+            for y in -3..3 {
+                for x in -3..3 {
+                    let cell = Vector2::new(x, y);
+                    assert_eq!(layout.local_to_cell(layout.cell_center(cell)), cell);
+                }
+            }
+        }
+        // The left-bottom corner of a pointy-top cell belongs to the cell below.
+        let layout = TileGridLayout::HexPointyTop;
+        assert_eq!(
+            layout.local_to_cell(Vector2::new(0.05, 0.05)),
+            Vector2::new(-1, -1)
+        );
+    }
+
+    #[test]
+    fn test_hex_neighbors() {
+        for layout in [TileGridLayout::HexPointyTop, TileGridLayout::HexFlatTop] {
+            for cell in [Vector2::new(0, 0), Vector2::new(3, -1), Vector2::new(-2, 5)] {
+                let center = layout.cell_center(cell);
+                assert_eq!(layout.neighbors(cell).count(), 6);
+                for n in layout.neighbors(cell) {
+                    // Neighbors are at the same distance on the squashed regular grid.
+                    assert_eq!(layout.distance(cell, n), 1);
+                    assert!(layout.are_neighbors(n, cell));
+                    assert!((layout.cell_center(n) - center).norm() < 1.01);
+                }
+            }
+            assert_eq!(layout.distance(Vector2::new(0, 0), Vector2::new(3, 3)), 5);
+        }
+        assert_eq!(
+            TileGridLayout::Square.distance(Vector2::new(0, 0), Vector2::new(3, -2)),
+            5
+        );
+    }
+}
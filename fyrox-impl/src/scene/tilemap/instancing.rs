@@ -0,0 +1,425 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Instanced rendering of tile maps. The tiles of every chunk are written into a vertex buffer
+//! with one record per tile, which is drawn with a single instanced draw call per material. See
+//! [`TileMap::set_instancing`] docs for more info.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Vector2, Vector4},
+        color::Color,
+        math::TriangleDefinition,
+    },
+    material::{
+        shader::{ShaderResource, ShaderResourceExtension},
+        Material, MaterialResource,
+    },
+    renderer::{bundle::SurfaceInstanceData, framework::ElementRange},
+    scene::mesh::{
+        buffer::{TriangleBuffer, VertexBuffer},
+        surface::{SurfaceData, SurfaceResource},
+    },
+};
+use fxhash::{FxHashMap, FxHashSet, FxHasher};
+use std::hash::{Hash, Hasher};
+
+use super::*;
+
+/// Per-instance data of a tile, that is rendered using instancing.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Pod, Zeroable)]
+#[repr(C)] // OpenGL expects this structure packed as in C
+pub struct TileInstance {
+    /// The left-bottom corner of the cell in local coordinates of the tile map.
+    pub position: Vector2<f32>,
+    /// Texture coordinates (in pixels) of the right-top and the left-top corners of the tile.
+    pub top_tex_coords: Vector4<f32>,
+    /// Texture coordinates (in pixels) of the left-bottom and the right-bottom corners of the
+    /// tile.
+    pub bottom_tex_coords: Vector4<f32>,
+    /// Diffuse color.
+    pub color: Color,
+}
+
+impl TileInstance {
+    /// Creates the instance data of a tile in the cell with the given origin. Tiles without
+    /// bounds use zero texture coordinates, which is the same as the plain color.
+    pub fn new(origin: Vector2<f32>, bounds: Option<&TileBounds>, color: Color) -> Self {
+        let corners = |a: Vector2<u32>, b: Vector2<u32>| {
+            Vector4::new(a.x as f32, a.y as f32, b.x as f32, b.y as f32)
+        };
+        Self {
+            position: origin,
+            top_tex_coords: bounds
+                .map(|b| corners(b.right_top_corner, b.left_top_corner))
+                .unwrap_or_default(),
+            bottom_tex_coords: bounds
+                .map(|b| corners(b.left_bottom_corner, b.right_bottom_corner))
+                .unwrap_or_default(),
+            color,
+        }
+    }
+}
+
+impl VertexTrait for TileInstance {
+    fn layout() -> &'static [VertexAttributeDescriptor] {
+        &[
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Position,
+                data_type: VertexAttributeDataType::F32,
+                size: 2,
+                divisor: 1,
+                shader_location: 0,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::TexCoord0,
+                data_type: VertexAttributeDataType::F32,
+                size: 4,
+                divisor: 1,
+                shader_location: 1,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::TexCoord1,
+                data_type: VertexAttributeDataType::F32,
+                size: 4,
+                divisor: 1,
+                shader_location: 2,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Color,
+                data_type: VertexAttributeDataType::U8,
+                size: 4,
+                divisor: 1,
+                shader_location: 3,
+                normalized: true,
+            },
+        ]
+    }
+}
+
+/// Tiles of a chunk that share the same material.
+#[derive(Debug)]
+struct TileInstanceBatch {
+    /// The material of the tiles in the tile set.
+    material: MaterialResource,
+    /// Instance data of the tiles, one vertex per tile.
+    surface: SurfaceResource,
+}
+
+/// Instance data of a chunk of a tile map.
+#[derive(Debug)]
+struct InstancedChunk {
+    /// Hash of the tile set and the revision of the chunk, which is used to detect changes.
+    hash: u64,
+    /// Batches of the tiles of the chunk. `None` if the chunk contains animated tiles, which must
+    /// be rendered tile by tile.
+    batches: Option<Vec<TileInstanceBatch>>,
+}
+
+/// A copy of a tile material, that uses the instanced tile shader.
+#[derive(Debug)]
+struct InstancedMaterial {
+    /// Content hash of the source material, which is used to detect changes.
+    content_hash: u64,
+    material: MaterialResource,
+}
+
+/// Instance data of the chunks of a tile map, that is reused until their tiles are changed.
+#[derive(Default, Debug)]
+pub(super) struct TileMapInstanceCache {
+    chunks: FxHashMap<Vector2<i32>, InstancedChunk>,
+    materials: FxHashMap<u64, InstancedMaterial>,
+}
+
+/// Collects the instance data of the tiles of the chunk with the given rect, grouped by their
+/// materials. Returns `None` if the chunk contains animated tiles.
+fn make_tile_instances(
+    tile_set: &OptionTileSet,
+    tiles: &TileMapData,
+    rect: TileRect,
+    grid_layout: TileGridLayout,
+) -> Option<Vec<(MaterialResource, Vec<TileInstance>)>> {
+    let mut batches: Vec<(MaterialResource, Vec<TileInstance>)> = Vec::new();
+    for (position, handle) in tiles.bounded_iter(rect.into()) {
+        if tile_set.get_animated_version(0.0, handle).is_some() {
+            return None;
+        }
+        let Some(data) = tile_set.get_tile_render_data(handle.into()) else {
+            continue;
+        };
+        let data = data.transformed(tiles.transformation(position));
+        let origin = grid_layout.cell_origin(position);
+        let (material, instance) = match data.material_bounds {
+            Some(TileMaterialBounds { material, bounds }) => (
+                material,
+                TileInstance::new(origin, Some(&bounds), data.color),
+            ),
+            None => (
+                DEFAULT_TILE_MATERIAL.clone(),
+                TileInstance::new(origin, None, data.color),
+            ),
+        };
+        if let Some((_, instances)) = batches.iter_mut().find(|(m, _)| m.key() == material.key()) {
+            instances.push(instance);
+        } else {
+            batches.push((material, vec![instance]));
+        }
+    }
+    Some(batches)
+}
+
+fn make_instance_surface(instances: Vec<TileInstance>) -> SurfaceResource {
+    let vertex_buffer = VertexBuffer::new(instances.len(), instances).unwrap();
+    // Every tile is drawn as the same two triangles, the shader uses the indices to find the
+    // corners of the quad.
+    let triangles = TriangleBuffer::new(vec![
+        TriangleDefinition([0, 1, 2]),
+        TriangleDefinition([2, 3, 0]),
+    ]);
+    SurfaceResource::new_ok(
+        ResourceKind::Embedded,
+        SurfaceData::new(vertex_buffer, triangles),
+    )
+}
+
+impl TileMapInstanceCache {
+    /// Removes every cached chunk and material.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.materials.clear();
+    }
+
+    /// Returns the batches of the chunk with the given rect, the instance data is collected again
+    /// if the tiles or the tile set were changed since the last call. Edits of the tile set are
+    /// detected by its change count, see [`ChangeFlag::count`].
+    fn batches(
+        &mut self,
+        rect: TileRect,
+        revision: u64,
+        tile_set_resource: Option<&TileSetResource>,
+        tile_set: &OptionTileSet,
+        tiles: &TileMapData,
+        grid_layout: TileGridLayout,
+    ) -> Option<&[TileInstanceBatch]> {
+        let mut hasher = FxHasher::default();
+        tile_set_resource.hash(&mut hasher);
+        tile_set.change_count().hash(&mut hasher);
+        revision.hash(&mut hasher);
+        grid_layout.hash(&mut hasher);
+        let hash = hasher.finish();
+        let is_outdated = self
+            .chunks
+            .get(&rect.position)
+            .map_or(true, |chunk| chunk.hash != hash);
+        if is_outdated {
+            let batches = make_tile_instances(tile_set, tiles, rect, grid_layout).map(|batches| {
+                batches
+                    .into_iter()
+                    .map(|(material, instances)| TileInstanceBatch {
+                        material,
+                        surface: make_instance_surface(instances),
+                    })
+                    .collect()
+            });
+            self.chunks
+                .insert(rect.position, InstancedChunk { hash, batches });
+        }
+        self.chunks.get(&rect.position)?.batches.as_deref()
+    }
+
+    /// Returns a copy of the given tile material, that uses the instanced tile shader. The copy is
+    /// updated every time the source material is changed. Returns `None` if the material is not
+    /// loaded yet or if it uses a custom shader, that has no instanced counterpart.
+    fn instanced_material(&mut self, source: &MaterialResource) -> Option<MaterialResource> {
+        let mut state = source.state();
+        let source_material = state.data()?;
+        if source_material.shader().key() != ShaderResource::standard_tile().key() {
+            return None;
+        }
+        let content_hash = source_material.content_hash();
+        if let Some(instanced) = self.materials.get(&source.key()) {
+            if instanced.content_hash == content_hash {
+                return Some(instanced.material.clone());
+            }
+        }
+        let mut material = Material::standard_tile_instanced();
+        for (name, binding) in source_material.bindings() {
+            material.bind(name.clone(), binding.clone());
+        }
+        let material = MaterialResource::new_ok(ResourceKind::Embedded, material);
+        self.materials.insert(
+            source.key(),
+            InstancedMaterial {
+                content_hash,
+                material: material.clone(),
+            },
+        );
+        Some(material)
+    }
+}
+
+impl TileMap {
+    /// Renders the visible chunks, that are not in the given set of already rendered chunks,
+    /// using instancing and adds their positions to the set, so their tiles could be skipped.
+    /// Chunks that contain hidden, tinted or animated tiles and chunks with custom tile shaders
+    /// are rendered tile by tile. Y-sorted tile maps are always rendered tile by tile, because
+    /// every row of tiles is sorted separately.
+    pub(super) fn render_instanced_chunks(
+        &self,
+        ctx: &mut TileMapRenderContext,
+        tiles: &TileMapData,
+        rendered_chunks: &mut FxHashSet<Vector2<i32>>,
+    ) {
+        let bounds = ctx.visible_bounds();
+        if !*self.instancing || bounds.is_none() || ctx.sort_mode != SortMode2D::Depth {
+            return;
+        }
+        let mut cache = self.instance_cache.lock();
+        let mut materials = Vec::new();
+        for (rect, revision) in tiles.bounded_chunks(bounds) {
+            if rendered_chunks.contains(&rect.position) {
+                continue;
+            }
+            if ctx.has_effects_in(rect) {
+                continue;
+            }
+            let Some(batches) = cache.batches(
+                rect,
+                revision,
+                self.tile_set.as_ref(),
+                &ctx.tile_set,
+                tiles,
+                ctx.grid_layout,
+            ) else {
+                continue;
+            };
+            let batches = batches
+                .iter()
+                .map(|batch| (batch.material.clone(), batch.surface.clone()))
+                .collect::<Vec<_>>();
+            materials.clear();
+            for (material, _) in batches.iter() {
+                match cache.instanced_material(material) {
+                    Some(material) => materials.push(material),
+                    None => break,
+                }
+            }
+            if materials.len() != batches.len() {
+                continue;
+            }
+            let sort_index = ctx.sorting_index(rect.position);
+            for ((_, surface), material) in batches.iter().zip(materials.iter()) {
+                ctx.context.storage.push(
+                    surface,
+                    material,
+                    RenderPath::Forward,
+                    sort_index,
+                    SurfaceInstanceData {
+                        world_transform: ctx.transform,
+                        bone_matrices: Default::default(),
+                        blend_shapes_weights: Default::default(),
+                        element_range: ElementRange::Full,
+                        node_handle: ctx.tile_map_handle,
+                    },
+                );
+            }
+            rendered_chunks.insert(rect.position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scene::tilemap::{
+        test_fixture::{self, FLOOR},
+        tileset::TileData,
+    };
+
+    #[test]
+    fn test_tile_set_change_invalidates_chunk() {
+        let tile_set = TileSetResource::new_ok(
+            ResourceKind::Embedded,
+            test_fixture::make_tile_set(|_| TileData::default()),
+        );
+        let mut tiles = TileMapData::default();
+        tiles.set(Vector2::new(0, 0), FLOOR);
+        let (rect, revision) = tiles
+            .bounded_chunks(TileRect::new(0, 0, 1, 1).into())
+            .next()
+            .unwrap();
+        let mut cache = TileMapInstanceCache::default();
+        let chunk_hash = |cache: &mut TileMapInstanceCache| {
+            let mut tile_set_ref = TileSetRef::new(&tile_set);
+            cache.batches(
+                rect,
+                revision,
+                Some(&tile_set),
+                &tile_set_ref.as_loaded(),
+                &tiles,
+                TileGridLayout::default(),
+            );
+            cache.chunks[&rect.position].hash
+        };
+        let hash = chunk_hash(&mut cache);
+        assert_eq!(chunk_hash(&mut cache), hash);
+        // The editor sets the change flag on every modification of the tile set.
+        tile_set.data_ref().change_count.set();
+        assert_ne!(chunk_hash(&mut cache), hash);
+    }
+
+    #[test]
+    fn test_tile_instance() {
+        let bounds = TileBounds {
+            left_top_corner: Vector2::new(0, 0),
+            right_top_corner: Vector2::new(16, 0),
+            right_bottom_corner: Vector2::new(16, 16),
+            left_bottom_corner: Vector2::new(0, 16),
+        };
+        let instance = TileInstance::new(Vector2::new(2.0, 3.0), Some(&bounds), Color::WHITE);
+        assert_eq!(instance.position, Vector2::new(2.0, 3.0));
+        assert_eq!(instance.top_tex_coords, Vector4::new(16.0, 0.0, 0.0, 0.0));
+        assert_eq!(
+            instance.bottom_tex_coords,
+            Vector4::new(0.0, 16.0, 16.0, 16.0)
+        );
+
+        let instance = TileInstance::new(Vector2::default(), None, Color::RED);
+        assert_eq!(instance.top_tex_coords, Vector4::default());
+        assert_eq!(instance.color, Color::RED);
+    }
+
+    #[test]
+    fn test_instance_surface() {
+        let instances = vec![TileInstance::default(); 3];
+        let surface = make_instance_surface(instances);
+        let data = surface.data_ref();
+        assert_eq!(data.vertex_buffer.vertex_count(), 3);
+        assert_eq!(
+            data.vertex_buffer.vertex_size() as usize,
+            std::mem::size_of::<TileInstance>()
+        );
+        assert_eq!(data.geometry_buffer.len(), 2);
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Level of detail for tile maps. Distant chunks of a tile map could be rendered as a single quad
+//! with a low-resolution texture instead of individual tiles. See [`TileMap::set_lod_cell_size`]
+//! docs for more info.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, Point3, Vector2},
+        color::Color,
+        math::TriangleDefinition,
+    },
+    material::{Material, MaterialResource},
+    renderer::bundle::ObserverInfo,
+    scene::mesh::{buffer::VertexTrait, RenderPath},
+};
+use fxhash::{FxHashMap, FxHashSet, FxHasher};
+use std::hash::{Hash, Hasher};
+
+use super::{
+    capture::{make_texture, rasterize_tiles},
+    data::chunk_rect,
+    *,
+};
+
+/// A baked chunk of a tile map.
+#[derive(Debug)]
+struct LodChunk {
+    /// Hash of the tile set and the revision of the chunk, which is used to detect changes.
+    hash: u64,
+    material: MaterialResource,
+}
+
+/// Baked chunks of a tile map, that are reused until their tiles are changed.
+#[derive(Default, Debug)]
+pub(super) struct TileMapLodCache {
+    chunks: FxHashMap<Vector2<i32>, LodChunk>,
+}
+
+impl TileMapLodCache {
+    /// Removes every baked chunk.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    /// Returns the material of the chunk with the given rect, the chunk is baked again if the
+    /// tiles or the tile set were changed since the last call.
+    fn material(
+        &mut self,
+        rect: TileRect,
+        revision: u64,
+        tile_set_resource: Option<&TileSetResource>,
+        tile_set: &OptionTileSet,
+        tiles: &TileMapData,
+    ) -> Option<MaterialResource> {
+        let mut hasher = FxHasher::default();
+        tile_set_resource.hash(&mut hasher);
+        revision.hash(&mut hasher);
+        let hash = hasher.finish();
+        if let Some(chunk) = self.chunks.get(&rect.position) {
+            if chunk.hash == hash {
+                return Some(chunk.material.clone());
+            }
+        }
+        // One texel per cell is enough, since the cells are only a few pixels wide on screen.
+        let resolution = rect.size.map(|v| v as u32);
+        let pixels = rasterize_tiles(
+            tile_set,
+            tiles,
+            rect,
+            resolution,
+            TileMapCaptureMode::Material,
+        );
+        let mut material = Material::standard_tile();
+        material.bind("diffuseTexture", make_texture(resolution, pixels)?);
+        let material = MaterialResource::new_ok(ResourceKind::Embedded, material);
+        self.chunks.insert(
+            rect.position,
+            LodChunk {
+                hash,
+                material: material.clone(),
+            },
+        );
+        Some(material)
+    }
+}
+
+/// Calculates the size of a cell at the given position (in local coordinates of a tile map with
+/// the given transform) projected on the screen of the observer. The size is measured in fractions
+/// of the viewport, so `0.01` means that a cell takes a hundredth of the screen. Returns `None`
+/// if the cell is behind the observer.
+pub fn projected_cell_size(
+    observer_info: &ObserverInfo,
+    transform: &Matrix4<f32>,
+    position: Vector2<f32>,
+) -> Option<f32> {
+    let view_projection = observer_info.projection_matrix * observer_info.view_matrix;
+    let project = |p: Vector2<f32>| {
+        let world = transform.transform_point(&Point3::new(p.x, p.y, 0.0));
+        let clip = view_projection * world.to_homogeneous();
+        (clip.w > f32::EPSILON).then(|| clip.xy() / clip.w)
+    };
+    let center = project(position)?;
+    let right = project(position + Vector2::new(1.0, 0.0))?;
+    let up = project(position + Vector2::new(0.0, 1.0))?;
+    // Normalized device coordinates are in [-1; 1] range, so the size is halved.
+    Some((right - center).norm().max((up - center).norm()) * 0.5)
+}
+
+impl TileMapRenderContext<'_, '_> {
+    fn push_lod_chunk(&mut self, rect: TileRect, material: &MaterialResource) {
+        let sort_index = self.sorting_index(rect.position);
+        let position = rect.position.cast::<f32>();
+        let size = rect.size.cast::<f32>();
+        let texels = rect.size.map(|v| v as u32);
+        // The first row of texels is the top of the chunk.
+        let vertices = [
+            (1.0, 1.0, Vector2::new(texels.x, 0)),
+            (0.0, 1.0, Vector2::new(0, 0)),
+            (0.0, 0.0, Vector2::new(0, texels.y)),
+            (1.0, 0.0, texels),
+        ]
+        .map(|(x, y, uv)| {
+            let p = position + Vector2::new(x * size.x, y * size.y);
+            make_tile_vertex(&self.transform, p, uv, Color::WHITE)
+        });
+
+        let triangles = [[0, 1, 2], [2, 3, 0]].map(TriangleDefinition);
+
+        self.context.storage.push_triangles(
+            TileVertex::layout(),
+            material,
+            RenderPath::Forward,
+            sort_index,
+            self.tile_map_handle,
+            &mut move |mut vertex_buffer, mut triangle_buffer| {
+                let start_vertex_index = vertex_buffer.vertex_count();
+
+                vertex_buffer.push_vertices(&vertices).unwrap();
+
+                triangle_buffer
+                    .push_triangles_iter_with_offset(start_vertex_index, triangles.into_iter());
+            },
+        );
+    }
+}
+
+impl TileMap {
+    /// Renders the visible chunks, whose cells are projected smaller than the LOD cell size, as
+    /// baked quads and returns the positions of these chunks, so their tiles could be skipped.
+    /// Chunks that contain hidden or tinted tiles are always rendered tile by tile, so the
+    /// effects keep working. Hexagonal tile maps are always rendered tile by tile.
+    pub(super) fn render_lod_chunks(
+        &self,
+        ctx: &mut TileMapRenderContext,
+        tiles: &TileMapData,
+    ) -> FxHashSet<Vector2<i32>> {
+        let mut lod_chunks = FxHashSet::default();
+        let lod_cell_size = *self.lod_cell_size;
+        let bounds = ctx.visible_bounds();
+        if lod_cell_size <= 0.0 || bounds.is_none() || ctx.grid_layout.is_hex() {
+            return lod_chunks;
+        }
+        let mut cache = self.lod_cache.lock();
+        for (rect, revision) in tiles.bounded_chunks(bounds) {
+            let center = rect.position.cast::<f32>() + rect.size.cast::<f32>() * 0.5;
+            let is_far = projected_cell_size(ctx.context.observer_info, &ctx.transform, center)
+                .is_some_and(|size| size < lod_cell_size);
+            if !is_far {
+                continue;
+            }
+            let has_effects = (!ctx.hidden_tiles.is_empty() || !ctx.tile_tints.is_empty())
+                && rect
+                    .iter()
+                    .any(|p| !ctx.is_tile_visible(p) || ctx.tile_tint(p).is_some());
+            if has_effects {
+                continue;
+            }
+            let Some(material) =
+                cache.material(rect, revision, self.tile_set.as_ref(), &ctx.tile_set, tiles)
+            else {
+                continue;
+            };
+            ctx.push_lod_chunk(rect, &material);
+            lod_chunks.insert(rect.position);
+        }
+        lod_chunks
+    }
+
+    /// True if the tile at the given position belongs to one of the given chunks.
+    pub(super) fn is_in_lod_chunk(
+        lod_chunks: &FxHashSet<Vector2<i32>>,
+        position: Vector2<i32>,
+    ) -> bool {
+        !lod_chunks.is_empty() && lod_chunks.contains(&chunk_rect(position).position)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_projected_cell_size() {
+        let observer_info = ObserverInfo {
+            observer_position: Default::default(),
+            z_near: 0.0,
+            z_far: 10.0,
+            view_matrix: Matrix4::identity(),
+            projection_matrix: Matrix4::new_orthographic(-50.0, 50.0, -50.0, 50.0, 0.0, 10.0),
+            pixel_grid_size: None,
+        };
+        let size = projected_cell_size(&observer_info, &Matrix4::identity(), Vector2::repeat(0.5))
+            .unwrap();
+        assert!((size - 0.01).abs() < 1.0e-6);
+
+        let scaled = Matrix4::new_scaling(4.0);
+        let size = projected_cell_size(&observer_info, &scaled, Vector2::repeat(0.5)).unwrap();
+        assert!((size - 0.04).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_is_in_lod_chunk() {
+        let mut lod_chunks = FxHashSet::default();
+        assert!(!TileMap::is_in_lod_chunk(&lod_chunks, Vector2::new(3, 4)));
+        lod_chunks.insert(chunk_rect(Vector2::new(0, 0)).position);
+        assert!(TileMap::is_in_lod_chunk(&lod_chunks, Vector2::new(3, 4)));
+        assert!(!TileMap::is_in_lod_chunk(&lod_chunks, Vector2::new(-1, 4)));
+    }
+}
@@ -21,32 +21,81 @@
 //! Tile map is a 2D "image", made out of a small blocks called tiles. Tile maps used in 2D games to
 //! build game worlds quickly and easily. See [`TileMap`] docs for more info and usage examples.
 
+mod autotile;
+mod bake;
+mod batching;
 pub mod brush;
+mod capture;
+mod clipboard;
+mod collider_merge;
 mod data;
 mod effect;
+mod fill;
+mod fog;
+mod generator;
+mod grid_layout;
+mod instancing;
+mod lod;
+mod navigation;
+mod parallax;
 mod property;
+mod property_query;
+mod region;
+mod spawn;
+mod stamp;
+mod streaming;
+#[cfg(test)]
+mod test_fixture;
+mod text_grid;
+mod tile_animation;
 mod tile_collider;
+mod tile_encoding;
 mod tile_rect;
 mod tile_source;
+pub mod tiled;
 pub mod tileset;
 mod transform;
+mod trigger;
 mod update;
+mod wfc;
 
+pub use autotile::*;
+use batching::*;
 use brush::*;
+pub use capture::*;
+pub use clipboard::*;
+pub use collider_merge::*;
 pub use data::*;
 pub use effect::*;
-use fxhash::FxHashSet;
+pub use fog::*;
+use fxhash::{FxHashMap, FxHashSet};
 use fyrox_core::{
     math::{frustum::Frustum, plane::Plane, ray::Ray},
     parking_lot::Mutex,
 };
 use fyrox_resource::Resource;
+pub use generator::*;
+pub use grid_layout::*;
+pub use instancing::*;
+pub use lod::*;
+pub use navigation::*;
+pub use parallax::*;
+pub use property_query::*;
+pub use region::*;
+pub use spawn::*;
+pub use stamp::*;
+pub use streaming::*;
+pub use text_grid::*;
+pub use tile_animation::*;
 pub use tile_collider::*;
+use tile_encoding::*;
 pub use tile_rect::*;
 pub use tile_source::*;
 use tileset::*;
 pub use transform::*;
+pub use trigger::*;
 pub use update::*;
+pub use wfc::*;
 
 use crate::{
     asset::{untyped::ResourceKind, ResourceDataRef},
@@ -66,6 +115,7 @@ use crate::{
     renderer::{self, bundle::RenderContext},
     scene::{
         base::{Base, BaseBuilder},
+        camera::Camera,
         graph::Graph,
         mesh::{
             buffer::{
@@ -74,7 +124,7 @@ use crate::{
             },
             RenderPath,
         },
-        node::{Node, NodeTrait, RdcControlFlow},
+        node::{Node, NodeTrait, RdcControlFlow, UpdateContext},
         Scene,
     },
 };
@@ -84,9 +134,13 @@ use std::{
     fmt::Display,
     ops::{Deref, DerefMut},
     path::PathBuf,
+    sync::atomic::{self, AtomicU64},
 };
 
-use super::{dim2::rectangle::RectangleVertex, node::constructor::NodeConstructor};
+use super::{
+    dim2::{rectangle::RectangleVertex, SortMode2D},
+    node::constructor::NodeConstructor,
+};
 
 use crate::lazy_static::*;
 
@@ -113,7 +167,12 @@ pub struct TileMapRenderContext<'a, 'b> {
     /// The visible tile positions.
     bounds: OptionTileRect,
     hidden_tiles: &'a mut FxHashSet<Vector2<i32>>,
+    tile_tints: FxHashMap<Vector2<i32>, Color>,
     tile_set: OptionTileSet<'a>,
+    sort_mode: SortMode2D,
+    /// The UUID of the property, that defines the sort bias of the tiles.
+    sort_bias_property: Option<Uuid>,
+    grid_layout: TileGridLayout,
 }
 
 impl TileMapRenderContext<'_, '_> {
@@ -129,6 +188,42 @@ impl TileMapRenderContext<'_, '_> {
     pub fn position(&self) -> Vector3<f32> {
         self.transform.position()
     }
+    /// The sorting mode of the TileMap
+    pub fn sort_mode(&self) -> SortMode2D {
+        self.sort_mode
+    }
+    /// The layout of the cells of the TileMap
+    pub fn grid_layout(&self) -> TileGridLayout {
+        self.grid_layout
+    }
+    /// The left-bottom corner of the quad of the cell at the given position, in the local
+    /// coordinates of the TileMap. See [`TileGridLayout::cell_origin`].
+    pub fn cell_origin(&self, position: Vector2<i32>) -> Vector2<f32> {
+        self.grid_layout.cell_origin(position)
+    }
+    /// The sorting index of a tile at the given position. Y-sorted tile maps use the bottom edge
+    /// of the tile as the sort origin, so every row of tiles is sorted separately. Other tile maps
+    /// use the same index for every tile.
+    pub fn sorting_index(&self, position: Vector2<i32>) -> u64 {
+        self.biased_sorting_index(position, 0.0)
+    }
+    /// The sorting index of a tile at the given position, whose sort origin is moved up by the
+    /// given bias (in cells) from the bottom edge of the tile. The bias is ignored by the tile
+    /// maps that are not Y-sorted. See [`TileMap::set_sort_bias_property`].
+    pub fn biased_sorting_index(&self, position: Vector2<i32>, sort_bias: f32) -> u64 {
+        match self.sort_mode {
+            SortMode2D::Depth => self.context.calculate_sorting_index(self.position()),
+            SortMode2D::WorldY => {
+                let origin = self.cell_origin(position) + Vector2::new(0.5, sort_bias);
+                let origin = self
+                    .transform
+                    .transform_point(&origin.to_homogeneous().into())
+                    .coords;
+                self.context
+                    .calculate_sorting_index_2d(origin, SortMode2D::WorldY)
+            }
+        }
+    }
     /// The area of tiles that are touching the frustum
     pub fn visible_bounds(&self) -> OptionTileRect {
         self.bounds
@@ -152,6 +247,21 @@ impl TileMapRenderContext<'_, '_> {
     pub fn is_tile_visible(&self, position: Vector2<i32>) -> bool {
         !self.hidden_tiles.contains(&position)
     }
+    /// Set the color that will be multiplied with the color of every tile that is
+    /// rendered at the given position after this call. `None` removes the tint.
+    /// This allows effects to darken or recolor tiles without rendering them by themselves.
+    pub fn set_tile_tint(&mut self, position: Vector2<i32>, tint: Option<Color>) {
+        if let Some(tint) = tint {
+            let _ = self.tile_tints.insert(position, tint);
+        } else {
+            let _ = self.tile_tints.remove(&position);
+        }
+    }
+    /// The tint of the tiles at the given position, if any.
+    /// See [`TileMapRenderContext::set_tile_tint`] for more info.
+    pub fn tile_tint(&self, position: Vector2<i32>) -> Option<Color> {
+        self.tile_tints.get(&position).copied()
+    }
     /// The handle of the tile that should be rendered at the current time in order
     /// to animate the tile at the given handle.
     pub fn get_animated_version(&self, handle: TileDefinitionHandle) -> TileDefinitionHandle {
@@ -159,41 +269,100 @@ impl TileMapRenderContext<'_, '_> {
             .get_animated_version(self.context.elapsed_time, handle)
             .unwrap_or(handle)
     }
+    /// The handle of the tile that should be rendered to show the given frame of the animation
+    /// of the tile at the given handle. See [`TileAnimationState`].
+    pub fn get_animation_frame(
+        &self,
+        handle: TileDefinitionHandle,
+        frame: i32,
+    ) -> TileDefinitionHandle {
+        self.tile_set
+            .get_animation_frame(handle, frame)
+            .unwrap_or(handle)
+    }
     /// Render the tile with the given handle at the given position.
     /// Normally [`TileMapRenderContext::is_tile_visible`] should be checked before calling this method
     /// to ensure that tiles are permitted to be rendered at this position,
     /// and then [`TileMapRenderContext::set_tile_visible`] should be used to set the position to false
     /// to prevent any future effects from rendering at this position.
     pub fn draw_tile(&mut self, position: Vector2<i32>, handle: TileDefinitionHandle) {
+        self.draw_transformed_tile(position, handle, OrthoTransformation::identity());
+    }
+
+    /// Render the tile with the given handle at the given position, flipped and rotated by the
+    /// given transformation. See [`TileMapRenderContext::draw_tile`] for more info.
+    pub fn draw_transformed_tile(
+        &mut self,
+        position: Vector2<i32>,
+        handle: TileDefinitionHandle,
+        transformation: OrthoTransformation,
+    ) {
         let Some(data) = self.tile_set.get_tile_render_data(handle.into()) else {
             return;
         };
-        self.push_tile(position, &data);
+        let data = data.transformed(transformation);
+        let sort_bias = self.sort_bias(handle);
+        self.push_biased_tile(position, &data, sort_bias);
+    }
+
+    /// The sort bias of the tile with the given handle, taken from the sort bias property of
+    /// the tile set. See [`TileMap::set_sort_bias_property`].
+    pub fn sort_bias(&self, handle: TileDefinitionHandle) -> f32 {
+        if self.sort_mode != SortMode2D::WorldY {
+            return 0.0;
+        }
+        let Some(property_id) = self.sort_bias_property else {
+            return 0.0;
+        };
+        match self.tile_set.property_value(handle, property_id) {
+            Some(TileSetPropertyValue::F32(value)) => value,
+            Some(TileSetPropertyValue::I32(value)) => value as f32,
+            _ => 0.0,
+        }
     }
 
     /// Render the given tile data at the given cell position. This makes it possible to render
     /// a tile that is not in the tile map's tile set.
     pub fn push_tile(&mut self, position: Vector2<i32>, data: &TileRenderData) {
-        let color = data.color;
+        self.push_biased_tile(position, data, 0.0);
+    }
+
+    /// Render the given tile data at the given cell position, with the sort origin moved up by
+    /// the given bias. See [`Self::biased_sorting_index`].
+    pub fn push_biased_tile(
+        &mut self,
+        position: Vector2<i32>,
+        data: &TileRenderData,
+        sort_bias: f32,
+    ) {
+        let mut color = data.color;
+        if let Some(tint) = self.tile_tint(position) {
+            let modulate = |a: u8, b: u8| ((a as u16 * b as u16) / 255) as u8;
+            color = Color::from_rgba(
+                modulate(color.r, tint.r),
+                modulate(color.g, tint.g),
+                modulate(color.b, tint.b),
+                modulate(color.a, tint.a),
+            );
+        }
         if let Some(tile_bounds) = data.material_bounds.as_ref() {
             let material = &tile_bounds.material;
             let bounds = &tile_bounds.bounds;
-            self.push_material_tile(position, material, bounds, color);
+            self.push_material_tile(position, material, bounds, color, sort_bias);
         } else {
-            self.push_color_tile(position, color);
+            self.push_color_tile(position, color, sort_bias);
         }
     }
 
-    fn push_color_tile(&mut self, position: Vector2<i32>, color: Color) {
-        let position = position.cast::<f32>();
+    fn push_color_tile(&mut self, position: Vector2<i32>, color: Color, sort_bias: f32) {
+        let sort_index = self.biased_sorting_index(position, sort_bias);
+        let position = self.cell_origin(position);
         let vertices = [(0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)]
             .map(|(x, y)| Vector2::new(x, y))
             .map(|p| make_rect_vertex(&self.transform, position + p, color));
 
         let triangles = [[0, 1, 2], [2, 3, 0]].map(TriangleDefinition);
 
-        let sort_index = self.context.calculate_sorting_index(self.position());
-
         self.context.storage.push_triangles(
             RectangleVertex::layout(),
             &STANDARD_2D.resource,
@@ -217,8 +386,10 @@ impl TileMapRenderContext<'_, '_> {
         material: &MaterialResource,
         bounds: &TileBounds,
         color: Color,
+        sort_bias: f32,
     ) {
-        let position = position.cast::<f32>();
+        let sort_index = self.biased_sorting_index(position, sort_bias);
+        let position = self.cell_origin(position);
         let uvs = [
             bounds.right_top_corner,
             bounds.left_top_corner,
@@ -236,8 +407,6 @@ impl TileMapRenderContext<'_, '_> {
 
         let triangles = [[0, 1, 2], [2, 3, 0]].map(TriangleDefinition);
 
-        let sort_index = self.context.calculate_sorting_index(self.position());
-
         self.context.storage.push_triangles(
             TileVertex::layout(),
             material,
@@ -287,23 +456,49 @@ fn make_tile_vertex(
 
 /// A record whether a change has happened since the most recent save.
 #[derive(Default, Debug, Copy, Clone)]
-pub struct ChangeFlag(bool);
+pub struct ChangeFlag {
+    needs_save: bool,
+    count: u64,
+}
 
 impl ChangeFlag {
     /// True if there are changes.
     #[inline]
     pub fn needs_save(&self) -> bool {
-        self.0
+        self.needs_save
     }
     /// Reset the flag to indicate that there are no unsaved changes.
     #[inline]
     pub fn reset(&mut self) {
-        self.0 = false;
+        self.needs_save = false;
     }
     /// Set the flat to indicate that there could be unsaved changes.
     #[inline]
     pub fn set(&mut self) {
-        self.0 = true;
+        self.needs_save = true;
+        self.count = self.count.wrapping_add(1);
+    }
+    /// The number of times the flag was set. Unlike [`Self::needs_save`], it is not reset when
+    /// the data is saved, so it can be used to detect changes of the data, for example to rebuild
+    /// the caches derived from it.
+    #[inline]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+static NEXT_RESOURCE_REVISION: AtomicU64 = AtomicU64::new(1);
+
+/// A number that identifies the data of a tile set or a brush. Every new instance of the data
+/// gets a revision that was never used before, so the data of a resource that was reloaded from
+/// disk has a different revision than the data it replaced. Tile maps and editors compare the
+/// revisions to detect reloads and refresh the data they derived from the resource.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TileResourceRevision(u64);
+
+impl Default for TileResourceRevision {
+    fn default() -> Self {
+        Self(NEXT_RESOURCE_REVISION.fetch_add(1, atomic::Ordering::Relaxed))
     }
 }
 
@@ -515,6 +710,16 @@ impl TileBook {
             TileBook::Brush(r) => r.state().data()?.page_icon(position),
         }
     }
+    /// The revision of the data of the resource, or `None` if the resource is not loaded.
+    /// See [`TileResourceRevision`] for more info.
+    #[inline]
+    pub fn revision(&self) -> Option<TileResourceRevision> {
+        match self {
+            TileBook::Empty => None,
+            TileBook::TileSet(r) => r.state().data().map(|d| d.revision()),
+            TileBook::Brush(r) => r.state().data().map(|d| d.revision()),
+        }
+    }
     /// Returns true if this resource is a tile set.
     #[inline]
     pub fn is_tile_set(&self) -> bool {
@@ -750,6 +955,23 @@ impl TileBook {
         }
     }
 
+    /// Construct clipboard data holding the tile definition handles for the tiles at the given
+    /// positions on the given page, along with the path of the tile set that the handles refer to.
+    /// See [`TileClipboard`] for more info.
+    pub fn copy_tiles<I: Iterator<Item = Vector2<i32>>>(
+        &self,
+        stage: TilePaletteStage,
+        page: Vector2<i32>,
+        iter: I,
+    ) -> TileClipboard {
+        let mut tiles = Tiles::default();
+        self.get_tiles(stage, page, iter, &mut tiles);
+        TileClipboard::new(
+            self.get_tile_set().as_ref(),
+            tiles.iter().map(|(p, h)| (*p, *h)),
+        )
+    }
+
     /// Returns true if the resource is a brush that has no tile set.
     pub fn is_missing_tile_set(&self) -> bool {
         match self {
@@ -892,12 +1114,66 @@ pub struct TileMap {
     /// Tile container of the tile map.
     #[reflect(hidden)]
     pub tiles: InheritableVariable<Option<TileMapDataResource>>,
-    tile_scale: InheritableVariable<Vector2<f32>>,
+    /// The size of a cell in the local coordinates of the tile map node. It could be non-square,
+    /// for example `(2.0, 1.0)` for 32×16 tiles.
+    cell_size: InheritableVariable<Vector2<f32>>,
+    /// Defines how the tiles are sorted relative to other 2D nodes. Use [`SortMode2D::WorldY`]
+    /// for the layers with props (walls, trees, etc.) in top-down games.
+    #[reflect(setter = "set_sort_mode")]
+    sort_mode: InheritableVariable<SortMode2D>,
+    /// The name of the property layer of the tile set, that defines the sort bias of the tiles in
+    /// [`SortMode2D::WorldY`] mode. Empty name means that every tile is sorted by its bottom edge.
+    #[reflect(setter = "set_sort_bias_property")]
+    sort_bias_property: InheritableVariable<ImmutableString>,
+    /// The name of the collider layer of the tile set, that defines shadow occluders for 2D lights.
+    /// Empty name means that the tile map does not cast 2D shadows.
+    #[reflect(setter = "set_shadow_collider")]
+    shadow_collider: InheritableVariable<ImmutableString>,
+    /// Defines how the cells of the tile map are arranged. See [`TileGridLayout`] docs for more
+    /// info.
+    #[reflect(setter = "set_grid_layout")]
+    grid_layout: InheritableVariable<TileGridLayout>,
+    /// The size of a cell projected on the screen (in fractions of the viewport), below which the
+    /// chunks of the tile map are rendered as single quads with baked low-resolution textures
+    /// instead of individual tiles. Zero disables the level of detail.
+    #[reflect(min_value = 0.0, step = 0.001, setter = "set_lod_cell_size")]
+    lod_cell_size: InheritableVariable<f32>,
+    /// If true, the chunks of the tile map are rendered using instancing: the tiles of every chunk
+    /// are stored in a vertex buffer on GPU and drawn with a single draw call per material.
+    #[reflect(setter = "set_instancing")]
+    instancing: InheritableVariable<bool>,
+    /// Parallax scrolling of the tile map. See [`TileMapParallax`] docs for more info.
+    #[reflect(setter = "set_parallax")]
+    parallax: InheritableVariable<TileMapParallax>,
     active_brush: InheritableVariable<Option<TileMapBrushResource>>,
     /// Temporary space to store which tiles are invisible during `collect_render_data`.
     /// This is part of how [`TileMapEffect`] can prevent a tile from being rendered.
     #[reflect(hidden)]
     hidden_tiles: Mutex<FxHashSet<Vector2<i32>>>,
+    /// Baked chunks that are rendered instead of tiles when the tile map is far away.
+    #[reflect(hidden)]
+    lod_cache: Mutex<TileMapLodCache>,
+    /// Instance data of the chunks, that is rendered when the instancing is enabled.
+    #[reflect(hidden)]
+    instance_cache: Mutex<TileMapInstanceCache>,
+    /// Vertex data of the chunks, that is rendered when the instancing is disabled.
+    #[reflect(hidden)]
+    batch_cache: Mutex<TileMapBatchCache>,
+    /// The revision of the tile set, that was used to build the cached data.
+    #[reflect(hidden)]
+    tile_set_revision: Mutex<Option<TileResourceRevision>>,
+    /// Optional streaming of the chunks of the tile map. See [`TileMapStreaming`] docs for more
+    /// info.
+    #[reflect(hidden)]
+    streaming: Option<TileMapStreaming>,
+    /// The animation states of the cells, which animations are controlled individually. See
+    /// [`TileAnimationState`] docs for more info.
+    #[reflect(hidden)]
+    animation_states: FxHashMap<Vector2<i32>, TileAnimationState>,
+    /// Temporary render data of the cells, that is rendered over the stored tiles. See
+    /// [`TileStamp`] docs for more info.
+    #[reflect(hidden)]
+    stamps: TileStamps,
     /// Special rendering effects that may change how the tile map renders.
     /// These effects are processed in order before the tile map performs the
     /// normal rendering of tiles, and they can prevent some times from being
@@ -919,8 +1195,21 @@ impl Visit for TileMap {
         let _ = version.visit("Version", &mut region);
         self.base.visit("Base", &mut region)?;
         self.tile_set.visit("TileSet", &mut region)?;
-        self.tile_scale.visit("TileScale", &mut region)?;
+        // Tile maps from older versions keep the size of the cells in `TileScale`.
+        if self.cell_size.visit("CellSize", &mut region).is_err() {
+            self.cell_size.visit("TileScale", &mut region)?;
+        }
         self.active_brush.visit("ActiveBrush", &mut region)?;
+        let _ = self.sort_mode.visit("SortMode", &mut region);
+        let _ = self
+            .sort_bias_property
+            .visit("SortBiasProperty", &mut region);
+        let _ = self.shadow_collider.visit("ShadowCollider", &mut region);
+        let _ = self.grid_layout.visit("GridLayout", &mut region);
+        let _ = self.lod_cell_size.visit("LodCellSize", &mut region);
+        let _ = self.instancing.visit("Instancing", &mut region);
+        let _ = self.parallax.visit("Parallax", &mut region);
+        let _ = self.animation_states.visit("AnimationStates", &mut region);
         match version {
             0 => {
                 let mut tiles = InheritableVariable::new_non_modified(Tiles::default());
@@ -1100,9 +1389,11 @@ impl TileMap {
         }
     }
     /// The global transform of the tile map with initial x-axis flip applied, so the positive x-axis points left instead of right.
+    /// The transform is also scaled by the [cell size](Self::cell_size), so one unit is one cell.
     pub fn tile_map_transform(&self) -> Matrix4<f32> {
+        let cell_size = *self.cell_size;
         self.global_transform()
-            .prepend_nonuniform_scaling(&Vector3::new(-1.0, 1.0, 1.0))
+            .prepend_nonuniform_scaling(&Vector3::new(-cell_size.x, cell_size.y, 1.0))
     }
     /// Returns a reference to the current tile set (if any).
     #[inline]
@@ -1128,16 +1419,339 @@ impl TileMap {
         self.tiles.set_value_and_mark_modified(Some(tiles));
     }
 
+    /// Returns the size of a cell in the local coordinates of the tile map node.
+    #[inline]
+    pub fn cell_size(&self) -> Vector2<f32> {
+        *self.cell_size
+    }
+
+    /// Sets the size of a cell in the local coordinates of the tile map node. Non-square sizes
+    /// allow to use non-square tiles, for example `(2.0, 1.0)` for 32×16 tiles. The scale of the
+    /// node is applied on top of it.
+    #[inline]
+    pub fn set_cell_size(&mut self, cell_size: Vector2<f32>) {
+        self.cell_size.set_value_and_mark_modified(cell_size);
+    }
+
     /// Returns current tile scaling.
     #[inline]
+    #[deprecated = "tile scale is the size of a cell, use cell_size instead"]
     pub fn tile_scale(&self) -> Vector2<f32> {
-        *self.tile_scale
+        self.cell_size()
     }
 
     /// Sets new tile scaling, which defines tile size.
     #[inline]
+    #[deprecated = "tile scale is the size of a cell, use set_cell_size instead"]
     pub fn set_tile_scale(&mut self, tile_scale: Vector2<f32>) {
-        self.tile_scale.set_value_and_mark_modified(tile_scale);
+        self.set_cell_size(tile_scale);
+    }
+
+    /// Returns current sorting mode of the tile map.
+    #[inline]
+    pub fn sort_mode(&self) -> SortMode2D {
+        *self.sort_mode
+    }
+
+    /// Sets new sorting mode of the tile map. In [`SortMode2D::WorldY`] mode every row of tiles is
+    /// sorted by the world Y coordinate of its bottom edge, which allows Y-sorted sprites (see
+    /// [`super::dim2::rectangle::Rectangle::set_sort_mode`]) to be drawn behind or in front of
+    /// the tiles.
+    #[inline]
+    pub fn set_sort_mode(&mut self, sort_mode: SortMode2D) -> SortMode2D {
+        self.sort_mode.set_value_and_mark_modified(sort_mode)
+    }
+
+    /// Returns the name of the property layer that defines the sort bias of the tiles.
+    #[inline]
+    pub fn sort_bias_property(&self) -> &ImmutableString {
+        &self.sort_bias_property
+    }
+
+    /// Sets the name of the property layer of the tile set, that defines the sort bias of the
+    /// tiles in [`SortMode2D::WorldY`] mode. The property must be `F32` or `I32`, its value is
+    /// the vertical offset of the sort origin of a tile from its bottom edge, in cells. For
+    /// example, a tall tree tile with a bias of `0.3` is drawn in front of the characters only
+    /// when they are below the lower 30% of its cell. Empty name disables the bias.
+    #[inline]
+    pub fn set_sort_bias_property(&mut self, name: ImmutableString) -> ImmutableString {
+        self.sort_bias_property.set_value_and_mark_modified(name)
+    }
+
+    /// Returns the name of the collider layer that is used for 2D shadows.
+    #[inline]
+    pub fn shadow_collider(&self) -> &ImmutableString {
+        &self.shadow_collider
+    }
+
+    /// Sets the name of the collider layer of the tile set, which colliders will be used as
+    /// shadow occluders for 2D lights (see [`super::dim2::light::Light2D`]). Empty name disables
+    /// 2D shadows from the tile map.
+    #[inline]
+    pub fn set_shadow_collider(&mut self, name: ImmutableString) -> ImmutableString {
+        self.shadow_collider.set_value_and_mark_modified(name)
+    }
+
+    /// Returns the layout of the cells of the tile map.
+    #[inline]
+    pub fn grid_layout(&self) -> TileGridLayout {
+        *self.grid_layout
+    }
+
+    /// Sets new layout of the cells of the tile map. The tiles keep their grid positions, so
+    /// switching between square and hexagonal layouts moves the tiles on screen. See
+    /// [`TileGridLayout`] docs for more info.
+    #[inline]
+    pub fn set_grid_layout(&mut self, layout: TileGridLayout) -> TileGridLayout {
+        self.grid_layout.set_value_and_mark_modified(layout)
+    }
+
+    /// Iterates over the grid positions of the cells that share an edge with the given cell,
+    /// four for square cells and six for hexagonal cells.
+    #[inline]
+    pub fn neighbors(&self, grid_position: Vector2<i32>) -> impl Iterator<Item = Vector2<i32>> {
+        self.grid_layout.neighbors(grid_position)
+    }
+
+    /// The number of steps between neighboring cells that it takes to get from one cell to
+    /// another. See [`TileGridLayout::distance`].
+    #[inline]
+    pub fn grid_distance(&self, from: Vector2<i32>, to: Vector2<i32>) -> u32 {
+        self.grid_layout.distance(from, to)
+    }
+
+    /// Returns the projected cell size, below which the chunks of the tile map are rendered as
+    /// baked quads. See [`Self::set_lod_cell_size`] for more info.
+    #[inline]
+    pub fn lod_cell_size(&self) -> f32 {
+        *self.lod_cell_size
+    }
+
+    /// Sets the size of a cell projected on the screen (in fractions of the viewport, see
+    /// [`projected_cell_size`]), below which every chunk of 16x16 tiles is rendered as a single
+    /// quad with a texture, that has one texel per cell. The textures are baked on demand from
+    /// the colors of the tiles and reused until the tiles of the chunk change. This makes
+    /// zoomed-out views of huge tile maps much cheaper to render. For example, `0.004` switches a
+    /// chunk to the baked quad when its cells become smaller than about 4 pixels on a 1080p
+    /// screen. Zero (default) disables the level of detail.
+    #[inline]
+    pub fn set_lod_cell_size(&mut self, size: f32) -> f32 {
+        self.lod_cell_size
+            .set_value_and_mark_modified(size.max(0.0))
+    }
+
+    /// Returns true if the chunks of the tile map are rendered using instancing. See
+    /// [`Self::set_instancing`] for more info.
+    #[inline]
+    pub fn instancing(&self) -> bool {
+        *self.instancing
+    }
+
+    /// Enables or disables instanced rendering of the tile map. When enabled, the tiles of every
+    /// visible chunk are written into a vertex buffer with one [`TileInstance`] per tile, which is
+    /// drawn with a single instanced draw call per material. The buffers are kept on GPU and
+    /// rebuilt only when the tiles of the chunk change, so large static tile maps take almost no
+    /// CPU time to render. Chunks that contain animated, hidden or tinted tiles, chunks whose tiles
+    /// use custom shaders and Y-sorted tile maps are still rendered tile by tile. Disabled by
+    /// default.
+    #[inline]
+    pub fn set_instancing(&mut self, instancing: bool) -> bool {
+        self.instancing.set_value_and_mark_modified(instancing)
+    }
+
+    /// Returns the parallax scrolling of the tile map.
+    #[inline]
+    pub fn parallax(&self) -> &TileMapParallax {
+        &self.parallax
+    }
+
+    /// Sets the parallax scrolling of the tile map. The tile map is shifted by a fraction of the
+    /// position of the camera, that renders it, so layers with a lower factor look farther away.
+    /// Optionally, the tiles could be repeated infinitely along any of the axes, which is useful
+    /// for backgrounds such as sky and mountains. See [`TileMapParallax`] docs for more info.
+    #[inline]
+    pub fn set_parallax(&mut self, parallax: TileMapParallax) -> TileMapParallax {
+        self.parallax.set_value_and_mark_modified(parallax)
+    }
+
+    /// Returns the streaming mode of the tile map, if any.
+    #[inline]
+    pub fn streaming(&self) -> Option<&TileMapStreaming> {
+        self.streaming.as_ref()
+    }
+
+    /// Returns the streaming mode of the tile map, if any.
+    #[inline]
+    pub fn streaming_mut(&mut self) -> Option<&mut TileMapStreaming> {
+        self.streaming.as_mut()
+    }
+
+    /// Sets the streaming mode of the tile map. When the streaming is enabled, only the chunks
+    /// around the cameras are kept in the tiles of the tile map, see [`TileMapStreaming`] docs
+    /// for more info. The streaming is not serialized and not cloned along with the tile map,
+    /// since it is a runtime state that refers to a chunk provider. When the streaming is
+    /// disabled, every loaded chunk is given back to the provider, so no changes are lost.
+    #[inline]
+    pub fn set_streaming(&mut self, streaming: Option<TileMapStreaming>) {
+        if let Some(mut old) = std::mem::replace(&mut self.streaming, streaming) {
+            if let Some(tiles) = self.tiles.as_ref() {
+                if let Some(tiles) = tiles.data_ref().as_loaded_mut() {
+                    old.unload_all(tiles);
+                }
+            }
+        }
+    }
+
+    /// Returns the animation state of the cell at the given position, if the animation of the cell
+    /// is controlled individually.
+    #[inline]
+    pub fn tile_animation_state(&self, position: Vector2<i32>) -> Option<&TileAnimationState> {
+        self.animation_states.get(&position)
+    }
+
+    /// Returns the animation state of the cell at the given position, if the animation of the cell
+    /// is controlled individually.
+    #[inline]
+    pub fn tile_animation_state_mut(
+        &mut self,
+        position: Vector2<i32>,
+    ) -> Option<&mut TileAnimationState> {
+        self.animation_states.get_mut(&position)
+    }
+
+    /// Takes the control of the animation of the cell at the given position. The cell shows the
+    /// given frame of the animation of its tile, and the frame advances over time if `playing`
+    /// is true. Frame `0` is the tile of the cell itself, so a door that opens when played
+    /// should be painted with the first frame of its animation. The state stays at the position
+    /// when the tile is replaced, use [`Self::reset_tile_animation_state`] to give the cell back
+    /// to the global animation clock.
+    #[inline]
+    pub fn set_tile_animation_state(&mut self, position: Vector2<i32>, frame: f32, playing: bool) {
+        let state = self.animation_states.entry(position).or_default();
+        state.frame = frame;
+        state.playing = playing;
+    }
+
+    /// Removes the animation state of the cell at the given position, so its tile is animated
+    /// along with every other tile of the tile map.
+    #[inline]
+    pub fn reset_tile_animation_state(
+        &mut self,
+        position: Vector2<i32>,
+    ) -> Option<TileAnimationState> {
+        self.animation_states.remove(&position)
+    }
+
+    /// Removes the animation states of all cells.
+    #[inline]
+    pub fn clear_tile_animation_states(&mut self) {
+        self.animation_states.clear();
+    }
+
+    /// Returns the stamp of the cell at the given position, if any.
+    #[inline]
+    pub fn tile_stamp(&self, position: Vector2<i32>) -> Option<&TileStamp> {
+        self.stamps.get(position)
+    }
+
+    /// Returns all stamps of the tile map.
+    #[inline]
+    pub fn tile_stamps(&self) -> &TileStamps {
+        &self.stamps
+    }
+
+    /// Puts the given stamp at the given position, replacing the previous stamp of the cell. The
+    /// stamp is rendered over (or instead of) the tile of the cell without modifying the tiles,
+    /// and it is removed automatically when its time to live is over. See [`TileStamp`] docs
+    /// for more info.
+    #[inline]
+    pub fn set_tile_stamp(
+        &mut self,
+        position: Vector2<i32>,
+        stamp: TileStamp,
+    ) -> Option<TileStamp> {
+        self.stamps.insert(position, stamp)
+    }
+
+    /// Removes the stamp of the cell at the given position.
+    #[inline]
+    pub fn remove_tile_stamp(&mut self, position: Vector2<i32>) -> Option<TileStamp> {
+        self.stamps.remove(position)
+    }
+
+    /// Removes the stamps of all cells.
+    #[inline]
+    pub fn clear_tile_stamps(&mut self) {
+        self.stamps.clear();
+    }
+
+    fn update_tile_animations(&mut self, dt: f32) {
+        if self.animation_states.is_empty() {
+            return;
+        }
+        let Some(tile_set) = self.tile_set.as_ref() else {
+            return;
+        };
+        let mut tile_set = TileSetRef::new(tile_set);
+        let tile_set = tile_set.as_loaded();
+        let Some(tiles) = self.tiles.as_ref().map(|r| r.data_ref()) else {
+            return;
+        };
+        let Some(tiles) = tiles.as_loaded_ref() else {
+            return;
+        };
+        for (position, state) in self.animation_states.iter_mut() {
+            let timing = tiles
+                .get(*position)
+                .and_then(|handle| tile_set.get_animation_timing(handle));
+            if let Some((frame_rate, length)) = timing {
+                state.advance(dt, frame_rate, length);
+            }
+        }
+    }
+
+    fn render_repeated_tiles(
+        &self,
+        ctx: &mut TileMapRenderContext,
+        tiles: &TileMapData,
+        visible: TileRect,
+        content: TileRect,
+    ) {
+        let Some(bounds) = *self.parallax.repeat_bounds(visible, content) else {
+            return;
+        };
+        for position in bounds.iter() {
+            if !ctx.is_tile_visible(position) {
+                continue;
+            }
+            let source = self.parallax.source_position(position, content);
+            if let Some(handle) = tiles.get(source) {
+                let handle = self.get_animated_version(ctx, source, handle);
+                ctx.draw_transformed_tile(position, handle, tiles.transformation(source));
+            }
+        }
+    }
+
+    fn get_animated_version(
+        &self,
+        ctx: &TileMapRenderContext<'_, '_>,
+        position: Vector2<i32>,
+        handle: TileDefinitionHandle,
+    ) -> TileDefinitionHandle {
+        match self.animation_states.get(&position) {
+            Some(state) => ctx.get_animation_frame(handle, state.frame_index()),
+            None => ctx.get_animated_version(handle),
+        }
+    }
+
+    /// Removes the baked textures of the level of detail and the instance data of the chunks, so
+    /// they are built again on the next frame. Changes of tiles and reloads of the tile set are
+    /// detected automatically, but changes of the tiles in the tile set (for example, a modified
+    /// tile color) are not, so this method must be called after such changes.
+    pub fn invalidate_lod(&self) {
+        self.lod_cache.lock().clear();
+        self.instance_cache.lock().clear();
+        self.batch_cache.lock().clear();
     }
 
     /// Inserts a tile in the tile map. Returns previous tile, located at the same position as
@@ -1155,6 +1769,35 @@ impl TileMap {
             .replace(position, Some(tile))
     }
 
+    /// Returns the transformation of the tile at the given position. See
+    /// [`TileMapData::set_transformation`] for more info.
+    #[inline]
+    pub fn tile_transformation(&self, position: Vector2<i32>) -> OrthoTransformation {
+        let Some(tiles) = self.tiles.as_ref() else {
+            return OrthoTransformation::identity();
+        };
+        let tiles = tiles.data_ref();
+        tiles
+            .as_loaded_ref()
+            .map(|tiles| tiles.transformation(position))
+            .unwrap_or_default()
+    }
+
+    /// Flips and rotates the tile at the given position. See [`TileMapData::set_transformation`]
+    /// for more info.
+    #[inline]
+    pub fn set_tile_transformation(
+        &mut self,
+        position: Vector2<i32>,
+        transformation: OrthoTransformation,
+    ) {
+        if let Some(tiles) = self.tiles.as_ref() {
+            if let Some(tiles) = tiles.data_ref().as_loaded_mut() {
+                tiles.set_transformation(position, transformation);
+            }
+        }
+    }
+
     /// Removes a tile from the tile map.
     #[inline]
     pub fn remove_tile(&mut self, position: Vector2<i32>) -> Option<TileDefinitionHandle> {
@@ -1196,22 +1839,115 @@ impl TileMap {
     pub fn world_to_grid(&self, world_position: Vector3<f32>) -> Vector2<i32> {
         let inv_global_transform = self.tile_map_transform().try_inverse().unwrap_or_default();
         let local_space_position = inv_global_transform.transform_point(&world_position.into());
-        Vector2::new(
-            local_space_position.x.floor() as i32,
-            local_space_position.y.floor() as i32,
-        )
+        self.grid_layout
+            .local_to_cell(local_space_position.coords.xy())
     }
 
     /// Calculates world-space position from grid-space position (tile coordinates).
     #[inline]
     pub fn grid_to_world(&self, grid_position: Vector2<i32>) -> Vector3<f32> {
-        let v3 = grid_position.cast::<f32>().to_homogeneous();
+        let v3 = self.grid_layout.cell_origin(grid_position).to_homogeneous();
         self.tile_map_transform().transform_point(&v3.into()).coords
     }
 
-    fn cells_touching_frustum(&self, frustum: &Frustum) -> OptionTileRect {
+    /// Repeatedly call the given function with each collider for each tile of the tile map.
+    /// The function is given the position of the cell and the transformation of its tile along
+    /// with the UUID and color of the collider layer. Tiles without a collider in some layer are skipped for that layer.
+    pub fn tile_collider_loop<F>(&self, mut func: F)
+    where
+        F: FnMut(Vector2<i32>, OrthoTransformation, Uuid, Color, &TileCollider),
+    {
+        let (Some(tile_set), Some(tiles)) = (self.tile_set(), self.tiles()) else {
+            return;
+        };
+        let tile_set = tile_set.data_ref();
+        let tiles = tiles.data_ref();
+        let (Some(tile_set), Some(tiles)) = (tile_set.as_loaded_ref(), tiles.as_loaded_ref())
+        else {
+            return;
+        };
+        for layer in tile_set.colliders.iter() {
+            for (position, handle) in tiles.iter() {
+                if let Some(tile_collider) = tile_set.get_tile_collider(handle, layer.uuid) {
+                    if !tile_collider.is_none() {
+                        let transformation = tiles.transformation(position);
+                        func(position, transformation, layer.uuid, layer.color, tile_collider);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The plane of the tiles in world coordinates.
+    fn tile_plane(&self) -> Plane {
         let global_transform = self.global_transform();
+        Plane::from_normal_and_point(&global_transform.look(), &global_transform.position())
+            .unwrap_or_default()
+    }
+
+    /// Intersects the given world-space ray with the plane of the tiles and returns the position
+    /// of the cell at the intersection point. The ray is treated as a segment from its origin to
+    /// `origin + dir`, as returned by [`crate::scene::camera::Camera::make_ray`]. Returns `None`
+    /// if the segment does not cross the plane.
+    pub fn ray_to_grid(&self, ray: &Ray) -> Option<Vector2<i32>> {
+        ray.plane_intersection_point(&self.tile_plane())
+            .map(|intersection| self.world_to_grid(intersection))
+    }
+
+    /// Finds the tile hit by the given world-space ray, for example a ray from the camera through
+    /// the mouse cursor. Returns the position of the cell and the handle of its tile, or `None`
+    /// if the ray does not cross the plane of the tiles or there is no tile at the intersection.
+    /// See [`Self::ray_to_grid`] for more info.
+    pub fn pick(&self, ray: &Ray) -> Option<(Vector2<i32>, TileDefinitionHandle)> {
+        let position = self.ray_to_grid(ray)?;
+        let tiles = self.tiles.as_ref()?.data_ref();
+        let handle = tiles.as_loaded_ref()?.get(position)?;
+        Some((position, handle))
+    }
 
+    /// Returns the positions and the handles of all tiles, which quads overlap the given
+    /// world-space bounding box. The box must intersect the plane of the tiles. This could be
+    /// used to find tiles touched by an explosion, a character and so on.
+    pub fn tiles_overlapping_aabb(
+        &self,
+        aabb: &AxisAlignedBoundingBox,
+    ) -> Vec<(Vector2<i32>, TileDefinitionHandle)> {
+        let inv_transform = self.tile_map_transform().try_inverse().unwrap_or_default();
+        let local = aabb.transform(&inv_transform);
+        if local.min.z > 0.0 || local.max.z < 0.0 {
+            return Vec::new();
+        }
+        let (min, max) = (local.min.xy(), local.max.xy());
+        let mut bounds = OptionTileRect::default();
+        for corner in [
+            min,
+            Vector2::new(max.x, min.y),
+            max,
+            Vector2::new(min.x, max.y),
+        ] {
+            bounds.push(self.grid_layout.local_to_cell(corner));
+        }
+        if self.grid_layout.is_hex() {
+            // Quads of hexagonal cells are larger than the cells themselves.
+            bounds = bounds.map(|r| r.inflate(1, 1)).into();
+        }
+        let Some(tiles) = self.tiles.as_ref().map(|r| r.data_ref()) else {
+            return Vec::new();
+        };
+        let Some(tiles) = tiles.as_loaded_ref() else {
+            return Vec::new();
+        };
+        tiles
+            .bounded_iter(bounds)
+            .filter(|(position, _)| {
+                let (cell_min, cell_max) =
+                    self.grid_layout.rect_bounds(*position, Vector2::repeat(1));
+                cell_min.x < max.x && cell_max.x > min.x && cell_min.y < max.y && cell_max.y > min.y
+            })
+            .collect()
+    }
+
+    fn cells_touching_frustum(&self, frustum: &Frustum, offset: Vector3<f32>) -> OptionTileRect {
         fn make_ray(a: Vector3<f32>, b: Vector3<f32>) -> Ray {
             Ray {
                 origin: a,
@@ -1236,9 +1972,7 @@ impl TileMap {
             frustum.right_bottom_back_corner(),
         );
 
-        let plane =
-            Plane::from_normal_and_point(&global_transform.look(), &global_transform.position())
-                .unwrap_or_default();
+        let plane = self.tile_plane();
 
         let Some(left_top) = left_top_ray.plane_intersection_point(&plane) else {
             return None.into();
@@ -1254,7 +1988,11 @@ impl TileMap {
         };
         let mut bounds = OptionTileRect::default();
         for corner in [left_top, right_top, left_bottom, right_bottom] {
-            bounds.push(self.world_to_grid(corner))
+            bounds.push(self.world_to_grid(corner - offset))
+        }
+        if self.grid_layout.is_hex() {
+            // Shifted rows (or columns) may stick out of the bounds by half of a cell.
+            return bounds.map(|r| r.inflate(1, 1)).into();
         }
         bounds
     }
@@ -1266,9 +2004,23 @@ impl Default for TileMap {
             base: Default::default(),
             tile_set: Default::default(),
             tiles: Default::default(),
-            tile_scale: Vector2::repeat(1.0).into(),
+            cell_size: Vector2::repeat(1.0).into(),
+            sort_mode: Default::default(),
+            sort_bias_property: Default::default(),
+            shadow_collider: Default::default(),
+            grid_layout: Default::default(),
+            lod_cell_size: Default::default(),
+            instancing: Default::default(),
+            parallax: Default::default(),
             active_brush: Default::default(),
             hidden_tiles: Mutex::default(),
+            lod_cache: Mutex::default(),
+            instance_cache: Mutex::default(),
+            batch_cache: Mutex::default(),
+            tile_set_revision: Mutex::default(),
+            streaming: None,
+            animation_states: Default::default(),
+            stamps: Default::default(),
             before_effects: Vec::default(),
             after_effects: Vec::default(),
         }
@@ -1281,9 +2033,24 @@ impl Clone for TileMap {
             base: self.base.clone(),
             tile_set: self.tile_set.clone(),
             tiles: self.tiles.clone(),
-            tile_scale: self.tile_scale.clone(),
+            cell_size: self.cell_size.clone(),
+            sort_mode: self.sort_mode.clone(),
+            sort_bias_property: self.sort_bias_property.clone(),
+            shadow_collider: self.shadow_collider.clone(),
+            grid_layout: self.grid_layout.clone(),
+            lod_cell_size: self.lod_cell_size.clone(),
+            instancing: self.instancing.clone(),
+            parallax: self.parallax.clone(),
             active_brush: self.active_brush.clone(),
             hidden_tiles: Mutex::default(),
+            lod_cache: Mutex::default(),
+            instance_cache: Mutex::default(),
+            batch_cache: Mutex::default(),
+            tile_set_revision: Mutex::default(),
+            // A chunk provider cannot be shared by multiple tile maps.
+            streaming: None,
+            animation_states: self.animation_states.clone(),
+            stamps: self.stamps.clone(),
             before_effects: self.before_effects.clone(),
             after_effects: self.after_effects.clone(),
         }
@@ -1322,10 +2089,10 @@ impl NodeTrait for TileMap {
             return AxisAlignedBoundingBox::default();
         };
 
-        let mut min_pos = rect.position.cast::<f32>().to_homogeneous();
-        let mut max_pos = (rect.position + rect.size).cast::<f32>().to_homogeneous();
-        min_pos.x *= -1.0;
-        max_pos.x *= -1.0;
+        let (min_pos, max_pos) = self.grid_layout.rect_bounds(rect.position, rect.size);
+        let scale = Vector2::new(-self.cell_size.x, self.cell_size.y);
+        let min_pos = min_pos.component_mul(&scale).to_homogeneous();
+        let max_pos = max_pos.component_mul(&scale).to_homogeneous();
         let (min, max) = min_pos.inf_sup(&max_pos);
 
         AxisAlignedBoundingBox::from_min_max(min, max)
@@ -1340,8 +2107,39 @@ impl NodeTrait for TileMap {
         Self::type_uuid()
     }
 
+    fn update(&mut self, context: &mut UpdateContext) {
+        self.update_tile_animations(context.dt);
+        self.stamps.update(context.dt);
+        if self.streaming.is_none() {
+            return;
+        }
+        let inv_transform = self.tile_map_transform().try_inverse().unwrap_or_default();
+        let observers = context
+            .nodes
+            .iter()
+            .filter_map(|node| node.cast::<Camera>())
+            .filter(|camera| camera.is_enabled())
+            .map(|camera| {
+                inv_transform
+                    .transform_point(&camera.global_position().into())
+                    .coords
+                    .xy()
+            })
+            .collect::<Vec<_>>();
+        let (Some(streaming), Some(tiles)) = (self.streaming.as_mut(), self.tiles.as_ref()) else {
+            return;
+        };
+        if let Some(tiles) = tiles.data_ref().as_loaded_mut() {
+            streaming.update(tiles, &observers);
+        }
+    }
+
     fn collect_render_data(&self, ctx: &mut RenderContext) -> RdcControlFlow {
-        if !self.should_be_rendered(ctx.frustum) {
+        // The bounding box of a tile map with parallax does not match its rendered tiles, so the
+        // culling is done per cell.
+        let parallax = &*self.parallax;
+        let frustum = ctx.frustum.filter(|_| !parallax.is_enabled());
+        if !self.should_be_rendered(frustum) {
             return RdcControlFlow::Continue;
         }
 
@@ -1356,27 +2154,43 @@ impl NodeTrait for TileMap {
         let mut tile_set_lock = TileSetRef::new(tile_set_resource);
         let tile_set = tile_set_lock.as_loaded();
 
+        // The cached data is built from the tile set, so it must be rebuilt when the tile set is
+        // replaced or reloaded.
+        let tile_set_revision = tile_set.revision();
+        if std::mem::replace(&mut *self.tile_set_revision.lock(), tile_set_revision)
+            != tile_set_revision
+        {
+            self.invalidate_lod();
+        }
+
         let mut hidden_tiles = self.hidden_tiles.lock();
         hidden_tiles.clear();
 
+        let parallax_offset = parallax.offset(ctx.observer_info.observer_position);
         let bounds = ctx
             .frustum
             .as_ref()
-            .map(|f| self.cells_touching_frustum(f))
+            .map(|f| self.cells_touching_frustum(f, parallax_offset))
             .unwrap_or_default();
+        let transform = Matrix4::new_translation(&parallax_offset) * self.tile_map_transform();
 
         let mut tile_render_context = TileMapRenderContext {
             tile_map_handle: self.handle(),
-            transform: self.tile_map_transform(),
+            transform: ctx.snap_to_pixel_grid(transform),
             hidden_tiles: &mut hidden_tiles,
+            tile_tints: Default::default(),
             context: ctx,
             bounds,
+            sort_bias_property: tile_set.property_name_to_uuid(&self.sort_bias_property),
             tile_set,
+            sort_mode: *self.sort_mode,
+            grid_layout: *self.grid_layout,
         };
 
         for effect in self.before_effects.iter() {
             effect.lock().render_special_tiles(&mut tile_render_context);
         }
+        self.stamps.render_before_tiles(&mut tile_render_context);
         let bounds = tile_render_context.visible_bounds();
         let Some(tiles) = self.tiles.as_ref().map(|r| r.data_ref()) else {
             return RdcControlFlow::Continue;
@@ -1384,21 +2198,41 @@ impl NodeTrait for TileMap {
         let Some(tiles) = tiles.as_loaded_ref() else {
             return RdcControlFlow::Continue;
         };
+        if parallax.is_repeated() {
+            if let (Some(visible), Some(content)) = (*bounds, *tiles.bounding_rect()) {
+                self.render_repeated_tiles(&mut tile_render_context, tiles, visible, content);
+                self.stamps.render_after_tiles(&mut tile_render_context);
+                for effect in self.after_effects.iter() {
+                    effect.lock().render_special_tiles(&mut tile_render_context);
+                }
+                return RdcControlFlow::Continue;
+            }
+        }
+        // Tiles of the chunks, that were rendered as a whole, are skipped.
+        let mut rendered_chunks = self.render_lod_chunks(&mut tile_render_context, tiles);
+        self.render_instanced_chunks(&mut tile_render_context, tiles, &mut rendered_chunks);
+        self.render_batched_chunks(&mut tile_render_context, tiles, &mut rendered_chunks);
         if bounds.is_some() {
             for (position, handle) in tiles.bounded_iter(bounds) {
-                if bounds.contains(position) && tile_render_context.is_tile_visible(position) {
-                    let handle = tile_render_context.get_animated_version(handle);
-                    tile_render_context.draw_tile(position, handle);
+                if bounds.contains(position)
+                    && tile_render_context.is_tile_visible(position)
+                    && !Self::is_in_lod_chunk(&rendered_chunks, position)
+                {
+                    let handle = self.get_animated_version(&tile_render_context, position, handle);
+                    let transformation = tiles.transformation(position);
+                    tile_render_context.draw_transformed_tile(position, handle, transformation);
                 }
             }
         } else {
             for (position, handle) in tiles.iter() {
                 if tile_render_context.is_tile_visible(position) {
-                    let handle = tile_render_context.get_animated_version(handle);
-                    tile_render_context.draw_tile(position, handle);
+                    let handle = self.get_animated_version(&tile_render_context, position, handle);
+                    let transformation = tiles.transformation(position);
+                    tile_render_context.draw_transformed_tile(position, handle, transformation);
                 }
             }
         }
+        self.stamps.render_after_tiles(&mut tile_render_context);
         for effect in self.after_effects.iter() {
             effect.lock().render_special_tiles(&mut tile_render_context);
         }
@@ -1422,7 +2256,14 @@ pub struct TileMapBuilder {
     base_builder: BaseBuilder,
     tile_set: Option<TileSetResource>,
     tiles: TileMapData,
-    tile_scale: Vector2<f32>,
+    cell_size: Vector2<f32>,
+    sort_mode: SortMode2D,
+    sort_bias_property: ImmutableString,
+    shadow_collider: ImmutableString,
+    grid_layout: TileGridLayout,
+    lod_cell_size: f32,
+    instancing: bool,
+    parallax: TileMapParallax,
     before_effects: Vec<TileMapEffectRef>,
     after_effects: Vec<TileMapEffectRef>,
 }
@@ -1434,7 +2275,14 @@ impl TileMapBuilder {
             base_builder,
             tile_set: None,
             tiles: TileMapData::default(),
-            tile_scale: Vector2::repeat(1.0),
+            cell_size: Vector2::repeat(1.0),
+            sort_mode: Default::default(),
+            sort_bias_property: Default::default(),
+            shadow_collider: Default::default(),
+            grid_layout: Default::default(),
+            lod_cell_size: 0.0,
+            instancing: false,
+            parallax: Default::default(),
             before_effects: Default::default(),
             after_effects: Default::default(),
         }
@@ -1454,9 +2302,63 @@ impl TileMapBuilder {
         self
     }
 
+    /// Sets the size of a cell. See [`TileMap::set_cell_size`] for more info.
+    pub fn with_cell_size(mut self, cell_size: Vector2<f32>) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
     /// Sets the actual tile scaling.
-    pub fn with_tile_scale(mut self, tile_scale: Vector2<f32>) -> Self {
-        self.tile_scale = tile_scale;
+    #[deprecated = "tile scale is the size of a cell, use with_cell_size instead"]
+    pub fn with_tile_scale(self, tile_scale: Vector2<f32>) -> Self {
+        self.with_cell_size(tile_scale)
+    }
+
+    /// Sets the desired sorting mode of the tile map. See [`TileMap::set_sort_mode`] for more info.
+    pub fn with_sort_mode(mut self, sort_mode: SortMode2D) -> Self {
+        self.sort_mode = sort_mode;
+        self
+    }
+
+    /// Sets the desired sort bias property layer of the tile map. See
+    /// [`TileMap::set_sort_bias_property`] for more info.
+    pub fn with_sort_bias_property(mut self, name: ImmutableString) -> Self {
+        self.sort_bias_property = name;
+        self
+    }
+
+    /// Sets the desired shadow collider layer of the tile map. See [`TileMap::set_shadow_collider`]
+    /// for more info.
+    pub fn with_shadow_collider(mut self, name: ImmutableString) -> Self {
+        self.shadow_collider = name;
+        self
+    }
+
+    /// Sets the desired layout of the cells of the tile map. See [`TileMap::set_grid_layout`] for
+    /// more info.
+    pub fn with_grid_layout(mut self, layout: TileGridLayout) -> Self {
+        self.grid_layout = layout;
+        self
+    }
+
+    /// Sets the desired level of detail cell size of the tile map. See
+    /// [`TileMap::set_lod_cell_size`] for more info.
+    pub fn with_lod_cell_size(mut self, size: f32) -> Self {
+        self.lod_cell_size = size;
+        self
+    }
+
+    /// Enables or disables instanced rendering of the tile map. See [`TileMap::set_instancing`]
+    /// for more info.
+    pub fn with_instancing(mut self, instancing: bool) -> Self {
+        self.instancing = instancing;
+        self
+    }
+
+    /// Sets the desired parallax scrolling of the tile map. See [`TileMap::set_parallax`] for
+    /// more info.
+    pub fn with_parallax(mut self, parallax: TileMapParallax) -> Self {
+        self.parallax = parallax;
         self
     }
 
@@ -1478,9 +2380,23 @@ impl TileMapBuilder {
             base: self.base_builder.build_base(),
             tile_set: self.tile_set.into(),
             tiles: Some(Resource::new_ok(ResourceKind::Embedded, self.tiles)).into(),
-            tile_scale: self.tile_scale.into(),
+            cell_size: self.cell_size.into(),
+            sort_mode: self.sort_mode.into(),
+            sort_bias_property: self.sort_bias_property.into(),
+            shadow_collider: self.shadow_collider.into(),
+            grid_layout: self.grid_layout.into(),
+            lod_cell_size: self.lod_cell_size.max(0.0).into(),
+            instancing: self.instancing.into(),
+            parallax: self.parallax.into(),
             active_brush: Default::default(),
             hidden_tiles: Mutex::default(),
+            lod_cache: Mutex::default(),
+            instance_cache: Mutex::default(),
+            batch_cache: Mutex::default(),
+            tile_set_revision: Mutex::default(),
+            streaming: None,
+            animation_states: Default::default(),
+            stamps: Default::default(),
             before_effects: self.before_effects,
             after_effects: self.after_effects,
         })
@@ -1491,3 +2407,95 @@ impl TileMapBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scene::tilemap::test_fixture::{make_tile_map, make_tile_set, FLOOR, WALL};
+
+    fn v(x: i32, y: i32) -> Vector2<i32> {
+        Vector2::new(x, y)
+    }
+
+    /// Tiles on both sides of the boundary between two chunks, plus a tile far away from it.
+    fn make_test_tile_map() -> TileMap {
+        make_tile_map(
+            make_tile_set(|_| Default::default()),
+            [
+                (v(2, 3), FLOOR),
+                (v(15, 0), FLOOR),
+                (v(16, 0), WALL),
+                (v(20, 0), WALL),
+            ],
+        )
+    }
+
+    /// A vertical ray through the center of the given cell. The x axis of tile maps is mirrored.
+    fn ray_through_cell(x: f32, y: f32) -> Ray {
+        Ray::from_two_points(
+            Vector3::new(-x - 0.5, y + 0.5, 5.0),
+            Vector3::new(-x - 0.5, y + 0.5, -5.0),
+        )
+    }
+
+    fn sorted(
+        mut tiles: Vec<(Vector2<i32>, TileDefinitionHandle)>,
+    ) -> Vec<(Vector2<i32>, TileDefinitionHandle)> {
+        tiles.sort_by_key(|(position, _)| (position.x, position.y));
+        tiles
+    }
+
+    #[test]
+    fn test_pick_hit() {
+        let tile_map = make_test_tile_map();
+        assert_eq!(
+            tile_map.pick(&ray_through_cell(2.0, 3.0)),
+            Some((v(2, 3), FLOOR))
+        );
+        assert_eq!(
+            tile_map.pick(&ray_through_cell(16.0, 0.0)),
+            Some((v(16, 0), WALL))
+        );
+    }
+
+    #[test]
+    fn test_pick_miss() {
+        let tile_map = make_test_tile_map();
+        // There is no tile in the cell.
+        assert_eq!(tile_map.pick(&ray_through_cell(3.0, 3.0)), None);
+        // The ray ends before it reaches the plane of the tiles.
+        let ray = Ray::from_two_points(Vector3::new(-2.5, 3.5, 5.0), Vector3::new(-2.5, 3.5, 1.0));
+        assert_eq!(tile_map.pick(&ray), None);
+    }
+
+    #[test]
+    fn test_tiles_overlapping_aabb_across_chunks() {
+        let tile_map = make_test_tile_map();
+        // Cells 15 and 16 belong to different chunks.
+        let aabb = AxisAlignedBoundingBox::from_min_max(
+            Vector3::new(-16.5, 0.25, -1.0),
+            Vector3::new(-15.5, 0.75, 1.0),
+        );
+        assert_eq!(
+            sorted(tile_map.tiles_overlapping_aabb(&aabb)),
+            vec![(v(15, 0), FLOOR), (v(16, 0), WALL)]
+        );
+    }
+
+    #[test]
+    fn test_tiles_overlapping_aabb_miss() {
+        let tile_map = make_test_tile_map();
+        // There are no tiles under the box.
+        let aabb = AxisAlignedBoundingBox::from_min_max(
+            Vector3::new(-10.0, 5.0, -1.0),
+            Vector3::new(-5.0, 10.0, 1.0),
+        );
+        assert!(tile_map.tiles_overlapping_aabb(&aabb).is_empty());
+        // The box is above the plane of the tiles.
+        let aabb = AxisAlignedBoundingBox::from_min_max(
+            Vector3::new(-16.5, 0.25, 1.0),
+            Vector3::new(-15.5, 0.75, 2.0),
+        );
+        assert!(tile_map.tiles_overlapping_aabb(&aabb).is_empty());
+    }
+}
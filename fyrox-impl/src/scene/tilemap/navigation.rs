@@ -0,0 +1,397 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Navigation grids for tile maps. See [`TileMapNavGrid`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        uuid::Uuid,
+    },
+    utils::astar::{Graph, GraphVertex},
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use super::*;
+
+/// A navigation grid of a tile map, that is built from a tile property, which defines the cost of
+/// entering the cells of the tile map. Cells, whose tiles have a positive value of the property
+/// (`I32` or `F32`), are walkable. The value is the cost of entering the cell, values less than
+/// one are treated as one, so the cheapest cells cost one. Empty cells and the cells with zero or
+/// negative value are blocked. Neighbors of the cells are defined by the
+/// [`TileGridLayout`] of the tile map.
+///
+/// The grid is built per chunk of the tile map, and [`Self::update`] rebuilds only the chunks
+/// whose tiles were changed since the last update, so it is cheap to call it every frame.
+/// Changes of the tile set are not tracked, a new grid must be built after such changes.
+///
+/// ```rust
+/// # use fyrox_impl::{core::{algebra::Vector2, uuid::Uuid}, scene::tilemap::TileMap};
+/// fn find_path(tile_map: &TileMap, walkable: Uuid) -> Option<Vec<Vector2<i32>>> {
+///     let grid = tile_map.build_nav_grid(walkable);
+///     grid.find_path(Vector2::new(0, 0), Vector2::new(10, 5))
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TileMapNavGrid {
+    property_id: Uuid,
+    grid_layout: TileGridLayout,
+    /// The key of the tile set resource that was used to build the grid.
+    tile_set: Option<u64>,
+    /// The costs of entering the walkable cells.
+    costs: FxHashMap<Vector2<i32>, f32>,
+    /// The revisions of the chunks of the tile map, that were used to build the grid.
+    revisions: FxHashMap<Vector2<i32>, u64>,
+}
+
+/// A cell in the open set of the path finder.
+struct OpenCell {
+    cell: Vector2<i32>,
+    f_score: f32,
+}
+
+impl PartialEq for OpenCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenCell {}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenCell {
+    /// Reversed, so the binary heap returns the cell with the lowest score first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.f_score.total_cmp(&other.f_score).reverse()
+    }
+}
+
+fn cell_cost(
+    tile_set: &OptionTileSet,
+    handle: TileDefinitionHandle,
+    property_id: Uuid,
+) -> Option<f32> {
+    let cost = match tile_set.property_value(handle, property_id)? {
+        TileSetPropertyValue::I32(value) => value as f32,
+        TileSetPropertyValue::F32(value) => value,
+        _ => return None,
+    };
+    (cost > 0.0).then(|| cost.max(1.0))
+}
+
+impl TileMapNavGrid {
+    /// Creates a new empty navigation grid for the tile property with the given UUID. Use
+    /// [`Self::update`] to fill the grid with the cells of a tile map.
+    pub fn new(property_id: Uuid) -> Self {
+        Self {
+            property_id,
+            ..Default::default()
+        }
+    }
+
+    /// The UUID of the tile property that defines the walkable cells.
+    pub fn property_id(&self) -> Uuid {
+        self.property_id
+    }
+
+    /// The layout of the cells of the grid.
+    pub fn grid_layout(&self) -> TileGridLayout {
+        self.grid_layout
+    }
+
+    /// True if the given cell is walkable.
+    pub fn is_walkable(&self, cell: Vector2<i32>) -> bool {
+        self.costs.contains_key(&cell)
+    }
+
+    /// The cost of entering the given cell, or `None` if the cell is blocked.
+    pub fn cost(&self, cell: Vector2<i32>) -> Option<f32> {
+        self.costs.get(&cell).copied()
+    }
+
+    /// Iterates over the walkable cells of the grid in arbitrary order.
+    pub fn walkable_cells(&self) -> impl Iterator<Item = Vector2<i32>> + '_ {
+        self.costs.keys().copied()
+    }
+
+    /// Iterates over the walkable neighbors of the given cell.
+    pub fn walkable_neighbors(
+        &self,
+        cell: Vector2<i32>,
+    ) -> impl Iterator<Item = Vector2<i32>> + '_ {
+        self.grid_layout
+            .neighbors(cell)
+            .filter(|n| self.is_walkable(*n))
+    }
+
+    /// Removes every cell from the grid.
+    pub fn clear(&mut self) {
+        self.costs.clear();
+        self.revisions.clear();
+    }
+
+    /// Updates the grid to match the tiles of the given tile map. Only the chunks of the tile map,
+    /// that were changed since the last update, are processed. The grid is rebuilt completely if
+    /// the tile set or the layout of the tile map were changed. Returns true if the grid was
+    /// changed.
+    pub fn update(&mut self, tile_map: &TileMap) -> bool {
+        let tile_set_key = tile_map.tile_set().map(|t| t.key());
+        let mut changed = false;
+        if self.tile_set != tile_set_key || self.grid_layout != tile_map.grid_layout() {
+            changed = !self.costs.is_empty();
+            self.clear();
+            self.tile_set = tile_set_key;
+            self.grid_layout = tile_map.grid_layout();
+        }
+        let Some(tiles) = tile_map.tiles() else {
+            changed |= !self.costs.is_empty();
+            self.clear();
+            return changed;
+        };
+        let tiles = tiles.data_ref();
+        let Some(tiles) = tiles.as_loaded_ref() else {
+            return changed;
+        };
+        let Some(tile_set) = tile_map.tile_set() else {
+            return changed;
+        };
+        let mut tile_set = TileSetRef::new(tile_set);
+        let tile_set = tile_set.as_loaded();
+
+        let chunks = tiles.chunks().collect::<Vec<_>>();
+        let present = chunks
+            .iter()
+            .map(|(rect, _)| rect.position)
+            .collect::<FxHashSet<_>>();
+        let removed = self
+            .revisions
+            .keys()
+            .filter(|position| !present.contains(position))
+            .copied()
+            .collect::<Vec<_>>();
+        for position in removed {
+            self.revisions.remove(&position);
+            for cell in chunk_rect(position).iter() {
+                changed |= self.costs.remove(&cell).is_some();
+            }
+        }
+
+        for (rect, revision) in chunks {
+            if self.revisions.get(&rect.position) == Some(&revision) {
+                continue;
+            }
+            for cell in rect.iter() {
+                changed |= self.costs.remove(&cell).is_some();
+            }
+            for (position, handle) in tiles.bounded_iter(rect.into()) {
+                if let Some(cost) = cell_cost(&tile_set, handle, self.property_id) {
+                    self.costs.insert(position, cost);
+                    changed = true;
+                }
+            }
+            self.revisions.insert(rect.position, revision);
+        }
+        changed
+    }
+
+    /// Finds the cheapest path between the given cells using A* algorithm. The path includes both
+    /// cells, consecutive cells of the path are neighbors. Returns `None` if one of the cells is
+    /// blocked or if there is no path between them.
+    pub fn find_path(&self, from: Vector2<i32>, to: Vector2<i32>) -> Option<Vec<Vector2<i32>>> {
+        if !self.is_walkable(from) || !self.is_walkable(to) {
+            return None;
+        }
+        // Every cell costs at least one, so the number of steps never overestimates the cost.
+        let heuristic = |cell: Vector2<i32>| self.grid_layout.distance(cell, to) as f32;
+        let mut open = BinaryHeap::new();
+        let mut came_from = FxHashMap::default();
+        let mut g_scores = FxHashMap::default();
+        g_scores.insert(from, 0.0);
+        open.push(OpenCell {
+            cell: from,
+            f_score: heuristic(from),
+        });
+        while let Some(OpenCell { cell, f_score }) = open.pop() {
+            let g_score = g_scores[&cell];
+            if cell == to {
+                let mut path = vec![to];
+                let mut current = to;
+                while let Some(previous) = came_from.get(&current) {
+                    current = *previous;
+                    path.push(current);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            // Skip outdated entries of the cells, that were reached by a cheaper path later.
+            if f_score > g_score + heuristic(cell) {
+                continue;
+            }
+            for neighbor in self.grid_layout.neighbors(cell) {
+                let Some(cost) = self.cost(neighbor) else {
+                    continue;
+                };
+                let neighbor_g_score = g_score + cost;
+                if g_scores
+                    .get(&neighbor)
+                    .is_some_and(|score| *score <= neighbor_g_score)
+                {
+                    continue;
+                }
+                g_scores.insert(neighbor, neighbor_g_score);
+                came_from.insert(neighbor, cell);
+                open.push(OpenCell {
+                    cell: neighbor,
+                    f_score: neighbor_g_score + heuristic(neighbor),
+                });
+            }
+        }
+        None
+    }
+
+    /// Converts the grid into a graph for [`crate::utils::astar`] path finder. Vertices of the
+    /// graph are the centers of the walkable cells in the local coordinates of the tile map, the
+    /// penalties of the vertices are the costs of the cells. Returns the graph along with the
+    /// cells of its vertices.
+    pub fn to_graph(&self) -> (Graph<GraphVertex>, Vec<Vector2<i32>>) {
+        let mut cells = self.walkable_cells().collect::<Vec<_>>();
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+        let indices = cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| (*cell, index as u32))
+            .collect::<FxHashMap<_, _>>();
+        let vertices = cells
+            .iter()
+            .map(|cell| {
+                let center = self.grid_layout.cell_center(*cell);
+                let mut vertex = GraphVertex::new(Vector3::new(center.x, center.y, 0.0));
+                vertex.g_penalty = self.costs[cell];
+                vertex.neighbours = self
+                    .walkable_neighbors(*cell)
+                    .map(|neighbor| indices[&neighbor])
+                    .collect();
+                vertex
+            })
+            .collect();
+        let mut graph = Graph::new();
+        graph.set_vertices(vertices);
+        (graph, cells)
+    }
+}
+
+impl TileMap {
+    /// Builds a navigation grid from the tile property with the given UUID. See
+    /// [`TileMapNavGrid`] docs for more info. Use [`TileMapNavGrid::update`] to keep the grid in
+    /// sync with the tiles of the tile map.
+    pub fn build_nav_grid(&self, property_id: Uuid) -> TileMapNavGrid {
+        let mut grid = TileMapNavGrid::new(property_id);
+        grid.update(self);
+        grid
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scene::tilemap::test_fixture::{self, data_with_property, FLOOR, WALL};
+
+    const WALKABLE: Uuid = Uuid::from_u128(0x1234);
+
+    fn v(x: i32, y: i32) -> Vector2<i32> {
+        Vector2::new(x, y)
+    }
+
+    fn make_tile_map() -> TileMap {
+        let tile_set = test_fixture::make_tile_set(|handle| {
+            let value = if handle == FLOOR { 1 } else { 0 };
+            data_with_property(WALKABLE, TileSetPropertyValue::I32(value))
+        });
+        let tiles = (0..3)
+            .flat_map(|y| (0..3).map(move |x| (v(x, y), FLOOR)))
+            .chain([(v(1, 0), WALL), (v(1, 1), WALL)]);
+        test_fixture::make_tile_map(tile_set, tiles)
+    }
+
+    #[test]
+    fn test_find_path() {
+        let tile_map = make_tile_map();
+        let grid = tile_map.build_nav_grid(WALKABLE);
+        assert!(grid.is_walkable(v(0, 0)));
+        assert!(!grid.is_walkable(v(1, 0)));
+        assert!(!grid.is_walkable(v(5, 5)));
+        let path = grid.find_path(v(0, 0), v(2, 0)).unwrap();
+        assert_eq!(
+            path,
+            vec![
+                v(0, 0),
+                v(0, 1),
+                v(0, 2),
+                v(1, 2),
+                v(2, 2),
+                v(2, 1),
+                v(2, 0)
+            ]
+        );
+        assert!(grid.find_path(v(0, 0), v(1, 0)).is_none());
+        assert_eq!(grid.find_path(v(0, 0), v(0, 0)).unwrap(), vec![v(0, 0)]);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut tile_map = make_tile_map();
+        let mut grid = tile_map.build_nav_grid(WALKABLE);
+        assert!(!grid.update(&tile_map));
+
+        // Block the only passage.
+        tile_map.insert_tile(v(1, 2), WALL);
+        assert!(grid.update(&tile_map));
+        assert!(!grid.is_walkable(v(1, 2)));
+        assert!(grid.find_path(v(0, 0), v(2, 0)).is_none());
+
+        // A new chunk far away.
+        tile_map.insert_tile(v(100, 100), FLOOR);
+        assert!(grid.update(&tile_map));
+        assert!(grid.is_walkable(v(100, 100)));
+    }
+
+    #[test]
+    fn test_to_graph() {
+        let grid = make_tile_map().build_nav_grid(WALKABLE);
+        let (graph, cells) = grid.to_graph();
+        assert_eq!(graph.vertices.len(), 7);
+        let index = |cell| cells.iter().position(|c| *c == cell).unwrap();
+        let vertex = graph.vertex(index(v(0, 1))).unwrap();
+        assert_eq!(vertex.position, Vector3::new(0.5, 1.5, 0.0));
+        let mut neighbours = vertex
+            .neighbours
+            .iter()
+            .map(|i| cells[*i as usize])
+            .collect::<Vec<_>>();
+        neighbours.sort_by_key(|cell| (cell.y, cell.x));
+        assert_eq!(neighbours, vec![v(0, 0), v(0, 2)]);
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Parallax scrolling of tile maps. See [`TileMapParallax`] docs for more info.
+
+use crate::core::{
+    algebra::{Vector2, Vector3},
+    reflect::prelude::*,
+    visitor::prelude::*,
+};
+
+use super::*;
+
+/// Parallax scrolling of a tile map. Background layers of a 2D scene could be made of tile maps,
+/// that move slower than the camera, which creates an illusion of depth. The offset is applied
+/// only when the tile map is rendered, so [`TileMap::world_to_grid`] and other methods of the
+/// tile map work with the tiles as if there is no parallax.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct TileMapParallax {
+    /// The fraction of the camera movement, that the tile map follows. `(1.0, 1.0)` means that
+    /// the tile map stays in place (no parallax), `(0.0, 0.0)` means that the tile map moves
+    /// along with the camera, so it looks like it is infinitely far away.
+    pub factor: Vector2<f32>,
+    /// If true, the tiles of the tile map are repeated infinitely along the X axis. The period of
+    /// the repetition is the width of the bounding rectangle of the tiles.
+    pub repeat_x: bool,
+    /// If true, the tiles of the tile map are repeated infinitely along the Y axis. The period of
+    /// the repetition is the height of the bounding rectangle of the tiles.
+    pub repeat_y: bool,
+}
+
+impl Default for TileMapParallax {
+    fn default() -> Self {
+        Self {
+            factor: Vector2::repeat(1.0),
+            repeat_x: false,
+            repeat_y: false,
+        }
+    }
+}
+
+impl TileMapParallax {
+    /// True if the tile map is rendered differently from the tiles of the tile map.
+    pub fn is_enabled(&self) -> bool {
+        self.factor != Vector2::repeat(1.0) || self.is_repeated()
+    }
+
+    /// True if the tiles are repeated along any of the axes.
+    pub fn is_repeated(&self) -> bool {
+        self.repeat_x || self.repeat_y
+    }
+
+    /// The world-space offset of the tile map, when it is seen by an observer at the given
+    /// position.
+    pub fn offset(&self, observer_position: Vector3<f32>) -> Vector3<f32> {
+        Vector3::new(
+            observer_position.x * (1.0 - self.factor.x),
+            observer_position.y * (1.0 - self.factor.y),
+            0.0,
+        )
+    }
+
+    /// The cells, where the tiles should be rendered, when the given cells are visible and the
+    /// tiles of the tile map occupy the given content rectangle.
+    pub fn repeat_bounds(&self, visible: TileRect, content: TileRect) -> OptionTileRect {
+        let (x0, x1) = repeat_range(
+            self.repeat_x,
+            visible.x(),
+            visible.w(),
+            content.x(),
+            content.w(),
+        );
+        let (y0, y1) = repeat_range(
+            self.repeat_y,
+            visible.y(),
+            visible.h(),
+            content.y(),
+            content.h(),
+        );
+        if x1 <= x0 || y1 <= y0 {
+            return None.into();
+        }
+        Some(TileRect::new(x0, y0, x1 - x0, y1 - y0)).into()
+    }
+
+    /// The position of the cell, which tile is rendered at the given position, when the tiles of
+    /// the tile map occupy the given content rectangle. Keep in mind, that the shifted rows (or
+    /// columns) of hexagonal layouts repeat correctly only if the size of the content is even
+    /// along the corresponding axis.
+    pub fn source_position(&self, position: Vector2<i32>, content: TileRect) -> Vector2<i32> {
+        let mut source = position;
+        if self.repeat_x && content.w() > 0 {
+            source.x = content.x() + (position.x - content.x()).rem_euclid(content.w());
+        }
+        if self.repeat_y && content.h() > 0 {
+            source.y = content.y() + (position.y - content.y()).rem_euclid(content.h());
+        }
+        source
+    }
+}
+
+fn repeat_range(
+    repeat: bool,
+    visible: i32,
+    visible_size: i32,
+    content: i32,
+    content_size: i32,
+) -> (i32, i32) {
+    if repeat {
+        (visible, visible + visible_size)
+    } else {
+        (
+            visible.max(content),
+            (visible + visible_size).min(content + content_size),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_offset() {
+        let parallax = TileMapParallax {
+            factor: Vector2::new(0.5, 1.0),
+            ..Default::default()
+        };
+        assert!(parallax.is_enabled());
+        assert_eq!(
+            parallax.offset(Vector3::new(10.0, 4.0, 2.0)),
+            Vector3::new(5.0, 0.0, 0.0)
+        );
+        assert!(!TileMapParallax::default().is_enabled());
+    }
+
+    #[test]
+    fn test_repeat() {
+        let parallax = TileMapParallax {
+            repeat_x: true,
+            ..Default::default()
+        };
+        let content = TileRect::new(2, 0, 3, 2);
+        assert_eq!(
+            parallax.source_position(Vector2::new(-1, 5), content),
+            Vector2::new(2, 5)
+        );
+        assert_eq!(
+            parallax.source_position(Vector2::new(5, 1), content),
+            Vector2::new(2, 1)
+        );
+        assert_eq!(
+            *parallax.repeat_bounds(TileRect::new(-10, -10, 20, 20), content),
+            Some(TileRect::new(-10, 0, 20, 2))
+        );
+        assert_eq!(
+            *parallax.repeat_bounds(TileRect::new(-10, 5, 20, 20), content),
+            None
+        );
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Region queries over the properties of the tiles of a tile map. See [`TilePropertyQuery`] docs
+//! for more info.
+
+use crate::{
+    asset::ResourceDataRef,
+    core::{algebra::Vector2, uuid::Uuid},
+};
+
+use super::*;
+
+/// Locked tile set and tiles of a tile map, that allows to scan the property values of lots of
+/// tiles at once, for example to find hazards or spawn points. Unlike
+/// [`TileMap::tile_property_value`], which locks the tile set for every tile, the resources are
+/// locked once, when the query is created by [`TileMap::property_query`], and stay locked until
+/// the query is dropped.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::uuid::Uuid,
+/// #     scene::tilemap::{tileset::TileSetPropertyValue, TileMap},
+/// # };
+/// fn find_spawn_points(tile_map: &TileMap, spawn_point: Uuid) {
+///     let Ok(query) = tile_map.property_query() else {
+///         return;
+///     };
+///     for (position, _) in query.tiles_with_property(spawn_point, |value| {
+///         *value == TileSetPropertyValue::I32(1)
+///     }) {
+///         println!("Spawn point at {position}");
+///     }
+/// }
+/// ```
+pub struct TilePropertyQuery<'a> {
+    tile_set: ResourceDataRef<'a, TileSet>,
+    tiles: Option<ResourceDataRef<'a, TileMapData>>,
+}
+
+impl TilePropertyQuery<'_> {
+    fn value(
+        &self,
+        handle: TileDefinitionHandle,
+        property_id: Uuid,
+    ) -> Option<TileSetPropertyValue> {
+        self.tile_set
+            .as_loaded_ref()?
+            .property_value(handle, property_id)
+    }
+
+    fn tiles(&self) -> Option<&TileMapData> {
+        self.tiles.as_ref()?.as_loaded_ref()
+    }
+
+    /// Iterates over the tiles, which value of the property with the given UUID satisfies the
+    /// given predicate, in the form of (position, value) pairs. Tiles without an explicit value
+    /// of the property have the default value of the property type.
+    pub fn tiles_with_property<'b, F>(
+        &'b self,
+        property_id: Uuid,
+        mut predicate: F,
+    ) -> impl Iterator<Item = (Vector2<i32>, TileSetPropertyValue)> + 'b
+    where
+        F: FnMut(&TileSetPropertyValue) -> bool + 'b,
+    {
+        self.tiles()
+            .into_iter()
+            .flat_map(|tiles| tiles.iter())
+            .filter_map(move |(position, handle)| {
+                let value = self.value(handle, property_id)?;
+                predicate(&value).then_some((position, value))
+            })
+    }
+
+    /// Iterates over the tiles within the given rect in the form of (position, value) pairs, where
+    /// value is the value of the property with the given UUID. Empty cells are skipped.
+    pub fn property_values_in_rect(
+        &self,
+        rect: TileRect,
+        property_id: Uuid,
+    ) -> impl Iterator<Item = (Vector2<i32>, TileSetPropertyValue)> + '_ {
+        self.tiles()
+            .into_iter()
+            .flat_map(move |tiles| tiles.bounded_iter(rect.into()))
+            .filter_map(move |(position, handle)| {
+                Some((position, self.value(handle, property_id)?))
+            })
+    }
+}
+
+impl TileMap {
+    /// Locks the tile set and the tiles of the tile map for scanning the property values of
+    /// multiple tiles. See [`TilePropertyQuery`] docs for more info.
+    pub fn property_query(&self) -> Result<TilePropertyQuery, TilePropertyError> {
+        let tile_set = self
+            .tile_set
+            .as_ref()
+            .ok_or(TilePropertyError::MissingTileSet)?
+            .data_ref();
+        if tile_set.as_loaded_ref().is_none() {
+            return Err(TilePropertyError::TileSetNotLoaded);
+        }
+        Ok(TilePropertyQuery {
+            tile_set,
+            tiles: self.tiles.as_ref().map(|tiles| tiles.data_ref()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scene::tilemap::test_fixture::{self, data_with_property, FLOOR, WALL};
+
+    const HAZARD: Uuid = Uuid::from_u128(0x4321);
+
+    fn v(x: i32, y: i32) -> Vector2<i32> {
+        Vector2::new(x, y)
+    }
+
+    fn make_tile_map() -> TileMap {
+        let tile_set = test_fixture::make_tile_set(|handle| {
+            let value = if handle == WALL { 5 } else { 0 };
+            data_with_property(HAZARD, TileSetPropertyValue::I32(value))
+        });
+        // Walls are lava here.
+        let tiles = (0..4)
+            .map(|x| (v(x, 0), FLOOR))
+            .chain([(v(1, 0), WALL), (v(30, 0), WALL)]);
+        test_fixture::make_tile_map(tile_set, tiles)
+    }
+
+    #[test]
+    fn test_tiles_with_property() {
+        let tile_map = make_tile_map();
+        let query = tile_map.property_query().unwrap();
+        let mut hazards = query
+            .tiles_with_property(HAZARD, |value| *value != TileSetPropertyValue::I32(0))
+            .map(|(position, _)| position)
+            .collect::<Vec<_>>();
+        hazards.sort_by_key(|p| p.x);
+        assert_eq!(hazards, vec![v(1, 0), v(30, 0)]);
+    }
+
+    #[test]
+    fn test_property_values_in_rect() {
+        let tile_map = make_tile_map();
+        let query = tile_map.property_query().unwrap();
+        let mut values = query
+            .property_values_in_rect(TileRect::new(1, 0, 2, 1), HAZARD)
+            .collect::<Vec<_>>();
+        values.sort_by_key(|(p, _)| p.x);
+        assert_eq!(
+            values,
+            vec![
+                (v(1, 0), TileSetPropertyValue::I32(5)),
+                (v(2, 0), TileSetPropertyValue::I32(0)),
+            ]
+        );
+        assert!(TileMap::default().property_query().is_err());
+    }
+}
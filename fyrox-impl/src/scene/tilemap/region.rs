@@ -0,0 +1,329 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Connectivity analysis of tiles, that finds connected regions of tiles, islands and the
+//! boundaries of the regions. See [`TileConnectedRegion`] docs for more info.
+
+use crate::core::{algebra::Vector2, uuid::Uuid};
+use fxhash::{FxHashMap, FxHashSet};
+
+use super::*;
+
+const NEIGHBORS: [Vector2<i32>; 4] = [
+    Vector2::new(1, 0),
+    Vector2::new(0, 1),
+    Vector2::new(-1, 0),
+    Vector2::new(0, -1),
+];
+
+/// The corners of a cell, such that the side from the corner `i` to the corner `i + 1` is shared
+/// with the neighbor `i` of [`NEIGHBORS`], and the cell is on the left of the side.
+const CORNERS: [Vector2<i32>; 4] = [
+    Vector2::new(1, 0),
+    Vector2::new(1, 1),
+    Vector2::new(0, 1),
+    Vector2::new(0, 0),
+];
+
+/// A set of cells, where every cell can be reached from any other cell by moving between the
+/// cells that share a side. Regions are found by flood labeling of the cells that satisfy some
+/// condition, see [`Tiles::connected_regions`] and [`TilePropertyQuery::regions_with_property`].
+/// Regions are useful for territory detection, finding bodies of water, and generating outline
+/// meshes, see [`Self::boundary`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TileConnectedRegion {
+    cells: FxHashSet<Vector2<i32>>,
+}
+
+impl TileConnectedRegion {
+    /// Splits the given cells into connected regions. Regions are sorted by their lowest cell,
+    /// with ties broken by the lowest x coordinate.
+    pub fn find_regions<I: IntoIterator<Item = Vector2<i32>>>(cells: I) -> Vec<Self> {
+        let mut remaining = cells.into_iter().collect::<FxHashSet<_>>();
+        let mut starts = remaining.iter().copied().collect::<Vec<_>>();
+        starts.sort_unstable_by_key(|p| (p.y, p.x));
+        let mut regions = Vec::new();
+        let mut stack = Vec::new();
+        for start in starts {
+            if !remaining.remove(&start) {
+                continue;
+            }
+            let mut region = Self::default();
+            stack.push(start);
+            while let Some(position) = stack.pop() {
+                region.cells.insert(position);
+                for offset in NEIGHBORS {
+                    let neighbor = position + offset;
+                    if remaining.remove(&neighbor) {
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            regions.push(region);
+        }
+        regions
+    }
+
+    /// The cells of the region.
+    pub fn cells(&self) -> &FxHashSet<Vector2<i32>> {
+        &self.cells
+    }
+
+    /// The number of cells in the region.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// True if the region has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// True if the given cell belongs to the region.
+    pub fn contains(&self, position: Vector2<i32>) -> bool {
+        self.cells.contains(&position)
+    }
+
+    /// The smallest rect that contains all the cells of the region.
+    pub fn bounding_rect(&self) -> OptionTileRect {
+        let mut result = OptionTileRect::default();
+        for position in self.cells.iter() {
+            result.push(*position);
+        }
+        result
+    }
+
+    /// The cells outside of the region, that share a side with the cells of the region.
+    pub fn neighbors(&self) -> FxHashSet<Vector2<i32>> {
+        self.cells
+            .iter()
+            .flat_map(|position| NEIGHBORS.map(|offset| position + offset))
+            .filter(|neighbor| !self.cells.contains(neighbor))
+            .collect()
+    }
+
+    /// The boundary of the region as a list of closed polylines in grid coordinates, where the
+    /// cell at (x, y) is the square from (x, y) to (x + 1, y + 1). The first point of a polyline
+    /// is not repeated at its end, and only the corners of the boundary are included. The outer
+    /// boundary is counter-clockwise, the boundaries of holes are clockwise. Cells that touch
+    /// only by their corners are separated by the boundary.
+    pub fn boundary(&self) -> Vec<Vec<Vector2<i32>>> {
+        // Every side of a cell that is not shared with another cell of the region is an edge of
+        // the boundary, directed so that the region is on its left.
+        let mut outgoing = FxHashMap::<Vector2<i32>, Vec<Vector2<i32>>>::default();
+        for position in self.cells.iter() {
+            for (i, offset) in NEIGHBORS.iter().enumerate() {
+                if self.cells.contains(&(position + offset)) {
+                    continue;
+                }
+                let from = position + CORNERS[i];
+                let to = position + CORNERS[(i + 1) % 4];
+                outgoing.entry(from).or_default().push(to - from);
+            }
+        }
+
+        let mut starts = outgoing.keys().copied().collect::<Vec<_>>();
+        starts.sort_unstable_by_key(|p| (p.y, p.x));
+
+        let mut polylines = Vec::new();
+        for start in starts {
+            while let Some(first) = outgoing.get_mut(&start).and_then(|edges| edges.pop()) {
+                let mut polyline = vec![start];
+                let mut direction = first;
+                let mut current = start + direction;
+                while current != start {
+                    let Some(edges) = outgoing.get_mut(&current) else {
+                        break;
+                    };
+                    // Prefer left turns, so the regions that touch by a corner are separated.
+                    let left = Vector2::new(-direction.y, direction.x);
+                    let Some(index) = [left, direction, -left]
+                        .iter()
+                        .find_map(|d| edges.iter().position(|e| e == d))
+                    else {
+                        break;
+                    };
+                    let next = edges.swap_remove(index);
+                    if next != direction {
+                        polyline.push(current);
+                    }
+                    direction = next;
+                    current += direction;
+                }
+                if first == direction {
+                    // The polyline has reached the start going straight, so the start is not a
+                    // corner.
+                    polyline.remove(0);
+                }
+                polylines.push(polyline);
+            }
+        }
+        polylines
+    }
+}
+
+impl Tiles {
+    /// Splits the tiles, that satisfy the given filter, into connected regions. See
+    /// [`TileConnectedRegion`] docs for more info.
+    pub fn connected_regions<F>(&self, mut filter: F) -> Vec<TileConnectedRegion>
+    where
+        F: FnMut(Vector2<i32>, TileDefinitionHandle) -> bool,
+    {
+        TileConnectedRegion::find_regions(
+            self.iter()
+                .filter(|(position, handle)| filter(**position, **handle))
+                .map(|(position, _)| *position),
+        )
+    }
+
+    /// Finds the connected regions of the tiles, that satisfy the given filter, and that are
+    /// completely surrounded by the other tiles, such as lakes within land or islands within
+    /// water. Regions that touch empty cells are excluded.
+    pub fn islands<F>(&self, filter: F) -> Vec<TileConnectedRegion>
+    where
+        F: FnMut(Vector2<i32>, TileDefinitionHandle) -> bool,
+    {
+        let mut regions = self.connected_regions(filter);
+        regions.retain(|region| {
+            region
+                .neighbors()
+                .iter()
+                .all(|neighbor| self.contains_key(neighbor))
+        });
+        regions
+    }
+
+    /// Splits the tiles, which value of the property with the given UUID satisfies the given
+    /// predicate, into connected regions. Tiles without an explicit value of the property have
+    /// the default value of the property type.
+    pub fn regions_with_property<F>(
+        &self,
+        tile_set: &TileSet,
+        property_id: Uuid,
+        mut predicate: F,
+    ) -> Vec<TileConnectedRegion>
+    where
+        F: FnMut(&TileSetPropertyValue) -> bool,
+    {
+        self.connected_regions(|_, handle| {
+            tile_set
+                .property_value(handle, property_id)
+                .is_some_and(|value| predicate(&value))
+        })
+    }
+}
+
+impl TilePropertyQuery<'_> {
+    /// Splits the tiles of the tile map, which value of the property with the given UUID satisfies
+    /// the given predicate, into connected regions. See [`TileConnectedRegion`] docs for more info.
+    pub fn regions_with_property<F>(
+        &self,
+        property_id: Uuid,
+        predicate: F,
+    ) -> Vec<TileConnectedRegion>
+    where
+        F: FnMut(&TileSetPropertyValue) -> bool,
+    {
+        TileConnectedRegion::find_regions(
+            self.tiles_with_property(property_id, predicate)
+                .map(|(position, _)| position),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LAND: TileDefinitionHandle = TileDefinitionHandle::new(0, 0, 0, 0);
+    const WATER: TileDefinitionHandle = TileDefinitionHandle::new(0, 0, 1, 0);
+
+    fn v(x: i32, y: i32) -> Vector2<i32> {
+        Vector2::new(x, y)
+    }
+
+    fn region(cells: &[(i32, i32)]) -> TileConnectedRegion {
+        TileConnectedRegion {
+            cells: cells.iter().map(|(x, y)| v(*x, *y)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_find_regions() {
+        let regions = TileConnectedRegion::find_regions([v(0, 0), v(1, 0), v(2, 1), v(5, 5)]);
+        assert_eq!(regions.len(), 3);
+        assert_eq!(regions[0], region(&[(0, 0), (1, 0)]));
+        assert_eq!(regions[1], region(&[(2, 1)]));
+        assert_eq!(regions[2], region(&[(5, 5)]));
+        assert_eq!(regions[0].bounding_rect(), TileRect::new(0, 0, 2, 1).into());
+    }
+
+    #[test]
+    fn test_islands() {
+        // A lake in the middle of the land, and a bay at the edge of the land.
+        let mut tiles = Tiles::default();
+        for position in TileRect::new(0, 0, 5, 3).iter() {
+            tiles.insert(position, LAND);
+        }
+        tiles.insert(v(1, 1), WATER);
+        tiles.insert(v(4, 1), WATER);
+        let water = tiles.connected_regions(|_, handle| handle == WATER);
+        assert_eq!(water.len(), 2);
+        let lakes = tiles.islands(|_, handle| handle == WATER);
+        assert_eq!(lakes, vec![region(&[(1, 1)])]);
+    }
+
+    #[test]
+    fn test_boundary() {
+        let square = region(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(
+            square.boundary(),
+            vec![vec![v(0, 0), v(2, 0), v(2, 2), v(0, 2)]]
+        );
+
+        let ring = region(&[
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (0, 1),
+            (2, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+        ]);
+        assert_eq!(
+            ring.boundary(),
+            vec![
+                vec![v(0, 0), v(3, 0), v(3, 3), v(0, 3)],
+                vec![v(1, 1), v(1, 2), v(2, 2), v(2, 1)],
+            ]
+        );
+
+        // The cells that touch by a corner have separate boundaries.
+        let diagonal = region(&[(0, 0), (1, 1)]);
+        assert_eq!(
+            diagonal.boundary(),
+            vec![
+                vec![v(0, 0), v(1, 0), v(1, 1), v(0, 1)],
+                vec![v(1, 1), v(2, 1), v(2, 2), v(1, 2)],
+            ]
+        );
+    }
+}
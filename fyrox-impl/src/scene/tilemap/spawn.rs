@@ -0,0 +1,348 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Spawning of scene nodes for the tiles of a tile map. See [`TileSpawner`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector2},
+        pool::Handle,
+        ImmutableString,
+    },
+    graph::SceneGraph,
+    resource::model::{ModelResource, ModelResourceExtension},
+    scene::{node::Node, Scene},
+};
+use fxhash::{FxHashMap, FxHashSet};
+
+use super::*;
+
+/// An entry of [`TileSet::spawn_table`], that maps a value of a spawn property to a prefab.
+/// See [`TileSpawner`] docs for more info.
+#[derive(Clone, Default, Debug, PartialEq, Visit, Reflect)]
+pub struct TileSpawnEntry {
+    /// The value of the spawn property, such as "chest" or "enemy".
+    pub name: ImmutableString,
+    /// The prefab that is instantiated for the tiles with this value of the spawn property.
+    pub prefab: Option<ModelResource>,
+}
+
+/// Tile spawner instantiates prefabs for the tiles of a [`TileMap`], that have a spawn property,
+/// such as chests, enemies or torches, which closes the gap between purely visual tiles and
+/// gameplay entities. The spawn property is a string property of the tile set, its values are
+/// mapped to prefabs by [`TileSet::spawn_table`]. Tiles with empty values or with the values that
+/// are missing in the table spawn nothing.
+///
+/// The nodes are spawned by [`TileSpawner::update`], at the centers of the cells of the tiles.
+/// The first update spawns the nodes for every tile of the tile map, the following updates only
+/// process the chunks of the tile map, that were changed since the previous update, so the
+/// spawner could be updated every frame from a script of the tile map. When a tile is removed or
+/// replaced by a tile with another spawn value, its node is removed from the scene.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     scene::{node::Node, tilemap::TileSpawner, Scene},
+/// # };
+/// fn setup(tile_map: Handle<Node>) -> TileSpawner {
+///     TileSpawner::new(tile_map, "spawn")
+/// }
+///
+/// fn update(spawner: &mut TileSpawner, scene: &mut Scene) {
+///     if let Err(err) = spawner.update(scene) {
+///         println!("Unable to spawn tile nodes: {err}");
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TileSpawner {
+    tile_map: Handle<Node>,
+    property: ImmutableString,
+    /// The key of the tile set resource that was used to spawn the nodes.
+    tile_set: Option<u64>,
+    /// The revisions of the chunks of the tile map, that were used to spawn the nodes.
+    revisions: FxHashMap<Vector2<i32>, u64>,
+    spawned: FxHashMap<Vector2<i32>, (ImmutableString, Handle<Node>)>,
+}
+
+/// The changes of the spawned nodes, that are found while the tile map is borrowed.
+#[derive(Default)]
+struct SpawnPlan {
+    despawn: Vec<Vector2<i32>>,
+    spawn: Vec<(Vector2<i32>, ImmutableString, ModelResource)>,
+    revisions: Vec<(Vector2<i32>, u64)>,
+}
+
+impl TileSpawner {
+    /// Creates new spawner for the given tile map, that uses the property with the given name.
+    /// Nothing is spawned until [`TileSpawner::update`] is called.
+    pub fn new<S: AsRef<str>>(tile_map: Handle<Node>, property: S) -> Self {
+        Self {
+            tile_map,
+            property: ImmutableString::new(property),
+            ..Default::default()
+        }
+    }
+
+    /// The handle of the tile map, whose tiles spawn the nodes.
+    pub fn tile_map(&self) -> Handle<Node> {
+        self.tile_map
+    }
+
+    /// The name of the spawn property.
+    pub fn property(&self) -> &ImmutableString {
+        &self.property
+    }
+
+    /// The node that was spawned for the tile at the given cell, if any.
+    pub fn node_at(&self, position: Vector2<i32>) -> Option<Handle<Node>> {
+        self.spawned.get(&position).map(|(_, node)| *node)
+    }
+
+    /// Iterates over the spawned nodes in the form of (cell, value, node) tuples, where value is
+    /// the value of the spawn property of the tile at the cell.
+    pub fn spawned(
+        &self,
+    ) -> impl Iterator<Item = (Vector2<i32>, &ImmutableString, Handle<Node>)> + '_ {
+        self.spawned
+            .iter()
+            .map(|(position, (name, node))| (*position, name, *node))
+    }
+
+    /// Removes every spawned node from the scene. The next update spawns the nodes again.
+    pub fn clear(&mut self, scene: &mut Scene) {
+        for (_, (_, node)) in self.spawned.drain() {
+            if scene.graph.is_valid_handle(node) {
+                scene.graph.remove_node(node);
+            }
+        }
+        self.revisions.clear();
+    }
+
+    /// Spawns the nodes for the tiles of the tile map, that were added or changed since the last
+    /// update, and removes the nodes of the tiles, that were removed or changed. If a prefab is
+    /// still loading, its tiles are processed again by the next update. All the nodes are removed
+    /// if the tile map no longer exists. Returns true if any node was spawned or removed.
+    pub fn update(&mut self, scene: &mut Scene) -> Result<bool, TilePropertyError> {
+        if !scene.graph.is_valid_handle(self.tile_map) {
+            let changed = !self.spawned.is_empty();
+            self.clear(scene);
+            return Ok(changed);
+        }
+        let (plan, transform, grid_layout) = {
+            let Some(tile_map) = scene.graph.try_get_of_type::<TileMap>(self.tile_map) else {
+                return Ok(false);
+            };
+            (
+                self.plan(tile_map)?,
+                tile_map.tile_map_transform(),
+                tile_map.grid_layout(),
+            )
+        };
+        let changed = !plan.despawn.is_empty() || !plan.spawn.is_empty();
+        for position in plan.despawn {
+            if let Some((_, node)) = self.spawned.remove(&position) {
+                if scene.graph.is_valid_handle(node) {
+                    scene.graph.remove_node(node);
+                }
+            }
+        }
+        for (position, name, prefab) in plan.spawn {
+            let center = grid_layout.cell_center(position).to_homogeneous();
+            let center = transform.transform_point(&center.into()).coords;
+            let node = prefab.instantiate_at(scene, center, UnitQuaternion::identity());
+            self.spawned.insert(position, (name, node));
+        }
+        self.revisions.extend(plan.revisions);
+        Ok(changed)
+    }
+
+    fn plan(&mut self, tile_map: &TileMap) -> Result<SpawnPlan, TilePropertyError> {
+        let mut plan = SpawnPlan::default();
+        let tile_set = tile_map
+            .tile_set()
+            .ok_or(TilePropertyError::MissingTileSet)?;
+        let tile_set_key = Some(tile_set.key());
+        if self.tile_set != tile_set_key {
+            self.tile_set = tile_set_key;
+            self.revisions.clear();
+        }
+        let tile_set = tile_set.data_ref();
+        let tile_set = tile_set
+            .as_loaded_ref()
+            .ok_or(TilePropertyError::TileSetNotLoaded)?;
+        let property = tile_set
+            .find_property_by_name(&self.property)
+            .ok_or_else(|| TilePropertyError::UnrecognizedName(self.property.clone()))?;
+        if property.prop_type != TileSetPropertyType::String {
+            return Err(TilePropertyError::WrongType("String"));
+        }
+
+        let tiles = tile_map.tiles().map(|tiles| tiles.data_ref());
+        let Some(tiles) = tiles.as_ref().and_then(|tiles| tiles.as_loaded_ref()) else {
+            plan.despawn.extend(self.spawned.keys());
+            self.revisions.clear();
+            return Ok(plan);
+        };
+
+        let chunks = tiles.chunks().collect::<Vec<_>>();
+        let present = chunks
+            .iter()
+            .map(|(rect, _)| rect.position)
+            .collect::<FxHashSet<_>>();
+        self.revisions
+            .retain(|position, _| present.contains(position));
+        plan.despawn.extend(
+            self.spawned
+                .keys()
+                .filter(|p| !present.contains(&chunk_rect(**p).position)),
+        );
+
+        for (rect, revision) in chunks {
+            if self.revisions.get(&rect.position) == Some(&revision) {
+                continue;
+            }
+            let mut wanted = FxHashMap::default();
+            for (position, handle) in tiles.bounded_iter(rect.into()) {
+                let Some(TileSetPropertyValue::String(name)) =
+                    tile_set.property_value(handle, property.uuid)
+                else {
+                    continue;
+                };
+                if let Some(prefab) = tile_set.spawn_prefab(&name) {
+                    wanted.insert(position, (name, prefab.clone()));
+                }
+            }
+            let mut complete = true;
+            for position in rect.iter() {
+                let spawned = self.spawned.get(&position).map(|(name, _)| name);
+                let wanted = wanted.remove(&position);
+                if spawned.is_some() {
+                    if spawned == wanted.as_ref().map(|(name, _)| name) {
+                        continue;
+                    }
+                    plan.despawn.push(position);
+                }
+                let Some((name, prefab)) = wanted else {
+                    continue;
+                };
+                if prefab.is_ok() {
+                    plan.spawn.push((position, name, prefab));
+                } else if prefab.is_loading() {
+                    complete = false;
+                }
+            }
+            if complete {
+                plan.revisions.push((rect.position, revision));
+            }
+        }
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::uuid::Uuid,
+        graph::NodeMapping,
+        resource::model::Model,
+        scene::{
+            pivot::Pivot,
+            tilemap::test_fixture::{self, data_with_property, FLOOR, ITEM},
+        },
+    };
+
+    const SPAWN: Uuid = Uuid::from_u128(0x5678);
+    const CHEST: TileDefinitionHandle = ITEM;
+
+    fn v(x: i32, y: i32) -> Vector2<i32> {
+        Vector2::new(x, y)
+    }
+
+    fn make_tile_set() -> TileSet {
+        let mut tile_set = test_fixture::make_tile_set(|handle| {
+            let value = if handle == CHEST { "chest" } else { "" };
+            data_with_property(
+                SPAWN,
+                TileSetPropertyValue::String(ImmutableString::new(value)),
+            )
+        });
+        tile_set.properties.push(TileSetPropertyLayer {
+            uuid: SPAWN,
+            name: ImmutableString::new("spawn"),
+            prop_type: TileSetPropertyType::String,
+            named_values: Vec::new(),
+        });
+        let mut prefab = Scene::new();
+        prefab.graph.add_node(Node::new(Pivot::default()));
+        tile_set.spawn_table.push(TileSpawnEntry {
+            name: ImmutableString::new("chest"),
+            prefab: Some(ModelResource::new_ok(
+                ResourceKind::Embedded,
+                Model::new(NodeMapping::UseNames, prefab),
+            )),
+        });
+        tile_set
+    }
+
+    fn set_tile(
+        scene: &Scene,
+        tile_map: Handle<Node>,
+        position: Vector2<i32>,
+        handle: TileDefinitionHandle,
+    ) {
+        let tile_map = scene.graph.try_get_of_type::<TileMap>(tile_map).unwrap();
+        let mut tiles = tile_map.tiles().unwrap().data_ref();
+        tiles.as_loaded_mut().unwrap().set(position, handle);
+    }
+
+    #[test]
+    fn test_spawner() {
+        let mut scene = Scene::new();
+        let tile_map = test_fixture::make_tile_map(
+            make_tile_set(),
+            [(v(0, 0), CHEST), (v(1, 0), FLOOR), (v(40, 0), CHEST)],
+        );
+        let tile_map = scene.graph.add_node(Node::new(tile_map));
+
+        let mut spawner = TileSpawner::new(tile_map, "spawn");
+        assert!(spawner.update(&mut scene).unwrap());
+        assert_eq!(spawner.spawned().count(), 2);
+        let chest = spawner.node_at(v(0, 0)).unwrap();
+        assert!(scene.graph.is_valid_handle(chest));
+        assert!(spawner.node_at(v(1, 0)).is_none());
+        // Nothing changed, so nothing is spawned again.
+        assert!(!spawner.update(&mut scene).unwrap());
+        assert_eq!(spawner.node_at(v(0, 0)), Some(chest));
+
+        set_tile(&scene, tile_map, v(0, 0), FLOOR);
+        set_tile(&scene, tile_map, v(1, 0), CHEST);
+        assert!(spawner.update(&mut scene).unwrap());
+        assert!(!scene.graph.is_valid_handle(chest));
+        assert!(spawner.node_at(v(0, 0)).is_none());
+        assert!(spawner.node_at(v(1, 0)).is_some());
+        assert!(spawner.node_at(v(40, 0)).is_some());
+
+        spawner.clear(&mut scene);
+        assert_eq!(spawner.spawned().count(), 0);
+    }
+}
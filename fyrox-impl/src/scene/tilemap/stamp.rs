@@ -0,0 +1,183 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Temporary tile stamps, such as scorch marks or construction previews, that are rendered over
+//! the stored tiles of a tile map without modifying them. See [`TileStamp`] docs for more info.
+
+use crate::core::algebra::Vector2;
+use fxhash::FxHashMap;
+
+use super::*;
+
+/// Defines how a [`TileStamp`] is combined with the tile in its cell.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub enum TileStampMode {
+    /// The stamp is rendered on top of the tile in the cell, like a decal.
+    #[default]
+    Overlay,
+    /// The tile in the cell is hidden and the stamp is rendered in its place.
+    Replace,
+}
+
+/// Render data that temporarily overrides the appearance of a single cell of a tile map, for
+/// example a scorch mark, blood splatter or a preview of a building that is about to be
+/// constructed. Stamps are never written to the [`Tiles`] of a tile map, so removing a stamp
+/// (or letting it expire) restores the original look of the cell. Stamps are not serialized.
+/// See [`TileMap::set_tile_stamp`].
+#[derive(Clone, Default, Debug)]
+pub struct TileStamp {
+    /// The data to render in the cell.
+    pub data: TileRenderData,
+    /// How the stamp is combined with the tile in the cell.
+    pub mode: TileStampMode,
+    /// The remaining time in seconds before the stamp is removed. `None` means the stamp stays
+    /// until it is removed explicitly.
+    pub time_to_live: Option<f32>,
+}
+
+impl TileStamp {
+    /// Creates a new overlay stamp that is never removed automatically.
+    pub fn new(data: TileRenderData) -> Self {
+        Self {
+            data,
+            mode: TileStampMode::Overlay,
+            time_to_live: None,
+        }
+    }
+
+    /// Sets how the stamp is combined with the tile in its cell.
+    pub fn with_mode(mut self, mode: TileStampMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the time in seconds after which the stamp is removed.
+    pub fn with_time_to_live(mut self, time_to_live: f32) -> Self {
+        self.time_to_live = Some(time_to_live);
+        self
+    }
+
+    /// Decreases the remaining time of the stamp by the given amount and returns true if the stamp
+    /// is still alive.
+    pub fn advance(&mut self, dt: f32) -> bool {
+        match self.time_to_live.as_mut() {
+            Some(time_to_live) => {
+                *time_to_live -= dt;
+                *time_to_live > 0.0
+            }
+            None => true,
+        }
+    }
+}
+
+/// The collection of stamps of a tile map. It works like a pair of effects: the tiles under
+/// [`TileStampMode::Replace`] stamps are hidden before the tile map renders its tiles, and
+/// [`TileStampMode::Overlay`] stamps are rendered after it.
+#[derive(Clone, Default, Debug)]
+pub struct TileStamps {
+    stamps: FxHashMap<Vector2<i32>, TileStamp>,
+}
+
+impl TileStamps {
+    /// True if there are no stamps.
+    pub fn is_empty(&self) -> bool {
+        self.stamps.is_empty()
+    }
+
+    /// The stamp at the given position, if any.
+    pub fn get(&self, position: Vector2<i32>) -> Option<&TileStamp> {
+        self.stamps.get(&position)
+    }
+
+    /// Puts the stamp at the given position and returns the stamp that was replaced, if any.
+    pub fn insert(&mut self, position: Vector2<i32>, stamp: TileStamp) -> Option<TileStamp> {
+        self.stamps.insert(position, stamp)
+    }
+
+    /// Removes the stamp at the given position.
+    pub fn remove(&mut self, position: Vector2<i32>) -> Option<TileStamp> {
+        self.stamps.remove(&position)
+    }
+
+    /// Removes all stamps.
+    pub fn clear(&mut self) {
+        self.stamps.clear();
+    }
+
+    /// Iterates through the positions and stamps.
+    pub fn iter(&self) -> impl Iterator<Item = (Vector2<i32>, &TileStamp)> {
+        self.stamps.iter().map(|(p, s)| (*p, s))
+    }
+
+    /// Advances the time of every stamp and removes the expired stamps.
+    pub fn update(&mut self, dt: f32) {
+        self.stamps.retain(|_, stamp| stamp.advance(dt));
+    }
+
+    /// Hides the tiles under [`TileStampMode::Replace`] stamps and renders the stamps in their
+    /// place. This must be called before the tiles of the tile map are rendered.
+    pub fn render_before_tiles(&self, context: &mut TileMapRenderContext) {
+        for (&position, stamp) in self.stamps.iter() {
+            if stamp.mode == TileStampMode::Replace && context.is_tile_visible(position) {
+                context.push_tile(position, &stamp.data);
+                context.set_tile_visible(position, false);
+            }
+        }
+    }
+
+    /// Renders [`TileStampMode::Overlay`] stamps. This must be called after the tiles of the tile
+    /// map are rendered.
+    pub fn render_after_tiles(&self, context: &mut TileMapRenderContext) {
+        for (&position, stamp) in self.stamps.iter() {
+            if stamp.mode == TileStampMode::Overlay && context.is_tile_visible(position) {
+                context.push_tile(position, &stamp.data);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_update() {
+        let mut stamps = TileStamps::default();
+        let data = TileRenderData::missing_data();
+        stamps.insert(Vector2::new(0, 0), TileStamp::new(data.clone()));
+        stamps.insert(
+            Vector2::new(1, 0),
+            TileStamp::new(data.clone()).with_time_to_live(1.0),
+        );
+        stamps.insert(
+            Vector2::new(2, 0),
+            TileStamp::new(data).with_time_to_live(2.0),
+        );
+        stamps.update(1.5);
+        assert!(stamps.get(Vector2::new(0, 0)).is_some());
+        assert!(stamps.get(Vector2::new(1, 0)).is_none());
+        assert_eq!(
+            stamps.get(Vector2::new(2, 0)).unwrap().time_to_live,
+            Some(0.5)
+        );
+        stamps.update(1.0);
+        assert_eq!(stamps.iter().count(), 1);
+    }
+}
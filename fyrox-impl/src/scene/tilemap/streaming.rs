@@ -0,0 +1,333 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Streaming of tile maps that are too large to be kept in memory. See [`TileMapStreaming`] docs
+//! for more info.
+
+use crate::{
+    core::{
+        algebra::Vector2,
+        log::Log,
+        parking_lot::Mutex,
+        visitor::{Visit, VisitError, Visitor},
+    },
+    scene::tilemap::data::chunk_rect,
+};
+use fxhash::FxHashSet;
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use super::*;
+
+/// A storage of the chunks of a tile map that are not kept in memory. The provider is used by
+/// [`TileMapStreaming`] to load the chunks that come close to the cameras and to store the
+/// chunks that are far away from them.
+pub trait TileChunkProvider: Debug + Send {
+    /// Returns the tiles of the chunk that covers the given rect. The provider may read the tiles
+    /// from some storage or generate them procedurally. `None` means that the chunk is empty.
+    /// Tiles outside of the rect are ignored.
+    fn load_chunk(&mut self, rect: TileRect) -> Option<Tiles>;
+
+    /// Takes the tiles of the chunk that covers the given rect, when the chunk is unloaded. The
+    /// tiles include every change that was made while the chunk was loaded, so the provider
+    /// should store them to return them from [`Self::load_chunk`] later. A provider that
+    /// generates the chunks could drop the tiles, if the changes do not need to be kept.
+    fn unload_chunk(&mut self, rect: TileRect, tiles: Tiles);
+}
+
+/// A shared reference to a [`TileChunkProvider`].
+pub type TileChunkProviderRef = Arc<Mutex<dyn TileChunkProvider>>;
+
+/// A chunk provider that stores every chunk in a separate file in the given directory. Chunks
+/// that have no file are considered empty.
+#[derive(Clone, Debug)]
+pub struct FileTileChunkProvider {
+    directory: PathBuf,
+}
+
+impl FileTileChunkProvider {
+    /// Creates a new provider that stores the chunks in the given directory. The directory is
+    /// created when the first chunk is stored.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// The directory with the chunk files.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    fn chunk_path(&self, rect: TileRect) -> PathBuf {
+        self.directory
+            .join(format!("{}_{}.tiles", rect.position.x, rect.position.y))
+    }
+}
+
+impl TileChunkProvider for FileTileChunkProvider {
+    fn load_chunk(&mut self, rect: TileRect) -> Option<Tiles> {
+        let path = self.chunk_path(rect);
+        let data = std::fs::read(&path).ok()?;
+        let mut tiles = Tiles::default();
+        let result = Visitor::load_from_memory(&data)
+            .and_then(|mut visitor| tiles.visit("Tiles", &mut visitor));
+        match result {
+            Ok(()) => Some(tiles),
+            Err(err) => {
+                Log::err(format!(
+                    "Unable to load tile map chunk {}: {err}",
+                    path.display()
+                ));
+                None
+            }
+        }
+    }
+
+    fn unload_chunk(&mut self, rect: TileRect, mut tiles: Tiles) {
+        let path = self.chunk_path(rect);
+        if tiles.is_empty() {
+            // The chunk may have had some tiles before it was cleared.
+            let _ = std::fs::remove_file(&path);
+            return;
+        }
+        let result = std::fs::create_dir_all(&self.directory)
+            .map_err(VisitError::from)
+            .and_then(|_| {
+                let mut visitor = Visitor::new();
+                tiles.visit("Tiles", &mut visitor)?;
+                visitor.save_binary(&path)
+            });
+        if let Err(err) = result {
+            Log::err(format!(
+                "Unable to save tile map chunk {}: {err}",
+                path.display()
+            ));
+        }
+    }
+}
+
+/// The distance from the point to the closest point of the rect, zero if the point is inside.
+fn distance_to_rect(point: Vector2<f32>, rect: TileRect) -> f32 {
+    let min = rect.position.cast::<f32>();
+    let max = min + rect.size.cast::<f32>();
+    Vector2::new(
+        (min.x - point.x).max(point.x - max.x).max(0.0),
+        (min.y - point.y).max(point.y - max.y).max(0.0),
+    )
+    .norm()
+}
+
+/// Streaming mode of a tile map. Only the chunks of the tile map that are within the given radius
+/// around the cameras are kept in memory, every other chunk is given to a [`TileChunkProvider`]
+/// and loaded back from it when a camera comes close to it again. This allows to have open worlds
+/// of any size, for example, worlds that are generated procedurally chunk by chunk.
+///
+/// The streaming is updated by the tile map on every frame using the positions of all enabled
+/// cameras of the scene, see [`TileMap::set_streaming`]. If there are no enabled cameras, the
+/// chunks are kept as is.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::parking_lot::Mutex,
+/// #     scene::tilemap::{FileTileChunkProvider, TileMap, TileMapStreaming},
+/// # };
+/// # use std::sync::Arc;
+/// fn enable_streaming(tile_map: &mut TileMap) {
+///     let provider = FileTileChunkProvider::new("save/world");
+///     tile_map.set_streaming(Some(TileMapStreaming::new(
+///         Arc::new(Mutex::new(provider)),
+///         64.0,
+///     )));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TileMapStreaming {
+    provider: TileChunkProviderRef,
+    radius: f32,
+    loaded: FxHashSet<Vector2<i32>>,
+}
+
+impl TileMapStreaming {
+    /// Creates a new streaming mode with the given provider. The chunks that are closer than
+    /// `radius` (in cells) to any camera are loaded.
+    pub fn new(provider: TileChunkProviderRef, radius: f32) -> Self {
+        Self {
+            provider,
+            radius: radius.max(0.0),
+            loaded: Default::default(),
+        }
+    }
+
+    /// The provider of the chunks.
+    pub fn provider(&self) -> &TileChunkProviderRef {
+        &self.provider
+    }
+
+    /// The distance (in cells) around the cameras, in which the chunks are loaded.
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Sets the distance (in cells) around the cameras, in which the chunks are loaded.
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius.max(0.0);
+    }
+
+    /// True if the chunk that contains the given cell was requested from the provider and it
+    /// is not unloaded yet.
+    pub fn is_loaded(&self, position: Vector2<i32>) -> bool {
+        self.loaded.contains(&chunk_rect(position).position)
+    }
+
+    /// Loads the chunks that are within the radius around the given observers (in local
+    /// coordinates of the tile map) and unloads the chunks that are farther than the radius
+    /// plus the size of a chunk. The extra distance prevents the chunks from being loaded
+    /// and unloaded on every frame, when an observer moves back and forth near a chunk border.
+    pub fn update(&mut self, tiles: &mut TileMapData, observers: &[Vector2<f32>]) {
+        if observers.is_empty() {
+            return;
+        }
+
+        let chunk_size = chunk_rect(Vector2::new(0, 0)).size;
+        let mut required = FxHashSet::default();
+        for observer in observers {
+            let extent = Vector2::repeat(self.radius);
+            let min = chunk_rect((observer - extent).map(|v| v.floor() as i32)).position;
+            let max = chunk_rect((observer + extent).map(|v| v.floor() as i32)).position;
+            for y in (min.y..=max.y).step_by(chunk_size.y as usize) {
+                for x in (min.x..=max.x).step_by(chunk_size.x as usize) {
+                    let rect = chunk_rect(Vector2::new(x, y));
+                    if distance_to_rect(*observer, rect) <= self.radius {
+                        required.insert(rect.position);
+                    }
+                }
+            }
+        }
+
+        let mut provider = self.provider.lock();
+
+        for position in required.iter() {
+            if !self.loaded.insert(*position) {
+                continue;
+            }
+            let rect = chunk_rect(*position);
+            if let Some(chunk) = provider.load_chunk(rect) {
+                for (p, h) in chunk.iter() {
+                    if rect.contains(*p) {
+                        tiles.set(*p, *h);
+                    }
+                }
+            }
+        }
+
+        let keep_distance = self.radius + chunk_size.x.max(chunk_size.y) as f32;
+        let candidates = self
+            .loaded
+            .iter()
+            .copied()
+            .chain(tiles.chunk_positions())
+            .filter(|position| !required.contains(position))
+            .collect::<FxHashSet<_>>();
+        for position in candidates {
+            let rect = chunk_rect(position);
+            if observers
+                .iter()
+                .all(|observer| distance_to_rect(*observer, rect) > keep_distance)
+            {
+                self.loaded.remove(&position);
+                provider.unload_chunk(rect, tiles.take_chunk(position));
+            }
+        }
+    }
+
+    /// Gives every chunk of the tiles to the provider. This could be used to store all the
+    /// changes of the tiles, for example, when the game is saved.
+    pub fn unload_all(&mut self, tiles: &mut TileMapData) {
+        let mut provider = self.provider.lock();
+        let positions = self
+            .loaded
+            .drain()
+            .chain(tiles.chunk_positions())
+            .collect::<FxHashSet<_>>();
+        for position in positions {
+            provider.unload_chunk(chunk_rect(position), tiles.take_chunk(position));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fxhash::FxHashMap;
+
+    #[derive(Debug, Default)]
+    struct MemoryProvider {
+        chunks: FxHashMap<Vector2<i32>, Tiles>,
+        loads: usize,
+    }
+
+    impl TileChunkProvider for MemoryProvider {
+        fn load_chunk(&mut self, rect: TileRect) -> Option<Tiles> {
+            self.loads += 1;
+            self.chunks.remove(&rect.position)
+        }
+
+        fn unload_chunk(&mut self, rect: TileRect, tiles: Tiles) {
+            if !tiles.is_empty() {
+                self.chunks.insert(rect.position, tiles);
+            }
+        }
+    }
+
+    #[test]
+    fn test_streaming() {
+        let provider = Arc::new(Mutex::new(MemoryProvider::default()));
+        let mut streaming = TileMapStreaming::new(provider.clone(), 8.0);
+        let handle = TileDefinitionHandle::new(0, 0, 1, 1);
+        let mut tiles = TileMapData::default();
+        tiles.set(Vector2::new(1, 1), handle);
+        tiles.set(Vector2::new(100, 0), handle);
+
+        streaming.update(&mut tiles, &[Vector2::new(0.0, 0.0)]);
+        assert_eq!(tiles.get(Vector2::new(1, 1)), Some(handle));
+        assert_eq!(tiles.get(Vector2::new(100, 0)), None);
+        assert!(streaming.is_loaded(Vector2::new(-1, -1)));
+        assert!(!streaming.is_loaded(Vector2::new(100, 0)));
+        assert_eq!(provider.lock().loads, 4);
+        assert!(provider.lock().chunks.contains_key(&Vector2::new(96, 0)));
+
+        // Loaded chunks are not requested again.
+        streaming.update(&mut tiles, &[Vector2::new(1.0, 1.0)]);
+        assert_eq!(provider.lock().loads, 4);
+
+        streaming.update(&mut tiles, &[Vector2::new(100.0, 0.0)]);
+        assert_eq!(tiles.get(Vector2::new(100, 0)), Some(handle));
+        assert_eq!(tiles.get(Vector2::new(1, 1)), None);
+        assert!(provider.lock().chunks.contains_key(&Vector2::new(0, 0)));
+
+        streaming.unload_all(&mut tiles);
+        assert_eq!(tiles.iter().count(), 0);
+        assert_eq!(provider.lock().chunks.len(), 2);
+    }
+}
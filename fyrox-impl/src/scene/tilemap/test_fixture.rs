@@ -0,0 +1,90 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Fixtures, that are shared by the tests of the tile map module.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{algebra::Vector2, uuid::Uuid},
+    scene::tilemap::{
+        tileset::{
+            TileData, TileDefinition, TileSet, TileSetPage, TileSetPageSource,
+            TileSetPropertyValue, TileSetResource,
+        },
+        TileDefinitionHandle, TileGridMap, TileMap, TileMapData, TileMapDataResource,
+    },
+};
+
+/// The first tile of the tile set made by [`make_tile_set`].
+pub(super) const FLOOR: TileDefinitionHandle = TileDefinitionHandle::new(0, 0, 0, 0);
+/// The second tile of the tile set made by [`make_tile_set`].
+pub(super) const WALL: TileDefinitionHandle = TileDefinitionHandle::new(0, 0, 1, 0);
+/// The third tile of the tile set made by [`make_tile_set`], for items placed on the floor.
+pub(super) const ITEM: TileDefinitionHandle = TileDefinitionHandle::new(0, 0, 2, 0);
+
+/// Creates tile data with a single property.
+pub(super) fn data_with_property(uuid: Uuid, value: TileSetPropertyValue) -> TileData {
+    let mut data = TileData::default();
+    data.properties.insert(uuid, value);
+    data
+}
+
+/// Creates a tile set with a single freeform page, that has [`FLOOR`], [`WALL`] and [`ITEM`] tiles.
+/// Data of the tiles is provided by the given function.
+pub(super) fn make_tile_set(data: impl Fn(TileDefinitionHandle) -> TileData) -> TileSet {
+    let mut tiles = TileGridMap::default();
+    for handle in [FLOOR, WALL, ITEM] {
+        tiles.insert(
+            handle.tile(),
+            TileDefinition {
+                material_bounds: Default::default(),
+                data: data(handle),
+            },
+        );
+    }
+    let mut tile_set = TileSet::default();
+    tile_set.insert_page(
+        FLOOR.page(),
+        TileSetPage {
+            icon: FLOOR,
+            source: TileSetPageSource::Freeform(tiles),
+        },
+    );
+    tile_set
+}
+
+/// Creates a tile map with the given tile set and tiles.
+pub(super) fn make_tile_map(
+    tile_set: TileSet,
+    tiles: impl IntoIterator<Item = (Vector2<i32>, TileDefinitionHandle)>,
+) -> TileMap {
+    let mut data = TileMapData::default();
+    for (position, handle) in tiles {
+        data.set(position, handle);
+    }
+
+    let mut tile_map = TileMap::default();
+    tile_map.set_tile_set(Some(TileSetResource::new_ok(
+        ResourceKind::Embedded,
+        tile_set,
+    )));
+    tile_map.set_tiles(TileMapDataResource::new_ok(ResourceKind::Embedded, data));
+    tile_map
+}
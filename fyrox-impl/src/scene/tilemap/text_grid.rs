@@ -0,0 +1,419 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Text representation of tile map layers, that is suitable for external tools, modding and
+//! version control. See [`TileTextGrid`] docs for more info.
+
+use crate::core::{algebra::Vector2, uuid::Uuid};
+use fxhash::FxHashMap;
+use serde::Deserialize;
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use super::*;
+
+/// The first line of a CSV grid, that is followed by the position of the left-top cell.
+const CSV_ORIGIN_PREFIX: &str = "# origin:";
+
+/// An error that may occur when a [`TileTextGrid`] is parsed or applied to a tile map.
+#[derive(Debug)]
+pub enum TileTextGridError {
+    /// The JSON document is not a valid grid.
+    Json(serde_json::Error),
+    /// The origin line of a CSV grid could not be parsed.
+    InvalidOrigin(String),
+    /// A quoted cell of a CSV grid is not closed.
+    UnterminatedQuote,
+    /// The content of a cell could not be parsed.
+    InvalidCell {
+        /// The position of the cell in the tile map.
+        position: Vector2<i32>,
+        /// The content of the cell.
+        text: String,
+    },
+}
+
+impl Display for TileTextGridError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "Invalid JSON grid: {err}"),
+            Self::InvalidOrigin(line) => write!(f, "Invalid origin of CSV grid: {line}"),
+            Self::UnterminatedQuote => write!(f, "Unterminated quote in CSV grid"),
+            Self::InvalidCell { position, text } => {
+                write!(
+                    f,
+                    "Invalid cell {text:?} at ({}, {})",
+                    position.x, position.y
+                )
+            }
+        }
+    }
+}
+
+impl Error for TileTextGridError {}
+
+impl From<serde_json::Error> for TileTextGridError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonGrid {
+    origin: [i32; 2],
+    rows: Vec<Vec<String>>,
+}
+
+/// A rectangular region of a tile map layer in the form of text cells, one cell per tile. The
+/// cells could store the handles of the tiles ([`TileMapData::to_text_grid`]) or the values of a
+/// property of the tiles ([`TileMap::property_text_grid`]). Empty strings are empty cells.
+///
+/// The grid could be written to and read from CSV or JSON. Both formats store one row of cells
+/// per line, from the top row to the bottom one, so changes of a few tiles produce small diffs.
+/// CSV grids start with `# origin: x,y` line, which stores the position of the left-top cell in
+/// the tile map; JSON grids store it in `origin` field. Handles are written as
+/// `page_x:page_y:tile_x:tile_y`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TileTextGrid {
+    /// The position of the left-top cell of the grid in the tile map.
+    pub origin: Vector2<i32>,
+    /// The rows of the cells from the top row to the bottom one.
+    pub rows: Vec<Vec<String>>,
+}
+
+fn format_handle(handle: TileDefinitionHandle) -> String {
+    let (page, tile) = (handle.page(), handle.tile());
+    format!("{}:{}:{}:{}", page.x, page.y, tile.x, tile.y)
+}
+
+fn format_property_value(value: &TileSetPropertyValue) -> String {
+    match value {
+        TileSetPropertyValue::I32(value) => value.to_string(),
+        TileSetPropertyValue::F32(value) => value.to_string(),
+        TileSetPropertyValue::String(value) => value.to_string(),
+        TileSetPropertyValue::NineSlice(value) => value
+            .0
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+fn parse_property_value(
+    text: &str,
+    prop_type: TileSetPropertyType,
+) -> Option<TileSetPropertyValue> {
+    Some(match prop_type {
+        TileSetPropertyType::I32 => TileSetPropertyValue::I32(text.trim().parse().ok()?),
+        TileSetPropertyType::F32 => TileSetPropertyValue::F32(text.trim().parse().ok()?),
+        TileSetPropertyType::String => TileSetPropertyValue::String(text.into()),
+        TileSetPropertyType::NineSlice => {
+            let mut values = [0; 9];
+            let mut iter = text.split_whitespace();
+            for value in values.iter_mut() {
+                *value = iter.next()?.parse().ok()?;
+            }
+            if iter.next().is_some() {
+                return None;
+            }
+            TileSetPropertyValue::NineSlice(NineI8(values))
+        }
+    })
+}
+
+fn csv_cell(cell: &str) -> Cow<str> {
+    if cell.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", cell.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(cell)
+    }
+}
+
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, TileTextGridError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c != '"' {
+                cell.push(c);
+            } else if chars.next_if_eq(&'"').is_some() {
+                cell.push('"');
+            } else {
+                in_quotes = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut cell)),
+            '\r' => (),
+            '\n' => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => cell.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(TileTextGridError::UnterminatedQuote);
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+impl TileTextGrid {
+    /// Creates a grid that covers the given rect, the content of every cell is defined by the
+    /// given function of the position of the cell.
+    pub fn new<F>(rect: TileRect, mut cell: F) -> Self
+    where
+        F: FnMut(Vector2<i32>) -> String,
+    {
+        let origin = Vector2::new(rect.x(), rect.y() + rect.h() - 1);
+        let rows = (0..rect.h())
+            .map(|row| {
+                (0..rect.w())
+                    .map(|column| cell(origin + Vector2::new(column, -row)))
+                    .collect()
+            })
+            .collect();
+        Self { origin, rows }
+    }
+
+    /// Iterates over the non-empty cells of the grid in the form of (position, text) pairs.
+    pub fn cells(&self) -> impl Iterator<Item = (Vector2<i32>, &str)> + '_ {
+        self.rows.iter().enumerate().flat_map(move |(row, cells)| {
+            cells
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| !cell.is_empty())
+                .map(move |(column, cell)| {
+                    let position = self.origin + Vector2::new(column as i32, -(row as i32));
+                    (position, cell.as_str())
+                })
+        })
+    }
+
+    /// Writes the grid in CSV format.
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{CSV_ORIGIN_PREFIX} {},{}\n", self.origin.x, self.origin.y);
+        for row in self.rows.iter() {
+            let cells = row.iter().map(|cell| csv_cell(cell)).collect::<Vec<_>>();
+            csv += &cells.join(",");
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Reads a grid from CSV format. The origin line is optional, the left-top cell of a grid
+    /// without it is at `(0, 0)`.
+    pub fn from_csv(text: &str) -> Result<Self, TileTextGridError> {
+        let (origin, text) = match text.strip_prefix(CSV_ORIGIN_PREFIX) {
+            Some(rest) => {
+                let (line, text) = rest.split_once('\n').unwrap_or((rest, ""));
+                let invalid = || TileTextGridError::InvalidOrigin(line.trim().to_string());
+                let (x, y) = line.split_once(',').ok_or_else(invalid)?;
+                let x = x.trim().parse().map_err(|_| invalid())?;
+                let y = y.trim().parse().map_err(|_| invalid())?;
+                (Vector2::new(x, y), text)
+            }
+            None => (Vector2::default(), text),
+        };
+        Ok(Self {
+            origin,
+            rows: parse_csv(text)?,
+        })
+    }
+
+    /// Writes the grid in JSON format.
+    pub fn to_json(&self) -> String {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| format!("    {}", serde_json::to_string(row).unwrap_or_default()))
+            .collect::<Vec<_>>();
+        format!(
+            "{{\n  \"origin\": [{}, {}],\n  \"rows\": [\n{}\n  ]\n}}\n",
+            self.origin.x,
+            self.origin.y,
+            rows.join(",\n")
+        )
+    }
+
+    /// Reads a grid from JSON format.
+    pub fn from_json(text: &str) -> Result<Self, TileTextGridError> {
+        let grid: JsonGrid = serde_json::from_str(text)?;
+        Ok(Self {
+            origin: Vector2::from(grid.origin),
+            rows: grid.rows,
+        })
+    }
+
+    /// Parses the cells of the grid as the values of a property of the given type. Returns the
+    /// non-empty cells in the form of (position, value) pairs.
+    pub fn property_values(
+        &self,
+        prop_type: TileSetPropertyType,
+    ) -> Result<Vec<(Vector2<i32>, TileSetPropertyValue)>, TileTextGridError> {
+        self.cells()
+            .map(|(position, text)| {
+                parse_property_value(text, prop_type)
+                    .map(|value| (position, value))
+                    .ok_or_else(|| TileTextGridError::InvalidCell {
+                        position,
+                        text: text.to_string(),
+                    })
+            })
+            .collect()
+    }
+}
+
+impl TileMapData {
+    /// Writes the handles of all tiles to a text grid, that covers the bounding rect of the
+    /// tiles. See [`TileTextGrid`] docs for more info.
+    pub fn to_text_grid(&self) -> TileTextGrid {
+        let Some(rect) = *self.bounding_rect() else {
+            return TileTextGrid::default();
+        };
+        TileTextGrid::new(rect, |position| {
+            self.get(position).map(format_handle).unwrap_or_default()
+        })
+    }
+
+    /// Replaces the tiles in the region of the given grid with the handles from the grid. Empty
+    /// cells remove the tiles. Nothing is changed, if any of the cells is not a valid handle.
+    pub fn apply_text_grid(&mut self, grid: &TileTextGrid) -> Result<(), TileTextGridError> {
+        let mut tiles = Vec::new();
+        for (row, cells) in grid.rows.iter().enumerate() {
+            for (column, cell) in cells.iter().enumerate() {
+                let position = grid.origin + Vector2::new(column as i32, -(row as i32));
+                if cell.is_empty() {
+                    tiles.push((position, None));
+                    continue;
+                }
+                let handle = TileDefinitionHandle::parse(cell).ok_or_else(|| {
+                    TileTextGridError::InvalidCell {
+                        position,
+                        text: cell.clone(),
+                    }
+                })?;
+                tiles.push((position, Some(handle)));
+            }
+        }
+        for (position, handle) in tiles {
+            match handle {
+                Some(handle) => self.set(position, handle),
+                None => self.remove(position),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TileMap {
+    /// Writes the values of the property with the given UUID of all tiles to a text grid, that
+    /// covers the bounding rect of the tiles. Empty cells stay empty. See [`TileTextGrid`] docs
+    /// for more info.
+    pub fn property_text_grid(&self, property_id: Uuid) -> Result<TileTextGrid, TilePropertyError> {
+        let Some(rect) = *self.bounding_rect() else {
+            return Ok(TileTextGrid::default());
+        };
+        let query = self.property_query()?;
+        let values = query
+            .property_values_in_rect(rect, property_id)
+            .collect::<FxHashMap<_, _>>();
+        Ok(TileTextGrid::new(rect, |position| {
+            values
+                .get(&position)
+                .map(format_property_value)
+                .unwrap_or_default()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn v(x: i32, y: i32) -> Vector2<i32> {
+        Vector2::new(x, y)
+    }
+
+    #[test]
+    fn test_csv() {
+        let grid = TileTextGrid {
+            origin: v(-1, 2),
+            rows: vec![
+                vec!["a".into(), "".into(), "b,\"c\"".into()],
+                vec!["".into(), "d".into(), "".into()],
+            ],
+        };
+        let csv = grid.to_csv();
+        assert_eq!(csv, "# origin: -1,2\na,,\"b,\"\"c\"\"\"\n,d,\n");
+        assert_eq!(TileTextGrid::from_csv(&csv).unwrap(), grid);
+        assert_eq!(TileTextGrid::from_json(&grid.to_json()).unwrap(), grid);
+        assert_eq!(
+            grid.cells().collect::<Vec<_>>(),
+            vec![(v(-1, 2), "a"), (v(1, 2), "b,\"c\""), (v(0, 1), "d")]
+        );
+    }
+
+    #[test]
+    fn test_tiles() {
+        let mut data = TileMapData::default();
+        data.set(v(0, 0), TileDefinitionHandle::new(0, 0, 1, -1));
+        data.set(v(1, 1), TileDefinitionHandle::new(1, 0, 2, -3));
+        let grid = data.to_text_grid();
+        assert_eq!(grid.origin, v(0, 1));
+        assert_eq!(grid.rows[0], vec!["".to_string(), "1:0:2:-3".to_string()]);
+
+        let mut copy = TileMapData::default();
+        copy.set(v(0, 1), TileDefinitionHandle::new(0, 0, 0, 0));
+        copy.apply_text_grid(&grid).unwrap();
+        assert_eq!(copy.get(v(0, 1)), None);
+        assert_eq!(copy.get(v(1, 1)), data.get(v(1, 1)));
+        assert_eq!(copy.get(v(0, 0)), data.get(v(0, 0)));
+    }
+
+    #[test]
+    fn test_property_values() {
+        let grid = TileTextGrid::from_csv("1 2 3 4 5 6 7 8 9,\n,x\n").unwrap();
+        assert_eq!(
+            grid.property_values(TileSetPropertyType::NineSlice)
+                .unwrap_err()
+                .to_string(),
+            "Invalid cell \"x\" at (1, -1)"
+        );
+        let values = TileTextGrid::from_csv("1.5,\n")
+            .unwrap()
+            .property_values(TileSetPropertyType::F32)
+            .unwrap();
+        assert_eq!(values, vec![(v(0, 0), TileSetPropertyValue::F32(1.5))]);
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Runtime control of tile animations. See [`TileAnimationState`] docs for more info.
+
+use crate::core::{reflect::prelude::*, visitor::prelude::*};
+
+/// The state of the animation of a single cell of a tile map. Normally every animated tile is
+/// animated by the global clock, so all the tiles of the same animation show the same frame.
+/// A cell with an animation state plays its animation independently, which allows to start, stop
+/// and rewind stateful tiles such as doors and chests without replacing their handles every frame.
+/// See [`super::TileMap::set_tile_animation_state`].
+#[derive(Copy, Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct TileAnimationState {
+    /// The current frame of the animation, relative to the tile in the cell. Frame `0` is the
+    /// tile itself, the fractional part is the progress to the next frame.
+    pub frame: f32,
+    /// True if the animation advances over time.
+    pub playing: bool,
+    /// True if the animation starts over after its last frame. Otherwise the animation stops at
+    /// its last frame.
+    pub looping: bool,
+}
+
+impl Default for TileAnimationState {
+    fn default() -> Self {
+        Self {
+            frame: 0.0,
+            playing: false,
+            looping: true,
+        }
+    }
+}
+
+impl TileAnimationState {
+    /// Creates a new looping animation state.
+    pub fn new(frame: f32, playing: bool) -> Self {
+        Self {
+            frame,
+            playing,
+            looping: true,
+        }
+    }
+
+    /// The index of the frame that should be rendered.
+    pub fn frame_index(&self) -> i32 {
+        self.frame.floor() as i32
+    }
+
+    /// Advances the animation by the given amount of time, using the frame rate and the length
+    /// of the animation sequence of the tile. A non-looping animation stops playing when it
+    /// reaches its last frame.
+    pub fn advance(&mut self, dt: f32, frame_rate: f32, length: i32) {
+        if !self.playing || length <= 0 {
+            return;
+        }
+        let length = length as f32;
+        self.frame += dt * frame_rate;
+        if self.looping {
+            self.frame = self.frame.rem_euclid(length);
+        } else if self.frame >= length - 1.0 {
+            self.frame = length - 1.0;
+            self.playing = false;
+        } else if self.frame < 0.0 {
+            // Negative frame rates play the animation backwards.
+            self.frame = 0.0;
+            self.playing = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_advance() {
+        let mut state = TileAnimationState::new(0.0, true);
+        state.advance(0.5, 10.0, 4);
+        assert_eq!(state.frame_index(), 1);
+        assert!(state.playing);
+
+        let mut state = TileAnimationState {
+            looping: false,
+            ..TileAnimationState::new(0.0, true)
+        };
+        state.advance(0.5, 10.0, 4);
+        assert_eq!(state.frame_index(), 3);
+        assert!(!state.playing);
+
+        let mut state = TileAnimationState::new(1.0, false);
+        state.advance(0.5, 10.0, 4);
+        assert_eq!(state.frame_index(), 1);
+    }
+}
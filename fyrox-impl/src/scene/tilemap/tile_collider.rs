@@ -156,6 +156,29 @@ impl TileCollider {
             TileCollider::Mesh => (), // TODO: Add image-to-mesh conversion
         }
     }
+
+    /// Generate the mesh for this collider, flipped and rotated around the center of the tile by
+    /// the given transformation, see [`TileMapData::set_transformation`].
+    pub fn build_transformed_collider_shape(
+        &self,
+        transform: &Matrix4<f32>,
+        position: Vector3<f32>,
+        transformation: OrthoTransformation,
+        vertices: &mut Vec<Point2<f32>>,
+        triangles: &mut Vec<[u32; 3]>,
+    ) {
+        if transformation.is_identity() {
+            self.build_collider_shape(transform, position, vertices, triangles);
+            return;
+        }
+        let center = position + Vector3::new(0.5, 0.5, 0.0);
+        let matrix = transformation.matrix().to_homogeneous().to_homogeneous();
+        let transform = transform
+            * Matrix4::new_translation(&center)
+            * matrix
+            * Matrix4::new_translation(&-center);
+        self.build_collider_shape(&transform, position, vertices, triangles);
+    }
 }
 
 /// A resource to hold triangle data for a tile collider arranged in rectangle from (0,0) to (1,1).
@@ -461,6 +484,57 @@ impl<'a> Iterator for TokenIter<'a> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn transformed_collider_shape() {
+        // A slope, that is solid at the right side of the tile.
+        let collider = TileCollider::Custom(Resource::new_ok(
+            ResourceKind::Embedded,
+            CustomTileCollider {
+                vertices: vec![
+                    Vector2::new(0.0, 0.0),
+                    Vector2::new(1.0, 0.0),
+                    Vector2::new(1.0, 1.0),
+                ],
+                triangles: vec![TriangleDefinition([0, 1, 2])],
+            },
+        ));
+        let position = Vector3::new(2.0, 3.0, 0.0);
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+        collider.build_transformed_collider_shape(
+            &Matrix4::identity(),
+            position,
+            OrthoTransformation::new(true, 0),
+            &mut vertices,
+            &mut triangles,
+        );
+        // The flipped slope is solid at the left side of the tile.
+        assert_eq!(triangles, vec![[0, 1, 2]]);
+        assert_eq!(
+            vertices,
+            vec![
+                Point2::new(3.0, 3.0),
+                Point2::new(2.0, 3.0),
+                Point2::new(2.0, 4.0)
+            ]
+        );
+
+        vertices.clear();
+        triangles.clear();
+        TileCollider::Rectangle.build_transformed_collider_shape(
+            &Matrix4::identity(),
+            position,
+            OrthoTransformation::new(false, 1),
+            &mut vertices,
+            &mut triangles,
+        );
+        // A rectangle covers the same cell, no matter how it is rotated.
+        for vertex in vertices.iter() {
+            assert!((2.0..=3.0).contains(&vertex.x.round()));
+            assert!((3.0..=4.0).contains(&vertex.y.round()));
+        }
+    }
+
     #[test]
     fn empty() {
         let mut iter = TokenIter::new("");
@@ -0,0 +1,248 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Compact binary encoding of [`Tiles`], that is used to store the tiles by the visitor. Storing
+//! every tile as a separate map entry is slow and produces large files, so the tiles are stored
+//! as a single binary blob instead. The chunks of tile map data are stored in the same form,
+//! with positions relative to the origin of a chunk.
+//!
+//! The blob starts with the version of the format, then goes the palette: the number of distinct
+//! tile handles and the handles themselves in sorted order. The tiles are stored as horizontal
+//! runs of adjacent tiles, sorted by rows and then by columns. Every run contains its position,
+//! its length and the palette indices of its tiles. The position of a run is relative to the
+//! previous run: the row is stored as the offset from the row of the previous run, and the
+//! column is stored as the offset from the end of the previous run, if both runs are in the same
+//! row. All numbers are stored as variable-length integers, signed numbers use zigzag encoding.
+
+use crate::core::{algebra::Vector2, visitor::prelude::*};
+use fxhash::FxHashMap;
+
+use super::*;
+
+const FORMAT_VERSION: u64 = 1;
+
+fn write_unsigned(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn write_signed(bytes: &mut Vec<u8>, value: i64) {
+    write_unsigned(bytes, ((value << 1) ^ (value >> 63)) as u64);
+}
+
+fn error(message: &str) -> VisitError {
+    VisitError::User(format!("Invalid tile data: {message}"))
+}
+
+struct Reader<'a>(std::slice::Iter<'a, u8>);
+
+impl Reader<'_> {
+    fn unsigned(&mut self) -> Result<u64, VisitError> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = *self.0.next().ok_or_else(|| error("unexpected end"))?;
+            if shift > 63 {
+                return Err(error("integer overflow"));
+            }
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+
+    fn signed(&mut self) -> Result<i64, VisitError> {
+        let value = self.unsigned()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    fn count(&mut self) -> Result<usize, VisitError> {
+        usize::try_from(self.unsigned()?).map_err(|_| error("count overflow"))
+    }
+
+    fn i16(&mut self) -> Result<i16, VisitError> {
+        i16::try_from(self.signed()?).map_err(|_| error("handle overflow"))
+    }
+}
+
+/// Encodes the given tiles into a compact binary form. See the module docs for the format.
+pub(super) fn encode_tiles(tiles: &Tiles) -> Vec<u8> {
+    let mut palette = tiles.values().copied().collect::<Vec<_>>();
+    palette.sort_unstable();
+    palette.dedup();
+    let indices = palette
+        .iter()
+        .enumerate()
+        .map(|(i, handle)| (*handle, i as u64))
+        .collect::<FxHashMap<_, _>>();
+
+    let mut positions = tiles.keys().copied().collect::<Vec<_>>();
+    positions.sort_unstable_by_key(|p| (p.y, p.x));
+    // Positions are converted to i64, so the ends of the runs could not overflow.
+    let mut runs = Vec::<(Vector2<i64>, usize)>::new();
+    for position in positions.into_iter().map(|p| p.cast::<i64>()) {
+        match runs.last_mut() {
+            Some((start, length))
+                if start.y == position.y && start.x + *length as i64 == position.x =>
+            {
+                *length += 1
+            }
+            _ => runs.push((position, 1)),
+        }
+    }
+
+    let mut bytes = Vec::new();
+    write_unsigned(&mut bytes, FORMAT_VERSION);
+    write_unsigned(&mut bytes, palette.len() as u64);
+    for handle in palette.iter() {
+        for value in [handle.page.x, handle.page.y, handle.tile.x, handle.tile.y] {
+            write_signed(&mut bytes, value as i64);
+        }
+    }
+    write_unsigned(&mut bytes, runs.len() as u64);
+    let mut previous = Vector2::<i64>::new(0, 0);
+    for (start, length) in runs {
+        let dy = start.y - previous.y;
+        let x = if dy == 0 { previous.x } else { 0 };
+        write_signed(&mut bytes, dy);
+        write_signed(&mut bytes, start.x - x);
+        write_unsigned(&mut bytes, length as u64);
+        for i in 0..length {
+            let position = Vector2::new(start.x + i as i64, start.y).map(|c| c as i32);
+            write_unsigned(&mut bytes, indices[&tiles[&position]]);
+        }
+        previous = Vector2::new(start.x + length as i64, start.y);
+    }
+    bytes
+}
+
+/// Decodes the tiles from the binary form, that was produced by [`encode_tiles`].
+pub(super) fn decode_tiles(bytes: &[u8]) -> Result<Tiles, VisitError> {
+    let mut reader = Reader(bytes.iter());
+    let version = reader.unsigned()?;
+    if version != FORMAT_VERSION {
+        return Err(error(&format!("unsupported version {version}")));
+    }
+    let palette_len = reader.count()?;
+    let mut palette = Vec::with_capacity(palette_len.min(bytes.len()));
+    for _ in 0..palette_len {
+        palette.push(TileDefinitionHandle::new(
+            reader.i16()?,
+            reader.i16()?,
+            reader.i16()?,
+            reader.i16()?,
+        ));
+    }
+    let mut tiles = Tiles::default();
+    let mut previous = Vector2::<i64>::new(0, 0);
+    for _ in 0..reader.count()? {
+        let dy = reader.signed()?;
+        let dx = reader.signed()?;
+        let x = if dy == 0 { previous.x } else { 0 };
+        let (Some(x), Some(y)) = (x.checked_add(dx), previous.y.checked_add(dy)) else {
+            return Err(error("position overflow"));
+        };
+        let row = i32::try_from(y).map_err(|_| error("position overflow"))?;
+        let length = reader.count()?;
+        for i in 0..length {
+            let index = reader.count()?;
+            let handle = *palette
+                .get(index)
+                .ok_or_else(|| error("palette index out of bounds"))?;
+            let column = x
+                .checked_add(i as i64)
+                .and_then(|x| i32::try_from(x).ok())
+                .ok_or_else(|| error("position overflow"))?;
+            tiles.insert(Vector2::new(column, row), handle);
+        }
+        previous = Vector2::new(x.saturating_add(length as i64), y);
+    }
+    if !reader.0.as_slice().is_empty() {
+        return Err(error("unexpected trailing data"));
+    }
+    Ok(tiles)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn v(x: i32, y: i32) -> Vector2<i32> {
+        Vector2::new(x, y)
+    }
+
+    fn make_tiles() -> Tiles {
+        let mut tiles = Tiles::default();
+        for x in -3..20 {
+            tiles.insert(v(x, 0), TileDefinitionHandle::new(0, 0, (x % 3) as i16, 0));
+        }
+        tiles.insert(v(5, -7), TileDefinitionHandle::new(-1, 2, -3, 4));
+        tiles.insert(v(i32::MAX, i32::MIN), TileDefinitionHandle::new(1, 1, 1, 1));
+        tiles
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let tiles = make_tiles();
+        let bytes = encode_tiles(&tiles);
+        assert_eq!(decode_tiles(&bytes).unwrap(), tiles);
+        assert!(decode_tiles(&bytes[..bytes.len() - 1]).is_err());
+        assert_eq!(
+            decode_tiles(&encode_tiles(&Tiles::default())).unwrap(),
+            Tiles::default()
+        );
+    }
+
+    #[test]
+    fn test_visit() {
+        let mut tiles = make_tiles();
+        let mut visitor = Visitor::new();
+        tiles.visit("Tiles", &mut visitor).unwrap();
+        let bytes = visitor.save_binary_to_vec().unwrap();
+        let mut visitor = Visitor::load_from_memory(&bytes).unwrap();
+        let mut loaded = Tiles::default();
+        loaded.visit("Tiles", &mut visitor).unwrap();
+        assert_eq!(loaded, tiles);
+    }
+
+    #[test]
+    fn test_visit_old_format() {
+        // The tiles were stored as a map before the compact encoding was added.
+        let tiles = make_tiles();
+        let mut map = TileGridMap::<TileDefinitionHandle>::default();
+        map.extend(tiles.iter().map(|(p, h)| (*p, *h)));
+        let mut visitor = Visitor::new();
+        map.visit("Tiles", &mut visitor).unwrap();
+        let old_bytes = visitor.save_binary_to_vec().unwrap();
+        let mut visitor = Visitor::load_from_memory(&old_bytes).unwrap();
+        let mut loaded = Tiles::default();
+        loaded.visit("Tiles", &mut visitor).unwrap();
+        assert_eq!(loaded, tiles);
+
+        let mut visitor = Visitor::new();
+        loaded.visit("Tiles", &mut visitor).unwrap();
+        assert!(visitor.save_binary_to_vec().unwrap().len() < old_bytes.len());
+    }
+}
@@ -23,7 +23,7 @@
 //! Tile sources can be randomized and they can repeat to create varied effects
 //! while editing tile maps.
 
-use fyrox_core::swap_hash_map_entry;
+use fyrox_core::{swap_hash_map_entry, visitor::BinaryBlob};
 
 use crate::{
     core::{algebra::Vector2, reflect::prelude::*, visitor::prelude::*},
@@ -319,6 +319,25 @@ impl TileSource for PartialRandomTileSource<'_> {
     }
 }
 
+/// A tile source that adapts another source so that the tiles which have variants are replaced
+/// by one of their variants, chosen randomly according to the weights. Tiles without variants
+/// are produced as they are. The variants are usually taken from [`TileMapBrush::variants`].
+/// See [`TileVariants`] for more info.
+pub struct VariantTileSource<'a, S>(pub &'a S, pub &'a [TileVariants]);
+
+impl<S: TileSource> TileSource for VariantTileSource<'_, S> {
+    fn transformation(&self) -> OrthoTransformation {
+        self.0.transformation()
+    }
+    fn get_at(&self, position: Vector2<i32>) -> Option<TileDefinitionHandle> {
+        let handle = self.0.get_at(position)?;
+        match self.1.iter().find(|v| v.tile == handle) {
+            Some(variants) => variants.choose(&mut thread_rng()),
+            None => Some(handle),
+        }
+    }
+}
+
 /// A tile source that adapts another source so that it infinitely repeats the tiles
 /// within the given rect.
 pub struct RepeatTileSource<'a, S> {
@@ -363,7 +382,23 @@ impl TileSource for Tiles {
 
 impl Visit for Tiles {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
-        self.0.visit(name, visitor)
+        // The tiles are stored in the compact binary form, but the tiles that were saved as a
+        // map before the compact form was introduced can still be loaded.
+        if visitor.is_reading() {
+            let mut bytes = Vec::<u8>::new();
+            let mut blob = BinaryBlob { vec: &mut bytes };
+            if blob.visit(name, visitor).is_ok() {
+                *self = decode_tiles(&bytes)?;
+                Ok(())
+            } else {
+                self.0.visit(name, visitor)
+            }
+        } else {
+            BinaryBlob {
+                vec: &mut encode_tiles(self),
+            }
+            .visit(name, visitor)
+        }
     }
 }
 
@@ -437,9 +472,19 @@ impl Stamp {
         let Some(rect) = *rect else {
             return;
         };
-        let center = rect.center();
+        self.build_with_anchor(source, rect.center());
+    }
+    /// Clear this stamp and fill it with the given tiles.
+    /// The tiles are moved so that the given anchor is (0,0).
+    /// The transform is set to identity.
+    pub fn build_with_anchor<I: Iterator<Item = (Vector2<i32>, TileDefinitionHandle)>>(
+        &mut self,
+        source: I,
+        anchor: Vector2<i32>,
+    ) {
+        self.clear();
         for (p, h) in source {
-            self.insert(p - center, h);
+            self.insert(p - anchor, h);
         }
     }
     /// Rotate the stamp by the given number of 90-degree turns.
@@ -539,4 +584,31 @@ mod tests {
             TileDefinitionHandle::default()
         );
     }
+
+    #[test]
+    fn variant_tile_source() {
+        let tile = |x| TileDefinitionHandle::new(0, 0, x, 0);
+        let variants = [TileVariants {
+            tile: tile(0),
+            variants: vec![
+                TileVariant {
+                    tile: tile(1),
+                    weight: 0.0,
+                },
+                TileVariant {
+                    tile: tile(2),
+                    weight: 1.0,
+                },
+            ],
+        }];
+        let mut tiles = Tiles::default();
+        tiles.insert(Vector2::new(0, 0), tile(0));
+        tiles.insert(Vector2::new(1, 0), tile(3));
+        let source = VariantTileSource(&tiles, &variants);
+        for _ in 0..10 {
+            assert_eq!(source.get_at(Vector2::new(0, 0)), Some(tile(2)));
+        }
+        assert_eq!(source.get_at(Vector2::new(1, 0)), Some(tile(3)));
+        assert_eq!(source.get_at(Vector2::new(2, 0)), None);
+    }
 }
@@ -0,0 +1,817 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Data structures of Tiled maps and tile sets and their parsing from `.tmx` and `.tsx` files.
+//! Only the data that has a counterpart in tile maps is read, everything else (object layers,
+//! image layers, animations, etc.) is silently skipped.
+
+use crate::{
+    core::{algebra::Vector2, log::Log},
+    scene::tilemap::TileGridLayout,
+};
+use base64::Engine;
+use roxmltree::{Document, Node};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+const FLIPPED_HORIZONTALLY_FLAG: u32 = 0x80000000;
+const FLIPPED_VERTICALLY_FLAG: u32 = 0x40000000;
+const FLIPPED_DIAGONALLY_FLAG: u32 = 0x20000000;
+const ROTATED_HEXAGONAL_120_FLAG: u32 = 0x10000000;
+const FLAGS_MASK: u32 = FLIPPED_HORIZONTALLY_FLAG
+    | FLIPPED_VERTICALLY_FLAG
+    | FLIPPED_DIAGONALLY_FLAG
+    | ROTATED_HEXAGONAL_120_FLAG;
+
+/// An error that may occur during Tiled map or tile set import.
+#[derive(Debug)]
+pub enum TiledError {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// The file is not a valid XML document.
+    Xml(roxmltree::Error),
+    /// A required attribute is missing.
+    MissingAttribute {
+        /// The name of the element.
+        element: String,
+        /// The name of the missing attribute.
+        attribute: &'static str,
+    },
+    /// An attribute has a value that could not be parsed.
+    InvalidAttribute {
+        /// The name of the attribute.
+        attribute: &'static str,
+        /// The value of the attribute.
+        value: String,
+    },
+    /// The file uses a feature that cannot be represented by a tile map.
+    Unsupported(String),
+    /// The tile data of a layer is malformed.
+    InvalidData(String),
+}
+
+impl Display for TiledError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TiledError::Io(err) => write!(f, "Unable to read Tiled file: {err}"),
+            TiledError::Xml(err) => write!(f, "Malformed Tiled file: {err}"),
+            TiledError::MissingAttribute { element, attribute } => {
+                write!(f, "Element <{element}> has no {attribute} attribute.")
+            }
+            TiledError::InvalidAttribute { attribute, value } => {
+                write!(f, "Invalid value of {attribute} attribute: {value}")
+            }
+            TiledError::Unsupported(feature) => write!(f, "Unsupported Tiled feature: {feature}"),
+            TiledError::InvalidData(message) => write!(f, "Invalid layer data: {message}"),
+        }
+    }
+}
+
+impl Error for TiledError {}
+
+impl From<std::io::Error> for TiledError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<roxmltree::Error> for TiledError {
+    fn from(err: roxmltree::Error) -> Self {
+        Self::Xml(err)
+    }
+}
+
+fn attribute<'a>(node: Node<'a, '_>, name: &'static str) -> Result<&'a str, TiledError> {
+    node.attribute(name)
+        .ok_or_else(|| TiledError::MissingAttribute {
+            element: node.tag_name().name().to_string(),
+            attribute: name,
+        })
+}
+
+fn parse_value<T: FromStr>(name: &'static str, value: &str) -> Result<T, TiledError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| TiledError::InvalidAttribute {
+            attribute: name,
+            value: value.to_string(),
+        })
+}
+
+fn parse_attribute<T: FromStr>(node: Node, name: &'static str) -> Result<T, TiledError> {
+    parse_value(name, attribute(node, name)?)
+}
+
+fn parse_optional_attribute<T: FromStr>(
+    node: Node,
+    name: &'static str,
+    default: T,
+) -> Result<T, TiledError> {
+    match node.attribute(name) {
+        Some(value) => parse_value(name, value),
+        None => Ok(default),
+    }
+}
+
+fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |child| child.has_tag_name(name))
+}
+
+fn child<'a, 'input: 'a>(node: Node<'a, 'input>, name: &'static str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+/// The value of a custom property of a tile.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TiledPropertyValue {
+    /// `int` property, `object` references are imported as integers too.
+    Int(i32),
+    /// `float` property.
+    Float(f32),
+    /// `bool` property.
+    Bool(bool),
+    /// `string`, `color` or `file` property.
+    String(String),
+}
+
+impl Display for TiledPropertyValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TiledPropertyValue::Int(value) => write!(f, "{value}"),
+            TiledPropertyValue::Float(value) => write!(f, "{value}"),
+            TiledPropertyValue::Bool(value) => write!(f, "{value}"),
+            TiledPropertyValue::String(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// A named custom property of a tile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledProperty {
+    /// The name of the property.
+    pub name: String,
+    /// The value of the property.
+    pub value: TiledPropertyValue,
+}
+
+fn parse_properties(node: Node) -> Result<Vec<TiledProperty>, TiledError> {
+    let mut properties = Vec::new();
+    let Some(list) = child(node, "properties") else {
+        return Ok(properties);
+    };
+    for property in children(list, "property") {
+        let name = attribute(property, "name")?.to_string();
+        // Multiline strings are stored as the text of the element.
+        let text = property
+            .attribute("value")
+            .or_else(|| property.text())
+            .unwrap_or_default();
+        let value = match property.attribute("type").unwrap_or("string") {
+            "int" | "object" => TiledPropertyValue::Int(parse_value("value", text)?),
+            "float" => TiledPropertyValue::Float(parse_value("value", text)?),
+            "bool" => TiledPropertyValue::Bool(parse_value("value", text)?),
+            "class" => {
+                Log::warn(format!(
+                    "Tiled property {name} of class type is not supported and will be skipped."
+                ));
+                continue;
+            }
+            _ => TiledPropertyValue::String(text.to_string()),
+        };
+        properties.push(TiledProperty { name, value });
+    }
+    Ok(properties)
+}
+
+/// An image referenced by a tile set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledImage {
+    /// The path to the image. Relative paths are resolved against the directory of the file
+    /// that references the image.
+    pub source: PathBuf,
+    /// The size of the image in pixels, if it is specified in the file.
+    pub size: Option<Vector2<u32>>,
+}
+
+impl TiledImage {
+    fn parse(node: Node, base_dir: &Path) -> Result<Self, TiledError> {
+        let size = match (node.attribute("width"), node.attribute("height")) {
+            (Some(width), Some(height)) => Some(Vector2::new(
+                parse_value("width", width)?,
+                parse_value("height", height)?,
+            )),
+            _ => None,
+        };
+        Ok(Self {
+            source: base_dir.join(attribute(node, "source")?),
+            size,
+        })
+    }
+}
+
+/// A collision shape of a tile. The coordinates are in pixels, relative to the left-top corner
+/// of the tile, with y axis pointing down.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TiledShape {
+    /// An axis-aligned rectangle.
+    Rectangle {
+        /// The position of the left-top corner of the rectangle.
+        position: Vector2<f32>,
+        /// The size of the rectangle.
+        size: Vector2<f32>,
+    },
+    /// A closed polygon.
+    Polygon(Vec<Vector2<f32>>),
+}
+
+impl TiledShape {
+    fn parse(object: Node) -> Result<Option<Self>, TiledError> {
+        let position = Vector2::new(
+            parse_optional_attribute(object, "x", 0.0)?,
+            parse_optional_attribute(object, "y", 0.0)?,
+        );
+        if let Some(polygon) = child(object, "polygon") {
+            let points = attribute(polygon, "points")?
+                .split_whitespace()
+                .map(|point| {
+                    let (x, y) =
+                        point
+                            .split_once(',')
+                            .ok_or_else(|| TiledError::InvalidAttribute {
+                                attribute: "points",
+                                value: point.to_string(),
+                            })?;
+                    Ok(position
+                        + Vector2::new(parse_value("points", x)?, parse_value("points", y)?))
+                })
+                .collect::<Result<Vec<_>, TiledError>>()?;
+            Ok(Some(TiledShape::Polygon(points)))
+        } else if ["ellipse", "point", "polyline", "text"]
+            .into_iter()
+            .any(|shape| child(object, shape).is_some())
+        {
+            // Ellipses, points, polylines and texts cannot be used as colliders.
+            Ok(None)
+        } else {
+            let size = Vector2::new(
+                parse_optional_attribute(object, "width", 0.0)?,
+                parse_optional_attribute(object, "height", 0.0)?,
+            );
+            if size.x > 0.0 && size.y > 0.0 {
+                Ok(Some(TiledShape::Rectangle { position, size }))
+            } else {
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// A tile of a tile set that has some data besides its image.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledTile {
+    /// The local id of the tile within its tile set.
+    pub id: u32,
+    /// The image of the tile, only used by image collection tile sets.
+    pub image: Option<TiledImage>,
+    /// Custom properties of the tile.
+    pub properties: Vec<TiledProperty>,
+    /// Collision shapes of the tile.
+    pub colliders: Vec<TiledShape>,
+}
+
+/// A Tiled tile set, either embedded in a map or loaded from a `.tsx` file. A tile set is either
+/// based on a single image that is split into tiles, or it is a collection of images where
+/// each tile has its own image.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledTileSet {
+    /// The name of the tile set.
+    pub name: String,
+    /// The size of each tile in pixels.
+    pub tile_size: Vector2<u32>,
+    /// The margin around the tiles in the image, in pixels.
+    pub margin: u32,
+    /// The spacing between the tiles in the image, in pixels.
+    pub spacing: u32,
+    /// The number of tile columns in the image. Zero for image collections.
+    pub columns: u32,
+    /// The number of tiles in the tile set.
+    pub tile_count: u32,
+    /// The image of the tile set, `None` for image collections.
+    pub image: Option<TiledImage>,
+    /// The tiles that have some additional data.
+    pub tiles: Vec<TiledTile>,
+}
+
+impl TiledTileSet {
+    /// Reads a tile set from the content of a `.tsx` file. `base_dir` is the directory that is
+    /// used to resolve relative paths of images.
+    pub fn parse(text: &str, base_dir: &Path) -> Result<Self, TiledError> {
+        let document = Document::parse(text)?;
+        Self::from_node(document.root_element(), base_dir)
+    }
+
+    /// Reads a tile set from a `.tsx` file.
+    pub fn from_file(path: &Path) -> Result<Self, TiledError> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text, path.parent().unwrap_or(Path::new("")))
+    }
+
+    fn from_node(node: Node, base_dir: &Path) -> Result<Self, TiledError> {
+        let mut tiles = Vec::new();
+        for tile in children(node, "tile") {
+            let colliders = match child(tile, "objectgroup") {
+                Some(group) => children(group, "object")
+                    .filter_map(|object| TiledShape::parse(object).transpose())
+                    .collect::<Result<Vec<_>, _>>()?,
+                None => Vec::new(),
+            };
+            tiles.push(TiledTile {
+                id: parse_attribute(tile, "id")?,
+                image: child(tile, "image")
+                    .map(|image| TiledImage::parse(image, base_dir))
+                    .transpose()?,
+                properties: parse_properties(tile)?,
+                colliders,
+            });
+        }
+        Ok(Self {
+            name: node.attribute("name").unwrap_or_default().to_string(),
+            tile_size: Vector2::new(
+                parse_attribute(node, "tilewidth")?,
+                parse_attribute(node, "tileheight")?,
+            ),
+            margin: parse_optional_attribute(node, "margin", 0)?,
+            spacing: parse_optional_attribute(node, "spacing", 0)?,
+            columns: parse_optional_attribute(node, "columns", 0)?,
+            tile_count: parse_optional_attribute(node, "tilecount", 0)?,
+            image: child(node, "image")
+                .map(|image| TiledImage::parse(image, base_dir))
+                .transpose()?,
+            tiles,
+        })
+    }
+
+    /// Returns the tile with the given local id, if it has any additional data.
+    pub fn tile(&self, id: u32) -> Option<&TiledTile> {
+        self.tiles.iter().find(|tile| tile.id == id)
+    }
+
+    /// Returns the pixel position of the left-top corner of the tile with the given local id
+    /// within the image of the tile set.
+    pub fn tile_origin(&self, id: u32) -> Vector2<u32> {
+        let columns = self.columns.max(1);
+        let (column, row) = (id % columns, id / columns);
+        Vector2::new(
+            self.margin + column * (self.tile_size.x + self.spacing),
+            self.margin + row * (self.tile_size.y + self.spacing),
+        )
+    }
+}
+
+/// A tile set used by a map along with the global id of its first tile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledMapTileSet {
+    /// The global id of the first tile of the tile set.
+    pub first_gid: u32,
+    /// The tile set.
+    pub tile_set: TiledTileSet,
+}
+
+/// Defines which rows or columns of a hexagonal map are shifted.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TiledStaggerIndex {
+    /// Odd rows or columns are shifted.
+    #[default]
+    Odd,
+    /// Even rows or columns are shifted.
+    Even,
+}
+
+/// A tile layer of a map.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledLayer {
+    /// The name of the layer. Names of the layers in groups are prefixed with the group names.
+    pub name: String,
+    /// Whether the layer is visible. A layer is hidden if its group is hidden.
+    pub visible: bool,
+    /// Non-empty cells of the layer, in Tiled coordinates (y axis pointing down) along with the
+    /// global tile id of each cell. Flipping flags are removed from the ids.
+    pub tiles: Vec<(Vector2<i32>, u32)>,
+}
+
+/// A Tiled map loaded from a `.tmx` file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledMap {
+    /// The grid layout of the map.
+    pub layout: TileGridLayout,
+    /// Rows or columns of the hexagonal map that are shifted.
+    pub stagger_index: TiledStaggerIndex,
+    /// The size of the map in cells. Infinite maps are not limited by this size.
+    pub size: Vector2<u32>,
+    /// The size of each cell in pixels.
+    pub tile_size: Vector2<u32>,
+    /// The tile sets used by the map, sorted by their first global id.
+    pub tile_sets: Vec<TiledMapTileSet>,
+    /// Tile layers of the map, in the order of drawing.
+    pub layers: Vec<TiledLayer>,
+}
+
+impl TiledMap {
+    /// Reads a map from the content of a `.tmx` file. `base_dir` is the directory that is used to
+    /// resolve relative paths of external tile sets and images.
+    pub fn parse(text: &str, base_dir: &Path) -> Result<Self, TiledError> {
+        let document = Document::parse(text)?;
+        let node = document.root_element();
+        if !node.has_tag_name("map") {
+            return Err(TiledError::InvalidData(format!(
+                "Expected <map> root element, got <{}>",
+                node.tag_name().name()
+            )));
+        }
+
+        let layout = match node.attribute("orientation").unwrap_or("orthogonal") {
+            "orthogonal" => TileGridLayout::Square,
+            "hexagonal" => match node.attribute("staggeraxis").unwrap_or("y") {
+                "x" => TileGridLayout::HexFlatTop,
+                _ => TileGridLayout::HexPointyTop,
+            },
+            orientation => {
+                return Err(TiledError::Unsupported(format!(
+                    "{orientation} map orientation"
+                )))
+            }
+        };
+        let stagger_index = match node.attribute("staggerindex") {
+            Some("even") => TiledStaggerIndex::Even,
+            _ => TiledStaggerIndex::Odd,
+        };
+
+        let mut tile_sets = Vec::new();
+        for tile_set in children(node, "tileset") {
+            let first_gid = parse_attribute(tile_set, "firstgid")?;
+            let tile_set = match tile_set.attribute("source") {
+                Some(source) => TiledTileSet::from_file(&base_dir.join(source))?,
+                None => TiledTileSet::from_node(tile_set, base_dir)?,
+            };
+            tile_sets.push(TiledMapTileSet {
+                first_gid,
+                tile_set,
+            });
+        }
+        tile_sets.sort_by_key(|tile_set| tile_set.first_gid);
+
+        let mut layers = Vec::new();
+        parse_layers(node, "", true, &mut layers)?;
+
+        Ok(Self {
+            layout,
+            stagger_index,
+            size: Vector2::new(
+                parse_attribute(node, "width")?,
+                parse_attribute(node, "height")?,
+            ),
+            tile_size: Vector2::new(
+                parse_attribute(node, "tilewidth")?,
+                parse_attribute(node, "tileheight")?,
+            ),
+            tile_sets,
+            layers,
+        })
+    }
+
+    /// Reads a map from a `.tmx` file, along with all external tile sets it uses.
+    pub fn from_file(path: &Path) -> Result<Self, TiledError> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text, path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// Returns the tile set that contains the tile with the given global id along with the
+    /// index of the tile set and the local id of the tile.
+    pub fn find_tile_set(&self, gid: u32) -> Option<(usize, &TiledTileSet, u32)> {
+        let index = self
+            .tile_sets
+            .partition_point(|tile_set| tile_set.first_gid <= gid)
+            .checked_sub(1)?;
+        let entry = &self.tile_sets[index];
+        Some((index, &entry.tile_set, gid - entry.first_gid))
+    }
+}
+
+fn parse_layers(
+    node: Node,
+    prefix: &str,
+    visible: bool,
+    layers: &mut Vec<TiledLayer>,
+) -> Result<(), TiledError> {
+    for layer in node.children().filter(|child| child.is_element()) {
+        let name = format!("{prefix}{}", layer.attribute("name").unwrap_or_default());
+        let layer_visible = visible && layer.attribute("visible") != Some("0");
+        match layer.tag_name().name() {
+            "layer" => {
+                let Some(data) = child(layer, "data") else {
+                    continue;
+                };
+                let width = parse_attribute(layer, "width")?;
+                let mut tiles = Vec::new();
+                let chunks = children(data, "chunk").collect::<Vec<_>>();
+                if chunks.is_empty() {
+                    read_tiles(data, data, Vector2::new(0, 0), width, &mut tiles)?;
+                } else {
+                    for chunk in chunks {
+                        let origin = Vector2::new(
+                            parse_attribute(chunk, "x")?,
+                            parse_attribute(chunk, "y")?,
+                        );
+                        let width = parse_attribute(chunk, "width")?;
+                        read_tiles(data, chunk, origin, width, &mut tiles)?;
+                    }
+                }
+                if tiles.iter().any(|(_, gid)| gid & FLAGS_MASK != 0) {
+                    Log::warn(format!(
+                        "Tiled layer {name} has flipped or rotated tiles. \
+                        Flipping is not supported and will be ignored."
+                    ));
+                    for (_, gid) in tiles.iter_mut() {
+                        *gid &= !FLAGS_MASK;
+                    }
+                }
+                layers.push(TiledLayer {
+                    name,
+                    visible: layer_visible,
+                    tiles,
+                });
+            }
+            "group" => parse_layers(layer, &format!("{name}/"), layer_visible, layers)?,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn read_tiles(
+    data: Node,
+    content: Node,
+    origin: Vector2<i32>,
+    width: i32,
+    tiles: &mut Vec<(Vector2<i32>, u32)>,
+) -> Result<(), TiledError> {
+    let gids = decode_gids(data, content)?;
+    let width = width.max(1);
+    for (i, gid) in gids.into_iter().enumerate() {
+        if gid != 0 {
+            let i = i as i32;
+            tiles.push((origin + Vector2::new(i % width, i / width), gid));
+        }
+    }
+    Ok(())
+}
+
+fn decode_gids(data: Node, content: Node) -> Result<Vec<u32>, TiledError> {
+    let text = content.text().unwrap_or_default().trim();
+    match data.attribute("encoding") {
+        None => children(content, "tile")
+            .map(|tile| parse_optional_attribute(tile, "gid", 0))
+            .collect(),
+        Some("csv") => text
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| parse_value("gid", value))
+            .collect(),
+        Some("base64") => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(text.as_bytes())
+                .map_err(|err| TiledError::InvalidData(err.to_string()))?;
+            let bytes = match data.attribute("compression") {
+                None => bytes,
+                Some("zlib") => {
+                    inflate::inflate_bytes_zlib(&bytes).map_err(TiledError::InvalidData)?
+                }
+                Some("gzip") => inflate::inflate_bytes(gzip_payload(&bytes)?)
+                    .map_err(TiledError::InvalidData)?,
+                Some(compression) => {
+                    return Err(TiledError::Unsupported(format!(
+                        "{compression} compression"
+                    )))
+                }
+            };
+            if bytes.len() % 4 != 0 {
+                return Err(TiledError::InvalidData(format!(
+                    "{} bytes of tile data is not a multiple of 4",
+                    bytes.len()
+                )));
+            }
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                .collect())
+        }
+        Some(encoding) => Err(TiledError::Unsupported(format!("{encoding} encoding"))),
+    }
+}
+
+/// Strips gzip header from the data, leaving raw deflate stream.
+fn gzip_payload(bytes: &[u8]) -> Result<&[u8], TiledError> {
+    const FHCRC: u8 = 2;
+    const FEXTRA: u8 = 4;
+    const FNAME: u8 = 8;
+    const FCOMMENT: u8 = 16;
+
+    let invalid = || TiledError::InvalidData("Malformed gzip header".to_string());
+    if bytes.len() < 10 || bytes[0] != 0x1f || bytes[1] != 0x8b {
+        return Err(invalid());
+    }
+    let flags = bytes[3];
+    let mut offset = 10;
+    if flags & FEXTRA != 0 {
+        let length = bytes.get(offset..offset + 2).ok_or_else(invalid)?;
+        offset += 2 + u16::from_le_bytes([length[0], length[1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = bytes
+                .get(offset..)
+                .and_then(|rest| rest.iter().position(|b| *b == 0))
+                .ok_or_else(invalid)?;
+            offset += end + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        offset += 2;
+    }
+    bytes.get(offset..).ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MAP: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="3" height="2" tilewidth="16" tileheight="16" infinite="0">
+ <tileset firstgid="1" name="terrain" tilewidth="16" tileheight="16" tilecount="4" columns="2">
+  <image source="terrain.png" width="32" height="32"/>
+  <tile id="1">
+   <properties>
+    <property name="damage" type="int" value="5"/>
+    <property name="surface" value="ice"/>
+   </properties>
+   <objectgroup>
+    <object id="1" x="0" y="0" width="16" height="8"/>
+    <object id="2" x="2" y="8">
+     <polygon points="0,0 4,0 4,4"/>
+    </object>
+    <object id="3" x="0" y="0" width="4" height="4">
+     <ellipse/>
+    </object>
+   </objectgroup>
+  </tile>
+ </tileset>
+ <tileset firstgid="5" name="props" tilewidth="16" tileheight="16" tilecount="1" columns="1">
+  <image source="props/props.png" width="16" height="16"/>
+ </tileset>
+ <layer id="1" name="ground" width="3" height="2">
+  <data encoding="csv">
+1,2,0,
+0,4,5
+</data>
+ </layer>
+ <group id="2" name="decor" visible="0">
+  <layer id="3" name="top" width="2" height="2">
+   <data>
+    <tile gid="2"/>
+    <tile/>
+    <tile/>
+    <tile gid="1"/>
+   </data>
+  </layer>
+ </group>
+</map>
+"#;
+
+    fn base64_map(compression: &str, data: &str) -> String {
+        format!(
+            r#"<map orientation="hexagonal" staggeraxis="x" staggerindex="even" width="2" height="2" tilewidth="16" tileheight="16">
+ <layer name="hex" width="2" height="2">
+  <data encoding="base64" compression="{compression}">
+   {data}
+  </data>
+ </layer>
+</map>"#
+        )
+    }
+
+    #[test]
+    fn test_parse_map() {
+        let map = TiledMap::parse(MAP, Path::new("maps")).unwrap();
+        assert_eq!(map.layout, TileGridLayout::Square);
+        assert_eq!(map.size, Vector2::new(3, 2));
+        assert_eq!(map.tile_sets.len(), 2);
+
+        let terrain = &map.tile_sets[0].tile_set;
+        assert_eq!(terrain.columns, 2);
+        assert_eq!(
+            terrain.image.as_ref().unwrap().source,
+            Path::new("maps").join("terrain.png")
+        );
+        assert_eq!(terrain.tile_origin(3), Vector2::new(16, 16));
+        let tile = terrain.tile(1).unwrap();
+        assert_eq!(
+            tile.properties,
+            vec![
+                TiledProperty {
+                    name: "damage".to_string(),
+                    value: TiledPropertyValue::Int(5),
+                },
+                TiledProperty {
+                    name: "surface".to_string(),
+                    value: TiledPropertyValue::String("ice".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            tile.colliders,
+            vec![
+                TiledShape::Rectangle {
+                    position: Vector2::new(0.0, 0.0),
+                    size: Vector2::new(16.0, 8.0),
+                },
+                TiledShape::Polygon(vec![
+                    Vector2::new(2.0, 8.0),
+                    Vector2::new(6.0, 8.0),
+                    Vector2::new(6.0, 12.0),
+                ]),
+            ]
+        );
+
+        assert_eq!(map.find_tile_set(4).map(|(i, _, id)| (i, id)), Some((0, 3)));
+        assert_eq!(map.find_tile_set(5).map(|(i, _, id)| (i, id)), Some((1, 0)));
+        assert!(map.find_tile_set(0).is_none());
+
+        assert_eq!(map.layers.len(), 2);
+        assert_eq!(map.layers[0].name, "ground");
+        assert!(map.layers[0].visible);
+        assert_eq!(
+            map.layers[0].tiles,
+            vec![
+                (Vector2::new(0, 0), 1),
+                (Vector2::new(1, 0), 2),
+                (Vector2::new(1, 1), 4),
+                (Vector2::new(2, 1), 5),
+            ]
+        );
+        assert_eq!(map.layers[1].name, "decor/top");
+        assert!(!map.layers[1].visible);
+        assert_eq!(
+            map.layers[1].tiles,
+            vec![(Vector2::new(0, 0), 2), (Vector2::new(1, 1), 1)]
+        );
+    }
+
+    #[test]
+    fn test_parse_base64() {
+        let expected = vec![
+            (Vector2::new(0, 0), 1),
+            (Vector2::new(0, 1), 2),
+            (Vector2::new(1, 1), 3),
+        ];
+        for (compression, data) in [
+            ("zlib", "eJxjZIAAJiBmZmBoAAAAvACH"),
+            ("gzip", "H4sIAAAAAAACA2NkgAAmIGZmYGgAAHfx+/8QAAAA"),
+        ] {
+            let map = TiledMap::parse(&base64_map(compression, data), Path::new("")).unwrap();
+            assert_eq!(map.layout, TileGridLayout::HexFlatTop);
+            assert_eq!(map.stagger_index, TiledStaggerIndex::Even);
+            assert_eq!(map.layers[0].tiles, expected);
+        }
+        assert!(matches!(
+            TiledMap::parse(&base64_map("zstd", "AAAA"), Path::new("")),
+            Err(TiledError::Unsupported(_))
+        ));
+    }
+}
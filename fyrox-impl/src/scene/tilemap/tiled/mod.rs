@@ -0,0 +1,530 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Importer of maps and tile sets made with [Tiled](https://www.mapeditor.org/) map editor.
+//!
+//! A `.tmx` map is converted to a pivot node with one [`TileMap`] child per tile layer, all the
+//! tile sets of the map (embedded or external `.tsx` files) are merged into a single [`TileSet`],
+//! one page per Tiled tile set. Custom tile properties become property layers of the tile set and
+//! collision shapes of tiles become colliders of the "Collision" layer.
+//!
+//! ```rust,no_run
+//! # use fyrox_impl::{
+//! #     asset::manager::ResourceManager,
+//! #     scene::{graph::Graph, tilemap::tiled},
+//! # };
+//! # use std::path::Path;
+//! fn load_level(resource_manager: &ResourceManager, graph: &mut Graph) {
+//!     let level = tiled::import(Path::new("data/levels/level1.tmx"), resource_manager, graph)
+//!         .expect("Level must be valid!");
+//! }
+//! ```
+
+mod format;
+
+pub use format::*;
+
+use crate::{
+    asset::{manager::ResourceManager, untyped::ResourceKind},
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        math::{triangulator::triangulate, TriangleDefinition},
+        pool::Handle,
+        uuid::Uuid,
+        ImmutableString,
+    },
+    material::{Material, MaterialResource},
+    resource::texture::Texture,
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        node::Node,
+        pivot::PivotBuilder,
+        tilemap::{
+            tileset::*, CustomTileCollider, CustomTileColliderResource, TileCollider,
+            TileDefinitionHandle, TileGridLayout, TileGridMap, TileMapBuilder, Tiles,
+        },
+        transform::TransformBuilder,
+    },
+};
+use std::path::Path;
+
+/// The name of the collider layer that receives collision shapes of Tiled tiles.
+pub const COLLIDER_LAYER_NAME: &str = "Collision";
+
+/// Image collection tile sets have no columns, their tiles are laid out on a tile set page in
+/// rows of this size.
+const IMAGE_COLLECTION_COLUMNS: u32 = 8;
+
+/// Distance between consecutive layers along Z axis. Layers that are drawn later in Tiled are
+/// placed closer to the camera.
+const LAYER_DEPTH_STEP: f32 = 0.01;
+
+fn tile_position(tile_set: &TiledTileSet, id: u32) -> Vector2<i32> {
+    let columns = if tile_set.columns == 0 {
+        IMAGE_COLLECTION_COLUMNS
+    } else {
+        tile_set.columns
+    };
+    Vector2::new((id % columns) as i32, -1 - (id / columns) as i32)
+}
+
+fn icon_id(tile_set: &TiledTileSet) -> Option<u32> {
+    if tile_set.image.is_some() {
+        (tile_set.tile_count > 0).then_some(0)
+    } else {
+        tile_set
+            .tiles
+            .iter()
+            .filter(|tile| tile.image.is_some())
+            .map(|tile| tile.id)
+            .min()
+    }
+}
+
+fn tile_bounds(origin: Vector2<u32>, size: Vector2<u32>) -> TileBounds {
+    TileBounds {
+        left_top_corner: origin,
+        right_top_corner: origin + Vector2::new(size.x, 0),
+        right_bottom_corner: origin + size,
+        left_bottom_corner: origin + Vector2::new(0, size.y),
+    }
+}
+
+fn make_material(resource_manager: &ResourceManager, path: &Path) -> MaterialResource {
+    let mut material = Material::standard_tile();
+    material.bind("diffuseTexture", resource_manager.request::<Texture>(path));
+    MaterialResource::new_ok(ResourceKind::Embedded, material)
+}
+
+fn property_type(value: &TiledPropertyValue) -> TileSetPropertyType {
+    match value {
+        TiledPropertyValue::Int(_) | TiledPropertyValue::Bool(_) => TileSetPropertyType::I32,
+        TiledPropertyValue::Float(_) => TileSetPropertyType::F32,
+        TiledPropertyValue::String(_) => TileSetPropertyType::String,
+    }
+}
+
+fn property_value(
+    value: &TiledPropertyValue,
+    prop_type: TileSetPropertyType,
+) -> TileSetPropertyValue {
+    match (prop_type, value) {
+        (TileSetPropertyType::I32, TiledPropertyValue::Int(value)) => {
+            TileSetPropertyValue::I32(*value)
+        }
+        (TileSetPropertyType::I32, TiledPropertyValue::Bool(value)) => {
+            TileSetPropertyValue::I32(*value as i32)
+        }
+        (TileSetPropertyType::F32, TiledPropertyValue::Float(value)) => {
+            TileSetPropertyValue::F32(*value)
+        }
+        (TileSetPropertyType::F32, TiledPropertyValue::Int(value)) => {
+            TileSetPropertyValue::F32(*value as f32)
+        }
+        _ => TileSetPropertyValue::String(ImmutableString::new(value.to_string())),
+    }
+}
+
+/// Converts collision shapes of a tile to a collider. `size` is the size of the tile in pixels.
+fn make_collider(shapes: &[TiledShape], size: Vector2<f32>) -> Option<TileCollider> {
+    if let [TiledShape::Rectangle {
+        position,
+        size: rect_size,
+    }] = shapes
+    {
+        if position.norm() < f32::EPSILON && (rect_size - size).norm() < f32::EPSILON {
+            return Some(TileCollider::Rectangle);
+        }
+    }
+    let mut collider = CustomTileCollider::default();
+    let mut triangles = Vec::new();
+    for shape in shapes {
+        let points = match shape {
+            TiledShape::Rectangle {
+                position,
+                size: rect_size,
+            } => vec![
+                *position,
+                position + Vector2::new(rect_size.x, 0.0),
+                position + rect_size,
+                position + Vector2::new(0.0, rect_size.y),
+            ],
+            TiledShape::Polygon(points) => points.clone(),
+        };
+        if points.len() < 3 {
+            continue;
+        }
+        let polygon = points
+            .iter()
+            .map(|p| Vector3::new(p.x, p.y, 0.0))
+            .collect::<Vec<_>>();
+        triangulate(&polygon, &mut triangles);
+        let start = collider.vertices.len() as u32;
+        // Tiled has Y axis pointing down, while tiles have it pointing up.
+        collider.vertices.extend(
+            points
+                .iter()
+                .map(|p| Vector2::new(p.x / size.x, 1.0 - p.y / size.y)),
+        );
+        collider.triangles.extend(triangles.iter().map(|t| {
+            TriangleDefinition([
+                start + t[0] as u32,
+                start + t[1] as u32,
+                start + t[2] as u32,
+            ])
+        }));
+    }
+    if collider.triangles.is_empty() {
+        None
+    } else {
+        Some(TileCollider::Custom(CustomTileColliderResource::new_ok(
+            ResourceKind::Embedded,
+            collider,
+        )))
+    }
+}
+
+impl TiledMap {
+    /// Returns the handle of the tile with the given global id within the tile set that is
+    /// produced by [`Self::build_tile_set`].
+    pub fn tile_handle(&self, gid: u32) -> Option<TileDefinitionHandle> {
+        let (index, tile_set, id) = self.find_tile_set(gid)?;
+        TileDefinitionHandle::try_new(Vector2::new(index as i32, 0), tile_position(tile_set, id))
+    }
+
+    /// Converts a position of a cell in Tiled coordinates to the position of the cell in a tile
+    /// map. Tiled has Y axis pointing down, so rows are mirrored. Hexagonal maps are additionally
+    /// shifted, so the staggered rows or columns match the ones of [`TileGridLayout`].
+    pub fn cell_position(&self, position: Vector2<i32>) -> Vector2<i32> {
+        let y = match (self.layout, self.stagger_index) {
+            (TileGridLayout::HexPointyTop, TiledStaggerIndex::Even) => -position.y - 1,
+            (TileGridLayout::HexFlatTop, TiledStaggerIndex::Odd)
+                if position.x.rem_euclid(2) == 1 =>
+            {
+                -position.y - 1
+            }
+            _ => -position.y,
+        };
+        Vector2::new(position.x, y)
+    }
+
+    /// Returns the tiles of the given layer, converted to tile map coordinates. Tiles with ids that
+    /// do not belong to any tile set are skipped.
+    pub fn layer_tiles(&self, layer: &TiledLayer) -> Tiles {
+        let mut tiles = TileGridMap::default();
+        for (position, gid) in layer.tiles.iter() {
+            if let Some(handle) = self.tile_handle(*gid) {
+                tiles.insert(self.cell_position(*position), handle);
+            }
+        }
+        Tiles::new(tiles)
+    }
+
+    /// Creates a tile set that contains a page for every tile set of the map. Images of the tile
+    /// sets are requested from the given resource manager.
+    pub fn build_tile_set(&self, resource_manager: &ResourceManager) -> TileSet {
+        let mut result = TileSet::default();
+
+        for property in self
+            .tile_sets
+            .iter()
+            .flat_map(|entry| entry.tile_set.tiles.iter())
+            .flat_map(|tile| tile.properties.iter())
+        {
+            let prop_type = property_type(&property.value);
+            match result
+                .properties
+                .iter_mut()
+                .find(|layer| layer.name.as_str() == property.name)
+            {
+                // Mixed types are stored in the most general type that fits all the values.
+                Some(layer) if layer.prop_type != prop_type => {
+                    layer.prop_type = match (layer.prop_type, prop_type) {
+                        (TileSetPropertyType::I32, TileSetPropertyType::F32)
+                        | (TileSetPropertyType::F32, TileSetPropertyType::I32) => {
+                            TileSetPropertyType::F32
+                        }
+                        _ => TileSetPropertyType::String,
+                    }
+                }
+                Some(_) => (),
+                None => result.properties.push(TileSetPropertyLayer {
+                    uuid: Uuid::new_v4(),
+                    name: ImmutableString::new(&property.name),
+                    prop_type,
+                    named_values: Vec::new(),
+                }),
+            }
+        }
+
+        let has_colliders = self
+            .tile_sets
+            .iter()
+            .flat_map(|entry| entry.tile_set.tiles.iter())
+            .any(|tile| !tile.colliders.is_empty());
+        if has_colliders {
+            result.colliders.push(TileSetColliderLayer {
+                uuid: Uuid::new_v4(),
+                name: ImmutableString::new(COLLIDER_LAYER_NAME),
+                color: Color::GREEN,
+            });
+        }
+
+        for (index, entry) in self.tile_sets.iter().enumerate() {
+            let page = Vector2::new(index as i32, 0);
+            let source = build_page(&result, &entry.tile_set, resource_manager);
+            let icon = icon_id(&entry.tile_set)
+                .and_then(|id| {
+                    TileDefinitionHandle::try_new(page, tile_position(&entry.tile_set, id))
+                })
+                .unwrap_or(TileDefinitionHandle::EMPTY);
+            result.insert_page(page, TileSetPage { icon, source });
+        }
+
+        result
+    }
+
+    /// Creates a pivot node with a tile map for every tile layer of the map. All the tile maps use
+    /// the given tile set, that should be created by [`Self::build_tile_set`].
+    pub fn instantiate(&self, tile_set: TileSetResource, graph: &mut Graph) -> Handle<Node> {
+        // The cells have the aspect ratio of the tiles of the map.
+        let cell_size = Vector2::new(
+            1.0,
+            self.tile_size.y as f32 / self.tile_size.x.max(1) as f32,
+        );
+        let layers = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| {
+                TileMapBuilder::new(
+                    BaseBuilder::new()
+                        .with_name(layer.name.as_str())
+                        .with_visibility(layer.visible)
+                        .with_local_transform(
+                            TransformBuilder::new()
+                                .with_local_position(Vector3::new(
+                                    0.0,
+                                    0.0,
+                                    -LAYER_DEPTH_STEP * index as f32,
+                                ))
+                                .build(),
+                        ),
+                )
+                .with_tile_set(tile_set.clone())
+                .with_tiles(&self.layer_tiles(layer))
+                .with_cell_size(cell_size)
+                .with_grid_layout(self.layout)
+                .build(graph)
+            })
+            .collect::<Vec<_>>();
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("TiledMap")
+                .with_children(&layers),
+        )
+        .build(graph)
+    }
+}
+
+fn build_page(
+    tile_set: &TileSet,
+    tiled: &TiledTileSet,
+    resource_manager: &ResourceManager,
+) -> TileSetPageSource {
+    let tile_data = |id: u32, size: Vector2<u32>| {
+        let mut data = TileData::default();
+        let Some(tile) = tiled.tile(id) else {
+            return data;
+        };
+        for property in tile.properties.iter() {
+            if let Some(layer) = tile_set
+                .properties
+                .iter()
+                .find(|layer| layer.name.as_str() == property.name)
+            {
+                data.properties
+                    .insert(layer.uuid, property_value(&property.value, layer.prop_type));
+            }
+        }
+        if let (Some(layer), Some(collider)) = (
+            tile_set.colliders.first(),
+            make_collider(&tile.colliders, size.cast::<f32>()),
+        ) {
+            data.colliders.insert(layer.uuid, collider);
+        }
+        data
+    };
+
+    let Some(image) = tiled.image.as_ref() else {
+        // Image collection, every tile has its own image.
+        let mut tiles = TileGridMap::default();
+        for tile in tiled.tiles.iter() {
+            let Some(image) = tile.image.as_ref() else {
+                continue;
+            };
+            let size = image.size.unwrap_or(tiled.tile_size);
+            tiles.insert(
+                tile_position(tiled, tile.id),
+                TileDefinition {
+                    material_bounds: TileMaterialBounds {
+                        material: make_material(resource_manager, &image.source),
+                        bounds: tile_bounds(Vector2::new(0, 0), size),
+                    },
+                    data: tile_data(tile.id, size),
+                },
+            );
+        }
+        return TileSetPageSource::Freeform(tiles);
+    };
+
+    let material = make_material(resource_manager, &image.source);
+    if tiled.margin == 0 && tiled.spacing == 0 {
+        let mut tiles = TileGridMap::default();
+        for id in 0..tiled.tile_count {
+            tiles.insert(tile_position(tiled, id), tile_data(id, tiled.tile_size));
+        }
+        TileSetPageSource::Atlas(TileMaterial {
+            material,
+            tile_size: tiled.tile_size,
+            tiles,
+        })
+    } else {
+        let mut tiles = TileGridMap::default();
+        for id in 0..tiled.tile_count {
+            tiles.insert(
+                tile_position(tiled, id),
+                TileDefinition {
+                    material_bounds: TileMaterialBounds {
+                        material: material.clone(),
+                        bounds: tile_bounds(tiled.tile_origin(id), tiled.tile_size),
+                    },
+                    data: tile_data(id, tiled.tile_size),
+                },
+            );
+        }
+        TileSetPageSource::Freeform(tiles)
+    }
+}
+
+/// Imports a Tiled map from the given `.tmx` file into the graph. See [module docs](self) for
+/// more info.
+pub fn import(
+    path: &Path,
+    resource_manager: &ResourceManager,
+    graph: &mut Graph,
+) -> Result<Handle<Node>, TiledError> {
+    let map = TiledMap::from_file(path)?;
+    let tile_set =
+        TileSetResource::new_ok(ResourceKind::Embedded, map.build_tile_set(resource_manager));
+    let root = map.instantiate(tile_set, graph);
+    if let Some(name) = path.file_stem() {
+        graph[root].set_name(name.to_string_lossy());
+    }
+    Ok(root)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hex_map(layout: TileGridLayout, stagger_index: TiledStaggerIndex) -> TiledMap {
+        TiledMap {
+            layout,
+            stagger_index,
+            tile_sets: vec![
+                TiledMapTileSet {
+                    first_gid: 1,
+                    tile_set: TiledTileSet {
+                        columns: 4,
+                        tile_count: 16,
+                        ..Default::default()
+                    },
+                },
+                TiledMapTileSet {
+                    first_gid: 17,
+                    tile_set: TiledTileSet::default(),
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cell_position() {
+        let map = hex_map(TileGridLayout::Square, TiledStaggerIndex::Odd);
+        assert_eq!(map.cell_position(Vector2::new(2, 3)), Vector2::new(2, -3));
+
+        // Staggered rows must stay staggered after conversion.
+        let map = hex_map(TileGridLayout::HexPointyTop, TiledStaggerIndex::Even);
+        let cell = map.cell_position(Vector2::new(0, 2));
+        assert_eq!(cell.y.rem_euclid(2), 1);
+
+        let map = hex_map(TileGridLayout::HexFlatTop, TiledStaggerIndex::Odd);
+        assert_eq!(map.cell_position(Vector2::new(1, 0)), Vector2::new(1, -1));
+        assert_eq!(map.cell_position(Vector2::new(2, 0)), Vector2::new(2, 0));
+    }
+
+    #[test]
+    fn test_tile_handle() {
+        let map = hex_map(TileGridLayout::Square, TiledStaggerIndex::Odd);
+        assert_eq!(
+            map.tile_handle(1),
+            Some(TileDefinitionHandle::new(0, 0, 0, -1))
+        );
+        assert_eq!(
+            map.tile_handle(7),
+            Some(TileDefinitionHandle::new(0, 0, 2, -2))
+        );
+        // Image collections are laid out in rows of fixed size.
+        assert_eq!(
+            map.tile_handle(17 + IMAGE_COLLECTION_COLUMNS),
+            Some(TileDefinitionHandle::new(1, 0, 0, -2))
+        );
+        assert_eq!(map.tile_handle(0), None);
+    }
+
+    #[test]
+    fn test_make_collider() {
+        let size = Vector2::new(16.0, 16.0);
+        let full = TiledShape::Rectangle {
+            position: Vector2::new(0.0, 0.0),
+            size,
+        };
+        assert!(matches!(
+            make_collider(&[full], size),
+            Some(TileCollider::Rectangle)
+        ));
+        assert!(make_collider(&[], size).is_none());
+
+        let half = TiledShape::Rectangle {
+            position: Vector2::new(0.0, 8.0),
+            size: Vector2::new(16.0, 8.0),
+        };
+        let Some(TileCollider::Custom(collider)) = make_collider(&[half], size) else {
+            panic!("Custom collider expected.");
+        };
+        let collider = collider.data_ref();
+        assert_eq!(collider.triangles.len(), 2);
+        assert!(collider.vertices.iter().all(|v| v.y <= 0.5 && v.y >= 0.0));
+    }
+}
@@ -41,8 +41,11 @@ use crate::{
         type_traits::prelude::*, visitor::prelude::*, ImmutableString,
     },
     fxhash::{FxHashMap, FxHashSet},
-    material::{MaterialResource, MaterialResourceExtension},
-    resource::texture::TextureResource,
+    material::{Material, MaterialResource, MaterialResourceExtension},
+    resource::{
+        model::ModelResource,
+        texture::{TextureKind, TextureResource},
+    },
 };
 use std::{
     collections::hash_map::{Entry, Keys},
@@ -102,6 +105,43 @@ impl From<VisitError> for TileSetResourceError {
     }
 }
 
+/// An error that may occur when a texture is sliced into tiles. See
+/// [`TileSet::add_atlas_page_from_texture`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TileAtlasError {
+    /// The texture is not loaded, so its size is unknown.
+    TextureNotLoaded,
+    /// The texture is not a rectangular 2D texture.
+    UnsupportedTextureKind,
+    /// Not a single tile of the given size fits into the texture.
+    NoTiles,
+}
+
+impl Display for TileAtlasError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TextureNotLoaded => write!(f, "The texture is not loaded."),
+            Self::UnsupportedTextureKind => {
+                write!(f, "Only rectangular textures could be sliced into tiles.")
+            }
+            Self::NoTiles => write!(f, "No tiles of the given size fit into the texture."),
+        }
+    }
+}
+
+impl Error for TileAtlasError {}
+
+/// The number of tiles of the given size, that fit into the given length of a texture.
+fn atlas_tile_count(length: u32, tile_size: u32, margin: u32, spacing: u32) -> u32 {
+    if tile_size == 0 {
+        return 0;
+    }
+    let Some(available) = length.checked_sub(margin.saturating_mul(2)) else {
+        return 0;
+    };
+    available.saturating_add(spacing) / tile_size.saturating_add(spacing)
+}
+
 /// Definition of a tile.
 #[derive(Clone, Default, PartialEq, Debug, Reflect, Visit)]
 #[visit(optional)]
@@ -881,6 +921,16 @@ impl<'a> OptionTileSet<'a> {
     pub fn page_icon(&self, page: Vector2<i32>) -> Option<TileDefinitionHandle> {
         self.as_ref().map(|t| t.page_icon(page)).unwrap_or_default()
     }
+    /// The revision of the data of the tile set, or `None` if the tile set is not loaded.
+    /// See [`TileResourceRevision`] for more info.
+    pub fn revision(&self) -> Option<TileResourceRevision> {
+        self.as_ref().map(|t| t.revision())
+    }
+    /// The number of changes of the tile set, or `None` if the tile set is not loaded.
+    /// See [`ChangeFlag::count`] for more info.
+    pub fn change_count(&self) -> Option<u64> {
+        self.as_ref().map(|t| t.change_count.count())
+    }
     /// Get the UUID of the property with the given name, if that property exists.
     pub fn property_name_to_uuid(&self, name: &ImmutableString) -> Option<Uuid> {
         self.as_ref()
@@ -1104,6 +1154,24 @@ impl<'a> OptionTileSet<'a> {
             .unwrap_or_default()
     }
 
+    /// The handle of the tile at the given frame of the animation sequence starting from the given
+    /// tile handle, or none if the given handle is not part of any animation sequence.
+    /// See [`TileSet::get_animation_frame`].
+    pub fn get_animation_frame(
+        &self,
+        handle: TileDefinitionHandle,
+        frame: i32,
+    ) -> Option<TileDefinitionHandle> {
+        self.as_ref()
+            .and_then(|t| t.get_animation_frame(handle, frame))
+    }
+
+    /// The frame rate and the number of frames of the animation sequence that contains the given
+    /// tile handle, or none if the given handle is not part of any animation sequence.
+    pub fn get_animation_timing(&self, handle: TileDefinitionHandle) -> Option<(f32, i32)> {
+        self.as_ref().and_then(|t| t.get_animation_timing(handle))
+    }
+
     /// Get the tile definition handles for all of the given coordinates on the given page.
     pub fn get_tiles<I: Iterator<Item = Vector2<i32>>>(
         &self,
@@ -1230,15 +1298,36 @@ pub struct TileSet {
     pub colliders: Vec<TileSetColliderLayer>,
     /// Property types in the order in which the layers should be presented in the editor.
     pub properties: Vec<TileSetPropertyLayer>,
+    /// The prefabs that are spawned for the values of spawn properties. See [`TileSpawner`]
+    /// docs for more info.
+    #[visit(optional)]
+    pub spawn_table: Vec<TileSpawnEntry>,
     /// A count of changes since last save. New changes add +1. Reverting to previous
     /// states add -1. Reverting to a state before the last save can result in negative
     /// values. Saving is unnecessary whenever this value is 0.
     #[reflect(hidden)]
     #[visit(skip)]
     pub change_count: ChangeFlag,
+    /// See [`TileSet::revision`].
+    #[reflect(hidden)]
+    #[visit(skip)]
+    revision: TileResourceRevision,
 }
 
 impl TileSet {
+    /// The revision of the data of the tile set, that changes when the tile set is reloaded.
+    /// See [`TileResourceRevision`] for more info.
+    pub fn revision(&self) -> TileResourceRevision {
+        self.revision
+    }
+    /// The prefab that is spawned for the given value of a spawn property, if any.
+    /// See [`TileSpawner`] docs for more info.
+    pub fn spawn_prefab(&self, name: &str) -> Option<&ModelResource> {
+        self.spawn_table
+            .iter()
+            .find(|entry| entry.name.as_str() == name)
+            .and_then(|entry| entry.prefab.as_ref())
+    }
     /// The color of the collider layer with the given uuid.
     pub fn collider_color(&self, uuid: Uuid) -> Option<Color> {
         self.find_collider(uuid).map(|layer| layer.color)
@@ -1471,6 +1560,91 @@ impl TileSet {
     pub fn get_page_mut(&mut self, position: Vector2<i32>) -> Option<&mut TileSetPage> {
         self.pages.get_mut(&position)
     }
+    /// Slices the given sprite sheet texture into tiles of the given size (in pixels) and puts
+    /// them on a new page at a free position of the tile set. `margin` is the number of pixels
+    /// between the edges of the texture and the tiles, `spacing` is the number of pixels between
+    /// neighboring tiles. The tiles keep the columns and rows of the texture, so the tile in the
+    /// left-top corner of the texture is at `(0, -1)` on the page. Returns the position of the
+    /// new page.
+    ///
+    /// The tiles of an atlas page are located by their size alone, so a texture with margin or
+    /// spacing becomes a freeform page instead, where every tile uses the same material with
+    /// its own bounds. The texture must be loaded, because its size defines the number of tiles.
+    pub fn add_atlas_page_from_texture(
+        &mut self,
+        texture: TextureResource,
+        tile_size: Vector2<u32>,
+        margin: u32,
+        spacing: u32,
+    ) -> Result<Vector2<i32>, TileAtlasError> {
+        let kind = texture
+            .state()
+            .data()
+            .map(|texture| texture.kind())
+            .ok_or(TileAtlasError::TextureNotLoaded)?;
+        let TextureKind::Rectangle { width, height } = kind else {
+            return Err(TileAtlasError::UnsupportedTextureKind);
+        };
+        let columns = atlas_tile_count(width, tile_size.x, margin, spacing);
+        let rows = atlas_tile_count(height, tile_size.y, margin, spacing);
+        if columns == 0 || rows == 0 {
+            return Err(TileAtlasError::NoTiles);
+        }
+        let mut material = Material::standard_tile();
+        material.bind("diffuseTexture", texture);
+        let material = MaterialResource::new_ok(ResourceKind::Embedded, material);
+        let positions = (0..rows).flat_map(|row| {
+            (0..columns).map(move |column| {
+                let origin = Vector2::new(
+                    margin + column * (tile_size.x + spacing),
+                    margin + row * (tile_size.y + spacing),
+                );
+                (Vector2::new(column as i32, -1 - row as i32), origin)
+            })
+        });
+        let source = if margin == 0 && spacing == 0 {
+            let mut tiles = TileGridMap::default();
+            for (position, _) in positions {
+                tiles.insert(position, TileData::default());
+            }
+            TileSetPageSource::Atlas(TileMaterial {
+                material,
+                tile_size,
+                tiles,
+            })
+        } else {
+            let mut tiles = TileGridMap::default();
+            for (position, origin) in positions {
+                let bounds = TileBounds {
+                    left_top_corner: origin,
+                    right_top_corner: origin + Vector2::new(tile_size.x, 0),
+                    right_bottom_corner: origin + tile_size,
+                    left_bottom_corner: origin + Vector2::new(0, tile_size.y),
+                };
+                tiles.insert(
+                    position,
+                    TileDefinition {
+                        material_bounds: TileMaterialBounds {
+                            material: material.clone(),
+                            bounds,
+                        },
+                        data: TileData::default(),
+                    },
+                );
+            }
+            TileSetPageSource::Freeform(tiles)
+        };
+        let page = self.find_free_location(
+            TilePaletteStage::Pages,
+            Vector2::default(),
+            Vector2::default(),
+        );
+        let icon = TileDefinitionHandle::try_new(page, Vector2::new(0, -1))
+            .unwrap_or(TileDefinitionHandle::EMPTY);
+        self.insert_page(page, TileSetPage { icon, source });
+        self.change_count.set();
+        Ok(page)
+    }
     /// Insert the given page at the given position.
     pub fn insert_page(
         &mut self,
@@ -1783,6 +1957,42 @@ impl TileSet {
         tiles.get(&frame).copied()
     }
 
+    /// The handle of the tile at the given frame of the animation sequence starting from the given
+    /// tile handle, or none if the given handle is not part of any animation sequence. Frame `0`
+    /// is the given tile itself, frames outside of the sequence wrap around.
+    pub fn get_animation_frame(
+        &self,
+        handle: TileDefinitionHandle,
+        frame: i32,
+    ) -> Option<TileDefinitionHandle> {
+        let (animation, offset) = self.animation_map.get_animation_and_offset(handle)?;
+        let page = self.get_page(animation.page())?;
+        let TileSetPageSource::Animation(AnimationTiles { tiles, .. }) = &page.source else {
+            return None;
+        };
+        let frame_index = frame.rem_euclid(animation.length) + offset;
+        let frame = animation.frame(frame_index);
+        tiles.get(&frame).copied()
+    }
+
+    /// The frame rate and the number of frames of the animation sequence that contains the given
+    /// tile handle, or none if the given handle is not part of any animation sequence.
+    pub fn get_animation_timing(&self, handle: TileDefinitionHandle) -> Option<(f32, i32)> {
+        let (animation, _) = self.animation_map.get_animation_and_offset(handle)?;
+        let page = self.get_page(animation.page())?;
+        let TileSetPageSource::Animation(AnimationTiles { frame_rate, .. }) = &page.source else {
+            return None;
+        };
+        Some((*frame_rate, animation.length))
+    }
+
+    /// The position of the animation page that contains the animation sequence of the given
+    /// tile handle, or none if the given handle is not part of any animation sequence.
+    pub fn get_animation_page(&self, handle: TileDefinitionHandle) -> Option<Vector2<i32>> {
+        let (animation, _) = self.animation_map.get_animation_and_offset(handle)?;
+        Some(animation.page())
+    }
+
     /// Finds the handle of the tile that represents a transformed version of the tile at the given handle, if such a tile exists.
     /// The given tile needs to have a `transform_tile` in its data, that handle needs to point to a transform set page,
     /// and that page needs to have a tile in the position corresponding to the desired transform relative to the `transform_tile` position.
@@ -0,0 +1,398 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Trigger regions built from tile properties. See [`TileTriggers`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector2, pool::Handle, ImmutableString},
+    graph::SceneGraph,
+    scene::{graph::Graph, node::Node},
+    script::ScriptMessageSender,
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::collections::BTreeMap;
+
+use super::*;
+
+/// Kind of a [`TileTriggerMessage`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TileTriggerEvent {
+    /// A tracked node has entered a trigger region.
+    Enter,
+    /// A tracked node has left a trigger region.
+    Leave,
+}
+
+/// A script message that is sent by [`TileTriggers::update`] when a tracked node crosses the
+/// border of a trigger region. The message is sent both to the tracked node and to the tile map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileTriggerMessage {
+    /// Whether the node entered or left the region.
+    pub event: TileTriggerEvent,
+    /// The tile map that contains the region.
+    pub tile_map: Handle<Node>,
+    /// The node that crossed the border of the region.
+    pub node: Handle<Node>,
+    /// Index of the region in [`TileTriggers::regions`].
+    pub region: usize,
+    /// The value of the trigger property of the region's tiles.
+    pub value: TileSetPropertyValue,
+}
+
+/// A connected group of cells that share the same value of the trigger property.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileTriggerRegion {
+    /// The value of the trigger property of every cell of the region.
+    pub value: TileSetPropertyValue,
+    /// Non-overlapping rectangles that cover the region exactly.
+    pub rects: Vec<TileRect>,
+}
+
+impl TileTriggerRegion {
+    /// True if the given cell belongs to the region.
+    pub fn contains(&self, position: Vector2<i32>) -> bool {
+        self.rects.iter().any(|r| r.contains(position))
+    }
+}
+
+/// Tile triggers scan a [`TileMap`] for the tiles that have a non-default value of a designated
+/// trigger property, merge the neighbouring tiles with equal values into regions, and send
+/// [`TileTriggerMessage`] when a tracked node enters or leaves one of these regions. This
+/// replaces manual setups of sensor colliders for doors, damage floors, zone transitions, etc.
+///
+/// The regions are built by [`TileTriggers::rebuild`], which must be called again every time the
+/// tiles of the tile map are changed. The tracked nodes are checked by [`TileTriggers::update`],
+/// usually once per frame from a script of the tile map. Scripts receive the messages after
+/// subscribing to them with `ScriptContext::message_dispatcher`.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     scene::{graph::Graph, node::Node, tilemap::TileTriggers},
+/// #     script::ScriptMessageSender,
+/// # };
+/// fn setup(tile_map: Handle<Node>, player: Handle<Node>, graph: &Graph) -> TileTriggers {
+///     let mut triggers = TileTriggers::new(tile_map, "trigger");
+///     triggers.rebuild(graph).unwrap();
+///     triggers.track(player);
+///     triggers
+/// }
+///
+/// fn update(triggers: &mut TileTriggers, graph: &Graph, sender: &ScriptMessageSender) {
+///     triggers.update(graph, sender);
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TileTriggers {
+    tile_map: Handle<Node>,
+    property: ImmutableString,
+    regions: Vec<TileTriggerRegion>,
+    cell_regions: FxHashMap<Vector2<i32>, usize>,
+    tracked: FxHashMap<Handle<Node>, Option<usize>>,
+}
+
+impl TileTriggers {
+    /// Creates new triggers for the given tile map, that use the property with the given name.
+    /// The triggers have no regions until [`TileTriggers::rebuild`] is called.
+    pub fn new<S: AsRef<str>>(tile_map: Handle<Node>, property: S) -> Self {
+        Self {
+            tile_map,
+            property: ImmutableString::new(property),
+            ..Default::default()
+        }
+    }
+
+    /// The handle of the tile map that contains the regions.
+    pub fn tile_map(&self) -> Handle<Node> {
+        self.tile_map
+    }
+
+    /// The name of the trigger property.
+    pub fn property(&self) -> &ImmutableString {
+        &self.property
+    }
+
+    /// The list of regions that were found by the last rebuild.
+    pub fn regions(&self) -> &[TileTriggerRegion] {
+        &self.regions
+    }
+
+    /// Index of the region that contains the given cell, if any.
+    pub fn region_at(&self, position: Vector2<i32>) -> Option<usize> {
+        self.cell_regions.get(&position).copied()
+    }
+
+    /// Starts tracking the given node. The node is considered to be outside of every region
+    /// until the next update, so it receives an enter message if it already stands in a region.
+    pub fn track(&mut self, node: Handle<Node>) {
+        self.tracked.entry(node).or_insert(None);
+    }
+
+    /// Stops tracking the given node. No leave message is sent.
+    pub fn untrack(&mut self, node: Handle<Node>) {
+        self.tracked.remove(&node);
+    }
+
+    /// Iterates over the tracked nodes along with the index of the region that they are in.
+    pub fn tracked(&self) -> impl Iterator<Item = (Handle<Node>, Option<usize>)> + '_ {
+        self.tracked.iter().map(|(h, r)| (*h, *r))
+    }
+
+    /// Scans the tiles of the tile map and rebuilds the regions. Tracked nodes are considered to
+    /// be outside of every region after the rebuild, because region indices may change.
+    pub fn rebuild(&mut self, graph: &Graph) -> Result<(), TilePropertyError> {
+        let Some(tile_map) = graph.try_get_of_type::<TileMap>(self.tile_map) else {
+            self.rebuild_from_cells(std::iter::empty());
+            return Ok(());
+        };
+        let tile_set = tile_map
+            .tile_set()
+            .ok_or(TilePropertyError::MissingTileSet)?
+            .data_ref();
+        let tile_set = tile_set
+            .as_loaded_ref()
+            .ok_or(TilePropertyError::TileSetNotLoaded)?;
+        let property = tile_set
+            .find_property_by_name(&self.property)
+            .ok_or_else(|| TilePropertyError::UnrecognizedName(self.property.clone()))?;
+        let default = property.prop_type.default_value();
+        let mut cells = Vec::new();
+        if let Some(tiles) = tile_map.tiles() {
+            if let Some(tiles) = tiles.data_ref().as_loaded_ref() {
+                for (position, handle) in tiles.iter() {
+                    match tile_set.property_value(handle, property.uuid) {
+                        Some(value) if value != default => cells.push((position, value)),
+                        _ => (),
+                    }
+                }
+            }
+        }
+        self.rebuild_from_cells(cells);
+        Ok(())
+    }
+
+    /// Rebuilds the regions from the given cells and their trigger property values. Neighbouring
+    /// cells are joined into the same region when their values are equal.
+    pub fn rebuild_from_cells<I>(&mut self, cells: I)
+    where
+        I: IntoIterator<Item = (Vector2<i32>, TileSetPropertyValue)>,
+    {
+        let cells = cells.into_iter().collect::<FxHashMap<_, _>>();
+        self.regions.clear();
+        self.cell_regions.clear();
+        for state in self.tracked.values_mut() {
+            *state = None;
+        }
+        // Sort the cells to make the order of regions independent of hashing.
+        let mut starts = cells.keys().copied().collect::<Vec<_>>();
+        starts.sort_by_key(|p| (p.y, p.x));
+        let mut stack = Vec::new();
+        for start in starts {
+            if self.cell_regions.contains_key(&start) {
+                continue;
+            }
+            let index = self.regions.len();
+            let value = cells[&start].clone();
+            let mut component = FxHashSet::default();
+            self.cell_regions.insert(start, index);
+            stack.push(start);
+            while let Some(position) = stack.pop() {
+                component.insert(position);
+                for offset in [
+                    Vector2::new(1, 0),
+                    Vector2::new(-1, 0),
+                    Vector2::new(0, 1),
+                    Vector2::new(0, -1),
+                ] {
+                    let next = position + offset;
+                    if self.cell_regions.contains_key(&next) {
+                        continue;
+                    }
+                    if cells.get(&next) == Some(&value) {
+                        self.cell_regions.insert(next, index);
+                        stack.push(next);
+                    }
+                }
+            }
+            self.regions.push(TileTriggerRegion {
+                value,
+                rects: cells_to_rects(&component),
+            });
+        }
+    }
+
+    /// Checks the positions of the tracked nodes and sends enter and leave messages to the nodes
+    /// and to the tile map. Nodes that no longer exist are untracked. Nothing happens if the tile
+    /// map does not exist.
+    pub fn update(&mut self, graph: &Graph, sender: &ScriptMessageSender) {
+        let Some(tile_map) = graph.try_get_of_type::<TileMap>(self.tile_map) else {
+            return;
+        };
+        self.tracked.retain(|node, _| graph.is_valid_handle(*node));
+        let positions = self
+            .tracked
+            .keys()
+            .map(|node| {
+                (
+                    *node,
+                    tile_map.world_to_grid(graph[*node].global_position()),
+                )
+            })
+            .collect::<Vec<_>>();
+        for (node, position) in positions {
+            self.move_node(node, position, |message| {
+                sender.send_to_target(message.tile_map, message.clone());
+                sender.send_to_target(message.node, message);
+            });
+        }
+    }
+
+    /// Moves the given tracked node to the given cell and calls the given function for every
+    /// event that this causes. When the node moves from one region directly into another, the
+    /// leave event comes first. Nothing happens if the node is not tracked.
+    pub fn move_node<F>(&mut self, node: Handle<Node>, position: Vector2<i32>, mut func: F)
+    where
+        F: FnMut(TileTriggerMessage),
+    {
+        let new_region = self.region_at(position);
+        let Some(state) = self.tracked.get_mut(&node) else {
+            return;
+        };
+        let old_region = std::mem::replace(state, new_region);
+        if old_region == new_region {
+            return;
+        }
+        for (event, region) in [
+            (TileTriggerEvent::Leave, old_region),
+            (TileTriggerEvent::Enter, new_region),
+        ] {
+            if let Some(region) = region {
+                func(TileTriggerMessage {
+                    event,
+                    tile_map: self.tile_map,
+                    node,
+                    region,
+                    value: self.regions[region].value.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Covers the given cells with non-overlapping rectangles by splitting the cells into horizontal
+/// runs and joining the runs of neighbouring rows that have the same span.
+fn cells_to_rects(cells: &FxHashSet<Vector2<i32>>) -> Vec<TileRect> {
+    let mut rows = BTreeMap::<i32, Vec<i32>>::new();
+    for p in cells {
+        rows.entry(p.y).or_default().push(p.x);
+    }
+    let mut rects = Vec::<TileRect>::new();
+    // Rects that end at the previous row, keyed by their horizontal span.
+    let mut open = FxHashMap::<(i32, i32), usize>::default();
+    for (y, mut xs) in rows {
+        xs.sort_unstable();
+        let mut next_open = FxHashMap::default();
+        let mut i = 0;
+        while i < xs.len() {
+            let start = xs[i];
+            let mut end = start;
+            while i + 1 < xs.len() && xs[i + 1] == end + 1 {
+                i += 1;
+                end += 1;
+            }
+            i += 1;
+            let index = match open.get(&(start, end)) {
+                Some(&index) if rects[index].position.y + rects[index].size.y == y => {
+                    rects[index].size.y += 1;
+                    index
+                }
+                _ => {
+                    rects.push(TileRect::new(start, y, end - start + 1, 1));
+                    rects.len() - 1
+                }
+            };
+            next_open.insert((start, end), index);
+        }
+        open = next_open;
+    }
+    rects
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cells(values: &[(i32, i32, i32)]) -> Vec<(Vector2<i32>, TileSetPropertyValue)> {
+        values
+            .iter()
+            .map(|(x, y, v)| (Vector2::new(*x, *y), TileSetPropertyValue::I32(*v)))
+            .collect()
+    }
+
+    #[test]
+    fn test_trigger_regions() {
+        let mut triggers = TileTriggers::new(Handle::NONE, "trigger");
+        // An L-shaped region of value 1 and a single cell of value 2 next to it.
+        triggers.rebuild_from_cells(cells(&[
+            (0, 0, 1),
+            (1, 0, 1),
+            (0, 1, 1),
+            (1, 1, 1),
+            (0, 2, 1),
+            (2, 0, 2),
+        ]));
+        let regions = triggers.regions();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].value, TileSetPropertyValue::I32(1));
+        assert_eq!(
+            regions[0].rects,
+            vec![TileRect::new(0, 0, 2, 2), TileRect::new(0, 2, 1, 1)]
+        );
+        assert_eq!(regions[1].rects, vec![TileRect::new(2, 0, 1, 1)]);
+        assert_eq!(triggers.region_at(Vector2::new(0, 2)), Some(0));
+        assert_eq!(triggers.region_at(Vector2::new(2, 0)), Some(1));
+        assert_eq!(triggers.region_at(Vector2::new(1, 2)), None);
+    }
+
+    #[test]
+    fn test_trigger_events() {
+        let mut triggers = TileTriggers::new(Handle::NONE, "trigger");
+        triggers.rebuild_from_cells(cells(&[(0, 0, 1), (1, 0, 2)]));
+        let node = Handle::new(1, 1);
+        triggers.track(node);
+        let mut events = Vec::new();
+        for x in [-1, 0, 0, 1, 2] {
+            triggers.move_node(node, Vector2::new(x, 0), |m| {
+                events.push((m.event, m.region))
+            });
+        }
+        assert_eq!(
+            events,
+            vec![
+                (TileTriggerEvent::Enter, 0),
+                (TileTriggerEvent::Leave, 0),
+                (TileTriggerEvent::Enter, 1),
+                (TileTriggerEvent::Leave, 1),
+            ]
+        );
+        triggers.untrack(node);
+        triggers.move_node(node, Vector2::new(0, 0), |_| panic!());
+    }
+}
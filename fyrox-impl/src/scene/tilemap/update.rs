@@ -34,8 +34,9 @@
 //! a tile set or a tile map. `TransTilesUpdate` has methods for various tile-drawing operations
 //! like lines, rect fills, and flood fills.
 //!
-//! [`TilesUpdate`] stores simple tile definition handles with no transformations. Constructing this
-//! update is the final step before finally applying the modification to a tile map.
+//! [`TilesUpdate`] stores tile definition handles along with the transformations of the cells of a
+//! tile map, see [`TileMapData::set_transformation`]. Constructing this update is the final step
+//! before finally applying the modification to a tile map.
 
 use super::*;
 use crate::core::{algebra::Vector2, color::Color, log::Log, type_traits::prelude::*};
@@ -114,6 +115,18 @@ impl Iterator for BresenhamLineIter {
     }
 }
 
+/// True if the center of the cell at the given position lies within the ellipse
+/// that is inscribed in the given rect.
+fn ellipse_contains(rect: &TileRect, position: Vector2<i32>) -> bool {
+    // Doubling the coordinates puts the center of the rect and the centers of the cells
+    // on integer coordinates, so no floating point math is required.
+    let w = rect.size.x as i64;
+    let h = rect.size.y as i64;
+    let dx = 2 * (position.x as i64 - rect.position.x as i64) + 1 - w;
+    let dy = 2 * (position.y as i64 - rect.position.y as i64) + 1 - h;
+    dx * dx * h * h + dy * dy * w * w <= w * w * h * h
+}
+
 /// This represents a change to some pages of a tile set, without specifying which tile set.
 #[derive(Clone, Debug, Default)]
 pub struct TileSetUpdate(FxHashMap<TileDefinitionHandle, TileDataUpdate>);
@@ -587,20 +600,68 @@ pub struct TransTilesUpdate(TileGridMap<Option<RotTileHandle>>);
 
 /// A set of changes to a set of tiles. A value of None indicates that a tile
 /// is being removed from the set. A None indicates that the tile is to be erased.
+/// Tile maps also take the transformations of the cells from the update, so swapping
+/// the update with a tile map keeps flipped and rotated tiles, see [`TilesUpdate::transformation`].
 #[derive(Clone, Debug, Default, PartialEq)]
-pub struct TilesUpdate(TileGridMap<Option<TileDefinitionHandle>>);
+pub struct TilesUpdate {
+    tiles: TileGridMap<Option<TileDefinitionHandle>>,
+    transformations: FxHashMap<Vector2<i32>, OrthoTransformation>,
+}
 
 impl Deref for TilesUpdate {
     type Target = TileGridMap<Option<TileDefinitionHandle>>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.tiles
     }
 }
 
 impl DerefMut for TilesUpdate {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.tiles
+    }
+}
+
+impl TilesUpdate {
+    /// Removes all the tiles and transformations from the update.
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.transformations.clear();
+    }
+    /// The transformation of the tile at the given position. Erased cells and the cells, that
+    /// were inserted without transformation, have the identity transformation.
+    pub fn transformation(&self, position: Vector2<i32>) -> OrthoTransformation {
+        match self.tiles.get(&position) {
+            Some(Some(_)) => self
+                .transformations
+                .get(&position)
+                .copied()
+                .unwrap_or_default(),
+            _ => OrthoTransformation::identity(),
+        }
+    }
+    /// Sets the transformation of the tile at the given position. It is applied only to the cells
+    /// of tile maps, see [`TileMapData::set_transformation`].
+    pub fn set_transformation(&mut self, position: Vector2<i32>, value: OrthoTransformation) {
+        if value.is_identity() {
+            self.transformations.remove(&position);
+        } else {
+            self.transformations.insert(position, value);
+        }
+    }
+    /// Inserts the given tile with the given transformation at the given position, or erases the
+    /// tile at the position, if the value is `None`.
+    pub fn insert_transformed(
+        &mut self,
+        position: Vector2<i32>,
+        value: Option<(OrthoTransformation, TileDefinitionHandle)>,
+    ) {
+        let (transformation, handle) = match value {
+            Some((transformation, handle)) => (transformation, Some(handle)),
+            None => (OrthoTransformation::identity(), None),
+        };
+        self.tiles.insert(position, handle);
+        self.set_transformation(position, transformation);
     }
 }
 
@@ -620,18 +681,18 @@ impl DerefMut for TransTilesUpdate {
 
 impl TransTilesUpdate {
     /// Construct a TilesUpdate by finding the transformed version of each tile
-    /// in the given tile set.
+    /// in the given tile set. If the tile set has no transformed version of a tile,
+    /// the transformation is kept in the update to be applied to the cell of a tile map.
     pub fn build_tiles_update(&self, tile_set: &OptionTileSet) -> TilesUpdate {
         let mut result = TilesUpdate::default();
         for (pos, value) in self.iter() {
-            if let Some((trans, handle)) = value {
-                let handle = tile_set
-                    .get_transformed_version(*trans, *handle)
-                    .unwrap_or(*handle);
-                result.insert(*pos, Some(handle));
-            } else {
-                result.insert(*pos, None);
-            }
+            let value = value.map(|(trans, handle)| {
+                match tile_set.get_transformed_version(trans, handle) {
+                    Some(handle) => (OrthoTransformation::identity(), handle),
+                    None => (trans, handle),
+                }
+            });
+            result.insert_transformed(*pos, value);
         }
         result
     }
@@ -679,6 +740,22 @@ impl TransTilesUpdate {
             self.insert(origin + local_position, Some((trans, *handle)));
         }
     }
+    /// Draws the tiles of the given source in the shape of the given stamp. The source is
+    /// sampled at the positions of the tiles of the stamp, so it may be the stamp itself wrapped
+    /// in another source, such as [`VariantTileSource`].
+    pub fn draw_tiles_from<S: TileSource>(
+        &mut self,
+        origin: Vector2<i32>,
+        brush: &Stamp,
+        source: &S,
+    ) {
+        let trans = source.transformation();
+        for local_position in brush.keys() {
+            if let Some(handle) = source.get_at(local_position) {
+                self.insert(origin + local_position, Some((trans, handle)));
+            }
+        }
+    }
     /// Erases the tiles under the given brush.
     #[inline]
     pub fn erase_stamp(&mut self, origin: Vector2<i32>, brush: &Stamp) {
@@ -692,9 +769,17 @@ impl TransTilesUpdate {
     }
     /// Fills the given rectangle using the given stamp.
     pub fn rect_fill(&mut self, start: Vector2<i32>, end: Vector2<i32>, stamp: &Stamp) {
-        let region = TileRegion::from_points(start, end);
-        let stamp_source = stamp.repeat(start, end);
-        self.rect_fill_inner(region, &stamp_source);
+        self.rect_fill_from(start, end, &stamp.repeat(start, end));
+    }
+    /// Fills the given rectangle using the tiles of the given source. The source is sampled
+    /// at the positions relative to `start`.
+    pub fn rect_fill_from<S: TileSource>(
+        &mut self,
+        start: Vector2<i32>,
+        end: Vector2<i32>,
+        source: &S,
+    ) {
+        self.rect_fill_inner(TileRegion::from_points(start, end), source);
     }
     /// Fills the given rectangle using random tiles from the given stamp.
     pub fn rect_fill_random(&mut self, start: Vector2<i32>, end: Vector2<i32>, stamp: &Stamp) {
@@ -719,6 +804,29 @@ impl TransTilesUpdate {
             }
         }
     }
+    /// Fills the ellipse that is inscribed within the rectangle between the given points.
+    /// The tiles are taken from the source exactly as [`TransTilesUpdate::rect_fill_from`] would take them,
+    /// except that cells outside of the ellipse are skipped.
+    pub fn ellipse_fill_from<S: TileSource>(
+        &mut self,
+        start: Vector2<i32>,
+        end: Vector2<i32>,
+        source: &S,
+    ) {
+        let region = TileRegion::from_points(start, end);
+        let Some(rect) = *region.bounds else {
+            return;
+        };
+        let trans = source.transformation();
+        for (target, source_position) in region.iter() {
+            if !ellipse_contains(&rect, target) {
+                continue;
+            }
+            if let Some(definition_handle) = source.get_at(source_position) {
+                self.insert(target, Some((trans, definition_handle)));
+            }
+        }
+    }
 
     /// Fills in a rectangle using special brush with 3x3 tiles. It puts
     /// corner tiles in the respective corners of the target rectangle and draws lines between each
@@ -755,6 +863,30 @@ impl TransTilesUpdate {
             },
         );
     }
+    /// Fills in a rectangle with tiles generated by Wave Function Collapse algorithm, using
+    /// the tiles of the stamp as an example. The given border tiles around the rectangle are
+    /// taken into account. Nothing is drawn if the generator fails. See [`WfcRules`] for more info.
+    pub fn generate<S: TileSource>(
+        &mut self,
+        start: Vector2<i32>,
+        end: Vector2<i32>,
+        stamp: &Stamp,
+        border: &S,
+        seed: u64,
+    ) {
+        let mut example = Tiles::default();
+        for (position, handle) in stamp.iter() {
+            example.insert(position, *handle);
+        }
+        let rules = WfcRules::from_example(&example);
+        let Ok(tiles) = rules.generate(TileRect::from_points(start, end), border, seed) else {
+            return;
+        };
+        let trans = stamp.transformation();
+        for (position, handle) in tiles.iter() {
+            self.insert(*position, Some((trans, *handle)));
+        }
+    }
 
     /// Fills in a rectangle using special brush with 3x3 tiles. It puts
     /// corner tiles in the respective corners of the target rectangle and draws lines between each
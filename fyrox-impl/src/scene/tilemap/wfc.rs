@@ -0,0 +1,567 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Procedural generation of tiles using Wave Function Collapse algorithm.
+//! See [`WfcRules`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector2, uuid::Uuid},
+    rand::{prelude::StdRng, Rng, SeedableRng},
+};
+use fxhash::{FxHashMap, FxHashSet};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use super::*;
+
+/// Offsets to the neighbors of a cell: up, right, down, left. The opposite of the direction `i`
+/// is `(i + 2) % 4`.
+const DIRECTIONS: [Vector2<i32>; 4] = [
+    Vector2::new(0, 1),
+    Vector2::new(1, 0),
+    Vector2::new(0, -1),
+    Vector2::new(-1, 0),
+];
+
+/// How many times the generator restarts with a different seed after it runs into a
+/// contradiction.
+const MAX_ATTEMPTS: u64 = 10;
+
+fn direction_index(offset: Vector2<i32>) -> Option<usize> {
+    DIRECTIONS.iter().position(|d| *d == offset)
+}
+
+fn opposite(direction: usize) -> usize {
+    (direction + 2) % 4
+}
+
+/// An error that may occur during generation of tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WfcError {
+    /// The rules have no tiles.
+    NoTiles,
+    /// The generator was unable to fill the region without breaking the rules, even after
+    /// several attempts.
+    Contradiction,
+    /// The tile map has no tile data, or the data is not loaded.
+    MissingTiles,
+}
+
+impl Display for WfcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WfcError::NoTiles => write!(f, "The rules have no tiles."),
+            WfcError::Contradiction => {
+                write!(f, "Unable to fill the region without breaking the rules.")
+            }
+            WfcError::MissingTiles => write!(f, "The tile map has no loaded tile data."),
+        }
+    }
+}
+
+impl Error for WfcError {}
+
+/// Adjacency rules for Wave Function Collapse generator. The rules consist of a set of tiles with
+/// weights, that define how often each tile appears, and a set of allowed pairs of neighbor
+/// tiles for each of the four directions. The generator fills a rectangle with tiles in a way
+/// that every pair of neighbor tiles is allowed by the rules.
+///
+/// The rules could be learned from an example region of tiles by [`Self::from_example`], so
+/// every pair of neighbors of the example is allowed and the weights are the number of
+/// occurrences of the tiles in the example. Alternatively, the rules could be built from sockets,
+/// that are stored in a string property of a tile set (see [`Self::from_sockets`]), or defined
+/// explicitly using [`Self::add_tile`] and [`Self::allow`].
+///
+/// Generation assumes square cells, only the four edge neighbors of each cell are taken into
+/// account.
+///
+/// ```rust
+/// # use fyrox_impl::scene::tilemap::{TileMap, TileRect, Tiles, WfcRules};
+/// fn generate(tile_map: &mut TileMap, example: &Tiles) {
+///     let rules = WfcRules::from_example(example);
+///     tile_map
+///         .generate_tiles(TileRect::new(0, 0, 32, 32), &rules, 42)
+///         .unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct WfcRules {
+    tiles: Vec<TileDefinitionHandle>,
+    weights: Vec<f32>,
+    indices: FxHashMap<TileDefinitionHandle, usize>,
+    /// Indices of the tiles that may be placed next to each tile in each direction.
+    adjacency: [Vec<FxHashSet<usize>>; 4],
+}
+
+impl WfcRules {
+    /// Learns the rules from the given example region. See [`Self::learn`] for more info.
+    pub fn from_example(example: &Tiles) -> Self {
+        let mut rules = Self::default();
+        rules.learn(example);
+        rules
+    }
+
+    /// Builds the rules from the sockets of the tiles of the given tile set. Sockets are stored in
+    /// a string property with the given UUID in the form of four comma-separated names in the
+    /// order of up, right, down, left sides of the tile, for example `grass,road,grass,road`.
+    /// Two tiles may be placed next to each other, if their touching sides have equal sockets.
+    /// Tiles without the property value are ignored.
+    pub fn from_sockets(tile_set: &TileSet, property_id: Uuid) -> Self {
+        let mut rules = Self::default();
+        let mut sockets = Vec::new();
+        for page in tile_set.page_keys() {
+            if !tile_set
+                .get_page(page)
+                .is_some_and(|p| p.is_material() || p.is_freeform())
+            {
+                continue;
+            }
+            for tile in tile_set.keys_on_page(page) {
+                let Some(handle) = TileDefinitionHandle::try_new(page, tile) else {
+                    continue;
+                };
+                let Some(TileSetPropertyValue::String(value)) =
+                    tile_set.property_value(handle, property_id)
+                else {
+                    continue;
+                };
+                let sides = value.split(',').map(|s| s.trim()).collect::<Vec<_>>();
+                if let Ok(sides) = <[&str; 4]>::try_from(sides) {
+                    sockets.push((handle, sides.map(|s| s.to_string())));
+                }
+            }
+        }
+        for (handle, _) in sockets.iter() {
+            rules.add_tile(*handle, 1.0);
+        }
+        for (a, a_sockets) in sockets.iter() {
+            for (b, b_sockets) in sockets.iter() {
+                for (direction, offset) in DIRECTIONS.iter().enumerate() {
+                    if a_sockets[direction] == b_sockets[opposite(direction)] {
+                        rules.allow(*a, *b, *offset);
+                    }
+                }
+            }
+        }
+        rules
+    }
+
+    /// Adds the tiles of the given example region to the rules. The weight of each tile is
+    /// increased by the number of its occurrences in the example, and every pair of neighbor
+    /// tiles of the example is allowed.
+    pub fn learn(&mut self, example: &Tiles) {
+        for (position, handle) in example.iter() {
+            self.add_tile(*handle, 1.0);
+            for offset in DIRECTIONS {
+                if let Some(neighbor) = example.get(&(position + offset)) {
+                    self.allow(*handle, *neighbor, offset);
+                }
+            }
+        }
+    }
+
+    /// Adds the given tile to the rules, or increases its weight if the tile is already there.
+    /// Tiles with greater weights appear more often.
+    pub fn add_tile(&mut self, handle: TileDefinitionHandle, weight: f32) {
+        if let Some(index) = self.indices.get(&handle) {
+            self.weights[*index] += weight;
+            return;
+        }
+        self.indices.insert(handle, self.tiles.len());
+        self.tiles.push(handle);
+        self.weights.push(weight);
+        for adjacency in self.adjacency.iter_mut() {
+            adjacency.push(FxHashSet::default());
+        }
+    }
+
+    /// Allows the tile `b` to be placed at the given offset from the tile `a`, which also allows
+    /// `a` to be placed at the opposite offset from `b`. The offset must be one of `(0, 1)`,
+    /// `(1, 0)`, `(0, -1)`, `(-1, 0)`, other offsets are ignored. The tiles are added to the
+    /// rules with zero weight if they are not there yet.
+    pub fn allow(
+        &mut self,
+        a: TileDefinitionHandle,
+        b: TileDefinitionHandle,
+        offset: Vector2<i32>,
+    ) {
+        let Some(direction) = direction_index(offset) else {
+            return;
+        };
+        for handle in [a, b] {
+            if !self.indices.contains_key(&handle) {
+                self.add_tile(handle, 0.0);
+            }
+        }
+        let (a, b) = (self.indices[&a], self.indices[&b]);
+        self.adjacency[direction][a].insert(b);
+        self.adjacency[opposite(direction)][b].insert(a);
+    }
+
+    /// True if the tile `b` may be placed at the given offset from the tile `a`.
+    pub fn is_allowed(
+        &self,
+        a: TileDefinitionHandle,
+        b: TileDefinitionHandle,
+        offset: Vector2<i32>,
+    ) -> bool {
+        let (Some(direction), Some(a), Some(b)) = (
+            direction_index(offset),
+            self.indices.get(&a),
+            self.indices.get(&b),
+        ) else {
+            return false;
+        };
+        self.adjacency[direction][*a].contains(b)
+    }
+
+    /// The tiles of the rules.
+    pub fn tiles(&self) -> &[TileDefinitionHandle] {
+        &self.tiles
+    }
+
+    /// True if the rules have no tiles.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Fills the given rectangle with tiles, so every pair of neighbor tiles is allowed by the
+    /// rules. The tiles of the given border source, that are adjacent to the rectangle, are taken
+    /// into account, so the generated tiles fit their surroundings. Border tiles that are unknown
+    /// to the rules are ignored. The same seed produces the same result.
+    pub fn generate<S: TileSource>(
+        &self,
+        rect: TileRect,
+        border: &S,
+        seed: u64,
+    ) -> Result<Tiles, WfcError> {
+        if self.is_empty() {
+            return Err(WfcError::NoTiles);
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        for _ in 0..MAX_ATTEMPTS {
+            let mut wave = Wave::new(self, rect);
+            if wave.constrain_border(border) && wave.collapse(&mut rng) {
+                return Ok(wave.into_tiles());
+            }
+        }
+        Err(WfcError::Contradiction)
+    }
+}
+
+/// The state of the generator: the set of possible tiles for each cell of the rectangle.
+struct Wave<'a> {
+    rules: &'a WfcRules,
+    rect: TileRect,
+    possible: Vec<Vec<bool>>,
+    counts: Vec<usize>,
+}
+
+impl<'a> Wave<'a> {
+    fn new(rules: &'a WfcRules, rect: TileRect) -> Self {
+        let cell_count = (rect.size.x.max(0) * rect.size.y.max(0)) as usize;
+        let tile_count = rules.tiles.len();
+        Self {
+            rules,
+            rect,
+            possible: vec![vec![true; tile_count]; cell_count],
+            counts: vec![tile_count; cell_count],
+        }
+    }
+
+    fn index(&self, position: Vector2<i32>) -> Option<usize> {
+        self.rect.contains(position).then(|| {
+            let local = position - self.rect.position;
+            (local.y * self.rect.size.x + local.x) as usize
+        })
+    }
+
+    fn position(&self, index: usize) -> Vector2<i32> {
+        let index = index as i32;
+        self.rect.position + Vector2::new(index % self.rect.size.x, index / self.rect.size.x)
+    }
+
+    /// Leaves only the given tiles in the cell. Returns false on contradiction.
+    fn restrict(&mut self, cell: usize, allowed: &[bool]) -> Result<bool, ()> {
+        let mut changed = false;
+        for (possible, allowed) in self.possible[cell].iter_mut().zip(allowed) {
+            if *possible && !allowed {
+                *possible = false;
+                self.counts[cell] -= 1;
+                changed = true;
+            }
+        }
+        if self.counts[cell] == 0 {
+            Err(())
+        } else {
+            Ok(changed)
+        }
+    }
+
+    /// The tiles that may be placed in the given direction from any of the possible tiles of
+    /// the cell.
+    fn supported(&self, cell: usize, direction: usize) -> Vec<bool> {
+        let mut supported = vec![false; self.rules.tiles.len()];
+        for (tile, possible) in self.possible[cell].iter().enumerate() {
+            if *possible {
+                for neighbor in self.rules.adjacency[direction][tile].iter() {
+                    supported[*neighbor] = true;
+                }
+            }
+        }
+        supported
+    }
+
+    /// Removes the tiles, that are not supported by the neighbors, starting from the given cells.
+    /// Returns false on contradiction.
+    fn propagate(&mut self, mut stack: Vec<usize>) -> bool {
+        while let Some(cell) = stack.pop() {
+            let position = self.position(cell);
+            for (direction, offset) in DIRECTIONS.iter().enumerate() {
+                let Some(neighbor) = self.index(position + offset) else {
+                    continue;
+                };
+                let supported = self.supported(cell, direction);
+                match self.restrict(neighbor, &supported) {
+                    Ok(true) => stack.push(neighbor),
+                    Ok(false) => (),
+                    Err(()) => return false,
+                }
+            }
+        }
+        true
+    }
+
+    fn constrain_border<S: TileSource>(&mut self, border: &S) -> bool {
+        let mut stack = Vec::new();
+        for cell in 0..self.counts.len() {
+            let position = self.position(cell);
+            for (direction, offset) in DIRECTIONS.iter().enumerate() {
+                let neighbor = position + offset;
+                if self.rect.contains(neighbor) {
+                    continue;
+                }
+                let Some(tile) = border
+                    .get_at(neighbor)
+                    .and_then(|h| self.rules.indices.get(&h))
+                else {
+                    continue;
+                };
+                let mut allowed = vec![false; self.rules.tiles.len()];
+                for index in self.rules.adjacency[opposite(direction)][*tile].iter() {
+                    allowed[*index] = true;
+                }
+                match self.restrict(cell, &allowed) {
+                    Ok(true) => stack.push(cell),
+                    Ok(false) => (),
+                    Err(()) => return false,
+                }
+            }
+        }
+        self.propagate(stack)
+    }
+
+    /// Finds the undecided cell with the lowest entropy, ties are broken randomly.
+    fn lowest_entropy(&self, rng: &mut StdRng) -> Option<usize> {
+        let mut result = None;
+        let mut lowest = f32::INFINITY;
+        for (cell, count) in self.counts.iter().enumerate() {
+            if *count <= 1 {
+                continue;
+            }
+            let (sum, sum_log) = self.possible[cell]
+                .iter()
+                .zip(self.rules.weights.iter())
+                .filter(|(possible, weight)| **possible && **weight > 0.0)
+                .fold((0.0, 0.0), |(sum, sum_log), (_, weight)| {
+                    (sum + weight, sum_log + weight * weight.ln())
+                });
+            let entropy = if sum > 0.0 {
+                sum.ln() - sum_log / sum
+            } else {
+                0.0
+            };
+            let entropy = entropy + rng.gen_range(0.0..1.0e-3);
+            if entropy < lowest {
+                lowest = entropy;
+                result = Some(cell);
+            }
+        }
+        result
+    }
+
+    /// Picks a random possible tile of the cell according to the weights of the tiles. Tiles with
+    /// zero weight are picked only when there is nothing else.
+    fn choose(&self, cell: usize, rng: &mut StdRng) -> usize {
+        let possible = self.possible[cell]
+            .iter()
+            .enumerate()
+            .filter(|(_, possible)| **possible)
+            .map(|(tile, _)| tile)
+            .collect::<Vec<_>>();
+        let total = possible.iter().map(|t| self.rules.weights[*t]).sum::<f32>();
+        if total <= 0.0 {
+            return possible[rng.gen_range(0..possible.len())];
+        }
+        let mut value = rng.gen_range(0.0..total);
+        for tile in possible.iter() {
+            let weight = self.rules.weights[*tile];
+            if value < weight {
+                return *tile;
+            }
+            value -= weight;
+        }
+        // Rounding errors may leave a tiny bit of the value.
+        *possible
+            .iter()
+            .rev()
+            .find(|t| self.rules.weights[**t] > 0.0)
+            .unwrap()
+    }
+
+    /// Collapses the cells one by one until every cell has a single tile. Returns false on
+    /// contradiction.
+    fn collapse(&mut self, rng: &mut StdRng) -> bool {
+        while let Some(cell) = self.lowest_entropy(rng) {
+            let tile = self.choose(cell, rng);
+            let mut allowed = vec![false; self.rules.tiles.len()];
+            allowed[tile] = true;
+            if self.restrict(cell, &allowed).is_err() || !self.propagate(vec![cell]) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn into_tiles(self) -> Tiles {
+        let mut tiles = Tiles::default();
+        for (cell, possible) in self.possible.iter().enumerate() {
+            if let Some(tile) = possible.iter().position(|p| *p) {
+                tiles.insert(self.position(cell), self.rules.tiles[tile]);
+            }
+        }
+        tiles
+    }
+}
+
+impl TileMap {
+    /// Fills the given rectangle of the tile map with tiles generated by Wave Function Collapse
+    /// algorithm using the given rules. The existing tiles around the rectangle are taken into
+    /// account, so the generated tiles fit their surroundings. See [`WfcRules`] docs for more
+    /// info.
+    pub fn generate_tiles(
+        &mut self,
+        rect: TileRect,
+        rules: &WfcRules,
+        seed: u64,
+    ) -> Result<(), WfcError> {
+        let resource = self.tiles.as_ref().ok_or(WfcError::MissingTiles)?;
+        let mut tiles = resource.data_ref();
+        let tiles = tiles.as_loaded_mut().ok_or(WfcError::MissingTiles)?;
+        let generated = rules.generate(rect, &*tiles, seed)?;
+        for (position, handle) in generated.iter() {
+            tiles.set(*position, *handle);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tile(x: i16) -> TileDefinitionHandle {
+        TileDefinitionHandle::new(0, 0, x, 0)
+    }
+
+    fn v(x: i32, y: i32) -> Vector2<i32> {
+        Vector2::new(x, y)
+    }
+
+    /// Checks that every pair of neighbors in the tiles is allowed by the rules.
+    fn is_valid(rules: &WfcRules, tiles: &Tiles) -> bool {
+        tiles.iter().all(|(position, handle)| {
+            DIRECTIONS.iter().all(|offset| {
+                tiles.get(&(position + offset)).map_or(true, |neighbor| {
+                    rules.is_allowed(*handle, *neighbor, *offset)
+                })
+            })
+        })
+    }
+
+    #[test]
+    fn test_learn() {
+        // Stripes: columns of tile 0 and tile 1 alternate.
+        let mut example = Tiles::default();
+        for y in 0..2 {
+            for x in 0..4 {
+                example.insert(v(x, y), tile((x % 2) as i16));
+            }
+        }
+        let rules = WfcRules::from_example(&example);
+        assert_eq!(rules.tiles().len(), 2);
+        assert!(rules.is_allowed(tile(0), tile(1), v(1, 0)));
+        assert!(rules.is_allowed(tile(0), tile(0), v(0, 1)));
+        assert!(!rules.is_allowed(tile(0), tile(0), v(1, 0)));
+
+        let rect = TileRect::new(-3, 2, 7, 5);
+        let result = rules.generate(rect, &Tiles::default(), 1).unwrap();
+        assert_eq!(result.len(), 35);
+        assert!(is_valid(&rules, &result));
+        // The stripes are vertical, so every column has a single tile.
+        for x in -3..4 {
+            assert!((2..7).all(|y| result[&v(x, y)] == result[&v(x, 2)]));
+        }
+        assert_eq!(result, rules.generate(rect, &Tiles::default(), 1).unwrap());
+    }
+
+    #[test]
+    fn test_border() {
+        let mut rules = WfcRules::default();
+        rules.add_tile(tile(0), 1.0);
+        rules.add_tile(tile(1), 1.0);
+        rules.allow(tile(0), tile(1), v(1, 0));
+        rules.allow(tile(1), tile(0), v(1, 0));
+        rules.allow(tile(0), tile(0), v(0, 1));
+        rules.allow(tile(1), tile(1), v(0, 1));
+
+        let mut border = Tiles::default();
+        border.insert(v(-1, 0), tile(1));
+        let result = rules
+            .generate(TileRect::new(0, 0, 4, 1), &border, 7)
+            .unwrap();
+        assert_eq!(result[&v(0, 0)], tile(0));
+        assert_eq!(result[&v(3, 0)], tile(1));
+
+        // The tiles alternate, so the last one must be the tile 1, which can't be followed by
+        // another tile 1.
+        border.insert(v(4, 0), tile(1));
+        assert_eq!(
+            rules.generate(TileRect::new(0, 0, 4, 1), &border, 7),
+            Err(WfcError::Contradiction)
+        );
+        assert_eq!(
+            WfcRules::default().generate(TileRect::new(0, 0, 4, 1), &border, 7),
+            Err(WfcError::NoTiles)
+        );
+    }
+}
@@ -0,0 +1,483 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Blackboard is a shared storage of named values, that is accessible from every script and plugin.
+//! See [`Blackboard`] docs for more info.
+
+use crate::{
+    asset::{io::ResourceIo, Resource, ResourceData},
+    core::{
+        algebra::{Vector2, Vector3},
+        io::FileLoadError,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        visitor::prelude::*,
+        ImmutableString,
+    },
+    scene::node::Node,
+};
+use fxhash::FxHashMap;
+use std::{
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+    marker::PhantomData,
+    path::Path,
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+pub mod loader;
+
+/// An error that may occur during blackboard loading.
+#[derive(Debug)]
+pub enum BlackboardError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// An error that may occur due to version incompatibilities.
+    Visit(VisitError),
+}
+
+impl Display for BlackboardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlackboardError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            BlackboardError::Visit(v) => {
+                write!(
+                    f,
+                    "An error that may occur due to version incompatibilities. {v:?}"
+                )
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for BlackboardError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<VisitError> for BlackboardError {
+    fn from(e: VisitError) -> Self {
+        Self::Visit(e)
+    }
+}
+
+/// A value stored in a blackboard.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub enum BlackboardValue {
+    /// Absence of a value.
+    #[default]
+    Nil,
+    /// Boolean value.
+    Bool(bool),
+    /// Integer value.
+    Integer(i64),
+    /// Numeric value.
+    Number(f32),
+    /// String value.
+    String(String),
+    /// Two-dimensional vector value.
+    Vector2(Vector2<f32>),
+    /// Three-dimensional vector value.
+    Vector3(Vector3<f32>),
+    /// A handle of a scene node.
+    Node(Handle<Node>),
+}
+
+/// A type, that could be stored in a blackboard.
+pub trait BlackboardType: Sized {
+    /// Wraps the value into [`BlackboardValue`].
+    fn into_value(self) -> BlackboardValue;
+
+    /// Tries to extract the value of this type from [`BlackboardValue`].
+    fn from_value(value: &BlackboardValue) -> Option<Self>;
+}
+
+macro_rules! impl_blackboard_type {
+    ($($ty:ty => $variant:ident),*) => {
+        $(
+            impl BlackboardType for $ty {
+                fn into_value(self) -> BlackboardValue {
+                    BlackboardValue::$variant(self)
+                }
+
+                fn from_value(value: &BlackboardValue) -> Option<Self> {
+                    if let BlackboardValue::$variant(value) = value {
+                        Some(value.clone())
+                    } else {
+                        None
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_blackboard_type!(
+    bool => Bool,
+    i64 => Integer,
+    f32 => Number,
+    String => String,
+    Vector2<f32> => Vector2,
+    Vector3<f32> => Vector3,
+    Handle<Node> => Node
+);
+
+/// A typed key of a blackboard entry. Keys are usually created once and stored in some shared place,
+/// so every user of the entry agrees on its type:
+///
+/// ```rust
+/// # use fyrox_impl::script::blackboard::BlackboardKey;
+/// fn dragon_slain() -> BlackboardKey<bool> {
+///     BlackboardKey::new("Quests/DragonSlain")
+/// }
+/// ```
+pub struct BlackboardKey<T> {
+    name: ImmutableString,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for BlackboardKey<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> Debug for BlackboardKey<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BlackboardKey({})", self.name)
+    }
+}
+
+impl<T: BlackboardType> BlackboardKey<T> {
+    /// Creates a new key with the given name.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: ImmutableString::new(name),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns name of the key.
+    pub fn name(&self) -> &ImmutableString {
+        &self.name
+    }
+}
+
+/// A named value of a blackboard.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct BlackboardEntry {
+    /// Name of the entry.
+    pub name: ImmutableString,
+    /// Value of the entry.
+    pub value: BlackboardValue,
+}
+
+/// A receiver of change notifications of a blackboard entry. See [`Blackboard::subscribe`] for more
+/// info.
+pub struct BlackboardReceiver<T> {
+    receiver: Receiver<BlackboardValue>,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Debug for BlackboardReceiver<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BlackboardReceiver")
+    }
+}
+
+impl<T: BlackboardType> BlackboardReceiver<T> {
+    /// Returns the next new value of the entry (if any). Values of other types (or removal of the
+    /// entry) are skipped.
+    pub fn try_recv(&self) -> Option<T> {
+        while let Ok(value) = self.receiver.try_recv() {
+            if let Some(value) = T::from_value(&value) {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// Returns the latest value of the entry, skipping every intermediate value. It is useful when
+    /// only the final state of the entry matters.
+    pub fn try_recv_latest(&self) -> Option<T> {
+        let mut latest = None;
+        while let Some(value) = self.try_recv() {
+            latest = Some(value);
+        }
+        latest
+    }
+}
+
+/// Blackboard is a shared storage of named values, that is used to share global game state (such as
+/// quest flags, difficulty, score, etc.) between scripts and plugins. Every script and plugin has
+/// access to the global blackboard of the engine via `context.blackboard`, it is a resource, so it
+/// could also be loaded from a file (`*.blackboard`) or saved to it.
+///
+/// Entries are accessed using typed keys (see [`BlackboardKey`]), which prevents type mismatch
+/// errors. Changes of an entry could be tracked by receivers created by [`Blackboard::subscribe`].
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+/// #     plugin::{Plugin, PluginContext},
+/// #     script::{
+/// #         blackboard::{BlackboardKey, BlackboardReceiver},
+/// #         ScriptContext, ScriptTrait,
+/// #     },
+/// # };
+/// #
+/// fn dragon_slain() -> BlackboardKey<bool> {
+///     BlackboardKey::new("Quests/DragonSlain")
+/// }
+///
+/// #[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+/// #[type_uuid(id = "3f0dfc1f-4c5c-4bb5-9b2c-e2cbb6a1f2f5")]
+/// struct Dragon {
+///     health: f32,
+/// }
+///
+/// impl ScriptTrait for Dragon {
+///     fn on_update(&mut self, ctx: &mut ScriptContext) {
+///         if self.health <= 0.0 {
+///             ctx.blackboard.data_ref().set(&dragon_slain(), true);
+///         }
+///     }
+/// }
+///
+/// #[derive(Visit, Reflect, Default, Debug)]
+/// struct Quests {
+///     #[visit(skip)]
+///     #[reflect(hidden)]
+///     dragon_slain: Option<BlackboardReceiver<bool>>,
+/// }
+///
+/// impl Plugin for Quests {
+///     fn update(&mut self, ctx: &mut PluginContext) {
+///         let receiver = self
+///             .dragon_slain
+///             .get_or_insert_with(|| ctx.blackboard.data_ref().subscribe(&dragon_slain()));
+///         if receiver.try_recv() == Some(true) {
+///             println!("The dragon is slain, the reward is ready!");
+///         }
+///     }
+/// }
+/// ```
+#[derive(Default, Debug, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "a4c1d7a2-5be9-4d3f-8f0e-2b8e5d9f7c61")]
+pub struct Blackboard {
+    entries: Vec<BlackboardEntry>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    subscribers: FxHashMap<ImmutableString, Vec<Sender<BlackboardValue>>>,
+}
+
+impl Clone for Blackboard {
+    fn clone(&self) -> Self {
+        // Subscriptions belong to the original blackboard only.
+        Self {
+            entries: self.entries.clone(),
+            subscribers: Default::default(),
+        }
+    }
+}
+
+impl Blackboard {
+    /// Loads a blackboard from the given file.
+    pub async fn from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, BlackboardError> {
+        let bytes = io.load_file(path).await?;
+        let mut visitor = Visitor::load_from_memory(&bytes)?;
+        let mut blackboard = Self::default();
+        blackboard.visit("Blackboard", &mut visitor)?;
+        Ok(blackboard)
+    }
+
+    /// Returns the value of the entry with the given key. Returns `None` if there's no such entry or
+    /// its value has different type.
+    pub fn get<T: BlackboardType>(&self, key: &BlackboardKey<T>) -> Option<T> {
+        self.value(key.name()).and_then(T::from_value)
+    }
+
+    /// Returns the value of the entry with the given key or the default value of the type.
+    pub fn get_or_default<T: BlackboardType + Default>(&self, key: &BlackboardKey<T>) -> T {
+        self.get(key).unwrap_or_default()
+    }
+
+    /// Sets a new value of the entry with the given key. Returns `true` if the value has changed.
+    pub fn set<T: BlackboardType>(&mut self, key: &BlackboardKey<T>, value: T) -> bool {
+        self.set_value(key.name().clone(), value.into_value())
+    }
+
+    /// Returns an untyped value of the entry with the given name.
+    pub fn value(&self, name: &str) -> Option<&BlackboardValue> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name.as_str() == name)
+            .map(|entry| &entry.value)
+    }
+
+    /// Sets a new untyped value of the entry with the given name. Every subscriber of the entry is
+    /// notified if the value has changed. Returns `true` if the value has changed.
+    pub fn set_value(&mut self, name: ImmutableString, value: BlackboardValue) -> bool {
+        match self.entries.iter_mut().find(|entry| entry.name == name) {
+            Some(entry) if entry.value == value => return false,
+            Some(entry) => entry.value = value.clone(),
+            None => self.entries.push(BlackboardEntry {
+                name: name.clone(),
+                value: value.clone(),
+            }),
+        }
+
+        self.notify(&name, value);
+
+        true
+    }
+
+    /// Removes the entry with the given name and returns its value (if any). Subscribers of the
+    /// entry receive [`BlackboardValue::Nil`].
+    pub fn remove(&mut self, name: &str) -> Option<BlackboardValue> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.name.as_str() == name)?;
+        let entry = self.entries.remove(index);
+        self.notify(&entry.name, BlackboardValue::Nil);
+        Some(entry.value)
+    }
+
+    /// Returns `true` if there's an entry with the given name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.value(name).is_some()
+    }
+
+    /// Returns a slice of every entry of the blackboard.
+    pub fn entries(&self) -> &[BlackboardEntry] {
+        &self.entries
+    }
+
+    /// Copies every entry of the other blackboard into this one, notifying subscribers of every
+    /// changed entry. It could be used to load some state from a blackboard resource, without
+    /// breaking existing subscriptions.
+    pub fn merge(&mut self, other: &Blackboard) {
+        for entry in other.entries.iter() {
+            self.set_value(entry.name.clone(), entry.value.clone());
+        }
+    }
+
+    /// Removes every entry of the blackboard. Subscribers of every entry receive
+    /// [`BlackboardValue::Nil`].
+    pub fn clear(&mut self) {
+        for entry in std::mem::take(&mut self.entries) {
+            self.notify(&entry.name, BlackboardValue::Nil);
+        }
+    }
+
+    /// Creates a new receiver of the new values of the entry with the given key. Dropping the
+    /// receiver removes the subscription.
+    pub fn subscribe<T: BlackboardType>(
+        &mut self,
+        key: &BlackboardKey<T>,
+    ) -> BlackboardReceiver<T> {
+        let (sender, receiver) = channel();
+        self.subscribers
+            .entry(key.name().clone())
+            .or_default()
+            .push(sender);
+        BlackboardReceiver {
+            receiver,
+            phantom: PhantomData,
+        }
+    }
+
+    fn notify(&mut self, name: &ImmutableString, value: BlackboardValue) {
+        if let Some(senders) = self.subscribers.get_mut(name) {
+            // Remove subscriptions with dropped receivers.
+            senders.retain(|sender| sender.send(value.clone()).is_ok());
+        }
+    }
+}
+
+impl ResourceData for Blackboard {
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut visitor = Visitor::new();
+        self.visit("Blackboard", &mut visitor)?;
+        visitor.save_binary(path)?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+/// Type alias for blackboard resources.
+pub type BlackboardResource = Resource<Blackboard>;
+
+#[cfg(test)]
+mod test {
+    use crate::script::blackboard::{Blackboard, BlackboardKey, BlackboardValue};
+
+    #[test]
+    fn test_blackboard() {
+        let flag = BlackboardKey::<bool>::new("Flag");
+        let score = BlackboardKey::<i64>::new("Score");
+
+        let mut blackboard = Blackboard::default();
+        let receiver = blackboard.subscribe(&score);
+
+        assert_eq!(blackboard.get(&flag), None);
+        assert!(blackboard.set(&flag, true));
+        assert!(!blackboard.set(&flag, true));
+        assert_eq!(blackboard.get(&flag), Some(true));
+
+        // Type mismatch.
+        assert_eq!(blackboard.get(&BlackboardKey::<f32>::new("Flag")), None);
+
+        assert!(blackboard.set(&score, 10));
+        assert!(blackboard.set(&score, 20));
+        assert_eq!(receiver.try_recv(), Some(10));
+        assert_eq!(receiver.try_recv_latest(), Some(20));
+        assert_eq!(receiver.try_recv(), None);
+
+        assert_eq!(
+            blackboard.remove("Score"),
+            Some(BlackboardValue::Integer(20))
+        );
+        assert_eq!(blackboard.get_or_default(&score), 0);
+        assert_eq!(receiver.try_recv(), None);
+
+        drop(receiver);
+        blackboard.set(&score, 30);
+        assert!(blackboard.subscribers[score.name()].is_empty());
+    }
+}
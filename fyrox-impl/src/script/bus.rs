@@ -0,0 +1,224 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Global message bus, that connects plugins and scripts of every scene. See [`MessageBus`] docs for
+//! more info.
+
+use crate::{
+    core::{parking_lot::Mutex, pool::Handle, ImmutableString},
+    script::{ScriptMessage, ScriptMessageKind, ScriptMessagePayload, ScriptMessageSender, Topic},
+};
+use fxhash::FxHashMap;
+use std::{
+    any::{Any, TypeId},
+    fmt::{Debug, Formatter},
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+};
+
+type TopicKey = (TypeId, ImmutableString);
+
+#[derive(Default)]
+struct MessageBusState {
+    // Every value is a `Sender<T>`, where `T` is the type from the key.
+    subscribers: FxHashMap<TopicKey, Vec<Box<dyn Any + Send>>>,
+    scene_senders: Vec<ScriptMessageSender>,
+}
+
+/// Global message bus allows plugins and scripts to communicate with each other using typed topics
+/// (see [`Topic`]). Every message published to the bus is delivered to:
+///
+/// - every receiver created by [`MessageBus::subscribe`] for the topic; plugins usually store such
+///   receivers and read messages from them in their `update` method.
+/// - every script in every scripted scene, that is subscribed to the topic using
+///   [`crate::engine::ScriptMessageDispatcher::subscribe_to_topic`]. Such messages have no sender
+///   node, so only subscriptions with [`crate::script::MessageFilter::Any`] will accept them.
+///
+/// The bus is cheap to clone, every clone refers to the same bus.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{reflect::prelude::*, visitor::prelude::*},
+/// #     plugin::{Plugin, PluginContext},
+/// #     script::{bus::BusReceiver, Topic},
+/// # };
+/// #[derive(Debug, Clone)]
+/// struct LevelCompleted {
+///     score: u32,
+/// }
+///
+/// fn level_completed() -> Topic<LevelCompleted> {
+///     Topic::new("LevelCompleted")
+/// }
+///
+/// #[derive(Visit, Reflect, Debug, Default)]
+/// struct Game {
+///     #[visit(skip)]
+///     #[reflect(hidden)]
+///     receiver: Option<BusReceiver<LevelCompleted>>,
+/// }
+///
+/// impl Plugin for Game {
+///     fn update(&mut self, context: &mut PluginContext) {
+///         let receiver = self
+///             .receiver
+///             .get_or_insert_with(|| context.message_bus.subscribe(&level_completed()));
+///         while let Some(message) = receiver.try_recv() {
+///             println!("Level completed with score {}", message.score);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct MessageBus {
+    state: Arc<Mutex<MessageBusState>>,
+}
+
+impl Debug for MessageBus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MessageBus")
+    }
+}
+
+impl MessageBus {
+    /// Creates a new receiver of the messages published to the given topic. Dropping the receiver
+    /// removes the subscription.
+    pub fn subscribe<T>(&self, topic: &Topic<T>) -> BusReceiver<T>
+    where
+        T: Send + 'static,
+    {
+        let (sender, receiver) = channel::<T>();
+        self.state
+            .lock()
+            .subscribers
+            .entry((TypeId::of::<T>(), topic.name().clone()))
+            .or_default()
+            .push(Box::new(sender));
+        BusReceiver { receiver }
+    }
+
+    /// Publishes the given payload to the topic. The payload is cloned for every receiver.
+    pub fn publish<T>(&self, topic: &Topic<T>, payload: T)
+    where
+        T: ScriptMessagePayload + Clone,
+    {
+        let mut state = self.state.lock();
+
+        if let Some(senders) = state
+            .subscribers
+            .get_mut(&(TypeId::of::<T>(), topic.name().clone()))
+        {
+            // Remove subscriptions with dropped receivers.
+            senders.retain(|sender| {
+                sender
+                    .downcast_ref::<Sender<T>>()
+                    .map_or(false, |sender| sender.send(payload.clone()).is_ok())
+            });
+        }
+
+        state.scene_senders.retain(|scene_sender| {
+            scene_sender
+                .sender
+                .send(ScriptMessage {
+                    payload: Box::new(payload.clone()),
+                    kind: ScriptMessageKind::Topic {
+                        name: topic.name().clone(),
+                        sender: Handle::NONE,
+                    },
+                })
+                .is_ok()
+        });
+    }
+
+    /// Returns amount of alive receivers of the given topic. Scripts subscribed to the topic are
+    /// not counted.
+    pub fn receiver_count<T>(&self, topic: &Topic<T>) -> usize
+    where
+        T: 'static,
+    {
+        self.state
+            .lock()
+            .subscribers
+            .get(&(TypeId::of::<T>(), topic.name().clone()))
+            .map_or(0, |senders| senders.len())
+    }
+
+    pub(crate) fn add_scene_sender(&self, sender: ScriptMessageSender) {
+        // Senders of removed scenes are removed on publishing.
+        self.state.lock().scene_senders.push(sender);
+    }
+}
+
+/// A receiver of messages published to a topic of the [`MessageBus`].
+pub struct BusReceiver<T> {
+    receiver: Receiver<T>,
+}
+
+impl<T> Debug for BusReceiver<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BusReceiver")
+    }
+}
+
+impl<T> BusReceiver<T> {
+    /// Tries to receive a next message, returns `None` if there are no more messages.
+    pub fn try_recv(&mut self) -> Option<T> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Returns an iterator over all pending messages.
+    pub fn try_iter(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.receiver.try_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Score(u32);
+
+    #[test]
+    fn test_message_bus() {
+        let bus = MessageBus::default();
+        let topic = Topic::<Score>::new("Score");
+        let other_topic = Topic::<Score>::new("Other");
+
+        let mut a = bus.subscribe(&topic);
+        let b = bus.subscribe(&topic);
+        let mut other = bus.subscribe(&other_topic);
+        assert_eq!(bus.receiver_count(&topic), 2);
+
+        bus.publish(&topic, Score(1));
+        assert_eq!(a.try_recv(), Some(Score(1)));
+        assert_eq!(a.try_recv(), None);
+        assert_eq!(other.try_recv(), None);
+
+        drop(b);
+        bus.publish(&topic, Score(2));
+        assert_eq!(bus.receiver_count(&topic), 1);
+        assert_eq!(a.try_iter().collect::<Vec<_>>(), vec![Score(2)]);
+    }
+}
@@ -0,0 +1,855 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Optional Lua scripting backend. It allows writing behaviors in Lua, which is useful for modding
+//! and fast iteration without recompiling Rust code. See [`LuaScript`] docs for more info.
+
+use crate::{
+    asset::{io::ResourceIo, manager::ResourceManager, Resource, ResourceData},
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        io::FileLoadError,
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::{task::TaskPoolHandler, ScriptMessageDispatcher},
+    graph::{BaseSceneGraph, SceneGraph},
+    resource::model::{Model, ModelResourceExtension},
+    scene::{node::Node, Scene},
+    script::{
+        MessageFilter, ScriptContext, ScriptDeinitContext, ScriptMessageContext,
+        ScriptMessagePayload, ScriptMessageSender, ScriptTrait, Topic,
+    },
+};
+use mlua::{FromLua, Function, IntoLua, IntoLuaMulti, Lua, Scope, Table, Value};
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+    path::Path,
+    string::FromUtf8Error,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+pub mod loader;
+
+/// Maximum nesting of tables, that could be converted to [`LuaMessageValue`]. It prevents infinite
+/// recursion on self-referencing tables.
+const MAX_TABLE_DEPTH: usize = 32;
+
+static REVISION: AtomicU64 = AtomicU64::new(1);
+
+/// An error that may occur during Lua source loading.
+#[derive(Debug)]
+pub enum LuaSourceError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// The source is not a valid UTF-8 text.
+    Utf8(FromUtf8Error),
+}
+
+impl Display for LuaSourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LuaSourceError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            LuaSourceError::Utf8(v) => {
+                write!(f, "The source is not a valid UTF-8 text. {v}")
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for LuaSourceError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<FromUtf8Error> for LuaSourceError {
+    fn from(e: FromUtf8Error) -> Self {
+        Self::Utf8(e)
+    }
+}
+
+/// Source code of a Lua script. Every time the source is (re)loaded it gets a new revision, which
+/// is used by [`LuaScript`] to detect changes and reload the code at runtime.
+#[derive(Debug, Clone, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "faa61bd2-8c9a-412e-9c02-5879f261dcf2")]
+pub struct LuaSource {
+    code: String,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    revision: u64,
+}
+
+impl Default for LuaSource {
+    fn default() -> Self {
+        Self::from_string(String::new())
+    }
+}
+
+impl LuaSource {
+    /// Creates new source from the given code.
+    pub fn from_string(code: String) -> Self {
+        Self {
+            code,
+            revision: REVISION.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Loads the source from the given file.
+    pub async fn from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, LuaSourceError> {
+        let bytes = io.load_file(path).await?;
+        Ok(Self::from_string(String::from_utf8(bytes)?))
+    }
+
+    /// Returns the code of the source.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Returns a unique revision of the source.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+}
+
+impl ResourceData for LuaSource {
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, &self.code)?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+/// Type alias for Lua source resources.
+pub type LuaSourceResource = Resource<LuaSource>;
+
+/// A value, that could be passed between Lua scripts in messages. Unlike Lua values, it does not
+/// depend on a particular Lua state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LuaMessageValue {
+    /// `nil` value.
+    Nil,
+    /// Boolean value.
+    Boolean(bool),
+    /// Integer value.
+    Integer(i64),
+    /// Floating point value.
+    Number(f64),
+    /// String value.
+    String(String),
+    /// A table with its key-value pairs. Functions, userdata and other values that cannot be
+    /// copied between Lua states are not included.
+    Table(Vec<(LuaMessageValue, LuaMessageValue)>),
+}
+
+impl LuaMessageValue {
+    fn from_value(value: Value, depth: usize) -> mlua::Result<Option<Self>> {
+        Ok(Some(match value {
+            Value::Nil => Self::Nil,
+            Value::Boolean(value) => Self::Boolean(value),
+            Value::Integer(value) => Self::Integer(value),
+            Value::Number(value) => Self::Number(value),
+            Value::String(value) => Self::String(value.to_str()?.to_string()),
+            Value::Table(table) => {
+                if depth >= MAX_TABLE_DEPTH {
+                    return Err(mlua::Error::runtime("table nesting is too deep"));
+                }
+                let mut pairs = Vec::new();
+                for pair in table.pairs::<Value, Value>() {
+                    let (key, value) = pair?;
+                    if let (Some(key), Some(value)) = (
+                        Self::from_value(key, depth + 1)?,
+                        Self::from_value(value, depth + 1)?,
+                    ) {
+                        pairs.push((key, value));
+                    }
+                }
+                Self::Table(pairs)
+            }
+            _ => return Ok(None),
+        }))
+    }
+}
+
+impl FromLua for LuaMessageValue {
+    fn from_lua(value: Value, _lua: &Lua) -> mlua::Result<Self> {
+        let type_name = value.type_name();
+        Self::from_value(value, 0)?.ok_or_else(|| {
+            mlua::Error::runtime(format!("{type_name} cannot be passed in a message"))
+        })
+    }
+}
+
+impl IntoLua for LuaMessageValue {
+    fn into_lua(self, lua: &Lua) -> mlua::Result<Value> {
+        Ok(match self {
+            Self::Nil => Value::Nil,
+            Self::Boolean(value) => Value::Boolean(value),
+            Self::Integer(value) => Value::Integer(value),
+            Self::Number(value) => Value::Number(value),
+            Self::String(value) => Value::String(lua.create_string(&value)?),
+            Self::Table(pairs) => {
+                let table = lua.create_table()?;
+                for (key, value) in pairs {
+                    table.raw_set(key.into_lua(lua)?, value.into_lua(lua)?)?;
+                }
+                Value::Table(table)
+            }
+        })
+    }
+}
+
+/// A message, that is sent by Lua scripts. Lua scripts receive such messages either directly (when
+/// sent with `ctx.send`) or via topics they're subscribed to (when sent with `ctx.publish`). It could
+/// be sent from Rust code too, to communicate with Lua scripts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LuaMessage {
+    /// Name of the topic of the message.
+    pub topic: String,
+    /// Value of the message.
+    pub value: LuaMessageValue,
+    /// A node, that sent the message.
+    pub sender: Handle<Node>,
+}
+
+impl LuaMessage {
+    /// Creates a topic, that could be used to publish messages to Lua scripts subscribed to the
+    /// topic with the given name.
+    pub fn topic(name: &str) -> Topic<LuaMessage> {
+        Topic::new(name)
+    }
+}
+
+/// Converts a node handle into an integer, that is used to identify nodes in Lua.
+pub fn encode_handle(handle: Handle<Node>) -> i64 {
+    ((handle.generation() as i64) << 32) | handle.index() as i64
+}
+
+/// Converts an integer produced by [`encode_handle`] back to a node handle.
+pub fn decode_handle(value: i64) -> Handle<Node> {
+    Handle::new(value as u32, (value >> 32) as u32)
+}
+
+/// A subset of the script context, that is available to Lua code.
+struct Host<'a> {
+    handle: Handle<Node>,
+    scene_handle: Handle<Scene>,
+    script_index: usize,
+    dt: f32,
+    elapsed_time: f32,
+    scene: &'a mut Scene,
+    resource_manager: &'a ResourceManager,
+    message_sender: &'a ScriptMessageSender,
+    message_dispatcher: Option<&'a mut ScriptMessageDispatcher>,
+    task_pool: &'a mut TaskPoolHandler,
+}
+
+impl<'a> Host<'a> {
+    fn from_script_context(ctx: &'a mut ScriptContext) -> Self {
+        Self {
+            handle: ctx.handle,
+            scene_handle: ctx.scene_handle,
+            script_index: ctx.script_index,
+            dt: ctx.dt,
+            elapsed_time: ctx.elapsed_time,
+            scene: &mut *ctx.scene,
+            resource_manager: ctx.resource_manager,
+            message_sender: ctx.message_sender,
+            message_dispatcher: Some(&mut *ctx.message_dispatcher),
+            task_pool: &mut *ctx.task_pool,
+        }
+    }
+
+    fn from_message_context(ctx: &'a mut ScriptMessageContext) -> Self {
+        Self {
+            handle: ctx.handle,
+            scene_handle: ctx.scene_handle,
+            script_index: ctx.script_index,
+            dt: ctx.dt,
+            elapsed_time: ctx.elapsed_time,
+            scene: &mut *ctx.scene,
+            resource_manager: ctx.resource_manager,
+            message_sender: ctx.message_sender,
+            message_dispatcher: None,
+            task_pool: &mut *ctx.task_pool,
+        }
+    }
+
+    fn from_deinit_context(ctx: &'a mut ScriptDeinitContext) -> Self {
+        Self {
+            handle: ctx.node_handle,
+            scene_handle: ctx.scene_handle,
+            script_index: ctx.script_index,
+            dt: 0.0,
+            elapsed_time: ctx.elapsed_time,
+            scene: &mut *ctx.scene,
+            resource_manager: ctx.resource_manager,
+            message_sender: ctx.message_sender,
+            message_dispatcher: None,
+            task_pool: &mut *ctx.task_pool,
+        }
+    }
+
+    fn node_mut(&mut self, handle: i64) -> mlua::Result<&mut Node> {
+        self.scene
+            .graph
+            .try_get_mut(decode_handle(handle))
+            .ok_or_else(|| mlua::Error::runtime(format!("{handle} is not a valid node handle")))
+    }
+}
+
+/// Creates the `ctx` table, that is passed to every Lua callback. Functions of the table are valid
+/// only during the callback.
+fn make_context_table<'scope, 'env>(
+    lua: &Lua,
+    scope: &'scope Scope<'scope, 'env>,
+    host: &'env RefCell<Host<'_>>,
+) -> mlua::Result<Table> {
+    let ctx = lua.create_table()?;
+
+    {
+        let host = host.borrow();
+        ctx.set("handle", encode_handle(host.handle))?;
+        ctx.set("dt", host.dt)?;
+        ctx.set("elapsed_time", host.elapsed_time)?;
+    }
+
+    ctx.set(
+        "log",
+        scope.create_function(move |_, message: String| {
+            Log::info(message);
+            Ok(())
+        })?,
+    )?;
+
+    ctx.set(
+        "find",
+        scope.create_function(move |_, name: String| {
+            Ok(host
+                .borrow()
+                .scene
+                .graph
+                .find_by_name_from_root(&name)
+                .map(|(handle, _)| encode_handle(handle)))
+        })?,
+    )?;
+
+    ctx.set(
+        "position",
+        scope.create_function(move |_, node: i64| {
+            let mut host = host.borrow_mut();
+            let position = **host.node_mut(node)?.local_transform().position();
+            Ok((position.x, position.y, position.z))
+        })?,
+    )?;
+
+    ctx.set(
+        "set_position",
+        scope.create_function(move |_, (node, x, y, z): (i64, f32, f32, f32)| {
+            host.borrow_mut()
+                .node_mut(node)?
+                .local_transform_mut()
+                .set_position(Vector3::new(x, y, z));
+            Ok(())
+        })?,
+    )?;
+
+    ctx.set(
+        "global_position",
+        scope.create_function(move |_, node: i64| {
+            let mut host = host.borrow_mut();
+            let position = host.node_mut(node)?.global_position();
+            Ok((position.x, position.y, position.z))
+        })?,
+    )?;
+
+    ctx.set(
+        "rotation",
+        scope.create_function(move |_, node: i64| {
+            let mut host = host.borrow_mut();
+            Ok(host
+                .node_mut(node)?
+                .local_transform()
+                .rotation()
+                .euler_angles())
+        })?,
+    )?;
+
+    ctx.set(
+        "set_rotation",
+        scope.create_function(move |_, (node, x, y, z): (i64, f32, f32, f32)| {
+            host.borrow_mut()
+                .node_mut(node)?
+                .local_transform_mut()
+                .set_rotation(UnitQuaternion::from_euler_angles(x, y, z));
+            Ok(())
+        })?,
+    )?;
+
+    ctx.set(
+        "scale",
+        scope.create_function(move |_, node: i64| {
+            let mut host = host.borrow_mut();
+            let scale = **host.node_mut(node)?.local_transform().scale();
+            Ok((scale.x, scale.y, scale.z))
+        })?,
+    )?;
+
+    ctx.set(
+        "set_scale",
+        scope.create_function(move |_, (node, x, y, z): (i64, f32, f32, f32)| {
+            host.borrow_mut()
+                .node_mut(node)?
+                .local_transform_mut()
+                .set_scale(Vector3::new(x, y, z));
+            Ok(())
+        })?,
+    )?;
+
+    ctx.set(
+        "destroy",
+        scope.create_function(move |_, node: i64| {
+            let mut host = host.borrow_mut();
+            host.node_mut(node)?;
+            host.scene.graph.remove_node(decode_handle(node));
+            Ok(())
+        })?,
+    )?;
+
+    ctx.set(
+        "send",
+        scope.create_function(
+            move |_, (node, topic, value): (i64, String, LuaMessageValue)| {
+                let host = host.borrow();
+                host.message_sender.send_to_target(
+                    decode_handle(node),
+                    LuaMessage {
+                        topic,
+                        value,
+                        sender: host.handle,
+                    },
+                );
+                Ok(())
+            },
+        )?,
+    )?;
+
+    ctx.set(
+        "publish",
+        scope.create_function(move |_, (topic, value): (String, LuaMessageValue)| {
+            let host = host.borrow();
+            host.message_sender.publish(
+                &LuaMessage::topic(&topic),
+                host.handle,
+                LuaMessage {
+                    topic,
+                    value,
+                    sender: host.handle,
+                },
+            );
+            Ok(())
+        })?,
+    )?;
+
+    ctx.set(
+        "subscribe",
+        scope.create_function(move |_, topic: String| {
+            let mut host = host.borrow_mut();
+            let handle = host.handle;
+            let dispatcher = host.message_dispatcher.as_mut().ok_or_else(|| {
+                mlua::Error::runtime("subscriptions are not allowed in this callback")
+            })?;
+            dispatcher.subscribe_to_topic(&LuaMessage::topic(&topic), handle, MessageFilter::Any);
+            Ok(())
+        })?,
+    )?;
+
+    ctx.set(
+        "instantiate",
+        scope.create_function(move |_, path: String| {
+            let mut host = host.borrow_mut();
+            let future = host.resource_manager.request::<Model>(&path);
+            let (scene_handle, handle, script_index) =
+                (host.scene_handle, host.handle, host.script_index);
+            host.task_pool.spawn_script_task(
+                scene_handle,
+                handle,
+                script_index,
+                future,
+                move |result, script: &mut LuaScript, ctx| match result {
+                    Ok(model) => {
+                        let instance = model.instantiate(ctx.scene);
+                        script.invoke(
+                            "on_instantiated",
+                            Host::from_script_context(ctx),
+                            (path.clone(), encode_handle(instance)),
+                        );
+                    }
+                    Err(err) => Log::err(format!("Unable to instantiate {path}: {err:?}")),
+                },
+            );
+            Ok(())
+        })?,
+    )?;
+
+    Ok(ctx)
+}
+
+struct LuaRuntime {
+    lua: Lua,
+    instance: Table,
+}
+
+impl LuaRuntime {
+    fn new(name: &str, code: &str, state: Option<LuaMessageValue>) -> mlua::Result<Self> {
+        let lua = Lua::new();
+        lua.load(code).set_name(name).exec()?;
+        let instance = match state.map(|state| state.into_lua(&lua)).transpose()? {
+            Some(Value::Table(table)) => table,
+            _ => lua.create_table()?,
+        };
+        Ok(Self { lua, instance })
+    }
+}
+
+#[derive(Default)]
+struct RuntimeSlot {
+    runtime: Option<LuaRuntime>,
+    // Revision of the source, that was loaded last time (successfully or not).
+    revision: u64,
+    started: bool,
+}
+
+impl Clone for RuntimeSlot {
+    fn clone(&self) -> Self {
+        // Lua state cannot be shared between script instances, every copy creates its own state.
+        Self::default()
+    }
+}
+
+impl Debug for RuntimeSlot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RuntimeSlot")
+            .field("revision", &self.revision)
+            .field("started", &self.started)
+            .finish()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum RuntimeStatus {
+    NotReady,
+    Ready,
+    Reloaded,
+}
+
+/// A script, that runs a behavior written in Lua. Every instance of the script has its own Lua state,
+/// and the behavior is defined by global functions of the source:
+///
+/// - `on_init(self, ctx)` and `on_start(self, ctx)` - called once, when the source is loaded.
+/// - `on_update(self, ctx)` - called every frame.
+/// - `on_message(self, ctx, topic, value, sender)` - called when a message is received.
+/// - `on_reload(self, ctx)` - called when the source was changed and reloaded.
+/// - `on_instantiated(self, ctx, path, node)` - called when a prefab requested by `ctx.instantiate`
+///   was instantiated.
+/// - `on_deinit(self, ctx)` - called when the script is destroyed.
+///
+/// `self` is a table, that could be used to store the state of the behavior. When the source is
+/// changed (the resource manager reloads changed resources automatically, if file system watcher
+/// is enabled), the code is reloaded at runtime and everything, that could be copied between Lua
+/// states (numbers, strings, booleans and tables of them) is transferred from the old `self` table
+/// to the new one. `ctx` is a table with the following content:
+///
+/// - `handle`, `dt`, `elapsed_time` - the node of the script, the time passed since the last
+///   frame and since the start of the engine respectively.
+/// - `log(message)` - writes the message to the log.
+/// - `find(name)` - returns a handle of a node with the given name or `nil`.
+/// - `position(node)`, `set_position(node, x, y, z)`, `global_position(node)`, `rotation(node)`,
+///   `set_rotation(node, x, y, z)` (Euler angles in radians), `scale(node)`,
+///   `set_scale(node, x, y, z)` - access to local transform of nodes.
+/// - `destroy(node)` - removes the node from the scene.
+/// - `send(node, topic, value)` - sends a message to the given node.
+/// - `publish(topic, value)` - publishes a message to the topic, see [`Topic`] docs for more info.
+/// - `subscribe(topic)` - subscribes the script to the topic. Not available in `on_message` and
+///   `on_deinit`.
+/// - `instantiate(path)` - asynchronously loads a prefab and instantiates it in the scene.
+///
+/// Node handles are passed to Lua as integers, see [`encode_handle`] and [`decode_handle`].
+///
+/// ## Example
+///
+/// ```lua
+/// function on_start(self, ctx)
+///     self.speed = 2.0
+///     ctx.subscribe("damage")
+/// end
+///
+/// function on_update(self, ctx)
+///     local x, y, z = ctx.position(ctx.handle)
+///     ctx.set_position(ctx.handle, x + self.speed * ctx.dt, y, z)
+/// end
+///
+/// function on_message(self, ctx, topic, value, sender)
+///     if topic == "damage" then
+///         ctx.destroy(ctx.handle)
+///     end
+/// end
+/// ```
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "f5a27a7b-524d-4e1f-9c0b-373f88f5a509")]
+#[visit(optional)]
+pub struct LuaScript {
+    /// Source code of the behavior.
+    pub source: Option<LuaSourceResource>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    slot: RuntimeSlot,
+}
+
+impl LuaScript {
+    /// Creates new script with the given source.
+    pub fn new(source: LuaSourceResource) -> Self {
+        Self {
+            source: Some(source),
+            slot: Default::default(),
+        }
+    }
+
+    /// Returns `true` if the source is loaded and the behavior is running.
+    pub fn is_running(&self) -> bool {
+        self.slot.runtime.is_some()
+    }
+
+    // Loads the source or reloads it, if it was changed.
+    fn sync_runtime(&mut self) -> RuntimeStatus {
+        let Some(source) = self.source.as_ref() else {
+            return RuntimeStatus::NotReady;
+        };
+        let source_ref = source.data_ref();
+        let Some(source_data) = source_ref.as_loaded_ref() else {
+            return RuntimeStatus::NotReady;
+        };
+
+        let status = if self.slot.runtime.is_some() {
+            RuntimeStatus::Ready
+        } else {
+            RuntimeStatus::NotReady
+        };
+
+        if self.slot.revision == source_data.revision {
+            return status;
+        }
+        self.slot.revision = source_data.revision;
+
+        let name = source.kind().to_string();
+        let state = match self.slot.runtime.as_ref() {
+            Some(runtime) => {
+                match LuaMessageValue::from_lua(
+                    Value::Table(runtime.instance.clone()),
+                    &runtime.lua,
+                ) {
+                    Ok(state) => Some(state),
+                    Err(err) => {
+                        Log::warn(format!(
+                            "Unable to transfer state of Lua script {name}: {err}"
+                        ));
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        match LuaRuntime::new(&name, source_data.code(), state) {
+            Ok(runtime) => {
+                self.slot.runtime = Some(runtime);
+                if status == RuntimeStatus::Ready {
+                    RuntimeStatus::Reloaded
+                } else {
+                    RuntimeStatus::Ready
+                }
+            }
+            Err(err) => {
+                // Keep the previous version running (if any), the broken one will not be loaded
+                // until the source is changed again.
+                Log::err(format!("Unable to load Lua script {name}: {err}"));
+                status
+            }
+        }
+    }
+
+    fn invoke(&self, name: &str, host: Host, args: impl IntoLuaMulti) {
+        let Some(runtime) = self.slot.runtime.as_ref() else {
+            return;
+        };
+
+        let host = RefCell::new(host);
+        let result = runtime.lua.scope(|scope| {
+            let Some(function) = runtime.lua.globals().get::<Option<Function>>(name)? else {
+                return Ok(());
+            };
+            let ctx = make_context_table(&runtime.lua, scope, &host)?;
+            let mut args = args.into_lua_multi(&runtime.lua)?;
+            args.push_front(Value::Table(ctx));
+            args.push_front(Value::Table(runtime.instance.clone()));
+            function.call::<()>(args)?;
+            Ok(())
+        });
+
+        if let Err(err) = result {
+            let source = self
+                .source
+                .as_ref()
+                .map(|source| source.kind().to_string())
+                .unwrap_or_default();
+            Log::err(format!("Lua script {source} failed in {name}: {err}"));
+        }
+    }
+
+    fn update_runtime(&mut self, ctx: &mut ScriptContext) -> bool {
+        match self.sync_runtime() {
+            RuntimeStatus::NotReady => return false,
+            RuntimeStatus::Ready => (),
+            RuntimeStatus::Reloaded => {
+                if self.slot.started {
+                    self.invoke("on_reload", Host::from_script_context(ctx), ());
+                }
+            }
+        }
+
+        if !self.slot.started {
+            self.slot.started = true;
+            self.invoke("on_init", Host::from_script_context(ctx), ());
+            self.invoke("on_start", Host::from_script_context(ctx), ());
+        }
+
+        true
+    }
+}
+
+impl ScriptTrait for LuaScript {
+    fn on_init(&mut self, ctx: &mut ScriptContext) {
+        ctx.message_dispatcher
+            .subscribe_to::<LuaMessage>(ctx.handle);
+    }
+
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        self.update_runtime(ctx);
+    }
+
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        if self.slot.started {
+            self.invoke("on_deinit", Host::from_deinit_context(ctx), ());
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if self.update_runtime(ctx) {
+            self.invoke("on_update", Host::from_script_context(ctx), ());
+        }
+    }
+
+    fn on_message(
+        &mut self,
+        message: &mut dyn ScriptMessagePayload,
+        ctx: &mut ScriptMessageContext,
+    ) {
+        if !self.slot.started {
+            return;
+        }
+
+        if let Some(message) = message.downcast_ref::<LuaMessage>() {
+            self.invoke(
+                "on_message",
+                Host::from_message_context(ctx),
+                (
+                    message.topic.clone(),
+                    message.value.clone(),
+                    encode_handle(message.sender),
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_handle_encoding() {
+        let handle = Handle::<Node>::new(123, 456);
+        assert_eq!(decode_handle(encode_handle(handle)), handle);
+    }
+
+    #[test]
+    fn test_message_value_roundtrip() {
+        let lua = Lua::new();
+        let value: Value = lua
+            .load(r#"{ name = "player", health = 100, speed = 2.5, alive = true, f = print }"#)
+            .eval()
+            .unwrap();
+        let converted = LuaMessageValue::from_lua(value, &lua).unwrap();
+        let LuaMessageValue::Table(ref pairs) = converted else {
+            panic!("table expected");
+        };
+        // Functions cannot be copied between states and must be skipped.
+        assert_eq!(pairs.len(), 4);
+
+        let other = Lua::new();
+        let table = converted.clone().into_lua(&other).unwrap();
+        let Value::Table(table) = table else {
+            panic!("table expected");
+        };
+        assert_eq!(table.get::<String>("name").unwrap(), "player");
+        assert_eq!(table.get::<i64>("health").unwrap(), 100);
+        assert_eq!(table.get::<f64>("speed").unwrap(), 2.5);
+        assert!(table.get::<bool>("alive").unwrap());
+    }
+
+    #[test]
+    fn test_state_transfer() {
+        let runtime = LuaRuntime::new("test", "function on_update(self) end", None).unwrap();
+        runtime.instance.set("counter", 42).unwrap();
+        let state = LuaMessageValue::from_lua(Value::Table(runtime.instance.clone()), &runtime.lua)
+            .unwrap();
+
+        let reloaded =
+            LuaRuntime::new("test", "function on_update(self) end", Some(state)).unwrap();
+        assert_eq!(reloaded.instance.get::<i64>("counter").unwrap(), 42);
+
+        assert!(LuaRuntime::new("test", "this is not lua", None).is_err());
+    }
+}
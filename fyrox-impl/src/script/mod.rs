@@ -28,26 +28,41 @@ use crate::{
         log::Log,
         pool::Handle,
         reflect::{FieldInfo, Reflect, ReflectArray, ReflectList},
+        task::{AsyncTask, AsyncTaskResult},
         type_traits::ComponentProvider,
         uuid::Uuid,
         visitor::{Visit, VisitResult, Visitor},
-        TypeUuidProvider,
+        ImmutableString, TypeUuidProvider,
     },
     engine::{task::TaskPoolHandler, GraphicsContext, ScriptMessageDispatcher},
     event::Event,
     gui::UiContainer,
     plugin::{Plugin, PluginContainer},
+    resource::localization::{Localization, LocalizationResource},
+    save::SaveState,
     scene::{base::NodeScriptMessage, node::Node, Scene},
+    script::{
+        blackboard::{Blackboard, BlackboardResource},
+        bus::MessageBus,
+        scheduler::{ScheduleHandle, Scheduler, Trigger},
+    },
 };
 use std::{
     any::{Any, TypeId},
     fmt::{Debug, Formatter},
+    marker::PhantomData,
     ops::{Deref, DerefMut},
     str::FromStr,
     sync::mpsc::Sender,
 };
 
+pub mod blackboard;
+pub mod bus;
 pub mod constructor;
+#[cfg(feature = "lua")]
+pub mod lua;
+pub mod scheduler;
+pub mod visual;
 
 pub(crate) trait UniversalScriptContext {
     fn node(&mut self) -> Option<&mut Node>;
@@ -127,6 +142,111 @@ pub enum ScriptMessageKind {
     /// An message that will be delivered for **every** scene node that is subscribed to receive messages
     /// of a particular type.
     Global,
+
+    /// An message for a named topic. It will be delivered to every scene node that is subscribed to
+    /// the topic (see [`crate::engine::ScriptMessageDispatcher::subscribe_to_topic`]) and whose
+    /// subscription filter accepts the sender.
+    Topic {
+        /// Name of the topic.
+        name: ImmutableString,
+        /// A node that has sent the message. Could be [`Handle::NONE`], if the message was sent
+        /// by something else than a script (for example - a plugin).
+        sender: Handle<Node>,
+    },
+}
+
+/// Topic is a named channel for script messages of a particular type `T`. Topics allow to have
+/// multiple independent streams of messages of the same type and the type parameter guarantees,
+/// that publishers and subscribers of a topic agree on the type of its messages.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::script::Topic;
+/// #[derive(Debug, Clone)]
+/// struct Damage {
+///     amount: f32,
+/// }
+///
+/// fn player_damage_topic() -> Topic<Damage> {
+///     Topic::new("PlayerDamage")
+/// }
+/// ```
+pub struct Topic<T> {
+    name: ImmutableString,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Topic<T> {
+    /// Creates a new topic with the given name.
+    pub fn new<S: AsRef<str>>(name: S) -> Self {
+        Self {
+            name: ImmutableString::new(name),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns name of the topic.
+    pub fn name(&self) -> &ImmutableString {
+        &self.name
+    }
+}
+
+impl<T> Clone for Topic<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> Debug for Topic<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Topic({})", self.name)
+    }
+}
+
+/// A filter of topic subscriptions, it defines which senders of topic messages a subscriber is
+/// interested in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MessageFilter {
+    /// Accept messages from any sender.
+    #[default]
+    Any,
+    /// Accept messages only from the given node.
+    Node(Handle<Node>),
+    /// Accept messages only from nodes with the given tag.
+    Tag(String),
+}
+
+impl MessageFilter {
+    /// Checks whether the filter accepts a message from the given sender.
+    pub fn accepts(&self, sender: Handle<Node>, scene: &Scene) -> bool {
+        match self {
+            MessageFilter::Any => true,
+            MessageFilter::Node(node) => *node == sender,
+            MessageFilter::Tag(tag) => scene
+                .graph
+                .try_get(sender)
+                .map_or(false, |node| node.tag() == tag),
+        }
+    }
+}
+
+/// Predefined execution order groups for [`ScriptTrait::execution_order`]. Any other values could be
+/// used as well, for example `execution_order::CONTROLLER + 1` to run a script right after controllers.
+pub mod execution_order {
+    /// Scripts, that gather input and store it for other scripts.
+    pub const INPUT: i32 = -200;
+    /// Scripts, that control characters and other objects using the input.
+    pub const CONTROLLER: i32 = -100;
+    /// Default group for all scripts.
+    pub const DEFAULT: i32 = 0;
+    /// Scripts, that control cameras and that should see final positions of objects.
+    pub const CAMERA: i32 = 100;
+    /// Scripts, that should run after everything else.
+    pub const LATE: i32 = 200;
 }
 
 /// A script message sender.
@@ -142,6 +262,13 @@ impl Debug for ScriptMessageSender {
 }
 
 impl ScriptMessageSender {
+    /// Creates a new sender, that sends messages to the given channel. It could be used to send
+    /// script messages to custom receivers (in tests, for example), messages to scripts of a scene
+    /// should be sent using the sender of the scene (see [`crate::engine::ScriptedScene`]).
+    pub fn new(sender: Sender<ScriptMessage>) -> Self {
+        Self { sender }
+    }
+
     /// Send a generic script message.
     pub fn send(&self, message: ScriptMessage) {
         if self.sender.send(message).is_err() {
@@ -181,6 +308,21 @@ impl ScriptMessageSender {
             kind: ScriptMessageKind::Hierarchical { root, routing },
         })
     }
+
+    /// Publishes the given payload to the topic on behalf of the `sender` node. The message will be
+    /// delivered to every node subscribed to the topic.
+    pub fn publish<T>(&self, topic: &Topic<T>, sender: Handle<Node>, payload: T)
+    where
+        T: ScriptMessagePayload,
+    {
+        self.send(ScriptMessage {
+            payload: Box::new(payload),
+            kind: ScriptMessageKind::Topic {
+                name: topic.name.clone(),
+                sender,
+            },
+        })
+    }
 }
 
 /// Base script trait is used to automatically implement some trait to reduce amount of boilerplate code.
@@ -385,6 +527,22 @@ pub struct ScriptContext<'a, 'b, 'c> {
     /// explicitly. See [`ScriptTrait::on_message`] for more examples.
     pub message_dispatcher: &'c mut ScriptMessageDispatcher,
 
+    /// Global message bus, that could be used to communicate with plugins and scripts of other
+    /// scenes. See [`MessageBus`] docs for more info.
+    pub message_bus: &'a MessageBus,
+
+    /// Global blackboard, that could be used to share some state with plugins and other scripts.
+    /// See [`Blackboard`] docs for more info.
+    pub blackboard: &'a BlackboardResource,
+
+    /// Global localization, that could be used to fetch translated strings for the current
+    /// language. See [`Localization`] docs for more info.
+    pub localization: &'a LocalizationResource,
+
+    /// Scheduler of the scene, that could be used to run actions later or periodically. See
+    /// [`Scheduler`] docs for more info.
+    pub scheduler: &'c mut Scheduler,
+
     /// Task pool for asynchronous task management.
     pub task_pool: &'a mut TaskPoolHandler,
 
@@ -396,6 +554,12 @@ pub struct ScriptContext<'a, 'b, 'c> {
     /// get a reference to it.
     pub user_interfaces: &'a mut UiContainer,
 
+    /// Fraction of the fixed time step, that was accumulated but not simulated yet. It is always in
+    /// `[0; 1)` range. It could be used to interpolate visual state of a node between its previous
+    /// and current logic states, to get smooth movement when the frame rate is higher than the
+    /// update rate.
+    pub interpolation_alpha: f32,
+
     /// Index of the script. Never save this index, it is only valid while this context exists!
     pub script_index: usize,
 }
@@ -423,6 +587,136 @@ impl UniversalScriptContext for ScriptContext<'_, '_, '_> {
     }
 }
 
+impl ScriptContext<'_, '_, '_> {
+    /// Spawns an asynchronous task, that belongs to the current script instance. When the task is
+    /// finished, the `on_complete` closure is called (at the beginning of the next update iteration)
+    /// with the result of the task, the script instance and a new context. This is a shortcut for
+    /// [`TaskPoolHandler::spawn_script_task`]. Returns an id of the task, that could be used to
+    /// cancel it via [`TaskPoolHandler::cancel_task`].
+    ///
+    /// The future could await any number of other futures, which makes it possible to write
+    /// sequential logic (like "load a model, then load its texture") as a plain async block instead
+    /// of a hand-written state machine.
+    ///
+    /// ## Example
+    ///
+    /// ```rust ,no_run
+    /// # use fyrox_impl::{
+    /// #     core::{reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+    /// #     resource::{model::{Model, ModelResourceExtension}, texture::Texture},
+    /// #     script::{ScriptContext, ScriptTrait},
+    /// # };
+    /// #
+    /// #[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+    /// #[type_uuid(id = "a7b4ef25-3bd0-46a1-bc37-4cdd1e4d1b19")]
+    /// struct MyScript;
+    ///
+    /// impl ScriptTrait for MyScript {
+    ///     fn on_start(&mut self, ctx: &mut ScriptContext) {
+    ///         let resource_manager = ctx.resource_manager.clone();
+    ///         ctx.spawn_task(
+    ///             async move {
+    ///                 let model = resource_manager.request::<Model>("path/to/model.fbx").await;
+    ///                 let texture = resource_manager.request::<Texture>("path/to/skin.png").await;
+    ///                 (model, texture)
+    ///             },
+    ///             |(model, _texture), _script: &mut MyScript, ctx| {
+    ///                 if let Ok(model) = model {
+    ///                     model.instantiate(&mut ctx.scene);
+    ///                 }
+    ///             },
+    ///         );
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn spawn_task<F, T, C, S>(&mut self, future: F, on_complete: C) -> Uuid
+    where
+        F: AsyncTask<T>,
+        T: AsyncTaskResult,
+        for<'a, 'b, 'c> C: Fn(T, &mut S, &mut ScriptContext<'a, 'b, 'c>) + 'static,
+        S: ScriptTrait,
+    {
+        self.task_pool.spawn_script_task(
+            self.scene_handle,
+            self.handle,
+            self.script_index,
+            future,
+            on_complete,
+        )
+    }
+
+    /// Spawns an asynchronous task, that belongs to the current script instance. When the task is
+    /// finished, its result is sent as a targeted message to the node of the script, and it could be
+    /// handled in [`ScriptTrait::on_message`]. Keep in mind, that the script must be subscribed to
+    /// messages of type `T`. This is a shortcut for [`TaskPoolHandler::spawn_script_message_task`].
+    /// Returns an id of the task, that could be used to cancel it via [`TaskPoolHandler::cancel_task`].
+    #[inline]
+    pub fn spawn_task_with_message<F, T>(&mut self, future: F) -> Uuid
+    where
+        F: AsyncTask<T>,
+        T: AsyncTaskResult + ScriptMessagePayload,
+    {
+        self.task_pool.spawn_script_message_task(
+            self.scene_handle,
+            self.handle,
+            self.script_index,
+            future,
+        )
+    }
+
+    /// Publishes the given payload to the topic on behalf of the node of the script. It is a
+    /// shortcut for [`ScriptMessageSender::publish`].
+    #[inline]
+    pub fn publish<T>(&self, topic: &Topic<T>, payload: T)
+    where
+        T: ScriptMessagePayload,
+    {
+        self.message_sender.publish(topic, self.handle, payload)
+    }
+
+    /// Subscribes the node of the script to the topic with the given filter. It is a shortcut for
+    /// [`ScriptMessageDispatcher::subscribe_to_topic`].
+    #[inline]
+    pub fn subscribe_to_topic<T: 'static>(&mut self, topic: &Topic<T>, filter: MessageFilter) {
+        self.message_dispatcher
+            .subscribe_to_topic(topic, self.handle, filter)
+    }
+
+    /// Schedules a closure, that will be called with the current script instance when the trigger
+    /// fires. The closure is cancelled automatically when the script is destroyed. This is a
+    /// shortcut for [`Scheduler::schedule_closure`]. Returns a handle, that could be used to cancel
+    /// the closure via [`Self::cancel_scheduled`].
+    #[inline]
+    pub fn schedule<S, C>(&mut self, trigger: Trigger, closure: C) -> ScheduleHandle
+    where
+        S: ScriptTrait,
+        for<'a, 'b, 'c> C: FnMut(&mut S, &mut ScriptContext<'a, 'b, 'c>) + 'static,
+    {
+        self.scheduler
+            .schedule_closure(self.handle, self.script_index, trigger, closure)
+    }
+
+    /// Schedules a targeted message, that will be sent to the node of the script when the trigger
+    /// fires. Keep in mind, that the script must be subscribed to messages of type `T`. This is a
+    /// shortcut for [`Scheduler::schedule_message`].
+    #[inline]
+    pub fn schedule_message<T>(&mut self, trigger: Trigger, payload: T) -> ScheduleHandle
+    where
+        T: ScriptMessagePayload + Clone,
+    {
+        self.scheduler
+            .schedule_message(trigger, self.handle, payload)
+    }
+
+    /// Cancels a scheduled action. Returns `true` if the action was pending, `false` - otherwise.
+    /// This is a shortcut for [`Scheduler::cancel`].
+    #[inline]
+    pub fn cancel_scheduled(&mut self, handle: ScheduleHandle) -> bool {
+        self.scheduler.cancel(handle)
+    }
+}
+
 /// A set of data, that provides contextual information for script methods.
 pub struct ScriptMessageContext<'a, 'b, 'c> {
     /// Amount of time that passed from last call. It has valid values only when called from `on_update`.
@@ -461,6 +755,18 @@ pub struct ScriptMessageContext<'a, 'b, 'c> {
     /// method of every script.
     pub message_sender: &'c ScriptMessageSender,
 
+    /// Global message bus, that could be used to communicate with plugins and scripts of other
+    /// scenes. See [`MessageBus`] docs for more info.
+    pub message_bus: &'a MessageBus,
+
+    /// Global blackboard, that could be used to share some state with plugins and other scripts.
+    /// See [`Blackboard`] docs for more info.
+    pub blackboard: &'a BlackboardResource,
+
+    /// Global localization, that could be used to fetch translated strings for the current
+    /// language. See [`Localization`] docs for more info.
+    pub localization: &'a LocalizationResource,
+
     /// Task pool for asynchronous task management.
     pub task_pool: &'a mut TaskPoolHandler,
 
@@ -604,6 +910,38 @@ pub trait ScriptTrait: BaseScript + ComponentProvider {
     /// [`crate::engine::executor::Executor::set_desired_update_rate`] method.
     fn on_update(&mut self, #[allow(unused_variables)] ctx: &mut ScriptContext) {}
 
+    /// Defines the order in which the engine calls methods of script instances. Scripts with lower
+    /// values are processed first. Scripts with equal values are processed in the order of their
+    /// nodes in the scene graph and then in the order of their indices in the node. The default
+    /// value is [`execution_order::DEFAULT`], see [`execution_order`] module for predefined groups.
+    ///
+    /// The order is applied to every pass of script processing: initialization, start, update and
+    /// OS events. Scripts, that were created during a pass (for example, a script of a prefab
+    /// instantiated in `on_update`), will be processed after all the other scripts of the pass.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use fyrox_impl::{
+    /// #     core::{reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+    /// #     script::{execution_order, ScriptContext, ScriptTrait},
+    /// # };
+    /// #
+    /// #[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+    /// #[type_uuid(id = "9d3bbae9-46d6-4e4f-8b3c-b5b3b2b6c7a1")]
+    /// struct CameraController;
+    ///
+    /// impl ScriptTrait for CameraController {
+    ///     fn execution_order(&self) -> i32 {
+    ///         // Camera must follow its target after the target has moved.
+    ///         execution_order::CAMERA
+    ///     }
+    /// }
+    /// ```
+    fn execution_order(&self) -> i32 {
+        execution_order::DEFAULT
+    }
+
     /// Allows you to react to certain script messages. It could be used for communication between scripts; to
     /// bypass borrowing issues. If you need to receive messages of a particular type, you must subscribe to a type
     /// explicitly. Usually it is done in [`ScriptTrait::on_start`] method:
@@ -650,6 +988,32 @@ pub trait ScriptTrait: BaseScript + ComponentProvider {
         #[allow(unused_variables)] ctx: &mut ScriptMessageContext,
     ) {
     }
+
+    /// The method is called when the code of the script is about to be unloaded during hot reloading
+    /// of a dynamic plugin. Right after this call the script will be serialized and destroyed, without
+    /// calling [`ScriptTrait::on_deinit`]. It could be used to move runtime state, that cannot be
+    /// serialized, into serializable fields.
+    ///
+    /// # State transfer
+    ///
+    /// When the new version of the code is loaded, a new script instance with default values is
+    /// created and the serialized state of the old instance is transferred into it field-by-field,
+    /// fields are matched by their names. Fields that are missing in the old version or that have
+    /// different type in the new version keep their default values.
+    fn on_before_reload(&mut self) {}
+
+    /// The method is called when the script was re-created from a new version of the code during hot
+    /// reloading of a dynamic plugin and its state was restored (see [`ScriptTrait::on_before_reload`]).
+    /// It could be used to restore runtime state, that cannot be serialized. The method is called
+    /// on the next update of the scene, before [`ScriptTrait::on_start`], which is called for
+    /// reloaded scripts once again.
+    fn on_after_reload(&mut self, #[allow(unused_variables)] ctx: &mut ScriptContext) {}
+
+    /// Returns the script as [`SaveState`], if the script has a state that should be stored in save
+    /// files. See [`crate::save`] module docs for more info.
+    fn as_save_state_mut(&mut self) -> Option<&mut dyn SaveState> {
+        None
+    }
 }
 
 /// A wrapper for actual script instance internals, it used by the engine.
@@ -850,11 +1214,15 @@ mod test {
     use crate::scene::base::ScriptRecord;
     use crate::{
         core::{
-            impl_component_provider, reflect::prelude::*, variable::try_inherit_properties,
-            variable::InheritableVariable, visitor::prelude::*,
+            impl_component_provider, pool::Handle, reflect::prelude::*,
+            variable::try_inherit_properties, variable::InheritableVariable, visitor::prelude::*,
+        },
+        scene::{
+            base::{Base, BaseBuilder},
+            pivot::PivotBuilder,
+            Scene,
         },
-        scene::base::Base,
-        script::{Script, ScriptTrait},
+        script::{MessageFilter, Script, ScriptTrait},
     };
     use fyrox_core::uuid_provider;
 
@@ -934,4 +1302,21 @@ mod test {
             3.21
         );
     }
+
+    #[test]
+    fn test_message_filter() {
+        let mut scene = Scene::new();
+        let enemy = PivotBuilder::new(BaseBuilder::new().with_tag("Enemy".to_string()))
+            .build(&mut scene.graph);
+        let player = PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+
+        assert!(MessageFilter::Any.accepts(Handle::NONE, &scene));
+        assert!(MessageFilter::Node(enemy).accepts(enemy, &scene));
+        assert!(!MessageFilter::Node(enemy).accepts(player, &scene));
+
+        let filter = MessageFilter::Tag("Enemy".to_string());
+        assert!(filter.accepts(enemy, &scene));
+        assert!(!filter.accepts(player, &scene));
+        assert!(!filter.accepts(Handle::NONE, &scene));
+    }
 }
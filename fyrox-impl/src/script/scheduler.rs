@@ -0,0 +1,406 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Scheduler allows scripts to run their code or send messages later: after some time, every N
+//! seconds or frames, or when some condition is met. See [`Scheduler`] docs for more info.
+
+use crate::{
+    core::pool::Handle,
+    scene::{node::Node, Scene},
+    script::{ScriptContext, ScriptMessagePayload, ScriptMessageSender, ScriptTrait},
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{Debug, Formatter},
+};
+
+/// Defines when a scheduled action must be executed.
+pub enum Trigger {
+    /// Executes the action once after the given amount of seconds.
+    After(f32),
+    /// Executes the action every given amount of seconds until cancelled.
+    Every(f32),
+    /// Executes the action once after the given amount of frames.
+    AfterFrames(u32),
+    /// Executes the action every given amount of frames until cancelled.
+    EveryFrames(u32),
+    /// Executes the action once, when the condition becomes `true`. The condition is checked once
+    /// per frame.
+    When(Box<dyn FnMut(&Scene) -> bool>),
+}
+
+impl Debug for Trigger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Trigger::After(time) => write!(f, "After({time})"),
+            Trigger::Every(time) => write!(f, "Every({time})"),
+            Trigger::AfterFrames(frames) => write!(f, "AfterFrames({frames})"),
+            Trigger::EveryFrames(frames) => write!(f, "EveryFrames({frames})"),
+            Trigger::When(_) => write!(f, "When"),
+        }
+    }
+}
+
+impl Trigger {
+    /// Creates a trigger, that fires once the given condition becomes `true`.
+    pub fn when<F>(condition: F) -> Self
+    where
+        F: FnMut(&Scene) -> bool + 'static,
+    {
+        Self::When(Box::new(condition))
+    }
+
+    fn is_repeating(&self) -> bool {
+        matches!(self, Trigger::Every(_) | Trigger::EveryFrames(_))
+    }
+}
+
+/// A handle of a scheduled action, that could be used to cancel it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScheduleHandle(u64);
+
+pub(crate) type ScheduledClosure =
+    Box<dyn for<'a, 'b, 'c> FnMut(&mut dyn ScriptTrait, &mut ScriptContext<'a, 'b, 'c>)>;
+
+pub(crate) enum ScheduledAction {
+    Script {
+        node: Handle<Node>,
+        script_index: usize,
+        closure: ScheduledClosure,
+    },
+    Message(Box<dyn FnMut(&ScriptMessageSender)>),
+}
+
+pub(crate) struct ScheduledEntry {
+    trigger: Trigger,
+    pub(crate) action: ScheduledAction,
+    time_left: f32,
+    frames_left: u32,
+}
+
+impl ScheduledEntry {
+    fn new(trigger: Trigger, action: ScheduledAction) -> Self {
+        let mut entry = Self {
+            trigger,
+            action,
+            time_left: 0.0,
+            frames_left: 0,
+        };
+        entry.reset();
+        entry
+    }
+
+    fn reset(&mut self) {
+        match self.trigger {
+            Trigger::After(time) | Trigger::Every(time) => self.time_left = time,
+            Trigger::AfterFrames(frames) | Trigger::EveryFrames(frames) => {
+                // Zero frames means "on the next frame", there's no way to fire earlier.
+                self.frames_left = frames.max(1)
+            }
+            Trigger::When(_) => (),
+        }
+    }
+
+    fn advance(&mut self, dt: f32, scene: &Scene) -> bool {
+        match self.trigger {
+            Trigger::After(_) | Trigger::Every(_) => {
+                self.time_left -= dt;
+                self.time_left <= 0.0
+            }
+            Trigger::AfterFrames(_) | Trigger::EveryFrames(_) => {
+                self.frames_left = self.frames_left.saturating_sub(1);
+                self.frames_left == 0
+            }
+            Trigger::When(ref mut condition) => condition(scene),
+        }
+    }
+}
+
+/// Scheduler is a per-scene service, that executes actions of scripts later. An action could be
+/// either a closure, that gets access to the script instance and a script context, or a message,
+/// that will be sent to a node. Every action has a [`Trigger`], that defines when the action is
+/// executed:
+///
+/// - one-shot timers - [`Trigger::After`], [`Trigger::AfterFrames`].
+/// - repeating timers - [`Trigger::Every`], [`Trigger::EveryFrames`].
+/// - conditions - [`Trigger::When`].
+///
+/// Every scheduled action has a [`ScheduleHandle`], that could be used to cancel it. Actions of a
+/// script are cancelled automatically when the script is destroyed. Timers use the delta time of
+/// the scene, which means that they're paused while the scene is disabled.
+///
+/// Actions are executed after `on_update` of every script, in the same order as they were
+/// scheduled. Scheduled messages are dispatched on the same frame.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+/// #     script::{scheduler::{ScheduleHandle, Trigger}, ScriptContext, ScriptTrait},
+/// # };
+/// #
+/// #[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+/// #[type_uuid(id = "7fbd3a53-0a7b-4a61-a8f6-1b6b0a86b4bd")]
+/// struct Turret {
+///     ammo: u32,
+///     #[visit(skip)]
+///     #[reflect(hidden)]
+///     shooting: Option<ScheduleHandle>,
+/// }
+///
+/// impl ScriptTrait for Turret {
+///     fn on_start(&mut self, ctx: &mut ScriptContext) {
+///         // Shoot every half of a second, until out of ammo.
+///         self.shooting = Some(ctx.schedule(
+///             Trigger::Every(0.5),
+///             |turret: &mut Turret, ctx: &mut ScriptContext| {
+///                 turret.ammo = turret.ammo.saturating_sub(1);
+///                 if turret.ammo == 0 {
+///                     if let Some(shooting) = turret.shooting.take() {
+///                         ctx.cancel_scheduled(shooting);
+///                     }
+///                     // Reload after 3 seconds.
+///                     ctx.schedule(
+///                         Trigger::After(3.0),
+///                         |turret: &mut Turret, _: &mut ScriptContext| turret.ammo = 10,
+///                     );
+///                 }
+///             },
+///         ));
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct Scheduler {
+    entries: BTreeMap<ScheduleHandle, ScheduledEntry>,
+    next_id: u64,
+    // An entry, that is currently executing. It is taken from the list of entries for execution.
+    running: Option<ScheduleHandle>,
+    running_cancelled: bool,
+}
+
+impl Debug for Scheduler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Scheduler with {} entries", self.entries.len())
+    }
+}
+
+impl Scheduler {
+    fn add(&mut self, entry: ScheduledEntry) -> ScheduleHandle {
+        self.next_id += 1;
+        let handle = ScheduleHandle(self.next_id);
+        self.entries.insert(handle, entry);
+        handle
+    }
+
+    /// Schedules a closure, that will be called with the script of type `S` with the given index
+    /// on the given node. The closure won't be called if the script is destroyed or replaced with
+    /// a script of some other type. Usually, [`ScriptContext::schedule`] is more convenient.
+    pub fn schedule_closure<S, C>(
+        &mut self,
+        node: Handle<Node>,
+        script_index: usize,
+        trigger: Trigger,
+        mut closure: C,
+    ) -> ScheduleHandle
+    where
+        S: ScriptTrait,
+        for<'a, 'b, 'c> C: FnMut(&mut S, &mut ScriptContext<'a, 'b, 'c>) + 'static,
+    {
+        self.add(ScheduledEntry::new(
+            trigger,
+            ScheduledAction::Script {
+                node,
+                script_index,
+                closure: Box::new(move |script, context| {
+                    if let Some(script) = script.as_any_ref_mut().downcast_mut::<S>() {
+                        closure(script, context)
+                    }
+                }),
+            },
+        ))
+    }
+
+    /// Schedules a targeted message, that will be sent to the given node. The payload is cloned
+    /// for repeating triggers.
+    pub fn schedule_message<T>(
+        &mut self,
+        trigger: Trigger,
+        target: Handle<Node>,
+        payload: T,
+    ) -> ScheduleHandle
+    where
+        T: ScriptMessagePayload + Clone,
+    {
+        self.add(ScheduledEntry::new(
+            trigger,
+            ScheduledAction::Message(Box::new(move |sender| {
+                sender.send_to_target(target, payload.clone())
+            })),
+        ))
+    }
+
+    /// Cancels a scheduled action. Returns `true` if the action was pending, `false` - otherwise.
+    /// An action could cancel itself while executing.
+    pub fn cancel(&mut self, handle: ScheduleHandle) -> bool {
+        if self.running == Some(handle) && !self.running_cancelled {
+            self.running_cancelled = true;
+            true
+        } else {
+            self.entries.remove(&handle).is_some()
+        }
+    }
+
+    /// Returns `true` if the action with the given handle is still pending.
+    pub fn is_scheduled(&self, handle: ScheduleHandle) -> bool {
+        self.entries.contains_key(&handle)
+            || (self.running == Some(handle) && !self.running_cancelled)
+    }
+
+    /// Returns the amount of pending actions.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there's no pending actions.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Cancels every pending action.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Cancels every closure of the script with the given index on the given node.
+    pub(crate) fn remove_script_entries(&mut self, handle: Handle<Node>, index: usize) {
+        self.entries.retain(|_, entry| match entry.action {
+            ScheduledAction::Script {
+                node, script_index, ..
+            } => node != handle || script_index != index,
+            ScheduledAction::Message(_) => true,
+        });
+    }
+
+    /// Advances every pending action and extracts the ones, that must be executed now.
+    pub(crate) fn take_due(
+        &mut self,
+        dt: f32,
+        scene: &Scene,
+    ) -> Vec<(ScheduleHandle, ScheduledEntry)> {
+        let due = self
+            .entries
+            .iter_mut()
+            .filter_map(|(handle, entry)| entry.advance(dt, scene).then_some(*handle))
+            .collect::<Vec<_>>();
+        due.into_iter()
+            .filter_map(|handle| self.entries.remove(&handle).map(|entry| (handle, entry)))
+            .collect()
+    }
+
+    /// Marks the entry as executing, so it could be cancelled from its own action.
+    pub(crate) fn begin(&mut self, handle: ScheduleHandle) {
+        self.running = Some(handle);
+        self.running_cancelled = false;
+    }
+
+    /// Puts a repeating entry back, unless it was cancelled during execution.
+    pub(crate) fn end(&mut self, handle: ScheduleHandle, mut entry: ScheduledEntry) {
+        if entry.trigger.is_repeating() && !self.running_cancelled {
+            entry.reset();
+            self.entries.insert(handle, entry);
+        }
+        self.running = None;
+        self.running_cancelled = false;
+    }
+
+    /// Sends the message of the entry, does nothing for script actions, because they need a
+    /// script context.
+    #[cfg(test)]
+    fn send_message(entry: &mut ScheduledEntry, sender: &ScriptMessageSender) {
+        if let ScheduledAction::Message(ref mut send) = entry.action {
+            send(sender)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    fn run(scheduler: &mut Scheduler, dt: f32, scene: &Scene, sender: &ScriptMessageSender) {
+        for (handle, mut entry) in scheduler.take_due(dt, scene) {
+            scheduler.begin(handle);
+            Scheduler::send_message(&mut entry, sender);
+            scheduler.end(handle, entry);
+        }
+    }
+
+    #[test]
+    fn test_scheduler_timers() {
+        let (tx, rx) = channel();
+        let sender = ScriptMessageSender { sender: tx };
+        let scene = Scene::new();
+        let mut scheduler = Scheduler::default();
+
+        let once = scheduler.schedule_message(Trigger::After(1.0), Handle::NONE, 1u32);
+        let every = scheduler.schedule_message(Trigger::EveryFrames(2), Handle::NONE, 2u32);
+        scheduler.schedule_message(Trigger::when(|_| true), Handle::NONE, 3u32);
+
+        run(&mut scheduler, 0.5, &scene, &sender);
+        assert_eq!(rx.try_iter().count(), 1);
+        assert!(scheduler.is_scheduled(once));
+
+        run(&mut scheduler, 0.5, &scene, &sender);
+        assert_eq!(rx.try_iter().count(), 2);
+        assert!(!scheduler.is_scheduled(once));
+        assert!(scheduler.is_scheduled(every));
+
+        run(&mut scheduler, 0.5, &scene, &sender);
+        run(&mut scheduler, 0.5, &scene, &sender);
+        assert_eq!(rx.try_iter().count(), 1);
+
+        assert!(scheduler.cancel(every));
+        assert!(!scheduler.cancel(every));
+        run(&mut scheduler, 0.5, &scene, &sender);
+        run(&mut scheduler, 0.5, &scene, &sender);
+        assert_eq!(rx.try_iter().count(), 0);
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn test_scheduler_self_cancellation() {
+        let (tx, _rx) = channel();
+        let sender = ScriptMessageSender { sender: tx };
+        let scene = Scene::new();
+        let mut scheduler = Scheduler::default();
+
+        let every = scheduler.schedule_message(Trigger::Every(1.0), Handle::NONE, 1u32);
+        for (handle, mut entry) in scheduler.take_due(1.0, &scene) {
+            scheduler.begin(handle);
+            Scheduler::send_message(&mut entry, &sender);
+            assert!(scheduler.cancel(every));
+            scheduler.end(handle, entry);
+        }
+        assert!(!scheduler.is_scheduled(every));
+    }
+}
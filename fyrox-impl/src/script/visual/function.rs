@@ -0,0 +1,242 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Native functions, that could be called from visual scripts. See [`register_function`] docs for
+//! more info.
+
+use crate::{
+    asset::manager::ResourceManager,
+    core::{algebra::Vector3, log::Log, parking_lot::Mutex, pool::Handle},
+    graph::{BaseSceneGraph, SceneGraph},
+    scene::{node::Node, Scene},
+    script::{
+        visual::{VisualMessage, VisualValue},
+        ScriptMessageSender,
+    },
+};
+use fxhash::FxHashMap;
+use lazy_static::lazy_static;
+use std::sync::Arc;
+
+/// A subset of the script context, that is available to native functions.
+pub struct VisualScriptContext<'a> {
+    /// Handle of a node to which the script instance belongs to.
+    pub handle: Handle<Node>,
+    /// A reference to a scene the script instance belongs to.
+    pub scene: &'a mut Scene,
+    /// A reference to resource manager.
+    pub resource_manager: &'a ResourceManager,
+    /// A message sender of the scene.
+    pub message_sender: &'a ScriptMessageSender,
+    /// Amount of time that passed from last frame.
+    pub dt: f32,
+    /// Amount of time (in seconds) that passed from creation of the engine.
+    pub elapsed_time: f32,
+}
+
+/// A native function, that takes a context and arguments and returns either a value or an error
+/// message.
+pub type VisualFunction = Arc<
+    dyn Fn(&mut VisualScriptContext, &[VisualValue]) -> Result<VisualValue, String> + Send + Sync,
+>;
+
+lazy_static! {
+    static ref FUNCTIONS: Mutex<FxHashMap<String, VisualFunction>> =
+        Mutex::new(built_in_functions());
+}
+
+fn arg(args: &[VisualValue], index: usize) -> &VisualValue {
+    args.get(index).unwrap_or(&VisualValue::Nil)
+}
+
+fn node_arg(ctx: &VisualScriptContext, args: &[VisualValue], index: usize) -> Handle<Node> {
+    match arg(args, index) {
+        VisualValue::Node(handle) => *handle,
+        _ => ctx.handle,
+    }
+}
+
+fn node_mut<'a>(
+    ctx: &'a mut VisualScriptContext,
+    handle: Handle<Node>,
+) -> Result<&'a mut Node, String> {
+    ctx.scene
+        .graph
+        .try_get_mut(handle)
+        .ok_or_else(|| format!("{handle} is not a valid node handle"))
+}
+
+fn built_in_functions() -> FxHashMap<String, VisualFunction> {
+    let mut functions = FxHashMap::<String, VisualFunction>::default();
+
+    functions.insert(
+        "log".to_string(),
+        Arc::new(|_, args| {
+            Log::info(arg(args, 0).to_string());
+            Ok(VisualValue::Nil)
+        }),
+    );
+    functions.insert(
+        "vector".to_string(),
+        Arc::new(|_, args| {
+            Ok(VisualValue::Vector(Vector3::new(
+                arg(args, 0).as_number(),
+                arg(args, 1).as_number(),
+                arg(args, 2).as_number(),
+            )))
+        }),
+    );
+    functions.insert(
+        "find".to_string(),
+        Arc::new(|ctx, args| {
+            Ok(ctx
+                .scene
+                .graph
+                .find_by_name_from_root(&arg(args, 0).to_string())
+                .map(|(handle, _)| VisualValue::Node(handle))
+                .unwrap_or_default())
+        }),
+    );
+    functions.insert(
+        "position".to_string(),
+        Arc::new(|ctx, args| {
+            let handle = node_arg(ctx, args, 0);
+            Ok(VisualValue::Vector(
+                **node_mut(ctx, handle)?.local_transform().position(),
+            ))
+        }),
+    );
+    functions.insert(
+        "set_position".to_string(),
+        Arc::new(|ctx, args| {
+            let handle = node_arg(ctx, args, 0);
+            let position = arg(args, 1).as_vector();
+            node_mut(ctx, handle)?
+                .local_transform_mut()
+                .set_position(position);
+            Ok(VisualValue::Nil)
+        }),
+    );
+    functions.insert(
+        "translate".to_string(),
+        Arc::new(|ctx, args| {
+            let handle = node_arg(ctx, args, 0);
+            let offset = arg(args, 1).as_vector();
+            node_mut(ctx, handle)?.local_transform_mut().offset(offset);
+            Ok(VisualValue::Nil)
+        }),
+    );
+    functions.insert(
+        "destroy".to_string(),
+        Arc::new(|ctx, args| {
+            let handle = node_arg(ctx, args, 0);
+            node_mut(ctx, handle)?;
+            ctx.scene.graph.remove_node(handle);
+            Ok(VisualValue::Nil)
+        }),
+    );
+    functions.insert(
+        "send".to_string(),
+        Arc::new(|ctx, args| {
+            let VisualValue::Node(target) = arg(args, 0) else {
+                return Err("The first argument of send must be a node!".to_string());
+            };
+            ctx.message_sender.send_to_target(
+                *target,
+                VisualMessage {
+                    topic: arg(args, 1).to_string(),
+                    value: arg(args, 2).clone(),
+                    sender: ctx.handle,
+                },
+            );
+            Ok(VisualValue::Nil)
+        }),
+    );
+    functions.insert(
+        "publish".to_string(),
+        Arc::new(|ctx, args| {
+            let topic = arg(args, 0).to_string();
+            ctx.message_sender.publish(
+                &VisualMessage::topic(&topic),
+                ctx.handle,
+                VisualMessage {
+                    topic,
+                    value: arg(args, 1).clone(),
+                    sender: ctx.handle,
+                },
+            );
+            Ok(VisualValue::Nil)
+        }),
+    );
+    functions.insert(
+        "delta_time".to_string(),
+        Arc::new(|ctx, _| Ok(VisualValue::Number(ctx.dt))),
+    );
+    functions.insert(
+        "elapsed_time".to_string(),
+        Arc::new(|ctx, _| Ok(VisualValue::Number(ctx.elapsed_time))),
+    );
+
+    functions
+}
+
+/// Registers a native function, that could be called from visual scripts by its name using
+/// [`super::VisualNodeKind::Call`] node. A function with the same name will be replaced. The
+/// engine provides a set of built-in functions: `log(value)`, `vector(x, y, z)`, `find(name)`,
+/// `position(node)`, `set_position(node, vector)`, `translate(node, vector)`, `destroy(node)`,
+/// `send(node, topic, value)`, `publish(topic, value)`, `delta_time()`, `elapsed_time()`. If a
+/// node argument is not a node, the node of the script is used.
+///
+/// Reflection gives access to the fields of objects, but not to their methods, so this registry
+/// is the way to expose game logic written in Rust to designers. Functions registered by a
+/// plugin should be registered in [`crate::plugin::Plugin::register`], so they will be
+/// re-registered when the plugin is hot reloaded.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::script::visual::{function::register_function, VisualValue};
+/// register_function("double", |_ctx, args| {
+///     Ok(VisualValue::Number(
+///         2.0 * args.first().map(|arg| arg.as_number()).unwrap_or_default(),
+///     ))
+/// });
+/// ```
+pub fn register_function<F>(name: &str, func: F)
+where
+    F: Fn(&mut VisualScriptContext, &[VisualValue]) -> Result<VisualValue, String>
+        + Send
+        + Sync
+        + 'static,
+{
+    FUNCTIONS.lock().insert(name.to_string(), Arc::new(func));
+}
+
+/// Returns a function with the given name, if any.
+pub fn function(name: &str) -> Option<VisualFunction> {
+    FUNCTIONS.lock().get(name).cloned()
+}
+
+/// Returns sorted names of all registered functions.
+pub fn function_names() -> Vec<String> {
+    let mut names = FUNCTIONS.lock().keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    names
+}
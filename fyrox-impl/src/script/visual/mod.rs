@@ -0,0 +1,1045 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Visual scripting allows defining behavior of game entities as a graph of nodes, which is useful
+//! for designers who can't write Rust code. See [`VisualScriptGraph`] and [`VisualScript`] docs for
+//! more info.
+
+use crate::{
+    asset::{io::ResourceIo, Resource, ResourceData},
+    core::{
+        algebra::{Vector2, Vector3},
+        io::FileLoadError,
+        log::Log,
+        pool::{Handle, Pool},
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        visitor::prelude::*,
+    },
+    scene::node::Node,
+    script::{
+        visual::function::VisualScriptContext, MessageFilter, ScriptContext, ScriptMessageContext,
+        ScriptMessagePayload, ScriptTrait, Topic,
+    },
+};
+use fxhash::FxHashMap;
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{Display, Formatter},
+    path::Path,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+pub mod function;
+pub mod loader;
+
+/// Maximum amount of flow nodes, that could be executed in response to a single event. It prevents
+/// infinite loops from freezing the game.
+const MAX_STEPS: usize = 10_000;
+
+/// Maximum depth of value nodes evaluation. It prevents infinite recursion on cyclic connections.
+const MAX_DEPTH: usize = 64;
+
+/// An error that may occur during visual script graph loading.
+#[derive(Debug)]
+pub enum VisualScriptGraphError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// An error that may occur due to version incompatibilities.
+    Visit(VisitError),
+}
+
+impl Display for VisualScriptGraphError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VisualScriptGraphError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            VisualScriptGraphError::Visit(v) => {
+                write!(
+                    f,
+                    "An error that may occur due to version incompatibilities. {v:?}"
+                )
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for VisualScriptGraphError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<VisitError> for VisualScriptGraphError {
+    fn from(e: VisitError) -> Self {
+        Self::Visit(e)
+    }
+}
+
+/// A value, that is produced and consumed by visual script nodes.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub enum VisualValue {
+    /// Absence of a value.
+    #[default]
+    Nil,
+    /// Boolean value.
+    Bool(bool),
+    /// Numeric value.
+    Number(f32),
+    /// String value.
+    String(String),
+    /// Vector value.
+    Vector(Vector3<f32>),
+    /// A handle of a scene node.
+    Node(Handle<Node>),
+}
+
+impl Display for VisualValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VisualValue::Nil => write!(f, "nil"),
+            VisualValue::Bool(value) => write!(f, "{value}"),
+            VisualValue::Number(value) => write!(f, "{value}"),
+            VisualValue::String(value) => write!(f, "{value}"),
+            VisualValue::Vector(value) => write!(f, "{} {} {}", value.x, value.y, value.z),
+            VisualValue::Node(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl VisualValue {
+    /// Parses a value from a string. `nil`, `true` and `false` are parsed as is, a number is parsed
+    /// as a number, three numbers separated by spaces are parsed as a vector and everything else
+    /// is treated as a string.
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        match text {
+            "nil" => return Self::Nil,
+            "true" => return Self::Bool(true),
+            "false" => return Self::Bool(false),
+            _ => (),
+        }
+
+        if let Ok(number) = text.parse::<f32>() {
+            return Self::Number(number);
+        }
+
+        let components = text
+            .split_whitespace()
+            .map(|component| component.parse::<f32>())
+            .collect::<Result<Vec<_>, _>>();
+        if let Ok([x, y, z]) = components.as_deref() {
+            return Self::Vector(Vector3::new(*x, *y, *z));
+        }
+
+        Self::String(text.to_string())
+    }
+
+    /// Returns `false` for `nil`, `false`, zero and empty strings, `true` - otherwise.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            VisualValue::Nil => false,
+            VisualValue::Bool(value) => *value,
+            VisualValue::Number(value) => *value != 0.0,
+            VisualValue::String(value) => !value.is_empty(),
+            VisualValue::Vector(_) => true,
+            VisualValue::Node(value) => value.is_some(),
+        }
+    }
+
+    /// Converts the value to a number. Values that cannot be converted produce zero.
+    pub fn as_number(&self) -> f32 {
+        match self {
+            VisualValue::Bool(value) => *value as u8 as f32,
+            VisualValue::Number(value) => *value,
+            VisualValue::String(value) => value.trim().parse().unwrap_or_default(),
+            _ => 0.0,
+        }
+    }
+
+    /// Converts the value to a vector. Numbers are converted to vectors with all components equal
+    /// to the number, values that cannot be converted produce zero vector.
+    pub fn as_vector(&self) -> Vector3<f32> {
+        match self {
+            VisualValue::Vector(value) => *value,
+            VisualValue::Number(value) => Vector3::repeat(*value),
+            _ => Vector3::default(),
+        }
+    }
+
+    fn from_reflect(value: &dyn Reflect) -> Option<Self> {
+        let mut result = None;
+        value.as_any(&mut |any| {
+            result = if let Some(value) = any.downcast_ref::<f32>() {
+                Some(Self::Number(*value))
+            } else if let Some(value) = any.downcast_ref::<f64>() {
+                Some(Self::Number(*value as f32))
+            } else if let Some(value) = any.downcast_ref::<i32>() {
+                Some(Self::Number(*value as f32))
+            } else if let Some(value) = any.downcast_ref::<u32>() {
+                Some(Self::Number(*value as f32))
+            } else if let Some(value) = any.downcast_ref::<bool>() {
+                Some(Self::Bool(*value))
+            } else if let Some(value) = any.downcast_ref::<String>() {
+                Some(Self::String(value.clone()))
+            } else if let Some(value) = any.downcast_ref::<Vector3<f32>>() {
+                Some(Self::Vector(*value))
+            } else {
+                any.downcast_ref::<Handle<Node>>()
+                    .map(|value| Self::Node(*value))
+            }
+        });
+        result
+    }
+
+    fn into_reflect(self) -> Option<Box<dyn Reflect>> {
+        match self {
+            VisualValue::Nil => None,
+            VisualValue::Bool(value) => Some(Box::new(value)),
+            VisualValue::Number(value) => Some(Box::new(value)),
+            VisualValue::String(value) => Some(Box::new(value)),
+            VisualValue::Vector(value) => Some(Box::new(value)),
+            VisualValue::Node(value) => Some(Box::new(value)),
+        }
+    }
+}
+
+/// An operator of [`VisualNodeKind::Binary`] node.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum BinaryOperator {
+    /// Sum of numbers or vectors, concatenation of strings.
+    #[default]
+    Add,
+    /// Difference of numbers or vectors.
+    Subtract,
+    /// Product of numbers, or a vector and a number.
+    Multiply,
+    /// Quotient of numbers, or a vector and a number.
+    Divide,
+    /// Equality of the values.
+    Equal,
+    /// Inequality of the values.
+    NotEqual,
+    /// `true` if the first number is less than the second.
+    Less,
+    /// `true` if the first number is greater than the second.
+    Greater,
+    /// `true` if both values are truthy.
+    And,
+    /// `true` if any of the values is truthy.
+    Or,
+}
+
+impl BinaryOperator {
+    /// Applies the operator to the given values.
+    pub fn apply(self, a: &VisualValue, b: &VisualValue) -> VisualValue {
+        use VisualValue as V;
+        match self {
+            BinaryOperator::Add => match (a, b) {
+                (V::String(a), b) => V::String(format!("{a}{b}")),
+                (V::Vector(_), _) | (_, V::Vector(_)) => V::Vector(a.as_vector() + b.as_vector()),
+                _ => V::Number(a.as_number() + b.as_number()),
+            },
+            BinaryOperator::Subtract => match (a, b) {
+                (V::Vector(_), _) | (_, V::Vector(_)) => V::Vector(a.as_vector() - b.as_vector()),
+                _ => V::Number(a.as_number() - b.as_number()),
+            },
+            BinaryOperator::Multiply => match (a, b) {
+                (V::Vector(a), b) | (b, V::Vector(a)) => V::Vector(a.scale(b.as_number())),
+                _ => V::Number(a.as_number() * b.as_number()),
+            },
+            BinaryOperator::Divide => match (a, b) {
+                (V::Vector(a), b) => V::Vector(a.unscale(b.as_number())),
+                _ => V::Number(a.as_number() / b.as_number()),
+            },
+            BinaryOperator::Equal => V::Bool(a == b),
+            BinaryOperator::NotEqual => V::Bool(a != b),
+            BinaryOperator::Less => V::Bool(a.as_number() < b.as_number()),
+            BinaryOperator::Greater => V::Bool(a.as_number() > b.as_number()),
+            BinaryOperator::And => V::Bool(a.is_truthy() && b.is_truthy()),
+            BinaryOperator::Or => V::Bool(a.is_truthy() || b.is_truthy()),
+        }
+    }
+}
+
+/// Kind of a visual script node. There are three groups of nodes:
+///
+/// - Events - [`Self::OnStart`], [`Self::OnUpdate`], [`Self::OnMessage`]. They start execution
+///   of the graph. Their value is the payload of the event (delta time or message value).
+/// - Flow nodes - [`Self::Call`], [`Self::SetVariable`], [`Self::SetProperty`], [`Self::If`].
+///   The first input of such nodes is an execution input, a flow node is executed right after
+///   the node connected to its execution input. Remaining inputs are arguments.
+/// - Value nodes - [`Self::Constant`], [`Self::GetVariable`], [`Self::GetProperty`],
+///   [`Self::SelfNode`], [`Self::Binary`], [`Self::Not`]. They're evaluated on demand, when some
+///   other node needs their value. [`Self::Call`] node, that is not connected to any flow, is
+///   evaluated on demand as well.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum VisualNodeKind {
+    /// Fired once, when the script starts.
+    #[default]
+    OnStart,
+    /// Fired every frame, the value is delta time.
+    OnUpdate,
+    /// Fired when a [`VisualMessage`] with the given topic is received, the value is message value.
+    OnMessage {
+        /// Topic of the message.
+        topic: String,
+    },
+    /// Calls a native function (see [`function::register_function`]) with the given name. Inputs:
+    /// execution, arguments. The value is the result of the function.
+    Call {
+        /// Name of the function.
+        function: String,
+    },
+    /// Sets a variable of the script instance. Inputs: execution, value.
+    SetVariable {
+        /// Name of the variable.
+        name: String,
+    },
+    /// Sets a property of a node using reflection. Inputs: execution, value, node (the node of the
+    /// script if not connected).
+    SetProperty {
+        /// Path to the property, for example `local_transform.position`.
+        path: String,
+    },
+    /// Continues the execution only if the condition is truthy. Inputs: execution, condition.
+    If,
+    /// A constant value.
+    Constant {
+        /// The value.
+        value: VisualValue,
+    },
+    /// Returns a variable of the script instance.
+    GetVariable {
+        /// Name of the variable.
+        name: String,
+    },
+    /// Returns a property of a node using reflection. Inputs: node (the node of the script if not
+    /// connected).
+    GetProperty {
+        /// Path to the property, for example `local_transform.position`.
+        path: String,
+    },
+    /// Returns the node of the script.
+    SelfNode,
+    /// Applies an operator to two inputs.
+    Binary {
+        /// The operator.
+        operator: BinaryOperator,
+    },
+    /// Logical negation of the input.
+    Not,
+}
+
+impl VisualNodeKind {
+    /// Returns `true` if the node starts execution of the graph.
+    pub fn is_event(&self) -> bool {
+        matches!(
+            self,
+            Self::OnStart | Self::OnUpdate | Self::OnMessage { .. }
+        )
+    }
+
+    /// Returns `true` if the node could be executed as a part of a flow.
+    pub fn is_flow(&self) -> bool {
+        matches!(
+            self,
+            Self::Call { .. } | Self::SetVariable { .. } | Self::SetProperty { .. } | Self::If
+        )
+    }
+
+    /// Returns the amount of inputs the node must have.
+    pub fn min_inputs(&self) -> usize {
+        match self {
+            Self::OnStart
+            | Self::OnUpdate
+            | Self::OnMessage { .. }
+            | Self::Constant { .. }
+            | Self::GetVariable { .. }
+            | Self::SelfNode => 0,
+            Self::Call { .. } | Self::GetProperty { .. } | Self::Not => 1,
+            Self::SetVariable { .. } | Self::If | Self::Binary { .. } => 2,
+            Self::SetProperty { .. } => 3,
+        }
+    }
+
+    /// Returns `true` if the node accepts any amount of additional inputs.
+    pub fn has_variable_inputs(&self) -> bool {
+        matches!(self, Self::Call { .. })
+    }
+
+    /// Returns a human-readable parameter of the node (a topic, a name, etc.), if any.
+    pub fn parameter(&self) -> Option<String> {
+        match self {
+            Self::OnMessage { topic } => Some(topic.clone()),
+            Self::Call { function } => Some(function.clone()),
+            Self::SetVariable { name } | Self::GetVariable { name } => Some(name.clone()),
+            Self::SetProperty { path } | Self::GetProperty { path } => Some(path.clone()),
+            Self::Constant { value } => Some(value.to_string()),
+            Self::Binary { operator } => Some(operator.as_ref().to_string()),
+            Self::OnStart | Self::OnUpdate | Self::If | Self::SelfNode | Self::Not => None,
+        }
+    }
+
+    /// Sets the parameter of the node from its human-readable form. Returns `false` if the node
+    /// has no parameter or the parameter cannot be parsed.
+    pub fn set_parameter(&mut self, parameter: &str) -> bool {
+        match self {
+            Self::OnMessage { topic: value }
+            | Self::Call { function: value }
+            | Self::SetVariable { name: value }
+            | Self::GetVariable { name: value }
+            | Self::SetProperty { path: value }
+            | Self::GetProperty { path: value } => {
+                *value = parameter.trim().to_string();
+                true
+            }
+            Self::Constant { value } => {
+                *value = VisualValue::parse(parameter);
+                true
+            }
+            Self::Binary { operator } => match parameter.trim().parse() {
+                Ok(new_operator) => {
+                    *operator = new_operator;
+                    true
+                }
+                Err(_) => false,
+            },
+            Self::OnStart | Self::OnUpdate | Self::If | Self::SelfNode | Self::Not => false,
+        }
+    }
+
+    /// Returns a human-readable title of the node.
+    pub fn title(&self) -> String {
+        match self.parameter() {
+            Some(parameter) => format!("{} ({parameter})", self.as_ref()),
+            None => self.as_ref().to_string(),
+        }
+    }
+}
+
+/// A node of a visual script graph.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct VisualNode {
+    /// Position of the node in the editor.
+    pub position: Vector2<f32>,
+    /// Kind of the node.
+    pub kind: VisualNodeKind,
+    /// Nodes connected to the inputs of the node. Unconnected inputs have [`Handle::NONE`].
+    pub inputs: Vec<Handle<VisualNode>>,
+}
+
+impl VisualNode {
+    /// Creates a new node of the given kind with all required inputs unconnected.
+    pub fn new(kind: VisualNodeKind) -> Self {
+        Self {
+            position: Default::default(),
+            inputs: vec![Handle::NONE; kind.min_inputs()],
+            kind,
+        }
+    }
+
+    /// Sets a new position of the node.
+    pub fn with_position(mut self, position: Vector2<f32>) -> Self {
+        self.position = position;
+        self
+    }
+}
+
+/// A variable of visual script with its default value. Every script instance has its own copy of
+/// the variable.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct VisualVariable {
+    /// Name of the variable.
+    pub name: String,
+    /// Default value of the variable.
+    pub value: VisualValue,
+}
+
+/// Visual script graph is a resource, that defines behavior as a graph of nodes (see
+/// [`VisualNodeKind`] docs for more info about nodes). It could be created in the editor or from
+/// code and then executed by [`VisualScript`].
+///
+/// ## Example
+///
+/// The following graph moves the node of the script along X axis with constant speed.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     script::visual::{VisualNode, VisualNodeKind, VisualScriptGraph, VisualValue},
+/// # };
+/// let mut graph = VisualScriptGraph::default();
+/// let on_update = graph.add_node(VisualNode::new(VisualNodeKind::OnUpdate));
+/// let velocity = graph.add_node(VisualNode::new(VisualNodeKind::Constant {
+///     value: VisualValue::parse("1 0 0"),
+/// }));
+/// let translate = graph.add_node(VisualNode::new(VisualNodeKind::Call {
+///     function: "translate".to_string(),
+/// }));
+/// graph.connect(on_update, translate, 0);
+/// let this = graph.add_node(VisualNode::new(VisualNodeKind::SelfNode));
+/// graph.connect(this, translate, 1);
+/// graph.connect(velocity, translate, 2);
+/// ```
+#[derive(Clone, Debug, Default, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "960e9d84-374f-41e6-bbde-cf1d6bdc6557")]
+pub struct VisualScriptGraph {
+    /// Nodes of the graph.
+    pub nodes: Pool<VisualNode>,
+    /// Variables of the graph.
+    pub variables: Vec<VisualVariable>,
+}
+
+impl VisualScriptGraph {
+    /// Loads a graph from the given file.
+    pub async fn from_file(
+        path: &Path,
+        io: &dyn ResourceIo,
+    ) -> Result<Self, VisualScriptGraphError> {
+        let bytes = io.load_file(path).await?;
+        let mut visitor = Visitor::load_from_memory(&bytes)?;
+        let mut graph = Self::default();
+        graph.visit("Graph", &mut visitor)?;
+        Ok(graph)
+    }
+
+    /// Adds a new node to the graph.
+    pub fn add_node(&mut self, node: VisualNode) -> Handle<VisualNode> {
+        self.nodes.spawn(node)
+    }
+
+    /// Removes the node from the graph and disconnects it from all other nodes.
+    pub fn remove_node(&mut self, handle: Handle<VisualNode>) -> VisualNode {
+        for node in self.nodes.iter_mut() {
+            for input in node.inputs.iter_mut() {
+                if *input == handle {
+                    *input = Handle::NONE;
+                }
+            }
+        }
+        self.nodes.free(handle)
+    }
+
+    /// Connects the source node to the input with the given index of the destination node. The
+    /// destination node gets more inputs if needed.
+    pub fn connect(
+        &mut self,
+        source: Handle<VisualNode>,
+        dest: Handle<VisualNode>,
+        input_index: usize,
+    ) {
+        if let Some(dest) = self.nodes.try_borrow_mut(dest) {
+            if dest.inputs.len() <= input_index {
+                dest.inputs.resize(input_index + 1, Handle::NONE);
+            }
+            dest.inputs[input_index] = source;
+        }
+    }
+
+    /// Returns an iterator over topics of all [`VisualNodeKind::OnMessage`] nodes of the graph.
+    pub fn topics(&self) -> impl Iterator<Item = &str> {
+        self.nodes.iter().filter_map(|node| match node.kind {
+            VisualNodeKind::OnMessage { ref topic } => Some(topic.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Executes all event nodes that satisfy the given predicate with the given payload.
+    pub fn execute<P>(
+        &self,
+        predicate: P,
+        payload: VisualValue,
+        variables: &mut FxHashMap<String, VisualValue>,
+        ctx: &mut VisualScriptContext,
+    ) -> Result<(), String>
+    where
+        P: Fn(&VisualNodeKind) -> bool,
+    {
+        for variable in self.variables.iter() {
+            if !variables.contains_key(&variable.name) {
+                variables.insert(variable.name.clone(), variable.value.clone());
+            }
+        }
+
+        for (event, node) in self.nodes.pair_iter() {
+            if node.kind.is_event() && predicate(&node.kind) {
+                Interpreter {
+                    graph: self,
+                    variables: &mut *variables,
+                    ctx: &mut *ctx,
+                    values: Default::default(),
+                    steps: 0,
+                }
+                .run(event, payload.clone())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ResourceData for VisualScriptGraph {
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut visitor = Visitor::new();
+        self.visit("Graph", &mut visitor)?;
+        visitor.save_binary(path)?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+/// Type alias for visual script graph resources.
+pub type VisualScriptGraphResource = Resource<VisualScriptGraph>;
+
+struct Interpreter<'a, 'b> {
+    graph: &'a VisualScriptGraph,
+    variables: &'a mut FxHashMap<String, VisualValue>,
+    ctx: &'a mut VisualScriptContext<'b>,
+    // Values of the nodes, that were already executed or evaluated during this run.
+    values: FxHashMap<Handle<VisualNode>, VisualValue>,
+    steps: usize,
+}
+
+impl Interpreter<'_, '_> {
+    fn run(&mut self, event: Handle<VisualNode>, payload: VisualValue) -> Result<(), String> {
+        let graph = self.graph;
+
+        self.values.insert(event, payload);
+        let mut queue = VecDeque::from([event]);
+        while let Some(current) = queue.pop_front() {
+            for (handle, node) in graph.nodes.pair_iter() {
+                if node.kind.is_flow() && node.inputs.first() == Some(&current) {
+                    self.steps += 1;
+                    if self.steps > MAX_STEPS {
+                        return Err("Too many steps, the graph probably has a loop!".to_string());
+                    }
+
+                    if self.execute(handle, node)? {
+                        queue.push_back(handle);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, handle: Handle<VisualNode>, node: &VisualNode) -> Result<bool, String> {
+        let (value, proceed) = match node.kind {
+            VisualNodeKind::Call { ref function } => (self.call(function, node, 0)?, true),
+            VisualNodeKind::SetVariable { ref name } => {
+                let value = self.input(node, 1, 0)?;
+                self.variables.insert(name.clone(), value.clone());
+                (value, true)
+            }
+            VisualNodeKind::SetProperty { ref path } => {
+                let value = self.input(node, 1, 0)?;
+                let target = self.node_input(node, 2, 0)?;
+                self.set_property(target, path, value.clone())?;
+                (value, true)
+            }
+            VisualNodeKind::If => {
+                let condition = self.input(node, 1, 0)?.is_truthy();
+                (VisualValue::Bool(condition), condition)
+            }
+            _ => return Ok(false),
+        };
+
+        self.values.insert(handle, value);
+
+        Ok(proceed)
+    }
+
+    fn input(
+        &mut self,
+        node: &VisualNode,
+        index: usize,
+        depth: usize,
+    ) -> Result<VisualValue, String> {
+        match node.inputs.get(index) {
+            Some(input) if input.is_some() => self.evaluate(*input, depth + 1),
+            _ => Ok(VisualValue::Nil),
+        }
+    }
+
+    fn node_input(
+        &mut self,
+        node: &VisualNode,
+        index: usize,
+        depth: usize,
+    ) -> Result<Handle<Node>, String> {
+        match self.input(node, index, depth)? {
+            VisualValue::Node(handle) => Ok(handle),
+            _ => Ok(self.ctx.handle),
+        }
+    }
+
+    fn call(&mut self, name: &str, node: &VisualNode, depth: usize) -> Result<VisualValue, String> {
+        let func =
+            function::function(name).ok_or_else(|| format!("There's no function {name}!"))?;
+        let args = (1..node.inputs.len())
+            .map(|index| self.input(node, index, depth))
+            .collect::<Result<Vec<_>, _>>()?;
+        func(self.ctx, &args)
+    }
+
+    fn evaluate(
+        &mut self,
+        handle: Handle<VisualNode>,
+        depth: usize,
+    ) -> Result<VisualValue, String> {
+        if depth > MAX_DEPTH {
+            return Err("The graph is too deep or has a cycle!".to_string());
+        }
+
+        if let Some(value) = self.values.get(&handle) {
+            return Ok(value.clone());
+        }
+
+        let graph = self.graph;
+        let node = graph
+            .nodes
+            .try_borrow(handle)
+            .ok_or_else(|| format!("{handle} is not a valid node of the graph!"))?;
+
+        Ok(match node.kind {
+            VisualNodeKind::Constant { ref value } => value.clone(),
+            VisualNodeKind::GetVariable { ref name } => {
+                self.variables.get(name).cloned().unwrap_or_default()
+            }
+            VisualNodeKind::GetProperty { ref path } => {
+                let target = self.node_input(node, 0, depth)?;
+                self.get_property(target, path)?
+            }
+            VisualNodeKind::SelfNode => VisualValue::Node(self.ctx.handle),
+            VisualNodeKind::Binary { operator } => {
+                let a = self.input(node, 0, depth)?;
+                let b = self.input(node, 1, depth)?;
+                operator.apply(&a, &b)
+            }
+            VisualNodeKind::Not => VisualValue::Bool(!self.input(node, 0, depth)?.is_truthy()),
+            VisualNodeKind::Call { ref function } => {
+                // Functions must not be called more than once per run, because they could have
+                // side effects.
+                let value = self.call(function, node, depth)?;
+                self.values.insert(handle, value.clone());
+                value
+            }
+            _ => VisualValue::Nil,
+        })
+    }
+
+    fn get_property(&self, target: Handle<Node>, path: &str) -> Result<VisualValue, String> {
+        let node = self
+            .ctx
+            .scene
+            .graph
+            .try_get(target)
+            .ok_or_else(|| format!("{target} is not a valid node handle!"))?;
+        let mut result = Err(format!("There's no property {path}!"));
+        node.resolve_path(path, &mut |field| {
+            if let Ok(field) = field {
+                result = VisualValue::from_reflect(field)
+                    .ok_or_else(|| format!("Property {path} has unsupported type!"));
+            }
+        });
+        result
+    }
+
+    fn set_property(
+        &mut self,
+        target: Handle<Node>,
+        path: &str,
+        value: VisualValue,
+    ) -> Result<(), String> {
+        let node = self
+            .ctx
+            .scene
+            .graph
+            .try_get_mut(target)
+            .ok_or_else(|| format!("{target} is not a valid node handle!"))?;
+        let value = value
+            .into_reflect()
+            .ok_or_else(|| format!("Nil cannot be assigned to {path}!"))?;
+        let mut result = Ok(());
+        (node as &mut dyn Reflect).set_field_by_path(path, value, &mut |set_result| {
+            if set_result.is_err() {
+                result = Err(format!("Unable to set property {path}!"));
+            }
+        });
+        result
+    }
+}
+
+/// A message, that is sent by visual scripts using `send` and `publish` functions. Visual scripts
+/// handle such messages using [`VisualNodeKind::OnMessage`] nodes. It could be sent from Rust code
+/// too, to communicate with visual scripts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VisualMessage {
+    /// Name of the topic of the message.
+    pub topic: String,
+    /// Value of the message.
+    pub value: VisualValue,
+    /// A node, that sent the message.
+    pub sender: Handle<Node>,
+}
+
+impl VisualMessage {
+    /// Creates a topic, that could be used to publish messages to visual scripts handling the topic
+    /// with the given name.
+    pub fn topic(name: &str) -> Topic<VisualMessage> {
+        Topic::new(name)
+    }
+}
+
+/// A script, that executes a [`VisualScriptGraph`]. Every instance of the script has its own set of
+/// variables, that are initialized with default values defined in the graph. The graph starts
+/// executing when its resource is loaded.
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "99158f4c-e00e-4495-836a-7494bde2b22f")]
+#[visit(optional)]
+pub struct VisualScript {
+    /// The graph to execute.
+    pub graph: Option<VisualScriptGraphResource>,
+    #[reflect(hidden)]
+    variables: FxHashMap<String, VisualValue>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    started: bool,
+}
+
+impl VisualScript {
+    /// Creates new script, that executes the given graph.
+    pub fn new(graph: VisualScriptGraphResource) -> Self {
+        Self {
+            graph: Some(graph),
+            ..Default::default()
+        }
+    }
+
+    /// Returns a value of the variable with the given name.
+    pub fn variable(&self, name: &str) -> Option<&VisualValue> {
+        self.variables.get(name)
+    }
+
+    /// Sets a new value of the variable with the given name.
+    pub fn set_variable(&mut self, name: &str, value: VisualValue) {
+        self.variables.insert(name.to_string(), value);
+    }
+
+    fn execute<P>(&mut self, predicate: P, payload: VisualValue, ctx: &mut VisualScriptContext)
+    where
+        P: Fn(&VisualNodeKind) -> bool,
+    {
+        let Some(graph) = self.graph.as_ref() else {
+            return;
+        };
+        let graph_ref = graph.data_ref();
+        let Some(graph_data) = graph_ref.as_loaded_ref() else {
+            return;
+        };
+        if let Err(err) = graph_data.execute(predicate, payload, &mut self.variables, ctx) {
+            Log::err(format!("Visual script {} failed: {err}", graph.kind()));
+        }
+    }
+
+    // Starts the graph when it is loaded. Returns `true` if the graph is running.
+    fn try_start(&mut self, ctx: &mut ScriptContext) -> bool {
+        if self.started {
+            return true;
+        }
+
+        let topics = {
+            let Some(graph) = self.graph.as_ref() else {
+                return false;
+            };
+            let graph_ref = graph.data_ref();
+            let Some(graph_data) = graph_ref.as_loaded_ref() else {
+                return false;
+            };
+            graph_data
+                .topics()
+                .map(|topic| topic.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        for topic in topics {
+            ctx.message_dispatcher.subscribe_to_topic(
+                &VisualMessage::topic(&topic),
+                ctx.handle,
+                MessageFilter::Any,
+            );
+        }
+
+        self.started = true;
+        self.execute(
+            |kind| matches!(kind, VisualNodeKind::OnStart),
+            VisualValue::Nil,
+            &mut VisualScriptContext {
+                handle: ctx.handle,
+                scene: &mut *ctx.scene,
+                resource_manager: ctx.resource_manager,
+                message_sender: ctx.message_sender,
+                dt: ctx.dt,
+                elapsed_time: ctx.elapsed_time,
+            },
+        );
+
+        true
+    }
+}
+
+impl ScriptTrait for VisualScript {
+    fn on_init(&mut self, ctx: &mut ScriptContext) {
+        ctx.message_dispatcher
+            .subscribe_to::<VisualMessage>(ctx.handle);
+    }
+
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        self.try_start(ctx);
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if self.try_start(ctx) {
+            self.execute(
+                |kind| matches!(kind, VisualNodeKind::OnUpdate),
+                VisualValue::Number(ctx.dt),
+                &mut VisualScriptContext {
+                    handle: ctx.handle,
+                    scene: &mut *ctx.scene,
+                    resource_manager: ctx.resource_manager,
+                    message_sender: ctx.message_sender,
+                    dt: ctx.dt,
+                    elapsed_time: ctx.elapsed_time,
+                },
+            );
+        }
+    }
+
+    fn on_message(
+        &mut self,
+        message: &mut dyn ScriptMessagePayload,
+        ctx: &mut ScriptMessageContext,
+    ) {
+        if !self.started {
+            return;
+        }
+
+        if let Some(message) = message.downcast_ref::<VisualMessage>() {
+            self.execute(
+                |kind| matches!(kind, VisualNodeKind::OnMessage { topic } if *topic == message.topic),
+                message.value.clone(),
+                &mut VisualScriptContext {
+                    handle: ctx.handle,
+                    scene: &mut *ctx.scene,
+                    resource_manager: ctx.resource_manager,
+                    message_sender: ctx.message_sender,
+                    dt: ctx.dt,
+                    elapsed_time: ctx.elapsed_time,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(VisualValue::parse("nil"), VisualValue::Nil);
+        assert_eq!(VisualValue::parse(" true "), VisualValue::Bool(true));
+        assert_eq!(VisualValue::parse("1.5"), VisualValue::Number(1.5));
+        assert_eq!(
+            VisualValue::parse("1 2 3"),
+            VisualValue::Vector(Vector3::new(1.0, 2.0, 3.0))
+        );
+        assert_eq!(
+            VisualValue::parse("hello"),
+            VisualValue::String("hello".to_string())
+        );
+    }
+
+    #[test]
+    fn test_binary_operators() {
+        let two = VisualValue::Number(2.0);
+        let three = VisualValue::Number(3.0);
+        assert_eq!(
+            BinaryOperator::Add.apply(&two, &three),
+            VisualValue::Number(5.0)
+        );
+        assert_eq!(
+            BinaryOperator::Less.apply(&two, &three),
+            VisualValue::Bool(true)
+        );
+        assert_eq!(
+            BinaryOperator::Multiply.apply(&VisualValue::Vector(Vector3::new(1.0, 0.0, 0.0)), &two),
+            VisualValue::Vector(Vector3::new(2.0, 0.0, 0.0))
+        );
+        assert_eq!(
+            BinaryOperator::Add.apply(&VisualValue::String("a".to_string()), &two),
+            VisualValue::String("a2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_remove_node_disconnects_inputs() {
+        let mut graph = VisualScriptGraph::default();
+        let constant = graph.add_node(VisualNode::new(VisualNodeKind::Constant {
+            value: VisualValue::Number(1.0),
+        }));
+        let not = graph.add_node(VisualNode::new(VisualNodeKind::Not));
+        graph.connect(constant, not, 0);
+        assert_eq!(graph.nodes[not].inputs, vec![constant]);
+
+        graph.remove_node(constant);
+        assert_eq!(graph.nodes[not].inputs, vec![Handle::NONE]);
+    }
+
+    #[test]
+    fn test_node_kind_parameter() {
+        let mut kind = VisualNodeKind::Binary {
+            operator: BinaryOperator::Add,
+        };
+        assert!(kind.set_parameter("Less"));
+        assert_eq!(
+            kind,
+            VisualNodeKind::Binary {
+                operator: BinaryOperator::Less
+            }
+        );
+        assert!(!kind.set_parameter("Unknown"));
+        assert_eq!(kind.title(), "Binary (Less)");
+    }
+}
@@ -0,0 +1,356 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! User settings persistence. Games usually have a set of options (graphics, audio, controls),
+//! that should survive restarts of the game. This module allows you to declare such settings as
+//! plain reflected structures, load and save them from/to the platform-specific config directory
+//! and get notified when they change. See [`UserSettings`] docs for more info.
+
+use crate::core::{log::Log, reflect::Reflect};
+use ron::ser::PrettyConfig;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+pub mod standard;
+pub mod ui;
+
+/// Default name of settings files.
+pub const DEFAULT_SETTINGS_FILE_NAME: &str = "settings.ron";
+
+/// An error that may occur during settings loading or saving.
+#[derive(Debug)]
+pub enum SettingsError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// Settings file is malformed.
+    RonSpanned(ron::error::SpannedError),
+    /// Unable to serialize settings.
+    Ron(ron::Error),
+    /// Settings have no file path, it happens when the current platform does not have a config
+    /// directory (for example, WebAssembly).
+    NoPath,
+}
+
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsError::Io(v) => write!(f, "An i/o error has occurred: {v}"),
+            SettingsError::RonSpanned(v) => write!(f, "Unable to parse settings. Reason: {v}"),
+            SettingsError::Ron(v) => write!(f, "Unable to serialize settings. Reason: {v}"),
+            SettingsError::NoPath => write!(f, "Settings do not have a file path."),
+        }
+    }
+}
+
+impl From<std::io::Error> for SettingsError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for SettingsError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::RonSpanned(e)
+    }
+}
+
+impl From<ron::Error> for SettingsError {
+    fn from(e: ron::Error) -> Self {
+        Self::Ron(e)
+    }
+}
+
+/// A trait for structures that could be used as user settings. It is implemented automatically
+/// for every type that satisfies the bounds, so you just need to add the derives:
+///
+/// ```rust
+/// use fyrox_impl::core::reflect::prelude::*;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, Reflect, Clone, Default, Debug)]
+/// #[serde(default)]
+/// struct GameplaySettings {
+///     #[reflect(min_value = 0.5, max_value = 2.0)]
+///     difficulty: f32,
+///     show_hints: bool,
+/// }
+/// ```
+///
+/// `#[serde(default)]` is highly recommended, it allows you to add new fields to settings without
+/// resetting the settings already saved by players.
+pub trait SettingsData: Serialize + DeserializeOwned + Reflect + Clone + Default {}
+
+impl<T> SettingsData for T where T: Serialize + DeserializeOwned + Reflect + Clone + Default {}
+
+/// Defines where settings are stored. Settings are stored in the config directory of the current
+/// platform, for example:
+///
+/// - Windows - `C:\Users\Alice\AppData\Roaming\<organization>\<application>\config\`
+/// - Linux - `/home/alice/.config/<application>/`
+/// - macOS - `/Users/Alice/Library/Application Support/<qualifier>.<organization>.<application>/`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SettingsLocation {
+    /// Reverse domain name notation of the application, excluding the organization or application
+    /// name itself. For example, `com` for `com.example.game`. Could be empty.
+    pub qualifier: String,
+    /// Name of the organization, that develops the game.
+    pub organization: String,
+    /// Name of the game.
+    pub application: String,
+    /// Name of the settings file.
+    pub file_name: String,
+}
+
+impl SettingsLocation {
+    /// Creates new settings location for the given organization and application, with the default
+    /// file name ([`DEFAULT_SETTINGS_FILE_NAME`]).
+    pub fn new(organization: impl Into<String>, application: impl Into<String>) -> Self {
+        Self {
+            qualifier: Default::default(),
+            organization: organization.into(),
+            application: application.into(),
+            file_name: DEFAULT_SETTINGS_FILE_NAME.to_string(),
+        }
+    }
+
+    /// Sets the desired qualifier. See [`Self::qualifier`] docs for more info.
+    pub fn with_qualifier(mut self, qualifier: impl Into<String>) -> Self {
+        self.qualifier = qualifier.into();
+        self
+    }
+
+    /// Sets the desired file name. Use separate files, if you want to store different kinds of
+    /// settings separately.
+    pub fn with_file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = file_name.into();
+        self
+    }
+
+    /// Returns the config directory of the application or `None` if the current platform does
+    /// not have one.
+    pub fn directory(&self) -> Option<PathBuf> {
+        directories::ProjectDirs::from(&self.qualifier, &self.organization, &self.application)
+            .map(|dirs| dirs.config_dir().to_path_buf())
+    }
+
+    /// Returns full path of the settings file or `None` if the current platform does not have a
+    /// config directory.
+    pub fn path(&self) -> Option<PathBuf> {
+        self.directory().map(|dir| dir.join(&self.file_name))
+    }
+}
+
+/// A message, that is sent to every subscriber of [`UserSettings`] when the settings change.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SettingsMessage {
+    /// Settings have changed.
+    Changed,
+}
+
+/// User settings is a wrapper around a settings structure, that tracks changes of the settings,
+/// notifies subscribers about them and saves the settings to disk. Any mutable access to the
+/// settings (via [`DerefMut`]) marks the settings as changed.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox_impl::{
+///     plugin::{Plugin, PluginContext},
+///     settings::{standard::GameSettings, SettingsLocation, UserSettings},
+/// };
+///
+/// fn load_settings() -> UserSettings<GameSettings> {
+///     UserSettings::load(&SettingsLocation::new("Example Studio", "My Game"))
+/// }
+///
+/// fn update(settings: &mut UserSettings<GameSettings>, context: &mut PluginContext) {
+///     if settings.controls.mouse_sensitivity < 0.1 {
+///         // Any modification marks the settings as changed.
+///         settings.controls.mouse_sensitivity = 0.1;
+///     }
+///
+///     // Save the settings (if changed) and apply them to the engine.
+///     if settings.try_save() {
+///         settings.apply(context);
+///     }
+/// }
+/// ```
+pub struct UserSettings<T> {
+    data: T,
+    path: Option<PathBuf>,
+    need_save: bool,
+    subscribers: Vec<Sender<SettingsMessage>>,
+}
+
+impl<T: SettingsData> Default for UserSettings<T> {
+    fn default() -> Self {
+        Self::new(T::default(), None)
+    }
+}
+
+impl<T> Deref for UserSettings<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl<T> DerefMut for UserSettings<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.need_save = true;
+
+        self.subscribers
+            .retain_mut(|subscriber| subscriber.send(SettingsMessage::Changed).is_ok());
+
+        &mut self.data
+    }
+}
+
+impl<T: SettingsData> UserSettings<T> {
+    /// Creates new user settings, that will be saved to the given path.
+    pub fn new(data: T, path: Option<PathBuf>) -> Self {
+        Self {
+            data,
+            path,
+            need_save: false,
+            subscribers: Default::default(),
+        }
+    }
+
+    /// Loads the settings from the given location. Default settings are used, if there's no
+    /// settings file yet or if it is malformed (the error is written to the log).
+    pub fn load(location: &SettingsLocation) -> Self {
+        let path = location.path();
+        let data = match path.as_deref() {
+            Some(path) if path.exists() => Self::load_data(path).unwrap_or_else(|err| {
+                Log::err(format!(
+                    "Unable to load settings from {}. Default settings will be used. Reason: {err}",
+                    path.display()
+                ));
+                T::default()
+            }),
+            _ => T::default(),
+        };
+        Self::new(data, path)
+    }
+
+    /// Loads settings data from the given file.
+    pub fn load_data(path: &Path) -> Result<T, SettingsError> {
+        let file = std::fs::File::open(path)?;
+        Ok(ron::de::from_reader(file)?)
+    }
+
+    /// Returns the path of the settings file.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Sets a new path of the settings file. The settings will be saved to the new path on the
+    /// next save.
+    pub fn set_path(&mut self, path: Option<PathBuf>) {
+        self.path = path;
+        self.need_save = true;
+    }
+
+    /// Returns `true` if the settings were changed since the last save.
+    pub fn need_save(&self) -> bool {
+        self.need_save
+    }
+
+    /// Creates a new receiver of [`SettingsMessage`], that will receive a message every time when
+    /// the settings change.
+    pub fn subscribe(&mut self) -> Receiver<SettingsMessage> {
+        let (tx, rx) = channel();
+        self.subscribers.push(tx);
+        rx
+    }
+
+    /// Resets the settings to their default values.
+    pub fn reset(&mut self) {
+        **self = T::default();
+    }
+
+    /// Saves the settings to disk, regardless of whether they were changed or not.
+    pub fn save(&mut self) -> Result<(), SettingsError> {
+        let path = self.path.as_ref().ok_or(SettingsError::NoPath)?;
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        std::fs::write(
+            path,
+            ron::ser::to_string_pretty(&self.data, PrettyConfig::default())?,
+        )?;
+        self.need_save = false;
+        Ok(())
+    }
+
+    /// Saves the settings, if they were changed. Returns `true` if the settings were changed.
+    pub fn try_save(&mut self) -> bool {
+        if self.need_save {
+            // Do not try to save the settings over and over again if the platform has no config
+            // directory.
+            self.need_save = false;
+            if self.path.is_some() {
+                Log::verify(self.save());
+            }
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::settings::standard::GameSettings;
+
+    #[test]
+    fn test_user_settings_save_load() {
+        let path = std::env::temp_dir()
+            .join("fyrox_settings_test")
+            .join(DEFAULT_SETTINGS_FILE_NAME);
+
+        let mut settings =
+            UserSettings::<GameSettings>::new(Default::default(), Some(path.clone()));
+        let receiver = settings.subscribe();
+        assert!(!settings.need_save());
+
+        settings.audio.master_volume = 0.25;
+        assert!(settings.need_save());
+        assert_eq!(receiver.try_recv(), Ok(SettingsMessage::Changed));
+        assert!(settings.try_save());
+        assert!(!settings.try_save());
+
+        let loaded = UserSettings::<GameSettings>::load_data(&path).unwrap();
+        assert_eq!(loaded.audio.master_volume, 0.25);
+
+        settings.reset();
+        assert_eq!(*settings, GameSettings::default());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,280 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Standard settings, that most of the games have. They could be used as is, or as a part of your
+//! own settings structure. See [`GameSettings`] docs for more info.
+
+use crate::{
+    core::{log::Log, reflect::prelude::*, type_traits::prelude::*},
+    engine::GraphicsContext,
+    gui::key::KeyBinding,
+    keyboard::KeyCode,
+    plugin::PluginContext,
+    renderer::QualitySettings,
+    scene::SceneContainer,
+    window::Fullscreen,
+};
+use serde::{Deserialize, Serialize};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Name of the audio bus, which volume is controlled by [`AudioSettings::music_volume`].
+pub const MUSIC_BUS_NAME: &str = "Music";
+
+/// Name of the audio bus, which volume is controlled by [`AudioSettings::effects_volume`].
+pub const EFFECTS_BUS_NAME: &str = "Effects";
+
+/// A predefined set of renderer quality settings.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "4d3b6a1e-8f27-4c59-b0e4-5a9c2d7f1e63")]
+pub enum QualityPreset {
+    /// See [`QualitySettings::low`].
+    Low,
+    /// See [`QualitySettings::medium`].
+    Medium,
+    /// See [`QualitySettings::high`].
+    #[default]
+    High,
+    /// See [`QualitySettings::ultra`].
+    Ultra,
+}
+
+impl QualityPreset {
+    /// Returns renderer quality settings of the preset.
+    pub fn quality_settings(self) -> QualitySettings {
+        match self {
+            QualityPreset::Low => QualitySettings::low(),
+            QualityPreset::Medium => QualitySettings::medium(),
+            QualityPreset::High => QualitySettings::high(),
+            QualityPreset::Ultra => QualitySettings::ultra(),
+        }
+    }
+}
+
+/// Graphics settings.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "a1f5c3e7-2b94-4d8e-9c61-7e0b3f5d2a48")]
+#[serde(default)]
+pub struct GraphicsSettings {
+    /// Quality of the renderer.
+    pub quality: QualityPreset,
+    /// Defines whether the game is running in borderless fullscreen mode or in a window.
+    pub fullscreen: bool,
+    /// Maximum amount of frames per second. Zero means unlimited frame rate.
+    #[reflect(min_value = 0.0, max_value = 1000.0)]
+    pub max_frame_rate: f32,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            quality: Default::default(),
+            fullscreen: false,
+            max_frame_rate: 0.0,
+        }
+    }
+}
+
+impl GraphicsSettings {
+    /// Applies the settings to the renderer and the main window. Does nothing, if the graphics
+    /// context is not initialized.
+    pub fn apply(&self, graphics_context: &mut GraphicsContext) {
+        let GraphicsContext::Initialized(graphics_context) = graphics_context else {
+            return;
+        };
+
+        let quality = self.quality.quality_settings();
+        if graphics_context.renderer.get_quality_settings() != quality {
+            if let Err(err) = graphics_context.renderer.set_quality_settings(&quality) {
+                Log::err(format!("Unable to apply quality settings: {err:?}"));
+            }
+        }
+
+        let fullscreen = self.fullscreen.then_some(Fullscreen::Borderless(None));
+        if graphics_context.window.fullscreen() != fullscreen {
+            graphics_context.window.set_fullscreen(fullscreen);
+        }
+    }
+
+    /// Returns the frame rate limit in a form suitable for
+    /// [`crate::engine::update_loop::UpdateLoopSettings::max_frame_rate`].
+    pub fn frame_rate_limit(&self) -> Option<f32> {
+        (self.max_frame_rate > 0.0).then_some(self.max_frame_rate)
+    }
+}
+
+/// Audio settings. Volumes are applied to the audio buses of scenes: the master volume is applied
+/// to the primary bus, and the other ones to the buses with [`MUSIC_BUS_NAME`] and
+/// [`EFFECTS_BUS_NAME`] names (if any).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "6c8e2d4f-1a3b-4e7c-8d5f-9b0a2c4e6f81")]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Volume of every sound.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub master_volume: f32,
+    /// Volume of the music.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub music_volume: f32,
+    /// Volume of the sound effects.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub effects_volume: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            effects_volume: 1.0,
+        }
+    }
+}
+
+impl AudioSettings {
+    /// Applies the volumes to every scene in the given container. Scenes that are loaded later
+    /// should be configured by calling this method again.
+    pub fn apply(&self, scenes: &mut SceneContainer) {
+        for scene in scenes.iter_mut() {
+            let mut state = scene.graph.sound_context.state();
+            let bus_graph = state.bus_graph_mut();
+            bus_graph.primary_bus_mut().set_gain(self.master_volume);
+            for bus in bus_graph.buses_iter_mut() {
+                match bus.name() {
+                    MUSIC_BUS_NAME => bus.set_gain(self.music_volume),
+                    EFFECTS_BUS_NAME => bus.set_gain(self.effects_volume),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+/// A key bound to a named game action.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "e2b7a9c4-5d1f-4a6e-b3c8-0f7d9e1a5b26")]
+pub struct ActionBinding {
+    /// Name of the action, for example `jump`.
+    pub action: String,
+    /// A key, that triggers the action.
+    pub key: KeyBinding,
+}
+
+/// Controls settings.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "9f4a1c6e-3b8d-4e2a-a7c5-1d6b0e8f3c94")]
+#[serde(default)]
+pub struct ControlsSettings {
+    /// Mouse sensitivity multiplier.
+    #[reflect(min_value = 0.05, max_value = 10.0, step = 0.05)]
+    pub mouse_sensitivity: f32,
+    /// Inverts vertical axis of the mouse.
+    pub invert_y: bool,
+    /// Key bindings of game actions.
+    pub bindings: Vec<ActionBinding>,
+}
+
+impl Default for ControlsSettings {
+    fn default() -> Self {
+        Self {
+            mouse_sensitivity: 1.0,
+            invert_y: false,
+            bindings: Default::default(),
+        }
+    }
+}
+
+impl ControlsSettings {
+    /// Adds a new binding for the given action. Could be used to define default bindings of a
+    /// game.
+    pub fn with_binding(mut self, action: impl Into<String>, key: KeyCode) -> Self {
+        self.bindings.push(ActionBinding {
+            action: action.into(),
+            key: KeyBinding::Some(key),
+        });
+        self
+    }
+
+    /// Returns the key bound to the given action (if any).
+    pub fn key(&self, action: &str) -> Option<KeyCode> {
+        self.bindings.iter().find_map(|binding| match binding.key {
+            KeyBinding::Some(key) if binding.action == action => Some(key),
+            _ => None,
+        })
+    }
+
+    /// Returns `true` if the given key triggers the given action.
+    pub fn is_action(&self, action: &str, key: KeyCode) -> bool {
+        self.bindings
+            .iter()
+            .any(|binding| binding.action == action && binding.key == key)
+    }
+}
+
+/// A typical set of game settings, which could be used with [`super::UserSettings`] directly.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "2c5e8b1d-7f3a-4d9c-b6e2-8a1f4c7d0e35")]
+#[serde(default)]
+pub struct GameSettings {
+    /// Graphics settings.
+    pub graphics: GraphicsSettings,
+    /// Audio settings.
+    pub audio: AudioSettings,
+    /// Controls settings.
+    pub controls: ControlsSettings,
+}
+
+impl GameSettings {
+    /// Applies the graphics and the audio settings to the engine.
+    pub fn apply(&self, context: &mut PluginContext) {
+        self.graphics.apply(context.graphics_context);
+        context.update_loop.max_frame_rate = self.graphics.frame_rate_limit();
+        self.audio.apply(context.scenes);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_controls_settings() {
+        let controls = ControlsSettings::default()
+            .with_binding("jump", KeyCode::Space)
+            .with_binding("fire", KeyCode::KeyF);
+        assert_eq!(controls.key("jump"), Some(KeyCode::Space));
+        assert_eq!(controls.key("crouch"), None);
+        assert!(controls.is_action("fire", KeyCode::KeyF));
+        assert!(!controls.is_action("fire", KeyCode::Space));
+    }
+}
@@ -0,0 +1,254 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Auto-generated settings user interface. See [`SettingsUi`] docs for more info.
+
+use crate::{
+    core::{log::Log, pool::Handle},
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
+        inspector::{
+            editors::{
+                collection::VecCollectionPropertyEditorDefinition,
+                enumeration::EnumPropertyEditorDefinition,
+                inspectable::InspectablePropertyEditorDefinition,
+                PropertyEditorDefinitionContainer,
+            },
+            InspectorBuilder, InspectorContext, InspectorMessage, PropertyAction,
+        },
+        message::{MessageDirection, UiMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        stack_panel::StackPanelBuilder,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+    },
+    settings::{
+        standard::{
+            ActionBinding, AudioSettings, ControlsSettings, GameSettings, GraphicsSettings,
+            QualityPreset,
+        },
+        SettingsData, UserSettings,
+    },
+};
+use std::sync::Arc;
+
+/// Creates a container of property editors, that is able to edit every type from the
+/// [`super::standard`] module. Custom settings types should be registered in this container as
+/// well, for example using [`InspectablePropertyEditorDefinition`] for structures and
+/// [`EnumPropertyEditorDefinition`] for enumerations.
+pub fn make_settings_property_editors() -> PropertyEditorDefinitionContainer {
+    let container = PropertyEditorDefinitionContainer::with_default_editors();
+    container.insert(InspectablePropertyEditorDefinition::<GameSettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<GraphicsSettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<AudioSettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<ControlsSettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<ActionBinding>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<ActionBinding>::new());
+    container.insert(EnumPropertyEditorDefinition::<QualityPreset>::new());
+    container
+}
+
+/// Settings UI is a window, that shows an editor of every field of a settings structure. The
+/// editors are generated automatically using reflection, so the window is a good starting point
+/// for the settings menu of a game. Any change made in the window is applied immediately (and
+/// notifies subscribers of the settings), the settings are saved when the `Save` button is
+/// clicked.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox_impl::{
+///     gui::{message::UiMessage, UserInterface},
+///     settings::{
+///         standard::GameSettings,
+///         ui::{make_settings_property_editors, SettingsUi},
+///         UserSettings,
+///     },
+/// };
+///
+/// struct Menu {
+///     settings_ui: SettingsUi,
+///     settings: UserSettings<GameSettings>,
+/// }
+///
+/// impl Menu {
+///     fn new(ui: &mut UserInterface, settings: UserSettings<GameSettings>) -> Self {
+///         let settings_ui = SettingsUi::new(
+///             &mut ui.build_ctx(),
+///             make_settings_property_editors(),
+///         );
+///         settings_ui.open(ui, &*settings);
+///         Self {
+///             settings_ui,
+///             settings,
+///         }
+///     }
+///
+///     fn on_ui_message(&mut self, message: &UiMessage, ui: &mut UserInterface) {
+///         self.settings_ui
+///             .handle_ui_message(message, ui, &mut self.settings);
+///     }
+/// }
+/// ```
+pub struct SettingsUi {
+    /// A handle of the settings window.
+    pub window: Handle<UiNode>,
+    inspector: Handle<UiNode>,
+    save: Handle<UiNode>,
+    reset: Handle<UiNode>,
+    close: Handle<UiNode>,
+    definitions: Arc<PropertyEditorDefinitionContainer>,
+}
+
+impl SettingsUi {
+    /// Creates a new (closed) settings window, that uses the given property editors. See
+    /// [`make_settings_property_editors`].
+    pub fn new(ctx: &mut BuildContext, definitions: PropertyEditorDefinitionContainer) -> Self {
+        let inspector = InspectorBuilder::new(WidgetBuilder::new()).build(ctx);
+        let make_button = |ctx: &mut BuildContext, text: &str| {
+            ButtonBuilder::new(
+                WidgetBuilder::new()
+                    .with_width(100.0)
+                    .with_height(24.0)
+                    .with_margin(Thickness::uniform(1.0)),
+            )
+            .with_text(text)
+            .build(ctx)
+        };
+        let save = make_button(ctx, "Save");
+        let reset = make_button(ctx, "Reset");
+        let close = make_button(ctx, "Close");
+        let window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(400.0)
+                .with_height(500.0)
+                .with_name("SettingsWindow"),
+        )
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(
+                        ScrollViewerBuilder::new(WidgetBuilder::new().on_row(0))
+                            .with_content(inspector)
+                            .build(ctx),
+                    )
+                    .with_child(
+                        StackPanelBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(1)
+                                .with_horizontal_alignment(HorizontalAlignment::Right)
+                                .with_child(save)
+                                .with_child(reset)
+                                .with_child(close),
+                        )
+                        .with_orientation(Orientation::Horizontal)
+                        .build(ctx),
+                    ),
+            )
+            .add_row(Row::stretch())
+            .add_row(Row::auto())
+            .add_column(Column::stretch())
+            .build(ctx),
+        )
+        .open(false)
+        .with_title(WindowTitle::text("Settings"))
+        .build(ctx);
+
+        Self {
+            window,
+            inspector,
+            save,
+            reset,
+            close,
+            definitions: Arc::new(definitions),
+        }
+    }
+
+    /// Opens the window and fills it with the editors of the given settings.
+    pub fn open<T: SettingsData>(&self, ui: &mut UserInterface, settings: &T) {
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+            true,
+        ));
+        self.sync_to_model(ui, settings);
+    }
+
+    /// Closes the window.
+    pub fn close(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    /// Re-creates the editors of the window, so they show the current values of the given
+    /// settings.
+    pub fn sync_to_model<T: SettingsData>(&self, ui: &mut UserInterface, settings: &T) {
+        let context = InspectorContext::from_object(
+            settings,
+            &mut ui.build_ctx(),
+            self.definitions.clone(),
+            None,
+            0,
+            0,
+            true,
+            Default::default(),
+            150.0,
+        );
+        ui.send_message(InspectorMessage::context(
+            self.inspector,
+            MessageDirection::ToWidget,
+            context,
+        ));
+    }
+
+    /// Handles the messages of the window, modifies and saves the settings.
+    pub fn handle_ui_message<T: SettingsData>(
+        &self,
+        message: &UiMessage,
+        ui: &mut UserInterface,
+        settings: &mut UserSettings<T>,
+    ) {
+        if let Some(InspectorMessage::PropertyChanged(property_changed)) = message.data() {
+            if message.destination() == self.inspector {
+                PropertyAction::from_field_kind(&property_changed.value).apply(
+                    &property_changed.path(),
+                    &mut **settings,
+                    &mut Log::verify,
+                );
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.save {
+                if settings.path().is_some() {
+                    Log::verify(settings.save());
+                }
+            } else if message.destination() == self.reset {
+                settings.reset();
+                self.sync_to_model(ui, &**settings);
+            } else if message.destination() == self.close {
+                self.close(ui);
+            }
+        }
+    }
+}
@@ -0,0 +1,430 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Headless asset baking. It prepares project assets without launching the editor, so it could be
+//! used in CI build pipelines. See [`bake`] docs for more info.
+
+use crate::{
+    asset::{
+        collect_used_resources,
+        manager::ResourceManager,
+        pack::{PackCompression, PackError, PackKey, ResourcePackBuilder},
+        state::ResourceState,
+        untyped::UntypedResource,
+    },
+    core::{futures::future::join_all, io::FileLoadError, log::Log, visitor::Visitor},
+    engine::SerializationContext,
+    resource::texture::Texture,
+    scene::SceneLoader,
+    utils::lightmap::{CancellationToken, Lightmap, LightmapInputData, ProgressIndicator},
+};
+use fxhash::FxHashSet;
+use std::{
+    ffi::OsStr,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Lightmap baking options.
+#[derive(Clone, Debug)]
+pub struct LightmapBakeOptions {
+    /// Paths of the scenes, that should have their lightmaps baked. Lightmap textures of a scene
+    /// are saved in `<scene_name>_lightmap` folder next to the scene.
+    pub scenes: Vec<PathBuf>,
+    /// Resolution of lightmaps. See [`Lightmap::new`] for more info.
+    pub texels_per_unit: u32,
+    /// Spacing between UV patches. See [`Lightmap::new`] for more info.
+    pub spacing: f32,
+}
+
+impl Default for LightmapBakeOptions {
+    fn default() -> Self {
+        Self {
+            scenes: Default::default(),
+            texels_per_unit: 64,
+            spacing: 0.005,
+        }
+    }
+}
+
+/// Asset pack generation options.
+#[derive(Clone, Debug)]
+pub struct PackBakeOptions {
+    /// Path of the asset pack.
+    pub path: PathBuf,
+    /// Compression of the asset pack.
+    pub compression: PackCompression,
+    /// Optional encryption key of the asset pack.
+    pub key: Option<PackKey>,
+}
+
+/// A set of options for [`bake`].
+#[derive(Clone, Debug)]
+pub struct BakeOptions {
+    /// Root folder of the assets. Default is `./`.
+    pub root: PathBuf,
+    /// Extensions of the files, that will be ignored.
+    pub ignored_extensions: Vec<String>,
+    /// If `true`, missing resource metadata files (with stable resource identifiers) will be
+    /// created.
+    pub update_metadata: bool,
+    /// If `true`, every imported resource will be checked for references to the resources, that
+    /// cannot be loaded.
+    pub validate_references: bool,
+    /// Optional lightmap baking options.
+    pub lightmaps: Option<LightmapBakeOptions>,
+    /// Optional asset pack generation options.
+    pub pack: Option<PackBakeOptions>,
+}
+
+impl Default for BakeOptions {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::from("./"),
+            ignored_extensions: vec!["log".to_string()],
+            update_metadata: true,
+            validate_references: true,
+            lightmaps: None,
+            pack: None,
+        }
+    }
+}
+
+impl BakeOptions {
+    fn is_ignored(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|ext| {
+            self.ignored_extensions
+                .iter()
+                .any(|ignored| ext == OsStr::new(ignored))
+        })
+    }
+}
+
+/// Results of asset baking.
+#[derive(Clone, Debug, Default)]
+pub struct BakeReport {
+    /// Paths of the successfully imported resources.
+    pub imported: Vec<PathBuf>,
+    /// Paths of the resources, that failed to import, with the respective error messages.
+    pub import_errors: Vec<(PathBuf, String)>,
+    /// Pairs of a resource path and a path of the resource it references, but which cannot be
+    /// loaded.
+    pub broken_references: Vec<(PathBuf, PathBuf)>,
+    /// Paths of the resources, that have their imported data written to the asset pack instead of
+    /// their source files.
+    pub baked: Vec<PathBuf>,
+    /// Paths of the scenes, that have their lightmaps baked.
+    pub baked_lightmaps: Vec<PathBuf>,
+    /// Paths of the scenes, that failed to bake their lightmaps, with the respective error
+    /// messages.
+    pub lightmap_errors: Vec<(PathBuf, String)>,
+    /// Path of the generated asset pack, if any.
+    pub pack: Option<PathBuf>,
+}
+
+impl BakeReport {
+    /// Returns `true` if there were no errors during baking.
+    pub fn is_ok(&self) -> bool {
+        self.import_errors.is_empty()
+            && self.broken_references.is_empty()
+            && self.lightmap_errors.is_empty()
+    }
+}
+
+impl Display for BakeReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Imported resources: {}", self.imported.len())?;
+        for (path, error) in self.import_errors.iter() {
+            writeln!(f, "Unable to import {}: {}", path.display(), error)?;
+        }
+        for (path, reference) in self.broken_references.iter() {
+            writeln!(
+                f,
+                "Resource {} has a broken reference to {}",
+                path.display(),
+                reference.display()
+            )?;
+        }
+        if !self.baked.is_empty() {
+            writeln!(f, "Packed imported resources: {}", self.baked.len())?;
+        }
+        for path in self.baked_lightmaps.iter() {
+            writeln!(f, "Lightmap of {} was baked", path.display())?;
+        }
+        for (path, error) in self.lightmap_errors.iter() {
+            writeln!(
+                f,
+                "Unable to bake lightmap of {}: {}",
+                path.display(),
+                error
+            )?;
+        }
+        if let Some(pack) = self.pack.as_ref() {
+            writeln!(f, "Assets were packed to {}", pack.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// An error, that may occur during asset baking.
+#[derive(Debug)]
+pub enum BakeError {
+    /// Unable to read the assets.
+    Io(FileLoadError),
+    /// Unable to generate an asset pack.
+    Pack(PackError),
+}
+
+impl Display for BakeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BakeError::Io(err) => write!(f, "Unable to read assets: {err:?}"),
+            BakeError::Pack(err) => write!(f, "Unable to generate asset pack: {err}"),
+        }
+    }
+}
+
+impl From<FileLoadError> for BakeError {
+    fn from(err: FileLoadError) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<PackError> for BakeError {
+    fn from(err: PackError) -> Self {
+        Self::Pack(err)
+    }
+}
+
+fn is_supported(path: &Path, resource_manager: &ResourceManager) -> bool {
+    path.extension().is_some_and(|ext| {
+        resource_manager
+            .state()
+            .loaders
+            .iter()
+            .any(|loader| loader.supports_extension(&ext.to_string_lossy()))
+    })
+}
+
+/// Extensions of the texture containers, that already store the data in the form, that is ready
+/// for GPU, so there is no need to store the imported data of such textures.
+const GPU_TEXTURE_EXTENSIONS: [&str; 3] = ["dds", "ktx2", "basis"];
+
+/// Returns the imported data of the given resource in the form, that could be loaded without
+/// importing it again, or `None` if the source file of the resource should be packed instead.
+/// Textures are stored in KTX2 containers with their generated mip levels and compressed pixels.
+/// Other resources have no such form, so their sources are packed as is.
+fn imported_data(path: &Path, resource: &UntypedResource) -> Option<Vec<u8>> {
+    let is_gpu_texture = path.extension().is_some_and(|ext| {
+        GPU_TEXTURE_EXTENSIONS
+            .iter()
+            .any(|gpu_ext| ext.eq_ignore_ascii_case(gpu_ext))
+    });
+    if is_gpu_texture {
+        return None;
+    }
+    let texture = resource.try_cast::<Texture>()?;
+    let texture = texture.data_ref();
+    texture.as_loaded_ref()?.to_ktx2().ok()
+}
+
+async fn bake_lightmap(
+    scene_path: &Path,
+    options: &LightmapBakeOptions,
+    resource_manager: &ResourceManager,
+    serialization_context: Arc<SerializationContext>,
+) -> Result<(), String> {
+    let io = resource_manager.resource_io();
+    let (loader, _) = SceneLoader::from_file(
+        scene_path,
+        io.as_ref(),
+        serialization_context,
+        resource_manager.clone(),
+    )
+    .await
+    .map_err(|err| err.to_string())?;
+    let mut scene = loader.finish().await;
+
+    let input_data = LightmapInputData::from_scene(
+        &scene,
+        |_, _| true,
+        CancellationToken::new(),
+        ProgressIndicator::new(),
+    )
+    .map_err(|err| err.to_string())?;
+    let lightmap = Lightmap::new(
+        input_data,
+        options.texels_per_unit,
+        options.spacing,
+        CancellationToken::new(),
+        ProgressIndicator::new(),
+    )
+    .map_err(|err| err.to_string())?;
+
+    let stem = scene_path.file_stem().unwrap_or_default().to_string_lossy();
+    let textures_path = scene_path.with_file_name(format!("{stem}_lightmap"));
+    lightmap
+        .save_textures(textures_path, resource_manager.clone())
+        .map_err(|err| err.to_string())?;
+    scene
+        .graph
+        .set_lightmap(lightmap)
+        .map_err(|err| err.to_string())?;
+
+    let mut visitor = Visitor::new();
+    scene
+        .save("Scene", &mut visitor)
+        .map_err(|err| err.to_string())?;
+    visitor
+        .save_binary(scene_path)
+        .map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+/// Bakes project assets without the editor. It performs the following steps (each step, except
+/// import, is optional and is defined by the respective field of [`BakeOptions`]):
+///
+/// 1) Creates missing resource metadata files (see [`crate::asset::registry`] module docs).
+/// 2) Bakes lightmaps of the specified scenes and saves them back.
+/// 3) Imports every resource in the root folder, using its import options. This step ensures that
+///    every resource can be loaded.
+/// 4) Checks references of every imported resource and reports the ones that cannot be loaded.
+/// 5) Packs the assets to an asset pack (see [`crate::asset::pack`] module docs). Imported textures
+///    are packed instead of their source files, so they are loaded from the pack without
+///    compression and mip-map generation (see [`crate::resource::texture::Texture::to_ktx2`]).
+///    Import options of the textures are still applied to sampling parameters.
+///
+/// The resource manager must have all the loaders registered (see
+/// [`crate::engine::initialize_resource_manager_loaders`]), and serialization context must contain
+/// all the scripts and nodes used by the game, otherwise scenes cannot be loaded correctly. Errors
+/// of separate resources do not stop baking, they are collected in the returned report instead.
+pub async fn bake(
+    resource_manager: &ResourceManager,
+    serialization_context: Arc<SerializationContext>,
+    options: &BakeOptions,
+) -> Result<BakeReport, BakeError> {
+    let mut report = BakeReport::default();
+
+    if options.update_metadata {
+        let count = resource_manager
+            .scan_resource_metadata(&options.root, true)
+            .await?;
+        Log::info(format!("{count} resources were registered."));
+    }
+
+    if let Some(lightmaps) = options.lightmaps.as_ref() {
+        for scene_path in lightmaps.scenes.iter() {
+            Log::info(format!("Baking lightmap of {}...", scene_path.display()));
+
+            match bake_lightmap(
+                scene_path,
+                lightmaps,
+                resource_manager,
+                serialization_context.clone(),
+            )
+            .await
+            {
+                Ok(_) => report.baked_lightmaps.push(scene_path.clone()),
+                Err(err) => report.lightmap_errors.push((scene_path.clone(), err)),
+            }
+        }
+    }
+
+    let io = resource_manager.resource_io();
+    let paths = io
+        .walk_directory(&options.root)
+        .await?
+        .filter(|path| !options.is_ignored(path) && is_supported(path, resource_manager))
+        .collect::<Vec<_>>();
+
+    Log::info(format!("Importing {} resources...", paths.len()));
+
+    let results = join_all(
+        paths
+            .iter()
+            .map(|path| resource_manager.request_untyped(path)),
+    )
+    .await;
+
+    let mut resources = Vec::new();
+    for (path, result) in paths.into_iter().zip(results) {
+        match result {
+            Ok(resource) => {
+                report.imported.push(path.clone());
+                resources.push((path, resource));
+            }
+            Err(err) => report.import_errors.push((path, format!("{err:?}"))),
+        }
+    }
+
+    if options.validate_references {
+        for (path, resource) in resources.iter() {
+            let mut used_resources = FxHashSet::default();
+            if let ResourceState::Ok(ref data) = resource.0.lock().state {
+                (**data).as_reflect(&mut |reflect| {
+                    collect_used_resources(reflect, &mut used_resources);
+                });
+            }
+
+            for used_resource in used_resources {
+                let Some(used_path) = used_resource.kind().into_path() else {
+                    continue;
+                };
+                if used_resource.await.is_err() {
+                    report.broken_references.push((path.clone(), used_path));
+                }
+            }
+        }
+    }
+
+    if let Some(pack) = options.pack.as_ref() {
+        Log::info(format!("Packing assets to {}...", pack.path.display()));
+
+        let imported = resources
+            .iter()
+            .filter_map(|(path, resource)| Some((path, imported_data(path, resource)?)))
+            .collect::<Vec<_>>();
+        let imported_paths = imported
+            .iter()
+            .filter_map(|(path, _)| path.canonicalize().ok())
+            .collect::<FxHashSet<_>>();
+
+        let mut builder = ResourcePackBuilder::new()
+            .with_compression(pack.compression)
+            .with_key(pack.key);
+        builder.add_directory(&options.root, |path| {
+            let canonical = path.canonicalize().ok();
+            !options.is_ignored(path)
+                && canonical != pack.path.canonicalize().ok()
+                && !canonical.is_some_and(|canonical| imported_paths.contains(&canonical))
+        })?;
+        for (path, data) in imported {
+            builder.add_file(path, data);
+            report.baked.push(path.clone());
+        }
+        builder.save(&pack.path)?;
+
+        report.pack = Some(pack.path.clone());
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,108 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Blackboard-driven conditions. A condition compares a value of a blackboard entry with some
+//! constant value, it is used by condition nodes and guard decorators of behavior trees.
+
+use crate::{
+    core::{reflect::prelude::*, visitor::prelude::*},
+    script::blackboard::{Blackboard, BlackboardValue},
+};
+use std::cmp::Ordering;
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Comparison operation of a [`BlackboardCondition`].
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum ComparisonOp {
+    /// The entry exists (the value is ignored).
+    Exists,
+    /// The entry is equal to the value.
+    #[default]
+    Equal,
+    /// The entry is not equal to the value (or does not exist).
+    NotEqual,
+    /// The entry is less than the value. Works only with numbers.
+    Less,
+    /// The entry is less than or equal to the value. Works only with numbers.
+    LessOrEqual,
+    /// The entry is greater than the value. Works only with numbers.
+    Greater,
+    /// The entry is greater than or equal to the value. Works only with numbers.
+    GreaterOrEqual,
+}
+
+/// A condition, that compares a value of a blackboard entry with a constant value.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct BlackboardCondition {
+    /// Name of the blackboard entry.
+    pub name: String,
+    /// Comparison operation.
+    pub op: ComparisonOp,
+    /// A value to compare the entry with.
+    pub value: BlackboardValue,
+}
+
+fn as_number(value: &BlackboardValue) -> Option<f64> {
+    match value {
+        BlackboardValue::Integer(value) => Some(*value as f64),
+        BlackboardValue::Number(value) => Some(*value as f64),
+        _ => None,
+    }
+}
+
+impl BlackboardCondition {
+    /// Creates a new condition.
+    pub fn new(name: impl Into<String>, op: ComparisonOp, value: BlackboardValue) -> Self {
+        Self {
+            name: name.into(),
+            op,
+            value,
+        }
+    }
+
+    /// Evaluates the condition using the given blackboard. Integers and numbers are compared by
+    /// their numeric value.
+    pub fn evaluate(&self, blackboard: &Blackboard) -> bool {
+        let Some(entry) = blackboard.value(&self.name) else {
+            return self.op == ComparisonOp::NotEqual;
+        };
+
+        let ordering = match (as_number(entry), as_number(&self.value)) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => (entry == &self.value).then_some(Ordering::Equal),
+        };
+
+        match self.op {
+            ComparisonOp::Exists => true,
+            ComparisonOp::Equal => ordering == Some(Ordering::Equal),
+            ComparisonOp::NotEqual => ordering != Some(Ordering::Equal),
+            ComparisonOp::Less => ordering == Some(Ordering::Less),
+            ComparisonOp::LessOrEqual => {
+                matches!(ordering, Some(Ordering::Less | Ordering::Equal))
+            }
+            ComparisonOp::Greater => ordering == Some(Ordering::Greater),
+            ComparisonOp::GreaterOrEqual => {
+                matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+            }
+        }
+    }
+}
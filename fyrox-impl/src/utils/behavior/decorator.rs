@@ -0,0 +1,79 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Decorator is a node with a single child, that modifies the status of its child or decides whether
+//! the child should be executed at all. See [`DecoratorKind`] for the list of available decorators.
+
+use crate::{
+    core::{pool::Handle, visitor::prelude::*},
+    utils::behavior::{condition::BlackboardCondition, BehaviorNode, BehaviorTree},
+};
+
+/// Defines exact behavior of the decorator node.
+#[derive(Debug, PartialEq, Visit, Clone, Default)]
+pub enum DecoratorKind {
+    /// Returns [`super::Status::Success`] when the child is finished, regardless of its result.
+    #[default]
+    Succeeder,
+    /// Returns [`super::Status::Failure`] when the child is finished, regardless of its result.
+    Failer,
+    /// Executes the child only if the condition holds, otherwise returns
+    /// [`super::Status::Failure`].
+    Guard(BlackboardCondition),
+}
+
+/// See module docs.
+#[derive(Debug, PartialEq, Visit, Clone)]
+pub struct Decorator<B>
+where
+    B: Clone,
+{
+    /// A handle of child node.
+    pub child: Handle<BehaviorNode<B>>,
+    /// Current kind of the node.
+    pub kind: DecoratorKind,
+}
+
+impl<B> Default for Decorator<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        Self {
+            child: Default::default(),
+            kind: Default::default(),
+        }
+    }
+}
+
+impl<B> Decorator<B>
+where
+    B: Clone + 'static,
+{
+    /// Creates new decorator node of given kind.
+    pub fn new(kind: DecoratorKind, child: Handle<BehaviorNode<B>>) -> Self {
+        Self { child, kind }
+    }
+
+    /// Adds self to given behavior tree and returns handle to self.
+    pub fn add_to(self, tree: &mut BehaviorTree<B>) -> Handle<BehaviorNode<B>> {
+        tree.add_node(BehaviorNode::Decorator(self))
+    }
+}
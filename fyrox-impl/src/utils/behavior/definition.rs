@@ -0,0 +1,380 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Behavior tree assets. A tree could be defined in a file (`*.btree`) and then instantiated with
+//! game-specific actions. See [`BehaviorTreeDefinition`] docs for more info.
+
+use crate::{
+    asset::{io::ResourceIo, Resource, ResourceData},
+    core::{
+        io::FileLoadError, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
+        visitor::prelude::*,
+    },
+    utils::behavior::{
+        composite::CompositeNode,
+        condition::BlackboardCondition,
+        decorator::{Decorator, DecoratorKind},
+        inverter::Inverter,
+        leaf::LeafNode,
+        BehaviorNode, BehaviorTree,
+    },
+};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    path::Path,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// An error that may occur during behavior tree definition loading.
+#[derive(Debug)]
+pub enum BehaviorTreeDefinitionError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// An error that may occur due to version incompatibilities.
+    Visit(VisitError),
+}
+
+impl Display for BehaviorTreeDefinitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BehaviorTreeDefinitionError::Io(v) => {
+                write!(f, "A file load error has occurred {v:?}")
+            }
+            BehaviorTreeDefinitionError::Visit(v) => {
+                write!(
+                    f,
+                    "An error that may occur due to version incompatibilities. {v:?}"
+                )
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for BehaviorTreeDefinitionError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<VisitError> for BehaviorTreeDefinitionError {
+    fn from(e: VisitError) -> Self {
+        Self::Visit(e)
+    }
+}
+
+/// Kind of a node in a behavior tree definition.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum BehaviorNodeDefinitionKind {
+    /// Sequence composite node, see [`crate::utils::behavior::composite::CompositeNodeKind`].
+    #[default]
+    Sequence,
+    /// Selector composite node, see [`crate::utils::behavior::composite::CompositeNodeKind`].
+    Selector,
+    /// Inverter node, must have exactly one child.
+    Inverter,
+    /// Succeeder decorator, must have exactly one child.
+    Succeeder,
+    /// Failer decorator, must have exactly one child.
+    Failer,
+    /// Guard decorator, must have exactly one child.
+    Guard(BlackboardCondition),
+    /// Condition node, must not have children.
+    Condition(BlackboardCondition),
+    /// Leaf node with an action. The name of the action is used to create the actual behavior
+    /// during instantiation, see [`BehaviorTreeDefinition::instantiate`]. Must not have children.
+    Action(String),
+}
+
+/// A single node of a behavior tree definition.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct BehaviorNodeDefinition {
+    /// Name of the node. It is used only for debugging and in the editor.
+    pub name: String,
+    /// Kind of the node.
+    pub kind: BehaviorNodeDefinitionKind,
+    /// Indices of the children nodes in [`BehaviorTreeDefinition::nodes`].
+    pub children: Vec<u32>,
+}
+
+/// Behavior tree definition is a description of a behavior tree, that could be stored in a file
+/// and shared between many agents. It does not contain any game-specific logic, actions are
+/// referenced by their names and must be provided when the definition is instantiated:
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::visitor::prelude::*,
+/// #     utils::behavior::{definition::BehaviorTreeDefinition, Behavior, Status},
+/// # };
+/// #[derive(Debug, Clone, Default, PartialEq, Visit)]
+/// enum Action {
+///     #[default]
+///     Attack,
+///     Patrol,
+/// }
+///
+/// impl<'a> Behavior<'a> for Action {
+///     type Context = ();
+///
+///     fn tick(&mut self, _context: &mut Self::Context) -> Status {
+///         Status::Success
+///     }
+/// }
+///
+/// fn instantiate(definition: &BehaviorTreeDefinition) {
+///     let _tree = definition
+///         .instantiate(|name| match name {
+///             "Attack" => Some(Action::Attack),
+///             "Patrol" => Some(Action::Patrol),
+///             _ => None,
+///         })
+///         .unwrap();
+/// }
+/// ```
+///
+/// Nodes are stored in a flat list and reference their children by indices, the root node is
+/// defined by [`Self::root`].
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect, TypeUuidProvider)]
+#[type_uuid(id = "6e1f8e4b-2b51-4c2e-9f0a-8a4f3d7c5b19")]
+pub struct BehaviorTreeDefinition {
+    /// All nodes of the tree.
+    pub nodes: Vec<BehaviorNodeDefinition>,
+    /// Index of the root node.
+    pub root: u32,
+}
+
+/// Maximum depth of a tree, deeper trees are considered malformed.
+const MAX_DEPTH: usize = 256;
+
+impl BehaviorTreeDefinition {
+    /// Loads a behavior tree definition from the given file.
+    pub async fn from_file(
+        path: &Path,
+        io: &dyn ResourceIo,
+    ) -> Result<Self, BehaviorTreeDefinitionError> {
+        let bytes = io.load_file(path).await?;
+        let mut visitor = Visitor::load_from_memory(&bytes)?;
+        let mut definition = Self::default();
+        definition.visit("BehaviorTreeDefinition", &mut visitor)?;
+        Ok(definition)
+    }
+
+    /// Adds a new node to the definition and returns its index.
+    pub fn add_node(&mut self, node: BehaviorNodeDefinition) -> u32 {
+        self.nodes.push(node);
+        (self.nodes.len() - 1) as u32
+    }
+
+    /// Creates a new behavior tree using the definition. `make_action` is used to create a behavior
+    /// for every action node, it must return `None` for unknown actions. Returns an error if the
+    /// definition is malformed (invalid indices, cycles, wrong amount of children) or contains an
+    /// unknown action.
+    pub fn instantiate<B, F>(&self, mut make_action: F) -> Result<BehaviorTree<B>, String>
+    where
+        B: Clone + 'static,
+        F: FnMut(&str) -> Option<B>,
+    {
+        let mut tree = BehaviorTree::new();
+        let entry = self.instantiate_recursive(self.root, &mut tree, &mut make_action, 0)?;
+        tree.set_entry_node(entry);
+        Ok(tree)
+    }
+
+    fn instantiate_recursive<B, F>(
+        &self,
+        index: u32,
+        tree: &mut BehaviorTree<B>,
+        make_action: &mut F,
+        depth: usize,
+    ) -> Result<Handle<BehaviorNode<B>>, String>
+    where
+        B: Clone + 'static,
+        F: FnMut(&str) -> Option<B>,
+    {
+        if depth > MAX_DEPTH {
+            return Err(format!(
+                "The tree is deeper than {MAX_DEPTH} nodes, it probably has a cycle!"
+            ));
+        }
+
+        let node = self
+            .nodes
+            .get(index as usize)
+            .ok_or_else(|| format!("Node index {index} is out of bounds!"))?;
+
+        let expect_children = |count: usize| {
+            if node.children.len() == count {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Node {} ({}) must have {} children, but has {}!",
+                    index,
+                    node.name,
+                    count,
+                    node.children.len()
+                ))
+            }
+        };
+
+        let mut children = Vec::with_capacity(node.children.len());
+        for child in node.children.iter() {
+            children.push(self.instantiate_recursive(*child, tree, make_action, depth + 1)?);
+        }
+
+        let handle = match node.kind {
+            BehaviorNodeDefinitionKind::Sequence => {
+                CompositeNode::new_sequence(children).add_to(tree)
+            }
+            BehaviorNodeDefinitionKind::Selector => {
+                CompositeNode::new_selector(children).add_to(tree)
+            }
+            BehaviorNodeDefinitionKind::Inverter => {
+                expect_children(1)?;
+                Inverter::new(children[0]).add_to(tree)
+            }
+            BehaviorNodeDefinitionKind::Succeeder => {
+                expect_children(1)?;
+                Decorator::new(DecoratorKind::Succeeder, children[0]).add_to(tree)
+            }
+            BehaviorNodeDefinitionKind::Failer => {
+                expect_children(1)?;
+                Decorator::new(DecoratorKind::Failer, children[0]).add_to(tree)
+            }
+            BehaviorNodeDefinitionKind::Guard(ref condition) => {
+                expect_children(1)?;
+                Decorator::new(DecoratorKind::Guard(condition.clone()), children[0]).add_to(tree)
+            }
+            BehaviorNodeDefinitionKind::Condition(ref condition) => {
+                expect_children(0)?;
+                tree.add_node(BehaviorNode::Condition(condition.clone()))
+            }
+            BehaviorNodeDefinitionKind::Action(ref action) => {
+                expect_children(0)?;
+                let behavior = make_action(action)
+                    .ok_or_else(|| format!("Unknown action {action} in node {index}!"))?;
+                LeafNode::new(behavior).add_to(tree)
+            }
+        };
+
+        Ok(handle)
+    }
+}
+
+impl ResourceData for BehaviorTreeDefinition {
+    fn type_uuid(&self) -> Uuid {
+        <Self as TypeUuidProvider>::type_uuid()
+    }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut visitor = Visitor::new();
+        self.visit("BehaviorTreeDefinition", &mut visitor)?;
+        visitor.save_binary(path)?;
+        Ok(())
+    }
+
+    fn can_be_saved(&self) -> bool {
+        true
+    }
+}
+
+/// Type alias for behavior tree definition resources.
+pub type BehaviorTreeDefinitionResource = Resource<BehaviorTreeDefinition>;
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{visitor::prelude::*, ImmutableString},
+        script::blackboard::{Blackboard, BlackboardValue},
+        utils::behavior::{
+            condition::{BlackboardCondition, ComparisonOp},
+            definition::{
+                BehaviorNodeDefinition, BehaviorNodeDefinitionKind, BehaviorTreeDefinition,
+            },
+            Behavior, Status,
+        },
+    };
+
+    #[derive(Debug, Clone, Default, PartialEq, Visit)]
+    struct Attack;
+
+    impl Behavior<'_> for Attack {
+        type Context = u32;
+
+        fn tick(&mut self, context: &mut Self::Context) -> Status {
+            *context += 1;
+            Status::Success
+        }
+    }
+
+    fn node(kind: BehaviorNodeDefinitionKind, children: Vec<u32>) -> BehaviorNodeDefinition {
+        BehaviorNodeDefinition {
+            name: Default::default(),
+            kind,
+            children,
+        }
+    }
+
+    #[test]
+    fn test_instantiate() {
+        let mut definition = BehaviorTreeDefinition::default();
+        let attack = definition.add_node(node(
+            BehaviorNodeDefinitionKind::Action("Attack".to_string()),
+            vec![],
+        ));
+        let guard = definition.add_node(node(
+            BehaviorNodeDefinitionKind::Guard(BlackboardCondition::new(
+                "Enemy Visible",
+                ComparisonOp::Equal,
+                BlackboardValue::Bool(true),
+            )),
+            vec![attack],
+        ));
+        definition.root =
+            definition.add_node(node(BehaviorNodeDefinitionKind::Selector, vec![guard]));
+
+        let tree = definition
+            .instantiate(|name| (name == "Attack").then_some(Attack))
+            .unwrap();
+
+        let mut blackboard = Blackboard::default();
+        let mut attacks = 0;
+        assert_eq!(
+            tree.tick_with_blackboard(&mut attacks, &blackboard),
+            Status::Failure
+        );
+        assert_eq!(attacks, 0);
+
+        blackboard.set_value(
+            ImmutableString::new("Enemy Visible"),
+            BlackboardValue::Bool(true),
+        );
+        assert_eq!(
+            tree.tick_with_blackboard(&mut attacks, &blackboard),
+            Status::Success
+        );
+        assert_eq!(attacks, 1);
+
+        // Unknown actions and cycles must be reported.
+        assert!(definition.instantiate(|_| None::<Attack>).is_err());
+        definition.nodes[guard as usize].children = vec![definition.root];
+        assert!(definition.instantiate(|_| Some(Attack)).is_err());
+    }
+}
@@ -26,8 +26,16 @@
 //! games. The main concept is in its name. Tree is a set of connected nodes, where each node could
 //! have single parent and zero or more children nodes. Execution path of the tree is defined by the
 //! actions of the nodes. Behavior tree has a set of hard coded nodes as well as leaf nodes with
-//! user-defined logic. Hard coded nodes are: Sequence, Selector, Leaf. Leaf is special - it has
-//! custom method `tick` that can contain any logic you want.
+//! user-defined logic. Hard coded nodes are: Sequence, Selector, Leaf, Inverter, Decorator and
+//! Condition. Leaf is special - it has custom method `tick` that can contain any logic you want.
+//!
+//! Condition nodes and guard decorators check values of a [`Blackboard`], which makes it possible to
+//! drive the tree by the shared game state without writing any code. The blackboard must be passed
+//! to [`BehaviorTree::tick_with_blackboard`], conditions fail if the tree is ticked without it.
+//!
+//! Trees could be created in code or defined as assets (`*.btree` files), see
+//! [`definition::BehaviorTreeDefinition`] for more info. Active branch of a tree could be captured
+//! using [`BehaviorTrace`] and printed using [`BehaviorTree::debug_string`] for debugging.
 //!
 //! For more info see:
 //! - [Wikipedia article](https://en.wikipedia.org/wiki/Behavior_tree_(artificial_intelligence,_robotics_and_control))
@@ -38,22 +46,30 @@ use crate::{
         pool::{Handle, Pool},
         visitor::prelude::*,
     },
+    script::blackboard::Blackboard,
     utils::behavior::{
         composite::{CompositeNode, CompositeNodeKind},
+        condition::BlackboardCondition,
+        decorator::{Decorator, DecoratorKind},
         inverter::Inverter,
         leaf::LeafNode,
     },
 };
 use std::{
-    fmt::Debug,
+    fmt::{Debug, Write},
     ops::{Index, IndexMut},
 };
 
 pub mod composite;
+pub mod condition;
+pub mod decorator;
+pub mod definition;
 pub mod inverter;
 pub mod leaf;
+pub mod loader;
 
 /// Status of execution of behavior tree node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Status {
     /// Action was successful.
     Success,
@@ -95,7 +111,7 @@ where
 }
 
 /// Possible variations of behavior nodes.
-#[derive(Debug, PartialEq, Visit, Eq, Clone)]
+#[derive(Debug, PartialEq, Visit, Clone)]
 pub enum BehaviorNode<B>
 where
     B: Clone,
@@ -111,6 +127,60 @@ where
     /// A node, that inverts its child state ([`Status::Failure`] becomes [`Status::Success`] and vice versa, [`Status::Running`] remains
     /// unchanged)
     Inverter(Inverter<B>),
+    /// A node, that modifies the state of its child or decides whether the child should be executed.
+    Decorator(Decorator<B>),
+    /// A node, that checks a condition using the blackboard. It returns [`Status::Success`] if the
+    /// condition holds, [`Status::Failure`] - otherwise.
+    Condition(BlackboardCondition),
+}
+
+/// A list of nodes, that were executed during a tick, with their statuses. It is used to debug
+/// behavior trees, see [`BehaviorTree::tick_traced`] and [`BehaviorTree::debug_string`].
+#[derive(Debug, Clone)]
+pub struct BehaviorTrace<B>
+where
+    B: Clone,
+{
+    /// Executed nodes in the order of completion (children go before their parents).
+    pub entries: Vec<(Handle<BehaviorNode<B>>, Status)>,
+}
+
+impl<B> Default for BehaviorTrace<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        Self {
+            entries: Default::default(),
+        }
+    }
+}
+
+impl<B> BehaviorTrace<B>
+where
+    B: Clone,
+{
+    /// Returns a status of the given node on the traced tick, or `None` if the node wasn't executed.
+    pub fn status_of(&self, handle: Handle<BehaviorNode<B>>) -> Option<Status> {
+        self.entries
+            .iter()
+            .find(|(node, _)| *node == handle)
+            .map(|(_, status)| *status)
+    }
+
+    /// Returns an iterator over the nodes of the active branch, which is formed by the nodes that
+    /// are still running.
+    pub fn active_branch(&self) -> impl Iterator<Item = Handle<BehaviorNode<B>>> + '_ {
+        self.entries
+            .iter()
+            .filter(|(_, status)| *status == Status::Running)
+            .map(|(node, _)| *node)
+    }
+
+    /// Clears the trace.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
 }
 
 impl<B> Default for BehaviorNode<B>
@@ -171,64 +241,126 @@ where
         }
     }
 
-    fn tick_recursive<'a, Ctx>(&self, handle: Handle<BehaviorNode<B>>, context: &mut Ctx) -> Status
+    fn tick_recursive<'a, Ctx>(
+        &self,
+        handle: Handle<BehaviorNode<B>>,
+        context: &mut Ctx,
+        blackboard: Option<&Blackboard>,
+        mut trace: Option<&mut BehaviorTrace<B>>,
+    ) -> Status
     where
         B: Behavior<'a, Context = Ctx>,
     {
-        match self.nodes[handle] {
+        let status = match self.nodes[handle] {
             BehaviorNode::Root(ref root) => {
                 if root.child.is_some() {
-                    self.tick_recursive(root.child, context)
+                    self.tick_recursive(root.child, context, blackboard, trace.as_deref_mut())
                 } else {
                     Status::Success
                 }
             }
             BehaviorNode::Composite(ref composite) => match composite.kind {
                 CompositeNodeKind::Sequence => {
-                    let mut all_succeeded = true;
+                    let mut status = Status::Success;
                     for child in composite.children.iter() {
-                        match self.tick_recursive(*child, context) {
+                        match self.tick_recursive(*child, context, blackboard, trace.as_deref_mut())
+                        {
                             Status::Failure => {
-                                all_succeeded = false;
+                                status = Status::Failure;
                                 break;
                             }
                             Status::Running => {
-                                return Status::Running;
+                                status = Status::Running;
+                                break;
                             }
                             _ => (),
                         }
                     }
-                    if all_succeeded {
-                        Status::Success
-                    } else {
-                        Status::Failure
-                    }
+                    status
                 }
                 CompositeNodeKind::Selector => {
+                    let mut status = Status::Failure;
                     for child in composite.children.iter() {
-                        match self.tick_recursive(*child, context) {
-                            Status::Success => return Status::Success,
-                            Status::Running => return Status::Running,
+                        match self.tick_recursive(*child, context, blackboard, trace.as_deref_mut())
+                        {
+                            Status::Success => {
+                                status = Status::Success;
+                                break;
+                            }
+                            Status::Running => {
+                                status = Status::Running;
+                                break;
+                            }
                             _ => (),
                         }
                     }
-                    Status::Failure
+                    status
                 }
             },
             BehaviorNode::Leaf(ref leaf) => {
                 leaf.behavior.as_ref().unwrap().borrow_mut().tick(context)
             }
             BehaviorNode::Inverter(ref inverter) => {
-                match self.tick_recursive(inverter.child, context) {
+                match self.tick_recursive(inverter.child, context, blackboard, trace.as_deref_mut())
+                {
                     Status::Success => Status::Failure,
                     Status::Failure => Status::Success,
                     Status::Running => Status::Running,
                 }
             }
+            BehaviorNode::Decorator(ref decorator) => match decorator.kind {
+                DecoratorKind::Succeeder => {
+                    match self.tick_recursive(
+                        decorator.child,
+                        context,
+                        blackboard,
+                        trace.as_deref_mut(),
+                    ) {
+                        Status::Running => Status::Running,
+                        _ => Status::Success,
+                    }
+                }
+                DecoratorKind::Failer => {
+                    match self.tick_recursive(
+                        decorator.child,
+                        context,
+                        blackboard,
+                        trace.as_deref_mut(),
+                    ) {
+                        Status::Running => Status::Running,
+                        _ => Status::Failure,
+                    }
+                }
+                DecoratorKind::Guard(ref condition) => {
+                    if blackboard.map_or(false, |blackboard| condition.evaluate(blackboard)) {
+                        self.tick_recursive(
+                            decorator.child,
+                            context,
+                            blackboard,
+                            trace.as_deref_mut(),
+                        )
+                    } else {
+                        Status::Failure
+                    }
+                }
+            },
+            BehaviorNode::Condition(ref condition) => {
+                if blackboard.map_or(false, |blackboard| condition.evaluate(blackboard)) {
+                    Status::Success
+                } else {
+                    Status::Failure
+                }
+            }
             BehaviorNode::Unknown => {
                 unreachable!()
             }
+        };
+
+        if let Some(trace) = trace {
+            trace.entries.push((handle, status));
         }
+
+        status
     }
 
     /// Tries to get a shared reference to a node by given handle.
@@ -241,12 +373,145 @@ where
         self.nodes.try_borrow_mut(handle)
     }
 
-    /// Performs a single update tick with given context.
+    /// Performs a single update tick with given context. Condition nodes and guard decorators will
+    /// fail, use [`Self::tick_with_blackboard`] if the tree has any of them.
     pub fn tick<'a, Ctx>(&self, context: &mut Ctx) -> Status
     where
         B: Behavior<'a, Context = Ctx>,
     {
-        self.tick_recursive(self.root, context)
+        self.tick_recursive(self.root, context, None, None)
+    }
+
+    /// Performs a single update tick with given context. Condition nodes and guard decorators will
+    /// use the given blackboard.
+    pub fn tick_with_blackboard<'a, Ctx>(
+        &self,
+        context: &mut Ctx,
+        blackboard: &Blackboard,
+    ) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        self.tick_recursive(self.root, context, Some(blackboard), None)
+    }
+
+    /// Performs a single update tick with given context and records every executed node with its
+    /// status in the given trace. The trace is cleared before the tick.
+    pub fn tick_traced<'a, Ctx>(
+        &self,
+        context: &mut Ctx,
+        blackboard: Option<&Blackboard>,
+        trace: &mut BehaviorTrace<B>,
+    ) -> Status
+    where
+        B: Behavior<'a, Context = Ctx>,
+    {
+        trace.clear();
+        self.tick_recursive(self.root, context, blackboard, Some(trace))
+    }
+
+    /// Returns a handle of the root node of the tree.
+    pub fn root(&self) -> Handle<BehaviorNode<B>> {
+        self.root
+    }
+
+    /// Returns a human-readable representation of the tree, where every node executed during the
+    /// traced tick is marked with its status. The nodes of the active branch are marked with `>`.
+    /// It is useful to print the state of an agent to the log or to show it on screen.
+    pub fn debug_string(&self, trace: &BehaviorTrace<B>) -> String
+    where
+        B: Debug,
+    {
+        let mut string = String::new();
+        self.debug_string_recursive(self.root, trace, 0, &mut string);
+        string
+    }
+
+    fn debug_string_recursive(
+        &self,
+        handle: Handle<BehaviorNode<B>>,
+        trace: &BehaviorTrace<B>,
+        depth: usize,
+        string: &mut String,
+    ) where
+        B: Debug,
+    {
+        let Some(node) = self.nodes.try_borrow(handle) else {
+            return;
+        };
+
+        let status = trace.status_of(handle);
+        let marker = if status == Some(Status::Running) {
+            '>'
+        } else {
+            ' '
+        };
+        let _ = write!(string, "{marker}{}", "  ".repeat(depth));
+
+        let children = match node {
+            BehaviorNode::Unknown => {
+                let _ = write!(string, "Unknown");
+                vec![]
+            }
+            BehaviorNode::Root(root) => {
+                let _ = write!(string, "Root");
+                vec![root.child]
+            }
+            BehaviorNode::Composite(composite) => {
+                let _ = write!(string, "{:?}", composite.kind);
+                composite.children.clone()
+            }
+            BehaviorNode::Leaf(leaf) => {
+                match leaf.behavior.as_ref() {
+                    Some(behavior) => {
+                        let _ = write!(string, "Leaf({:?})", behavior.borrow());
+                    }
+                    None => {
+                        let _ = write!(string, "Leaf");
+                    }
+                }
+                vec![]
+            }
+            BehaviorNode::Inverter(inverter) => {
+                let _ = write!(string, "Inverter");
+                vec![inverter.child]
+            }
+            BehaviorNode::Decorator(decorator) => {
+                match decorator.kind {
+                    DecoratorKind::Succeeder => {
+                        let _ = write!(string, "Succeeder");
+                    }
+                    DecoratorKind::Failer => {
+                        let _ = write!(string, "Failer");
+                    }
+                    DecoratorKind::Guard(ref condition) => {
+                        let _ = write!(
+                            string,
+                            "Guard({} {:?} {:?})",
+                            condition.name, condition.op, condition.value
+                        );
+                    }
+                }
+                vec![decorator.child]
+            }
+            BehaviorNode::Condition(condition) => {
+                let _ = write!(
+                    string,
+                    "Condition({} {:?} {:?})",
+                    condition.name, condition.op, condition.value
+                );
+                vec![]
+            }
+        };
+
+        if let Some(status) = status {
+            let _ = write!(string, " [{status:?}]");
+        }
+        string.push('\n');
+
+        for child in children {
+            self.debug_string_recursive(child, trace, depth + 1, string);
+        }
     }
 }
 
@@ -294,6 +559,29 @@ where
     LeafNode::new(behavior).add_to(tree)
 }
 
+/// Creates a new decorator.
+pub fn decorator<B>(
+    kind: DecoratorKind,
+    child: Handle<BehaviorNode<B>>,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    Decorator::new(kind, child).add_to(tree)
+}
+
+/// Creates a new condition.
+pub fn condition<B>(
+    condition: BlackboardCondition,
+    tree: &mut BehaviorTree<B>,
+) -> Handle<BehaviorNode<B>>
+where
+    B: Clone + 'static,
+{
+    tree.add_node(BehaviorNode::Condition(condition))
+}
+
 /// Creates a new inverter.
 pub fn inverter<B>(
     child: Handle<BehaviorNode<B>>,
@@ -308,11 +596,14 @@ where
 #[cfg(test)]
 mod test {
     use crate::{
-        core::{futures::executor::block_on, visitor::prelude::*},
+        core::{futures::executor::block_on, visitor::prelude::*, ImmutableString},
+        script::blackboard::{Blackboard, BlackboardValue},
         utils::behavior::{
             composite::{CompositeNode, CompositeNodeKind},
+            condition::{BlackboardCondition, ComparisonOp},
+            decorator::DecoratorKind,
             leaf::LeafNode,
-            Behavior, BehaviorTree, Status,
+            Behavior, BehaviorTrace, BehaviorTree, Status,
         },
     };
     use std::{env, fs::File, io::Write, path::PathBuf};
@@ -460,6 +751,72 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_blackboard_conditions() {
+        let mut tree = BehaviorTree::new();
+        let walk = LeafNode::new(BotBehavior::Walk(WalkAction)).add_to(&mut tree);
+        let guard = super::decorator(
+            DecoratorKind::Guard(BlackboardCondition::new(
+                "Door Locked",
+                ComparisonOp::Equal,
+                BlackboardValue::Bool(false),
+            )),
+            walk,
+            &mut tree,
+        );
+        let locked = super::condition(
+            BlackboardCondition::new(
+                "Door Locked",
+                ComparisonOp::Equal,
+                BlackboardValue::Bool(true),
+            ),
+            &mut tree,
+        );
+        let entry = CompositeNode::new_selector(vec![guard, locked]).add_to(&mut tree);
+        tree.set_entry_node(entry);
+
+        let mut ctx = Environment {
+            distance_to_door: 3.0,
+            door_opened: false,
+            done: false,
+        };
+
+        // Conditions fail without blackboard.
+        assert_eq!(tree.tick(&mut ctx), Status::Failure);
+
+        let mut blackboard = Blackboard::default();
+        blackboard.set_value(
+            ImmutableString::new("Door Locked"),
+            BlackboardValue::Bool(false),
+        );
+        let mut trace = BehaviorTrace::default();
+        assert_eq!(
+            tree.tick_traced(&mut ctx, Some(&blackboard), &mut trace),
+            Status::Running
+        );
+        assert_eq!(trace.status_of(walk), Some(Status::Running));
+        assert_eq!(trace.status_of(locked), None);
+        assert_eq!(
+            trace.active_branch().collect::<Vec<_>>(),
+            vec![walk, guard, entry, tree.root()]
+        );
+        assert!(tree
+            .debug_string(&trace)
+            .contains("Walk(WalkAction) [Running]"));
+
+        blackboard.set_value(
+            ImmutableString::new("Door Locked"),
+            BlackboardValue::Bool(true),
+        );
+        assert_eq!(
+            tree.tick_traced(&mut ctx, Some(&blackboard), &mut trace),
+            Status::Success
+        );
+        assert_eq!(trace.status_of(guard), Some(Status::Failure));
+        assert_eq!(trace.status_of(locked), Some(Status::Success));
+        assert_eq!(trace.active_branch().count(), 0);
+    }
+
     #[test]
     fn test_behavior_save_load() {
         let (bin, txt) = {
@@ -23,10 +23,12 @@
 //! Utilities module provides set of commonly used algorithms.
 
 pub mod astar;
+pub mod bake;
 pub mod behavior;
 pub mod lightmap;
 pub mod navmesh;
 pub mod raw_mesh;
+pub mod utility;
 pub mod uvgen;
 
 use crate::{
@@ -0,0 +1,529 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Utility AI is a decision making technique, where every possible action of an agent is scored
+//! using a set of considerations and the action with the highest score is selected. Unlike state
+//! machines, it does not require to define transitions between the actions explicitly, which makes
+//! it much easier to add new actions. See [`UtilitySelector`] docs for more info.
+
+use crate::{
+    core::{math::curve::Curve, reflect::prelude::*, visitor::prelude::*, ImmutableString},
+    script::blackboard::{Blackboard, BlackboardValue},
+    utils::behavior::condition::{BlackboardCondition, ComparisonOp},
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Response curve maps a normalized input value of a consideration (in `[0; 1]` range) to a score
+/// (also in `[0; 1]` range). The resulting value is always clamped.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum ResponseCurve {
+    /// `slope * x + offset`.
+    Linear {
+        /// Slope of the line.
+        slope: f32,
+        /// Offset of the line.
+        offset: f32,
+    },
+    /// `x ^ exponent`, exponents greater than one make the curve steeper at the end, exponents less
+    /// than one - at the beginning.
+    Power {
+        /// Exponent of the curve.
+        exponent: f32,
+    },
+    /// S-shaped curve `1 / (1 + e ^ (-steepness * (x - midpoint)))`.
+    Logistic {
+        /// Steepness of the curve.
+        steepness: f32,
+        /// An input value at which the curve has the value of `0.5`.
+        midpoint: f32,
+    },
+    /// Returns one if the input is greater than or equal to the threshold, zero - otherwise.
+    Step {
+        /// Input threshold.
+        threshold: f32,
+    },
+    /// Arbitrary curve, that could be edited in the curve editor.
+    Custom(Curve),
+}
+
+impl Default for ResponseCurve {
+    fn default() -> Self {
+        Self::Linear {
+            slope: 1.0,
+            offset: 0.0,
+        }
+    }
+}
+
+impl ResponseCurve {
+    /// Maps the given input value to a score.
+    pub fn evaluate(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        let y = match self {
+            ResponseCurve::Linear { slope, offset } => slope * x + offset,
+            ResponseCurve::Power { exponent } => x.powf(*exponent),
+            ResponseCurve::Logistic {
+                steepness,
+                midpoint,
+            } => 1.0 / (1.0 + (-steepness * (x - midpoint)).exp()),
+            ResponseCurve::Step { threshold } => {
+                if x >= *threshold {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ResponseCurve::Custom(curve) => curve.value_at(x),
+        };
+        if y.is_nan() {
+            0.0
+        } else {
+            y.clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Source of an input value of a consideration.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum ConsiderationInput {
+    /// Constant value (it should be in `[0; 1]` range).
+    Constant(f32),
+    /// A numeric (or boolean) value of a blackboard entry, remapped from `[min; max]` range to
+    /// `[0; 1]` range. Missing entries and entries with non-numeric values produce zero.
+    Blackboard {
+        /// Name of the blackboard entry.
+        name: String,
+        /// A value of the entry, that is mapped to zero.
+        min: f32,
+        /// A value of the entry, that is mapped to one.
+        max: f32,
+    },
+}
+
+impl Default for ConsiderationInput {
+    fn default() -> Self {
+        Self::Constant(1.0)
+    }
+}
+
+impl ConsiderationInput {
+    /// Fetches a normalized input value.
+    pub fn fetch(&self, blackboard: &Blackboard) -> f32 {
+        match self {
+            ConsiderationInput::Constant(value) => *value,
+            ConsiderationInput::Blackboard { name, min, max } => {
+                let value = match blackboard.value(name) {
+                    Some(BlackboardValue::Number(value)) => *value,
+                    Some(BlackboardValue::Integer(value)) => *value as f32,
+                    Some(BlackboardValue::Bool(value)) => {
+                        if *value {
+                            1.0
+                        } else {
+                            0.0
+                        }
+                    }
+                    _ => return 0.0,
+                };
+                let range = max - min;
+                if range.abs() <= f32::EPSILON {
+                    if value >= *max {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                } else {
+                    (value - min) / range
+                }
+            }
+        }
+    }
+}
+
+/// Consideration is a single factor, that affects the score of an action. For example, an action
+/// "Heal" could have a consideration "Health", that produces higher scores for lower health.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct Consideration {
+    /// Name of the consideration. It is used only for debugging and in the editor.
+    pub name: String,
+    /// Source of an input value.
+    pub input: ConsiderationInput,
+    /// Response curve, that maps the input value to a score.
+    pub curve: ResponseCurve,
+}
+
+impl Consideration {
+    /// Creates a new consideration.
+    pub fn new(name: impl Into<String>, input: ConsiderationInput, curve: ResponseCurve) -> Self {
+        Self {
+            name: name.into(),
+            input,
+            curve,
+        }
+    }
+
+    /// Calculates the score of the consideration in `[0; 1]` range.
+    pub fn score(&self, blackboard: &Blackboard) -> f32 {
+        self.curve.evaluate(self.input.fetch(blackboard))
+    }
+}
+
+/// An action, that could be selected by [`UtilitySelector`]. The action itself does not contain any
+/// logic, it is identified by its name.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct UtilityAction {
+    /// Name of the action.
+    pub name: String,
+    /// A set of considerations, their scores are multiplied to get the score of the action.
+    pub considerations: Vec<Consideration>,
+    /// A multiplier of the action score, it could be used to prioritize some actions over others.
+    #[reflect(min_value = 0.0)]
+    pub weight: f32,
+    /// Amount of time (in seconds) during which the action can't be selected again after it was
+    /// deselected.
+    #[reflect(min_value = 0.0)]
+    pub cooldown: f32,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    remaining_cooldown: f32,
+}
+
+impl Default for UtilityAction {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            considerations: Default::default(),
+            weight: 1.0,
+            cooldown: 0.0,
+            remaining_cooldown: 0.0,
+        }
+    }
+}
+
+impl UtilityAction {
+    /// Creates a new action with the given name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a consideration to the action.
+    pub fn with_consideration(mut self, consideration: Consideration) -> Self {
+        self.considerations.push(consideration);
+        self
+    }
+
+    /// Sets a new weight of the action.
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Sets a new cooldown of the action.
+    pub fn with_cooldown(mut self, cooldown: f32) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Returns `true` if the action is on cooldown.
+    pub fn is_on_cooldown(&self) -> bool {
+        self.remaining_cooldown > 0.0
+    }
+
+    /// Calculates the score of the action. Scores of considerations are multiplied, the result is
+    /// compensated for the number of considerations (otherwise actions with many considerations
+    /// would always lose to the actions with few of them) and then multiplied by the weight.
+    pub fn score(&self, blackboard: &Blackboard) -> f32 {
+        if self.considerations.is_empty() {
+            return self.weight;
+        }
+
+        let modification = 1.0 - 1.0 / self.considerations.len() as f32;
+        let mut score = 1.0;
+        for consideration in self.considerations.iter() {
+            let value = consideration.score(blackboard);
+            let make_up = (1.0 - value) * modification;
+            score *= value + make_up * value;
+            if score <= 0.0 {
+                return 0.0;
+            }
+        }
+
+        score * self.weight
+    }
+}
+
+/// Utility selector picks the best action from a set of actions. It should be updated every frame
+/// (or less often) using [`Self::update`] and the game logic should execute the selected action.
+///
+/// To prevent rapid switching between actions with similar scores, the currently selected action
+/// receives a bonus to its score called inertia. Also, every action could have a cooldown, that
+/// prevents it from being selected again right after it was deselected.
+///
+/// ## Blackboard integration
+///
+/// Inputs of considerations are taken from a [`Blackboard`], it could be the global one or some
+/// local blackboard of an agent. The name of the selected action could be written back to the
+/// blackboard by [`Self::update_blackboard`], which allows to use the selector with behavior trees -
+/// a guard decorator with a condition created by [`Self::selected_condition`] executes its branch only
+/// when the respective action is selected.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::ImmutableString,
+/// #     script::blackboard::{Blackboard, BlackboardValue},
+/// #     utils::utility::{
+/// #         Consideration, ConsiderationInput, ResponseCurve, UtilityAction, UtilitySelector,
+/// #     },
+/// # };
+/// let mut selector = UtilitySelector::default()
+///     .with_action(
+///         UtilityAction::new("Heal").with_consideration(Consideration::new(
+///             "Health",
+///             ConsiderationInput::Blackboard {
+///                 name: "Health".to_string(),
+///                 min: 100.0,
+///                 max: 0.0,
+///             },
+///             ResponseCurve::Power { exponent: 2.0 },
+///         )),
+///     )
+///     .with_action(UtilityAction::new("Attack").with_weight(0.5));
+///
+/// let mut blackboard = Blackboard::default();
+/// blackboard.set_value(ImmutableString::new("Health"), BlackboardValue::Number(10.0));
+///
+/// let selected = selector.update_blackboard(1.0 / 60.0, &mut blackboard);
+/// assert_eq!(selected, Some("Heal"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct UtilitySelector {
+    /// A set of actions to select from.
+    pub actions: Vec<UtilityAction>,
+    /// A bonus, that is added to the score of the currently selected action.
+    #[reflect(min_value = 0.0)]
+    pub inertia: f32,
+    /// Minimal score of an action to be selected. If every action has lower score, nothing is
+    /// selected.
+    #[reflect(min_value = 0.0)]
+    pub min_score: f32,
+    /// Name of the blackboard entry, that receives the name of the selected action in
+    /// [`Self::update_blackboard`].
+    pub output: String,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    selected: Option<usize>,
+}
+
+impl Default for UtilitySelector {
+    fn default() -> Self {
+        Self {
+            actions: Default::default(),
+            inertia: 0.1,
+            min_score: 0.0,
+            output: "Utility Action".to_string(),
+            selected: None,
+        }
+    }
+}
+
+impl UtilitySelector {
+    /// Adds an action to the selector.
+    pub fn with_action(mut self, action: UtilityAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Sets a new inertia of the selector.
+    pub fn with_inertia(mut self, inertia: f32) -> Self {
+        self.inertia = inertia;
+        self
+    }
+
+    /// Sets a new minimal score of an action to be selected.
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    /// Sets a new name of the output blackboard entry.
+    pub fn with_output(mut self, output: impl Into<String>) -> Self {
+        self.output = output.into();
+        self
+    }
+
+    /// Returns the currently selected action (if any).
+    pub fn selected(&self) -> Option<&UtilityAction> {
+        self.selected.and_then(|index| self.actions.get(index))
+    }
+
+    /// Returns the name of the currently selected action (if any).
+    pub fn selected_name(&self) -> Option<&str> {
+        self.selected().map(|action| action.name.as_str())
+    }
+
+    /// Calculates scores of every action, including inertia. Actions on cooldown have zero score.
+    pub fn scores(&self, blackboard: &Blackboard) -> Vec<f32> {
+        self.actions
+            .iter()
+            .enumerate()
+            .map(|(index, action)| {
+                if action.is_on_cooldown() {
+                    0.0
+                } else if self.selected == Some(index) {
+                    action.score(blackboard) + self.inertia
+                } else {
+                    action.score(blackboard)
+                }
+            })
+            .collect()
+    }
+
+    /// Updates cooldowns and selects the best action. Returns the name of the selected action.
+    pub fn update(&mut self, dt: f32, blackboard: &Blackboard) -> Option<&str> {
+        for action in self.actions.iter_mut() {
+            action.remaining_cooldown = (action.remaining_cooldown - dt).max(0.0);
+        }
+
+        let mut best = None;
+        let mut best_score = self.min_score;
+        for (index, score) in self.scores(blackboard).into_iter().enumerate() {
+            if score > best_score || (best.is_none() && score > 0.0 && score >= best_score) {
+                best = Some(index);
+                best_score = score;
+            }
+        }
+
+        if best != self.selected {
+            if let Some(previous) = self.selected.and_then(|index| self.actions.get_mut(index)) {
+                previous.remaining_cooldown = previous.cooldown;
+            }
+            self.selected = best;
+        }
+
+        self.selected_name()
+    }
+
+    /// Does the same as [`Self::update`], but also writes the name of the selected action to the
+    /// blackboard entry defined by [`Self::output`]. [`BlackboardValue::Nil`] is written if nothing
+    /// is selected.
+    pub fn update_blackboard(&mut self, dt: f32, blackboard: &mut Blackboard) -> Option<&str> {
+        self.update(dt, blackboard);
+        let value = match self.selected_name() {
+            Some(name) => BlackboardValue::String(name.to_string()),
+            None => BlackboardValue::Nil,
+        };
+        blackboard.set_value(ImmutableString::new(&self.output), value);
+        self.selected_name()
+    }
+
+    /// Creates a blackboard condition, that holds only when the given action is selected. It could
+    /// be used with guard decorators and condition nodes of behavior trees.
+    pub fn selected_condition(&self, action: &str) -> BlackboardCondition {
+        BlackboardCondition::new(
+            self.output.clone(),
+            ComparisonOp::Equal,
+            BlackboardValue::String(action.to_string()),
+        )
+    }
+
+    /// Deselects the current action and resets every cooldown.
+    pub fn reset(&mut self) {
+        self.selected = None;
+        for action in self.actions.iter_mut() {
+            action.remaining_cooldown = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::ImmutableString,
+        script::blackboard::{Blackboard, BlackboardValue},
+        utils::utility::{
+            Consideration, ConsiderationInput, ResponseCurve, UtilityAction, UtilitySelector,
+        },
+    };
+
+    fn set(blackboard: &mut Blackboard, name: &str, value: f32) {
+        blackboard.set_value(ImmutableString::new(name), BlackboardValue::Number(value));
+    }
+
+    fn selector() -> UtilitySelector {
+        UtilitySelector::default()
+            .with_inertia(0.1)
+            .with_action(
+                UtilityAction::new("Flee")
+                    .with_cooldown(1.0)
+                    .with_consideration(Consideration::new(
+                        "Danger",
+                        ConsiderationInput::Blackboard {
+                            name: "Danger".to_string(),
+                            min: 0.0,
+                            max: 10.0,
+                        },
+                        ResponseCurve::default(),
+                    )),
+            )
+            .with_action(UtilityAction::new("Idle").with_weight(0.5))
+    }
+
+    #[test]
+    fn test_response_curves() {
+        assert_eq!(ResponseCurve::default().evaluate(0.25), 0.25);
+        assert_eq!(ResponseCurve::default().evaluate(2.0), 1.0);
+        assert_eq!(ResponseCurve::Power { exponent: 2.0 }.evaluate(0.5), 0.25);
+        assert_eq!(ResponseCurve::Step { threshold: 0.5 }.evaluate(0.4), 0.0);
+        let logistic = ResponseCurve::Logistic {
+            steepness: 10.0,
+            midpoint: 0.5,
+        };
+        assert_eq!(logistic.evaluate(0.5), 0.5);
+    }
+
+    #[test]
+    fn test_utility_selector() {
+        let mut selector = selector();
+        let mut blackboard = Blackboard::default();
+
+        set(&mut blackboard, "Danger", 8.0);
+        assert_eq!(
+            selector.update_blackboard(0.1, &mut blackboard),
+            Some("Flee")
+        );
+        assert!(selector.selected_condition("Flee").evaluate(&blackboard));
+
+        // Inertia keeps the current action.
+        set(&mut blackboard, "Danger", 4.5);
+        assert_eq!(selector.update(0.1, &blackboard), Some("Flee"));
+
+        // Flee goes on cooldown when deselected.
+        set(&mut blackboard, "Danger", 2.0);
+        assert_eq!(selector.update(0.1, &blackboard), Some("Idle"));
+        set(&mut blackboard, "Danger", 10.0);
+        assert_eq!(selector.update(0.5, &blackboard), Some("Idle"));
+        assert_eq!(selector.update(0.6, &blackboard), Some("Flee"));
+
+        selector.reset();
+        assert_eq!(selector.selected_name(), None);
+    }
+}
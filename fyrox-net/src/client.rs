@@ -0,0 +1,324 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Client connects to a server and exchanges messages with it. See [`Client`] docs for more info.
+
+use crate::{
+    connection::{Channel, Connection, NetConfig, Packet},
+    replication::ReplicationMessage,
+    transport::{Address, Transport, UdpTransport},
+    websocket::WebSocketTransport,
+    ClientId, Envelope,
+};
+use fyrox::core::log::Log;
+use serde::Serialize;
+use std::{
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs},
+};
+
+/// State of a client.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClientState {
+    /// The client is trying to connect to the server.
+    Connecting,
+    /// The client is connected to the server and has the given id.
+    Connected(ClientId),
+    /// The client is disconnected.
+    Disconnected,
+}
+
+/// An event, that happened on a client.
+#[derive(Debug, PartialEq)]
+pub enum ClientEvent {
+    /// The client has connected to the server.
+    Connected(ClientId),
+    /// The server has refused the connection.
+    Denied(String),
+    /// The client was disconnected by the server, or the connection was lost, or the server didn't
+    /// respond to connection requests.
+    Disconnected,
+    /// A message was received from the server.
+    Message {
+        /// A channel, that was used to send the message.
+        channel: Channel,
+        /// Message data.
+        data: Vec<u8>,
+    },
+    /// A replication message was received from the server. It should be passed to
+    /// [`crate::replication::Replicator::handle_client_event`].
+    Replication(ReplicationMessage),
+}
+
+/// Client connects to a server and exchanges messages with it. Just like [`crate::server::Server`],
+/// it does not spawn any threads, [`Self::update`] and [`Self::flush`] methods should be called
+/// every frame:
+///
+/// ```rust,no_run
+/// # use fyrox_net::{
+/// #     client::{Client, ClientEvent},
+/// #     connection::{Channel, NetConfig},
+/// # };
+/// let mut client = Client::connect("127.0.0.1:7777", NetConfig::default()).unwrap();
+///
+/// loop {
+///     for event in client.update(1.0 / 60.0) {
+///         match event {
+///             ClientEvent::Connected(id) => println!("Connected as {id}"),
+///             ClientEvent::Message { data, .. } => {
+///                 println!("{}", String::from_utf8_lossy(&data))
+///             }
+///             _ => (),
+///         }
+///     }
+///
+///     client.send(Channel::Unreliable, b"Hello!".to_vec());
+///     client.flush();
+/// #   break;
+/// }
+/// ```
+pub struct Client<T: Transport = UdpTransport> {
+    transport: T,
+    server: Address,
+    config: NetConfig,
+    state: ClientState,
+    connection: Connection,
+    connect_timer: f32,
+    connecting_time: f32,
+}
+
+impl Client<UdpTransport> {
+    /// Creates a new client, that connects to a server with the given address using UDP transport.
+    pub fn connect<A: ToSocketAddrs>(address: A, config: NetConfig) -> io::Result<Self> {
+        let server = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "No address to connect to!"))?;
+        let local: SocketAddr = if server.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        Ok(Self::new(
+            UdpTransport::bind(local)?,
+            Address::Socket(server),
+            config,
+        ))
+    }
+}
+
+impl Client<WebSocketTransport> {
+    /// Creates a new client, that connects to a server with the given URL using WebSocket
+    /// transport. This is the only way to connect to a server from web builds.
+    pub fn connect_websocket(url: &str, config: NetConfig) -> io::Result<Self> {
+        let transport = WebSocketTransport::connect(url)?;
+        let server = transport.server_address();
+        Ok(Self::new(transport, server, config))
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Creates a new client, that connects to a server with the given address using the given
+    /// transport.
+    pub fn new(transport: T, server: Address, config: NetConfig) -> Self {
+        Self {
+            transport,
+            server,
+            // Send the first connection request right on the first update.
+            connect_timer: config.heartbeat_interval,
+            config,
+            state: ClientState::Connecting,
+            connection: Default::default(),
+            connecting_time: 0.0,
+        }
+    }
+
+    /// Returns current state of the client.
+    pub fn state(&self) -> ClientState {
+        self.state
+    }
+
+    /// Returns id of the client, if it is connected.
+    pub fn id(&self) -> Option<ClientId> {
+        match self.state {
+            ClientState::Connected(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the client is connected to the server.
+    pub fn is_connected(&self) -> bool {
+        self.id().is_some()
+    }
+
+    /// Returns the address of the server.
+    pub fn server_address(&self) -> &Address {
+        &self.server
+    }
+
+    /// Returns a reference to the transport of the client.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    fn send_packet(&mut self, packet: &Packet) {
+        if let Err(err) = self.transport.send(&self.server, &packet.encode()) {
+            Log::err(format!(
+                "Unable to send a packet to {}. Reason: {err}",
+                self.server
+            ));
+        }
+    }
+
+    /// Receives every incoming packet, sends connection requests and handles timeouts. Returns a
+    /// list of events, that happened since the last update.
+    pub fn update(&mut self, dt: f32) -> Vec<ClientEvent> {
+        let mut events = Vec::new();
+
+        if self.state == ClientState::Disconnected {
+            return events;
+        }
+
+        self.connection.update(dt);
+
+        if self.state == ClientState::Connecting {
+            self.connecting_time += dt;
+            self.connect_timer += dt;
+            if self.connecting_time > self.config.timeout {
+                self.state = ClientState::Disconnected;
+                events.push(ClientEvent::Disconnected);
+                return events;
+            }
+            if self.connect_timer >= self.config.heartbeat_interval {
+                self.connect_timer = 0.0;
+                self.send_packet(&Packet::Connect {
+                    protocol_id: self.config.protocol_id,
+                });
+            }
+        }
+
+        while let Some((address, data)) = self.transport.receive() {
+            if address != self.server {
+                continue;
+            }
+
+            let Some(packet) = Packet::decode(&data) else {
+                continue;
+            };
+
+            match (packet, self.state) {
+                (Packet::Accept { client_id }, ClientState::Connecting) => {
+                    self.state = ClientState::Connected(client_id);
+                    self.connection.mark_received();
+                    events.push(ClientEvent::Connected(client_id));
+                }
+                (Packet::Deny { reason }, ClientState::Connecting) => {
+                    self.state = ClientState::Disconnected;
+                    events.push(ClientEvent::Denied(reason));
+                    return events;
+                }
+                (Packet::Disconnect, ClientState::Connected(_)) => {
+                    self.state = ClientState::Disconnected;
+                    events.push(ClientEvent::Disconnected);
+                    return events;
+                }
+                (
+                    Packet::Payload {
+                        acks,
+                        reliable,
+                        unreliable,
+                    },
+                    ClientState::Connected(_),
+                ) => {
+                    for (channel, data) in
+                        self.connection
+                            .receive(acks, reliable, unreliable, &self.config)
+                    {
+                        match Envelope::decode(&data) {
+                            Some(Envelope::User(data)) => {
+                                events.push(ClientEvent::Message { channel, data })
+                            }
+                            Some(Envelope::Replication(message)) => {
+                                events.push(ClientEvent::Replication(message))
+                            }
+                            None => Log::warn("Malformed message received from the server!"),
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if self.is_connected() && self.connection.is_timed_out(&self.config) {
+            self.state = ClientState::Disconnected;
+            events.push(ClientEvent::Disconnected);
+        }
+
+        events
+    }
+
+    /// Sends every queued message to the server. Should be called once per frame, after every
+    /// message was sent. Messages are kept in the queue while the client is connecting.
+    pub fn flush(&mut self) {
+        if self.is_connected() {
+            for packet in self.connection.flush(&self.config) {
+                self.send_packet(&packet);
+            }
+        }
+    }
+
+    fn send_envelope(&mut self, channel: Channel, envelope: &Envelope) {
+        if self.state != ClientState::Disconnected {
+            if let Err(err) = self
+                .connection
+                .send(channel, envelope.encode(), &self.config)
+            {
+                Log::err(format!(
+                    "Unable to send a message to the server. Reason: {err}"
+                ));
+            }
+        }
+    }
+
+    /// Queues a message for the server.
+    pub fn send(&mut self, channel: Channel, data: Vec<u8>) {
+        self.send_envelope(channel, &Envelope::User(data))
+    }
+
+    /// Serializes the given message and queues it for the server.
+    pub fn send_message<M: Serialize>(&mut self, channel: Channel, message: &M) {
+        match bincode::serialize(message) {
+            Ok(data) => self.send(channel, data),
+            Err(err) => Log::err(format!("Unable to serialize a message. Reason: {err}")),
+        }
+    }
+
+    pub(crate) fn send_replication(&mut self, channel: Channel, message: ReplicationMessage) {
+        self.send_envelope(channel, &Envelope::Replication(message))
+    }
+
+    /// Disconnects from the server. [`ClientEvent::Disconnected`] is not generated in this case.
+    pub fn disconnect(&mut self) {
+        if self.is_connected() {
+            self.send_packet(&Packet::Disconnect);
+        }
+        self.state = ClientState::Disconnected;
+    }
+}
@@ -0,0 +1,597 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Connection implements reliable and unreliable channels on top of an unreliable transport.
+//! Reliable messages are resent until they're acknowledged by the remote side and delivered in the
+//! order they were sent. Large reliable messages are split into fragments, that fit into a single
+//! packet. Unreliable messages are sent once and could be lost or arrive out of order.
+
+use crate::ClientId;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+/// Maximum size of a UDP datagram payload.
+pub const MAX_DATAGRAM_SIZE: usize = 65507;
+
+/// Delivery guarantees of a message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Channel {
+    /// Messages are guaranteed to be delivered in the order they were sent. Lost messages are
+    /// resent, which increases latency of every following message.
+    Reliable,
+    /// Messages could be lost, duplicated or arrive out of order. Use it for frequently updated
+    /// data, where only the latest value is important (positions, for instance).
+    Unreliable,
+}
+
+/// Settings of connections.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetConfig {
+    /// Unique id of the game protocol. Endpoints with different ids can't connect to each other, it
+    /// prevents accidental connection of incompatible versions of a game.
+    pub protocol_id: u32,
+    /// Amount of time (in seconds) without any incoming packets after which the connection is
+    /// considered lost.
+    pub timeout: f32,
+    /// Amount of time (in seconds) after which an empty packet is sent to keep the connection alive.
+    /// It is also used as an interval between connection attempts.
+    pub heartbeat_interval: f32,
+    /// Amount of time (in seconds) after which an unacknowledged reliable message is sent again.
+    pub resend_interval: f32,
+    /// Desired maximum size of a packet in bytes. Messages are packed into packets up to this size,
+    /// larger reliable messages are split into fragments. Unreliable messages, that are larger than
+    /// this, are sent in separate packets, but they must fit into a single datagram.
+    pub max_packet_size: usize,
+    /// Maximum size of a message in bytes. Larger messages are rejected by the sender and
+    /// discarded by the receiver.
+    pub max_message_size: usize,
+    /// Maximum amount of reliable messages (or fragments), that could be sent without
+    /// acknowledgement. The receiver drops the messages, that are too far ahead of the expected
+    /// one, so memory usage is bounded on both sides.
+    pub window_size: u32,
+    /// Maximum amount of clients, that could be connected to a server.
+    pub max_clients: usize,
+}
+
+impl Default for NetConfig {
+    fn default() -> Self {
+        Self {
+            protocol_id: 0,
+            timeout: 10.0,
+            heartbeat_interval: 0.25,
+            resend_interval: 0.2,
+            max_packet_size: 1200,
+            max_message_size: 16 * 1024 * 1024,
+            window_size: 256,
+            max_clients: 32,
+        }
+    }
+}
+
+/// A datagram, that is sent between endpoints.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Packet {
+    Connect {
+        protocol_id: u32,
+    },
+    Accept {
+        client_id: ClientId,
+    },
+    Deny {
+        reason: String,
+    },
+    Disconnect,
+    Payload {
+        acks: Vec<u32>,
+        reliable: Vec<ReliableMessage>,
+        unreliable: Vec<Vec<u8>>,
+    },
+}
+
+/// A reliable message or a fragment of it.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ReliableMessage {
+    pub(crate) id: u32,
+    /// `true` if the message is a fragment and the next message continues it.
+    pub(crate) more_fragments: bool,
+    pub(crate) data: Vec<u8>,
+}
+
+/// An error, that may occur when a message is queued for sending.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum SendError {
+    /// The message is larger than the maximum size.
+    MessageTooLarge { size: usize, max_size: usize },
+}
+
+impl Display for SendError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendError::MessageTooLarge { size, max_size } => write!(
+                f,
+                "The message is too large ({size} bytes), maximum size is {max_size} bytes."
+            ),
+        }
+    }
+}
+
+impl Packet {
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("packet serialization must not fail")
+    }
+
+    pub(crate) fn decode(data: &[u8]) -> Option<Self> {
+        bincode::deserialize(data).ok()
+    }
+}
+
+struct PendingMessage {
+    data: Vec<u8>,
+    more_fragments: bool,
+    // `None` if the message wasn't sent yet.
+    time_since_sent: Option<f32>,
+}
+
+/// Approximate overhead of a message in a packet (length prefix, id and fragment flag).
+const MESSAGE_OVERHEAD: usize = 13;
+/// Approximate overhead of a packet (packet kind and lengths of message lists).
+const PACKET_OVERHEAD: usize = 32;
+
+fn max_fragment_size(config: &NetConfig) -> usize {
+    config
+        .max_packet_size
+        .min(MAX_DATAGRAM_SIZE)
+        .saturating_sub(PACKET_OVERHEAD + MESSAGE_OVERHEAD)
+        .max(1)
+}
+
+/// State of a connection with a remote endpoint.
+pub(crate) struct Connection {
+    next_reliable_id: u32,
+    unacked: BTreeMap<u32, PendingMessage>,
+    next_expected_id: u32,
+    out_of_order: BTreeMap<u32, ReliableMessage>,
+    // Fragments of the message, that is being received.
+    fragments: Vec<u8>,
+    // `true` if the fragments of the current message are discarded, because it is too large.
+    discard_fragments: bool,
+    pending_acks: Vec<u32>,
+    unreliable: Vec<Vec<u8>>,
+    time_since_received: f32,
+    time_since_sent: f32,
+}
+
+impl Default for Connection {
+    fn default() -> Self {
+        Self {
+            next_reliable_id: 0,
+            unacked: Default::default(),
+            next_expected_id: 0,
+            out_of_order: Default::default(),
+            fragments: Default::default(),
+            discard_fragments: false,
+            pending_acks: Default::default(),
+            unreliable: Default::default(),
+            time_since_received: 0.0,
+            time_since_sent: 0.0,
+        }
+    }
+}
+
+impl Connection {
+    /// Queues a message for sending. Reliable messages, that do not fit into a single packet, are
+    /// split into fragments. Unreliable messages could not be fragmented, so they must fit into
+    /// a single datagram.
+    pub(crate) fn send(
+        &mut self,
+        channel: Channel,
+        data: Vec<u8>,
+        config: &NetConfig,
+    ) -> Result<(), SendError> {
+        let max_size = match channel {
+            Channel::Reliable => config.max_message_size,
+            Channel::Unreliable => config
+                .max_message_size
+                .min(MAX_DATAGRAM_SIZE - PACKET_OVERHEAD - MESSAGE_OVERHEAD),
+        };
+        if data.len() > max_size {
+            return Err(SendError::MessageTooLarge {
+                size: data.len(),
+                max_size,
+            });
+        }
+
+        match channel {
+            Channel::Reliable => {
+                let fragment_size = max_fragment_size(config);
+                let mut push = |data: Vec<u8>, more_fragments| {
+                    let id = self.next_reliable_id;
+                    self.next_reliable_id = self.next_reliable_id.wrapping_add(1);
+                    self.unacked.insert(
+                        id,
+                        PendingMessage {
+                            data,
+                            more_fragments,
+                            time_since_sent: None,
+                        },
+                    );
+                };
+                if data.len() <= fragment_size {
+                    push(data, false);
+                } else {
+                    let count = data.len().div_ceil(fragment_size);
+                    for (i, fragment) in data.chunks(fragment_size).enumerate() {
+                        push(fragment.to_vec(), i + 1 < count);
+                    }
+                }
+            }
+            Channel::Unreliable => self.unreliable.push(data),
+        }
+
+        Ok(())
+    }
+
+    /// Returns amount of reliable messages, that were not acknowledged yet.
+    pub(crate) fn unacked_count(&self) -> usize {
+        self.unacked.len()
+    }
+
+    pub(crate) fn update(&mut self, dt: f32) {
+        self.time_since_received += dt;
+        self.time_since_sent += dt;
+        for message in self.unacked.values_mut() {
+            if let Some(time) = message.time_since_sent.as_mut() {
+                *time += dt;
+            }
+        }
+    }
+
+    pub(crate) fn is_timed_out(&self, config: &NetConfig) -> bool {
+        self.time_since_received > config.timeout
+    }
+
+    /// Must be called for every packet received from the remote endpoint.
+    pub(crate) fn mark_received(&mut self) {
+        self.time_since_received = 0.0;
+    }
+
+    /// Processes the payload of a packet and returns messages, that are ready to be delivered.
+    pub(crate) fn receive(
+        &mut self,
+        acks: Vec<u32>,
+        reliable: Vec<ReliableMessage>,
+        unreliable: Vec<Vec<u8>>,
+        config: &NetConfig,
+    ) -> Vec<(Channel, Vec<u8>)> {
+        self.mark_received();
+
+        for ack in acks {
+            self.unacked.remove(&ack);
+        }
+
+        let mut messages = Vec::new();
+
+        for message in reliable {
+            // Wrapping distance from the next expected id, old messages are far "ahead" of it.
+            let distance = message.id.wrapping_sub(self.next_expected_id);
+            if distance >= u32::MAX / 2 {
+                // Acknowledge duplicates again - the previous ack could be lost.
+                self.pending_acks.push(message.id);
+            } else if distance < config.window_size {
+                self.pending_acks.push(message.id);
+                self.out_of_order.entry(message.id).or_insert(message);
+            }
+            // Messages outside of the receive window are dropped without acknowledgement, the
+            // sender will resend them later.
+        }
+
+        while let Some(message) = self.out_of_order.remove(&self.next_expected_id) {
+            self.next_expected_id = self.next_expected_id.wrapping_add(1);
+
+            if self.discard_fragments
+                || self.fragments.len() + message.data.len() > config.max_message_size
+            {
+                self.fragments.clear();
+                self.discard_fragments = message.more_fragments;
+                continue;
+            }
+
+            if message.more_fragments {
+                self.fragments.extend_from_slice(&message.data);
+            } else if self.fragments.is_empty() {
+                messages.push((Channel::Reliable, message.data));
+            } else {
+                self.fragments.extend_from_slice(&message.data);
+                messages.push((Channel::Reliable, std::mem::take(&mut self.fragments)));
+            }
+        }
+
+        messages.extend(
+            unreliable
+                .into_iter()
+                .map(|data| (Channel::Unreliable, data)),
+        );
+
+        messages
+    }
+
+    /// Packs every pending message into packets. Only the reliable messages within the send window
+    /// (starting from the oldest unacknowledged one) are sent. Returns an empty packet if there's
+    /// nothing to send, but the heartbeat interval has passed.
+    pub(crate) fn flush(&mut self, config: &NetConfig) -> Vec<Packet> {
+        let mut packets = Vec::new();
+        let mut acks = std::mem::take(&mut self.pending_acks);
+        let mut reliable = Vec::new();
+        let mut unreliable = Vec::new();
+        let mut size = acks.len() * 4;
+
+        let push = |packets: &mut Vec<Packet>,
+                    acks: &mut Vec<u32>,
+                    reliable: &mut Vec<ReliableMessage>,
+                    unreliable: &mut Vec<Vec<u8>>,
+                    size: &mut usize| {
+            packets.push(Packet::Payload {
+                acks: std::mem::take(acks),
+                reliable: std::mem::take(reliable),
+                unreliable: std::mem::take(unreliable),
+            });
+            *size = 0;
+        };
+
+        // Ids, that are not less than the next id, were sent before the id counter wrapped around.
+        let oldest_unacked = self
+            .unacked
+            .range(self.next_reliable_id..)
+            .chain(self.unacked.iter())
+            .next()
+            .map(|(id, _)| *id)
+            .unwrap_or(self.next_reliable_id);
+        for offset in 0..config.window_size {
+            let id = oldest_unacked.wrapping_add(offset);
+            if id == self.next_reliable_id {
+                break;
+            }
+            let Some(message) = self.unacked.get_mut(&id) else {
+                continue;
+            };
+
+            if message
+                .time_since_sent
+                .is_some_and(|time| time < config.resend_interval)
+            {
+                continue;
+            }
+
+            let message_size = message.data.len() + MESSAGE_OVERHEAD;
+            if size > 0 && PACKET_OVERHEAD + size + message_size > config.max_packet_size {
+                push(
+                    &mut packets,
+                    &mut acks,
+                    &mut reliable,
+                    &mut unreliable,
+                    &mut size,
+                );
+            }
+            size += message_size;
+            reliable.push(ReliableMessage {
+                id,
+                more_fragments: message.more_fragments,
+                data: message.data.clone(),
+            });
+            message.time_since_sent = Some(0.0);
+        }
+
+        for data in self.unreliable.drain(..) {
+            let message_size = data.len() + MESSAGE_OVERHEAD;
+            if size > 0 && PACKET_OVERHEAD + size + message_size > config.max_packet_size {
+                push(
+                    &mut packets,
+                    &mut acks,
+                    &mut reliable,
+                    &mut unreliable,
+                    &mut size,
+                );
+            }
+            size += message_size;
+            unreliable.push(data);
+        }
+
+        if !acks.is_empty()
+            || !reliable.is_empty()
+            || !unreliable.is_empty()
+            || (packets.is_empty() && self.time_since_sent >= config.heartbeat_interval)
+        {
+            push(
+                &mut packets,
+                &mut acks,
+                &mut reliable,
+                &mut unreliable,
+                &mut size,
+            );
+        }
+
+        if !packets.is_empty() {
+            self.time_since_sent = 0.0;
+        }
+
+        packets
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::connection::{Channel, Connection, NetConfig, Packet, ReliableMessage, SendError};
+
+    fn deliver(packets: Vec<Packet>, to: &mut Connection, config: &NetConfig) -> Vec<Vec<u8>> {
+        let mut messages = Vec::new();
+        for packet in packets {
+            if let Packet::Payload {
+                acks,
+                reliable,
+                unreliable,
+            } = Packet::decode(&packet.encode()).unwrap()
+            {
+                messages.extend(
+                    to.receive(acks, reliable, unreliable, config)
+                        .into_iter()
+                        .map(|(_, data)| data),
+                );
+            }
+        }
+        messages
+    }
+
+    #[test]
+    fn test_reliable_delivery() {
+        let config = NetConfig::default();
+        let mut a = Connection::default();
+        let mut b = Connection::default();
+
+        a.send(Channel::Reliable, vec![1], &config).unwrap();
+        a.send(Channel::Reliable, vec![2], &config).unwrap();
+        a.send(Channel::Unreliable, vec![3], &config).unwrap();
+
+        // The first packet is lost, unreliable message is lost forever.
+        let lost = a.flush(&config);
+        assert_eq!(lost.len(), 1);
+
+        a.send(Channel::Reliable, vec![4], &config).unwrap();
+        // Only the new message is sent, the other ones wait for resend.
+        let packets = a.flush(&config);
+        // The message can't be delivered until the lost ones are received.
+        assert!(deliver(packets, &mut b, &config).is_empty());
+
+        a.update(config.resend_interval);
+        let packets = a.flush(&config);
+        assert_eq!(
+            deliver(packets, &mut b, &config),
+            vec![vec![1], vec![2], vec![4]]
+        );
+
+        // Acks are sent back and the messages are not resent anymore.
+        let acks = b.flush(&config);
+        deliver(acks, &mut a, &config);
+        assert_eq!(a.unacked_count(), 0);
+        a.update(config.resend_interval);
+        assert!(a.flush(&config).is_empty());
+    }
+
+    #[test]
+    fn test_packet_splitting() {
+        let config = NetConfig {
+            max_packet_size: 100,
+            ..Default::default()
+        };
+        let mut a = Connection::default();
+        let mut b = Connection::default();
+        for i in 0..10 {
+            a.send(Channel::Reliable, vec![i; 50], &config).unwrap();
+        }
+        let packets = a.flush(&config);
+        assert_eq!(packets.len(), 10);
+        assert_eq!(deliver(packets, &mut b, &config).len(), 10);
+    }
+
+    #[test]
+    fn test_fragmentation() {
+        let config = NetConfig {
+            max_packet_size: 100,
+            ..Default::default()
+        };
+        let mut a = Connection::default();
+        let mut b = Connection::default();
+
+        let large = (0..1000).map(|i| i as u8).collect::<Vec<_>>();
+        a.send(Channel::Reliable, large.clone(), &config).unwrap();
+        a.send(Channel::Reliable, vec![1], &config).unwrap();
+        assert!(a.unacked_count() > 2);
+
+        let packets = a.flush(&config);
+        for packet in packets.iter() {
+            assert!(packet.encode().len() <= config.max_packet_size);
+        }
+        assert_eq!(deliver(packets, &mut b, &config), vec![large, vec![1]]);
+
+        // Unreliable messages can't be fragmented, too large ones are rejected.
+        assert!(matches!(
+            a.send(Channel::Unreliable, vec![0; 70000], &config),
+            Err(SendError::MessageTooLarge { .. })
+        ));
+        let config = NetConfig {
+            max_message_size: 10,
+            ..config
+        };
+        assert_eq!(
+            a.send(Channel::Reliable, vec![0; 11], &config),
+            Err(SendError::MessageTooLarge {
+                size: 11,
+                max_size: 10
+            })
+        );
+    }
+
+    #[test]
+    fn test_windows() {
+        let config = NetConfig {
+            window_size: 4,
+            ..Default::default()
+        };
+        let mut a = Connection::default();
+        let mut b = Connection::default();
+        for i in 0..10 {
+            a.send(Channel::Reliable, vec![i], &config).unwrap();
+        }
+
+        // Only the messages within the send window are sent.
+        let packets = a.flush(&config);
+        assert_eq!(
+            deliver(packets, &mut b, &config),
+            vec![vec![0], vec![1], vec![2], vec![3]]
+        );
+        assert!(a.flush(&config).is_empty());
+
+        deliver(b.flush(&config), &mut a, &config);
+        assert_eq!(a.unacked_count(), 6);
+        let packets = a.flush(&config);
+        assert_eq!(
+            deliver(packets, &mut b, &config),
+            vec![vec![4], vec![5], vec![6], vec![7]]
+        );
+
+        // Messages too far ahead of the expected one are dropped and not acknowledged.
+        assert!(b
+            .receive(
+                Vec::new(),
+                vec![ReliableMessage {
+                    id: u32::MAX / 4,
+                    more_fragments: false,
+                    data: vec![0],
+                }],
+                Vec::new(),
+                &config,
+            )
+            .is_empty());
+        assert!(b.out_of_order.is_empty());
+        assert!(!b.pending_acks.contains(&(u32::MAX / 4)));
+    }
+}
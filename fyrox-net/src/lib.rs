@@ -0,0 +1,240 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! High-level client/server networking for the Fyrox engine.
+//!
+//! The crate consists of several layers:
+//!
+//! - [`transport`] - low-level datagram delivery. [`transport::UdpTransport`] is used by default,
+//! [`transport::LocalNetwork`] could be used for tests and for games, that run a server and a
+//! client in the same process.
+//! - [`websocket`] - WebSocket transports, that allow web builds to connect to servers.
+//! - [`connection`] - reliable and unreliable channels on top of a transport.
+//! - [`server`] and [`client`] - connection management: handshakes, timeouts, disconnections.
+//! - [`replication`] - replication of scene nodes and their scripts with authority rules.
+//! - [`rpc`] - remote procedure calls, that are delivered to scripts as script messages.
+//! - [`snapshot`] - interpolation of received states of remote entities.
+//! - [`prediction`] - client-side prediction and server reconciliation for locally controlled
+//! entities.
+//!
+//! Nothing in the crate spawns threads, every endpoint should be updated from the game loop (for
+//! example, in `Plugin::update`).
+
+#![warn(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+pub mod client;
+pub mod connection;
+pub mod prediction;
+pub mod replication;
+pub mod rpc;
+pub mod server;
+pub mod snapshot;
+pub mod transport;
+pub mod websocket;
+
+/// Unique id of a client, assigned by a server on connection.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct ClientId(pub u64);
+
+impl Display for ClientId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Every message sent by a client or a server is wrapped into an envelope, that allows to
+/// distinguish user messages from internal ones.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Envelope {
+    User(Vec<u8>),
+    Replication(replication::ReplicationMessage),
+}
+
+impl Envelope {
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("envelope serialization must not fail")
+    }
+
+    pub(crate) fn decode(data: &[u8]) -> Option<Self> {
+        bincode::deserialize(data).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        client::{Client, ClientEvent, ClientState},
+        connection::{Channel, NetConfig},
+        replication::{Authority, NetworkId, Replicator},
+        server::{Server, ServerEvent},
+        transport::LocalNetwork,
+        ClientId,
+    };
+    use fyrox::{
+        asset::manager::ResourceManager,
+        core::{algebra::Vector3, pool::Handle, task::TaskPool},
+        scene::{base::BaseBuilder, node::Node, pivot::PivotBuilder, Scene},
+        script::ScriptMessageSender,
+    };
+    use serde::{Deserialize, Serialize};
+    use std::sync::{mpsc::channel, Arc};
+
+    const DT: f32 = 1.0 / 60.0;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Chat(String);
+
+    #[test]
+    fn test_connection_and_messages() {
+        let network = LocalNetwork::default();
+        let mut server = Server::new(network.endpoint("server"), NetConfig::default());
+        let server_address = server.transport().address();
+        let mut client = Client::new(
+            network.endpoint("client"),
+            server_address.clone(),
+            NetConfig::default(),
+        );
+
+        assert!(client.update(DT).is_empty());
+        assert_eq!(
+            server.update(DT),
+            vec![ServerEvent::ClientConnected(ClientId(0))]
+        );
+        assert_eq!(client.update(DT), vec![ClientEvent::Connected(ClientId(0))]);
+
+        client.send_message(Channel::Reliable, &Chat("Hello".to_string()));
+        client.flush();
+        let events = server.update(DT);
+        let Some(ServerEvent::Message {
+            client: id, data, ..
+        }) = events.first()
+        else {
+            panic!("Message expected!");
+        };
+        assert_eq!(*id, ClientId(0));
+        assert_eq!(
+            bincode::deserialize::<Chat>(data).unwrap(),
+            Chat("Hello".to_string())
+        );
+
+        // Incompatible clients are denied.
+        let mut other = Client::new(
+            network.endpoint("other"),
+            server_address,
+            NetConfig {
+                protocol_id: 1,
+                ..Default::default()
+            },
+        );
+        other.update(DT);
+        server.update(DT);
+        assert!(matches!(other.update(DT)[..], [ClientEvent::Denied(_)]));
+        assert_eq!(other.state(), ClientState::Disconnected);
+
+        client.disconnect();
+        assert_eq!(
+            server.update(DT),
+            vec![ServerEvent::ClientDisconnected(ClientId(0))]
+        );
+    }
+
+    fn add_node(scene: &mut Scene) -> Handle<Node> {
+        PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph)
+    }
+
+    #[test]
+    fn test_replication() {
+        let network = LocalNetwork::default();
+        let mut server = Server::new(network.endpoint("server"), NetConfig::default());
+        let mut client = Client::new(
+            network.endpoint("client"),
+            server.transport().address(),
+            NetConfig::default(),
+        );
+        let (sender, _receiver) = channel();
+        let message_sender = ScriptMessageSender::new(sender);
+        let resource_manager = ResourceManager::new(Arc::new(TaskPool::new()));
+
+        let mut server_scene = Scene::new();
+        let mut client_scene = Scene::new();
+        let mut server_replicator = Replicator::server();
+        let mut client_replicator = Replicator::client();
+
+        let server_node = add_node(&mut server_scene);
+        let server_player = add_node(&mut server_scene);
+        server_replicator.register(NetworkId(0), server_node, Authority::Server);
+        server_replicator.register(NetworkId(1), server_player, Authority::Client(ClientId(0)));
+        let client_node = add_node(&mut client_scene);
+        let client_player = add_node(&mut client_scene);
+        client_replicator.register(NetworkId(0), client_node, Authority::Server);
+        client_replicator.register(NetworkId(1), client_player, Authority::Client(ClientId(0)));
+
+        server_scene.graph[server_node]
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 2.0, 3.0));
+        client_scene.graph[client_player]
+            .local_transform_mut()
+            .set_position(Vector3::new(4.0, 5.0, 6.0));
+        // The client can't move nodes owned by the server.
+        client_scene.graph[client_node]
+            .local_transform_mut()
+            .set_position(Vector3::new(-1.0, -1.0, -1.0));
+
+        for _ in 0..10 {
+            for event in server.update(DT) {
+                server_replicator.handle_server_event(
+                    &event,
+                    &mut server,
+                    &mut server_scene,
+                    &message_sender,
+                );
+            }
+            server_replicator.update_server(&mut server, &mut server_scene, 0.1);
+            server.flush();
+
+            for event in client.update(DT) {
+                client_replicator.handle_client_event(
+                    &event,
+                    &mut client_scene,
+                    &resource_manager,
+                    &message_sender,
+                );
+            }
+            client_replicator.update_client(&mut client, &mut client_scene, 0.1);
+            client.flush();
+        }
+
+        assert_eq!(
+            **client_scene.graph[client_node].local_transform().position(),
+            Vector3::new(1.0, 2.0, 3.0)
+        );
+        assert_eq!(
+            **server_scene.graph[server_player]
+                .local_transform()
+                .position(),
+            Vector3::new(4.0, 5.0, 6.0)
+        );
+    }
+}
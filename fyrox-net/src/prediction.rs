@@ -0,0 +1,349 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Client-side prediction and server reconciliation. A locally controlled entity is simulated on
+//! the client immediately, without waiting for the server response, and corrected when an
+//! authoritative state arrives. See [`Predictor`] docs for more info.
+
+use crate::snapshot::Interpolate;
+use fyrox::core::algebra::Vector3;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Input of a single simulation step, numbered by its sequence number.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputFrame<I> {
+    /// Sequence number of the input.
+    pub sequence: u32,
+    /// Time step of the simulation.
+    pub dt: f32,
+    /// User-defined input.
+    pub input: I,
+}
+
+/// A state of a predicted entity.
+pub trait PredictedState: Interpolate {
+    /// Returns a distance between two states. It is used to decide whether the visual state should
+    /// smoothly move to the corrected state or jump to it.
+    fn distance(&self, other: &Self) -> f32;
+}
+
+impl PredictedState for f32 {
+    fn distance(&self, other: &Self) -> f32 {
+        (self - other).abs()
+    }
+}
+
+impl PredictedState for Vector3<f32> {
+    fn distance(&self, other: &Self) -> f32 {
+        self.metric_distance(other)
+    }
+}
+
+/// Predictor runs the prediction/reconciliation loop for a locally controlled entity:
+///
+/// 1. Every simulation step, the client records its input using [`Self::record`], simulates the
+///    step locally and sends the input to the server. Inputs should be sent over unreliable
+///    channel, it is a good idea to send every unacknowledged input ([`Self::unacknowledged`])
+///    every time, so lost inputs are recovered with the next packet.
+/// 2. The server applies the inputs in order (see [`InputBuffer`]) and sends back the
+///    authoritative state along with the sequence number of the last applied input.
+/// 3. The client calls [`Self::reconcile`], which replaces the local state with the authoritative
+///    one and replays every input, that was not applied by the server yet.
+/// 4. The state, that should be displayed, is fetched using [`Self::visual_state`]. It smoothly
+///    moves to the corrected state, instead of jumping to it.
+///
+/// ```rust
+/// # use fyrox_net::prediction::Predictor;
+/// fn simulate(position: &mut f32, velocity: &f32, dt: f32) {
+///     *position += velocity * dt;
+/// }
+///
+/// let mut predictor = Predictor::default();
+/// let mut position = 0.0f32;
+///
+/// // Client moves ahead of the server.
+/// for _ in 0..3 {
+///     let frame = predictor.record(1.0f32, 1.0);
+///     simulate(&mut position, &frame.input, frame.dt);
+/// }
+/// assert_eq!(position, 3.0);
+///
+/// // The server has applied only the first input, and the result was a bit different.
+/// predictor.reconcile(0, 0.5, &mut position, simulate);
+/// assert_eq!(position, 2.5);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Predictor<I, S> {
+    next_sequence: u32,
+    pending: VecDeque<InputFrame<I>>,
+    visual: Option<S>,
+    correction_time: f32,
+    /// Maximum amount of unacknowledged inputs. Older inputs are discarded.
+    pub max_pending: usize,
+    /// Corrections with the distance larger than this value are applied immediately.
+    pub snap_distance: f32,
+    /// Corrections with the distance smaller than this value are ignored.
+    pub min_correction: f32,
+    /// Duration (in seconds) of smoothing of a correction.
+    pub smoothing_time: f32,
+    /// How fast the visual state moves to the corrected state during smoothing (fraction of the
+    /// distance per second).
+    pub smoothing_rate: f32,
+}
+
+impl<I, S> Default for Predictor<I, S> {
+    fn default() -> Self {
+        Self {
+            next_sequence: 0,
+            pending: Default::default(),
+            visual: None,
+            correction_time: 0.0,
+            max_pending: 256,
+            snap_distance: 5.0,
+            min_correction: 0.001,
+            smoothing_time: 0.25,
+            smoothing_rate: 15.0,
+        }
+    }
+}
+
+impl<I: Clone, S: PredictedState> Predictor<I, S> {
+    /// Records an input of the next simulation step and returns a frame, that should be sent to
+    /// the server.
+    pub fn record(&mut self, input: I, dt: f32) -> InputFrame<I> {
+        let frame = InputFrame {
+            sequence: self.next_sequence,
+            dt,
+            input,
+        };
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        self.pending.push_back(frame.clone());
+        while self.pending.len() > self.max_pending {
+            self.pending.pop_front();
+        }
+        frame
+    }
+
+    /// Returns every input, that was not acknowledged by the server yet.
+    pub fn unacknowledged(&self) -> impl Iterator<Item = &InputFrame<I>> {
+        self.pending.iter()
+    }
+
+    /// Replaces the state with the authoritative one received from the server and replays every
+    /// input, that was recorded after the input with `acknowledged` sequence number. Returns the
+    /// distance between the predicted and the corrected states (prediction error).
+    pub fn reconcile<F>(
+        &mut self,
+        acknowledged: u32,
+        authoritative: S,
+        state: &mut S,
+        mut simulate: F,
+    ) -> f32
+    where
+        F: FnMut(&mut S, &I, f32),
+    {
+        while self.pending.front().map_or(false, |frame| {
+            // Wrapping comparison, `frame.sequence <= acknowledged`.
+            acknowledged.wrapping_sub(frame.sequence) < u32::MAX / 2
+        }) {
+            self.pending.pop_front();
+        }
+
+        let mut corrected = authoritative;
+        for frame in self.pending.iter() {
+            simulate(&mut corrected, &frame.input, frame.dt);
+        }
+
+        let error = state.distance(&corrected);
+        if error > self.min_correction {
+            if error > self.snap_distance {
+                self.visual = None;
+                self.correction_time = 0.0;
+            } else {
+                // Keep displaying the old state and smooth the difference out.
+                if self.visual.is_none() {
+                    self.visual = Some(state.clone());
+                }
+                self.correction_time = self.smoothing_time;
+            }
+            *state = corrected;
+        }
+
+        error
+    }
+
+    /// Returns a state, that should be displayed. It is equal to the given (simulated) state, unless
+    /// a correction is being smoothed. Should be called once per frame.
+    pub fn visual_state(&mut self, state: &S, dt: f32) -> S {
+        if self.correction_time <= 0.0 {
+            self.visual = None;
+            return state.clone();
+        }
+
+        self.correction_time -= dt;
+        let visual = match self.visual.take() {
+            Some(visual) => visual.interpolate(state, (dt * self.smoothing_rate).min(1.0)),
+            None => state.clone(),
+        };
+        self.visual = Some(visual.clone());
+        visual
+    }
+
+    /// Discards every pending input and the correction.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.visual = None;
+        self.correction_time = 0.0;
+    }
+}
+
+/// Input buffer is used on the server to apply inputs of a client in order. Inputs could arrive
+/// out of order or several times (if the client resends unacknowledged inputs), the buffer filters
+/// out duplicates and inputs, that were already applied.
+#[derive(Clone, Debug)]
+pub struct InputBuffer<I> {
+    frames: BTreeMap<u32, InputFrame<I>>,
+    last_applied: Option<u32>,
+    /// Maximum amount of buffered inputs. If the buffer is full, the oldest inputs are applied
+    /// (or skipped) to catch up.
+    pub capacity: usize,
+}
+
+impl<I> Default for InputBuffer<I> {
+    fn default() -> Self {
+        Self {
+            frames: Default::default(),
+            last_applied: None,
+            capacity: 64,
+        }
+    }
+}
+
+impl<I> InputBuffer<I> {
+    /// Adds an input frame to the buffer.
+    pub fn push(&mut self, frame: InputFrame<I>) {
+        if let Some(last_applied) = self.last_applied {
+            if frame.sequence.wrapping_sub(last_applied).wrapping_sub(1) >= u32::MAX / 2 {
+                // Already applied.
+                return;
+            }
+        }
+        self.frames.entry(frame.sequence).or_insert(frame);
+    }
+
+    /// Returns the next input, that should be applied. Inputs are returned strictly in order, if
+    /// an input is missing, the buffer waits for it until the buffer is full.
+    pub fn pop(&mut self) -> Option<InputFrame<I>> {
+        let next = self.last_applied.map(|last| last.wrapping_add(1));
+        let key = match next {
+            Some(next) if self.frames.contains_key(&next) => next,
+            Some(_) if self.frames.len() < self.capacity => return None,
+            _ => *self.frames.keys().next()?,
+        };
+        let frame = self.frames.remove(&key)?;
+        self.last_applied = Some(frame.sequence);
+        Some(frame)
+    }
+
+    /// Returns the sequence number of the last applied input. It should be sent to the client
+    /// along with the authoritative state.
+    pub fn last_applied(&self) -> Option<u32> {
+        self.last_applied
+    }
+
+    /// Returns amount of buffered inputs.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if there's no buffered inputs.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prediction::{InputBuffer, InputFrame, Predictor};
+
+    fn simulate(position: &mut f32, velocity: &f32, dt: f32) {
+        *position += velocity * dt;
+    }
+
+    fn frame(sequence: u32) -> InputFrame<f32> {
+        InputFrame {
+            sequence,
+            dt: 1.0,
+            input: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_prediction_and_smoothing() {
+        let mut predictor = Predictor::default();
+        let mut position = 0.0f32;
+        for _ in 0..4 {
+            let frame = predictor.record(1.0, 1.0);
+            simulate(&mut position, &frame.input, frame.dt);
+        }
+
+        // Correct prediction - nothing changes.
+        assert_eq!(predictor.reconcile(1, 2.0, &mut position, simulate), 0.0);
+        assert_eq!(predictor.unacknowledged().count(), 2);
+        assert_eq!(predictor.visual_state(&position, 0.1), 4.0);
+
+        // Small error is smoothed out.
+        let error = predictor.reconcile(2, 2.5, &mut position, simulate);
+        assert_eq!(error, 0.5);
+        assert_eq!(position, 3.5);
+        let visual = predictor.visual_state(&position, 0.01);
+        assert!(visual > 3.5 && visual < 4.0);
+        for _ in 0..100 {
+            predictor.visual_state(&position, 0.01);
+        }
+        assert_eq!(predictor.visual_state(&position, 0.01), 3.5);
+
+        // Large error is applied immediately.
+        predictor.reconcile(3, 100.0, &mut position, simulate);
+        assert_eq!(predictor.visual_state(&position, 0.01), 100.0);
+    }
+
+    #[test]
+    fn test_input_buffer() {
+        let mut buffer = InputBuffer::default();
+        buffer.push(frame(1));
+        buffer.push(frame(0));
+        buffer.push(frame(1));
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.pop().unwrap().sequence, 0);
+        assert_eq!(buffer.pop().unwrap().sequence, 1);
+        assert!(buffer.pop().is_none());
+
+        // Already applied inputs are ignored, missing inputs are awaited.
+        buffer.push(frame(0));
+        buffer.push(frame(3));
+        assert_eq!(buffer.len(), 1);
+        assert!(buffer.pop().is_none());
+        buffer.push(frame(2));
+        assert_eq!(buffer.pop().unwrap().sequence, 2);
+        assert_eq!(buffer.last_applied(), Some(2));
+    }
+}
@@ -0,0 +1,873 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Replication keeps the state of scene nodes in sync between a server and its clients. See
+//! [`Replicator`] docs for more info.
+
+use crate::{
+    client::{Client, ClientEvent},
+    connection::Channel,
+    rpc::{RemoteCall, RpcRegistry},
+    server::{Server, ServerEvent},
+    snapshot::SnapshotBuffer,
+    transport::Transport,
+    ClientId,
+};
+use fyrox::{
+    asset::manager::ResourceManager,
+    core::{
+        algebra::{Quaternion, UnitQuaternion, Vector3},
+        log::Log,
+        pool::Handle,
+        uuid::Uuid,
+        visitor::{Visit, Visitor},
+    },
+    graph::BaseSceneGraph,
+    resource::model::{Model, ModelResource, ModelResourceExtension},
+    scene::{node::Node, Scene},
+    script::{ScriptMessage, ScriptMessageKind, ScriptMessageSender},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// Unique id of a replicated node. Handles can't be used to identify nodes, because they differ on
+/// every endpoint.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct NetworkId(pub u64);
+
+/// Ids of nodes spawned by [`Replicator::spawn`] start from this value, lower ids could be used for
+/// nodes registered manually by [`Replicator::register`].
+pub const FIRST_DYNAMIC_ID: NetworkId = NetworkId(1 << 32);
+
+/// Defines which endpoint is allowed to change the state of a replicated node. Other endpoints
+/// receive the state and can't change it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Authority {
+    /// The server owns the node.
+    #[default]
+    Server,
+    /// The client with the given id owns the node. It is typically used for characters controlled
+    /// by players.
+    Client(ClientId),
+}
+
+/// Replicated state of a node.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeState {
+    /// Local position of the node.
+    pub position: Option<[f32; 3]>,
+    /// Local rotation of the node (quaternion coordinates).
+    pub rotation: Option<[f32; 4]>,
+    /// Local scale of the node.
+    pub scale: Option<[f32; 3]>,
+    /// Serialized scripts of the node.
+    pub scripts: Vec<Vec<u8>>,
+}
+
+/// A message, that is used by replicators to communicate.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplicationMessage {
+    /// A node was spawned on the server using the given prefab.
+    Spawn {
+        /// Id of the node.
+        id: NetworkId,
+        /// Path of the prefab.
+        prefab: PathBuf,
+        /// Authority of the node.
+        authority: Authority,
+    },
+    /// A node was removed.
+    Despawn {
+        /// Id of the node.
+        id: NetworkId,
+    },
+    /// Authority of a node has changed.
+    Authority {
+        /// Id of the node.
+        id: NetworkId,
+        /// New authority.
+        authority: Authority,
+    },
+    /// New state of a node.
+    State {
+        /// Id of the node.
+        id: NetworkId,
+        /// Time of the state on the endpoint with authority.
+        time: f32,
+        /// The state.
+        state: NodeState,
+    },
+    /// A remote procedure call on the scripts of a node.
+    Rpc {
+        /// Id of the node.
+        id: NetworkId,
+        /// Type UUID of the payload, see [`RemoteCall`].
+        call: Uuid,
+        /// Serialized payload.
+        data: Vec<u8>,
+    },
+}
+
+/// Role of a replicator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReplicationRole {
+    /// The replicator works on a server.
+    Server,
+    /// The replicator works on a client.
+    Client,
+}
+
+/// A node, that is replicated over the network.
+#[derive(Clone, Debug)]
+pub struct ReplicatedNode {
+    /// Handle of the node in the local scene.
+    pub node: Handle<Node>,
+    /// Authority of the node.
+    pub authority: Authority,
+    /// Path of a prefab, that was used to spawn the node. `None` for nodes registered manually.
+    pub prefab: Option<PathBuf>,
+    /// Whether the local transform of the node should be replicated or not. Default is `true`.
+    pub replicate_transform: bool,
+    /// Whether the scripts of the node should be replicated or not. Default is `false`. Scripts are
+    /// serialized entirely, so they should not contain any data, that is meaningful only for the
+    /// local endpoint (handles of other nodes, for instance).
+    pub replicate_scripts: bool,
+    /// Whether the received transform should be interpolated or applied immediately. See
+    /// [`SnapshotBuffer`] docs for more info. Default value is defined by
+    /// [`Replicator::interpolation`].
+    pub interpolate: bool,
+    /// Received states of the node, that are used for interpolation.
+    pub snapshots: SnapshotBuffer<NodeState>,
+    last_state: Option<NodeState>,
+    time_since_sent: f32,
+}
+
+impl ReplicatedNode {
+    fn new(
+        node: Handle<Node>,
+        authority: Authority,
+        prefab: Option<PathBuf>,
+        interpolate: bool,
+    ) -> Self {
+        Self {
+            node,
+            authority,
+            prefab,
+            replicate_transform: true,
+            replicate_scripts: false,
+            interpolate,
+            snapshots: Default::default(),
+            last_state: None,
+            time_since_sent: 0.0,
+        }
+    }
+
+    fn set_authority(&mut self, authority: Authority) {
+        if self.authority != authority {
+            self.authority = authority;
+            // Timestamps of the new owner are not comparable with the old ones.
+            self.snapshots.clear();
+        }
+    }
+
+    fn capture(&self, scene: &mut Scene) -> Option<NodeState> {
+        let node = scene.graph.try_get_mut(self.node)?;
+        let mut state = NodeState::default();
+
+        if self.replicate_transform {
+            let transform = node.local_transform();
+            state.position = Some((**transform.position()).into());
+            state.rotation = Some(transform.rotation().coords.into());
+            state.scale = Some((**transform.scale()).into());
+        }
+
+        if self.replicate_scripts {
+            for script in node.scripts_mut() {
+                let mut visitor = Visitor::new();
+                let mut data = Vec::new();
+                if (**script).visit("Data", &mut visitor).is_ok() {
+                    if let Ok(bytes) = visitor.save_binary_to_vec() {
+                        data = bytes;
+                    }
+                }
+                state.scripts.push(data);
+            }
+        }
+
+        Some(state)
+    }
+
+    fn apply_transform(&self, state: &NodeState, scene: &mut Scene) {
+        let Some(node) = scene.graph.try_get_mut(self.node) else {
+            return;
+        };
+
+        let transform = node.local_transform_mut();
+        if let Some(position) = state.position {
+            transform.set_position(Vector3::from(position));
+        }
+        if let Some(rotation) = state.rotation {
+            transform.set_rotation(UnitQuaternion::new_normalize(Quaternion::from(rotation)));
+        }
+        if let Some(scale) = state.scale {
+            transform.set_scale(Vector3::from(scale));
+        }
+    }
+
+    fn apply(&mut self, time: f32, state: &NodeState, scene: &mut Scene) {
+        if self.interpolate {
+            self.snapshots.push(time, state.clone());
+        } else {
+            self.apply_transform(state, scene);
+        }
+
+        let Some(node) = scene.graph.try_get_mut(self.node) else {
+            return;
+        };
+
+        for (script, data) in node.scripts_mut().zip(state.scripts.iter()) {
+            if data.is_empty() {
+                continue;
+            }
+            match Visitor::load_from_memory(data) {
+                Ok(mut visitor) => {
+                    if let Err(err) = (**script).visit("Data", &mut visitor) {
+                        Log::err(format!("Unable to apply replicated script state. {err:?}"));
+                    }
+                }
+                Err(err) => Log::err(format!("Malformed replicated script state. {err:?}")),
+            }
+        }
+
+        self.last_state = Some(state.clone());
+    }
+}
+
+fn is_authority(
+    role: ReplicationRole,
+    local_client: Option<ClientId>,
+    authority: Authority,
+) -> bool {
+    match (role, authority) {
+        (ReplicationRole::Server, Authority::Server) => true,
+        (ReplicationRole::Client, Authority::Client(client)) => local_client == Some(client),
+        _ => false,
+    }
+}
+
+struct PendingSpawn {
+    id: NetworkId,
+    prefab: ModelResource,
+    authority: Authority,
+    state: Option<(f32, NodeState)>,
+}
+
+enum Target {
+    Remote,
+    Client(ClientId),
+    AllExcept(ClientId),
+}
+
+/// Replicator keeps the state of scene nodes in sync between a server and its clients. Every
+/// replicated node has a [`NetworkId`] and an [`Authority`] - the only endpoint, that is allowed to
+/// change the state of the node. The state is captured periodically on the endpoint with authority
+/// and sent to the other endpoints (clients send the state to the server, which relays it to other
+/// clients). The state consists of the local transform and (optionally) of the scripts of a node.
+///
+/// Nodes could be spawned dynamically by the server from prefabs using [`Self::spawn`] (clients
+/// load the same prefabs by their paths), or registered manually on every endpoint using
+/// [`Self::register`] with the same id (it is useful for nodes of a level, that is loaded on every
+/// endpoint).
+///
+/// Replicator also delivers remote procedure calls, see [`Self::rpc`] and [`RpcRegistry`] for more
+/// info.
+///
+/// ## Authority rules
+///
+/// - Only the server can spawn and despawn nodes and change their authority.
+/// - State updates from a client are accepted only for the nodes owned by this client.
+/// - A client can't invoke remote calls on the nodes owned by other clients.
+/// - Nodes owned by a client are despawned when the client disconnects.
+///
+/// ## Usage
+///
+/// Replicator does not own a server or a client, it should be updated along with them. Typical
+/// server loop looks like this:
+///
+/// ```rust,no_run
+/// # use fyrox_net::{replication::Replicator, server::Server};
+/// # use fyrox::{scene::Scene, script::ScriptMessageSender};
+/// fn update(
+///     server: &mut Server,
+///     replicator: &mut Replicator,
+///     scene: &mut Scene,
+///     message_sender: &ScriptMessageSender,
+///     dt: f32,
+/// ) {
+///     for event in server.update(dt) {
+///         if !replicator.handle_server_event(&event, server, scene, message_sender) {
+///             // Handle game-specific events here.
+///         }
+///     }
+///     replicator.update_server(server, scene, dt);
+///     server.flush();
+/// }
+/// ```
+///
+/// Script message sender of a scene could be found in `PluginContext::script_processor`.
+pub struct Replicator {
+    role: ReplicationRole,
+    local_client: Option<ClientId>,
+    nodes: BTreeMap<NetworkId, ReplicatedNode>,
+    pending_spawns: Vec<PendingSpawn>,
+    outgoing: Vec<(Target, Channel, ReplicationMessage)>,
+    next_dynamic_id: u64,
+    time: f32,
+    time_since_sent: f32,
+    /// Registry of remote calls, that could be received.
+    pub rpc: RpcRegistry,
+    /// Interval (in seconds) between state updates. Default is `1/30` of a second.
+    pub send_interval: f32,
+    /// Interval (in seconds) after which the state of a node is sent even if it has not changed. It
+    /// is needed, because state updates are sent over unreliable channel and could be lost. Default
+    /// is one second.
+    pub keyframe_interval: f32,
+    /// Whether the transform of new replicated nodes should be interpolated or not. Default is
+    /// `true`. See [`ReplicatedNode::interpolate`].
+    pub interpolation: bool,
+}
+
+impl Replicator {
+    fn new(role: ReplicationRole) -> Self {
+        Self {
+            role,
+            local_client: None,
+            nodes: Default::default(),
+            pending_spawns: Default::default(),
+            outgoing: Default::default(),
+            next_dynamic_id: FIRST_DYNAMIC_ID.0,
+            time: 0.0,
+            time_since_sent: 0.0,
+            rpc: Default::default(),
+            send_interval: 1.0 / 30.0,
+            keyframe_interval: 1.0,
+            interpolation: true,
+        }
+    }
+
+    /// Creates a new replicator for a server.
+    pub fn server() -> Self {
+        Self::new(ReplicationRole::Server)
+    }
+
+    /// Creates a new replicator for a client.
+    pub fn client() -> Self {
+        Self::new(ReplicationRole::Client)
+    }
+
+    /// Returns the role of the replicator.
+    pub fn role(&self) -> ReplicationRole {
+        self.role
+    }
+
+    /// Returns `true` if this endpoint is allowed to change the state of nodes with the given
+    /// authority.
+    pub fn has_authority(&self, authority: Authority) -> bool {
+        is_authority(self.role, self.local_client, authority)
+    }
+
+    /// Registers an existing node for replication with the given id. The same node must be
+    /// registered with the same id on every endpoint. Ids must be lower than [`FIRST_DYNAMIC_ID`].
+    pub fn register(
+        &mut self,
+        id: NetworkId,
+        node: Handle<Node>,
+        authority: Authority,
+    ) -> &mut ReplicatedNode {
+        debug_assert!(id < FIRST_DYNAMIC_ID);
+        let interpolate = self.interpolation;
+        self.nodes
+            .entry(id)
+            .or_insert_with(|| ReplicatedNode::new(node, authority, None, interpolate))
+    }
+
+    /// Instantiates the given prefab and replicates it on every client. Works only on the server,
+    /// the prefab must be loaded and must have a path (it is used by clients to load the same
+    /// prefab).
+    pub fn spawn(
+        &mut self,
+        prefab: &ModelResource,
+        authority: Authority,
+        scene: &mut Scene,
+    ) -> Option<NetworkId> {
+        if self.role != ReplicationRole::Server {
+            Log::err("Only the server can spawn replicated nodes!");
+            return None;
+        }
+
+        let Some(path) = prefab.kind().path_owned() else {
+            Log::err("Unable to spawn a replicated node from an embedded prefab!");
+            return None;
+        };
+
+        if !prefab.is_ok() {
+            Log::err(format!(
+                "Unable to spawn a replicated node, prefab {} is not loaded!",
+                path.display()
+            ));
+            return None;
+        }
+
+        let node = prefab.instantiate(scene);
+        let id = NetworkId(self.next_dynamic_id);
+        self.next_dynamic_id += 1;
+        let replicated =
+            ReplicatedNode::new(node, authority, Some(path.clone()), self.interpolation);
+        self.nodes.insert(id, replicated);
+        self.outgoing.push((
+            Target::Remote,
+            Channel::Reliable,
+            ReplicationMessage::Spawn {
+                id,
+                prefab: path,
+                authority,
+            },
+        ));
+        Some(id)
+    }
+
+    /// Removes the node from the scene and from every client. Works only on the server.
+    pub fn despawn(&mut self, id: NetworkId, scene: &mut Scene) {
+        if self.role != ReplicationRole::Server {
+            Log::err("Only the server can despawn replicated nodes!");
+            return;
+        }
+
+        if let Some(replicated) = self.nodes.remove(&id) {
+            if scene.graph.is_valid_handle(replicated.node) {
+                scene.graph.remove_node(replicated.node);
+            }
+            self.outgoing.push((
+                Target::Remote,
+                Channel::Reliable,
+                ReplicationMessage::Despawn { id },
+            ));
+        }
+    }
+
+    /// Changes the authority of the node. Works only on the server.
+    pub fn set_authority(&mut self, id: NetworkId, authority: Authority) {
+        if self.role != ReplicationRole::Server {
+            Log::err("Only the server can change authority of replicated nodes!");
+            return;
+        }
+
+        if let Some(replicated) = self.nodes.get_mut(&id) {
+            replicated.set_authority(authority);
+            self.outgoing.push((
+                Target::Remote,
+                Channel::Reliable,
+                ReplicationMessage::Authority { id, authority },
+            ));
+        }
+    }
+
+    /// Invokes a remote call on the scripts of the given node on the remote side (the server sends
+    /// the call to every client, a client sends it to the server). The payload type must be
+    /// registered in [`Self::rpc`] registry of the remote side.
+    pub fn rpc<T: RemoteCall>(&mut self, id: NetworkId, payload: &T) {
+        let (call, data) = RpcRegistry::encode(payload);
+        self.outgoing.push((
+            Target::Remote,
+            Channel::Reliable,
+            ReplicationMessage::Rpc { id, call, data },
+        ));
+    }
+
+    /// Invokes a remote call on the scripts of the given node on the given client. Works only on
+    /// the server.
+    pub fn rpc_to<T: RemoteCall>(&mut self, client: ClientId, id: NetworkId, payload: &T) {
+        if self.role != ReplicationRole::Server {
+            Log::err("Only the server can send remote calls to a specific client!");
+            return;
+        }
+
+        let (call, data) = RpcRegistry::encode(payload);
+        self.outgoing.push((
+            Target::Client(client),
+            Channel::Reliable,
+            ReplicationMessage::Rpc { id, call, data },
+        ));
+    }
+
+    /// Returns a replicated node by its id.
+    pub fn get(&self, id: NetworkId) -> Option<&ReplicatedNode> {
+        self.nodes.get(&id)
+    }
+
+    /// Returns a replicated node by its id.
+    pub fn get_mut(&mut self, id: NetworkId) -> Option<&mut ReplicatedNode> {
+        self.nodes.get_mut(&id)
+    }
+
+    /// Returns a handle of the replicated node with the given id.
+    pub fn node_of(&self, id: NetworkId) -> Option<Handle<Node>> {
+        self.nodes.get(&id).map(|replicated| replicated.node)
+    }
+
+    /// Returns an id of the given replicated node.
+    pub fn id_of(&self, node: Handle<Node>) -> Option<NetworkId> {
+        self.nodes
+            .iter()
+            .find(|(_, replicated)| replicated.node == node)
+            .map(|(id, _)| *id)
+    }
+
+    /// Returns an iterator over every replicated node.
+    pub fn nodes(&self) -> impl Iterator<Item = (NetworkId, &ReplicatedNode)> {
+        self.nodes.iter().map(|(id, replicated)| (*id, replicated))
+    }
+
+    fn deliver_rpc(
+        &self,
+        id: NetworkId,
+        call: Uuid,
+        data: &[u8],
+        message_sender: &ScriptMessageSender,
+    ) {
+        let Some(node) = self.node_of(id) else {
+            return;
+        };
+
+        match self.rpc.decode(call, data) {
+            Some(payload) => message_sender.send(ScriptMessage {
+                payload,
+                kind: ScriptMessageKind::Targeted(node),
+            }),
+            None => Log::warn(format!("Unknown or malformed remote call {call}!")),
+        }
+    }
+
+    fn play_snapshots(&mut self, scene: &mut Scene, dt: f32) {
+        for replicated in self.nodes.values_mut() {
+            if !replicated.interpolate
+                || is_authority(self.role, self.local_client, replicated.authority)
+            {
+                continue;
+            }
+
+            replicated.snapshots.update(dt);
+            if let Some(state) = replicated.snapshots.sample() {
+                replicated.apply_transform(&state, scene);
+            }
+        }
+    }
+
+    fn capture_states(&mut self, scene: &mut Scene, dt: f32) {
+        self.time += dt;
+        self.time_since_sent += dt;
+        for replicated in self.nodes.values_mut() {
+            replicated.time_since_sent += dt;
+        }
+
+        if self.time_since_sent < self.send_interval {
+            return;
+        }
+        self.time_since_sent = 0.0;
+
+        for (id, replicated) in self.nodes.iter_mut() {
+            if !is_authority(self.role, self.local_client, replicated.authority) {
+                continue;
+            }
+
+            let Some(state) = replicated.capture(scene) else {
+                continue;
+            };
+
+            if replicated.last_state.as_ref() != Some(&state)
+                || replicated.time_since_sent >= self.keyframe_interval
+            {
+                replicated.time_since_sent = 0.0;
+                replicated.last_state = Some(state.clone());
+                self.outgoing.push((
+                    Target::Remote,
+                    Channel::Unreliable,
+                    ReplicationMessage::State {
+                        id: *id,
+                        time: self.time,
+                        state,
+                    },
+                ));
+            }
+        }
+    }
+
+    /// Handles the given server event. Returns `true` if the event was fully handled by the
+    /// replicator and should not be handled by the game (replication messages).
+    pub fn handle_server_event<T: Transport>(
+        &mut self,
+        event: &ServerEvent,
+        server: &mut Server<T>,
+        scene: &mut Scene,
+        message_sender: &ScriptMessageSender,
+    ) -> bool {
+        match event {
+            ServerEvent::ClientConnected(client) => {
+                // Send the current state of the world to the new client.
+                for (id, replicated) in self.nodes.iter() {
+                    match replicated.prefab {
+                        Some(ref prefab) => server.send_replication(
+                            *client,
+                            Channel::Reliable,
+                            ReplicationMessage::Spawn {
+                                id: *id,
+                                prefab: prefab.clone(),
+                                authority: replicated.authority,
+                            },
+                        ),
+                        None => server.send_replication(
+                            *client,
+                            Channel::Reliable,
+                            ReplicationMessage::Authority {
+                                id: *id,
+                                authority: replicated.authority,
+                            },
+                        ),
+                    }
+                    if let Some(ref state) = replicated.last_state {
+                        server.send_replication(
+                            *client,
+                            Channel::Reliable,
+                            ReplicationMessage::State {
+                                id: *id,
+                                time: self.time,
+                                state: state.clone(),
+                            },
+                        );
+                    }
+                }
+                false
+            }
+            ServerEvent::ClientDisconnected(client) => {
+                let owned = self
+                    .nodes
+                    .iter()
+                    .filter(|(_, replicated)| replicated.authority == Authority::Client(*client))
+                    .map(|(id, _)| *id)
+                    .collect::<Vec<_>>();
+                for id in owned {
+                    self.despawn(id, scene);
+                }
+                false
+            }
+            ServerEvent::Message { .. } => false,
+            ServerEvent::Replication { client, message } => {
+                match message {
+                    ReplicationMessage::State { id, time, state } => match self.nodes.get_mut(id) {
+                        Some(replicated) if replicated.authority == Authority::Client(*client) => {
+                            replicated.apply(*time, state, scene);
+                            self.outgoing.push((
+                                Target::AllExcept(*client),
+                                Channel::Unreliable,
+                                message.clone(),
+                            ));
+                        }
+                        Some(_) => Log::warn(format!(
+                            "Client {client} tried to change the state of node {id:?} \
+                                without authority!"
+                        )),
+                        None => (),
+                    },
+                    ReplicationMessage::Rpc { id, call, data } => match self.nodes.get(id) {
+                        Some(replicated) if matches!(replicated.authority, Authority::Client(owner) if owner != *client) => {
+                            Log::warn(format!(
+                                "Client {client} tried to invoke a remote call on node {id:?} \
+                                owned by another client!"
+                            ))
+                        }
+                        Some(_) => self.deliver_rpc(*id, *call, data, message_sender),
+                        None => (),
+                    },
+                    ReplicationMessage::Spawn { .. }
+                    | ReplicationMessage::Despawn { .. }
+                    | ReplicationMessage::Authority { .. } => Log::warn(format!(
+                        "Client {client} tried to send a server-only replication message!"
+                    )),
+                }
+                true
+            }
+        }
+    }
+
+    /// Interpolates the state of nodes owned by clients, captures the state of every node owned by
+    /// the server and sends it, along with every other
+    /// queued message, to the clients. Should be called every frame after handling server events.
+    pub fn update_server<T: Transport>(
+        &mut self,
+        server: &mut Server<T>,
+        scene: &mut Scene,
+        dt: f32,
+    ) {
+        self.play_snapshots(scene, dt);
+        self.capture_states(scene, dt);
+
+        for (target, channel, message) in self.outgoing.drain(..) {
+            match target {
+                Target::Remote => {
+                    for client in server.clients().collect::<Vec<_>>() {
+                        server.send_replication(client, channel, message.clone());
+                    }
+                }
+                Target::Client(client) => server.send_replication(client, channel, message),
+                Target::AllExcept(except) => {
+                    for client in server
+                        .clients()
+                        .filter(|c| *c != except)
+                        .collect::<Vec<_>>()
+                    {
+                        server.send_replication(client, channel, message.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Handles the given client event. Returns `true` if the event was fully handled by the
+    /// replicator and should not be handled by the game (replication messages).
+    pub fn handle_client_event(
+        &mut self,
+        event: &ClientEvent,
+        scene: &mut Scene,
+        resource_manager: &ResourceManager,
+        message_sender: &ScriptMessageSender,
+    ) -> bool {
+        match event {
+            ClientEvent::Connected(client) => {
+                self.local_client = Some(*client);
+                false
+            }
+            ClientEvent::Replication(message) => {
+                match message {
+                    ReplicationMessage::Spawn {
+                        id,
+                        prefab,
+                        authority,
+                    } => {
+                        if !self.nodes.contains_key(id)
+                            && !self.pending_spawns.iter().any(|pending| pending.id == *id)
+                        {
+                            self.pending_spawns.push(PendingSpawn {
+                                id: *id,
+                                prefab: resource_manager.request::<Model>(prefab),
+                                authority: *authority,
+                                state: None,
+                            });
+                        }
+                    }
+                    ReplicationMessage::Despawn { id } => {
+                        if let Some(replicated) = self.nodes.remove(id) {
+                            if scene.graph.is_valid_handle(replicated.node) {
+                                scene.graph.remove_node(replicated.node);
+                            }
+                        }
+                        self.pending_spawns.retain(|pending| pending.id != *id);
+                    }
+                    ReplicationMessage::Authority { id, authority } => {
+                        if let Some(replicated) = self.nodes.get_mut(id) {
+                            replicated.set_authority(*authority);
+                        } else if let Some(pending) =
+                            self.pending_spawns.iter_mut().find(|p| p.id == *id)
+                        {
+                            pending.authority = *authority;
+                        }
+                    }
+                    ReplicationMessage::State { id, time, state } => {
+                        if let Some(replicated) = self.nodes.get_mut(id) {
+                            // The state of own nodes is defined by this client.
+                            if !is_authority(self.role, self.local_client, replicated.authority) {
+                                replicated.apply(*time, state, scene);
+                            }
+                        } else if let Some(pending) =
+                            self.pending_spawns.iter_mut().find(|p| p.id == *id)
+                        {
+                            pending.state = Some((*time, state.clone()));
+                        }
+                    }
+                    ReplicationMessage::Rpc { id, call, data } => {
+                        self.deliver_rpc(*id, *call, data, message_sender)
+                    }
+                }
+                true
+            }
+            ClientEvent::Denied(_) | ClientEvent::Disconnected | ClientEvent::Message { .. } => {
+                false
+            }
+        }
+    }
+
+    /// Instantiates loaded prefabs of spawned nodes, interpolates the state of remote nodes,
+    /// captures the state of every node owned by the
+    /// client and sends it, along with every other queued message, to the server. Should be called
+    /// every frame after handling client events.
+    pub fn update_client<T: Transport>(
+        &mut self,
+        client: &mut Client<T>,
+        scene: &mut Scene,
+        dt: f32,
+    ) {
+        let mut i = 0;
+        while i < self.pending_spawns.len() {
+            let pending = &self.pending_spawns[i];
+            if pending.prefab.is_loading() {
+                i += 1;
+                continue;
+            }
+
+            let pending = self.pending_spawns.remove(i);
+            if pending.prefab.is_ok() {
+                let node = pending.prefab.instantiate(scene);
+                let mut replicated = ReplicatedNode::new(
+                    node,
+                    pending.authority,
+                    pending.prefab.kind().path_owned(),
+                    self.interpolation,
+                );
+                if let Some((time, state)) = pending.state {
+                    replicated.apply(time, &state, scene);
+                }
+                self.nodes.insert(pending.id, replicated);
+            } else {
+                Log::err(format!(
+                    "Unable to spawn replicated node {:?}, because its prefab failed to load!",
+                    pending.id
+                ));
+            }
+        }
+
+        self.play_snapshots(scene, dt);
+        self.capture_states(scene, dt);
+
+        for (_, channel, message) in self.outgoing.drain(..) {
+            client.send_replication(channel, message);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Remote procedure calls. An RPC is a script message, that is sent over the network and delivered
+//! to the scripts of a replicated node on the remote side. See [`RpcRegistry`] docs for more info.
+
+use fxhash::FxHashMap;
+use fyrox::{
+    core::{uuid::Uuid, TypeUuidProvider},
+    script::ScriptMessagePayload,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A payload of a remote procedure call. It is implemented automatically for every script message
+/// payload, that could be serialized and has a type UUID. Calls are identified by the type UUID on
+/// the wire, so the UUID must be the same on every endpoint and must not change between versions
+/// of the game, unlike type names, that depend on the module structure and the compiler.
+pub trait RemoteCall:
+    ScriptMessagePayload + Serialize + DeserializeOwned + TypeUuidProvider
+{
+}
+
+impl<T> RemoteCall for T where
+    T: ScriptMessagePayload + Serialize + DeserializeOwned + TypeUuidProvider
+{
+}
+
+type Deserializer = fn(&[u8]) -> Option<Box<dyn ScriptMessagePayload>>;
+
+fn deserialize<T: RemoteCall>(data: &[u8]) -> Option<Box<dyn ScriptMessagePayload>> {
+    bincode::deserialize::<T>(data)
+        .ok()
+        .map(|payload| Box::new(payload) as Box<dyn ScriptMessagePayload>)
+}
+
+/// RPC registry contains every type of remote calls, that could be received. Every type must be
+/// registered on the receiving side, otherwise the call will be ignored:
+///
+/// ```rust
+/// # use fyrox::core::uuid_provider;
+/// # use fyrox_net::rpc::RpcRegistry;
+/// # use serde::{Deserialize, Serialize};
+/// #[derive(Debug, Serialize, Deserialize)]
+/// struct Jump {
+///     height: f32,
+/// }
+///
+/// uuid_provider!(Jump = "2f7a0c1e-5b3d-4e8f-9a6c-1d2e3f4a5b6c");
+///
+/// let mut registry = RpcRegistry::default();
+/// registry.register::<Jump>();
+/// ```
+///
+/// Received calls are delivered as targeted script messages, so scripts must subscribe to the
+/// payload type (using `ScriptContext::message_dispatcher`) to receive them.
+#[derive(Default)]
+pub struct RpcRegistry {
+    deserializers: FxHashMap<Uuid, Deserializer>,
+}
+
+impl RpcRegistry {
+    /// Registers a new remote call type.
+    pub fn register<T: RemoteCall>(&mut self) {
+        self.deserializers.insert(T::type_uuid(), deserialize::<T>);
+    }
+
+    /// Returns `true` if the remote call type is registered.
+    pub fn is_registered<T: RemoteCall>(&self) -> bool {
+        self.deserializers.contains_key(&T::type_uuid())
+    }
+
+    pub(crate) fn encode<T: RemoteCall>(payload: &T) -> (Uuid, Vec<u8>) {
+        (
+            T::type_uuid(),
+            bincode::serialize(payload).expect("RPC serialization must not fail"),
+        )
+    }
+
+    pub(crate) fn decode(&self, call: Uuid, data: &[u8]) -> Option<Box<dyn ScriptMessagePayload>> {
+        self.deserializers
+            .get(&call)
+            .and_then(|deserializer| deserializer(data))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fyrox::core::uuid_provider;
+    use serde::Deserialize;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Jump {
+        height: f32,
+    }
+
+    uuid_provider!(Jump = "2f7a0c1e-5b3d-4e8f-9a6c-1d2e3f4a5b6c");
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Crouch;
+
+    uuid_provider!(Crouch = "7c5e9b2a-3f1d-4a6e-8b0c-9d8e7f6a5b4c");
+
+    #[test]
+    fn test_rpc_registry() {
+        let mut registry = RpcRegistry::default();
+        registry.register::<Jump>();
+        assert!(registry.is_registered::<Jump>());
+        assert!(!registry.is_registered::<Crouch>());
+
+        let (call, data) = RpcRegistry::encode(&Jump { height: 2.0 });
+        assert_eq!(call, Jump::type_uuid());
+        let payload = registry.decode(call, &data).unwrap();
+        assert_eq!(
+            payload.as_any_ref().downcast_ref::<Jump>().unwrap().height,
+            2.0
+        );
+
+        let (call, data) = RpcRegistry::encode(&Crouch);
+        assert!(registry.decode(call, &data).is_none());
+    }
+}
@@ -0,0 +1,340 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Server accepts connections from clients and exchanges messages with them. See [`Server`] docs
+//! for more info.
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::websocket::WebSocketServerTransport;
+use crate::{
+    connection::{Channel, Connection, NetConfig, Packet},
+    replication::ReplicationMessage,
+    transport::{Address, Transport, UdpTransport},
+    ClientId, Envelope,
+};
+use fxhash::FxHashMap;
+use fyrox::core::log::Log;
+use serde::Serialize;
+use std::{collections::BTreeMap, io, net::ToSocketAddrs};
+
+/// An event, that happened on a server.
+#[derive(Debug, PartialEq)]
+pub enum ServerEvent {
+    /// A new client has connected.
+    ClientConnected(ClientId),
+    /// A client has disconnected or its connection was lost.
+    ClientDisconnected(ClientId),
+    /// A message was received from a client.
+    Message {
+        /// Id of the client, that sent the message.
+        client: ClientId,
+        /// A channel, that was used to send the message.
+        channel: Channel,
+        /// Message data.
+        data: Vec<u8>,
+    },
+    /// A replication message was received from a client. It should be passed to
+    /// [`crate::replication::Replicator::handle_server_event`].
+    Replication {
+        /// Id of the client, that sent the message.
+        client: ClientId,
+        /// The message.
+        message: ReplicationMessage,
+    },
+}
+
+struct RemoteClient {
+    address: Address,
+    connection: Connection,
+}
+
+fn send_packet<T: Transport>(transport: &mut T, address: &Address, packet: &Packet) {
+    if let Err(err) = transport.send(address, &packet.encode()) {
+        Log::err(format!(
+            "Unable to send a packet to {address}. Reason: {err}"
+        ));
+    }
+}
+
+/// Server accepts connections from clients and exchanges messages with them. It does not spawn any
+/// threads, everything is done in [`Self::update`] and [`Self::flush`] methods, which should be
+/// called every frame:
+///
+/// ```rust,no_run
+/// # use fyrox_net::{connection::{Channel, NetConfig}, server::{Server, ServerEvent}};
+/// let mut server = Server::bind("0.0.0.0:7777", NetConfig::default()).unwrap();
+///
+/// loop {
+///     for event in server.update(1.0 / 60.0) {
+///         match event {
+///             ServerEvent::ClientConnected(client) => {
+///                 server.send(client, Channel::Reliable, b"Welcome!".to_vec());
+///             }
+///             ServerEvent::Message { client, data, .. } => {
+///                 // Echo every message back.
+///                 server.send(client, Channel::Reliable, data);
+///             }
+///             _ => (),
+///         }
+///     }
+///
+///     server.flush();
+/// #   break;
+/// }
+/// ```
+pub struct Server<T: Transport = UdpTransport> {
+    transport: T,
+    config: NetConfig,
+    clients: BTreeMap<ClientId, RemoteClient>,
+    addresses: FxHashMap<Address, ClientId>,
+    next_client_id: u64,
+}
+
+impl Server<UdpTransport> {
+    /// Creates a new server, that listens for UDP datagrams on the given address.
+    pub fn bind<A: ToSocketAddrs>(address: A, config: NetConfig) -> io::Result<Self> {
+        Ok(Self::new(UdpTransport::bind(address)?, config))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Server<WebSocketServerTransport> {
+    /// Creates a new server, that listens for WebSocket connections on the given address.
+    pub fn bind_websocket<A: ToSocketAddrs>(address: A, config: NetConfig) -> io::Result<Self> {
+        Ok(Self::new(WebSocketServerTransport::bind(address)?, config))
+    }
+}
+
+impl<T: Transport> Server<T> {
+    /// Creates a new server, that uses the given transport.
+    pub fn new(transport: T, config: NetConfig) -> Self {
+        Self {
+            transport,
+            config,
+            clients: Default::default(),
+            addresses: Default::default(),
+            next_client_id: 0,
+        }
+    }
+
+    /// Returns a reference to the transport of the server.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Returns a reference to the config of the server.
+    pub fn config(&self) -> &NetConfig {
+        &self.config
+    }
+
+    /// Returns an iterator over ids of every connected client.
+    pub fn clients(&self) -> impl Iterator<Item = ClientId> + '_ {
+        self.clients.keys().copied()
+    }
+
+    /// Returns amount of connected clients.
+    pub fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Returns an address of the given client.
+    pub fn client_address(&self, client: ClientId) -> Option<&Address> {
+        self.clients.get(&client).map(|remote| &remote.address)
+    }
+
+    /// Receives every incoming packet, handles connection requests and timeouts. Returns a list of
+    /// events, that happened since the last update.
+    pub fn update(&mut self, dt: f32) -> Vec<ServerEvent> {
+        let mut events = Vec::new();
+
+        for remote in self.clients.values_mut() {
+            remote.connection.update(dt);
+        }
+
+        while let Some((address, data)) = self.transport.receive() {
+            let Some(packet) = Packet::decode(&data) else {
+                continue;
+            };
+
+            match packet {
+                Packet::Connect { protocol_id } => {
+                    if let Some(client_id) = self.addresses.get(&address).copied() {
+                        // The client didn't receive the previous response.
+                        if let Some(remote) = self.clients.get_mut(&client_id) {
+                            remote.connection.mark_received();
+                        }
+                        send_packet(&mut self.transport, &address, &Packet::Accept { client_id });
+                    } else if protocol_id != self.config.protocol_id {
+                        send_packet(
+                            &mut self.transport,
+                            &address,
+                            &Packet::Deny {
+                                reason: "Protocol mismatch.".to_string(),
+                            },
+                        );
+                    } else if self.clients.len() >= self.config.max_clients {
+                        send_packet(
+                            &mut self.transport,
+                            &address,
+                            &Packet::Deny {
+                                reason: "The server is full.".to_string(),
+                            },
+                        );
+                    } else {
+                        let client_id = ClientId(self.next_client_id);
+                        self.next_client_id += 1;
+                        self.addresses.insert(address.clone(), client_id);
+                        self.clients.insert(
+                            client_id,
+                            RemoteClient {
+                                address: address.clone(),
+                                connection: Default::default(),
+                            },
+                        );
+                        send_packet(&mut self.transport, &address, &Packet::Accept { client_id });
+                        events.push(ServerEvent::ClientConnected(client_id));
+                    }
+                }
+                Packet::Disconnect => {
+                    if let Some(client_id) = self.addresses.remove(&address) {
+                        self.clients.remove(&client_id);
+                        events.push(ServerEvent::ClientDisconnected(client_id));
+                    }
+                }
+                Packet::Payload {
+                    acks,
+                    reliable,
+                    unreliable,
+                } => {
+                    let Some(client) = self.addresses.get(&address).copied() else {
+                        continue;
+                    };
+                    let Some(remote) = self.clients.get_mut(&client) else {
+                        continue;
+                    };
+                    for (channel, data) in
+                        remote
+                            .connection
+                            .receive(acks, reliable, unreliable, &self.config)
+                    {
+                        match Envelope::decode(&data) {
+                            Some(Envelope::User(data)) => events.push(ServerEvent::Message {
+                                client,
+                                channel,
+                                data,
+                            }),
+                            Some(Envelope::Replication(message)) => {
+                                events.push(ServerEvent::Replication { client, message })
+                            }
+                            None => Log::warn(format!(
+                                "Malformed message received from client {client}!"
+                            )),
+                        }
+                    }
+                }
+                Packet::Accept { .. } | Packet::Deny { .. } => (),
+            }
+        }
+
+        let timed_out = self
+            .clients
+            .iter()
+            .filter(|(_, remote)| remote.connection.is_timed_out(&self.config))
+            .map(|(client, _)| *client)
+            .collect::<Vec<_>>();
+        for client in timed_out {
+            if let Some(remote) = self.clients.remove(&client) {
+                self.addresses.remove(&remote.address);
+                events.push(ServerEvent::ClientDisconnected(client));
+            }
+        }
+
+        events
+    }
+
+    /// Sends every queued message to the clients. Should be called once per frame, after every
+    /// message was sent.
+    pub fn flush(&mut self) {
+        for remote in self.clients.values_mut() {
+            for packet in remote.connection.flush(&self.config) {
+                send_packet(&mut self.transport, &remote.address, &packet);
+            }
+        }
+    }
+
+    fn send_envelope(&mut self, client: ClientId, channel: Channel, envelope: &Envelope) {
+        if let Some(remote) = self.clients.get_mut(&client) {
+            if let Err(err) = remote
+                .connection
+                .send(channel, envelope.encode(), &self.config)
+            {
+                Log::err(format!(
+                    "Unable to send a message to the client {client}. Reason: {err}"
+                ));
+            }
+        }
+    }
+
+    /// Queues a message for the given client. Does nothing if there's no such client.
+    pub fn send(&mut self, client: ClientId, channel: Channel, data: Vec<u8>) {
+        self.send_envelope(client, channel, &Envelope::User(data))
+    }
+
+    /// Serializes the given message and queues it for the given client.
+    pub fn send_message<M: Serialize>(&mut self, client: ClientId, channel: Channel, message: &M) {
+        match bincode::serialize(message) {
+            Ok(data) => self.send(client, channel, data),
+            Err(err) => Log::err(format!("Unable to serialize a message. Reason: {err}")),
+        }
+    }
+
+    /// Queues a message for every connected client.
+    pub fn broadcast(&mut self, channel: Channel, data: Vec<u8>) {
+        let envelope = Envelope::User(data);
+        for client in self.clients().collect::<Vec<_>>() {
+            self.send_envelope(client, channel, &envelope);
+        }
+    }
+
+    pub(crate) fn send_replication(
+        &mut self,
+        client: ClientId,
+        channel: Channel,
+        message: ReplicationMessage,
+    ) {
+        self.send_envelope(client, channel, &Envelope::Replication(message))
+    }
+
+    /// Disconnects the given client. [`ServerEvent::ClientDisconnected`] is not generated in this
+    /// case.
+    pub fn disconnect(&mut self, client: ClientId) {
+        if let Some(remote) = self.clients.remove(&client) {
+            self.addresses.remove(&remote.address);
+            send_packet(&mut self.transport, &remote.address, &Packet::Disconnect);
+        }
+    }
+
+    /// Disconnects every client.
+    pub fn disconnect_all(&mut self) {
+        for client in self.clients().collect::<Vec<_>>() {
+            self.disconnect(client);
+        }
+    }
+}
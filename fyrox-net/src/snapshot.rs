@@ -0,0 +1,291 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Snapshot interpolation hides the discrete nature of network updates. Remote entities are
+//! displayed slightly in the past, which allows to smoothly interpolate between two received states
+//! instead of jumping from one to another. See [`SnapshotBuffer`] docs for more info.
+
+use crate::replication::NodeState;
+use fyrox::core::algebra::{Quaternion, UnitQuaternion, Vector2, Vector3};
+use std::collections::VecDeque;
+
+/// A value, that could be interpolated between two states.
+pub trait Interpolate: Clone {
+    /// Returns a value between `self` (`t = 0`) and `other` (`t = 1`).
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Vector2<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Interpolate for Vector3<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Interpolate for UnitQuaternion<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.try_slerp(other, t, f32::EPSILON)
+            .unwrap_or_else(|| self.nlerp(other, t))
+    }
+}
+
+fn interpolate_option<T, U>(
+    a: Option<T>,
+    b: Option<T>,
+    t: f32,
+    map: impl Fn(T) -> U,
+    back: impl Fn(U) -> T,
+) -> Option<T>
+where
+    U: Interpolate,
+{
+    match (a, b) {
+        (Some(a), Some(b)) => Some(back(map(a).interpolate(&map(b), t))),
+        (a, b) => {
+            if t < 0.5 {
+                a
+            } else {
+                b
+            }
+        }
+    }
+}
+
+impl Interpolate for NodeState {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Self {
+            position: interpolate_option(
+                self.position,
+                other.position,
+                t,
+                Vector3::<f32>::from,
+                |v| v.into(),
+            ),
+            rotation: interpolate_option(
+                self.rotation,
+                other.rotation,
+                t,
+                |q| UnitQuaternion::new_normalize(Quaternion::from(q)),
+                |q: UnitQuaternion<f32>| q.coords.into(),
+            ),
+            scale: interpolate_option(self.scale, other.scale, t, Vector3::<f32>::from, |v| {
+                v.into()
+            }),
+            // Scripts can't be interpolated, the nearest state is used.
+            scripts: if t < 0.5 {
+                self.scripts.clone()
+            } else {
+                other.scripts.clone()
+            },
+        }
+    }
+}
+
+/// How fast the playback time catches up with the desired time (fraction of the difference per
+/// second).
+const CATCH_UP_RATE: f32 = 2.0;
+
+/// Snapshot buffer stores timestamped states of a remote entity and plays them back with a fixed
+/// delay, interpolating between the two nearest states. The delay should be a bit larger than the
+/// interval between updates (two intervals is a good start), so there's always a next state to
+/// interpolate to, even if one update was lost.
+///
+/// Playback time runs along with the local time and smoothly catches up with the time of the newest
+/// snapshot minus the delay, so the playback is not affected by jitter of network latency.
+///
+/// ```rust
+/// # use fyrox_net::snapshot::SnapshotBuffer;
+/// let mut buffer = SnapshotBuffer::new(0.1);
+/// buffer.push(0.0, 0.0f32);
+/// buffer.push(0.1, 1.0f32);
+/// buffer.push(0.2, 2.0f32);
+///
+/// buffer.update(0.0);
+/// // Playback is 0.1 seconds behind the newest snapshot.
+/// assert_eq!(buffer.sample(), Some(1.0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct SnapshotBuffer<T> {
+    snapshots: VecDeque<(f32, T)>,
+    playback_time: Option<f32>,
+    /// Playback delay (in seconds).
+    pub delay: f32,
+    /// Maximum amount of stored snapshots.
+    pub capacity: usize,
+    /// Maximum difference between the playback time and the desired time (in seconds), after which
+    /// the playback time jumps to the desired one instead of catching up smoothly.
+    pub max_drift: f32,
+}
+
+impl<T> Default for SnapshotBuffer<T> {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+impl<T> SnapshotBuffer<T> {
+    /// Creates a new buffer with the given playback delay.
+    pub fn new(delay: f32) -> Self {
+        Self {
+            snapshots: Default::default(),
+            playback_time: None,
+            delay,
+            capacity: 32,
+            max_drift: 1.0,
+        }
+    }
+
+    /// Adds a new snapshot. Snapshots could arrive out of order, they're sorted by time. Snapshots,
+    /// that are older than the current playback time, are ignored.
+    pub fn push(&mut self, time: f32, value: T) {
+        if self.playback_time.map_or(false, |playback| time < playback) {
+            return;
+        }
+
+        let index = self.snapshots.partition_point(|(t, _)| *t < time);
+        if self.snapshots.get(index).map_or(false, |(t, _)| *t == time) {
+            // Duplicate.
+            return;
+        }
+        self.snapshots.insert(index, (time, value));
+
+        while self.snapshots.len() > self.capacity.max(2) {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Returns the time of the newest snapshot.
+    pub fn newest_time(&self) -> Option<f32> {
+        self.snapshots.back().map(|(time, _)| *time)
+    }
+
+    /// Returns current playback time.
+    pub fn playback_time(&self) -> Option<f32> {
+        self.playback_time
+    }
+
+    /// Returns amount of stored snapshots.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Returns `true` if the buffer has no snapshots.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Removes every snapshot and resets the playback.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.playback_time = None;
+    }
+
+    /// Advances the playback time. Should be called every frame.
+    pub fn update(&mut self, dt: f32) {
+        let Some(newest) = self.newest_time() else {
+            return;
+        };
+
+        let target = newest - self.delay;
+        let playback = match self.playback_time {
+            Some(playback) => {
+                let playback = playback + dt;
+                if (target - playback).abs() > self.max_drift {
+                    target
+                } else {
+                    playback + (target - playback) * (dt * CATCH_UP_RATE).min(1.0)
+                }
+            }
+            None => target,
+        };
+        self.playback_time = Some(playback);
+
+        // Keep only one snapshot before the playback time, it is needed for interpolation.
+        while self.snapshots.len() > 2 && self.snapshots[1].0 <= playback {
+            self.snapshots.pop_front();
+        }
+    }
+}
+
+impl<T: Interpolate> SnapshotBuffer<T> {
+    /// Returns an interpolated state at the current playback time. If the playback time is out of
+    /// the range of stored snapshots, the nearest snapshot is returned (no extrapolation is done).
+    pub fn sample(&self) -> Option<T> {
+        let playback = self.playback_time?;
+
+        let index = self.snapshots.partition_point(|(t, _)| *t <= playback);
+        if index == 0 {
+            return self.snapshots.front().map(|(_, value)| value.clone());
+        }
+        let (prev_time, prev) = &self.snapshots[index - 1];
+        match self.snapshots.get(index) {
+            Some((next_time, next)) => {
+                let t = (playback - prev_time) / (next_time - prev_time);
+                Some(prev.interpolate(next, t))
+            }
+            None => Some(prev.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::snapshot::SnapshotBuffer;
+
+    #[test]
+    fn test_snapshot_interpolation() {
+        let mut buffer = SnapshotBuffer::new(0.2);
+        assert_eq!(buffer.sample(), None);
+
+        // Out of order arrival.
+        buffer.push(0.2, 2.0f32);
+        buffer.push(0.0, 0.0f32);
+        buffer.push(0.1, 1.0f32);
+        buffer.push(0.1, 1.0f32);
+        assert_eq!(buffer.len(), 3);
+
+        buffer.update(0.0);
+        assert_eq!(buffer.playback_time(), Some(0.0));
+        assert_eq!(buffer.sample(), Some(0.0));
+
+        buffer.update(0.05);
+        // Playback runs ahead of the desired time, it is slowed down a bit.
+        let value = buffer.sample().unwrap();
+        assert!(value > 0.4 && value < 0.5);
+
+        // No new snapshots - the last one is held.
+        for _ in 0..10 {
+            buffer.update(0.1);
+        }
+        assert_eq!(buffer.sample(), Some(2.0));
+        assert_eq!(buffer.len(), 2);
+    }
+}
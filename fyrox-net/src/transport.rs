@@ -0,0 +1,201 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Transports are responsible for delivering raw datagrams between endpoints. The networking layer
+//! does not rely on any properties of a transport, except that it is able to deliver a datagram
+//! (maybe out of order, maybe never) to a given address. See [`Transport`] docs for more info.
+
+use fxhash::FxHashMap;
+use fyrox::core::{log::Log, parking_lot::Mutex};
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    sync::Arc,
+};
+
+/// Maximum size of a datagram, that could be received by [`UdpTransport`].
+pub const MAX_DATAGRAM_SIZE: usize = 65507;
+
+/// An address of a remote endpoint.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Address {
+    /// Socket address of an endpoint, used by [`UdpTransport`].
+    Socket(SocketAddr),
+    /// Named address of an endpoint, used by transports, that do not use socket addresses (for
+    /// example [`LocalTransport`]).
+    Name(String),
+    /// URL of a WebSocket server, used by [`crate::websocket::WebSocketTransport`].
+    Url(String),
+}
+
+impl Display for Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Address::Socket(address) => write!(f, "{address}"),
+            Address::Name(name) => write!(f, "{name}"),
+            Address::Url(url) => write!(f, "{url}"),
+        }
+    }
+}
+
+impl From<SocketAddr> for Address {
+    fn from(address: SocketAddr) -> Self {
+        Self::Socket(address)
+    }
+}
+
+/// Transport is a low-level, unreliable datagram delivery mechanism. Both methods must not block.
+pub trait Transport {
+    /// Sends the given datagram to the given address.
+    fn send(&mut self, address: &Address, data: &[u8]) -> io::Result<()>;
+
+    /// Tries to receive a next datagram. Returns `None` if there's no more datagrams available at
+    /// the moment.
+    fn receive(&mut self) -> Option<(Address, Vec<u8>)>;
+}
+
+/// Native transport, that uses UDP sockets.
+pub struct UdpTransport {
+    socket: UdpSocket,
+    buffer: Box<[u8]>,
+}
+
+impl UdpTransport {
+    /// Creates a new non-blocking UDP socket bound to the given address.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            buffer: vec![0; MAX_DATAGRAM_SIZE].into_boxed_slice(),
+        })
+    }
+
+    /// Returns the local address of the socket.
+    pub fn local_address(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, address: &Address, data: &[u8]) -> io::Result<()> {
+        match address {
+            Address::Socket(address) => self.socket.send_to(data, address).map(|_| ()),
+            address => Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("UDP transport does not support address {address}!"),
+            )),
+        }
+    }
+
+    fn receive(&mut self) -> Option<(Address, Vec<u8>)> {
+        loop {
+            match self.socket.recv_from(&mut self.buffer) {
+                Ok((count, address)) => {
+                    return Some((Address::Socket(address), self.buffer[..count].to_vec()))
+                }
+                Err(err) => match err.kind() {
+                    ErrorKind::WouldBlock => return None,
+                    // Some platforms report ICMP "port unreachable" responses for previously sent
+                    // datagrams as errors, these should not stop the receiving.
+                    ErrorKind::Interrupted
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionRefused => (),
+                    _ => {
+                        Log::err(format!("Unable to receive a datagram. Reason: {err}"));
+                        return None;
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct LocalNetworkState {
+    queues: FxHashMap<String, VecDeque<(Address, Vec<u8>)>>,
+}
+
+/// In-memory network, that delivers datagrams between [`LocalTransport`]s instantly and reliably.
+/// It is useful for tests and for games, that run the server and the client in the same process.
+#[derive(Clone, Default)]
+pub struct LocalNetwork {
+    state: Arc<Mutex<LocalNetworkState>>,
+}
+
+impl LocalNetwork {
+    /// Creates a new transport with the given name in the network. The name is used as an address
+    /// of the endpoint, see [`Address::Name`].
+    pub fn endpoint(&self, name: impl Into<String>) -> LocalTransport {
+        let name = name.into();
+        self.state.lock().queues.entry(name.clone()).or_default();
+        LocalTransport {
+            name,
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// Transport of a [`LocalNetwork`].
+pub struct LocalTransport {
+    name: String,
+    state: Arc<Mutex<LocalNetworkState>>,
+}
+
+impl LocalTransport {
+    /// Returns the address of the endpoint.
+    pub fn address(&self) -> Address {
+        Address::Name(self.name.clone())
+    }
+}
+
+impl Transport for LocalTransport {
+    fn send(&mut self, address: &Address, data: &[u8]) -> io::Result<()> {
+        let Address::Name(name) = address else {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "Local transport supports only named addresses!",
+            ));
+        };
+
+        // Datagrams to unknown endpoints are silently dropped, just like in real networks.
+        if let Some(queue) = self.state.lock().queues.get_mut(name) {
+            queue.push_back((self.address(), data.to_vec()));
+        }
+
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Option<(Address, Vec<u8>)> {
+        self.state
+            .lock()
+            .queues
+            .get_mut(&self.name)
+            .and_then(|queue| queue.pop_front())
+    }
+}
+
+impl Drop for LocalTransport {
+    fn drop(&mut self) {
+        self.state.lock().queues.remove(&self.name);
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! WebSocket transports. Web browsers cannot use UDP sockets, so web builds of a game should use
+//! [`WebSocketTransport`] to connect to a server, that uses [`WebSocketServerTransport`]. Both
+//! transports implement [`crate::transport::Transport`], so [`crate::server::Server`] and
+//! [`crate::client::Client`] work with them exactly the same way as with UDP.
+//!
+//! WebSocket works on top of TCP, which means that unreliable messages are still delivered reliably
+//! and in order, and a lost packet delays every message after it. Unreliable WebRTC data channels
+//! are not supported yet.
+//!
+//! On native platforms, only `ws://` URLs are supported (no TLS). Web builds could use `wss://`
+//! URLs as well, encryption is handled by the browser.
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+#[cfg(target_arch = "wasm32")]
+mod web;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::{WebSocketServerTransport, WebSocketTransport};
+#[cfg(target_arch = "wasm32")]
+pub use web::WebSocketTransport;
@@ -0,0 +1,379 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Native WebSocket transports, that use non-blocking TCP sockets.
+
+use crate::transport::{Address, Transport};
+use fxhash::FxHashMap;
+use fyrox::core::log::Log;
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+};
+use tungstenite::{
+    client::IntoClientRequest,
+    handshake::{
+        client::ClientHandshake,
+        server::{NoCallback, ServerHandshake},
+        HandshakeError, MidHandshake,
+    },
+    Message, WebSocket,
+};
+
+fn to_io_error(err: tungstenite::Error) -> io::Error {
+    match err {
+        tungstenite::Error::Io(err) => err,
+        err => io::Error::new(ErrorKind::Other, err),
+    }
+}
+
+fn is_would_block(err: &tungstenite::Error) -> bool {
+    matches!(err, tungstenite::Error::Io(err) if err.kind() == ErrorKind::WouldBlock)
+}
+
+/// Sends a binary message. If the message could not be written right away, it stays in the write
+/// buffer of the socket and will be written on the next flush.
+fn send_message(socket: &mut WebSocket<TcpStream>, data: &[u8]) -> io::Result<()> {
+    match socket.send(Message::Binary(data.to_vec())) {
+        Err(err) if !is_would_block(&err) => Err(to_io_error(err)),
+        _ => Ok(()),
+    }
+}
+
+/// Flushes pending messages and reads every available message from the socket. Returns `false`
+/// if the socket was closed.
+fn read_messages(socket: &mut WebSocket<TcpStream>, mut on_message: impl FnMut(Vec<u8>)) -> bool {
+    if let Err(err) = socket.flush() {
+        if !is_would_block(&err) {
+            return false;
+        }
+    }
+
+    loop {
+        match socket.read() {
+            Ok(Message::Binary(data)) => on_message(data),
+            // Pings are answered automatically, other messages are not used.
+            Ok(_) => (),
+            Err(err) if is_would_block(&err) => return true,
+            Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                return false
+            }
+            Err(err) => {
+                Log::warn(format!("WebSocket connection was closed. Reason: {err}"));
+                return false;
+            }
+        }
+    }
+}
+
+enum ClientSocket {
+    Handshake(MidHandshake<ClientHandshake<TcpStream>>),
+    Open(WebSocket<TcpStream>),
+    Closed,
+}
+
+/// Client-side WebSocket transport. It is connected to a single server, which is addressed by the
+/// URL of the server (see [`Address::Url`]). Datagrams sent before the connection is established
+/// are dropped, the connection layer will resend them if needed.
+pub struct WebSocketTransport {
+    url: String,
+    socket: ClientSocket,
+    incoming: VecDeque<Vec<u8>>,
+}
+
+impl WebSocketTransport {
+    /// Starts connecting to a WebSocket server with the given URL (for example
+    /// `ws://127.0.0.1:5000`). The handshake is done in background, during
+    /// [`Transport::receive`] calls.
+    pub fn connect(url: &str) -> io::Result<Self> {
+        let request = url.into_client_request().map_err(to_io_error)?;
+        if request.uri().scheme_str() != Some("ws") {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unsupported WebSocket URL {url}! Only ws:// URLs are supported."),
+            ));
+        }
+        let host = request
+            .uri()
+            .host()
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "WebSocket URL has no host!"))?
+            .to_string();
+        let port = request.uri().port_u16().unwrap_or(80);
+
+        let stream = TcpStream::connect((host.as_str(), port))?;
+        stream.set_nodelay(true)?;
+        stream.set_nonblocking(true)?;
+
+        let socket = match tungstenite::client(request, stream) {
+            Ok((socket, _)) => ClientSocket::Open(socket),
+            Err(HandshakeError::Interrupted(handshake)) => ClientSocket::Handshake(handshake),
+            Err(HandshakeError::Failure(err)) => return Err(to_io_error(err)),
+        };
+
+        Ok(Self {
+            url: url.to_string(),
+            socket,
+            incoming: Default::default(),
+        })
+    }
+
+    /// Returns the address of the server.
+    pub fn server_address(&self) -> Address {
+        Address::Url(self.url.clone())
+    }
+
+    /// Returns `true` if the connection is established.
+    pub fn is_open(&self) -> bool {
+        matches!(self.socket, ClientSocket::Open(_))
+    }
+
+    fn poll(&mut self) {
+        self.socket = match std::mem::replace(&mut self.socket, ClientSocket::Closed) {
+            ClientSocket::Handshake(handshake) => match handshake.handshake() {
+                Ok((socket, _)) => ClientSocket::Open(socket),
+                Err(HandshakeError::Interrupted(handshake)) => ClientSocket::Handshake(handshake),
+                Err(HandshakeError::Failure(err)) => {
+                    Log::err(format!(
+                        "Unable to connect to WebSocket server {}. Reason: {err}",
+                        self.url
+                    ));
+                    ClientSocket::Closed
+                }
+            },
+            socket => socket,
+        };
+
+        if let ClientSocket::Open(ref mut socket) = self.socket {
+            let incoming = &mut self.incoming;
+            if !read_messages(socket, |data| incoming.push_back(data)) {
+                self.socket = ClientSocket::Closed;
+            }
+        }
+    }
+}
+
+impl Transport for WebSocketTransport {
+    fn send(&mut self, address: &Address, data: &[u8]) -> io::Result<()> {
+        if !matches!(address, Address::Url(url) if *url == self.url) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("WebSocket transport cannot send data to {address}, only to its server!"),
+            ));
+        }
+
+        match self.socket {
+            ClientSocket::Handshake(_) => Ok(()),
+            ClientSocket::Open(ref mut socket) => send_message(socket, data),
+            ClientSocket::Closed => Err(ErrorKind::NotConnected.into()),
+        }
+    }
+
+    fn receive(&mut self) -> Option<(Address, Vec<u8>)> {
+        if self.incoming.is_empty() {
+            self.poll();
+        }
+        self.incoming
+            .pop_front()
+            .map(|data| (self.server_address(), data))
+    }
+}
+
+enum Peer {
+    Handshake(MidHandshake<ServerHandshake<TcpStream, NoCallback>>),
+    Open(WebSocket<TcpStream>),
+}
+
+impl Peer {
+    fn from_handshake_result(
+        address: SocketAddr,
+        result: Result<
+            WebSocket<TcpStream>,
+            HandshakeError<ServerHandshake<TcpStream, NoCallback>>,
+        >,
+    ) -> Option<Self> {
+        match result {
+            Ok(socket) => Some(Peer::Open(socket)),
+            Err(HandshakeError::Interrupted(handshake)) => Some(Peer::Handshake(handshake)),
+            Err(HandshakeError::Failure(err)) => {
+                Log::warn(format!(
+                    "WebSocket handshake with {address} has failed. Reason: {err}"
+                ));
+                None
+            }
+        }
+    }
+}
+
+/// Server-side WebSocket transport. It accepts connections from [`WebSocketTransport`]s, including
+/// the ones running in web browsers. Every connected socket is addressed by its socket address
+/// (see [`Address::Socket`]).
+pub struct WebSocketServerTransport {
+    listener: TcpListener,
+    peers: FxHashMap<SocketAddr, Peer>,
+    incoming: VecDeque<(Address, Vec<u8>)>,
+}
+
+impl WebSocketServerTransport {
+    /// Creates a new transport, that listens for WebSocket connections on the given address.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            peers: Default::default(),
+            incoming: Default::default(),
+        })
+    }
+
+    /// Returns the local address of the listener.
+    pub fn local_address(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Returns amount of open sockets.
+    pub fn peer_count(&self) -> usize {
+        self.peers.len()
+    }
+
+    fn accept(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, address)) => {
+                    if let Err(err) = stream
+                        .set_nonblocking(true)
+                        .and_then(|_| stream.set_nodelay(true))
+                    {
+                        Log::err(format!(
+                            "Unable to configure a socket of {address}. Reason: {err}"
+                        ));
+                        continue;
+                    }
+                    if let Some(peer) =
+                        Peer::from_handshake_result(address, tungstenite::accept(stream))
+                    {
+                        self.peers.insert(address, peer);
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    Log::err(format!(
+                        "Unable to accept a WebSocket connection. Reason: {err}"
+                    ));
+                    break;
+                }
+            }
+        }
+    }
+
+    fn poll(&mut self) {
+        self.accept();
+
+        for (address, peer) in std::mem::take(&mut self.peers) {
+            let peer = match peer {
+                Peer::Handshake(handshake) => {
+                    match Peer::from_handshake_result(address, handshake.handshake()) {
+                        Some(peer) => peer,
+                        None => continue,
+                    }
+                }
+                peer => peer,
+            };
+
+            let peer = match peer {
+                Peer::Open(mut socket) => {
+                    let incoming = &mut self.incoming;
+                    // Closed sockets are simply removed, the connection layer will notice it by
+                    // the timeout.
+                    if !read_messages(&mut socket, |data| {
+                        incoming.push_back((Address::Socket(address), data))
+                    }) {
+                        continue;
+                    }
+                    Peer::Open(socket)
+                }
+                peer => peer,
+            };
+
+            self.peers.insert(address, peer);
+        }
+    }
+}
+
+impl Transport for WebSocketServerTransport {
+    fn send(&mut self, address: &Address, data: &[u8]) -> io::Result<()> {
+        let Address::Socket(address) = address else {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("WebSocket server transport does not support address {address}!"),
+            ));
+        };
+
+        // Datagrams to unknown or not yet connected peers are dropped.
+        match self.peers.get_mut(address) {
+            Some(Peer::Open(socket)) => send_message(socket, data),
+            _ => Ok(()),
+        }
+    }
+
+    fn receive(&mut self) -> Option<(Address, Vec<u8>)> {
+        if self.incoming.is_empty() {
+            self.poll();
+        }
+        self.incoming.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn test_websocket_transport() {
+        let mut server = WebSocketServerTransport::bind("127.0.0.1:0").unwrap();
+        let port = server.local_address().unwrap().port();
+        let mut client = WebSocketTransport::connect(&format!("ws://127.0.0.1:{port}")).unwrap();
+        let server_address = client.server_address();
+
+        let mut response = None;
+        for _ in 0..1000 {
+            // Datagrams are dropped until the connection is established.
+            client.send(&server_address, b"ping").unwrap();
+
+            while let Some((address, data)) = server.receive() {
+                assert_eq!(data, b"ping");
+                server.send(&address, b"pong").unwrap();
+            }
+
+            if let Some((address, data)) = client.receive() {
+                assert_eq!(address, server_address);
+                response = Some(data);
+                break;
+            }
+
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert!(client.is_open());
+        assert_eq!(server.peer_count(), 1);
+        assert_eq!(response.as_deref(), Some(b"pong".as_slice()));
+    }
+}
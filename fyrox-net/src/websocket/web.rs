@@ -0,0 +1,114 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! WebSocket transport for web builds, that uses WebSocket API of the browser.
+
+use crate::transport::{Address, Transport};
+use js_sys::{ArrayBuffer, Uint8Array};
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    io::{self, ErrorKind},
+    rc::Rc,
+};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{BinaryType, MessageEvent, WebSocket};
+
+fn to_io_error(err: JsValue) -> io::Error {
+    io::Error::new(ErrorKind::Other, format!("{err:?}"))
+}
+
+/// Client-side WebSocket transport. It is connected to a single server, which is addressed by the
+/// URL of the server (see [`Address::Url`]). Datagrams sent before the connection is established
+/// are dropped, the connection layer will resend them if needed.
+pub struct WebSocketTransport {
+    url: String,
+    socket: WebSocket,
+    incoming: Rc<RefCell<VecDeque<Vec<u8>>>>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+}
+
+impl WebSocketTransport {
+    /// Starts connecting to a WebSocket server with the given URL (for example
+    /// `wss://example.com:5000`). The connection is established in background by the browser.
+    pub fn connect(url: &str) -> io::Result<Self> {
+        let socket = WebSocket::new(url).map_err(to_io_error)?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+
+        let incoming = Rc::new(RefCell::new(VecDeque::new()));
+        let on_message = {
+            let incoming = incoming.clone();
+            Closure::wrap(Box::new(move |event: MessageEvent| {
+                if let Ok(buffer) = event.data().dyn_into::<ArrayBuffer>() {
+                    incoming
+                        .borrow_mut()
+                        .push_back(Uint8Array::new(&buffer).to_vec());
+                }
+            }) as Box<dyn FnMut(MessageEvent)>)
+        };
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        Ok(Self {
+            url: url.to_string(),
+            socket,
+            incoming,
+            _on_message: on_message,
+        })
+    }
+
+    /// Returns the address of the server.
+    pub fn server_address(&self) -> Address {
+        Address::Url(self.url.clone())
+    }
+
+    /// Returns `true` if the connection is established.
+    pub fn is_open(&self) -> bool {
+        self.socket.ready_state() == WebSocket::OPEN
+    }
+}
+
+impl Transport for WebSocketTransport {
+    fn send(&mut self, address: &Address, data: &[u8]) -> io::Result<()> {
+        if !matches!(address, Address::Url(url) if *url == self.url) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("WebSocket transport cannot send data to {address}, only to its server!"),
+            ));
+        }
+
+        match self.socket.ready_state() {
+            WebSocket::CONNECTING => Ok(()),
+            WebSocket::OPEN => self.socket.send_with_u8_array(data).map_err(to_io_error),
+            _ => Err(ErrorKind::NotConnected.into()),
+        }
+    }
+
+    fn receive(&mut self) -> Option<(Address, Vec<u8>)> {
+        let data = self.incoming.borrow_mut().pop_front()?;
+        Some((self.server_address(), data))
+    }
+}
+
+impl Drop for WebSocketTransport {
+    fn drop(&mut self) {
+        self.socket.set_onmessage(None);
+        let _ = self.socket.close();
+    }
+}
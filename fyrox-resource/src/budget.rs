@@ -0,0 +1,46 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Memory budgets allow to limit the amount of memory occupied by the resources of a particular
+//! category. See [`crate::manager::ResourceManagerState::set_memory_budget`] for more info.
+
+/// A category of resources, that is used to group resources for memory budgets.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ResourceMemoryCategory {
+    /// Textures of any kind.
+    Texture,
+    /// Sound buffers.
+    Sound,
+    /// Meshes (surface data).
+    Mesh,
+    /// Every other kind of resources.
+    #[default]
+    Other,
+}
+
+impl ResourceMemoryCategory {
+    /// All the categories.
+    pub const ALL: [ResourceMemoryCategory; 4] = [
+        ResourceMemoryCategory::Texture,
+        ResourceMemoryCategory::Sound,
+        ResourceMemoryCategory::Mesh,
+        ResourceMemoryCategory::Other,
+    ];
+}
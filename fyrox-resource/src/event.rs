@@ -39,11 +39,32 @@ pub enum ResourceEvent {
     /// Occurs when a resource was already fully loaded, but was reloaded by an explicit request.
     Reloaded(UntypedResource),
 
+    /// Occurs when a resource, that is used by another resource was reloaded. `resource` here is the
+    /// resource that uses the reloaded `dependency`. It could be used to update some derived data
+    /// of the resource (if any).
+    DependencyReloaded {
+        /// A resource that uses the reloaded resource.
+        resource: UntypedResource,
+        /// The reloaded resource.
+        dependency: UntypedResource,
+    },
+
     /// Occurs when a resource was just added to a resource container.
     Added(UntypedResource),
 
     /// Occurs when a resource was removed from a resource container.
     Removed(PathBuf),
+
+    /// Occurs when a loading resource has read more data. Events of this kind are sent at most once
+    /// per resource manager update.
+    Progress {
+        /// A resource that is loading.
+        resource: UntypedResource,
+        /// Total amount of bytes read so far.
+        bytes_loaded: u64,
+        /// Total amount of bytes that is known to be read, could be zero if it is unknown.
+        bytes_total: u64,
+    },
 }
 
 /// Type alias for event sender.
@@ -0,0 +1,491 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Resource IO backend, that fetches resources over HTTP(S). It could be used to load the assets of
+//! WebAssembly builds from a server or to deliver additional content of live-service games. Resource
+//! paths are relative to the base URL of the backend, absolute URLs are used as is.
+//!
+//! Fetched files are cached in memory, optionally they could be cached on disk as well (see
+//! [`HttpResourceIo::with_disk_cache`]). Disk cache is revalidated using `ETag` headers, and it is
+//! used as is if the server is unreachable, so the content remains available offline.
+//!
+//! ```rust,no_run
+//! # use fyrox_resource::{http::HttpResourceIo, manager::ResourceManager};
+//! # use std::sync::Arc;
+//! fn use_http(resource_manager: &ResourceManager) {
+//!     resource_manager.state().set_resource_io(Arc::new(
+//!         HttpResourceIo::new("https://example.com/game/").with_disk_cache("cache"),
+//!     ));
+//! }
+//! ```
+
+use crate::io::{ResourceIo, ResourceIoFuture};
+use fxhash::FxHashMap;
+use fyrox_core::{io::FileLoadError, parking_lot::Mutex};
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Formatter},
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+    sync::Arc,
+};
+
+/// Default limit of the memory cache in bytes.
+pub const DEFAULT_MEMORY_CACHE_LIMIT: usize = 256 * 1024 * 1024;
+
+#[derive(Default)]
+struct MemoryCache {
+    files: FxHashMap<String, Arc<Vec<u8>>>,
+    order: VecDeque<String>,
+    size: usize,
+}
+
+impl MemoryCache {
+    fn get(&self, url: &str) -> Option<Arc<Vec<u8>>> {
+        self.files.get(url).cloned()
+    }
+
+    fn insert(&mut self, url: String, data: Arc<Vec<u8>>, limit: usize) {
+        if data.len() > limit {
+            return;
+        }
+
+        // Evict the oldest files first.
+        while self.size + data.len() > limit {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            if let Some(oldest) = self.files.remove(&oldest) {
+                self.size -= oldest.len();
+            }
+        }
+
+        self.size += data.len();
+        if let Some(previous) = self.files.insert(url.clone(), data) {
+            self.size -= previous.len();
+        } else {
+            self.order.push_back(url);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.files.clear();
+        self.order.clear();
+        self.size = 0;
+    }
+}
+
+/// Resource IO backend, that fetches resources over HTTP(S). See [module docs](self) for more info.
+///
+/// # Platform-specific
+///
+/// - WebAssembly - requests are performed using browser's `fetch` API, disk cache is not supported
+///   (browser cache is used instead).
+/// - Other platforms - requests are performed using a blocking HTTP client on the resource loading
+///   threads.
+pub struct HttpResourceIo {
+    base_url: String,
+    memory_cache: Mutex<MemoryCache>,
+    memory_cache_limit: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    disk_cache: Option<std::path::PathBuf>,
+    #[cfg(not(target_arch = "wasm32"))]
+    agent: ureq::Agent,
+}
+
+impl Debug for HttpResourceIo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpResourceIo")
+            .field("base_url", &self.base_url)
+            .finish()
+    }
+}
+
+impl HttpResourceIo {
+    /// Creates new HTTP resource IO with the given base URL.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            memory_cache: Default::default(),
+            memory_cache_limit: DEFAULT_MEMORY_CACHE_LIMIT,
+            #[cfg(not(target_arch = "wasm32"))]
+            disk_cache: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            agent: ureq::AgentBuilder::new().build(),
+        }
+    }
+
+    /// Sets the maximum size of the memory cache in bytes. Zero disables the memory cache.
+    pub fn with_memory_cache_limit(mut self, limit: usize) -> Self {
+        self.memory_cache_limit = limit;
+        self
+    }
+
+    /// Sets a folder, that will be used to cache fetched files on disk. Has no effect on
+    /// WebAssembly.
+    pub fn with_disk_cache(#[allow(unused_mut)] mut self, path: impl AsRef<Path>) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.disk_cache = Some(path.as_ref().to_path_buf());
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = path;
+        }
+        self
+    }
+
+    /// Returns base URL of the backend.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Returns the URL of a resource at the given path.
+    pub fn url(&self, path: &Path) -> String {
+        let path = path.to_string_lossy().replace('\\', "/");
+        if path.starts_with("http://") || path.starts_with("https://") {
+            return path;
+        }
+        let path = path.trim_start_matches("./").trim_start_matches('/');
+        if self.base_url.ends_with('/') {
+            format!("{}{}", self.base_url, path)
+        } else {
+            format!("{}/{}", self.base_url, path)
+        }
+    }
+
+    /// Removes every file from the memory cache. Disk cache is kept, since it is revalidated on
+    /// every request anyway.
+    pub fn clear_memory_cache(&self) {
+        self.memory_cache.lock().clear();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn disk_cache_paths(&self, url: &str) -> Option<(std::path::PathBuf, std::path::PathBuf)> {
+        let disk_cache = self.disk_cache.as_ref()?;
+        let name = blake3::hash(url.as_bytes()).to_hex();
+        Some((
+            disk_cache.join(name.as_str()),
+            disk_cache.join(format!("{name}.etag")),
+        ))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, FileLoadError> {
+        let cache_paths = self.disk_cache_paths(url);
+        let cached_etag = cache_paths
+            .as_ref()
+            .and_then(|(data_path, etag_path)| {
+                data_path
+                    .exists()
+                    .then(|| std::fs::read_to_string(etag_path).ok())
+            })
+            .flatten();
+
+        let mut request = self.agent.get(url);
+        if let Some(etag) = cached_etag.as_ref() {
+            request = request.set("If-None-Match", etag);
+        }
+
+        match request.call() {
+            Ok(response) if response.status() == 304 => {
+                // The cached file is up-to-date.
+                let (data_path, _) = cache_paths.unwrap();
+                Ok(std::fs::read(data_path)?)
+            }
+            Ok(response) => {
+                let etag = response.header("ETag").map(|etag| etag.to_string());
+                let mut data = Vec::new();
+                response.into_reader().read_to_end(&mut data)?;
+
+                if let Some((data_path, etag_path)) = cache_paths {
+                    std::fs::create_dir_all(self.disk_cache.as_ref().unwrap())?;
+                    std::fs::write(data_path, &data)?;
+                    match etag {
+                        Some(etag) => std::fs::write(etag_path, etag)?,
+                        None => {
+                            let _ = std::fs::remove_file(etag_path);
+                        }
+                    }
+                }
+
+                Ok(data)
+            }
+            Err(ureq::Error::Transport(transport)) => match cache_paths {
+                // Use cached data if the server is unreachable.
+                Some((data_path, _)) if data_path.exists() => Ok(std::fs::read(data_path)?),
+                _ => Err(FileLoadError::Custom(format!(
+                    "Unable to fetch {url}. Reason: {transport}"
+                ))),
+            },
+            Err(ureq::Error::Status(status, _)) => Err(FileLoadError::Custom(format!(
+                "Unable to fetch {url}. Server responded with {status} status."
+            ))),
+        }
+    }
+}
+
+/// A reader, that reads the data from a stream on demand and keeps the data that was read, so it
+/// could be seeked.
+pub struct StreamReader {
+    stream: Box<dyn Read + Send>,
+    buffer: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl Debug for StreamReader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamReader")
+            .field("buffered", &self.buffer.len())
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl StreamReader {
+    /// Creates new stream reader.
+    pub fn new(stream: Box<dyn Read + Send>) -> Self {
+        Self {
+            stream,
+            buffer: Default::default(),
+            position: 0,
+            finished: false,
+        }
+    }
+
+    fn fill_to(&mut self, size: usize) -> std::io::Result<()> {
+        const CHUNK_SIZE: usize = 64 * 1024;
+
+        while !self.finished && self.buffer.len() < size {
+            let old_len = self.buffer.len();
+            self.buffer.resize(old_len + CHUNK_SIZE, 0);
+            let count = self.stream.read(&mut self.buffer[old_len..])?;
+            self.buffer.truncate(old_len + count);
+            self.finished = count == 0;
+        }
+
+        Ok(())
+    }
+}
+
+impl Read for StreamReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.fill_to(self.position + buf.len())?;
+        let available = &self.buffer[self.position.min(self.buffer.len())..];
+        let count = available.len().min(buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+impl Seek for StreamReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::Current(offset) => self.position as i64 + offset,
+            SeekFrom::End(offset) => {
+                // The size is unknown until the entire stream is read.
+                self.fill_to(usize::MAX)?;
+                self.buffer.len() as i64 + offset
+            }
+        };
+
+        if position < 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Seek to a negative position!",
+            ));
+        }
+
+        self.position = position as usize;
+        Ok(self.position as u64)
+    }
+}
+
+impl ResourceIo for HttpResourceIo {
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(async move {
+            let url = self.url(path);
+
+            if let Some(data) = self.memory_cache.lock().get(&url) {
+                return Ok((*data).clone());
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            let data = self.fetch(&url)?;
+
+            #[cfg(target_arch = "wasm32")]
+            let data = fyrox_core::io::load_file(&url).await?;
+
+            self.memory_cache
+                .lock()
+                .insert(url, Arc::new(data.clone()), self.memory_cache_limit);
+
+            Ok(data)
+        })
+    }
+
+    fn move_file<'a>(
+        &'a self,
+        source: &'a Path,
+        _dest: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<(), FileLoadError>> {
+        Box::pin(async move {
+            Err(FileLoadError::Custom(format!(
+                "Unable to move {}. HTTP resource IO is read-only!",
+                source.display()
+            )))
+        })
+    }
+
+    /// Streams the file, if the file is not cached and disk cache is disabled. Otherwise, the file
+    /// is loaded entirely.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn file_reader<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn crate::io::FileReader>, FileLoadError>> {
+        use crate::io::FileReader;
+        use std::io::Cursor;
+
+        Box::pin(async move {
+            let url = self.url(path);
+
+            let cached = self.memory_cache.lock().get(&url);
+            if cached.is_some() || self.disk_cache.is_some() {
+                let data = match cached {
+                    Some(data) => (*data).clone(),
+                    None => self.load_file(path).await?,
+                };
+                let reader: Box<dyn FileReader> = Box::new(Cursor::new(data));
+                return Ok(reader);
+            }
+
+            let response = self.agent.get(&url).call().map_err(|err| {
+                FileLoadError::Custom(format!("Unable to fetch {url}. Reason: {err}"))
+            })?;
+            let reader: Box<dyn FileReader> = Box::new(StreamReader::new(response.into_reader()));
+            Ok(reader)
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn file_size<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, Option<u64>> {
+        Box::pin(async move {
+            let url = self.url(path);
+            if let Some(data) = self.memory_cache.lock().get(&url) {
+                return Some(data.len() as u64);
+            }
+            self.agent
+                .head(&url)
+                .call()
+                .ok()?
+                .header("Content-Length")?
+                .parse()
+                .ok()
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move {
+            let url = self.url(path);
+            if self.memory_cache.lock().get(&url).is_some() {
+                return true;
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                self.agent.head(&url).call().is_ok()
+                    || self
+                        .disk_cache_paths(&url)
+                        .is_some_and(|(data_path, _)| data_path.exists())
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            {
+                fyrox_core::io::exists(&url).await
+            }
+        })
+    }
+
+    fn is_file<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        self.exists(path)
+    }
+
+    fn is_dir<'a>(&'a self, _path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        // HTTP has no concept of directories.
+        Box::pin(async move { false })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_http_resource_io_url() {
+        let io = HttpResourceIo::new("https://example.com/game");
+        assert_eq!(
+            io.url(Path::new("./data/foo.png")),
+            "https://example.com/game/data/foo.png"
+        );
+        assert_eq!(
+            io.url(Path::new("https://cdn.example.com/bar.png")),
+            "https://cdn.example.com/bar.png"
+        );
+    }
+
+    #[test]
+    fn test_stream_reader() {
+        let data = (0..255u8).collect::<Vec<_>>();
+        let mut reader = StreamReader::new(Box::new(Cursor::new(data.clone())));
+
+        let mut buf = [0; 10];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, &data[0..10]);
+
+        reader.seek(SeekFrom::Start(2)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, &data[2..12]);
+
+        assert_eq!(reader.seek(SeekFrom::End(-5)).unwrap(), 250);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, &data[250..]);
+    }
+
+    #[test]
+    fn test_memory_cache_eviction() {
+        let mut cache = MemoryCache::default();
+        cache.insert("a".to_string(), Arc::new(vec![0; 4]), 8);
+        cache.insert("b".to_string(), Arc::new(vec![0; 4]), 8);
+        cache.insert("c".to_string(), Arc::new(vec![0; 4]), 8);
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("b").is_some());
+        assert!(cache.get("c").is_some());
+        assert_eq!(cache.size, 8);
+    }
+}
@@ -103,6 +103,17 @@ pub trait ResourceIo: Send + Sync + 'static {
         })
     }
 
+    /// Returns size of the file at the given path in bytes, if it is known. It is used to report
+    /// loading progress.
+    ///
+    /// Default implementation returns `None`
+    fn file_size<'a>(
+        &'a self,
+        #[allow(unused)] path: &'a Path,
+    ) -> ResourceIoFuture<'a, Option<u64>> {
+        Box::pin(ready(None))
+    }
+
     /// Used to check whether a path exists
     fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool>;
 
@@ -216,6 +227,12 @@ impl ResourceIo for FsResourceIo {
         })
     }
 
+    /// File metadata is not available on wasm and Android assets.
+    #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+    fn file_size<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, Option<u64>> {
+        Box::pin(async move { std::fs::metadata(path).ok().map(|metadata| metadata.len()) })
+    }
+
     fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
         Box::pin(fyrox_core::io::exists(path))
     }
@@ -26,6 +26,7 @@
 #![allow(clippy::mutable_key_type)]
 
 use crate::{
+    budget::ResourceMemoryCategory,
     core::{
         parking_lot::MutexGuard,
         reflect::prelude::*,
@@ -55,14 +56,21 @@ pub use fyrox_core as core;
 use fyrox_core::log::Log;
 use fyrox_core::{combine_uuids, Downcast};
 
+pub mod budget;
 pub mod constructor;
 pub mod entry;
 pub mod event;
 pub mod graph;
+#[cfg(feature = "http")]
+pub mod http;
 pub mod io;
 pub mod loader;
 pub mod manager;
+pub mod mount;
 pub mod options;
+pub mod pack;
+pub mod progress;
+pub mod registry;
 pub mod state;
 pub mod untyped;
 
@@ -94,6 +102,18 @@ pub trait ResourceData: Downcast + Debug + Visit + Send + Reflect {
     /// resource type supports saving, for example there might be temporary resource type that is
     /// used only at runtime which does not need saving at all.
     fn can_be_saved(&self) -> bool;
+
+    /// Returns an approximate amount of memory (in bytes) occupied by the resource data. It is
+    /// used to enforce memory budgets. Default implementation returns zero.
+    fn memory_usage(&self) -> usize {
+        0
+    }
+
+    /// Returns a memory budget category of the resource data. Default implementation returns
+    /// [`ResourceMemoryCategory::Other`].
+    fn memory_category(&self) -> ResourceMemoryCategory {
+        ResourceMemoryCategory::Other
+    }
 }
 
 /// Extension trait for a resource data of a particular type, which adds additional functionality,
@@ -22,6 +22,7 @@
 //! docs for more info.
 
 use crate::{
+    budget::ResourceMemoryCategory,
     collect_used_resources,
     constructor::ResourceConstructorContainer,
     core::{
@@ -38,8 +39,12 @@ use crate::{
     entry::{TimedEntry, DEFAULT_RESOURCE_LIFETIME},
     event::{ResourceEvent, ResourceEventBroadcaster},
     io::{FsResourceIo, ResourceIo},
-    loader::{ResourceLoader, ResourceLoadersContainer},
+    loader::{BoxedLoaderFuture, ResourceLoader, ResourceLoadersContainer},
+    mount::{DirectoryResourceIo, MountPolicy},
     options::OPTIONS_EXTENSION,
+    pack::{PackedResourceIo, ResourcePack},
+    progress::{ProgressResourceIo, ResourceBatch, ResourceLoadPriority, ResourceLoadProgress},
+    registry::{ResourceMetadata, ResourceRegistry, METADATA_EXTENSION},
     state::{LoadError, ResourceState},
     untyped::ResourceKind,
     Resource, ResourceData, TypedResourceData, UntypedResource,
@@ -49,10 +54,17 @@ use rayon::prelude::*;
 use std::borrow::Cow;
 use std::ops::{Deref, DerefMut};
 use std::{
+    cell::Cell,
     fmt::{Debug, Display, Formatter},
+    future::Future,
     marker::PhantomData,
     path::{Path, PathBuf},
-    sync::Arc,
+    pin::Pin,
+    sync::{
+        atomic::{self, AtomicUsize},
+        Arc,
+    },
+    task::{Context, Poll},
 };
 
 /// A set of resources that can be waited for.
@@ -211,10 +223,56 @@ pub struct ResourceManagerState {
     pub built_in_resources: BuiltInResourcesContainer,
     /// File system abstraction interface. Could be used to support virtual file systems.
     pub resource_io: Arc<dyn ResourceIo>,
+    /// Maps stable resource identifiers to resource paths. It is filled by
+    /// [`ResourceManager::scan_resource_metadata`].
+    pub registry: ResourceRegistry,
 
     resources: Vec<TimedEntry<UntypedResource>>,
     task_pool: Arc<TaskPool>,
     watcher: Option<FileSystemWatcher>,
+    max_concurrent_loads: usize,
+    active_loads: Arc<AtomicUsize>,
+    queued_loads: Vec<QueuedLoad>,
+    load_counter: u64,
+    loads_in_progress: Vec<(UntypedResource, Arc<ResourceLoadProgress>)>,
+    reloading: Vec<UntypedResource>,
+    memory_budgets: FxHashMap<ResourceMemoryCategory, usize>,
+    pinned: FxHashSet<UntypedResource>,
+}
+
+thread_local! {
+    /// `true` while a loading task is polled on the current thread.
+    static IS_LOADER_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Loading task, that marks the current thread as a loader thread while it is polled. Requests made
+/// by resource loaders (for example, textures of a model) bypass the limit of simultaneous loads,
+/// otherwise loaders waiting for their dependencies could occupy every slot and stall the loading.
+struct LoaderTask(BoxedLoaderFuture);
+
+impl Future for LoaderTask {
+    type Output = <BoxedLoaderFuture as Future>::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let was_loader_thread = IS_LOADER_THREAD.with(|flag| flag.replace(true));
+        let result = self.0.as_mut().poll(cx);
+        IS_LOADER_THREAD.with(|flag| flag.set(was_loader_thread));
+        result
+    }
+}
+
+/// Default limit of simultaneous loads, that is equal to the amount of logical CPU cores.
+fn default_max_concurrent_loads() -> usize {
+    std::thread::available_parallelism().map_or(4, |count| count.get())
+}
+
+struct QueuedLoad {
+    path: PathBuf,
+    resource: UntypedResource,
+    reload: bool,
+    priority: ResourceLoadPriority,
+    sequence: u64,
+    progress: Arc<ResourceLoadProgress>,
 }
 
 /// Resource manager controls loading and lifetime of resource in the engine. Resource manager can hold
@@ -288,6 +346,33 @@ impl ResourceManager {
         state.resource_io.clone()
     }
 
+    /// Mounts the given asset pack. Every resource, that is stored in the pack, will be loaded
+    /// from it, other resources will be loaded using the previous resource IO. Packs mounted later
+    /// have higher priority. See [`crate::pack`] module docs for more info.
+    pub fn mount_pack(&self, pack: ResourcePack) {
+        self.mount_pack_with_policy(pack, MountPolicy::Override)
+    }
+
+    /// Same as [`Self::mount_pack`], but allows to specify what happens with the files that
+    /// already exist in the previous resource IO. See [`MountPolicy`] docs for more info.
+    pub fn mount_pack_with_policy(&self, pack: ResourcePack, policy: MountPolicy) {
+        let mut state = self.state();
+        let fallback = state.resource_io.clone();
+        state.set_resource_io(Arc::new(
+            PackedResourceIo::new(pack, fallback).with_policy(policy),
+        ));
+    }
+
+    /// Mounts the given directory as an additional asset root. Resource paths are resolved relative
+    /// to the directory first, other resources will be loaded using the previous resource IO.
+    /// Directories mounted later have higher priority. Keep in mind, that already loaded resources
+    /// are not affected, they should be reloaded to pick up the new files.
+    pub fn mount_directory(&self, root: impl AsRef<Path>, policy: MountPolicy) {
+        let mut state = self.state();
+        let fallback = state.resource_io.clone();
+        state.set_resource_io(Arc::new(DirectoryResourceIo::new(root, policy, fallback)));
+    }
+
     /// Returns the task pool used by this resource manager.
     pub fn task_pool(&self) -> Arc<TaskPool> {
         let state = self.state();
@@ -368,6 +453,77 @@ impl ResourceManager {
         self.state().request(path)
     }
 
+    /// The same as [`Self::request`], but allows you to specify the priority of the request. The
+    /// priority matters only if the amount of simultaneous loads is limited, see
+    /// [`ResourceManagerState::set_max_concurrent_loads`] for more info. If the resource is already
+    /// waiting in the loading queue, its priority will be raised to the given one (if it is higher).
+    ///
+    /// ## Panic
+    ///
+    /// This method will panic, if type UUID of `T` does not match the actual type UUID of the resource.
+    pub fn request_with_priority<T>(
+        &self,
+        path: impl AsRef<Path>,
+        priority: ResourceLoadPriority,
+    ) -> Resource<T>
+    where
+        T: TypedResourceData,
+    {
+        let untyped = self.state().request_with_priority(path, priority);
+        let actual_type_uuid = untyped.type_uuid();
+        assert_eq!(actual_type_uuid, <T as TypeUuidProvider>::type_uuid());
+        Resource {
+            untyped,
+            phantom: PhantomData::<T>,
+        }
+    }
+
+    /// Same as [`Self::request_with_priority`], but returns untyped resource.
+    pub fn request_untyped_with_priority<P>(
+        &self,
+        path: P,
+        priority: ResourceLoadPriority,
+    ) -> UntypedResource
+    where
+        P: AsRef<Path>,
+    {
+        self.state().request_with_priority(path, priority)
+    }
+
+    /// Requests a set of resources with the given priority and returns a batch, that could be used
+    /// to track aggregate loading progress of the resources. It is useful for loading screens, that
+    /// should display real loading progress.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// # use fyrox_resource::{manager::ResourceManager, progress::ResourceLoadPriority};
+    /// # fn foo(resource_manager: &ResourceManager) {
+    /// let batch = resource_manager.request_batch(
+    ///     ["data/level1.rgs", "data/music.ogg"],
+    ///     ResourceLoadPriority::Critical,
+    /// );
+    ///
+    /// // Later, on every frame.
+    /// let progress = batch.progress();
+    /// println!("Loaded {}%", progress.percentage());
+    /// # }
+    /// ```
+    pub fn request_batch<I, P>(&self, paths: I, priority: ResourceLoadPriority) -> ResourceBatch
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut state = self.state();
+        let mut batch = ResourceBatch::new();
+        for path in paths {
+            let resource = state.request_with_priority(path, priority);
+            let progress = state.resource_load_progress(&resource);
+            batch.add(resource, progress);
+        }
+        batch
+    }
+
     /// Saves given resources in the specified path and registers it in resource manager, so
     /// it will be accessible through it later.
     pub fn register<P, F>(
@@ -392,6 +548,7 @@ impl ResourceManager {
 
         let mut header = resource.0.lock();
         header.kind.make_external(path.as_ref().to_path_buf());
+        header.resource_uuid = state.registry.path_to_uuid(path.as_ref());
         if let ResourceState::Ok(ref mut data) = header.state {
             if !on_register(&mut **data, path.as_ref()) {
                 Err(ResourceRegistrationError::UnableToRegister)
@@ -505,10 +662,168 @@ impl ResourceManager {
             let new_options_path = append_extension(&new_path, OPTIONS_EXTENSION);
             io.move_file(&options_path, &new_options_path).await?;
         }
+        let metadata_path = append_extension(&existing_path, METADATA_EXTENSION);
+        if io.exists(&metadata_path).await {
+            let new_metadata_path = append_extension(&new_path, METADATA_EXTENSION);
+            io.move_file(&metadata_path, &new_metadata_path).await?;
+        }
+
+        let mut state = self.state();
+        if let Some(resource_uuid) = state.registry.unregister_path(&existing_path) {
+            state.registry.register(resource_uuid, &new_path);
+        }
 
         Ok(())
     }
 
+    /// Scans the given directory for resources and registers their stable identifiers (stored in
+    /// metadata files) in the resource registry. If `create_missing` is `true`, metadata files
+    /// will be created for every resource that does not have one yet. Resources copied together
+    /// with their metadata files get new identifiers in this case as well. The previous content
+    /// of the registry is discarded, so the directory should be the root directory of the
+    /// project. Returns the amount of registered resources.
+    pub async fn scan_resource_metadata(
+        &self,
+        root: impl AsRef<Path>,
+        create_missing: bool,
+    ) -> Result<usize, FileLoadError> {
+        let io = self.resource_io();
+
+        let paths = io
+            .walk_directory(root.as_ref())
+            .await?
+            .filter(|path| self.state().find_loader(path).is_some())
+            .collect::<Vec<_>>();
+
+        let mut registry = ResourceRegistry::default();
+        for path in paths {
+            let metadata_path = append_extension(&path, METADATA_EXTENSION);
+            let mut metadata =
+                match ResourceMetadata::load_from_file(&metadata_path, io.as_ref()).await {
+                    Ok(metadata) => metadata,
+                    Err(_) if create_missing => ResourceMetadata::new_with_random_id(),
+                    Err(_) => continue,
+                };
+
+            let is_copy = match registry.uuid_to_path(metadata.resource_uuid) {
+                Some(original_path) if !create_missing => {
+                    Log::warn(format!(
+                        "Resource {} has the same identifier as {}!",
+                        path.display(),
+                        original_path.display()
+                    ));
+                    continue;
+                }
+                Some(_) => {
+                    metadata = ResourceMetadata::new_with_random_id();
+                    true
+                }
+                None => false,
+            };
+
+            if is_copy || !io.exists(&metadata_path).await {
+                if let Err(err) = metadata.save_sync(&metadata_path) {
+                    Log::err(format!(
+                        "Unable to save metadata file {}. Reason: {:?}",
+                        metadata_path.display(),
+                        err
+                    ));
+                    continue;
+                }
+            }
+
+            registry.register(metadata.resource_uuid, &path);
+        }
+
+        let count = registry.len();
+
+        let mut state = self.state();
+        for resource in state.resources.iter() {
+            let mut header = resource.0.lock();
+            let resource_uuid = header
+                .kind
+                .path()
+                .and_then(|path| registry.path_to_uuid(path));
+            header.resource_uuid = resource_uuid;
+        }
+        state.registry.replace_entries(registry);
+
+        Ok(count)
+    }
+
+    /// Saves back every resource in the given directory, that references resources that were moved
+    /// (i.e. references, that were resolved using stable identifiers, not the paths). This way the
+    /// paths stored in the resources will be actualized. `filter` could be used to skip the resources
+    /// that should not (or cannot) be saved. Returns the amount of fixed resources.
+    pub async fn fix_redirected_references(
+        &self,
+        working_directory: impl AsRef<Path>,
+        mut filter: impl FnMut(&UntypedResource) -> bool,
+    ) -> Result<usize, FileLoadError> {
+        let io = self.resource_io();
+
+        // Loading of the resources resolves the references and collects the redirected ones.
+        let resources = io
+            .walk_directory(working_directory.as_ref())
+            .await?
+            .filter(|path| self.state().find_loader(path).is_some())
+            .map(|path| self.request_untyped(path))
+            .collect::<Vec<_>>();
+        let resources = join_all(resources)
+            .await
+            .into_iter()
+            .filter_map(|resource| resource.ok())
+            .filter(|resource| filter(resource))
+            .collect::<Vec<_>>();
+
+        let redirected = self.state().registry.take_redirected();
+        if redirected.is_empty() {
+            return Ok(0);
+        }
+
+        let mut count = 0;
+        for resource in resources {
+            let mut header = resource.0.lock();
+            let Some(path) = header.kind.path_owned() else {
+                continue;
+            };
+            let ResourceState::Ok(ref mut data) = header.state else {
+                continue;
+            };
+
+            let mut used_resources = FxHashSet::default();
+            (**data).as_reflect(&mut |reflect| {
+                collect_used_resources(reflect, &mut used_resources);
+            });
+
+            let has_redirected_references = used_resources.iter().any(|used_resource| {
+                used_resource
+                    .kind()
+                    .path()
+                    .is_some_and(|used_path| redirected.contains(used_path))
+            });
+
+            if has_redirected_references {
+                match data.save(&path) {
+                    Ok(_) => {
+                        Log::info(format!(
+                            "References of resource {} were fixed successfully!",
+                            path.display()
+                        ));
+                        count += 1;
+                    }
+                    Err(err) => Log::err(format!(
+                        "Unable to save {} resource. Reason: {:?}",
+                        path.display(),
+                        err
+                    )),
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
     /// Reloads all loaded resources. Normally it should never be called, because it is **very** heavy
     /// method! This method is asynchronous, it uses all available CPU power to reload resources as
     /// fast as possible.
@@ -530,6 +845,15 @@ impl ResourceManagerState {
             built_in_resources: Default::default(),
             // Use the file system resource io by default
             resource_io: Arc::new(FsResourceIo),
+            registry: Default::default(),
+            max_concurrent_loads: default_max_concurrent_loads(),
+            active_loads: Default::default(),
+            queued_loads: Default::default(),
+            load_counter: 0,
+            loads_in_progress: Default::default(),
+            reloading: Default::default(),
+            memory_budgets: Default::default(),
+            pinned: Default::default(),
         }
     }
 
@@ -552,6 +876,158 @@ impl ResourceManagerState {
         self.watcher = watcher;
     }
 
+    /// Sets the maximum amount of resources that could be loaded simultaneously. Other requests will
+    /// wait in a queue and will be started in the order of their priority (see [`ResourceLoadPriority`])
+    /// on [`Self::update`] calls. By default, the limit is equal to the amount of logical CPU cores
+    /// (or 4, if it cannot be determined).
+    ///
+    /// ## Important notes
+    ///
+    /// Some resource loaders (for example, model loaders) request and wait for other resources. Such
+    /// requests are started immediately, regardless of the limit, otherwise the loaders waiting for
+    /// their dependencies could occupy every slot and stall the loading.
+    pub fn set_max_concurrent_loads(&mut self, max_concurrent_loads: usize) {
+        self.max_concurrent_loads = max_concurrent_loads.max(1);
+        self.dispatch_queued_loads();
+    }
+
+    /// Returns the maximum amount of resources that could be loaded simultaneously.
+    pub fn max_concurrent_loads(&self) -> usize {
+        self.max_concurrent_loads
+    }
+
+    /// Sets a memory budget (in bytes) for the resources of the given category. When the total
+    /// memory usage of the resources of the category exceeds the budget, the manager starts to
+    /// unload unused resources of this category, starting from the least recently used ones.
+    /// Resources, that are used somewhere (or pinned, see [`Self::pin`]), are never unloaded, so
+    /// the budget is a soft limit. Unloaded resources will be loaded again on next request.
+    /// `None` removes the budget, which is the default.
+    pub fn set_memory_budget(&mut self, category: ResourceMemoryCategory, budget: Option<usize>) {
+        match budget {
+            Some(budget) => {
+                self.memory_budgets.insert(category, budget);
+            }
+            None => {
+                self.memory_budgets.remove(&category);
+            }
+        }
+    }
+
+    /// Returns a memory budget (in bytes) of the given category, if any.
+    pub fn memory_budget(&self, category: ResourceMemoryCategory) -> Option<usize> {
+        self.memory_budgets.get(&category).cloned()
+    }
+
+    /// Returns total amount of memory (in bytes) occupied by the loaded resources of the given
+    /// category. See [`ResourceData::memory_usage`] for more info.
+    pub fn memory_usage(&self, category: ResourceMemoryCategory) -> usize {
+        self.resources
+            .iter()
+            .map(|entry| {
+                let header = entry.value.0.lock();
+                match header.state {
+                    ResourceState::Ok(ref data) if data.memory_category() == category => {
+                        data.memory_usage()
+                    }
+                    _ => 0,
+                }
+            })
+            .sum()
+    }
+
+    /// Pins the given resource, which means that it will never be unloaded by the manager, even if
+    /// it is not used anywhere. Could be useful to keep critical resources (for example, the ones
+    /// that are used in loading screens) always loaded.
+    pub fn pin(&mut self, resource: &UntypedResource) {
+        self.pinned.insert(resource.clone());
+    }
+
+    /// Unpins the given resource, so it will be unloaded once it is not used anywhere.
+    pub fn unpin(&mut self, resource: &UntypedResource) {
+        self.pinned.remove(resource);
+    }
+
+    /// Returns `true` if the given resource is pinned, `false` - otherwise.
+    pub fn is_pinned(&self, resource: &UntypedResource) -> bool {
+        self.pinned.contains(resource)
+    }
+
+    fn enforce_memory_budgets(&mut self) {
+        if self.memory_budgets.is_empty() {
+            return;
+        }
+
+        let mut usage = FxHashMap::<ResourceMemoryCategory, usize>::default();
+        let mut candidates = Vec::new();
+        for (index, entry) in self.resources.iter().enumerate() {
+            let header = entry.value.0.lock();
+            if let ResourceState::Ok(ref data) = header.state {
+                let category = data.memory_category();
+                let size = data.memory_usage();
+                *usage.entry(category).or_default() += size;
+                // Only external resources could be loaded again on demand. Pinned resources have
+                // an extra strong reference, so they will never get here.
+                if entry.value.use_count() <= 1 && header.kind.is_external() {
+                    candidates.push((index, category, size, entry.time_to_live));
+                }
+            }
+        }
+
+        // Least recently used resources have the smallest time to live.
+        candidates.sort_by(|a, b| a.3.total_cmp(&b.3));
+
+        let mut evicted = FxHashSet::default();
+        for (index, category, size, _) in candidates {
+            let Some(budget) = self.memory_budgets.get(&category) else {
+                continue;
+            };
+            let category_usage = usage.entry(category).or_default();
+            if *category_usage > *budget {
+                *category_usage -= size;
+                evicted.insert(index);
+            }
+        }
+
+        if evicted.is_empty() {
+            return;
+        }
+
+        let mut index = 0;
+        self.resources.retain(|entry| {
+            let keep = !evicted.contains(&index);
+            index += 1;
+            if !keep {
+                if let Some(path) = entry.0.lock().kind.path_owned() {
+                    Log::info(format!(
+                        "Resource {} unloaded because its memory budget is exceeded!",
+                        path.display()
+                    ));
+
+                    self.event_broadcaster
+                        .broadcast(ResourceEvent::Removed(path));
+                }
+            }
+            keep
+        });
+    }
+
+    /// Returns total amount of resources that are waiting in the loading queue.
+    pub fn count_queued_loads(&self) -> usize {
+        self.queued_loads.len()
+    }
+
+    /// Returns loading progress of the given resource. Returns `None` if the resource is not
+    /// loading.
+    pub fn resource_load_progress(
+        &self,
+        resource: &UntypedResource,
+    ) -> Option<Arc<ResourceLoadProgress>> {
+        self.loads_in_progress
+            .iter()
+            .find(|(r, _)| r == resource)
+            .map(|(_, progress)| progress.clone())
+    }
+
     /// Returns total amount of registered resources.
     pub fn count_registered_resources(&self) -> usize {
         self.resources.len()
@@ -609,24 +1085,106 @@ impl ResourceManagerState {
             }
         });
 
+        self.enforce_memory_budgets();
+
+        self.dispatch_queued_loads();
+
+        let event_broadcaster = &self.event_broadcaster;
+        self.loads_in_progress.retain(|(resource, progress)| {
+            if progress.take_changed() {
+                event_broadcaster.broadcast(ResourceEvent::Progress {
+                    resource: resource.clone(),
+                    bytes_loaded: progress.bytes_loaded(),
+                    bytes_total: progress.bytes_total(),
+                });
+            }
+            !progress.is_finished()
+        });
+
+        self.notify_dependents_of_reloaded_resources();
+
+        let mut changed_paths = Vec::new();
         if let Some(watcher) = self.watcher.as_ref() {
-            if let Some(evt) = watcher.try_get_event() {
-                if let notify::EventKind::Modify(_) = evt.kind {
-                    for path in evt.paths {
-                        if let Ok(relative_path) = make_relative_path(path) {
-                            if self.try_reload_resource_from_path(&relative_path) {
-                                Log::info(format!(
-                                        "File {} was changed, trying to reload a respective resource...",
-                                        relative_path.display()
-                                    ));
-
-                                break;
-                            }
-                        }
-                    }
+            while let Some(evt) = watcher.try_get_event() {
+                // Some editors save files by writing a new file and renaming it, so creation
+                // events must be handled too.
+                if let notify::EventKind::Modify(_) | notify::EventKind::Create(_) = evt.kind {
+                    changed_paths.extend(evt.paths);
                 }
             }
         }
+
+        for path in changed_paths {
+            if let Ok(mut relative_path) = make_relative_path(path) {
+                // Changes in import options must reload the resource itself.
+                if relative_path
+                    .extension()
+                    .is_some_and(|ext| ext == OPTIONS_EXTENSION)
+                {
+                    relative_path.set_extension("");
+                }
+
+                if self.try_reload_resource_from_path(&relative_path) {
+                    Log::info(format!(
+                        "File {} was changed, trying to reload a respective resource...",
+                        relative_path.display()
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Returns a list of resources that directly use the given resource. Only loaded resources are
+    /// checked. This method uses reflection to find the usages, so it is quite slow.
+    pub fn find_dependents(&self, resource: &UntypedResource) -> Vec<UntypedResource> {
+        let mut dependents = Vec::new();
+        for entry in self.resources.iter() {
+            let candidate = &entry.value;
+            if candidate == resource {
+                continue;
+            }
+
+            let mut used_resources = FxHashSet::default();
+            {
+                let header = candidate.0.lock();
+                if let ResourceState::Ok(ref data) = header.state {
+                    (**data).as_reflect(&mut |entity| {
+                        collect_used_resources(entity, &mut used_resources);
+                    });
+                }
+            }
+
+            if used_resources.contains(resource) {
+                dependents.push(candidate.clone());
+            }
+        }
+        dependents
+    }
+
+    fn notify_dependents_of_reloaded_resources(&mut self) {
+        let mut reloaded = Vec::new();
+        self.reloading.retain(|resource| {
+            if resource.is_loading() {
+                true
+            } else {
+                reloaded.push(resource.clone());
+                false
+            }
+        });
+
+        for resource in reloaded {
+            if !matches!(resource.0.lock().state, ResourceState::Ok(_)) {
+                continue;
+            }
+
+            for dependent in self.find_dependents(&resource) {
+                self.event_broadcaster
+                    .broadcast(ResourceEvent::DependencyReloaded {
+                        resource: dependent,
+                        dependency: resource.clone(),
+                    });
+            }
+        }
     }
 
     /// Adds a new resource in the container.
@@ -706,6 +1264,19 @@ impl ResourceManagerState {
 
     /// Tries to load a resources at a given path.
     pub fn request<P>(&mut self, path: P) -> UntypedResource
+    where
+        P: AsRef<Path>,
+    {
+        self.request_with_priority(path, ResourceLoadPriority::Normal)
+    }
+
+    /// Tries to load a resources at a given path with the given priority. See
+    /// [`Self::set_max_concurrent_loads`] for more info about priorities.
+    pub fn request_with_priority<P>(
+        &mut self,
+        path: P,
+        priority: ResourceLoadPriority,
+    ) -> UntypedResource
     where
         P: AsRef<Path>,
     {
@@ -713,15 +1284,32 @@ impl ResourceManagerState {
             return built_in_resource.resource.clone();
         }
 
-        match self.find(path.as_ref()) {
-            Some(existing) => existing.clone(),
+        match self.find(path.as_ref()).cloned() {
+            Some(existing) => {
+                if let Some(index) = self
+                    .queued_loads
+                    .iter()
+                    .position(|queued| queued.resource == existing)
+                {
+                    if IS_LOADER_THREAD.with(Cell::get) {
+                        // Another loader waits for the resource, it must not wait in the queue.
+                        let queued = self.queued_loads.swap_remove(index);
+                        self.dispatch_load(queued);
+                    } else {
+                        let queued = &mut self.queued_loads[index];
+                        queued.priority = queued.priority.max(priority);
+                    }
+                }
+                existing
+            }
             None => {
                 let path = path.as_ref().to_owned();
                 let kind = ResourceKind::External(path.clone());
 
                 if let Some(loader) = self.find_loader(path.as_ref()) {
                     let resource = UntypedResource::new_pending(kind, loader.data_type_uuid());
-                    self.spawn_loading_task(path, resource.clone(), loader, false);
+                    resource.0.lock().resource_uuid = self.registry.path_to_uuid(&path);
+                    self.queue_load(path, resource.clone(), false, priority);
                     self.push(resource.clone());
                     resource
                 } else {
@@ -741,17 +1329,89 @@ impl ResourceManagerState {
         })
     }
 
+    fn queue_load(
+        &mut self,
+        path: PathBuf,
+        resource: UntypedResource,
+        reload: bool,
+        priority: ResourceLoadPriority,
+    ) {
+        let progress = Arc::new(ResourceLoadProgress::default());
+        self.loads_in_progress
+            .push((resource.clone(), progress.clone()));
+        self.queued_loads.push(QueuedLoad {
+            path,
+            resource,
+            reload,
+            priority,
+            sequence: self.load_counter,
+            progress,
+        });
+        self.load_counter += 1;
+
+        if IS_LOADER_THREAD.with(Cell::get) {
+            // The request is made by another loader, start it right away.
+            let queued = self.queued_loads.pop().unwrap();
+            self.dispatch_load(queued);
+        } else {
+            self.dispatch_queued_loads();
+        }
+    }
+
+    fn dispatch_queued_loads(&mut self) {
+        while self.active_loads.load(atomic::Ordering::Acquire) < self.max_concurrent_loads {
+            // Highest priority first, then the oldest request first.
+            let Some(index) = self
+                .queued_loads
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, queued)| (queued.priority, std::cmp::Reverse(queued.sequence)))
+                .map(|(index, _)| index)
+            else {
+                break;
+            };
+
+            let queued = self.queued_loads.swap_remove(index);
+            self.dispatch_load(queued);
+        }
+    }
+
+    fn dispatch_load(&self, queued: QueuedLoad) {
+        if let Some(loader) = self.find_loader(&queued.path) {
+            self.spawn_loading_task(
+                queued.path,
+                queued.resource,
+                loader,
+                queued.reload,
+                queued.progress,
+            );
+        } else {
+            queued.progress.finish();
+            queued.resource.commit_error(format!(
+                "There's no resource loader for {} resource!",
+                queued.path.display()
+            ));
+        }
+    }
+
     fn spawn_loading_task(
         &self,
         path: PathBuf,
         resource: UntypedResource,
         loader: &dyn ResourceLoader,
         reload: bool,
+        progress: Arc<ResourceLoadProgress>,
     ) {
         let event_broadcaster = self.event_broadcaster.clone();
-        let loader_future = loader.load(path.clone(), self.resource_io.clone());
+        let active_loads = self.active_loads.clone();
+        active_loads.fetch_add(1, atomic::Ordering::AcqRel);
+        let io = Arc::new(ProgressResourceIo {
+            inner: self.resource_io.clone(),
+            progress: progress.clone(),
+        });
+        let loader_future = loader.load(path.clone(), io);
         self.task_pool.spawn_task(async move {
-            match loader_future.await {
+            match LoaderTask(loader_future).await {
                 Ok(data) => {
                     let data = data.0;
 
@@ -768,6 +1428,9 @@ impl ResourceManagerState {
                         mutex_guard.state.commit(ResourceState::Ok(data));
                     }
 
+                    progress.finish();
+                    active_loads.fetch_sub(1, atomic::Ordering::AcqRel);
+
                     event_broadcaster.broadcast_loaded_or_reloaded(resource, reload);
                 }
                 Err(error) => {
@@ -778,6 +1441,9 @@ impl ResourceManagerState {
                     ));
 
                     resource.commit_error(error);
+
+                    progress.finish();
+                    active_loads.fetch_sub(1, atomic::Ordering::AcqRel);
                 }
             }
         });
@@ -789,11 +1455,12 @@ impl ResourceManagerState {
 
         if !header.state.is_loading() {
             if let Some(path) = header.kind.path_owned() {
-                if let Some(loader) = self.find_loader(&path) {
+                if self.find_loader(&path).is_some() {
                     header.state.switch_to_pending_state();
                     drop(header);
 
-                    self.spawn_loading_task(path, resource, loader, true);
+                    self.reloading.push(resource.clone());
+                    self.queue_load(path, resource, true, ResourceLoadPriority::Normal);
                 } else {
                     let msg = format!(
                         "There's no resource loader for {} resource!",
@@ -981,6 +1648,126 @@ mod test {
         assert_eq!(state.len(), 3);
     }
 
+    #[derive(Debug, Default, Reflect, Visit)]
+    struct StubWithDependency {
+        dependency: Option<UntypedResource>,
+    }
+
+    impl TypeUuidProvider for StubWithDependency {
+        fn type_uuid() -> Uuid {
+            uuid!("b0b2fd4b-3b8b-4b8a-9a56-0c3f2f3b0c8e")
+        }
+    }
+
+    impl ResourceData for StubWithDependency {
+        fn type_uuid(&self) -> Uuid {
+            <Self as TypeUuidProvider>::type_uuid()
+        }
+
+        fn save(&mut self, _path: &Path) -> Result<(), Box<dyn Error>> {
+            Err("Saving is not supported!".to_string().into())
+        }
+
+        fn can_be_saved(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn resource_manager_state_find_dependents() {
+        let mut state = new_resource_manager();
+
+        let dependency = UntypedResource::new_ok(Default::default(), Stub {});
+        let dependent = UntypedResource::new_ok(
+            Default::default(),
+            StubWithDependency {
+                dependency: Some(dependency.clone()),
+            },
+        );
+        let unrelated = UntypedResource::new_ok(Default::default(), Stub {});
+        state.push(dependency.clone());
+        state.push(dependent.clone());
+        state.push(unrelated.clone());
+
+        assert_eq!(state.find_dependents(&dependency), vec![dependent]);
+        assert!(state.find_dependents(&unrelated).is_empty());
+    }
+
+    #[derive(Debug, Default, Reflect, Visit)]
+    struct StubWithSize {
+        size: usize,
+    }
+
+    impl TypeUuidProvider for StubWithSize {
+        fn type_uuid() -> Uuid {
+            uuid!("5c1e3a4c-7d0e-4f5b-8a0e-2b9d6f4e1a37")
+        }
+    }
+
+    impl ResourceData for StubWithSize {
+        fn type_uuid(&self) -> Uuid {
+            <Self as TypeUuidProvider>::type_uuid()
+        }
+
+        fn save(&mut self, _path: &Path) -> Result<(), Box<dyn Error>> {
+            Err("Saving is not supported!".to_string().into())
+        }
+
+        fn can_be_saved(&self) -> bool {
+            false
+        }
+
+        fn memory_usage(&self) -> usize {
+            self.size
+        }
+
+        fn memory_category(&self) -> ResourceMemoryCategory {
+            ResourceMemoryCategory::Texture
+        }
+    }
+
+    #[test]
+    fn resource_manager_state_memory_budget() {
+        let mut state = new_resource_manager();
+
+        let make = |name: &str| {
+            UntypedResource::new_ok(PathBuf::from(name).into(), StubWithSize { size: 100 })
+        };
+        let oldest = make("oldest.txt");
+        let pinned = make("pinned.txt");
+        let used = make("used.txt");
+        let newest = make("newest.txt");
+        for resource in [&oldest, &pinned, &used, &newest] {
+            state.push(resource.clone());
+        }
+        state.pin(&pinned);
+        assert!(state.is_pinned(&pinned));
+        assert_eq!(state.memory_usage(ResourceMemoryCategory::Texture), 400);
+
+        // Keep one external reference to the resource.
+        let _used = used.clone();
+        drop((oldest, pinned, used, newest));
+
+        // Simulate that the first resource is unused for a long time.
+        state.resources[0].time_to_live = 1.0;
+
+        state.set_memory_budget(ResourceMemoryCategory::Texture, Some(300));
+        state.update(0.0);
+
+        // The least recently used resource must be unloaded first.
+        assert_eq!(state.len(), 3);
+        assert!(state.find("oldest.txt").is_none());
+        assert_eq!(state.memory_usage(ResourceMemoryCategory::Texture), 300);
+
+        state.set_memory_budget(ResourceMemoryCategory::Texture, Some(0));
+        state.update(0.0);
+
+        // Pinned and used resources must stay.
+        assert_eq!(state.len(), 2);
+        assert!(state.find("pinned.txt").is_some());
+        assert!(state.find("used.txt").is_some());
+    }
+
     #[test]
     fn resource_manager_state_loading_progress() {
         let mut state = new_resource_manager();
@@ -1087,6 +1874,26 @@ mod test {
         assert!(resource.is_loading());
     }
 
+    #[test]
+    fn resource_manager_state_loader_requests_bypass_limit() {
+        let mut state = new_resource_manager();
+        assert!(state.max_concurrent_loads() < usize::MAX);
+        state.loaders.set(Stub {});
+        state.set_max_concurrent_loads(1);
+        // Simulate a loader, that waits for its dependencies.
+        state.active_loads.store(1, atomic::Ordering::Release);
+
+        state.request("queued.txt");
+        assert_eq!(state.queued_loads.len(), 1);
+
+        IS_LOADER_THREAD.with(|flag| flag.set(true));
+        state.request("dependency.txt");
+        state.request("queued.txt");
+        IS_LOADER_THREAD.with(|flag| flag.set(false));
+
+        assert!(state.queued_loads.is_empty());
+    }
+
     #[test]
     fn resource_manager_state_get_wait_context() {
         let mut state = new_resource_manager();
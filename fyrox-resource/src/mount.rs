@@ -0,0 +1,262 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Mounting of additional asset roots. Mounted roots are layered on top of the current resource
+//! IO of a resource manager, which allows mods and patches to add new assets or override the
+//! existing ones without touching the original files. See [`MountPolicy`] and
+//! [`DirectoryResourceIo`] docs for more info.
+
+use crate::{
+    io::{FileReader, FsResourceIo, PathIter, ResourceIo, ResourceIoFuture},
+    pack::normalize_path,
+};
+use fxhash::FxHashSet;
+use fyrox_core::io::FileLoadError;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Defines what happens when a mounted asset root contains a file, that already exists in the
+/// layers below it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MountPolicy {
+    /// Files of the mounted root replace the existing files.
+    #[default]
+    Override,
+    /// Only new files are taken from the mounted root, existing files are left untouched.
+    Append,
+}
+
+/// Resource IO, that reads files from a directory on the file system as if its content was located
+/// at the root of the resource paths. For example, if `mods/foo` directory is mounted, then
+/// `data/textures/bar.png` path is resolved to `mods/foo/data/textures/bar.png`. Every other request
+/// is forwarded to another (fallback) resource IO.
+pub struct DirectoryResourceIo {
+    root: PathBuf,
+    policy: MountPolicy,
+    fs: FsResourceIo,
+    fallback: Arc<dyn ResourceIo>,
+}
+
+impl DirectoryResourceIo {
+    /// Creates a new resource IO for the given directory.
+    pub fn new(root: impl AsRef<Path>, policy: MountPolicy, fallback: Arc<dyn ResourceIo>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            policy,
+            fs: FsResourceIo,
+            fallback,
+        }
+    }
+
+    /// Returns the mounted directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the mount policy.
+    pub fn policy(&self) -> MountPolicy {
+        self.policy
+    }
+
+    fn local_path(&self, path: &Path) -> PathBuf {
+        self.root.join(normalize_path(path))
+    }
+
+    /// Returns `true` if the file at the given path should be read from the mounted directory.
+    async fn provides_file(&self, path: &Path) -> bool {
+        self.fs.is_file(&self.local_path(path)).await
+            && (self.policy == MountPolicy::Override || !self.fallback.exists(path).await)
+    }
+
+    fn merge_directory_content(
+        &self,
+        local: Option<PathIter>,
+        fallback: Option<PathIter>,
+    ) -> PathIter {
+        let mut seen = FxHashSet::default();
+        let mut content = Vec::new();
+        let local = local.into_iter().flatten().filter_map(|local| {
+            local
+                .strip_prefix(&self.root)
+                .ok()
+                .filter(|relative| !relative.as_os_str().is_empty())
+                .map(Path::to_path_buf)
+        });
+        for path in local.chain(fallback.into_iter().flatten()) {
+            if seen.insert(normalize_path(&path)) {
+                content.push(path);
+            }
+        }
+        Box::new(content.into_iter())
+    }
+}
+
+impl ResourceIo for DirectoryResourceIo {
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(async move {
+            if self.provides_file(path).await {
+                self.fs.load_file(&self.local_path(path)).await
+            } else {
+                self.fallback.load_file(path).await
+            }
+        })
+    }
+
+    fn move_file<'a>(
+        &'a self,
+        source: &'a Path,
+        dest: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<(), FileLoadError>> {
+        // Mounted roots are read-only.
+        self.fallback.move_file(source, dest)
+    }
+
+    fn canonicalize_path<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathBuf, FileLoadError>> {
+        Box::pin(async move {
+            if self.fs.exists(&self.local_path(path)).await {
+                Ok(PathBuf::from(normalize_path(path)))
+            } else {
+                self.fallback.canonicalize_path(path).await
+            }
+        })
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathIter, FileLoadError>> {
+        Box::pin(async move {
+            let local = self.fs.read_directory(&self.local_path(path)).await.ok();
+            let fallback = self.fallback.read_directory(path).await.ok();
+            Ok(self.merge_directory_content(local, fallback))
+        })
+    }
+
+    fn walk_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathIter, FileLoadError>> {
+        Box::pin(async move {
+            let local = self.fs.walk_directory(&self.local_path(path)).await.ok();
+            let fallback = self.fallback.walk_directory(path).await.ok();
+            Ok(self.merge_directory_content(local, fallback))
+        })
+    }
+
+    fn file_reader<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn FileReader>, FileLoadError>> {
+        Box::pin(async move {
+            if self.provides_file(path).await {
+                self.fs.file_reader(&self.local_path(path)).await
+            } else {
+                self.fallback.file_reader(path).await
+            }
+        })
+    }
+
+    fn file_size<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, Option<u64>> {
+        Box::pin(async move {
+            if self.provides_file(path).await {
+                self.fs.file_size(&self.local_path(path)).await
+            } else {
+                self.fallback.file_size(path).await
+            }
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move {
+            self.fs.exists(&self.local_path(path)).await || self.fallback.exists(path).await
+        })
+    }
+
+    fn is_file<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move {
+            self.fs.is_file(&self.local_path(path)).await || self.fallback.is_file(path).await
+        })
+    }
+
+    fn is_dir<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move {
+            self.fs.is_dir(&self.local_path(path)).await || self.fallback.is_dir(path).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fyrox_core::futures::executor::block_on;
+
+    #[test]
+    fn test_directory_resource_io() {
+        let directory = std::env::temp_dir().join("fyrox_directory_resource_io_test");
+        let base = directory.join("base");
+        let layer = directory.join("mod");
+        for (root, file, content) in [
+            (&base, "data/a.txt", "base a"),
+            (&base, "data/b.txt", "base b"),
+            (&layer, "data/a.txt", "mod a"),
+            (&layer, "data/c.txt", "mod c"),
+        ] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        let base_io: Arc<dyn ResourceIo> = Arc::new(DirectoryResourceIo::new(
+            &base,
+            MountPolicy::Override,
+            Arc::new(FsResourceIo),
+        ));
+
+        let load = |io: &DirectoryResourceIo, path: &str| {
+            String::from_utf8(block_on(io.load_file(Path::new(path))).unwrap()).unwrap()
+        };
+
+        let io = DirectoryResourceIo::new(&layer, MountPolicy::Override, base_io.clone());
+        assert_eq!(load(&io, "data/a.txt"), "mod a");
+        assert_eq!(load(&io, "data/b.txt"), "base b");
+        assert_eq!(load(&io, "data/c.txt"), "mod c");
+
+        let mut content = block_on(io.read_directory(Path::new("data")))
+            .unwrap()
+            .map(|path| normalize_path(&path))
+            .collect::<Vec<_>>();
+        content.sort();
+        assert_eq!(content, vec!["data/a.txt", "data/b.txt", "data/c.txt"]);
+
+        let io = DirectoryResourceIo::new(&layer, MountPolicy::Append, base_io);
+        assert_eq!(load(&io, "data/a.txt"), "base a");
+        assert_eq!(load(&io, "data/c.txt"), "mod c");
+
+        let _ = std::fs::remove_dir_all(directory);
+    }
+}
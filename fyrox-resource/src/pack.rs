@@ -0,0 +1,1050 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Asset packs are single-file archives containing game assets. They are meant to be used in
+//! shipped games, so the assets are not exposed as loose files. An asset pack could be mounted
+//! to a resource manager using [`crate::manager::ResourceManager::mount_pack`], after that every
+//! resource stored in the pack will be loaded from it.
+//!
+//! ## Format
+//!
+//! A pack consists of a fixed-size header, a sequence of data blobs and an index at the end of
+//! the file. The index maps file paths to the blobs, every blob is identified by a hash of its
+//! content, so identical files are stored only once. Every blob could be compressed (LZ4 or
+//! zstd) and encrypted (ChaCha20 with a random per-blob nonce).
+//!
+//! ## Encryption
+//!
+//! Keep in mind, that the encryption key must be embedded in the game executable, so encryption
+//! only prevents casual extraction of the assets and cannot protect them from a determined person.
+
+use crate::{
+    io::{FileReader, ResourceIo, ResourceIoFuture},
+    mount::MountPolicy,
+};
+use chacha20::{
+    cipher::{KeyIvInit, StreamCipher},
+    ChaCha20, Key, Nonce,
+};
+use fxhash::{FxHashMap, FxHashSet};
+use fyrox_core::{io::FileLoadError, rand};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Debug, Display, Formatter},
+    fs::File,
+    future::ready,
+    io::{BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write},
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+/// Name of the asset pack, that is created on project export and mounted automatically by the
+/// game executor.
+pub const DEFAULT_PACK_PATH: &str = "assets.fyrpak";
+
+const MAGIC: [u8; 8] = *b"FYRXPAK\0";
+const VERSION: u32 = 1;
+const HEADER_SIZE: u64 = 64;
+const FLAG_ENCRYPTED: u32 = 1;
+const KEY_DERIVATION_CONTEXT: &str = "Fyrox Engine asset pack 2024-01-01 encryption key";
+const KEY_CHECK_DATA: &[u8] = b"Fyrox asset pack key check";
+/// Maximal size of a file in a pack. Sizes of the files are read from the pack and used to
+/// allocate memory for decompression, so they must be limited to not abort on malformed packs.
+const MAX_FILE_SIZE: u64 = 1 << 30;
+/// Maximal ratio of the size of a file to the size of its compressed data. LZ4 can't compress
+/// better than ~255:1 and zstd ~32768:1, anything above that is a malformed pack.
+const MAX_COMPRESSION_RATIO: u64 = 1 << 16;
+
+/// Compression method of asset pack blobs.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PackCompression {
+    /// Data is stored as is.
+    None,
+    /// LZ4 compression. It has moderate compression ratio, but very fast decompression.
+    #[default]
+    Lz4,
+    /// Zstandard compression. It has better compression ratio than LZ4 at the cost of slower
+    /// decompression. Requires `zstd` feature.
+    Zstd,
+}
+
+/// A 256-bit key, that is used to encrypt and decrypt asset packs.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct PackKey([u8; 32]);
+
+impl Debug for PackKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Do not leak the key to logs.
+        write!(f, "PackKey(..)")
+    }
+}
+
+impl PackKey {
+    /// Creates a new key from raw bytes.
+    pub fn new(key: [u8; 32]) -> Self {
+        Self(key)
+    }
+
+    /// Derives a key from the given passphrase. The same passphrase always gives the same key.
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        Self(blake3::derive_key(
+            KEY_DERIVATION_CONTEXT,
+            passphrase.as_bytes(),
+        ))
+    }
+
+    fn check_value(&self) -> [u8; 32] {
+        *blake3::keyed_hash(&self.0, KEY_CHECK_DATA).as_bytes()
+    }
+
+    fn apply(&self, nonce: &[u8; 12], data: &mut [u8]) {
+        ChaCha20::new(&Key::from(self.0), &Nonce::from(*nonce)).apply_keystream(data);
+    }
+}
+
+/// An error, that may occur during reading or writing of an asset pack.
+#[derive(Debug)]
+pub enum PackError {
+    /// An IO error has occurred.
+    Io(std::io::Error),
+    /// The data is not an asset pack.
+    InvalidHeader,
+    /// The pack was created with an unsupported version of the format.
+    UnsupportedVersion(u32),
+    /// The pack is encrypted, but no key was specified.
+    KeyRequired,
+    /// The specified key does not match the key, that was used to encrypt the pack.
+    InvalidKey,
+    /// The index of the pack is malformed.
+    InvalidIndex(String),
+    /// The compression method is not supported (most likely because of disabled feature).
+    UnsupportedCompression(PackCompression),
+    /// There's no such file in the pack.
+    NotFound(PathBuf),
+    /// The content of the file does not match its hash.
+    Corrupted(PathBuf),
+    /// The file is too large to be stored in a pack.
+    TooLarge(PathBuf),
+}
+
+impl Display for PackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackError::Io(err) => write!(f, "An IO error has occurred: {err}"),
+            PackError::InvalidHeader => write!(f, "The data is not an asset pack!"),
+            PackError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported asset pack version {version}!")
+            }
+            PackError::KeyRequired => write!(f, "The asset pack is encrypted, a key is required!"),
+            PackError::InvalidKey => write!(f, "Invalid asset pack key!"),
+            PackError::InvalidIndex(err) => write!(f, "Invalid asset pack index: {err}"),
+            PackError::UnsupportedCompression(compression) => {
+                write!(f, "Unsupported asset pack compression {compression:?}!")
+            }
+            PackError::NotFound(path) => {
+                write!(f, "There's no {} file in the asset pack!", path.display())
+            }
+            PackError::Corrupted(path) => {
+                write!(f, "The content of {} file is corrupted!", path.display())
+            }
+            PackError::TooLarge(path) => {
+                write!(
+                    f,
+                    "The {} file is too large for an asset pack!",
+                    path.display()
+                )
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for PackError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<PackError> for FileLoadError {
+    fn from(err: PackError) -> Self {
+        match err {
+            PackError::Io(err) => FileLoadError::Io(err),
+            _ => FileLoadError::Custom(err.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PackBlob {
+    offset: u64,
+    stored_size: u64,
+    size: u64,
+    compression: PackCompression,
+    hash: [u8; 32],
+    /// Random nonce, that is used to encrypt the blob. Content hash must not be used for this,
+    /// because it is stored in the index as is and nonce must never be reused with the same key.
+    nonce: [u8; 12],
+}
+
+impl PackBlob {
+    /// Checks that the size of the file is sane, so it can be used to allocate memory for
+    /// decompression.
+    fn has_valid_size(&self) -> bool {
+        match self.compression {
+            PackCompression::None => self.size == self.stored_size,
+            PackCompression::Lz4 | PackCompression::Zstd => {
+                self.size <= MAX_FILE_SIZE
+                    && self.size <= self.stored_size.saturating_mul(MAX_COMPRESSION_RATIO)
+            }
+        }
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+struct PackIndex {
+    blobs: Vec<PackBlob>,
+    files: Vec<(String, u32)>,
+}
+
+struct PackHeader {
+    flags: u32,
+    index_offset: u64,
+    index_size: u64,
+    key_check: [u8; 32],
+}
+
+impl PackHeader {
+    fn write(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&self.flags.to_le_bytes())?;
+        writer.write_all(&self.index_offset.to_le_bytes())?;
+        writer.write_all(&self.index_size.to_le_bytes())?;
+        writer.write_all(&self.key_check)
+    }
+
+    fn read(reader: &mut dyn Read) -> Result<Self, PackError> {
+        let mut bytes = [0; HEADER_SIZE as usize];
+        reader
+            .read_exact(&mut bytes)
+            .map_err(|_| PackError::InvalidHeader)?;
+        if bytes[0..8] != MAGIC {
+            return Err(PackError::InvalidHeader);
+        }
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        let u64_at =
+            |offset: usize| u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+        let version = u32_at(8);
+        if version != VERSION {
+            return Err(PackError::UnsupportedVersion(version));
+        }
+        Ok(Self {
+            flags: u32_at(12),
+            index_offset: u64_at(16),
+            index_size: u64_at(24),
+            key_check: bytes[32..64].try_into().unwrap(),
+        })
+    }
+}
+
+/// Converts the path to the form, that is used in the pack index - relative path with `/`
+/// separators and without `.` and `..` components.
+pub(crate) fn normalize_path(path: &Path) -> String {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => components.push(name.to_string_lossy()),
+            Component::ParentDir => {
+                components.pop();
+            }
+            _ => (),
+        }
+    }
+    components.join("/")
+}
+
+fn compress(data: &[u8], compression: PackCompression) -> Result<Vec<u8>, PackError> {
+    match compression {
+        PackCompression::None => Ok(data.to_vec()),
+        PackCompression::Lz4 => Ok(lz4_flex::compress(data)),
+        #[cfg(feature = "zstd")]
+        PackCompression::Zstd => Ok(zstd::bulk::compress(data, 0)?),
+        #[cfg(not(feature = "zstd"))]
+        PackCompression::Zstd => Err(PackError::UnsupportedCompression(compression)),
+    }
+}
+
+fn decompress(data: Vec<u8>, blob: &PackBlob) -> Result<Vec<u8>, PackError> {
+    if !blob.has_valid_size() {
+        return Err(PackError::InvalidIndex(format!(
+            "invalid file size {} of {} bytes of compressed data",
+            blob.size, blob.stored_size
+        )));
+    }
+    match blob.compression {
+        PackCompression::None => Ok(data),
+        PackCompression::Lz4 => lz4_flex::decompress(&data, blob.size as usize)
+            .map_err(|err| PackError::Io(std::io::Error::other(err))),
+        #[cfg(feature = "zstd")]
+        PackCompression::Zstd => Ok(zstd::bulk::decompress(&data, blob.size as usize)?),
+        #[cfg(not(feature = "zstd"))]
+        PackCompression::Zstd => Err(PackError::UnsupportedCompression(blob.compression)),
+    }
+}
+
+enum PackSource {
+    File(PathBuf),
+    Memory(Arc<[u8]>),
+}
+
+/// A read-only asset pack. See [module docs](self) for more info.
+pub struct ResourcePack {
+    source: PackSource,
+    key: Option<PackKey>,
+    blobs: Vec<PackBlob>,
+    files: FxHashMap<String, usize>,
+    directories: FxHashSet<String>,
+}
+
+impl Debug for ResourcePack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourcePack")
+            .field("files", &self.files.len())
+            .field("blobs", &self.blobs.len())
+            .finish()
+    }
+}
+
+impl ResourcePack {
+    /// Opens an asset pack at the given path. Only the index of the pack is read, the content of
+    /// the files will be read on demand. `key` must be specified for encrypted packs.
+    pub fn open(path: impl AsRef<Path>, key: Option<PackKey>) -> Result<Self, PackError> {
+        let path = path.as_ref();
+        let mut reader = BufReader::new(File::open(path)?);
+        Self::from_reader(&mut reader, PackSource::File(path.to_path_buf()), key)
+    }
+
+    /// Creates an asset pack from the given bytes. It is useful on platforms without a file
+    /// system (such as WebAssembly), where the whole pack must be downloaded first. `key` must be
+    /// specified for encrypted packs.
+    pub fn from_bytes(bytes: Vec<u8>, key: Option<PackKey>) -> Result<Self, PackError> {
+        let bytes: Arc<[u8]> = Arc::from(bytes);
+        let mut reader = Cursor::new(bytes.clone());
+        Self::from_reader(&mut reader, PackSource::Memory(bytes), key)
+    }
+
+    fn from_reader<R: Read + Seek>(
+        reader: &mut R,
+        source: PackSource,
+        key: Option<PackKey>,
+    ) -> Result<Self, PackError> {
+        let header = PackHeader::read(reader)?;
+
+        if header.flags & FLAG_ENCRYPTED != 0 {
+            match key {
+                Some(key) if key.check_value() == header.key_check => (),
+                Some(_) => return Err(PackError::InvalidKey),
+                None => return Err(PackError::KeyRequired),
+            }
+        }
+
+        // The header is not trusted, so the index must be validated against the actual size of
+        // the pack before allocating memory for it.
+        let pack_size = reader.seek(SeekFrom::End(0))?;
+        if header.index_offset < HEADER_SIZE
+            || header
+                .index_offset
+                .checked_add(header.index_size)
+                .map_or(true, |index_end| index_end > pack_size)
+        {
+            return Err(PackError::InvalidIndex(
+                "the index is out of bounds of the pack".to_string(),
+            ));
+        }
+
+        reader.seek(SeekFrom::Start(header.index_offset))?;
+        let mut index_bytes = vec![0; header.index_size as usize];
+        reader.read_exact(&mut index_bytes)?;
+        let index: PackIndex = bincode::deserialize(&index_bytes)
+            .map_err(|err| PackError::InvalidIndex(err.to_string()))?;
+
+        // Blobs are stored between the header and the index.
+        for (i, blob) in index.blobs.iter().enumerate() {
+            if blob.offset < HEADER_SIZE
+                || blob
+                    .offset
+                    .checked_add(blob.stored_size)
+                    .map_or(true, |blob_end| blob_end > header.index_offset)
+            {
+                return Err(PackError::InvalidIndex(format!(
+                    "blob {i} is out of bounds of the pack"
+                )));
+            }
+            if !blob.has_valid_size() {
+                return Err(PackError::InvalidIndex(format!(
+                    "blob {i} has invalid size {}",
+                    blob.size
+                )));
+            }
+        }
+
+        let mut files = FxHashMap::default();
+        let mut directories = FxHashSet::default();
+        for (path, blob) in index.files {
+            if blob as usize >= index.blobs.len() {
+                return Err(PackError::InvalidIndex(format!(
+                    "{path} refers to non-existent blob {blob}"
+                )));
+            }
+            let mut parent = Path::new(&path).parent();
+            while let Some(directory) = parent {
+                directories.insert(normalize_path(directory));
+                parent = directory.parent();
+            }
+            files.insert(path, blob as usize);
+        }
+
+        Ok(Self {
+            source,
+            key: if header.flags & FLAG_ENCRYPTED != 0 {
+                key
+            } else {
+                None
+            },
+            blobs: index.blobs,
+            files,
+            directories,
+        })
+    }
+
+    /// Returns `true` if the pack is encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Returns total amount of files in the pack.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns `true` if the pack has no files.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns an iterator over the paths of all files in the pack.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(|path| path.as_str())
+    }
+
+    /// Returns `true` if the pack contains a file at the given path.
+    pub fn contains_file(&self, path: &Path) -> bool {
+        self.files.contains_key(&normalize_path(path))
+    }
+
+    /// Returns `true` if the pack contains a directory at the given path. Directories are not
+    /// stored explicitly, every directory of a file path is considered as existing.
+    pub fn contains_directory(&self, path: &Path) -> bool {
+        self.directories.contains(&normalize_path(path))
+    }
+
+    /// Returns size of the file (uncompressed) at the given path.
+    pub fn file_size(&self, path: &Path) -> Option<u64> {
+        self.files
+            .get(&normalize_path(path))
+            .map(|blob| self.blobs[*blob].size)
+    }
+
+    /// Returns paths of files and directories in the given directory. If `recursive` is `true`,
+    /// then the content of subdirectories will be returned as well.
+    pub fn directory_content(&self, path: &Path, recursive: bool) -> Vec<PathBuf> {
+        let directory = normalize_path(path);
+        let is_inside = |entry: &str| {
+            let relative = if directory.is_empty() {
+                Some(entry)
+            } else {
+                entry
+                    .strip_prefix(directory.as_str())
+                    .and_then(|relative| relative.strip_prefix('/'))
+            };
+            relative.is_some_and(|relative| {
+                !relative.is_empty() && (recursive || !relative.contains('/'))
+            })
+        };
+        self.files
+            .keys()
+            .chain(self.directories.iter())
+            .filter(|entry| is_inside(entry))
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    /// Reads the entire content of the file at the given path. The content is decrypted,
+    /// decompressed and checked against its hash.
+    pub fn read_file(&self, path: &Path) -> Result<Vec<u8>, PackError> {
+        let blob = self
+            .files
+            .get(&normalize_path(path))
+            .map(|blob| &self.blobs[*blob])
+            .ok_or_else(|| PackError::NotFound(path.to_path_buf()))?;
+
+        let mut data = vec![0; blob.stored_size as usize];
+        match self.source {
+            PackSource::File(ref pack_path) => {
+                let mut file = File::open(pack_path)?;
+                file.seek(SeekFrom::Start(blob.offset))?;
+                file.read_exact(&mut data)?;
+            }
+            PackSource::Memory(ref bytes) => {
+                let blob_data = blob
+                    .offset
+                    .checked_add(blob.stored_size)
+                    .and_then(|end| bytes.get(blob.offset as usize..end as usize))
+                    .ok_or_else(|| PackError::Corrupted(path.to_path_buf()))?;
+                data.copy_from_slice(blob_data);
+            }
+        }
+
+        if let Some(key) = self.key.as_ref() {
+            key.apply(&blob.nonce, &mut data);
+        }
+
+        let data = decompress(data, blob)?;
+
+        if blake3::hash(&data).as_bytes() != &blob.hash {
+            return Err(PackError::Corrupted(path.to_path_buf()));
+        }
+
+        Ok(data)
+    }
+}
+
+/// Creates asset packs. Files are added to the pack with their paths, these paths must be the
+/// same as the paths used to load the resources (usually relative to the working directory of the
+/// game, for example `data/textures/foo.png`).
+#[derive(Default)]
+pub struct ResourcePackBuilder {
+    compression: PackCompression,
+    key: Option<PackKey>,
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl ResourcePackBuilder {
+    /// Creates a new empty pack builder with LZ4 compression and without encryption.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets compression method of the files.
+    pub fn with_compression(mut self, compression: PackCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets an encryption key of the pack. The same key must be used to open the pack.
+    pub fn with_key(mut self, key: Option<PackKey>) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// Adds a file with the given content to the pack.
+    pub fn add_file(&mut self, path: impl AsRef<Path>, data: Vec<u8>) {
+        self.files.push((normalize_path(path.as_ref()), data));
+    }
+
+    /// Adds every file from the given directory (recursively) to the pack. Files are added with
+    /// their paths as is, so if `directory` is `data`, then a file in it will have `data/foo.png`
+    /// path. `filter` could be used to skip unwanted files, it must return `true` for every file
+    /// that should be added.
+    pub fn add_directory<F>(
+        &mut self,
+        directory: impl AsRef<Path>,
+        filter: F,
+    ) -> Result<(), PackError>
+    where
+        F: Fn(&Path) -> bool,
+    {
+        for entry in walkdir::WalkDir::new(directory.as_ref()) {
+            let entry = entry.map_err(|err| PackError::Io(err.into()))?;
+            if entry.file_type().is_file() && filter(entry.path()) {
+                let data = std::fs::read(entry.path())?;
+                self.add_file(entry.path(), data);
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the pack to the given writer.
+    pub fn write<W: Write + Seek>(&self, writer: &mut W) -> Result<(), PackError> {
+        let mut header = PackHeader {
+            flags: if self.key.is_some() {
+                FLAG_ENCRYPTED
+            } else {
+                0
+            },
+            index_offset: 0,
+            index_size: 0,
+            key_check: self
+                .key
+                .as_ref()
+                .map(|key| key.check_value())
+                .unwrap_or_default(),
+        };
+
+        // The header will be rewritten with actual values at the end.
+        header.write(writer)?;
+
+        let mut index = PackIndex::default();
+        let mut blob_by_hash = FxHashMap::default();
+        let mut offset = HEADER_SIZE;
+        for (path, data) in self.files.iter() {
+            if data.len() as u64 > MAX_FILE_SIZE {
+                return Err(PackError::TooLarge(PathBuf::from(path)));
+            }
+
+            let hash = *blake3::hash(data).as_bytes();
+
+            let blob_index = match blob_by_hash.get(&hash) {
+                Some(blob_index) => *blob_index,
+                None => {
+                    let mut compression = self.compression;
+                    let mut stored = compress(data, compression)?;
+                    // Do not waste time on decompression if there's no gain.
+                    if stored.len() >= data.len() {
+                        compression = PackCompression::None;
+                        stored = data.clone();
+                    }
+
+                    let nonce = if let Some(key) = self.key.as_ref() {
+                        let nonce = rand::random();
+                        key.apply(&nonce, &mut stored);
+                        nonce
+                    } else {
+                        Default::default()
+                    };
+
+                    writer.write_all(&stored)?;
+
+                    let blob_index = index.blobs.len() as u32;
+                    index.blobs.push(PackBlob {
+                        offset,
+                        stored_size: stored.len() as u64,
+                        size: data.len() as u64,
+                        compression,
+                        hash,
+                        nonce,
+                    });
+                    blob_by_hash.insert(hash, blob_index);
+                    offset += stored.len() as u64;
+                    blob_index
+                }
+            };
+
+            index.files.push((path.clone(), blob_index));
+        }
+
+        let index_bytes =
+            bincode::serialize(&index).map_err(|err| PackError::InvalidIndex(err.to_string()))?;
+        writer.write_all(&index_bytes)?;
+
+        header.index_offset = offset;
+        header.index_size = index_bytes.len() as u64;
+        writer.seek(SeekFrom::Start(0))?;
+        header.write(writer)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Writes the pack to a file at the given path.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PackError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)
+    }
+}
+
+/// Resource IO, that reads files from an asset pack and forwards every other request to another
+/// (fallback) resource IO. Packs could be layered by using another [`PackedResourceIo`] as a
+/// fallback. By default, files of the pack override the files of the fallback, see [`MountPolicy`]
+/// for other options.
+pub struct PackedResourceIo {
+    pack: ResourcePack,
+    policy: MountPolicy,
+    fallback: Arc<dyn ResourceIo>,
+}
+
+impl PackedResourceIo {
+    /// Creates a new resource IO for the given pack.
+    pub fn new(pack: ResourcePack, fallback: Arc<dyn ResourceIo>) -> Self {
+        Self {
+            pack,
+            policy: MountPolicy::Override,
+            fallback,
+        }
+    }
+
+    /// Sets the mount policy of the pack.
+    pub fn with_policy(mut self, policy: MountPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns a reference to the mounted pack.
+    pub fn pack(&self) -> &ResourcePack {
+        &self.pack
+    }
+
+    /// Returns the mount policy of the pack.
+    pub fn policy(&self) -> MountPolicy {
+        self.policy
+    }
+
+    /// Returns `true` if the file at the given path should be read from the pack.
+    async fn provides_file(&self, path: &Path) -> bool {
+        self.pack.contains_file(path)
+            && (self.policy == MountPolicy::Override || !self.fallback.exists(path).await)
+    }
+}
+
+impl ResourceIo for PackedResourceIo {
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(async move {
+            if self.provides_file(path).await {
+                self.pack.read_file(path).map_err(Into::into)
+            } else {
+                self.fallback.load_file(path).await
+            }
+        })
+    }
+
+    fn move_file<'a>(
+        &'a self,
+        source: &'a Path,
+        dest: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<(), FileLoadError>> {
+        // Packs are read-only.
+        self.fallback.move_file(source, dest)
+    }
+
+    fn canonicalize_path<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathBuf, FileLoadError>> {
+        if self.pack.contains_file(path) || self.pack.contains_directory(path) {
+            Box::pin(ready(Ok(PathBuf::from(normalize_path(path)))))
+        } else {
+            self.fallback.canonicalize_path(path)
+        }
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn Iterator<Item = PathBuf> + Send>, FileLoadError>> {
+        Box::pin(async move {
+            let mut content = self.pack.directory_content(path, false);
+            if let Ok(iter) = self.fallback.read_directory(path).await {
+                content.extend(iter);
+            }
+            let iter: Box<dyn Iterator<Item = PathBuf> + Send> = Box::new(content.into_iter());
+            Ok(iter)
+        })
+    }
+
+    fn walk_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn Iterator<Item = PathBuf> + Send>, FileLoadError>> {
+        Box::pin(async move {
+            let mut content = self.pack.directory_content(path, true);
+            if let Ok(iter) = self.fallback.walk_directory(path).await {
+                content.extend(iter);
+            }
+            let iter: Box<dyn Iterator<Item = PathBuf> + Send> = Box::new(content.into_iter());
+            Ok(iter)
+        })
+    }
+
+    fn file_reader<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn FileReader>, FileLoadError>> {
+        Box::pin(async move {
+            if self.provides_file(path).await {
+                let bytes = self.pack.read_file(path)?;
+                let reader: Box<dyn FileReader> = Box::new(Cursor::new(bytes));
+                Ok(reader)
+            } else {
+                self.fallback.file_reader(path).await
+            }
+        })
+    }
+
+    fn file_size<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, Option<u64>> {
+        Box::pin(async move {
+            if self.provides_file(path).await {
+                self.pack.file_size(path)
+            } else {
+                self.fallback.file_size(path).await
+            }
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        if self.pack.contains_file(path) || self.pack.contains_directory(path) {
+            Box::pin(ready(true))
+        } else {
+            self.fallback.exists(path)
+        }
+    }
+
+    fn is_file<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        if self.pack.contains_file(path) {
+            Box::pin(ready(true))
+        } else {
+            self.fallback.is_file(path)
+        }
+    }
+
+    fn is_dir<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        if self.pack.contains_directory(path) {
+            Box::pin(ready(true))
+        } else {
+            self.fallback.is_dir(path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::io::FsResourceIo;
+    use fyrox_core::futures::executor::block_on;
+
+    fn make_pack(compression: PackCompression, key: Option<PackKey>) -> Vec<u8> {
+        let mut builder = ResourcePackBuilder::new()
+            .with_compression(compression)
+            .with_key(key);
+        builder.add_file("./data/textures/foo.png", vec![1; 1024]);
+        builder.add_file("data/textures/bar.png", vec![1; 1024]);
+        builder.add_file("data/scene.rgs", b"scene data".to_vec());
+        let mut cursor = Cursor::new(Vec::new());
+        builder.write(&mut cursor).unwrap();
+        cursor.into_inner()
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        for compression in [PackCompression::None, PackCompression::Lz4] {
+            let pack = ResourcePack::from_bytes(make_pack(compression, None), None).unwrap();
+            assert_eq!(pack.len(), 3);
+            // Identical files must share the same blob.
+            assert_eq!(pack.blobs.len(), 2);
+            assert!(!pack.is_encrypted());
+            assert_eq!(
+                pack.read_file(Path::new("data/textures/foo.png")).unwrap(),
+                vec![1; 1024]
+            );
+            assert_eq!(
+                pack.read_file(Path::new("./data/scene.rgs")).unwrap(),
+                b"scene data"
+            );
+            assert!(matches!(
+                pack.read_file(Path::new("data/missing.png")),
+                Err(PackError::NotFound(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_pack_encryption() {
+        let key = PackKey::from_passphrase("secret");
+        let data = make_pack(PackCompression::Lz4, Some(key));
+
+        assert!(matches!(
+            ResourcePack::from_bytes(data.clone(), None),
+            Err(PackError::KeyRequired)
+        ));
+        assert!(matches!(
+            ResourcePack::from_bytes(data.clone(), Some(PackKey::from_passphrase("wrong"))),
+            Err(PackError::InvalidKey)
+        ));
+
+        let pack = ResourcePack::from_bytes(data, Some(key)).unwrap();
+        assert!(pack.is_encrypted());
+        assert_eq!(
+            pack.read_file(Path::new("data/scene.rgs")).unwrap(),
+            b"scene data"
+        );
+        // Nonce must not be derived from the content.
+        assert!(pack
+            .blobs
+            .iter()
+            .all(|blob| blob.nonce[..] != blob.hash[..12]));
+    }
+
+    #[test]
+    fn test_pack_out_of_bounds() {
+        let data = make_pack(PackCompression::None, None);
+
+        // Huge index size must be rejected without allocation.
+        let mut huge_index = data.clone();
+        huge_index[24..32].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            ResourcePack::from_bytes(huge_index, None),
+            Err(PackError::InvalidIndex(_))
+        ));
+
+        // Truncated pack, the index points past the end of the data.
+        let mut truncated = data.clone();
+        truncated.truncate(data.len() - 1);
+        assert!(matches!(
+            ResourcePack::from_bytes(truncated, None),
+            Err(PackError::InvalidIndex(_))
+        ));
+
+        // Blob range overflows.
+        let index = PackIndex {
+            blobs: vec![PackBlob {
+                offset: u64::MAX - 1,
+                stored_size: 16,
+                size: 16,
+                compression: PackCompression::None,
+                hash: [0; 32],
+                nonce: [0; 12],
+            }],
+            files: vec![("data/foo.png".to_string(), 0)],
+        };
+        let index_bytes = bincode::serialize(&index).unwrap();
+        let mut malformed = Vec::new();
+        PackHeader {
+            flags: 0,
+            index_offset: HEADER_SIZE,
+            index_size: index_bytes.len() as u64,
+            key_check: [0; 32],
+        }
+        .write(&mut malformed)
+        .unwrap();
+        malformed.extend_from_slice(&index_bytes);
+        assert!(matches!(
+            ResourcePack::from_bytes(malformed, None),
+            Err(PackError::InvalidIndex(_))
+        ));
+    }
+
+    #[test]
+    fn test_pack_huge_file_size() {
+        let make_malformed = |compression, size| {
+            let index = PackIndex {
+                blobs: vec![PackBlob {
+                    offset: HEADER_SIZE,
+                    stored_size: 16,
+                    size,
+                    compression,
+                    hash: [0; 32],
+                    nonce: [0; 12],
+                }],
+                files: vec![("data/foo.png".to_string(), 0)],
+            };
+            let index_bytes = bincode::serialize(&index).unwrap();
+            let mut malformed = Vec::new();
+            PackHeader {
+                flags: 0,
+                index_offset: HEADER_SIZE + 16,
+                index_size: index_bytes.len() as u64,
+                key_check: [0; 32],
+            }
+            .write(&mut malformed)
+            .unwrap();
+            malformed.extend_from_slice(&[0; 16]);
+            malformed.extend_from_slice(&index_bytes);
+            ResourcePack::from_bytes(malformed, None)
+        };
+
+        // Huge decompressed size must be rejected before any allocation.
+        for compression in [PackCompression::Lz4, PackCompression::Zstd] {
+            assert!(matches!(
+                make_malformed(compression, u64::MAX),
+                Err(PackError::InvalidIndex(_))
+            ));
+            assert!(matches!(
+                make_malformed(compression, 16 * MAX_COMPRESSION_RATIO + 1),
+                Err(PackError::InvalidIndex(_))
+            ));
+            assert!(make_malformed(compression, 1024).is_ok());
+        }
+        // Uncompressed files are stored as is.
+        assert!(matches!(
+            make_malformed(PackCompression::None, 17),
+            Err(PackError::InvalidIndex(_))
+        ));
+    }
+
+    #[test]
+    fn test_packed_resource_io() {
+        let pack = ResourcePack::from_bytes(make_pack(PackCompression::Lz4, None), None).unwrap();
+        let io = PackedResourceIo::new(pack, Arc::new(FsResourceIo));
+
+        assert!(block_on(io.exists(Path::new("data/textures"))));
+        assert!(block_on(io.is_dir(Path::new("data/textures"))));
+        assert!(block_on(io.is_file(Path::new("data/scene.rgs"))));
+        assert_eq!(
+            block_on(io.load_file(Path::new("data/scene.rgs"))).unwrap(),
+            b"scene data"
+        );
+
+        let mut content = block_on(io.read_directory(Path::new("data")))
+            .unwrap()
+            .filter(|path| path.starts_with("data"))
+            .collect::<Vec<_>>();
+        content.sort();
+        assert_eq!(
+            content,
+            vec![
+                PathBuf::from("data/scene.rgs"),
+                PathBuf::from("data/textures")
+            ]
+        );
+
+        let make_patch = || {
+            let mut builder = ResourcePackBuilder::new();
+            builder.add_file("data/scene.rgs", b"patched scene".to_vec());
+            let mut cursor = Cursor::new(Vec::new());
+            builder.write(&mut cursor).unwrap();
+            ResourcePack::from_bytes(cursor.into_inner(), None).unwrap()
+        };
+        let io: Arc<dyn ResourceIo> = Arc::new(io);
+
+        let overriding = PackedResourceIo::new(make_patch(), io.clone());
+        assert_eq!(
+            block_on(overriding.load_file(Path::new("data/scene.rgs"))).unwrap(),
+            b"patched scene"
+        );
+
+        let appending = PackedResourceIo::new(make_patch(), io).with_policy(MountPolicy::Append);
+        assert_eq!(
+            block_on(appending.load_file(Path::new("data/scene.rgs"))).unwrap(),
+            b"scene data"
+        );
+    }
+}
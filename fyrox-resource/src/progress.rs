@@ -0,0 +1,388 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Load priorities and loading progress tracking. See [`ResourceLoadPriority`], [`ResourceLoadProgress`]
+//! and [`ResourceBatch`] docs for more info.
+
+use crate::{
+    core::io::FileLoadError,
+    io::{FileReader, ResourceIo, ResourceIoFuture},
+    UntypedResource,
+};
+use std::{
+    fmt::{Debug, Formatter},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// Priority of a resource loading request. Requests with higher priority are started first when the
+/// amount of simultaneous loads is limited (see [`crate::manager::ResourceManagerState::set_max_concurrent_loads`]).
+/// Requests with the same priority are started in the order they were made.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ResourceLoadPriority {
+    /// Resources that are not needed right away, for example assets of distant parts of a level.
+    Low,
+    /// Default priority.
+    #[default]
+    Normal,
+    /// Resources that are needed soon.
+    High,
+    /// Resources without which the game cannot continue, for example assets of a loading screen.
+    Critical,
+}
+
+/// Loading progress of a single resource. Progress is measured in bytes read by a resource loader
+/// from a [`ResourceIo`]. Total amount of bytes is known only if the resource io is able to report
+/// file sizes (see [`ResourceIo::file_size`]).
+#[derive(Default)]
+pub struct ResourceLoadProgress {
+    bytes_loaded: AtomicU64,
+    bytes_total: AtomicU64,
+    reported_bytes: AtomicU64,
+    finished: AtomicBool,
+}
+
+impl Debug for ResourceLoadProgress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourceLoadProgress")
+            .field("bytes_loaded", &self.bytes_loaded())
+            .field("bytes_total", &self.bytes_total())
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+impl ResourceLoadProgress {
+    /// Returns total amount of bytes read so far.
+    pub fn bytes_loaded(&self) -> u64 {
+        self.bytes_loaded.load(Ordering::Relaxed)
+    }
+
+    /// Returns total amount of bytes that is known to be read. Could be zero, if the resource io
+    /// cannot report file sizes.
+    pub fn bytes_total(&self) -> u64 {
+        self.bytes_total.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the loading is finished (either successfully or not).
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
+    /// Returns loading progress in `[0; 1]` range.
+    pub fn fraction(&self) -> f32 {
+        if self.is_finished() {
+            return 1.0;
+        }
+        let total = self.bytes_total();
+        if total == 0 {
+            0.0
+        } else {
+            (self.bytes_loaded() as f64 / total as f64).min(1.0) as f32
+        }
+    }
+
+    pub(crate) fn add_loaded(&self, bytes: u64) {
+        self.bytes_loaded.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_total(&self, bytes: u64) {
+        self.bytes_total.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn finish(&self) {
+        self.finished.store(true, Ordering::Release);
+    }
+
+    /// Returns `true` if there were any changes since the last call of this method.
+    pub(crate) fn take_changed(&self) -> bool {
+        let loaded = self.bytes_loaded();
+        self.reported_bytes.swap(loaded, Ordering::Relaxed) != loaded
+    }
+}
+
+/// Aggregate progress of a [`ResourceBatch`].
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct BatchProgress {
+    /// Amount of resources that are finished loading (either successfully or not).
+    pub finished: usize,
+    /// Total amount of resources in the batch.
+    pub total: usize,
+    /// Total amount of bytes read by all the resources in the batch.
+    pub bytes_loaded: u64,
+    /// Total amount of bytes that is known to be read by all the resources in the batch.
+    pub bytes_total: u64,
+    /// Average loading progress of all the resources in the batch in `[0; 1]` range.
+    pub fraction: f32,
+}
+
+impl BatchProgress {
+    /// Returns loading progress in percents.
+    pub fn percentage(&self) -> u32 {
+        (self.fraction * 100.0).round() as u32
+    }
+
+    /// Returns `true` if every resource in the batch is finished loading.
+    pub fn is_finished(&self) -> bool {
+        self.finished == self.total
+    }
+}
+
+/// A set of resources, that could be used to track their aggregate loading progress. It is
+/// primarily intended to be used on loading screens. See
+/// [`crate::manager::ResourceManager::request_batch`] for more info.
+#[derive(Default, Debug)]
+pub struct ResourceBatch {
+    entries: Vec<(UntypedResource, Option<Arc<ResourceLoadProgress>>)>,
+}
+
+impl ResourceBatch {
+    /// Creates a new empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a resource to the batch. Progress is an optional progress of the resource loading, if
+    /// it is not set, the progress is calculated by the state of the resource.
+    pub fn add(&mut self, resource: UntypedResource, progress: Option<Arc<ResourceLoadProgress>>) {
+        self.entries.push((resource, progress));
+    }
+
+    /// Returns an iterator over the resources in the batch.
+    pub fn resources(&self) -> impl Iterator<Item = &UntypedResource> {
+        self.entries.iter().map(|(resource, _)| resource)
+    }
+
+    /// Returns total amount of resources in the batch.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the batch is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Calculates aggregate loading progress of the batch.
+    pub fn progress(&self) -> BatchProgress {
+        let mut progress = BatchProgress {
+            total: self.entries.len(),
+            ..Default::default()
+        };
+
+        if self.entries.is_empty() {
+            progress.fraction = 1.0;
+            return progress;
+        }
+
+        let mut fraction_sum = 0.0;
+        for (resource, resource_progress) in self.entries.iter() {
+            if let Some(resource_progress) = resource_progress {
+                progress.bytes_loaded += resource_progress.bytes_loaded();
+                progress.bytes_total += resource_progress.bytes_total();
+            }
+
+            if resource.is_loading() {
+                fraction_sum += resource_progress
+                    .as_ref()
+                    .map_or(0.0, |resource_progress| resource_progress.fraction());
+            } else {
+                progress.finished += 1;
+                fraction_sum += 1.0;
+            }
+        }
+        progress.fraction = fraction_sum / self.entries.len() as f32;
+
+        progress
+    }
+
+    /// Returns `true` if every resource in the batch is finished loading (either successfully or not).
+    pub fn is_finished(&self) -> bool {
+        self.entries
+            .iter()
+            .all(|(resource, _)| !resource.is_loading())
+    }
+}
+
+#[derive(Debug)]
+struct ProgressReader {
+    inner: Box<dyn FileReader>,
+    progress: Arc<ResourceLoadProgress>,
+}
+
+impl Read for ProgressReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.progress.add_loaded(count as u64);
+        Ok(count)
+    }
+}
+
+impl Seek for ProgressReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+/// Resource io wrapper that records the amount of read bytes into a load progress.
+pub(crate) struct ProgressResourceIo {
+    pub(crate) inner: Arc<dyn ResourceIo>,
+    pub(crate) progress: Arc<ResourceLoadProgress>,
+}
+
+impl ResourceIo for ProgressResourceIo {
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+        Box::pin(async move {
+            if let Some(size) = self.inner.file_size(path).await {
+                self.progress.add_total(size);
+            }
+            let data = self.inner.load_file(path).await?;
+            self.progress.add_loaded(data.len() as u64);
+            Ok(data)
+        })
+    }
+
+    fn move_file<'a>(
+        &'a self,
+        source: &'a Path,
+        dest: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<(), FileLoadError>> {
+        self.inner.move_file(source, dest)
+    }
+
+    fn canonicalize_path<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathBuf, FileLoadError>> {
+        self.inner.canonicalize_path(path)
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn Iterator<Item = PathBuf> + Send>, FileLoadError>> {
+        self.inner.read_directory(path)
+    }
+
+    fn walk_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn Iterator<Item = PathBuf> + Send>, FileLoadError>> {
+        self.inner.walk_directory(path)
+    }
+
+    fn file_reader<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn FileReader>, FileLoadError>> {
+        Box::pin(async move {
+            if let Some(size) = self.inner.file_size(path).await {
+                self.progress.add_total(size);
+            }
+            let inner = self.inner.file_reader(path).await?;
+            let reader: Box<dyn FileReader> = Box::new(ProgressReader {
+                inner,
+                progress: self.progress.clone(),
+            });
+            Ok(reader)
+        })
+    }
+
+    fn file_size<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, Option<u64>> {
+        self.inner.file_size(path)
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        self.inner.exists(path)
+    }
+
+    fn is_file<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        self.inner.is_file(path)
+    }
+
+    fn is_dir<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        self.inner.is_dir(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::untyped::ResourceKind;
+    use fyrox_core::uuid::Uuid;
+
+    #[test]
+    fn load_priority_order() {
+        assert!(ResourceLoadPriority::Critical > ResourceLoadPriority::High);
+        assert!(ResourceLoadPriority::High > ResourceLoadPriority::Normal);
+        assert!(ResourceLoadPriority::Normal > ResourceLoadPriority::Low);
+    }
+
+    #[test]
+    fn load_progress_fraction() {
+        let progress = ResourceLoadProgress::default();
+        assert_eq!(progress.fraction(), 0.0);
+        progress.add_total(200);
+        progress.add_loaded(50);
+        assert_eq!(progress.fraction(), 0.25);
+        assert!(progress.take_changed());
+        assert!(!progress.take_changed());
+        progress.finish();
+        assert_eq!(progress.fraction(), 1.0);
+    }
+
+    #[test]
+    fn batch_progress() {
+        let mut batch = ResourceBatch::new();
+        assert_eq!(batch.progress().fraction, 1.0);
+
+        let progress = Arc::new(ResourceLoadProgress::default());
+        progress.add_total(100);
+        progress.add_loaded(50);
+        batch.add(
+            UntypedResource::new_pending(ResourceKind::External("a.txt".into()), Uuid::default()),
+            Some(progress),
+        );
+        batch.add(
+            UntypedResource::new_load_error(
+                ResourceKind::External("b.txt".into()),
+                Default::default(),
+                Uuid::default(),
+            ),
+            None,
+        );
+
+        let progress = batch.progress();
+        assert_eq!(progress.total, 2);
+        assert_eq!(progress.finished, 1);
+        assert_eq!(progress.bytes_loaded, 50);
+        assert_eq!(progress.bytes_total, 100);
+        assert_eq!(progress.fraction, 0.75);
+        assert_eq!(progress.percentage(), 75);
+        assert!(!batch.is_finished());
+    }
+}
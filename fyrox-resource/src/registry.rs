@@ -0,0 +1,214 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Resource registry maps stable resource identifiers (UUIDs) to the paths of the resources. The
+//! identifiers are stored in metadata files next to the resources (`<resource>.meta`), so they
+//! survive file moves and renames. Serialized resource references store both the path and the
+//! identifier of a resource, and the identifier has higher priority when the reference is resolved.
+
+use crate::{
+    core::{io::FileLoadError, log::Log, replace_slashes, uuid::Uuid},
+    io::ResourceIo,
+};
+use fxhash::{FxHashMap, FxHashSet};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// Extension of resource metadata files.
+pub const METADATA_EXTENSION: &str = "meta";
+
+/// Resource metadata, that is stored in a separate file next to the resource.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceMetadata {
+    /// Unique and stable identifier of the resource.
+    pub resource_uuid: Uuid,
+}
+
+impl ResourceMetadata {
+    /// Creates new metadata with a random resource identifier.
+    pub fn new_with_random_id() -> Self {
+        Self {
+            resource_uuid: Uuid::new_v4(),
+        }
+    }
+
+    /// Tries to load resource metadata from the given file.
+    pub async fn load_from_file(path: &Path, io: &dyn ResourceIo) -> Result<Self, FileLoadError> {
+        let bytes = io.load_file(path).await?;
+        ron::de::from_bytes(&bytes).map_err(|err| FileLoadError::Custom(err.to_string()))
+    }
+
+    /// Tries to save resource metadata to the given file.
+    pub fn save_sync(&self, path: &Path) -> Result<(), FileLoadError> {
+        let string = ron::ser::to_string_pretty(self, PrettyConfig::default())
+            .map_err(|err| FileLoadError::Custom(err.to_string()))?;
+        std::fs::write(path, string)?;
+        Ok(())
+    }
+}
+
+/// Removes `.` components from the path and replaces `\` separators with `/`, so the same path
+/// written in different forms is registered only once.
+fn normalize_path(path: &Path) -> PathBuf {
+    replace_slashes(
+        path.components()
+            .filter(|component| !matches!(component, Component::CurDir))
+            .collect::<PathBuf>(),
+    )
+}
+
+/// Resource registry maps stable resource identifiers to the actual paths of the resources. See
+/// [module docs](self) for more info.
+#[derive(Default, Debug)]
+pub struct ResourceRegistry {
+    paths: FxHashMap<Uuid, PathBuf>,
+    uuids: FxHashMap<PathBuf, Uuid>,
+    redirected: FxHashSet<PathBuf>,
+}
+
+impl ResourceRegistry {
+    /// Registers a resource at the given path with the given identifier. Returns a previous path of
+    /// the resource, if any.
+    pub fn register(&mut self, uuid: Uuid, path: &Path) -> Option<PathBuf> {
+        let path = normalize_path(path);
+        if let Some(previous_uuid) = self.uuids.insert(path.clone(), uuid) {
+            if previous_uuid != uuid {
+                self.paths.remove(&previous_uuid);
+            }
+        }
+        let previous_path = self.paths.insert(uuid, path.clone());
+        if let Some(previous_path) = previous_path.as_ref() {
+            if previous_path != &path {
+                self.uuids.remove(previous_path);
+            }
+        }
+        previous_path
+    }
+
+    /// Removes a resource at the given path from the registry. Returns the identifier of the
+    /// resource, if any.
+    pub fn unregister_path(&mut self, path: &Path) -> Option<Uuid> {
+        let uuid = self.uuids.remove(&normalize_path(path))?;
+        self.paths.remove(&uuid);
+        Some(uuid)
+    }
+
+    /// Returns a path of the resource with the given identifier.
+    pub fn uuid_to_path(&self, uuid: Uuid) -> Option<&Path> {
+        self.paths.get(&uuid).map(|path| path.as_path())
+    }
+
+    /// Returns an identifier of the resource at the given path.
+    pub fn path_to_uuid(&self, path: &Path) -> Option<Uuid> {
+        self.uuids.get(&normalize_path(path)).cloned()
+    }
+
+    /// Returns total amount of registered resources.
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Returns `true` if the registry is empty.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Removes every registered resource.
+    pub fn clear(&mut self) {
+        self.paths.clear();
+        self.uuids.clear();
+    }
+
+    /// Replaces every registered resource with the resources from the other registry. Redirected
+    /// paths (see [`Self::take_redirected`]) are kept.
+    pub fn replace_entries(&mut self, other: ResourceRegistry) {
+        self.paths = other.paths;
+        self.uuids = other.uuids;
+    }
+
+    /// Resolves an actual path of a resource reference, that was serialized with the given
+    /// identifier and path. The identifier has the priority, the path is used as a fallback if
+    /// the identifier is unknown. Paths of the references, that were redirected to a new location,
+    /// are remembered, so they could be fixed later (see [`Self::take_redirected`]).
+    pub fn resolve_path(&mut self, uuid: Uuid, path: PathBuf) -> PathBuf {
+        match self.paths.get(&uuid) {
+            Some(actual_path) if actual_path != &normalize_path(&path) => {
+                Log::warn(format!(
+                    "Resource {} was moved to {}, the reference is redirected to the new location.",
+                    path.display(),
+                    actual_path.display()
+                ));
+                self.redirected.insert(actual_path.clone());
+                actual_path.clone()
+            }
+            _ => path,
+        }
+    }
+
+    /// Returns a set of paths of the resources, references to which were redirected to a new
+    /// location (see [`Self::resolve_path`]), and clears the set.
+    pub fn take_redirected(&mut self) -> FxHashSet<PathBuf> {
+        std::mem::take(&mut self.redirected)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resource_registry() {
+        let mut registry = ResourceRegistry::default();
+        let uuid = Uuid::new_v4();
+
+        assert!(registry
+            .register(uuid, Path::new("./data/foo.png"))
+            .is_none());
+        assert_eq!(registry.path_to_uuid(Path::new("data/foo.png")), Some(uuid));
+        assert_eq!(registry.uuid_to_path(uuid), Some(Path::new("data/foo.png")));
+
+        // Move.
+        assert_eq!(
+            registry.register(uuid, Path::new("data/bar.png")),
+            Some(PathBuf::from("data/foo.png"))
+        );
+        assert_eq!(registry.len(), 1);
+        assert!(registry.path_to_uuid(Path::new("data/foo.png")).is_none());
+
+        assert_eq!(
+            registry.resolve_path(uuid, PathBuf::from("data/foo.png")),
+            PathBuf::from("data/bar.png")
+        );
+        assert_eq!(
+            registry.resolve_path(Uuid::new_v4(), PathBuf::from("data/baz.png")),
+            PathBuf::from("data/baz.png")
+        );
+        assert!(registry
+            .take_redirected()
+            .contains(&PathBuf::from("data/bar.png")));
+
+        assert_eq!(
+            registry.unregister_path(Path::new("data/bar.png")),
+            Some(uuid)
+        );
+        assert!(registry.is_empty());
+    }
+}
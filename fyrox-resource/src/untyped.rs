@@ -197,6 +197,9 @@ pub struct ResourceHeader {
     pub type_uuid: Uuid,
     /// Kind of the resource. See [`ResourceKind`] for more info.
     pub kind: ResourceKind,
+    /// Stable identifier of an external resource, if any. See [`crate::registry`] module docs for
+    /// more info.
+    pub resource_uuid: Option<Uuid>,
     /// Actual state of the resource. See [`ResourceState`] for more info.
     pub state: ResourceState,
 }
@@ -268,6 +271,18 @@ impl Visit for ResourceHeader {
         self.kind.visit("Kind", &mut region)?;
         self.type_uuid.visit("TypeUuid", &mut region)?;
 
+        if self.kind.is_external() {
+            // Optional, because old versions does not have the identifier.
+            if region.is_reading() {
+                let mut resource_uuid = Uuid::nil();
+                if resource_uuid.visit("ResourceUuid", &mut region).is_ok() {
+                    self.resource_uuid = Some(resource_uuid).filter(|uuid| !uuid.is_nil());
+                }
+            } else if let Some(mut resource_uuid) = self.resource_uuid {
+                resource_uuid.visit("ResourceUuid", &mut region)?;
+            }
+        }
+
         if self.kind == ResourceKind::Embedded {
             self.state.visit("State", &mut region)?;
         }
@@ -330,7 +345,18 @@ impl Visit for UntypedResource {
                 .get::<ResourceManager>()
                 .expect("Resource manager must be available when deserializing resources!");
 
-            let path = self.kind().path_owned().unwrap();
+            let (path, resource_uuid) = {
+                let header = self.0.lock();
+                (header.kind.path_owned().unwrap(), header.resource_uuid)
+            };
+            // The identifier has priority over the path, because the resource could be moved.
+            let path = match resource_uuid {
+                Some(resource_uuid) => resource_manager
+                    .state()
+                    .registry
+                    .resolve_path(resource_uuid, path),
+                None => path,
+            };
             self.0 = resource_manager.request_untyped(path).0;
         }
 
@@ -343,6 +369,7 @@ impl Default for UntypedResource {
         Self(Arc::new(Mutex::new(ResourceHeader {
             kind: Default::default(),
             type_uuid: Default::default(),
+            resource_uuid: None,
             state: ResourceState::new_load_error(LoadError::new(
                 "Default resource state of unknown type.",
             )),
@@ -376,6 +403,7 @@ impl UntypedResource {
         Self(Arc::new(Mutex::new(ResourceHeader {
             kind,
             type_uuid,
+            resource_uuid: None,
             state: ResourceState::new_pending(),
         })))
     }
@@ -389,6 +417,7 @@ impl UntypedResource {
         Self(Arc::new(Mutex::new(ResourceHeader {
             kind,
             type_uuid: data.type_uuid(),
+            resource_uuid: None,
             state: ResourceState::new_ok(data),
         })))
     }
@@ -398,6 +427,7 @@ impl UntypedResource {
         Self(Arc::new(Mutex::new(ResourceHeader {
             kind,
             type_uuid,
+            resource_uuid: None,
             state: ResourceState::new_load_error(error),
         })))
     }
@@ -658,6 +688,7 @@ mod test {
         let mut r = UntypedResource(Arc::new(Mutex::new(ResourceHeader {
             kind: path.clone().into(),
             type_uuid: Uuid::default(),
+            resource_uuid: None,
             state: ResourceState::Ok(Box::new(stub)),
         })));
         assert!(Pin::new(&mut r).poll(&mut cx).is_ready());
@@ -665,6 +696,7 @@ mod test {
         let mut r = UntypedResource(Arc::new(Mutex::new(ResourceHeader {
             kind: path.clone().into(),
             type_uuid: Uuid::default(),
+            resource_uuid: None,
             state: ResourceState::LoadError {
                 error: Default::default(),
             },
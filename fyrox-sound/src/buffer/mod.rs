@@ -36,6 +36,7 @@ use fyrox_core::{
     io::FileLoadError, reflect::prelude::*, uuid::Uuid, visitor::prelude::*, TypeUuidProvider,
 };
 use fyrox_resource::{
+    budget::ResourceMemoryCategory,
     io::{FileReader, ResourceIo},
     Resource, ResourceData, SOUND_BUFFER_RESOURCE_UUID,
 };
@@ -296,4 +297,12 @@ impl ResourceData for SoundBuffer {
     fn can_be_saved(&self) -> bool {
         false
     }
+
+    fn memory_usage(&self) -> usize {
+        std::mem::size_of_val(self.samples())
+    }
+
+    fn memory_category(&self) -> ResourceMemoryCategory {
+        ResourceMemoryCategory::Sound
+    }
 }
@@ -23,11 +23,16 @@
 
 use crate::effects::{Effect, EffectRenderTrait};
 use fyrox_core::{
+    log::Log,
     pool::{Handle, Pool, Ticket},
     reflect::prelude::*,
+    uuid_provider,
     visitor::prelude::*,
 };
-use std::fmt::{Debug, Formatter};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{Debug, Formatter},
+};
 
 #[derive(Default, Clone)]
 struct PingPongBuffer {
@@ -96,6 +101,43 @@ impl PingPongBuffer {
     }
 }
 
+/// Auxiliary send routes a copy of a signal to another audio bus (so called return bus) with an adjustable
+/// level. It is a standard mixing technique used to share a single expensive effect (reverb, delay, etc.)
+/// between multiple sources or buses, instead of duplicating the effect on each of them. Sends could be
+/// specified for audio buses (see [`AudioBus::add_send`]) as well as for individual sound sources (see
+/// [`crate::source::SoundSource::add_send`]).
+///
+/// Just like sound sources, sends are bound to a return bus by its name.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct AudioBusSend {
+    /// A name of an audio bus to which the signal will be sent.
+    pub bus: String,
+    /// A level of the signal sent to the return bus.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub level: f32,
+}
+
+uuid_provider!(AudioBusSend = "0b4f3a2e-93f4-4d0c-8f57-6d1c9e2a7b15");
+
+impl Default for AudioBusSend {
+    fn default() -> Self {
+        Self {
+            bus: AudioBusGraph::PRIMARY_BUS.to_string(),
+            level: 1.0,
+        }
+    }
+}
+
+impl AudioBusSend {
+    /// Creates a new send to an audio bus with the given name.
+    pub fn new<S: AsRef<str>>(bus: S, level: f32) -> Self {
+        Self {
+            bus: bus.as_ref().to_owned(),
+            level: level.max(0.0),
+        }
+    }
+}
+
 /// Audio bus is a top-level audio processing unit. It takes data from multiple audio sources and passes their
 /// samples through a chain of effects. Output signal is then can be either sent to an audio playback device or
 /// to some other audio bus and be processed again, but with different sound effects (this can be done via
@@ -106,6 +148,9 @@ pub struct AudioBus {
     effects: Vec<Effect>,
     gain: f32,
 
+    #[visit(optional)]
+    sends: Vec<AudioBusSend>,
+
     #[reflect(hidden)]
     child_buses: Vec<Handle<AudioBus>>,
 
@@ -124,6 +169,7 @@ impl Default for AudioBus {
             child_buses: Default::default(),
             effects: Default::default(),
             gain: 1.0,
+            sends: Default::default(),
             ping_pong_buffer: Default::default(),
             parent_bus: Default::default(),
         }
@@ -222,6 +268,47 @@ impl AudioBus {
     pub fn effects_mut(&mut self) -> impl Iterator<Item = &mut Effect> {
         self.effects.iter_mut()
     }
+
+    /// Adds a new auxiliary send to the audio bus. Output signal of the bus (after its effects, child buses and
+    /// gain) will be sent to the return bus with the given level, in addition to the parent bus. See [`AudioBusSend`]
+    /// docs for more info.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use fyrox_sound::bus::{AudioBus, AudioBusGraph, AudioBusSend};
+    /// use fyrox_sound::effects::{Effect, reverb::Reverb};
+    ///
+    /// let mut graph = AudioBusGraph::new();
+    /// let primary_bus = graph.primary_bus_handle();
+    ///
+    /// // A return bus with a single reverb shared by every other bus.
+    /// let mut reverb_bus = AudioBus::new("Reverb".to_string());
+    /// reverb_bus.add_effect(Effect::Reverb(Reverb::new()));
+    /// graph.add_bus(reverb_bus, primary_bus);
+    ///
+    /// let mut sfx_bus = AudioBus::new("SFX".to_string());
+    /// sfx_bus.add_send(AudioBusSend::new("Reverb", 0.3));
+    /// graph.add_bus(sfx_bus, primary_bus);
+    /// ```
+    pub fn add_send(&mut self, send: AudioBusSend) {
+        self.sends.push(send)
+    }
+
+    /// Removes an auxiliary send at the given index.
+    pub fn remove_send(&mut self, index: usize) -> AudioBusSend {
+        self.sends.remove(index)
+    }
+
+    /// Returns a slice with every auxiliary send of the audio bus.
+    pub fn sends(&self) -> &[AudioBusSend] {
+        &self.sends
+    }
+
+    /// Returns a mutable reference to the list of auxiliary sends of the audio bus.
+    pub fn sends_mut(&mut self) -> &mut Vec<AudioBusSend> {
+        &mut self.sends
+    }
 }
 
 /// Audio bus graph is a complex audio data processing entity; it allows you to route samples from
@@ -483,47 +570,144 @@ impl AudioBusGraph {
         }
     }
 
-    pub(crate) fn end_render(&mut self, output_device_buffer: &mut [(f32, f32)]) {
-        let mut leafs = Vec::new();
-        for (handle, bus) in self.buses.pair_iter_mut() {
-            bus.apply_effects();
+    /// Returns handles of every audio bus in the order in which they should be processed. Every bus is processed
+    /// after its child buses and after every bus that sends its signal to it, this way the signal of a bus is
+    /// complete at the moment when it is sent to its return buses.
+    fn processing_order(&self) -> Vec<Handle<AudioBus>> {
+        if self.buses.iter().all(|bus| bus.sends.is_empty()) {
+            // Fast path - reversed depth-first order puts children before their parents.
+            let mut order = Vec::with_capacity(self.buses.alive_count() as usize);
+            let mut stack = vec![self.root];
+            while let Some(handle) = stack.pop() {
+                order.push(handle);
+                stack.extend_from_slice(&self.buses[handle].child_buses);
+            }
+            order.reverse();
+            return order;
+        }
 
-            if bus.child_buses.is_empty() {
-                leafs.push(handle);
+        let name_to_handle = self
+            .buses
+            .pair_iter()
+            .map(|(handle, bus)| (bus.name.as_str(), handle))
+            .collect::<HashMap<_, _>>();
+
+        let dependents = |bus: &AudioBus| {
+            bus.sends
+                .iter()
+                .filter_map(|send| name_to_handle.get(send.bus.as_str()).copied())
+                .chain(Some(bus.parent_bus).filter(|parent| parent.is_some()))
+                .collect::<Vec<_>>()
+        };
+
+        let mut incoming = HashMap::<Handle<AudioBus>, usize>::new();
+        for bus in self.buses.iter() {
+            for dependent in dependents(bus) {
+                *incoming.entry(dependent).or_default() += 1;
             }
         }
 
-        for mut leaf in leafs {
-            while leaf.is_some() {
-                let ctx = self.buses.begin_multi_borrow();
-
-                let leaf_ref = ctx.try_get_mut(leaf).expect("Malformed bus graph!");
-
-                let input_buffer = leaf_ref.ping_pong_buffer.input_ref();
-                let leaf_gain = leaf_ref.gain;
-                let mut parent_buffer = ctx.try_get_mut(leaf_ref.parent_bus);
-                let output_buffer = parent_buffer
-                    .as_mut()
-                    .map(|parent| parent.ping_pong_buffer.input_mut())
-                    // Special case for the root bus - it writes directly to the output device buffer.
-                    .unwrap_or(&mut *output_device_buffer);
-                for ((input_left, input_right), (output_left, output_right)) in
-                    input_buffer.iter().zip(output_buffer)
-                {
-                    *output_left += *input_left * leaf_gain;
-                    *output_right += *input_right * leaf_gain;
+        let mut queue = self
+            .buses
+            .pair_iter()
+            .filter_map(|(handle, _)| (!incoming.contains_key(&handle)).then_some(handle))
+            .collect::<VecDeque<_>>();
+
+        let mut order = Vec::with_capacity(self.buses.alive_count() as usize);
+        while let Some(handle) = queue.pop_front() {
+            order.push(handle);
+            for dependent in dependents(&self.buses[handle]) {
+                if let Some(count) = incoming.get_mut(&dependent) {
+                    *count -= 1;
+                    if *count == 0 {
+                        queue.push_back(dependent);
+                    }
                 }
+            }
+        }
+
+        if order.len() != self.buses.alive_count() as usize {
+            Log::warn("Audio bus graph has a loop in auxiliary sends, the loop will be ignored.");
 
-                leaf = leaf_ref.parent_bus;
+            for (handle, _) in self.buses.pair_iter() {
+                if !order.contains(&handle) {
+                    order.push(handle);
+                }
             }
         }
+
+        order
+    }
+
+    pub(crate) fn end_render(&mut self, output_device_buffer: &mut [(f32, f32)]) {
+        let order = self.processing_order();
+
+        for (position, handle) in order.iter().enumerate() {
+            let ctx = self.buses.begin_multi_borrow();
+
+            let mut bus = ctx.try_get_mut(*handle).expect("Malformed bus graph!");
+
+            bus.apply_effects();
+
+            // Mix child buses, they're already processed and contain the signal of all their descendants.
+            for i in 0..bus.child_buses.len() {
+                if let Ok(child) = ctx.try_get(bus.child_buses[i]) {
+                    let child_gain = child.gain;
+                    for ((input_left, input_right), (output_left, output_right)) in child
+                        .ping_pong_buffer
+                        .input_ref()
+                        .iter()
+                        .zip(bus.ping_pong_buffer.input_mut())
+                    {
+                        *output_left += *input_left * child_gain;
+                        *output_right += *input_right * child_gain;
+                    }
+                }
+            }
+
+            // Sends are taken after the child mix, so return buses receive the entire signal of the bus.
+            for send in bus.sends.iter() {
+                // Ignore sends to already processed buses - this could only happen if there's a loop.
+                let Some(target) = order[position + 1..]
+                    .iter()
+                    .find(|h| ctx.try_get(**h).is_ok_and(|target| target.name == send.bus))
+                else {
+                    continue;
+                };
+
+                if let Ok(mut target) = ctx.try_get_mut(*target) {
+                    let level = bus.gain * send.level;
+                    for ((input_left, input_right), (output_left, output_right)) in bus
+                        .ping_pong_buffer
+                        .input_ref()
+                        .iter()
+                        .zip(target.ping_pong_buffer.input_mut())
+                    {
+                        *output_left += *input_left * level;
+                        *output_right += *input_right * level;
+                    }
+                }
+            }
+        }
+
+        // The root bus writes directly to the output device buffer.
+        let root = &self.buses[self.root];
+        for ((input_left, input_right), (output_left, output_right)) in root
+            .ping_pong_buffer
+            .input_ref()
+            .iter()
+            .zip(output_device_buffer)
+        {
+            *output_left += *input_left * root.gain;
+            *output_right += *input_right * root.gain;
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use crate::{
-        bus::{AudioBus, AudioBusGraph},
+        bus::{AudioBus, AudioBusGraph, AudioBusSend},
         effects::{Attenuate, Effect},
     };
 
@@ -606,4 +790,87 @@ mod test {
 
         assert_eq!(output_buffer[0], (0.75, 0.75));
     }
+
+    #[test]
+    fn test_sibling_buses_data_flow() {
+        let mut output_buffer = [(0.0f32, 0.0f32)];
+
+        let mut graph = AudioBusGraph::new();
+
+        let bus1 = graph.add_bus(AudioBus::new("Bus1".to_string()), graph.root);
+        let bus2 = graph.add_bus(AudioBus::new("Bus2".to_string()), graph.root);
+
+        graph.begin_render(output_buffer.len());
+
+        for bus in [bus1, bus2] {
+            for (left, right) in graph.buses[bus].input_buffer() {
+                *left = 1.0;
+                *right = 1.0;
+            }
+        }
+
+        graph.end_render(&mut output_buffer);
+
+        assert_eq!(output_buffer[0], (2.0, 2.0));
+    }
+
+    #[test]
+    fn test_aux_send_data_flow() {
+        let mut output_buffer = [(0.0f32, 0.0f32)];
+
+        let mut graph = AudioBusGraph::new();
+
+        let mut return_bus = AudioBus::new("Return".to_string());
+        return_bus.add_effect(Effect::Attenuate(Attenuate::new(0.5)));
+        // Add the return bus first to make sure that the processing order does not depend on
+        // the order of buses in the graph.
+        graph.add_bus(return_bus, graph.root);
+
+        let mut bus1 = AudioBus::new("Bus1".to_string());
+        bus1.add_send(AudioBusSend::new("Return", 0.5));
+        let bus1 = graph.add_bus(bus1, graph.root);
+
+        graph.begin_render(output_buffer.len());
+
+        for (left, right) in graph.buses[bus1].input_buffer() {
+            *left = 1.0;
+            *right = 1.0;
+        }
+
+        graph.end_render(&mut output_buffer);
+
+        // 1.0 from the Bus1 directly plus 1.0 * 0.5 (send level) * 0.5 (return effect).
+        assert_eq!(output_buffer[0], (1.25, 1.25));
+    }
+
+    #[test]
+    fn test_aux_send_after_child_mix() {
+        let mut output_buffer = [(0.0f32, 0.0f32)];
+
+        let mut graph = AudioBusGraph::new();
+
+        let mut return_bus = AudioBus::new("Return".to_string());
+        return_bus.add_effect(Effect::Attenuate(Attenuate::new(0.5)));
+        graph.add_bus(return_bus, graph.root);
+
+        let mut bus1 = AudioBus::new("Bus1".to_string());
+        bus1.add_send(AudioBusSend::new("Return", 0.5));
+        let bus1 = graph.add_bus(bus1, graph.root);
+
+        let bus2 = graph.add_bus(AudioBus::new("Bus2".to_string()), bus1);
+
+        graph.begin_render(output_buffer.len());
+
+        for bus in [bus1, bus2] {
+            for (left, right) in graph.buses[bus].input_buffer() {
+                *left = 1.0;
+                *right = 1.0;
+            }
+        }
+
+        graph.end_render(&mut output_buffer);
+
+        // 2.0 from the Bus1 and its child directly plus 2.0 * 0.5 (send level) * 0.5 (return effect).
+        assert_eq!(output_buffer[0], (2.5, 2.5));
+    }
 }
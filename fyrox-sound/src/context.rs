@@ -133,6 +133,7 @@ pub struct SerializationOptions {
 pub struct State {
     sources: Pool<SoundSource>,
     listener: Listener,
+    additional_listeners: Vec<Listener>,
     render_duration: Duration,
     renderer: Renderer,
     bus_graph: AudioBusGraph,
@@ -142,6 +143,9 @@ pub struct State {
     /// serialization of a sound context.
     #[reflect(hidden)]
     pub serialization_options: SerializationOptions,
+    // Temporary buffer for sources with auxiliary sends.
+    #[reflect(hidden)]
+    send_buffer: Vec<(f32, f32)>,
 }
 
 impl State {
@@ -254,16 +258,49 @@ impl State {
         self.sources.try_borrow_mut(handle)
     }
 
-    /// Returns shared reference to listener. Engine has only one listener.
+    /// Returns shared reference to the primary listener.
     pub fn listener(&self) -> &Listener {
         &self.listener
     }
 
-    /// Returns mutable reference to listener. Engine has only one listener.
+    /// Returns mutable reference to the primary listener.
     pub fn listener_mut(&mut self) -> &mut Listener {
         &mut self.listener
     }
 
+    /// Adds a new listener in addition to the primary one and returns its index. Every sound source will
+    /// be rendered for every listener and the loudest result of each channel is used, taking into account
+    /// gain and output channels of each listener (see [`Listener::set_gain`] and [`Listener::set_output`]). This is useful for local
+    /// split-screen games, where each player should hear the world from its own position.
+    ///
+    /// # Notes
+    ///
+    /// HRTF renderer ignores additional listeners, since its state is tied to a single point of view.
+    pub fn add_listener(&mut self, listener: Listener) -> usize {
+        self.additional_listeners.push(listener);
+        self.additional_listeners.len() - 1
+    }
+
+    /// Removes an additional listener at the given index.
+    pub fn remove_listener(&mut self, index: usize) -> Listener {
+        self.additional_listeners.remove(index)
+    }
+
+    /// Returns a shared reference to the list of additional listeners.
+    pub fn additional_listeners(&self) -> &[Listener] {
+        &self.additional_listeners
+    }
+
+    /// Returns a mutable reference to the list of additional listeners.
+    pub fn additional_listeners_mut(&mut self) -> &mut Vec<Listener> {
+        &mut self.additional_listeners
+    }
+
+    /// Returns an iterator over every listener of the context, the primary listener goes first.
+    pub fn listeners(&self) -> impl Iterator<Item = &Listener> {
+        std::iter::once(&self.listener).chain(self.additional_listeners.iter())
+    }
+
     /// Returns a reference to the audio bus graph.
     pub fn bus_graph_ref(&self) -> &AudioBusGraph {
         &self.bus_graph
@@ -291,27 +328,59 @@ impl State {
                 .iter_mut()
                 .filter(|s| s.status() == Status::Playing)
             {
-                if let Some(bus_input_buffer) = self.bus_graph.try_get_bus_input_buffer(&source.bus)
-                {
-                    source.render(output_device_buffer.len());
-
-                    match self.renderer {
-                        Renderer::Default => {
-                            // Simple rendering path. Much faster (4-5 times) than HRTF path.
-                            render_source_default(
-                                source,
-                                &self.listener,
-                                self.distance_model,
-                                bus_input_buffer,
-                            );
-                        }
-                        Renderer::HrtfRenderer(ref mut hrtf_renderer) => {
-                            hrtf_renderer.render_source(
-                                source,
-                                &self.listener,
-                                self.distance_model,
-                                bus_input_buffer,
-                            );
+                let Some(bus_input_buffer) = self.bus_graph.try_get_bus_input_buffer(&source.bus)
+                else {
+                    continue;
+                };
+
+                source.render(output_device_buffer.len());
+
+                // Sources with auxiliary sends are rendered to a temporary buffer first, which is
+                // then mixed to the main bus and to every return bus.
+                let render_target = if source.sends.is_empty() {
+                    bus_input_buffer
+                } else {
+                    self.send_buffer.clear();
+                    self.send_buffer
+                        .resize(output_device_buffer.len(), (0.0, 0.0));
+                    self.send_buffer.as_mut_slice()
+                };
+
+                match self.renderer {
+                    Renderer::Default => {
+                        // Simple rendering path. Much faster (4-5 times) than HRTF path.
+                        render_source_default(
+                            source,
+                            std::iter::once(&self.listener).chain(self.additional_listeners.iter()),
+                            self.distance_model,
+                            render_target,
+                        );
+                    }
+                    Renderer::HrtfRenderer(ref mut hrtf_renderer) => {
+                        hrtf_renderer.render_source(
+                            source,
+                            &self.listener,
+                            self.distance_model,
+                            render_target,
+                        );
+                    }
+                }
+
+                if !source.sends.is_empty() {
+                    let main = std::iter::once((source.bus.as_str(), 1.0));
+                    let sends = source
+                        .sends
+                        .iter()
+                        .map(|send| (send.bus.as_str(), send.level));
+                    for (bus, level) in main.chain(sends) {
+                        if let Some(bus_input_buffer) = self.bus_graph.try_get_bus_input_buffer(bus)
+                        {
+                            for ((out_left, out_right), (left, right)) in
+                                bus_input_buffer.iter_mut().zip(self.send_buffer.iter())
+                            {
+                                *out_left += *left * level;
+                                *out_right += *right * level;
+                            }
                         }
                     }
                 }
@@ -342,12 +411,14 @@ impl SoundContext {
             state: Some(Arc::new(Mutex::new(State {
                 sources: Pool::new(),
                 listener: Listener::new(),
+                additional_listeners: Default::default(),
                 render_duration: Default::default(),
                 renderer: Renderer::Default,
                 bus_graph: AudioBusGraph::new(),
                 distance_model: DistanceModel::InverseDistance,
                 paused: false,
                 serialization_options: Default::default(),
+                send_buffer: Default::default(),
             }))),
         }
     }
@@ -396,6 +467,9 @@ impl Visit for State {
         let mut region = visitor.enter_region(name)?;
 
         self.listener.visit("Listener", &mut region)?;
+        let _ = self
+            .additional_listeners
+            .visit("AdditionalListeners", &mut region);
         if !self.serialization_options.skip_sources {
             let _ = self.sources.visit("Sources", &mut region);
         }
@@ -27,6 +27,7 @@
 //! - Streaming.
 //! - Head-related transfer function support ([HRTF](https://en.wikipedia.org/wiki/Head-related_transfer_function)).
 //! - Reverb effect.
+//! - Adaptive music with layered stems and beat-synced transitions.
 //!
 //! ## Examples
 //!
@@ -96,6 +97,7 @@ pub mod effects;
 pub mod engine;
 pub mod error;
 pub mod listener;
+pub mod music;
 pub mod renderer;
 pub mod source;
 
@@ -22,21 +22,68 @@
 //!
 //! # Overview
 //!
-//! Engine has only one listener which can be positioned and oriented in space. Listener defined as coordinate
-//! system which is used to compute spatial properties of sound sources.
+//! Each sound context has a primary listener which can be positioned and oriented in space. Listener defined as
+//! coordinate system which is used to compute spatial properties of sound sources. Contexts can also have any
+//! number of additional listeners (see [`crate::context::State::add_listener`]), which is useful for local
+//! split-screen games, where each player needs to hear the world from its own position.
 
 use fyrox_core::{
     algebra::{Matrix3, Vector3},
     math::Matrix3Ext,
     reflect::prelude::*,
+    uuid_provider,
     visitor::prelude::*,
 };
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines output channels to which a listener will write its samples.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum ListenerOutput {
+    /// Listener writes to both channels as usual.
+    #[default]
+    Stereo,
+    /// Listener mixes its signal to mono and writes it to the left channel only.
+    Left,
+    /// Listener mixes its signal to mono and writes it to the right channel only.
+    Right,
+}
+
+uuid_provider!(ListenerOutput = "a9c6e0b4-3a0e-4bd6-93c4-39a1b0b0b2c1");
+
+impl ListenerOutput {
+    /// Routes a pair of left and right channel gains to the output channels.
+    pub fn route(self, left_gain: f32, right_gain: f32) -> (f32, f32) {
+        match self {
+            ListenerOutput::Stereo => (left_gain, right_gain),
+            ListenerOutput::Left => ((left_gain + right_gain) * 0.5, 0.0),
+            ListenerOutput::Right => (0.0, (left_gain + right_gain) * 0.5),
+        }
+    }
+}
 
 /// See module docs.
 #[derive(Debug, Clone, Visit, Reflect)]
 pub struct Listener {
     basis: Matrix3<f32>,
     position: Vector3<f32>,
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, step = 0.05)]
+    gain: f32,
+    #[visit(optional)]
+    output: ListenerOutput,
 }
 
 impl Default for Listener {
@@ -46,13 +93,39 @@ impl Default for Listener {
 }
 
 impl Listener {
-    pub(crate) fn new() -> Self {
+    /// Creates a new listener at the origin, with identity basis, unit gain and stereo output.
+    pub fn new() -> Self {
         Self {
             basis: Matrix3::identity(),
             position: Vector3::new(0.0, 0.0, 0.0),
+            gain: 1.0,
+            output: Default::default(),
         }
     }
 
+    /// Sets the weight of the listener in the final mix. When there are multiple listeners in a context,
+    /// the signal that each of them receives is multiplied by its gain and the loudest signal of each
+    /// channel is used. For example, a listener with `0.5` gain will be quieter than the others, so sounds
+    /// near the other players will be more prominent.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain.max(0.0);
+    }
+
+    /// Returns the weight of the listener in the final mix.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Sets output channels of the listener. See [`ListenerOutput`] docs for more info.
+    pub fn set_output(&mut self, output: ListenerOutput) {
+        self.output = output;
+    }
+
+    /// Returns output channels of the listener.
+    pub fn output(&self) -> ListenerOutput {
+        self.output
+    }
+
     /// Sets new basis from given vectors in left-handed coordinate system.
     /// See `set_basis` for more info.
     pub fn set_orientation_lh(&mut self, look: Vector3<f32>, up: Vector3<f32>) {
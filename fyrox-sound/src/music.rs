@@ -0,0 +1,570 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Adaptive music. See [`MusicManager`] docs for more info.
+
+use crate::{
+    context::State,
+    source::{SoundSource, Status},
+};
+use fyrox_core::{
+    pool::{Handle, Pool},
+    reflect::prelude::*,
+    uuid_provider,
+    visitor::prelude::*,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// A single layer of a music track. Stems of the same track play in sync and are faded in or out
+/// depending on the current intensity of the [`MusicManager`].
+#[derive(Debug, Clone, Default, PartialEq, Visit, Reflect)]
+pub struct MusicStem {
+    /// A handle of a sound source (in the same sound context) that plays the stem.
+    pub source: Handle<SoundSource>,
+    /// Minimal intensity at which the stem becomes audible. Base layer should use `0.0`.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub intensity_threshold: f32,
+    /// Volume of the stem at full fade.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub gain: f32,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    fade: f32,
+}
+
+impl MusicStem {
+    /// Creates a new stem for the given sound source.
+    pub fn new(source: Handle<SoundSource>, intensity_threshold: f32) -> Self {
+        Self {
+            source,
+            intensity_threshold,
+            gain: 1.0,
+            fade: 0.0,
+        }
+    }
+}
+
+/// A music track that consists of one or more stems (layers). All stems of a track are expected to
+/// have the same tempo and length.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct MusicTrack {
+    name: String,
+    #[reflect(min_value = 1.0, step = 1.0)]
+    bpm: f32,
+    #[reflect(min_value = 1.0, step = 1.0)]
+    beats_per_bar: u32,
+    stems: Vec<MusicStem>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    fade: f32,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    fade_target: f32,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    fade_speed: f32,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    time: f32,
+}
+
+impl Default for MusicTrack {
+    fn default() -> Self {
+        Self {
+            name: "Track".to_string(),
+            bpm: 120.0,
+            beats_per_bar: 4,
+            stems: Default::default(),
+            fade: 0.0,
+            fade_target: 0.0,
+            fade_speed: 0.0,
+            time: 0.0,
+        }
+    }
+}
+
+impl MusicTrack {
+    /// Creates a new music track with the given name and tempo.
+    pub fn new<S: AsRef<str>>(name: S, bpm: f32, beats_per_bar: u32) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            bpm: bpm.max(1.0),
+            beats_per_bar: beats_per_bar.max(1),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a new stem to the track.
+    pub fn with_stem(mut self, stem: MusicStem) -> Self {
+        self.stems.push(stem);
+        self
+    }
+
+    /// Adds a new stem to the track.
+    pub fn add_stem(&mut self, stem: MusicStem) {
+        self.stems.push(stem);
+    }
+
+    /// Returns a slice with all the stems of the track.
+    pub fn stems(&self) -> &[MusicStem] {
+        &self.stems
+    }
+
+    /// Returns name of the track.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns tempo of the track in beats per minute.
+    pub fn bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    /// Returns amount of beats in a single bar.
+    pub fn beats_per_bar(&self) -> u32 {
+        self.beats_per_bar
+    }
+
+    /// Returns duration of a single beat in seconds.
+    pub fn beat_duration(&self) -> f32 {
+        60.0 / self.bpm
+    }
+
+    /// Returns duration of a single bar in seconds.
+    pub fn bar_duration(&self) -> f32 {
+        self.beat_duration() * self.beats_per_bar as f32
+    }
+
+    /// Returns current playback position of the track in seconds.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Returns current fade factor of the whole track in `0..1` range.
+    pub fn fade(&self) -> f32 {
+        self.fade
+    }
+
+    fn is_active(&self) -> bool {
+        self.fade > 0.0 || self.fade_target > 0.0
+    }
+
+    fn quantum(&self, quantization: TransitionQuantization) -> Option<f32> {
+        match quantization {
+            TransitionQuantization::Immediate => None,
+            TransitionQuantization::Beat => Some(self.beat_duration()),
+            TransitionQuantization::Bar => Some(self.bar_duration()),
+        }
+    }
+
+    fn start_fade(&mut self, target: f32, duration: f32) {
+        self.fade_target = target;
+        if duration > 0.0 {
+            self.fade_speed = 1.0 / duration;
+        } else {
+            self.fade = target;
+            self.fade_speed = 0.0;
+        }
+    }
+}
+
+/// Defines a moment at which a transition between music tracks will start.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum TransitionQuantization {
+    /// Transition starts right away.
+    Immediate,
+    /// Transition starts at the next beat of the current track.
+    Beat,
+    /// Transition starts at the next bar of the current track.
+    #[default]
+    Bar,
+}
+
+uuid_provider!(TransitionQuantization = "4d1b7b5e-6b8e-4f0a-9cc5-20a5a8e1f0a3");
+
+#[derive(Debug, Clone, PartialEq)]
+struct PendingTransition {
+    target: Handle<MusicTrack>,
+    quantization: TransitionQuantization,
+    fade_duration: f32,
+}
+
+/// Music manager plays adaptive music using sound sources of a sound context. It supports:
+///
+/// - Layered tracks - each [`MusicTrack`] consists of a set of [`MusicStem`]s, that are faded in or out
+/// depending on current intensity (see [`MusicManager::set_intensity`]). For example, a combat track
+/// could have a percussion stem that starts playing only when intensity is above `0.5`.
+/// - Quantized transitions - switching between tracks could be delayed until the next beat or bar
+/// of the current track (see [`TransitionQuantization`]), after that the tracks are crossfaded.
+///
+/// The manager does not own sound sources, instead it only controls their status and gain. Sound sources
+/// of the stems should be added to a sound context beforehand and should usually be looped and non-spatial.
+///
+/// # Examples
+///
+/// ```rust
+/// use fyrox_sound::{
+///     context::SoundContext,
+///     music::{MusicManager, MusicStem, MusicTrack, TransitionQuantization},
+///     source::SoundSourceBuilder,
+/// };
+///
+/// let context = SoundContext::new();
+/// let mut state = context.state();
+///
+/// let mut make_stem = |threshold| {
+///     let source = SoundSourceBuilder::new()
+///         .with_looping(true)
+///         .with_spatial_blend_factor(0.0)
+///         .build()
+///         .unwrap();
+///     MusicStem::new(state.add_source(source), threshold)
+/// };
+///
+/// let explore = MusicTrack::new("Explore", 100.0, 4).with_stem(make_stem(0.0));
+/// let combat = MusicTrack::new("Combat", 140.0, 4)
+///     .with_stem(make_stem(0.0))
+///     .with_stem(make_stem(0.5));
+///
+/// let mut manager = MusicManager::new();
+/// let explore = manager.add_track(explore);
+/// let combat = manager.add_track(combat);
+///
+/// manager.play(explore, 0.0);
+///
+/// // Later, when a fight starts.
+/// manager.transition_to(combat, TransitionQuantization::Bar, 2.0);
+/// manager.set_intensity(1.0);
+///
+/// // Must be called every frame.
+/// manager.update(&mut state, 1.0 / 60.0);
+/// ```
+#[derive(Debug, Clone, Visit, Reflect)]
+pub struct MusicManager {
+    tracks: Pool<MusicTrack>,
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    intensity: f32,
+    #[reflect(min_value = 0.0, step = 0.05)]
+    stem_fade_duration: f32,
+    #[reflect(min_value = 0.0, step = 0.05)]
+    gain: f32,
+    #[reflect(hidden)]
+    current: Handle<MusicTrack>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pending: Option<PendingTransition>,
+}
+
+impl Default for MusicManager {
+    fn default() -> Self {
+        Self {
+            tracks: Default::default(),
+            intensity: 0.0,
+            stem_fade_duration: 1.0,
+            gain: 1.0,
+            current: Default::default(),
+            pending: None,
+        }
+    }
+}
+
+impl MusicManager {
+    /// Creates a new music manager without any tracks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new track to the manager and returns its handle.
+    pub fn add_track(&mut self, track: MusicTrack) -> Handle<MusicTrack> {
+        self.tracks.spawn(track)
+    }
+
+    /// Removes a track from the manager. Sound sources of the track are left untouched.
+    pub fn remove_track(&mut self, handle: Handle<MusicTrack>) -> Option<MusicTrack> {
+        if self.current == handle {
+            self.current = Handle::NONE;
+        }
+        if self.pending.as_ref().is_some_and(|p| p.target == handle) {
+            self.pending = None;
+        }
+        self.tracks.try_free(handle)
+    }
+
+    /// Tries to borrow a track by its handle.
+    pub fn try_get_track(&self, handle: Handle<MusicTrack>) -> Option<&MusicTrack> {
+        self.tracks.try_borrow(handle)
+    }
+
+    /// Tries to borrow a track by its handle.
+    pub fn try_get_track_mut(&mut self, handle: Handle<MusicTrack>) -> Option<&mut MusicTrack> {
+        self.tracks.try_borrow_mut(handle)
+    }
+
+    /// Returns a handle of the track that is currently playing (or fading in).
+    pub fn current_track(&self) -> Handle<MusicTrack> {
+        self.current
+    }
+
+    /// Returns `true` if there is a transition waiting for a beat or bar boundary.
+    pub fn has_pending_transition(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Sets current music intensity in `0..1` range. Stems with intensity threshold less or equal than
+    /// the intensity will be faded in, the rest will be faded out.
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity.clamp(0.0, 1.0);
+    }
+
+    /// Returns current music intensity.
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// Sets the time (in seconds) that is needed for a stem to fade in or out when intensity changes.
+    pub fn set_stem_fade_duration(&mut self, duration: f32) {
+        self.stem_fade_duration = duration.max(0.0);
+    }
+
+    /// Returns the time (in seconds) that is needed for a stem to fade in or out.
+    pub fn stem_fade_duration(&self) -> f32 {
+        self.stem_fade_duration
+    }
+
+    /// Sets master gain of the music.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain.max(0.0);
+    }
+
+    /// Returns master gain of the music.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Starts playing the given track right away, fading it in during the given time. Every other
+    /// active track will be faded out during the same time.
+    pub fn play(&mut self, track: Handle<MusicTrack>, fade_duration: f32) {
+        self.pending = None;
+        self.begin_transition(track, fade_duration);
+    }
+
+    /// Schedules a transition to the given track. The transition starts when the current track reaches
+    /// a boundary defined by `quantization`, after that both tracks are crossfaded during `fade_duration`
+    /// seconds. If there's no track playing, the transition starts immediately. Scheduling a transition
+    /// replaces any other pending transition.
+    pub fn transition_to(
+        &mut self,
+        track: Handle<MusicTrack>,
+        quantization: TransitionQuantization,
+        fade_duration: f32,
+    ) {
+        if track == self.current {
+            self.pending = None;
+            return;
+        }
+
+        self.pending = Some(PendingTransition {
+            target: track,
+            quantization,
+            fade_duration,
+        });
+    }
+
+    /// Stops every track, fading them out during the given time.
+    pub fn stop(&mut self, fade_duration: f32) {
+        self.pending = None;
+        self.current = Handle::NONE;
+        for track in self.tracks.iter_mut() {
+            track.start_fade(0.0, fade_duration);
+        }
+    }
+
+    fn begin_transition(&mut self, target: Handle<MusicTrack>, fade_duration: f32) {
+        for (handle, track) in self.tracks.pair_iter_mut() {
+            if handle == target {
+                if !track.is_active() {
+                    track.time = 0.0;
+                }
+                track.start_fade(1.0, fade_duration);
+            } else {
+                track.start_fade(0.0, fade_duration);
+            }
+        }
+        self.current = target;
+    }
+
+    /// Updates the state of the manager: advances track clocks, starts pending transitions, applies fades
+    /// and writes resulting gains to the sound sources of the stems. Must be called every frame with the
+    /// state of the sound context in which stem sources live.
+    pub fn update(&mut self, state: &mut State, dt: f32) {
+        if let Some(pending) = self.pending.clone() {
+            let should_start = match self.tracks.try_borrow(self.current) {
+                Some(current) if current.is_active() => match current.quantum(pending.quantization)
+                {
+                    // Check whether the next boundary will be crossed during this frame.
+                    Some(quantum) => {
+                        (current.time / quantum).floor() != ((current.time + dt) / quantum).floor()
+                    }
+                    None => true,
+                },
+                _ => true,
+            };
+
+            if should_start {
+                self.pending = None;
+                self.begin_transition(pending.target, pending.fade_duration);
+            }
+        }
+
+        let stem_fade_step = if self.stem_fade_duration > 0.0 {
+            dt / self.stem_fade_duration
+        } else {
+            1.0
+        };
+
+        for track in self.tracks.iter_mut() {
+            if track.fade < track.fade_target {
+                track.fade = (track.fade + track.fade_speed * dt).min(track.fade_target);
+            } else if track.fade > track.fade_target {
+                track.fade = (track.fade - track.fade_speed * dt).max(track.fade_target);
+            }
+
+            let active = track.is_active();
+            if active {
+                track.time += dt;
+            }
+
+            for stem in track.stems.iter_mut() {
+                let stem_target = if self.intensity >= stem.intensity_threshold {
+                    1.0
+                } else {
+                    0.0
+                };
+                if stem.fade < stem_target {
+                    stem.fade = (stem.fade + stem_fade_step).min(stem_target);
+                } else {
+                    stem.fade = (stem.fade - stem_fade_step).max(stem_target);
+                }
+
+                let Some(source) = state.try_get_source_mut(stem.source) else {
+                    continue;
+                };
+
+                if active {
+                    source.set_gain(self.gain * track.fade * stem.fade * stem.gain);
+                    if source.status() != Status::Playing {
+                        // Stems are started all at once so they stay in sync, silent stems are
+                        // playing with zero gain.
+                        source.play();
+                    }
+                } else if source.status() != Status::Stopped {
+                    source.set_gain(0.0);
+                    let _ = source.stop();
+                }
+            }
+
+            if !active {
+                track.time = 0.0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        context::SoundContext,
+        music::{MusicManager, MusicStem, MusicTrack, TransitionQuantization},
+        source::{SoundSourceBuilder, Status},
+    };
+
+    #[test]
+    fn test_quantized_crossfade() {
+        let context = SoundContext::new();
+        let mut state = context.state();
+
+        let a = state.add_source(SoundSourceBuilder::new().build().unwrap());
+        let b = state.add_source(SoundSourceBuilder::new().build().unwrap());
+
+        let mut manager = MusicManager::new();
+        // 60 BPM - one beat per second, four seconds per bar.
+        let track_a =
+            manager.add_track(MusicTrack::new("A", 60.0, 4).with_stem(MusicStem::new(a, 0.0)));
+        let track_b =
+            manager.add_track(MusicTrack::new("B", 60.0, 4).with_stem(MusicStem::new(b, 0.0)));
+
+        manager.set_stem_fade_duration(0.0);
+        manager.play(track_a, 0.0);
+        manager.update(&mut state, 0.5);
+        assert_eq!(state.source(a).status(), Status::Playing);
+        assert_eq!(state.source(a).gain(), 1.0);
+        assert_eq!(state.source(b).status(), Status::Stopped);
+
+        manager.transition_to(track_b, TransitionQuantization::Bar, 1.0);
+
+        // Still within the first bar.
+        manager.update(&mut state, 3.0);
+        assert!(manager.has_pending_transition());
+        assert_eq!(manager.current_track(), track_a);
+
+        // Crosses the bar boundary, crossfade begins.
+        manager.update(&mut state, 0.5);
+        assert!(!manager.has_pending_transition());
+        assert_eq!(manager.current_track(), track_b);
+        assert_eq!(state.source(a).gain(), 0.5);
+        assert_eq!(state.source(b).gain(), 0.5);
+
+        manager.update(&mut state, 0.5);
+        assert_eq!(state.source(a).status(), Status::Stopped);
+        assert_eq!(state.source(b).gain(), 1.0);
+    }
+
+    #[test]
+    fn test_intensity_layers() {
+        let context = SoundContext::new();
+        let mut state = context.state();
+
+        let base = state.add_source(SoundSourceBuilder::new().build().unwrap());
+        let drums = state.add_source(SoundSourceBuilder::new().build().unwrap());
+
+        let mut manager = MusicManager::new();
+        let track = manager.add_track(
+            MusicTrack::new("Combat", 120.0, 4)
+                .with_stem(MusicStem::new(base, 0.0))
+                .with_stem(MusicStem::new(drums, 0.5)),
+        );
+
+        manager.set_stem_fade_duration(1.0);
+        manager.play(track, 0.0);
+        manager.update(&mut state, 1.0);
+        assert_eq!(state.source(base).gain(), 1.0);
+        assert_eq!(state.source(drums).gain(), 0.0);
+        // Silent stems keep playing to stay in sync.
+        assert_eq!(state.source(drums).status(), Status::Playing);
+
+        manager.set_intensity(0.75);
+        manager.update(&mut state, 0.5);
+        assert_eq!(state.source(drums).gain(), 0.5);
+        manager.update(&mut state, 0.5);
+        assert_eq!(state.source(drums).gain(), 1.0);
+    }
+}
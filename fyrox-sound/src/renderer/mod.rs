@@ -96,12 +96,11 @@ fn render_with_params(
     }
 }
 
-pub(crate) fn render_source_default(
-    source: &mut SoundSource,
+fn listener_gains(
+    source: &SoundSource,
     listener: &Listener,
     distance_model: DistanceModel,
-    mix_buffer: &mut [(f32, f32)],
-) {
+) -> (f32, f32) {
     let distance_gain = lerpf(
         1.0,
         source.calculate_distance_gain(listener, distance_model),
@@ -112,9 +111,28 @@ pub(crate) fn render_source_default(
         source.calculate_panning(listener),
         source.spatial_blend(),
     );
-    let gain = distance_gain * source.gain();
-    let left_gain = gain * (1.0 + panning);
-    let right_gain = gain * (1.0 - panning);
+    let gain = distance_gain * source.gain() * listener.gain();
+    listener
+        .output()
+        .route(gain * (1.0 + panning), gain * (1.0 - panning))
+}
+
+pub(crate) fn render_source_default<'a>(
+    source: &mut SoundSource,
+    listeners: impl IntoIterator<Item = &'a Listener>,
+    distance_model: DistanceModel,
+    mix_buffer: &mut [(f32, f32)],
+) {
+    // Each listener hears the source differently. Summing the gains would make sources louder with
+    // every listener that hears them, so the loudest gain of each channel is used instead. This way
+    // a source heard by two listeners on the same channels has the same loudness as with one
+    // listener, while listeners routed to separate channels do not affect each other.
+    let (left_gain, right_gain) = listeners
+        .into_iter()
+        .map(|listener| listener_gains(source, listener, distance_model))
+        .fold((0.0f32, 0.0f32), |(left, right), (l, r)| {
+            (left.max(l), right.max(r))
+        });
     render_with_params(source, left_gain, right_gain, mix_buffer);
     source.last_left_gain = Some(left_gain);
     source.last_right_gain = Some(right_gain);
@@ -128,3 +146,38 @@ pub(crate) fn render_source_2d_only(source: &mut SoundSource, mix_buffer: &mut [
     source.last_left_gain = Some(left_gain);
     source.last_right_gain = Some(right_gain);
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        context::DistanceModel,
+        listener::{Listener, ListenerOutput},
+        renderer::render_source_default,
+        source::SoundSource,
+    };
+
+    fn render(listeners: &[Listener]) -> (f32, f32) {
+        let mut source = SoundSource::default();
+        source.frame_samples = vec![(1.0, 1.0)];
+        let mut mix_buffer = [(0.0, 0.0)];
+        render_source_default(&mut source, listeners, DistanceModel::None, &mut mix_buffer);
+        mix_buffer[0]
+    }
+
+    #[test]
+    fn test_two_listeners() {
+        let single = render(&[Listener::new()]);
+        assert_eq!(single, (1.0, 1.0));
+
+        // Two listeners at the same place must not make the source louder.
+        assert_eq!(render(&[Listener::new(), Listener::new()]), single);
+
+        // Split-screen: each listener writes to its own channel.
+        let mut left = Listener::new();
+        left.set_output(ListenerOutput::Left);
+        let mut right = Listener::new();
+        right.set_output(ListenerOutput::Right);
+        assert_eq!(render(&[left.clone()]), (1.0, 0.0));
+        assert_eq!(render(&[left, right]), single);
+    }
+}
@@ -50,7 +50,7 @@
 
 use crate::{
     buffer::{streaming::StreamingBuffer, SoundBuffer, SoundBufferResource},
-    bus::AudioBusGraph,
+    bus::{AudioBusGraph, AudioBusSend},
     context::DistanceModel,
     error::SoundError,
     listener::Listener,
@@ -118,6 +118,8 @@ pub struct SoundSource {
     status: Status,
     #[visit(optional)]
     pub(crate) bus: String,
+    #[visit(optional)]
+    pub(crate) sends: Vec<AudioBusSend>,
     play_once: bool,
     // Here we use Option because when source is just created it has no info about it
     // previous left and right channel gains. We can't set it to 1.0 for example
@@ -175,6 +177,7 @@ impl Default for SoundSource {
             resampling_multiplier: 1.0,
             status: Status::Stopped,
             bus: "Master".to_string(),
+            sends: Default::default(),
             play_once: false,
             last_left_gain: None,
             last_right_gain: None,
@@ -425,6 +428,27 @@ impl SoundSource {
         &self.bus
     }
 
+    /// Adds a new auxiliary send, that will route a copy of the signal of the source to a return bus with the
+    /// given level. See [`AudioBusSend`] docs for more info.
+    pub fn add_send(&mut self, send: AudioBusSend) {
+        self.sends.push(send);
+    }
+
+    /// Removes an auxiliary send at the given index.
+    pub fn remove_send(&mut self, index: usize) -> AudioBusSend {
+        self.sends.remove(index)
+    }
+
+    /// Returns a slice with every auxiliary send of the source.
+    pub fn sends(&self) -> &[AudioBusSend] {
+        &self.sends
+    }
+
+    /// Returns a mutable reference to the list of auxiliary sends of the source.
+    pub fn sends_mut(&mut self) -> &mut Vec<AudioBusSend> {
+        &mut self.sends
+    }
+
     // Distance models were taken from OpenAL Specification because it looks like they're
     // standard in industry and there is no need to reinvent it.
     // https://www.openal.org/documentation/openal-1.1-specification.pdf
@@ -736,6 +760,7 @@ pub struct SoundSourceBuilder {
     rolloff_factor: f32,
     spatial_blend: f32,
     bus: String,
+    sends: Vec<AudioBusSend>,
 }
 
 impl Default for SoundSourceBuilder {
@@ -763,6 +788,7 @@ impl SoundSourceBuilder {
             rolloff_factor: 1.0,
             spatial_blend: 1.0,
             bus: AudioBusGraph::PRIMARY_BUS.to_string(),
+            sends: Default::default(),
         }
     }
 
@@ -862,6 +888,12 @@ impl SoundSourceBuilder {
         self
     }
 
+    /// Sets auxiliary sends of the source. See [`SoundSource::add_send`] for more info.
+    pub fn with_sends(mut self, sends: Vec<AudioBusSend>) -> Self {
+        self.sends = sends;
+        self
+    }
+
     /// Creates new instance of generic sound source. May fail if buffer is invalid.
     pub fn build(self) -> Result<SoundSource, SoundError> {
         let mut source = SoundSource {
@@ -882,6 +914,7 @@ impl SoundSourceBuilder {
             prev_left_samples: Default::default(),
             prev_right_samples: Default::default(),
             bus: self.bus,
+            sends: self.sends,
             ..Default::default()
         };
 
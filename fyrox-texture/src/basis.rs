@@ -0,0 +1,641 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Basis Universal textures transcoding. Basis Universal is a "supercompressed" texture format,
+//! that could be transcoded to a GPU compressed format supported by the current platform at load
+//! time. The data could be stored either in `.basis` files or in KTX2 containers.
+
+use crate::{
+    ktx2::{self, DecodedImage, Ktx2Header},
+    BasisEncoding, TextureError, TextureImportOptions, TextureKind, TexturePixelKind,
+    TranscodeTarget,
+};
+use basis_universal::{
+    encoder_init, transcoder_init, BasisTextureFormat, BasisTextureType, ColorSpace, Compressor,
+    CompressorParams, TranscodeParameters, Transcoder, TranscoderTextureFormat, ETC1S_QUALITY_MAX,
+    ETC1S_QUALITY_MIN, UASTC_QUALITY_MAX, UASTC_QUALITY_MIN,
+};
+use image::RgbaImage;
+use std::{borrow::Cow, io::Read};
+
+const SUPERCOMPRESSION_NONE: u32 = 0;
+const SUPERCOMPRESSION_BASIS_LZ: u32 = 1;
+const SUPERCOMPRESSION_ZSTD: u32 = 2;
+
+const DF_MODEL_ETC1S: u8 = 163;
+const DF_MODEL_UASTC: u8 = 166;
+const DF_TRANSFER_SRGB: u8 = 2;
+const DF_CHANNEL_UASTC_RGBA: u8 = 3;
+const DF_CHANNEL_UASTC_RRRG: u8 = 5;
+
+const BASIS_HEADER_SIZE: usize = 77;
+const BASIS_SLICE_DESC_SIZE: usize = 23;
+const BASIS_VERSION: u16 = 0x13;
+const BASIS_TEX_FORMAT_ETC1S: u8 = 0;
+const BASIS_TEX_FORMAT_UASTC: u8 = 1;
+const BASIS_TEX_TYPE_2D: u8 = 0;
+const BASIS_TEX_TYPE_CUBEMAP_ARRAY: u8 = 2;
+const BASIS_HEADER_FLAG_ETC1S: u16 = 1;
+const BASIS_HEADER_FLAG_HAS_ALPHA_SLICES: u16 = 4;
+const BASIS_HEADER_FLAG_SRGB: u16 = 16;
+const BASIS_SLICE_FLAG_HAS_ALPHA: u8 = 1;
+
+/// Size of the fixed part of BasisLZ global data.
+const BASIS_LZ_GLOBAL_HEADER_SIZE: usize = 20;
+/// Size of a single image descriptor in BasisLZ global data.
+const BASIS_LZ_IMAGE_DESC_SIZE: usize = 20;
+/// Size of a single UASTC block (4x4 texels).
+const UASTC_BLOCK_SIZE: usize = 16;
+
+/// Returns `true` if the data starts with the signature of `.basis` files.
+pub(crate) fn is_basis(data: &[u8]) -> bool {
+    data.starts_with(b"sB")
+}
+
+fn transcoder_format(target: TranscodeTarget) -> (TranscoderTextureFormat, TexturePixelKind) {
+    match target.resolve() {
+        TranscodeTarget::Bc => (
+            TranscoderTextureFormat::BC3_RGBA,
+            TexturePixelKind::DXT5RGBA,
+        ),
+        TranscodeTarget::Etc2 => (
+            TranscoderTextureFormat::ETC2_RGBA,
+            TexturePixelKind::ETC2RGBA,
+        ),
+        TranscodeTarget::Astc => (
+            TranscoderTextureFormat::ASTC_4x4_RGBA,
+            TexturePixelKind::ASTC4x4RGBA,
+        ),
+        TranscodeTarget::Uncompressed | TranscodeTarget::Auto => {
+            (TranscoderTextureFormat::RGBA32, TexturePixelKind::RGBA8)
+        }
+    }
+}
+
+fn decode_error(message: &str) -> TextureError {
+    TextureError::Decode(message.to_string())
+}
+
+/// CRC-16 that is used by Basis Universal to check the integrity of `.basis` files.
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = !0u16;
+    for &byte in data {
+        let q = (byte as u16) ^ (crc >> 8);
+        let k = (q >> 4) ^ q;
+        crc = (crc << 8) ^ k ^ (k << 5) ^ (k << 12);
+    }
+    !crc
+}
+
+fn write_u24(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes()[..3]);
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, TextureError> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| decode_error("Unexpected end of KTX2 data!"))
+}
+
+fn read_u8(data: &[u8], offset: usize) -> Result<u8, TextureError> {
+    data.get(offset)
+        .copied()
+        .ok_or_else(|| decode_error("Unexpected end of KTX2 data!"))
+}
+
+fn sub_slice(data: &[u8], offset: usize, length: usize) -> Result<&[u8], TextureError> {
+    offset
+        .checked_add(length)
+        .and_then(|end| data.get(offset..end))
+        .ok_or_else(|| decode_error("KTX2 data is out of bounds!"))
+}
+
+/// A slice of a `.basis` file, that holds the data of a single mip level of a single face.
+struct BasisSlice<'a> {
+    image_index: u32,
+    level_index: u8,
+    flags: u8,
+    width: u32,
+    height: u32,
+    data: Cow<'a, [u8]>,
+}
+
+/// Codebooks of ETC1S textures, they're stored in BasisLZ global data of KTX2 container.
+#[derive(Default)]
+struct Etc1sCodebooks<'a> {
+    endpoint_count: u16,
+    selector_count: u16,
+    endpoints: &'a [u8],
+    selectors: &'a [u8],
+    tables: &'a [u8],
+    extended: &'a [u8],
+}
+
+fn level_size(size: u32, level: usize) -> u32 {
+    (size >> level).max(1)
+}
+
+fn block_count(size: u32) -> u32 {
+    size.div_ceil(4)
+}
+
+/// Assembles `.basis` file from the given slices and codebooks.
+fn write_basis_file(
+    tex_format: u8,
+    flags: u16,
+    tex_type: u8,
+    image_count: u32,
+    slices: &[BasisSlice],
+    codebooks: &Etc1sCodebooks,
+) -> Result<Vec<u8>, TextureError> {
+    // Data section layout: slice descriptors, codebooks, slice data.
+    let slice_descs_offset = BASIS_HEADER_SIZE;
+    let mut offset = slice_descs_offset + slices.len() * BASIS_SLICE_DESC_SIZE;
+    let mut take = |length: usize| {
+        let current = offset;
+        offset += length;
+        u32::try_from(current).map_err(|_| decode_error("Basis Universal data is too large!"))
+    };
+    let endpoints_offset = take(codebooks.endpoints.len())?;
+    let selectors_offset = take(codebooks.selectors.len())?;
+    let tables_offset = take(codebooks.tables.len())?;
+    let extended_offset = take(codebooks.extended.len())?;
+
+    let mut data = Vec::new();
+    for slice in slices {
+        let width = u16::try_from(slice.width)
+            .map_err(|_| decode_error("Basis Universal texture is too large!"))?;
+        let height = u16::try_from(slice.height)
+            .map_err(|_| decode_error("Basis Universal texture is too large!"))?;
+        write_u24(&mut data, slice.image_index);
+        data.push(slice.level_index);
+        data.push(slice.flags);
+        data.extend_from_slice(&width.to_le_bytes());
+        data.extend_from_slice(&height.to_le_bytes());
+        data.extend_from_slice(&(block_count(slice.width) as u16).to_le_bytes());
+        data.extend_from_slice(&(block_count(slice.height) as u16).to_le_bytes());
+        data.extend_from_slice(&take(slice.data.len())?.to_le_bytes());
+        data.extend_from_slice(&(slice.data.len() as u32).to_le_bytes());
+        data.extend_from_slice(&crc16(&slice.data).to_le_bytes());
+    }
+    data.extend_from_slice(codebooks.endpoints);
+    data.extend_from_slice(codebooks.selectors);
+    data.extend_from_slice(codebooks.tables);
+    data.extend_from_slice(codebooks.extended);
+    for slice in slices {
+        data.extend_from_slice(&slice.data);
+    }
+
+    // Header fields that are covered by the header checksum.
+    let mut header = Vec::with_capacity(BASIS_HEADER_SIZE);
+    header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    header.extend_from_slice(&crc16(&data).to_le_bytes());
+    write_u24(&mut header, slices.len() as u32);
+    write_u24(&mut header, image_count);
+    header.push(tex_format);
+    header.extend_from_slice(&flags.to_le_bytes());
+    header.push(tex_type);
+    // Microseconds per frame, reserved field and user data.
+    header.extend_from_slice(&[0; 3 + 4 + 4 + 4]);
+    header.extend_from_slice(&codebooks.endpoint_count.to_le_bytes());
+    header.extend_from_slice(&endpoints_offset.to_le_bytes());
+    write_u24(&mut header, codebooks.endpoints.len() as u32);
+    header.extend_from_slice(&codebooks.selector_count.to_le_bytes());
+    header.extend_from_slice(&selectors_offset.to_le_bytes());
+    write_u24(&mut header, codebooks.selectors.len() as u32);
+    header.extend_from_slice(&tables_offset.to_le_bytes());
+    header.extend_from_slice(&(codebooks.tables.len() as u32).to_le_bytes());
+    header.extend_from_slice(&(slice_descs_offset as u32).to_le_bytes());
+    header.extend_from_slice(&extended_offset.to_le_bytes());
+    header.extend_from_slice(&(codebooks.extended.len() as u32).to_le_bytes());
+
+    let mut file = Vec::with_capacity(BASIS_HEADER_SIZE + data.len());
+    file.extend_from_slice(b"sB");
+    file.extend_from_slice(&BASIS_VERSION.to_le_bytes());
+    file.extend_from_slice(&(BASIS_HEADER_SIZE as u16).to_le_bytes());
+    file.extend_from_slice(&crc16(&header).to_le_bytes());
+    file.extend_from_slice(&header);
+    debug_assert_eq!(file.len(), BASIS_HEADER_SIZE);
+    file.extend_from_slice(&data);
+    Ok(file)
+}
+
+/// Repacks Basis Universal data of KTX2 container into `.basis` file, that could be consumed by
+/// the transcoder. ETC1S data uses BasisLZ supercompression and its codebooks are stored in the
+/// global data of the container, UASTC data is stored as is or supercompressed with Zstandard.
+fn ktx2_to_basis(data: &[u8]) -> Result<Vec<u8>, TextureError> {
+    let header = Ktx2Header::read(data)?;
+
+    if header.layer_count > 1 || header.depth > 1 {
+        return Err(decode_error(
+            "Array and volume Basis Universal textures are not supported!",
+        ));
+    }
+
+    let face_count = header.face_count.max(1) as usize;
+    let level_count = header.level_count as usize;
+    let image_count = level_count * face_count;
+
+    // Basic data format descriptor block follows the total size of descriptors.
+    let dfd = header.dfd_offset + 4;
+    let color_model = read_u8(data, dfd + 8)?;
+    let transfer_function = read_u8(data, dfd + 10)?;
+    let first_channel = read_u8(data, dfd + 24 + 3)? & 0x0F;
+
+    let mut flags = if transfer_function == DF_TRANSFER_SRGB {
+        BASIS_HEADER_FLAG_SRGB
+    } else {
+        0
+    };
+    let mut slices = Vec::new();
+    let mut codebooks = Etc1sCodebooks::default();
+
+    let tex_format = match (color_model, header.supercompression_scheme) {
+        (DF_MODEL_ETC1S, SUPERCOMPRESSION_BASIS_LZ) => {
+            flags |= BASIS_HEADER_FLAG_ETC1S;
+
+            let global = sub_slice(data, header.sgd_offset, header.sgd_length)?;
+            let endpoints_length = ktx2::read_u32(global, 4)? as usize;
+            let selectors_length = ktx2::read_u32(global, 8)? as usize;
+            let tables_length = ktx2::read_u32(global, 12)? as usize;
+            let extended_length = ktx2::read_u32(global, 16)? as usize;
+            let image_descs = BASIS_LZ_GLOBAL_HEADER_SIZE;
+            let endpoints_offset = image_descs + image_count * BASIS_LZ_IMAGE_DESC_SIZE;
+            let selectors_offset = endpoints_offset + endpoints_length;
+            let tables_offset = selectors_offset + selectors_length;
+            let extended_offset = tables_offset + tables_length;
+            codebooks = Etc1sCodebooks {
+                endpoint_count: read_u16(global, 0)?,
+                selector_count: read_u16(global, 2)?,
+                endpoints: sub_slice(global, endpoints_offset, endpoints_length)?,
+                selectors: sub_slice(global, selectors_offset, selectors_length)?,
+                tables: sub_slice(global, tables_offset, tables_length)?,
+                extended: sub_slice(global, extended_offset, extended_length)?,
+            };
+
+            for level in 0..level_count {
+                let (level_data, _) = ktx2::level_data(data, level)?;
+                for face in 0..face_count {
+                    // Image descriptors are ordered by levels, then by faces.
+                    let desc = image_descs + (level * face_count + face) * BASIS_LZ_IMAGE_DESC_SIZE;
+                    let rgb_offset = ktx2::read_u32(global, desc + 4)? as usize;
+                    let rgb_length = ktx2::read_u32(global, desc + 8)? as usize;
+                    let alpha_offset = ktx2::read_u32(global, desc + 12)? as usize;
+                    let alpha_length = ktx2::read_u32(global, desc + 16)? as usize;
+
+                    let mut slice = |slice_flags, offset, length| -> Result<(), TextureError> {
+                        slices.push(BasisSlice {
+                            image_index: face as u32,
+                            level_index: level as u8,
+                            flags: slice_flags,
+                            width: level_size(header.width, level),
+                            height: level_size(header.height, level),
+                            data: sub_slice(level_data, offset, length)?.into(),
+                        });
+                        Ok(())
+                    };
+                    slice(0, rgb_offset, rgb_length)?;
+                    if alpha_length > 0 {
+                        flags |= BASIS_HEADER_FLAG_HAS_ALPHA_SLICES;
+                        slice(BASIS_SLICE_FLAG_HAS_ALPHA, alpha_offset, alpha_length)?;
+                    }
+                }
+            }
+
+            BASIS_TEX_FORMAT_ETC1S
+        }
+        (DF_MODEL_UASTC, SUPERCOMPRESSION_NONE | SUPERCOMPRESSION_ZSTD) => {
+            let has_alpha = matches!(first_channel, DF_CHANNEL_UASTC_RGBA | DF_CHANNEL_UASTC_RRRG);
+            let slice_flags = if has_alpha {
+                flags |= BASIS_HEADER_FLAG_HAS_ALPHA_SLICES;
+                BASIS_SLICE_FLAG_HAS_ALPHA
+            } else {
+                0
+            };
+
+            for level in 0..level_count {
+                let (level_data, uncompressed_length) = ktx2::level_data(data, level)?;
+                let level_data = if header.supercompression_scheme == SUPERCOMPRESSION_ZSTD {
+                    let mut decompressed = Vec::with_capacity(uncompressed_length.min(1 << 28));
+                    ruzstd::StreamingDecoder::new(level_data)
+                        .map_err(|err| TextureError::Decode(err.to_string()))?
+                        .read_to_end(&mut decompressed)?;
+                    decompressed
+                } else {
+                    level_data.to_vec()
+                };
+
+                let width = level_size(header.width, level);
+                let height = level_size(header.height, level);
+                let face_size =
+                    block_count(width) as usize * block_count(height) as usize * UASTC_BLOCK_SIZE;
+                if level_data.len() < face_size * face_count {
+                    return Err(TextureError::Decode(format!(
+                        "KTX2 level {level} has not enough data!"
+                    )));
+                }
+
+                for (face, face_data) in level_data
+                    .chunks_exact(face_size)
+                    .take(face_count)
+                    .enumerate()
+                {
+                    slices.push(BasisSlice {
+                        image_index: face as u32,
+                        level_index: level as u8,
+                        flags: slice_flags,
+                        width,
+                        height,
+                        data: face_data.to_vec().into(),
+                    });
+                }
+            }
+
+            BASIS_TEX_FORMAT_UASTC
+        }
+        (model, scheme) => {
+            return Err(TextureError::Decode(format!(
+                "Unsupported combination of KTX2 color model {model} and \
+                supercompression scheme {scheme}!"
+            )))
+        }
+    };
+
+    let tex_type = if face_count == 6 {
+        BASIS_TEX_TYPE_CUBEMAP_ARRAY
+    } else {
+        BASIS_TEX_TYPE_2D
+    };
+
+    write_basis_file(
+        tex_format,
+        flags,
+        tex_type,
+        face_count as u32,
+        &slices,
+        &codebooks,
+    )
+}
+
+/// Transcodes KTX2 container with Basis Universal data to the GPU format defined by the given
+/// target.
+pub(crate) fn read_ktx2_basis(
+    data: &[u8],
+    target: TranscodeTarget,
+) -> Result<DecodedImage, TextureError> {
+    read_basis(&ktx2_to_basis(data)?, target)
+}
+
+/// Transcodes `.basis` file data to the GPU format defined by the given target.
+pub(crate) fn read_basis(
+    data: &[u8],
+    target: TranscodeTarget,
+) -> Result<DecodedImage, TextureError> {
+    transcoder_init();
+
+    let mut transcoder = Transcoder::new();
+    if !transcoder.validate_header(data) {
+        return Err(TextureError::Decode(
+            "Invalid Basis Universal header!".to_string(),
+        ));
+    }
+
+    let image_count = transcoder.image_count(data);
+    let is_cube_map =
+        transcoder.basis_texture_type(data) == BasisTextureType::TextureTypeCubemapArray;
+    let face_count = if is_cube_map { 6 } else { 1 };
+    if image_count < face_count {
+        return Err(TextureError::Decode(
+            "Basis Universal file has no images!".to_string(),
+        ));
+    }
+
+    let description = transcoder
+        .image_level_description(data, 0, 0)
+        .ok_or_else(|| TextureError::Decode("Invalid Basis Universal image!".to_string()))?;
+    let width = description.original_width;
+    let height = description.original_height;
+    let level_count = transcoder.image_level_count(data, 0);
+
+    transcoder
+        .prepare_transcoding(data)
+        .map_err(|_| TextureError::Decode("Unable to prepare Basis transcoding!".to_string()))?;
+
+    let (format, pixel_kind) = transcoder_format(target);
+
+    // The engine expects levels to be stored one after another with every face inside of each
+    // level.
+    let mut bytes = Vec::new();
+    for level_index in 0..level_count {
+        for image_index in 0..face_count {
+            let level = transcoder
+                .transcode_image_level(
+                    data,
+                    format,
+                    TranscodeParameters {
+                        image_index,
+                        level_index,
+                        ..Default::default()
+                    },
+                )
+                .map_err(|err| {
+                    TextureError::Decode(format!("Unable to transcode Basis texture: {err:?}"))
+                })?;
+            bytes.extend_from_slice(&level);
+        }
+    }
+
+    transcoder.end_transcoding();
+
+    Ok(DecodedImage {
+        pixel_kind,
+        kind: if is_cube_map {
+            TextureKind::Cube { width, height }
+        } else {
+            TextureKind::Rectangle { width, height }
+        },
+        mip_count: level_count,
+        bytes,
+    })
+}
+
+/// Encodes the given image to Basis Universal at import time and transcodes it to the target
+/// format of the import options. See [`BasisEncoding`] docs for more info.
+pub(crate) fn encode(
+    image: &RgbaImage,
+    import_options: &TextureImportOptions,
+) -> Result<DecodedImage, TextureError> {
+    encoder_init();
+
+    let lerp = |min: u32, max: u32| {
+        min + ((max - min) as f32 * import_options.basis_quality.clamp(0.0, 1.0)).round() as u32
+    };
+
+    let mut params = CompressorParams::new();
+    match import_options.basis_encoding {
+        BasisEncoding::Etc1s | BasisEncoding::Disabled => {
+            params.set_basis_format(BasisTextureFormat::ETC1S);
+            params.set_etc1s_quality_level(lerp(ETC1S_QUALITY_MIN, ETC1S_QUALITY_MAX));
+        }
+        BasisEncoding::Uastc => {
+            params.set_basis_format(BasisTextureFormat::UASTC4x4);
+            params.set_uastc_quality_level(lerp(UASTC_QUALITY_MIN, UASTC_QUALITY_MAX));
+        }
+    }
+    params.set_color_space(if import_options.srgb {
+        ColorSpace::Srgb
+    } else {
+        ColorSpace::Linear
+    });
+    params.set_generate_mipmaps(import_options.minification_filter.is_using_mip_mapping());
+    params.set_print_status_to_stdout(false);
+    params
+        .source_image_mut(0)
+        .init(image.as_raw(), image.width(), image.height(), 4);
+
+    let thread_count = std::thread::available_parallelism().map_or(1, |count| count.get() as u32);
+    let mut compressor = Compressor::new(thread_count);
+    // SAFETY: The parameters are fully initialized above and outlive the compressor.
+    unsafe {
+        if !compressor.init(&params) {
+            return Err(decode_error(
+                "Unable to initialize Basis Universal encoder!",
+            ));
+        }
+        compressor.process().map_err(|err| {
+            TextureError::Decode(format!("Unable to encode Basis texture: {err:?}"))
+        })?;
+    }
+
+    read_basis(compressor.basis_file(), import_options.transcode_target)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 32 bytes from 0 to 31 compressed with Zstandard.
+    const COMPRESSED_LEVEL: [u8; 41] = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x68, 0x01, 0x01, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05,
+        0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14,
+        0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+    ];
+
+    fn make_uastc_ktx2(width: u32, height: u32, level: &[u8], uncompressed_len: u64) -> Vec<u8> {
+        let mut data = b"\xABKTX 20\xBB\r\n\x1A\n".to_vec();
+        let level_index_size = ktx2::LEVEL_INDEX_ENTRY_SIZE as u32;
+        let dfd_offset = ktx2::HEADER_SIZE as u32 + level_index_size;
+        let dfd_size = 4 + 24 + 16;
+        for value in [
+            0,
+            1,
+            width,
+            height,
+            0,
+            0,
+            1,
+            1,
+            SUPERCOMPRESSION_ZSTD,
+            dfd_offset,
+            dfd_size,
+            0,
+            0,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        // Supercompression global data is not used.
+        data.extend_from_slice(&[0; 16]);
+        let level_offset = (dfd_offset + dfd_size) as u64;
+        for value in [level_offset, level.len() as u64, uncompressed_len] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        // Data format descriptor with a single RGBA sample.
+        data.extend_from_slice(&dfd_size.to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&[DF_MODEL_UASTC, 1, DF_TRANSFER_SRGB, 0]);
+        data.extend_from_slice(&[0; 12]);
+        data.extend_from_slice(&[0, 0, 0, DF_CHANNEL_UASTC_RGBA]);
+        data.extend_from_slice(&[0; 12]);
+        data.extend_from_slice(level);
+        data
+    }
+
+    #[test]
+    fn test_encode() {
+        let image = RgbaImage::from_pixel(8, 8, image::Rgba([255, 0, 0, 255]));
+        let options = TextureImportOptions::default()
+            .with_basis_encoding(BasisEncoding::Uastc)
+            .with_transcode_target(TranscodeTarget::Uncompressed);
+        let decoded = encode(&image, &options).unwrap();
+        assert_eq!(decoded.pixel_kind, TexturePixelKind::RGBA8);
+        assert!(matches!(
+            decoded.kind,
+            TextureKind::Rectangle {
+                width: 8,
+                height: 8
+            }
+        ));
+        // Mip levels are generated by the encoder: 8x8, 4x4, 2x2 and 1x1.
+        assert_eq!(decoded.mip_count, 4);
+        assert!(decoded.bytes[0] > 240 && decoded.bytes[1] < 16);
+    }
+
+    #[test]
+    fn test_crc16() {
+        assert_eq!(crc16(b"123456789"), 0xD64E);
+    }
+
+    #[test]
+    fn test_uastc_ktx2_to_basis() {
+        let data = make_uastc_ktx2(8, 4, &COMPRESSED_LEVEL, 32);
+        let basis = ktx2_to_basis(&data).unwrap();
+        assert!(is_basis(&basis));
+        assert_eq!(
+            u16::from_le_bytes([basis[6], basis[7]]),
+            crc16(&basis[8..BASIS_HEADER_SIZE])
+        );
+        // Total slices, total images, texture format, flags and texture type.
+        assert_eq!(&basis[14..17], &[1, 0, 0]);
+        assert_eq!(&basis[17..20], &[1, 0, 0]);
+        assert_eq!(basis[20], BASIS_TEX_FORMAT_UASTC);
+        assert_eq!(
+            u16::from_le_bytes([basis[21], basis[22]]),
+            BASIS_HEADER_FLAG_SRGB | BASIS_HEADER_FLAG_HAS_ALPHA_SLICES
+        );
+        assert_eq!(basis[23], BASIS_TEX_TYPE_2D);
+
+        let desc = &basis[BASIS_HEADER_SIZE..BASIS_HEADER_SIZE + BASIS_SLICE_DESC_SIZE];
+        assert_eq!(desc[4], BASIS_SLICE_FLAG_HAS_ALPHA);
+        // Original size and size in blocks.
+        assert_eq!(&desc[5..13], &[8, 0, 4, 0, 2, 0, 1, 0]);
+        let offset = u32::from_le_bytes(desc[13..17].try_into().unwrap()) as usize;
+        let size = u32::from_le_bytes(desc[17..21].try_into().unwrap()) as usize;
+        assert_eq!(size, 32);
+        let slice = &basis[offset..offset + size];
+        assert!(slice.iter().copied().eq(0..32));
+        assert_eq!(u16::from_le_bytes([desc[21], desc[22]]), crc16(slice));
+    }
+
+    #[test]
+    fn test_uastc_ktx2_not_enough_data() {
+        let data = make_uastc_ktx2(16, 16, &COMPRESSED_LEVEL, 32);
+        assert!(ktx2_to_basis(&data).is_err());
+    }
+}
@@ -0,0 +1,398 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! KTX2 container reader and writer. Textures with Basis Universal data (ETC1S with BasisLZ supercompression
+//! or UASTC, optionally supercompressed with Zstandard) are transcoded by the `basis` module when
+//! `basis-universal` feature is enabled.
+
+use crate::{TextureError, TextureKind, TexturePixelKind};
+
+const IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+pub(crate) const HEADER_SIZE: usize = 80;
+pub(crate) const LEVEL_INDEX_ENTRY_SIZE: usize = 24;
+/// Vulkan format of textures with Basis Universal data.
+const VK_FORMAT_UNDEFINED: u32 = 0;
+
+/// Texture data decoded from a container, that is ready to be uploaded to GPU.
+pub(crate) struct DecodedImage {
+    pub pixel_kind: TexturePixelKind,
+    pub kind: TextureKind,
+    pub mip_count: u32,
+    /// Mip levels stored one after another, starting from the largest one. Every level of cube
+    /// maps contains six faces.
+    pub bytes: Vec<u8>,
+}
+
+/// Returns `true` if the data starts with KTX2 identifier.
+pub(crate) fn is_ktx2(data: &[u8]) -> bool {
+    data.starts_with(&IDENTIFIER)
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Result<u32, TextureError> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| TextureError::Decode("Unexpected end of KTX2 data!".to_string()))
+}
+
+pub(crate) fn read_u64(data: &[u8], offset: usize) -> Result<u64, TextureError> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| TextureError::Decode("Unexpected end of KTX2 data!".to_string()))
+}
+
+/// Maps Vulkan format to engine's pixel kind. sRGB formats are mapped to their linear
+/// counterparts, because the engine decides the color space by itself.
+fn convert_vk_format(vk_format: u32) -> Option<TexturePixelKind> {
+    Some(match vk_format {
+        9 => TexturePixelKind::R8,
+        16 => TexturePixelKind::RG8,
+        23 | 29 => TexturePixelKind::RGB8,
+        30 | 36 => TexturePixelKind::BGR8,
+        37 | 43 => TexturePixelKind::RGBA8,
+        44 | 50 => TexturePixelKind::BGRA8,
+        70 => TexturePixelKind::R16,
+        76 => TexturePixelKind::R16F,
+        77 => TexturePixelKind::RG16,
+        84 => TexturePixelKind::RGB16,
+        90 => TexturePixelKind::RGB16F,
+        91 => TexturePixelKind::RGBA16,
+        100 => TexturePixelKind::R32F,
+        106 => TexturePixelKind::RGB32F,
+        109 => TexturePixelKind::RGBA32F,
+        131 | 132 => TexturePixelKind::DXT1RGB,
+        133 | 134 => TexturePixelKind::DXT1RGBA,
+        135 | 136 => TexturePixelKind::DXT3RGBA,
+        137 | 138 => TexturePixelKind::DXT5RGBA,
+        139 => TexturePixelKind::R8RGTC,
+        141 => TexturePixelKind::RG8RGTC,
+        147 | 148 => TexturePixelKind::ETC2RGB,
+        151 | 152 => TexturePixelKind::ETC2RGBA,
+        157 | 158 => TexturePixelKind::ASTC4x4RGBA,
+        _ => return None,
+    })
+}
+
+/// Maps engine's pixel kind to Vulkan format, it is the inverse of [`convert_vk_format`]. Luminance
+/// formats have no Vulkan counterparts.
+fn to_vk_format(pixel_kind: TexturePixelKind) -> Option<u32> {
+    Some(match pixel_kind {
+        TexturePixelKind::R8 => 9,
+        TexturePixelKind::RG8 => 16,
+        TexturePixelKind::RGB8 => 23,
+        TexturePixelKind::BGR8 => 30,
+        TexturePixelKind::RGBA8 => 37,
+        TexturePixelKind::BGRA8 => 44,
+        TexturePixelKind::R16 => 70,
+        TexturePixelKind::R16F => 76,
+        TexturePixelKind::RG16 => 77,
+        TexturePixelKind::RGB16 => 84,
+        TexturePixelKind::RGB16F => 90,
+        TexturePixelKind::RGBA16 => 91,
+        TexturePixelKind::R32F => 100,
+        TexturePixelKind::RGB32F => 106,
+        TexturePixelKind::RGBA32F => 109,
+        TexturePixelKind::DXT1RGB => 131,
+        TexturePixelKind::DXT1RGBA => 133,
+        TexturePixelKind::DXT3RGBA => 135,
+        TexturePixelKind::DXT5RGBA => 137,
+        TexturePixelKind::R8RGTC => 139,
+        TexturePixelKind::RG8RGTC => 141,
+        TexturePixelKind::ETC2RGB => 147,
+        TexturePixelKind::ETC2RGBA => 151,
+        TexturePixelKind::ASTC4x4RGBA => 157,
+        TexturePixelKind::Luminance8
+        | TexturePixelKind::Luminance16
+        | TexturePixelKind::LuminanceAlpha8
+        | TexturePixelKind::LuminanceAlpha16 => return None,
+    })
+}
+
+/// Fields of KTX2 header that are used by the engine.
+pub(crate) struct Ktx2Header {
+    pub vk_format: u32,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    pub layer_count: u32,
+    pub face_count: u32,
+    pub level_count: u32,
+    pub supercompression_scheme: u32,
+    /// Offset of the data format descriptor.
+    pub dfd_offset: usize,
+    /// Offset and length of the supercompression global data.
+    pub sgd_offset: usize,
+    pub sgd_length: usize,
+}
+
+impl Ktx2Header {
+    pub fn read(data: &[u8]) -> Result<Self, TextureError> {
+        if !is_ktx2(data) || data.len() < HEADER_SIZE {
+            return Err(TextureError::Decode("Invalid KTX2 header!".to_string()));
+        }
+
+        Ok(Self {
+            vk_format: read_u32(data, 12)?,
+            width: read_u32(data, 20)?,
+            height: read_u32(data, 24)?,
+            depth: read_u32(data, 28)?,
+            layer_count: read_u32(data, 32)?,
+            face_count: read_u32(data, 36)?,
+            level_count: read_u32(data, 40)?.max(1),
+            supercompression_scheme: read_u32(data, 44)?,
+            dfd_offset: read_u32(data, 48)? as usize,
+            sgd_offset: read_u64(data, 64)? as usize,
+            sgd_length: read_u64(data, 72)? as usize,
+        })
+    }
+
+    /// Returns `true` if the texture contains Basis Universal data.
+    pub fn is_basis_universal(&self) -> bool {
+        self.vk_format == VK_FORMAT_UNDEFINED
+    }
+}
+
+/// Returns the data of the given mip level as it is stored in the file (possibly supercompressed)
+/// and the length of the uncompressed data.
+pub(crate) fn level_data(data: &[u8], level: usize) -> Result<(&[u8], usize), TextureError> {
+    let entry = HEADER_SIZE + level * LEVEL_INDEX_ENTRY_SIZE;
+    let offset = read_u64(data, entry)? as usize;
+    let length = read_u64(data, entry + 8)? as usize;
+    let uncompressed_length = read_u64(data, entry + 16)? as usize;
+    let level_data = offset
+        .checked_add(length)
+        .and_then(|end| data.get(offset..end))
+        .ok_or_else(|| TextureError::Decode(format!("KTX2 level {level} is out of bounds!")))?;
+    Ok((level_data, uncompressed_length))
+}
+
+/// Reads a texture from KTX2 container. Textures with Basis Universal data must be read using
+/// `basis::read_ktx2_basis`.
+pub(crate) fn read_ktx2(data: &[u8]) -> Result<DecodedImage, TextureError> {
+    let Ktx2Header {
+        vk_format,
+        width,
+        height,
+        depth,
+        layer_count,
+        face_count,
+        level_count,
+        supercompression_scheme,
+        ..
+    } = Ktx2Header::read(data)?;
+
+    if vk_format == VK_FORMAT_UNDEFINED {
+        return Err(TextureError::Decode(
+            "KTX2 texture contains Basis Universal data, enable basis-universal feature \
+            to load such textures."
+                .to_string(),
+        ));
+    }
+
+    if supercompression_scheme != 0 {
+        return Err(TextureError::Decode(format!(
+            "KTX2 supercompression scheme {supercompression_scheme} is not supported!"
+        )));
+    }
+
+    if layer_count > 1 {
+        return Err(TextureError::Decode(
+            "Array textures are not supported!".to_string(),
+        ));
+    }
+
+    let pixel_kind = convert_vk_format(vk_format).ok_or_else(|| {
+        TextureError::Decode(format!("Unsupported KTX2 pixel format {vk_format}!"))
+    })?;
+
+    let kind = if face_count == 6 {
+        TextureKind::Cube { width, height }
+    } else if depth > 0 {
+        TextureKind::Volume {
+            width,
+            height,
+            depth,
+        }
+    } else if height > 0 {
+        TextureKind::Rectangle { width, height }
+    } else {
+        TextureKind::Line { length: width }
+    };
+
+    // Levels are stored in the file from the smallest one, but the level index starts from the
+    // largest one, so it defines the order we need.
+    let mut bytes = Vec::new();
+    for level in 0..level_count as usize {
+        bytes.extend_from_slice(level_data(data, level)?.0);
+    }
+
+    Ok(DecodedImage {
+        pixel_kind,
+        kind,
+        mip_count: level_count,
+        bytes,
+    })
+}
+
+/// Writes the given mip levels (starting from the largest one) into KTX2 container, that could be
+/// read back by [`read_ktx2`]. Data format descriptor and key-value data are not written, because
+/// the engine does not use them.
+pub(crate) fn write_ktx2(
+    pixel_kind: TexturePixelKind,
+    kind: TextureKind,
+    levels: &[&[u8]],
+) -> Result<Vec<u8>, TextureError> {
+    let vk_format = to_vk_format(pixel_kind).ok_or(TextureError::UnsupportedFormat)?;
+    let (width, height, depth, face_count) = match kind {
+        TextureKind::Line { length } => (length, 0, 0, 1),
+        TextureKind::Rectangle { width, height } => (width, height, 0, 1),
+        TextureKind::Cube { width, height } => (width, height, 0, 6),
+        TextureKind::Volume {
+            width,
+            height,
+            depth,
+        } => (width, height, depth, 1),
+    };
+
+    let mut data = IDENTIFIER.to_vec();
+    let level_count = levels.len() as u32;
+    for value in [
+        vk_format,
+        1,
+        width,
+        height,
+        depth,
+        0,
+        face_count,
+        level_count,
+        0,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    // Data format descriptor, key-value data and supercompression global data.
+    data.extend_from_slice(&[0; 32]);
+
+    // Levels are stored from the smallest one, as the specification requires.
+    let mut offsets = vec![0u64; levels.len()];
+    let mut offset = (HEADER_SIZE + levels.len() * LEVEL_INDEX_ENTRY_SIZE) as u64;
+    for (level, level_offset) in levels.iter().zip(offsets.iter_mut()).rev() {
+        *level_offset = offset;
+        offset += level.len() as u64;
+    }
+    for (level, level_offset) in levels.iter().zip(offsets) {
+        data.extend_from_slice(&level_offset.to_le_bytes());
+        data.extend_from_slice(&(level.len() as u64).to_le_bytes());
+        data.extend_from_slice(&(level.len() as u64).to_le_bytes());
+    }
+    for level in levels.iter().rev() {
+        data.extend_from_slice(level);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_ktx2(vk_format: u32, width: u32, height: u32, levels: &[&[u8]]) -> Vec<u8> {
+        let mut data = IDENTIFIER.to_vec();
+        for value in [vk_format, 1, width, height, 0, 0, 1, levels.len() as u32, 0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        // Data format descriptor, key-value data and supercompression data are not used.
+        data.extend_from_slice(&[0; 32]);
+        let mut offset = (HEADER_SIZE + levels.len() * LEVEL_INDEX_ENTRY_SIZE) as u64;
+        for level in levels {
+            data.extend_from_slice(&offset.to_le_bytes());
+            data.extend_from_slice(&(level.len() as u64).to_le_bytes());
+            data.extend_from_slice(&(level.len() as u64).to_le_bytes());
+            offset += level.len() as u64;
+        }
+        for level in levels {
+            data.extend_from_slice(level);
+        }
+        data
+    }
+
+    #[test]
+    fn test_read_ktx2() {
+        let data = make_ktx2(37, 2, 2, &[&[1; 16], &[2; 4]]);
+        assert!(is_ktx2(&data));
+
+        let image = read_ktx2(&data).unwrap();
+        assert_eq!(image.pixel_kind, TexturePixelKind::RGBA8);
+        assert_eq!(image.mip_count, 2);
+        assert!(matches!(
+            image.kind,
+            TextureKind::Rectangle {
+                width: 2,
+                height: 2
+            }
+        ));
+        assert_eq!(image.bytes.len(), 20);
+        assert_eq!(&image.bytes[16..], &[2; 4]);
+    }
+
+    #[test]
+    fn test_write_ktx2() {
+        let levels: [&[u8]; 3] = [&[1; 32], &[2; 8], &[3; 8]];
+        let data = write_ktx2(
+            TexturePixelKind::DXT5RGBA,
+            TextureKind::Rectangle {
+                width: 8,
+                height: 4,
+            },
+            &levels,
+        )
+        .unwrap();
+
+        let image = read_ktx2(&data).unwrap();
+        assert_eq!(image.pixel_kind, TexturePixelKind::DXT5RGBA);
+        assert_eq!(image.mip_count, 3);
+        assert!(matches!(
+            image.kind,
+            TextureKind::Rectangle {
+                width: 8,
+                height: 4
+            }
+        ));
+        assert_eq!(image.bytes, levels.concat());
+        // The smallest level goes first.
+        assert_eq!(data[HEADER_SIZE + 3 * LEVEL_INDEX_ENTRY_SIZE], 3);
+
+        assert!(write_ktx2(
+            TexturePixelKind::Luminance8,
+            TextureKind::Line { length: 4 },
+            &[&[0; 4]]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_read_ktx2_unsupported_format() {
+        let data = make_ktx2(1, 2, 2, &[&[1; 16]]);
+        assert!(read_ktx2(&data).is_err());
+        // Basis Universal data must be transcoded.
+        let data = make_ktx2(VK_FORMAT_UNDEFINED, 2, 2, &[&[1; 16]]);
+        assert!(read_ktx2(&data).is_err());
+        assert!(!is_ktx2(&[0; 16]));
+    }
+}
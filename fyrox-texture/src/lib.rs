@@ -27,11 +27,15 @@
 //! ## Supported formats
 //!
 //! To load images and decode them, Fyrox uses image and ddsfile crates. Here is the list of
-//! supported formats: png, tga, bmp, dds, jpg, gif, tiff, dds.
+//! supported formats: png, tga, bmp, dds, jpg, gif, tiff, dds, ktx2. Basis Universal textures
+//! (`.basis` files and supercompressed KTX2 containers) are supported when `basis-universal`
+//! feature is enabled.
 //!
 //! ## Compressed textures
 //!
-//! Fyrox supports most commonly used formats of compressed textures: DXT1, DXT3, DXT5.
+//! Fyrox supports most commonly used formats of compressed textures: DXT1, DXT3, DXT5, RGTC, and
+//! ETC2 and ASTC 4x4 on platforms that support them. Basis Universal textures are transcoded at
+//! load time to a format defined by [`TranscodeTarget`].
 //!
 //! ## Render target
 //!
@@ -57,13 +61,14 @@ use fyrox_core::{
     TypeUuidProvider,
 };
 use fyrox_resource::{
-    embedded_data_source, io::ResourceIo, manager::BuiltInResource, options::ImportOptions,
-    untyped::ResourceKind, Resource, ResourceData, TEXTURE_RESOURCE_UUID,
+    budget::ResourceMemoryCategory, embedded_data_source, io::ResourceIo, manager::BuiltInResource,
+    options::ImportOptions, untyped::ResourceKind, Resource, ResourceData, TEXTURE_RESOURCE_UUID,
 };
 use image::{ColorType, DynamicImage, ImageError, ImageFormat, Pixel};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::{
+    borrow::Cow,
     fmt::{Debug, Display, Formatter},
     hash::{Hash, Hasher},
     io::Cursor,
@@ -73,6 +78,9 @@ use std::{
 };
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
+#[cfg(feature = "basis-universal")]
+mod basis;
+mod ktx2;
 pub mod loader;
 
 /// Texture kind.
@@ -323,7 +331,12 @@ impl ResourceData for Texture {
             | TexturePixelKind::BGRA8
             | TexturePixelKind::RGB16F
             | TexturePixelKind::R32F
-            | TexturePixelKind::R16F => return Err(Box::new(TextureError::UnsupportedFormat)),
+            | TexturePixelKind::R16F
+            | TexturePixelKind::ETC2RGB
+            | TexturePixelKind::ETC2RGBA
+            | TexturePixelKind::ASTC4x4RGBA => {
+                return Err(Box::new(TextureError::UnsupportedFormat))
+            }
         };
         if let TextureKind::Rectangle { width, height } = self.kind {
             Ok(image::save_buffer(
@@ -341,6 +354,14 @@ impl ResourceData for Texture {
     fn can_be_saved(&self) -> bool {
         true
     }
+
+    fn memory_usage(&self) -> usize {
+        self.bytes.len()
+    }
+
+    fn memory_category(&self) -> ResourceMemoryCategory {
+        ResourceMemoryCategory::Texture
+    }
 }
 
 impl Visit for Texture {
@@ -481,6 +502,8 @@ pub struct TextureImportOptions {
     #[serde(default)]
     pub(crate) flip_green_channel: bool,
     #[serde(default)]
+    pub(crate) srgb: bool,
+    #[serde(default)]
     pub(crate) base_level: usize,
     #[serde(default = "default_max_level")]
     pub(crate) max_level: usize,
@@ -490,6 +513,12 @@ pub struct TextureImportOptions {
     pub(crate) max_lod: f32,
     #[serde(default)]
     pub(crate) lod_bias: f32,
+    #[serde(default)]
+    pub(crate) transcode_target: TranscodeTarget,
+    #[serde(default)]
+    pub(crate) basis_encoding: BasisEncoding,
+    #[serde(default = "default_basis_quality")]
+    pub(crate) basis_quality: f32,
 }
 
 fn default_max_level() -> usize {
@@ -504,6 +533,10 @@ fn default_max_lod() -> f32 {
     1000.0
 }
 
+fn default_basis_quality() -> f32 {
+    0.5
+}
+
 impl Default for TextureImportOptions {
     fn default() -> Self {
         Self {
@@ -516,11 +549,15 @@ impl Default for TextureImportOptions {
             compression: CompressionOptions::default(),
             mip_filter: Default::default(),
             flip_green_channel: false,
+            srgb: false,
             base_level: 0,
             max_level: default_max_level(),
             min_lod: default_min_lod(),
             max_lod: default_max_lod(),
             lod_bias: 0.0,
+            transcode_target: Default::default(),
+            basis_encoding: Default::default(),
+            basis_quality: default_basis_quality(),
         }
     }
 }
@@ -610,6 +647,57 @@ impl TextureImportOptions {
         self.compression = compression;
     }
 
+    /// Sets a GPU format, that Basis Universal textures will be transcoded to.
+    pub fn with_transcode_target(mut self, transcode_target: TranscodeTarget) -> Self {
+        self.transcode_target = transcode_target;
+        self
+    }
+
+    /// Sets a GPU format, that Basis Universal textures will be transcoded to.
+    pub fn set_transcode_target(&mut self, transcode_target: TranscodeTarget) {
+        self.transcode_target = transcode_target;
+    }
+
+    /// Sets Basis Universal encoding, that will be applied to images at import time. See
+    /// [`BasisEncoding`] docs for more info.
+    pub fn with_basis_encoding(mut self, basis_encoding: BasisEncoding) -> Self {
+        self.basis_encoding = basis_encoding;
+        self
+    }
+
+    /// Sets Basis Universal encoding, that will be applied to images at import time. See
+    /// [`BasisEncoding`] docs for more info.
+    pub fn set_basis_encoding(&mut self, basis_encoding: BasisEncoding) {
+        self.basis_encoding = basis_encoding;
+    }
+
+    /// Sets the quality of Basis Universal encoding in `[0; 1]` range. Higher values give better
+    /// quality, but make the encoding slower (and the size larger in case of ETC1S).
+    pub fn with_basis_quality(mut self, basis_quality: f32) -> Self {
+        self.basis_quality = basis_quality.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the quality of Basis Universal encoding in `[0; 1]` range. See
+    /// [`Self::with_basis_quality`] for more info.
+    pub fn set_basis_quality(&mut self, basis_quality: f32) {
+        self.basis_quality = basis_quality.clamp(0.0, 1.0);
+    }
+
+    /// Defines whether the texture stores colors in sRGB color space (diffuse textures, for
+    /// example). Mip levels of such textures are generated in linear color space, which prevents
+    /// darkening of distant surfaces. Has effect only on 8-bit RGB and RGBA textures.
+    pub fn with_srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    /// Defines whether the texture stores colors in sRGB color space. See [`Self::with_srgb`]
+    /// for more info.
+    pub fn set_srgb(&mut self, srgb: bool) {
+        self.srgb = srgb;
+    }
+
     /// Same effect as [`Texture::set_base_level`].
     pub fn with_base_level(mut self, base_level: usize) -> Self {
         self.base_level = base_level;
@@ -1041,6 +1129,27 @@ pub enum TexturePixelKind {
 
     /// Red component as 2-byte, half-precision float.
     R16F = 24,
+
+    /// Compressed ETC2 RGB (no alpha).
+    ///
+    /// # Platform-specific
+    ///
+    /// Supported mostly by mobile GPUs and WebGL on mobile devices.
+    ETC2RGB = 25,
+
+    /// Compressed ETC2 RGBA (with EAC alpha).
+    ///
+    /// # Platform-specific
+    ///
+    /// Supported mostly by mobile GPUs and WebGL on mobile devices.
+    ETC2RGBA = 26,
+
+    /// Compressed ASTC RGBA with 4x4 blocks.
+    ///
+    /// # Platform-specific
+    ///
+    /// Supported mostly by mobile GPUs and WebGL on mobile devices.
+    ASTC4x4RGBA = 27,
 }
 
 impl TexturePixelKind {
@@ -1071,6 +1180,9 @@ impl TexturePixelKind {
             22 => Ok(Self::RGB16F),
             23 => Ok(Self::R32F),
             24 => Ok(Self::R16F),
+            25 => Ok(Self::ETC2RGB),
+            26 => Ok(Self::ETC2RGBA),
+            27 => Ok(Self::ASTC4x4RGBA),
             _ => Err(format!("Invalid texture kind {id}!")),
         }
     }
@@ -1100,7 +1212,10 @@ impl TexturePixelKind {
             | Self::DXT3RGBA
             | Self::DXT5RGBA
             | Self::R8RGTC
-            | Self::RG8RGTC => None,
+            | Self::RG8RGTC
+            | Self::ETC2RGB
+            | Self::ETC2RGBA
+            | Self::ASTC4x4RGBA => None,
         }
     }
 }
@@ -1116,6 +1231,8 @@ pub enum TextureError {
     Image(image::ImageError),
     /// An error occurred during file loading.
     FileLoadError(FileLoadError),
+    /// Texture data is invalid or cannot be decoded (transcoded).
+    Decode(String),
 }
 
 impl Display for TextureError {
@@ -1133,6 +1250,9 @@ impl Display for TextureError {
             TextureError::FileLoadError(v) => {
                 write!(f, "A file load error has occurred {v:?}")
             }
+            TextureError::Decode(v) => {
+                write!(f, "Unable to decode texture data: {v}")
+            }
         }
     }
 }
@@ -1209,6 +1329,90 @@ impl Default for CompressionOptions {
     }
 }
 
+/// A GPU format, that Basis Universal textures will be transcoded to at load time.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    Deserialize,
+    Serialize,
+    PartialEq,
+    Eq,
+    Debug,
+    Reflect,
+    VariantNames,
+    EnumString,
+    AsRefStr,
+)]
+pub enum TranscodeTarget {
+    /// The most suitable format for the current platform: BC on desktop, ETC2 on mobile devices
+    /// (it is mandatory in OpenGL ES 3.0, unlike ASTC, which is missing on many older GPUs) and
+    /// uncompressed RGBA on WebAssembly (since the set of supported formats cannot be known in
+    /// advance there). Use [`Self::Astc`] explicitly, if the target devices are known to support
+    /// it.
+    #[default]
+    Auto,
+    /// BC3 (DXT5) compression, supported by every desktop GPU.
+    Bc,
+    /// ETC2 compression, supported by most mobile GPUs.
+    Etc2,
+    /// ASTC 4x4 compression, supported by modern mobile GPUs.
+    Astc,
+    /// Uncompressed RGBA, supported everywhere, but consumes a lot of memory.
+    Uncompressed,
+}
+
+uuid_provider!(TranscodeTarget = "1f9f3b52-5a4e-4a4c-9c0c-6a7e8f1f2d3b");
+
+/// Basis Universal encoding, that is applied to images (PNG, JPG, etc.) at import time. Encoded
+/// images are then transcoded to [`TranscodeTarget`] the same way as KTX2 and `.basis` files.
+/// Requires `basis-universal` feature, the option is ignored if the feature is disabled.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    Deserialize,
+    Serialize,
+    PartialEq,
+    Eq,
+    Debug,
+    Reflect,
+    VariantNames,
+    EnumString,
+    AsRefStr,
+)]
+pub enum BasisEncoding {
+    /// Images are not encoded, see [`CompressionOptions`] for other ways of compression.
+    #[default]
+    Disabled,
+    /// ETC1S encoding, which gives the smallest size and the fastest transcoding, but lower
+    /// quality. Suitable for diffuse textures.
+    Etc1s,
+    /// UASTC encoding, which gives high quality, but larger size. Suitable for normal maps and
+    /// textures with fine details.
+    Uastc,
+}
+
+uuid_provider!(BasisEncoding = "6b0e5c1e-8a4f-4f0c-9d1b-2f5a3e7c9b41");
+
+impl TranscodeTarget {
+    /// Replaces [`Self::Auto`] with an actual target for the current platform.
+    pub fn resolve(self) -> Self {
+        match self {
+            Self::Auto => {
+                if cfg!(target_arch = "wasm32") {
+                    Self::Uncompressed
+                } else if cfg!(any(target_os = "android", target_os = "ios")) {
+                    Self::Etc2
+                } else {
+                    Self::Bc
+                }
+            }
+            _ => self,
+        }
+    }
+}
+
 fn transmute_slice<T>(bytes: &[u8]) -> &'_ [T] {
     // SAFETY: This is absolutely safe because `image` crate's Rgb8/Rgba8/etc. and `tbc`s Rgb8/Rgba8/etc.
     // have exactly the same memory layout.
@@ -1335,14 +1539,20 @@ fn bytes_in_mip_level(kind: TextureKind, pixel_kind: TexturePixelKind, mip: usiz
         | TexturePixelKind::DXT3RGBA
         | TexturePixelKind::DXT5RGBA
         | TexturePixelKind::R8RGTC
-        | TexturePixelKind::RG8RGTC => {
+        | TexturePixelKind::RG8RGTC
+        | TexturePixelKind::ETC2RGB
+        | TexturePixelKind::ETC2RGBA
+        | TexturePixelKind::ASTC4x4RGBA => {
             let block_size = match pixel_kind {
                 TexturePixelKind::DXT1RGB
                 | TexturePixelKind::DXT1RGBA
-                | TexturePixelKind::R8RGTC => 8,
+                | TexturePixelKind::R8RGTC
+                | TexturePixelKind::ETC2RGB => 8,
                 TexturePixelKind::DXT3RGBA
                 | TexturePixelKind::DXT5RGBA
-                | TexturePixelKind::RG8RGTC => 16,
+                | TexturePixelKind::RG8RGTC
+                | TexturePixelKind::ETC2RGBA
+                | TexturePixelKind::ASTC4x4RGBA => 16,
                 _ => unreachable!(),
             };
             match kind {
@@ -1391,6 +1601,63 @@ fn convert_pixel_type_enum(pixel_kind: TexturePixelKind) -> fr::PixelType {
     }
 }
 
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Converts 8-bit sRGB pixels to 16-bit linear pixels (in native byte order). Alpha channel (if
+/// any) is not converted, since it is always linear.
+fn srgb8_to_linear16(bytes: &[u8], channel_count: usize) -> Vec<u8> {
+    let table = (0..=255u8)
+        .map(|value| (srgb_to_linear(value as f32 / 255.0) * 65535.0).round() as u16)
+        .collect::<Vec<_>>();
+
+    let mut result = Vec::with_capacity(bytes.len() * 2);
+    for (i, value) in bytes.iter().enumerate() {
+        let linear = if i % channel_count == 3 {
+            *value as u16 * 257
+        } else {
+            table[*value as usize]
+        };
+        result.extend_from_slice(&linear.to_ne_bytes());
+    }
+    result
+}
+
+/// Converts 16-bit linear pixels (in native byte order) back to 8-bit sRGB pixels. Alpha channel
+/// (if any) is not converted, since it is always linear.
+fn linear16_to_srgb8(bytes: &[u8], channel_count: usize) -> Vec<u8> {
+    // 12-bit precision is more than enough to get exact 8-bit values in sRGB color space.
+    let table = (0..4096u32)
+        .map(|value| (linear_to_srgb(value as f32 / 4095.0) * 255.0).round() as u8)
+        .collect::<Vec<_>>();
+
+    bytes
+        .chunks_exact(2)
+        .enumerate()
+        .map(|(i, value)| {
+            let linear = u16::from_ne_bytes([value[0], value[1]]);
+            if i % channel_count == 3 {
+                (linear / 257) as u8
+            } else {
+                table[(linear >> 4) as usize]
+            }
+        })
+        .collect()
+}
+
 fn flip_green_channel<'a, P>(pixels: impl Iterator<Item = &'a mut P>)
 where
     P: Pixel + 'a,
@@ -1403,7 +1670,33 @@ where
 }
 
 impl Texture {
-    /// Tries to load a texture from given data in one of the following formats: PNG, BMP, TGA, JPG, DDS, GIF. Use
+    fn from_decoded_image(
+        image: ktx2::DecodedImage,
+        import_options: &TextureImportOptions,
+    ) -> Self {
+        Self {
+            pixel_kind: image.pixel_kind,
+            modifications_counter: 0,
+            minification_filter: import_options.minification_filter,
+            magnification_filter: import_options.magnification_filter,
+            s_wrap_mode: import_options.s_wrap_mode,
+            t_wrap_mode: import_options.t_wrap_mode,
+            r_wrap_mode: import_options.r_wrap_mode,
+            base_level: import_options.base_level,
+            max_level: import_options.max_level,
+            min_lod: import_options.min_lod,
+            max_lod: import_options.max_lod,
+            anisotropy: import_options.anisotropy,
+            mip_count: image.mip_count,
+            bytes: image.bytes.into(),
+            kind: image.kind,
+            is_render_target: false,
+            cache_index: Default::default(),
+            lod_bias: import_options.lod_bias,
+        }
+    }
+
+    /// Tries to load a texture from given data in one of the following formats: PNG, BMP, TGA, JPG, DDS, GIF, KTX2. Use
     /// this method if you want to load a texture from embedded data.
     ///
     /// # On-demand compression and mip-map generation
@@ -1428,6 +1721,29 @@ impl Texture {
         data: &[u8],
         import_options: TextureImportOptions,
     ) -> Result<Self, TextureError> {
+        if ktx2::is_ktx2(data) {
+            #[cfg(feature = "basis-universal")]
+            if ktx2::Ktx2Header::read(data)?.is_basis_universal() {
+                return Ok(Self::from_decoded_image(
+                    basis::read_ktx2_basis(data, import_options.transcode_target)?,
+                    &import_options,
+                ));
+            }
+
+            return Ok(Self::from_decoded_image(
+                ktx2::read_ktx2(data)?,
+                &import_options,
+            ));
+        }
+
+        #[cfg(feature = "basis-universal")]
+        if basis::is_basis(data) {
+            return Ok(Self::from_decoded_image(
+                basis::read_basis(data, import_options.transcode_target)?,
+                &import_options,
+            ));
+        }
+
         // DDS is special. It can contain various kinds of textures as well as textures with
         // various pixel formats.
         //
@@ -1543,6 +1859,14 @@ impl Texture {
                 }
             }
 
+            #[cfg(feature = "basis-universal")]
+            if import_options.basis_encoding != BasisEncoding::Disabled {
+                return Ok(Self::from_decoded_image(
+                    basis::encode(&dyn_img.to_rgba8(), &import_options)?,
+                    &import_options,
+                ));
+            }
+
             let src_pixel_kind = match dyn_img {
                 DynamicImage::ImageLuma8(_) => TexturePixelKind::Luminance8,
                 DynamicImage::ImageLumaA8(_) => TexturePixelKind::LuminanceAlpha8,
@@ -1564,13 +1888,33 @@ impl Texture {
             );
 
             if import_options.minification_filter.is_using_mip_mapping() {
-                let src_pixel_type = convert_pixel_type_enum(src_pixel_kind);
+                // Mip levels of sRGB textures must be generated in linear color space, otherwise
+                // the mip levels will be darker than they should be.
+                let srgb_channel_count = match src_pixel_kind {
+                    TexturePixelKind::RGB8 if import_options.srgb => Some(3),
+                    TexturePixelKind::RGBA8 if import_options.srgb => Some(4),
+                    _ => None,
+                };
+                let (src_pixel_type, src_bytes) = match srgb_channel_count {
+                    Some(3) => (
+                        fr::PixelType::U16x3,
+                        srgb8_to_linear16(dyn_img.as_bytes(), 3),
+                    ),
+                    Some(_) => (
+                        fr::PixelType::U16x4,
+                        srgb8_to_linear16(dyn_img.as_bytes(), 4),
+                    ),
+                    None => (
+                        convert_pixel_type_enum(src_pixel_kind),
+                        dyn_img.as_bytes().to_vec(),
+                    ),
+                };
                 let mut level_width = width;
                 let mut level_height = height;
                 let mut current_level = fr::images::Image::from_vec_u8(
                     level_width,
                     level_height,
-                    dyn_img.as_bytes().to_vec(),
+                    src_bytes,
                     src_pixel_type,
                 )
                 .map_err(|_| TextureError::UnsupportedFormat)?;
@@ -1599,13 +1943,23 @@ impl Texture {
                         current_level = dst_img;
                     }
 
+                    let level_bytes = match srgb_channel_count {
+                        // The first level is the source image itself, there is no need to convert
+                        // it back and forth.
+                        Some(_) if mip_count == 0 => Cow::Borrowed(dyn_img.as_bytes()),
+                        Some(channel_count) => {
+                            Cow::Owned(linear16_to_srgb8(current_level.buffer(), channel_count))
+                        }
+                        None => Cow::Borrowed(current_level.buffer()),
+                    };
+
                     mip_count += 1;
 
                     if import_options.compression == CompressionOptions::NoCompression {
-                        bytes.extend_from_slice(current_level.buffer())
+                        bytes.extend_from_slice(&level_bytes)
                     } else if let Some((compressed_data, new_pixel_kind)) = try_compress(
                         src_pixel_kind,
-                        current_level.buffer(),
+                        &level_bytes,
                         level_width as usize,
                         level_height as usize,
                         import_options.compression,
@@ -1613,7 +1967,7 @@ impl Texture {
                         final_pixel_kind = new_pixel_kind;
                         bytes.extend_from_slice(&compressed_data);
                     } else {
-                        bytes.extend_from_slice(current_level.buffer())
+                        bytes.extend_from_slice(&level_bytes)
                     }
 
                     level_width = level_width.checked_shr(1).unwrap_or_default();
@@ -1898,6 +2252,23 @@ impl Texture {
         &self.bytes[mip_begin..mip_end]
     }
 
+    /// Writes the texture with all its mip levels into KTX2 container. Such textures are loaded as is,
+    /// without compression and mip-map generation, so this method could be used to store imported
+    /// textures (for example, in asset packs). Luminance pixel formats are not supported.
+    pub fn to_ktx2(&self) -> Result<Vec<u8>, TextureError> {
+        let mut levels = Vec::with_capacity(self.mip_count as usize);
+        let mut offset = 0;
+        for mip in 0..self.mip_count as usize {
+            let size = bytes_in_mip_level(self.kind, self.pixel_kind, mip) as usize;
+            let level = self.bytes.get(offset..offset + size).ok_or_else(|| {
+                TextureError::Decode(format!("Mip level {mip} is out of bounds!"))
+            })?;
+            levels.push(level);
+            offset += size;
+        }
+        ktx2::write_ktx2(self.pixel_kind, self.kind, &levels)
+    }
+
     /// Tries to cast the specific mip level data of the internal data buffer to the given type. Type casting
     /// will succeed only if the the size of the type `T` is equal with the size of the pixel.
     ///
@@ -37,9 +37,18 @@ pub struct TextureLoader {
 
 impl ResourceLoader for TextureLoader {
     fn extensions(&self) -> &[&str] {
-        &[
-            "jpg", "jpeg", "tga", "gif", "bmp", "png", "tiff", "tif", "dds",
-        ]
+        #[cfg(feature = "basis-universal")]
+        {
+            &[
+                "jpg", "jpeg", "tga", "gif", "bmp", "png", "tiff", "tif", "dds", "ktx2", "basis",
+            ]
+        }
+        #[cfg(not(feature = "basis-universal"))]
+        {
+            &[
+                "jpg", "jpeg", "tga", "gif", "bmp", "png", "tiff", "tif", "dds", "ktx2",
+            ]
+        }
     }
 
     fn data_type_uuid(&self) -> Uuid {
@@ -0,0 +1,526 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Touch gesture recognition. See [`GestureRecognizer`] docs for more info.
+
+#![warn(missing_docs)]
+
+use crate::{
+    core::algebra::Vector2,
+    message::{OsEvent, TouchPhase},
+};
+use std::f32::consts::PI;
+
+/// Main direction of a swipe.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SwipeDirection {
+    /// Swipe to the left.
+    Left,
+    /// Swipe to the right.
+    Right,
+    /// Swipe up (to the top of the screen).
+    Up,
+    /// Swipe down (to the bottom of the screen).
+    Down,
+}
+
+impl SwipeDirection {
+    /// Returns the main direction of the given screen-space vector.
+    pub fn from_vector(vector: Vector2<f32>) -> Self {
+        if vector.x.abs() >= vector.y.abs() {
+            if vector.x < 0.0 {
+                Self::Left
+            } else {
+                Self::Right
+            }
+        } else if vector.y < 0.0 {
+            Self::Up
+        } else {
+            Self::Down
+        }
+    }
+}
+
+/// A recognized gesture. All positions are in screen coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Gesture {
+    /// A finger touched the screen and was lifted quickly without moving.
+    Tap {
+        /// Position of the tap.
+        position: Vector2<f32>,
+    },
+    /// Second tap near the previous one in a short period of time. It is emitted instead of the
+    /// second [`Gesture::Tap`].
+    DoubleTap {
+        /// Position of the tap.
+        position: Vector2<f32>,
+    },
+    /// A finger is held on the screen without moving for some time. Once emitted, no tap or swipe
+    /// will be emitted for the touch.
+    LongPress {
+        /// Position of the finger.
+        position: Vector2<f32>,
+    },
+    /// Two fingers are moving towards or away from each other.
+    Pinch {
+        /// Center point between the fingers.
+        center: Vector2<f32>,
+        /// Ratio between the current distance between the fingers and the distance at the
+        /// start of the gesture.
+        scale: f32,
+        /// Ratio between the current distance between the fingers and the previous one. Multiply
+        /// zoom factor by this value to zoom incrementally.
+        delta: f32,
+    },
+    /// Two fingers are rotating around their center point.
+    Rotate {
+        /// Center point between the fingers.
+        center: Vector2<f32>,
+        /// Total rotation angle (in radians) since the start of the gesture. Positive values mean
+        /// clockwise rotation on the screen.
+        angle: f32,
+        /// Rotation angle (in radians) since the previous event.
+        delta: f32,
+    },
+    /// A finger was quickly moved across the screen and lifted.
+    Swipe {
+        /// Position, where the swipe has started.
+        start: Vector2<f32>,
+        /// Position, where the swipe has ended.
+        end: Vector2<f32>,
+        /// Main direction of the swipe.
+        direction: SwipeDirection,
+        /// Average velocity of the swipe (in pixels per second).
+        velocity: Vector2<f32>,
+    },
+}
+
+#[derive(Clone, Debug)]
+struct TouchPoint {
+    id: u64,
+    start_position: Vector2<f32>,
+    start_time: f32,
+    position: Vector2<f32>,
+    moved: bool,
+}
+
+#[derive(Clone, Debug)]
+struct TwoFingerState {
+    start_distance: f32,
+    start_angle: f32,
+    distance: f32,
+    angle: f32,
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    let mut angle = angle % (2.0 * PI);
+    if angle > PI {
+        angle -= 2.0 * PI;
+    } else if angle < -PI {
+        angle += 2.0 * PI;
+    }
+    angle
+}
+
+/// Gesture recognizer turns raw touch events into high-level gestures (see [`Gesture`]). Every
+/// user interface has its own recognizer, that sends recognized gestures as
+/// [`crate::widget::WidgetMessage::Gesture`] messages to the widget, that was under the first
+/// finger of the gesture. Recognizer could also be used directly (for example in scripts):
+///
+/// ```rust
+/// # use fyrox_ui::{
+/// #     core::algebra::Vector2,
+/// #     gesture::{Gesture, GestureRecognizer},
+/// #     message::{OsEvent, TouchPhase},
+/// # };
+/// let mut recognizer = GestureRecognizer::default();
+///
+/// for phase in [TouchPhase::Started, TouchPhase::Ended] {
+///     recognizer.process_os_event(&OsEvent::Touch {
+///         phase,
+///         location: Vector2::new(10.0, 20.0),
+///         force: None,
+///         id: 0,
+///     });
+/// }
+/// // Should be called every frame.
+/// recognizer.update(1.0 / 60.0);
+///
+/// let gestures = recognizer.drain().collect::<Vec<_>>();
+/// assert_eq!(
+///     gestures,
+///     [Gesture::Tap {
+///         position: Vector2::new(10.0, 20.0)
+///     }]
+/// );
+/// ```
+///
+/// Gestures, that involve multiple fingers, suppress single-finger gestures until every finger is
+/// lifted. Time is measured by [`Self::update`] calls, so the recognizer must be updated every frame.
+#[derive(Clone, Debug)]
+pub struct GestureRecognizer {
+    /// Maximum duration of a tap (in seconds).
+    pub tap_max_duration: f32,
+    /// Maximum distance (in pixels), that a finger could move and still be considered as static.
+    pub tap_slop: f32,
+    /// Maximum time (in seconds) between two taps of a double tap.
+    pub double_tap_interval: f32,
+    /// Maximum distance (in pixels) between two taps of a double tap.
+    pub double_tap_distance: f32,
+    /// Time (in seconds), after which a static finger is considered as a long press.
+    pub long_press_duration: f32,
+    /// Minimal distance (in pixels) of a swipe.
+    pub swipe_min_distance: f32,
+    /// Minimal average velocity (in pixels per second) of a swipe.
+    pub swipe_min_velocity: f32,
+    time: f32,
+    touches: Vec<TouchPoint>,
+    last_tap: Option<(f32, Vector2<f32>)>,
+    long_press_fired: bool,
+    multi_touch: bool,
+    two_finger: Option<TwoFingerState>,
+    gestures: Vec<Gesture>,
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self {
+            tap_max_duration: 0.3,
+            tap_slop: 10.0,
+            double_tap_interval: 0.3,
+            double_tap_distance: 30.0,
+            long_press_duration: 0.5,
+            swipe_min_distance: 50.0,
+            swipe_min_velocity: 300.0,
+            time: 0.0,
+            touches: Default::default(),
+            last_tap: None,
+            long_press_fired: false,
+            multi_touch: false,
+            two_finger: None,
+            gestures: Default::default(),
+        }
+    }
+}
+
+impl GestureRecognizer {
+    /// Returns `true` if there's no fingers on the screen.
+    pub fn is_idle(&self) -> bool {
+        self.touches.is_empty()
+    }
+
+    /// Returns amount of fingers on the screen.
+    pub fn touch_count(&self) -> usize {
+        self.touches.len()
+    }
+
+    /// Processes the given OS event. Only touch events are processed, any other events are ignored.
+    pub fn process_os_event(&mut self, event: &OsEvent) {
+        if let OsEvent::Touch {
+            phase,
+            location,
+            id,
+            ..
+        } = *event
+        {
+            match phase {
+                TouchPhase::Started => self.on_touch_started(id, location),
+                TouchPhase::Moved => self.on_touch_moved(id, location),
+                TouchPhase::Ended => self.on_touch_ended(id, location, false),
+                TouchPhase::Cancelled => self.on_touch_ended(id, location, true),
+            }
+        }
+    }
+
+    /// Advances the internal time and checks time-dependent gestures (such as long press). Must
+    /// be called every frame.
+    pub fn update(&mut self, dt: f32) {
+        self.time += dt;
+
+        if let [touch] = self.touches.as_slice() {
+            if !touch.moved
+                && !self.long_press_fired
+                && !self.multi_touch
+                && self.time - touch.start_time >= self.long_press_duration
+            {
+                self.long_press_fired = true;
+                self.gestures.push(Gesture::LongPress {
+                    position: touch.position,
+                });
+            }
+        }
+    }
+
+    /// Returns every recognized gesture since the last call.
+    pub fn drain(&mut self) -> impl Iterator<Item = Gesture> + '_ {
+        self.gestures.drain(..)
+    }
+
+    /// Forgets every active touch. Could be used, for example, when the window loses focus.
+    pub fn reset(&mut self) {
+        self.touches.clear();
+        self.two_finger = None;
+        self.multi_touch = false;
+        self.long_press_fired = false;
+        self.last_tap = None;
+    }
+
+    fn two_finger_metrics(&self) -> Option<(Vector2<f32>, f32, f32)> {
+        let [a, b, ..] = self.touches.as_slice() else {
+            return None;
+        };
+        let offset = b.position - a.position;
+        Some((
+            (a.position + b.position).scale(0.5),
+            offset.norm(),
+            offset.y.atan2(offset.x),
+        ))
+    }
+
+    fn reset_two_finger_state(&mut self) {
+        self.two_finger = self
+            .two_finger_metrics()
+            .map(|(_, distance, angle)| TwoFingerState {
+                start_distance: distance,
+                start_angle: angle,
+                distance,
+                angle,
+            });
+    }
+
+    fn on_touch_started(&mut self, id: u64, position: Vector2<f32>) {
+        self.touches.retain(|touch| touch.id != id);
+        self.touches.push(TouchPoint {
+            id,
+            start_position: position,
+            start_time: self.time,
+            position,
+            moved: false,
+        });
+
+        if self.touches.len() > 1 {
+            self.multi_touch = true;
+            if self.two_finger.is_none() {
+                self.reset_two_finger_state();
+            }
+        }
+    }
+
+    fn on_touch_moved(&mut self, id: u64, position: Vector2<f32>) {
+        let tap_slop = self.tap_slop;
+        let Some(touch) = self.touches.iter_mut().find(|touch| touch.id == id) else {
+            return;
+        };
+        touch.position = position;
+        if (position - touch.start_position).norm() > tap_slop {
+            touch.moved = true;
+        }
+
+        let (Some((center, distance, angle)), Some(state)) =
+            (self.two_finger_metrics(), self.two_finger.as_mut())
+        else {
+            return;
+        };
+
+        if distance > f32::EPSILON && state.distance > f32::EPSILON && distance != state.distance {
+            self.gestures.push(Gesture::Pinch {
+                center,
+                scale: distance / state.start_distance.max(f32::EPSILON),
+                delta: distance / state.distance,
+            });
+        }
+        state.distance = distance;
+
+        let delta = wrap_angle(angle - state.angle);
+        if delta != 0.0 {
+            self.gestures.push(Gesture::Rotate {
+                center,
+                angle: wrap_angle(angle - state.start_angle),
+                delta,
+            });
+        }
+        state.angle = angle;
+    }
+
+    fn on_touch_ended(&mut self, id: u64, position: Vector2<f32>, cancelled: bool) {
+        let Some(index) = self.touches.iter().position(|touch| touch.id == id) else {
+            return;
+        };
+        let touch = self.touches.remove(index);
+
+        if index < 2 {
+            // One of the two fingers of a two-finger gesture was lifted, the next pair (if any)
+            // starts a new gesture.
+            self.reset_two_finger_state();
+        }
+
+        if !cancelled && !self.multi_touch && !self.long_press_fired {
+            let duration = self.time - touch.start_time;
+            let offset = position - touch.start_position;
+            if !touch.moved && offset.norm() <= self.tap_slop {
+                if duration <= self.tap_max_duration {
+                    self.on_tap(position);
+                }
+            } else if offset.norm() >= self.swipe_min_distance {
+                // Events could arrive in the same frame, avoid division by zero.
+                let velocity = offset.scale(1.0 / duration.max(1.0 / 120.0));
+                if velocity.norm() >= self.swipe_min_velocity {
+                    self.gestures.push(Gesture::Swipe {
+                        start: touch.start_position,
+                        end: position,
+                        direction: SwipeDirection::from_vector(offset),
+                        velocity,
+                    });
+                }
+            }
+        }
+
+        if self.touches.is_empty() {
+            self.multi_touch = false;
+            self.long_press_fired = false;
+        }
+    }
+
+    fn on_tap(&mut self, position: Vector2<f32>) {
+        match self.last_tap {
+            Some((time, last_position))
+                if self.time - time <= self.double_tap_interval
+                    && (position - last_position).norm() <= self.double_tap_distance =>
+            {
+                self.last_tap = None;
+                self.gestures.push(Gesture::DoubleTap { position });
+            }
+            _ => {
+                self.last_tap = Some((self.time, position));
+                self.gestures.push(Gesture::Tap { position });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn touch(recognizer: &mut GestureRecognizer, phase: TouchPhase, id: u64, x: f32, y: f32) {
+        recognizer.process_os_event(&OsEvent::Touch {
+            phase,
+            location: Vector2::new(x, y),
+            force: None,
+            id,
+        });
+    }
+
+    fn gestures(recognizer: &mut GestureRecognizer) -> Vec<Gesture> {
+        recognizer.drain().collect()
+    }
+
+    #[test]
+    fn test_tap_double_tap_and_long_press() {
+        let mut recognizer = GestureRecognizer::default();
+
+        touch(&mut recognizer, TouchPhase::Started, 0, 10.0, 10.0);
+        recognizer.update(0.1);
+        touch(&mut recognizer, TouchPhase::Ended, 0, 12.0, 10.0);
+        recognizer.update(0.1);
+        touch(&mut recognizer, TouchPhase::Started, 0, 15.0, 10.0);
+        touch(&mut recognizer, TouchPhase::Ended, 0, 15.0, 10.0);
+        assert_eq!(
+            gestures(&mut recognizer),
+            [
+                Gesture::Tap {
+                    position: Vector2::new(12.0, 10.0)
+                },
+                Gesture::DoubleTap {
+                    position: Vector2::new(15.0, 10.0)
+                }
+            ]
+        );
+
+        recognizer.update(1.0);
+        touch(&mut recognizer, TouchPhase::Started, 1, 50.0, 50.0);
+        for _ in 0..10 {
+            recognizer.update(0.1);
+        }
+        touch(&mut recognizer, TouchPhase::Ended, 1, 50.0, 50.0);
+        assert_eq!(
+            gestures(&mut recognizer),
+            [Gesture::LongPress {
+                position: Vector2::new(50.0, 50.0)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_swipe() {
+        let mut recognizer = GestureRecognizer::default();
+
+        touch(&mut recognizer, TouchPhase::Started, 0, 100.0, 100.0);
+        recognizer.update(0.05);
+        touch(&mut recognizer, TouchPhase::Moved, 0, 150.0, 100.0);
+        recognizer.update(0.05);
+        touch(&mut recognizer, TouchPhase::Ended, 0, 200.0, 100.0);
+
+        match gestures(&mut recognizer).as_slice() {
+            [Gesture::Swipe {
+                direction,
+                velocity,
+                ..
+            }] => {
+                assert_eq!(*direction, SwipeDirection::Right);
+                assert!((velocity.x - 1000.0).abs() < 1.0);
+            }
+            gestures => panic!("Unexpected gestures {gestures:?}"),
+        }
+    }
+
+    #[test]
+    fn test_pinch_and_rotate() {
+        let mut recognizer = GestureRecognizer::default();
+
+        touch(&mut recognizer, TouchPhase::Started, 0, 0.0, 0.0);
+        touch(&mut recognizer, TouchPhase::Started, 1, 10.0, 0.0);
+        touch(&mut recognizer, TouchPhase::Moved, 1, 20.0, 0.0);
+
+        assert_eq!(
+            gestures(&mut recognizer),
+            [Gesture::Pinch {
+                center: Vector2::new(10.0, 0.0),
+                scale: 2.0,
+                delta: 2.0
+            }]
+        );
+
+        touch(&mut recognizer, TouchPhase::Moved, 1, 0.0, 20.0);
+        match gestures(&mut recognizer).as_slice() {
+            [Gesture::Rotate { angle, delta, .. }] => {
+                assert!((angle - PI / 2.0).abs() < 0.001);
+                assert!((delta - PI / 2.0).abs() < 0.001);
+            }
+            gestures => panic!("Unexpected gestures {gestures:?}"),
+        }
+
+        // Lifting fingers after a multi-touch gesture must not produce taps.
+        touch(&mut recognizer, TouchPhase::Ended, 0, 0.0, 0.0);
+        touch(&mut recognizer, TouchPhase::Ended, 1, 0.0, 20.0);
+        assert!(gestures(&mut recognizer).is_empty());
+        assert!(recognizer.is_idle());
+    }
+}
@@ -136,6 +136,9 @@ where
         max_value: array_property_info.max_value,
         step: array_property_info.step,
         precision: array_property_info.precision,
+        category: "",
+        slider: array_property_info.slider,
+        visible: true,
         description: array_property_info.description,
         tag: array_property_info.tag,
         type_name: array_property_info.type_name,
@@ -256,6 +256,9 @@ where
         max_value: collection_property_info.max_value,
         step: collection_property_info.step,
         precision: collection_property_info.precision,
+        category: "",
+        slider: collection_property_info.slider,
+        visible: true,
         description: collection_property_info.description,
         tag: collection_property_info.tag,
         type_name: collection_property_info.type_name,
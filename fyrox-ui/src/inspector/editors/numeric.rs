@@ -29,11 +29,20 @@ use crate::{
     },
     message::{MessageDirection, UiMessage},
     numeric::{NumericType, NumericUpDownBuilder, NumericUpDownMessage},
+    scroll_bar::{ScrollBar, ScrollBarBuilder, ScrollBarMessage},
     widget::WidgetBuilder,
     Thickness,
 };
+use fyrox_graph::BaseSceneGraph;
 use std::{any::TypeId, marker::PhantomData};
 
+/// Converts a value of a slider to the value of the property. Integer values are rounded to the
+/// nearest integer instead of truncation.
+fn slider_value<T: NumericType>(value: f32) -> Option<T> {
+    let is_integer = NumCast::from(0.5f32).map_or(true, |half: T| half == T::default());
+    NumCast::from(if is_integer { value.round() } else { value })
+}
+
 #[derive(Debug)]
 pub struct NumericPropertyEditorDefinition<T>
 where
@@ -66,6 +75,27 @@ where
         ctx: PropertyEditorBuildContext,
     ) -> Result<PropertyEditorInstance, InspectorError> {
         let value = ctx.property_info.cast_value::<T>()?;
+
+        if let (true, Some(min), Some(max)) = (
+            ctx.property_info.slider,
+            ctx.property_info.min_value,
+            ctx.property_info.max_value,
+        ) {
+            let step = ctx.property_info.step.unwrap_or((max - min) / 100.0);
+            return Ok(PropertyEditorInstance::Simple {
+                editor: ScrollBarBuilder::new(
+                    WidgetBuilder::new().with_margin(Thickness::top_bottom(1.0)),
+                )
+                .with_min(min as f32)
+                .with_max(max as f32)
+                .with_step(step as f32)
+                .with_value(NumCast::from(*value).unwrap_or_default())
+                .show_value(true)
+                .with_value_precision(ctx.property_info.precision.unwrap_or(3))
+                .build(ctx.build_context),
+            });
+        }
+
         Ok(PropertyEditorInstance::Simple {
             editor: NumericUpDownBuilder::new(
                 WidgetBuilder::new().with_margin(Thickness::top_bottom(1.0)),
@@ -99,6 +129,17 @@ where
         ctx: PropertyEditorMessageContext,
     ) -> Result<Option<UiMessage>, InspectorError> {
         let value = ctx.property_info.cast_value::<T>()?;
+
+        if let Some(slider) = ctx.ui.try_get(ctx.instance) {
+            if slider.cast::<ScrollBar>().is_some() {
+                return Ok(Some(ScrollBarMessage::value(
+                    ctx.instance,
+                    MessageDirection::ToWidget,
+                    NumCast::from(*value).unwrap_or_default(),
+                )));
+            }
+        }
+
         Ok(Some(NumericUpDownMessage::value(
             ctx.instance,
             MessageDirection::ToWidget,
@@ -117,6 +158,14 @@ where
                     value: FieldKind::object(*value),
                 });
             }
+
+            if let Some(ScrollBarMessage::Value(value)) = ctx.message.data::<ScrollBarMessage>() {
+                return Some(PropertyChanged {
+                    name: ctx.name.to_string(),
+                    owner_type_id: ctx.owner_type_id,
+                    value: FieldKind::object(slider_value::<T>(*value)?),
+                });
+            }
         }
 
         None
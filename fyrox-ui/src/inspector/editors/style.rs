@@ -224,6 +224,9 @@ where
         max_value: property_info.max_value,
         step: property_info.step,
         precision: property_info.precision,
+        category: property_info.category,
+        slider: property_info.slider,
+        visible: property_info.visible,
         description: property_info.description,
         tag: property_info.tag,
         type_name: property_info.type_name,
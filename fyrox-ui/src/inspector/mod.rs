@@ -750,6 +750,21 @@ fn create_header(ctx: &mut BuildContext, text: &str, layer_index: usize) -> Hand
         .build(ctx)
 }
 
+fn make_category_container(
+    layer_index: usize,
+    name: &str,
+    content: Handle<UiNode>,
+    width: f32,
+    ctx: &mut BuildContext,
+) -> Handle<UiNode> {
+    ExpanderBuilder::new(WidgetBuilder::new())
+        .with_checkbox(make_expander_check_box(layer_index, name, "", ctx))
+        .with_expander_column(Column::strict(width))
+        .with_expanded(true)
+        .with_content(content)
+        .build(ctx)
+}
+
 fn make_tooltip(ctx: &mut BuildContext, text: &str) -> Option<RcUiNodeHandle> {
     if text.is_empty() {
         None
@@ -856,24 +871,33 @@ impl InspectorContext {
         });
 
         let mut editors = Vec::new();
+        let mut categories = Vec::<(String, Vec<Handle<UiNode>>)>::new();
+        let mut category_positions = Vec::new();
         object.fields_info(&mut |fields_info| {
             for (i, (field_text, info)) in fields_text.iter().zip(fields_info.iter()).enumerate() {
                 if !filter.pass(info.reflect_value) {
                     continue;
                 }
 
-                let description = if info.description.is_empty() {
+                // Doc comment is used as a tooltip, if there's no explicit description.
+                let tooltip = if info.description.is_empty() {
+                    info.doc.trim()
+                } else {
+                    info.description
+                };
+                let description = if tooltip.is_empty() {
                     info.display_name.to_string()
                 } else {
-                    format!("{}\n\n{}", info.display_name, info.description)
+                    format!("{}\n\n{}", info.display_name, tooltip)
                 };
 
-                if let Some(definition) = definition_container
+                let container = if let Some(definition) = definition_container
                     .definitions()
                     .get(&info.value.type_id())
                 {
-                    let editor = match definition.property_editor.create_instance(
-                        PropertyEditorBuildContext {
+                    match definition
+                        .property_editor
+                        .create_instance(PropertyEditorBuildContext {
                             build_context: ctx,
                             property_info: info,
                             environment: environment.clone(),
@@ -883,8 +907,7 @@ impl InspectorContext {
                             generate_property_string_values,
                             filter: filter.clone(),
                             name_column_width,
-                        },
-                    ) {
+                        }) {
                         Ok(instance) => {
                             let (container, editor) = match instance {
                                 PropertyEditorInstance::Simple { editor } => (
@@ -934,11 +957,9 @@ impl InspectorContext {
                             name_column_width,
                             ctx,
                         ),
-                    };
-
-                    editors.push(editor);
+                    }
                 } else {
-                    editors.push(make_simple_property_container(
+                    make_simple_property_container(
                         create_header(ctx, info.display_name, layer_index),
                         TextBuilder::new(WidgetBuilder::new().on_row(i).on_column(1))
                             .with_wrap(WrapMode::Word)
@@ -951,11 +972,41 @@ impl InspectorContext {
                         &description,
                         name_column_width,
                         ctx,
-                    ));
+                    )
+                };
+
+                if !info.visible {
+                    ctx[container].set_visibility(false);
+                }
+
+                if info.category.is_empty() {
+                    editors.push(container);
+                } else if let Some((_, category_editors)) = categories
+                    .iter_mut()
+                    .find(|(name, _)| name == info.category)
+                {
+                    category_editors.push(container);
+                } else {
+                    // Remember the position of the first property of the category, the whole
+                    // category will be placed there.
+                    category_positions.push(editors.len());
+                    editors.push(Handle::NONE);
+                    categories.push((info.category.to_string(), vec![container]));
                 }
             }
         });
 
+        for (position, (name, category_editors)) in category_positions.into_iter().zip(categories) {
+            editors[position] = make_category_container(
+                layer_index,
+                &name,
+                StackPanelBuilder::new(WidgetBuilder::new().with_children(category_editors))
+                    .build(ctx),
+                name_column_width,
+                ctx,
+            );
+        }
+
         let copy_value_as_string;
         let menu = ContextMenuBuilder::new(
             PopupBuilder::new(WidgetBuilder::new().with_visibility(false)).with_content(
@@ -1035,6 +1086,16 @@ impl InspectorContext {
                     .get(&info.value.type_id())
                 {
                     if let Some(property_editor) = self.find_property_editor(info.name) {
+                        if let Some(container) = ui.try_get(property_editor.property_container) {
+                            if container.visibility() != info.visible {
+                                ui.send_message(WidgetMessage::visibility(
+                                    property_editor.property_container,
+                                    MessageDirection::ToWidget,
+                                    info.visible,
+                                ));
+                            }
+                        }
+
                         let ctx = PropertyEditorMessageContext {
                             sync_flag: self.sync_flag,
                             instance: property_editor.property_editor,
@@ -227,6 +227,7 @@ pub mod expander;
 pub mod file_browser;
 pub mod font;
 pub mod formatted_text;
+pub mod gesture;
 pub mod grid;
 pub mod image;
 pub mod inspector;
@@ -288,6 +289,7 @@ use crate::{
     draw::{CommandTexture, Draw, DrawingContext},
     font::FontResource,
     font::BUILT_IN_FONT,
+    gesture::GestureRecognizer,
     message::{
         ButtonState, CursorIcon, KeyboardModifiers, MessageDirection, MouseButton, OsEvent,
         UiMessage,
@@ -695,6 +697,9 @@ pub struct UserInterface {
     double_click_entries: FxHashMap<MouseButton, DoubleClickEntry>,
     pub double_click_time_slice: f32,
     pub tooltip_appear_delay: f32,
+    #[reflect(hidden)]
+    gesture_recognizer: GestureRecognizer,
+    gesture_target: Handle<UiNode>,
 }
 
 impl Visit for UserInterface {
@@ -782,6 +787,8 @@ impl Clone for UserInterface {
             double_click_entries: self.double_click_entries.clone(),
             double_click_time_slice: self.double_click_time_slice,
             tooltip_appear_delay: self.tooltip_appear_delay,
+            gesture_recognizer: self.gesture_recognizer.clone(),
+            gesture_target: self.gesture_target,
         }
     }
 }
@@ -1082,6 +1089,8 @@ impl UserInterface {
             double_click_entries: Default::default(),
             double_click_time_slice: 0.5, // 500 ms is standard in most operating systems.
             tooltip_appear_delay: 0.55,
+            gesture_recognizer: Default::default(),
+            gesture_target: Handle::NONE,
         };
         let root_node = UiNode::new(Canvas {
             widget: WidgetBuilder::new().build(&ui.build_ctx()),
@@ -1099,6 +1108,31 @@ impl UserInterface {
         self.tooltip_appear_delay
     }
 
+    /// Returns a reference to the gesture recognizer of the user interface.
+    pub fn gesture_recognizer(&self) -> &GestureRecognizer {
+        &self.gesture_recognizer
+    }
+
+    /// Returns a reference to the gesture recognizer of the user interface. It could be used to
+    /// tweak thresholds of the gestures.
+    pub fn gesture_recognizer_mut(&mut self) -> &mut GestureRecognizer {
+        &mut self.gesture_recognizer
+    }
+
+    fn send_gestures(&mut self) {
+        let target = self.gesture_target;
+        let gestures = self.gesture_recognizer.drain().collect::<Vec<_>>();
+        if self.nodes.is_valid_handle(target) {
+            for gesture in gestures {
+                self.send_message(WidgetMessage::gesture(
+                    target,
+                    MessageDirection::FromWidget,
+                    gesture,
+                ));
+            }
+        }
+    }
+
     pub fn active_tooltip(&self) -> Option<&TooltipEntry> {
         self.active_tooltip.as_ref()
     }
@@ -1296,6 +1330,9 @@ impl UserInterface {
             entry.timer -= dt;
         }
 
+        self.gesture_recognizer.update(dt);
+        self.send_gestures();
+
         self.update_layout(screen_size);
 
         if let Some(node_overrides) = switches.node_overrides.as_ref() {
@@ -2719,6 +2756,15 @@ impl UserInterface {
             },
         }
 
+        if let OsEvent::Touch { phase, .. } = event {
+            // Gestures are sent to the widget under the first finger.
+            if *phase == TouchPhase::Started && self.gesture_recognizer.is_idle() {
+                self.gesture_target = self.picked_node;
+            }
+            self.gesture_recognizer.process_os_event(event);
+            self.send_gestures();
+        }
+
         self.prev_picked_node = self.picked_node;
 
         let on_os_event_subs = std::mem::take(&mut self.methods_registry.handle_os_event);
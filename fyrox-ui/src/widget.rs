@@ -38,6 +38,7 @@ use crate::{
     },
     core::{parking_lot::Mutex, variable::InheritableVariable},
     define_constructor,
+    gesture::Gesture,
     message::{CursorIcon, Force, KeyCode, MessageDirection, UiMessage},
     style::resource::StyleResourceExt,
     style::Style,
@@ -435,6 +436,13 @@ pub enum WidgetMessage {
         id: u64,
     },
 
+    /// Initiated when a touch gesture was recognized. The message is sent to the widget, that was
+    /// under the first finger of the gesture. See [`crate::gesture::GestureRecognizer`] docs for
+    /// more info.
+    ///
+    /// Direction: **From UI**.
+    Gesture(Gesture),
+
     /// Sorts children widgets of a widget.
     ///
     /// Direction: **To UI**.
@@ -734,6 +742,12 @@ impl WidgetMessage {
         WidgetMessage:DoubleTap => fn double_tap(pos: Vector2<f32>, force: Option<Force>, id: u64), layout: false
     );
 
+    define_constructor!(
+        /// Creates [`WidgetMessage::Gesture`] message. This method is for internal use only, and should not
+        /// be used anywhere else.
+        WidgetMessage:Gesture => fn gesture(Gesture), layout: false
+    );
+
     define_constructor!(
         /// Creates [`WidgetMessage::SortChildren`] message.
         WidgetMessage:SortChildren => fn sort_children(SortingPredicate), layout: false
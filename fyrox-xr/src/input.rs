@@ -0,0 +1,210 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! State of tracked controllers. See [`XrInput`] docs for more info.
+
+use crate::pose::Pose;
+use fyrox::core::algebra::Vector2;
+
+/// A hand, that holds a controller.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Hand {
+    /// Left hand.
+    Left,
+    /// Right hand.
+    Right,
+}
+
+impl Hand {
+    /// Both hands in the order, that matches [`Self::index`].
+    pub const ALL: [Hand; 2] = [Hand::Left, Hand::Right];
+
+    /// Returns index of the hand: 0 for the left one, 1 for the right one.
+    pub fn index(self) -> usize {
+        match self {
+            Hand::Left => 0,
+            Hand::Right => 1,
+        }
+    }
+}
+
+/// A digital button of a controller. Analog inputs (trigger and grip) are considered pressed, if
+/// their value exceeds [`ANALOG_PRESS_THRESHOLD`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ControllerButton {
+    /// Index finger trigger.
+    Trigger,
+    /// Grip (squeeze) button.
+    Grip,
+    /// Primary face button (A or X on most controllers).
+    Primary,
+    /// Secondary face button (B or Y on most controllers).
+    Secondary,
+    /// Menu button.
+    Menu,
+    /// Thumbstick click.
+    Thumbstick,
+}
+
+impl ControllerButton {
+    fn mask(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Value of an analog input, after which it is considered as pressed.
+pub const ANALOG_PRESS_THRESHOLD: f32 = 0.5;
+
+/// State of a controller in the current frame.
+#[derive(Clone, Debug, Default)]
+pub struct ControllerState {
+    /// `true` if the controller is connected and tracked.
+    pub is_active: bool,
+    /// Pose of the hand, that holds the controller. Use it to place hand models.
+    pub grip_pose: Option<Pose>,
+    /// Pose of the pointing ray of the controller. Use it for pointing and aiming.
+    pub aim_pose: Option<Pose>,
+    /// Value of the trigger in `[0; 1]` range.
+    pub trigger: f32,
+    /// Value of the grip in `[0; 1]` range.
+    pub grip: f32,
+    /// Position of the thumbstick, each axis is in `[-1; 1]` range. +X is right, +Y is up.
+    pub thumbstick: Vector2<f32>,
+    buttons: u8,
+    prev_buttons: u8,
+}
+
+impl ControllerState {
+    /// Returns `true` if the button is pressed.
+    pub fn is_pressed(&self, button: ControllerButton) -> bool {
+        self.buttons & button.mask() != 0
+    }
+
+    /// Returns `true` if the button was pressed in the current frame.
+    pub fn is_just_pressed(&self, button: ControllerButton) -> bool {
+        self.is_pressed(button) && self.prev_buttons & button.mask() == 0
+    }
+
+    /// Returns `true` if the button was released in the current frame.
+    pub fn is_just_released(&self, button: ControllerButton) -> bool {
+        !self.is_pressed(button) && self.prev_buttons & button.mask() != 0
+    }
+
+    /// Sets the new state of the button. Should be called by input backends only.
+    pub fn set_pressed(&mut self, button: ControllerButton, pressed: bool) {
+        if pressed {
+            self.buttons |= button.mask();
+        } else {
+            self.buttons &= !button.mask();
+        }
+    }
+
+    /// Remembers the current state of the buttons as the previous one. Should be called by input
+    /// backends before updating the state.
+    pub fn begin_frame(&mut self) {
+        self.prev_buttons = self.buttons;
+    }
+}
+
+/// Haptic feedback request.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HapticPulse {
+    /// A hand, which controller should vibrate.
+    pub hand: Hand,
+    /// Amplitude of the vibration in `[0; 1]` range.
+    pub amplitude: f32,
+    /// Duration of the vibration in seconds.
+    pub duration: f32,
+    /// Frequency of the vibration in Hz. `None` means the optimal frequency of the device.
+    pub frequency: Option<f32>,
+}
+
+/// Tracked input of a VR system: the pose of the head and the state of both controllers. Game code
+/// should read the state and request haptic feedback using this structure, the session updates it
+/// every frame.
+#[derive(Clone, Debug, Default)]
+pub struct XrInput {
+    /// Pose of the head in the tracking space.
+    pub head: Option<Pose>,
+    controllers: [ControllerState; 2],
+    haptics: Vec<HapticPulse>,
+}
+
+impl XrInput {
+    /// Returns the state of the controller in the given hand.
+    pub fn controller(&self, hand: Hand) -> &ControllerState {
+        &self.controllers[hand.index()]
+    }
+
+    /// Returns the state of the controller in the given hand.
+    pub fn controller_mut(&mut self, hand: Hand) -> &mut ControllerState {
+        &mut self.controllers[hand.index()]
+    }
+
+    /// Requests a vibration of the controller in the given hand.
+    pub fn vibrate(&mut self, hand: Hand, amplitude: f32, duration: f32) {
+        self.haptics.push(HapticPulse {
+            hand,
+            amplitude,
+            duration,
+            frequency: None,
+        });
+    }
+
+    /// Requests a haptic feedback with the given parameters.
+    pub fn haptic_pulse(&mut self, pulse: HapticPulse) {
+        self.haptics.push(pulse);
+    }
+
+    /// Returns every requested haptic feedback and clears the requests.
+    pub fn drain_haptics(&mut self) -> impl Iterator<Item = HapticPulse> + '_ {
+        self.haptics.drain(..)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_button_transitions() {
+        let mut input = XrInput::default();
+        let controller = input.controller_mut(Hand::Right);
+
+        controller.begin_frame();
+        controller.set_pressed(ControllerButton::Trigger, true);
+        assert!(controller.is_just_pressed(ControllerButton::Trigger));
+
+        controller.begin_frame();
+        assert!(controller.is_pressed(ControllerButton::Trigger));
+        assert!(!controller.is_just_pressed(ControllerButton::Trigger));
+
+        controller.begin_frame();
+        controller.set_pressed(ControllerButton::Trigger, false);
+        assert!(controller.is_just_released(ControllerButton::Trigger));
+        assert!(!input
+            .controller(Hand::Left)
+            .is_pressed(ControllerButton::Trigger));
+
+        input.vibrate(Hand::Left, 0.5, 0.1);
+        assert_eq!(input.drain_haptics().count(), 1);
+        assert_eq!(input.drain_haptics().count(), 0);
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Virtual reality support for the Fyrox engine, based on [OpenXR](https://www.khronos.org/openxr/).
+//!
+//! The crate consists of several parts:
+//!
+//! - [`session`] - OpenXR session, that tracks the head-mounted display (HMD) and controllers and
+//! submits rendered frames to the HMD.
+//! - [`rig`] - a set of scene nodes (per-eye cameras, head and hands), that follow tracked poses.
+//! - [`input`] - state of controllers (poses, buttons, axes) and haptics requests.
+//! - [`plugin`] - a plugin, that ties everything together and runs the frame loop.
+//!
+//! Stereo rendering is done by two cameras, that render the scene side-by-side into a single render
+//! target. Each camera uses [`fyrox::scene::camera::OffCenterProjection`], that matches the optics
+//! of the HMD. Once the frame is rendered, the render target is copied to the swapchain of the HMD.
+//! Only OpenGL graphics server is supported.
+
+#![warn(missing_docs)]
+
+pub mod input;
+pub mod plugin;
+pub mod pose;
+pub mod rig;
+pub mod session;
+
+pub use openxr;
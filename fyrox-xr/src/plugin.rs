@@ -0,0 +1,173 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Plugin, that runs OpenXR frame loop. See [`XrPlugin`] docs for more info.
+
+use crate::{input::XrInput, rig::XrRig, session::XrSession};
+use fyrox::{
+    core::{log::Log, pool::Handle, reflect::prelude::*, visitor::prelude::*},
+    engine::GraphicsContext,
+    plugin::{Plugin, PluginContext},
+    resource::texture::TextureResource,
+    scene::Scene,
+};
+
+/// A plugin, that runs OpenXR frame loop: it tracks the HMD and controllers, moves the nodes of an
+/// [`XrRig`] accordingly and submits rendered frames to the HMD. Every other plugin could read the
+/// state of controllers from [`Self::input`]. Typical usage looks like this:
+///
+/// ```rust,no_run
+/// # use fyrox::{core::pool::Handle, plugin::PluginContext, scene::Scene};
+/// # use fyrox_xr::{plugin::XrPlugin, rig::XrRig, session::XrSession};
+/// fn setup(xr: &mut XrPlugin, session: XrSession, scene: Handle<Scene>, ctx: &mut PluginContext) {
+///     xr.set_session(session);
+///     let rig = XrRig::new(&mut ctx.scenes[scene].graph, 0.025, 512.0);
+///     xr.attach(scene, rig, ctx);
+/// }
+/// ```
+#[derive(Default, Debug, Visit, Reflect)]
+pub struct XrPlugin {
+    #[visit(skip)]
+    #[reflect(hidden)]
+    session: Option<XrSession>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    rig: Option<(Handle<Scene>, XrRig)>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    render_target: Option<TextureResource>,
+    /// State of the HMD and controllers. It is updated at the beginning of every frame.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub input: XrInput,
+}
+
+impl XrPlugin {
+    /// Sets a new OpenXR session. See [`XrSession::new`] for more info.
+    pub fn set_session(&mut self, session: XrSession) {
+        self.session = Some(session);
+    }
+
+    /// Returns a reference to the current OpenXR session (if any).
+    pub fn session(&self) -> Option<&XrSession> {
+        self.session.as_ref()
+    }
+
+    /// Attaches the given rig to the scene. The scene will be rendered into a stereo render target
+    /// (replacing any existing one), that will be submitted to the HMD. Must be called after
+    /// [`Self::set_session`], because the size of the render target depends on the HMD.
+    pub fn attach(&mut self, scene: Handle<Scene>, rig: XrRig, context: &mut PluginContext) {
+        let Some(session) = self.session.as_ref() else {
+            Log::err("Unable to attach XR rig: there's no OpenXR session!");
+            return;
+        };
+        let render_target = XrRig::create_render_target(session.eye_size());
+        if let Some(scene) = context.scenes.try_get_mut(scene) {
+            scene.rendering_options.render_target = Some(render_target.clone());
+        }
+        self.render_target = Some(render_target);
+        self.rig = Some((scene, rig));
+    }
+
+    /// Detaches current rig (if any) and returns it. The nodes of the rig are not removed from
+    /// the scene.
+    pub fn detach(&mut self, context: &mut PluginContext) -> Option<(Handle<Scene>, XrRig)> {
+        let (scene, rig) = self.rig.take()?;
+        if let Some(scene) = context.scenes.try_get_mut(scene) {
+            scene.rendering_options.render_target = None;
+        }
+        self.render_target = None;
+        Some((scene, rig))
+    }
+
+    /// Returns a reference to the current rig and the scene it is attached to.
+    pub fn rig(&self) -> Option<&(Handle<Scene>, XrRig)> {
+        self.rig.as_ref()
+    }
+}
+
+impl Plugin for XrPlugin {
+    fn update(&mut self, context: &mut PluginContext) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+
+        if let Err(err) = session.poll_events() {
+            Log::err(format!("Unable to poll OpenXR events. Reason: {err}"));
+        }
+
+        if session.is_exit_requested() {
+            Log::info("OpenXR session was closed by the runtime.");
+            self.session = None;
+            return;
+        }
+
+        if let Some((scene, rig)) = self.rig.as_ref() {
+            if let Some(scene) = context.scenes.try_get_mut(*scene) {
+                rig.update_input(&mut scene.graph, &self.input);
+            }
+        }
+    }
+
+    fn before_rendering(&mut self, context: PluginContext) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+
+        // Every begun frame must be ended, and that requires a render target and the renderer.
+        if self.render_target.is_none()
+            || !matches!(context.graphics_context, GraphicsContext::Initialized(_))
+        {
+            return;
+        }
+
+        match session.begin_frame(&mut self.input) {
+            Ok(Some(views)) => {
+                if let Some((scene, rig)) = self.rig.as_ref() {
+                    if let Some(scene) = context.scenes.try_get_mut(*scene) {
+                        // Head and hands are moved here as well to reduce the latency.
+                        rig.update_input(&mut scene.graph, &self.input);
+                        rig.update_views(&mut scene.graph, &views);
+                    }
+                }
+            }
+            Ok(None) => (),
+            Err(err) => Log::err(format!("Unable to begin OpenXR frame. Reason: {err}")),
+        }
+    }
+
+    fn after_rendering(&mut self, context: PluginContext) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+
+        if let (Some(render_target), GraphicsContext::Initialized(graphics_context)) =
+            (self.render_target.as_ref(), context.graphics_context)
+        {
+            if let Err(err) = session.end_frame(&mut graphics_context.renderer, render_target) {
+                Log::err(format!("Unable to submit OpenXR frame. Reason: {err}"));
+            }
+        }
+
+        if let Err(err) = session.apply_haptics(&mut self.input) {
+            Log::err(format!("Unable to apply haptic feedback. Reason: {err}"));
+        }
+    }
+}
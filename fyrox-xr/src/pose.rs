@@ -0,0 +1,112 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Tracked poses and views. See [`Pose`] docs for more info.
+
+use fyrox::{
+    core::algebra::{Quaternion, UnitQuaternion, Vector3},
+    scene::camera::OffCenterProjection,
+};
+
+/// Position and orientation of a tracked object in the tracking space. Poses are always stored in
+/// engine's coordinate system (+Z is forward, +Y is up, -X is right).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Pose {
+    /// Position of the object (in meters).
+    pub position: Vector3<f32>,
+    /// Orientation of the object.
+    pub orientation: UnitQuaternion<f32>,
+}
+
+impl Default for Pose {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            orientation: UnitQuaternion::identity(),
+        }
+    }
+}
+
+impl Pose {
+    /// Converts a pose from OpenXR coordinate system (-Z is forward, +Y is up, +X is right) to the
+    /// engine's one. Both systems are right-handed and differ only by 180 degrees rotation around
+    /// Y axis. `orientation` is a quaternion in `[x, y, z, w]` form.
+    pub fn from_openxr(position: [f32; 3], orientation: [f32; 4]) -> Self {
+        let [x, y, z, w] = orientation;
+        Self {
+            position: Vector3::new(-position[0], position[1], -position[2]),
+            orientation: UnitQuaternion::new_normalize(Quaternion::new(w, -x, y, -z)),
+        }
+    }
+}
+
+/// Field of view of an eye. Every angle is in radians and measured from the view direction, left
+/// and down angles are usually negative.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fov {
+    /// Angle of the left side of the field of view.
+    pub angle_left: f32,
+    /// Angle of the right side of the field of view.
+    pub angle_right: f32,
+    /// Angle of the top side of the field of view.
+    pub angle_up: f32,
+    /// Angle of the bottom side of the field of view.
+    pub angle_down: f32,
+}
+
+impl Fov {
+    /// Creates off-center projection for the field of view with the given clipping planes.
+    pub fn projection(&self, z_near: f32, z_far: f32) -> OffCenterProjection {
+        OffCenterProjection {
+            angle_left: self.angle_left,
+            angle_right: self.angle_right,
+            angle_up: self.angle_up,
+            angle_down: self.angle_down,
+            z_near,
+            z_far,
+        }
+    }
+}
+
+/// A view of an eye.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct View {
+    /// Pose of the eye in the tracking space.
+    pub pose: Pose,
+    /// Field of view of the eye.
+    pub fov: Fov,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_openxr() {
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        // Turn left by 90 degrees and step forward in OpenXR coordinates.
+        let pose = Pose::from_openxr([0.0, 1.7, -1.0], [0.0, half, 0.0, half]);
+
+        assert_eq!(pose.position, Vector3::new(0.0, 1.7, 1.0));
+        // Engine's forward vector must point to the left (+X).
+        let look = pose.orientation * Vector3::z();
+        assert!((look - Vector3::x()).norm() < 1.0e-5);
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! A set of scene nodes, that follow tracked poses. See [`XrRig`] docs for more info.
+
+use crate::{
+    input::{Hand, XrInput},
+    pose::{Pose, View},
+};
+use fyrox::{
+    core::{algebra::Vector2, math::Rect, pool::Handle},
+    graph::BaseSceneGraph,
+    resource::texture::{TextureResource, TextureResourceExtension},
+    scene::{
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder, OffCenterProjection, Projection},
+        graph::Graph,
+        node::Node,
+        pivot::PivotBuilder,
+    },
+};
+
+/// VR rig is a set of scene nodes, that represents the player in the scene: the origin of the
+/// tracking space, the head, two cameras (one per eye) and two hands. Every node except the origin
+/// is a child of the origin and its local transform is defined by the tracked pose, so the player
+/// could be moved in the scene (for example, teleported) by moving the origin.
+///
+/// Eye cameras render the scene side-by-side: the left eye uses the left half of the render target
+/// and the right eye uses the right half. The render target must be set to the scene, see
+/// [`Self::create_render_target`].
+#[derive(Clone, Debug)]
+pub struct XrRig {
+    /// Origin of the tracking space (usually the center of the play area on the floor).
+    pub origin: Handle<Node>,
+    /// The node, that follows the head.
+    pub head: Handle<Node>,
+    /// Cameras of the left and right eyes.
+    pub eyes: [Handle<Node>; 2],
+    /// The nodes, that follow the grip poses of the left and right hands. Attach hand or
+    /// controller models to these nodes.
+    pub hands: [Handle<Node>; 2],
+}
+
+impl XrRig {
+    /// Creates a new rig in the given graph. Clipping planes are used by the eye cameras.
+    pub fn new(graph: &mut Graph, z_near: f32, z_far: f32) -> Self {
+        let head = PivotBuilder::new(BaseBuilder::new().with_name("XrHead")).build(graph);
+
+        let mut make_eye = |name: &str, x: f32| {
+            CameraBuilder::new(BaseBuilder::new().with_name(name))
+                .with_projection(Projection::OffCenter(OffCenterProjection {
+                    z_near,
+                    z_far,
+                    ..Default::default()
+                }))
+                .with_viewport(Rect::new(x, 0.0, 0.5, 1.0))
+                .build(graph)
+        };
+        let eyes = [make_eye("XrLeftEye", 0.0), make_eye("XrRightEye", 0.5)];
+
+        let hands = [
+            PivotBuilder::new(BaseBuilder::new().with_name("XrLeftHand")).build(graph),
+            PivotBuilder::new(BaseBuilder::new().with_name("XrRightHand")).build(graph),
+        ];
+
+        let origin = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("XrOrigin")
+                .with_children(&[head, eyes[0], eyes[1], hands[0], hands[1]]),
+        )
+        .build(graph);
+
+        Self {
+            origin,
+            head,
+            eyes,
+            hands,
+        }
+    }
+
+    /// Creates a render target for side-by-side stereo rendering with the given size of a single eye
+    /// (see [`crate::session::XrSession::eye_size`]). Assign it to
+    /// [`fyrox::scene::SceneRenderingOptions::render_target`] of the scene with the rig.
+    pub fn create_render_target(eye_size: Vector2<u32>) -> TextureResource {
+        TextureResource::new_render_target(eye_size.x * 2, eye_size.y)
+    }
+
+    fn set_pose(graph: &mut Graph, node: Handle<Node>, pose: Option<Pose>) {
+        if let Some(node) = graph.try_get_mut(node) {
+            if let Some(pose) = pose {
+                node.local_transform_mut()
+                    .set_position(pose.position)
+                    .set_rotation(pose.orientation);
+            }
+            node.set_visibility(pose.is_some());
+        }
+    }
+
+    /// Updates eye cameras using the given views. Should be called every frame before rendering.
+    pub fn update_views(&self, graph: &mut Graph, views: &[View; 2]) {
+        for (eye, view) in self.eyes.iter().zip(views) {
+            if let Some(camera) = graph.try_get_mut_of_type::<Camera>(*eye) {
+                camera
+                    .local_transform_mut()
+                    .set_position(view.pose.position)
+                    .set_rotation(view.pose.orientation);
+                let projection = camera.projection();
+                let (z_near, z_far) = (projection.z_near(), projection.z_far());
+                camera.set_projection(Projection::OffCenter(view.fov.projection(z_near, z_far)));
+            }
+        }
+    }
+
+    /// Updates the head and the hands using the given input. Hands of inactive controllers are
+    /// hidden.
+    pub fn update_input(&self, graph: &mut Graph, input: &XrInput) {
+        if let Some(head) = input.head {
+            if let Some(node) = graph.try_get_mut(self.head) {
+                node.local_transform_mut()
+                    .set_position(head.position)
+                    .set_rotation(head.orientation);
+            }
+        }
+
+        for hand in Hand::ALL {
+            let controller = input.controller(hand);
+            let pose = controller.grip_pose.filter(|_| controller.is_active);
+            Self::set_pose(graph, self.hands[hand.index()], pose);
+        }
+    }
+}
@@ -0,0 +1,671 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! OpenXR session. See [`XrSession`] docs for more info.
+
+use crate::{
+    input::{ControllerButton, Hand, XrInput, ANALOG_PRESS_THRESHOLD},
+    pose::{Fov, Pose, View},
+};
+use fyrox::{
+    core::{algebra::Vector2, Downcast},
+    renderer::{
+        framework::{
+            gl::{server::GlGraphicsServer, texture::GlTexture},
+            server::GraphicsServer,
+        },
+        Renderer,
+    },
+    resource::texture::{TextureKind, TextureResource},
+};
+use glow::HasContext;
+use openxr as xr;
+use std::{
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+    num::NonZeroU32,
+};
+
+const VIEW_TYPE: xr::ViewConfigurationType = xr::ViewConfigurationType::PRIMARY_STEREO;
+
+/// An error, that may occur during OpenXR operations.
+#[derive(Debug)]
+pub enum XrError {
+    /// Unable to load OpenXR loader library.
+    Loader(String),
+    /// OpenXR runtime returned an error.
+    Runtime(xr::sys::Result),
+    /// OpenXR runtime or the current graphics server does not support required functionality.
+    Unsupported(String),
+    /// An error of graphics interop.
+    Graphics(String),
+}
+
+impl Display for XrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XrError::Loader(err) => write!(f, "Unable to load OpenXR loader. Reason: {err}"),
+            XrError::Runtime(err) => write!(f, "OpenXR runtime error: {err}"),
+            XrError::Unsupported(err) => write!(f, "Unsupported functionality: {err}"),
+            XrError::Graphics(err) => write!(f, "Graphics error: {err}"),
+        }
+    }
+}
+
+impl Error for XrError {}
+
+impl From<xr::sys::Result> for XrError {
+    fn from(err: xr::sys::Result) -> Self {
+        Self::Runtime(err)
+    }
+}
+
+struct Actions {
+    set: xr::ActionSet,
+    grip_pose: xr::Action<xr::Posef>,
+    aim_pose: xr::Action<xr::Posef>,
+    trigger: xr::Action<f32>,
+    grip: xr::Action<f32>,
+    thumbstick: xr::Action<xr::Vector2f>,
+    thumbstick_click: xr::Action<bool>,
+    primary: xr::Action<bool>,
+    secondary: xr::Action<bool>,
+    menu: xr::Action<bool>,
+    haptic: xr::Action<xr::Haptic>,
+}
+
+impl Actions {
+    fn new(instance: &xr::Instance, hands: &[xr::Path; 2]) -> Result<Self, XrError> {
+        let set = instance.create_action_set("gameplay", "Gameplay", 0)?;
+        Ok(Self {
+            grip_pose: set.create_action("grip_pose", "Grip Pose", hands)?,
+            aim_pose: set.create_action("aim_pose", "Aim Pose", hands)?,
+            trigger: set.create_action("trigger", "Trigger", hands)?,
+            grip: set.create_action("grip", "Grip", hands)?,
+            thumbstick: set.create_action("thumbstick", "Thumbstick", hands)?,
+            thumbstick_click: set.create_action("thumbstick_click", "Thumbstick Click", hands)?,
+            primary: set.create_action("primary", "Primary Button", hands)?,
+            secondary: set.create_action("secondary", "Secondary Button", hands)?,
+            menu: set.create_action("menu", "Menu", hands)?,
+            haptic: set.create_action("haptic", "Haptic Feedback", hands)?,
+            set,
+        })
+    }
+
+    fn suggest_bindings(&self, instance: &xr::Instance) -> Result<(), XrError> {
+        let path = |path: &str| instance.string_to_path(path);
+
+        // Oculus Touch layout is emulated by most runtimes for other controllers.
+        let mut touch = Vec::new();
+        for hand in ["left", "right"] {
+            let input = |name: &str| path(&format!("/user/hand/{hand}/input/{name}"));
+            touch.extend([
+                xr::Binding::new(&self.grip_pose, input("grip/pose")?),
+                xr::Binding::new(&self.aim_pose, input("aim/pose")?),
+                xr::Binding::new(&self.trigger, input("trigger/value")?),
+                xr::Binding::new(&self.grip, input("squeeze/value")?),
+                xr::Binding::new(&self.thumbstick, input("thumbstick")?),
+                xr::Binding::new(&self.thumbstick_click, input("thumbstick/click")?),
+                xr::Binding::new(
+                    &self.haptic,
+                    path(&format!("/user/hand/{hand}/output/haptic"))?,
+                ),
+            ]);
+        }
+        touch.extend([
+            xr::Binding::new(&self.primary, path("/user/hand/left/input/x/click")?),
+            xr::Binding::new(&self.secondary, path("/user/hand/left/input/y/click")?),
+            xr::Binding::new(&self.menu, path("/user/hand/left/input/menu/click")?),
+            xr::Binding::new(&self.primary, path("/user/hand/right/input/a/click")?),
+            xr::Binding::new(&self.secondary, path("/user/hand/right/input/b/click")?),
+        ]);
+        instance.suggest_interaction_profile_bindings(
+            path("/interaction_profiles/oculus/touch_controller")?,
+            &touch,
+        )?;
+
+        // Fallback for any other controllers.
+        let mut simple = Vec::new();
+        for hand in ["left", "right"] {
+            let input = |name: &str| path(&format!("/user/hand/{hand}/input/{name}"));
+            simple.extend([
+                xr::Binding::new(&self.grip_pose, input("grip/pose")?),
+                xr::Binding::new(&self.aim_pose, input("aim/pose")?),
+                xr::Binding::new(&self.trigger, input("select/click")?),
+                xr::Binding::new(&self.menu, input("menu/click")?),
+                xr::Binding::new(
+                    &self.haptic,
+                    path(&format!("/user/hand/{hand}/output/haptic"))?,
+                ),
+            ]);
+        }
+        instance.suggest_interaction_profile_bindings(
+            path("/interaction_profiles/khr/simple_controller")?,
+            &simple,
+        )?;
+
+        Ok(())
+    }
+}
+
+struct HandSpaces {
+    grip: xr::Space,
+    aim: xr::Space,
+}
+
+struct PendingFrame {
+    display_time: xr::Time,
+    views: Vec<xr::View>,
+    should_render: bool,
+}
+
+fn locate(space: &xr::Space, base: &xr::Space, time: xr::Time) -> Result<Option<Pose>, XrError> {
+    let location = space.locate(base, time)?;
+    let valid = xr::SpaceLocationFlags::POSITION_VALID | xr::SpaceLocationFlags::ORIENTATION_VALID;
+    Ok(location
+        .location_flags
+        .contains(valid)
+        .then(|| convert_pose(&location.pose)))
+}
+
+fn convert_pose(pose: &xr::Posef) -> Pose {
+    let p = pose.position;
+    let o = pose.orientation;
+    Pose::from_openxr([p.x, p.y, p.z], [o.x, o.y, o.z, o.w])
+}
+
+fn convert_view(view: &xr::View) -> View {
+    View {
+        pose: convert_pose(&view.pose),
+        fov: Fov {
+            angle_left: view.fov.angle_left,
+            angle_right: view.fov.angle_right,
+            angle_up: view.fov.angle_up,
+            angle_down: view.fov.angle_down,
+        },
+    }
+}
+
+/// OpenXR session, that works with the head-mounted display (HMD) using OpenGL graphics binding.
+/// Typical frame looks like this:
+///
+/// 1) [`Self::poll_events`] - handles lifecycle of the session.
+/// 2) [`Self::begin_frame`] - waits for the HMD, updates the input and returns the views of the eyes,
+/// that should be used to render the frame.
+/// 3) The scene is rendered by the engine into a render target.
+/// 4) [`Self::end_frame`] - copies the render target to the HMD.
+///
+/// [`crate::plugin::XrPlugin`] does all of this automatically.
+pub struct XrSession {
+    // Drop order matters: every OpenXR object must be destroyed before the instance.
+    swapchain: xr::Swapchain<xr::OpenGL>,
+    swapchain_images: Vec<u32>,
+    hand_spaces: [HandSpaces; 2],
+    view_space: xr::Space,
+    stage: xr::Space,
+    actions: Actions,
+    frame_stream: xr::FrameStream<xr::OpenGL>,
+    frame_waiter: xr::FrameWaiter,
+    session: xr::Session<xr::OpenGL>,
+    instance: xr::Instance,
+    hand_paths: [xr::Path; 2],
+    eye_size: Vector2<u32>,
+    framebuffers: Option<(glow::Framebuffer, glow::Framebuffer)>,
+    event_buffer: xr::EventDataBuffer,
+    frame: Option<PendingFrame>,
+    running: bool,
+    exit_requested: bool,
+}
+
+impl Debug for XrSession {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("XrSession")
+            .field("eye_size", &self.eye_size)
+            .field("running", &self.running)
+            .field("exit_requested", &self.exit_requested)
+            .finish_non_exhaustive()
+    }
+}
+
+impl XrSession {
+    /// Loads OpenXR runtime and creates a new session for the head-mounted display.
+    ///
+    /// # Safety
+    ///
+    /// `graphics` must contain valid handles of the OpenGL context, that is used by the engine's
+    /// renderer. The handles are platform-specific, they could be obtained from the windowing
+    /// system (for example, `wglGetCurrentDC`/`wglGetCurrentContext` on Windows or
+    /// `glXGetCurrentDisplay`/`glXGetCurrentContext`/`glXGetCurrentDrawable` on Linux) while the
+    /// context is current.
+    pub unsafe fn new(
+        application_name: &str,
+        graphics: xr::opengl::SessionCreateInfo,
+    ) -> Result<Self, XrError> {
+        let entry = xr::Entry::load().map_err(|err| XrError::Loader(err.to_string()))?;
+
+        if !entry.enumerate_extensions()?.khr_opengl_enable {
+            return Err(XrError::Unsupported(
+                "OpenXR runtime does not support OpenGL!".to_string(),
+            ));
+        }
+        let mut extensions = xr::ExtensionSet::default();
+        extensions.khr_opengl_enable = true;
+
+        let instance = entry.create_instance(
+            &xr::ApplicationInfo {
+                application_name,
+                application_version: 0,
+                engine_name: "Fyrox",
+                engine_version: 0,
+                api_version: xr::Version::new(1, 0, 0),
+            },
+            &extensions,
+            &[],
+        )?;
+        let system = instance.system(xr::FormFactor::HEAD_MOUNTED_DISPLAY)?;
+
+        // Must be called before session creation, even if the requirements are not checked.
+        instance.graphics_requirements::<xr::OpenGL>(system)?;
+
+        let (session, frame_waiter, frame_stream) =
+            instance.create_session::<xr::OpenGL>(system, &graphics)?;
+
+        let hand_paths = [
+            instance.string_to_path("/user/hand/left")?,
+            instance.string_to_path("/user/hand/right")?,
+        ];
+        let actions = Actions::new(&instance, &hand_paths)?;
+        actions.suggest_bindings(&instance)?;
+        session.attach_action_sets(&[&actions.set])?;
+
+        let make_hand_spaces = |path: xr::Path| -> Result<HandSpaces, XrError> {
+            Ok(HandSpaces {
+                grip: actions
+                    .grip_pose
+                    .create_space(session.clone(), path, xr::Posef::IDENTITY)?,
+                aim: actions
+                    .aim_pose
+                    .create_space(session.clone(), path, xr::Posef::IDENTITY)?,
+            })
+        };
+        let hand_spaces = [
+            make_hand_spaces(hand_paths[0])?,
+            make_hand_spaces(hand_paths[1])?,
+        ];
+
+        // Stage space has its origin on the floor, but it is optional.
+        let stage_type = if session
+            .enumerate_reference_spaces()?
+            .contains(&xr::ReferenceSpaceType::STAGE)
+        {
+            xr::ReferenceSpaceType::STAGE
+        } else {
+            xr::ReferenceSpaceType::LOCAL
+        };
+        let stage = session.create_reference_space(stage_type, xr::Posef::IDENTITY)?;
+        let view_space =
+            session.create_reference_space(xr::ReferenceSpaceType::VIEW, xr::Posef::IDENTITY)?;
+
+        let views = instance.enumerate_view_configuration_views(system, VIEW_TYPE)?;
+        let view = views.first().ok_or_else(|| {
+            XrError::Unsupported("Stereo view configuration is not supported!".to_string())
+        })?;
+        let eye_size = Vector2::new(
+            view.recommended_image_rect_width,
+            view.recommended_image_rect_height,
+        );
+
+        let formats = session.enumerate_swapchain_formats()?;
+        let format = [glow::SRGB8_ALPHA8, glow::RGBA8]
+            .into_iter()
+            .find(|format| formats.contains(format))
+            .ok_or_else(|| {
+                XrError::Unsupported("No suitable swapchain format is available!".to_string())
+            })?;
+
+        // Both eyes share the same swapchain, each eye uses its own half of the image.
+        let swapchain = session.create_swapchain(&xr::SwapchainCreateInfo {
+            create_flags: xr::SwapchainCreateFlags::EMPTY,
+            usage_flags: xr::SwapchainUsageFlags::COLOR_ATTACHMENT
+                | xr::SwapchainUsageFlags::TRANSFER_DST,
+            format,
+            sample_count: 1,
+            width: eye_size.x * 2,
+            height: eye_size.y,
+            face_count: 1,
+            array_size: 1,
+            mip_count: 1,
+        })?;
+        let swapchain_images = swapchain.enumerate_images()?;
+
+        Ok(Self {
+            swapchain,
+            swapchain_images,
+            hand_spaces,
+            view_space,
+            stage,
+            actions,
+            frame_stream,
+            frame_waiter,
+            session,
+            instance,
+            hand_paths,
+            eye_size,
+            framebuffers: None,
+            event_buffer: xr::EventDataBuffer::new(),
+            frame: None,
+            running: false,
+            exit_requested: false,
+        })
+    }
+
+    /// Returns recommended size of the image of a single eye in pixels.
+    pub fn eye_size(&self) -> Vector2<u32> {
+        self.eye_size
+    }
+
+    /// Returns `true` if the session is running and frames could be submitted.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Returns `true` if the runtime has requested to close the application (for example, the
+    /// user has quit it from the system menu).
+    pub fn is_exit_requested(&self) -> bool {
+        self.exit_requested
+    }
+
+    /// Handles events of the runtime. Should be called every frame before [`Self::begin_frame`].
+    pub fn poll_events(&mut self) -> Result<(), XrError> {
+        while let Some(event) = self.instance.poll_event(&mut self.event_buffer)? {
+            match event {
+                xr::Event::SessionStateChanged(event) => match event.state() {
+                    xr::SessionState::READY => {
+                        self.session.begin(VIEW_TYPE)?;
+                        self.running = true;
+                    }
+                    xr::SessionState::STOPPING => {
+                        self.session.end()?;
+                        self.running = false;
+                    }
+                    xr::SessionState::EXITING | xr::SessionState::LOSS_PENDING => {
+                        self.running = false;
+                        self.exit_requested = true;
+                    }
+                    _ => (),
+                },
+                xr::Event::InstanceLossPending(_) => {
+                    self.running = false;
+                    self.exit_requested = true;
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits for the next frame of the HMD, updates the input and returns the views of the eyes.
+    /// Returns `None` if nothing should be rendered (the session is not running or the application
+    /// is not visible), [`Self::end_frame`] must be called anyway.
+    pub fn begin_frame(&mut self, input: &mut XrInput) -> Result<Option<[View; 2]>, XrError> {
+        if !self.running {
+            return Ok(None);
+        }
+
+        let state = self.frame_waiter.wait()?;
+        self.frame_stream.begin()?;
+        let display_time = state.predicted_display_time;
+
+        self.update_input(input, display_time)?;
+
+        let (_, views) = self
+            .session
+            .locate_views(VIEW_TYPE, display_time, &self.stage)?;
+        let should_render = state.should_render && views.len() >= 2;
+        let result = should_render.then(|| [convert_view(&views[0]), convert_view(&views[1])]);
+
+        self.frame = Some(PendingFrame {
+            display_time,
+            views,
+            should_render,
+        });
+
+        Ok(result)
+    }
+
+    fn update_input(&mut self, input: &mut XrInput, time: xr::Time) -> Result<(), XrError> {
+        self.session.sync_actions(&[(&self.actions.set).into()])?;
+
+        input.head = locate(&self.view_space, &self.stage, time)?;
+
+        for hand in Hand::ALL {
+            let path = self.hand_paths[hand.index()];
+            let spaces = &self.hand_spaces[hand.index()];
+            let actions = &self.actions;
+            let session = &self.session;
+
+            let controller = input.controller_mut(hand);
+            controller.begin_frame();
+
+            controller.is_active = actions.grip_pose.is_active(session, path)?;
+            controller.grip_pose = locate(&spaces.grip, &self.stage, time)?;
+            controller.aim_pose = locate(&spaces.aim, &self.stage, time)?;
+
+            controller.trigger = actions.trigger.state(session, path)?.current_state;
+            controller.grip = actions.grip.state(session, path)?.current_state;
+            let thumbstick = actions.thumbstick.state(session, path)?.current_state;
+            controller.thumbstick = Vector2::new(thumbstick.x, thumbstick.y);
+
+            let trigger_pressed = controller.trigger > ANALOG_PRESS_THRESHOLD;
+            let grip_pressed = controller.grip > ANALOG_PRESS_THRESHOLD;
+            controller.set_pressed(ControllerButton::Trigger, trigger_pressed);
+            controller.set_pressed(ControllerButton::Grip, grip_pressed);
+            for (button, action) in [
+                (ControllerButton::Primary, &actions.primary),
+                (ControllerButton::Secondary, &actions.secondary),
+                (ControllerButton::Menu, &actions.menu),
+                (ControllerButton::Thumbstick, &actions.thumbstick_click),
+            ] {
+                controller.set_pressed(button, action.state(session, path)?.current_state);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sends every requested haptic feedback to the controllers.
+    pub fn apply_haptics(&mut self, input: &mut XrInput) -> Result<(), XrError> {
+        if !self.running {
+            input.drain_haptics().for_each(drop);
+            return Ok(());
+        }
+        for pulse in input.drain_haptics() {
+            let vibration = xr::HapticVibration::new()
+                .amplitude(pulse.amplitude.clamp(0.0, 1.0))
+                .duration(xr::Duration::from_nanos(
+                    (pulse.duration.max(0.0) * 1.0e9) as i64,
+                ))
+                // Zero means "unspecified", the runtime will choose the optimal frequency.
+                .frequency(pulse.frequency.unwrap_or(0.0));
+            self.actions.haptic.apply_feedback(
+                &self.session,
+                self.hand_paths[pulse.hand.index()],
+                &vibration,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Copies the given render target (which must contain both eyes side-by-side) to the HMD and
+    /// finishes the frame started by [`Self::begin_frame`].
+    pub fn end_frame(
+        &mut self,
+        renderer: &mut Renderer,
+        render_target: &TextureResource,
+    ) -> Result<(), XrError> {
+        let Some(frame) = self.frame.take() else {
+            return Ok(());
+        };
+
+        if !frame.should_render {
+            self.frame_stream
+                .end(frame.display_time, xr::EnvironmentBlendMode::OPAQUE, &[])?;
+            return Ok(());
+        }
+
+        let image_index = self.swapchain.acquire_image()?;
+        self.swapchain.wait_image(xr::Duration::INFINITE)?;
+        let image = self.swapchain_images[image_index as usize];
+        let copy_result = self.copy_render_target(renderer, render_target, image);
+        self.swapchain.release_image()?;
+
+        if let Err(err) = copy_result {
+            // Submit an empty frame, the runtime expects every begun frame to be ended.
+            self.frame_stream
+                .end(frame.display_time, xr::EnvironmentBlendMode::OPAQUE, &[])?;
+            return Err(err);
+        }
+
+        let (width, height) = (self.eye_size.x as i32, self.eye_size.y as i32);
+        let views = [0, 1].map(|eye| {
+            xr::CompositionLayerProjectionView::new()
+                .pose(frame.views[eye].pose)
+                .fov(frame.views[eye].fov)
+                .sub_image(
+                    xr::SwapchainSubImage::new()
+                        .swapchain(&self.swapchain)
+                        .image_array_index(0)
+                        .image_rect(xr::Rect2Di {
+                            offset: xr::Offset2Di {
+                                x: eye as i32 * width,
+                                y: 0,
+                            },
+                            extent: xr::Extent2Di { width, height },
+                        }),
+                )
+        });
+        self.frame_stream.end(
+            frame.display_time,
+            xr::EnvironmentBlendMode::OPAQUE,
+            &[&xr::CompositionLayerProjection::new()
+                .space(&self.stage)
+                .views(&views)],
+        )?;
+
+        Ok(())
+    }
+
+    fn copy_render_target(
+        &mut self,
+        renderer: &mut Renderer,
+        render_target: &TextureResource,
+        image: u32,
+    ) -> Result<(), XrError> {
+        let (source_width, source_height) = match render_target.data_ref().kind() {
+            TextureKind::Rectangle { width, height } => (width as i32, height as i32),
+            _ => {
+                return Err(XrError::Graphics(
+                    "Render target must be a rectangle texture!".to_string(),
+                ))
+            }
+        };
+
+        let server: &dyn GraphicsServer = &*renderer.server;
+        let gl_server = server
+            .as_any()
+            .downcast_ref::<GlGraphicsServer>()
+            .ok_or_else(|| {
+                XrError::Unsupported("Only OpenGL graphics server is supported!".to_string())
+            })?;
+        let gpu_texture = renderer
+            .texture_cache
+            .get(server, render_target)
+            .ok_or_else(|| XrError::Graphics("Render target is not ready!".to_string()))?
+            .borrow();
+        let source = gpu_texture
+            .as_any()
+            .downcast_ref::<GlTexture>()
+            .ok_or_else(|| XrError::Graphics("Render target is not an OpenGL texture!".into()))?
+            .id();
+        let destination = NonZeroU32::new(image)
+            .map(glow::NativeTexture)
+            .ok_or_else(|| XrError::Graphics("Invalid swapchain image!".to_string()))?;
+
+        let gl = &gl_server.gl;
+        unsafe {
+            let (read_framebuffer, draw_framebuffer) = match self.framebuffers {
+                Some(framebuffers) => framebuffers,
+                None => {
+                    let framebuffers = (
+                        gl.create_framebuffer().map_err(XrError::Graphics)?,
+                        gl.create_framebuffer().map_err(XrError::Graphics)?,
+                    );
+                    *self.framebuffers.insert(framebuffers)
+                }
+            };
+
+            // The engine caches the state of OpenGL, so everything that is changed here must be
+            // restored.
+            let prev_read = gl.get_parameter_i32(glow::READ_FRAMEBUFFER_BINDING);
+            let prev_draw = gl.get_parameter_i32(glow::DRAW_FRAMEBUFFER_BINDING);
+            let scissor_test = gl.is_enabled(glow::SCISSOR_TEST);
+            gl.disable(glow::SCISSOR_TEST);
+
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(read_framebuffer));
+            gl.framebuffer_texture_2d(
+                glow::READ_FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(source),
+                0,
+            );
+            gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(draw_framebuffer));
+            gl.framebuffer_texture_2d(
+                glow::DRAW_FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(destination),
+                0,
+            );
+            gl.blit_framebuffer(
+                0,
+                0,
+                source_width,
+                source_height,
+                0,
+                0,
+                self.eye_size.x as i32 * 2,
+                self.eye_size.y as i32,
+                glow::COLOR_BUFFER_BIT,
+                glow::LINEAR,
+            );
+
+            let restore =
+                |binding: i32| NonZeroU32::new(binding as u32).map(glow::NativeFramebuffer);
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, restore(prev_read));
+            gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, restore(prev_draw));
+            if scissor_test {
+                gl.enable(glow::SCISSOR_TEST);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -86,6 +86,7 @@ fn main() {
         serialization_context,
         task_pool,
         widget_constructors: Arc::new(new_widget_constructor_container()),
+        update_loop: Default::default(),
     })
     .unwrap();
 